@@ -0,0 +1,30 @@
+-- Phase 11 migration: full-text search over thoughts
+-- Creates an FTS5 index of thought content kept in sync by triggers
+
+-- Thought search index: thought_id links back to thoughts(id)
+CREATE VIRTUAL TABLE IF NOT EXISTS thoughts_fts USING fts5(
+    thought_id UNINDEXED,
+    content,
+    tokenize = 'porter unicode61'
+);
+
+-- Index thoughts that existed before this migration
+INSERT INTO thoughts_fts (thought_id, content)
+SELECT id, content FROM thoughts;
+
+-- Keep the index in sync with the thoughts table
+CREATE TRIGGER IF NOT EXISTS thoughts_fts_insert AFTER INSERT ON thoughts
+BEGIN
+    INSERT INTO thoughts_fts (thought_id, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS thoughts_fts_delete AFTER DELETE ON thoughts
+BEGIN
+    DELETE FROM thoughts_fts WHERE thought_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS thoughts_fts_update AFTER UPDATE OF content ON thoughts
+BEGIN
+    DELETE FROM thoughts_fts WHERE thought_id = old.id;
+    INSERT INTO thoughts_fts (thought_id, content) VALUES (new.id, new.content);
+END;
@@ -0,0 +1,13 @@
+-- Phase 12 migration: session tags
+-- Free-form labels for organizing and retrieving related sessions
+
+CREATE TABLE IF NOT EXISTS session_tags (
+    session_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (session_id, tag),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+-- Index for tag lookups
+CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag);
@@ -0,0 +1,38 @@
+-- Phase 13 migration: vector embeddings for semantic similarity search
+-- Stores one vector per (thought or graph node, embedding model)
+
+CREATE TABLE IF NOT EXISTS embeddings (
+    source_id TEXT NOT NULL,
+    source_type TEXT NOT NULL CHECK (source_type IN ('thought', 'graph_node')),
+    session_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    dimensions INTEGER NOT NULL,
+    vector BLOB NOT NULL,          -- little-endian f32 values
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (source_id, model),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_embeddings_model ON embeddings(model, source_type);
+CREATE INDEX IF NOT EXISTS idx_embeddings_session ON embeddings(session_id);
+
+-- Drop embeddings when the embedded record is deleted or its content changes
+CREATE TRIGGER IF NOT EXISTS embeddings_thought_delete AFTER DELETE ON thoughts
+BEGIN
+    DELETE FROM embeddings WHERE source_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS embeddings_thought_update AFTER UPDATE OF content ON thoughts
+BEGIN
+    DELETE FROM embeddings WHERE source_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS embeddings_node_delete AFTER DELETE ON graph_nodes
+BEGIN
+    DELETE FROM embeddings WHERE source_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS embeddings_node_update AFTER UPDATE OF content ON graph_nodes
+BEGIN
+    DELETE FROM embeddings WHERE source_id = old.id;
+END;
@@ -0,0 +1,6 @@
+-- Phase 14 migration: soft-deleted sessions
+-- Sessions moved to the trash keep their data until restored or purged
+
+ALTER TABLE sessions ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_deleted_at ON sessions(deleted_at);
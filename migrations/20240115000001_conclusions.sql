@@ -0,0 +1,45 @@
+-- Phase 15 migration: cross-session knowledge base
+-- Accepted conclusions promoted from GoT nodes, decisions, and evidence assessments
+
+CREATE TABLE IF NOT EXISTS conclusions (
+    id TEXT PRIMARY KEY,
+    source_type TEXT NOT NULL CHECK (source_type IN ('graph_node', 'decision', 'evidence')),
+    source_id TEXT NOT NULL,
+    session_id TEXT,               -- originating session; cleared if it is deleted
+    topic TEXT NOT NULL,           -- question, claim, or problem the conclusion answers
+    content TEXT NOT NULL,         -- the accepted conclusion
+    confidence REAL,
+    created_at TEXT NOT NULL,
+    metadata TEXT,
+    UNIQUE (source_type, source_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_conclusions_source_type ON conclusions(source_type);
+CREATE INDEX IF NOT EXISTS idx_conclusions_created ON conclusions(created_at);
+
+-- Conclusion search index: conclusion_id links back to conclusions(id)
+CREATE VIRTUAL TABLE IF NOT EXISTS conclusions_fts USING fts5(
+    conclusion_id UNINDEXED,
+    topic,
+    content,
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS conclusions_fts_insert AFTER INSERT ON conclusions
+BEGIN
+    INSERT INTO conclusions_fts (conclusion_id, topic, content)
+    VALUES (new.id, new.topic, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS conclusions_fts_delete AFTER DELETE ON conclusions
+BEGIN
+    DELETE FROM conclusions_fts WHERE conclusion_id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS conclusions_fts_update AFTER UPDATE OF topic, content ON conclusions
+BEGIN
+    DELETE FROM conclusions_fts WHERE conclusion_id = old.id;
+    INSERT INTO conclusions_fts (conclusion_id, topic, content)
+    VALUES (new.id, new.topic, new.content);
+END;
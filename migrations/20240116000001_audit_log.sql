@@ -0,0 +1,28 @@
+-- Phase 16 migration: append-only audit trail of mutating tool calls
+-- Entries are kept independently of sessions so they survive deletion
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id TEXT PRIMARY KEY,
+    created_at TEXT NOT NULL,
+    actor TEXT NOT NULL,           -- who made the change (e.g. 'mcp:<client name>')
+    tool_name TEXT NOT NULL,
+    session_id TEXT,               -- affected session, if any (not a foreign key)
+    before_hash TEXT,              -- SHA-256 of the session state before the call
+    after_hash TEXT,               -- SHA-256 of the session state after the call
+    success INTEGER NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_session ON audit_log(session_id, created_at);
+
+-- Reject any modification of recorded entries
+CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only');
+END;
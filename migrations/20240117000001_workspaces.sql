@@ -0,0 +1,13 @@
+-- Phase 17 migration: workspaces
+-- Isolates sessions and everything recorded without a session (invocations,
+-- standalone detections, conclusions whose session was deleted) per workspace
+
+ALTER TABLE sessions ADD COLUMN workspace_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE invocations ADD COLUMN workspace_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE detections ADD COLUMN workspace_id TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE conclusions ADD COLUMN workspace_id TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_sessions_workspace ON sessions(workspace_id, updated_at);
+CREATE INDEX IF NOT EXISTS idx_invocations_workspace ON invocations(workspace_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_detections_workspace ON detections(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_conclusions_workspace ON conclusions(workspace_id);
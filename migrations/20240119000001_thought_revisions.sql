@@ -0,0 +1,18 @@
+-- Phase 19 migration: thought revisions
+-- Keeps every superseded version of a revised thought
+
+CREATE TABLE IF NOT EXISTS thought_revisions (
+    id TEXT PRIMARY KEY NOT NULL,
+    thought_id TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    version INTEGER NOT NULL,      -- 1 is the thought as first written
+    content TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    reason TEXT,                   -- why this version was replaced
+    revised_at TEXT NOT NULL,      -- when this version was replaced
+    FOREIGN KEY (thought_id) REFERENCES thoughts(id) ON DELETE CASCADE,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    UNIQUE (thought_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thought_revisions_session ON thought_revisions(session_id);
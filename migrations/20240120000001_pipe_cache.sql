@@ -0,0 +1,14 @@
+-- Phase 20 migration: pipe response cache
+-- Persists cached pipe responses across restarts and flags cached invocations
+
+CREATE TABLE IF NOT EXISTS pipe_cache (
+    key TEXT PRIMARY KEY NOT NULL,   -- SHA-256 of pipe name and request content
+    pipe_name TEXT NOT NULL,
+    response TEXT NOT NULL,          -- JSON-serialized pipe response
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipe_cache_expires ON pipe_cache(expires_at);
+
+ALTER TABLE invocations ADD COLUMN cache_hit INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Phase 21 migration: session threads
+-- Binds a session to a Langbase thread holding its conversation history
+
+ALTER TABLE sessions ADD COLUMN thread_id TEXT;
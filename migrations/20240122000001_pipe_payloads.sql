@@ -0,0 +1,16 @@
+-- Phase 22 migration: pipe payload log
+-- Redacted request/response bodies of pipe calls, written only when payload logging is enabled
+
+CREATE TABLE IF NOT EXISTS pipe_payloads (
+    id TEXT PRIMARY KEY NOT NULL,
+    pipe_name TEXT NOT NULL,
+    attempt INTEGER NOT NULL,        -- 1 for the first try, 2+ for retries
+    request TEXT NOT NULL,           -- JSON-serialized request body
+    response TEXT,                   -- JSON-serialized response body (NULL on failure)
+    error TEXT,
+    latency_ms INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipe_payloads_pipe ON pipe_payloads(pipe_name, created_at);
+CREATE INDEX IF NOT EXISTS idx_pipe_payloads_created ON pipe_payloads(created_at);
@@ -0,0 +1,24 @@
+-- Phase 23 migration: SWOT analyses
+-- Strengths/weaknesses/opportunities/threats kept per subject so later sessions can retrieve them
+
+CREATE TABLE IF NOT EXISTS swot_analyses (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    strengths TEXT NOT NULL,            -- JSON array of items with evidence and confidence
+    weaknesses TEXT NOT NULL,           -- JSON array of items
+    opportunities TEXT NOT NULL,        -- JSON array of items
+    threats TEXT NOT NULL,              -- JSON array of items
+    perspective_id TEXT,                -- perspective analysis whose stakeholders items reference
+    summary TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    metadata TEXT,                      -- JSON for additional context
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (perspective_id) REFERENCES perspective_analyses(id) ON DELETE SET NULL,
+    CHECK (confidence BETWEEN 0.0 AND 1.0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_swot_session ON swot_analyses(session_id);
+CREATE INDEX IF NOT EXISTS idx_swot_subject ON swot_analyses(subject);
+CREATE INDEX IF NOT EXISTS idx_swot_created ON swot_analyses(created_at);
@@ -0,0 +1,29 @@
+-- Phase 24 migration: prioritization rankings
+-- Rankings are stored as decisions; allow the RICE and MoSCoW methods.
+-- SQLite cannot alter a CHECK constraint, so the table is rebuilt.
+
+CREATE TABLE decisions_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    question TEXT NOT NULL,
+    options TEXT NOT NULL,              -- JSON array of option strings
+    criteria TEXT,                      -- JSON array of criteria with weights
+    method TEXT NOT NULL,               -- 'weighted_sum', 'pairwise', 'topsis', 'rice', 'moscow'
+    recommendation TEXT NOT NULL,       -- JSON object with option, score, confidence, rationale
+    scores TEXT NOT NULL,               -- JSON array of option scores
+    sensitivity_analysis TEXT,          -- JSON object with robustness analysis
+    trade_offs TEXT,                    -- JSON array of trade-off descriptions
+    constraints_satisfied TEXT,         -- JSON object mapping options to boolean
+    created_at TEXT NOT NULL,
+    metadata TEXT,                      -- JSON for additional context
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    CHECK (method IN ('weighted_sum', 'pairwise', 'topsis', 'rice', 'moscow'))
+);
+
+INSERT INTO decisions_new SELECT * FROM decisions;
+DROP TABLE decisions;
+ALTER TABLE decisions_new RENAME TO decisions;
+
+CREATE INDEX IF NOT EXISTS idx_decisions_session ON decisions(session_id);
+CREATE INDEX IF NOT EXISTS idx_decisions_method ON decisions(method);
+CREATE INDEX IF NOT EXISTS idx_decisions_created ON decisions(created_at);
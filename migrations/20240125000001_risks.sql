@@ -0,0 +1,26 @@
+-- Phase 25 migration: risk register
+-- Risks scored for likelihood, impact and detectability, re-scored as evidence arrives
+
+CREATE TABLE IF NOT EXISTS risks (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    category TEXT,
+    likelihood INTEGER NOT NULL,        -- 1 (rare) to 5 (almost certain)
+    impact INTEGER NOT NULL,            -- 1 (negligible) to 5 (severe)
+    detectability INTEGER NOT NULL,     -- 1 (obvious in time) to 5 (undetectable)
+    owner TEXT,
+    mitigation TEXT,
+    history TEXT NOT NULL,              -- JSON array of earlier scores and why they changed
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    metadata TEXT,                      -- JSON for additional context
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    CHECK (likelihood BETWEEN 1 AND 5),
+    CHECK (impact BETWEEN 1 AND 5),
+    CHECK (detectability BETWEEN 1 AND 5)
+);
+
+CREATE INDEX IF NOT EXISTS idx_risks_session ON risks(session_id);
+CREATE INDEX IF NOT EXISTS idx_risks_created ON risks(created_at);
@@ -0,0 +1,35 @@
+-- Phase 26 migration: calculation detections
+-- Mismatched numeric claims are stored as detections; allow the
+-- 'calculation' type. SQLite cannot alter a CHECK constraint, so the table
+-- is rebuilt.
+
+CREATE TABLE detections_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT,
+    thought_id TEXT,
+    detection_type TEXT NOT NULL,  -- 'bias', 'fallacy' or 'calculation'
+    detected_issue TEXT NOT NULL,  -- specific issue name (e.g., 'confirmation_bias', 'calculation_error')
+    severity INTEGER NOT NULL,     -- 1-5 scale (1=minor, 5=critical)
+    confidence REAL NOT NULL,      -- 0.0-1.0 detection confidence
+    explanation TEXT NOT NULL,     -- why this is an issue
+    remediation TEXT,              -- how to correct the reasoning
+    created_at TEXT NOT NULL,
+    metadata TEXT,                 -- JSON for additional context
+    workspace_id TEXT NOT NULL DEFAULT 'default',
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (thought_id) REFERENCES thoughts(id) ON DELETE SET NULL,
+    CHECK (detection_type IN ('bias', 'fallacy', 'calculation')),
+    CHECK (severity BETWEEN 1 AND 5),
+    CHECK (confidence BETWEEN 0.0 AND 1.0)
+);
+
+INSERT INTO detections_new SELECT * FROM detections;
+DROP TABLE detections;
+ALTER TABLE detections_new RENAME TO detections;
+
+CREATE INDEX IF NOT EXISTS idx_detections_session ON detections(session_id);
+CREATE INDEX IF NOT EXISTS idx_detections_thought ON detections(thought_id);
+CREATE INDEX IF NOT EXISTS idx_detections_type ON detections(detection_type);
+CREATE INDEX IF NOT EXISTS idx_detections_severity ON detections(severity);
+CREATE INDEX IF NOT EXISTS idx_detections_issue ON detections(detected_issue);
+CREATE INDEX IF NOT EXISTS idx_detections_workspace ON detections(workspace_id);
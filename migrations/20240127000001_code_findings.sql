@@ -0,0 +1,23 @@
+-- Phase 27 migration: code reasoning findings
+-- Invariants, risks and suggested tests from diff explanation, data flow
+-- tracing and edge case analysis, accumulated per session
+
+CREATE TABLE IF NOT EXISTS code_findings (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    thought_id TEXT,
+    operation TEXT NOT NULL,            -- 'explain_diff', 'trace_flow' or 'edge_cases'
+    kind TEXT NOT NULL,                 -- 'invariant', 'risk' or 'test'
+    content TEXT NOT NULL,
+    location TEXT,                      -- file, function or line the finding applies to
+    severity INTEGER,                   -- 1-5 for risks
+    created_at TEXT NOT NULL,
+    metadata TEXT,                      -- JSON for additional context
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (thought_id) REFERENCES thoughts(id) ON DELETE SET NULL,
+    CHECK (kind IN ('invariant', 'risk', 'test')),
+    CHECK (severity IS NULL OR severity BETWEEN 1 AND 5)
+);
+
+CREATE INDEX IF NOT EXISTS idx_code_findings_session ON code_findings(session_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_code_findings_kind ON code_findings(kind);
@@ -0,0 +1,28 @@
+-- Phase 28 migration: MCTS iteration statistics
+-- One row per iteration of a reasoning_mcts_explore search, with the search
+-- settings, so the metrics CLI can compare budget against result quality
+
+CREATE TABLE IF NOT EXISTS mcts_iterations (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    root_node_id TEXT NOT NULL,         -- root of the search the iteration belongs to
+    iteration INTEGER NOT NULL,
+    policy TEXT NOT NULL,               -- 'ucb1' or 'progressive_widening'
+    exploration_constant REAL NOT NULL,
+    rollout_depth INTEGER NOT NULL,
+    expansion_width INTEGER NOT NULL,
+    selected_node_id TEXT NOT NULL,
+    selected_depth INTEGER NOT NULL,
+    children_created INTEGER NOT NULL,
+    simulation_value REAL NOT NULL,
+    best_value REAL NOT NULL,           -- highest mean value of a visited node so far
+    nodes_total INTEGER NOT NULL,
+    pipe_calls INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    CHECK (policy IN ('ucb1', 'progressive_widening'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_mcts_iterations_session ON mcts_iterations(session_id);
+CREATE INDEX IF NOT EXISTS idx_mcts_iterations_root ON mcts_iterations(root_node_id, iteration);
@@ -0,0 +1,35 @@
+-- Phase 29 migration: auto-routing history
+-- One row per reasoning_auto decision with the embedding of the routed
+-- content and the outcome later observed for it, so routing can learn which
+-- modes work for similar content
+
+CREATE TABLE IF NOT EXISTS routing_records (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT,                    -- session the decision was made for (may not exist yet)
+    workspace_id TEXT NOT NULL DEFAULT 'default',
+    content_preview TEXT NOT NULL,
+    model TEXT NOT NULL,                -- embedding model of the vector
+    vector BLOB NOT NULL,               -- little-endian f32 values
+    recommended_mode TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    history_applied INTEGER NOT NULL DEFAULT 0,  -- past outcomes changed the recommendation
+    outcome_mode TEXT,                  -- mode the session actually ran
+    outcome_confidence REAL,            -- mean confidence of the session's results
+    detection_count INTEGER NOT NULL DEFAULT 0,
+    feedback_score REAL,                -- 0.0-1.0 rating from reasoning_routing_feedback
+    feedback_note TEXT,
+    outcome_score REAL,                 -- combined outcome, NULL until one is observed
+    created_at TEXT NOT NULL,
+    evaluated_at TEXT,
+    CHECK (feedback_score IS NULL OR feedback_score BETWEEN 0.0 AND 1.0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_records_workspace ON routing_records(workspace_id, model, created_at);
+CREATE INDEX IF NOT EXISTS idx_routing_records_session ON routing_records(session_id);
+
+-- Routing records may name a session before it is created, so they are
+-- removed with their session by trigger rather than by foreign key
+CREATE TRIGGER IF NOT EXISTS routing_records_session_delete AFTER DELETE ON sessions
+BEGIN
+    DELETE FROM routing_records WHERE session_id = old.id;
+END;
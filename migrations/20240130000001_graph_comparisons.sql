@@ -0,0 +1,24 @@
+-- Phase 30 migration: pairwise GoT comparisons
+-- One row per head-to-head judgment between two graph nodes, so rankings
+-- can be derived locally (Elo or Bradley-Terry) and refined by later rounds
+
+CREATE TABLE IF NOT EXISTS graph_comparisons (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    parent_node_id TEXT,                -- common parent of the compared siblings, if any
+    node_a_id TEXT NOT NULL,            -- node shown first to the judge
+    node_b_id TEXT NOT NULL,
+    winner_id TEXT,                     -- NULL for a tie
+    confidence REAL NOT NULL,
+    rationale TEXT NOT NULL DEFAULT '',
+    round INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+    FOREIGN KEY (node_a_id) REFERENCES graph_nodes(id) ON DELETE CASCADE,
+    FOREIGN KEY (node_b_id) REFERENCES graph_nodes(id) ON DELETE CASCADE,
+    CHECK (winner_id IS NULL OR winner_id IN (node_a_id, node_b_id)),
+    CHECK (confidence BETWEEN 0.0 AND 1.0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_graph_comparisons_session ON graph_comparisons(session_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_graph_comparisons_nodes ON graph_comparisons(node_a_id, node_b_id);
@@ -88,6 +88,20 @@ pub enum StorageError {
         /// Description of the serialization issue.
         message: String,
     },
+
+    /// Database backup or restore failed.
+    #[error("Backup failed: {message}")]
+    Backup {
+        /// Description of the backup or restore issue.
+        message: String,
+    },
+
+    /// A reasoning result could not be promoted to the knowledge base.
+    #[error("Knowledge promotion failed: {message}")]
+    Knowledge {
+        /// Description of why the result was rejected.
+        message: String,
+    },
 }
 
 /// Langbase API errors for pipe communication.
@@ -111,6 +125,15 @@ pub enum LangbaseError {
         message: String,
     },
 
+    /// API rejected the request with 429 Too Many Requests.
+    #[error("Rate limited: {message}")]
+    RateLimited {
+        /// Error message from the API.
+        message: String,
+        /// Delay requested by the `Retry-After` header, in milliseconds.
+        retry_after_ms: Option<u64>,
+    },
+
     /// Invalid response from the API.
     #[error("Invalid response: {message}")]
     InvalidResponse {
@@ -146,6 +169,41 @@ pub enum LangbaseError {
         /// Name of the missing pipe.
         pipe: String,
     },
+
+    /// Pipe completion does not match the output schema requested for it.
+    #[error("Response from pipe '{pipe}' violates schema '{schema}' at {path}: {message}")]
+    SchemaViolation {
+        /// Name of the pipe that returned the completion.
+        pipe: String,
+        /// Name of the violated schema.
+        schema: String,
+        /// JSON path of the offending field.
+        path: String,
+        /// What is wrong with the field.
+        message: String,
+    },
+
+    /// The HTTP client could not be built from the configured proxy or TLS
+    /// settings.
+    #[error("Invalid client configuration: {message}")]
+    ClientConfig {
+        /// What is wrong with the settings.
+        message: String,
+    },
+
+    /// A spend budget is used up; no further pipe calls are made until it
+    /// resets.
+    #[error("Budget exceeded: {scope} budget of {limit} is used up")]
+    BudgetExceeded {
+        /// Which budget ran out: `daily` or `session <id>`.
+        scope: String,
+        /// The limit that was reached, e.g. `50000 tokens` or `$2.00`.
+        limit: String,
+    },
+
+    /// The client cancelled the request the call was made for.
+    #[error("Request cancelled by client")]
+    Cancelled,
 }
 
 /// MCP protocol errors for request handling.
@@ -174,6 +232,13 @@ pub enum McpError {
         message: String,
     },
 
+    /// Requested resource does not exist.
+    #[error("Resource not found: {uri}")]
+    ResourceNotFound {
+        /// URI of the missing resource.
+        uri: String,
+    },
+
     /// Tool execution failed.
     #[error("Tool execution failed: {message}")]
     ExecutionFailed {
@@ -181,6 +246,20 @@ pub enum McpError {
         message: String,
     },
 
+    /// The client's credentials do not allow the operation.
+    #[error("Unauthorized: {message}")]
+    Unauthorized {
+        /// Why the operation is not allowed.
+        message: String,
+    },
+
+    /// Another call is changing the same state.
+    #[error("Conflict: {message}")]
+    Conflict {
+        /// Description of the conflicting operation.
+        message: String,
+    },
+
     /// JSON serialization/deserialization error.
     #[error("JSON serialization error: {0}")]
     Json(#[from] serde_json::Error),
@@ -394,6 +473,19 @@ mod tests {
         };
         assert_eq!(err.to_string(), "Migration failed: version mismatch");
 
+        let err = StorageError::Backup {
+            message: "target exists".to_string(),
+        };
+        assert_eq!(err.to_string(), "Backup failed: target exists");
+
+        let err = StorageError::Knowledge {
+            message: "graph node n1 is not terminal".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Knowledge promotion failed: graph node n1 is not terminal"
+        );
+
         let err = StorageError::Serialization {
             message: "invalid utf-8 in metadata".to_string(),
         };
@@ -420,6 +512,12 @@ mod tests {
         };
         assert_eq!(err.to_string(), "API error: 401 - unauthorized");
 
+        let err = LangbaseError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after_ms: Some(2000),
+        };
+        assert_eq!(err.to_string(), "Rate limited: slow down");
+
         let err = LangbaseError::InvalidResponse {
             message: "malformed JSON".to_string(),
         };
@@ -450,10 +548,28 @@ mod tests {
             "Invalid parameters for reasoning.linear: missing content"
         );
 
+        let err = McpError::ResourceNotFound {
+            uri: "reasoning://session/missing".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Resource not found: reasoning://session/missing"
+        );
+
         let err = McpError::ExecutionFailed {
             message: "pipe failed".to_string(),
         };
         assert_eq!(err.to_string(), "Tool execution failed: pipe failed");
+
+        let err = McpError::Conflict {
+            message: "session s1 is busy".to_string(),
+        };
+        assert_eq!(err.to_string(), "Conflict: session s1 is busy");
+
+        let err = McpError::Unauthorized {
+            message: "read-only key".to_string(),
+        };
+        assert_eq!(err.to_string(), "Unauthorized: read-only key");
     }
 
     #[test]
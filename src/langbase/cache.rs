@@ -0,0 +1,224 @@
+//! Pipe response cache.
+//!
+//! Detection and scoring prompts are often sent verbatim several times in one
+//! session. [`PipeCache`] keys responses by pipe name and a hash of the
+//! request content, keeps recent ones in an in-memory LRU, and optionally
+//! persists them to the `pipe_cache` table so they survive restarts.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use hashlink::LruCache;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use super::types::{PipeRequest, PipeResponse};
+use crate::config::PipeCacheConfig;
+use crate::storage::{PipeCacheEntry, SharedStorage};
+
+/// In-memory LRU of pipe responses with optional persistence.
+pub struct PipeCache {
+    config: PipeCacheConfig,
+    entries: Mutex<LruCache<String, (PipeResponse, DateTime<Utc>)>>,
+    storage: Option<SharedStorage>,
+}
+
+impl PipeCache {
+    /// Create an in-memory cache.
+    pub fn new(config: PipeCacheConfig) -> Self {
+        let capacity = config.capacity.max(1);
+        Self {
+            config,
+            entries: Mutex::new(LruCache::new(capacity)),
+            storage: None,
+        }
+    }
+
+    /// Persist entries to storage so they outlive the process.
+    pub fn with_storage(mut self, storage: SharedStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Cache key for a request, or `None` if the request should not be cached.
+    ///
+    /// Requests that continue a thread are never cached, since their output
+    /// depends on conversation state outside the request.
+    pub fn key(&self, request: &PipeRequest) -> Option<String> {
+        if request.thread_id.is_some() || !self.config.caches(&request.name) {
+            return None;
+        }
+
+        let variables: Option<BTreeMap<&String, &String>> =
+            request.variables.as_ref().map(|v| v.iter().collect());
+        let mut content = serde_json::json!({
+            "name": request.name,
+            "messages": request.messages,
+            "variables": variables,
+            "response_format": request.response_format,
+        });
+        // Only hashed when set, so keys of requests without overrides are unchanged
+        if !request.generation.is_empty() {
+            content["generation"] = serde_json::json!(request.generation);
+        }
+        let digest = Sha256::digest(content.to_string().as_bytes());
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Look up an unexpired response.
+    pub async fn get(&self, key: &str) -> Option<PipeResponse> {
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            match entries.get(key) {
+                Some((response, expires_at)) if *expires_at > Utc::now() => {
+                    return Some(response.clone());
+                }
+                Some(_) => {
+                    entries.remove(key);
+                }
+                None => {}
+            }
+        }
+
+        let storage = self.storage.as_ref()?;
+        let entry = match storage.get_pipe_cache(key).await {
+            Ok(entry) => entry?,
+            Err(e) => {
+                warn!(error = %e, "Failed to read pipe cache entry");
+                return None;
+            }
+        };
+        let response: PipeResponse = match serde_json::from_value(entry.response) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(error = %e, pipe = %entry.pipe_name, "Discarding unreadable pipe cache entry");
+                return None;
+            }
+        };
+
+        debug!(pipe = %entry.pipe_name, "Loaded pipe response from persistent cache");
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), (response.clone(), entry.expires_at));
+        Some(response)
+    }
+
+    /// Store a successful response.
+    ///
+    /// Persistence failures are logged and otherwise ignored; the cache must
+    /// never fail a pipe call.
+    pub async fn put(&self, key: &str, pipe_name: &str, response: &PipeResponse) {
+        let value = match serde_json::to_value(response) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!(error = %e, pipe = %pipe_name, "Failed to serialize pipe response for cache");
+                return;
+            }
+        };
+        let entry = PipeCacheEntry::new(key, pipe_name, value, self.config.ttl_secs);
+
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), (response.clone(), entry.expires_at));
+
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.put_pipe_cache(&entry).await {
+                warn!(error = %e, pipe = %pipe_name, "Failed to persist pipe cache entry");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langbase::Message;
+
+    fn enabled() -> PipeCacheConfig {
+        PipeCacheConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    fn response(completion: &str) -> PipeResponse {
+        PipeResponse {
+            success: true,
+            completion: completion.to_string(),
+            thread_id: None,
+            raw: None,
+            cache_hit: false,
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn test_key_depends_on_pipe_and_content() {
+        let cache = PipeCache::new(enabled());
+        let a = PipeRequest::new("detection-v1", vec![Message::user("x")]);
+        let b = PipeRequest::new("detection-v1", vec![Message::user("y")]);
+        let c = PipeRequest::new("other-v1", vec![Message::user("x")]);
+
+        let key = cache.key(&a).unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(Some(key.clone()), cache.key(&a.clone()));
+        assert_ne!(Some(key.clone()), cache.key(&b));
+        assert_ne!(Some(key), cache.key(&c));
+    }
+
+    #[test]
+    fn test_key_depends_on_generation_params() {
+        use crate::langbase::GenerationParams;
+
+        let cache = PipeCache::new(enabled());
+        let request = PipeRequest::new("detection-v1", vec![Message::user("x")]);
+        let hot = request.clone().with_generation(GenerationParams {
+            temperature: Some(1.5),
+            ..Default::default()
+        });
+
+        assert_ne!(cache.key(&request), cache.key(&hot));
+        assert_eq!(
+            cache.key(&request),
+            cache.key(&request.clone().with_generation(GenerationParams::default()))
+        );
+    }
+
+    #[test]
+    fn test_key_skips_disabled_and_threaded_requests() {
+        let request = PipeRequest::new("detection-v1", vec![Message::user("x")]);
+        assert!(PipeCache::new(PipeCacheConfig::default())
+            .key(&request)
+            .is_none());
+
+        let cache = PipeCache::new(enabled());
+        assert!(cache
+            .key(&request.clone().with_thread_id("thread-1"))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_after_put() {
+        let cache = PipeCache::new(enabled());
+        assert!(cache.get("k").await.is_none());
+
+        cache.put("k", "detection-v1", &response("cached")).await;
+        assert_eq!(cache.get("k").await.unwrap().completion, "cached");
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let cache = PipeCache::new(PipeCacheConfig {
+            capacity: 1,
+            ..enabled()
+        });
+        cache.put("a", "p", &response("a")).await;
+        cache.put("b", "p", &response("b")).await;
+
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.get("b").await.is_some());
+    }
+}
@@ -0,0 +1,99 @@
+//! Cooperative cancellation of pipe calls.
+//!
+//! A [`CancellationToken`] installed for the current task with
+//! [`with_cancellation`] applies to every pipe call made under it: once the
+//! token is cancelled, calls in flight are abandoned and later ones fail
+//! straight away with [`LangbaseError::Cancelled`]. Modes need no changes;
+//! the error propagates like any other pipe failure, so the invocation is
+//! logged as cancelled instead of completing in the background.
+
+use std::future::Future;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{LangbaseError, LangbaseResult};
+
+tokio::task_local! {
+    static CANCELLATION: CancellationToken;
+}
+
+/// Run `future` with `token` cancelling every pipe call it makes.
+pub async fn with_cancellation<F: Future>(token: CancellationToken, future: F) -> F::Output {
+    CANCELLATION.scope(token, future).await
+}
+
+/// The token installed for the current task, if any.
+pub fn current_cancellation() -> Option<CancellationToken> {
+    CANCELLATION.try_with(CancellationToken::clone).ok()
+}
+
+/// Run `future` unless the current task's token is cancelled first.
+///
+/// Returns [`LangbaseError::Cancelled`] without polling `future` if the token
+/// is already cancelled, and drops `future` as soon as it is.
+pub async fn cancellable<T>(future: impl Future<Output = LangbaseResult<T>>) -> LangbaseResult<T> {
+    match current_cancellation() {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(LangbaseError::Cancelled),
+            result = future => result,
+        },
+        None => future.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancellable_without_token_runs_future() {
+        let result = cancellable(async { Ok::<_, LangbaseError>(42) }).await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_cancellable_stops_in_flight_future() {
+        let token = CancellationToken::new();
+        let finished = AtomicBool::new(false);
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+        let result = with_cancellation(
+            token,
+            cancellable(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                finished.store(true, Ordering::SeqCst);
+                Ok(())
+            }),
+        )
+        .await;
+
+        assert!(matches!(result, Err(LangbaseError::Cancelled)));
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_cancellable_after_cancel_fails_immediately() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let polled = AtomicBool::new(false);
+
+        let result = with_cancellation(
+            token,
+            cancellable(async {
+                polled.store(true, Ordering::SeqCst);
+                Ok(())
+            }),
+        )
+        .await;
+
+        assert!(matches!(result, Err(LangbaseError::Cancelled)));
+        assert!(!polled.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,183 @@
+//! Per-pipe circuit breakers for the Langbase client.
+//!
+//! Each pipe gets its own [`CircuitBreaker`] (the same state machine the
+//! self-improvement system uses), so one failing pipe is short-circuited
+//! without blocking calls to healthy ones.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::config::PipeCircuitBreakerConfig;
+use crate::error::LangbaseError;
+use crate::self_improvement::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+
+/// Circuit breakers keyed by pipe name, created on first use.
+pub struct PipeCircuitBreakers {
+    config: CircuitBreakerConfig,
+    breakers: Mutex<HashMap<String, CircuitBreaker>>,
+}
+
+/// Circuit breaker state of a single pipe.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipeCircuitStatus {
+    /// Pipe name.
+    pub pipe: String,
+    /// Current circuit state.
+    pub state: CircuitState,
+    /// Failures since the last success.
+    pub consecutive_failures: u32,
+    /// Failed calls since startup.
+    pub total_failures: u32,
+    /// Successful calls since startup.
+    pub total_successes: u32,
+    /// Seconds until an open circuit allows a trial call.
+    pub retry_after_secs: Option<i64>,
+}
+
+impl PipeCircuitBreakers {
+    /// Create an empty set of breakers.
+    pub fn new(config: &PipeCircuitBreakerConfig) -> Self {
+        Self {
+            config: CircuitBreakerConfig {
+                failure_threshold: config.failure_threshold,
+                success_threshold: 1,
+                recovery_timeout_secs: config.cooldown_secs,
+            },
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check whether `pipe` may be called.
+    ///
+    /// Returns [`LangbaseError::Unavailable`] while the pipe's circuit is open.
+    pub fn check(&self, pipe: &str) -> Result<(), LangbaseError> {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(breaker) = breakers.get_mut(pipe) else {
+            return Ok(());
+        };
+        if breaker.can_execute() {
+            return Ok(());
+        }
+
+        let retry_after = breaker
+            .time_until_recovery()
+            .map(|d| d.num_seconds().max(0))
+            .unwrap_or(0);
+        Err(LangbaseError::Unavailable {
+            message: format!(
+                "Circuit open for pipe '{}' after {} consecutive failures; retry in {}s",
+                pipe,
+                breaker.consecutive_failures(),
+                retry_after
+            ),
+            retries: 0,
+        })
+    }
+
+    /// Record a successful call to `pipe`.
+    pub fn record_success(&self, pipe: &str) {
+        self.with_breaker(pipe, CircuitBreaker::record_success);
+    }
+
+    /// Record a failed call to `pipe`.
+    pub fn record_failure(&self, pipe: &str) {
+        self.with_breaker(pipe, CircuitBreaker::record_failure);
+    }
+
+    /// State of every pipe called so far, sorted by pipe name.
+    pub fn status(&self) -> Vec<PipeCircuitStatus> {
+        let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let mut status: Vec<_> = breakers
+            .iter()
+            .map(|(pipe, breaker)| PipeCircuitStatus {
+                pipe: pipe.clone(),
+                state: breaker.state(),
+                consecutive_failures: breaker.consecutive_failures(),
+                total_failures: breaker.total_failures(),
+                total_successes: breaker.total_successes(),
+                retry_after_secs: breaker
+                    .time_until_recovery()
+                    .map(|d| d.num_seconds().max(0)),
+            })
+            .collect();
+        status.sort_by(|a, b| a.pipe.cmp(&b.pipe));
+        status
+    }
+
+    fn with_breaker(&self, pipe: &str, f: impl FnOnce(&mut CircuitBreaker)) {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = breakers
+            .entry(pipe.to_string())
+            .or_insert_with(|| CircuitBreaker::new(self.config.clone()));
+        f(breaker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakers(failure_threshold: u32, cooldown_secs: u64) -> PipeCircuitBreakers {
+        PipeCircuitBreakers::new(&PipeCircuitBreakerConfig {
+            failure_threshold,
+            cooldown_secs,
+        })
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breakers = breakers(2, 60);
+        assert!(breakers.check("got").is_ok());
+
+        breakers.record_failure("got");
+        assert!(breakers.check("got").is_ok());
+        breakers.record_failure("got");
+
+        let err = breakers.check("got").unwrap_err();
+        assert!(matches!(err, LangbaseError::Unavailable { retries: 0, .. }));
+        assert!(err.to_string().contains("Circuit open for pipe 'got'"));
+
+        // Other pipes are unaffected
+        assert!(breakers.check("linear").is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breakers = breakers(2, 60);
+        breakers.record_failure("got");
+        breakers.record_success("got");
+        breakers.record_failure("got");
+        assert!(breakers.check("got").is_ok());
+    }
+
+    #[test]
+    fn test_trial_call_after_cooldown_closes_circuit() {
+        let breakers = breakers(1, 0);
+        breakers.record_failure("got");
+
+        // Cooldown elapsed: the trial call is allowed
+        assert!(breakers.check("got").is_ok());
+        assert_eq!(breakers.status()[0].state, CircuitState::HalfOpen);
+
+        breakers.record_success("got");
+        assert_eq!(breakers.status()[0].state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_status_reports_open_pipes() {
+        let breakers = breakers(1, 60);
+        breakers.record_success("linear");
+        breakers.record_failure("got");
+
+        let status = breakers.status();
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[0].pipe, "got");
+        assert_eq!(status[0].state, CircuitState::Open);
+        assert_eq!(status[0].total_failures, 1);
+        assert!(status[0].retry_after_secs.is_some());
+        assert_eq!(status[1].state, CircuitState::Closed);
+        assert_eq!(status[1].retry_after_secs, None);
+    }
+}
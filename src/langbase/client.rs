@@ -1,12 +1,31 @@
-use reqwest::Client;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use super::types::{CreatePipeRequest, CreatePipeResponse, Message, PipeRequest, PipeResponse};
-use crate::config::{LangbaseConfig, RequestConfig};
+use super::cache::PipeCache;
+use super::cancellation::cancellable;
+use super::circuit_breaker::PipeCircuitBreakers;
+use super::client_log::{client_log, LogLevel};
+use super::concurrency::ConcurrencyLimiter;
+use super::fan_out::{fan_out, FanOut};
+use super::middleware::{PipeAttempt, PipeMiddleware};
+use super::payload_log::PayloadLogger;
+use super::rate_limit::RateLimiter;
+use super::retry::{is_retryable, next_retry_delay, parse_retry_after, status_error};
+use super::stream::{current_stream_sink, read_sse_completion, StreamSink};
+use super::types::{
+    CreatePipeRequest, CreatePipeResponse, CreateThreadRequest, EmbedRequest, Message, PipeInfo,
+    PipeRequest, PipeResponse, Thread, ThreadMessage, WorkflowRunRequest, WorkflowRunResponse,
+};
+use crate::config::{LangbaseConfig, NetworkConfig, RequestConfig, TlsVersion};
 use crate::error::{LangbaseError, LangbaseResult};
 use crate::prompts::{
-    DIVERGENT_REASONING_PROMPT, LINEAR_REASONING_PROMPT, REFLECTION_PROMPT, TREE_REASONING_PROMPT,
+    PipeDefinition, AUTO_ROUTER_PIPE, CODE_PIPE, DECISION_FRAMEWORK_PIPE, DETECTION_PIPE,
+    DIVERGENT_PIPE, GOT_PIPE, LINEAR_PIPE, REFLECTION_PIPE, TREE_PIPE,
 };
 
 /// Client for interacting with Langbase Pipes API
@@ -16,13 +35,18 @@ pub struct LangbaseClient {
     base_url: String,
     api_key: String,
     request_config: RequestConfig,
+    cache: Option<Arc<PipeCache>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    circuit_breakers: Option<Arc<PipeCircuitBreakers>>,
+    concurrency: Option<Arc<ConcurrencyLimiter>>,
+    middlewares: Vec<Arc<dyn PipeMiddleware>>,
 }
 
 impl LangbaseClient {
     /// Create a new Langbase client
     pub fn new(config: &LangbaseConfig, request_config: RequestConfig) -> LangbaseResult<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_millis(request_config.timeout_ms))
+        let builder = Client::builder().timeout(Duration::from_millis(request_config.timeout_ms));
+        let client = configure_network(builder, &config.network)?
             .build()
             .map_err(LangbaseError::Http)?;
 
@@ -31,41 +55,189 @@ impl LangbaseClient {
             base_url: config.base_url.trim_end_matches('/').to_string(),
             api_key: config.api_key.clone(),
             request_config,
+            cache: None,
+            rate_limiter: None,
+            circuit_breakers: None,
+            concurrency: None,
+            middlewares: Vec::new(),
         })
     }
 
+    /// Serve repeated requests from a pipe response cache.
+    ///
+    /// Responses served from the cache have [`PipeResponse::cache_hit`] set.
+    pub fn with_cache(mut self, cache: Arc<PipeCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Throttle requests per pipe.
+    ///
+    /// Requests over a pipe's limit wait for a token rather than failing.
+    /// Retries draw tokens too; cache hits do not.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Short-circuit pipes that keep failing.
+    ///
+    /// While a pipe's circuit is open, calls to it fail immediately with
+    /// [`LangbaseError::Unavailable`] instead of being sent.
+    pub fn with_circuit_breakers(mut self, circuit_breakers: Arc<PipeCircuitBreakers>) -> Self {
+        self.circuit_breakers = Some(circuit_breakers);
+        self
+    }
+
+    /// Bound the number of pipe requests in flight at once.
+    ///
+    /// Requests over a limit wait for a running one to finish. Slots are held
+    /// only while a request is being sent and read, not between retries.
+    pub fn with_concurrency_limiter(mut self, concurrency: Arc<ConcurrencyLimiter>) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Store the redacted request and response body of every attempt.
+    ///
+    /// Cache hits and short-circuited calls are not logged since nothing is
+    /// sent.
+    pub fn with_payload_logger(self, payload_logger: Arc<PayloadLogger>) -> Self {
+        self.with_middleware(payload_logger)
+    }
+
+    /// Run `middleware` around every pipe call attempt.
+    ///
+    /// Middlewares run in the order they are added. See [`PipeMiddleware`]
+    /// for what each hook may do.
+    pub fn with_middleware(mut self, middleware: Arc<dyn PipeMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
     /// Call a Langbase pipe with the given request
+    ///
+    /// When a stream sink is installed for the current task (see
+    /// [`with_stream_sink`](super::with_stream_sink)), the run is streamed and
+    /// each text fragment is passed to the sink before the full response is
+    /// returned.
+    ///
+    /// When a cancellation token is installed for the current task (see
+    /// [`with_cancellation`](super::with_cancellation)), cancelling it
+    /// abandons the call, including any retry it is waiting to make, with
+    /// [`LangbaseError::Cancelled`].
     pub async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let pipe_name = request.name.clone();
+        let result = cancellable(self.run_pipe(request)).await;
+        if let Err(LangbaseError::Cancelled) = result {
+            warn!(pipe = %pipe_name, "Langbase pipe call cancelled");
+        }
+        result
+    }
+
+    /// Run a pipe with caching, retries and middleware (internal)
+    async fn run_pipe(&self, mut request: PipeRequest) -> LangbaseResult<PipeResponse> {
         let url = format!("{}/v1/pipes/run", self.base_url);
         let pipe_name = request.name.clone();
+        let sink = current_stream_sink();
+        request.stream = sink.is_some();
+
+        let cache_key = self.cache.as_ref().and_then(|cache| cache.key(&request));
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            if let Some(mut response) = cache.get(key).await {
+                info!(pipe = %pipe_name, "Serving Langbase pipe response from cache");
+                if let Some(sink) = &sink {
+                    sink(&response.completion);
+                }
+                response.cache_hit = true;
+                return Ok(response);
+            }
+        }
+
+        if let Some(breakers) = &self.circuit_breakers {
+            if let Err(e) = breakers.check(&pipe_name) {
+                warn!(pipe = %pipe_name, "Langbase pipe call short-circuited");
+                return Err(e);
+            }
+        }
 
         info!(
             pipe = %pipe_name,
             url = %url,
             messages_count = request.messages.len(),
+            stream = request.stream,
             "Calling Langbase pipe"
         );
 
         let mut last_error = None;
         let mut retries = 0;
+        let mut delay = Duration::ZERO;
 
         while retries <= self.request_config.max_retries {
             if retries > 0 {
-                let delay = Duration::from_millis(
-                    self.request_config.retry_delay_ms * (2_u64.pow(retries - 1)),
-                );
                 warn!(
                     pipe = %pipe_name,
                     retry = retries,
                     delay_ms = delay.as_millis(),
                     "Retrying Langbase request"
                 );
+                client_log(
+                    LogLevel::Warning,
+                    "langbase",
+                    serde_json::json!({
+                        "message": "Retrying Langbase request",
+                        "pipe": pipe_name,
+                        "retry": retries,
+                        "delay_ms": delay.as_millis() as u64,
+                    }),
+                );
                 tokio::time::sleep(delay).await;
             }
 
-            let start = Instant::now();
+            if let Some(limiter) = &self.rate_limiter {
+                let waited = limiter.acquire(&pipe_name).await;
+                if !waited.is_zero() {
+                    info!(
+                        pipe = %pipe_name,
+                        waited_ms = waited.as_millis(),
+                        "Langbase request delayed by pipe rate limit"
+                    );
+                }
+            }
+
+            let permit = match &self.concurrency {
+                Some(concurrency) => Some(concurrency.acquire(&pipe_name).await),
+                None => None,
+            };
+            if let Some(waited) = permit.as_ref().map(|p| p.waited()) {
+                if !waited.is_zero() {
+                    info!(
+                        pipe = %pipe_name,
+                        waited_ms = waited.as_millis(),
+                        "Langbase request delayed by concurrency limit"
+                    );
+                }
+            }
 
-            match self.execute_request(&url, &request).await {
+            let attempt = retries + 1;
+            let (outgoing, headers) = match self.prepare_attempt(&request, attempt).await {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    warn!(pipe = %pipe_name, error = %e, "Langbase pipe call refused by middleware");
+                    return Err(e);
+                }
+            };
+
+            let start = Instant::now();
+            let result = self
+                .execute_request(&url, &outgoing, headers, sink.as_ref())
+                .await;
+            drop(permit);
+            let result = self
+                .finish_attempt(&outgoing, result, attempt, start.elapsed())
+                .await;
+
+            match result {
                 Ok(response) => {
                     let latency = start.elapsed();
                     info!(
@@ -73,6 +245,29 @@ impl LangbaseClient {
                         latency_ms = latency.as_millis(),
                         "Langbase pipe call succeeded"
                     );
+                    if let Some(breakers) = &self.circuit_breakers {
+                        breakers.record_success(&pipe_name);
+                    }
+                    if let Some(schema) = request.output_schema() {
+                        if let Err(violation) = schema.validate_completion(&response.completion) {
+                            warn!(
+                                pipe = %pipe_name,
+                                schema = %schema.name,
+                                path = %violation.path,
+                                error = %violation.message,
+                                "Langbase pipe response violates its output schema"
+                            );
+                            return Err(LangbaseError::SchemaViolation {
+                                pipe: pipe_name,
+                                schema: schema.name.clone(),
+                                path: violation.path,
+                                message: violation.message,
+                            });
+                        }
+                    }
+                    if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+                        cache.put(key, &pipe_name, &response).await;
+                    }
                     return Ok(response);
                 }
                 Err(e) => {
@@ -84,12 +279,24 @@ impl LangbaseClient {
                         retry = retries,
                         "Langbase pipe call failed"
                     );
-                    last_error = Some(e);
                     retries += 1;
+                    let next_delay = next_retry_delay(&self.request_config, retries, &e);
+                    last_error = Some(e);
+                    match next_delay {
+                        Some(next_delay) => delay = next_delay,
+                        None => break,
+                    }
                 }
             }
         }
 
+        // Only failures that suggest the pipe itself is unhealthy trip the breaker
+        if let Some(breakers) = &self.circuit_breakers {
+            if last_error.as_ref().is_some_and(is_retryable) {
+                breakers.record_failure(&pipe_name);
+            }
+        }
+
         Err(LangbaseError::Unavailable {
             message: last_error
                 .map(|e| e.to_string())
@@ -98,11 +305,75 @@ impl LangbaseClient {
         })
     }
 
+    /// Call a pipe once per request, running up to `max_concurrency` of the
+    /// calls at a time (0 for no limit)
+    ///
+    /// Every call goes through [`call_pipe`](Self::call_pipe), so caching,
+    /// retries and the configured concurrency limits apply to each one. The
+    /// result holds the responses and errors, tagged with request indices.
+    pub async fn call_pipe_parallel(
+        &self,
+        requests: Vec<PipeRequest>,
+        max_concurrency: usize,
+    ) -> FanOut {
+        fan_out(requests, max_concurrency, |request| self.call_pipe(request)).await
+    }
+
+    /// Run the middlewares' request hooks on a copy of `request`
+    async fn prepare_attempt<'a>(
+        &self,
+        request: &'a PipeRequest,
+        attempt: u32,
+    ) -> LangbaseResult<(Cow<'a, PipeRequest>, HeaderMap)> {
+        let mut headers = HeaderMap::new();
+        if self.middlewares.is_empty() {
+            return Ok((Cow::Borrowed(request), headers));
+        }
+
+        let mut outgoing = request.clone();
+        for middleware in &self.middlewares {
+            middleware
+                .on_request(&mut outgoing, &mut headers, attempt)
+                .await?;
+        }
+        Ok((Cow::Owned(outgoing), headers))
+    }
+
+    /// Run the middlewares' response or error hooks on an attempt's result
+    async fn finish_attempt(
+        &self,
+        request: &PipeRequest,
+        mut result: LangbaseResult<PipeResponse>,
+        attempt: u32,
+        latency: Duration,
+    ) -> LangbaseResult<PipeResponse> {
+        let attempt = PipeAttempt {
+            number: attempt,
+            latency,
+        };
+        if let Ok(response) = &mut result {
+            for middleware in &self.middlewares {
+                if let Err(e) = middleware.on_response(request, response, &attempt).await {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if let Err(e) = &result {
+            for middleware in &self.middlewares {
+                middleware.on_error(request, e, &attempt).await;
+            }
+        }
+        result
+    }
+
     /// Execute a single request (internal)
     async fn execute_request(
         &self,
         url: &str,
         request: &PipeRequest,
+        headers: HeaderMap,
+        sink: Option<&StreamSink>,
     ) -> LangbaseResult<PipeResponse> {
         debug!(
             pipe = %request.name,
@@ -115,6 +386,7 @@ impl LangbaseClient {
             .post(url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
+            .headers(headers)
             .json(request)
             .send()
             .await
@@ -131,14 +403,16 @@ impl LangbaseClient {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = parse_retry_after(response.headers());
             let error_body = response.text().await.unwrap_or_else(|e| {
                 warn!(error = %e, status = %status, "Failed to read pipe run error response body");
                 "Unable to read error response".to_string()
             });
-            return Err(LangbaseError::Api {
-                status: status.as_u16(),
-                message: error_body,
-            });
+            return Err(status_error(status, retry_after, error_body));
+        }
+
+        if let Some(sink) = sink {
+            return read_sse_completion(response, sink, self.request_config.timeout_ms).await;
         }
 
         let pipe_response: PipeResponse =
@@ -152,6 +426,182 @@ impl LangbaseClient {
         Ok(pipe_response)
     }
 
+    /// Compute embeddings for text chunks with the Langbase embed API.
+    ///
+    /// Chunks are sent in batches of [`EmbedRequest::MAX_CHUNKS`]; the returned
+    /// vectors are in the same order as the input.
+    pub async fn embed(&self, chunks: &[String], model: &str) -> LangbaseResult<Vec<Vec<f32>>> {
+        let url = format!("{}/v1/embed", self.base_url);
+        let mut vectors = Vec::with_capacity(chunks.len());
+
+        for batch in chunks.chunks(EmbedRequest::MAX_CHUNKS) {
+            debug!(model = %model, chunks = batch.len(), "Calling Langbase embed");
+
+            let request = EmbedRequest::new(batch.to_vec(), model);
+            let response = self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.is_timeout() {
+                        LangbaseError::Timeout {
+                            timeout_ms: self.request_config.timeout_ms,
+                        }
+                    } else {
+                        LangbaseError::Http(e)
+                    }
+                })?;
+
+            let status = response.status();
+
+            if !status.is_success() {
+                let error_body = response.text().await.unwrap_or_else(|e| {
+                    warn!(error = %e, status = %status, "Failed to read embed error response body");
+                    "Unable to read error response".to_string()
+                });
+                return Err(LangbaseError::Api {
+                    status: status.as_u16(),
+                    message: error_body,
+                });
+            }
+
+            let batch_vectors: Vec<Vec<f32>> =
+                response
+                    .json()
+                    .await
+                    .map_err(|e| LangbaseError::InvalidResponse {
+                        message: format!("Failed to parse embed response: {}", e),
+                    })?;
+
+            if batch_vectors.len() != batch.len() {
+                return Err(LangbaseError::InvalidResponse {
+                    message: format!(
+                        "Embed returned {} vectors for {} chunks",
+                        batch_vectors.len(),
+                        batch.len()
+                    ),
+                });
+            }
+            vectors.extend(batch_vectors);
+        }
+
+        Ok(vectors)
+    }
+
+    /// Create a thread, optionally seeded with messages.
+    ///
+    /// Pass the returned [`Thread::id`] to [`PipeRequest::with_thread_id`] so
+    /// later pipe runs reuse the server-side history.
+    pub async fn create_thread(&self, request: CreateThreadRequest) -> LangbaseResult<Thread> {
+        let url = format!("{}/v1/threads", self.base_url);
+        debug!(
+            messages = request.messages.len(),
+            "Creating Langbase thread"
+        );
+
+        let thread: Thread = self
+            .send_json_request(self.client.post(&url).json(&request), "create thread")
+            .await?;
+
+        info!(thread_id = %thread.id, "Thread created");
+        Ok(thread)
+    }
+
+    /// Append messages to a thread and return the stored messages.
+    pub async fn append_thread_messages(
+        &self,
+        thread_id: &str,
+        messages: &[Message],
+    ) -> LangbaseResult<Vec<ThreadMessage>> {
+        let url = format!("{}/v1/threads/{}/messages", self.base_url, thread_id);
+        debug!(thread_id = %thread_id, messages = messages.len(), "Appending thread messages");
+
+        self.send_json_request(
+            self.client.post(&url).json(messages),
+            "append thread messages",
+        )
+        .await
+    }
+
+    /// List the messages stored in a thread, oldest first.
+    pub async fn list_thread_messages(
+        &self,
+        thread_id: &str,
+    ) -> LangbaseResult<Vec<ThreadMessage>> {
+        let url = format!("{}/v1/threads/{}/messages", self.base_url, thread_id);
+        debug!(thread_id = %thread_id, "Listing thread messages");
+
+        self.send_json_request(self.client.get(&url), "list thread messages")
+            .await
+    }
+
+    /// Run a workflow of chained pipe calls server-side.
+    ///
+    /// Steps run in order on Langbase, so an N-step chain costs one round
+    /// trip instead of N. Failed steps are reported per step rather than as
+    /// an error; only a failed run as a whole returns `Err`.
+    pub async fn run_workflow(
+        &self,
+        request: &WorkflowRunRequest,
+    ) -> LangbaseResult<WorkflowRunResponse> {
+        let url = format!("{}/v1/workflows/run", self.base_url);
+        debug!(workflow = %request.name, steps = request.steps.len(), "Running Langbase workflow");
+
+        let response: WorkflowRunResponse = self
+            .send_json_request(self.client.post(&url).json(request), "run workflow")
+            .await?;
+
+        info!(
+            workflow = %request.name,
+            run_id = %response.id,
+            steps = response.steps.len(),
+            "Workflow run completed"
+        );
+        Ok(response)
+    }
+
+    async fn send_json_request<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        action: &str,
+    ) -> LangbaseResult<T> {
+        let response = request
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    LangbaseError::Timeout {
+                        timeout_ms: self.request_config.timeout_ms,
+                    }
+                } else {
+                    LangbaseError::Http(e)
+                }
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            let retry_after = parse_retry_after(response.headers());
+            let error_body = response.text().await.unwrap_or_else(|e| {
+                warn!(error = %e, status = %status, action = %action, "Failed to read error response body");
+                "Unable to read error response".to_string()
+            });
+            return Err(status_error(status, retry_after, error_body));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| LangbaseError::InvalidResponse {
+                message: format!("Failed to parse {} response: {}", action, e),
+            })
+    }
+
     /// Get the base URL (for testing)
     pub fn base_url(&self) -> &str {
         &self.base_url
@@ -238,61 +688,37 @@ impl LangbaseClient {
         Ok(())
     }
 
+    /// List the pipes in the Langbase account with their stored settings
+    pub async fn list_pipes(&self) -> LangbaseResult<Vec<PipeInfo>> {
+        let url = format!("{}/v1/pipes", self.base_url);
+        debug!("Listing Langbase pipes");
+
+        self.send_json_request(self.client.get(&url), "list pipes")
+            .await
+    }
+
     /// Ensure the linear reasoning pipe exists, creating it if needed
     pub async fn ensure_linear_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Linear reasoning mode for MCP server")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.7)
-            .with_max_tokens(2000)
-            .with_messages(vec![Message::system(LINEAR_REASONING_PROMPT)]);
-
-        self.ensure_pipe_internal(request, "Linear reasoning").await
+        self.ensure_defined_pipe(pipe_name, &LINEAR_PIPE, "Linear reasoning")
+            .await
     }
 
     /// Ensure the tree reasoning pipe exists, creating it if needed
     pub async fn ensure_tree_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Tree-based reasoning mode for exploring multiple paths")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.8) // Slightly higher for exploration
-            .with_max_tokens(3000) // More tokens for multiple branches
-            .with_messages(vec![Message::system(TREE_REASONING_PROMPT)]);
-
-        self.ensure_pipe_internal(request, "Tree reasoning").await
+        self.ensure_defined_pipe(pipe_name, &TREE_PIPE, "Tree reasoning")
+            .await
     }
 
     /// Ensure the divergent reasoning pipe exists, creating it if needed
     pub async fn ensure_divergent_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Divergent reasoning mode for creative perspectives")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.9) // Higher for maximum creativity
-            .with_max_tokens(3000) // More tokens for multiple perspectives
-            .with_messages(vec![Message::system(DIVERGENT_REASONING_PROMPT)]);
-
-        self.ensure_pipe_internal(request, "Divergent reasoning")
+        self.ensure_defined_pipe(pipe_name, &DIVERGENT_PIPE, "Divergent reasoning")
             .await
     }
 
     /// Ensure the reflection reasoning pipe exists, creating it if needed
     pub async fn ensure_reflection_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Reflection mode for meta-cognitive analysis")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.6) // Lower for precise analysis
-            .with_max_tokens(2500)
-            .with_messages(vec![Message::system(REFLECTION_PROMPT)]);
-
-        self.ensure_pipe_internal(request, "Reflection").await
+        self.ensure_defined_pipe(pipe_name, &REFLECTION_PIPE, "Reflection")
+            .await
     }
 
     /// Ensure all reasoning pipes exist, creating them if needed
@@ -313,52 +739,32 @@ impl LangbaseClient {
         // Consolidated decision framework mode (1 pipe - prompts passed dynamically)
         self.ensure_decision_framework_pipes().await?;
 
+        // Code reasoning mode (1 pipe - prompts passed dynamically)
+        self.ensure_code_pipe("code-reasoning-v1").await?;
+
         // Auto mode router (1 pipe)
         self.ensure_auto_router_pipe().await?;
 
-        info!("All pipes ready (8 total)");
+        info!("All pipes ready (9 total)");
         Ok(())
     }
 
     /// Ensure the auto mode router pipe exists
     pub async fn ensure_auto_router_pipe(&self) -> LangbaseResult<()> {
-        let request = CreatePipeRequest::new("mode-router-v1")
-            .with_description("Auto mode router for intelligent mode selection")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.5)
-            .with_max_tokens(1000);
-
-        self.ensure_pipe_internal(request, "Auto mode router").await
+        self.ensure_defined_pipe(AUTO_ROUTER_PIPE.name, &AUTO_ROUTER_PIPE, "Auto mode router")
+            .await
     }
 
     /// Ensure the consolidated detection pipe exists (prompts passed dynamically)
     pub async fn ensure_consolidated_detection_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        // No system prompt - detection type (bias/fallacy) prompt passed dynamically
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Consolidated detection mode for bias and fallacy analysis")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.5) // Lower for precise analysis
-            .with_max_tokens(3000);
-
-        self.ensure_pipe_internal(request, "Detection").await
+        self.ensure_defined_pipe(pipe_name, &DETECTION_PIPE, "Detection")
+            .await
     }
 
     /// Ensure the consolidated GoT pipe exists (prompts passed dynamically)
     pub async fn ensure_consolidated_got_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
-        // No system prompt - operation type prompt passed dynamically
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description("Consolidated GoT mode for graph-based reasoning operations")
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.7)
-            .with_max_tokens(2500);
-
-        self.ensure_pipe_internal(request, "GoT reasoning").await
+        self.ensure_defined_pipe(pipe_name, &GOT_PIPE, "GoT reasoning")
+            .await
     }
 
     /// Ensure detection pipe exists
@@ -382,18 +788,7 @@ impl LangbaseClient {
         &self,
         pipe_name: &str,
     ) -> LangbaseResult<()> {
-        // No system prompt - operation type prompt passed dynamically
-        let request = CreatePipeRequest::new(pipe_name)
-            .with_description(
-                "Consolidated decision framework for decision, perspective, evidence, and Bayesian analysis",
-            )
-            .with_model("openai:gpt-4o-mini")
-            .with_upsert(true)
-            .with_json_output(true)
-            .with_temperature(0.6)
-            .with_max_tokens(4000);
-
-        self.ensure_pipe_internal(request, "Decision framework")
+        self.ensure_defined_pipe(pipe_name, &DECISION_FRAMEWORK_PIPE, "Decision framework")
             .await
     }
 
@@ -405,6 +800,23 @@ impl LangbaseClient {
         Ok(())
     }
 
+    /// Ensure the code reasoning pipe exists (prompts passed dynamically)
+    pub async fn ensure_code_pipe(&self, pipe_name: &str) -> LangbaseResult<()> {
+        self.ensure_defined_pipe(pipe_name, &CODE_PIPE, "Code reasoning")
+            .await
+    }
+
+    /// Ensure a pipe from the prompts module exists under the given name
+    async fn ensure_defined_pipe(
+        &self,
+        pipe_name: &str,
+        definition: &PipeDefinition,
+        mode_name: &str,
+    ) -> LangbaseResult<()> {
+        let request = CreatePipeRequest::from_definition(pipe_name, definition);
+        self.ensure_pipe_internal(request, mode_name).await
+    }
+
     /// Internal helper to ensure a pipe exists
     async fn ensure_pipe_internal(
         &self,
@@ -428,6 +840,50 @@ impl LangbaseClient {
     }
 }
 
+/// Apply proxy and TLS settings to a client builder
+fn configure_network(
+    mut builder: reqwest::ClientBuilder,
+    network: &NetworkConfig,
+) -> LangbaseResult<reqwest::ClientBuilder> {
+    if let Some(url) = &network.proxy {
+        let proxy = reqwest::Proxy::all(url).map_err(|e| LangbaseError::ClientConfig {
+            message: format!("invalid proxy URL: {}", e),
+        })?;
+        builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env()));
+    }
+
+    if let Some(path) = &network.ca_cert_path {
+        let pem = std::fs::read(path).map_err(|e| LangbaseError::ClientConfig {
+            message: format!("failed to read CA certificate {}: {}", path.display(), e),
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            LangbaseError::ClientConfig {
+                message: format!("invalid CA certificate {}: {}", path.display(), e),
+            }
+        })?;
+        if certs.is_empty() {
+            return Err(LangbaseError::ClientConfig {
+                message: format!("no certificates found in {}", path.display()),
+            });
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+        if network.ca_cert_only {
+            builder = builder.tls_built_in_root_certs(false);
+        }
+    }
+
+    if let Some(version) = network.min_tls_version {
+        builder = builder.min_tls_version(match version {
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        });
+    }
+
+    Ok(builder)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,6 +893,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let request_config = RequestConfig::default();
@@ -450,6 +907,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let request_config = RequestConfig::default();
@@ -463,6 +921,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com/".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let request_config = RequestConfig::default();
@@ -472,17 +931,65 @@ mod tests {
         assert_eq!(client.base_url(), "https://api.langbase.com");
     }
 
+    #[test]
+    fn test_client_rejects_bad_network_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty_pem = dir.path().join("empty.pem");
+        std::fs::write(&empty_pem, "not a certificate").unwrap();
+
+        let cases = [
+            NetworkConfig {
+                proxy: Some("not a url".to_string()),
+                ..Default::default()
+            },
+            NetworkConfig {
+                ca_cert_path: Some(dir.path().join("missing.pem")),
+                ..Default::default()
+            },
+            NetworkConfig {
+                ca_cert_path: Some(empty_pem),
+                ..Default::default()
+            },
+        ];
+        for network in cases {
+            let config = LangbaseConfig {
+                api_key: "test_key".to_string(),
+                base_url: "https://api.langbase.com".to_string(),
+                network,
+            };
+            let result = LangbaseClient::new(&config, RequestConfig::default());
+            assert!(matches!(result, Err(LangbaseError::ClientConfig { .. })));
+        }
+    }
+
+    #[test]
+    fn test_client_with_proxy_and_min_tls_version() {
+        let config = LangbaseConfig {
+            api_key: "test_key".to_string(),
+            base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig {
+                proxy: Some("http://proxy.internal:3128".to_string()),
+                min_tls_version: Some(TlsVersion::Tls12),
+                ..Default::default()
+            },
+        };
+        assert!(LangbaseClient::new(&config, RequestConfig::default()).is_ok());
+    }
+
     #[test]
     fn test_client_with_custom_request_config() {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://custom.api.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let request_config = RequestConfig {
             timeout_ms: 60000,
             max_retries: 5,
             retry_delay_ms: 2000,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
 
         let client = LangbaseClient::new(&config, request_config);
@@ -582,6 +1089,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com///".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         assert_eq!(client.base_url(), "https://api.langbase.com");
@@ -592,6 +1100,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com/v2/".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         assert_eq!(client.base_url(), "https://api.langbase.com/v2");
@@ -602,6 +1111,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         assert_eq!(client.base_url(), "api.langbase.com");
@@ -612,6 +1122,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "http://localhost:8080/".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         assert_eq!(client.base_url(), "http://localhost:8080");
@@ -622,6 +1133,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_key".to_string(),
             base_url: "https://api.langbase.com:443/".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         assert_eq!(client.base_url(), "https://api.langbase.com:443");
@@ -880,10 +1392,13 @@ mod tests {
             timeout_ms: 30000,
             max_retries: 0,
             retry_delay_ms: 1000,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
         let langbase_config = LangbaseConfig {
             api_key: "test".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&langbase_config, config);
         assert!(client.is_ok());
@@ -895,10 +1410,13 @@ mod tests {
             timeout_ms: 300000,
             max_retries: 3,
             retry_delay_ms: 1000,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
         let langbase_config = LangbaseConfig {
             api_key: "test".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&langbase_config, config);
         assert!(client.is_ok());
@@ -910,10 +1428,13 @@ mod tests {
             timeout_ms: 30000,
             max_retries: 10,
             retry_delay_ms: 500,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
         let langbase_config = LangbaseConfig {
             api_key: "test".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&langbase_config, config);
         assert!(client.is_ok());
@@ -925,6 +1446,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default());
         assert!(client.is_ok());
@@ -936,6 +1458,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: long_key.clone(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default());
         assert!(client.is_ok());
@@ -946,6 +1469,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test".to_string(),
             base_url: "https://api.langbase.com/".to_string(),
+            network: NetworkConfig::default(),
         };
         let client = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         let url1 = client.base_url();
@@ -959,6 +1483,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let client1 = LangbaseClient::new(&config, RequestConfig::default()).unwrap();
         let client2 = client1.clone();
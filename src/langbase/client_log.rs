@@ -0,0 +1,113 @@
+//! Log messages for the MCP client.
+//!
+//! Key events of a tool call, such as pipe retries, provider fallbacks and
+//! checkpoint restores, are reported through the [`ClientLogSink`] installed
+//! for the current task with [`with_client_log_sink`], which the MCP layer
+//! forwards as `notifications/message`. They are traced as usual either way.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Severity of a client log message, in increasing order (RFC 5424).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    /// Detailed debugging information.
+    Debug,
+    /// Routine progress.
+    #[default]
+    Info,
+    /// Normal but significant events.
+    Notice,
+    /// Recoverable problems.
+    Warning,
+    /// Failed operations.
+    Error,
+    /// Critical conditions.
+    Critical,
+    /// Conditions needing immediate action.
+    Alert,
+    /// The server is unusable.
+    Emergency,
+}
+
+/// One log message for the client.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientLogMessage {
+    /// Severity of the message.
+    pub level: LogLevel,
+    /// Component that logged the message, e.g. `langbase` or `backtracking`.
+    pub logger: String,
+    /// The message and its fields.
+    pub data: Value,
+}
+
+/// Callback receiving client log messages.
+pub type ClientLogSink = Arc<dyn Fn(&ClientLogMessage) + Send + Sync>;
+
+tokio::task_local! {
+    static CLIENT_LOG_SINK: ClientLogSink;
+}
+
+/// Run `future` with `sink` receiving the client log messages it produces.
+pub async fn with_client_log_sink<F: Future>(sink: ClientLogSink, future: F) -> F::Output {
+    CLIENT_LOG_SINK.scope(sink, future).await
+}
+
+/// Send a log message to the current task's client, if a sink is installed.
+pub(crate) fn client_log(level: LogLevel, logger: &str, data: Value) {
+    let _ = CLIENT_LOG_SINK.try_with(|sink| {
+        sink(&ClientLogMessage {
+            level,
+            logger: logger.to_string(),
+            data,
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_log_level_order_and_names() {
+        assert!(LogLevel::Debug < LogLevel::Info);
+        assert!(LogLevel::Warning < LogLevel::Emergency);
+        assert_eq!(serde_json::to_value(LogLevel::Warning).unwrap(), "warning");
+        assert_eq!(
+            serde_json::from_value::<LogLevel>(json!("critical")).unwrap(),
+            LogLevel::Critical
+        );
+        assert!(serde_json::from_value::<LogLevel>(json!("verbose")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_log_reaches_sink() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let sink: ClientLogSink = Arc::new(move |message: &ClientLogMessage| {
+            recorder.lock().unwrap().push(message.clone());
+        });
+
+        with_client_log_sink(sink, async {
+            client_log(LogLevel::Warning, "langbase", json!({"message": "retry"}));
+        })
+        .await;
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].level, LogLevel::Warning);
+        assert_eq!(seen[0].logger, "langbase");
+        assert_eq!(seen[0].data["message"], "retry");
+    }
+
+    #[test]
+    fn test_client_log_without_sink_is_noop() {
+        client_log(LogLevel::Error, "langbase", Value::Null);
+    }
+}
@@ -0,0 +1,143 @@
+//! Limits on simultaneous in-flight pipe requests.
+//!
+//! A global semaphore bounds the total number of open requests and optional
+//! per-pipe semaphores bound individual pipes. Callers over a limit wait for
+//! a slot instead of failing, so large fan-outs are queued rather than
+//! exhausting sockets and file descriptors.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::config::ConcurrencyConfig;
+
+/// Global and per-pipe request slots.
+pub struct ConcurrencyLimiter {
+    global: Option<Semaphore>,
+    pipes: HashMap<String, Semaphore>,
+}
+
+/// Slots held for one in-flight request; released on drop.
+pub struct ConcurrencyPermit<'a> {
+    _pipe: Option<SemaphorePermit<'a>>,
+    _global: Option<SemaphorePermit<'a>>,
+    waited: Duration,
+}
+
+impl ConcurrencyPermit<'_> {
+    /// How long the caller waited for its slots.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl ConcurrencyLimiter {
+    /// Create semaphores for the configured limits.
+    pub fn new(config: &ConcurrencyConfig) -> Self {
+        Self {
+            global: (config.max_concurrent_requests > 0)
+                .then(|| Semaphore::new(config.max_concurrent_requests)),
+            pipes: config
+                .pipes
+                .iter()
+                .filter(|(_, limit)| **limit > 0)
+                .map(|(pipe, limit)| (pipe.clone(), Semaphore::new(*limit)))
+                .collect(),
+        }
+    }
+
+    /// Requests to `pipe` currently allowed to start, or `None` if unlimited.
+    pub fn available(&self, pipe: &str) -> Option<usize> {
+        let global = self.global.as_ref().map(Semaphore::available_permits);
+        let pipe = self.pipes.get(pipe).map(Semaphore::available_permits);
+        match (global, pipe) {
+            (Some(g), Some(p)) => Some(g.min(p)),
+            (g, p) => g.or(p),
+        }
+    }
+
+    /// Wait for a slot to send a request to `pipe`.
+    ///
+    /// The pipe slot is taken before the global one, so a call queued behind
+    /// a busy pipe does not hold a global slot other pipes could use.
+    pub async fn acquire(&self, pipe: &str) -> ConcurrencyPermit<'_> {
+        let start = Instant::now();
+        if self.available(pipe) == Some(0) {
+            debug!(pipe = %pipe, "Pipe concurrency limit reached, waiting for a slot");
+        }
+
+        // The semaphores are never closed, so acquire cannot fail
+        let pipe_permit = match self.pipes.get(pipe) {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+        let global_permit = match &self.global {
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        };
+
+        ConcurrencyPermit {
+            _pipe: pipe_permit,
+            _global: global_permit,
+            waited: start.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(global: usize, pipes: &[(&str, usize)]) -> ConcurrencyLimiter {
+        ConcurrencyLimiter::new(&ConcurrencyConfig {
+            max_concurrent_requests: global,
+            pipes: pipes.iter().map(|(p, n)| (p.to_string(), *n)).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_permits_are_released_on_drop() {
+        let limiter = limiter(2, &[]);
+        let first = limiter.acquire("got").await;
+        let _second = limiter.acquire("linear").await;
+        assert_eq!(limiter.available("got"), Some(0));
+
+        drop(first);
+        assert_eq!(limiter.available("got"), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_pipe_limit_is_tighter_than_global() {
+        let limiter = limiter(4, &[("got", 1)]);
+        let _permit = limiter.acquire("got").await;
+
+        assert_eq!(limiter.available("got"), Some(0));
+        assert_eq!(limiter.available("linear"), Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited() {
+        let limiter = limiter(0, &[]);
+        let _permit = limiter.acquire("got").await;
+        assert_eq!(limiter.available("got"), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_callers_over_limit_wait() {
+        let limiter = std::sync::Arc::new(limiter(1, &[]));
+        let permit = limiter.acquire("got").await;
+
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire("got").await.waited() })
+        };
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        drop(permit);
+
+        assert!(waiter.await.unwrap() >= Duration::from_secs(1));
+    }
+}
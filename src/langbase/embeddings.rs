@@ -0,0 +1,196 @@
+//! Embedding providers for semantic similarity search.
+//!
+//! An [`EmbeddingProvider`] turns text into fixed-length vectors. Vectors are
+//! stored alongside the model identifier that produced them, so embeddings
+//! from different providers or models are never compared with each other.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::LangbaseClient;
+use crate::config::{EmbeddingConfig, EmbeddingProviderKind};
+use crate::error::LangbaseResult;
+
+/// Source of text embeddings.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifier of the model producing the vectors (stored with each embedding).
+    fn model(&self) -> &str;
+
+    /// Embed each text, returning one vector per input in the same order.
+    async fn embed(&self, texts: &[String]) -> LangbaseResult<Vec<Vec<f32>>>;
+}
+
+/// Build the embedding provider selected by configuration.
+pub fn embedding_provider(
+    config: &EmbeddingConfig,
+    client: &LangbaseClient,
+) -> Arc<dyn EmbeddingProvider> {
+    match config.provider {
+        EmbeddingProviderKind::Langbase => Arc::new(LangbaseEmbeddingProvider::new(
+            client.clone(),
+            config.model.clone(),
+        )),
+        EmbeddingProviderKind::Local => Arc::new(LocalEmbeddingProvider::default()),
+    }
+}
+
+/// Embeddings computed by the Langbase embed API.
+#[derive(Clone)]
+pub struct LangbaseEmbeddingProvider {
+    client: LangbaseClient,
+    model: String,
+}
+
+impl LangbaseEmbeddingProvider {
+    /// Create a provider using the given Langbase embedding model.
+    pub fn new(client: LangbaseClient, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LangbaseEmbeddingProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> LangbaseResult<Vec<Vec<f32>>> {
+        self.client.embed(texts, &self.model).await
+    }
+}
+
+/// Default dimensionality of local embeddings.
+pub const LOCAL_EMBEDDING_DIMENSIONS: usize = 256;
+
+/// Deterministic local embeddings using feature hashing.
+///
+/// Words and adjacent word pairs are hashed into a fixed number of signed
+/// buckets and the result is L2-normalized. This captures lexical overlap
+/// rather than meaning, but needs no network access or API key.
+#[derive(Debug, Clone)]
+pub struct LocalEmbeddingProvider {
+    dimensions: usize,
+    model: String,
+}
+
+impl LocalEmbeddingProvider {
+    /// Create a local provider producing vectors of the given length.
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            model: format!("local:hash-{}", dimensions),
+        }
+    }
+
+    /// Embed a single text.
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let lowered = text.to_lowercase();
+        let tokens: Vec<&str> = lowered
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| t.len() > 1)
+            .collect();
+
+        for token in &tokens {
+            self.add_feature(&mut vector, token.as_bytes(), 1.0);
+        }
+        for pair in tokens.windows(2) {
+            let bigram = format!("{} {}", pair[0], pair[1]);
+            self.add_feature(&mut vector, bigram.as_bytes(), 0.5);
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for v in &mut vector {
+                *v /= norm;
+            }
+        }
+        vector
+    }
+
+    fn add_feature(&self, vector: &mut [f32], feature: &[u8], weight: f32) {
+        let hash = fnv1a(feature);
+        let index = (hash % self.dimensions as u64) as usize;
+        let sign = if hash >> 63 == 1 { -1.0 } else { 1.0 };
+        vector[index] += sign * weight;
+    }
+}
+
+impl Default for LocalEmbeddingProvider {
+    fn default() -> Self {
+        Self::new(LOCAL_EMBEDDING_DIMENSIONS)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> LangbaseResult<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+}
+
+/// 64-bit FNV-1a hash (stable across platforms and Rust versions).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::cosine_similarity;
+
+    #[test]
+    fn test_local_embedding_is_deterministic_and_normalized() {
+        let provider = LocalEmbeddingProvider::default();
+        let a = provider.embed_text("Cache invalidation strategies");
+        let b = provider.embed_text("Cache invalidation strategies");
+
+        assert_eq!(a.len(), LOCAL_EMBEDDING_DIMENSIONS);
+        assert_eq!(a, b);
+        let norm: f32 = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_local_embedding_similarity_tracks_overlap() {
+        let provider = LocalEmbeddingProvider::default();
+        let query = provider.embed_text("database connection pool sizing");
+        let close = provider.embed_text("Sizing the database connection pool for load");
+        let far = provider.embed_text("Choosing a color palette for the landing page");
+
+        assert!(cosine_similarity(&query, &close) > cosine_similarity(&query, &far));
+    }
+
+    #[test]
+    fn test_local_embedding_empty_text() {
+        let provider = LocalEmbeddingProvider::new(8);
+        assert_eq!(provider.model(), "local:hash-8");
+        assert!(provider.embed_text("  ").iter().all(|v| *v == 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_local_provider_embeds_in_order() {
+        let provider = LocalEmbeddingProvider::default();
+        let texts = vec!["first idea".to_string(), "second idea".to_string()];
+        let vectors = provider.embed(&texts).await.unwrap();
+
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[0], provider.embed_text("first idea"));
+        assert_eq!(vectors[1], provider.embed_text("second idea"));
+    }
+}
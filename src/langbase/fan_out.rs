@@ -0,0 +1,170 @@
+//! Bounded fan-out of independent pipe requests.
+//!
+//! Requests run concurrently on the calling task rather than on spawned
+//! tasks, so task-local state such as the stream sink and the budget session
+//! applies to every call. The client's [`ConcurrencyLimiter`] still bounds
+//! how many of them are in flight across the whole server.
+//!
+//! [`ConcurrencyLimiter`]: super::ConcurrencyLimiter
+
+use std::future::Future;
+
+use futures::stream::{self, StreamExt};
+
+use super::{PipeRequest, PipeResponse};
+use crate::error::{LangbaseError, LangbaseResult};
+
+/// Responses and errors of a fan-out, each tagged with its request's index.
+#[derive(Debug, Default)]
+pub struct FanOut {
+    /// Successful responses, in request order.
+    pub responses: Vec<(usize, PipeResponse)>,
+    /// Failed requests, in request order.
+    pub errors: Vec<(usize, LangbaseError)>,
+}
+
+impl FanOut {
+    /// Whether every request succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The successful responses, or the first error if every request failed.
+    pub fn into_responses(self) -> LangbaseResult<Vec<(usize, PipeResponse)>> {
+        if self.responses.is_empty() {
+            if let Some((_, error)) = self.errors.into_iter().next() {
+                return Err(error);
+            }
+        }
+        Ok(self.responses)
+    }
+}
+
+/// Run `call` on every request, at most `max_concurrency` at a time.
+///
+/// A `max_concurrency` of 0 runs all requests at once.
+pub async fn fan_out<F, Fut>(requests: Vec<PipeRequest>, max_concurrency: usize, call: F) -> FanOut
+where
+    F: Fn(PipeRequest) -> Fut,
+    Fut: Future<Output = LangbaseResult<PipeResponse>>,
+{
+    let limit = match max_concurrency {
+        0 => requests.len().max(1),
+        n => n,
+    };
+    let mut results: Vec<(usize, LangbaseResult<PipeResponse>)> =
+        stream::iter(requests.into_iter().enumerate())
+            .map(|(index, request)| {
+                let call = call(request);
+                async move { (index, call.await) }
+            })
+            .buffer_unordered(limit)
+            .collect()
+            .await;
+    results.sort_by_key(|(index, _)| *index);
+
+    let mut fan_out = FanOut::default();
+    for (index, result) in results {
+        match result {
+            Ok(response) => fan_out.responses.push((index, response)),
+            Err(error) => fan_out.errors.push((index, error)),
+        }
+    }
+    fan_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langbase::Message;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn request(content: &str) -> PipeRequest {
+        PipeRequest::new("test-pipe", vec![Message::user(content)])
+    }
+
+    fn response(completion: String) -> PipeResponse {
+        PipeResponse {
+            success: true,
+            completion,
+            thread_id: None,
+            raw: None,
+            cache_hit: false,
+            fallback: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_keeps_request_order_and_errors() {
+        let requests = vec![request("a"), request("fail"), request("c")];
+        let result = fan_out(requests, 0, |request| async move {
+            let content = request.messages[0].content.clone();
+            if content == "fail" {
+                return Err(LangbaseError::Timeout { timeout_ms: 10 });
+            }
+            // Later requests finish first
+            let delay = if content == "a" { 20 } else { 1 };
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(response(content))
+        })
+        .await;
+
+        assert!(!result.is_complete());
+        let completions: Vec<(usize, &str)> = result
+            .responses
+            .iter()
+            .map(|(i, r)| (*i, r.completion.as_str()))
+            .collect();
+        assert_eq!(completions, vec![(0, "a"), (2, "c")]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, 1);
+        assert_eq!(result.into_responses().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_bounds_concurrency() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let requests = (0..6).map(|i| request(&i.to_string())).collect();
+
+        let result = fan_out(requests, 2, |request| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(response(request.messages[0].content.clone()))
+            }
+        })
+        .await;
+
+        assert!(result.is_complete());
+        assert_eq!(result.responses.len(), 6);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_all_failed_returns_first_error() {
+        let requests = vec![request("a"), request("b")];
+        let result = fan_out(requests, 1, |request| async move {
+            Err(LangbaseError::Api {
+                status: 500,
+                message: request.messages[0].content.clone(),
+            })
+        })
+        .await;
+
+        match result.into_responses() {
+            Err(LangbaseError::Api { message, .. }) => assert_eq!(message, "a"),
+            other => panic!("expected first error, got {:?}", other),
+        }
+
+        let empty = fan_out(Vec::new(), 0, |_| async {
+            Err(LangbaseError::Timeout { timeout_ms: 1 })
+        })
+        .await;
+        assert!(empty.into_responses().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+//! Canned pipe completions for offline mode.
+//!
+//! There is one JSON fixture per default pipe name in `fixtures/`. Several
+//! modes share a pipe (GoT generation, scoring, aggregation, and refinement
+//! all call `got-reasoning-v1`), so each fixture holds the union of the
+//! fields every caller of that pipe parses.
+
+/// Default pipe names and their fixtures.
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "linear-reasoning-v1",
+        include_str!("fixtures/linear-reasoning-v1.json"),
+    ),
+    (
+        "tree-reasoning-v1",
+        include_str!("fixtures/tree-reasoning-v1.json"),
+    ),
+    (
+        "divergent-reasoning-v1",
+        include_str!("fixtures/divergent-reasoning-v1.json"),
+    ),
+    ("reflection-v1", include_str!("fixtures/reflection-v1.json")),
+    (
+        "mode-router-v1",
+        include_str!("fixtures/mode-router-v1.json"),
+    ),
+    (
+        "backtracking-reasoning-v1",
+        include_str!("fixtures/backtracking-reasoning-v1.json"),
+    ),
+    (
+        "got-reasoning-v1",
+        include_str!("fixtures/got-reasoning-v1.json"),
+    ),
+    ("detection-v1", include_str!("fixtures/detection-v1.json")),
+    (
+        "decision-framework-v1",
+        include_str!("fixtures/decision-framework-v1.json"),
+    ),
+    (
+        "code-reasoning-v1",
+        include_str!("fixtures/code-reasoning-v1.json"),
+    ),
+];
+
+/// Fixture completion for a default pipe name, if there is one.
+pub fn pipe_fixture(pipe: &str) -> Option<&'static str> {
+    FIXTURES
+        .iter()
+        .find(|(name, _)| *name == pipe)
+        .map(|(_, fixture)| fixture.trim())
+}
+
+/// Default pipe names that have fixtures.
+pub fn fixture_pipes() -> impl Iterator<Item = &'static str> {
+    FIXTURES.iter().map(|(name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures_are_json_objects() {
+        for pipe in fixture_pipes() {
+            let fixture = pipe_fixture(pipe).unwrap();
+            let value: serde_json::Value = serde_json::from_str(fixture)
+                .unwrap_or_else(|e| panic!("{} fixture is not valid JSON: {}", pipe, e));
+            assert!(value.is_object(), "{} fixture must be an object", pipe);
+        }
+    }
+
+    #[test]
+    fn test_unknown_pipe_has_no_fixture() {
+        assert!(pipe_fixture("no-such-pipe").is_none());
+    }
+}
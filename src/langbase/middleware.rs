@@ -0,0 +1,74 @@
+//! Hooks around pipe calls.
+//!
+//! A [`PipeMiddleware`] registered with
+//! [`LangbaseClient::with_middleware`](super::LangbaseClient::with_middleware)
+//! sees every attempt the client sends: it can edit the request and add
+//! headers before it goes out, inspect or rewrite the response, and observe
+//! failures. This is the extension point for custom headers, request
+//! signing, metrics and content filters; the payload logger is built on it.
+//!
+//! Cache hits and calls short-circuited by a circuit breaker are not sent,
+//! so middleware does not see them.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+
+use super::types::{PipeRequest, PipeResponse};
+use crate::error::{LangbaseError, LangbaseResult};
+
+/// One attempt of a pipe call that has completed.
+#[derive(Debug, Clone, Copy)]
+pub struct PipeAttempt {
+    /// Attempt number, starting at 1; retries count up from there.
+    pub number: u32,
+    /// Time from sending the request to receiving the whole response.
+    pub latency: Duration,
+}
+
+/// Hooks run around each pipe call attempt.
+///
+/// Middlewares run in the order they were registered, for every hook. All
+/// hooks default to doing nothing, so implementations only override the
+/// ones they need.
+#[async_trait]
+pub trait PipeMiddleware: Send + Sync {
+    /// Called before attempt `attempt` is sent.
+    ///
+    /// Edits to `request` and `headers` apply to this attempt only; a retry
+    /// starts again from the caller's request. Returning an error refuses
+    /// the call: nothing is sent, it is not retried, and the error is
+    /// returned to the caller.
+    async fn on_request(
+        &self,
+        _request: &mut PipeRequest,
+        _headers: &mut HeaderMap,
+        _attempt: u32,
+    ) -> LangbaseResult<()> {
+        Ok(())
+    }
+
+    /// Called with the response of a successful attempt.
+    ///
+    /// The response may be edited. Returning an error fails the attempt as
+    /// if the pipe had returned that error, so it may be retried.
+    async fn on_response(
+        &self,
+        _request: &PipeRequest,
+        _response: &mut PipeResponse,
+        _attempt: &PipeAttempt,
+    ) -> LangbaseResult<()> {
+        Ok(())
+    }
+
+    /// Called when an attempt fails, including failures raised by
+    /// [`on_response`](Self::on_response).
+    async fn on_error(
+        &self,
+        _request: &PipeRequest,
+        _error: &LangbaseError,
+        _attempt: &PipeAttempt,
+    ) {
+    }
+}
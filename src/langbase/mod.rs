@@ -1,5 +1,41 @@
+mod cache;
+mod cancellation;
+mod circuit_breaker;
 mod client;
+mod client_log;
+mod concurrency;
+mod embeddings;
+mod fan_out;
+mod fixtures;
+mod middleware;
+mod payload_log;
+mod rate_limit;
+mod retry;
+mod schema;
+mod stream;
+mod sync;
 mod types;
 
+pub use cache::PipeCache;
+pub use cancellation::{cancellable, current_cancellation, with_cancellation};
+pub use circuit_breaker::{PipeCircuitBreakers, PipeCircuitStatus};
 pub use client::LangbaseClient;
+pub(crate) use client_log::client_log;
+pub use client_log::{with_client_log_sink, ClientLogMessage, ClientLogSink, LogLevel};
+pub use concurrency::{ConcurrencyLimiter, ConcurrencyPermit};
+pub use embeddings::{
+    embedding_provider, EmbeddingProvider, LangbaseEmbeddingProvider, LocalEmbeddingProvider,
+    LOCAL_EMBEDDING_DIMENSIONS,
+};
+pub use fan_out::{fan_out, FanOut};
+pub use fixtures::{fixture_pipes, pipe_fixture};
+pub use middleware::{PipeAttempt, PipeMiddleware};
+pub use payload_log::{PayloadLogger, Redactor, REDACTED};
+pub use rate_limit::RateLimiter;
+pub(crate) use retry::{next_retry_delay, parse_retry_after, status_error};
+pub(crate) use schema::extract_json_from_completion;
+pub use schema::{OutputSchema, SchemaViolation};
+pub(crate) use stream::{current_stream_sink, read_sse_completion};
+pub use stream::{with_stream_sink, SseParser, StreamSink};
+pub use sync::{plan_pipe_sync, sync_pipes, PipeSync, SyncAction};
 pub use types::*;
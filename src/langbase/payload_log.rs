@@ -0,0 +1,239 @@
+//! Redacted pipe payload logging.
+//!
+//! When payload logging is enabled, [`PayloadLogger`] runs as a client
+//! middleware and stores the request and response body of every pipe call
+//! attempt in the `pipe_payloads` table for debugging. Every body passes through a [`Redactor`] first so API keys,
+//! bearer tokens and email addresses never reach the database.
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::Value;
+use tracing::warn;
+
+use super::middleware::{PipeAttempt, PipeMiddleware};
+use super::types::{PipeRequest, PipeResponse};
+use crate::config::PayloadLogConfig;
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::storage::{PipePayload, SharedStorage};
+
+/// Replacement for redacted text.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Patterns that are always redacted: bearer tokens, API-key-like strings
+/// (`sk-...`, `pipe_...` and similar) and email addresses.
+const BUILTIN_PATTERNS: &[&str] = &[
+    r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]+",
+    r"\b(?:sk|pk|rk|pipe|user|org)[-_](?:[A-Za-z0-9]+[-_])?[A-Za-z0-9]{20,}",
+    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+];
+
+/// Field names whose string values are always redacted.
+const BUILTIN_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "authorization",
+    "password",
+    "secret",
+    "token",
+];
+
+/// Removes secrets and personal data from payloads.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+    keys: Vec<String>,
+}
+
+impl Redactor {
+    /// Build a redactor from the built-in rules plus the configured ones.
+    pub fn new(config: &PayloadLogConfig) -> Result<Self, regex::Error> {
+        let mut patterns = BUILTIN_PATTERNS
+            .iter()
+            .map(|p| Regex::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(pattern) = &config.redact_pattern {
+            patterns.push(Regex::new(pattern)?);
+        }
+
+        let keys = BUILTIN_KEYS
+            .iter()
+            .map(|k| k.to_string())
+            .chain(config.redact_keys.iter().map(|k| k.to_lowercase()))
+            .collect();
+
+        Ok(Self { patterns, keys })
+    }
+
+    /// Replace every match of a redaction pattern in `text`.
+    pub fn redact_str(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for pattern in &self.patterns {
+            if pattern.is_match(&redacted) {
+                redacted = pattern.replace_all(&redacted, REDACTED).into_owned();
+            }
+        }
+        redacted
+    }
+
+    /// Redact a JSON document.
+    ///
+    /// String values of secret-looking fields are replaced outright; other
+    /// strings are scrubbed with the patterns. Non-string values of matching
+    /// fields are kept, so counters like `max_tokens` survive.
+    pub fn redact(&self, value: Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact_str(&s)),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.redact(v)).collect())
+            }
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let value = match value {
+                            Value::String(_) if self.is_secret_key(&key) => {
+                                Value::String(REDACTED.to_string())
+                            }
+                            other => self.redact(other),
+                        };
+                        (key, value)
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn is_secret_key(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.keys.iter().any(|k| key.contains(k.as_str()))
+    }
+}
+
+/// Persists redacted pipe call payloads.
+pub struct PayloadLogger {
+    redactor: Redactor,
+    storage: SharedStorage,
+}
+
+impl PayloadLogger {
+    /// Create a logger writing to `storage`.
+    pub fn new(redactor: Redactor, storage: SharedStorage) -> Self {
+        Self { redactor, storage }
+    }
+
+    /// Store one attempt of a pipe call.
+    ///
+    /// Storage failures are logged and otherwise ignored; payload logging
+    /// must never fail a pipe call.
+    pub async fn record(
+        &self,
+        request: &PipeRequest,
+        attempt: &PipeAttempt,
+        result: Result<&PipeResponse, &LangbaseError>,
+    ) {
+        let body = match serde_json::to_value(request) {
+            Ok(body) => self.redactor.redact(body),
+            Err(e) => {
+                warn!(error = %e, pipe = %request.name, "Failed to serialize pipe request for payload log");
+                return;
+            }
+        };
+
+        let mut payload = PipePayload::new(&request.name, attempt.number, body)
+            .with_latency(attempt.latency.as_millis() as i64);
+        payload = match result {
+            Ok(response) => match serde_json::to_value(response) {
+                Ok(value) => payload.with_response(self.redactor.redact(value)),
+                Err(e) => payload.with_error(format!("Unserializable response: {}", e)),
+            },
+            Err(e) => payload.with_error(self.redactor.redact_str(&e.to_string())),
+        };
+
+        if let Err(e) = self.storage.log_pipe_payload(&payload).await {
+            warn!(error = %e, pipe = %request.name, "Failed to store pipe payload");
+        }
+    }
+}
+
+#[async_trait]
+impl PipeMiddleware for PayloadLogger {
+    async fn on_response(
+        &self,
+        request: &PipeRequest,
+        response: &mut PipeResponse,
+        attempt: &PipeAttempt,
+    ) -> LangbaseResult<()> {
+        self.record(request, attempt, Ok(response)).await;
+        Ok(())
+    }
+
+    async fn on_error(&self, request: &PipeRequest, error: &LangbaseError, attempt: &PipeAttempt) {
+        self.record(request, attempt, Err(error)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn redactor() -> Redactor {
+        Redactor::new(&PayloadLogConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        let r = redactor();
+        assert_eq!(
+            r.redact_str("mail alice.smith@example.com now"),
+            "mail [REDACTED] now"
+        );
+        assert_eq!(
+            r.redact_str("Authorization: Bearer abc.def-123"),
+            "Authorization: [REDACTED]"
+        );
+        assert_eq!(
+            r.redact_str("key sk-proj-abcdefghijklmnopqrstuvwx"),
+            "key [REDACTED]"
+        );
+        assert_eq!(r.redact_str("nothing to hide"), "nothing to hide");
+    }
+
+    #[test]
+    fn test_redact_json_keys_and_strings() {
+        let value = json!({
+            "name": "linear-reasoning-v1",
+            "max_tokens": 2000,
+            "variables": {"api_key": "plain", "user_email": "bob@example.org"},
+            "messages": [{"role": "user", "content": "Contact bob@example.org"}]
+        });
+        let redacted = redactor().redact(value);
+
+        assert_eq!(redacted["name"], "linear-reasoning-v1");
+        assert_eq!(redacted["max_tokens"], 2000);
+        assert_eq!(redacted["variables"]["api_key"], REDACTED);
+        assert_eq!(redacted["variables"]["user_email"], REDACTED);
+        assert_eq!(redacted["messages"][0]["content"], "Contact [REDACTED]");
+    }
+
+    #[test]
+    fn test_configured_rules() {
+        let config = PayloadLogConfig {
+            enabled: true,
+            redact_pattern: Some(r"\bACME-\d+\b".to_string()),
+            redact_keys: vec!["SSN".to_string()],
+        };
+        let r = Redactor::new(&config).unwrap();
+
+        assert_eq!(r.redact_str("ticket ACME-42"), "ticket [REDACTED]");
+        let redacted = r.redact(json!({"customer_ssn": "123-45-6789"}));
+        assert_eq!(redacted["customer_ssn"], REDACTED);
+
+        let invalid = PayloadLogConfig {
+            redact_pattern: Some("(".to_string()),
+            ..config
+        };
+        assert!(Redactor::new(&invalid).is_err());
+    }
+}
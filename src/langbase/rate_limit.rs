@@ -0,0 +1,163 @@
+//! Per-pipe request rate limiting.
+//!
+//! Each limited pipe has a token bucket. A caller that finds the bucket empty
+//! waits for the next token while holding the bucket's lock, so concurrent
+//! callers queue up in arrival order and are released one token at a time
+//! instead of failing or stampeding the upstream API.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::config::{RateLimit, RateLimitConfig};
+
+/// Token buckets for every rate-limited pipe.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Create buckets for the configured pipes.
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            buckets: config
+                .pipes
+                .iter()
+                .map(|(pipe, limit)| (pipe.clone(), TokenBucket::new(*limit)))
+                .collect(),
+        }
+    }
+
+    /// Check whether any pipe is limited.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Wait until a request to `pipe` may be sent.
+    ///
+    /// Returns how long the caller was held back. Pipes without a limit
+    /// return immediately.
+    pub async fn acquire(&self, pipe: &str) -> Duration {
+        match self.buckets.get(pipe) {
+            Some(bucket) => bucket.acquire(pipe).await,
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// A token bucket that refills continuously up to its burst capacity.
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        let capacity = limit.requests as f64;
+        Self {
+            capacity,
+            per_second: limit.per_second(),
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    async fn acquire(&self, pipe: &str) -> Duration {
+        // tokio's Mutex is fair, so waiters are served in arrival order
+        let mut state = self.state.lock().await;
+        let mut waited = Duration::ZERO;
+
+        loop {
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * self.per_second).min(self.capacity);
+            state.refilled_at = now;
+
+            if state.tokens >= 1.0 {
+                state.tokens -= 1.0;
+                return waited;
+            }
+
+            let wait = Duration::from_secs_f64((1.0 - state.tokens) / self.per_second);
+            debug!(
+                pipe = %pipe,
+                wait_ms = wait.as_millis(),
+                "Pipe rate limit reached, waiting for token"
+            );
+            tokio::time::sleep(wait).await;
+            waited += wait;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(pipe: &str, requests: u32, period_secs: u64) -> RateLimiter {
+        let mut config = RateLimitConfig::default();
+        config.pipes.insert(
+            pipe.to_string(),
+            RateLimit {
+                requests,
+                period_secs,
+            },
+        );
+        RateLimiter::new(&config)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_then_throttle() {
+        let limiter = limiter("got", 2, 1);
+
+        assert_eq!(limiter.acquire("got").await, Duration::ZERO);
+        assert_eq!(limiter.acquire("got").await, Duration::ZERO);
+
+        let start = Instant::now();
+        limiter.acquire("got").await;
+        assert!(start.elapsed() >= Duration::from_millis(499));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unlimited_pipe_never_waits() {
+        let limiter = limiter("got", 1, 3600);
+        limiter.acquire("got").await;
+
+        for _ in 0..10 {
+            assert_eq!(limiter.acquire("linear").await, Duration::ZERO);
+        }
+        assert!(!limiter.is_empty());
+        assert!(RateLimiter::default().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_callers_are_queued() {
+        let limiter = std::sync::Arc::new(limiter("detect", 1, 1));
+        let start = Instant::now();
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire("detect").await })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        // One immediate token, then one per second for the other two
+        assert!(start.elapsed() >= Duration::from_millis(1999));
+    }
+}
@@ -0,0 +1,206 @@
+//! Retry decisions shared by the Langbase client and the other providers.
+//!
+//! The policy itself (backoff, cap, jitter, retryable statuses) lives on
+//! [`RequestConfig`]; this module applies it to concrete errors and
+//! responses.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use crate::config::RequestConfig;
+use crate::error::LangbaseError;
+
+/// Whether a failed attempt is worth retrying.
+pub(crate) fn is_retryable(error: &LangbaseError) -> bool {
+    match error {
+        LangbaseError::Api { status, .. } => RequestConfig::is_retryable_status(*status),
+        LangbaseError::RateLimited { .. }
+        | LangbaseError::Timeout { .. }
+        | LangbaseError::Http(_)
+        | LangbaseError::InvalidResponse { .. } => true,
+        LangbaseError::Unavailable { .. }
+        | LangbaseError::ResponseParseFailed { .. }
+        | LangbaseError::PipeNotFound { .. }
+        | LangbaseError::SchemaViolation { .. }
+        | LangbaseError::ClientConfig { .. }
+        | LangbaseError::BudgetExceeded { .. }
+        | LangbaseError::Cancelled => false,
+    }
+}
+
+/// Delay before retry number `retry` (1-based) after `error`, or `None` to
+/// give up.
+///
+/// A `Retry-After` longer than the backoff wins. One longer than
+/// `max_retry_delay_ms` means retrying sooner would only be rejected again,
+/// so the call gives up instead.
+pub(crate) fn next_retry_delay(
+    config: &RequestConfig,
+    retry: u32,
+    error: &LangbaseError,
+) -> Option<Duration> {
+    if !is_retryable(error) {
+        return None;
+    }
+
+    let backoff = config.backoff_delay(retry);
+    match error {
+        LangbaseError::RateLimited {
+            retry_after_ms: Some(retry_after_ms),
+            ..
+        } => {
+            if *retry_after_ms > config.max_retry_delay_ms {
+                return None;
+            }
+            Some(backoff.max(Duration::from_millis(*retry_after_ms)))
+        }
+        _ => Some(backoff),
+    }
+}
+
+/// Parse a `Retry-After` header given either as seconds or as an HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Error for a non-success response.
+pub(crate) fn status_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    message: String,
+) -> LangbaseError {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        LangbaseError::RateLimited {
+            message,
+            retry_after_ms: retry_after.map(|d| d.as_millis() as u64),
+        }
+    } else {
+        LangbaseError::Api {
+            status: status.as_u16(),
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn config() -> RequestConfig {
+        RequestConfig {
+            timeout_ms: 1000,
+            max_retries: 3,
+            retry_delay_ms: 100,
+            max_retry_delay_ms: 1000,
+            retry_jitter: false,
+        }
+    }
+
+    fn api(status: u16) -> LangbaseError {
+        LangbaseError::Api {
+            status,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        assert!(next_retry_delay(&config(), 1, &api(400)).is_none());
+        assert!(next_retry_delay(&config(), 1, &api(401)).is_none());
+        assert!(next_retry_delay(&config(), 1, &api(404)).is_none());
+        assert!(next_retry_delay(&config(), 1, &api(408)).is_some());
+        assert!(next_retry_delay(&config(), 1, &api(503)).is_some());
+        assert!(
+            next_retry_delay(&config(), 1, &LangbaseError::Timeout { timeout_ms: 1 }).is_some()
+        );
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let config = config();
+        assert_eq!(config.backoff_delay(1), Duration::from_millis(100));
+        assert_eq!(config.backoff_delay(2), Duration::from_millis(200));
+        assert_eq!(config.backoff_delay(4), Duration::from_millis(800));
+        assert_eq!(config.backoff_delay(5), Duration::from_millis(1000));
+        assert_eq!(config.backoff_delay(64), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_upper_half() {
+        let config = RequestConfig {
+            retry_jitter: true,
+            ..config()
+        };
+        for _ in 0..50 {
+            let delay = config.backoff_delay(3);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_is_honored_within_cap() {
+        let limited = |ms| LangbaseError::RateLimited {
+            message: String::new(),
+            retry_after_ms: Some(ms),
+        };
+        assert_eq!(
+            next_retry_delay(&config(), 1, &limited(500)),
+            Some(Duration::from_millis(500))
+        );
+        // Shorter than the backoff: the backoff wins
+        assert_eq!(
+            next_retry_delay(&config(), 2, &limited(50)),
+            Some(Duration::from_millis(200))
+        );
+        // Longer than the cap: give up
+        assert_eq!(next_retry_delay(&config(), 1, &limited(5000)), None);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_status_error() {
+        let err = status_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(2)),
+            "slow down".to_string(),
+        );
+        assert!(matches!(
+            err,
+            LangbaseError::RateLimited {
+                retry_after_ms: Some(2000),
+                ..
+            }
+        ));
+        assert!(matches!(
+            status_error(StatusCode::BAD_REQUEST, None, String::new()),
+            LangbaseError::Api { status: 400, .. }
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+//! Structured output schemas for pipe responses.
+//!
+//! A mode attaches an [`OutputSchema`] to its [`PipeRequest`](super::PipeRequest).
+//! The client then asks the model for JSON matching the schema and checks the
+//! completion against it, so a malformed response fails with the path of the
+//! offending field instead of a generic parse error inside the mode.
+//!
+//! Only the subset of JSON Schema the modes use is checked: `type`,
+//! `properties`, `additionalProperties` (as a schema), `required`, `items`,
+//! `enum`, `minimum` and `maximum`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// JSON schema a pipe's completion must satisfy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputSchema {
+    /// Schema name reported to the model (letters, digits, `_` and `-`).
+    pub name: String,
+    /// The JSON Schema document.
+    pub schema: Value,
+    /// Ask the model to follow the schema exactly.
+    pub strict: bool,
+}
+
+/// A completion field that does not match its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON path of the field, e.g. `$.branches[1].confidence`.
+    pub path: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl OutputSchema {
+    /// Create a strict schema.
+    pub fn new(name: impl Into<String>, schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+            strict: true,
+        }
+    }
+
+    /// Extract the JSON document from a completion and check it.
+    pub fn validate_completion(&self, completion: &str) -> Result<Value, SchemaViolation> {
+        let json = extract_json_from_completion(completion).map_err(|message| SchemaViolation {
+            path: "$".to_string(),
+            message,
+        })?;
+        let value: Value = serde_json::from_str(json).map_err(|e| SchemaViolation {
+            path: "$".to_string(),
+            message: format!("Invalid JSON: {}", e),
+        })?;
+        self.validate(&value)?;
+        Ok(value)
+    }
+
+    /// Check a JSON value, returning the first violation found.
+    pub fn validate(&self, value: &Value) -> Result<(), SchemaViolation> {
+        check(&self.schema, value, "$")
+    }
+}
+
+fn check(schema: &Value, value: &Value, path: &str) -> Result<(), SchemaViolation> {
+    let violation = |message: String| SchemaViolation {
+        path: path.to_string(),
+        message,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(violation(format!(
+                "expected {}, found {}",
+                types.join(" or "),
+                type_name(value)
+            )));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(violation(format!(
+                "{} is not one of {}",
+                value,
+                Value::from(allowed.clone())
+            )));
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                return Err(violation(format!("{} is below the minimum {}", n, min)));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                return Err(violation(format!("{} is above the maximum {}", n, max)));
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        for field in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(field) {
+                return Err(SchemaViolation {
+                    path: format!("{}.{}", path, field),
+                    message: "required field is missing".to_string(),
+                });
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (field, field_schema) in properties.into_iter().flatten() {
+            if let Some(field_value) = object.get(field) {
+                check(field_schema, field_value, &format!("{}.{}", path, field))?;
+            }
+        }
+        if let Some(extra) = schema.get("additionalProperties").filter(|s| s.is_object()) {
+            for (field, field_value) in object {
+                if !properties.is_some_and(|p| p.contains_key(field)) {
+                    check(extra, field_value, &format!("{}.{}", path, field))?;
+                }
+            }
+        }
+    }
+
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            check(items, item, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Extract JSON from a completion string, handling markdown code blocks.
+///
+/// Attempts extraction in this order:
+/// 1. Try parsing as raw JSON first (fast path)
+/// 2. Extract from ```json ... ``` code blocks
+/// 3. Extract from ``` ... ``` code blocks
+/// 4. Return error if none work
+///
+/// Used by the client to validate structured output and by modes that parse
+/// structured responses.
+pub(crate) fn extract_json_from_completion(completion: &str) -> Result<&str, String> {
+    // Fast path: raw JSON
+    let trimmed = completion.trim();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Ok(trimmed);
+    }
+
+    // Try ```json ... ``` blocks
+    if completion.contains("```json") {
+        return completion
+            .split("```json")
+            .nth(1)
+            .and_then(|s| s.split("```").next())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Found ```json block but content was empty or malformed".to_string());
+    }
+
+    // Try ``` ... ``` blocks
+    if completion.contains("```") {
+        return completion
+            .split("```")
+            .nth(1)
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| "Found ``` block but content was empty or malformed".to_string());
+    }
+
+    Err(format!(
+        "No JSON found in response. First 100 chars: '{}'",
+        completion.chars().take(100).collect::<String>()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> OutputSchema {
+        OutputSchema::new(
+            "tree_response",
+            json!({
+                "type": "object",
+                "required": ["branches", "recommended_branch"],
+                "properties": {
+                    "branches": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["thought", "confidence"],
+                            "properties": {
+                                "thought": {"type": "string"},
+                                "confidence": {"type": "number", "minimum": 0, "maximum": 1}
+                            }
+                        }
+                    },
+                    "recommended_branch": {"type": "integer", "minimum": 0},
+                    "kind": {"enum": ["a", "b"]}
+                }
+            }),
+        )
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let value = json!({
+            "branches": [{"thought": "x", "confidence": 0.5}],
+            "recommended_branch": 0,
+            "kind": "a"
+        });
+        assert!(schema().validate(&value).is_ok());
+    }
+
+    #[test]
+    fn test_violations_name_the_field() {
+        let missing = json!({"branches": []});
+        let err = schema().validate(&missing).unwrap_err();
+        assert_eq!(err.path, "$.recommended_branch");
+
+        let wrong_type = json!({
+            "branches": [{"thought": "x", "confidence": 0.5}, {"thought": 7, "confidence": 0.5}],
+            "recommended_branch": 0
+        });
+        let err = schema().validate(&wrong_type).unwrap_err();
+        assert_eq!(err.path, "$.branches[1].thought");
+        assert_eq!(err.message, "expected string, found number");
+
+        let out_of_range = json!({
+            "branches": [{"thought": "x", "confidence": 1.5}],
+            "recommended_branch": 0
+        });
+        let err = schema().validate(&out_of_range).unwrap_err();
+        assert_eq!(err.path, "$.branches[0].confidence");
+
+        let not_integer = json!({"branches": [], "recommended_branch": 0.5});
+        assert!(schema().validate(&not_integer).is_err());
+
+        let bad_enum = json!({"branches": [], "recommended_branch": 0, "kind": "c"});
+        assert_eq!(schema().validate(&bad_enum).unwrap_err().path, "$.kind");
+    }
+
+    #[test]
+    fn test_validate_completion_extracts_code_blocks() {
+        let completion =
+            "Here you go:\n```json\n{\"branches\": [], \"recommended_branch\": 1}\n```";
+        let value = schema().validate_completion(completion).unwrap();
+        assert_eq!(value["recommended_branch"], 1);
+
+        let err = schema().validate_completion("no json here").unwrap_err();
+        assert_eq!(err.path, "$");
+    }
+
+    #[test]
+    fn test_validate_additional_properties() {
+        let schema = OutputSchema::new(
+            "scores",
+            json!({
+                "type": "object",
+                "properties": {"label": {"type": "string"}},
+                "additionalProperties": {"type": "number", "maximum": 1}
+            }),
+        );
+        assert!(schema.validate(&json!({"label": "x", "a": 0.5})).is_ok());
+        let err = schema.validate(&json!({"label": "x", "b": 2})).unwrap_err();
+        assert_eq!(err.path, "$.b");
+    }
+}
@@ -0,0 +1,211 @@
+//! Streaming support for pipe runs.
+//!
+//! Pipe runs stream as server-sent events when a [`StreamSink`] is installed
+//! for the current task with [`with_stream_sink`]. Modes keep calling
+//! [`LangbaseClient::call_pipe`](super::LangbaseClient::call_pipe) and still get
+//! the full completion back; the sink additionally sees each text fragment as
+//! it arrives, which the MCP layer forwards as progress notifications.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tracing::debug;
+
+use super::types::{PipeResponse, RawResponse, StreamChunk};
+use crate::error::{LangbaseError, LangbaseResult};
+
+/// Callback receiving completion text fragments as they are streamed.
+pub type StreamSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+tokio::task_local! {
+    static STREAM_SINK: StreamSink;
+}
+
+/// Run `future` with `sink` receiving the streamed text of every pipe call it makes.
+pub async fn with_stream_sink<F: Future>(sink: StreamSink, future: F) -> F::Output {
+    STREAM_SINK.scope(sink, future).await
+}
+
+/// The sink installed for the current task, if any.
+pub(crate) fn current_stream_sink() -> Option<StreamSink> {
+    STREAM_SINK.try_with(Arc::clone).ok()
+}
+
+/// Incremental parser for a `text/event-stream` response body.
+///
+/// Only `data` fields are kept; comments and other fields (`event`, `id`,
+/// `retry`) are ignored since pipe runs do not use them.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Create an empty parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the body, returning the data of every event it completes
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            events.extend(self.process_line(&line));
+        }
+        events
+    }
+
+    /// Flush an event left open when the body ended without a blank line
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        self.process_line(&line);
+        self.process_line(b"")
+    }
+
+    /// Handle one line, returning the event data if the line ends an event
+    fn process_line(&mut self, line: &[u8]) -> Option<String> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if line.is_empty() {
+            if self.data.is_empty() {
+                return None;
+            }
+            let event = self.data.join("\n");
+            self.data.clear();
+            return Some(event);
+        }
+        if let Some(value) = line.strip_prefix("data:") {
+            self.data
+                .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+        None
+    }
+}
+
+/// Collect a streamed chat completion, passing each text fragment to `sink`
+///
+/// Works for any OpenAI-style `chat.completion.chunk` event stream; the
+/// thread ID is taken from Langbase's `lb-thread-id` header when present.
+pub(crate) async fn read_sse_completion(
+    mut response: reqwest::Response,
+    sink: &StreamSink,
+    timeout_ms: u64,
+) -> LangbaseResult<PipeResponse> {
+    let thread_id = response
+        .headers()
+        .get("lb-thread-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let mut parser = SseParser::new();
+    let mut completion = String::new();
+    let mut raw = RawResponse {
+        model: None,
+        usage: None,
+    };
+
+    loop {
+        let bytes = response.chunk().await.map_err(|e| {
+            if e.is_timeout() {
+                LangbaseError::Timeout { timeout_ms }
+            } else {
+                LangbaseError::Http(e)
+            }
+        })?;
+        let events = match &bytes {
+            Some(bytes) => parser.feed(bytes),
+            None => parser.finish().into_iter().collect(),
+        };
+
+        for data in events {
+            if data == "[DONE]" {
+                continue;
+            }
+            let chunk: StreamChunk =
+                serde_json::from_str(&data).map_err(|e| LangbaseError::InvalidResponse {
+                    message: format!("Failed to parse stream chunk: {}", e),
+                })?;
+            let text = chunk.content();
+            if !text.is_empty() {
+                sink(text);
+                completion.push_str(text);
+            }
+            raw.model = chunk.model.or(raw.model);
+            raw.usage = chunk.usage.or(raw.usage);
+        }
+
+        if bytes.is_none() {
+            break;
+        }
+    }
+
+    debug!(chars = completion.len(), "Langbase pipe stream finished");
+
+    Ok(PipeResponse {
+        success: true,
+        completion,
+        thread_id,
+        raw: Some(raw),
+        cache_hit: false,
+        fallback: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_single_event() {
+        let mut parser = SseParser::new();
+        assert_eq!(parser.feed(b"data: hello\n\n"), vec!["hello"]);
+        assert!(parser.finish().is_none());
+    }
+
+    #[test]
+    fn test_sse_parser_event_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: {\"a\":").is_empty());
+        assert!(parser.feed(b" 1}\r\n").is_empty());
+        assert_eq!(
+            parser.feed(b"\r\ndata: [DONE]\n\n"),
+            vec!["{\"a\": 1}", "[DONE]"]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_multibyte_split() {
+        let mut parser = SseParser::new();
+        let bytes = "data: caf\u{e9}\n\n".as_bytes();
+        let (first, second) = bytes.split_at(10);
+        assert!(parser.feed(first).is_empty());
+        assert_eq!(parser.feed(second), vec!["caf\u{e9}"]);
+    }
+
+    #[test]
+    fn test_sse_parser_ignores_comments_and_other_fields() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b": keep-alive\nevent: message\nid: 1\ndata: a\ndata: b\n\n");
+        assert_eq!(events, vec!["a\nb"]);
+    }
+
+    #[test]
+    fn test_sse_parser_finish_flushes_unterminated_event() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: tail").is_empty());
+        assert_eq!(parser.finish().as_deref(), Some("tail"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_sink_scoped_to_task() {
+        assert!(current_stream_sink().is_none());
+        let sink: StreamSink = Arc::new(|_| {});
+        let installed = with_stream_sink(sink, async { current_stream_sink().is_some() }).await;
+        assert!(installed);
+        assert!(current_stream_sink().is_none());
+    }
+}
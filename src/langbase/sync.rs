@@ -0,0 +1,240 @@
+//! Pipe synchronization.
+//!
+//! The pipe definitions in [`crate::prompts`] are the source of truth for
+//! the pipes in the Langbase account. [`plan_pipe_sync`] compares them with
+//! the remote pipes and [`sync_pipes`] upserts the ones that differ, so
+//! editing a prompt locally and running `pipes sync` is enough to roll it out.
+
+use serde::Serialize;
+use tracing::info;
+
+use super::client::LangbaseClient;
+use super::types::{CreatePipeRequest, PipeInfo};
+use crate::error::LangbaseResult;
+use crate::prompts::PipeDefinition;
+
+/// What syncing does to a pipe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    /// The pipe does not exist remotely and is created.
+    Create,
+    /// The remote pipe differs from its definition and is overwritten.
+    Update,
+    /// The remote pipe already matches its definition.
+    Unchanged,
+}
+
+impl std::fmt::Display for SyncAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncAction::Create => write!(f, "create"),
+            SyncAction::Update => write!(f, "update"),
+            SyncAction::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// Sync plan for a single pipe.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipeSync {
+    /// Pipe name.
+    pub name: String,
+    /// What syncing does to the pipe.
+    pub action: SyncAction,
+    /// Changed settings, as `field: remote -> local`.
+    pub changes: Vec<String>,
+    /// Line diff of the remote system prompt against the local one; removed
+    /// lines start with `-`, added lines with `+`.
+    pub prompt_diff: Vec<String>,
+}
+
+/// Compare local pipe definitions with the remote pipes.
+pub fn plan_pipe_sync(definitions: &[PipeDefinition], remote: &[PipeInfo]) -> Vec<PipeSync> {
+    definitions
+        .iter()
+        .map(|def| match remote.iter().find(|p| p.name == def.name) {
+            None => PipeSync {
+                name: def.name.to_string(),
+                action: SyncAction::Create,
+                changes: Vec::new(),
+                prompt_diff: diff_lines("", def.system_prompt.unwrap_or("")),
+            },
+            Some(pipe) => {
+                let changes = setting_changes(def, pipe);
+                let prompt_diff = diff_lines(
+                    pipe.system_prompt().unwrap_or(""),
+                    def.system_prompt.unwrap_or(""),
+                );
+                let action = if changes.is_empty() && prompt_diff.is_empty() {
+                    SyncAction::Unchanged
+                } else {
+                    SyncAction::Update
+                };
+                PipeSync {
+                    name: def.name.to_string(),
+                    action,
+                    changes,
+                    prompt_diff,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Upsert every pipe whose remote copy is missing or out of date.
+///
+/// With `dry_run` nothing is written; the returned plan shows what would be.
+pub async fn sync_pipes(
+    client: &LangbaseClient,
+    definitions: &[PipeDefinition],
+    dry_run: bool,
+) -> LangbaseResult<Vec<PipeSync>> {
+    let remote = client.list_pipes().await?;
+    let plan = plan_pipe_sync(definitions, &remote);
+
+    if !dry_run {
+        for (def, entry) in definitions.iter().zip(&plan) {
+            if entry.action == SyncAction::Unchanged {
+                continue;
+            }
+            client
+                .create_pipe(CreatePipeRequest::from_definition(def.name, def))
+                .await?;
+            info!(pipe = %entry.name, action = %entry.action, "Pipe synced");
+        }
+    }
+
+    Ok(plan)
+}
+
+fn setting_changes(def: &PipeDefinition, pipe: &PipeInfo) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, remote: Option<String>, local: String| {
+        if remote.as_deref() != Some(local.as_str()) {
+            changes.push(format!(
+                "{}: {} -> {}",
+                field,
+                remote.as_deref().unwrap_or("<unset>"),
+                local
+            ));
+        }
+    };
+    compare(
+        "description",
+        pipe.description.clone(),
+        def.description.to_string(),
+    );
+    compare("model", pipe.model.clone(), def.model.to_string());
+    compare(
+        "temperature",
+        pipe.temperature.map(|t| t.to_string()),
+        def.temperature.to_string(),
+    );
+    compare(
+        "max_tokens",
+        pipe.max_tokens.map(|t| t.to_string()),
+        def.max_tokens.to_string(),
+    );
+    compare("json", pipe.json.map(|j| j.to_string()), true.to_string());
+    changes
+}
+
+/// Minimal line diff (longest common subsequence), listing only the lines
+/// removed from `old` and added in `new`.
+fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompts::{AUTO_ROUTER_PIPE, LINEAR_PIPE};
+    use serde_json::json;
+
+    fn remote(def: &PipeDefinition, prompt: Option<&str>) -> PipeInfo {
+        PipeInfo {
+            name: def.name.to_string(),
+            description: Some(def.description.to_string()),
+            model: Some(def.model.to_string()),
+            temperature: Some(def.temperature),
+            max_tokens: Some(def.max_tokens),
+            json: Some(true),
+            messages: prompt
+                .map(|p| vec![json!({"role": "system", "content": p})])
+                .unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_plan_unchanged_and_missing() {
+        let remote = vec![remote(&LINEAR_PIPE, LINEAR_PIPE.system_prompt)];
+        let plan = plan_pipe_sync(&[LINEAR_PIPE, AUTO_ROUTER_PIPE], &remote);
+
+        assert_eq!(plan[0].action, SyncAction::Unchanged);
+        assert!(plan[0].prompt_diff.is_empty());
+        assert_eq!(plan[1].action, SyncAction::Create);
+        assert!(plan[1].prompt_diff.is_empty());
+    }
+
+    #[test]
+    fn test_plan_reports_setting_and_prompt_changes() {
+        let mut pipe = remote(&LINEAR_PIPE, Some("Old first line\nShared line"));
+        pipe.model = Some("openai:gpt-4o".to_string());
+        pipe.temperature = None;
+        let def = PipeDefinition {
+            system_prompt: Some("New first line\nShared line"),
+            ..LINEAR_PIPE
+        };
+
+        let plan = plan_pipe_sync(&[def], &[pipe]);
+        assert_eq!(plan[0].action, SyncAction::Update);
+        assert_eq!(
+            plan[0].changes,
+            vec![
+                "model: openai:gpt-4o -> openai:gpt-4o-mini".to_string(),
+                "temperature: <unset> -> 0.7".to_string(),
+            ]
+        );
+        assert_eq!(
+            plan[0].prompt_diff,
+            vec!["-Old first line".to_string(), "+New first line".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert!(diff_lines("a\nb", "a\nb").is_empty());
+        assert_eq!(diff_lines("a\nb\nc", "a\nc\nd"), vec!["-b", "+d"]);
+        assert_eq!(diff_lines("", "x"), vec!["+x"]);
+    }
+}
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::warn;
 
+use super::schema::OutputSchema;
+use crate::error::ToolError;
+use crate::prompts::PipeDefinition;
+
 #[cfg(test)]
 #[path = "types_tests.rs"]
 mod types_tests;
@@ -47,10 +51,78 @@ pub struct PipeRequest {
     /// Optional thread ID for conversation continuity.
     #[serde(rename = "threadId", skip_serializing_if = "Option::is_none")]
     pub thread_id: Option<String>,
+    /// Structured output format the completion must follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Sampling overrides for this call; unset fields keep the pipe's settings.
+    #[serde(flatten)]
+    pub generation: GenerationParams,
+}
+
+/// Per-call overrides of a pipe's model parameters.
+///
+/// Reasoning tools accept these as a `generation` object so a caller can, for
+/// example, run divergent mode hot and evidence mode at temperature 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    /// Sampling temperature (0.0-2.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling probability mass (0.0-1.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Maximum tokens to generate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationParams {
+    /// Whether no parameter is overridden
+    pub fn is_empty(&self) -> bool {
+        self.temperature.is_none() && self.top_p.is_none() && self.max_tokens.is_none()
+    }
+
+    /// Check that every set parameter is within range
+    pub fn validate(&self) -> Result<(), ToolError> {
+        let invalid = |field: &str, reason: String| ToolError::Validation {
+            field: format!("generation.{}", field),
+            reason,
+        };
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(invalid(
+                    "temperature",
+                    format!("must be between 0.0 and 2.0, got {}", t),
+                ));
+            }
+        }
+        if let Some(p) = self.top_p {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(invalid(
+                    "top_p",
+                    format!("must be between 0.0 and 1.0, got {}", p),
+                ));
+            }
+        }
+        if self.max_tokens == Some(0) {
+            return Err(invalid("max_tokens", "must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Structured output request (OpenAI-style `json_schema` response format).
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseFormat {
+    /// Always `json_schema`.
+    #[serde(rename = "type")]
+    pub format_type: String,
+    /// Schema the completion must satisfy.
+    pub json_schema: OutputSchema,
 }
 
 /// Response from a Langbase pipe.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipeResponse {
     /// Whether the request succeeded.
     pub success: bool,
@@ -61,10 +133,17 @@ pub struct PipeResponse {
     pub thread_id: Option<String>,
     /// Raw model response details.
     pub raw: Option<RawResponse>,
+    /// Whether this response was served from the pipe response cache.
+    #[serde(skip)]
+    pub cache_hit: bool,
+    /// Fallback type (e.g. `fallback_pipe`) when the configured pipe was
+    /// unavailable and a fallback answered instead.
+    #[serde(skip)]
+    pub fallback: Option<String>,
 }
 
 /// Raw model response details.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawResponse {
     /// Model name used for completion.
     pub model: Option<String>,
@@ -73,7 +152,7 @@ pub struct RawResponse {
 }
 
 /// Token usage information.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Usage {
     /// Number of prompt tokens.
     pub prompt_tokens: Option<u32>,
@@ -118,6 +197,8 @@ impl PipeRequest {
             stream: false, // Disable streaming for synchronous responses
             variables: None,
             thread_id: None,
+            response_format: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -140,6 +221,210 @@ impl PipeRequest {
         self.thread_id = Some(thread_id.into());
         self
     }
+
+    /// Request JSON output matching `schema`; the client rejects completions
+    /// that do not satisfy it
+    pub fn with_output_schema(mut self, schema: OutputSchema) -> Self {
+        self.response_format = Some(ResponseFormat {
+            format_type: "json_schema".to_string(),
+            json_schema: schema,
+        });
+        self
+    }
+
+    /// Schema the completion must satisfy, if any
+    pub fn output_schema(&self) -> Option<&OutputSchema> {
+        self.response_format.as_ref().map(|f| &f.json_schema)
+    }
+
+    /// Override the pipe's model parameters for this call
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+/// One event of a streamed pipe run (an OpenAI-style `chat.completion.chunk`).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamChunk {
+    /// Model name used for completion.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Choices carrying the new text.
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+    /// Token usage, sent with the final chunk by some models.
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// A single choice within a stream chunk.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamChoice {
+    /// Text added by this chunk.
+    #[serde(default)]
+    pub delta: StreamDelta,
+    /// Why generation stopped, set on the last chunk.
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+}
+
+/// Incremental content of a stream choice.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamDelta {
+    /// Completion text fragment.
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+impl StreamChunk {
+    /// Completion text carried by this chunk (empty if none)
+    pub fn content(&self) -> &str {
+        self.choices
+            .first()
+            .and_then(|c| c.delta.content.as_deref())
+            .unwrap_or("")
+    }
+}
+
+/// Request to the Langbase embed API.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbedRequest {
+    /// Text chunks to embed.
+    pub chunks: Vec<String>,
+    /// Embedding model identifier (e.g. "openai:text-embedding-3-large").
+    #[serde(rename = "embeddingModel")]
+    pub embedding_model: String,
+}
+
+impl EmbedRequest {
+    /// Maximum number of chunks accepted per embed request.
+    pub const MAX_CHUNKS: usize = 100;
+
+    /// Create a new embed request
+    pub fn new(chunks: Vec<String>, embedding_model: impl Into<String>) -> Self {
+        Self {
+            chunks,
+            embedding_model: embedding_model.into(),
+        }
+    }
+}
+
+/// Request to create a Langbase thread.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CreateThreadRequest {
+    /// Messages to seed the thread with.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
+    /// Optional key-value metadata stored on the thread.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl CreateThreadRequest {
+    /// Create a thread seeded with messages.
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            metadata: None,
+        }
+    }
+
+    /// Attach metadata to the thread.
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// A Langbase thread holding server-side conversation history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thread {
+    /// Thread ID, passed as `threadId` on pipe runs to continue it.
+    pub id: String,
+    /// Creation time in Unix seconds.
+    #[serde(default)]
+    pub created_at: i64,
+    /// Metadata stored on the thread.
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// A message stored in a Langbase thread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadMessage {
+    /// Message ID.
+    pub id: String,
+    /// Thread the message belongs to.
+    pub thread_id: String,
+    /// Role of the message sender.
+    pub role: MessageRole,
+    /// Message content (absent for tool-call messages).
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Creation time in Unix seconds.
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+/// Request to run a Langbase workflow: a chain of pipe runs executed
+/// server-side in one round trip.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowRunRequest {
+    /// Workflow name, used to identify runs.
+    pub name: String,
+    /// Steps in execution order.
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// One pipe run in a Langbase workflow.
+///
+/// Message contents may reference the completion of an earlier step as
+/// `{{steps.<id>.output}}`; Langbase fills it in before running the step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Step identifier, unique within the workflow.
+    pub id: String,
+    /// Pipe to run.
+    pub pipe: String,
+    /// Messages sent to the pipe.
+    pub messages: Vec<Message>,
+    /// Steps that must finish before this one starts.
+    #[serde(rename = "dependsOn", default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Whether the workflow continues when this step fails.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl WorkflowStep {
+    /// Placeholder for this step's completion in a later step's messages.
+    pub fn output_ref(step_id: &str) -> String {
+        format!("{{{{steps.{}.output}}}}", step_id)
+    }
+}
+
+/// Result of a Langbase workflow run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRunResponse {
+    /// Run ID.
+    pub id: String,
+    /// Per-step outcomes, in execution order.
+    #[serde(default)]
+    pub steps: Vec<WorkflowStepResult>,
+}
+
+/// Outcome of one workflow step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStepResult {
+    /// Step identifier.
+    pub id: String,
+    /// Pipe completion, if the step ran successfully.
+    #[serde(default)]
+    pub completion: Option<String>,
+    /// Error message, if the step failed.
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 /// Structured reasoning response from a pipe.
@@ -221,6 +506,43 @@ pub struct CreatePipeResponse {
     pub api_key: String,
 }
 
+/// A pipe as stored in the Langbase account (from `GET /v1/pipes`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipeInfo {
+    /// Pipe name.
+    pub name: String,
+    /// Pipe description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Model the pipe runs.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Model temperature.
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    /// Maximum tokens to generate.
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Whether JSON output is enabled.
+    #[serde(default)]
+    pub json: Option<bool>,
+    /// Stored prompt messages. Kept as raw JSON because remote pipes may hold
+    /// roles and content shapes the server never sends.
+    #[serde(default)]
+    pub messages: Vec<serde_json::Value>,
+}
+
+impl PipeInfo {
+    /// The pipe's stored system prompt, if any.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.messages
+            .iter()
+            .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("system"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+    }
+}
+
 impl CreatePipeRequest {
     /// Create a new pipe request with just a name
     pub fn new(name: impl Into<String>) -> Self {
@@ -286,6 +608,22 @@ impl CreatePipeRequest {
         self.messages = Some(messages);
         self
     }
+
+    /// Upsert request for a pipe definition from the prompts module, under
+    /// the given pipe name
+    pub fn from_definition(name: impl Into<String>, definition: &PipeDefinition) -> Self {
+        let request = Self::new(name)
+            .with_description(definition.description)
+            .with_model(definition.model)
+            .with_upsert(true)
+            .with_json_output(true)
+            .with_temperature(definition.temperature)
+            .with_max_tokens(definition.max_tokens);
+        match definition.system_prompt {
+            Some(prompt) => request.with_messages(vec![Message::system(prompt)]),
+            None => request,
+        }
+    }
 }
 
 impl ReasoningResponse {
@@ -205,6 +205,54 @@ fn test_pipe_request_serialize() {
     assert!(json.contains("thread-1"));
 }
 
+#[test]
+fn test_pipe_request_serialize_generation() {
+    let req = PipeRequest::new("test-pipe", vec![Message::user("Test")]);
+    let json = serde_json::to_value(&req).unwrap();
+    assert!(json.get("temperature").is_none());
+
+    let req = req.with_generation(GenerationParams {
+        temperature: Some(0.0),
+        top_p: Some(0.9),
+        max_tokens: Some(256),
+    });
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["temperature"], 0.0);
+    assert_eq!(json["top_p"], 0.9);
+    assert_eq!(json["max_tokens"], 256);
+}
+
+#[test]
+fn test_generation_params_validate() {
+    assert!(GenerationParams::default().validate().is_ok());
+    let valid = GenerationParams {
+        temperature: Some(2.0),
+        top_p: Some(1.0),
+        max_tokens: Some(1),
+    };
+    assert!(valid.validate().is_ok());
+
+    let hot = GenerationParams {
+        temperature: Some(2.5),
+        ..Default::default()
+    };
+    assert!(hot
+        .validate()
+        .unwrap_err()
+        .to_string()
+        .contains("generation.temperature"));
+    let top_p = GenerationParams {
+        top_p: Some(-0.1),
+        ..Default::default()
+    };
+    assert!(top_p.validate().is_err());
+    let no_tokens = GenerationParams {
+        max_tokens: Some(0),
+        ..Default::default()
+    };
+    assert!(no_tokens.validate().is_err());
+}
+
 #[test]
 fn test_pipe_response_deserialize() {
     let json = r#"{
@@ -43,6 +43,10 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! `AppState::new` accepts any storage convertible into
+//! [`storage::SharedStorage`], so a custom [`storage::Storage`] implementation
+//! can be passed as `Arc::new(my_storage)` in place of `SqliteStorage`.
 
 #![warn(missing_docs)]
 
@@ -58,6 +62,10 @@ pub mod modes;
 pub mod presets;
 /// System prompts for Langbase pipes.
 pub mod prompts;
+/// Versioned response schemas for pipe completions.
+pub mod schemas;
+/// LLM provider backends (Langbase, OpenAI-compatible, Ollama) and per-pipe routing.
+pub mod providers;
 /// MCP server implementation and request handling.
 pub mod server;
 /// SQLite storage layer for persistence.
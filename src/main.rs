@@ -5,11 +5,21 @@ use tracing::{error, info};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 use mcp_langbase_reasoning::{
-    config::Config,
-    langbase::LangbaseClient,
+    config::{Config, TransportKind},
+    langbase::{sync_pipes, LangbaseClient, SyncAction},
+    modes::{
+        branch_thought_counts, check_pipes, evaluate_routing_outcome, mcts_budget_curve,
+        pipe_probes, render_branch_tree, render_graph, routing_table, summarize_mcts_searches,
+        GraphExportFormat, TreeExportFormat,
+    },
+    prompts::PIPE_DEFINITIONS,
+    providers::{ProviderRouter, SharedBackend},
     self_improvement::{execute_command, SelfImproveCommands},
     server::{AppState, McpServer},
-    storage::{MetricsFilter, SqliteStorage, Storage},
+    storage::{
+        prune, AuditFilter, MctsIterationFilter, MetricsFilter, RoutingRecordFilter, SessionFilter,
+        SqliteStorage, Storage,
+    },
 };
 
 /// MCP Langbase Reasoning Server
@@ -25,6 +35,9 @@ struct Cli {
 enum Commands {
     /// Query pipe usage metrics
     Metrics {
+        /// Only count calls made in this workspace (default: all workspaces)
+        #[arg(short, long, global = true)]
+        workspace: Option<String>,
         #[command(subcommand)]
         action: MetricsAction,
     },
@@ -33,6 +46,110 @@ enum Commands {
         #[command(subcommand)]
         action: SelfImproveCommands,
     },
+    /// List reasoning sessions, most recently updated first
+    Sessions {
+        /// Filter by reasoning mode
+        #[arg(short, long)]
+        mode: Option<String>,
+        /// Filter by tag
+        #[arg(short, long)]
+        tag: Option<String>,
+        /// Show only sessions with bias/fallacy detections
+        #[arg(long)]
+        with_detections: bool,
+        /// Filter by workspace (default: all workspaces)
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+    /// Query the audit log of mutating tool calls, most recent first
+    Audit {
+        /// Filter by session ID
+        #[arg(short, long)]
+        session: Option<String>,
+        /// Filter by tool name
+        #[arg(short, long)]
+        tool: Option<String>,
+        /// Filter by actor (e.g. mcp:claude-desktop)
+        #[arg(short, long)]
+        actor: Option<String>,
+        /// Only show entries from the last this many days
+        #[arg(long)]
+        days: Option<u32>,
+        /// Maximum number of results
+        #[arg(short, long, default_value = "50")]
+        limit: u32,
+    },
+    /// Prune expired sessions, snapshots, and invocation logs
+    Prune {
+        /// Delete data older than this many days (overrides RETENTION_DAYS)
+        #[arg(long)]
+        days: Option<u32>,
+        /// Keep at most this many invocation log entries (overrides MAX_INVOCATIONS)
+        #[arg(long)]
+        max_invocations: Option<u64>,
+        /// Purge sessions trashed more than this many days ago (overrides TRASH_RETENTION_DAYS)
+        #[arg(long)]
+        trash_days: Option<u32>,
+    },
+    /// Export a Graph-of-Thoughts session as Graphviz DOT or GraphML
+    GotExport {
+        /// Session ID of the graph
+        session: String,
+        /// Output format: dot or graphml
+        #[arg(short, long, default_value = "dot")]
+        format: GraphExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Export a tree session's branch hierarchy as Mermaid or ASCII
+    TreeExport {
+        /// Session ID of the tree
+        session: String,
+        /// Output format: mermaid or ascii
+        #[arg(short, long, default_value = "mermaid")]
+        format: TreeExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Back up the database to a file while the server may be running
+    Backup {
+        /// Path to write the backup to (must not exist)
+        output: std::path::PathBuf,
+        /// Gzip-compress the backup
+        #[arg(long)]
+        compress: bool,
+    },
+    /// Restore the database from a backup (stop the server first)
+    Restore {
+        /// Backup file to restore from (plain or gzip-compressed)
+        input: std::path::PathBuf,
+        /// Overwrite the existing database at DATABASE_PATH
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show applied and pending schema migrations and apply the pending ones
+    Migrate {
+        /// Only show what would be applied
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check the configured pipes
+    Pipes {
+        #[command(subcommand)]
+        action: PipesAction,
+    },
+    /// Encrypt the plaintext database at DATABASE_PATH using DATABASE_ENCRYPTION_KEY
+    #[cfg(feature = "sqlcipher")]
+    EncryptDb {
+        /// Path to write the encrypted copy to (must not exist)
+        #[arg(long)]
+        output: std::path::PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -44,6 +161,11 @@ enum MetricsAction {
         /// Name of the pipe to query
         name: String,
     },
+    /// Compare success rate and latency across the versions of a pipe
+    Versions {
+        /// Configured pipe name whose versions are listed in PIPE_VERSIONS
+        name: String,
+    },
     /// List recent invocations
     Invocations {
         /// Filter by pipe name
@@ -59,6 +181,40 @@ enum MetricsAction {
         #[arg(long)]
         success_only: bool,
     },
+    /// Show recent MCTS searches and how result quality grows with the
+    /// iterations and pipe calls spent
+    Mcts {
+        /// Filter by session ID
+        #[arg(short, long)]
+        session: Option<String>,
+        /// Maximum number of searches
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+    /// Show the routing table auto mode learned from past decisions and
+    /// their outcomes
+    Routing {
+        /// Maximum number of recent decisions to include
+        #[arg(short, long, default_value = "200")]
+        limit: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum PipesAction {
+    /// Send a probe prompt through every configured pipe and report reachability,
+    /// latency, and schema compliance
+    Check {
+        /// Only probe these pipe names
+        pipes: Vec<String>,
+    },
+    /// Upsert the pipe definitions from the prompts module to Langbase, showing
+    /// how each remote pipe differs
+    Sync {
+        /// Only show the differences
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[tokio::main]
@@ -75,14 +231,53 @@ async fn main() -> anyhow::Result<()> {
     };
 
     match cli.command {
-        Some(Commands::Metrics { action }) => {
+        Some(Commands::Metrics { workspace, action }) => {
             // Metrics commands don't need full server initialization
-            run_metrics_command(&config, action).await
+            run_metrics_command(&config, workspace.as_deref(), action).await
         }
         Some(Commands::SelfImprove { action }) => {
             // Self-improvement commands
             run_self_improve_command(&config, action).await
         }
+        Some(Commands::Sessions {
+            mode,
+            tag,
+            with_detections,
+            workspace,
+            limit,
+        }) => run_sessions_command(&config, mode, tag, with_detections, workspace, limit).await,
+        Some(Commands::Audit {
+            session,
+            tool,
+            actor,
+            days,
+            limit,
+        }) => run_audit_command(&config, session, tool, actor, days, limit).await,
+        Some(Commands::Prune {
+            days,
+            max_invocations,
+            trash_days,
+        }) => run_prune_command(&config, days, max_invocations, trash_days).await,
+        Some(Commands::GotExport {
+            session,
+            format,
+            output,
+        }) => run_got_export_command(&config, &session, format, output.as_deref()).await,
+        Some(Commands::TreeExport {
+            session,
+            format,
+            output,
+        }) => run_tree_export_command(&config, &session, format, output.as_deref()).await,
+        Some(Commands::Backup { output, compress }) => {
+            run_backup_command(&config, &output, compress).await
+        }
+        Some(Commands::Restore { input, force }) => {
+            run_restore_command(&config, &input, force).await
+        }
+        Some(Commands::Migrate { dry_run }) => run_migrate_command(&config, dry_run).await,
+        Some(Commands::Pipes { action }) => run_pipes_command(&config, action).await,
+        #[cfg(feature = "sqlcipher")]
+        Some(Commands::EncryptDb { output }) => run_encrypt_db_command(&config, &output).await,
         None => {
             // Default: run the MCP server
             run_server(config).await
@@ -91,7 +286,11 @@ async fn main() -> anyhow::Result<()> {
 }
 
 /// Run metrics CLI commands
-async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::Result<()> {
+async fn run_metrics_command(
+    config: &Config,
+    workspace: Option<&str>,
+    action: MetricsAction,
+) -> anyhow::Result<()> {
     // Initialize storage only (no langbase client needed for metrics)
     let storage = match SqliteStorage::new(&config.database).await {
         Ok(s) => s,
@@ -103,7 +302,7 @@ async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::
 
     match action {
         MetricsAction::Summary => {
-            let summaries = storage.get_pipe_usage_summary().await?;
+            let summaries = storage.get_pipe_usage_summary(workspace).await?;
             if summaries.is_empty() {
                 println!("No pipe usage data found.");
                 return Ok(());
@@ -138,7 +337,7 @@ async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::
             }
         }
 
-        MetricsAction::Pipe { name } => match storage.get_pipe_summary(&name).await? {
+        MetricsAction::Pipe { name } => match storage.get_pipe_summary(&name, workspace).await? {
             Some(summary) => {
                 println!("\n{:=<80}", "");
                 println!("METRICS FOR PIPE: {}", summary.pipe_name);
@@ -170,6 +369,45 @@ async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::
             }
         },
 
+        MetricsAction::Versions { name } => {
+            let Some(versions) = config.pipes.versions.get(&name) else {
+                println!(
+                    "No versions configured for pipe: {} (see PIPE_VERSIONS)",
+                    name
+                );
+                return Ok(());
+            };
+            let total_weight: u32 = versions.iter().map(|v| v.weight).sum();
+
+            println!("\n{:=<80}", "");
+            println!("VERSIONS OF PIPE: {}", name);
+            println!("{:=<80}\n", "");
+            println!(
+                "{:<32} {:>7} {:>8} {:>9} {:>13}",
+                "Version", "Share", "Calls", "Success", "Avg Latency"
+            );
+            println!("{:-<80}", "");
+
+            for version in versions {
+                let share = version.weight as f64 / total_weight.max(1) as f64 * 100.0;
+                match storage.get_pipe_summary(&version.name, workspace).await? {
+                    Some(summary) => println!(
+                        "{:<32} {:>6.1}% {:>8} {:>8.1}% {:>11.2}ms",
+                        version.name,
+                        share,
+                        summary.total_calls,
+                        summary.success_rate * 100.0,
+                        summary.avg_latency_ms
+                    ),
+                    None => println!(
+                        "{:<32} {:>6.1}% {:>8} {:>9} {:>13}",
+                        version.name, share, 0, "-", "-"
+                    ),
+                }
+            }
+            println!();
+        }
+
         MetricsAction::Invocations {
             pipe,
             session,
@@ -181,6 +419,7 @@ async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::
                 session_id: session,
                 limit: Some(limit),
                 success_only: if success_only { Some(true) } else { None },
+                workspace_id: workspace.map(str::to_string),
                 ..Default::default()
             };
 
@@ -226,6 +465,148 @@ async fn run_metrics_command(config: &Config, action: MetricsAction) -> anyhow::
             }
             println!();
         }
+
+        MetricsAction::Mcts { session, limit } => {
+            let mut filter = MctsIterationFilter::new().with_limit(limit);
+            if let Some(session) = session {
+                filter = filter.in_session(session);
+            }
+            if let Some(workspace) = workspace {
+                filter = filter.in_workspace(workspace);
+            }
+
+            let iterations = storage.get_mcts_iterations(filter).await?;
+            if iterations.is_empty() {
+                println!("No MCTS searches found.");
+                return Ok(());
+            }
+
+            println!("\n{:=<80}", "");
+            println!("MCTS SEARCHES");
+            println!("{:=<80}\n", "");
+            println!(
+                "{:<10} {:<20} {:>5} {:>5} {:>5} {:>5} {:>6} {:>6} {:>6} {:>9}",
+                "Root",
+                "Policy",
+                "c",
+                "Depth",
+                "Width",
+                "Iter",
+                "Nodes",
+                "Calls",
+                "Best",
+                "Latency"
+            );
+            println!("{:-<80}", "");
+            for search in summarize_mcts_searches(&iterations) {
+                println!(
+                    "{:<10} {:<20} {:>5.2} {:>5} {:>5} {:>5} {:>6} {:>6} {:>6.2} {:>7}ms",
+                    search.root_node_id.chars().take(8).collect::<String>(),
+                    search.policy.to_string(),
+                    search.exploration_constant,
+                    search.rollout_depth,
+                    search.expansion_width,
+                    search.iterations,
+                    search.nodes,
+                    search.pipe_calls,
+                    search.best_value,
+                    search.latency_ms
+                );
+            }
+
+            println!("\n{:=<80}", "");
+            println!("QUALITY BY BUDGET");
+            println!("{:=<80}\n", "");
+            println!(
+                "{:<20} {:>9} {:>9} {:>10} {:>11} {:>10}",
+                "Policy", "Iteration", "Searches", "Avg Best", "Avg Calls", "Avg Nodes"
+            );
+            println!("{:-<80}", "");
+            for point in mcts_budget_curve(&iterations) {
+                println!(
+                    "{:<20} {:>9} {:>9} {:>10.3} {:>11.1} {:>10.1}",
+                    point.policy.to_string(),
+                    point.iteration,
+                    point.searches,
+                    point.mean_best_value,
+                    point.mean_pipe_calls,
+                    point.mean_nodes
+                );
+            }
+            println!();
+        }
+
+        MetricsAction::Routing { limit } => {
+            let mut filter = RoutingRecordFilter::new().with_limit(limit);
+            if let Some(workspace) = workspace {
+                filter = filter.in_workspace(workspace);
+            }
+
+            let mut records = storage.list_routing_records(filter).await?;
+            if records.is_empty() {
+                println!("No routing decisions found.");
+                return Ok(());
+            }
+            // Pick up sessions that ran or were reviewed since routing
+            for record in &mut records {
+                evaluate_routing_outcome(&storage, record).await?;
+            }
+
+            let format_score =
+                |score: Option<f64>| score.map_or("-".to_string(), |s| format!("{:.2}", s));
+
+            println!("\n{:=<80}", "");
+            println!("ROUTING TABLE ({} decisions)", records.len());
+            println!("{:=<80}\n", "");
+            println!(
+                "{:<16} {:>11} {:>8} {:>5} {:>9} {:>8} {:>6} {:>9} {:>10}",
+                "Mode",
+                "Recommended",
+                "History",
+                "Ran",
+                "Evaluated",
+                "Outcome",
+                "Rated",
+                "Feedback",
+                "Detections"
+            );
+            println!("{:-<80}", "");
+            for row in routing_table(&records) {
+                println!(
+                    "{:<16} {:>11} {:>8} {:>5} {:>9} {:>8} {:>6} {:>9} {:>10}",
+                    row.mode,
+                    row.recommended,
+                    row.history_applied,
+                    row.ran,
+                    row.evaluated,
+                    format_score(row.mean_outcome),
+                    row.rated,
+                    format_score(row.mean_feedback),
+                    row.detections
+                );
+            }
+
+            println!("\n{:=<80}", "");
+            println!("RECENT DECISIONS");
+            println!("{:=<80}\n", "");
+            println!(
+                "{:<10} {:<16} {:<16} {:>8} {:>8}  Content",
+                "ID", "Recommended", "Ran", "Outcome", "Feedback"
+            );
+            println!("{:-<80}", "");
+            for record in records.iter().take(10) {
+                println!(
+                    "{:<10} {:<16} {:<16} {:>8} {:>8}  {}",
+                    record.id.chars().take(8).collect::<String>(),
+                    record.recommended_mode,
+                    record.outcome_mode.as_deref().unwrap_or("-"),
+                    format_score(record.outcome_score),
+                    format_score(record.feedback_score),
+                    record.content_preview.chars().take(30).collect::<String>()
+                );
+            }
+            println!();
+        }
     }
 
     Ok(())
@@ -257,6 +638,459 @@ async fn run_self_improve_command(
     Ok(())
 }
 
+/// List sessions matching the given filters
+async fn run_sessions_command(
+    config: &Config,
+    mode: Option<String>,
+    tag: Option<String>,
+    with_detections: bool,
+    workspace: Option<String>,
+    limit: u32,
+) -> anyhow::Result<()> {
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut filter = SessionFilter::new().with_limit(limit);
+    if let Some(mode) = mode {
+        filter = filter.with_mode(mode);
+    }
+    if let Some(tag) = tag {
+        filter = filter.with_tag(tag);
+    }
+    if with_detections {
+        filter = filter.with_detections(true);
+    }
+    if let Some(workspace) = workspace {
+        filter = filter.in_workspace(workspace);
+    }
+
+    let sessions = storage.list_sessions(filter).await?;
+
+    if sessions.is_empty() {
+        println!("No sessions found matching the criteria.");
+        return Ok(());
+    }
+
+    println!("\n{:=<80}", "");
+    println!("SESSIONS");
+    println!("{:=<80}\n", "");
+
+    for session in sessions {
+        println!(
+            "{} | {} | {} | created {} | updated {}",
+            session.id,
+            session.mode,
+            session.workspace_id,
+            session.created_at.format("%Y-%m-%d %H:%M:%S"),
+            session.updated_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        if !session.tags.is_empty() {
+            println!("    Tags: {}", session.tags.join(", "));
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// List audit log entries
+async fn run_audit_command(
+    config: &Config,
+    session: Option<String>,
+    tool: Option<String>,
+    actor: Option<String>,
+    days: Option<u32>,
+    limit: u32,
+) -> anyhow::Result<()> {
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut filter = AuditFilter::new().with_limit(limit);
+    if let Some(session) = session {
+        filter = filter.with_session(session);
+    }
+    if let Some(tool) = tool {
+        filter = filter.with_tool(tool);
+    }
+    if let Some(actor) = actor {
+        filter = filter.with_actor(actor);
+    }
+    if let Some(days) = days {
+        filter = filter.after(chrono::Utc::now() - chrono::Duration::days(i64::from(days)));
+    }
+
+    let entries = storage.list_audit_entries(filter).await?;
+
+    if entries.is_empty() {
+        println!("No audit entries found matching the criteria.");
+        return Ok(());
+    }
+
+    println!("\n{:=<80}", "");
+    println!("AUDIT LOG");
+    println!("{:=<80}\n", "");
+
+    let short = |hash: &Option<String>| {
+        hash.as_deref()
+            .map(|h| h.chars().take(12).collect::<String>())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    for entry in entries {
+        println!(
+            "{} | {} | {} | {}",
+            entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+            entry.actor,
+            entry.tool_name,
+            if entry.success { "ok" } else { "failed" }
+        );
+        if let Some(ref session_id) = entry.session_id {
+            println!(
+                "    Session: {} ({} -> {})",
+                session_id,
+                short(&entry.before_hash),
+                short(&entry.after_hash)
+            );
+        }
+        if let Some(ref error) = entry.error {
+            println!("    Error: {}", error);
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Run a manual retention pruning pass
+async fn run_prune_command(
+    config: &Config,
+    days: Option<u32>,
+    max_invocations: Option<u64>,
+    trash_days: Option<u32>,
+) -> anyhow::Result<()> {
+    let mut retention = config.retention.clone();
+    if days.is_some() {
+        retention.retention_days = days;
+    }
+    if max_invocations.is_some() {
+        retention.max_invocations = max_invocations;
+    }
+    if trash_days.is_some() {
+        retention.trash_retention_days = trash_days;
+    }
+
+    if !retention.is_enabled() {
+        println!("No retention limits configured. Set RETENTION_DAYS / MAX_INVOCATIONS / TRASH_RETENTION_DAYS or pass --days / --max-invocations / --trash-days.");
+        return Ok(());
+    }
+
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = prune(&storage, &retention).await?;
+
+    println!("Pruning complete:");
+    println!("   Sessions deleted:    {}", report.sessions_deleted);
+    println!("   Snapshots deleted:   {}", report.snapshots_deleted);
+    println!("   Invocations deleted: {}", report.invocations_deleted);
+    println!("   Payloads deleted:    {}", report.payloads_deleted);
+    println!("   Trash purged:        {}", report.trashed_sessions_purged);
+
+    Ok(())
+}
+
+/// Render a session's reasoning graph
+async fn run_got_export_command(
+    config: &Config,
+    session_id: &str,
+    format: GraphExportFormat,
+    output: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let nodes = storage.get_session_graph_nodes(session_id).await?;
+    if nodes.is_empty() {
+        anyhow::bail!("Session {} has no graph nodes", session_id);
+    }
+    let edges = storage.get_session_edges(session_id).await?;
+    let rendered = render_graph(session_id, &nodes, &edges, format);
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            eprintln!(
+                "Exported {} nodes and {} edges to {}",
+                nodes.len(),
+                edges.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// Render a session's branch hierarchy
+async fn run_tree_export_command(
+    config: &Config,
+    session_id: &str,
+    format: TreeExportFormat,
+    output: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let branches = storage.get_session_branches(session_id).await?;
+    if branches.is_empty() {
+        anyhow::bail!("Session {} has no branches", session_id);
+    }
+    let thoughts = storage.get_session_thoughts(session_id).await?;
+    let thought_counts = branch_thought_counts(&thoughts);
+    let rendered = render_branch_tree(&branches, &thought_counts, format);
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            eprintln!("Exported {} branches to {}", branches.len(), path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// Write a backup of the database
+async fn run_backup_command(
+    config: &Config,
+    output: &std::path::Path,
+    compress: bool,
+) -> anyhow::Result<()> {
+    let storage = match SqliteStorage::new(&config.database).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = storage.backup(output, compress).await?;
+
+    println!("Backup written to {}", report.path.display());
+    println!("   Size:       {} bytes", report.size_bytes);
+    println!("   Compressed: {}", report.compressed);
+
+    Ok(())
+}
+
+/// Replace the database with the contents of a backup
+async fn run_restore_command(
+    config: &Config,
+    input: &std::path::Path,
+    force: bool,
+) -> anyhow::Result<()> {
+    if config.database.path.exists() && !force {
+        anyhow::bail!(
+            "{} already exists; pass --force to overwrite it",
+            config.database.path.display()
+        );
+    }
+
+    SqliteStorage::restore(input, &config.database).await?;
+
+    println!(
+        "Restored {} from {}",
+        config.database.path.display(),
+        input.display()
+    );
+
+    Ok(())
+}
+
+/// Show migration status and apply pending migrations
+async fn run_migrate_command(config: &Config, dry_run: bool) -> anyhow::Result<()> {
+    let status = SqliteStorage::migration_status(&config.database).await?;
+
+    println!(
+        "{:<16} {:<10} {:<20} DESCRIPTION",
+        "VERSION", "STATE", "INSTALLED"
+    );
+    for m in &status.migrations {
+        println!(
+            "{:<16} {:<10} {:<20} {}",
+            m.version,
+            m.state.to_string(),
+            m.installed_on
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            m.description
+        );
+    }
+    println!();
+
+    // Print the diff instead of letting the migrator fail with a generic error
+    if let Err(e) = status.check() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    let pending = status.pending();
+    if pending.is_empty() {
+        println!("Database is up to date.");
+        return Ok(());
+    }
+
+    if pending.iter().any(|m| !m.reversible) {
+        println!(
+            "Rollback: these migrations are forward-only. Take a backup first (`backup`) to be able to restore the current schema."
+        );
+    }
+
+    if dry_run {
+        println!("Dry run: {} migration(s) would be applied.", pending.len());
+        return Ok(());
+    }
+
+    let storage = SqliteStorage::new(&config.database).await?;
+    storage.pool().close().await;
+    println!("Applied {} migration(s).", pending.len());
+
+    Ok(())
+}
+
+/// Run pipe CLI commands
+async fn run_pipes_command(config: &Config, action: PipesAction) -> anyhow::Result<()> {
+    match action {
+        PipesAction::Check { pipes } => run_pipes_check(config, pipes).await,
+        PipesAction::Sync { dry_run } => run_pipes_sync(config, dry_run).await,
+    }
+}
+
+/// Probe pipes and print their health
+async fn run_pipes_check(config: &Config, pipes: Vec<String>) -> anyhow::Result<()> {
+    let langbase = LangbaseClient::new(&config.langbase, config.request.clone())?;
+    let backend: SharedBackend = Arc::new(ProviderRouter::from_config(config, langbase)?);
+
+    let mut probes = pipe_probes(&config.pipes);
+    if !pipes.is_empty() {
+        probes.retain(|p| pipes.contains(&p.pipe));
+    }
+    if probes.is_empty() {
+        anyhow::bail!("No configured pipe matches {}", pipes.join(", "));
+    }
+
+    let report = check_pipes(&backend, probes).await;
+
+    println!(
+        "{:<14} {:<32} {:<12} {:>9}  SCHEMA",
+        "ROLE", "PIPE", "STATUS", "LATENCY"
+    );
+    for pipe in &report.pipes {
+        let status = if !pipe.reachable {
+            "unreachable"
+        } else if pipe.is_healthy() {
+            "ok"
+        } else {
+            "invalid"
+        };
+        let schema = match (&pipe.schema, pipe.schema_compliant) {
+            (Some(name), Some(true)) => format!("{} ✓", name),
+            (Some(name), Some(false)) => format!("{} ✗", name),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<14} {:<32} {:<12} {:>7}ms  {}",
+            pipe.role, pipe.pipe, status, pipe.latency_ms, schema
+        );
+        if let Some(error) = &pipe.error {
+            println!("    Error: {}", error);
+        }
+    }
+    println!();
+    println!("{} healthy, {} unhealthy", report.healthy, report.unhealthy);
+
+    if !report.is_healthy() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Push the prompts module's pipe definitions to Langbase
+async fn run_pipes_sync(config: &Config, dry_run: bool) -> anyhow::Result<()> {
+    let langbase = LangbaseClient::new(&config.langbase, config.request.clone())?;
+    let plan = sync_pipes(&langbase, PIPE_DEFINITIONS, dry_run).await?;
+
+    for pipe in &plan {
+        println!("{:<10} {}", pipe.action.to_string(), pipe.name);
+        for change in &pipe.changes {
+            println!("    {}", change);
+        }
+        if !pipe.prompt_diff.is_empty() {
+            println!("    system prompt:");
+            for line in &pipe.prompt_diff {
+                println!("      {}", line);
+            }
+        }
+    }
+    println!();
+
+    let changed = plan
+        .iter()
+        .filter(|p| p.action != SyncAction::Unchanged)
+        .count();
+    if dry_run {
+        println!("Dry run: {} pipe(s) would be synced.", changed);
+    } else {
+        println!("Synced {} pipe(s).", changed);
+    }
+
+    Ok(())
+}
+
+/// Encrypt an existing plaintext database into a new SQLCipher file
+#[cfg(feature = "sqlcipher")]
+async fn run_encrypt_db_command(config: &Config, output: &std::path::Path) -> anyhow::Result<()> {
+    let Some(key) = config.database.encryption_key.as_deref() else {
+        anyhow::bail!("DATABASE_ENCRYPTION_KEY must be set to the key for the encrypted database");
+    };
+
+    SqliteStorage::encrypt_database(&config.database.path, output, key).await?;
+
+    println!("Encrypted database written to {}", output.display());
+    println!(
+        "Replace {} with it and keep DATABASE_ENCRYPTION_KEY set when starting the server.",
+        config.database.path.display()
+    );
+
+    Ok(())
+}
+
 /// Run the MCP server (default behavior)
 async fn run_server(config: Config) -> anyhow::Result<()> {
     // Initialize logging
@@ -292,20 +1126,40 @@ async fn run_server(config: Config) -> anyhow::Result<()> {
     };
 
     // Ensure all required pipes exist (create if needed via upsert)
-    info!("Ensuring all required Langbase pipes exist...");
-    if let Err(e) = langbase.ensure_all_pipes().await {
-        error!(error = %e, "Failed to ensure pipes exist");
-        return Err(e.into());
+    if config.providers.uses_langbase() {
+        info!("Ensuring all required Langbase pipes exist...");
+        if let Err(e) = langbase.ensure_all_pipes().await {
+            error!(error = %e, "Failed to ensure pipes exist");
+            return Err(e.into());
+        }
+    } else {
+        info!(
+            provider = %config.providers.default,
+            "No pipes use Langbase, skipping pipe setup"
+        );
     }
 
     // Create application state
     let state = Arc::new(AppState::new(config, storage, langbase));
 
+    // Report broken pipes now rather than on the first tool call; the server
+    // still starts so healthy modes stay usable
+    if state.config.pipes.health_check_on_startup {
+        info!("Checking pipe health...");
+        let probes = pipe_probes(&state.config.pipes);
+        check_pipes(&state.backend, probes).await.log();
+    }
+
     // Start MCP server
+    match state.config.transport.kind {
+        TransportKind::Stdio => info!("Server ready, waiting for requests on stdin..."),
+        TransportKind::WebSocket => info!(
+            addr = %state.config.transport.ws_bind,
+            "Server ready, waiting for WebSocket clients..."
+        ),
+    }
     let server = McpServer::new(state);
 
-    info!("Server ready, waiting for requests on stdin...");
-
     if let Err(e) = server.run().await {
         error!(error = %e, "Server error");
         return Err(e.into());
@@ -0,0 +1,82 @@
+//! Partial results of multi-artifact tools.
+//!
+//! Modes that produce several artifacts in one call, such as divergent
+//! perspectives and GoT continuations, report each one through the
+//! [`ArtifactSink`] installed for the current task with [`with_artifact_sink`]
+//! as soon as it is ready. The tool still returns the complete result at the
+//! end; the sink lets the MCP layer forward the artifacts early as progress
+//! notifications.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+/// One artifact of a tool call, reported before the call completes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Artifact {
+    /// What the artifact is, e.g. `perspective` or `continuation`.
+    pub kind: String,
+    /// Position of the artifact among those of its kind in the call.
+    pub index: usize,
+    /// The artifact, shaped as in the tool's final result.
+    pub data: Value,
+}
+
+/// Callback receiving artifacts as they are produced.
+pub type ArtifactSink = Arc<dyn Fn(&Artifact) + Send + Sync>;
+
+tokio::task_local! {
+    static ARTIFACT_SINK: ArtifactSink;
+}
+
+/// Run `future` with `sink` receiving the artifacts of every mode call it makes.
+pub async fn with_artifact_sink<F: Future>(sink: ArtifactSink, future: F) -> F::Output {
+    ARTIFACT_SINK.scope(sink, future).await
+}
+
+/// Report an artifact to the current task's sink, if one is installed.
+pub(crate) fn emit_artifact(kind: &str, index: usize, data: &impl Serialize) {
+    let _ = ARTIFACT_SINK.try_with(|sink| match serde_json::to_value(data) {
+        Ok(data) => sink(&Artifact {
+            kind: kind.to_string(),
+            index,
+            data,
+        }),
+        Err(e) => warn!(error = %e, kind = %kind, "Failed to serialize artifact"),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_emit_artifact_reaches_sink() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let sink: ArtifactSink = Arc::new(move |artifact: &Artifact| {
+            recorder.lock().unwrap().push(artifact.clone());
+        });
+
+        with_artifact_sink(sink, async {
+            emit_artifact("perspective", 0, &serde_json::json!({"thought": "a"}));
+            emit_artifact("perspective", 1, &serde_json::json!({"thought": "b"}));
+        })
+        .await;
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[1].kind, "perspective");
+        assert_eq!(seen[1].index, 1);
+        assert_eq!(seen[1].data["thought"], "b");
+    }
+
+    #[test]
+    fn test_emit_artifact_without_sink_is_noop() {
+        emit_artifact("perspective", 0, &"ignored");
+    }
+}
@@ -1,16 +1,39 @@
 //! Auto mode router - automatically selects the most appropriate reasoning mode
 
+use chrono::Utc;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
 use super::{serialize_for_log, ModeCore};
 use crate::config::Config;
-use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::error::{AppResult, StorageResult, ToolError};
+use crate::langbase::{EmbeddingProvider, GenerationParams, Message, PipeRequest};
 use crate::modes::ReasoningMode;
 use crate::prompts::AUTO_ROUTER_PROMPT;
-use crate::storage::{Invocation, SqliteStorage, Storage};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{
+    cosine_similarity, Invocation, RoutingRecord, RoutingRecordFilter, SharedStorage, Storage,
+};
+
+/// Minimum similarity for a past decision to count as similar content.
+const HISTORY_SIMILARITY_THRESHOLD: f64 = 0.6;
+/// Most similar past decisions weighed per routing.
+const HISTORY_NEIGHBORS: usize = 10;
+/// Recent decisions scanned for similar content.
+const HISTORY_SCAN_LIMIT: u32 = 500;
+/// Summed similarity at which a mode's history reaches full weight.
+const HISTORY_FULL_EVIDENCE: f64 = 3.0;
+/// Largest share of a mode's confidence that its history can decide.
+const HISTORY_MAX_WEIGHT: f64 = 0.5;
+/// Outcome score lost per bias or fallacy detected in the session.
+const DETECTION_PENALTY: f64 = 0.1;
+/// Characters of routed content kept with each routing record.
+const CONTENT_PREVIEW_CHARS: usize = 200;
 
 /// Input parameters for auto mode routing
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,10 +46,16 @@ pub struct AutoParams {
     /// Optional session ID for context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+    /// Weigh outcomes of past decisions for similar content (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_history: Option<bool>,
 }
 
 /// Result of auto mode routing.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AutoResult {
     /// The recommended reasoning mode.
     pub recommended_mode: ReasoningMode,
@@ -44,10 +73,107 @@ pub struct AutoResult {
     /// The original invalid mode string if fallback was used.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_invalid_mode: Option<String>,
+    /// ID of the stored routing decision, for `reasoning_routing_feedback`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_id: Option<String>,
+    /// How outcomes of past decisions for similar content were weighed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<RoutingHistory>,
 }
 
-/// A mode recommendation with confidence.
+/// Outcomes of past routing decisions for similar content.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RoutingHistory {
+    /// Similar past decisions with an observed outcome.
+    pub similar_decisions: usize,
+    /// Outcome evidence per mode, best first.
+    pub modes: Vec<ModeOutcome>,
+    /// Whether history changed the recommended mode.
+    pub applied: bool,
+    /// Mode recommended before history was weighed, if history changed it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_mode: Option<ReasoningMode>,
+}
+
+/// How a mode worked out for similar content.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModeOutcome {
+    /// The reasoning mode the outcomes are credited to.
+    pub mode: ReasoningMode,
+    /// Similar decisions credited to the mode.
+    pub decisions: usize,
+    /// Summed similarity of those decisions.
+    pub evidence: f64,
+    /// Similarity-weighted mean outcome score (0.0-1.0).
+    pub mean_score: f64,
+}
+
+/// Input parameters for rating a routing decision.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingFeedbackParams {
+    /// Routing decision to rate (from `reasoning_auto`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_id: Option<String>,
+    /// Rate the latest routing decision of this session instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// How well the routed mode worked (0.0-1.0)
+    pub score: f64,
+    /// Optional note explaining the rating
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A routing decision after feedback was recorded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RoutingFeedbackResult {
+    /// The rated routing decision.
+    pub routing_id: String,
+    /// Session the decision was made for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Mode the router recommended.
+    pub recommended_mode: String,
+    /// Mode the session actually ran, if observed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome_mode: Option<String>,
+    /// Mean confidence of the session's results, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome_confidence: Option<f64>,
+    /// Bias and fallacy detections recorded in the session.
+    pub detection_count: i32,
+    /// The recorded rating.
+    pub feedback_score: f64,
+    /// Combined outcome score used by later routing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome_score: Option<f64>,
+}
+
+/// Learned routing outcomes of one mode, for the metrics CLI.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingTableRow {
+    /// Reasoning mode.
+    pub mode: String,
+    /// Decisions that recommended the mode.
+    pub recommended: usize,
+    /// Of those, decisions whose recommendation history changed.
+    pub history_applied: usize,
+    /// Decisions whose session ran the mode.
+    pub ran: usize,
+    /// Decisions credited to the mode with an outcome score.
+    pub evaluated: usize,
+    /// Mean outcome score of the evaluated decisions.
+    pub mean_outcome: Option<f64>,
+    /// Decisions credited to the mode with feedback.
+    pub rated: usize,
+    /// Mean feedback score of the rated decisions.
+    pub mean_feedback: Option<f64>,
+    /// Detections in the sessions credited to the mode.
+    pub detections: i64,
+}
+
+/// A mode recommendation with confidence.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ModeRecommendation {
     /// The reasoning mode.
     pub mode: ReasoningMode,
@@ -89,28 +215,45 @@ impl AutoResponse {
 /// Auto mode router
 #[derive(Clone)]
 pub struct AutoMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for auto routing.
     pipe_name: String,
+    /// Embeddings for finding past decisions on similar content; without
+    /// them routing neither records nor weighs history.
+    embeddings: Option<Arc<dyn EmbeddingProvider>>,
 }
 
 impl AutoMode {
     /// Create a new auto mode router
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config
                 .pipes
                 .auto
                 .clone()
                 .unwrap_or_else(|| "mode-router-v1".to_string()),
+            embeddings: None,
         }
     }
 
+    /// Record routing decisions and weigh past outcomes for similar content
+    /// using `embeddings`
+    pub fn with_embeddings(mut self, embeddings: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
     /// Route to the appropriate reasoning mode
     pub async fn route(&self, params: AutoParams) -> AppResult<AutoResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         debug!(content_len = params.content.len(), "Auto-routing content");
 
@@ -122,26 +265,30 @@ impl AutoMode {
                 source = "heuristics",
                 "Auto-routing completed via heuristics"
             );
-            return Ok(result);
+            return Ok(self.weigh_history(&params, result).await);
         }
 
         // Build messages for Langbase
         let messages = self.build_messages(&params);
 
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+
         // Log invocation
         let mut invocation = Invocation::new(
             "reasoning.auto",
             serialize_for_log(&params, "reasoning.auto input"),
         )
-        .with_pipe(&self.pipe_name);
+        .with_pipe(&pipe_name);
 
         if let Some(session_id) = &params.session_id {
             invocation = invocation.with_session(session_id);
         }
 
         // Call Langbase
-        let request = PipeRequest::new(&self.pipe_name, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request = PipeRequest::new(&pipe_name, messages)
+            .with_output_schema(self.core.response_schema(SchemaKind::Auto, &pipe_name))
+            .with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -156,6 +303,9 @@ impl AutoMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let auto_response = AutoResponse::from_completion(&response.completion)?;
@@ -208,7 +358,7 @@ impl AutoMode {
             "Auto-routing completed"
         );
 
-        Ok(AutoResult {
+        let result = AutoResult {
             recommended_mode,
             confidence: auto_response.confidence,
             rationale: auto_response.rationale,
@@ -216,9 +366,176 @@ impl AutoMode {
             alternative_modes: alternatives,
             fallback_used,
             original_invalid_mode,
+            routing_id: None,
+            history: None,
+        };
+        Ok(self.weigh_history(&params, result).await)
+    }
+
+    /// Record a rating of a routing decision and refresh its outcome
+    pub async fn feedback(
+        &self,
+        params: RoutingFeedbackParams,
+    ) -> AppResult<RoutingFeedbackResult> {
+        if !(0.0..=1.0).contains(&params.score) {
+            return Err(ToolError::Validation {
+                field: "score".to_string(),
+                reason: "Score must be between 0.0 and 1.0".to_string(),
+            }
+            .into());
+        }
+
+        let storage = self.core.storage();
+        let record = match (&params.routing_id, &params.session_id) {
+            (Some(routing_id), _) => {
+                storage
+                    .get_routing_record(routing_id)
+                    .await?
+                    .ok_or_else(|| ToolError::Validation {
+                        field: "routing_id".to_string(),
+                        reason: format!("Routing decision not found: {}", routing_id),
+                    })?
+            }
+            (None, Some(session_id)) => storage
+                .list_routing_records(
+                    RoutingRecordFilter::new()
+                        .in_session(session_id)
+                        .with_limit(1),
+                )
+                .await?
+                .pop()
+                .ok_or_else(|| ToolError::Validation {
+                    field: "session_id".to_string(),
+                    reason: format!("No routing decision for session: {}", session_id),
+                })?,
+            (None, None) => {
+                return Err(ToolError::Validation {
+                    field: "routing_id".to_string(),
+                    reason: "Give routing_id or session_id".to_string(),
+                }
+                .into())
+            }
+        };
+
+        let mut record = record;
+        record.feedback_score = Some(params.score);
+        record.feedback_note = params.note;
+        evaluate_routing_outcome(storage, &mut record).await?;
+        // Feedback alone is an outcome, so store it even without a session
+        record.outcome_score = outcome_score(
+            record.outcome_confidence,
+            record.detection_count,
+            record.feedback_score,
+        );
+        record.evaluated_at = Some(Utc::now());
+        storage.update_routing_outcome(&record).await?;
+
+        info!(
+            routing_id = %record.id,
+            score = params.score,
+            outcome_score = ?record.outcome_score,
+            "Routing feedback recorded"
+        );
+
+        Ok(RoutingFeedbackResult {
+            routing_id: record.id,
+            session_id: record.session_id,
+            recommended_mode: record.recommended_mode,
+            outcome_mode: record.outcome_mode,
+            outcome_confidence: record.outcome_confidence,
+            detection_count: record.detection_count,
+            feedback_score: params.score,
+            outcome_score: record.outcome_score,
         })
     }
 
+    /// Weigh outcomes of past decisions for similar content into `result`
+    /// and record the decision.
+    ///
+    /// History never fails a routing: without an embedding provider, or when
+    /// embedding or storage fails, `result` is returned unweighed.
+    async fn weigh_history(&self, params: &AutoParams, mut result: AutoResult) -> AutoResult {
+        let Some(provider) = self.embeddings.as_ref() else {
+            return result;
+        };
+        let vector = match provider.embed(std::slice::from_ref(&params.content)).await {
+            Ok(mut vectors) if vectors.len() == 1 => vectors.remove(0),
+            Ok(vectors) => {
+                warn!(
+                    received = vectors.len(),
+                    "Embedding count mismatch, skipping routing history"
+                );
+                return result;
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to embed routed content, skipping routing history");
+                return result;
+            }
+        };
+        let model = provider.model().to_string();
+
+        if params.use_history.unwrap_or(true) {
+            match self.similar_outcomes(&model, &vector).await {
+                Ok((similar, outcomes)) => result = apply_history(result, similar, outcomes),
+                Err(e) => warn!(error = %e, "Failed to read routing history"),
+            }
+        }
+
+        let preview: String = params.content.chars().take(CONTENT_PREVIEW_CHARS).collect();
+        let applied = result.history.as_ref().is_some_and(|h| h.applied);
+        let mut record = RoutingRecord::new(
+            preview,
+            model,
+            vector,
+            result.recommended_mode.as_str(),
+            result.confidence,
+        )
+        .with_history_applied(applied);
+        if let Some(session_id) = &params.session_id {
+            record = record.with_session(session_id);
+        }
+        match self.core.storage().create_routing_record(&record).await {
+            Ok(()) => result.routing_id = Some(record.id),
+            Err(e) => warn!(error = %e, "Failed to record routing decision"),
+        }
+        result
+    }
+
+    /// Outcomes per mode of the past decisions most similar to `vector`,
+    /// with the number of those decisions that have an outcome.
+    ///
+    /// Each decision's outcome is refreshed from its session first, so
+    /// sessions that ran or were reviewed since are counted.
+    async fn similar_outcomes(
+        &self,
+        model: &str,
+        vector: &[f32],
+    ) -> StorageResult<(usize, Vec<ModeOutcome>)> {
+        let storage = self.core.storage();
+        let records = storage
+            .list_routing_records(
+                RoutingRecordFilter::new()
+                    .with_model(model)
+                    .with_limit(HISTORY_SCAN_LIMIT),
+            )
+            .await?;
+
+        let mut neighbors: Vec<(f64, RoutingRecord)> = records
+            .into_iter()
+            .map(|record| (cosine_similarity(vector, &record.vector), record))
+            .filter(|(similarity, _)| *similarity >= HISTORY_SIMILARITY_THRESHOLD)
+            .collect();
+        neighbors.sort_by(|a, b| b.0.total_cmp(&a.0));
+        neighbors.truncate(HISTORY_NEIGHBORS);
+
+        for (_, record) in &mut neighbors {
+            evaluate_routing_outcome(storage, record).await?;
+        }
+        neighbors.retain(|(_, record)| record.outcome_score.is_some());
+
+        Ok((neighbors.len(), mode_outcomes(&neighbors)))
+    }
+
     /// Apply local heuristics for obvious cases
     fn local_heuristics(&self, params: &AutoParams) -> Option<AutoResult> {
         let content_lower = params.content.to_lowercase();
@@ -233,6 +550,8 @@ impl AutoMode {
                 alternative_modes: vec![],
                 fallback_used: false,
                 original_invalid_mode: None,
+                routing_id: None,
+                history: None,
             });
         }
 
@@ -254,6 +573,8 @@ impl AutoMode {
                 }],
                 fallback_used: false,
                 original_invalid_mode: None,
+                routing_id: None,
+                history: None,
             });
         }
 
@@ -275,6 +596,8 @@ impl AutoMode {
                 }],
                 fallback_used: false,
                 original_invalid_mode: None,
+                routing_id: None,
+                history: None,
             });
         }
 
@@ -296,6 +619,8 @@ impl AutoMode {
                 }],
                 fallback_used: false,
                 original_invalid_mode: None,
+                routing_id: None,
+                history: None,
             });
         }
 
@@ -317,6 +642,8 @@ impl AutoMode {
                 }],
                 fallback_used: false,
                 original_invalid_mode: None,
+                routing_id: None,
+                history: None,
             });
         }
 
@@ -398,6 +725,8 @@ impl AutoParams {
             content: content.into(),
             hints: None,
             session_id: None,
+            generation: GenerationParams::default(),
+            use_history: None,
         }
     }
 
@@ -412,11 +741,287 @@ impl AutoParams {
         self.session_id = Some(session_id.into());
         self
     }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Enable or disable weighing of past outcomes
+    pub fn with_history(mut self, use_history: bool) -> Self {
+        self.use_history = Some(use_history);
+        self
+    }
+}
+
+/// Combine the signals observed for a routing decision into one outcome
+/// score (0.0-1.0).
+///
+/// The session's mean confidence loses `DETECTION_PENALTY` per bias or
+/// fallacy detected in it; feedback, when given, counts as much as the
+/// session itself. `None` when neither signal has been observed.
+pub fn outcome_score(
+    confidence: Option<f64>,
+    detections: i32,
+    feedback: Option<f64>,
+) -> Option<f64> {
+    let observed =
+        confidence.map(|c| (c - DETECTION_PENALTY * f64::from(detections.max(0))).clamp(0.0, 1.0));
+    match (observed, feedback) {
+        (Some(observed), Some(feedback)) => Some((observed + feedback) / 2.0),
+        (Some(observed), None) => Some(observed),
+        (None, Some(feedback)) => Some(feedback),
+        (None, None) => None,
+    }
+}
+
+/// Refresh a routing decision's outcome from its session and store it if
+/// it changed. Returns whether it changed.
+///
+/// The outcome is credited to the mode the session ran, with the mean
+/// confidence of its thoughts (or GoT node scores) and the number of bias
+/// and fallacy detections in it. Decisions without a stored session are
+/// left alone.
+pub async fn evaluate_routing_outcome(
+    storage: &dyn Storage,
+    record: &mut RoutingRecord,
+) -> StorageResult<bool> {
+    let Some(session_id) = record.session_id.clone() else {
+        return Ok(false);
+    };
+    let Some(session) = storage.get_session(&session_id).await? else {
+        return Ok(false);
+    };
+
+    let thoughts = storage.get_session_thoughts(&session_id).await?;
+    let confidences: Vec<f64> = if thoughts.is_empty() {
+        storage
+            .get_session_graph_nodes(&session_id)
+            .await?
+            .iter()
+            .filter_map(|n| n.score)
+            .collect()
+    } else {
+        thoughts.iter().map(|t| t.confidence).collect()
+    };
+    let confidence = if confidences.is_empty() {
+        None
+    } else {
+        Some(confidences.iter().sum::<f64>() / confidences.len() as f64)
+    };
+    let detections = storage.get_session_detections(&session_id).await?.len() as i32;
+    let score = outcome_score(confidence, detections, record.feedback_score);
+
+    let changed = record.outcome_mode.as_deref() != Some(session.mode.as_str())
+        || record.outcome_confidence != confidence
+        || record.detection_count != detections
+        || record.outcome_score != score;
+    if changed {
+        record.outcome_mode = Some(session.mode);
+        record.outcome_confidence = confidence;
+        record.detection_count = detections;
+        record.outcome_score = score;
+        record.evaluated_at = Some(Utc::now());
+        storage.update_routing_outcome(record).await?;
+    }
+    Ok(changed)
+}
+
+/// Similarity-weighted outcomes per mode of scored neighbors, best first.
+///
+/// Outcomes credited to a mode that is not a known reasoning mode, or to
+/// auto routing itself, are skipped.
+fn mode_outcomes(neighbors: &[(f64, RoutingRecord)]) -> Vec<ModeOutcome> {
+    let mut by_mode: Vec<ModeOutcome> = Vec::new();
+    for (similarity, record) in neighbors {
+        let Some(score) = record.outcome_score else {
+            continue;
+        };
+        let Ok(mode) = record.credited_mode().parse::<ReasoningMode>() else {
+            continue;
+        };
+        if mode == ReasoningMode::Auto {
+            continue;
+        }
+        let weight = similarity.max(0.0);
+        match by_mode.iter_mut().find(|o| o.mode == mode) {
+            Some(outcome) => {
+                // mean_score holds the weighted sum until all are counted
+                outcome.decisions += 1;
+                outcome.evidence += weight;
+                outcome.mean_score += score * weight;
+            }
+            None => by_mode.push(ModeOutcome {
+                mode,
+                decisions: 1,
+                evidence: weight,
+                mean_score: score * weight,
+            }),
+        }
+    }
+    for outcome in &mut by_mode {
+        outcome.mean_score = if outcome.evidence > 0.0 {
+            outcome.mean_score / outcome.evidence
+        } else {
+            0.0
+        };
+    }
+    by_mode.sort_by(|a, b| b.mean_score.total_cmp(&a.mean_score));
+    by_mode
+}
+
+/// Blend each candidate mode's confidence with its past outcomes and
+/// re-rank, recording how history was weighed in `result.history`.
+///
+/// A mode's history counts for up to `HISTORY_MAX_WEIGHT` of its
+/// confidence, reached at `HISTORY_FULL_EVIDENCE` summed similarity. Modes
+/// the router did not suggest but that similar sessions ran join as
+/// candidates with no confidence of their own.
+fn apply_history(mut result: AutoResult, similar: usize, outcomes: Vec<ModeOutcome>) -> AutoResult {
+    if outcomes.is_empty() {
+        return result;
+    }
+
+    let original = result.recommended_mode;
+    let mut candidates: Vec<ModeRecommendation> = Vec::new();
+    candidates.push(ModeRecommendation {
+        mode: result.recommended_mode,
+        confidence: result.confidence,
+        rationale: result.rationale.clone(),
+    });
+    for alternative in &result.alternative_modes {
+        if !candidates.iter().any(|c| c.mode == alternative.mode) {
+            candidates.push(alternative.clone());
+        }
+    }
+    for outcome in &outcomes {
+        if !candidates.iter().any(|c| c.mode == outcome.mode) {
+            candidates.push(ModeRecommendation {
+                mode: outcome.mode,
+                confidence: 0.0,
+                rationale: "Similar content was handled with this mode before".to_string(),
+            });
+        }
+    }
+
+    for candidate in &mut candidates {
+        if let Some(outcome) = outcomes.iter().find(|o| o.mode == candidate.mode) {
+            let weight = HISTORY_MAX_WEIGHT * (outcome.evidence / HISTORY_FULL_EVIDENCE).min(1.0);
+            candidate.confidence = (candidate.confidence * (1.0 - weight)
+                + outcome.mean_score * weight)
+                .clamp(0.0, 1.0);
+        }
+    }
+    // Stable sort keeps the router's order between equal candidates
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let best = candidates.remove(0);
+    let applied = best.mode != original;
+    result.recommended_mode = best.mode;
+    result.confidence = best.confidence;
+    if applied {
+        let describe = |outcome: &ModeOutcome| {
+            format!(
+                "mean outcome {:.2} over {} decision{}",
+                outcome.mean_score,
+                outcome.decisions,
+                if outcome.decisions == 1 { "" } else { "s" }
+            )
+        };
+        // Either the new mode's history lifted it, or the original's sank it
+        let reason = match outcomes.iter().find(|o| o.mode == best.mode) {
+            Some(outcome) => format!(
+                "Similar content did better with {} ({}) than with {}.",
+                best.mode,
+                describe(outcome),
+                original
+            ),
+            None => {
+                let outcome = outcomes.iter().find(|o| o.mode == original);
+                format!(
+                    "Similar content did poorly with {}{}.",
+                    original,
+                    outcome.map_or(String::new(), |o| format!(" ({})", describe(o)))
+                )
+            }
+        };
+        result.rationale = format!("{} {}", reason, best.rationale);
+    }
+    result.alternative_modes = candidates;
+    result.history = Some(RoutingHistory {
+        similar_decisions: similar,
+        modes: outcomes,
+        applied,
+        original_mode: applied.then_some(original),
+    });
+    result
+}
+
+/// Summarize routing decisions per mode: how often each was recommended
+/// and run, and how its sessions turned out.
+pub fn routing_table(records: &[RoutingRecord]) -> Vec<RoutingTableRow> {
+    fn row<'a>(
+        rows: &'a mut BTreeMap<String, RoutingTableRow>,
+        mode: &str,
+    ) -> &'a mut RoutingTableRow {
+        rows.entry(mode.to_string())
+            .or_insert_with(|| RoutingTableRow {
+                mode: mode.to_string(),
+                ..Default::default()
+            })
+    }
+
+    let mut rows: BTreeMap<String, RoutingTableRow> = BTreeMap::new();
+    let mut outcome_sums: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+
+    for record in records {
+        let recommended = row(&mut rows, &record.recommended_mode);
+        recommended.recommended += 1;
+        if record.history_applied {
+            recommended.history_applied += 1;
+        }
+
+        let mode = record.credited_mode().to_string();
+        let credited = row(&mut rows, &mode);
+        if record.outcome_mode.is_some() {
+            credited.ran += 1;
+        }
+        credited.detections += i64::from(record.detection_count);
+        let sums = outcome_sums.entry(mode).or_insert((0.0, 0.0));
+        if let Some(score) = record.outcome_score {
+            credited.evaluated += 1;
+            sums.0 += score;
+        }
+        if let Some(feedback) = record.feedback_score {
+            credited.rated += 1;
+            sums.1 += feedback;
+        }
+    }
+
+    let mut table: Vec<RoutingTableRow> = rows
+        .into_values()
+        .map(|mut row| {
+            let (outcomes, feedback) = outcome_sums.get(&row.mode).copied().unwrap_or_default();
+            row.mean_outcome = (row.evaluated > 0).then(|| outcomes / row.evaluated as f64);
+            row.mean_feedback = (row.rated > 0).then(|| feedback / row.rated as f64);
+            row
+        })
+        .collect();
+    table.sort_by(|a, b| {
+        b.mean_outcome
+            .unwrap_or(-1.0)
+            .total_cmp(&a.mean_outcome.unwrap_or(-1.0))
+            .then_with(|| a.mode.cmp(&b.mode))
+    });
+    table
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langbase::LangbaseClient;
+    use crate::storage::SqliteStorage;
 
     // ============================================================================
     // AutoParams Tests
@@ -579,6 +1184,8 @@ mod tests {
             }],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("tree"));
@@ -622,6 +1229,8 @@ mod tests {
             ],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         let parsed: AutoResult = serde_json::from_str(&json).unwrap();
@@ -654,6 +1263,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: true,
             original_invalid_mode: Some("invalid_mode_xyz".to_string()),
+            routing_id: None,
+            history: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("\"fallback_used\":true"));
@@ -671,6 +1282,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         let json = serde_json::to_string(&result).unwrap();
         // original_invalid_mode should be skipped when None
@@ -690,6 +1303,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: true,
             original_invalid_mode: Some("bad_mode".to_string()),
+            routing_id: None,
+            history: None,
         };
         let json = serde_json::to_string(&original).unwrap();
         let parsed: AutoResult = serde_json::from_str(&json).unwrap();
@@ -825,6 +1440,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         assert!(result.alternative_modes.is_empty());
     }
@@ -871,6 +1488,8 @@ mod tests {
             }],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -1279,14 +1898,18 @@ mod tests {
     #[test]
     fn test_auto_mode_new_with_custom_pipe() {
         use crate::config::{
-            Config, DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig, RequestConfig,
+            BudgetConfig, ConcurrencyConfig, Config, DatabaseConfig, EmbeddingConfig,
+            ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig,
+            MctsConfig, NetworkConfig, PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig,
+            PipeConfig, ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig,
+            ToolsConfig, TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
         let langbase_config = LangbaseConfig {
             api_key: "test-key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let pipes = PipeConfig {
@@ -1299,6 +1922,10 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1307,6 +1934,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes,
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1320,14 +1959,15 @@ mod tests {
     #[test]
     fn test_auto_mode_new_without_custom_pipe_uses_default() {
         use crate::config::{
-            Config, DatabaseConfig, LangbaseConfig, LogFormat, LoggingConfig, PipeConfig,
-            RequestConfig,
+            Config, DatabaseConfig, LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig,
+            NetworkConfig, PipeConfig, RequestConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
         let langbase_config = LangbaseConfig {
             api_key: "test-key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let pipes = PipeConfig {
@@ -1340,6 +1980,10 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1348,6 +1992,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes,
             error_handling: crate::config::ErrorHandlingConfig::default(),
+            retention: crate::config::RetentionConfig::default(),
+            embeddings: crate::config::EmbeddingConfig::default(),
+            providers: crate::config::ProviderConfig::default(),
+            pipe_cache: crate::config::PipeCacheConfig::default(),
+            rate_limits: crate::config::RateLimitConfig::default(),
+            circuit_breaker: crate::config::PipeCircuitBreakerConfig::default(),
+            concurrency: crate::config::ConcurrencyConfig::default(),
+            payload_log: crate::config::PayloadLogConfig::default(),
+            budget: crate::config::BudgetConfig::default(),
+            transport: crate::config::TransportConfig::default(),
+            tools: crate::config::ToolsConfig::default(),
+            mcts: crate::config::MctsConfig::default(),
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1723,6 +2379,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         assert_eq!(result.complexity, 0.0);
 
@@ -1734,6 +2392,8 @@ mod tests {
             alternative_modes: vec![],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         assert_eq!(result2.complexity, 1.0);
     }
@@ -1764,6 +2424,8 @@ mod tests {
             ],
             fallback_used: false,
             original_invalid_mode: None,
+            routing_id: None,
+            history: None,
         };
         assert_eq!(result.alternative_modes.len(), 3);
     }
@@ -1825,20 +2487,197 @@ mod tests {
         assert!(params.hints.unwrap()[0].contains('\n'));
     }
 
+    // ============================================================================
+    // Routing History Tests
+    // ============================================================================
+
+    fn routing_result(mode: ReasoningMode, confidence: f64) -> AutoResult {
+        AutoResult {
+            recommended_mode: mode,
+            confidence,
+            rationale: "Router choice".to_string(),
+            complexity: 0.5,
+            alternative_modes: vec![ModeRecommendation {
+                mode: ReasoningMode::Tree,
+                confidence: 0.5,
+                rationale: "Branching alternative".to_string(),
+            }],
+            fallback_used: false,
+            original_invalid_mode: None,
+            routing_id: None,
+            history: None,
+        }
+    }
+
+    fn mode_outcome(mode: ReasoningMode, evidence: f64, mean_score: f64) -> ModeOutcome {
+        ModeOutcome {
+            mode,
+            decisions: evidence.ceil() as usize,
+            evidence,
+            mean_score,
+        }
+    }
+
+    fn scored_record(mode: &str, score: Option<f64>) -> RoutingRecord {
+        let mut record = RoutingRecord::new("content", "local:hash-4", vec![1.0], mode, 0.8);
+        record.outcome_mode = Some(mode.to_string());
+        record.outcome_score = score;
+        record
+    }
+
+    #[test]
+    fn test_auto_params_with_history() {
+        let params = AutoParams::new("Content").with_history(false);
+        assert_eq!(params.use_history, Some(false));
+        let json = serde_json::to_string(&AutoParams::new("Content")).unwrap();
+        assert!(!json.contains("use_history"));
+    }
+
+    #[test]
+    fn test_outcome_score_combines_signals() {
+        assert_eq!(outcome_score(None, 0, None), None);
+        assert_eq!(outcome_score(Some(0.8), 0, None), Some(0.8));
+        assert!((outcome_score(Some(0.8), 2, None).unwrap() - 0.6).abs() < 1e-9);
+        assert_eq!(outcome_score(None, 3, Some(0.4)), Some(0.4));
+        assert!((outcome_score(Some(0.8), 0, Some(0.2)).unwrap() - 0.5).abs() < 1e-9);
+        // Detections never push the session below zero
+        assert_eq!(outcome_score(Some(0.3), 10, None), Some(0.0));
+    }
+
+    #[test]
+    fn test_mode_outcomes_weight_by_similarity() {
+        let neighbors = vec![
+            (1.0, scored_record("linear", Some(0.9))),
+            (0.5, scored_record("linear", Some(0.3))),
+            (0.8, scored_record("tree", Some(0.6))),
+            (0.9, scored_record("auto", Some(1.0))),
+            (0.9, scored_record("not_a_mode", Some(1.0))),
+            (0.9, scored_record("divergent", None)),
+        ];
+        let outcomes = mode_outcomes(&neighbors);
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].mode, ReasoningMode::Linear);
+        assert_eq!(outcomes[0].decisions, 2);
+        assert!((outcomes[0].evidence - 1.5).abs() < 1e-9);
+        assert!((outcomes[0].mean_score - 0.7).abs() < 1e-9);
+        assert_eq!(outcomes[1].mode, ReasoningMode::Tree);
+    }
+
+    #[test]
+    fn test_apply_history_without_outcomes_keeps_result() {
+        let result = apply_history(routing_result(ReasoningMode::Linear, 0.8), 0, vec![]);
+        assert_eq!(result.recommended_mode, ReasoningMode::Linear);
+        assert_eq!(result.confidence, 0.8);
+        assert!(result.history.is_none());
+    }
+
+    #[test]
+    fn test_apply_history_switches_to_better_mode() {
+        let outcomes = vec![
+            mode_outcome(ReasoningMode::Tree, 3.0, 0.9),
+            mode_outcome(ReasoningMode::Linear, 3.0, 0.1),
+        ];
+        let result = apply_history(routing_result(ReasoningMode::Linear, 0.8), 6, outcomes);
+
+        // Linear: 0.8 * 0.5 + 0.1 * 0.5 = 0.45; tree: 0.5 * 0.5 + 0.9 * 0.5 = 0.7
+        assert_eq!(result.recommended_mode, ReasoningMode::Tree);
+        assert!((result.confidence - 0.7).abs() < 1e-9);
+        assert!(result.rationale.contains("did better with tree"));
+        assert_eq!(result.alternative_modes.len(), 1);
+        assert_eq!(result.alternative_modes[0].mode, ReasoningMode::Linear);
+        assert!((result.alternative_modes[0].confidence - 0.45).abs() < 1e-9);
+
+        let history = result.history.unwrap();
+        assert!(history.applied);
+        assert_eq!(history.similar_decisions, 6);
+        assert_eq!(history.original_mode, Some(ReasoningMode::Linear));
+    }
+
+    #[test]
+    fn test_apply_history_weak_evidence_keeps_recommendation() {
+        // One loosely similar decision barely moves the confidence
+        let outcomes = vec![mode_outcome(ReasoningMode::Linear, 0.6, 0.0)];
+        let result = apply_history(routing_result(ReasoningMode::Linear, 0.8), 1, outcomes);
+
+        assert_eq!(result.recommended_mode, ReasoningMode::Linear);
+        assert!((result.confidence - 0.72).abs() < 1e-9);
+        let history = result.history.unwrap();
+        assert!(!history.applied);
+        assert!(history.original_mode.is_none());
+    }
+
+    #[test]
+    fn test_apply_history_adds_modes_sessions_ran() {
+        let outcomes = vec![
+            mode_outcome(ReasoningMode::Decision, 3.0, 1.0),
+            mode_outcome(ReasoningMode::Linear, 3.0, 0.0),
+        ];
+        let result = apply_history(routing_result(ReasoningMode::Linear, 0.6), 6, outcomes);
+
+        // Linear sinks to 0.3; decision has no router confidence but strong
+        // history and ties tree at 0.5, which the router's order breaks
+        assert_eq!(result.recommended_mode, ReasoningMode::Tree);
+        assert!(result
+            .rationale
+            .starts_with("Similar content did poorly with linear"));
+        assert!(result
+            .alternative_modes
+            .iter()
+            .any(|a| a.mode == ReasoningMode::Decision && (a.confidence - 0.5).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_routing_table_credits_modes_sessions_ran() {
+        let mut overridden = scored_record("linear", Some(0.4));
+        overridden.outcome_mode = Some("tree".to_string());
+        overridden.detection_count = 2;
+        let mut rated = scored_record("linear", Some(0.9));
+        rated.feedback_score = Some(1.0);
+        rated.history_applied = true;
+        let mut pending = scored_record("linear", None);
+        pending.outcome_mode = None;
+
+        let table = routing_table(&[overridden, rated, pending]);
+
+        assert_eq!(table.len(), 2);
+        let linear = &table[0];
+        assert_eq!(linear.mode, "linear");
+        assert_eq!(linear.recommended, 3);
+        assert_eq!(linear.history_applied, 1);
+        assert_eq!(linear.ran, 1);
+        assert_eq!(linear.evaluated, 1);
+        assert_eq!(linear.mean_outcome, Some(0.9));
+        assert_eq!(linear.rated, 1);
+        assert_eq!(linear.mean_feedback, Some(1.0));
+
+        let tree = &table[1];
+        assert_eq!(tree.mode, "tree");
+        assert_eq!(tree.recommended, 0);
+        assert_eq!(tree.ran, 1);
+        assert_eq!(tree.detections, 2);
+        assert_eq!(tree.mean_outcome, Some(0.4));
+        assert_eq!(tree.mean_feedback, None);
+    }
+
     // ============================================================================
     // Helper Functions
     // ============================================================================
 
     fn create_test_mode() -> AutoMode {
         use crate::config::{
-            Config, DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig, RequestConfig,
+            BudgetConfig, ConcurrencyConfig, Config, DatabaseConfig, EmbeddingConfig,
+            ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig,
+            MctsConfig, NetworkConfig, PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig,
+            PipeConfig, ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig,
+            ToolsConfig, TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
         let langbase_config = LangbaseConfig {
             api_key: "test-key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
 
         let config = Config {
@@ -1846,6 +2685,10 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1854,6 +2697,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         };
 
         // Use a runtime for async operations in tests
@@ -1,15 +1,20 @@
 //! Backtracking reasoning mode - restore from checkpoints and explore alternative paths
 
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use tracing::{debug, info};
 
 use super::ModeCore;
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{client_log, GenerationParams, LogLevel, Message, PipeRequest};
 use crate::prompts::BACKTRACKING_PROMPT;
-use crate::storage::{Checkpoint, SnapshotType, SqliteStorage, StateSnapshot, Storage, Thought};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{Branch, Checkpoint, SharedStorage, SnapshotType, StateSnapshot, Thought};
 
 /// Input parameters for backtracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +30,9 @@ pub struct BacktrackingParams {
     /// Confidence threshold
     #[serde(default = "default_confidence")]
     pub confidence: f64,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_confidence() -> f64 {
@@ -32,7 +40,7 @@ fn default_confidence() -> f64 {
 }
 
 /// Result of backtracking operation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BacktrackingResult {
     /// The ID of the new thought created after backtracking.
     pub thought_id: String,
@@ -50,6 +58,251 @@ pub struct BacktrackingResult {
     pub snapshot_id: String,
 }
 
+/// Most key conclusions reported per checkpoint.
+const MAX_CONCLUSIONS: usize = 5;
+
+/// Input parameters for comparing two checkpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointDiffParams {
+    /// The earlier checkpoint
+    pub checkpoint_a: String,
+    /// The later checkpoint, in the same session
+    pub checkpoint_b: String,
+}
+
+/// Structured difference between two checkpoints of a session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckpointDiff {
+    /// The session both checkpoints belong to.
+    pub session_id: String,
+    /// State captured by `checkpoint_a`.
+    pub from: CheckpointState,
+    /// State captured by `checkpoint_b`.
+    pub to: CheckpointState,
+    /// Change in the number of thoughts.
+    pub thoughts: CountChange,
+    /// Thoughts in `checkpoint_b` but not in `checkpoint_a`.
+    pub added_thoughts: Vec<ThoughtSummary>,
+    /// Thoughts in `checkpoint_a` but not in `checkpoint_b`.
+    pub removed_thoughts: Vec<ThoughtSummary>,
+    /// Change in the number of branches.
+    pub branches: CountChange,
+    /// Branches that appeared, disappeared or changed state.
+    pub branch_changes: Vec<BranchChange>,
+    /// Change in the graph-of-thoughts graph, if both checkpoints recorded it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<GraphChange>,
+    /// Conclusions of `checkpoint_b` that `checkpoint_a` did not have.
+    pub new_conclusions: Vec<ThoughtSummary>,
+    /// Conclusions of `checkpoint_a` that `checkpoint_b` no longer has.
+    pub dropped_conclusions: Vec<ThoughtSummary>,
+    /// One-line summary of the changes.
+    pub summary: String,
+}
+
+/// What one checkpoint captured.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckpointState {
+    /// The checkpoint ID.
+    pub checkpoint_id: String,
+    /// The checkpoint name.
+    pub name: String,
+    /// When the checkpoint was created.
+    pub created_at: DateTime<Utc>,
+    /// Number of thoughts in the snapshot.
+    pub thought_count: usize,
+    /// Number of branches in the snapshot.
+    pub branch_count: usize,
+    /// Number of graph nodes, for checkpoints that recorded the graph.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph_nodes: Option<usize>,
+    /// Number of graph edges, for checkpoints that recorded the graph.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph_edges: Option<usize>,
+    /// Most confident thoughts nothing builds on yet, best first.
+    pub conclusions: Vec<ThoughtSummary>,
+}
+
+/// A count before and after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CountChange {
+    /// Count at `checkpoint_a`.
+    pub before: usize,
+    /// Count at `checkpoint_b`.
+    pub after: usize,
+    /// `after - before`.
+    pub change: i64,
+}
+
+impl CountChange {
+    fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            change: after as i64 - before as i64,
+        }
+    }
+}
+
+/// Change in graph size between two checkpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GraphChange {
+    /// Change in the number of nodes.
+    pub nodes: CountChange,
+    /// Change in the number of edges.
+    pub edges: CountChange,
+}
+
+/// A thought as listed in a checkpoint diff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ThoughtSummary {
+    /// The thought ID.
+    pub thought_id: String,
+    /// Mode that produced the thought.
+    pub mode: String,
+    /// The thought content.
+    pub content: String,
+    /// Confidence in the thought (0.0-1.0).
+    pub confidence: f64,
+    /// Branch the thought belongs to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_id: Option<String>,
+}
+
+impl From<&Thought> for ThoughtSummary {
+    fn from(thought: &Thought) -> Self {
+        Self {
+            thought_id: thought.id.clone(),
+            mode: thought.mode.clone(),
+            content: thought.content.clone(),
+            confidence: thought.confidence,
+            branch_id: thought.branch_id.clone(),
+        }
+    }
+}
+
+/// A branch whose presence or state differs between two checkpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BranchChange {
+    /// The branch ID.
+    pub branch_id: String,
+    /// The branch name, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// State at `checkpoint_a`, or `None` if the branch did not exist.
+    pub before: Option<String>,
+    /// State at `checkpoint_b`, or `None` if the branch did not exist.
+    pub after: Option<String>,
+}
+
+/// Session state read back from a checkpoint snapshot
+struct SnapshotContents {
+    thoughts: Vec<Thought>,
+    branches: Vec<Branch>,
+    /// Graph node and edge counts, absent from older checkpoints
+    graph: Option<(usize, usize)>,
+}
+
+impl SnapshotContents {
+    fn parse(checkpoint: &Checkpoint, field: &str) -> Result<Self, ToolError> {
+        let unreadable = |e: serde_json::Error| ToolError::Validation {
+            field: field.to_string(),
+            reason: format!("Checkpoint snapshot is unreadable: {}", e),
+        };
+        let list = |key: &str| {
+            checkpoint
+                .snapshot
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!([]))
+        };
+        let graph = checkpoint.snapshot.get("graph").and_then(|g| {
+            let count = |key: &str| g.get(key).and_then(|n| n.as_u64()).map(|n| n as usize);
+            Some((count("nodes")?, count("edges")?))
+        });
+        Ok(Self {
+            thoughts: serde_json::from_value(list("thoughts")).map_err(unreadable)?,
+            branches: serde_json::from_value(list("branches")).map_err(unreadable)?,
+            graph,
+        })
+    }
+
+    /// Most confident thoughts that no other thought builds on
+    fn conclusions(&self) -> Vec<ThoughtSummary> {
+        let parents: HashSet<&str> = self
+            .thoughts
+            .iter()
+            .filter_map(|t| t.parent_id.as_deref())
+            .collect();
+        let mut leaves: Vec<&Thought> = self
+            .thoughts
+            .iter()
+            .filter(|t| !parents.contains(t.id.as_str()))
+            .collect();
+        leaves.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        leaves
+            .into_iter()
+            .take(MAX_CONCLUSIONS)
+            .map(ThoughtSummary::from)
+            .collect()
+    }
+
+    fn state(&self, checkpoint: &Checkpoint) -> CheckpointState {
+        CheckpointState {
+            checkpoint_id: checkpoint.id.clone(),
+            name: checkpoint.name.clone(),
+            created_at: checkpoint.created_at,
+            thought_count: self.thoughts.len(),
+            branch_count: self.branches.len(),
+            graph_nodes: self.graph.map(|(nodes, _)| nodes),
+            graph_edges: self.graph.map(|(_, edges)| edges),
+            conclusions: self.conclusions(),
+        }
+    }
+}
+
+/// Thoughts of `from` whose IDs `to` lacks, in `from`'s order
+fn missing_thoughts(from: &[Thought], to: &[Thought]) -> Vec<ThoughtSummary> {
+    let ids: HashSet<&str> = to.iter().map(|t| t.id.as_str()).collect();
+    from.iter()
+        .filter(|t| !ids.contains(t.id.as_str()))
+        .map(ThoughtSummary::from)
+        .collect()
+}
+
+/// Conclusions of `from` that `to` does not share
+fn missing_conclusions(from: &[ThoughtSummary], to: &[ThoughtSummary]) -> Vec<ThoughtSummary> {
+    from.iter()
+        .filter(|c| !to.iter().any(|t| t.thought_id == c.thought_id))
+        .cloned()
+        .collect()
+}
+
+/// Branches that appear, disappear or change state between two snapshots
+fn branch_changes(from: &[Branch], to: &[Branch]) -> Vec<BranchChange> {
+    let before: HashMap<&str, &Branch> = from.iter().map(|b| (b.id.as_str(), b)).collect();
+    let after: HashMap<&str, &Branch> = to.iter().map(|b| (b.id.as_str(), b)).collect();
+    let mut seen = HashSet::new();
+    from.iter()
+        .chain(to)
+        .map(|b| b.id.as_str())
+        .filter(|id| seen.insert(*id))
+        .filter_map(|id| {
+            let (a, b) = (before.get(id), after.get(id));
+            let state = |branch: Option<&&Branch>| branch.map(|b| b.state.to_string());
+            if a.map(|b| b.state) == b.map(|b| b.state) {
+                return None;
+            }
+            Some(BranchChange {
+                branch_id: id.to_string(),
+                name: b.or(a).and_then(|b| b.name.clone()),
+                before: state(a),
+                after: state(b),
+            })
+        })
+        .collect()
+}
+
 /// Langbase response for backtracking.
 /// Some fields are parsed for JSON completeness but only `thought` and `confidence` are used.
 #[allow(dead_code)] // Fields parsed for JSON completeness
@@ -83,7 +336,7 @@ impl BacktrackingResponse {
 /// Backtracking mode handler for checkpoint-based exploration.
 #[derive(Clone)]
 pub struct BacktrackingMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for backtracking.
     pipe_name: String,
@@ -91,9 +344,14 @@ pub struct BacktrackingMode {
 
 impl BacktrackingMode {
     /// Create a new backtracking mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config
                 .pipes
                 .backtracking
@@ -105,6 +363,7 @@ impl BacktrackingMode {
     /// Process a backtracking request
     pub async fn process(&self, params: BacktrackingParams) -> AppResult<BacktrackingResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Get the checkpoint
         let checkpoint = self
@@ -162,8 +421,14 @@ impl BacktrackingMode {
         let messages = self.build_messages(&checkpoint, params.new_direction.as_deref());
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.pipe_name, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+        let request = PipeRequest::new(&pipe_name, messages)
+            .with_output_schema(
+                self.core
+                    .response_schema(SchemaKind::Backtracking, &pipe_name),
+            )
+            .with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let backtrack_response = BacktrackingResponse::from_completion(&response.completion)?;
@@ -182,6 +447,16 @@ impl BacktrackingMode {
             latency_ms = latency,
             "Backtracking completed"
         );
+        client_log(
+            LogLevel::Notice,
+            "backtracking",
+            serde_json::json!({
+                "message": "Checkpoint restored",
+                "session_id": session.id,
+                "checkpoint_id": checkpoint.id,
+                "thought_id": thought.id,
+            }),
+        );
 
         Ok(BacktrackingResult {
             thought_id: thought.id,
@@ -240,11 +515,18 @@ impl BacktrackingMode {
         // Get current session state
         let thoughts = self.core.storage().get_session_thoughts(session_id).await?;
         let branches = self.core.storage().get_session_branches(session_id).await?;
+        let graph_nodes = self
+            .core
+            .storage()
+            .get_session_graph_nodes(session_id)
+            .await?;
+        let graph_edges = self.core.storage().get_session_edges(session_id).await?;
 
         // Serialize state
         let state = serde_json::json!({
             "thoughts": thoughts,
             "branches": branches,
+            "graph": {"nodes": graph_nodes.len(), "edges": graph_edges.len()},
             "created_at": chrono::Utc::now().to_rfc3339(),
         });
 
@@ -272,6 +554,97 @@ impl BacktrackingMode {
             .get_session_checkpoints(session_id)
             .await?)
     }
+
+    /// Compare two checkpoints of a session
+    pub async fn diff(&self, params: CheckpointDiffParams) -> AppResult<CheckpointDiff> {
+        let a = self
+            .load_checkpoint(&params.checkpoint_a, "checkpoint_a")
+            .await?;
+        let b = self
+            .load_checkpoint(&params.checkpoint_b, "checkpoint_b")
+            .await?;
+        if a.session_id != b.session_id {
+            return Err(ToolError::Validation {
+                field: "checkpoint_b".to_string(),
+                reason: "Checkpoints belong to different sessions".to_string(),
+            }
+            .into());
+        }
+
+        let before = SnapshotContents::parse(&a, "checkpoint_a")?;
+        let after = SnapshotContents::parse(&b, "checkpoint_b")?;
+        let from = before.state(&a);
+        let to = after.state(&b);
+
+        let added_thoughts = missing_thoughts(&after.thoughts, &before.thoughts);
+        let removed_thoughts = missing_thoughts(&before.thoughts, &after.thoughts);
+        let branch_changes = branch_changes(&before.branches, &after.branches);
+        let graph =
+            before
+                .graph
+                .zip(after.graph)
+                .map(|((nodes_a, edges_a), (nodes_b, edges_b))| GraphChange {
+                    nodes: CountChange::new(nodes_a, nodes_b),
+                    edges: CountChange::new(edges_a, edges_b),
+                });
+        let new_conclusions = missing_conclusions(&to.conclusions, &from.conclusions);
+        let dropped_conclusions = missing_conclusions(&from.conclusions, &to.conclusions);
+
+        let mut changes = vec![format!(
+            "{} thoughts added, {} removed",
+            added_thoughts.len(),
+            removed_thoughts.len()
+        )];
+        if !branch_changes.is_empty() {
+            changes.push(format!("{} branches changed", branch_changes.len()));
+        }
+        if let Some(graph) = graph.filter(|g| g.nodes.change != 0 || g.edges.change != 0) {
+            changes.push(format!(
+                "graph {:+} nodes, {:+} edges",
+                graph.nodes.change, graph.edges.change
+            ));
+        }
+        changes.push(format!(
+            "{} new conclusions, {} dropped",
+            new_conclusions.len(),
+            dropped_conclusions.len()
+        ));
+
+        debug!(
+            checkpoint_a = %a.id,
+            checkpoint_b = %b.id,
+            added = added_thoughts.len(),
+            removed = removed_thoughts.len(),
+            "Checkpoints compared"
+        );
+
+        Ok(CheckpointDiff {
+            session_id: a.session_id,
+            thoughts: CountChange::new(from.thought_count, to.thought_count),
+            branches: CountChange::new(from.branch_count, to.branch_count),
+            from,
+            to,
+            added_thoughts,
+            removed_thoughts,
+            branch_changes,
+            graph,
+            new_conclusions,
+            dropped_conclusions,
+            summary: changes.join("; "),
+        })
+    }
+
+    async fn load_checkpoint(&self, id: &str, field: &str) -> AppResult<Checkpoint> {
+        Ok(self
+            .core
+            .storage()
+            .get_checkpoint(id)
+            .await?
+            .ok_or_else(|| ToolError::Validation {
+                field: field.to_string(),
+                reason: format!("Checkpoint not found: {}", id),
+            })?)
+    }
 }
 
 impl BacktrackingParams {
@@ -282,6 +655,7 @@ impl BacktrackingParams {
             new_direction: None,
             session_id: None,
             confidence: default_confidence(),
+            generation: GenerationParams::default(),
         }
     }
 
@@ -296,6 +670,12 @@ impl BacktrackingParams {
         self.session_id = Some(session_id.into());
         self
     }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -308,8 +688,11 @@ mod tests {
 
     fn create_test_config() -> Config {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RetentionConfig, ToolsConfig, TransportConfig,
+            DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -317,10 +700,15 @@ mod tests {
             langbase: LangbaseConfig {
                 api_key: "test-key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -329,6 +717,18 @@ mod tests {
             request: crate::config::RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         }
     }
 
@@ -1247,4 +1647,70 @@ mod tests {
 
         assert_eq!(parsed.confidence, 0.0001);
     }
+
+    // ============================================================================
+    // Checkpoint Diff Tests
+    // ============================================================================
+
+    #[test]
+    fn test_snapshot_conclusions_are_unextended_thoughts() {
+        let root = Thought::new("s1", "Root", "linear").with_confidence(0.9);
+        let weak = Thought::new("s1", "Weak lead", "tree")
+            .with_confidence(0.4)
+            .with_parent(&root.id);
+        let strong = Thought::new("s1", "Strong lead", "tree")
+            .with_confidence(0.8)
+            .with_parent(&root.id);
+        let checkpoint = Checkpoint::new(
+            "s1",
+            "cp",
+            serde_json::json!({"thoughts": [root, weak, strong], "branches": []}),
+        );
+
+        let contents = SnapshotContents::parse(&checkpoint, "checkpoint_a").unwrap();
+        assert!(contents.graph.is_none());
+        let conclusions: Vec<String> = contents
+            .conclusions()
+            .into_iter()
+            .map(|c| c.content)
+            .collect();
+        assert_eq!(conclusions, vec!["Strong lead", "Weak lead"]);
+    }
+
+    #[test]
+    fn test_snapshot_parse_rejects_malformed_thoughts() {
+        let checkpoint = Checkpoint::new("s1", "cp", serde_json::json!({"thoughts": [1]}));
+        let err = SnapshotContents::parse(&checkpoint, "checkpoint_b")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("checkpoint_b"));
+    }
+
+    #[test]
+    fn test_branch_changes() {
+        let kept = Branch::new("s1").with_name("kept");
+        let mut finished = kept.clone();
+        finished.state = crate::storage::BranchState::Completed;
+        let dropped = Branch::new("s1");
+        let added = Branch::new("s1").with_name("new");
+
+        let changes = branch_changes(&[kept.clone(), dropped.clone()], &[finished, added]);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].branch_id, kept.id);
+        assert_eq!(changes[0].before.as_deref(), Some("active"));
+        assert_eq!(changes[0].after.as_deref(), Some("completed"));
+        assert_eq!(changes[1].branch_id, dropped.id);
+        assert!(changes[1].after.is_none());
+        assert_eq!(changes[2].name.as_deref(), Some("new"));
+        assert!(changes[2].before.is_none());
+
+        let unchanged = [kept];
+        assert!(branch_changes(&unchanged, &unchanged).is_empty());
+    }
+
+    #[test]
+    fn test_count_change() {
+        assert_eq!(CountChange::new(5, 3).change, -2);
+        assert_eq!(CountChange::new(3, 5).change, 2);
+    }
 }
@@ -0,0 +1,1044 @@
+//! Calculation mode - recomputing the numbers in reasoning.
+//!
+//! Every numeric claim in a thought, a session's thoughts or given content
+//! is checked by a local expression evaluator rather than trusted:
+//! - explicit equations (`120 * 0.15 = 18`) are found by scanning the text
+//! - the detection pipe extracts the remaining claims, turning prose like
+//!   "a 15% cut of 120 leaves 102" into a formula and its stated result
+//!
+//! Each formula is evaluated locally. A stated result off from the computed
+//! one by more than the tolerance (and more than the rounding its digits
+//! allow) is filed as a `calculation` detection on the thought.
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::CALC_EXTRACTION_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Detection, DetectionType, Invocation, SharedStorage, Thought};
+
+/// Relative difference allowed between a stated and a computed result when
+/// the caller does not say.
+pub const DEFAULT_CALC_TOLERANCE: f64 = 0.01;
+
+/// Largest relative tolerance a caller may allow.
+pub const MAX_CALC_TOLERANCE: f64 = 0.5;
+
+/// Most claims checked in one call.
+pub const MAX_CALC_CLAIMS: usize = 50;
+
+/// Detected issue name of calculation detections.
+pub const CALCULATION_ISSUE: &str = "calculation_error";
+
+fn default_tolerance() -> f64 {
+    DEFAULT_CALC_TOLERANCE
+}
+
+/// Input parameters for calculation verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalcParams {
+    /// Content to check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Thought to check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thought_id: Option<String>,
+    /// Session whose thoughts to check when neither content nor a thought
+    /// is given; detections are filed in it either way
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Relative difference allowed between stated and computed results
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    /// Check only explicit equations, without a pipe call
+    #[serde(default)]
+    pub local_only: bool,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Where a claim was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimSource {
+    /// An equation written out in the text.
+    Explicit,
+    /// A claim in prose, turned into a formula by the pipe.
+    Extracted,
+}
+
+/// Outcome of checking a claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CalcStatus {
+    /// The stated result matches the computed one.
+    Correct,
+    /// The stated result is off from the computed one.
+    Mismatch,
+    /// A formula without a stated result; the computed value is reported.
+    Computed,
+    /// The formula could not be evaluated.
+    Unparsed,
+}
+
+/// One checked claim
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CalcCheck {
+    /// Thought the claim is in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thought_id: Option<String>,
+    /// The claim as written
+    pub text: String,
+    /// The formula evaluated
+    pub expression: String,
+    /// The result the text states
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stated: Option<f64>,
+    /// The result computed locally
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub computed: Option<f64>,
+    /// |stated - computed| / |computed|
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_error: Option<f64>,
+    /// Outcome of the check
+    pub status: CalcStatus,
+    /// Where the claim was found
+    pub source: ClaimSource,
+    /// Why the formula could not be evaluated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Detection filed for a mismatch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detection_id: Option<String>,
+}
+
+/// Result of calculation verification
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CalcResult {
+    /// Session the detections were filed in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Number of thoughts (or content blocks) checked
+    pub sources_checked: usize,
+    /// Every claim checked, in text order
+    pub checks: Vec<CalcCheck>,
+    /// Number of mismatches
+    pub mismatches: usize,
+    /// Detections filed for the mismatches
+    pub detections: Vec<Detection>,
+}
+
+/// Calculation mode handler.
+#[derive(Clone)]
+pub struct CalcMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Detection pipe extracting claims from prose
+    detection_pipe: String,
+}
+
+impl CalcMode {
+    /// Create a new calculation mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            detection_pipe: config
+                .pipes
+                .detection
+                .as_ref()
+                .and_then(|d| d.pipe.clone())
+                .unwrap_or_else(|| "detection-v1".to_string()),
+        }
+    }
+
+    /// Recompute the numeric claims of content, a thought or a session
+    pub async fn verify(&self, params: CalcParams) -> AppResult<CalcResult> {
+        let start = Instant::now();
+
+        if !(0.0..=MAX_CALC_TOLERANCE).contains(&params.tolerance) {
+            return Err(ToolError::Validation {
+                field: "tolerance".to_string(),
+                reason: format!("Must be between 0 and {}", MAX_CALC_TOLERANCE),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let (sources, session_id) = self.resolve_sources(&params).await?;
+        debug!(
+            sources = sources.len(),
+            "Processing calculation check request"
+        );
+
+        let mut checks: Vec<CalcCheck> = Vec::new();
+        for source in &sources {
+            checks.extend(
+                scan_equations(&source.content)
+                    .into_iter()
+                    .map(|claim| check_claim(claim, source.thought_id.clone(), params.tolerance)),
+            );
+        }
+
+        if !params.local_only {
+            let pipe = self.core.select_pipe(&self.detection_pipe);
+            let mut invocation = Invocation::new(
+                "reasoning.verify_calculations",
+                serialize_for_log(&params, "reasoning.verify_calculations input"),
+            )
+            .with_pipe(&pipe);
+            if let Some(ref session_id) = session_id {
+                invocation = invocation.with_session(session_id);
+            }
+
+            let messages = vec![
+                Message::system(CALC_EXTRACTION_PROMPT),
+                Message::user(extraction_prompt(&sources)),
+            ];
+            let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+            let result = match self.core.backend().call_pipe(request).await {
+                Ok(response) => parse_response(&response.completion)
+                    .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+                Err(e) => Err(e.into()),
+            };
+            let (claims, cache_hit, fallback) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    let latency = start.elapsed().as_millis() as i64;
+                    invocation = invocation.failure(e.to_string(), latency);
+                    self.core.storage().log_invocation(&invocation).await?;
+                    return Err(e);
+                }
+            };
+            for claim in claims {
+                if checks
+                    .iter()
+                    .any(|c| same_expression(&c.expression, &claim.expression))
+                {
+                    continue;
+                }
+                let thought_id = claim
+                    .thought_id
+                    .filter(|id| sources.iter().any(|s| s.thought_id.as_ref() == Some(id)))
+                    .or_else(|| (sources.len() == 1).then(|| sources[0].thought_id.clone())?);
+                checks.push(check_claim(
+                    Claim {
+                        text: claim.text,
+                        expression: claim.expression,
+                        stated: claim.stated.filter(|v| v.is_finite()),
+                        source: ClaimSource::Extracted,
+                    },
+                    thought_id,
+                    params.tolerance,
+                ));
+            }
+
+            let latency = start.elapsed().as_millis() as i64;
+            invocation = invocation
+                .with_cache_hit(cache_hit)
+                .with_pipe_fallback(fallback.as_deref())
+                .success(
+                    serialize_for_log(&checks, "reasoning.verify_calculations output"),
+                    latency,
+                );
+            self.core.storage().log_invocation(&invocation).await?;
+        }
+        checks.truncate(MAX_CALC_CLAIMS);
+
+        let mut detections = Vec::new();
+        for check in checks.iter_mut() {
+            if check.status != CalcStatus::Mismatch {
+                continue;
+            }
+            let detection = mismatch_detection(check, session_id.as_deref());
+            self.core.storage().create_detection(&detection).await?;
+            check.detection_id = Some(detection.id.clone());
+            detections.push(detection);
+        }
+
+        let result = CalcResult {
+            session_id,
+            sources_checked: sources.len(),
+            mismatches: detections.len(),
+            checks,
+            detections,
+        };
+
+        info!(
+            checks = result.checks.len(),
+            mismatches = result.mismatches,
+            latency_ms = start.elapsed().as_millis() as i64,
+            "Calculation check completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Text to check, with the thought each block comes from, and the
+    /// session to file detections in
+    async fn resolve_sources(
+        &self,
+        params: &CalcParams,
+    ) -> AppResult<(Vec<Source>, Option<String>)> {
+        let storage = self.core.storage();
+        if let Some(ref content) = params.content {
+            if content.trim().is_empty() {
+                return Err(ToolError::Validation {
+                    field: "content".to_string(),
+                    reason: "Content cannot be empty".to_string(),
+                }
+                .into());
+            }
+            let source = Source {
+                thought_id: params.thought_id.clone(),
+                content: content.clone(),
+            };
+            return Ok((vec![source], params.session_id.clone()));
+        }
+        if let Some(ref thought_id) = params.thought_id {
+            let thought = storage
+                .get_thought(thought_id)
+                .await?
+                .ok_or_else(|| ToolError::Session(format!("Thought not found: {}", thought_id)))?;
+            let session_id = params
+                .session_id
+                .clone()
+                .or(Some(thought.session_id.clone()));
+            return Ok((vec![Source::from(thought)], session_id));
+        }
+        if let Some(ref session_id) = params.session_id {
+            let thoughts = storage.get_session_thoughts(session_id).await?;
+            if thoughts.is_empty() {
+                return Err(ToolError::Validation {
+                    field: "session_id".to_string(),
+                    reason: format!("Session has no thoughts: {}", session_id),
+                }
+                .into());
+            }
+            let sources = thoughts.into_iter().map(Source::from).collect();
+            return Ok((sources, Some(session_id.clone())));
+        }
+        Err(ToolError::Validation {
+            field: "content/thought_id/session_id".to_string(),
+            reason: "One of 'content', 'thought_id' or 'session_id' must be provided".to_string(),
+        }
+        .into())
+    }
+}
+
+/// A block of text to check
+#[derive(Debug)]
+struct Source {
+    thought_id: Option<String>,
+    content: String,
+}
+
+impl From<Thought> for Source {
+    fn from(thought: Thought) -> Self {
+        Self {
+            thought_id: Some(thought.id),
+            content: thought.content,
+        }
+    }
+}
+
+/// A claim before it is checked
+#[derive(Debug, Clone, PartialEq)]
+struct Claim {
+    text: String,
+    expression: String,
+    stated: Option<f64>,
+    source: ClaimSource,
+}
+
+fn extraction_prompt(sources: &[Source]) -> String {
+    let mut prompt = String::new();
+    for source in sources {
+        match source.thought_id {
+            Some(ref id) => prompt.push_str(&format!("[THOUGHT {}]\n", id)),
+            None => prompt.push_str("[CONTENT]\n"),
+        }
+        prompt.push_str(&source.content);
+        prompt.push_str("\n\n");
+    }
+    prompt.trim_end().to_string()
+}
+
+fn parse_response(completion: &str) -> AppResult<Vec<ClaimDraft>> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: CalcResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse calculation claims: {}", e),
+        })?;
+    Ok(parsed
+        .claims
+        .into_iter()
+        .filter(|c| !c.expression.trim().is_empty())
+        .collect())
+}
+
+/// Whether two formulas are the same up to whitespace
+fn same_expression(a: &str, b: &str) -> bool {
+    let strip = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    strip(a) == strip(b)
+}
+
+/// Evaluate a claim's formula and compare it with the stated result
+fn check_claim(claim: Claim, thought_id: Option<String>, tolerance: f64) -> CalcCheck {
+    let mut check = CalcCheck {
+        thought_id,
+        text: claim.text,
+        expression: claim.expression,
+        stated: claim.stated,
+        computed: None,
+        relative_error: None,
+        status: CalcStatus::Unparsed,
+        source: claim.source,
+        error: None,
+        detection_id: None,
+    };
+    let computed = match evaluate_expression(&check.expression) {
+        Ok(value) => value,
+        Err(e) => {
+            check.error = Some(e);
+            return check;
+        }
+    };
+    check.computed = Some(computed);
+    check.status = match claim.stated {
+        None => CalcStatus::Computed,
+        Some(stated) => {
+            let difference = (stated - computed).abs();
+            check.relative_error = Some(difference / computed.abs().max(f64::MIN_POSITIVE));
+            if difference <= (tolerance * computed.abs()).max(rounding_allowance(stated)) {
+                CalcStatus::Correct
+            } else {
+                CalcStatus::Mismatch
+            }
+        }
+    };
+    check
+}
+
+/// Half a unit of the last digit `value` is written to, so "0.7" for 2/3
+/// or "333" for 1000/3 count as correctly rounded
+fn rounding_allowance(value: f64) -> f64 {
+    let written = format!("{}", value);
+    let decimals = written
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    0.5 * 10f64.powi(-(decimals as i32))
+}
+
+/// Severity of a mismatch by its relative error: 2 within 10%, 3 within
+/// 50%, 4 beyond
+fn mismatch_severity(relative_error: f64) -> i32 {
+    if relative_error <= 0.1 {
+        2
+    } else if relative_error <= 0.5 {
+        3
+    } else {
+        4
+    }
+}
+
+fn mismatch_detection(check: &CalcCheck, session_id: Option<&str>) -> Detection {
+    let computed = check.computed.unwrap_or_default();
+    let stated = check.stated.unwrap_or_default();
+    let relative_error = check.relative_error.unwrap_or_default();
+    // Explicit equations are read straight from the text; extracted ones
+    // depend on the pipe's reading of the prose
+    let confidence = match check.source {
+        ClaimSource::Explicit => 1.0,
+        ClaimSource::Extracted => 0.8,
+    };
+    let mut detection = Detection::new(
+        DetectionType::Calculation,
+        CALCULATION_ISSUE,
+        mismatch_severity(relative_error),
+        confidence,
+        format!(
+            "'{}' states {} but {} computes to {}",
+            check.text,
+            format_number(stated),
+            check.expression,
+            format_number(computed)
+        ),
+    )
+    .with_remediation(format!("Use {}", format_number(computed)))
+    .with_metadata(serde_json::json!({
+        "expression": check.expression,
+        "stated": stated,
+        "computed": computed,
+        "relative_error": relative_error,
+        "source": check.source,
+    }));
+    if let Some(session_id) = session_id {
+        detection = detection.with_session(session_id);
+    }
+    if let Some(ref thought_id) = check.thought_id {
+        detection = detection.with_thought(thought_id);
+    }
+    detection
+}
+
+/// A number with at most six significant decimals
+fn format_number(value: f64) -> String {
+    let rounded = format!("{:.6}", value);
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Equations written out in text, such as "120 * 0.15 = 18" or
+/// "2,400 / 12 ≈ 200"; matches that are a bare number or do not evaluate
+/// are not claims
+fn scan_equations(text: &str) -> Vec<Claim> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?P<expr>[0-9(][0-9\s.,()%+\-−*/×÷^]*?[0-9)%])\s*(?:=|≈)\s*(?P<value>[-−]?[0-9][0-9,]*(?:\.[0-9]+)?%?)",
+        )
+        .expect("equation pattern is valid")
+    });
+    let mut claims = Vec::new();
+    for captures in pattern.captures_iter(text) {
+        let (Some(expr), Some(value)) = (captures.name("expr"), captures.name("value")) else {
+            continue;
+        };
+        // Part of a word or a longer number, like "v2" or "1.5"
+        if text[..expr.start()]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '.' || c == '_')
+        {
+            continue;
+        }
+        let expression = expr.as_str().trim();
+        let has_operator = expression.chars().skip(1).any(|c| "+-−*/×÷^%".contains(c));
+        if !has_operator || evaluate_expression(expression).is_err() {
+            continue;
+        }
+        let Ok(stated) = evaluate_expression(value.as_str()) else {
+            continue;
+        };
+        claims.push(Claim {
+            text: captures[0].trim().to_string(),
+            expression: expression.to_string(),
+            stated: Some(stated),
+            source: ClaimSource::Explicit,
+        });
+    }
+    claims
+}
+
+/// Evaluate an arithmetic expression.
+///
+/// Supports `+ - * / ^` (also `×`, `÷`, `**` and `−`), parentheses, unary
+/// signs, postfix `%` (divides by 100), thousands separators (`1,200`),
+/// scientific notation, the constants `pi` and `e`, and the functions
+/// `sqrt`, `ln`, `log` (base 10), `exp`, `abs`, `round`, `floor`, `ceil`,
+/// `min` and `max`.
+pub fn evaluate_expression(expression: &str) -> Result<f64, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("Empty expression".to_string());
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expression()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!(
+            "Unexpected {} at token {}",
+            parser.tokens[parser.pos],
+            parser.pos + 1
+        ));
+    }
+    if !value.is_finite() {
+        return Err("Result is not a finite number".to_string());
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while i < chars.len() {
+                    let c = chars[i];
+                    if c.is_ascii_digit() || c == '.' {
+                        number.push(c);
+                        i += 1;
+                    } else if c == ',' && is_thousands_group(&chars[i + 1..]) {
+                        i += 1;
+                    } else if (c == 'e' || c == 'E') && is_exponent(&chars[i + 1..]) {
+                        number.push('e');
+                        i += 1;
+                        if chars[i] == '+' || chars[i] == '-' {
+                            number.push(chars[i]);
+                            i += 1;
+                        }
+                    } else {
+                        break;
+                    }
+                }
+                let value = number
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}'", number))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() => {
+                let mut name = String::new();
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    name.push(chars[i]);
+                    i += 1;
+                }
+                tokens.push(Token::Ident(name.to_lowercase()));
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::Op('^'));
+                i += 2;
+            }
+            '+' | '-' | '*' | '/' | '^' | '%' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '−' => {
+                tokens.push(Token::Op('-'));
+                i += 1;
+            }
+            '×' | '·' => {
+                tokens.push(Token::Op('*'));
+                i += 1;
+            }
+            '÷' => {
+                tokens.push(Token::Op('/'));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            _ => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Whether a comma is followed by exactly three digits, as in "1,200"
+fn is_thousands_group(rest: &[char]) -> bool {
+    rest.len() >= 3
+        && rest[..3].iter().all(|c| c.is_ascii_digit())
+        && !rest.get(3).is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Whether an `e` continues a number as its exponent, as in "1.5e-3"
+fn is_exponent(rest: &[char]) -> bool {
+    match rest {
+        [d, ..] if d.is_ascii_digit() => true,
+        ['+' | '-', d, ..] => d.is_ascii_digit(),
+        _ => false,
+    }
+}
+
+/// Recursive-descent parser over the tokens:
+///
+/// ```text
+/// expression := term (("+" | "-") term)*
+/// term       := unary (("*" | "/") unary)*
+/// unary      := ("+" | "-") unary | power
+/// power      := postfix ("^" unary)?
+/// postfix    := primary "%"*
+/// primary    := number | constant | function "(" args ")" | "(" expression ")"
+/// ```
+///
+/// Parentheses, function arguments, exponents and chained signs nest at most
+/// [`MAX_NESTING_DEPTH`] deep, so hostile input fails instead of exhausting
+/// the stack.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+/// Deepest nesting [`Parser`] accepts.
+const MAX_NESTING_DEPTH: usize = 64;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse a nested part of the expression with `parse`, one level deeper.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<f64, String>) -> Result<f64, String> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err("Expression nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.eat_op(&['*', '/']) {
+            let rhs = self.unary()?;
+            if op == '/' {
+                if rhs == 0.0 {
+                    return Err("Division by zero".to_string());
+                }
+                value /= rhs;
+            } else {
+                value *= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        match self.eat_op(&['+', '-']) {
+            Some('-') => Ok(-self.nested(Self::unary)?),
+            Some(_) => self.nested(Self::unary),
+            None => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.postfix()?;
+        if self.eat_op(&['^']).is_some() {
+            let exponent = self.nested(Self::unary)?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<f64, String> {
+        let mut value = self.primary()?;
+        while self.eat_op(&['%']).is_some() {
+            value /= 100.0;
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::LParen) => {
+                let value = self.nested(Self::expression)?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err("Missing ')'".to_string()),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return match name.as_str() {
+                        "pi" | "π" => Ok(std::f64::consts::PI),
+                        "e" => Ok(std::f64::consts::E),
+                        _ => Err(format!("Unknown name '{}'", name)),
+                    };
+                }
+                self.pos += 1;
+                let mut args = vec![self.nested(Self::expression)?];
+                loop {
+                    match self.next() {
+                        Some(Token::Comma) => args.push(self.nested(Self::expression)?),
+                        Some(Token::RParen) => break,
+                        _ => return Err(format!("Missing ')' after arguments of {}", name)),
+                    }
+                }
+                call_function(&name, &args)
+            }
+            Some(token) => Err(format!("Unexpected {}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn call_function(name: &str, args: &[f64]) -> Result<f64, String> {
+    let one = |f: fn(f64) -> f64| match args {
+        [x] => Ok(f(*x)),
+        _ => Err(format!("{} takes one argument", name)),
+    };
+    match name {
+        "sqrt" => match args {
+            [x] if *x < 0.0 => Err("Square root of a negative number".to_string()),
+            _ => one(f64::sqrt),
+        },
+        "ln" => one(f64::ln),
+        "log" | "log10" => one(f64::log10),
+        "exp" => one(f64::exp),
+        "abs" => one(f64::abs),
+        "round" => one(f64::round),
+        "floor" => one(f64::floor),
+        "ceil" => one(f64::ceil),
+        "min" => args
+            .iter()
+            .copied()
+            .reduce(f64::min)
+            .ok_or_else(|| "min takes at least one argument".to_string()),
+        "max" => args
+            .iter()
+            .copied()
+            .reduce(f64::max)
+            .ok_or_else(|| "max takes at least one argument".to_string()),
+        _ => Err(format!("Unknown function '{}'", name)),
+    }
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Deserialize)]
+struct CalcResponse {
+    #[serde(default)]
+    claims: Vec<ClaimDraft>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimDraft {
+    #[serde(default)]
+    thought_id: Option<String>,
+    #[serde(default)]
+    text: String,
+    expression: String,
+    #[serde(default)]
+    stated: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn claim(expression: &str, stated: Option<f64>) -> Claim {
+        Claim {
+            text: expression.to_string(),
+            expression: expression.to_string(),
+            stated,
+            source: ClaimSource::Extracted,
+        }
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: CalcParams = serde_json::from_value(json!({"content": "2 + 2 = 5"})).unwrap();
+        assert_eq!(params.tolerance, DEFAULT_CALC_TOLERANCE);
+        assert!(!params.local_only);
+    }
+
+    #[test]
+    fn test_evaluate_precedence_and_signs() {
+        assert_eq!(evaluate_expression("2 + 3 * 4").unwrap(), 14.0);
+        assert_eq!(evaluate_expression("(2 + 3) * 4").unwrap(), 20.0);
+        assert_eq!(evaluate_expression("2 ^ 3 ^ 2").unwrap(), 512.0);
+        assert_eq!(evaluate_expression("-2 ^ 2").unwrap(), -4.0);
+        assert_eq!(evaluate_expression("2 ** -1").unwrap(), 0.5);
+        assert_eq!(evaluate_expression("10 − 4 × 2 ÷ 8").unwrap(), 9.0);
+        assert_eq!(evaluate_expression("7 - -3").unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_evaluate_numbers_and_functions() {
+        assert_eq!(evaluate_expression("1,200 * 3").unwrap(), 3600.0);
+        assert_eq!(evaluate_expression("1.5e3 + 2E-1").unwrap(), 1500.2);
+        assert_eq!(evaluate_expression("200 * 15%").unwrap(), 30.0);
+        assert_eq!(
+            evaluate_expression("sqrt(16) + max(1, 7, 3)").unwrap(),
+            11.0
+        );
+        assert_eq!(evaluate_expression("log(1000)").unwrap(), 3.0);
+        assert!((evaluate_expression("2 * pi").unwrap() - std::f64::consts::TAU).abs() < 1e-12);
+        assert_eq!(
+            evaluate_expression("round(2.6) + floor(-1.5)").unwrap(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_evaluate_errors() {
+        assert!(evaluate_expression("").is_err());
+        assert!(evaluate_expression("1 / 0").is_err());
+        assert!(evaluate_expression("(1 + 2").is_err());
+        assert!(evaluate_expression("1 + ").is_err());
+        assert!(evaluate_expression("2 3").is_err());
+        assert!(evaluate_expression("foo(1)").is_err());
+        assert!(evaluate_expression("sqrt(-1)").is_err());
+        assert!(evaluate_expression("10 ^ 400").is_err());
+        assert!(evaluate_expression("1 $ 2").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_rejects_deep_nesting() {
+        let nested = |depth: usize| format!("{}1 + 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate_expression(&nested(32)).unwrap(), 2.0);
+
+        for expression in [
+            nested(10_000),
+            format!("{}1", "-".repeat(10_000)),
+            format!("2{}", " ^ 1".repeat(10_000)),
+            format!("{}1{}", "abs(".repeat(10_000), ")".repeat(10_000)),
+        ] {
+            let err = evaluate_expression(&expression).unwrap_err();
+            assert!(err.contains("nested too deeply"), "{}", err);
+        }
+
+        // Equations found in submitted text go through the same check
+        let text = format!("{} = 2", nested(10_000));
+        assert!(scan_equations(&text).is_empty());
+    }
+
+    #[test]
+    fn test_scan_equations() {
+        let claims = scan_equations(
+            "In 2024 we sold 120 * 3 = 360 units. Revenue: 1,200 × 15% ≈ 180. \
+             Version v2 + 3 = 5 is not math, and 42 = 42 is no claim.",
+        );
+        let expressions: Vec<&str> = claims.iter().map(|c| c.expression.as_str()).collect();
+        assert_eq!(expressions, ["120 * 3", "1,200 × 15%"]);
+        assert_eq!(claims[0].stated, Some(360.0));
+        assert_eq!(claims[0].text, "120 * 3 = 360");
+        assert_eq!(claims[1].stated, Some(180.0));
+        assert!(claims.iter().all(|c| c.source == ClaimSource::Explicit));
+    }
+
+    #[test]
+    fn test_check_claim() {
+        let correct = check_claim(claim("120 * 0.15", Some(18.0)), None, 0.01);
+        assert_eq!(correct.status, CalcStatus::Correct);
+        assert_eq!(correct.relative_error, Some(0.0));
+
+        let wrong = check_claim(claim("120 * 0.25", Some(18.0)), Some("t1".into()), 0.01);
+        assert_eq!(wrong.status, CalcStatus::Mismatch);
+        assert_eq!(wrong.computed, Some(30.0));
+        assert!((wrong.relative_error.unwrap() - 0.4).abs() < 1e-9);
+
+        // Rounded to the digits written
+        assert_eq!(
+            check_claim(claim("2 / 3", Some(0.7)), None, 0.0).status,
+            CalcStatus::Correct
+        );
+        assert_eq!(
+            check_claim(claim("1000 / 3", Some(333.0)), None, 0.0).status,
+            CalcStatus::Correct
+        );
+        assert_eq!(
+            check_claim(claim("1000 / 3", Some(340.0)), None, 0.01).status,
+            CalcStatus::Mismatch
+        );
+
+        assert_eq!(
+            check_claim(claim("2 + 2", None), None, 0.01).status,
+            CalcStatus::Computed
+        );
+        let unparsed = check_claim(claim("two plus two", Some(4.0)), None, 0.01);
+        assert_eq!(unparsed.status, CalcStatus::Unparsed);
+        assert!(unparsed.error.is_some());
+    }
+
+    #[test]
+    fn test_mismatch_detection() {
+        let check = check_claim(claim("120 * 0.25", Some(18.0)), Some("t1".into()), 0.01);
+        let detection = mismatch_detection(&check, Some("s1"));
+        assert_eq!(detection.detection_type, DetectionType::Calculation);
+        assert_eq!(detection.detected_issue, CALCULATION_ISSUE);
+        assert_eq!(detection.severity, 3);
+        assert_eq!(detection.confidence, 0.8);
+        assert_eq!(detection.thought_id.as_deref(), Some("t1"));
+        assert_eq!(detection.session_id.as_deref(), Some("s1"));
+        assert_eq!(detection.remediation.as_deref(), Some("Use 30"));
+        assert_eq!(mismatch_severity(0.05), 2);
+        assert_eq!(mismatch_severity(2.0), 4);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let claims = parse_response(
+            r#"{"claims": [{"text": "a 15% cut of 120 leaves 102", "expression": "120 * (1 - 15%)", "stated": 102}, {"text": "noise", "expression": " "}]}"#,
+        )
+        .unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].stated, Some(102.0));
+        assert!(same_expression("120*(1-15%)", &claims[0].expression));
+    }
+}
@@ -0,0 +1,1168 @@
+//! Code reasoning mode - explaining diffs, tracing data flow and finding edge cases.
+//!
+//! Three operations share the dedicated code reasoning pipe:
+//! - `explain_diff` summarizes what a unified diff changes and how behavior
+//!   shifts; the files touched and lines added or removed are counted locally
+//! - `trace_flow` follows a variable or value through a snippet from its
+//!   sources to its sinks; the snippet is sent with line numbers and every
+//!   step is checked against the lines it cites
+//! - `edge_cases` enumerates the inputs and states a snippet may mishandle,
+//!   unhandled ones first
+//!
+//! Each analysis is stored as a thought, and its structured findings
+//! (invariants, risks and suggested tests) accumulate per session; findings
+//! already recorded in the session are referenced rather than repeated.
+
+use std::collections::HashSet;
+use std::time::Instant;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{CODE_EDGE_CASES_PROMPT, CODE_EXPLAIN_DIFF_PROMPT, CODE_TRACE_FLOW_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{CodeFinding, CodeFindingKind, Invocation, SharedStorage, Thought};
+
+/// Edge cases enumerated when the caller does not say.
+pub const DEFAULT_MAX_EDGE_CASES: u32 = 8;
+
+/// Most edge cases one call may enumerate.
+pub const MAX_EDGE_CASES: u32 = 20;
+
+/// Most findings of each kind kept from one analysis.
+pub const MAX_FINDINGS_PER_KIND: usize = 10;
+
+fn default_max_edge_cases() -> u32 {
+    DEFAULT_MAX_EDGE_CASES
+}
+
+/// Input parameters for explaining a diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainDiffParams {
+    /// The change, as a unified diff
+    pub diff: String,
+    /// Optional background: what the change is for, surrounding code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for tracing data flow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceFlowParams {
+    /// The code to trace through
+    pub code: String,
+    /// The variable, parameter or value to follow
+    pub target: String,
+    /// Language of the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Optional background on the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for enumerating edge cases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeCasesParams {
+    /// The code to examine
+    pub code: String,
+    /// Language of the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Optional background on the code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Most edge cases to enumerate
+    #[serde(default = "default_max_edge_cases")]
+    pub max_cases: u32,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for listing a session's code findings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeFindingsParams {
+    /// The session whose findings to list
+    pub session_id: String,
+    /// Only findings of this kind
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CodeFindingKind>,
+}
+
+/// Code reasoning operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeOperation {
+    /// Explaining a diff.
+    ExplainDiff,
+    /// Tracing data flow.
+    TraceFlow,
+    /// Enumerating edge cases.
+    EdgeCases,
+}
+
+impl CodeOperation {
+    /// Get the operation name as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CodeOperation::ExplainDiff => "explain_diff",
+            CodeOperation::TraceFlow => "trace_flow",
+            CodeOperation::EdgeCases => "edge_cases",
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self {
+            CodeOperation::ExplainDiff => CODE_EXPLAIN_DIFF_PROMPT,
+            CodeOperation::TraceFlow => CODE_TRACE_FLOW_PROMPT,
+            CodeOperation::EdgeCases => CODE_EDGE_CASES_PROMPT,
+        }
+    }
+}
+
+/// A property the code relies on or preserves
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CodeInvariant {
+    /// Stored finding ID
+    pub finding_id: String,
+    /// The invariant
+    pub statement: String,
+    /// Where it applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// A way the code could fail or regress
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CodeRisk {
+    /// Stored finding ID
+    pub finding_id: String,
+    /// The risk
+    pub description: String,
+    /// Severity (1-5)
+    pub severity: i32,
+    /// Where it applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// A test that would pin the behavior down
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CodeTest {
+    /// Stored finding ID
+    pub finding_id: String,
+    /// Test name
+    pub name: String,
+    /// What the test does and asserts
+    pub description: String,
+    /// Code under test
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// Invariants, risks and suggested tests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CodeFindings {
+    /// Invariants
+    pub invariants: Vec<CodeInvariant>,
+    /// Risks, most severe first
+    pub risks: Vec<CodeRisk>,
+    /// Suggested tests
+    pub suggested_tests: Vec<CodeTest>,
+}
+
+/// Lines a diff adds and removes in one file, counted locally
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DiffFileStat {
+    /// Path of the file
+    pub path: String,
+    /// Lines added
+    pub added: u32,
+    /// Lines removed
+    pub removed: u32,
+}
+
+/// One change a diff makes
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiffChange {
+    /// File changed, when it is one of the diff's files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// What changed
+    pub description: String,
+}
+
+/// Result of explaining a diff
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExplainDiffResult {
+    /// The session ID
+    pub session_id: String,
+    /// Thought holding the explanation
+    pub thought_id: String,
+    /// What the diff does, in a few sentences
+    pub summary: String,
+    /// Files touched, in diff order
+    pub files: Vec<DiffFileStat>,
+    /// The changes, one by one
+    pub changes: Vec<DiffChange>,
+    /// How observable behavior differs after the change
+    pub behavior_changes: Vec<String>,
+    /// Invariants, risks and suggested tests
+    pub findings: CodeFindings,
+    /// Findings not already recorded in the session
+    pub new_findings: usize,
+}
+
+/// One step of a data flow
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FlowStep {
+    /// Line of the snippet (1-based), when the step cites one that exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// The cited line's code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// What happens to the value: source, assign, transform, check, call,
+    /// return or sink
+    pub operation: String,
+    /// What happens at this step
+    pub description: String,
+}
+
+/// Result of tracing data flow
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TraceFlowResult {
+    /// The session ID
+    pub session_id: String,
+    /// Thought holding the trace
+    pub thought_id: String,
+    /// The value traced
+    pub target: String,
+    /// Whether the target appears in the code at all
+    pub target_found: bool,
+    /// Summary of the flow
+    pub summary: String,
+    /// The steps, in flow order
+    pub steps: Vec<FlowStep>,
+    /// Where the value comes from
+    pub sources: Vec<String>,
+    /// Where the value ends up
+    pub sinks: Vec<String>,
+    /// Steps whose cited line does not exist in the snippet
+    pub unverified_steps: usize,
+    /// Invariants, risks and suggested tests
+    pub findings: CodeFindings,
+    /// Findings not already recorded in the session
+    pub new_findings: usize,
+}
+
+/// An input or state the code may mishandle
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EdgeCase {
+    /// The input or state
+    pub input: String,
+    /// Kind of edge case (empty, boundary, overflow, concurrency, ...)
+    pub category: String,
+    /// What the code should do
+    pub expected: String,
+    /// Whether the code already handles it
+    pub handled: bool,
+    /// Severity if mishandled (1-5)
+    pub severity: i32,
+    /// Line of the snippet (1-based) where it matters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+/// Result of enumerating edge cases
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EdgeCasesResult {
+    /// The session ID
+    pub session_id: String,
+    /// Thought holding the analysis
+    pub thought_id: String,
+    /// Summary of the analysis
+    pub summary: String,
+    /// Edge cases, unhandled first, then most severe first
+    pub edge_cases: Vec<EdgeCase>,
+    /// Number of unhandled edge cases
+    pub unhandled: usize,
+    /// Invariants, risks and suggested tests
+    pub findings: CodeFindings,
+    /// Findings not already recorded in the session
+    pub new_findings: usize,
+}
+
+/// Result of listing a session's code findings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CodeFindingsResult {
+    /// The session ID
+    pub session_id: String,
+    /// Number of findings listed
+    pub total: usize,
+    /// The findings, oldest first within each kind
+    pub findings: CodeFindings,
+}
+
+/// Code reasoning mode handler.
+#[derive(Clone)]
+pub struct CodeReasoningMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Dedicated code reasoning pipe
+    code_pipe: String,
+}
+
+impl CodeReasoningMode {
+    /// Create a new code reasoning mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            code_pipe: config
+                .pipes
+                .code
+                .clone()
+                .unwrap_or_else(|| "code-reasoning-v1".to_string()),
+        }
+    }
+
+    /// Explain what a diff changes
+    pub async fn explain_diff(&self, params: ExplainDiffParams) -> AppResult<ExplainDiffResult> {
+        let files = diff_stats(&params.diff);
+        if files.iter().all(|f| f.added == 0 && f.removed == 0) {
+            return Err(ToolError::Validation {
+                field: "diff".to_string(),
+                reason: "Must be a unified diff with at least one added or removed line"
+                    .to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let mut prompt = format!("Diff:\n{}", params.diff.trim_end());
+        if let Some(ref context) = params.context {
+            prompt = format!("Context: {}\n\n{}", context, prompt);
+        }
+        let analysis = self
+            .analyze(
+                CodeOperation::ExplainDiff,
+                &params.session_id,
+                prompt,
+                serialize_for_log(&params, "reasoning.code_explain_diff input"),
+                params.generation,
+            )
+            .await?;
+
+        let changes = analysis
+            .response
+            .changes
+            .iter()
+            .filter(|c| !c.description.trim().is_empty())
+            .map(|c| DiffChange {
+                // Attribute a change only to a file the diff touches
+                file: c.file.as_ref().and_then(|file| matching_file(&files, file)),
+                description: c.description.trim().to_string(),
+            })
+            .collect();
+        Ok(ExplainDiffResult {
+            session_id: analysis.session_id,
+            thought_id: analysis.thought_id,
+            summary: analysis.response.summary.clone(),
+            files,
+            changes,
+            behavior_changes: non_empty(&analysis.response.behavior_changes),
+            findings: analysis.findings,
+            new_findings: analysis.new_findings,
+        })
+    }
+
+    /// Trace a value through code from its sources to its sinks
+    pub async fn trace_flow(&self, params: TraceFlowParams) -> AppResult<TraceFlowResult> {
+        validate_code(&params.code)?;
+        let target = params.target.trim();
+        if target.is_empty() {
+            return Err(ToolError::Validation {
+                field: "target".to_string(),
+                reason: "Target cannot be empty".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let prompt = format!(
+            "{}Trace: {}\n\nCode{}:\n{}",
+            context_line(&params.context),
+            target,
+            language_suffix(&params.language),
+            numbered(&params.code)
+        );
+        let analysis = self
+            .analyze(
+                CodeOperation::TraceFlow,
+                &params.session_id,
+                prompt,
+                serialize_for_log(&params, "reasoning.code_trace_flow input"),
+                params.generation,
+            )
+            .await?;
+
+        let lines: Vec<&str> = params.code.lines().collect();
+        let mut unverified_steps = 0;
+        let steps = analysis
+            .response
+            .steps
+            .iter()
+            .filter(|s| !s.description.trim().is_empty())
+            .map(|step| {
+                let code = step.line.and_then(|line| source_line(&lines, line));
+                if step.line.is_some() && code.is_none() {
+                    unverified_steps += 1;
+                }
+                FlowStep {
+                    line: code.as_ref().and(step.line),
+                    code,
+                    operation: normalize_operation(&step.operation),
+                    description: step.description.trim().to_string(),
+                }
+            })
+            .collect();
+        Ok(TraceFlowResult {
+            session_id: analysis.session_id,
+            thought_id: analysis.thought_id,
+            target: target.to_string(),
+            target_found: params.code.contains(target),
+            summary: analysis.response.summary.clone(),
+            steps,
+            sources: non_empty(&analysis.response.sources),
+            sinks: non_empty(&analysis.response.sinks),
+            unverified_steps,
+            findings: analysis.findings,
+            new_findings: analysis.new_findings,
+        })
+    }
+
+    /// Enumerate the edge cases a snippet may mishandle
+    pub async fn edge_cases(&self, params: EdgeCasesParams) -> AppResult<EdgeCasesResult> {
+        validate_code(&params.code)?;
+        if params.max_cases == 0 || params.max_cases > MAX_EDGE_CASES {
+            return Err(ToolError::Validation {
+                field: "max_cases".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_EDGE_CASES),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let prompt = format!(
+            "{}List at most {} edge cases.\n\nCode{}:\n{}",
+            context_line(&params.context),
+            params.max_cases,
+            language_suffix(&params.language),
+            numbered(&params.code)
+        );
+        let analysis = self
+            .analyze(
+                CodeOperation::EdgeCases,
+                &params.session_id,
+                prompt,
+                serialize_for_log(&params, "reasoning.code_edge_cases input"),
+                params.generation,
+            )
+            .await?;
+
+        let line_count = params.code.lines().count() as u32;
+        let mut edge_cases: Vec<EdgeCase> = analysis
+            .response
+            .edge_cases
+            .iter()
+            .filter(|c| !c.input.trim().is_empty())
+            .map(|c| EdgeCase {
+                input: c.input.trim().to_string(),
+                category: c.category.trim().to_lowercase(),
+                expected: c.expected.trim().to_string(),
+                handled: c.handled,
+                severity: c.severity.clamp(1, 5),
+                line: c.line.filter(|line| (1..=line_count).contains(line)),
+            })
+            .collect();
+        edge_cases.sort_by_key(|c| (c.handled, std::cmp::Reverse(c.severity)));
+        edge_cases.truncate(params.max_cases as usize);
+        Ok(EdgeCasesResult {
+            session_id: analysis.session_id,
+            thought_id: analysis.thought_id,
+            summary: analysis.response.summary.clone(),
+            unhandled: edge_cases.iter().filter(|c| !c.handled).count(),
+            edge_cases,
+            findings: analysis.findings,
+            new_findings: analysis.new_findings,
+        })
+    }
+
+    /// List the findings recorded in a session
+    pub async fn findings(&self, params: CodeFindingsParams) -> AppResult<CodeFindingsResult> {
+        let stored = self
+            .core
+            .storage()
+            .get_session_code_findings(&params.session_id)
+            .await?;
+        let stored: Vec<CodeFinding> = stored
+            .into_iter()
+            .filter(|f| params.kind.map_or(true, |kind| f.kind == kind))
+            .collect();
+        Ok(CodeFindingsResult {
+            session_id: params.session_id,
+            total: stored.len(),
+            findings: group_findings(&stored),
+        })
+    }
+
+    /// Call the pipe for an operation, then store its thought and findings
+    async fn analyze(
+        &self,
+        operation: CodeOperation,
+        session_id: &Option<String>,
+        prompt: String,
+        input: serde_json::Value,
+        generation: GenerationParams,
+    ) -> AppResult<Analysis> {
+        let start = Instant::now();
+        let storage = self.core.storage();
+        let session = storage.get_or_create_session(session_id, "code").await?;
+        debug!(
+            session_id = %session.id,
+            operation = operation.as_str(),
+            "Processing code reasoning request"
+        );
+
+        let operation_name = format!("reasoning.code_{}", operation.as_str());
+        let pipe = self.core.select_pipe(&self.code_pipe);
+        let mut invocation = Invocation::new(&operation_name, input)
+            .with_session(&session.id)
+            .with_pipe(&pipe);
+
+        let messages = vec![Message::system(operation.prompt()), Message::user(prompt)];
+        let request = PipeRequest::new(&pipe, messages).with_generation(generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let latency = start.elapsed().as_millis() as i64;
+        let (response, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&response, &format!("{} output", operation_name)),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        let thought = Thought::new(&session.id, &response.summary, "code")
+            .with_confidence(response.confidence)
+            .with_metadata(serde_json::json!({ "operation": operation }));
+        storage.create_thought(&thought).await?;
+
+        let existing = storage.get_session_code_findings(&session.id).await?;
+        let drafts = finding_drafts(&session.id, &thought.id, operation, &response);
+        let (findings, new_findings) = dedupe_findings(drafts, &existing);
+        for finding in &new_findings {
+            storage.create_code_finding(finding).await?;
+        }
+
+        info!(
+            session_id = %session.id,
+            operation = operation.as_str(),
+            findings = findings.len(),
+            new_findings = new_findings.len(),
+            latency_ms = latency,
+            "Code reasoning completed"
+        );
+
+        Ok(Analysis {
+            session_id: session.id,
+            thought_id: thought.id,
+            findings: group_findings(&findings),
+            new_findings: new_findings.len(),
+            response,
+        })
+    }
+}
+
+/// A pipe analysis with its thought and findings stored
+struct Analysis {
+    session_id: String,
+    thought_id: String,
+    response: CodeResponse,
+    findings: CodeFindings,
+    new_findings: usize,
+}
+
+fn validate_code(code: &str) -> AppResult<()> {
+    if code.trim().is_empty() {
+        return Err(ToolError::Validation {
+            field: "code".to_string(),
+            reason: "Code cannot be empty".to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn context_line(context: &Option<String>) -> String {
+    context
+        .as_ref()
+        .map(|c| format!("Context: {}\n\n", c))
+        .unwrap_or_default()
+}
+
+fn language_suffix(language: &Option<String>) -> String {
+    language
+        .as_ref()
+        .map(|l| format!(" ({})", l))
+        .unwrap_or_default()
+}
+
+/// Code with 1-based line numbers, so the pipe can cite lines
+fn numbered(code: &str) -> String {
+    let lines: Vec<&str> = code.trim_end().lines().collect();
+    let width = lines.len().to_string().len();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:>width$} | {}", i + 1, line, width = width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The trimmed code of a 1-based line, if the line exists and is not blank
+fn source_line(lines: &[&str], line: u32) -> Option<String> {
+    let text = lines.get((line as usize).checked_sub(1)?)?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Files of a unified diff with the lines each adds and removes.
+///
+/// Files come from `diff --git` or `+++` headers; changes before any header
+/// are counted under an unnamed file.
+pub fn diff_stats(diff: &str) -> Vec<DiffFileStat> {
+    let mut files: Vec<DiffFileStat> = Vec::new();
+    let mut in_header = false;
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let path = rest
+                .split_whitespace()
+                .last()
+                .map(strip_diff_prefix)
+                .unwrap_or_default();
+            files.push(DiffFileStat {
+                path,
+                added: 0,
+                removed: 0,
+            });
+            in_header = true;
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            let path = strip_diff_prefix(rest.split('\t').next().unwrap_or(rest).trim());
+            let path = if path == "/dev/null" {
+                // A deleted file keeps the name from its `---` line
+                files.last().map(|f| f.path.clone()).unwrap_or_default()
+            } else {
+                path
+            };
+            match files.last_mut() {
+                Some(file) if in_header => file.path = path,
+                _ => files.push(DiffFileStat {
+                    path,
+                    added: 0,
+                    removed: 0,
+                }),
+            }
+            in_header = false;
+        } else if line.starts_with("--- ") && (in_header || files.is_empty()) {
+            // Old file name; the `+++` line that follows names the file
+            let path = strip_diff_prefix(line[4..].split('\t').next().unwrap_or("").trim());
+            if files.is_empty() {
+                files.push(DiffFileStat {
+                    path,
+                    added: 0,
+                    removed: 0,
+                });
+                in_header = true;
+            } else if let Some(file) = files.last_mut() {
+                if file.path.is_empty() {
+                    file.path = path;
+                }
+            }
+        } else if line.starts_with("@@") {
+            in_header = false;
+        } else if !in_header && (line.starts_with('+') || line.starts_with('-')) {
+            if files.is_empty() {
+                files.push(DiffFileStat {
+                    path: String::new(),
+                    added: 0,
+                    removed: 0,
+                });
+            }
+            if let Some(file) = files.last_mut() {
+                if line.starts_with('+') {
+                    file.added += 1;
+                } else {
+                    file.removed += 1;
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Path without git's `a/` or `b/` prefix
+fn strip_diff_prefix(path: &str) -> String {
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// The diff file a pipe-named file refers to: the same path, or the only
+/// file ending in it
+fn matching_file(files: &[DiffFileStat], name: &str) -> Option<String> {
+    let name = strip_diff_prefix(name.trim());
+    if let Some(file) = files.iter().find(|f| f.path == name) {
+        return Some(file.path.clone());
+    }
+    let mut suffixed = files
+        .iter()
+        .filter(|f| !name.is_empty() && f.path.ends_with(&format!("/{}", name)));
+    match (suffixed.next(), suffixed.next()) {
+        (Some(file), None) => Some(file.path.clone()),
+        _ => None,
+    }
+}
+
+fn normalize_operation(operation: &str) -> String {
+    match operation.trim().to_lowercase().as_str() {
+        op @ ("source" | "assign" | "transform" | "check" | "call" | "return" | "sink") => {
+            op.to_string()
+        }
+        _ => "transform".to_string(),
+    }
+}
+
+fn non_empty(items: &[String]) -> Vec<String> {
+    items
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lowercase, whitespace-collapsed text for spotting repeated findings
+fn finding_key(kind: CodeFindingKind, content: &str) -> (CodeFindingKind, String) {
+    let normalized = content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    (kind, normalized)
+}
+
+/// Findings of a pipe response, capped per kind, not yet deduplicated
+fn finding_drafts(
+    session_id: &str,
+    thought_id: &str,
+    operation: CodeOperation,
+    response: &CodeResponse,
+) -> Vec<CodeFinding> {
+    let new = |kind, content: &str, location: &Option<String>| {
+        let mut finding = CodeFinding::new(session_id, operation.as_str(), kind, content.trim())
+            .with_thought(thought_id);
+        if let Some(location) = location.as_ref().filter(|l| !l.trim().is_empty()) {
+            finding = finding.with_location(location.trim());
+        }
+        finding
+    };
+    let invariants = response
+        .invariants
+        .iter()
+        .filter(|i| !i.statement.trim().is_empty())
+        .take(MAX_FINDINGS_PER_KIND)
+        .map(|i| new(CodeFindingKind::Invariant, &i.statement, &i.location));
+    let risks = response
+        .risks
+        .iter()
+        .filter(|r| !r.description.trim().is_empty())
+        .take(MAX_FINDINGS_PER_KIND)
+        .map(|r| new(CodeFindingKind::Risk, &r.description, &r.location).with_severity(r.severity));
+    let tests = response
+        .suggested_tests
+        .iter()
+        .filter(|t| !t.description.trim().is_empty())
+        .take(MAX_FINDINGS_PER_KIND)
+        .map(|t| {
+            new(CodeFindingKind::Test, &t.description, &t.location)
+                .with_metadata(serde_json::json!({ "name": t.name.trim() }))
+        });
+    invariants.chain(risks).chain(tests).collect()
+}
+
+/// Split drafts into the findings of this analysis (reusing the stored
+/// finding where the session already has it) and the ones to store
+fn dedupe_findings(
+    drafts: Vec<CodeFinding>,
+    existing: &[CodeFinding],
+) -> (Vec<CodeFinding>, Vec<CodeFinding>) {
+    let mut seen = HashSet::new();
+    let mut findings = Vec::new();
+    let mut new_findings = Vec::new();
+    for draft in drafts {
+        let key = finding_key(draft.kind, &draft.content);
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        match existing
+            .iter()
+            .find(|f| finding_key(f.kind, &f.content) == key)
+        {
+            Some(stored) => findings.push(stored.clone()),
+            None => {
+                new_findings.push(draft.clone());
+                findings.push(draft);
+            }
+        }
+    }
+    (findings, new_findings)
+}
+
+/// Group stored findings by kind, risks most severe first
+fn group_findings(findings: &[CodeFinding]) -> CodeFindings {
+    let mut grouped = CodeFindings::default();
+    for finding in findings {
+        match finding.kind {
+            CodeFindingKind::Invariant => grouped.invariants.push(CodeInvariant {
+                finding_id: finding.id.clone(),
+                statement: finding.content.clone(),
+                location: finding.location.clone(),
+            }),
+            CodeFindingKind::Risk => grouped.risks.push(CodeRisk {
+                finding_id: finding.id.clone(),
+                description: finding.content.clone(),
+                severity: finding.severity.unwrap_or(3),
+                location: finding.location.clone(),
+            }),
+            CodeFindingKind::Test => grouped.suggested_tests.push(CodeTest {
+                finding_id: finding.id.clone(),
+                name: finding
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("name"))
+                    .and_then(|n| n.as_str())
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or("unnamed")
+                    .to_string(),
+                description: finding.content.clone(),
+                location: finding.location.clone(),
+            }),
+        }
+    }
+    grouped.risks.sort_by_key(|r| std::cmp::Reverse(r.severity));
+    grouped
+}
+
+fn parse_response(completion: &str) -> AppResult<CodeResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let mut parsed: CodeResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse code analysis: {}", e),
+        })?;
+    if parsed.summary.trim().is_empty() {
+        return Err(ToolError::Reasoning {
+            message: "Code analysis has no summary".to_string(),
+        }
+        .into());
+    }
+    parsed.summary = parsed.summary.trim().to_string();
+    parsed.confidence = parsed.confidence.clamp(0.0, 1.0);
+    Ok(parsed)
+}
+
+// Internal response types for parsing; one shape serves every operation
+
+fn default_confidence() -> f64 {
+    0.7
+}
+
+fn default_severity() -> i32 {
+    3
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CodeResponse {
+    #[serde(default)]
+    summary: String,
+    #[serde(default = "default_confidence")]
+    confidence: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<ChangeDraft>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    behavior_changes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    steps: Vec<StepDraft>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sinks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    edge_cases: Vec<EdgeCaseDraft>,
+    #[serde(default)]
+    invariants: Vec<InvariantDraft>,
+    #[serde(default)]
+    risks: Vec<RiskDraft>,
+    #[serde(default)]
+    suggested_tests: Vec<TestDraft>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChangeDraft {
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StepDraft {
+    #[serde(default)]
+    line: Option<u32>,
+    #[serde(default)]
+    operation: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EdgeCaseDraft {
+    #[serde(default)]
+    input: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    expected: String,
+    #[serde(default)]
+    handled: bool,
+    #[serde(default = "default_severity")]
+    severity: i32,
+    #[serde(default)]
+    line: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct InvariantDraft {
+    #[serde(default)]
+    statement: String,
+    #[serde(default)]
+    location: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RiskDraft {
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_severity")]
+    severity: i32,
+    #[serde(default)]
+    location: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TestDraft {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    location: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DIFF: &str = "\
+diff --git a/src/cache.rs b/src/cache.rs
+index 1111111..2222222 100644
+--- a/src/cache.rs
++++ b/src/cache.rs
+@@ -10,4 +10,5 @@ impl Cache {
+-        self.entries.insert(key, value);
++        if self.entries.len() < self.capacity {
++            self.entries.insert(key, value);
++        }
+     }
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+--- a/old.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-one
+-two
+";
+
+    fn response(value: serde_json::Value) -> CodeResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: EdgeCasesParams = serde_json::from_value(json!({"code": "fn f() {}"})).unwrap();
+        assert_eq!(params.max_cases, DEFAULT_MAX_EDGE_CASES);
+        let params: CodeFindingsParams =
+            serde_json::from_value(json!({"session_id": "s", "kind": "test"})).unwrap();
+        assert_eq!(params.kind, Some(CodeFindingKind::Test));
+    }
+
+    #[test]
+    fn test_diff_stats() {
+        assert_eq!(
+            diff_stats(DIFF),
+            vec![
+                DiffFileStat {
+                    path: "src/cache.rs".to_string(),
+                    added: 3,
+                    removed: 1,
+                },
+                DiffFileStat {
+                    path: "old.txt".to_string(),
+                    added: 0,
+                    removed: 2,
+                },
+            ]
+        );
+        // Plain `---`/`+++` headers and headerless hunks
+        let stats = diff_stats("--- a/x.py\n+++ b/x.py\n@@ -1 +1 @@\n-a\n+b\n");
+        assert_eq!(stats[0].path, "x.py");
+        assert_eq!((stats[0].added, stats[0].removed), (1, 1));
+        let stats = diff_stats("-a\n+b\n+c\n");
+        assert_eq!(stats[0].path, "");
+        assert_eq!(stats[0].added, 2);
+        assert!(diff_stats("just some text").is_empty());
+    }
+
+    #[test]
+    fn test_matching_file() {
+        let files = diff_stats(DIFF);
+        assert_eq!(
+            matching_file(&files, "b/src/cache.rs").as_deref(),
+            Some("src/cache.rs")
+        );
+        assert_eq!(
+            matching_file(&files, "cache.rs").as_deref(),
+            Some("src/cache.rs")
+        );
+        assert_eq!(matching_file(&files, "other.rs"), None);
+        assert_eq!(matching_file(&files, ""), None);
+    }
+
+    #[test]
+    fn test_numbered_and_source_line() {
+        let code = "let x = 1;\n\nreturn x;\n";
+        assert_eq!(numbered(code), "1 | let x = 1;\n2 | \n3 | return x;");
+        let lines: Vec<&str> = code.lines().collect();
+        assert_eq!(source_line(&lines, 3).as_deref(), Some("return x;"));
+        assert_eq!(source_line(&lines, 2), None);
+        assert_eq!(source_line(&lines, 0), None);
+        assert_eq!(source_line(&lines, 9), None);
+    }
+
+    #[test]
+    fn test_normalize_operation() {
+        assert_eq!(normalize_operation(" Sink "), "sink");
+        assert_eq!(normalize_operation("mutate"), "transform");
+    }
+
+    #[test]
+    fn test_findings_dedupe_and_group() {
+        let parsed = response(json!({
+            "summary": "s",
+            "invariants": [
+                {"statement": "Entries never exceed capacity", "location": "insert"},
+                {"statement": "entries  never exceed CAPACITY"},
+                {"statement": " "}
+            ],
+            "risks": [
+                {"description": "Silent drop of new entries", "severity": 2},
+                {"description": "Stale reads", "severity": 9}
+            ],
+            "suggested_tests": [
+                {"name": "test_full_cache", "description": "Insert past capacity"}
+            ]
+        }));
+        let existing =
+            vec![
+                CodeFinding::new("s1", "trace_flow", CodeFindingKind::Risk, "stale reads")
+                    .with_severity(4),
+            ];
+        let drafts = finding_drafts("s1", "t1", CodeOperation::ExplainDiff, &parsed);
+        assert_eq!(drafts.len(), 5);
+        let (findings, new_findings) = dedupe_findings(drafts, &existing);
+        assert_eq!(findings.len(), 4);
+        assert_eq!(new_findings.len(), 3);
+
+        let grouped = group_findings(&findings);
+        assert_eq!(grouped.invariants.len(), 1);
+        assert_eq!(grouped.invariants[0].location.as_deref(), Some("insert"));
+        // The stored risk is referenced, and risks are ordered by severity
+        assert_eq!(grouped.risks[0].finding_id, existing[0].id);
+        assert_eq!(grouped.risks[0].severity, 4);
+        assert_eq!(grouped.risks[1].severity, 2);
+        assert_eq!(grouped.suggested_tests[0].name, "test_full_cache");
+        assert!(new_findings
+            .iter()
+            .all(|f| f.thought_id.as_deref() == Some("t1")));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let parsed =
+            parse_response(r#"{"summary": " Adds a capacity check ", "confidence": 3}"#).unwrap();
+        assert_eq!(parsed.summary, "Adds a capacity check");
+        assert_eq!(parsed.confidence, 1.0);
+        assert!(parsed.invariants.is_empty());
+        assert!(parse_response(r#"{"summary": ""}"#).is_err());
+        assert!(parse_response("not json").is_err());
+    }
+}
@@ -1,17 +1,24 @@
 //! Core infrastructure shared by all reasoning modes.
 //!
 //! This module provides the [`ModeCore`] struct that centralizes common
-//! dependencies (storage and Langbase client) used across all mode implementations.
+//! dependencies (storage and LLM backend) used across all mode implementations.
 
-use crate::langbase::LangbaseClient;
-use crate::storage::SqliteStorage;
+use crate::langbase::OutputSchema;
+use crate::providers::{ReasoningBackend, SharedBackend};
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{SharedStorage, Storage};
 
 /// Core infrastructure shared by all reasoning modes.
 ///
-/// Contains the storage backend and Langbase client needed for
+/// Contains the storage backend and LLM backend needed for
 /// persisting data and calling LLM pipes. This struct is composed
 /// into each mode to avoid duplicating these common fields.
 ///
+/// Storage is held as a [`SharedStorage`], so any [`Storage`]
+/// implementation can back the modes, not only `SqliteStorage`. Pipe calls
+/// go through a [`SharedBackend`], which may be the Langbase client or any
+/// other [`ReasoningBackend`].
+///
 /// # Example
 ///
 /// ```ignore
@@ -21,16 +28,16 @@ use crate::storage::SqliteStorage;
 /// }
 ///
 /// impl MyMode {
-///     pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+///     pub fn new(storage: impl Into<SharedStorage>, backend: impl Into<SharedBackend>, config: &Config) -> Self {
 ///         Self {
-///             core: ModeCore::new(storage, langbase),
+///             core: ModeCore::new(storage, backend),
 ///             pipe_name: config.pipes.my_mode.clone(),
 ///         }
 ///     }
 ///
 ///     pub async fn process(&self) -> AppResult<()> {
 ///         let session = self.core.storage().get_or_create_session("id").await?;
-///         let response = self.core.langbase().call_pipe(request).await?;
+///         let response = self.core.backend().call_pipe(request).await?;
 ///         Ok(())
 ///     }
 /// }
@@ -38,34 +45,68 @@ use crate::storage::SqliteStorage;
 #[derive(Clone)]
 pub struct ModeCore {
     /// Storage backend for persisting data.
-    storage: SqliteStorage,
-    /// Langbase client for LLM-powered operations.
-    langbase: LangbaseClient,
+    storage: SharedStorage,
+    /// LLM backend for pipe calls.
+    backend: SharedBackend,
+    /// Response schema versions pipes are validated against.
+    schemas: SchemaRegistry,
 }
 
 impl ModeCore {
-    /// Create a new mode core with the given storage and langbase client.
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient) -> Self {
-        Self { storage, langbase }
+    /// Create a new mode core with the given storage and LLM backend.
+    pub fn new(storage: impl Into<SharedStorage>, backend: impl Into<SharedBackend>) -> Self {
+        Self {
+            storage: storage.into(),
+            backend: backend.into(),
+            schemas: SchemaRegistry::default(),
+        }
+    }
+
+    /// Validate pipe responses against the given schema versions instead of
+    /// the latest ones.
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
     }
 
     /// Get a reference to the storage backend.
     #[inline]
-    pub fn storage(&self) -> &SqliteStorage {
-        &self.storage
+    pub fn storage(&self) -> &dyn Storage {
+        self.storage.as_ref()
     }
 
-    /// Get a reference to the Langbase client.
+    /// Get a reference to the LLM backend.
     #[inline]
-    pub fn langbase(&self) -> &LangbaseClient {
-        &self.langbase
+    pub fn backend(&self) -> &dyn ReasoningBackend {
+        self.backend.as_ref()
+    }
+
+    /// Pipe name to call for the configured `pipe`.
+    ///
+    /// When traffic for `pipe` is split across versions this picks one, so
+    /// the request and its invocation log name the version that served it.
+    pub fn select_pipe(&self, pipe: &str) -> String {
+        self.backend.select_pipe(pipe)
+    }
+
+    /// Schema `kind` responses from `pipe` must match.
+    ///
+    /// `pipe` is the name actually called, so a pipe version rolled out
+    /// with an updated prompt can be validated against its own schema.
+    pub fn response_schema(&self, kind: SchemaKind, pipe: &str) -> OutputSchema {
+        self.schemas.schema(kind, pipe)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{DatabaseConfig, LangbaseConfig, RequestConfig};
+    use crate::config::{
+        DatabaseConfig, LangbaseConfig, MaintenanceConfig, NetworkConfig, RequestConfig,
+        DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
+    };
+    use crate::langbase::LangbaseClient;
+    use crate::storage::SqliteStorage;
     use std::path::PathBuf;
 
     // Helper function to create a test storage instance
@@ -73,6 +114,10 @@ mod tests {
         let config = DatabaseConfig {
             path: PathBuf::from(":memory:"),
             max_connections: 5,
+            encryption_key: None,
+            maintenance: MaintenanceConfig::default(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
         };
         SqliteStorage::new(&config)
             .await
@@ -84,6 +129,7 @@ mod tests {
         let config = LangbaseConfig {
             api_key: "test_api_key".to_string(),
             base_url: "https://api.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let request_config = RequestConfig::default();
         LangbaseClient::new(&config, request_config).expect("Failed to create test client")
@@ -121,14 +167,31 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_mode_core_langbase_access() {
+    async fn test_mode_core_accepts_shared_storage() {
+        let storage: SharedStorage = create_test_storage().await.into();
+        let langbase = create_test_langbase();
+
+        let core = ModeCore::new(storage.clone(), langbase);
+        let session = core
+            .storage()
+            .get_or_create_session(&None, "linear")
+            .await
+            .unwrap();
+
+        // The session is visible through the shared handle
+        let fetched = storage.get_session(&session.id).await.unwrap();
+        assert!(fetched.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mode_core_backend_access() {
         let storage = create_test_storage().await;
         let langbase = create_test_langbase();
 
         let core = ModeCore::new(storage, langbase);
 
-        // Verify we can access the langbase reference
-        let _langbase_ref = core.langbase();
+        // Verify we can access the backend reference
+        let _backend_ref = core.backend();
         // Test passes if we reach here without panic
     }
 
@@ -148,18 +211,18 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_mode_core_langbase_returns_correct_reference() {
+    async fn test_mode_core_backend_returns_correct_reference() {
         let storage = create_test_storage().await;
         let langbase = create_test_langbase();
 
         let core = ModeCore::new(storage, langbase);
 
-        // Access langbase multiple times to ensure consistency
-        let langbase1 = core.langbase();
-        let langbase2 = core.langbase();
+        // Access backend multiple times to ensure consistency
+        let backend1 = core.backend();
+        let backend2 = core.backend();
 
-        // Both references should point to the same client
-        assert!(std::ptr::eq(langbase1, langbase2));
+        // Both references should point to the same backend
+        assert!(std::ptr::eq(backend1, backend2));
     }
 
     #[tokio::test]
@@ -190,16 +253,16 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_mode_core_clone_preserves_langbase() {
+    async fn test_mode_core_clone_preserves_backend() {
         let storage = create_test_storage().await;
         let langbase = create_test_langbase();
 
         let core1 = ModeCore::new(storage, langbase);
         let core2 = core1.clone();
 
-        // Verify both can access langbase
-        let _langbase1 = core1.langbase();
-        let _langbase2 = core2.langbase();
+        // Verify both can access the backend
+        let _backend1 = core1.backend();
+        let _backend2 = core2.backend();
         // Test passes if we reach here without panic
     }
 
@@ -219,16 +282,16 @@ mod tests {
         let _s3 = core3.storage();
         let _s4 = core4.storage();
 
-        let _l1 = core1.langbase();
-        let _l2 = core2.langbase();
-        let _l3 = core3.langbase();
-        let _l4 = core4.langbase();
+        let _l1 = core1.backend();
+        let _l2 = core2.backend();
+        let _l3 = core3.backend();
+        let _l4 = core4.backend();
         // Test passes if we reach here without panic
     }
 
     #[tokio::test]
     async fn test_mode_core_inline_methods() {
-        // Verify that storage() and langbase() are marked as inline
+        // Verify that storage() and backend() are marked as inline
         // This is a compile-time check that the inline attribute is present
         let storage = create_test_storage().await;
         let langbase = create_test_langbase();
@@ -238,7 +301,7 @@ mod tests {
         // Multiple rapid accesses should be optimized by inlining
         for _ in 0..100 {
             let _s = core.storage();
-            let _l = core.langbase();
+            let _l = core.backend();
         }
         // Test passes if we reach here without panic
     }
@@ -269,12 +332,12 @@ mod tests {
                 }
             }
 
-            fn storage(&self) -> &SqliteStorage {
+            fn storage(&self) -> &dyn Storage {
                 self.core.storage()
             }
 
-            fn langbase(&self) -> &LangbaseClient {
-                self.core.langbase()
+            fn backend(&self) -> &dyn ReasoningBackend {
+                self.core.backend()
             }
         }
 
@@ -285,7 +348,7 @@ mod tests {
 
         // Verify composition works
         let _storage = mode.storage();
-        let _langbase = mode.langbase();
+        let _backend = mode.backend();
         assert_eq!(mode.pipe_name, "test-pipe");
     }
 
@@ -315,12 +378,14 @@ mod tests {
         let config1 = LangbaseConfig {
             api_key: "key1".to_string(),
             base_url: "https://api1.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let langbase1 = LangbaseClient::new(&config1, RequestConfig::default()).unwrap();
 
         let config2 = LangbaseConfig {
             api_key: "key2".to_string(),
             base_url: "https://api2.langbase.com".to_string(),
+            network: NetworkConfig::default(),
         };
         let langbase2 = LangbaseClient::new(&config2, RequestConfig::default()).unwrap();
 
@@ -329,8 +394,8 @@ mod tests {
         let core2 = ModeCore::new(storage.clone(), langbase2);
 
         // Both should work
-        let _l1 = core1.langbase();
-        let _l2 = core2.langbase();
+        let _l1 = core1.backend();
+        let _l2 = core2.backend();
         // Test passes if we reach here without panic
     }
 
@@ -350,9 +415,9 @@ mod tests {
         assert!(std::ptr::eq(storage_ref1, storage_ref2));
         assert!(std::ptr::eq(storage_ref2, storage_ref3));
 
-        let langbase_ref1 = core.langbase();
-        let langbase_ref2 = core.langbase();
-        let langbase_ref3 = core.langbase();
+        let langbase_ref1 = core.backend();
+        let langbase_ref2 = core.backend();
+        let langbase_ref3 = core.backend();
 
         // All should point to the same location
         assert!(std::ptr::eq(langbase_ref1, langbase_ref2));
@@ -371,8 +436,8 @@ mod tests {
         let storage1 = core1.storage();
         let storage2 = core2.storage();
 
-        let langbase1 = core1.langbase();
-        let langbase2 = core2.langbase();
+        let langbase1 = core1.backend();
+        let langbase2 = core2.backend();
 
         // Core instances should be different
         assert!(!std::ptr::eq(&core1, &core2));
@@ -388,14 +453,15 @@ mod tests {
     #[test]
     fn test_mode_core_zero_cost_abstraction() {
         // ModeCore should be a zero-cost abstraction
-        // Size should be just two fields
+        // Size should be just its fields
         use std::mem::size_of;
 
-        let storage_size = size_of::<SqliteStorage>();
-        let langbase_size = size_of::<LangbaseClient>();
+        let storage_size = size_of::<SharedStorage>();
+        let backend_size = size_of::<SharedBackend>();
+        let schemas_size = size_of::<SchemaRegistry>();
         let core_size = size_of::<ModeCore>();
 
         // Core should be exactly the sum of its parts (no overhead)
-        assert_eq!(core_size, storage_size + langbase_size);
+        assert_eq!(core_size, storage_size + backend_size + schemas_size);
     }
 }
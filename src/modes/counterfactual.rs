@@ -5,17 +5,23 @@
 //! - Causal attribution scoring
 //! - Comparison of actual vs counterfactual outcomes
 //! - Pearl's Ladder of Causation integration
+//! - Search for the minimal change of antecedents that flips an outcome
 
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
-use tracing::info;
+use tracing::{debug, info};
 
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{COUNTERFACTUAL_PROPOSE_PROMPT, COUNTERFACTUAL_TEST_PROMPT};
+use crate::providers::SharedBackend;
 use crate::storage::{
-    Branch, CounterfactualAnalysis, InterventionType, Invocation, SqliteStorage, Storage, Thought,
+    Branch, CounterfactualAnalysis, InterventionType, Invocation, SharedStorage, Thought,
 };
 
 /// Input parameters for counterfactual analysis
@@ -36,6 +42,9 @@ pub struct CounterfactualParams {
     /// Optional timeline ID to associate with
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeline_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Intervention type for counterfactual analysis
@@ -65,7 +74,7 @@ impl From<CounterfactualInterventionType> for InterventionType {
 }
 
 /// Result of counterfactual analysis
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CounterfactualResult {
     /// Analysis ID
     pub analysis_id: String,
@@ -88,7 +97,7 @@ pub struct CounterfactualResult {
 }
 
 /// Comparison between actual and counterfactual outcomes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CounterfactualComparison {
     /// Description of the actual outcome
     pub actual_outcome: String,
@@ -104,6 +113,118 @@ pub struct CounterfactualComparison {
     pub unchanged_factors: Vec<String>,
 }
 
+/// Input parameters for the minimal-change search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinimalChangeParams {
+    /// The branch whose outcome to flip
+    pub branch_id: String,
+    /// Outcome the search should reach instead (default: any materially
+    /// different outcome)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_outcome: Option<String>,
+    /// Proposal rounds of single-variable interventions (1-4, default 2)
+    #[serde(default = "default_rounds")]
+    pub max_rounds: usize,
+    /// Interventions proposed per round (1-6, default 4)
+    #[serde(default = "default_candidates_per_round")]
+    pub candidates_per_round: usize,
+    /// Largest change set tried (1-4, default 3)
+    #[serde(default = "default_max_set_size")]
+    pub max_set_size: usize,
+    /// Most change sets tested (1-40, default 16)
+    #[serde(default = "default_max_tests")]
+    pub max_tests: usize,
+    /// Optional timeline ID to associate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeline_id: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+fn default_rounds() -> usize {
+    2
+}
+
+fn default_candidates_per_round() -> usize {
+    4
+}
+
+fn default_max_set_size() -> usize {
+    3
+}
+
+fn default_max_tests() -> usize {
+    16
+}
+
+/// A change of one antecedent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SingleIntervention {
+    /// The antecedent changed
+    pub antecedent: String,
+    /// What it is changed to
+    pub change: String,
+    /// Why the change could flip the outcome
+    #[serde(default)]
+    pub rationale: String,
+}
+
+/// Result of testing one set of interventions applied together
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InterventionTest {
+    /// Indices into `candidates` of the interventions applied
+    pub interventions: Vec<usize>,
+    /// Whether the outcome flipped
+    pub flips: bool,
+    /// Likelihood that the changes flip the outcome (0.0-1.0)
+    pub flip_probability: f64,
+    /// Outcome with the changes applied
+    pub resulting_outcome: String,
+    /// Causal path from the changes to the outcome
+    pub reasoning: String,
+}
+
+/// Result of the minimal-change search
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MinimalChangeResult {
+    /// The session of the analyzed branch
+    pub session_id: String,
+    /// The analyzed branch
+    pub branch_id: String,
+    /// The outcome the search tried to flip
+    pub outcome: String,
+    /// The outcome the search tried to reach, if one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_outcome: Option<String>,
+    /// Whether a change set flipping the outcome was found
+    pub found: bool,
+    /// The smallest change set found that flips the outcome
+    pub change_set: Vec<SingleIntervention>,
+    /// Whether every smaller subset of the change set was tested and
+    /// failed to flip the outcome
+    pub minimal: bool,
+    /// Outcome with the change set applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resulting_outcome: Option<String>,
+    /// Causal path from the change set to the resulting outcome
+    pub reasoning: String,
+    /// Every intervention proposed, in order
+    pub candidates: Vec<SingleIntervention>,
+    /// Every change set tested, in order
+    pub tests: Vec<InterventionTest>,
+    /// Proposal rounds run
+    pub rounds: usize,
+    /// Analysis ID, when a change set was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analysis_id: Option<String>,
+    /// Branch holding the counterfactual, when a change set was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterfactual_branch_id: Option<String>,
+    /// Flip probability of the change set, or of the best set tested
+    pub confidence: f64,
+}
+
 /// Counterfactual mode handler for "what if" reasoning.
 #[derive(Clone)]
 pub struct CounterfactualMode {
@@ -119,7 +240,11 @@ pub struct CounterfactualMode {
 
 impl CounterfactualMode {
     /// Create a new counterfactual mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         // Extract pipe names from config, with defaults
         let decision_pipe = config
             .pipes
@@ -129,7 +254,7 @@ impl CounterfactualMode {
             .unwrap_or_else(|| "decision-framework-v1".to_string());
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend),
             reflection_pipe: config.pipes.reflection.clone(),
             decision_pipe,
         }
@@ -138,6 +263,7 @@ impl CounterfactualMode {
     /// Perform counterfactual analysis
     pub async fn analyze(&self, params: CounterfactualParams) -> AppResult<CounterfactualResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate input
         if params.question.trim().is_empty() {
@@ -249,8 +375,10 @@ impl CounterfactualMode {
             Message::user(counterfactual_prompt),
         ];
 
-        let request = PipeRequest::new(&self.reflection_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let reflection_pipe = self.core.select_pipe(&self.reflection_pipe);
+        let request =
+            PipeRequest::new(&reflection_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let json_str = extract_json_from_completion(&response.completion)
@@ -318,7 +446,9 @@ impl CounterfactualMode {
             serialize_for_log(&params, "counterfactual_params"),
         )
         .with_session(&original_branch.session_id)
-        .with_pipe(&self.reflection_pipe)
+        .with_pipe(&reflection_pipe)
+        .with_cache_hit(response.cache_hit)
+        .with_pipe_fallback(response.fallback.as_deref())
         .success(serialize_for_log(&analysis, "counterfactual_analysis"), latency);
         self.core.storage().log_invocation(&invocation).await?;
 
@@ -351,6 +481,456 @@ impl CounterfactualMode {
             insights: analysis.insights,
         })
     }
+
+    /// Search for the smallest set of changed antecedents that flips the
+    /// branch's outcome.
+    ///
+    /// Each round the pipe proposes single-variable interventions, which are
+    /// tested one at a time. If none flips the outcome, combinations of the
+    /// most promising ones are tested, smallest first, until one flips it or
+    /// the test budget runs out.
+    pub async fn minimal_change(
+        &self,
+        params: MinimalChangeParams,
+    ) -> AppResult<MinimalChangeResult> {
+        let start = Instant::now();
+        params.generation.validate()?;
+        let max_rounds = params.max_rounds.clamp(1, 4);
+        let per_round = params.candidates_per_round.clamp(1, 6);
+        let max_set_size = params.max_set_size.clamp(1, 4);
+        let max_tests = params.max_tests.clamp(1, 40);
+
+        let original_branch = self
+            .core
+            .storage()
+            .get_branch(&params.branch_id)
+            .await?
+            .ok_or_else(|| ToolError::Validation {
+                field: "branch_id".to_string(),
+                reason: format!("Branch not found: {}", params.branch_id),
+            })?;
+        let thoughts = self
+            .core
+            .storage()
+            .get_branch_thoughts(&params.branch_id)
+            .await?;
+        if thoughts.is_empty() {
+            return Err(ToolError::Validation {
+                field: "branch_id".to_string(),
+                reason: "Branch has no thoughts to analyze".to_string(),
+            }
+            .into());
+        }
+        let session_id = original_branch.session_id.clone();
+        let chain = thoughts
+            .iter()
+            .map(|t| t.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n---\n");
+        let target = match &params.target_outcome {
+            Some(target) => format!("TARGET OUTCOME: {}", target),
+            None => "TARGET OUTCOME: any materially different outcome".to_string(),
+        };
+
+        let mut outcome = thoughts
+            .last()
+            .map(|t| t.content.clone())
+            .unwrap_or_default();
+        let mut candidates: Vec<SingleIntervention> = Vec::new();
+        let mut tests: Vec<InterventionTest> = Vec::new();
+        let mut rounds = 0;
+
+        // Propose and test single-variable interventions
+        while rounds < max_rounds && tests.len() < max_tests {
+            rounds += 1;
+            let proposal: ProposalResponse = self
+                .call_json(
+                    &session_id,
+                    "propose",
+                    COUNTERFACTUAL_PROPOSE_PROMPT,
+                    format!(
+                        "REASONING CHAIN:\n{}\n\n{}\n\nALREADY TESTED:\n{}\n\n\
+                         Propose up to {} new single-variable interventions.",
+                        chain,
+                        target,
+                        describe_tests(&candidates, &tests),
+                        per_round
+                    ),
+                    params.generation,
+                )
+                .await?;
+            if rounds == 1 && !proposal.outcome.trim().is_empty() {
+                outcome = proposal.outcome;
+            }
+
+            let new = dedupe_interventions(&candidates, proposal.interventions, per_round);
+            if new.is_empty() {
+                break;
+            }
+            for intervention in new {
+                if tests.len() >= max_tests {
+                    break;
+                }
+                candidates.push(intervention);
+                let set = vec![candidates.len() - 1];
+                let test = self
+                    .test_interventions(&session_id, &chain, &target, &candidates, set, &params)
+                    .await?;
+                tests.push(test);
+            }
+            if tests.iter().any(|t| t.flips) {
+                break;
+            }
+        }
+
+        // No single change flips the outcome: combine the most promising
+        if !tests.iter().any(|t| t.flips) {
+            let pool = promising_candidates(&tests, COMBINATION_POOL);
+            'sizes: for size in 2..=max_set_size.min(pool.len()) {
+                for set in ranked_combinations(&pool, &tests, size) {
+                    if tests.len() >= max_tests {
+                        break 'sizes;
+                    }
+                    let test = self
+                        .test_interventions(&session_id, &chain, &target, &candidates, set, &params)
+                        .await?;
+                    tests.push(test);
+                }
+                if tests.iter().any(|t| t.flips) {
+                    break;
+                }
+            }
+        }
+
+        // The smallest flipping set, most likely first
+        let best = tests.iter().filter(|t| t.flips).min_by(|a, b| {
+            a.interventions.len().cmp(&b.interventions.len()).then(
+                b.flip_probability
+                    .partial_cmp(&a.flip_probability)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+        let confidence = match best {
+            Some(best) => best.flip_probability,
+            None => tests.iter().map(|t| t.flip_probability).fold(0.0, f64::max),
+        };
+
+        let mut result = MinimalChangeResult {
+            session_id: session_id.clone(),
+            branch_id: params.branch_id.clone(),
+            outcome,
+            target_outcome: params.target_outcome.clone(),
+            found: best.is_some(),
+            change_set: best
+                .map(|b| {
+                    b.interventions
+                        .iter()
+                        .map(|&i| candidates[i].clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            minimal: best.is_some_and(|b| is_minimal(&b.interventions, &tests)),
+            resulting_outcome: best.map(|b| b.resulting_outcome.clone()),
+            reasoning: best.map(|b| b.reasoning.clone()).unwrap_or_default(),
+            candidates: Vec::new(),
+            tests: Vec::new(),
+            rounds,
+            analysis_id: None,
+            counterfactual_branch_id: None,
+            confidence,
+        };
+
+        if let Some(best) = best {
+            // Store the counterfactual like a single analysis
+            let changes = result
+                .change_set
+                .iter()
+                .map(|i| format!("{} -> {}", i.antecedent, i.change))
+                .collect::<Vec<_>>();
+            let cf_branch = Branch::new(&session_id)
+                .with_parent(&original_branch.id)
+                .with_name(format!("Minimal change ({})", changes.len()))
+                .with_confidence(best.flip_probability);
+            self.core.storage().create_branch(&cf_branch).await?;
+
+            let cf_thought = Thought::new(&session_id, &best.resulting_outcome, "counterfactual")
+                .with_branch(&cf_branch.id)
+                .with_confidence(best.flip_probability)
+                .with_metadata(serde_json::json!({
+                    "minimal_change": changes,
+                    "reasoning": best.reasoning
+                }));
+            self.core.storage().create_thought(&cf_thought).await?;
+
+            let mut cf_analysis = CounterfactualAnalysis::new(
+                &session_id,
+                &params.branch_id,
+                format!("What is the smallest change that flips: {}", result.outcome),
+                InterventionType::Change,
+                changes.join("; "),
+                &cf_branch.id,
+            )
+            .with_causal_attribution(best.flip_probability)
+            .with_confidence(best.flip_probability)
+            .with_comparison(serde_json::json!({
+                "actual_outcome": result.outcome,
+                "counterfactual_outcome": best.resulting_outcome,
+                "outcome_delta": 0.0,
+                "differences": [],
+                "changed_factors": result.change_set.iter().map(|i| &i.antecedent).collect::<Vec<_>>(),
+                "unchanged_factors": []
+            }))
+            .with_metadata(serde_json::json!({
+                "search": "minimal_change",
+                "minimal": result.minimal,
+                "tests_run": tests.len()
+            }));
+            if let Some(ref timeline_id) = params.timeline_id {
+                cf_analysis = cf_analysis.with_timeline(timeline_id);
+            }
+            self.core
+                .storage()
+                .create_counterfactual(&cf_analysis)
+                .await?;
+
+            result.analysis_id = Some(cf_analysis.id);
+            result.counterfactual_branch_id = Some(cf_branch.id);
+        }
+
+        info!(
+            branch_id = %params.branch_id,
+            found = result.found,
+            set_size = result.change_set.len(),
+            tests = tests.len(),
+            latency_ms = start.elapsed().as_millis() as i64,
+            "Minimal-change search complete"
+        );
+
+        result.candidates = candidates;
+        result.tests = tests;
+        Ok(result)
+    }
+
+    /// Ask the pipe whether the interventions at `set` flip the outcome
+    async fn test_interventions(
+        &self,
+        session_id: &str,
+        chain: &str,
+        target: &str,
+        candidates: &[SingleIntervention],
+        set: Vec<usize>,
+        params: &MinimalChangeParams,
+    ) -> AppResult<InterventionTest> {
+        let changes = set
+            .iter()
+            .map(|&i| format!("- {}: {}", candidates[i].antecedent, candidates[i].change))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let response: TestResponse = self
+            .call_json(
+                session_id,
+                "test",
+                COUNTERFACTUAL_TEST_PROMPT,
+                format!(
+                    "REASONING CHAIN:\n{}\n\n{}\n\nCHANGES APPLIED TOGETHER:\n{}",
+                    chain, target, changes
+                ),
+                params.generation,
+            )
+            .await?;
+        debug!(set = ?set, flips = response.flips, "Tested interventions");
+        Ok(InterventionTest {
+            interventions: set,
+            flips: response.flips,
+            flip_probability: response.flip_probability.clamp(0.0, 1.0),
+            resulting_outcome: response.resulting_outcome,
+            reasoning: response.reasoning,
+        })
+    }
+
+    /// Call the reflection pipe for one step of the search and parse its JSON
+    async fn call_json<T: DeserializeOwned + Serialize>(
+        &self,
+        session_id: &str,
+        step: &str,
+        system_prompt: &str,
+        prompt: String,
+        generation: GenerationParams,
+    ) -> AppResult<T> {
+        let start = Instant::now();
+        let pipe = self.core.select_pipe(&self.reflection_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning_counterfactual_minimal_change",
+            serde_json::json!({ "step": step, "prompt": &prompt }),
+        )
+        .with_session(session_id)
+        .with_pipe(&pipe);
+
+        let messages = vec![Message::system(system_prompt), Message::user(prompt)];
+        let request = PipeRequest::new(&pipe, messages).with_generation(generation);
+        let result: AppResult<_> = match self.core.backend().call_pipe(request).await {
+            Ok(response) => extract_json_from_completion(&response.completion)
+                .map_err(|e| ToolError::Reasoning { message: e }.into())
+                .and_then(|json| {
+                    serde_json::from_str::<T>(json).map_err(|e| {
+                        ToolError::Reasoning {
+                            message: format!("Failed to parse {} response: {}", step, e),
+                        }
+                        .into()
+                    })
+                })
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let latency = start.elapsed().as_millis() as i64;
+        match result {
+            Ok((parsed, cache_hit, fallback)) => {
+                invocation = invocation
+                    .with_cache_hit(cache_hit)
+                    .with_pipe_fallback(fallback.as_deref())
+                    .success(serialize_for_log(&parsed, "minimal_change output"), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                Ok(parsed)
+            }
+            Err(e) => {
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Most single interventions combined into larger change sets
+const COMBINATION_POOL: usize = 5;
+
+/// The tested interventions and their results, for the next proposal round
+fn describe_tests(candidates: &[SingleIntervention], tests: &[InterventionTest]) -> String {
+    if tests.is_empty() {
+        return "(none)".to_string();
+    }
+    tests
+        .iter()
+        .map(|test| {
+            let changes = test
+                .interventions
+                .iter()
+                .map(|&i| format!("{} -> {}", candidates[i].antecedent, candidates[i].change))
+                .collect::<Vec<_>>()
+                .join(" + ");
+            format!(
+                "- {}: {} (flip probability {:.2})",
+                changes,
+                if test.flips { "flips" } else { "does not flip" },
+                test.flip_probability
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Proposed interventions not already among `existing` or each other, at
+/// most `limit`
+fn dedupe_interventions(
+    existing: &[SingleIntervention],
+    proposed: Vec<SingleIntervention>,
+    limit: usize,
+) -> Vec<SingleIntervention> {
+    let key = |i: &SingleIntervention| {
+        format!(
+            "{}\u{0}{}",
+            i.antecedent.trim().to_lowercase(),
+            i.change.trim().to_lowercase()
+        )
+    };
+    let mut seen: HashSet<String> = existing.iter().map(key).collect();
+    proposed
+        .into_iter()
+        .filter(|i| !i.antecedent.trim().is_empty() && !i.change.trim().is_empty())
+        .filter(|i| seen.insert(key(i)))
+        .take(limit)
+        .collect()
+}
+
+/// Indices of the single interventions most likely to flip the outcome,
+/// at most `limit`
+fn promising_candidates(tests: &[InterventionTest], limit: usize) -> Vec<usize> {
+    let mut singles: Vec<&InterventionTest> = tests
+        .iter()
+        .filter(|t| t.interventions.len() == 1)
+        .collect();
+    singles.sort_by(|a, b| {
+        b.flip_probability
+            .partial_cmp(&a.flip_probability)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    singles
+        .into_iter()
+        .take(limit)
+        .map(|t| t.interventions[0])
+        .collect()
+}
+
+/// Every `size`-combination of `pool`, ordered by the summed flip
+/// probability of its single interventions, highest first
+fn ranked_combinations(pool: &[usize], tests: &[InterventionTest], size: usize) -> Vec<Vec<usize>> {
+    let single: HashMap<usize, f64> = tests
+        .iter()
+        .filter(|t| t.interventions.len() == 1)
+        .map(|t| (t.interventions[0], t.flip_probability))
+        .collect();
+    let mut combinations = Vec::new();
+    combine(pool, size, &mut Vec::new(), &mut combinations);
+    let score = |set: &Vec<usize>| set.iter().filter_map(|i| single.get(i)).sum::<f64>();
+    combinations.sort_by(|a, b| {
+        score(b)
+            .partial_cmp(&score(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    combinations
+        .into_iter()
+        .map(|mut set| {
+            set.sort_unstable();
+            set
+        })
+        .collect()
+}
+
+fn combine(pool: &[usize], size: usize, current: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+    if current.len() == size {
+        out.push(current.clone());
+        return;
+    }
+    for (i, &item) in pool.iter().enumerate() {
+        current.push(item);
+        combine(&pool[i + 1..], size, current, out);
+        current.pop();
+    }
+}
+
+/// Whether every non-empty proper subset of `set` was tested and did not
+/// flip the outcome
+fn is_minimal(set: &[usize], tests: &[InterventionTest]) -> bool {
+    let tested: HashMap<Vec<usize>, bool> = tests
+        .iter()
+        .map(|t| {
+            let mut key = t.interventions.clone();
+            key.sort_unstable();
+            (key, t.flips)
+        })
+        .collect();
+    let mut sorted = set.to_vec();
+    sorted.sort_unstable();
+    // Subsets as bit masks over `sorted`, without the empty and full sets
+    (1..(1u32 << sorted.len()) - 1).all(|mask| {
+        let subset: Vec<usize> = sorted
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1 << bit) != 0)
+            .map(|(_, &i)| i)
+            .collect();
+        tested.get(&subset) == Some(&false)
+    })
 }
 
 // Internal response type for parsing
@@ -369,6 +949,25 @@ struct CounterfactualResponseData {
     insights: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct ProposalResponse {
+    #[serde(default)]
+    outcome: String,
+    #[serde(default)]
+    interventions: Vec<SingleIntervention>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TestResponse {
+    flips: bool,
+    #[serde(default)]
+    flip_probability: f64,
+    #[serde(default)]
+    resulting_outcome: String,
+    #[serde(default)]
+    reasoning: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -423,6 +1022,7 @@ mod tests {
             intervention: "Replace X with Y".to_string(),
             target_thought_id: None,
             timeline_id: Some("tl".to_string()),
+            generation: GenerationParams::default(),
         };
         let json = serde_json::to_value(&params).unwrap();
         assert_eq!(json["question"], "Test");
@@ -650,6 +1250,7 @@ mod tests {
             intervention: "Inject new hypothesis".to_string(),
             target_thought_id: Some("thought-123".to_string()),
             timeline_id: Some("timeline-456".to_string()),
+            generation: GenerationParams::default(),
         };
         let json = serde_json::to_string(&original).unwrap();
         let deserialized: CounterfactualParams = serde_json::from_str(&json).unwrap();
@@ -781,6 +1382,7 @@ mod tests {
             intervention: "B".repeat(5000),
             target_thought_id: None,
             timeline_id: None,
+            generation: GenerationParams::default(),
         };
         let json = serde_json::to_value(&params).unwrap();
         assert!(json["question"].as_str().unwrap().len() > 10000);
@@ -799,6 +1401,7 @@ mod tests {
             intervention: "I".to_string(),
             target_thought_id: Some("T".to_string()),
             timeline_id: Some("TL".to_string()),
+            generation: GenerationParams::default(),
         };
         let cloned = params.clone();
         assert_eq!(params.question, cloned.question);
@@ -831,4 +1434,100 @@ mod tests {
         assert_eq!(result.causal_attribution, cloned.causal_attribution);
         assert_eq!(result.insights, cloned.insights);
     }
+
+    // ============================================================================
+    // Minimal-Change Search Tests
+    // ============================================================================
+
+    fn intervention(antecedent: &str, change: &str) -> SingleIntervention {
+        SingleIntervention {
+            antecedent: antecedent.to_string(),
+            change: change.to_string(),
+            rationale: String::new(),
+        }
+    }
+
+    fn tested(interventions: Vec<usize>, flips: bool, flip_probability: f64) -> InterventionTest {
+        InterventionTest {
+            interventions,
+            flips,
+            flip_probability,
+            resulting_outcome: String::new(),
+            reasoning: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_minimal_change_params_defaults() {
+        let params: MinimalChangeParams =
+            serde_json::from_value(json!({"branch_id": "b-1"})).unwrap();
+        assert_eq!(params.max_rounds, 2);
+        assert_eq!(params.candidates_per_round, 4);
+        assert_eq!(params.max_set_size, 3);
+        assert_eq!(params.max_tests, 16);
+        assert!(params.target_outcome.is_none());
+    }
+
+    #[test]
+    fn test_dedupe_interventions() {
+        let existing = vec![intervention("Budget", "Doubled")];
+        let proposed = vec![
+            intervention(" budget ", "DOUBLED"),
+            intervention("Deadline", "One month later"),
+            intervention("Deadline", "one month later"),
+            intervention("", "Anything"),
+            intervention("Scope", "Halved"),
+        ];
+        let new = dedupe_interventions(&existing, proposed, 1);
+        assert_eq!(new, vec![intervention("Deadline", "One month later")]);
+    }
+
+    #[test]
+    fn test_ranked_combinations() {
+        let tests = vec![
+            tested(vec![0], false, 0.2),
+            tested(vec![1], false, 0.6),
+            tested(vec![2], false, 0.4),
+        ];
+        let pool = promising_candidates(&tests, 5);
+        assert_eq!(pool, vec![1, 2, 0]);
+        let pairs = ranked_combinations(&pool, &tests, 2);
+        assert_eq!(pairs, vec![vec![1, 2], vec![0, 1], vec![0, 2]]);
+        assert_eq!(ranked_combinations(&pool, &tests, 3), vec![vec![0, 1, 2]]);
+        assert!(ranked_combinations(&pool, &tests, 4).is_empty());
+    }
+
+    #[test]
+    fn test_is_minimal() {
+        let mut tests = vec![
+            tested(vec![0], false, 0.2),
+            tested(vec![1], false, 0.6),
+            tested(vec![2], false, 0.4),
+            tested(vec![1, 0], true, 0.8),
+        ];
+        // A single change is minimal, and so is a pair whose parts both fail
+        assert!(is_minimal(&[1], &tests));
+        assert!(is_minimal(&[0, 1], &tests));
+        // A triple with an untested or flipping pair is not
+        assert!(!is_minimal(&[0, 1, 2], &tests));
+        tests.push(tested(vec![0, 2], false, 0.5));
+        tests.push(tested(vec![1, 2], false, 0.5));
+        assert!(!is_minimal(&[0, 1, 2], &tests));
+        tests[3].flips = false;
+        assert!(is_minimal(&[0, 1, 2], &tests));
+    }
+
+    #[test]
+    fn test_describe_tests() {
+        let candidates = vec![
+            intervention("Budget", "Doubled"),
+            intervention("Scope", "Halved"),
+        ];
+        assert_eq!(describe_tests(&candidates, &[]), "(none)");
+        let described = describe_tests(&candidates, &[tested(vec![0, 1], true, 0.75)]);
+        assert_eq!(
+            described,
+            "- Budget -> Doubled + Scope -> Halved: flips (flip probability 0.75)"
+        );
+    }
 }
@@ -0,0 +1,674 @@
+//! Debate reasoning mode - adversarial pro/con argumentation.
+//!
+//! This module pits two pipe-driven personas against each other:
+//! - A PRO side argues for a proposition, a CON side against it
+//! - Each argument after the first rebuts the opponent's latest one
+//! - A judge scores every argument and declares a verdict
+//!
+//! The transcript is stored as a chain of branches, one per argument, under
+//! a branch holding the proposition. Each rebuttal `contradicts` the argument
+//! it answers, and the verdict branch `supports` the winning side's arguments.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{emit_artifact, extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{DEBATE_ARGUMENT_PROMPT, DEBATE_JUDGE_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{Branch, CrossRef, CrossRefType, Invocation, SharedStorage, Thought};
+
+/// Rounds debated when the caller does not say.
+pub const DEFAULT_DEBATE_ROUNDS: u32 = 2;
+
+/// Most rounds one debate may run; each round costs two pipe calls.
+pub const MAX_DEBATE_ROUNDS: u32 = 5;
+
+fn default_rounds() -> u32 {
+    DEFAULT_DEBATE_ROUNDS
+}
+
+/// Input parameters for a debate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebateParams {
+    /// The proposition argued for and against
+    pub proposition: String,
+    /// Rounds to debate; each round is one PRO and one CON argument
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    /// Optional background both sides and the judge are given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// A side of the debate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebateSide {
+    /// Argues for the proposition
+    Pro,
+    /// Argues against the proposition
+    Con,
+}
+
+impl DebateSide {
+    /// The opposing side
+    pub fn opponent(self) -> Self {
+        match self {
+            DebateSide::Pro => DebateSide::Con,
+            DebateSide::Con => DebateSide::Pro,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            DebateSide::Pro => "PRO",
+            DebateSide::Con => "CON",
+        }
+    }
+}
+
+/// Outcome of a debate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebateWinner {
+    /// The PRO side argued better
+    Pro,
+    /// The CON side argued better
+    Con,
+    /// Neither side was clearly stronger
+    Draw,
+}
+
+impl DebateWinner {
+    /// The winning side, unless the debate was a draw
+    pub fn side(self) -> Option<DebateSide> {
+        match self {
+            DebateWinner::Pro => Some(DebateSide::Pro),
+            DebateWinner::Con => Some(DebateSide::Con),
+            DebateWinner::Draw => None,
+        }
+    }
+}
+
+/// One argument of the debate transcript
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DebateArgument {
+    /// Round the argument was made in, from 1
+    pub round: u32,
+    /// Side that made the argument
+    pub side: DebateSide,
+    /// Branch holding the argument
+    pub branch_id: String,
+    /// Thought holding the argument
+    pub thought_id: String,
+    /// The argument
+    pub content: String,
+    /// Points the argument rests on
+    pub key_points: Vec<String>,
+    /// The arguing side's own confidence in the argument
+    pub confidence: f64,
+    /// The judge's score for the argument, if it scored it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Branch of the opponent's argument this one rebuts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rebuts_branch_id: Option<String>,
+}
+
+/// Result of a debate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DebateResult {
+    /// The session ID
+    pub session_id: String,
+    /// The proposition debated
+    pub proposition: String,
+    /// Branch holding the proposition, parent of the transcript
+    pub debate_branch_id: String,
+    /// Every argument, in the order made
+    pub transcript: Vec<DebateArgument>,
+    /// Side the judge found stronger
+    pub winner: DebateWinner,
+    /// The judge's reasoning
+    pub verdict: String,
+    /// Overall strength of the PRO side (0.0-1.0)
+    pub pro_score: f64,
+    /// Overall strength of the CON side (0.0-1.0)
+    pub con_score: f64,
+    /// Confidence in the verdict (0.0-1.0)
+    pub confidence: f64,
+    /// Branch holding the verdict
+    pub verdict_branch_id: String,
+}
+
+/// Debate mode handler for adversarial reasoning.
+#[derive(Clone)]
+pub struct DebateMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Reflection pipe arguing each side
+    reflection_pipe: String,
+    /// Decision pipe judging the debate
+    decision_pipe: String,
+}
+
+impl DebateMode {
+    /// Create a new debate mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|c| c.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+
+        Self {
+            core: ModeCore::new(storage, backend),
+            reflection_pipe: config.pipes.reflection.clone(),
+            decision_pipe,
+        }
+    }
+
+    /// Run a debate and judge it
+    pub async fn debate(&self, params: DebateParams) -> AppResult<DebateResult> {
+        let start = Instant::now();
+
+        if params.proposition.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "proposition".to_string(),
+                reason: "Proposition cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.rounds == 0 || params.rounds > MAX_DEBATE_ROUNDS {
+            return Err(ToolError::Validation {
+                field: "rounds".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_DEBATE_ROUNDS),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "debate")
+            .await?;
+        debug!(session_id = %session.id, rounds = params.rounds, "Processing debate");
+
+        let argument_pipe = self.core.select_pipe(&self.reflection_pipe);
+        let judge_pipe = self.core.select_pipe(&self.decision_pipe);
+
+        let mut invocation = Invocation::new(
+            "reasoning.debate",
+            serialize_for_log(&params, "reasoning.debate input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&judge_pipe);
+
+        let debate_branch = Branch::new(&session.id).with_name(format!(
+            "Debate: {}",
+            params.proposition.chars().take(30).collect::<String>()
+        ));
+
+        let outcome = match self
+            .argue_and_judge(
+                &params,
+                &session.id,
+                &debate_branch,
+                &argument_pipe,
+                &judge_pipe,
+            )
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let DebateOutcome {
+            mut transcript,
+            mut branches,
+            mut thoughts,
+            judgement,
+            cache_hit,
+            fallback,
+        } = outcome;
+
+        for argument in &mut transcript {
+            argument.score = judgement
+                .argument_scores
+                .iter()
+                .find(|s| s.side == argument.side && s.round == argument.round)
+                .map(|s| s.score.clamp(0.0, 1.0));
+        }
+
+        // The verdict hangs off the proposition and backs the winning side
+        let confidence = judgement.confidence.clamp(0.0, 1.0);
+        let verdict_branch = Branch::new(&session.id)
+            .with_parent(&debate_branch.id)
+            .with_name("Verdict")
+            .with_confidence(confidence);
+        thoughts.push(
+            Thought::new(&session.id, &judgement.verdict, "debate")
+                .with_branch(&verdict_branch.id)
+                .with_confidence(confidence)
+                .with_metadata(serde_json::json!({
+                    "winner": judgement.winner,
+                    "pro_score": judgement.pro_score,
+                    "con_score": judgement.con_score
+                })),
+        );
+
+        let mut cross_refs: Vec<CrossRef> = transcript
+            .iter()
+            .filter_map(|argument| {
+                let rebutted = argument.rebuts_branch_id.as_ref()?;
+                Some(
+                    CrossRef::new(&argument.branch_id, rebutted, CrossRefType::Contradicts)
+                        .with_reason(format!(
+                            "{} rebuttal in round {}",
+                            argument.side.label(),
+                            argument.round
+                        ))
+                        .with_strength(argument.confidence),
+                )
+            })
+            .collect();
+        if let Some(side) = judgement.winner.side() {
+            cross_refs.extend(
+                transcript
+                    .iter()
+                    .filter(|a| a.side == side)
+                    .map(|argument| {
+                        CrossRef::new(
+                            &verdict_branch.id,
+                            &argument.branch_id,
+                            CrossRefType::Supports,
+                        )
+                        .with_reason(format!("Verdict backs the {} side", side.label()))
+                        .with_strength(argument.score.unwrap_or(confidence))
+                    }),
+            );
+        }
+
+        // Store the whole transcript only once the judge has ruled, so a
+        // failed pipe call never leaves half a debate behind
+        branches.insert(0, debate_branch.clone());
+        branches.push(verdict_branch.clone());
+        for branch in &branches {
+            self.core.storage().create_branch(branch).await?;
+        }
+        self.core.storage().create_thoughts_batch(&thoughts).await?;
+        for cross_ref in &cross_refs {
+            self.core.storage().create_cross_ref(cross_ref).await?;
+        }
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&judgement, "reasoning.debate output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            rounds = params.rounds,
+            winner = ?judgement.winner,
+            confidence = confidence,
+            latency_ms = latency,
+            "Debate judged"
+        );
+
+        Ok(DebateResult {
+            session_id: session.id,
+            proposition: params.proposition,
+            debate_branch_id: debate_branch.id,
+            transcript,
+            winner: judgement.winner,
+            verdict: judgement.verdict,
+            pro_score: judgement.pro_score.clamp(0.0, 1.0),
+            con_score: judgement.con_score.clamp(0.0, 1.0),
+            confidence,
+            verdict_branch_id: verdict_branch.id,
+        })
+    }
+
+    /// Have both sides argue every round, then have the judge rule
+    async fn argue_and_judge(
+        &self,
+        params: &DebateParams,
+        session_id: &str,
+        debate_branch: &Branch,
+        argument_pipe: &str,
+        judge_pipe: &str,
+    ) -> AppResult<DebateOutcome> {
+        let mut transcript: Vec<DebateArgument> = Vec::new();
+        let mut branches = vec![];
+        let mut thoughts = vec![Thought::new(session_id, &params.proposition, "debate")
+            .with_branch(&debate_branch.id)
+            .with_metadata(serde_json::json!({"is_proposition": true}))];
+        let mut cache_hit = true;
+        let mut fallback = None;
+
+        for round in 1..=params.rounds {
+            for side in [DebateSide::Pro, DebateSide::Con] {
+                let messages = vec![
+                    Message::system(DEBATE_ARGUMENT_PROMPT),
+                    Message::user(argument_prompt(params, side, round, &transcript)),
+                ];
+                let request =
+                    PipeRequest::new(argument_pipe, messages).with_generation(params.generation);
+                let response = self.core.backend().call_pipe(request).await?;
+                cache_hit &= response.cache_hit;
+                fallback = fallback.or(response.fallback);
+
+                let parsed: ArgumentResponse = parse_response(&response.completion, "argument")?;
+                let confidence = parsed.confidence.clamp(0.0, 1.0);
+
+                // Each argument answers the one before it, so the branches
+                // chain in speaking order
+                let rebutted = transcript.last().map(|a| a.branch_id.clone());
+                let branch = Branch::new(session_id)
+                    .with_parent(rebutted.as_deref().unwrap_or(&debate_branch.id))
+                    .with_name(format!("Round {} {}", round, side.label()))
+                    .with_confidence(confidence);
+                let thought = Thought::new(session_id, &parsed.argument, "debate")
+                    .with_branch(&branch.id)
+                    .with_confidence(confidence)
+                    .with_metadata(serde_json::json!({
+                        "side": side,
+                        "round": round,
+                        "key_points": parsed.key_points
+                    }));
+
+                let argument = DebateArgument {
+                    round,
+                    side,
+                    branch_id: branch.id.clone(),
+                    thought_id: thought.id.clone(),
+                    content: parsed.argument,
+                    key_points: parsed.key_points,
+                    confidence,
+                    score: None,
+                    rebuts_branch_id: rebutted,
+                };
+                emit_artifact("argument", transcript.len(), &argument);
+                transcript.push(argument);
+                branches.push(branch);
+                thoughts.push(thought);
+            }
+        }
+
+        let messages = vec![
+            Message::system(DEBATE_JUDGE_PROMPT),
+            Message::user(judge_prompt(params, &transcript)),
+        ];
+        let request = PipeRequest::new(judge_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
+        cache_hit &= response.cache_hit;
+        fallback = fallback.or(response.fallback);
+        let judgement: JudgeResponse = parse_response(&response.completion, "judgement")?;
+
+        Ok(DebateOutcome {
+            transcript,
+            branches,
+            thoughts,
+            judgement,
+            cache_hit,
+            fallback,
+        })
+    }
+}
+
+/// Prompt asking `side` for its argument in `round`
+fn argument_prompt(
+    params: &DebateParams,
+    side: DebateSide,
+    round: u32,
+    transcript: &[DebateArgument],
+) -> String {
+    let stance = match side {
+        DebateSide::Pro => "FOR",
+        DebateSide::Con => "AGAINST",
+    };
+    let mut prompt = format!(
+        "PROPOSITION: {}\n\nYou are the {} side and argue {} the proposition. \
+         This is round {} of {}.\n",
+        params.proposition,
+        side.label(),
+        stance,
+        round,
+        params.rounds
+    );
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+    if transcript.is_empty() {
+        prompt.push_str("\nYou open the debate.");
+    } else {
+        prompt.push_str(&format!(
+            "\nDEBATE SO FAR:\n{}\n\nRebut the {} side's latest argument.",
+            format_transcript(transcript),
+            side.opponent().label()
+        ));
+    }
+    prompt
+}
+
+/// Prompt asking the judge to rule on `transcript`
+fn judge_prompt(params: &DebateParams, transcript: &[DebateArgument]) -> String {
+    let mut prompt = format!("PROPOSITION: {}\n", params.proposition);
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+    prompt.push_str(&format!(
+        "\nTRANSCRIPT:\n{}\n\nScore every argument and declare the winner.",
+        format_transcript(transcript)
+    ));
+    prompt
+}
+
+/// The transcript as `[Round n] SIDE: argument` lines
+fn format_transcript(transcript: &[DebateArgument]) -> String {
+    transcript
+        .iter()
+        .map(|a| format!("[Round {}] {}: {}", a.round, a.side.label(), a.content))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse a JSON completion of the given `kind`
+fn parse_response<T: serde::de::DeserializeOwned>(completion: &str, kind: &str) -> AppResult<T> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    serde_json::from_str(json_str).map_err(|e| {
+        ToolError::Reasoning {
+            message: format!("Failed to parse debate {}: {}", kind, e),
+        }
+        .into()
+    })
+}
+
+/// Everything a debate produced before it is stored
+struct DebateOutcome {
+    transcript: Vec<DebateArgument>,
+    branches: Vec<Branch>,
+    thoughts: Vec<Thought>,
+    judgement: JudgeResponse,
+    cache_hit: bool,
+    fallback: Option<String>,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArgumentResponse {
+    argument: String,
+    #[serde(default)]
+    key_points: Vec<String>,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JudgeResponse {
+    winner: DebateWinner,
+    verdict: String,
+    pro_score: f64,
+    con_score: f64,
+    #[serde(default)]
+    argument_scores: Vec<ArgumentScore>,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArgumentScore {
+    side: DebateSide,
+    round: u32,
+    score: f64,
+    #[serde(default)]
+    reasoning: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn argument(round: u32, side: DebateSide, content: &str) -> DebateArgument {
+        DebateArgument {
+            round,
+            side,
+            branch_id: format!("b-{}", round),
+            thought_id: format!("t-{}", round),
+            content: content.to_string(),
+            key_points: vec![],
+            confidence: 0.7,
+            score: None,
+            rebuts_branch_id: None,
+        }
+    }
+
+    #[test]
+    fn test_debate_params_defaults() {
+        let params: DebateParams =
+            serde_json::from_value(json!({"proposition": "Remote work is better"})).unwrap();
+        assert_eq!(params.proposition, "Remote work is better");
+        assert_eq!(params.rounds, DEFAULT_DEBATE_ROUNDS);
+        assert!(params.context.is_none());
+        assert!(params.session_id.is_none());
+        assert!(params.generation.is_empty());
+    }
+
+    #[test]
+    fn test_debate_params_with_all_fields() {
+        let params: DebateParams = serde_json::from_value(json!({
+            "proposition": "P",
+            "rounds": 3,
+            "context": "Background",
+            "session_id": "s-1",
+            "generation": {"temperature": 0.2}
+        }))
+        .unwrap();
+        assert_eq!(params.rounds, 3);
+        assert_eq!(params.context.as_deref(), Some("Background"));
+        assert_eq!(params.session_id.as_deref(), Some("s-1"));
+        assert!(!params.generation.is_empty());
+    }
+
+    #[test]
+    fn test_debate_side_and_winner() {
+        assert_eq!(DebateSide::Pro.opponent(), DebateSide::Con);
+        assert_eq!(DebateSide::Con.opponent(), DebateSide::Pro);
+        assert_eq!(DebateWinner::Con.side(), Some(DebateSide::Con));
+        assert_eq!(DebateWinner::Draw.side(), None);
+        assert_eq!(serde_json::to_value(DebateSide::Pro).unwrap(), "pro");
+        assert_eq!(
+            serde_json::from_value::<DebateWinner>(json!("draw")).unwrap(),
+            DebateWinner::Draw
+        );
+    }
+
+    #[test]
+    fn test_argument_prompt_opens_then_rebuts() {
+        let params: DebateParams = serde_json::from_value(json!({
+            "proposition": "Tabs beat spaces",
+            "context": "A new codebase"
+        }))
+        .unwrap();
+
+        let opening = argument_prompt(&params, DebateSide::Pro, 1, &[]);
+        assert!(opening.contains("argue FOR"));
+        assert!(opening.contains("round 1 of 2"));
+        assert!(opening.contains("A new codebase"));
+        assert!(opening.contains("You open the debate."));
+
+        let transcript = [argument(1, DebateSide::Pro, "Tabs are configurable")];
+        let rebuttal = argument_prompt(&params, DebateSide::Con, 1, &transcript);
+        assert!(rebuttal.contains("argue AGAINST"));
+        assert!(rebuttal.contains("[Round 1] PRO: Tabs are configurable"));
+        assert!(rebuttal.contains("Rebut the PRO side's latest argument."));
+    }
+
+    #[test]
+    fn test_judge_prompt_lists_transcript() {
+        let params: DebateParams =
+            serde_json::from_value(json!({"proposition": "Tabs beat spaces"})).unwrap();
+        let transcript = [
+            argument(1, DebateSide::Pro, "Tabs are configurable"),
+            argument(1, DebateSide::Con, "Spaces render the same everywhere"),
+        ];
+        let prompt = judge_prompt(&params, &transcript);
+        assert!(prompt.starts_with("PROPOSITION: Tabs beat spaces"));
+        assert!(prompt.contains(
+            "[Round 1] PRO: Tabs are configurable\n[Round 1] CON: Spaces render the same everywhere"
+        ));
+    }
+
+    #[test]
+    fn test_parse_judge_response() {
+        let judgement: JudgeResponse = parse_response(
+            r#"```json
+            {"winner": "con", "verdict": "CON was more concrete", "pro_score": 0.5,
+             "con_score": 0.7, "confidence": 0.8,
+             "argument_scores": [{"side": "con", "round": 1, "score": 0.7}]}
+            ```"#,
+            "judgement",
+        )
+        .unwrap();
+        assert_eq!(judgement.winner, DebateWinner::Con);
+        assert_eq!(judgement.argument_scores[0].side, DebateSide::Con);
+        assert!(judgement.argument_scores[0].reasoning.is_empty());
+
+        let err = parse_response::<JudgeResponse>(r#"{"winner": "nobody"}"#, "judgement");
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_parse_argument_response_without_key_points() {
+        let parsed: ArgumentResponse =
+            parse_response(r#"{"argument": "A", "confidence": 0.6}"#, "argument").unwrap();
+        assert_eq!(parsed.argument, "A");
+        assert!(parsed.key_points.is_empty());
+    }
+}
@@ -7,6 +7,7 @@
 //! - Stakeholder perspective analysis with power/interest mapping
 //! - Conflict and alignment detection
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Instant;
@@ -15,11 +16,12 @@ use tracing::{debug, error, info, warn};
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
 use crate::prompts::{DECISION_MAKER_PROMPT, PERSPECTIVE_ANALYZER_PROMPT};
+use crate::providers::SharedBackend;
 use crate::storage::{
     Decision as StoredDecision, Invocation, PerspectiveAnalysis as StoredPerspective,
-    SqliteStorage, Storage, StoredCriterion,
+    SharedStorage, StoredCriterion,
 };
 
 // ============================================================================
@@ -45,6 +47,9 @@ pub struct DecisionParams {
     /// Decision method to use.
     #[serde(default)]
     pub method: DecisionMethod,
+    /// Model parameter overrides for the pipe call.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// A single criterion for decision evaluation.
@@ -99,6 +104,9 @@ pub struct PerspectiveParams {
     /// Include power/interest analysis.
     #[serde(default = "default_true")]
     pub include_power_matrix: bool,
+    /// Model parameter overrides for the pipe call.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_true() -> bool {
@@ -176,7 +184,7 @@ struct AlignmentResponse {
 // ============================================================================
 
 /// Result of multi-criteria decision analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DecisionResult {
     /// Unique decision ID.
     pub decision_id: String,
@@ -197,7 +205,7 @@ pub struct DecisionResult {
 }
 
 /// The recommended option.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Recommendation {
     /// The recommended option.
     pub option: String,
@@ -210,7 +218,7 @@ pub struct Recommendation {
 }
 
 /// Score breakdown for an option.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OptionScore {
     /// The option.
     pub option: String,
@@ -223,7 +231,7 @@ pub struct OptionScore {
 }
 
 /// Score for a single criterion.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CriterionScore {
     /// Raw score (0.0-1.0).
     pub score: f64,
@@ -232,7 +240,7 @@ pub struct CriterionScore {
 }
 
 /// Sensitivity analysis results.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SensitivityAnalysis {
     /// Whether the recommendation is robust.
     pub robust: bool,
@@ -243,7 +251,7 @@ pub struct SensitivityAnalysis {
 }
 
 /// Trade-off between options.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TradeOff {
     /// Options being compared.
     pub between: (String, String),
@@ -252,7 +260,7 @@ pub struct TradeOff {
 }
 
 /// Result of stakeholder perspective analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerspectiveResult {
     /// Unique analysis ID.
     pub analysis_id: String,
@@ -276,7 +284,7 @@ pub struct PerspectiveResult {
 }
 
 /// Analysis of a single stakeholder.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StakeholderAnalysis {
     /// Stakeholder name.
     pub name: String,
@@ -299,7 +307,7 @@ pub struct StakeholderAnalysis {
 }
 
 /// Quadrant in power/interest matrix.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Quadrant {
     /// High power, high interest.
@@ -324,7 +332,7 @@ impl std::fmt::Display for Quadrant {
 }
 
 /// Power/interest matrix breakdown.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PowerMatrix {
     /// Key players (high power, high interest).
     pub key_players: Vec<String>,
@@ -337,7 +345,7 @@ pub struct PowerMatrix {
 }
 
 /// Conflict between stakeholders.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Conflict {
     /// Stakeholders in conflict.
     pub stakeholders: (String, String),
@@ -350,7 +358,7 @@ pub struct Conflict {
 }
 
 /// Alignment between stakeholders.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Alignment {
     /// Aligned stakeholders.
     pub stakeholders: (String, String),
@@ -359,7 +367,7 @@ pub struct Alignment {
 }
 
 /// Synthesis of stakeholder perspectives.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Synthesis {
     /// Areas of consensus.
     pub consensus_areas: Vec<String>,
@@ -376,7 +384,7 @@ pub struct Synthesis {
 /// Decision framework mode handler.
 #[derive(Clone)]
 pub struct DecisionMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// Consolidated pipe name for decision framework operations (prompts passed dynamically).
     decision_framework_pipe: String,
@@ -384,7 +392,11 @@ pub struct DecisionMode {
 
 impl DecisionMode {
     /// Create a new decision mode handler.
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         let decision_framework_pipe = config
             .pipes
             .decision
@@ -399,7 +411,7 @@ impl DecisionMode {
         );
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend),
             decision_framework_pipe,
         }
     }
@@ -410,6 +422,7 @@ impl DecisionMode {
 
         // Validate input
         self.validate_decision_params(&params)?;
+        params.generation.validate()?;
 
         // Get or create session
         let session = self
@@ -422,17 +435,20 @@ impl DecisionMode {
         // Build messages for Langbase
         let messages = self.build_decision_messages(&params);
 
+        let decision_framework_pipe = self.core.select_pipe(&self.decision_framework_pipe);
+
         // Create invocation log
         let mut invocation = Invocation::new(
             "reasoning.make_decision",
             serialize_for_log(&params, "reasoning.make_decision input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.decision_framework_pipe);
+        .with_pipe(&decision_framework_pipe);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.decision_framework_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request =
+            PipeRequest::new(&decision_framework_pipe, messages).with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -441,6 +457,9 @@ impl DecisionMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let decision_response = self.parse_decision_response(&response.completion)?;
@@ -534,6 +553,7 @@ impl DecisionMode {
         params: PerspectiveParams,
     ) -> AppResult<PerspectiveResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate input
         if params.topic.trim().is_empty() {
@@ -555,17 +575,20 @@ impl DecisionMode {
         // Build messages for Langbase
         let messages = self.build_perspective_messages(&params);
 
+        let decision_framework_pipe = self.core.select_pipe(&self.decision_framework_pipe);
+
         // Create invocation log
         let mut invocation = Invocation::new(
             "reasoning.analyze_perspectives",
             serialize_for_log(&params, "reasoning.analyze_perspectives input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.decision_framework_pipe);
+        .with_pipe(&decision_framework_pipe);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.decision_framework_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request =
+            PipeRequest::new(&decision_framework_pipe, messages).with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -574,6 +597,9 @@ impl DecisionMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let perspective_response = self.parse_perspective_response(&response.completion)?;
@@ -632,6 +658,8 @@ impl DecisionMode {
         )
         .with_conflicts(serde_json::to_value(&result.conflicts).unwrap_or_default())
         .with_alignments(serde_json::to_value(&result.alignments).unwrap_or_default());
+        // Store under the ID the caller sees so the analysis can be referenced later
+        stored_perspective.id = analysis_id.clone();
 
         if let Some(pm) = &result.power_matrix {
             stored_perspective =
@@ -852,6 +880,7 @@ impl DecisionParams {
             constraints: Vec::new(),
             session_id: None,
             method: DecisionMethod::default(),
+            generation: GenerationParams::default(),
         }
     }
 
@@ -882,6 +911,11 @@ impl DecisionParams {
         self.method = method;
         self
     }
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl PerspectiveParams {
@@ -893,6 +927,7 @@ impl PerspectiveParams {
             context: None,
             session_id: None,
             include_power_matrix: true,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -923,6 +958,11 @@ impl PerspectiveParams {
         self.include_power_matrix = false;
         self
     }
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 // ============================================================================
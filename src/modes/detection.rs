@@ -6,6 +6,7 @@
 //! - Storage persistence for detected issues
 //! - Integration with thought analysis
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use tracing::info;
@@ -14,10 +15,12 @@ use super::ModeCore;
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
 use crate::langbase::{
-    BiasDetectionResponse, FallacyDetectionResponse, LangbaseClient, Message, PipeRequest,
+    BiasDetectionResponse, FallacyDetectionResponse, GenerationParams, Message, PipeRequest,
 };
 use crate::prompts::{BIAS_DETECTION_PROMPT, FALLACY_DETECTION_PROMPT};
-use crate::storage::{Detection, DetectionType, SqliteStorage, Storage};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{Detection, DetectionType, SharedStorage};
 
 // ============================================================================
 // Bias Detection
@@ -34,10 +37,13 @@ pub struct DetectBiasesParams {
     pub session_id: Option<String>,
     /// Specific bias types to check (optional)
     pub check_types: Option<Vec<String>>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default)]
+    pub generation: GenerationParams,
 }
 
 /// Result of bias detection
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DetectBiasesResult {
     /// Detected biases
     pub detections: Vec<Detection>,
@@ -70,6 +76,9 @@ pub struct DetectFallaciesParams {
     /// Check for informal logical fallacies (default: true)
     #[serde(default = "default_true")]
     pub check_informal: bool,
+    /// Model parameter overrides for the pipe call
+    #[serde(default)]
+    pub generation: GenerationParams,
 }
 
 fn default_true() -> bool {
@@ -77,7 +86,7 @@ fn default_true() -> bool {
 }
 
 /// Result of fallacy detection
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct DetectFallaciesResult {
     /// Detected fallacies
     pub detections: Vec<Detection>,
@@ -98,7 +107,7 @@ pub struct DetectFallaciesResult {
 /// Detection mode handler for bias and fallacy detection.
 #[derive(Clone)]
 pub struct DetectionMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// Consolidated pipe name for all detection operations (prompts passed dynamically).
     detection_pipe: String,
@@ -106,7 +115,11 @@ pub struct DetectionMode {
 
 impl DetectionMode {
     /// Create a new detection mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         let detection_pipe = config
             .pipes
             .detection
@@ -121,7 +134,8 @@ impl DetectionMode {
         );
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             detection_pipe,
         }
     }
@@ -129,6 +143,7 @@ impl DetectionMode {
     /// Detect biases in content or a thought
     pub async fn detect_biases(&self, params: DetectBiasesParams) -> AppResult<DetectBiasesResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate and resolve content
         let (analysis_content, thought_id) = self
@@ -164,8 +179,14 @@ impl DetectionMode {
         }
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.detection_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let detection_pipe = self.core.select_pipe(&self.detection_pipe);
+        let schema = self
+            .core
+            .response_schema(SchemaKind::BiasDetection, &detection_pipe);
+        let request = PipeRequest::new(&detection_pipe, messages)
+            .with_output_schema(schema)
+            .with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let bias_response = BiasDetectionResponse::from_completion(&response.completion);
@@ -221,6 +242,7 @@ impl DetectionMode {
         params: DetectFallaciesParams,
     ) -> AppResult<DetectFallaciesResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate check flags
         if !params.check_formal && !params.check_informal {
@@ -263,8 +285,14 @@ impl DetectionMode {
         )));
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.detection_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let detection_pipe = self.core.select_pipe(&self.detection_pipe);
+        let schema = self
+            .core
+            .response_schema(SchemaKind::FallacyDetection, &detection_pipe);
+        let request = PipeRequest::new(&detection_pipe, messages)
+            .with_output_schema(schema)
+            .with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let fallacy_response = FallacyDetectionResponse::from_completion(&response.completion);
@@ -670,6 +698,7 @@ mod tests {
             thought_id: Some("t1".to_string()),
             session_id: Some("s1".to_string()),
             check_types: Some(vec!["bias1".to_string()]),
+            generation: GenerationParams::default(),
         };
         let cloned = params.clone();
         assert_eq!(params.content, cloned.content);
@@ -686,6 +715,7 @@ mod tests {
             session_id: Some("s1".to_string()),
             check_formal: true,
             check_informal: false,
+            generation: GenerationParams::default(),
         };
         let cloned = params.clone();
         assert_eq!(params.content, cloned.content);
@@ -732,6 +762,7 @@ mod tests {
             thought_id: None,
             session_id: None,
             check_types: None,
+            generation: GenerationParams::default(),
         };
         let debug_str = format!("{:?}", params);
         assert!(debug_str.contains("DetectBiasesParams"));
@@ -746,6 +777,7 @@ mod tests {
             session_id: None,
             check_formal: true,
             check_informal: true,
+            generation: GenerationParams::default(),
         };
         let debug_str = format!("{:?}", params);
         assert!(debug_str.contains("DetectFallaciesParams"));
@@ -0,0 +1,598 @@
+//! Dialectic reasoning mode - thesis, antithesis, synthesis.
+//!
+//! This module develops a position by repeated opposition:
+//! - The pipe builds the strongest antithesis to the current position
+//! - A synthesis keeps the valid core of both and becomes the next position
+//! - Rounds repeat until the position stops changing or the round limit is
+//!   reached; convergence is the lexical similarity between successive
+//!   positions, computed locally
+//!
+//! Positions are stored as a chain of branches. Each antithesis branch
+//! `contradicts` the position it opposes, and each synthesis branch
+//! `extends` both the position and the antithesis it reconciles.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{emit_artifact, extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, LocalEmbeddingProvider, Message, PipeRequest};
+use crate::prompts::{DIALECTIC_ANTITHESIS_PROMPT, DIALECTIC_SYNTHESIS_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{
+    cosine_similarity, Branch, CrossRef, CrossRefType, Invocation, SharedStorage, Thought,
+};
+
+/// Rounds run when the caller does not say.
+pub const DEFAULT_DIALECTIC_ROUNDS: u32 = 3;
+
+/// Most rounds one request may run; each round costs two pipe calls.
+pub const MAX_DIALECTIC_ROUNDS: u32 = 5;
+
+/// Similarity between successive positions at which the position counts as
+/// settled, when the caller does not say.
+pub const DEFAULT_CONVERGENCE_THRESHOLD: f64 = 0.9;
+
+fn default_rounds() -> u32 {
+    DEFAULT_DIALECTIC_ROUNDS
+}
+
+fn default_convergence_threshold() -> f64 {
+    DEFAULT_CONVERGENCE_THRESHOLD
+}
+
+/// Input parameters for dialectical reasoning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialecticParams {
+    /// The starting position
+    pub thesis: String,
+    /// Most thesis-antithesis-synthesis rounds to run
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    /// Similarity (0.0-1.0) between a synthesis and the position it
+    /// developed from at which iteration stops
+    #[serde(default = "default_convergence_threshold")]
+    pub convergence_threshold: f64,
+    /// Optional background for every call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// One thesis-antithesis-synthesis round
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DialecticRound {
+    /// Round number, from 1
+    pub round: u32,
+    /// Branch of the position opposed this round
+    pub position_branch_id: String,
+    /// Branch holding the antithesis
+    pub antithesis_branch_id: String,
+    /// The antithesis
+    pub antithesis: String,
+    /// Where the antithesis says the position fails
+    pub antithesis_points: Vec<String>,
+    /// Strength of the antithesis against the position (0.0-1.0)
+    pub antithesis_strength: f64,
+    /// Branch holding the synthesis, the next round's position
+    pub synthesis_branch_id: String,
+    /// Thought holding the synthesis
+    pub synthesis_thought_id: String,
+    /// The synthesis
+    pub synthesis: String,
+    /// What the synthesis keeps from the position
+    pub preserved_from_thesis: Vec<String>,
+    /// What the synthesis keeps from the antithesis
+    pub preserved_from_antithesis: Vec<String>,
+    /// Confidence in the synthesis (0.0-1.0)
+    pub confidence: f64,
+    /// Lexical similarity of the synthesis to the position (0.0-1.0)
+    pub similarity: f64,
+}
+
+/// Result of dialectical reasoning
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DialecticResult {
+    /// The session ID
+    pub session_id: String,
+    /// The starting position
+    pub thesis: String,
+    /// Branch holding the thesis
+    pub thesis_branch_id: String,
+    /// Every round run, in order
+    pub rounds: Vec<DialecticRound>,
+    /// The last synthesis
+    pub final_position: String,
+    /// Branch holding the last synthesis
+    pub final_branch_id: String,
+    /// Whether the position settled before the round limit
+    pub converged: bool,
+}
+
+/// Dialectic mode handler for thesis-antithesis-synthesis reasoning.
+#[derive(Clone)]
+pub struct DialecticMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Reflection pipe opposing and reconciling positions
+    reflection_pipe: String,
+    /// Local embeddings measuring how far a position moved
+    embedder: LocalEmbeddingProvider,
+}
+
+impl DialecticMode {
+    /// Create a new dialectic mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            reflection_pipe: config.pipes.reflection.clone(),
+            embedder: LocalEmbeddingProvider::default(),
+        }
+    }
+
+    /// Develop a thesis through rounds of antithesis and synthesis
+    pub async fn develop(&self, params: DialecticParams) -> AppResult<DialecticResult> {
+        let start = Instant::now();
+
+        if params.thesis.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "thesis".to_string(),
+                reason: "Thesis cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.rounds == 0 || params.rounds > MAX_DIALECTIC_ROUNDS {
+            return Err(ToolError::Validation {
+                field: "rounds".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_DIALECTIC_ROUNDS),
+            }
+            .into());
+        }
+        if !(0.0..=1.0).contains(&params.convergence_threshold) {
+            return Err(ToolError::Validation {
+                field: "convergence_threshold".to_string(),
+                reason: "Must be between 0.0 and 1.0".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "dialectic")
+            .await?;
+        debug!(session_id = %session.id, rounds = params.rounds, "Processing dialectic");
+
+        let pipe = self.core.select_pipe(&self.reflection_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.dialectic",
+            serialize_for_log(&params, "reasoning.dialectic input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let thesis_branch = Branch::new(&session.id).with_name(format!(
+            "Thesis: {}",
+            params.thesis.chars().take(30).collect::<String>()
+        ));
+
+        let outcome = match self
+            .run_rounds(&params, &session.id, &thesis_branch, &pipe)
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let DialecticOutcome {
+            rounds,
+            mut branches,
+            thoughts,
+            cross_refs,
+            converged,
+            cache_hit,
+            fallback,
+        } = outcome;
+
+        // Store the chain only once every round is in, so a failed pipe call
+        // never leaves an antithesis without its synthesis
+        branches.insert(0, thesis_branch.clone());
+        for branch in &branches {
+            self.core.storage().create_branch(branch).await?;
+        }
+        self.core.storage().create_thoughts_batch(&thoughts).await?;
+        for cross_ref in &cross_refs {
+            self.core.storage().create_cross_ref(cross_ref).await?;
+        }
+
+        // At least one round always runs
+        let last = &rounds[rounds.len() - 1];
+        let result = DialecticResult {
+            session_id: session.id.clone(),
+            thesis: params.thesis.clone(),
+            thesis_branch_id: thesis_branch.id,
+            final_position: last.synthesis.clone(),
+            final_branch_id: last.synthesis_branch_id.clone(),
+            rounds,
+            converged,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.dialectic output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            rounds = result.rounds.len(),
+            converged = converged,
+            latency_ms = latency,
+            "Dialectic completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Oppose and reconcile the position until it settles or rounds run out
+    async fn run_rounds(
+        &self,
+        params: &DialecticParams,
+        session_id: &str,
+        thesis_branch: &Branch,
+        pipe: &str,
+    ) -> AppResult<DialecticOutcome> {
+        let mut rounds: Vec<DialecticRound> = Vec::new();
+        let mut branches = Vec::new();
+        let mut thoughts = vec![Thought::new(session_id, &params.thesis, "dialectic")
+            .with_branch(&thesis_branch.id)
+            .with_metadata(serde_json::json!({"is_thesis": true}))];
+        let mut cross_refs = Vec::new();
+        let mut position = params.thesis.clone();
+        let mut position_branch_id = thesis_branch.id.clone();
+        let mut converged = false;
+        let mut cache_hit = true;
+        let mut fallback = None;
+
+        for round in 1..=params.rounds {
+            let messages = vec![
+                Message::system(DIALECTIC_ANTITHESIS_PROMPT),
+                Message::user(antithesis_prompt(params, &position, &rounds)),
+            ];
+            let request = PipeRequest::new(pipe, messages).with_generation(params.generation);
+            let response = self.core.backend().call_pipe(request).await?;
+            cache_hit &= response.cache_hit;
+            fallback = fallback.or(response.fallback);
+            let antithesis: AntithesisResponse =
+                parse_response(&response.completion, "antithesis")?;
+            let strength = antithesis.confidence.clamp(0.0, 1.0);
+
+            let messages = vec![
+                Message::system(DIALECTIC_SYNTHESIS_PROMPT),
+                Message::user(synthesis_prompt(params, &position, &antithesis)),
+            ];
+            let request = PipeRequest::new(pipe, messages).with_generation(params.generation);
+            let response = self.core.backend().call_pipe(request).await?;
+            cache_hit &= response.cache_hit;
+            fallback = fallback.or(response.fallback);
+            let synthesis: SynthesisResponse = parse_response(&response.completion, "synthesis")?;
+            let confidence = synthesis.confidence.clamp(0.0, 1.0);
+            let similarity = self.similarity(&position, &synthesis.synthesis);
+
+            // Antithesis and synthesis both hang off the position they answer
+            let antithesis_branch = Branch::new(session_id)
+                .with_parent(&position_branch_id)
+                .with_name(format!("Round {} antithesis", round))
+                .with_confidence(strength);
+            let synthesis_branch = Branch::new(session_id)
+                .with_parent(&position_branch_id)
+                .with_name(format!("Round {} synthesis", round))
+                .with_confidence(confidence);
+            thoughts.push(
+                Thought::new(session_id, &antithesis.argument, "dialectic")
+                    .with_branch(&antithesis_branch.id)
+                    .with_confidence(strength)
+                    .with_metadata(serde_json::json!({
+                        "round": round,
+                        "role": "antithesis",
+                        "key_points": antithesis.key_points
+                    })),
+            );
+            let synthesis_thought = Thought::new(session_id, &synthesis.synthesis, "dialectic")
+                .with_branch(&synthesis_branch.id)
+                .with_confidence(confidence)
+                .with_metadata(serde_json::json!({
+                    "round": round,
+                    "role": "synthesis",
+                    "preserved_from_thesis": synthesis.preserved_from_thesis,
+                    "preserved_from_antithesis": synthesis.preserved_from_antithesis,
+                    "similarity": similarity
+                }));
+            cross_refs.push(
+                CrossRef::new(
+                    &antithesis_branch.id,
+                    &position_branch_id,
+                    CrossRefType::Contradicts,
+                )
+                .with_reason(format!("Round {} antithesis", round))
+                .with_strength(strength),
+            );
+            cross_refs.push(
+                CrossRef::new(
+                    &synthesis_branch.id,
+                    &position_branch_id,
+                    CrossRefType::Extends,
+                )
+                .with_reason(format!(
+                    "Round {} synthesis keeps the position's core",
+                    round
+                ))
+                .with_strength(confidence),
+            );
+            cross_refs.push(
+                CrossRef::new(
+                    &synthesis_branch.id,
+                    &antithesis_branch.id,
+                    CrossRefType::Extends,
+                )
+                .with_reason(format!(
+                    "Round {} synthesis keeps the antithesis' core",
+                    round
+                ))
+                .with_strength(confidence),
+            );
+
+            let entry = DialecticRound {
+                round,
+                position_branch_id: position_branch_id.clone(),
+                antithesis_branch_id: antithesis_branch.id.clone(),
+                antithesis: antithesis.argument,
+                antithesis_points: antithesis.key_points,
+                antithesis_strength: strength,
+                synthesis_branch_id: synthesis_branch.id.clone(),
+                synthesis_thought_id: synthesis_thought.id.clone(),
+                synthesis: synthesis.synthesis,
+                preserved_from_thesis: synthesis.preserved_from_thesis,
+                preserved_from_antithesis: synthesis.preserved_from_antithesis,
+                confidence,
+                similarity,
+            };
+            emit_artifact("round", rounds.len(), &entry);
+
+            position = entry.synthesis.clone();
+            position_branch_id = synthesis_branch.id.clone();
+            thoughts.push(synthesis_thought);
+            branches.push(antithesis_branch);
+            branches.push(synthesis_branch);
+            rounds.push(entry);
+
+            if similarity >= params.convergence_threshold {
+                converged = true;
+                break;
+            }
+        }
+
+        Ok(DialecticOutcome {
+            rounds,
+            branches,
+            thoughts,
+            cross_refs,
+            converged,
+            cache_hit,
+            fallback,
+        })
+    }
+
+    /// Lexical similarity of two positions, clamped to 0.0-1.0
+    fn similarity(&self, a: &str, b: &str) -> f64 {
+        cosine_similarity(&self.embedder.embed_text(a), &self.embedder.embed_text(b))
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// Prompt asking for the antithesis to `position`
+fn antithesis_prompt(
+    params: &DialecticParams,
+    position: &str,
+    rounds: &[DialecticRound],
+) -> String {
+    let mut prompt = String::new();
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("CONTEXT:\n{}\n\n", context));
+    }
+    if !rounds.is_empty() {
+        let objections: Vec<String> = rounds
+            .iter()
+            .map(|r| format!("[Round {}] {}", r.round, r.antithesis))
+            .collect();
+        prompt.push_str(&format!(
+            "OBJECTIONS ALREADY ABSORBED:\n{}\n\n",
+            objections.join("\n")
+        ));
+    }
+    prompt.push_str(&format!(
+        "POSITION: {}\n\nConstruct the strongest antithesis to this position.",
+        position
+    ));
+    prompt
+}
+
+/// Prompt asking for the synthesis of `position` and `antithesis`
+fn synthesis_prompt(
+    params: &DialecticParams,
+    position: &str,
+    antithesis: &AntithesisResponse,
+) -> String {
+    let mut prompt = String::new();
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("CONTEXT:\n{}\n\n", context));
+    }
+    prompt.push_str(&format!(
+        "POSITION: {}\n\nANTITHESIS: {}\n",
+        position, antithesis.argument
+    ));
+    if !antithesis.key_points.is_empty() {
+        prompt.push_str(&format!(
+            "\nOBJECTIONS:\n- {}\n",
+            antithesis.key_points.join("\n- ")
+        ));
+    }
+    prompt.push_str("\nForm a synthesis that preserves the valid core of both.");
+    prompt
+}
+
+/// Parse a JSON completion of the given `kind`
+fn parse_response<T: serde::de::DeserializeOwned>(completion: &str, kind: &str) -> AppResult<T> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    serde_json::from_str(json_str).map_err(|e| {
+        ToolError::Reasoning {
+            message: format!("Failed to parse dialectic {}: {}", kind, e),
+        }
+        .into()
+    })
+}
+
+/// Everything the rounds produced before they are stored
+struct DialecticOutcome {
+    rounds: Vec<DialecticRound>,
+    branches: Vec<Branch>,
+    thoughts: Vec<Thought>,
+    cross_refs: Vec<CrossRef>,
+    converged: bool,
+    cache_hit: bool,
+    fallback: Option<String>,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AntithesisResponse {
+    argument: String,
+    #[serde(default)]
+    key_points: Vec<String>,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SynthesisResponse {
+    synthesis: String,
+    #[serde(default)]
+    preserved_from_thesis: Vec<String>,
+    #[serde(default)]
+    preserved_from_antithesis: Vec<String>,
+    confidence: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params() -> DialecticParams {
+        serde_json::from_value(json!({"thesis": "Remote work raises productivity"})).unwrap()
+    }
+
+    #[test]
+    fn test_dialectic_params_defaults() {
+        let params = params();
+        assert_eq!(params.rounds, DEFAULT_DIALECTIC_ROUNDS);
+        assert_eq!(params.convergence_threshold, DEFAULT_CONVERGENCE_THRESHOLD);
+        assert!(params.context.is_none());
+    }
+
+    #[test]
+    fn test_antithesis_prompt_lists_absorbed_objections() {
+        let first = antithesis_prompt(&params(), "Remote work raises productivity", &[]);
+        assert!(!first.contains("ALREADY ABSORBED"));
+        assert!(first.contains("POSITION: Remote work raises productivity"));
+
+        let round = DialecticRound {
+            round: 1,
+            position_branch_id: "b0".to_string(),
+            antithesis_branch_id: "b1".to_string(),
+            antithesis: "Collaboration suffers".to_string(),
+            antithesis_points: Vec::new(),
+            antithesis_strength: 0.7,
+            synthesis_branch_id: "b2".to_string(),
+            synthesis_thought_id: "t2".to_string(),
+            synthesis: "Hybrid".to_string(),
+            preserved_from_thesis: Vec::new(),
+            preserved_from_antithesis: Vec::new(),
+            confidence: 0.7,
+            similarity: 0.3,
+        };
+        let second = antithesis_prompt(&params(), "Hybrid", &[round]);
+        assert!(second.contains("[Round 1] Collaboration suffers"));
+        assert!(second.contains("POSITION: Hybrid"));
+    }
+
+    #[test]
+    fn test_synthesis_prompt() {
+        let antithesis = AntithesisResponse {
+            argument: "Collaboration suffers".to_string(),
+            key_points: vec!["Fewer chance meetings".to_string()],
+            confidence: 0.6,
+        };
+        let mut params = params();
+        params.context = Some("A 40-person startup".to_string());
+        let prompt = synthesis_prompt(&params, "Remote work raises productivity", &antithesis);
+        assert!(prompt.starts_with("CONTEXT:\nA 40-person startup"));
+        assert!(prompt.contains("ANTITHESIS: Collaboration suffers"));
+        assert!(prompt.contains("- Fewer chance meetings"));
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let antithesis: AntithesisResponse = parse_response(
+            r#"{"argument": "Collaboration suffers", "confidence": 0.6}"#,
+            "antithesis",
+        )
+        .unwrap();
+        assert!(antithesis.key_points.is_empty());
+        let synthesis: SynthesisResponse = parse_response(
+            r#"{"synthesis": "Hybrid", "preserved_from_thesis": ["Focus time"], "confidence": 0.7}"#,
+            "synthesis",
+        )
+        .unwrap();
+        assert_eq!(synthesis.preserved_from_thesis, ["Focus time"]);
+        assert!(synthesis.preserved_from_antithesis.is_empty());
+        assert!(parse_response::<SynthesisResponse>("{}", "synthesis").is_err());
+    }
+
+    #[test]
+    fn test_similarity_bounds() {
+        let embedder = LocalEmbeddingProvider::default();
+        let same = cosine_similarity(
+            &embedder.embed_text("Hybrid work with focus days"),
+            &embedder.embed_text("Hybrid work with focus days"),
+        );
+        assert!((same - 1.0).abs() < 1e-6);
+        let different = cosine_similarity(
+            &embedder.embed_text("Hybrid work with focus days"),
+            &embedder.embed_text("Ban meetings on Fridays"),
+        );
+        assert!(different < 0.5);
+    }
+}
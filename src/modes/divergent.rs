@@ -5,17 +5,41 @@
 //! - Assumption challenging
 //! - Rebellion/contrarian mode for maximum creativity
 //! - Novelty and viability scoring
+//! - Structured ideation techniques (SCAMPER, reverse brainstorming, random
+//!   stimulus, TRIZ) that drive the prompt and tag each perspective
+//! - Clustering perspectives by theme into a ranked shortlist
+//! - Scoring generated ideas against weighted criteria, with the winners
+//!   handed to decision mode as options
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
-use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use super::got::default_similarity_threshold;
+use super::{
+    emit_artifact, extract_json_from_completion, merge_duplicates, serialize_for_log,
+    DuplicateFate, ModeCore,
+};
+use super::{Criterion, DecisionParams, DecisionResult};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
-use crate::prompts::DIVERGENT_REASONING_PROMPT;
-use crate::storage::{Invocation, SqliteStorage, Storage, Thought};
+use crate::langbase::{EmbeddingProvider, GenerationParams, Message, OutputSchema, PipeRequest};
+use crate::prompts::{
+    DIVERGENT_EVALUATE_PROMPT, DIVERGENT_REASONING_PROMPT, RANDOM_STIMULUS_TECHNIQUE_PROMPT,
+    REVERSE_BRAINSTORM_TECHNIQUE_PROMPT, SCAMPER_TECHNIQUE_PROMPT, TRIZ_TECHNIQUE_PROMPT,
+};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{cosine_similarity, Invocation, SharedStorage, Thought};
+
+/// Embedding similarity at which a perspective joins a cluster's theme.
+///
+/// Lower than the duplicate threshold: members of a cluster share a theme
+/// without paraphrasing each other.
+pub const CLUSTER_SIMILARITY_THRESHOLD: f64 = 0.5;
 
 /// Input parameters for divergent reasoning
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +64,16 @@ pub struct DivergentParams {
     /// Confidence threshold (0.0-1.0)
     #[serde(default = "default_confidence")]
     pub confidence: f64,
+    /// Optional structured ideation technique
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technique: Option<IdeationTechnique>,
+    /// Cluster the perspectives by theme and return this many of the
+    /// best-ranked clusters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shortlist: Option<usize>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_confidence() -> f64 {
@@ -50,6 +84,91 @@ fn default_num_perspectives() -> usize {
     3
 }
 
+/// SCAMPER operations, in order.
+const SCAMPER_OPERATIONS: [&str; 7] = [
+    "Substitute",
+    "Combine",
+    "Adapt",
+    "Modify",
+    "Put to another use",
+    "Eliminate",
+    "Reverse",
+];
+
+/// Words random stimulus ideation draws from: concrete, with many
+/// attributes, and unrelated to any particular problem.
+const STIMULUS_WORDS: [&str; 24] = [
+    "lighthouse",
+    "beehive",
+    "umbrella",
+    "volcano",
+    "library",
+    "bicycle",
+    "spider web",
+    "orchestra",
+    "tide",
+    "passport",
+    "compost",
+    "origami",
+    "vending machine",
+    "glacier",
+    "chess clock",
+    "lantern",
+    "ant colony",
+    "airport",
+    "sourdough",
+    "kaleidoscope",
+    "zipper",
+    "coral reef",
+    "marathon",
+    "thermostat",
+];
+
+/// Structured ideation technique driving divergent reasoning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdeationTechnique {
+    /// Substitute, combine, adapt, modify, put to another use, eliminate,
+    /// reverse.
+    Scamper,
+    /// Find ways to make the problem worse, then invert them.
+    ReverseBrainstorming,
+    /// Force connections between the subject and unrelated words.
+    RandomStimulus,
+    /// Resolve the core contradiction with inventive principles.
+    Triz,
+}
+
+impl IdeationTechnique {
+    /// Get the technique name as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdeationTechnique::Scamper => "scamper",
+            IdeationTechnique::ReverseBrainstorming => "reverse_brainstorming",
+            IdeationTechnique::RandomStimulus => "random_stimulus",
+            IdeationTechnique::Triz => "triz",
+        }
+    }
+
+    /// Prompt addition describing the technique
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            IdeationTechnique::Scamper => SCAMPER_TECHNIQUE_PROMPT,
+            IdeationTechnique::ReverseBrainstorming => REVERSE_BRAINSTORM_TECHNIQUE_PROMPT,
+            IdeationTechnique::RandomStimulus => RANDOM_STIMULUS_TECHNIQUE_PROMPT,
+            IdeationTechnique::Triz => TRIZ_TECHNIQUE_PROMPT,
+        }
+    }
+}
+
+/// `count` distinct stimulus words from a random starting point
+fn pick_stimuli(count: usize) -> Vec<String> {
+    let start = (uuid::Uuid::new_v4().as_u128() % STIMULUS_WORDS.len() as u128) as usize;
+    (0..count.min(STIMULUS_WORDS.len()))
+        .map(|i| STIMULUS_WORDS[(start + i) % STIMULUS_WORDS.len()].to_string())
+        .collect()
+}
+
 /// Response from divergent reasoning Langbase pipe.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DivergentResponse {
@@ -76,8 +195,15 @@ pub struct Perspective {
     pub assumptions_challenged: Option<Vec<String>>,
 }
 
+impl DivergentResponse {
+    /// Latest output schema for the divergent pipe.
+    pub fn output_schema() -> OutputSchema {
+        SchemaKind::Divergent.latest()
+    }
+}
+
 /// Result of divergent reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DivergentResult {
     /// The session ID.
     pub session_id: String,
@@ -98,10 +224,41 @@ pub struct DivergentResult {
     /// Optional branch ID for tree mode integration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch_id: Option<String>,
+    /// Ideation technique used, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technique: Option<IdeationTechnique>,
+    /// Stimulus words given to random stimulus ideation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stimuli: Vec<String>,
+    /// Perspectives folded into a better-scoring near-duplicate.
+    #[serde(default)]
+    pub duplicates_merged: usize,
+    /// Best-ranked theme clusters, when a shortlist was requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shortlist: Vec<PerspectiveCluster>,
+}
+
+/// Perspectives sharing a theme, scored as a group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PerspectiveCluster {
+    /// Position in the ranking (1 = best).
+    pub rank: usize,
+    /// The theme: content of the best-scoring member.
+    pub theme: String,
+    /// Indices of the member perspectives, best-scoring first.
+    pub perspectives: Vec<usize>,
+    /// Thought IDs of the member perspectives, in the same order.
+    pub thought_ids: Vec<String>,
+    /// Mean novelty of the members (0.0-1.0).
+    pub novelty: f64,
+    /// Mean viability of the members (0.0-1.0).
+    pub feasibility: f64,
+    /// Mean of novelty and feasibility, by which clusters are ranked.
+    pub score: f64,
 }
 
 /// Perspective information in result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PerspectiveInfo {
     /// The ID of the perspective thought.
     pub thought_id: String,
@@ -114,23 +271,234 @@ pub struct PerspectiveInfo {
     /// Assumptions that were challenged (None if not analyzed by AI).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assumptions_challenged: Option<Vec<String>>,
+    /// Ideation technique that generated the perspective, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technique: Option<IdeationTechnique>,
+}
+
+/// Input parameters for evaluating generated ideas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivergentEvaluateParams {
+    /// Session holding the perspectives
+    pub session_id: String,
+    /// Perspective thoughts to evaluate; every perspective in the session
+    /// when empty
+    #[serde(default)]
+    pub thought_ids: Vec<String>,
+    /// Criteria to score against, weighted
+    pub criteria: Vec<Criterion>,
+    /// Question the winners should answer; defaults to the prompt that
+    /// generated the perspectives
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+    /// How many of the best-scoring ideas win (1-6)
+    #[serde(default = "default_winners")]
+    pub winners: usize,
+    /// Hand the winners to decision mode as options
+    #[serde(default = "default_decide")]
+    pub decide: bool,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+fn default_winners() -> usize {
+    3
+}
+
+fn default_decide() -> bool {
+    true
+}
+
+/// Most winners decision mode takes as options.
+const MAX_WINNERS: usize = 6;
+
+/// Result of evaluating ideas.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DivergentEvaluateResult {
+    /// The session ID.
+    pub session_id: String,
+    /// The question the ideas were evaluated for.
+    pub question: String,
+    /// Every evaluated idea, best first.
+    pub evaluations: Vec<IdeaEvaluation>,
+    /// Thought IDs of the winning ideas, best first.
+    pub winners: Vec<String>,
+    /// Decision among the winners, when they were handed to decision mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<DecisionResult>,
+}
+
+/// Scores of one idea.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IdeaEvaluation {
+    /// The perspective thought holding the idea.
+    pub thought_id: String,
+    /// The idea.
+    pub content: String,
+    /// Position in the ranking (1 = best).
+    pub rank: usize,
+    /// Weighted mean of the criterion scores (0.0-1.0).
+    pub score: f64,
+    /// Score per criterion (0.0-1.0); a criterion the pipe left out scores 0.
+    pub criteria_scores: HashMap<String, f64>,
+    /// Why the idea scores as it does.
+    pub rationale: String,
+}
+
+impl DivergentEvaluateResult {
+    /// Decision among the winners, or `None` when `params` did not ask for
+    /// one or fewer than two ideas won
+    pub fn decision_params(&self, params: &DivergentEvaluateParams) -> Option<DecisionParams> {
+        if !params.decide || self.winners.len() < 2 {
+            return None;
+        }
+        let options = self
+            .evaluations
+            .iter()
+            .filter(|e| self.winners.contains(&e.thought_id))
+            .map(|e| e.content.clone())
+            .collect();
+        let mut decision = DecisionParams::new(&self.question, options)
+            .with_session(&self.session_id)
+            .with_generation(params.generation);
+        decision.criteria = params.criteria.clone();
+        Some(decision)
+    }
+}
+
+/// Response from the divergent pipe when evaluating ideas.
+#[derive(Debug, Deserialize)]
+struct EvaluateResponse {
+    evaluations: Vec<EvaluateEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvaluateEntry {
+    /// 1-based index of the idea
+    idea: usize,
+    #[serde(default)]
+    scores: HashMap<String, f64>,
+    #[serde(default)]
+    rationale: String,
 }
 
 /// Divergent reasoning mode handler for creative exploration.
 #[derive(Clone)]
 pub struct DivergentMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for divergent reasoning.
     pipe_name: String,
+    /// Parallel calls a request may split into; 0 asks for all perspectives
+    /// in one call.
+    fan_out: usize,
+    /// Embeddings for merging near-duplicate perspectives; without them every
+    /// perspective is kept.
+    embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    /// Embedding similarity at which two perspectives count as duplicates.
+    similarity_threshold: f64,
 }
 
 impl DivergentMode {
     /// Create a new divergent mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config.pipes.divergent.clone(),
+            fan_out: config.concurrency.fan_out,
+            embeddings: None,
+            similarity_threshold: config
+                .pipes
+                .got
+                .as_ref()
+                .and_then(|g| g.similarity_threshold)
+                .unwrap_or_else(default_similarity_threshold),
+        }
+    }
+
+    /// Merge near-duplicate perspectives with `embeddings`
+    pub fn with_embeddings(mut self, embeddings: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Embed the text of each perspective, or `None` when no provider is set
+    /// or embedding fails
+    async fn embed_perspectives(&self, perspectives: &[Perspective]) -> Option<Vec<Vec<f32>>> {
+        let provider = self.embeddings.as_ref()?;
+        let texts: Vec<String> = perspectives.iter().map(|p| p.thought.clone()).collect();
+        match provider.embed(&texts).await {
+            Ok(vectors) if vectors.len() == texts.len() => Some(vectors),
+            Ok(vectors) => {
+                warn!(
+                    expected = texts.len(),
+                    received = vectors.len(),
+                    "Embedding count mismatch for perspectives"
+                );
+                None
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to embed perspectives");
+                None
+            }
+        }
+    }
+
+    /// Fold perspectives that paraphrase a better-scoring one into it.
+    ///
+    /// Returns the kept perspectives, each with the text of those merged into
+    /// it. Everything is kept when no provider is set or embedding fails.
+    async fn merge_perspectives(
+        &self,
+        perspectives: Vec<Perspective>,
+    ) -> Vec<(Perspective, Vec<String>)> {
+        let vectors = if perspectives.len() > 1 {
+            self.embed_perspectives(&perspectives).await
+        } else {
+            None
+        };
+        let Some(vectors) = vectors else {
+            return perspectives.into_iter().map(|p| (p, Vec::new())).collect();
+        };
+
+        let scores: Vec<f64> = perspectives.iter().map(perspective_score).collect();
+        let fates = merge_duplicates(&[], &[], &vectors, &scores, self.similarity_threshold);
+        let mut merged: Vec<Vec<String>> = vec![Vec::new(); perspectives.len()];
+        for (i, fate) in fates.iter().enumerate() {
+            if let DuplicateFate::DuplicateOfCandidate(kept) = fate {
+                merged[*kept].push(perspectives[i].thought.clone());
+            }
+        }
+        perspectives
+            .into_iter()
+            .zip(merged)
+            .zip(fates)
+            .filter(|(_, fate)| *fate == DuplicateFate::Distinct)
+            .map(|(kept, _)| kept)
+            .collect()
+    }
+
+    /// Group perspectives by theme; returns each perspective's cluster index.
+    ///
+    /// Without embeddings every perspective is its own cluster.
+    async fn cluster_perspectives(&self, perspectives: &[Perspective]) -> Vec<usize> {
+        let vectors = if perspectives.len() > 1 {
+            self.embed_perspectives(perspectives).await
+        } else {
+            None
+        };
+        match vectors {
+            Some(vectors) => {
+                let scores: Vec<f64> = perspectives.iter().map(perspective_score).collect();
+                cluster_by_theme(&vectors, &scores, CLUSTER_SIMILARITY_THRESHOLD)
+            }
+            None => (0..perspectives.len()).collect(),
         }
     }
 
@@ -146,8 +514,20 @@ impl DivergentMode {
             }
             .into());
         }
+        params.generation.validate()?;
+        if params.shortlist == Some(0) {
+            return Err(ToolError::Validation {
+                field: "shortlist".to_string(),
+                reason: "Must be at least 1".to_string(),
+            }
+            .into());
+        }
 
         let num_perspectives = params.num_perspectives.clamp(2, 5);
+        let stimuli = match params.technique {
+            Some(IdeationTechnique::RandomStimulus) => pick_stimuli(num_perspectives),
+            _ => Vec::new(),
+        };
 
         // Get or create session
         let session = self
@@ -164,14 +544,7 @@ impl DivergentMode {
             .get_session_thoughts(&session.id)
             .await?;
 
-        // Build messages for Langbase
-        let messages = self.build_messages(
-            &params.content,
-            &previous_thoughts,
-            num_perspectives,
-            params.challenge_assumptions,
-            params.force_rebellion,
-        );
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
 
         // Create invocation log
         let mut invocation = Invocation::new(
@@ -179,34 +552,69 @@ impl DivergentMode {
             serialize_for_log(&params, "reasoning.divergent input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.pipe_name);
-
-        // Call Langbase pipe
-        let request = PipeRequest::new(&self.pipe_name, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
-            Ok(resp) => resp,
+        .with_pipe(&pipe_name);
+
+        // Call Langbase pipe, fanning out one call per perspective if enabled
+        let result = if self.fan_out > 0 {
+            self.call_fanned_out(
+                &pipe_name,
+                &params,
+                &previous_thoughts,
+                num_perspectives,
+                &stimuli,
+            )
+            .await
+        } else {
+            let messages = self.build_technique_messages(
+                &params,
+                &previous_thoughts,
+                num_perspectives,
+                &stimuli,
+            );
+            let request = PipeRequest::new(&pipe_name, messages)
+                .with_output_schema(self.core.response_schema(SchemaKind::Divergent, &pipe_name))
+                .with_generation(params.generation);
+            self.core
+                .backend()
+                .call_pipe(request)
+                .await
+                .map_err(Into::into)
+                .and_then(|response| {
+                    let parsed = self.parse_response(&response.completion)?;
+                    emit_perspectives(&parsed.perspectives);
+                    Ok((parsed, response.cache_hit, response.fallback))
+                })
+        };
+        let (divergent_response, cache_hit, fallback) = match result {
+            Ok(result) => result,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
                 invocation = invocation.failure(e.to_string(), latency);
                 self.core.storage().log_invocation(&invocation).await?;
-                return Err(e.into());
+                return Err(e);
             }
         };
-
-        // Parse response
-        let divergent_response = self.parse_response(&response.completion)?;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref());
 
         // Create main thought for the original input
-        let main_thought = Thought::new(&session.id, &params.content, "divergent")
+        let mut main_thought = Thought::new(&session.id, &params.content, "divergent")
             .with_confidence(params.confidence);
+        if let Some(technique) = params.technique {
+            main_thought = main_thought.with_metadata(serde_json::json!({
+                "technique": technique.as_str(),
+                "stimuli": stimuli
+            }));
+        }
         let main_thought = if let Some(ref branch_id) = params.branch_id {
             main_thought.with_branch(branch_id)
         } else {
             main_thought
         };
-        self.core.storage().create_thought(&main_thought).await?;
 
         // Create thoughts for each perspective
+        let mut thoughts = Vec::new();
         let mut perspectives = Vec::new();
         let mut total_novelty = 0.0;
         let mut most_viable_idx = 0;
@@ -214,16 +622,49 @@ impl DivergentMode {
         let mut max_viability = 0.0;
         let mut max_novelty = 0.0;
 
-        for (i, p) in divergent_response.perspectives.iter().enumerate() {
+        let generated = divergent_response.perspectives.len();
+        let kept = self
+            .merge_perspectives(divergent_response.perspectives.clone())
+            .await;
+        let duplicates_merged = generated - kept.len();
+
+        // Rank theme clusters when a shortlist was asked for
+        let ranking = match params.shortlist {
+            Some(_) => {
+                let kept_perspectives: Vec<Perspective> =
+                    kept.iter().map(|(p, _)| p.clone()).collect();
+                let assignment = self.cluster_perspectives(&kept_perspectives).await;
+                Some(rank_clusters(&kept_perspectives, &assignment))
+            }
+            None => None,
+        };
+
+        for (i, (p, merged)) in kept.iter().enumerate() {
+            let mut metadata = serde_json::json!({
+                "novelty": p.novelty,
+                "viability": p.viability,
+                "perspective_index": i,
+                "assumptions_challenged": p.assumptions_challenged,
+                "technique": params.technique.map(|t| t.as_str())
+            });
+            if !merged.is_empty() {
+                metadata["merged_perspectives"] = serde_json::json!(merged);
+            }
+            if let Some(cluster) = ranking
+                .as_ref()
+                .and_then(|r| r.iter().find(|c| c.perspectives.contains(&i)))
+            {
+                metadata["cluster"] = serde_json::json!({
+                    "rank": cluster.rank,
+                    "theme": cluster.theme,
+                    "size": cluster.perspectives.len(),
+                    "score": cluster.score
+                });
+            }
             let perspective_thought = Thought::new(&session.id, &p.thought, "divergent")
                 .with_confidence((p.novelty + p.viability) / 2.0)
                 .with_parent(&main_thought.id)
-                .with_metadata(serde_json::json!({
-                    "novelty": p.novelty,
-                    "viability": p.viability,
-                    "perspective_index": i,
-                    "assumptions_challenged": p.assumptions_challenged
-                }));
+                .with_metadata(metadata);
 
             let perspective_thought = if let Some(ref branch_id) = params.branch_id {
                 perspective_thought.with_branch(branch_id)
@@ -231,11 +672,6 @@ impl DivergentMode {
                 perspective_thought
             };
 
-            self.core
-                .storage()
-                .create_thought(&perspective_thought)
-                .await?;
-
             total_novelty += p.novelty;
 
             if p.viability > max_viability {
@@ -248,14 +684,32 @@ impl DivergentMode {
             }
 
             perspectives.push(PerspectiveInfo {
-                thought_id: perspective_thought.id,
+                thought_id: perspective_thought.id.clone(),
                 content: p.thought.clone(),
                 novelty: p.novelty,
                 viability: p.viability,
                 assumptions_challenged: p.assumptions_challenged.clone(),
+                technique: params.technique,
             });
+            thoughts.push(perspective_thought);
         }
 
+        let shortlist: Vec<PerspectiveCluster> = match (ranking, params.shortlist) {
+            (Some(ranking), Some(size)) => ranking
+                .into_iter()
+                .take(size)
+                .map(|mut cluster| {
+                    cluster.thought_ids = cluster
+                        .perspectives
+                        .iter()
+                        .map(|&i| perspectives[i].thought_id.clone())
+                        .collect();
+                    cluster
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
         // Create synthesis thought
         let synthesis_thought =
             Thought::new(&session.id, &divergent_response.synthesis, "divergent")
@@ -272,10 +726,12 @@ impl DivergentMode {
             synthesis_thought
         };
 
-        self.core
-            .storage()
-            .create_thought(&synthesis_thought)
-            .await?;
+        // Store the input, perspectives, and synthesis together so a failure
+        // never leaves a partial set of perspectives behind
+        let synthesis_thought_id = synthesis_thought.id.clone();
+        thoughts.insert(0, main_thought.clone());
+        thoughts.push(synthesis_thought);
+        self.core.storage().create_thoughts_batch(&thoughts).await?;
 
         // Log successful invocation
         let latency = start.elapsed().as_millis() as i64;
@@ -295,6 +751,9 @@ impl DivergentMode {
             session_id = %session.id,
             thought_id = %main_thought.id,
             num_perspectives = perspectives.len(),
+            duplicates_merged = duplicates_merged,
+            shortlisted = shortlist.len(),
+            technique = params.technique.map(|t| t.as_str()),
             avg_novelty = avg_novelty,
             latency_ms = latency,
             "Divergent reasoning completed"
@@ -305,14 +764,310 @@ impl DivergentMode {
             thought_id: main_thought.id,
             perspectives,
             synthesis: divergent_response.synthesis,
-            synthesis_thought_id: synthesis_thought.id,
+            synthesis_thought_id,
             total_novelty_score: avg_novelty,
             most_viable_perspective: most_viable_idx,
             most_novel_perspective: most_novel_idx,
             branch_id: params.branch_id,
+            technique: params.technique,
+            stimuli,
+            duplicates_merged,
+            shortlist,
         })
     }
 
+    /// Score perspectives of a session against weighted criteria and pick
+    /// the winners.
+    ///
+    /// The pipe scores each idea on each criterion; the weighted means are
+    /// computed here. Each evaluated thought records its score under
+    /// `evaluation`. Handing the winners to decision mode is left to the
+    /// caller, see [`DivergentEvaluateResult::decision_params`].
+    pub async fn evaluate(
+        &self,
+        params: DivergentEvaluateParams,
+    ) -> AppResult<DivergentEvaluateResult> {
+        let start = Instant::now();
+        validate_evaluate_params(&params)?;
+        params.generation.validate()?;
+
+        let thoughts = self
+            .core
+            .storage()
+            .get_session_thoughts(&params.session_id)
+            .await?;
+        let ideas: Vec<Thought> = if params.thought_ids.is_empty() {
+            thoughts.into_iter().filter(is_perspective).collect()
+        } else {
+            params
+                .thought_ids
+                .iter()
+                .map(|id| {
+                    thoughts
+                        .iter()
+                        .find(|t| &t.id == id && is_perspective(t))
+                        .cloned()
+                        .ok_or_else(|| ToolError::Validation {
+                            field: "thought_ids".to_string(),
+                            reason: format!("Not a perspective in this session: {}", id),
+                        })
+                })
+                .collect::<Result<_, _>>()?
+        };
+        if ideas.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: "Session has no perspectives to evaluate".to_string(),
+            }
+            .into());
+        }
+        let question = match &params.question {
+            Some(question) => question.clone(),
+            None => self.generating_prompt(&ideas[0]).await?,
+        };
+
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+        let mut invocation = Invocation::new(
+            "reasoning.divergent.evaluate",
+            serialize_for_log(&params, "reasoning.divergent.evaluate input"),
+        )
+        .with_session(&params.session_id)
+        .with_pipe(&pipe_name);
+
+        let messages = build_evaluate_messages(&question, &ideas, &params.criteria);
+        let request = PipeRequest::new(&pipe_name, messages).with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e.into());
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
+
+        let json_str = extract_json_from_completion(&response.completion).map_err(|e| {
+            ToolError::Reasoning {
+                message: format!("Idea evaluation extraction failed: {}", e),
+            }
+        })?;
+        let parsed: EvaluateResponse =
+            serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                message: format!("Failed to parse idea evaluation: {}", e),
+            })?;
+
+        let evaluations = score_ideas(&ideas, &params.criteria, parsed.evaluations);
+        let winners: Vec<String> = evaluations
+            .iter()
+            .take(params.winners)
+            .map(|e| e.thought_id.clone())
+            .collect();
+
+        for evaluation in &evaluations {
+            let Some(idea) = ideas.iter().find(|t| t.id == evaluation.thought_id) else {
+                continue;
+            };
+            let mut thought = idea.clone();
+            let mut metadata = match thought.metadata.take() {
+                Some(serde_json::Value::Object(map)) => map,
+                _ => serde_json::Map::new(),
+            };
+            metadata.insert(
+                "evaluation".to_string(),
+                serde_json::json!({
+                    "rank": evaluation.rank,
+                    "score": evaluation.score,
+                    "criteria_scores": evaluation.criteria_scores,
+                    "winner": winners.contains(&evaluation.thought_id)
+                }),
+            );
+            thought.metadata = Some(serde_json::Value::Object(metadata));
+            self.core
+                .storage()
+                .update_thought_metadata(&thought)
+                .await?;
+        }
+
+        let result = DivergentEvaluateResult {
+            session_id: params.session_id.clone(),
+            question,
+            evaluations,
+            winners,
+            decision: None,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation.success(
+            serialize_for_log(&result, "reasoning.divergent.evaluate output"),
+            latency,
+        );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %result.session_id,
+            ideas = result.evaluations.len(),
+            winners = result.winners.len(),
+            latency_ms = latency,
+            "Idea evaluation completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Content of the thought that generated `perspective`, or an error when
+    /// it has none
+    async fn generating_prompt(&self, perspective: &Thought) -> AppResult<String> {
+        let parent = match perspective.parent_id.as_deref() {
+            Some(id) => self.core.storage().get_thought(id).await?,
+            None => None,
+        };
+        parent.map(|t| t.content).ok_or_else(|| {
+            ToolError::Validation {
+                field: "question".to_string(),
+                reason: "The perspectives have no generating prompt; pass a question".to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Ask for each perspective in its own call, then synthesize them in one
+    /// further call
+    async fn call_fanned_out(
+        &self,
+        pipe_name: &str,
+        params: &DivergentParams,
+        history: &[Thought],
+        num_perspectives: usize,
+        stimuli: &[String],
+    ) -> AppResult<(DivergentResponse, bool, Option<String>)> {
+        let schema = self.core.response_schema(SchemaKind::Divergent, pipe_name);
+        let requests = (0..num_perspectives)
+            .map(|i| {
+                // Each call works from its own stimulus word
+                let stimulus = stimuli.get(i).map(std::slice::from_ref).unwrap_or_default();
+                let mut messages = self.build_technique_messages(params, history, 1, stimulus);
+                messages.push(Message::user(format!(
+                    "This is perspective {} of {} generated separately. Take an angle the \
+                     others are unlikely to choose.",
+                    i + 1,
+                    num_perspectives
+                )));
+                if params.technique == Some(IdeationTechnique::Scamper) {
+                    messages.push(Message::user(format!(
+                        "Apply the {} operation.",
+                        SCAMPER_OPERATIONS[i % SCAMPER_OPERATIONS.len()]
+                    )));
+                }
+                PipeRequest::new(pipe_name, messages)
+                    .with_output_schema(schema.clone())
+                    .with_generation(params.generation)
+            })
+            .collect();
+
+        let fan_out = self
+            .core
+            .backend()
+            .call_pipe_parallel(requests, self.fan_out)
+            .await;
+        // Partial failures still yield the perspectives that came back
+        for (index, error) in &fan_out.errors {
+            warn!(
+                perspective = index,
+                error = %error,
+                "Divergent perspective call failed, continuing with the rest"
+            );
+        }
+        let responses = fan_out.into_responses()?;
+        let mut cache_hit = responses.iter().all(|(_, r)| r.cache_hit);
+        let mut fallback = responses.iter().find_map(|(_, r)| r.fallback.clone());
+
+        let mut perspectives = Vec::new();
+        for (_, response) in &responses {
+            let parsed = self.parse_response(&response.completion)?;
+            perspectives.extend(parsed.perspectives.into_iter().take(1));
+        }
+        // The perspectives are final; only the synthesis is still to come
+        emit_perspectives(&perspectives);
+
+        let messages = self.build_synthesis_messages(&params.content, &perspectives);
+        let request = PipeRequest::new(pipe_name, messages)
+            .with_output_schema(schema)
+            .with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
+        cache_hit &= response.cache_hit;
+        fallback = fallback.or(response.fallback);
+        let synthesis = self.parse_response(&response.completion)?.synthesis;
+
+        Ok((
+            DivergentResponse {
+                perspectives,
+                synthesis,
+                metadata: serde_json::Value::Null,
+            },
+            cache_hit,
+            fallback,
+        ))
+    }
+
+    /// Messages asking for a synthesis of perspectives generated separately
+    fn build_synthesis_messages(
+        &self,
+        content: &str,
+        perspectives: &[Perspective],
+    ) -> Vec<Message> {
+        let listed: Vec<String> = perspectives
+            .iter()
+            .enumerate()
+            .map(|(i, p)| format!("{}. {}", i + 1, p.thought))
+            .collect();
+
+        vec![
+            Message::system(DIVERGENT_REASONING_PROMPT),
+            Message::user(format!(
+                "Perspectives on:\n{}\n\n{}\n\nReturn these perspectives as given, with a \
+                 synthesis that integrates their insights.",
+                content,
+                listed.join("\n")
+            )),
+        ]
+    }
+
+    /// Messages for a request, with the prompt of the requested ideation
+    /// technique and its stimulus words added
+    fn build_technique_messages(
+        &self,
+        params: &DivergentParams,
+        history: &[Thought],
+        num_perspectives: usize,
+        stimuli: &[String],
+    ) -> Vec<Message> {
+        let mut messages = self.build_messages(
+            &params.content,
+            history,
+            num_perspectives,
+            params.challenge_assumptions,
+            params.force_rebellion,
+        );
+        if let Some(technique) = params.technique {
+            // The system prompt comes first
+            if let Some(system) = messages.first_mut() {
+                system.content.push_str("\n\n");
+                system.content.push_str(technique.prompt());
+            }
+            if !stimuli.is_empty() {
+                let last = messages.len() - 1;
+                messages.insert(
+                    last,
+                    Message::user(format!("Stimulus words: {}", stimuli.join(", "))),
+                );
+            }
+        }
+        messages
+    }
+
     fn build_messages(
         &self,
         content: &str,
@@ -386,6 +1141,225 @@ impl DivergentMode {
     }
 }
 
+/// Whether `thought` is a perspective generated by divergent reasoning
+fn is_perspective(thought: &Thought) -> bool {
+    thought.mode == "divergent"
+        && thought
+            .metadata
+            .as_ref()
+            .is_some_and(|m| m.get("perspective_index").is_some())
+}
+
+fn validate_evaluate_params(params: &DivergentEvaluateParams) -> AppResult<()> {
+    if params.criteria.is_empty() {
+        return Err(ToolError::Validation {
+            field: "criteria".to_string(),
+            reason: "At least one criterion is required".to_string(),
+        }
+        .into());
+    }
+    if let Some(criterion) = params
+        .criteria
+        .iter()
+        .find(|c| c.name.trim().is_empty() || c.weight <= 0.0 || !c.weight.is_finite())
+    {
+        return Err(ToolError::Validation {
+            field: "criteria".to_string(),
+            reason: format!(
+                "Criteria need a name and a positive weight, got \"{}\" with weight {}",
+                criterion.name, criterion.weight
+            ),
+        }
+        .into());
+    }
+    if !(1..=MAX_WINNERS).contains(&params.winners) {
+        return Err(ToolError::Validation {
+            field: "winners".to_string(),
+            reason: format!("Must be between 1 and {}", MAX_WINNERS),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Messages asking the pipe to score `ideas` on `criteria`
+fn build_evaluate_messages(
+    question: &str,
+    ideas: &[Thought],
+    criteria: &[Criterion],
+) -> Vec<Message> {
+    let listed_ideas: Vec<String> = ideas
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!("{}. {}", i + 1, t.content))
+        .collect();
+    let listed_criteria: Vec<String> = criteria
+        .iter()
+        .map(|c| match &c.description {
+            Some(description) => format!("- {}: {}", c.name, description),
+            None => format!("- {}", c.name),
+        })
+        .collect();
+
+    vec![
+        Message::system(DIVERGENT_EVALUATE_PROMPT),
+        Message::user(format!(
+            "Question: {}\n\nIdeas:\n{}\n\nCriteria:\n{}",
+            question,
+            listed_ideas.join("\n"),
+            listed_criteria.join("\n")
+        )),
+    ]
+}
+
+/// Weight the pipe's criterion scores into one score per idea and rank the
+/// ideas, best first.
+///
+/// Scores are clamped to 0.0-1.0. An idea or criterion the pipe left out
+/// scores 0, and entries for unknown ideas are ignored.
+fn score_ideas(
+    ideas: &[Thought],
+    criteria: &[Criterion],
+    entries: Vec<EvaluateEntry>,
+) -> Vec<IdeaEvaluation> {
+    let total_weight: f64 = criteria.iter().map(|c| c.weight).sum();
+    let mut by_idea: HashMap<usize, EvaluateEntry> = HashMap::new();
+    for entry in entries {
+        by_idea.entry(entry.idea).or_insert(entry);
+    }
+
+    let mut evaluations: Vec<IdeaEvaluation> = ideas
+        .iter()
+        .enumerate()
+        .map(|(i, idea)| {
+            let entry = by_idea.remove(&(i + 1));
+            if entry.is_none() {
+                warn!(thought_id = %idea.id, "Idea missing from evaluation, scoring it 0");
+            }
+            let criteria_scores: HashMap<String, f64> = criteria
+                .iter()
+                .map(|c| {
+                    let score = entry
+                        .as_ref()
+                        .and_then(|e| e.scores.get(&c.name))
+                        .copied()
+                        .unwrap_or(0.0)
+                        .clamp(0.0, 1.0);
+                    (c.name.clone(), score)
+                })
+                .collect();
+            let score = criteria
+                .iter()
+                .map(|c| c.weight * criteria_scores[&c.name])
+                .sum::<f64>()
+                / total_weight;
+            IdeaEvaluation {
+                thought_id: idea.id.clone(),
+                content: idea.content.clone(),
+                rank: 0,
+                score,
+                criteria_scores,
+                rationale: entry.map(|e| e.rationale).unwrap_or_default(),
+            }
+        })
+        .collect();
+    // Stable, so ties keep the order the ideas were generated in
+    evaluations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for (i, evaluation) in evaluations.iter_mut().enumerate() {
+        evaluation.rank = i + 1;
+    }
+    evaluations
+}
+
+/// Score of a perspective: the mean of its novelty and viability
+fn perspective_score(p: &Perspective) -> f64 {
+    (p.novelty + p.viability) / 2.0
+}
+
+/// Assign each vector to a theme cluster; returns cluster indices in input
+/// order.
+///
+/// Items are taken best score first. Each joins the first cluster whose
+/// founding item is at least `threshold` similar, or founds a new one, so a
+/// cluster's best-scoring member is its founder. Clusters are numbered in the
+/// order they were founded.
+pub(crate) fn cluster_by_theme(vectors: &[Vec<f32>], scores: &[f64], threshold: f64) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..vectors.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+    let mut founders: Vec<usize> = Vec::new();
+    let mut assignment = vec![0; vectors.len()];
+    for i in order {
+        match founders
+            .iter()
+            .position(|&f| cosine_similarity(&vectors[i], &vectors[f]) >= threshold)
+        {
+            Some(cluster) => assignment[i] = cluster,
+            None => {
+                assignment[i] = founders.len();
+                founders.push(i);
+            }
+        }
+    }
+    assignment
+}
+
+/// Score the clusters of `assignment` and rank them, best first.
+///
+/// Ties in score go to the larger cluster. Thought IDs are left empty.
+fn rank_clusters(perspectives: &[Perspective], assignment: &[usize]) -> Vec<PerspectiveCluster> {
+    let count = assignment.iter().max().map_or(0, |&m| m + 1);
+    let mut clusters: Vec<PerspectiveCluster> = (0..count)
+        .filter_map(|c| {
+            let mut members: Vec<usize> = (0..perspectives.len())
+                .filter(|&i| assignment[i] == c)
+                .collect();
+            if members.is_empty() {
+                return None;
+            }
+            members.sort_by(|&a, &b| {
+                perspective_score(&perspectives[b]).total_cmp(&perspective_score(&perspectives[a]))
+            });
+            let size = members.len() as f64;
+            let novelty = members
+                .iter()
+                .map(|&i| perspectives[i].novelty)
+                .sum::<f64>()
+                / size;
+            let feasibility = members
+                .iter()
+                .map(|&i| perspectives[i].viability)
+                .sum::<f64>()
+                / size;
+            Some(PerspectiveCluster {
+                rank: 0,
+                theme: perspectives[members[0]].thought.clone(),
+                perspectives: members,
+                thought_ids: Vec::new(),
+                novelty,
+                feasibility,
+                score: (novelty + feasibility) / 2.0,
+            })
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.perspectives.len().cmp(&a.perspectives.len()))
+    });
+    for (i, cluster) in clusters.iter_mut().enumerate() {
+        cluster.rank = i + 1;
+    }
+    clusters
+}
+
+/// Report each perspective as a partial result of the call
+fn emit_perspectives(perspectives: &[Perspective]) {
+    for (i, perspective) in perspectives.iter().enumerate() {
+        emit_artifact("perspective", i, perspective);
+    }
+}
+
 impl DivergentParams {
     /// Create new params with just content
     pub fn new(content: impl Into<String>) -> Self {
@@ -397,6 +1371,9 @@ impl DivergentParams {
             challenge_assumptions: false,
             force_rebellion: false,
             confidence: default_confidence(),
+            technique: None,
+            shortlist: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -430,16 +1407,75 @@ impl DivergentParams {
         self
     }
 
+    /// Use a structured ideation technique
+    pub fn with_technique(mut self, technique: IdeationTechnique) -> Self {
+        self.technique = Some(technique);
+        self
+    }
+
     /// Set the confidence threshold
     pub fn with_confidence(mut self, confidence: f64) -> Self {
         self.confidence = confidence.clamp(0.0, 1.0);
         self
     }
+
+    /// Cluster perspectives by theme and return the `size` best clusters
+    pub fn with_shortlist(mut self, size: usize) -> Self {
+        self.shortlist = Some(size);
+        self
+    }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+impl DivergentEvaluateParams {
+    /// Evaluate every perspective of a session against `criteria`
+    pub fn new(session_id: impl Into<String>, criteria: Vec<Criterion>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            thought_ids: Vec::new(),
+            criteria,
+            question: None,
+            winners: default_winners(),
+            decide: default_decide(),
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Evaluate only these perspective thoughts
+    pub fn with_thoughts(mut self, thought_ids: Vec<String>) -> Self {
+        self.thought_ids = thought_ids;
+        self
+    }
+
+    /// Set the question the winners should answer
+    pub fn with_question(mut self, question: impl Into<String>) -> Self {
+        self.question = Some(question.into());
+        self
+    }
+
+    /// Set how many ideas win
+    pub fn with_winners(mut self, winners: usize) -> Self {
+        self.winners = winners;
+        self
+    }
+
+    /// Do not hand the winners to decision mode
+    pub fn without_decision(mut self) -> Self {
+        self.decide = false;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langbase::LangbaseClient;
+    use crate::storage::SqliteStorage;
 
     // ============================================================================
     // Default Function Tests
@@ -661,6 +1697,7 @@ mod tests {
             novelty: 0.8,
             viability: 0.75,
             assumptions_challenged: Some(vec!["Assumption A".to_string()]),
+            technique: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -697,6 +1734,7 @@ mod tests {
             novelty: 0.7,
             viability: 0.8,
             assumptions_challenged: None,
+            technique: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -744,6 +1782,22 @@ mod tests {
         assert_eq!(response.synthesis, "Synthesis text");
     }
 
+    #[test]
+    fn test_divergent_output_schema() {
+        let schema = DivergentResponse::output_schema();
+        let valid = r#"{
+            "perspectives": [
+                {"thought": "P1", "novelty": 0.7, "viability": 0.8, "assumptions_challenged": ["A"]}
+            ],
+            "synthesis": "Synthesis text"
+        }"#;
+        assert!(schema.validate_completion(valid).is_ok());
+
+        let invalid = r#"{"perspectives": [{"thought": "P1", "novelty": 7, "viability": 0.8}], "synthesis": ""}"#;
+        let err = schema.validate_completion(invalid).unwrap_err();
+        assert_eq!(err.path, "$.perspectives[0].novelty");
+    }
+
     // ============================================================================
     // DivergentResult Tests
     // ============================================================================
@@ -759,6 +1813,7 @@ mod tests {
                 novelty: 0.85,
                 viability: 0.7,
                 assumptions_challenged: Some(vec!["Challenge 1".to_string()]),
+                technique: None,
             }],
             synthesis: "Final synthesis".to_string(),
             synthesis_thought_id: "t-synth".to_string(),
@@ -766,6 +1821,10 @@ mod tests {
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: Some("branch-1".to_string()),
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -811,6 +1870,10 @@ mod tests {
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: None,
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -825,8 +1888,11 @@ mod tests {
     /// Helper to create a test mode instance
     fn create_test_mode() -> DivergentMode {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LoggingConfig, PipeConfig,
-            RequestConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig,
+            TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -834,10 +1900,15 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             langbase: LangbaseConfig {
                 api_key: "test_key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -846,6 +1917,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -936,6 +2019,61 @@ mod tests {
         assert!(system_msg.content.contains("unconventional"));
     }
 
+    #[test]
+    fn test_build_technique_messages() {
+        let mode = create_test_mode();
+
+        let plain = DivergentParams::new("Content");
+        let messages = mode.build_technique_messages(&plain, &[], 3, &[]);
+        assert_eq!(messages.len(), 2);
+        assert!(!messages[0].content.contains("TECHNIQUE"));
+
+        let scamper = DivergentParams::new("Content").with_technique(IdeationTechnique::Scamper);
+        let messages = mode.build_technique_messages(&scamper, &[], 3, &[]);
+        assert!(messages[0].content.contains("TECHNIQUE: SCAMPER"));
+        assert_eq!(messages.len(), 2);
+
+        let stimulus =
+            DivergentParams::new("Content").with_technique(IdeationTechnique::RandomStimulus);
+        let stimuli = vec!["tide".to_string(), "zipper".to_string()];
+        let messages = mode.build_technique_messages(&stimulus, &[], 2, &stimuli);
+        assert!(messages[0].content.contains("TECHNIQUE: Random stimulus"));
+        assert_eq!(messages[1].content, "Stimulus words: tide, zipper");
+        // The content stays last
+        assert_eq!(messages[2].content, "Content");
+    }
+
+    #[test]
+    fn test_ideation_technique_serde() {
+        let params: DivergentParams = serde_json::from_value(serde_json::json!({
+            "content": "Content",
+            "technique": "reverse_brainstorming"
+        }))
+        .unwrap();
+        assert_eq!(
+            params.technique,
+            Some(IdeationTechnique::ReverseBrainstorming)
+        );
+        assert_eq!(IdeationTechnique::Triz.as_str(), "triz");
+        assert!(IdeationTechnique::Triz.prompt().contains("TRIZ"));
+        assert!(
+            serde_json::from_value::<DivergentParams>(serde_json::json!({
+                "content": "Content",
+                "technique": "mind_map"
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pick_stimuli_distinct() {
+        let stimuli = pick_stimuli(5);
+        assert_eq!(stimuli.len(), 5);
+        let unique: std::collections::HashSet<_> = stimuli.iter().collect();
+        assert_eq!(unique.len(), 5);
+        assert_eq!(pick_stimuli(100).len(), STIMULUS_WORDS.len());
+    }
+
     #[test]
     fn test_build_messages_with_both_flags() {
         let mode = create_test_mode();
@@ -1135,6 +2273,10 @@ mod tests {
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: Some("branch-123".to_string()),
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         assert_eq!(result.branch_id, Some("branch-123".to_string()));
@@ -1148,6 +2290,7 @@ mod tests {
             novelty: 0.5,
             viability: 0.5,
             assumptions_challenged: Some(vec![]),
+            technique: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -1225,6 +2368,7 @@ mod tests {
                 novelty: 0.9,
                 viability: 0.8,
                 assumptions_challenged: Some(vec!["Challenge".to_string()]),
+                technique: None,
             }],
             synthesis: "Synth".to_string(),
             synthesis_thought_id: "t-synth".to_string(),
@@ -1232,6 +2376,10 @@ mod tests {
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: Some("branch".to_string()),
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -1251,8 +2399,11 @@ mod tests {
     #[test]
     fn test_divergent_mode_new() {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LoggingConfig, PipeConfig,
-            RequestConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig,
+            TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -1260,10 +2411,15 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             langbase: LangbaseConfig {
                 api_key: "test_key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1272,6 +2428,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1286,8 +2454,11 @@ mod tests {
     #[test]
     fn test_divergent_mode_new_with_custom_pipe() {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LoggingConfig, PipeConfig,
-            RequestConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig,
+            TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -1300,10 +2471,15 @@ mod tests {
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             langbase: LangbaseConfig {
                 api_key: "api_key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1312,6 +2488,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes,
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         };
 
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1723,6 +2911,10 @@ Here's the response:
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: None,
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -1911,6 +3103,7 @@ Here's the response:
                     novelty: 0.5,
                     viability: 0.8,
                     assumptions_challenged: None,
+                    technique: None,
                 },
                 PerspectiveInfo {
                     thought_id: "p-1".to_string(),
@@ -1918,6 +3111,7 @@ Here's the response:
                     novelty: 0.9,
                     viability: 0.6,
                     assumptions_challenged: None,
+                    technique: None,
                 },
             ],
             synthesis: "Test".to_string(),
@@ -1926,6 +3120,10 @@ Here's the response:
             most_viable_perspective: 0,
             most_novel_perspective: 1,
             branch_id: None,
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         assert_eq!(result.most_viable_perspective, 0);
@@ -1956,6 +3154,7 @@ Here's the response:
             novelty: 0.5,
             viability: 0.5,
             assumptions_challenged: None,
+            technique: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -1974,9 +3173,269 @@ Here's the response:
             most_viable_perspective: 0,
             most_novel_perspective: 0,
             branch_id: None,
+            technique: None,
+            stimuli: Vec::new(),
+            duplicates_merged: 0,
+            shortlist: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(!json.contains("\"branch_id\""));
     }
+
+    // ============================================================================
+    // Perspective Merging Tests
+    // ============================================================================
+
+    fn perspective(thought: &str, novelty: f64, viability: f64) -> Perspective {
+        Perspective {
+            thought: thought.to_string(),
+            novelty,
+            viability,
+            assumptions_challenged: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_perspectives_folds_paraphrase_into_better_one() {
+        let mode = create_test_mode()
+            .with_embeddings(Arc::new(crate::langbase::LocalEmbeddingProvider::default()));
+        let perspectives = vec![
+            perspective("Ask the end users what outcome they notice first", 0.4, 0.5),
+            perspective(
+                "Invert the problem and list what guarantees failure",
+                0.8,
+                0.7,
+            ),
+            perspective("Ask the end users what outcome they notice first", 0.6, 0.8),
+        ];
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let kept = rt.block_on(mode.merge_perspectives(perspectives));
+
+        assert_eq!(kept.len(), 2);
+        assert_eq!(
+            kept[0].0.thought,
+            "Invert the problem and list what guarantees failure"
+        );
+        assert!(kept[0].1.is_empty());
+        assert_eq!(kept[1].0.viability, 0.8);
+        assert_eq!(
+            kept[1].1,
+            vec!["Ask the end users what outcome they notice first".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_merge_perspectives_without_embeddings_keeps_all() {
+        let mode = create_test_mode();
+        let perspectives = vec![
+            perspective("Same idea", 0.4, 0.5),
+            perspective("Same idea", 0.6, 0.8),
+        ];
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let kept = rt.block_on(mode.merge_perspectives(perspectives));
+
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|(_, merged)| merged.is_empty()));
+    }
+
+    // ============================================================================
+    // Clustering Tests
+    // ============================================================================
+
+    #[test]
+    fn test_cluster_by_theme_groups_similar_vectors() {
+        let vectors = vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.9, 0.1],
+            vec![0.1, 0.9],
+        ];
+        let scores = [0.5, 0.9, 0.7, 0.4];
+        // The best-scoring item founds the first cluster
+        assert_eq!(cluster_by_theme(&vectors, &scores, 0.8), vec![1, 0, 1, 0]);
+        assert_eq!(cluster_by_theme(&vectors, &scores, 1.1), vec![2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn test_rank_clusters_scores_and_orders() {
+        let perspectives = vec![
+            perspective("Cache hot reads", 0.2, 0.9),
+            perspective("Rewrite the storage engine", 0.9, 0.2),
+            perspective("Cache at the edge", 0.4, 0.9),
+        ];
+        let clusters = rank_clusters(&perspectives, &[0, 1, 0]);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].rank, 1);
+        assert_eq!(clusters[0].perspectives, vec![2, 0]);
+        assert_eq!(clusters[0].theme, "Cache at the edge");
+        assert!((clusters[0].novelty - 0.3).abs() < 1e-9);
+        assert!((clusters[0].feasibility - 0.9).abs() < 1e-9);
+        assert!((clusters[0].score - 0.6).abs() < 1e-9);
+        assert_eq!(clusters[1].rank, 2);
+        assert_eq!(clusters[1].perspectives, vec![1]);
+    }
+
+    #[test]
+    fn test_cluster_perspectives_by_embedding() {
+        let mode = create_test_mode()
+            .with_embeddings(Arc::new(crate::langbase::LocalEmbeddingProvider::default()));
+        let perspectives = vec![
+            perspective("Cache the hot read queries in memory", 0.4, 0.9),
+            perspective("Shard the database by customer region", 0.7, 0.5),
+            perspective("Cache the hot read queries at the edge", 0.6, 0.8),
+        ];
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let assignment = rt.block_on(mode.cluster_perspectives(&perspectives));
+        assert_eq!(assignment[0], assignment[2]);
+        assert_ne!(assignment[0], assignment[1]);
+
+        // Without embeddings each perspective stands alone
+        let assignment = rt.block_on(create_test_mode().cluster_perspectives(&perspectives));
+        assert_eq!(assignment, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_divergent_params_with_shortlist() {
+        let params = DivergentParams::new("Ideas").with_shortlist(2);
+        assert_eq!(params.shortlist, Some(2));
+
+        let json = serde_json::to_string(&DivergentParams::new("Ideas")).unwrap();
+        assert!(!json.contains("shortlist"));
+    }
+
+    // ============================================================================
+    // Idea Evaluation Tests
+    // ============================================================================
+
+    fn criterion(name: &str, weight: f64) -> Criterion {
+        Criterion {
+            name: name.to_string(),
+            weight,
+            description: None,
+        }
+    }
+
+    fn entry(idea: usize, impact: f64, effort: f64) -> EvaluateEntry {
+        EvaluateEntry {
+            idea,
+            scores: HashMap::from([
+                ("impact".to_string(), impact),
+                ("effort".to_string(), effort),
+            ]),
+            rationale: format!("Idea {}", idea),
+        }
+    }
+
+    #[test]
+    fn test_score_ideas_weights_and_ranks() {
+        let ideas = vec![
+            Thought::new("s1", "Survey users", "divergent"),
+            Thought::new("s1", "Invert the problem", "divergent"),
+            Thought::new("s1", "Do nothing", "divergent"),
+        ];
+        let criteria = vec![criterion("impact", 3.0), criterion("effort", 1.0)];
+        let evaluations = score_ideas(
+            &ideas,
+            &criteria,
+            vec![entry(1, 0.4, 1.0), entry(2, 0.8, 0.4), entry(7, 1.0, 1.0)],
+        );
+
+        assert_eq!(evaluations.len(), 3);
+        assert_eq!(evaluations[0].content, "Invert the problem");
+        assert_eq!(evaluations[0].rank, 1);
+        assert!((evaluations[0].score - 0.7).abs() < 1e-9);
+        assert_eq!(evaluations[0].rationale, "Idea 2");
+        assert!((evaluations[1].score - 0.55).abs() < 1e-9);
+        // Left out by the pipe
+        assert_eq!(evaluations[2].content, "Do nothing");
+        assert_eq!(evaluations[2].score, 0.0);
+        assert_eq!(evaluations[2].criteria_scores["impact"], 0.0);
+    }
+
+    #[test]
+    fn test_score_ideas_clamps_scores() {
+        let ideas = vec![Thought::new("s1", "Idea", "divergent")];
+        let evaluations = score_ideas(
+            &ideas,
+            &[criterion("impact", 1.0)],
+            vec![entry(1, 1.5, 0.0)],
+        );
+        assert_eq!(evaluations[0].score, 1.0);
+    }
+
+    #[test]
+    fn test_validate_evaluate_params() {
+        let criteria = vec![criterion("impact", 1.0)];
+        assert!(
+            validate_evaluate_params(&DivergentEvaluateParams::new("s1", criteria.clone())).is_ok()
+        );
+        assert!(validate_evaluate_params(&DivergentEvaluateParams::new("s1", Vec::new())).is_err());
+        assert!(validate_evaluate_params(&DivergentEvaluateParams::new(
+            "s1",
+            vec![criterion("impact", 0.0)]
+        ))
+        .is_err());
+        assert!(validate_evaluate_params(&DivergentEvaluateParams::new(
+            "s1",
+            vec![criterion(" ", 1.0)]
+        ))
+        .is_err());
+        assert!(validate_evaluate_params(
+            &DivergentEvaluateParams::new("s1", criteria.clone()).with_winners(0)
+        )
+        .is_err());
+        assert!(validate_evaluate_params(
+            &DivergentEvaluateParams::new("s1", criteria).with_winners(7)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_evaluate_decision_params() {
+        let ideas = vec![
+            Thought::new("s1", "Survey users", "divergent"),
+            Thought::new("s1", "Invert the problem", "divergent"),
+        ];
+        let params = DivergentEvaluateParams::new("s1", vec![criterion("impact", 1.0)]);
+        let evaluations = score_ideas(
+            &ideas,
+            &params.criteria,
+            vec![entry(1, 0.2, 0.0), entry(2, 0.9, 0.0)],
+        );
+        let mut result = DivergentEvaluateResult {
+            session_id: "s1".to_string(),
+            question: "Reduce churn".to_string(),
+            winners: evaluations.iter().map(|e| e.thought_id.clone()).collect(),
+            evaluations,
+            decision: None,
+        };
+
+        let decision = result.decision_params(&params).unwrap();
+        assert_eq!(decision.question, "Reduce churn");
+        assert_eq!(decision.options, vec!["Invert the problem", "Survey users"]);
+        assert_eq!(decision.criteria.len(), 1);
+        assert_eq!(decision.session_id.as_deref(), Some("s1"));
+
+        assert!(result
+            .decision_params(&params.clone().without_decision())
+            .is_none());
+        result.winners.truncate(1);
+        assert!(result.decision_params(&params).is_none());
+    }
+
+    #[test]
+    fn test_is_perspective() {
+        let perspective = Thought::new("s1", "Idea", "divergent")
+            .with_metadata(serde_json::json!({ "perspective_index": 0 }));
+        let synthesis = Thought::new("s1", "Both", "divergent")
+            .with_metadata(serde_json::json!({ "is_synthesis": true }));
+        assert!(is_perspective(&perspective));
+        assert!(!is_perspective(&synthesis));
+        assert!(!is_perspective(&Thought::new("s1", "Step", "linear")));
+    }
 }
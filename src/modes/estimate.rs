@@ -0,0 +1,728 @@
+//! Estimate mode - Fermi estimation with local arithmetic.
+//!
+//! This module answers quantitative questions by decomposition:
+//! - The pipe breaks the question into factors, optionally into sub-factors,
+//!   and estimates each leaf with a 90% range
+//! - The aggregate and its bounds are computed here, never taken from the
+//!   pipe: worst-case bounds from the range ends, and a likely interval that
+//!   treats the factors as independent log-normal quantities
+//! - Numbers the pipe stated for the answer or for broken-down factors are
+//!   checked against the computed ones and mismatches reported
+//!
+//! The factor tree is stored in the graph tables: the question is the root
+//! node and each factor a node aggregated by its parent.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::FERMI_ESTIMATE_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{EdgeType, GraphEdge, GraphNode, Invocation, NodeType, SharedStorage};
+
+/// Relative difference above which a stated number counts as wrong.
+pub const ARITHMETIC_TOLERANCE: f64 = 0.05;
+
+/// z-score of the 95th percentile; factor ranges are 90% intervals.
+const Z_90: f64 = 1.6448536269514722;
+
+/// Metadata key under which factor details are stored on graph nodes.
+const FACTOR_METADATA_KEY: &str = "fermi_factor";
+
+/// Input parameters for Fermi estimation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateParams {
+    /// The quantitative question to estimate
+    pub question: String,
+    /// Optional known facts or constraints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// How a factor combines with its siblings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorOperation {
+    /// Multiplies the product of its siblings
+    #[default]
+    Multiply,
+    /// Divides the product of its siblings
+    Divide,
+}
+
+/// One factor of the decomposition
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EstimateFactor {
+    /// Graph node holding the factor
+    pub node_id: String,
+    /// Factor this one was broken out of, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_node_id: Option<String>,
+    /// What the factor measures
+    pub name: String,
+    /// How the factor combines with its siblings
+    pub operation: FactorOperation,
+    /// Low end of the range
+    pub low: f64,
+    /// Point estimate
+    pub estimate: f64,
+    /// High end of the range
+    pub high: f64,
+    /// Unit of the factor
+    pub unit: String,
+    /// Why the numbers are plausible
+    pub rationale: String,
+    /// Whether the numbers were computed from sub-factors
+    pub derived: bool,
+    /// Nesting depth, 1 for top-level factors
+    pub depth: u32,
+}
+
+/// A number the pipe stated that the local arithmetic disagrees with
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArithmeticCheck {
+    /// Graph node the number belongs to (the root node for the answer)
+    pub node_id: String,
+    /// Factor name, or "answer"
+    pub name: String,
+    /// Number the pipe stated
+    pub stated: f64,
+    /// Number computed from the factors
+    pub computed: f64,
+}
+
+/// Result of Fermi estimation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EstimateResult {
+    /// The session ID
+    pub session_id: String,
+    /// The question estimated
+    pub question: String,
+    /// Root graph node holding the question
+    pub root_node_id: String,
+    /// Factors in decomposition order, parents before their sub-factors
+    pub factors: Vec<EstimateFactor>,
+    /// Unit of the answer
+    pub unit: String,
+    /// Product of the factor estimates
+    pub estimate: f64,
+    /// Lower bound with every factor at the unfavorable end of its range
+    pub low: f64,
+    /// Upper bound with every factor at the favorable end of its range
+    pub high: f64,
+    /// Low end of the 90% interval, treating factors as independent
+    /// log-normal quantities
+    pub likely_low: f64,
+    /// High end of the 90% interval
+    pub likely_high: f64,
+    /// Answer the pipe computed itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipe_answer: Option<f64>,
+    /// Stated numbers that disagree with the computed ones
+    pub arithmetic_checks: Vec<ArithmeticCheck>,
+    /// Confidence in the decomposition (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Estimate mode handler for Fermi estimation.
+#[derive(Clone)]
+pub struct EstimateMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Linear pipe estimating factors
+    linear_pipe: String,
+}
+
+impl EstimateMode {
+    /// Create a new estimate mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            linear_pipe: config.pipes.linear.clone(),
+        }
+    }
+
+    /// Estimate a quantity by decomposing it into factors
+    pub async fn estimate(&self, params: EstimateParams) -> AppResult<EstimateResult> {
+        let start = Instant::now();
+
+        if params.question.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "question".to_string(),
+                reason: "Question cannot be empty".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "estimate")
+            .await?;
+        debug!(session_id = %session.id, "Processing estimate request");
+
+        let pipe = self.core.select_pipe(&self.linear_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.estimate",
+            serialize_for_log(&params, "reasoning.estimate input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let result =
+            self.call(&pipe, &params)
+                .await
+                .and_then(|(mut response, cache_hit, fallback)| {
+                    let tree =
+                        FactorTree::build(&session.id, std::mem::take(&mut response.factors))?;
+                    Ok((tree, (response, cache_hit, fallback)))
+                });
+        let (tree, (response, cache_hit, fallback)) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let confidence = response.confidence.clamp(0.0, 1.0);
+        let total = tree.total();
+
+        let root = GraphNode::new(&session.id, &params.question)
+            .with_type(NodeType::Root)
+            .as_root()
+            .with_score(confidence)
+            .with_metadata(serde_json::json!({
+                "fermi_estimate": {
+                    "estimate": total.estimate,
+                    "low": total.low,
+                    "high": total.high,
+                    "unit": response.unit,
+                }
+            }));
+        let mut arithmetic_checks = tree.checks();
+        if let Some(answer) = response.answer {
+            if disagrees(answer, total.estimate) {
+                arithmetic_checks.push(ArithmeticCheck {
+                    node_id: root.id.clone(),
+                    name: "answer".to_string(),
+                    stated: answer,
+                    computed: total.estimate,
+                });
+            }
+        }
+        let (factor_nodes, edges) = tree.graph(&session.id, &root);
+        let mut nodes = vec![root.clone()];
+        nodes.extend(factor_nodes);
+        self.core.storage().create_graph_nodes_batch(&nodes).await?;
+        self.core.storage().create_graph_edges_batch(&edges).await?;
+
+        let (likely_low, likely_high) = total.likely();
+        let result = EstimateResult {
+            session_id: session.id.clone(),
+            question: params.question.clone(),
+            root_node_id: root.id,
+            factors: tree.factors,
+            unit: response.unit,
+            estimate: total.estimate,
+            low: total.low,
+            high: total.high,
+            likely_low,
+            likely_high,
+            pipe_answer: response.answer,
+            arithmetic_checks,
+            confidence,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.estimate output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            factors = result.factors.len(),
+            estimate = result.estimate,
+            checks = result.arithmetic_checks.len(),
+            latency_ms = latency,
+            "Estimate computed"
+        );
+
+        Ok(result)
+    }
+
+    async fn call(
+        &self,
+        pipe: &str,
+        params: &EstimateParams,
+    ) -> AppResult<(EstimateResponse, bool, Option<String>)> {
+        let mut prompt = format!("QUESTION: {}", params.question);
+        if let Some(ref context) = params.context {
+            prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+        }
+        let messages = vec![
+            Message::system(FERMI_ESTIMATE_PROMPT),
+            Message::user(prompt),
+        ];
+        let request = PipeRequest::new(pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
+
+        let json_str = extract_json_from_completion(&response.completion)
+            .map_err(|e| ToolError::Reasoning { message: e })?;
+        let estimate: EstimateResponse =
+            serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                message: format!("Failed to parse estimate: {}", e),
+            })?;
+        if estimate.factors.is_empty() {
+            return Err(ToolError::Reasoning {
+                message: "Estimate has no factors".to_string(),
+            }
+            .into());
+        }
+        Ok((estimate, response.cache_hit, response.fallback))
+    }
+}
+
+/// Whether `stated` is off from `computed` by more than the tolerance
+fn disagrees(stated: f64, computed: f64) -> bool {
+    !stated.is_finite()
+        || (stated - computed).abs() > ARITHMETIC_TOLERANCE * computed.abs().max(f64::MIN_POSITIVE)
+}
+
+/// A quantity with its worst-case range and log-space variance
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Quantity {
+    estimate: f64,
+    low: f64,
+    high: f64,
+    log_variance: f64,
+}
+
+impl Quantity {
+    /// The neutral element of multiplication
+    const ONE: Quantity = Quantity {
+        estimate: 1.0,
+        low: 1.0,
+        high: 1.0,
+        log_variance: 0.0,
+    };
+
+    /// A leaf whose range is a 90% interval of a log-normal quantity
+    fn leaf(low: f64, estimate: f64, high: f64) -> Self {
+        let sigma = (high / low).ln() / (2.0 * Z_90);
+        Self {
+            estimate,
+            low,
+            high,
+            log_variance: sigma * sigma,
+        }
+    }
+
+    /// Combine `other` into this product
+    fn apply(self, operation: FactorOperation, other: Quantity) -> Self {
+        let (estimate, low, high) = match operation {
+            FactorOperation::Multiply => (
+                self.estimate * other.estimate,
+                self.low * other.low,
+                self.high * other.high,
+            ),
+            FactorOperation::Divide => (
+                self.estimate / other.estimate,
+                self.low / other.high,
+                self.high / other.low,
+            ),
+        };
+        Self {
+            estimate,
+            low,
+            high,
+            log_variance: self.log_variance + other.log_variance,
+        }
+    }
+
+    /// The 90% interval around the estimate
+    fn likely(&self) -> (f64, f64) {
+        let spread = (Z_90 * self.log_variance.sqrt()).exp();
+        (self.estimate / spread, self.estimate * spread)
+    }
+}
+
+/// The factors with their computed quantities
+#[derive(Debug)]
+struct FactorTree {
+    factors: Vec<EstimateFactor>,
+    quantities: Vec<Quantity>,
+    /// Estimates the pipe stated for factors that were broken down
+    stated: Vec<Option<f64>>,
+    top: Vec<usize>,
+}
+
+impl FactorTree {
+    /// Resolve parents and compute every factor bottom-up.
+    ///
+    /// A factor whose parent is unknown or listed after it is treated as
+    /// top-level, so the tree cannot contain cycles.
+    fn build(session_id: &str, drafts: Vec<FactorDraft>) -> AppResult<Self> {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut parents: Vec<Option<usize>> = Vec::with_capacity(drafts.len());
+        let mut children: Vec<Vec<usize>> = Vec::with_capacity(drafts.len());
+        let mut factors: Vec<EstimateFactor> = Vec::with_capacity(drafts.len());
+        let mut leaves: Vec<(f64, f64, f64)> = Vec::with_capacity(drafts.len());
+        let mut top = Vec::new();
+
+        for draft in drafts {
+            let i = factors.len();
+            let parent = draft.parent.as_ref().and_then(|p| index.get(p).copied());
+            if draft.parent.is_some() && parent.is_none() {
+                debug!(key = %draft.key, parent = ?draft.parent, "Unknown factor parent, treating factor as top-level");
+            }
+            match parent {
+                Some(p) => children[p].push(i),
+                None => top.push(i),
+            }
+            if !draft.key.is_empty() {
+                index.entry(draft.key).or_insert(i);
+            }
+            let depth = parent.map_or(1, |p| factors[p].depth + 1);
+            factors.push(EstimateFactor {
+                node_id: GraphNode::new(session_id, "").id,
+                parent_node_id: parent.map(|p| factors[p].node_id.clone()),
+                name: draft.name,
+                operation: draft.operation,
+                low: draft.low,
+                estimate: draft.estimate,
+                high: draft.high,
+                unit: draft.unit,
+                rationale: draft.rationale,
+                derived: false,
+                depth,
+            });
+            parents.push(parent);
+            children.push(Vec::new());
+            leaves.push((draft.low, draft.estimate, draft.high));
+        }
+
+        // Children always come after their parent, so walking backwards
+        // computes every sub-factor before the factor it belongs to
+        let mut quantities = vec![Quantity::ONE; factors.len()];
+        let mut stated = vec![None; factors.len()];
+        for i in (0..factors.len()).rev() {
+            if children[i].is_empty() {
+                let (low, estimate, high) = leaves[i];
+                let valid = [low, estimate, high].iter().all(|v| v.is_finite())
+                    && 0.0 < low
+                    && low <= estimate
+                    && estimate <= high;
+                if !valid {
+                    return Err(ToolError::Reasoning {
+                        message: format!(
+                            "Factor '{}' needs 0 < low <= estimate <= high, got {} / {} / {}",
+                            factors[i].name, low, estimate, high
+                        ),
+                    }
+                    .into());
+                }
+                quantities[i] = Quantity::leaf(low, estimate, high);
+            } else {
+                let quantity = children[i].iter().fold(Quantity::ONE, |acc, &c| {
+                    acc.apply(factors[c].operation, quantities[c])
+                });
+                quantities[i] = quantity;
+                stated[i] = Some(factors[i].estimate);
+                let factor = &mut factors[i];
+                factor.low = quantity.low;
+                factor.estimate = quantity.estimate;
+                factor.high = quantity.high;
+                factor.derived = true;
+            }
+        }
+
+        Ok(Self {
+            factors,
+            quantities,
+            stated,
+            top,
+        })
+    }
+
+    /// The answer: every top-level factor combined
+    fn total(&self) -> Quantity {
+        self.top.iter().fold(Quantity::ONE, |acc, &i| {
+            acc.apply(self.factors[i].operation, self.quantities[i])
+        })
+    }
+
+    /// Broken-down factors whose stated estimate disagrees with their
+    /// sub-factors
+    fn checks(&self) -> Vec<ArithmeticCheck> {
+        self.factors
+            .iter()
+            .zip(&self.stated)
+            .filter_map(|(factor, stated)| {
+                let stated = (*stated)?;
+                disagrees(stated, factor.estimate).then(|| ArithmeticCheck {
+                    node_id: factor.node_id.clone(),
+                    name: factor.name.clone(),
+                    stated,
+                    computed: factor.estimate,
+                })
+            })
+            .collect()
+    }
+
+    /// Graph nodes for the factors and edges from each parent to them
+    fn graph(&self, session_id: &str, root: &GraphNode) -> (Vec<GraphNode>, Vec<GraphEdge>) {
+        let mut nodes = Vec::with_capacity(self.factors.len());
+        let mut edges = Vec::with_capacity(self.factors.len());
+        for factor in &self.factors {
+            let mut node = GraphNode::new(session_id, &factor.name)
+                .with_type(if factor.derived {
+                    NodeType::Aggregation
+                } else {
+                    NodeType::Thought
+                })
+                .with_depth(factor.depth as i32)
+                .with_metadata(serde_json::json!({
+                    FACTOR_METADATA_KEY: {
+                        "operation": factor.operation,
+                        "low": factor.low,
+                        "estimate": factor.estimate,
+                        "high": factor.high,
+                        "unit": factor.unit,
+                        "rationale": factor.rationale,
+                    }
+                }));
+            node.id = factor.node_id.clone();
+            let parent = factor.parent_node_id.as_deref().unwrap_or(&root.id);
+            edges.push(
+                GraphEdge::new(session_id, parent, &factor.node_id).with_type(EdgeType::Aggregates),
+            );
+            nodes.push(node);
+        }
+        (nodes, edges)
+    }
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EstimateResponse {
+    factors: Vec<FactorDraft>,
+    #[serde(default)]
+    unit: String,
+    #[serde(default)]
+    answer: Option<f64>,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FactorDraft {
+    #[serde(default)]
+    key: String,
+    name: String,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    operation: FactorOperation,
+    low: f64,
+    estimate: f64,
+    high: f64,
+    #[serde(default)]
+    unit: String,
+    #[serde(default)]
+    rationale: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(
+        key: &str,
+        parent: Option<&str>,
+        operation: FactorOperation,
+        range: [f64; 3],
+    ) -> FactorDraft {
+        FactorDraft {
+            key: key.to_string(),
+            name: format!("factor {}", key),
+            parent: parent.map(str::to_string),
+            operation,
+            low: range[0],
+            estimate: range[1],
+            high: range[2],
+            unit: String::new(),
+            rationale: String::new(),
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_estimate_params_defaults() {
+        let params: EstimateParams =
+            serde_json::from_value(serde_json::json!({"question": "How many?"})).unwrap();
+        assert_eq!(params.question, "How many?");
+        assert!(params.context.is_none());
+        assert!(params.session_id.is_none());
+        assert!(params.generation.is_empty());
+    }
+
+    #[test]
+    fn test_total_multiplies_and_divides() {
+        use FactorOperation::*;
+        let tree = FactorTree::build(
+            "s1",
+            vec![
+                draft("a", None, Multiply, [100.0, 200.0, 400.0]),
+                draft("b", None, Divide, [2.0, 4.0, 8.0]),
+                draft("c", None, Multiply, [3.0, 3.0, 3.0]),
+            ],
+        )
+        .unwrap();
+        let total = tree.total();
+        assert!(close(total.estimate, 150.0));
+        // Dividing by the high end of b gives the lowest answer
+        assert!(close(total.low, 100.0 / 8.0 * 3.0));
+        assert!(close(total.high, 400.0 / 2.0 * 3.0));
+
+        // Independent errors partly cancel, so the likely interval sits
+        // inside the worst case but still around the estimate
+        let (likely_low, likely_high) = total.likely();
+        assert!(total.low < likely_low && likely_low < total.estimate);
+        assert!(total.estimate < likely_high && likely_high < total.high);
+    }
+
+    #[test]
+    fn test_exact_factors_have_no_spread() {
+        let tree = FactorTree::build(
+            "s1",
+            vec![draft("a", None, FactorOperation::Multiply, [5.0, 5.0, 5.0])],
+        )
+        .unwrap();
+        let (likely_low, likely_high) = tree.total().likely();
+        assert!(close(likely_low, 5.0));
+        assert!(close(likely_high, 5.0));
+    }
+
+    #[test]
+    fn test_sub_factors_replace_stated_numbers() {
+        use FactorOperation::*;
+        let tree = FactorTree::build(
+            "s1",
+            vec![
+                draft("a", None, Multiply, [10.0, 10.0, 10.0]),
+                // Stated as 50, but its sub-factors give 4 * 10 = 40
+                draft("b", None, Multiply, [1.0, 50.0, 100.0]),
+                draft("b1", Some("b"), Multiply, [2.0, 4.0, 8.0]),
+                draft("b2", Some("b"), Multiply, [10.0, 10.0, 10.0]),
+            ],
+        )
+        .unwrap();
+        let b = &tree.factors[1];
+        assert!(b.derived);
+        assert!(close(b.estimate, 40.0));
+        assert!(close(b.low, 20.0));
+        assert_eq!(tree.factors[2].depth, 2);
+        assert_eq!(tree.factors[2].parent_node_id.as_ref(), Some(&b.node_id));
+        assert!(close(tree.total().estimate, 400.0));
+
+        let checks = tree.checks();
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].node_id, b.node_id);
+        assert_eq!(checks[0].stated, 50.0);
+        assert!(close(checks[0].computed, 40.0));
+    }
+
+    #[test]
+    fn test_late_or_unknown_parents_become_top_level() {
+        use FactorOperation::*;
+        let tree = FactorTree::build(
+            "s1",
+            vec![
+                draft("a", Some("b"), Multiply, [2.0, 2.0, 2.0]),
+                draft("b", Some("a"), Multiply, [3.0, 3.0, 3.0]),
+                draft("c", Some("missing"), Multiply, [5.0, 5.0, 5.0]),
+            ],
+        )
+        .unwrap();
+        // b really is a sub-factor of a; a and c are top-level
+        assert_eq!(tree.top, [0, 2]);
+        assert!(tree.factors[0].derived);
+        assert!(close(tree.total().estimate, 15.0));
+    }
+
+    #[test]
+    fn test_invalid_leaf_range_is_rejected() {
+        for range in [
+            [5.0, 2.0, 8.0],
+            [0.0, 1.0, 2.0],
+            [1.0, f64::NAN, 2.0],
+            [1.0, 3.0, 2.0],
+        ] {
+            let err = FactorTree::build(
+                "s1",
+                vec![draft("a", None, FactorOperation::Multiply, range)],
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("factor a"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_disagrees() {
+        assert!(!disagrees(102.0, 100.0));
+        assert!(disagrees(110.0, 100.0));
+        assert!(disagrees(f64::NAN, 100.0));
+    }
+
+    #[test]
+    fn test_graph_links_factors_to_parents() {
+        use FactorOperation::*;
+        let tree = FactorTree::build(
+            "s1",
+            vec![
+                draft("a", None, Multiply, [1.0, 2.0, 3.0]),
+                draft("a1", Some("a"), Divide, [1.0, 2.0, 3.0]),
+            ],
+        )
+        .unwrap();
+        let root = GraphNode::new("s1", "question");
+        let (nodes, edges) = tree.graph("s1", &root);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].id, tree.factors[0].node_id);
+        assert_eq!(nodes[0].node_type, NodeType::Aggregation);
+        assert_eq!(nodes[1].node_type, NodeType::Thought);
+        assert_eq!(edges[0].from_node, root.id);
+        assert_eq!(edges[1].from_node, nodes[0].id);
+        assert_eq!(edges[1].to_node, nodes[1].id);
+        assert!(edges.iter().all(|e| e.edge_type == EdgeType::Aggregates));
+        let metadata = nodes[1].metadata.as_ref().unwrap();
+        assert_eq!(metadata[FACTOR_METADATA_KEY]["operation"], "divide");
+    }
+}
@@ -7,6 +7,7 @@
 //! - Bayesian probability updates
 //! - Uncertainty quantification with entropy
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use tracing::{debug, error, info, warn};
@@ -14,11 +15,12 @@ use tracing::{debug, error, info, warn};
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
 use crate::prompts::{BAYESIAN_UPDATER_PROMPT, EVIDENCE_ASSESSOR_PROMPT};
+use crate::providers::SharedBackend;
 use crate::storage::{
     EvidenceAssessment as StoredEvidence, Invocation, ProbabilityUpdate as StoredProbability,
-    SqliteStorage, Storage,
+    SharedStorage,
 };
 
 // ============================================================================
@@ -38,6 +40,9 @@ pub struct EvidenceParams {
     /// Additional context for the assessment.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
+    /// Model parameter overrides for the pipe call.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Evidence input item for assessment.
@@ -96,6 +101,9 @@ pub struct ProbabilisticParams {
     /// Optional session ID for context persistence.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call.
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Evidence item for Bayesian updating.
@@ -282,7 +290,7 @@ struct Interpretation {
 // ============================================================================
 
 /// Result of evidence assessment.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EvidenceResult {
     /// Unique assessment ID.
     pub assessment_id: String,
@@ -306,7 +314,7 @@ pub struct EvidenceResult {
 }
 
 /// Overall support level for a claim.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SupportLevel {
     /// Support level (strong, moderate, weak, insufficient, contradictory).
     pub level: String,
@@ -317,7 +325,7 @@ pub struct SupportLevel {
 }
 
 /// Analysis of a single piece of evidence.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EvidenceAnalysis {
     /// Evidence identifier.
     pub evidence_id: String,
@@ -336,7 +344,7 @@ pub struct EvidenceAnalysis {
 }
 
 /// Inferential chain from evidence to claim.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct InferentialChain {
     /// Primary reasoning chain.
     pub primary_chain: Vec<String>,
@@ -347,7 +355,7 @@ pub struct InferentialChain {
 }
 
 /// Weakness in an inferential chain.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChainWeakness {
     /// From node.
     pub from: String,
@@ -360,7 +368,7 @@ pub struct ChainWeakness {
 }
 
 /// Contradiction between evidence items.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EvidenceContradiction {
     /// First evidence item.
     pub evidence_a: String,
@@ -373,7 +381,7 @@ pub struct EvidenceContradiction {
 }
 
 /// Gap in evidence coverage.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Gap {
     /// Description of what's missing.
     pub gap: String,
@@ -384,7 +392,7 @@ pub struct Gap {
 }
 
 /// Result of Bayesian probability update.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProbabilisticResult {
     /// Unique update ID.
     pub update_id: String,
@@ -408,7 +416,7 @@ pub struct ProbabilisticResult {
 }
 
 /// Confidence interval for probability.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProbabilityInterval {
     /// Lower bound.
     pub lower: f64,
@@ -419,7 +427,7 @@ pub struct ProbabilityInterval {
 }
 
 /// Single Bayesian update step.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BayesianUpdateStep {
     /// Evidence description.
     pub evidence: String,
@@ -432,7 +440,7 @@ pub struct BayesianUpdateStep {
 }
 
 /// Uncertainty metrics for probability assessment.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UncertaintyMetrics {
     /// Shannon entropy before updates.
     pub entropy_before: f64,
@@ -443,7 +451,7 @@ pub struct UncertaintyMetrics {
 }
 
 /// Human interpretation of probability.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ProbabilityInterpretation {
     /// Verbal probability (almost_certain, highly_likely, likely, possible, unlikely, etc.).
     pub verbal: String,
@@ -460,7 +468,7 @@ pub struct ProbabilityInterpretation {
 /// Evidence assessment mode handler.
 #[derive(Clone)]
 pub struct EvidenceMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// Consolidated pipe name for decision framework operations (prompts passed dynamically).
     decision_framework_pipe: String,
@@ -468,9 +476,13 @@ pub struct EvidenceMode {
 
 impl EvidenceMode {
     /// Create a new evidence mode handler.
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend),
             decision_framework_pipe: config
                 .pipes
                 .evidence
@@ -486,6 +498,7 @@ impl EvidenceMode {
 
         // Validate input
         self.validate_evidence_params(&params)?;
+        params.generation.validate()?;
 
         // Get or create session
         let session = self
@@ -498,17 +511,20 @@ impl EvidenceMode {
         // Build messages for Langbase
         let messages = self.build_evidence_messages(&params);
 
+        let decision_framework_pipe = self.core.select_pipe(&self.decision_framework_pipe);
+
         // Create invocation log
         let mut invocation = Invocation::new(
             "reasoning.assess_evidence",
             serialize_for_log(&params, "reasoning.assess_evidence input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.decision_framework_pipe);
+        .with_pipe(&decision_framework_pipe);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.decision_framework_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request =
+            PipeRequest::new(&decision_framework_pipe, messages).with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -517,6 +533,9 @@ impl EvidenceMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let evidence_response = self.parse_evidence_response(&response.completion)?;
@@ -594,6 +613,8 @@ impl EvidenceMode {
         .with_contradictions(serde_json::to_value(&result.contradictions).unwrap_or_default())
         .with_gaps(serde_json::to_value(&result.gaps).unwrap_or_default())
         .with_recommendations(serde_json::to_value(&result.recommendations).unwrap_or_default());
+        // Store under the ID the caller sees so the assessment can be referenced later
+        stored_evidence.id = assessment_id.clone();
 
         if let Some(chain) = &result.chain_analysis {
             stored_evidence = stored_evidence
@@ -640,6 +661,7 @@ impl EvidenceMode {
 
         // Validate input
         self.validate_probabilistic_params(&params)?;
+        params.generation.validate()?;
 
         // Get or create session
         let session = self
@@ -652,17 +674,20 @@ impl EvidenceMode {
         // Build messages for Langbase
         let messages = self.build_probabilistic_messages(&params);
 
+        let decision_framework_pipe = self.core.select_pipe(&self.decision_framework_pipe);
+
         // Create invocation log
         let mut invocation = Invocation::new(
             "reasoning.probabilistic",
             serialize_for_log(&params, "reasoning.probabilistic input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.decision_framework_pipe);
+        .with_pipe(&decision_framework_pipe);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.decision_framework_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request =
+            PipeRequest::new(&decision_framework_pipe, messages).with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -677,6 +702,9 @@ impl EvidenceMode {
                 .into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let bayesian_response = self.parse_bayesian_response(&response.completion, &params)?;
@@ -741,6 +769,8 @@ impl EvidenceMode {
             serde_json::to_value(&result.interpretation).unwrap_or_default(),
         )
         .with_uncertainty(serde_json::to_value(&result.uncertainty).unwrap_or_default());
+        // Store under the ID the caller sees so the update can be referenced later
+        stored_probability.id = update_id.clone();
 
         if let Some(ci) = &result.confidence_interval {
             stored_probability = stored_probability.with_confidence_interval(
@@ -932,6 +962,7 @@ impl EvidenceParams {
             evidence: Vec::new(),
             session_id: None,
             context: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -973,6 +1004,11 @@ impl EvidenceParams {
         self.context = Some(context.into());
         self
     }
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl ProbabilisticParams {
@@ -983,6 +1019,7 @@ impl ProbabilisticParams {
             prior,
             evidence: Vec::new(),
             session_id: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -1006,6 +1043,11 @@ impl ProbabilisticParams {
         });
         self
     }
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 // ============================================================================
@@ -2071,6 +2113,7 @@ mod tests {
             evidence: vec![],
             session_id: None,
             context: None,
+            generation: GenerationParams::default(),
         };
         assert!(params.session_id.is_none());
         assert!(params.context.is_none());
@@ -4,25 +4,34 @@
 //! - Initialize: Create a new graph with root node
 //! - Generate: Create k diverse continuations from a node
 //! - Score: Evaluate node quality
+//! - Rank: Order sibling nodes by head-to-head comparison (Bradley-Terry or Elo)
 //! - Aggregate: Merge multiple nodes into unified insight
+//! - Aggregate best: Merge the top-k scoring active nodes in one call
 //! - Refine: Improve a node through self-critique
 //! - Prune: Remove low-scoring nodes
 //! - Finalize: Mark terminal nodes and get conclusions
+//! - Export: Render the graph as Graphviz DOT or GraphML
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
-use super::{serialize_for_log, ModeCore};
+use super::{emit_artifact, merge_duplicates, serialize_for_log, DuplicateFate, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{EmbeddingProvider, GenerationParams, Message, PipeRequest};
 use crate::prompts::{
-    GOT_AGGREGATE_PROMPT, GOT_GENERATE_PROMPT, GOT_REFINE_PROMPT, GOT_SCORE_PROMPT,
+    GOT_AGGREGATE_PROMPT, GOT_COMPARE_PROMPT, GOT_GENERATE_PROMPT, GOT_REFINE_PROMPT,
+    GOT_SCORE_PROMPT,
 };
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
 use crate::storage::{
-    EdgeType, GraphEdge, GraphNode, Invocation, NodeType, SqliteStorage, Storage,
+    cosine_similarity, EdgeType, GraphComparison, GraphEdge, GraphNode, Invocation, NodeType, Page,
+    Pagination, SharedStorage,
 };
 
 #[cfg(test)]
@@ -30,7 +39,7 @@ use crate::storage::{
 mod got_tests;
 
 /// Configuration for GoT operations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotConfig {
     /// Maximum number of nodes in the graph
     #[serde(default = "default_max_nodes")]
@@ -44,6 +53,12 @@ pub struct GotConfig {
     /// Score threshold for pruning
     #[serde(default = "default_prune_threshold")]
     pub prune_threshold: f64,
+    /// Cosine similarity at or above which two nodes count as duplicates
+    #[serde(default = "default_similarity_threshold")]
+    pub similarity_threshold: f64,
+    /// Reject edges that would close a cycle instead of logging a warning
+    #[serde(default)]
+    pub strict_dag: bool,
 }
 
 fn default_max_nodes() -> usize {
@@ -62,6 +77,10 @@ fn default_prune_threshold() -> f64 {
     0.3
 }
 
+pub(crate) fn default_similarity_threshold() -> f64 {
+    0.9
+}
+
 impl Default for GotConfig {
     fn default() -> Self {
         Self {
@@ -69,6 +88,8 @@ impl Default for GotConfig {
             max_depth: default_max_depth(),
             default_k: default_k(),
             prune_threshold: default_prune_threshold(),
+            similarity_threshold: default_similarity_threshold(),
+            strict_dag: false,
         }
     }
 }
@@ -94,7 +115,7 @@ pub struct GotInitParams {
 }
 
 /// Result of initializing a GoT graph
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotInitResult {
     /// The session ID for the graph.
     pub session_id: String,
@@ -124,10 +145,13 @@ pub struct GotGenerateParams {
     /// Problem context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub problem: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// A generated continuation from a source node.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GeneratedContinuation {
     /// The ID of the newly created node.
     pub node_id: String,
@@ -139,10 +163,13 @@ pub struct GeneratedContinuation {
     pub novelty: f64,
     /// Explanation for why this continuation was generated.
     pub rationale: String,
+    /// Lower-scoring node this continuation paraphrases and replaces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaces_node_id: Option<String>,
 }
 
 /// Result of generating continuations from a node.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotGenerateResult {
     /// The session ID.
     pub session_id: String,
@@ -152,6 +179,10 @@ pub struct GotGenerateResult {
     pub continuations: Vec<GeneratedContinuation>,
     /// The number of continuations requested (k).
     pub count: usize,
+    /// Continuations dropped as near-duplicates of a node or continuation
+    /// scoring at least as well.
+    #[serde(default)]
+    pub duplicates_skipped: usize,
 }
 
 /// Langbase response for generate operation
@@ -204,10 +235,13 @@ pub struct GotScoreParams {
     /// Problem context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub problem: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Score breakdown for a node across multiple quality dimensions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ScoreBreakdown {
     /// How relevant the thought is to the problem (0.0-1.0).
     pub relevance: f64,
@@ -220,7 +254,7 @@ pub struct ScoreBreakdown {
 }
 
 /// Result of scoring a node.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotScoreResult {
     /// The session ID.
     pub session_id: String,
@@ -278,6 +312,189 @@ impl ScoreResponse {
     }
 }
 
+// ============================================================================
+// Rank Operation
+// ============================================================================
+
+/// Most nodes one rank call compares; every pair is judged, so calls grow
+/// quadratically.
+pub const MAX_RANK_NODES: usize = 8;
+
+/// Most comparison rounds one rank call runs.
+pub const MAX_RANK_ROUNDS: u32 = 3;
+
+/// Elo rating of a node before its first comparison.
+const ELO_INITIAL_RATING: f64 = 1500.0;
+
+/// Elo update factor.
+const ELO_K: f64 = 32.0;
+
+/// Model used to derive rankings from pairwise comparisons.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingMethod {
+    /// Bradley-Terry strengths fitted to all comparisons at once.
+    #[default]
+    BradleyTerry,
+    /// Elo ratings updated one comparison at a time, oldest first.
+    Elo,
+}
+
+impl std::fmt::Display for RankingMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RankingMethod::BradleyTerry => write!(f, "bradley_terry"),
+            RankingMethod::Elo => write!(f, "elo"),
+        }
+    }
+}
+
+impl std::str::FromStr for RankingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "bradley_terry" | "bt" => Ok(RankingMethod::BradleyTerry),
+            "elo" => Ok(RankingMethod::Elo),
+            _ => Err(format!("Unknown ranking method: {}", s)),
+        }
+    }
+}
+
+/// Parameters for ranking nodes by pairwise comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotRankParams {
+    /// Session ID
+    pub session_id: String,
+    /// Node whose generated children are ranked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Nodes to rank instead of the children of `parent_id`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_ids: Vec<String>,
+    /// Rating model
+    #[serde(default)]
+    pub method: RankingMethod,
+    /// Comparison rounds; each round judges every pair once, showing the
+    /// pair in the opposite order to the previous round
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    /// Problem context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+fn default_rounds() -> u32 {
+    1
+}
+
+/// One head-to-head judgment made by a rank call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NodeComparison {
+    /// ID of the stored comparison.
+    pub comparison_id: String,
+    /// The node shown first.
+    pub node_a: String,
+    /// The node shown second.
+    pub node_b: String,
+    /// The better node, or `None` for a tie.
+    pub winner: Option<String>,
+    /// The judge's confidence in the outcome (0.0-1.0).
+    pub confidence: f64,
+    /// Why the judge preferred the winner.
+    pub rationale: String,
+}
+
+/// A node's standing derived from its comparisons.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NodeRanking {
+    /// The node ID.
+    pub node_id: String,
+    /// Position in the ranking, starting at 1.
+    pub rank: usize,
+    /// Bradley-Terry strength (1.0 is average) or Elo rating.
+    pub rating: f64,
+    /// Expected share of wins against the other ranked nodes (0.0-1.0),
+    /// stored as the node's score.
+    pub score: f64,
+    /// The node's score before ranking.
+    pub previous_score: Option<f64>,
+    /// Comparisons won.
+    pub wins: u32,
+    /// Comparisons lost.
+    pub losses: u32,
+    /// Comparisons tied.
+    pub ties: u32,
+}
+
+/// Result of ranking nodes by pairwise comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotRankResult {
+    /// The session ID.
+    pub session_id: String,
+    /// Parent of the ranked nodes, when they were its children.
+    pub parent_id: Option<String>,
+    /// The rating model used.
+    pub method: RankingMethod,
+    /// Comparisons made by this call.
+    pub comparisons: Vec<NodeComparison>,
+    /// Comparisons between the ranked nodes the rankings are derived from,
+    /// including those of earlier calls.
+    pub comparisons_used: usize,
+    /// Comparisons that failed or could not be parsed.
+    pub failed_comparisons: usize,
+    /// The nodes, best first.
+    pub rankings: Vec<NodeRanking>,
+}
+
+/// Langbase response for a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CompareResponse {
+    winner: String,
+    #[serde(default = "default_score")]
+    confidence: f64,
+    #[serde(default)]
+    rationale: String,
+    #[serde(default)]
+    metadata: Option<serde_json::Value>,
+}
+
+impl CompareResponse {
+    /// Parse completion - returns error on parse failure (no fallbacks).
+    fn from_completion(completion: &str) -> Result<Self, ToolError> {
+        let preview = || completion.chars().take(200).collect::<String>();
+        let response = serde_json::from_str::<CompareResponse>(completion).map_err(|e| {
+            ToolError::ParseFailed {
+                mode: "got.rank".to_string(),
+                message: format!("JSON parse error: {} | Response preview: {}", e, preview()),
+            }
+        })?;
+        match response.winner.trim().to_lowercase().as_str() {
+            "a" | "b" | "tie" => Ok(response),
+            other => Err(ToolError::ParseFailed {
+                mode: "got.rank".to_string(),
+                message: format!(
+                    "Unknown winner '{}', expected a, b or tie | Response preview: {}",
+                    other,
+                    preview()
+                ),
+            }),
+        }
+    }
+
+    /// Score of the node shown first: 1 for a win, 0.5 for a tie, 0 for a loss
+    fn first_score(&self) -> f64 {
+        match self.winner.trim().to_lowercase().as_str() {
+            "a" => 1.0,
+            "b" => 0.0,
+            _ => 0.5,
+        }
+    }
+}
+
 // ============================================================================
 // Aggregate Operation
 // ============================================================================
@@ -292,10 +509,13 @@ pub struct GotAggregateParams {
     /// Problem context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub problem: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Result of aggregating multiple nodes into a unified insight.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotAggregateResult {
     /// The session ID.
     pub session_id: String,
@@ -342,6 +562,58 @@ impl AggregateResponse {
     }
 }
 
+/// Most nodes one aggregate-best call merges.
+pub const MAX_AGGREGATE_BEST_K: usize = 10;
+
+/// Parameters for aggregating the best-scoring active nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotAggregateBestParams {
+    /// Session ID
+    pub session_id: String,
+    /// Number of top-scoring active nodes to merge
+    #[serde(default = "default_k")]
+    pub k: usize,
+    /// Only merge nodes scoring at least this much
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f64>,
+    /// Mark the aggregated node terminal, as finalize would
+    #[serde(default)]
+    pub mark_terminal: bool,
+    /// Problem context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// An active node chosen for aggregation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SelectedNode {
+    /// The node ID.
+    pub node_id: String,
+    /// The node's score when it was selected.
+    pub score: f64,
+    /// The node's depth in the graph.
+    pub depth: i32,
+}
+
+/// Result of aggregating the best-scoring active nodes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotAggregateBestResult {
+    /// The session ID.
+    pub session_id: String,
+    /// The nodes merged, best first.
+    pub selected: Vec<SelectedNode>,
+    /// Active nodes passed over: unscored, below `min_score`, or outside
+    /// the top k.
+    pub skipped_count: usize,
+    /// The aggregation of the selected nodes.
+    pub aggregation: GotAggregateResult,
+    /// Whether the aggregated node was marked terminal.
+    pub is_terminal: bool,
+}
+
 // ============================================================================
 // Refine Operation
 // ============================================================================
@@ -356,10 +628,13 @@ pub struct GotRefineParams {
     /// Problem context
     #[serde(skip_serializing_if = "Option::is_none")]
     pub problem: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Result of refining a node through self-critique.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotRefineResult {
     /// The session ID.
     pub session_id: String,
@@ -421,7 +696,7 @@ pub struct GotPruneParams {
 }
 
 /// Result of pruning low-scoring nodes from the graph.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotPruneResult {
     /// The session ID.
     pub session_id: String,
@@ -433,6 +708,10 @@ pub struct GotPruneResult {
     pub threshold_used: f64,
     /// IDs of the nodes that were pruned.
     pub pruned_node_ids: Vec<String>,
+    /// IDs of pruned nodes that scored above the threshold but nearly
+    /// duplicated a better-scoring node.
+    #[serde(default)]
+    pub redundant_node_ids: Vec<String>,
 }
 
 // ============================================================================
@@ -450,7 +729,7 @@ pub struct GotFinalizeParams {
 }
 
 /// A terminal conclusion node representing a final insight.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TerminalConclusion {
     /// The ID of the terminal node.
     pub node_id: String,
@@ -463,7 +742,7 @@ pub struct TerminalConclusion {
 }
 
 /// Result of finalizing the graph and extracting conclusions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotFinalizeResult {
     /// The session ID.
     pub session_id: String,
@@ -482,10 +761,16 @@ pub struct GotFinalizeResult {
 pub struct GotGetStateParams {
     /// Session ID
     pub session_id: String,
+    /// Include a page of at most this many nodes in the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Number of nodes to skip when paging through nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
 }
 
 /// Graph state summary showing the current structure and status.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GotStateResult {
     /// The session ID.
     pub session_id: String,
@@ -505,6 +790,288 @@ pub struct GotStateResult {
     pub active_node_ids: Vec<String>,
     /// IDs of all terminal nodes.
     pub terminal_node_ids: Vec<String>,
+    /// Page of full node records, present when a `limit` was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<Page<GraphNode>>,
+}
+
+// ============================================================================
+// Export Operation Types
+// ============================================================================
+
+/// Output format for exporting a reasoning graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    /// Graphviz DOT.
+    #[default]
+    Dot,
+    /// GraphML (XML), readable by Gephi, yEd, and Cytoscape.
+    Graphml,
+}
+
+impl std::fmt::Display for GraphExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphExportFormat::Dot => write!(f, "dot"),
+            GraphExportFormat::Graphml => write!(f, "graphml"),
+        }
+    }
+}
+
+impl std::str::FromStr for GraphExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" | "gv" => Ok(GraphExportFormat::Dot),
+            "graphml" => Ok(GraphExportFormat::Graphml),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// Parameters for exporting a graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotExportParams {
+    /// Session ID
+    pub session_id: String,
+    /// Output format
+    #[serde(default)]
+    pub format: GraphExportFormat,
+}
+
+/// Rendered graph ready to be written to a file.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotExportResult {
+    /// The session ID.
+    pub session_id: String,
+    /// Format of `content`.
+    pub format: GraphExportFormat,
+    /// Number of nodes rendered, including pruned ones.
+    pub node_count: usize,
+    /// Number of edges rendered.
+    pub edge_count: usize,
+    /// The rendered graph document.
+    pub content: String,
+}
+
+// ============================================================================
+// Validate Operation Types
+// ============================================================================
+
+/// Parameters for checking a graph's structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotValidateParams {
+    /// Session ID
+    pub session_id: String,
+}
+
+/// Kind of structural problem found in a reasoning graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphIssueKind {
+    /// Nodes that reach each other through their edges.
+    Cycle,
+    /// An edge from a node to itself.
+    SelfLoop,
+    /// An edge whose endpoint is not a node of the session.
+    DanglingEdge,
+    /// An edge repeating the endpoints and type of an earlier edge.
+    DuplicateEdge,
+    /// No node is marked as a root.
+    MissingRoot,
+    /// More than one node is marked as a root.
+    MultipleRoots,
+    /// A node that no root reaches.
+    Unreachable,
+    /// A generated or aggregated node no deeper than its source.
+    DepthMismatch,
+    /// A terminal node still marked active.
+    ActiveTerminal,
+}
+
+impl GraphIssueKind {
+    /// Whether this kind of issue breaks traversal rather than just
+    /// looking suspicious
+    pub fn severity(self) -> IssueSeverity {
+        match self {
+            GraphIssueKind::Cycle
+            | GraphIssueKind::SelfLoop
+            | GraphIssueKind::DanglingEdge
+            | GraphIssueKind::MissingRoot => IssueSeverity::Error,
+            _ => IssueSeverity::Warning,
+        }
+    }
+}
+
+/// How serious a structural issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The graph cannot be traversed reliably.
+    Error,
+    /// The graph is usable but likely not what was intended.
+    Warning,
+}
+
+/// A structural problem found in a reasoning graph.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GraphIssue {
+    /// What is wrong.
+    pub kind: GraphIssueKind,
+    /// Whether the issue is an error or a warning.
+    pub severity: IssueSeverity,
+    /// Human-readable description.
+    pub message: String,
+    /// Nodes involved in the issue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_ids: Vec<String>,
+    /// Edges involved in the issue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edge_ids: Vec<String>,
+}
+
+/// Structural report on a reasoning graph.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotValidateResult {
+    /// The session ID.
+    pub session_id: String,
+    /// Whether no issue of error severity was found.
+    pub is_valid: bool,
+    /// Whether the graph has no cycles or self-loops.
+    pub is_dag: bool,
+    /// Number of nodes checked.
+    pub node_count: usize,
+    /// Number of edges checked.
+    pub edge_count: usize,
+    /// Number of issues of error severity.
+    pub error_count: usize,
+    /// Number of issues of warning severity.
+    pub warning_count: usize,
+    /// Every issue found, errors first.
+    pub issues: Vec<GraphIssue>,
+}
+
+// ============================================================================
+// Best Path Operation Types
+// ============================================================================
+
+/// Parameters for extracting the best root-to-terminal path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotBestPathParams {
+    /// Session ID
+    pub session_id: String,
+    /// Node the path must end at (default: the best-scoring terminal node,
+    /// or leaf if nothing is terminal yet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_node_id: Option<String>,
+}
+
+/// One node on an extracted path.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PathStep {
+    /// The node ID.
+    pub node_id: String,
+    /// The node's thought content.
+    pub content: String,
+    /// The node's type.
+    pub node_type: NodeType,
+    /// The node's depth in the graph.
+    pub depth: i32,
+    /// The node's quality score, if scored.
+    pub score: Option<f64>,
+    /// Type of the edge leading into this node (absent for the root).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+    /// Sum of scores from the root through this node.
+    pub cumulative_score: f64,
+}
+
+/// Highest-scoring path through a reasoning graph.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotBestPathResult {
+    /// The session ID.
+    pub session_id: String,
+    /// The root the path starts at.
+    pub root_node_id: String,
+    /// The node the path ends at.
+    pub end_node_id: String,
+    /// Whether the path ends at a terminal node rather than a leaf.
+    pub reached_terminal: bool,
+    /// Nodes on the path, root first.
+    pub steps: Vec<PathStep>,
+    /// Sum of node scores along the path (unscored nodes count as 0).
+    pub total_score: f64,
+    /// Mean score of the scored nodes on the path.
+    pub mean_score: Option<f64>,
+    /// The path as a numbered narrative, one step per line.
+    pub explanation: String,
+}
+
+// ============================================================================
+// Resume Operation Types
+// ============================================================================
+
+/// Most frontier nodes a single resume may expand.
+pub const MAX_RESUME_EXPANSIONS: usize = 5;
+
+fn default_resume_expand() -> usize {
+    1
+}
+
+/// Parameters for resuming a stored graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GotResumeParams {
+    /// Session ID
+    pub session_id: String,
+    /// Number of frontier nodes to expand, best first (0 only reloads)
+    #[serde(default = "default_resume_expand")]
+    pub expand: usize,
+    /// Continuations per expanded node (default: the graph's `default_k`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k: Option<usize>,
+    /// Problem context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// An active node that can still be expanded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FrontierNode {
+    /// The node ID.
+    pub node_id: String,
+    /// The thought content.
+    pub content: String,
+    /// The quality score, if scored.
+    pub score: Option<f64>,
+    /// The depth in the graph.
+    pub depth: i32,
+}
+
+/// Result of resuming a stored graph.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GotResumeResult {
+    /// The session ID.
+    pub session_id: String,
+    /// The limits the graph was initialized with.
+    pub config: GotConfig,
+    /// Nodes in the graph after any expansion.
+    pub node_count: usize,
+    /// Nodes that may still be added before `max_nodes` is reached.
+    pub remaining_nodes: usize,
+    /// Nodes marked inactive because they had already been expanded.
+    pub repaired_node_ids: Vec<String>,
+    /// Expandable nodes after any expansion, best score first.
+    pub frontier: Vec<FrontierNode>,
+    /// One generate result per expanded node.
+    pub expanded: Vec<GotGenerateResult>,
+    /// Why expansion stopped before `expand` nodes, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_reason: Option<String>,
 }
 
 // ============================================================================
@@ -514,17 +1081,27 @@ pub struct GotStateResult {
 /// Graph-of-Thoughts mode handler for managing complex reasoning graphs.
 #[derive(Clone)]
 pub struct GotMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// Consolidated pipe name for all GoT operations (prompts passed dynamically).
     got_pipe: String,
     /// Configuration for GoT operations.
     config: GotConfig,
+    /// Embeddings for duplicate detection; without them nodes are compared
+    /// by score only.
+    embeddings: Option<Arc<dyn EmbeddingProvider>>,
+    /// Parallel calls a generate may split into; 0 asks for all
+    /// continuations in one call.
+    fan_out: usize,
 }
 
 impl GotMode {
     /// Create a new GoT mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         let got_config = config
             .pipes
             .got
@@ -534,11 +1111,16 @@ impl GotMode {
                 max_depth: g.max_depth.unwrap_or_else(default_max_depth),
                 default_k: g.default_k.unwrap_or_else(default_k),
                 prune_threshold: g.prune_threshold.unwrap_or_else(default_prune_threshold),
+                similarity_threshold: g
+                    .similarity_threshold
+                    .unwrap_or_else(default_similarity_threshold),
+                strict_dag: g.strict_dag.unwrap_or(false),
             })
             .unwrap_or_default();
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             got_pipe: config
                 .pipes
                 .got
@@ -546,6 +1128,35 @@ impl GotMode {
                 .and_then(|g| g.pipe.clone())
                 .unwrap_or_else(|| "got-reasoning-v1".to_string()),
             config: got_config,
+            embeddings: None,
+            fan_out: config.concurrency.fan_out,
+        }
+    }
+
+    /// Detect near-duplicate nodes with `embeddings` when generating and
+    /// pruning
+    pub fn with_embeddings(mut self, embeddings: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Embed `texts`, or `None` when no provider is set or embedding fails
+    async fn embed(&self, texts: &[String]) -> Option<Vec<Vec<f32>>> {
+        let provider = self.embeddings.as_ref()?;
+        match provider.embed(texts).await {
+            Ok(vectors) if vectors.len() == texts.len() => Some(vectors),
+            Ok(vectors) => {
+                warn!(
+                    expected = texts.len(),
+                    received = vectors.len(),
+                    "Embedding count mismatch, skipping GoT duplicate detection"
+                );
+                None
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to embed GoT nodes, skipping duplicate detection");
+                None
+            }
         }
     }
 
@@ -572,12 +1183,14 @@ impl GotMode {
         // Merge config with params override
         let effective_config = params.config.unwrap_or_else(|| self.config.clone());
 
-        // Create root node
+        // Create root node, keeping the limits with it so later calls (and
+        // resumed sessions) honor them
         let root_node = GraphNode::new(&session.id, &params.content)
             .with_type(NodeType::Root)
             .with_depth(0)
             .as_root()
-            .as_active();
+            .as_active()
+            .with_metadata(serde_json::json!({ "got_config": effective_config }));
 
         self.core.storage().create_graph_node(&root_node).await?;
 
@@ -600,6 +1213,7 @@ impl GotMode {
     /// Generate continuations from a node
     pub async fn generate(&self, params: GotGenerateParams) -> AppResult<GotGenerateResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Get source node (specified or first active)
         let source_node = match &params.node_id {
@@ -635,18 +1249,48 @@ impl GotMode {
             "Generating GoT continuations"
         );
 
-        // Check depth limit
-        if source_node.depth >= self.config.max_depth as i32 {
+        let graph = self
+            .core
+            .storage()
+            .get_session_graph_nodes(&params.session_id)
+            .await?;
+        let config = self.config_for(&graph);
+
+        // Check depth and size limits
+        if source_node.depth >= config.max_depth as i32 {
             return Err(ToolError::Validation {
                 field: "depth".to_string(),
-                reason: format!("Maximum depth {} reached", self.config.max_depth),
+                reason: format!("Maximum depth {} reached", config.max_depth),
+            }
+            .into());
+        }
+        let node_budget = config.max_nodes.saturating_sub(graph.len());
+        if node_budget == 0 {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Maximum nodes {} reached", config.max_nodes),
             }
             .into());
         }
 
-        // Build messages for Langbase
-        let messages =
-            self.build_generate_messages(&source_node, params.k, params.problem.as_deref());
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+
+        // With fan-out enabled, each continuation gets its own call
+        let fanned_out = self.fan_out > 0 && params.k > 1;
+        let message_sets = if fanned_out {
+            (0..params.k)
+                .map(|i| {
+                    self.build_fan_out_generate_messages(
+                        &source_node,
+                        i,
+                        params.k,
+                        params.problem.as_deref(),
+                    )
+                })
+                .collect()
+        } else {
+            vec![self.build_generate_messages(&source_node, params.k, params.problem.as_deref())]
+        };
 
         // Log invocation
         let mut invocation = Invocation::new(
@@ -654,12 +1298,38 @@ impl GotMode {
             serialize_for_log(&params, "reasoning.got.generate input"),
         )
         .with_session(&params.session_id)
-        .with_pipe(&self.got_pipe);
+        .with_pipe(&got_pipe);
 
         // Call Langbase
-        let request = PipeRequest::new(&self.got_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
-            Ok(resp) => resp,
+        let schema = self
+            .core
+            .response_schema(SchemaKind::GotGenerate, &got_pipe);
+        let requests = message_sets
+            .into_iter()
+            .map(|messages| {
+                PipeRequest::new(&got_pipe, messages)
+                    .with_output_schema(schema.clone())
+                    .with_generation(params.generation)
+            })
+            .collect();
+        let fan_out = self
+            .core
+            .backend()
+            .call_pipe_parallel(requests, self.fan_out)
+            .await;
+        // Partial failures still yield the continuations that came back
+        if fanned_out {
+            for (index, error) in &fan_out.errors {
+                warn!(
+                    session_id = %params.session_id,
+                    continuation = index,
+                    error = %error,
+                    "GoT continuation call failed, continuing with the rest"
+                );
+            }
+        }
+        let responses = match fan_out.into_responses() {
+            Ok(responses) => responses,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
                 invocation = invocation.failure(e.to_string(), latency);
@@ -673,36 +1343,119 @@ impl GotMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(responses.iter().all(|(_, r)| r.cache_hit))
+            .with_pipe_fallback(responses.iter().find_map(|(_, r)| r.fallback.as_deref()));
+
+        // Parse responses; a fanned-out call contributes one continuation
+        let per_response = if fanned_out { 1 } else { params.k };
+        let mut items: Vec<ContinuationItem> = Vec::new();
+        for (_, response) in &responses {
+            let gen_response = GenerateResponse::from_completion(&response.completion)?;
+            items.extend(gen_response.continuations.into_iter().take(per_response));
+        }
+        items.truncate(params.k);
+
+        // Of a continuation and a paraphrase of it, already in the graph or
+        // generated alongside it, keep whichever scores higher. A kept
+        // continuation takes the place of the node it paraphrases.
+        let mut duplicates_skipped = 0;
+        let mut replaces: Vec<Option<&GraphNode>> = vec![None; items.len()];
+        if self.embeddings.is_some() && !items.is_empty() {
+            let existing: Vec<String> = graph.iter().map(|n| n.content.clone()).collect();
+            let mut texts = existing.clone();
+            texts.extend(items.iter().map(|i| i.thought.clone()));
+            if let Some(mut vectors) = self.embed(&texts).await {
+                let candidates = vectors.split_off(existing.len());
+                // Conclusions and the node being expanded are never replaced
+                let existing_scores: Vec<Option<f64>> = graph
+                    .iter()
+                    .map(|n| n.score.filter(|_| !n.is_terminal && n.id != source_node.id))
+                    .collect();
+                let scores: Vec<f64> = items.iter().map(|i| i.confidence).collect();
+                let fates = merge_duplicates(
+                    &vectors,
+                    &existing_scores,
+                    &candidates,
+                    &scores,
+                    config.similarity_threshold,
+                );
+                let mut kept = Vec::new();
+                replaces.clear();
+                for (item, fate) in items.into_iter().zip(fates) {
+                    match fate {
+                        DuplicateFate::Distinct => {
+                            kept.push(item);
+                            replaces.push(None);
+                        }
+                        DuplicateFate::Replaces(j) => {
+                            kept.push(item);
+                            replaces.push(Some(&graph[j]));
+                        }
+                        DuplicateFate::DuplicateOfExisting(_)
+                        | DuplicateFate::DuplicateOfCandidate(_) => duplicates_skipped += 1,
+                    }
+                }
+                items = kept;
+            }
+        }
 
-        // Parse response
-        let gen_response = GenerateResponse::from_completion(&response.completion)?;
+        // Stay within the graph's node budget
+        items.truncate(node_budget);
+        replaces.truncate(node_budget);
 
-        // Create nodes and edges for each continuation
+        // Build nodes and edges for each continuation
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
         let mut continuations = Vec::new();
-        for item in gen_response.continuations.into_iter().take(params.k) {
-            // Create new node
+        let mut replaced: Vec<GraphNode> = Vec::new();
+        for (item, old) in items.into_iter().zip(replaces) {
             let node = GraphNode::new(&params.session_id, &item.thought)
                 .with_type(NodeType::Thought)
                 .with_depth(source_node.depth + 1)
                 .with_score(item.confidence)
                 .as_active();
 
-            self.core.storage().create_graph_node(&node).await?;
-
-            // Create edge from source to new node
+            // Edge from source to new node
             let edge = GraphEdge::new(&params.session_id, &source_node.id, &node.id)
                 .with_type(EdgeType::Generates)
                 .with_weight(item.confidence);
 
-            self.core.storage().create_graph_edge(&edge).await?;
+            // The paraphrased node is refined by its better-scoring successor
+            if let Some(old) = old {
+                edges.push(
+                    GraphEdge::new(&params.session_id, &old.id, &node.id)
+                        .with_type(EdgeType::Refines),
+                );
+                if old.is_active && !replaced.iter().any(|r| r.id == old.id) {
+                    replaced.push(old.clone());
+                }
+            }
 
             continuations.push(GeneratedContinuation {
-                node_id: node.id,
+                node_id: node.id.clone(),
                 content: item.thought,
                 confidence: item.confidence,
                 novelty: item.novelty,
                 rationale: item.rationale,
+                replaces_node_id: old.map(|n| n.id.clone()),
             });
+            nodes.push(node);
+            edges.push(edge);
+        }
+
+        // Nodes must exist before the edges that reference them
+        self.check_acyclic(&params.session_id, &edges).await?;
+        self.core.storage().create_graph_nodes_batch(&nodes).await?;
+        self.core.storage().create_graph_edges_batch(&edges).await?;
+        for (i, continuation) in continuations.iter().enumerate() {
+            emit_artifact("continuation", i, continuation);
+        }
+
+        // Replaced nodes leave the frontier to their successors
+        for mut node in replaced {
+            node.is_active = false;
+            self.core.storage().update_graph_node(&node).await?;
         }
 
         // Mark source node as no longer active (branched)
@@ -730,6 +1483,7 @@ impl GotMode {
             session_id = %params.session_id,
             source_node_id = %source_node.id,
             generated_count = continuations.len(),
+            duplicates_skipped = duplicates_skipped,
             latency_ms = latency,
             "GoT generate completed"
         );
@@ -739,12 +1493,14 @@ impl GotMode {
             source_node_id: source_node.id,
             continuations,
             count: params.k,
+            duplicates_skipped,
         })
     }
 
     /// Score a node
     pub async fn score(&self, params: GotScoreParams) -> AppResult<GotScoreResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Get the node
         let node = self
@@ -766,17 +1522,21 @@ impl GotMode {
         // Build messages for Langbase
         let messages = self.build_score_messages(&node, params.problem.as_deref());
 
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+
         // Log invocation
         let mut invocation = Invocation::new(
             "reasoning.got.score",
             serialize_for_log(&params, "reasoning.got.score input"),
         )
         .with_session(&params.session_id)
-        .with_pipe(&self.got_pipe);
+        .with_pipe(&got_pipe);
 
         // Call Langbase
-        let request = PipeRequest::new(&self.got_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request = PipeRequest::new(&got_pipe, messages)
+            .with_output_schema(self.core.response_schema(SchemaKind::GotScore, &got_pipe))
+            .with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -791,6 +1551,9 @@ impl GotMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let score_response = ScoreResponse::from_completion(&response.completion)?;
@@ -836,30 +1599,381 @@ impl GotMode {
         })
     }
 
-    /// Aggregate multiple nodes
-    pub async fn aggregate(&self, params: GotAggregateParams) -> AppResult<GotAggregateResult> {
+    /// Rank sibling nodes by head-to-head comparison
+    ///
+    /// Every pair of nodes is judged by the pipe once per round, and
+    /// ratings are fitted locally to all stored comparisons between the
+    /// nodes, so repeated calls refine the ranking. Each node's score is
+    /// replaced by its expected share of wins against the others.
+    pub async fn rank(&self, params: GotRankParams) -> AppResult<GotRankResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
-        if params.node_ids.len() < 2 {
+        if params.rounds == 0 || params.rounds > MAX_RANK_ROUNDS {
             return Err(ToolError::Validation {
-                field: "node_ids".to_string(),
-                reason: "At least 2 nodes required for aggregation".to_string(),
+                field: "rounds".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_RANK_ROUNDS),
             }
             .into());
         }
 
-        // Get all nodes
-        let mut nodes = Vec::new();
-        for id in &params.node_ids {
-            let node = self
-                .core
-                .storage()
-                .get_graph_node(id)
-                .await?
-                .ok_or_else(|| ToolError::Validation {
-                    field: "node_ids".to_string(),
-                    reason: format!("Node not found: {}", id),
-                })?;
+        let nodes = self.rank_candidates(&params).await?;
+        let field = if params.node_ids.is_empty() {
+            "parent_id"
+        } else {
+            "node_ids"
+        };
+        if nodes.len() < 2 {
+            return Err(ToolError::Validation {
+                field: field.to_string(),
+                reason: format!("At least 2 nodes required to rank, found {}", nodes.len()),
+            }
+            .into());
+        }
+        if nodes.len() > MAX_RANK_NODES {
+            return Err(ToolError::Validation {
+                field: field.to_string(),
+                reason: format!(
+                    "At most {} nodes can be ranked at once, found {}",
+                    MAX_RANK_NODES,
+                    nodes.len()
+                ),
+            }
+            .into());
+        }
+
+        debug!(
+            session_id = %params.session_id,
+            node_count = nodes.len(),
+            rounds = params.rounds,
+            method = %params.method,
+            "Ranking GoT nodes"
+        );
+
+        // Each round shows every pair in the opposite order to the round
+        // before, so a judge's position bias cancels out over two rounds
+        let mut pairs = Vec::new();
+        for round in 1..=params.rounds {
+            for i in 0..nodes.len() {
+                for j in (i + 1)..nodes.len() {
+                    pairs.push(if round % 2 == 1 {
+                        (i, j, round)
+                    } else {
+                        (j, i, round)
+                    });
+                }
+            }
+        }
+
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+
+        // Log invocation
+        let mut invocation = Invocation::new(
+            "reasoning.got.rank",
+            serialize_for_log(&params, "reasoning.got.rank input"),
+        )
+        .with_session(&params.session_id)
+        .with_pipe(&got_pipe);
+
+        // Call Langbase, one independent call per pair
+        let requests = pairs
+            .iter()
+            .map(|&(a, b, _)| {
+                let messages =
+                    self.build_compare_messages(&nodes[a], &nodes[b], params.problem.as_deref());
+                PipeRequest::new(&got_pipe, messages).with_generation(params.generation)
+            })
+            .collect();
+        let fan_out = self
+            .core
+            .backend()
+            .call_pipe_parallel(requests, self.fan_out)
+            .await;
+        let mut failed_comparisons = fan_out.errors.len();
+        for (index, error) in &fan_out.errors {
+            warn!(
+                session_id = %params.session_id,
+                comparison = index,
+                error = %error,
+                "GoT comparison call failed, continuing with the rest"
+            );
+        }
+        let responses = match fan_out.into_responses() {
+            Ok(responses) => responses,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                if let Err(log_err) = self.core.storage().log_invocation(&invocation).await {
+                    warn!(
+                        error = %log_err,
+                        tool = %invocation.tool_name,
+                        "Failed to log invocation - audit trail incomplete"
+                    );
+                }
+                return Err(e.into());
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(responses.iter().all(|(_, r)| r.cache_hit))
+            .with_pipe_fallback(responses.iter().find_map(|(_, r)| r.fallback.as_deref()));
+
+        // Parse responses; an unparsable judgment is skipped like a failed call
+        let mut stored = Vec::new();
+        let mut parse_error = None;
+        for (index, response) in &responses {
+            let (a, b, round) = pairs[*index];
+            let compare_response = match CompareResponse::from_completion(&response.completion) {
+                Ok(compare_response) => compare_response,
+                Err(e) => {
+                    warn!(
+                        session_id = %params.session_id,
+                        comparison = index,
+                        error = %e,
+                        "Failed to parse GoT comparison, continuing with the rest"
+                    );
+                    failed_comparisons += 1;
+                    parse_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            let winner = match compare_response.first_score() {
+                s if s > 0.5 => Some(nodes[a].id.clone()),
+                s if s < 0.5 => Some(nodes[b].id.clone()),
+                _ => None,
+            };
+            let mut comparison = GraphComparison::new(
+                &params.session_id,
+                &nodes[a].id,
+                &nodes[b].id,
+                winner,
+                compare_response.confidence,
+            )
+            .with_rationale(compare_response.rationale)
+            .with_round(round as i32);
+            if let Some(parent_id) = &params.parent_id {
+                comparison = comparison.with_parent(parent_id);
+            }
+            stored.push(comparison);
+        }
+        if stored.is_empty() {
+            if let Some(e) = parse_error {
+                return Err(e.into());
+            }
+        }
+        self.core
+            .storage()
+            .create_graph_comparisons_batch(&stored)
+            .await?;
+
+        // Fit ratings to every comparison between these nodes, this call's
+        // included
+        let index_of = |id: &str| nodes.iter().position(|n| n.id == id);
+        let outcomes: Vec<(usize, usize, f64)> = self
+            .core
+            .storage()
+            .get_session_graph_comparisons(&params.session_id)
+            .await?
+            .into_iter()
+            .filter_map(|c| {
+                let a = index_of(&c.node_a_id)?;
+                let b = index_of(&c.node_b_id)?;
+                let first_score = match c.winner_id.as_deref() {
+                    Some(w) if w == c.node_a_id => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                Some((a, b, first_score))
+            })
+            .collect();
+        let ratings = params.method.ratings(nodes.len(), &outcomes);
+        let scores = expected_scores(&ratings, |a, b| params.method.win_probability(a, b));
+
+        let mut order: Vec<usize> = (0..nodes.len()).collect();
+        order.sort_by(|&i, &j| scores[j].total_cmp(&scores[i]));
+
+        let mut rankings = Vec::with_capacity(nodes.len());
+        for (position, &i) in order.iter().enumerate() {
+            let node = &nodes[i];
+            let (mut wins, mut losses, mut ties) = (0, 0, 0);
+            for &(a, b, first_score) in &outcomes {
+                let score = if a == i {
+                    first_score
+                } else if b == i {
+                    1.0 - first_score
+                } else {
+                    continue;
+                };
+                match score {
+                    s if s > 0.5 => wins += 1,
+                    s if s < 0.5 => losses += 1,
+                    _ => ties += 1,
+                }
+            }
+            let ranking = NodeRanking {
+                node_id: node.id.clone(),
+                rank: position + 1,
+                rating: ratings[i],
+                score: scores[i],
+                previous_score: node.score,
+                wins,
+                losses,
+                ties,
+            };
+
+            // Keep the absolute score a node had before it was first ranked
+            let absolute_score = node
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("pairwise"))
+                .and_then(|p| p.get("absolute_score"))
+                .cloned()
+                .unwrap_or_else(|| serde_json::json!(node.score));
+            let mut metadata = match node.metadata.clone() {
+                Some(serde_json::Value::Object(map)) => map,
+                _ => serde_json::Map::new(),
+            };
+            metadata.insert(
+                "pairwise".to_string(),
+                serde_json::json!({
+                    "method": params.method,
+                    "rank": ranking.rank,
+                    "rating": ranking.rating,
+                    "wins": wins,
+                    "losses": losses,
+                    "ties": ties,
+                    "absolute_score": absolute_score,
+                }),
+            );
+            let mut updated_node = node.clone();
+            updated_node.score = Some(ranking.score);
+            updated_node.metadata = Some(serde_json::Value::Object(metadata));
+            self.core.storage().update_graph_node(&updated_node).await?;
+
+            rankings.push(ranking);
+        }
+
+        let comparisons: Vec<NodeComparison> = stored
+            .into_iter()
+            .map(|c| NodeComparison {
+                comparison_id: c.id,
+                node_a: c.node_a_id,
+                node_b: c.node_b_id,
+                winner: c.winner_id,
+                confidence: c.confidence,
+                rationale: c.rationale,
+            })
+            .collect();
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation.success(
+            serialize_for_log(&rankings, "reasoning.got.rank output"),
+            latency,
+        );
+        if let Err(log_err) = self.core.storage().log_invocation(&invocation).await {
+            warn!(
+                error = %log_err,
+                tool = %invocation.tool_name,
+                "Failed to log invocation - audit trail incomplete"
+            );
+        }
+
+        info!(
+            session_id = %params.session_id,
+            node_count = nodes.len(),
+            comparisons = comparisons.len(),
+            comparisons_used = outcomes.len(),
+            failed_comparisons = failed_comparisons,
+            latency_ms = latency,
+            "GoT rank completed"
+        );
+
+        Ok(GotRankResult {
+            session_id: params.session_id,
+            parent_id: params.parent_id,
+            method: params.method,
+            comparisons,
+            comparisons_used: outcomes.len(),
+            failed_comparisons,
+            rankings,
+        })
+    }
+
+    /// Nodes a rank call compares: `node_ids`, or the nodes `parent_id`
+    /// generated
+    async fn rank_candidates(&self, params: &GotRankParams) -> AppResult<Vec<GraphNode>> {
+        let (field, ids) = if !params.node_ids.is_empty() {
+            let mut ids = params.node_ids.clone();
+            let mut seen = HashSet::new();
+            ids.retain(|id| seen.insert(id.clone()));
+            ("node_ids", ids)
+        } else if let Some(parent_id) = &params.parent_id {
+            let parent = self.core.storage().get_graph_node(parent_id).await?;
+            if !parent.is_some_and(|p| p.session_id == params.session_id) {
+                return Err(ToolError::Validation {
+                    field: "parent_id".to_string(),
+                    reason: format!("Node not found: {}", parent_id),
+                }
+                .into());
+            }
+            let ids = self
+                .core
+                .storage()
+                .get_edges_from(parent_id)
+                .await?
+                .into_iter()
+                .filter(|e| e.edge_type == EdgeType::Generates)
+                .map(|e| e.to_node)
+                .collect();
+            ("parent_id", ids)
+        } else {
+            return Err(ToolError::Validation {
+                field: "parent_id".to_string(),
+                reason: "Either parent_id or node_ids is required".to_string(),
+            }
+            .into());
+        };
+
+        let mut nodes = Vec::with_capacity(ids.len());
+        for id in ids {
+            match self.core.storage().get_graph_node(&id).await? {
+                Some(node) if node.session_id == params.session_id => nodes.push(node),
+                _ => {
+                    return Err(ToolError::Validation {
+                        field: field.to_string(),
+                        reason: format!("Node not found: {}", id),
+                    }
+                    .into())
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Aggregate multiple nodes
+    pub async fn aggregate(&self, params: GotAggregateParams) -> AppResult<GotAggregateResult> {
+        let start = Instant::now();
+        params.generation.validate()?;
+
+        if params.node_ids.len() < 2 {
+            return Err(ToolError::Validation {
+                field: "node_ids".to_string(),
+                reason: "At least 2 nodes required for aggregation".to_string(),
+            }
+            .into());
+        }
+
+        // Get all nodes
+        let mut nodes = Vec::new();
+        for id in &params.node_ids {
+            let node = self
+                .core
+                .storage()
+                .get_graph_node(id)
+                .await?
+                .ok_or_else(|| ToolError::Validation {
+                    field: "node_ids".to_string(),
+                    reason: format!("Node not found: {}", id),
+                })?;
             nodes.push(node);
         }
 
@@ -872,17 +1986,24 @@ impl GotMode {
         // Build messages for Langbase
         let messages = self.build_aggregate_messages(&nodes, params.problem.as_deref());
 
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+
         // Log invocation
         let mut invocation = Invocation::new(
             "reasoning.got.aggregate",
             serialize_for_log(&params, "reasoning.got.aggregate input"),
         )
         .with_session(&params.session_id)
-        .with_pipe(&self.got_pipe);
+        .with_pipe(&got_pipe);
 
         // Call Langbase
-        let request = PipeRequest::new(&self.got_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request = PipeRequest::new(&got_pipe, messages)
+            .with_output_schema(
+                self.core
+                    .response_schema(SchemaKind::GotAggregate, &got_pipe),
+            )
+            .with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -897,6 +2018,9 @@ impl GotMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let agg_response = AggregateResponse::from_completion(&response.completion)?;
@@ -911,14 +2035,19 @@ impl GotMode {
             .with_score(agg_response.confidence)
             .as_active();
 
+        // Create edges from source nodes to aggregated node
+        let edges: Vec<GraphEdge> = nodes
+            .iter()
+            .map(|node| {
+                GraphEdge::new(&params.session_id, &node.id, &agg_node.id)
+                    .with_type(EdgeType::Aggregates)
+            })
+            .collect();
+        self.check_acyclic(&params.session_id, &edges).await?;
         self.core.storage().create_graph_node(&agg_node).await?;
+        self.core.storage().create_graph_edges_batch(&edges).await?;
 
-        // Create edges from source nodes to aggregated node
         for node in &nodes {
-            let edge = GraphEdge::new(&params.session_id, &node.id, &agg_node.id)
-                .with_type(EdgeType::Aggregates);
-            self.core.storage().create_graph_edge(&edge).await?;
-
             // Mark source nodes as no longer active
             let mut updated = node.clone();
             updated.is_active = false;
@@ -957,9 +2086,128 @@ impl GotMode {
         })
     }
 
+    /// Aggregate the k best-scoring active nodes
+    ///
+    /// Selects the nodes, merges them through [`GotMode::aggregate`] (which
+    /// wires the `aggregates` edges and retires the sources) and, when
+    /// asked, marks the aggregated node terminal as finalize would.
+    pub async fn aggregate_best(
+        &self,
+        params: GotAggregateBestParams,
+    ) -> AppResult<GotAggregateBestResult> {
+        if params.k < 2 || params.k > MAX_AGGREGATE_BEST_K {
+            return Err(ToolError::Validation {
+                field: "k".to_string(),
+                reason: format!("Must be between 2 and {}", MAX_AGGREGATE_BEST_K),
+            }
+            .into());
+        }
+        if let Some(min_score) = params.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err(ToolError::Validation {
+                    field: "min_score".to_string(),
+                    reason: "Must be between 0.0 and 1.0".to_string(),
+                }
+                .into());
+            }
+        }
+
+        let active = self
+            .core
+            .storage()
+            .get_active_graph_nodes(&params.session_id)
+            .await?;
+        let active_count = active.len();
+        let mut scored: Vec<(GraphNode, f64)> = active
+            .into_iter()
+            .filter_map(|n| {
+                let score = n.score?;
+                (score >= params.min_score.unwrap_or(0.0)).then_some((n, score))
+            })
+            .collect();
+        // Stable, so equal scores keep creation order
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(params.k);
+
+        if scored.len() < 2 {
+            let qualifier = match params.min_score {
+                Some(min_score) => format!(" scoring at least {}", min_score),
+                None => String::new(),
+            };
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!(
+                    "At least 2 scored active nodes{} required for aggregation, found {}",
+                    qualifier,
+                    scored.len()
+                ),
+            }
+            .into());
+        }
+
+        let selected: Vec<SelectedNode> = scored
+            .iter()
+            .map(|(node, score)| SelectedNode {
+                node_id: node.id.clone(),
+                score: *score,
+                depth: node.depth,
+            })
+            .collect();
+
+        debug!(
+            session_id = %params.session_id,
+            selected_count = selected.len(),
+            active_count = active_count,
+            "Aggregating best GoT nodes"
+        );
+
+        let mut aggregate_params = GotAggregateParams::new(
+            &params.session_id,
+            selected.iter().map(|s| s.node_id.clone()).collect(),
+        )
+        .with_generation(params.generation);
+        if let Some(problem) = &params.problem {
+            aggregate_params = aggregate_params.with_problem(problem);
+        }
+        let aggregation = self.aggregate(aggregate_params).await?;
+
+        if params.mark_terminal {
+            let mut node = self
+                .core
+                .storage()
+                .get_graph_node(&aggregation.aggregated_node_id)
+                .await?
+                .ok_or_else(|| ToolError::Validation {
+                    field: "session_id".to_string(),
+                    reason: format!("Node not found: {}", aggregation.aggregated_node_id),
+                })?;
+            node.is_terminal = true;
+            node.is_active = false;
+            node.node_type = NodeType::Terminal;
+            self.core.storage().update_graph_node(&node).await?;
+        }
+
+        info!(
+            session_id = %params.session_id,
+            aggregated_node_id = %aggregation.aggregated_node_id,
+            selected_count = selected.len(),
+            is_terminal = params.mark_terminal,
+            "GoT aggregate best completed"
+        );
+
+        Ok(GotAggregateBestResult {
+            session_id: params.session_id,
+            skipped_count: active_count - selected.len(),
+            selected,
+            aggregation,
+            is_terminal: params.mark_terminal,
+        })
+    }
+
     /// Refine a node
     pub async fn refine(&self, params: GotRefineParams) -> AppResult<GotRefineResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Get the node
         let node = self
@@ -981,17 +2229,21 @@ impl GotMode {
         // Build messages for Langbase
         let messages = self.build_refine_messages(&node, params.problem.as_deref());
 
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+
         // Log invocation
         let mut invocation = Invocation::new(
             "reasoning.got.refine",
             serialize_for_log(&params, "reasoning.got.refine input"),
         )
         .with_session(&params.session_id)
-        .with_pipe(&self.got_pipe);
+        .with_pipe(&got_pipe);
 
         // Call Langbase
-        let request = PipeRequest::new(&self.got_pipe, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request = PipeRequest::new(&got_pipe, messages)
+            .with_output_schema(self.core.response_schema(SchemaKind::GotRefine, &got_pipe))
+            .with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -1006,6 +2258,9 @@ impl GotMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let refine_response = RefineResponse::from_completion(&response.completion)?;
@@ -1017,11 +2272,12 @@ impl GotMode {
             .with_score(refine_response.confidence)
             .as_active();
 
-        self.core.storage().create_graph_node(&refined_node).await?;
-
         // Create edge from original to refined
         let edge = GraphEdge::new(&params.session_id, &node.id, &refined_node.id)
             .with_type(EdgeType::Refines);
+        self.check_acyclic(&params.session_id, std::slice::from_ref(&edge))
+            .await?;
+        self.core.storage().create_graph_node(&refined_node).await?;
         self.core.storage().create_graph_edge(&edge).await?;
 
         // Mark original as no longer active
@@ -1066,35 +2322,42 @@ impl GotMode {
     pub async fn prune(&self, params: GotPruneParams) -> AppResult<GotPruneResult> {
         let start = Instant::now();
 
-        let threshold = params.threshold.unwrap_or(self.config.prune_threshold);
-
         // Get all nodes for session
         let nodes = self
             .core
             .storage()
             .get_session_graph_nodes(&params.session_id)
             .await?;
+        let config = self.config_for(&nodes);
+        let threshold = params.threshold.unwrap_or(config.prune_threshold);
 
-        // Find nodes to prune (low score, not root, not terminal)
+        // Find nodes to prune (low score, not root, not terminal). Only
+        // childless nodes are candidates, so the graph stays connected.
         let mut pruned_ids = Vec::new();
+        let mut leaves = Vec::new();
         for node in &nodes {
             // Skip root and terminal nodes
             if node.is_root || node.is_terminal {
                 continue;
             }
 
-            // Prune if score is below threshold (or unscored nodes)
-            if let Some(score) = node.score {
-                if score < threshold {
-                    // Check if this node has children (don't prune if it does)
-                    let children = self.core.storage().get_edges_from(&node.id).await?;
-                    if children.is_empty() {
-                        pruned_ids.push(node.id.clone());
-                    }
-                }
+            let children = self.core.storage().get_edges_from(&node.id).await?;
+            if !children.is_empty() {
+                continue;
+            }
+            match node.score {
+                Some(score) if score < threshold => pruned_ids.push(node.id.clone()),
+                _ => leaves.push(node),
             }
         }
 
+        // Among the surviving leaves, prune near-duplicates of a
+        // better-scoring node so the frontier stays diverse
+        let redundant_ids = self
+            .redundant_leaves(&nodes, leaves, &pruned_ids, config.similarity_threshold)
+            .await;
+        pruned_ids.extend(redundant_ids.iter().cloned());
+
         // Delete pruned nodes and their edges
         for id in &pruned_ids {
             // Delete edges to/from this node
@@ -1115,6 +2378,7 @@ impl GotMode {
         info!(
             session_id = %params.session_id,
             pruned_count = pruned_ids.len(),
+            redundant_count = redundant_ids.len(),
             remaining_count = remaining_count,
             threshold = threshold,
             latency_ms = latency,
@@ -1127,9 +2391,52 @@ impl GotMode {
             remaining_count,
             threshold_used: threshold,
             pruned_node_ids: pruned_ids,
+            redundant_node_ids: redundant_ids,
         })
     }
 
+    /// IDs of `leaves` whose content nearly duplicates a node that is kept
+    ///
+    /// Every node that is neither a leaf nor in `pruned` is kept; leaves are
+    /// then considered best score first, so of two duplicates the
+    /// better-scoring one survives.
+    async fn redundant_leaves(
+        &self,
+        nodes: &[GraphNode],
+        mut leaves: Vec<&GraphNode>,
+        pruned: &[String],
+        similarity_threshold: f64,
+    ) -> Vec<String> {
+        if self.embeddings.is_none() || leaves.is_empty() {
+            return Vec::new();
+        }
+        leaves.sort_by(|a, b| {
+            b.score
+                .unwrap_or(f64::MIN)
+                .total_cmp(&a.score.unwrap_or(f64::MIN))
+        });
+        let leaf_ids: HashSet<&str> = leaves.iter().map(|n| n.id.as_str()).collect();
+        let anchors: Vec<String> = nodes
+            .iter()
+            .filter(|n| !leaf_ids.contains(n.id.as_str()) && !pruned.contains(&n.id))
+            .map(|n| n.content.clone())
+            .collect();
+
+        let mut texts = anchors.clone();
+        texts.extend(leaves.iter().map(|n| n.content.clone()));
+        let Some(mut vectors) = self.embed(&texts).await else {
+            return Vec::new();
+        };
+        let candidates = vectors.split_off(anchors.len());
+        let keep = distinct_candidates(&vectors, &candidates, similarity_threshold);
+        leaves
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !keep.contains(i))
+            .map(|(_, n)| n.id.clone())
+            .collect()
+    }
+
     /// Finalize the graph and get conclusions
     pub async fn finalize(&self, params: GotFinalizeParams) -> AppResult<GotFinalizeResult> {
         let start = Instant::now();
@@ -1218,6 +2525,19 @@ impl GotMode {
         let root_nodes: Vec<_> = nodes.iter().filter(|n| n.is_root).collect();
         let max_depth = nodes.iter().map(|n| n.depth).max().unwrap_or(0);
 
+        let node_page = match params.limit {
+            Some(limit) => Some(
+                self.core
+                    .storage()
+                    .get_session_graph_nodes_page(
+                        &params.session_id,
+                        Pagination::new(limit, params.offset.unwrap_or(0)),
+                    )
+                    .await?,
+            ),
+            None => None,
+        };
+
         Ok(GotStateResult {
             session_id: params.session_id,
             total_nodes: nodes.len(),
@@ -1228,64 +2548,338 @@ impl GotMode {
             root_node_ids: root_nodes.iter().map(|n| n.id.clone()).collect(),
             active_node_ids: active_nodes.iter().map(|n| n.id.clone()).collect(),
             terminal_node_ids: terminal_nodes.iter().map(|n| n.id.clone()).collect(),
+            nodes: node_page,
         })
     }
 
     /// Detect cycles in the graph (returns true if cycle exists)
     pub async fn has_cycle(&self, session_id: &str) -> AppResult<bool> {
+        let edges = self.core.storage().get_session_edges(session_id).await?;
+        Ok(edges.iter().any(|e| e.from_node == e.to_node) || !cyclic_components(&edges).is_empty())
+    }
+
+    /// Check the session's graph for cycles, dangling or duplicate edges,
+    /// unreachable nodes, and other structural issues
+    pub async fn validate(&self, params: GotValidateParams) -> AppResult<GotValidateResult> {
         let nodes = self
             .core
             .storage()
-            .get_session_graph_nodes(session_id)
+            .get_session_graph_nodes(&params.session_id)
             .await?;
-        let edges = self.core.storage().get_session_edges(session_id).await?;
-
-        // Build adjacency list
-        let mut adj: std::collections::HashMap<String, Vec<String>> =
-            std::collections::HashMap::new();
-        for edge in &edges {
-            adj.entry(edge.from_node.clone())
-                .or_default()
-                .push(edge.to_node.clone());
-        }
-
-        // DFS-based cycle detection
-        let mut visited = HashSet::new();
-        let mut rec_stack = HashSet::new();
-
-        fn dfs(
-            node: &str,
-            adj: &std::collections::HashMap<String, Vec<String>>,
-            visited: &mut HashSet<String>,
-            rec_stack: &mut HashSet<String>,
-        ) -> bool {
-            visited.insert(node.to_string());
-            rec_stack.insert(node.to_string());
-
-            if let Some(neighbors) = adj.get(node) {
-                for neighbor in neighbors {
-                    if !visited.contains(neighbor) {
-                        if dfs(neighbor, adj, visited, rec_stack) {
-                            return true;
-                        }
-                    } else if rec_stack.contains(neighbor) {
-                        return true;
-                    }
-                }
+        if nodes.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session has no graph nodes: {}", params.session_id),
             }
-
-            rec_stack.remove(node);
-            false
+            .into());
         }
+        let edges = self
+            .core
+            .storage()
+            .get_session_edges(&params.session_id)
+            .await?;
 
-        for node in &nodes {
-            if !visited.contains(&node.id) && dfs(&node.id, &adj, &mut visited, &mut rec_stack) {
-                return Ok(true);
+        let result = validate_graph(&params.session_id, &nodes, &edges);
+        info!(
+            session_id = %params.session_id,
+            is_valid = result.is_valid,
+            is_dag = result.is_dag,
+            errors = result.error_count,
+            warnings = result.warning_count,
+            "GoT validate completed"
+        );
+        Ok(result)
+    }
+
+    /// Extract the highest-scoring path from a root to a terminal node as
+    /// an ordered explanation
+    pub async fn best_path(&self, params: GotBestPathParams) -> AppResult<GotBestPathResult> {
+        let nodes = self
+            .core
+            .storage()
+            .get_session_graph_nodes(&params.session_id)
+            .await?;
+        if nodes.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session has no graph nodes: {}", params.session_id),
+            }
+            .into());
+        }
+        let edges: Vec<GraphEdge> = self
+            .core
+            .storage()
+            .get_session_edges(&params.session_id)
+            .await?
+            .into_iter()
+            .filter(is_derivation)
+            .collect();
+        if edges.iter().any(|e| e.from_node == e.to_node) || !cyclic_components(&edges).is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: "Graph has a cycle; run reasoning_got_validate for details".to_string(),
             }
+            .into());
         }
 
-        Ok(false)
-    }
+        let targets: Vec<usize> = match &params.terminal_node_id {
+            Some(id) => vec![nodes.iter().position(|n| &n.id == id).ok_or_else(|| {
+                ToolError::Validation {
+                    field: "terminal_node_id".to_string(),
+                    reason: format!("Node not found: {}", id),
+                }
+            })?],
+            None => {
+                let terminals: Vec<usize> =
+                    (0..nodes.len()).filter(|&i| nodes[i].is_terminal).collect();
+                if terminals.is_empty() {
+                    let parents: HashSet<&str> =
+                        edges.iter().map(|e| e.from_node.as_str()).collect();
+                    (0..nodes.len())
+                        .filter(|&i| !parents.contains(nodes[i].id.as_str()))
+                        .collect()
+                } else {
+                    terminals
+                }
+            }
+        };
+
+        let path =
+            heaviest_path(&nodes, &edges, &targets).ok_or_else(|| ToolError::Validation {
+                field: "terminal_node_id".to_string(),
+                reason: "No root reaches the requested node".to_string(),
+            })?;
+
+        let mut cumulative_score = 0.0;
+        let steps: Vec<PathStep> = path
+            .iter()
+            .map(|&(i, via)| {
+                let node = &nodes[i];
+                cumulative_score += node.score.unwrap_or(0.0);
+                PathStep {
+                    node_id: node.id.clone(),
+                    content: node.content.clone(),
+                    node_type: node.node_type,
+                    depth: node.depth,
+                    score: node.score,
+                    via: via.map(|edge_type| edge_type.to_string()),
+                    cumulative_score,
+                }
+            })
+            .collect();
+        let scores: Vec<f64> = steps.iter().filter_map(|s| s.score).collect();
+        let mean_score =
+            (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64);
+        let end = &nodes[path[path.len() - 1].0];
+
+        info!(
+            session_id = %params.session_id,
+            end_node_id = %end.id,
+            steps = steps.len(),
+            total_score = cumulative_score,
+            "GoT best path extracted"
+        );
+
+        Ok(GotBestPathResult {
+            session_id: params.session_id,
+            root_node_id: steps[0].node_id.clone(),
+            end_node_id: end.id.clone(),
+            reached_terminal: end.is_terminal,
+            explanation: explain_path(&steps),
+            total_score: cumulative_score,
+            mean_score,
+            steps,
+        })
+    }
+
+    /// Pick up a stored graph: repair stale active flags, recompute the
+    /// frontier, and expand its best nodes within the graph's own limits
+    pub async fn resume(&self, params: GotResumeParams) -> AppResult<GotResumeResult> {
+        if params.expand > MAX_RESUME_EXPANSIONS {
+            return Err(ToolError::Validation {
+                field: "expand".to_string(),
+                reason: format!("Must be at most {}", MAX_RESUME_EXPANSIONS),
+            }
+            .into());
+        }
+        if params.k == Some(0) {
+            return Err(ToolError::Validation {
+                field: "k".to_string(),
+                reason: "Must be at least 1".to_string(),
+            }
+            .into());
+        }
+        let session = self
+            .core
+            .storage()
+            .get_session(&params.session_id)
+            .await?
+            .ok_or_else(|| ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session not found: {}", params.session_id),
+            })?;
+        if session.mode != "got" {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session {} is a {} session", session.id, session.mode),
+            }
+            .into());
+        }
+        let nodes = self
+            .core
+            .storage()
+            .get_session_graph_nodes(&session.id)
+            .await?;
+        if nodes.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session has no graph nodes: {}", session.id),
+            }
+            .into());
+        }
+        let config = self.config_for(&nodes);
+
+        // A node with derived children was already expanded; if a call was
+        // cut short before marking it, it would otherwise be expanded again
+        let edges = self.core.storage().get_session_edges(&session.id).await?;
+        let expanded_ids: HashSet<&str> = edges
+            .iter()
+            .filter(|e| is_derivation(e))
+            .map(|e| e.from_node.as_str())
+            .collect();
+        let mut repaired_node_ids = Vec::new();
+        for node in nodes
+            .iter()
+            .filter(|n| n.is_active && expanded_ids.contains(n.id.as_str()))
+        {
+            let mut updated = node.clone();
+            updated.is_active = false;
+            self.core.storage().update_graph_node(&updated).await?;
+            repaired_node_ids.push(node.id.clone());
+        }
+
+        let mut node_count = nodes.len();
+        let mut frontier = frontier_nodes(&nodes, &repaired_node_ids, config.max_depth);
+        let mut expanded = Vec::new();
+        let mut stopped_reason = None;
+        for node_id in frontier
+            .iter()
+            .take(params.expand)
+            .map(|n| n.node_id.clone())
+            .collect::<Vec<_>>()
+        {
+            let remaining = config.max_nodes.saturating_sub(node_count);
+            if remaining == 0 {
+                stopped_reason = Some(format!("Maximum nodes {} reached", config.max_nodes));
+                break;
+            }
+            let mut generate = GotGenerateParams::new(&session.id)
+                .with_node(node_id)
+                .with_k(params.k.unwrap_or(config.default_k).min(remaining))
+                .with_generation(params.generation);
+            if let Some(problem) = &params.problem {
+                generate = generate.with_problem(problem);
+            }
+            let result = self.generate(generate).await?;
+            node_count += result.continuations.len();
+            expanded.push(result);
+        }
+        if params.expand > frontier.len() && stopped_reason.is_none() {
+            stopped_reason = Some(format!("Only {} frontier nodes", frontier.len()));
+        }
+        if !expanded.is_empty() {
+            let nodes = self
+                .core
+                .storage()
+                .get_session_graph_nodes(&session.id)
+                .await?;
+            frontier = frontier_nodes(&nodes, &[], config.max_depth);
+        }
+
+        info!(
+            session_id = %session.id,
+            node_count = node_count,
+            repaired = repaired_node_ids.len(),
+            frontier = frontier.len(),
+            expanded = expanded.len(),
+            "GoT session resumed"
+        );
+
+        Ok(GotResumeResult {
+            session_id: session.id,
+            remaining_nodes: config.max_nodes.saturating_sub(node_count),
+            config,
+            node_count,
+            repaired_node_ids,
+            frontier,
+            expanded,
+            stopped_reason,
+        })
+    }
+
+    /// Limits the graph of `nodes` was initialized with, or the server's
+    /// for graphs initialized before limits were stored
+    fn config_for(&self, nodes: &[GraphNode]) -> GotConfig {
+        stored_config(nodes).unwrap_or_else(|| self.config.clone())
+    }
+
+    /// Check that storing `edges` keeps the session's graph acyclic
+    ///
+    /// A cycle is rejected in strict DAG mode and only logged otherwise.
+    async fn check_acyclic(&self, session_id: &str, edges: &[GraphEdge]) -> AppResult<()> {
+        let existing = self.core.storage().get_session_edges(session_id).await?;
+        let cycle = match closing_cycle(&existing, edges) {
+            Some(cycle) => cycle,
+            None => return Ok(()),
+        };
+        let nodes = self
+            .core
+            .storage()
+            .get_session_graph_nodes(session_id)
+            .await?;
+        if self.config_for(&nodes).strict_dag {
+            return Err(ToolError::Validation {
+                field: "edges".to_string(),
+                reason: format!("Edge would create a cycle through: {}", cycle.join(", ")),
+            }
+            .into());
+        }
+        warn!(
+            session_id = %session_id,
+            cycle = ?cycle,
+            "GoT edge creates a cycle; storing it because strict DAG mode is off"
+        );
+        Ok(())
+    }
+
+    /// Render the session's graph as DOT or GraphML
+    pub async fn export(&self, params: GotExportParams) -> AppResult<GotExportResult> {
+        let nodes = self
+            .core
+            .storage()
+            .get_session_graph_nodes(&params.session_id)
+            .await?;
+        if nodes.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session has no graph nodes: {}", params.session_id),
+            }
+            .into());
+        }
+        let edges = self
+            .core
+            .storage()
+            .get_session_edges(&params.session_id)
+            .await?;
+
+        Ok(GotExportResult {
+            content: render_graph(&params.session_id, &nodes, &edges, params.format),
+            session_id: params.session_id,
+            format: params.format,
+            node_count: nodes.len(),
+            edge_count: edges.len(),
+        })
+    }
 
     // ========================================================================
     // Helper methods for building Langbase messages
@@ -1315,6 +2909,24 @@ impl GotMode {
         messages
     }
 
+    /// Messages asking for continuation `index` of `k` generated in parallel
+    fn build_fan_out_generate_messages(
+        &self,
+        source_node: &GraphNode,
+        index: usize,
+        k: usize,
+        problem: Option<&str>,
+    ) -> Vec<Message> {
+        let mut messages = self.build_generate_messages(source_node, 1, problem);
+        messages.push(Message::user(format!(
+            "This is continuation {} of {} generated separately. Take a direction the \
+             others are unlikely to choose.",
+            index + 1,
+            k
+        )));
+        messages
+    }
+
     fn build_score_messages(&self, node: &GraphNode, problem: Option<&str>) -> Vec<Message> {
         let mut messages = Vec::new();
         messages.push(Message::system(GOT_SCORE_PROMPT));
@@ -1334,6 +2946,28 @@ impl GotMode {
         messages
     }
 
+    fn build_compare_messages(
+        &self,
+        a: &GraphNode,
+        b: &GraphNode,
+        problem: Option<&str>,
+    ) -> Vec<Message> {
+        let mut messages = Vec::new();
+        messages.push(Message::system(GOT_COMPARE_PROMPT));
+
+        let mut user_msg = format!(
+            "Which thought is better?\n\nA: \"{}\"\n\nB: \"{}\"",
+            a.content, b.content
+        );
+
+        if let Some(p) = problem {
+            user_msg.push_str(&format!("\n\nProblem context: {}", p));
+        }
+
+        messages.push(Message::user(user_msg));
+        messages
+    }
+
     fn build_aggregate_messages(&self, nodes: &[GraphNode], problem: Option<&str>) -> Vec<Message> {
         let mut messages = Vec::new();
         messages.push(Message::system(GOT_AGGREGATE_PROMPT));
@@ -1377,6 +3011,779 @@ impl GotMode {
     }
 }
 
+// ============================================================================
+// Graph structure
+// ============================================================================
+
+/// Node sets that lie on a common cycle, found as the strongly connected
+/// components (Tarjan) of more than one node
+///
+/// Self-loops are not reported here. Each component is sorted, and so is
+/// the list of components.
+fn cyclic_components(edges: &[GraphEdge]) -> Vec<Vec<String>> {
+    #[derive(Default)]
+    struct Search<'a> {
+        adjacency: HashMap<&'a str, Vec<&'a str>>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<Vec<String>>,
+    }
+
+    impl<'a> Search<'a> {
+        fn visit(&mut self, node: &'a str) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            let neighbors = self.adjacency.get(node).cloned().unwrap_or_default();
+            for next in neighbors {
+                let reached = if !self.index.contains_key(next) {
+                    self.visit(next);
+                    self.low[next]
+                } else if self.on_stack.contains(next) {
+                    self.index[next]
+                } else {
+                    continue;
+                };
+                if reached < self.low[node] {
+                    self.low.insert(node, reached);
+                }
+            }
+
+            if self.low[node] == index {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut search = Search::default();
+    for edge in edges {
+        search
+            .adjacency
+            .entry(&edge.from_node)
+            .or_default()
+            .push(&edge.to_node);
+    }
+    for edge in edges {
+        if !search.index.contains_key(edge.from_node.as_str()) {
+            search.visit(&edge.from_node);
+        }
+    }
+    search.components.sort();
+    search.components
+}
+
+/// The nodes of a cycle that adding `new` edges to `existing` would close,
+/// if any
+///
+/// Cycles already present in `existing` are ignored; only a cycle running
+/// through one of the new edges counts.
+fn closing_cycle(existing: &[GraphEdge], new: &[GraphEdge]) -> Option<Vec<String>> {
+    if let Some(edge) = new.iter().find(|e| e.from_node == e.to_node) {
+        return Some(vec![edge.from_node.clone()]);
+    }
+    let combined: Vec<GraphEdge> = existing.iter().chain(new).cloned().collect();
+    cyclic_components(&combined).into_iter().find(|component| {
+        new.iter()
+            .any(|e| component.contains(&e.from_node) && component.contains(&e.to_node))
+    })
+}
+
+/// Active, non-terminal nodes above `max_depth` (other than `excluded`),
+/// best score first with unscored nodes last
+fn frontier_nodes(nodes: &[GraphNode], excluded: &[String], max_depth: usize) -> Vec<FrontierNode> {
+    let mut frontier: Vec<&GraphNode> = nodes
+        .iter()
+        .filter(|n| n.is_active && !n.is_terminal && (n.depth as usize) < max_depth)
+        .filter(|n| !excluded.contains(&n.id))
+        .collect();
+    frontier.sort_by(|a, b| {
+        b.score
+            .unwrap_or(f64::MIN)
+            .total_cmp(&a.score.unwrap_or(f64::MIN))
+    });
+    frontier
+        .into_iter()
+        .map(|n| FrontierNode {
+            node_id: n.id.clone(),
+            content: n.content.clone(),
+            score: n.score,
+            depth: n.depth,
+        })
+        .collect()
+}
+
+/// Config stored on the first root of a graph by initialize
+fn stored_config(nodes: &[GraphNode]) -> Option<GotConfig> {
+    nodes
+        .iter()
+        .filter(|n| n.is_root)
+        .find_map(|n| n.metadata.as_ref()?.get("got_config"))
+        .and_then(|config| serde_json::from_value(config.clone()).ok())
+}
+
+/// Whether an edge records one node being derived from another, as opposed
+/// to a relation between independent nodes
+fn is_derivation(edge: &GraphEdge) -> bool {
+    matches!(
+        edge.edge_type,
+        EdgeType::Generates | EdgeType::Refines | EdgeType::Aggregates
+    )
+}
+
+/// The highest-scoring path of derivation edges from a root to one of
+/// `targets`, as indices into `nodes` with the edge leading into each
+///
+/// A path scores the sum of its nodes' scores, unscored nodes counting as
+/// 0; ties go to the shorter path, then to the earlier node. Nodes on a
+/// cycle are never reached, so callers should reject cyclic graphs first.
+fn heaviest_path(
+    nodes: &[GraphNode],
+    edges: &[GraphEdge],
+    targets: &[usize],
+) -> Option<Vec<(usize, Option<EdgeType>)>> {
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    let mut children: Vec<Vec<(usize, EdgeType)>> = vec![Vec::new(); nodes.len()];
+    let mut incoming = vec![0usize; nodes.len()];
+    for edge in edges.iter().filter(|e| is_derivation(e)) {
+        if let (Some(&from), Some(&to)) = (
+            index.get(edge.from_node.as_str()),
+            index.get(edge.to_node.as_str()),
+        ) {
+            children[from].push((to, edge.edge_type));
+            incoming[to] += 1;
+        }
+    }
+
+    /// Best way found so far to reach a node
+    #[derive(Clone, Copy)]
+    struct Reach {
+        total: f64,
+        steps: usize,
+        previous: Option<(usize, EdgeType)>,
+    }
+
+    impl Reach {
+        fn beats(&self, other: Option<Reach>) -> bool {
+            other.map_or(true, |o| {
+                self.total > o.total || (self.total == o.total && self.steps < o.steps)
+            })
+        }
+    }
+
+    // Relax edges in topological order, starting from the roots
+    let score = |i: usize| nodes[i].score.unwrap_or(0.0);
+    let mut best: Vec<Option<Reach>> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            n.is_root.then(|| Reach {
+                total: score(i),
+                steps: 1,
+                previous: None,
+            })
+        })
+        .collect();
+    let mut ready: Vec<usize> = (0..nodes.len())
+        .filter(|&i| incoming[i] == 0)
+        .rev()
+        .collect();
+    while let Some(node) = ready.pop() {
+        for &(child, edge_type) in &children[node] {
+            if let Some(reach) = best[node] {
+                let candidate = Reach {
+                    total: reach.total + score(child),
+                    steps: reach.steps + 1,
+                    previous: Some((node, edge_type)),
+                };
+                if candidate.beats(best[child]) {
+                    best[child] = Some(candidate);
+                }
+            }
+            incoming[child] -= 1;
+            if incoming[child] == 0 {
+                ready.push(child);
+            }
+        }
+    }
+
+    let mut end: Option<usize> = None;
+    for &target in targets {
+        if let Some(reach) = best[target] {
+            if reach.beats(end.and_then(|e| best[e])) {
+                end = Some(target);
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut current = end?;
+    loop {
+        match best[current].and_then(|reach| reach.previous) {
+            Some((previous, edge_type)) => {
+                path.push((current, Some(edge_type)));
+                current = previous;
+            }
+            None => {
+                path.push((current, None));
+                break;
+            }
+        }
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Numbered narrative of a path, one step per line
+fn explain_path(steps: &[PathStep]) -> String {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let mut label = step.node_type.to_string();
+            if let Some(via) = &step.via {
+                label = format!("{}, via {}", label, via);
+            }
+            if let Some(score) = step.score {
+                label = format!("{}, score {:.2}", label, score);
+            }
+            format!("{}. [{}] {}", i + 1, label, step.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl GraphIssue {
+    fn new(
+        kind: GraphIssueKind,
+        message: String,
+        node_ids: Vec<String>,
+        edge_ids: Vec<String>,
+    ) -> Self {
+        Self {
+            kind,
+            severity: kind.severity(),
+            message,
+            node_ids,
+            edge_ids,
+        }
+    }
+}
+
+/// Check a session's nodes and edges for structural issues
+fn validate_graph(session_id: &str, nodes: &[GraphNode], edges: &[GraphEdge]) -> GotValidateResult {
+    let by_id: HashMap<&str, &GraphNode> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let mut issues = Vec::new();
+
+    // Edges one at a time; only edges between known nodes go on to the
+    // cycle and reachability checks
+    let mut connected = Vec::new();
+    let mut seen = HashSet::new();
+    for edge in edges {
+        let missing: Vec<String> = [&edge.from_node, &edge.to_node]
+            .into_iter()
+            .filter(|id| !by_id.contains_key(id.as_str()))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            issues.push(GraphIssue::new(
+                GraphIssueKind::DanglingEdge,
+                format!(
+                    "Edge {} references nodes outside the graph: {}",
+                    edge.id,
+                    missing.join(", ")
+                ),
+                missing,
+                vec![edge.id.clone()],
+            ));
+            continue;
+        }
+        connected.push(edge.clone());
+
+        if edge.from_node == edge.to_node {
+            issues.push(GraphIssue::new(
+                GraphIssueKind::SelfLoop,
+                format!(
+                    "Edge {} connects node {} to itself",
+                    edge.id, edge.from_node
+                ),
+                vec![edge.from_node.clone()],
+                vec![edge.id.clone()],
+            ));
+        }
+        if !seen.insert((
+            edge.from_node.as_str(),
+            edge.to_node.as_str(),
+            edge.edge_type.to_string(),
+        )) {
+            issues.push(GraphIssue::new(
+                GraphIssueKind::DuplicateEdge,
+                format!(
+                    "Edge {} repeats a {} edge from {} to {}",
+                    edge.id, edge.edge_type, edge.from_node, edge.to_node
+                ),
+                vec![edge.from_node.clone(), edge.to_node.clone()],
+                vec![edge.id.clone()],
+            ));
+        }
+        let (from, to) = (by_id[edge.from_node.as_str()], by_id[edge.to_node.as_str()]);
+        if matches!(edge.edge_type, EdgeType::Generates | EdgeType::Aggregates)
+            && from.id != to.id
+            && to.depth <= from.depth
+        {
+            issues.push(GraphIssue::new(
+                GraphIssueKind::DepthMismatch,
+                format!(
+                    "Node {} at depth {} derives from node {} at depth {}",
+                    to.id, to.depth, from.id, from.depth
+                ),
+                vec![from.id.clone(), to.id.clone()],
+                vec![edge.id.clone()],
+            ));
+        }
+    }
+
+    for component in cyclic_components(&connected) {
+        let edge_ids = connected
+            .iter()
+            .filter(|e| component.contains(&e.from_node) && component.contains(&e.to_node))
+            .map(|e| e.id.clone())
+            .collect();
+        issues.push(GraphIssue::new(
+            GraphIssueKind::Cycle,
+            format!("{} nodes form a cycle", component.len()),
+            component,
+            edge_ids,
+        ));
+    }
+
+    let roots: Vec<&GraphNode> = nodes.iter().filter(|n| n.is_root).collect();
+    if roots.is_empty() && !nodes.is_empty() {
+        issues.push(GraphIssue::new(
+            GraphIssueKind::MissingRoot,
+            "No node is marked as a root".to_string(),
+            Vec::new(),
+            Vec::new(),
+        ));
+    }
+    if roots.len() > 1 {
+        issues.push(GraphIssue::new(
+            GraphIssueKind::MultipleRoots,
+            format!("{} nodes are marked as roots", roots.len()),
+            roots.iter().map(|n| n.id.clone()).collect(),
+            Vec::new(),
+        ));
+    }
+
+    if !roots.is_empty() {
+        let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &connected {
+            children
+                .entry(&edge.from_node)
+                .or_default()
+                .push(&edge.to_node);
+        }
+        let mut reached: HashSet<&str> = roots.iter().map(|n| n.id.as_str()).collect();
+        let mut queue: Vec<&str> = reached.iter().copied().collect();
+        while let Some(id) = queue.pop() {
+            for &child in children.get(id).into_iter().flatten() {
+                if reached.insert(child) {
+                    queue.push(child);
+                }
+            }
+        }
+        let unreachable: Vec<String> = nodes
+            .iter()
+            .filter(|n| !reached.contains(n.id.as_str()))
+            .map(|n| n.id.clone())
+            .collect();
+        if !unreachable.is_empty() {
+            issues.push(GraphIssue::new(
+                GraphIssueKind::Unreachable,
+                format!("{} nodes cannot be reached from a root", unreachable.len()),
+                unreachable,
+                Vec::new(),
+            ));
+        }
+    }
+
+    let active_terminals: Vec<String> = nodes
+        .iter()
+        .filter(|n| n.is_terminal && n.is_active)
+        .map(|n| n.id.clone())
+        .collect();
+    if !active_terminals.is_empty() {
+        issues.push(GraphIssue::new(
+            GraphIssueKind::ActiveTerminal,
+            format!("{} terminal nodes are still active", active_terminals.len()),
+            active_terminals,
+            Vec::new(),
+        ));
+    }
+
+    // Errors first, otherwise in the order found
+    issues.sort_by_key(|issue| issue.severity == IssueSeverity::Warning);
+    let error_count = issues
+        .iter()
+        .filter(|issue| issue.severity == IssueSeverity::Error)
+        .count();
+    let is_dag = !issues
+        .iter()
+        .any(|issue| matches!(issue.kind, GraphIssueKind::Cycle | GraphIssueKind::SelfLoop));
+
+    GotValidateResult {
+        session_id: session_id.to_string(),
+        is_valid: error_count == 0,
+        is_dag,
+        node_count: nodes.len(),
+        edge_count: edges.len(),
+        error_count,
+        warning_count: issues.len() - error_count,
+        issues,
+    }
+}
+
+// ============================================================================
+// Pairwise ranking
+// ============================================================================
+
+/// Most Bradley-Terry fitting iterations.
+const BRADLEY_TERRY_MAX_ITERATIONS: usize = 500;
+
+/// Bradley-Terry fitting stops once no strength changes by more than this.
+const BRADLEY_TERRY_TOLERANCE: f64 = 1e-9;
+
+impl RankingMethod {
+    /// Ratings of `n` nodes fitted to `outcomes`, each the indices of the
+    /// nodes shown first and second and the first node's score (1 for a
+    /// win, 0.5 for a tie, 0 for a loss)
+    fn ratings(self, n: usize, outcomes: &[(usize, usize, f64)]) -> Vec<f64> {
+        match self {
+            RankingMethod::BradleyTerry => bradley_terry_strengths(n, outcomes),
+            RankingMethod::Elo => elo_ratings(n, outcomes),
+        }
+    }
+
+    /// Probability that a node rated `a` beats one rated `b`
+    fn win_probability(self, a: f64, b: f64) -> f64 {
+        match self {
+            RankingMethod::BradleyTerry => a / (a + b),
+            RankingMethod::Elo => 1.0 / (1.0 + 10f64.powf((b - a) / 400.0)),
+        }
+    }
+}
+
+/// Bradley-Terry strengths fitted by minorization-maximization
+///
+/// Each node also plays one virtual tie against an opponent of strength 1,
+/// which keeps strengths finite for nodes that never won (or never lost)
+/// and anchors the scale: an unjudged node has strength 1.
+fn bradley_terry_strengths(n: usize, outcomes: &[(usize, usize, f64)]) -> Vec<f64> {
+    let mut wins = vec![0.5; n];
+    for &(a, b, first_score) in outcomes {
+        wins[a] += first_score;
+        wins[b] += 1.0 - first_score;
+    }
+
+    let mut strengths = vec![1.0; n];
+    for _ in 0..BRADLEY_TERRY_MAX_ITERATIONS {
+        let mut games: Vec<f64> = strengths.iter().map(|p| 1.0 / (p + 1.0)).collect();
+        for &(a, b, _) in outcomes {
+            let pair = 1.0 / (strengths[a] + strengths[b]);
+            games[a] += pair;
+            games[b] += pair;
+        }
+        let next: Vec<f64> = wins.iter().zip(&games).map(|(w, g)| w / g).collect();
+        let change = next
+            .iter()
+            .zip(&strengths)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        strengths = next;
+        if change < BRADLEY_TERRY_TOLERANCE {
+            break;
+        }
+    }
+    strengths
+}
+
+/// Elo ratings after applying `outcomes` in order
+fn elo_ratings(n: usize, outcomes: &[(usize, usize, f64)]) -> Vec<f64> {
+    let mut ratings = vec![ELO_INITIAL_RATING; n];
+    for &(a, b, first_score) in outcomes {
+        let expected = RankingMethod::Elo.win_probability(ratings[a], ratings[b]);
+        let delta = ELO_K * (first_score - expected);
+        ratings[a] += delta;
+        ratings[b] -= delta;
+    }
+    ratings
+}
+
+/// Each node's mean probability of beating each of the other nodes
+fn expected_scores(ratings: &[f64], win_probability: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+    let others = ratings.len().saturating_sub(1).max(1) as f64;
+    ratings
+        .iter()
+        .enumerate()
+        .map(|(i, &rating)| {
+            ratings
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &other)| win_probability(rating, other))
+                .sum::<f64>()
+                / others
+        })
+        .collect()
+}
+
+// ============================================================================
+// Graph rendering
+// ============================================================================
+
+/// Maximum number of characters of node content shown in a DOT label.
+const EXPORT_LABEL_CHARS: usize = 60;
+
+/// Indices of `candidates` to keep, in order
+///
+/// A candidate is kept when its cosine similarity to every `existing` vector
+/// and to every candidate kept before it is below `threshold`.
+fn distinct_candidates(
+    existing: &[Vec<f32>],
+    candidates: &[Vec<f32>],
+    threshold: f64,
+) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        let duplicate = existing
+            .iter()
+            .chain(kept.iter().map(|&k| &candidates[k]))
+            .any(|other| cosine_similarity(candidate, other) >= threshold);
+        if !duplicate {
+            kept.push(i);
+        }
+    }
+    kept
+}
+
+/// Render graph nodes and edges in the given format.
+///
+/// Pruned nodes are kept so the export shows everything that was explored;
+/// they are drawn dashed and grey in DOT and carry `active=false` in GraphML.
+pub fn render_graph(
+    session_id: &str,
+    nodes: &[GraphNode],
+    edges: &[GraphEdge],
+    format: GraphExportFormat,
+) -> String {
+    match format {
+        GraphExportFormat::Dot => render_dot(session_id, nodes, edges),
+        GraphExportFormat::Graphml => render_graphml(session_id, nodes, edges),
+    }
+}
+
+fn render_dot(session_id: &str, nodes: &[GraphNode], edges: &[GraphEdge]) -> String {
+    let mut out = format!("digraph \"{}\" {{\n", escape_dot(session_id));
+    out.push_str("  rankdir=TB;\n");
+    out.push_str("  node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+    out.push_str("  edge [fontname=\"Helvetica\", fontsize=10];\n");
+
+    for node in nodes {
+        let mut label = truncate_label(&node.content, EXPORT_LABEL_CHARS);
+        label.push_str(&format!("\n[{}", node.node_type));
+        if let Some(score) = node.score {
+            label.push_str(&format!(" {:.2}", score));
+        }
+        label.push(']');
+
+        let (shape, fill) = match node.node_type {
+            NodeType::Root => ("ellipse", "lightblue"),
+            NodeType::Hypothesis => ("box", "lightyellow"),
+            NodeType::Aggregation => ("hexagon", "plum"),
+            NodeType::Refinement => ("box", "lightcyan"),
+            NodeType::Conclusion | NodeType::Terminal => ("doubleoctagon", "palegreen"),
+            NodeType::Thought => ("box", "white"),
+        };
+        let style = if node.is_active {
+            "rounded,filled"
+        } else {
+            "rounded,filled,dashed"
+        };
+        let color = if node.is_active { "black" } else { "gray" };
+        let peripheries = if node.is_terminal { 2 } else { 1 };
+
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\", tooltip=\"{}\", shape={}, fillcolor={}, style=\"{}\", color={}, fontcolor={}, peripheries={}];\n",
+            escape_dot(&node.id),
+            escape_dot(&label),
+            escape_dot(&node.content),
+            shape,
+            fill,
+            style,
+            color,
+            color,
+            peripheries
+        ));
+    }
+
+    for edge in edges {
+        let style = match edge.edge_type {
+            EdgeType::Contradicts => ", style=dashed, color=red",
+            EdgeType::Supports => ", color=darkgreen",
+            _ => "",
+        };
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\", penwidth={:.2}{}];\n",
+            escape_dot(&edge.from_node),
+            escape_dot(&edge.to_node),
+            edge.edge_type,
+            0.5 + 2.5 * edge.weight.clamp(0.0, 1.0),
+            style
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn render_graphml(session_id: &str, nodes: &[GraphNode], edges: &[GraphEdge]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, target, name, kind) in [
+        ("content", "node", "content", "string"),
+        ("node_type", "node", "node_type", "string"),
+        ("score", "node", "score", "double"),
+        ("depth", "node", "depth", "int"),
+        ("active", "node", "active", "boolean"),
+        ("terminal", "node", "terminal", "boolean"),
+        ("root", "node", "root", "boolean"),
+        ("edge_type", "edge", "edge_type", "string"),
+        ("weight", "edge", "weight", "double"),
+    ] {
+        out.push_str(&format!(
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            id, target, name, kind
+        ));
+    }
+    out.push_str(&format!(
+        "  <graph id=\"{}\" edgedefault=\"directed\">\n",
+        escape_xml(session_id)
+    ));
+
+    for node in nodes {
+        out.push_str(&format!("    <node id=\"{}\">\n", escape_xml(&node.id)));
+        out.push_str(&format!(
+            "      <data key=\"content\">{}</data>\n",
+            escape_xml(&node.content)
+        ));
+        out.push_str(&format!(
+            "      <data key=\"node_type\">{}</data>\n",
+            node.node_type
+        ));
+        if let Some(score) = node.score {
+            out.push_str(&format!("      <data key=\"score\">{}</data>\n", score));
+        }
+        out.push_str(&format!(
+            "      <data key=\"depth\">{}</data>\n",
+            node.depth
+        ));
+        out.push_str(&format!(
+            "      <data key=\"active\">{}</data>\n",
+            node.is_active
+        ));
+        out.push_str(&format!(
+            "      <data key=\"terminal\">{}</data>\n",
+            node.is_terminal
+        ));
+        out.push_str(&format!(
+            "      <data key=\"root\">{}</data>\n",
+            node.is_root
+        ));
+        out.push_str("    </node>\n");
+    }
+
+    for edge in edges {
+        out.push_str(&format!(
+            "    <edge id=\"{}\" source=\"{}\" target=\"{}\">\n",
+            escape_xml(&edge.id),
+            escape_xml(&edge.from_node),
+            escape_xml(&edge.to_node)
+        ));
+        out.push_str(&format!(
+            "      <data key=\"edge_type\">{}</data>\n",
+            edge.edge_type
+        ));
+        out.push_str(&format!(
+            "      <data key=\"weight\">{}</data>\n",
+            edge.weight
+        ));
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Shorten text to at most `max_chars` characters, collapsing whitespace.
+fn truncate_label(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        text
+    } else {
+        let mut short: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        short.push_str("...");
+        short
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 // ============================================================================
 // Builder implementations
 // ============================================================================
@@ -1419,6 +3826,7 @@ impl GotGenerateParams {
             node_id: None,
             k: default_k(),
             problem: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -1439,6 +3847,12 @@ impl GotGenerateParams {
         self.problem = Some(problem.into());
         self
     }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl GotScoreParams {
@@ -1448,14 +3862,66 @@ impl GotScoreParams {
             session_id: session_id.into(),
             node_id: node_id.into(),
             problem: None,
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Set the problem context.
+    pub fn with_problem(mut self, problem: impl Into<String>) -> Self {
+        self.problem = Some(problem.into());
+        self
+    }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+impl GotRankParams {
+    /// Create new rank parameters for the children of the given node.
+    pub fn new(session_id: impl Into<String>, parent_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            parent_id: Some(parent_id.into()),
+            node_ids: Vec::new(),
+            method: RankingMethod::default(),
+            rounds: default_rounds(),
+            problem: None,
+            generation: GenerationParams::default(),
         }
     }
 
+    /// Rank these nodes instead of the parent's children.
+    pub fn with_nodes(mut self, node_ids: Vec<String>) -> Self {
+        self.node_ids = node_ids;
+        self
+    }
+
+    /// Set the rating model.
+    pub fn with_method(mut self, method: RankingMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the number of comparison rounds.
+    pub fn with_rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
     /// Set the problem context.
     pub fn with_problem(mut self, problem: impl Into<String>) -> Self {
         self.problem = Some(problem.into());
         self
     }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl GotAggregateParams {
@@ -1465,6 +3931,7 @@ impl GotAggregateParams {
             session_id: session_id.into(),
             node_ids,
             problem: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -1473,6 +3940,56 @@ impl GotAggregateParams {
         self.problem = Some(problem.into());
         self
     }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+impl GotAggregateBestParams {
+    /// Create new aggregate-best parameters for the given session.
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            k: default_k(),
+            min_score: None,
+            mark_terminal: false,
+            problem: None,
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Set the number of nodes to merge.
+    pub fn with_k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Only merge nodes scoring at least `min_score`.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Mark the aggregated node terminal.
+    pub fn as_terminal(mut self) -> Self {
+        self.mark_terminal = true;
+        self
+    }
+
+    /// Set the problem context.
+    pub fn with_problem(mut self, problem: impl Into<String>) -> Self {
+        self.problem = Some(problem.into());
+        self
+    }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl GotRefineParams {
@@ -1482,6 +3999,7 @@ impl GotRefineParams {
             session_id: session_id.into(),
             node_id: node_id.into(),
             problem: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -1490,6 +4008,12 @@ impl GotRefineParams {
         self.problem = Some(problem.into());
         self
     }
+
+    /// Override the pipe's model parameters.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 impl GotPruneParams {
@@ -1529,6 +4053,93 @@ impl GotGetStateParams {
     pub fn new(session_id: impl Into<String>) -> Self {
         Self {
             session_id: session_id.into(),
+            limit: None,
+            offset: None,
         }
     }
+
+    /// Include a page of node records in the result.
+    pub fn with_page(mut self, limit: u32, offset: u32) -> Self {
+        self.limit = Some(limit);
+        self.offset = Some(offset);
+        self
+    }
+}
+
+impl GotValidateParams {
+    /// Create validate parameters for a session.
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+        }
+    }
+}
+
+impl GotBestPathParams {
+    /// Create best-path parameters for a session.
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            terminal_node_id: None,
+        }
+    }
+
+    /// End the path at a specific node.
+    pub fn with_terminal_node(mut self, node_id: impl Into<String>) -> Self {
+        self.terminal_node_id = Some(node_id.into());
+        self
+    }
+}
+
+impl GotResumeParams {
+    /// Create resume parameters that expand the best frontier node.
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            expand: default_resume_expand(),
+            k: None,
+            problem: None,
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Set how many frontier nodes to expand (0 only reloads).
+    pub fn with_expand(mut self, expand: usize) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    /// Set continuations per expanded node.
+    pub fn with_k(mut self, k: usize) -> Self {
+        self.k = Some(k);
+        self
+    }
+
+    /// Set the problem context.
+    pub fn with_problem(mut self, problem: impl Into<String>) -> Self {
+        self.problem = Some(problem.into());
+        self
+    }
+
+    /// Set model parameter overrides.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+impl GotExportParams {
+    /// Create export parameters for a session (DOT by default).
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            format: GraphExportFormat::default(),
+        }
+    }
+
+    /// Set the output format.
+    pub fn with_format(mut self, format: GraphExportFormat) -> Self {
+        self.format = format;
+        self
+    }
 }
@@ -25,6 +25,8 @@ fn test_got_config_serialize() {
         max_depth: 5,
         default_k: 4,
         prune_threshold: 0.4,
+        similarity_threshold: 0.9,
+        strict_dag: false,
     };
     let json = serde_json::to_string(&config).unwrap();
     assert!(json.contains("\"max_nodes\":50"));
@@ -191,6 +193,24 @@ fn test_got_aggregate_params_builder() {
     assert_eq!(params.problem, Some("Synthesize ideas".to_string()));
 }
 
+#[test]
+fn test_aggregate_best_params_defaults() {
+    let params: GotAggregateBestParams = serde_json::from_str(r#"{"session_id": "s"}"#).unwrap();
+    assert_eq!(params.k, 3);
+    assert!(params.min_score.is_none());
+    assert!(!params.mark_terminal);
+
+    let params = GotAggregateBestParams::new("s")
+        .with_k(4)
+        .with_min_score(0.6)
+        .as_terminal()
+        .with_problem("Ship faster");
+    assert_eq!(params.k, 4);
+    assert_eq!(params.min_score, Some(0.6));
+    assert!(params.mark_terminal);
+    assert_eq!(params.problem.as_deref(), Some("Ship faster"));
+}
+
 // ============================================================================
 // Refine Params Tests
 // ============================================================================
@@ -268,6 +288,361 @@ fn test_got_get_state_params_new() {
     assert_eq!(params.session_id, "sess-123");
 }
 
+// ============================================================================
+// Export Tests
+// ============================================================================
+
+fn export_fixture() -> (Vec<GraphNode>, Vec<GraphEdge>) {
+    let root = GraphNode::new("sess-1", "Root \"question\" <a & b>")
+        .with_type(NodeType::Root)
+        .as_root();
+    let kept = GraphNode::new("sess-1", "Promising idea")
+        .with_score(0.85)
+        .with_depth(1)
+        .as_terminal();
+    let pruned = GraphNode::new("sess-1", "Weak idea")
+        .with_score(0.1)
+        .with_depth(1)
+        .as_inactive();
+    let edges = vec![
+        GraphEdge::new("sess-1", &root.id, &kept.id),
+        GraphEdge::new("sess-1", &root.id, &pruned.id)
+            .with_type(EdgeType::Contradicts)
+            .with_weight(0.2),
+    ];
+    (vec![root, kept, pruned], edges)
+}
+
+#[test]
+fn test_graph_export_format_from_str() {
+    assert_eq!(
+        "dot".parse::<GraphExportFormat>().unwrap(),
+        GraphExportFormat::Dot
+    );
+    assert_eq!(
+        "GraphML".parse::<GraphExportFormat>().unwrap(),
+        GraphExportFormat::Graphml
+    );
+    assert!("svg".parse::<GraphExportFormat>().is_err());
+    assert_eq!(GraphExportFormat::Graphml.to_string(), "graphml");
+}
+
+#[test]
+fn test_got_export_params_defaults_to_dot() {
+    let params: GotExportParams = serde_json::from_str(r#"{"session_id": "s"}"#).unwrap();
+    assert_eq!(params.format, GraphExportFormat::Dot);
+
+    let params = GotExportParams::new("s").with_format(GraphExportFormat::Graphml);
+    assert_eq!(params.format, GraphExportFormat::Graphml);
+}
+
+#[test]
+fn test_render_dot() {
+    let (nodes, edges) = export_fixture();
+    let dot = render_graph("sess-1", &nodes, &edges, GraphExportFormat::Dot);
+
+    assert!(dot.starts_with("digraph \"sess-1\" {"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("Root \\\"question\\\" <a & b>"));
+    assert!(dot.contains("[thought 0.85]"));
+    assert!(dot.contains("peripheries=2"));
+    assert!(dot.contains("rounded,filled,dashed"));
+    assert!(dot.contains(&format!("\"{}\" -> \"{}\"", nodes[0].id, nodes[1].id)));
+    assert!(dot.contains("label=\"contradicts\", penwidth=1.00, style=dashed"));
+}
+
+#[test]
+fn test_render_dot_truncates_long_labels() {
+    let node = GraphNode::new("s", "x".repeat(100));
+    let dot = render_graph("s", &[node], &[], GraphExportFormat::Dot);
+    assert!(dot.contains(&format!("label=\"{}...\\n[thought]", "x".repeat(57))));
+}
+
+#[test]
+fn test_render_graphml() {
+    let (nodes, edges) = export_fixture();
+    let xml = render_graph("sess-1", &nodes, &edges, GraphExportFormat::Graphml);
+
+    assert!(xml.starts_with("<?xml"));
+    assert!(xml.contains("<graph id=\"sess-1\" edgedefault=\"directed\">"));
+    assert!(xml.contains("Root &quot;question&quot; &lt;a &amp; b&gt;"));
+    assert_eq!(xml.matches("<node ").count(), 3);
+    assert_eq!(xml.matches("<edge ").count(), 2);
+    assert!(xml.contains("<data key=\"score\">0.85</data>"));
+    assert!(xml.contains("<data key=\"active\">false</data>"));
+    assert!(xml.contains("<data key=\"edge_type\">contradicts</data>"));
+    assert!(xml.trim_end().ends_with("</graphml>"));
+}
+
+// ============================================================================
+// Validate Tests
+// ============================================================================
+
+fn chain(ids: &[&str]) -> Vec<GraphEdge> {
+    ids.windows(2)
+        .map(|pair| GraphEdge::new("s", pair[0], pair[1]))
+        .collect()
+}
+
+#[test]
+fn test_got_config_strict_dag_defaults_off() {
+    assert!(!GotConfig::default().strict_dag);
+    let config: GotConfig = serde_json::from_str(r#"{"strict_dag": true}"#).unwrap();
+    assert!(config.strict_dag);
+}
+
+#[test]
+fn test_cyclic_components() {
+    assert!(cyclic_components(&chain(&["a", "b", "c"])).is_empty());
+
+    let mut edges = chain(&["a", "b", "c", "a"]);
+    edges.extend(chain(&["c", "d", "e", "d"]));
+    edges.push(GraphEdge::new("s", "f", "f"));
+    assert_eq!(
+        cyclic_components(&edges),
+        vec![vec!["a", "b", "c"], vec!["d", "e"]]
+    );
+}
+
+#[test]
+fn test_closing_cycle() {
+    let existing = chain(&["a", "b", "c"]);
+    assert!(closing_cycle(&existing, &chain(&["c", "d"])).is_none());
+    assert_eq!(
+        closing_cycle(&existing, &chain(&["c", "a"])),
+        Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+    );
+    assert_eq!(
+        closing_cycle(&existing, &chain(&["d", "d"])),
+        Some(vec!["d".to_string()])
+    );
+
+    // A cycle already present is not blamed on an unrelated new edge
+    let cyclic = chain(&["a", "b", "a"]);
+    assert!(closing_cycle(&cyclic, &chain(&["b", "c"])).is_none());
+}
+
+#[test]
+fn test_validate_graph_reports_active_terminal() {
+    let (nodes, edges) = export_fixture();
+    let result = validate_graph("sess-1", &nodes, &edges);
+
+    assert!(result.is_valid);
+    assert!(result.is_dag);
+    assert_eq!(result.node_count, 3);
+    assert_eq!(result.edge_count, 2);
+    assert_eq!(result.error_count, 0);
+    assert_eq!(result.warning_count, 1);
+    assert_eq!(result.issues[0].kind, GraphIssueKind::ActiveTerminal);
+    assert_eq!(result.issues[0].node_ids, vec![nodes[1].id.clone()]);
+}
+
+#[test]
+fn test_validate_graph_structural_issues() {
+    let root = GraphNode::new("s", "root").as_root();
+    let a = GraphNode::new("s", "a").with_depth(1);
+    let b = GraphNode::new("s", "b").with_depth(1);
+    let stray = GraphNode::new("s", "stray").with_depth(1);
+    let edges = vec![
+        GraphEdge::new("s", &root.id, &a.id),
+        GraphEdge::new("s", &a.id, &b.id),
+        GraphEdge::new("s", &b.id, &a.id).with_type(EdgeType::Refines),
+        GraphEdge::new("s", &root.id, &a.id),
+        GraphEdge::new("s", &b.id, &b.id).with_type(EdgeType::Supports),
+        GraphEdge::new("s", &root.id, "missing"),
+    ];
+    let nodes = vec![root.clone(), a.clone(), b.clone(), stray.clone()];
+    let result = validate_graph("s", &nodes, &edges);
+
+    assert!(!result.is_valid);
+    assert!(!result.is_dag);
+    let kinds: Vec<GraphIssueKind> = result.issues.iter().map(|i| i.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            GraphIssueKind::SelfLoop,
+            GraphIssueKind::DanglingEdge,
+            GraphIssueKind::Cycle,
+            GraphIssueKind::DepthMismatch,
+            GraphIssueKind::DuplicateEdge,
+            GraphIssueKind::Unreachable,
+        ]
+    );
+    assert_eq!(result.error_count, 3);
+    assert_eq!(result.warning_count, 3);
+    assert_eq!(result.issues[1].node_ids, vec!["missing".to_string()]);
+    assert_eq!(result.issues[2].node_ids.len(), 2);
+    assert_eq!(result.issues[2].edge_ids.len(), 3);
+    assert_eq!(result.issues[5].node_ids, vec![stray.id.clone()]);
+}
+
+#[test]
+fn test_validate_graph_roots() {
+    let a = GraphNode::new("s", "a");
+    let result = validate_graph("s", std::slice::from_ref(&a), &[]);
+    assert!(!result.is_valid);
+    assert_eq!(result.issues[0].kind, GraphIssueKind::MissingRoot);
+
+    let result = validate_graph("s", &[a.as_root(), GraphNode::new("s", "b").as_root()], &[]);
+    assert!(result.is_valid);
+    assert_eq!(result.issues[0].kind, GraphIssueKind::MultipleRoots);
+    assert_eq!(result.issues[0].severity, IssueSeverity::Warning);
+}
+
+// ============================================================================
+// Best Path Tests
+// ============================================================================
+
+/// Root with a strong first step that leads nowhere good and a weaker
+/// first step that leads to the best conclusion
+fn path_fixture() -> (Vec<GraphNode>, Vec<GraphEdge>) {
+    let root = GraphNode::new("s", "root").as_root();
+    let strong = GraphNode::new("s", "strong").with_score(0.9).with_depth(1);
+    let weak = GraphNode::new("s", "weak").with_score(0.5).with_depth(1);
+    let dead_end = GraphNode::new("s", "dead end")
+        .with_score(0.1)
+        .with_depth(2);
+    let best = GraphNode::new("s", "best")
+        .with_score(0.8)
+        .with_depth(2)
+        .as_terminal();
+    let edges = vec![
+        GraphEdge::new("s", &root.id, &strong.id),
+        GraphEdge::new("s", &root.id, &weak.id),
+        GraphEdge::new("s", &strong.id, &dead_end.id),
+        GraphEdge::new("s", &weak.id, &best.id),
+        GraphEdge::new("s", &dead_end.id, &best.id).with_type(EdgeType::Supports),
+    ];
+    (vec![root, strong, weak, dead_end, best], edges)
+}
+
+#[test]
+fn test_got_best_path_params() {
+    let params: GotBestPathParams = serde_json::from_str(r#"{"session_id": "s"}"#).unwrap();
+    assert!(params.terminal_node_id.is_none());
+    let params = GotBestPathParams::new("s").with_terminal_node("n");
+    assert_eq!(params.terminal_node_id, Some("n".to_string()));
+}
+
+#[test]
+fn test_heaviest_path_follows_derivation_edges() {
+    let (nodes, edges) = path_fixture();
+    // The supports edge is not a derivation, so the dead end cannot reach
+    // the conclusion
+    let path = heaviest_path(&nodes, &edges, &[4]).unwrap();
+    assert_eq!(
+        path,
+        vec![
+            (0, None),
+            (2, Some(EdgeType::Generates)),
+            (4, Some(EdgeType::Generates)),
+        ]
+    );
+
+    // Among several targets the best-scoring end wins
+    let path = heaviest_path(&nodes, &edges, &[3, 4]).unwrap();
+    assert_eq!(path.last().unwrap().0, 4);
+
+    // Unreachable targets yield no path
+    let orphan = vec![GraphNode::new("s", "orphan")];
+    assert!(heaviest_path(&orphan, &[], &[0]).is_none());
+}
+
+#[test]
+fn test_heaviest_path_prefers_shorter_on_tie() {
+    let root = GraphNode::new("s", "root").as_root();
+    let middle = GraphNode::new("s", "middle");
+    let end = GraphNode::new("s", "end").with_score(0.5);
+    let edges = vec![
+        GraphEdge::new("s", &root.id, &middle.id),
+        GraphEdge::new("s", &middle.id, &end.id),
+        GraphEdge::new("s", &root.id, &end.id).with_type(EdgeType::Refines),
+    ];
+    let path = heaviest_path(&[root, middle, end], &edges, &[2]).unwrap();
+    assert_eq!(path, vec![(0, None), (2, Some(EdgeType::Refines))]);
+}
+
+#[test]
+fn test_explain_path() {
+    let step = |content: &str, score: Option<f64>, via: Option<&str>| PathStep {
+        node_id: content.to_string(),
+        content: content.to_string(),
+        node_type: if via.is_none() {
+            NodeType::Root
+        } else {
+            NodeType::Thought
+        },
+        depth: 0,
+        score,
+        via: via.map(str::to_string),
+        cumulative_score: 0.0,
+    };
+    let explanation = explain_path(&[
+        step("Question", None, None),
+        step("Idea", Some(0.75), Some("generates")),
+    ]);
+    assert_eq!(
+        explanation,
+        "1. [root] Question\n2. [thought, via generates, score 0.75] Idea"
+    );
+}
+
+// ============================================================================
+// Resume Tests
+// ============================================================================
+
+#[test]
+fn test_got_resume_params_defaults() {
+    let params: GotResumeParams = serde_json::from_str(r#"{"session_id": "s"}"#).unwrap();
+    assert_eq!(params.expand, 1);
+    assert!(params.k.is_none());
+
+    let params = GotResumeParams::new("s").with_expand(0).with_k(4);
+    assert_eq!(params.expand, 0);
+    assert_eq!(params.k, Some(4));
+}
+
+#[test]
+fn test_stored_config_reads_first_root() {
+    let config = GotConfig {
+        max_nodes: 20,
+        ..GotConfig::default()
+    };
+    let plain = GraphNode::new("s", "plain").as_root();
+    let configured = GraphNode::new("s", "root")
+        .as_root()
+        .with_metadata(serde_json::json!({ "got_config": config }));
+    let child = GraphNode::new("s", "child")
+        .with_metadata(serde_json::json!({ "got_config": GotConfig::default() }));
+
+    assert!(stored_config(std::slice::from_ref(&child)).is_none());
+    let stored = stored_config(&[child, plain, configured]).unwrap();
+    assert_eq!(stored.max_nodes, 20);
+    assert_eq!(stored.max_depth, 10);
+}
+
+#[test]
+fn test_frontier_nodes() {
+    let root = GraphNode::new("s", "root").as_root().as_inactive();
+    let unscored = GraphNode::new("s", "unscored").with_depth(1);
+    let good = GraphNode::new("s", "good").with_score(0.9).with_depth(1);
+    let fair = GraphNode::new("s", "fair").with_score(0.4).with_depth(1);
+    let deep = GraphNode::new("s", "deep").with_score(0.95).with_depth(2);
+    let done = GraphNode::new("s", "done")
+        .with_score(0.99)
+        .with_depth(1)
+        .as_terminal();
+    let nodes = vec![root, unscored, good, fair, deep, done];
+
+    let frontier = frontier_nodes(&nodes, &[], 2);
+    let contents: Vec<&str> = frontier.iter().map(|n| n.content.as_str()).collect();
+    assert_eq!(contents, ["good", "fair", "unscored"]);
+
+    let frontier = frontier_nodes(&nodes, &[nodes[2].id.clone()], 3);
+    let contents: Vec<&str> = frontier.iter().map(|n| n.content.as_str()).collect();
+    assert_eq!(contents, ["deep", "fair", "unscored"]);
+}
+
 // ============================================================================
 // Response Parsing Tests - Generate
 // ============================================================================
@@ -365,6 +740,99 @@ fn test_score_response_partial_breakdown() {
     assert_eq!(resp.breakdown.validity, 0.5); // default from default_score()
 }
 
+// ============================================================================
+// Pairwise Ranking Tests
+// ============================================================================
+
+#[test]
+fn test_rank_params_defaults() {
+    let params: GotRankParams =
+        serde_json::from_str(r#"{"session_id": "s", "parent_id": "p"}"#).unwrap();
+    assert_eq!(params.method, RankingMethod::BradleyTerry);
+    assert_eq!(params.rounds, 1);
+    assert!(params.node_ids.is_empty());
+
+    let params = GotRankParams::new("s", "p")
+        .with_nodes(vec!["a".to_string(), "b".to_string()])
+        .with_method(RankingMethod::Elo)
+        .with_rounds(2);
+    let json = serde_json::to_value(&params).unwrap();
+    assert_eq!(json["method"], "elo");
+    assert_eq!(json["rounds"], 2);
+    assert_eq!(json["node_ids"], serde_json::json!(["a", "b"]));
+}
+
+#[test]
+fn test_ranking_method_round_trip() {
+    for method in [RankingMethod::BradleyTerry, RankingMethod::Elo] {
+        assert_eq!(method.to_string().parse::<RankingMethod>().unwrap(), method);
+    }
+    assert_eq!(
+        "Bradley-Terry".parse::<RankingMethod>().unwrap(),
+        RankingMethod::BradleyTerry
+    );
+    assert!("glicko".parse::<RankingMethod>().is_err());
+}
+
+#[test]
+fn test_compare_response_winner() {
+    let resp = CompareResponse::from_completion(r#"{"winner": "B", "confidence": 0.9}"#).unwrap();
+    assert_eq!(resp.first_score(), 0.0);
+    let resp = CompareResponse::from_completion(r#"{"winner": "tie"}"#).unwrap();
+    assert_eq!(resp.first_score(), 0.5);
+    assert_eq!(resp.confidence, 0.5);
+    assert!(CompareResponse::from_completion(r#"{"winner": "both"}"#).is_err());
+    assert!(CompareResponse::from_completion("A is better").is_err());
+}
+
+#[test]
+fn test_bradley_terry_orders_by_wins() {
+    // 0 beats 1 and 2, 1 beats 2
+    let outcomes = [(0, 1, 1.0), (0, 2, 1.0), (2, 1, 0.0)];
+    let strengths = RankingMethod::BradleyTerry.ratings(3, &outcomes);
+    assert!(strengths[0] > strengths[1] && strengths[1] > strengths[2]);
+    assert!(strengths.iter().all(|s| s.is_finite() && *s > 0.0));
+
+    let scores = expected_scores(&strengths, |a, b| {
+        RankingMethod::BradleyTerry.win_probability(a, b)
+    });
+    assert!(scores[0] > 0.5 && scores[2] < 0.5);
+    // Win probabilities of each pair sum to one, so scores average 0.5
+    assert!((scores.iter().sum::<f64>() / 3.0 - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_bradley_terry_ties_and_unjudged_nodes_are_average() {
+    let strengths = bradley_terry_strengths(3, &[(0, 1, 0.5), (1, 0, 0.5)]);
+    for strength in strengths {
+        assert!((strength - 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_swapped_rounds_cancel_position_bias() {
+    // A judge that always prefers the first node shown
+    let outcomes = [(0, 1, 1.0), (1, 0, 1.0)];
+    for method in [RankingMethod::BradleyTerry, RankingMethod::Elo] {
+        let ratings = method.ratings(2, &outcomes);
+        let scores = expected_scores(&ratings, |a, b| method.win_probability(a, b));
+        assert!(
+            (scores[0] - scores[1]).abs() < 0.05,
+            "{}: {:?}",
+            method,
+            scores
+        );
+    }
+}
+
+#[test]
+fn test_elo_ratings_update() {
+    let ratings = elo_ratings(2, &[(0, 1, 1.0)]);
+    assert!((ratings[0] - 1516.0).abs() < 1e-9);
+    assert!((ratings[1] - 1484.0).abs() < 1e-9);
+    assert!((RankingMethod::Elo.win_probability(1500.0, 1500.0) - 0.5).abs() < 1e-12);
+}
+
 // ============================================================================
 // Response Parsing Tests - Aggregate
 // ============================================================================
@@ -450,8 +918,10 @@ fn test_got_generate_result_serialize() {
             confidence: 0.85,
             novelty: 0.7,
             rationale: "Reason 1".to_string(),
+            replaces_node_id: None,
         }],
         count: 1,
+        duplicates_skipped: 0,
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"count\":1"));
@@ -520,6 +990,7 @@ fn test_got_prune_result_serialize() {
         remaining_count: 15,
         threshold_used: 0.3,
         pruned_node_ids: vec!["p1".to_string(), "p2".to_string()],
+        redundant_node_ids: vec![],
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"pruned_count\":5"));
@@ -556,6 +1027,7 @@ fn test_got_state_result_serialize() {
         root_node_ids: vec!["root-1".to_string()],
         active_node_ids: vec!["a1".to_string(), "a2".to_string(), "a3".to_string()],
         terminal_node_ids: vec!["t1".to_string(), "t2".to_string()],
+        nodes: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"total_nodes\":10"));
@@ -612,6 +1084,7 @@ fn test_generated_continuation_serialize() {
         confidence: 0.8,
         novelty: 0.9,
         rationale: "Because".to_string(),
+        replaces_node_id: None,
     };
     let json = serde_json::to_string(&cont).unwrap();
     assert!(json.contains("New idea"));
@@ -837,6 +1310,18 @@ fn test_get_state_params_deserialize() {
     assert_eq!(params.session_id, "sess-123");
 }
 
+#[test]
+fn test_get_state_params_with_page() {
+    let json = r#"{"session_id": "sess-123", "limit": 10, "offset": 20}"#;
+    let params: GotGetStateParams = serde_json::from_str(json).unwrap();
+    assert_eq!(params.limit, Some(10));
+    assert_eq!(params.offset, Some(20));
+
+    let params = GotGetStateParams::new("sess-123").with_page(5, 0);
+    assert_eq!(params.limit, Some(5));
+    assert_eq!(params.offset, Some(0));
+}
+
 // ============================================================================
 // Edge Cases - Init Params
 // ============================================================================
@@ -1125,6 +1610,7 @@ fn test_generated_continuation_zero_confidence() {
         confidence: 0.0,
         novelty: 0.0,
         rationale: "Uncertain".to_string(),
+        replaces_node_id: None,
     };
     let json = serde_json::to_string(&cont).unwrap();
     assert!(json.contains("\"confidence\":0.0"));
@@ -1139,6 +1625,7 @@ fn test_generated_continuation_max_confidence() {
         confidence: 1.0,
         novelty: 1.0,
         rationale: "Certain".to_string(),
+        replaces_node_id: None,
     };
     let json = serde_json::to_string(&cont).unwrap();
     assert!(json.contains("\"confidence\":1.0"));
@@ -1161,6 +1648,7 @@ fn test_state_result_empty_graph() {
         root_node_ids: vec![],
         active_node_ids: vec![],
         terminal_node_ids: vec![],
+        nodes: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"total_nodes\":0"));
@@ -1199,6 +1687,7 @@ fn test_prune_result_no_pruning() {
         remaining_count: 10,
         threshold_used: 0.3,
         pruned_node_ids: vec![],
+        redundant_node_ids: vec![],
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"pruned_count\":0"));
@@ -1263,6 +1752,7 @@ fn test_generate_result_empty_continuations() {
         source_node_id: "node-1".to_string(),
         continuations: vec![],
         count: 0,
+        duplicates_skipped: 0,
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"count\":0"));
@@ -1307,6 +1797,8 @@ fn test_config_boundary_values() {
         max_depth: 1,
         default_k: 1,
         prune_threshold: 0.0,
+        similarity_threshold: 0.9,
+        strict_dag: false,
     };
     let json = serde_json::to_string(&config).unwrap();
     assert!(json.contains("\"max_nodes\":1"));
@@ -1322,6 +1814,8 @@ fn test_config_large_values() {
         max_depth: 1000,
         default_k: 100,
         prune_threshold: 1.0,
+        similarity_threshold: 0.9,
+        strict_dag: false,
     };
     let json = serde_json::to_string(&config).unwrap();
     assert!(json.contains("\"max_nodes\":10000"));
@@ -1830,6 +2324,7 @@ fn test_state_result_negative_max_depth() {
         root_node_ids: vec!["root".to_string()],
         active_node_ids: vec![],
         terminal_node_ids: vec![],
+        nodes: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     assert!(json.contains("\"max_depth\":-1"));
@@ -1847,6 +2342,7 @@ fn test_state_result_large_graph() {
         root_node_ids: vec!["root".to_string()],
         active_node_ids: (0..500).map(|i| format!("a{}", i)).collect(),
         terminal_node_ids: (0..100).map(|i| format!("t{}", i)).collect(),
+        nodes: None,
     };
     assert_eq!(result.active_node_ids.len(), 500);
     assert_eq!(result.terminal_node_ids.len(), 100);
@@ -1878,6 +2374,7 @@ fn test_generated_continuation_empty_fields() {
         confidence: 0.0,
         novelty: 0.0,
         rationale: "".to_string(),
+        replaces_node_id: None,
     };
     let json = serde_json::to_string(&cont).unwrap();
     let parsed: GeneratedContinuation = serde_json::from_str(&json).unwrap();
@@ -1926,3 +2423,25 @@ fn test_score_breakdown_all_ones() {
     let parsed: ScoreBreakdown = serde_json::from_str(&json).unwrap();
     assert_eq!(parsed.novelty, 1.0);
 }
+
+// ============================================================================
+// Duplicate Detection Tests
+// ============================================================================
+
+#[test]
+fn test_distinct_candidates_drops_near_duplicates() {
+    let existing = vec![vec![1.0, 0.0]];
+    let candidates = vec![
+        vec![0.99, 0.05], // paraphrase of the existing node
+        vec![0.0, 1.0],
+        vec![0.05, 0.99], // paraphrase of the candidate above
+        vec![0.7, -0.7],
+    ];
+    assert_eq!(distinct_candidates(&existing, &candidates, 0.9), vec![1, 3]);
+}
+
+#[test]
+fn test_distinct_candidates_threshold_one_keeps_all_but_identical() {
+    let candidates = vec![vec![1.0, 0.0], vec![0.99, 0.05], vec![1.0, 0.0]];
+    assert_eq!(distinct_candidates(&[], &candidates, 1.0), vec![0, 1]);
+}
@@ -0,0 +1,396 @@
+//! Pipe health checks.
+//!
+//! Sends a tiny probe prompt through every configured pipe (and every pipe
+//! version) and reports whether it answered, how long it took, and whether
+//! the answer matched the mode's output schema. Used by the
+//! `reasoning_pipe_health` tool, the `pipes check` command and the optional
+//! startup check, so misconfigured pipes surface before a mode depends on them.
+
+use serde::Serialize;
+use std::time::Instant;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::PipeConfig;
+use crate::error::LangbaseError;
+use crate::langbase::{Message, OutputSchema, PipeRequest};
+use crate::prompts::{
+    DIVERGENT_REASONING_PROMPT, LINEAR_REASONING_PROMPT, REFLECTION_PROMPT, TREE_REASONING_PROMPT,
+};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+
+/// User message sent to every pipe.
+const PROBE_PROMPT: &str =
+    "Health check. Reason briefly about this thought: \"Regular testing catches problems early.\"";
+
+/// A pipe to probe.
+#[derive(Debug, Clone)]
+pub struct PipeProbe {
+    /// Mode or role the pipe serves (e.g. "tree", "detection").
+    pub role: &'static str,
+    /// Pipe name to call.
+    pub pipe: String,
+    /// System prompt the mode sends with its requests, if fixed.
+    pub system_prompt: Option<&'static str>,
+    /// Output schema the mode requests, if any.
+    pub schema: Option<OutputSchema>,
+}
+
+/// Health of a single pipe.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipeHealth {
+    /// Mode or role the pipe serves.
+    pub role: String,
+    /// Pipe name.
+    pub pipe: String,
+    /// Whether the pipe returned a completion.
+    pub reachable: bool,
+    /// Round-trip time of the probe, including retries.
+    pub latency_ms: i64,
+    /// Name of the schema the completion was checked against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Whether the completion matched the schema (`None` without a schema
+    /// or when the pipe was unreachable).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_compliant: Option<bool>,
+    /// Why the probe failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PipeHealth {
+    /// Reachable and, if it has a schema, compliant with it.
+    pub fn is_healthy(&self) -> bool {
+        self.reachable && self.schema_compliant != Some(false)
+    }
+}
+
+/// Outcome of probing a set of pipes.
+#[derive(Debug, Clone, Serialize)]
+pub struct PipeHealthReport {
+    /// Number of healthy pipes.
+    pub healthy: usize,
+    /// Number of unreachable or non-compliant pipes.
+    pub unhealthy: usize,
+    /// Per-pipe results, in probe order.
+    pub pipes: Vec<PipeHealth>,
+}
+
+impl PipeHealthReport {
+    /// Whether every probed pipe is healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.unhealthy == 0
+    }
+
+    /// Log one line per pipe: info for healthy pipes, warn for the rest.
+    pub fn log(&self) {
+        for pipe in &self.pipes {
+            if pipe.is_healthy() {
+                info!(
+                    role = pipe.role,
+                    pipe = %pipe.pipe,
+                    latency_ms = pipe.latency_ms,
+                    "Pipe healthy"
+                );
+            } else {
+                warn!(
+                    role = pipe.role,
+                    pipe = %pipe.pipe,
+                    latency_ms = pipe.latency_ms,
+                    error = pipe.error.as_deref().unwrap_or(""),
+                    "Pipe unhealthy"
+                );
+            }
+        }
+        info!(
+            healthy = self.healthy,
+            unhealthy = self.unhealthy,
+            "Pipe health check complete"
+        );
+    }
+}
+
+/// Probes for every pipe the modes call, resolving unset optional pipes to
+/// the defaults the modes fall back to.
+///
+/// Each pipe is probed once, under the first role that uses it; versions in
+/// `config.versions` are probed with their base pipe's prompt and checked
+/// against the schema version pinned for them.
+pub fn pipe_probes(config: &PipeConfig) -> Vec<PipeProbe> {
+    let schemas = SchemaRegistry::from_config(config);
+    let optional =
+        |pipe: Option<&String>, default: &str| pipe.cloned().unwrap_or_else(|| default.to_string());
+    let base = vec![
+        probe(
+            "linear",
+            &config.linear,
+            Some(LINEAR_REASONING_PROMPT),
+            Some(SchemaKind::Linear),
+        ),
+        probe(
+            "tree",
+            &config.tree,
+            Some(TREE_REASONING_PROMPT),
+            Some(SchemaKind::Tree),
+        ),
+        probe(
+            "divergent",
+            &config.divergent,
+            Some(DIVERGENT_REASONING_PROMPT),
+            Some(SchemaKind::Divergent),
+        ),
+        probe(
+            "reflection",
+            &config.reflection,
+            Some(REFLECTION_PROMPT),
+            Some(SchemaKind::Reflection),
+        ),
+        probe("auto_router", &config.auto_router, None, None),
+        probe(
+            "auto",
+            &optional(config.auto.as_ref(), "mode-router-v1"),
+            None,
+            None,
+        ),
+        probe(
+            "backtracking",
+            &optional(config.backtracking.as_ref(), "backtracking-reasoning-v1"),
+            None,
+            None,
+        ),
+        probe(
+            "got",
+            &optional(
+                config.got.as_ref().and_then(|c| c.pipe.as_ref()),
+                "got-reasoning-v1",
+            ),
+            None,
+            None,
+        ),
+        probe(
+            "detection",
+            &optional(
+                config.detection.as_ref().and_then(|c| c.pipe.as_ref()),
+                "detection-v1",
+            ),
+            None,
+            None,
+        ),
+        probe(
+            "decision",
+            &optional(
+                config.decision.as_ref().and_then(|c| c.pipe.as_ref()),
+                "decision-framework-v1",
+            ),
+            None,
+            None,
+        ),
+        probe(
+            "evidence",
+            &optional(
+                config.evidence.as_ref().and_then(|c| c.pipe.as_ref()),
+                "decision-framework-v1",
+            ),
+            None,
+            None,
+        ),
+        probe(
+            "code",
+            &optional(config.code.as_ref(), "code-reasoning-v1"),
+            None,
+            None,
+        ),
+    ];
+
+    let mut probes: Vec<PipeProbe> = Vec::new();
+    for (p, kind) in base {
+        let versions = config.versions.get(&p.pipe).into_iter().flatten();
+        let versioned: Vec<PipeProbe> = versions
+            .map(|v| PipeProbe {
+                pipe: v.name.clone(),
+                ..p.clone()
+            })
+            .collect();
+        for mut candidate in std::iter::once(p).chain(versioned) {
+            candidate.schema = kind.map(|kind| schemas.schema(kind, &candidate.pipe));
+            if !probes
+                .iter()
+                .any(|existing| existing.pipe == candidate.pipe)
+            {
+                probes.push(candidate);
+            }
+        }
+    }
+    probes
+}
+
+/// A probe and the kind of response its schema is resolved from
+fn probe(
+    role: &'static str,
+    pipe: &str,
+    system_prompt: Option<&'static str>,
+    kind: Option<SchemaKind>,
+) -> (PipeProbe, Option<SchemaKind>) {
+    let probe = PipeProbe {
+        role,
+        pipe: pipe.to_string(),
+        system_prompt,
+        schema: None,
+    };
+    (probe, kind)
+}
+
+/// Probe pipes concurrently and collect the results in probe order.
+pub async fn check_pipes(backend: &SharedBackend, probes: Vec<PipeProbe>) -> PipeHealthReport {
+    let mut tasks = JoinSet::new();
+    for (index, probe) in probes.into_iter().enumerate() {
+        let backend = backend.clone();
+        tasks.spawn(async move { (index, check_pipe(&backend, probe).await) });
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => warn!(error = %e, "Pipe health probe task failed"),
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+
+    let pipes: Vec<PipeHealth> = results.into_iter().map(|(_, health)| health).collect();
+    let healthy = pipes.iter().filter(|p| p.is_healthy()).count();
+    PipeHealthReport {
+        healthy,
+        unhealthy: pipes.len() - healthy,
+        pipes,
+    }
+}
+
+async fn check_pipe(backend: &SharedBackend, probe: PipeProbe) -> PipeHealth {
+    let mut messages = Vec::new();
+    if let Some(prompt) = probe.system_prompt {
+        messages.push(Message::system(prompt));
+    }
+    messages.push(Message::user(PROBE_PROMPT));
+
+    // A unique variable keeps the probe out of the response cache
+    let mut request = PipeRequest::new(&probe.pipe, messages)
+        .with_variable("health_probe", Uuid::new_v4().to_string());
+    if let Some(schema) = &probe.schema {
+        request = request.with_output_schema(schema.clone());
+    }
+
+    let start = Instant::now();
+    let result = backend.call_pipe(request).await;
+    let latency_ms = start.elapsed().as_millis() as i64;
+
+    let mut health = PipeHealth {
+        role: probe.role.to_string(),
+        pipe: probe.pipe,
+        reachable: false,
+        latency_ms,
+        schema: probe.schema.as_ref().map(|s| s.name.clone()),
+        schema_compliant: None,
+        error: None,
+    };
+    match result {
+        // A fallback answering means the pipe itself is down
+        Ok(response) if response.fallback.is_some() => {
+            health.error = Some(format!(
+                "Pipe unavailable; answered by {} fallback",
+                response.fallback.unwrap_or_default()
+            ));
+        }
+        // Backends other than Langbase do not enforce schemas, so check here
+        Ok(response) => {
+            health.reachable = true;
+            if let Some(schema) = &probe.schema {
+                let checked = schema.validate_completion(&response.completion);
+                health.schema_compliant = Some(checked.is_ok());
+                health.error = checked
+                    .err()
+                    .map(|v| format!("Schema violation at {}: {}", v.path, v.message));
+            }
+        }
+        Err(e @ LangbaseError::SchemaViolation { .. }) => {
+            health.reachable = true;
+            health.schema_compliant = Some(false);
+            health.error = Some(e.to_string());
+        }
+        Err(e) => health.error = Some(e.to_string()),
+    }
+    health
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GotPipeConfig, PipeVersion};
+
+    #[test]
+    fn test_probes_cover_defaults_once() {
+        let probes = pipe_probes(&PipeConfig::default());
+        let pipes: Vec<&str> = probes.iter().map(|p| p.pipe.as_str()).collect();
+
+        assert!(pipes.contains(&"got-reasoning-v1"));
+        assert!(pipes.contains(&"detection-v1"));
+        // Decision and evidence share a pipe; auto and the router do too
+        assert_eq!(
+            pipes
+                .iter()
+                .filter(|p| **p == "decision-framework-v1")
+                .count(),
+            1
+        );
+        assert_eq!(pipes.iter().filter(|p| **p == "mode-router-v1").count(), 1);
+        let tree = probes.iter().find(|p| p.role == "tree").unwrap();
+        assert!(tree.schema.is_some());
+    }
+
+    #[test]
+    fn test_probes_include_configured_pipes_and_versions() {
+        let mut config = PipeConfig {
+            got: Some(GotPipeConfig {
+                pipe: Some("custom-got".to_string()),
+                max_nodes: None,
+                max_depth: None,
+                default_k: None,
+                prune_threshold: None,
+                similarity_threshold: None,
+                strict_dag: None,
+            }),
+            ..Default::default()
+        };
+        config.versions.insert(
+            config.tree.clone(),
+            vec![PipeVersion {
+                name: "tree-reasoning-v2".to_string(),
+                weight: 1,
+            }],
+        );
+
+        let probes = pipe_probes(&config);
+        assert!(probes.iter().any(|p| p.pipe == "custom-got"));
+        let version = probes
+            .iter()
+            .find(|p| p.pipe == "tree-reasoning-v2")
+            .unwrap();
+        assert_eq!(version.role, "tree");
+        assert!(version.schema.is_some());
+    }
+
+    #[test]
+    fn test_probes_use_pinned_schema_versions() {
+        let mut config = PipeConfig::default();
+        config.schema_versions.insert(config.linear.clone(), 1);
+
+        let probes = pipe_probes(&config);
+        let linear = probes.iter().find(|p| p.role == "linear").unwrap();
+        assert_eq!(linear.schema.as_ref().unwrap().name, "linear_response");
+        let got = probes.iter().find(|p| p.role == "got").unwrap();
+        assert!(got.schema.is_none());
+    }
+}
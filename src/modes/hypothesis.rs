@@ -0,0 +1,834 @@
+//! Hypothesis mode - testable predictions and pre-registered experiments.
+//!
+//! This module runs a hypothesis through a test cycle:
+//! - `design` has the pipe turn the hypothesis into falsifiable predictions
+//!   and experiments, each listing its possible outcomes with their
+//!   likelihoods if the hypothesis is true and if it is false
+//! - The likelihoods are fixed (pre-registered) when the design is stored;
+//!   experiments are ranked by how much they are expected to tell
+//! - `observe` records the outcome of an experiment and applies Bayes' rule
+//!   with the pre-registered likelihoods, locally and without a pipe call
+//!
+//! Designs and observations are stored as probability updates, the same
+//! records `reasoning_probabilistic` writes, so the whole history of a
+//! hypothesis reads from one place.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::HYPOTHESIS_DESIGN_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Invocation, ProbabilityUpdate, SharedStorage};
+
+/// Experiments designed when the caller does not say.
+pub const DEFAULT_EXPERIMENTS: usize = 3;
+
+/// Most experiments one design may hold.
+pub const MAX_EXPERIMENTS: usize = 6;
+
+/// Smallest likelihood an outcome keeps, so no pre-registered outcome is
+/// treated as impossible and a single observation cannot settle the
+/// hypothesis for good.
+pub const MIN_OUTCOME_LIKELIHOOD: f64 = 0.001;
+
+/// Metadata key of the stored design.
+const DESIGN_METADATA_KEY: &str = "hypothesis_test";
+
+/// Metadata key linking an observation to its design.
+const OBSERVATION_METADATA_KEY: &str = "hypothesis_test_id";
+
+fn default_prior() -> f64 {
+    0.5
+}
+
+fn default_max_experiments() -> usize {
+    DEFAULT_EXPERIMENTS
+}
+
+/// Input parameters for designing a hypothesis test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HypothesisParams {
+    /// The hypothesis to test
+    pub hypothesis: String,
+    /// Optional background, constraints or available resources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Probability of the hypothesis before any experiment (exclusive 0-1)
+    #[serde(default = "default_prior")]
+    pub prior: f64,
+    /// Most experiments to design
+    #[serde(default = "default_max_experiments")]
+    pub max_experiments: usize,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for recording an experiment's result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HypothesisObserveParams {
+    /// Session holding the test
+    pub session_id: String,
+    /// The test design
+    pub test_id: String,
+    /// The experiment that was run
+    pub experiment_id: String,
+    /// The observed outcome, one of the experiment's pre-registered outcomes
+    pub outcome: String,
+    /// Optional notes on how the result was obtained
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// A testable prediction of the hypothesis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Prediction {
+    /// Prediction key, referenced by experiments
+    pub prediction_id: String,
+    /// What should be observed if the hypothesis is true
+    pub statement: String,
+    /// What should be observed instead if it is false
+    pub if_false: String,
+}
+
+/// A pre-registered outcome of an experiment
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExpectedOutcome {
+    /// Short name of the result
+    pub outcome: String,
+    /// Probability of the outcome if the hypothesis is true
+    pub likelihood_if_true: f64,
+    /// Probability of the outcome if the hypothesis is false
+    pub likelihood_if_false: f64,
+}
+
+/// An experiment or observation that discriminates the hypothesis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Experiment {
+    /// Experiment key
+    pub experiment_id: String,
+    /// What to do or observe, and how
+    pub description: String,
+    /// Predictions it tests
+    pub predictions: Vec<String>,
+    /// Every outcome it can have, likelihoods normalized per column
+    pub outcomes: Vec<ExpectedOutcome>,
+    /// Expected reduction in uncertainty about the hypothesis at the
+    /// design's prior, in bits
+    pub expected_information_bits: f64,
+}
+
+/// Result of designing a hypothesis test
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HypothesisResult {
+    /// Stored design, referenced when recording results
+    pub test_id: String,
+    /// The session ID
+    pub session_id: String,
+    /// The hypothesis tested
+    pub hypothesis: String,
+    /// Probability before any experiment
+    pub prior: f64,
+    /// Testable predictions
+    pub predictions: Vec<Prediction>,
+    /// Experiments, most informative first
+    pub experiments: Vec<Experiment>,
+}
+
+/// Result of recording an experiment's result
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HypothesisObserveResult {
+    /// The test design
+    pub test_id: String,
+    /// Stored probability update for this observation
+    pub update_id: String,
+    /// The hypothesis tested
+    pub hypothesis: String,
+    /// The experiment that was run
+    pub experiment_id: String,
+    /// The pre-registered outcome observed
+    pub outcome: String,
+    /// Probability before this observation
+    pub prior: f64,
+    /// Probability after this observation
+    pub posterior: f64,
+    /// How much more likely the outcome is if the hypothesis is true
+    pub likelihood_ratio: f64,
+    /// Verbal probability of the posterior
+    pub verbal: String,
+    /// Experiments of the design not yet observed
+    pub remaining_experiments: Vec<String>,
+}
+
+/// Hypothesis mode handler for experiment design and result tracking.
+#[derive(Clone)]
+pub struct HypothesisMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Decision framework pipe designing experiments
+    decision_pipe: String,
+}
+
+impl HypothesisMode {
+    /// Create a new hypothesis mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            decision_pipe,
+        }
+    }
+
+    /// Design predictions and experiments and pre-register their outcomes
+    pub async fn design(&self, params: HypothesisParams) -> AppResult<HypothesisResult> {
+        let start = Instant::now();
+
+        if params.hypothesis.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "hypothesis".to_string(),
+                reason: "Hypothesis cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if !(params.prior > 0.0 && params.prior < 1.0) {
+            return Err(ToolError::Validation {
+                field: "prior".to_string(),
+                reason: "Prior must be strictly between 0 and 1".to_string(),
+            }
+            .into());
+        }
+        if params.max_experiments == 0 || params.max_experiments > MAX_EXPERIMENTS {
+            return Err(ToolError::Validation {
+                field: "max_experiments".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_EXPERIMENTS),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "hypothesis")
+            .await?;
+        debug!(session_id = %session.id, "Processing hypothesis design request");
+
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.hypothesis",
+            serialize_for_log(&params, "reasoning.hypothesis input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let mut prompt = format!(
+            "HYPOTHESIS: {}\n\nPRIOR PROBABILITY: {}\n\nDesign at most {} experiments.",
+            params.hypothesis, params.prior, params.max_experiments
+        );
+        if let Some(ref context) = params.context {
+            prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+        }
+        let messages = vec![
+            Message::system(HYPOTHESIS_DESIGN_PROMPT),
+            Message::user(prompt),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => {
+                parse_design(&response.completion, params.prior, params.max_experiments)
+                    .map(|design| (design, response.cache_hit, response.fallback))
+            }
+            Err(e) => Err(e.into()),
+        };
+        let ((predictions, experiments), cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+
+        let stored = ProbabilityUpdate::new(
+            &session.id,
+            &params.hypothesis,
+            params.prior,
+            params.prior,
+            serde_json::json!([]),
+            serde_json::json!({"verbal": verbal_probability(params.prior)}),
+        )
+        .with_metadata(serde_json::json!({
+            DESIGN_METADATA_KEY: StoredDesign {
+                predictions: predictions.clone(),
+                experiments: experiments.clone(),
+            }
+        }));
+        self.core
+            .storage()
+            .create_probability_update(&stored)
+            .await?;
+
+        let result = HypothesisResult {
+            test_id: stored.id,
+            session_id: session.id.clone(),
+            hypothesis: params.hypothesis.clone(),
+            prior: params.prior,
+            predictions,
+            experiments,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.hypothesis output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            test_id = %result.test_id,
+            experiments = result.experiments.len(),
+            latency_ms = latency,
+            "Hypothesis test designed"
+        );
+
+        Ok(result)
+    }
+
+    /// Record an experiment's outcome and update the hypothesis' probability
+    pub async fn observe(
+        &self,
+        params: HypothesisObserveParams,
+    ) -> AppResult<HypothesisObserveResult> {
+        let start = Instant::now();
+        let storage = self.core.storage();
+
+        let design_record = storage
+            .get_probability_update(&params.test_id)
+            .await?
+            .filter(|u| u.session_id == params.session_id);
+        let (design_record, design) = match design_record.and_then(|record| {
+            let design = stored_design(&record)?;
+            Some((record, design))
+        }) {
+            Some(found) => found,
+            None => {
+                return Err(ToolError::Validation {
+                    field: "test_id".to_string(),
+                    reason: format!("Hypothesis test not found in session: {}", params.test_id),
+                }
+                .into())
+            }
+        };
+
+        let experiment = design
+            .experiments
+            .iter()
+            .find(|e| e.experiment_id == params.experiment_id)
+            .ok_or_else(|| ToolError::Validation {
+                field: "experiment_id".to_string(),
+                reason: format!(
+                    "Unknown experiment; the design has: {}",
+                    experiment_ids(&design.experiments).join(", ")
+                ),
+            })?;
+        let outcome = experiment
+            .outcomes
+            .iter()
+            .find(|o| o.outcome.eq_ignore_ascii_case(params.outcome.trim()))
+            .ok_or_else(|| ToolError::Validation {
+                field: "outcome".to_string(),
+                reason: format!(
+                    "Not a pre-registered outcome of {}; expected one of: {}",
+                    experiment.experiment_id,
+                    experiment
+                        .outcomes
+                        .iter()
+                        .map(|o| o.outcome.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })?;
+
+        let observations: Vec<Observation> = storage
+            .get_hypothesis_updates(&params.session_id, &design_record.hypothesis)
+            .await?
+            .iter()
+            .filter_map(|u| observation(u, &params.test_id))
+            .collect();
+        if observations
+            .iter()
+            .any(|o| o.experiment_id == experiment.experiment_id)
+        {
+            return Err(ToolError::Validation {
+                field: "experiment_id".to_string(),
+                reason: format!(
+                    "Experiment {} was already observed",
+                    experiment.experiment_id
+                ),
+            }
+            .into());
+        }
+        let latest = observations.iter().max_by_key(|o| o.sequence);
+        let prior = latest.map_or(design_record.prior, |o| o.posterior);
+        let sequence = latest.map_or(1, |o| o.sequence + 1);
+
+        let posterior = bayes(prior, outcome);
+        let likelihood_ratio = outcome.likelihood_if_true / outcome.likelihood_if_false;
+        let evidence = format!("{}: {}", experiment.experiment_id, outcome.outcome);
+        let mut metadata = serde_json::json!({
+            OBSERVATION_METADATA_KEY: params.test_id,
+            "experiment_id": experiment.experiment_id,
+            "outcome": outcome.outcome,
+            "sequence": sequence,
+        });
+        if let Some(ref notes) = params.notes {
+            metadata["notes"] = serde_json::json!(notes);
+        }
+        let stored = ProbabilityUpdate::new(
+            &params.session_id,
+            &design_record.hypothesis,
+            prior,
+            posterior,
+            serde_json::json!([{
+                "evidence": evidence,
+                "prior": prior,
+                "posterior": posterior,
+                "likelihood_ratio": likelihood_ratio,
+            }]),
+            serde_json::json!({"verbal": verbal_probability(posterior)}),
+        )
+        .with_metadata(metadata);
+        storage.create_probability_update(&stored).await?;
+
+        let observed: HashSet<&str> = observations
+            .iter()
+            .map(|o| o.experiment_id.as_str())
+            .chain(std::iter::once(experiment.experiment_id.as_str()))
+            .collect();
+        let result = HypothesisObserveResult {
+            test_id: params.test_id.clone(),
+            update_id: stored.id,
+            hypothesis: design_record.hypothesis.clone(),
+            experiment_id: experiment.experiment_id.clone(),
+            outcome: outcome.outcome.clone(),
+            prior,
+            posterior,
+            likelihood_ratio,
+            verbal: verbal_probability(posterior).to_string(),
+            remaining_experiments: design
+                .experiments
+                .iter()
+                .map(|e| e.experiment_id.clone())
+                .filter(|id| !observed.contains(id.as_str()))
+                .collect(),
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        let invocation = Invocation::new(
+            "reasoning.hypothesis_observe",
+            serialize_for_log(&params, "reasoning.hypothesis_observe input"),
+        )
+        .with_session(&params.session_id)
+        .success(
+            serialize_for_log(&result, "reasoning.hypothesis_observe output"),
+            latency,
+        );
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %params.session_id,
+            test_id = %params.test_id,
+            prior = prior,
+            posterior = posterior,
+            "Hypothesis observation recorded"
+        );
+
+        Ok(result)
+    }
+}
+
+/// Bayes' rule for one observed outcome
+fn bayes(prior: f64, outcome: &ExpectedOutcome) -> f64 {
+    let if_true = prior * outcome.likelihood_if_true;
+    let if_false = (1.0 - prior) * outcome.likelihood_if_false;
+    if_true / (if_true + if_false)
+}
+
+/// Binary entropy in bits
+fn entropy(p: f64) -> f64 {
+    if p <= 0.0 || p >= 1.0 {
+        return 0.0;
+    }
+    let q = 1.0 - p;
+    -(p * p.log2() + q * q.log2())
+}
+
+/// Expected drop in entropy of the hypothesis from running an experiment
+fn expected_information(prior: f64, outcomes: &[ExpectedOutcome]) -> f64 {
+    let expected_after: f64 = outcomes
+        .iter()
+        .map(|o| {
+            let p_outcome = prior * o.likelihood_if_true + (1.0 - prior) * o.likelihood_if_false;
+            p_outcome * entropy(bayes(prior, o))
+        })
+        .sum();
+    (entropy(prior) - expected_after).max(0.0)
+}
+
+/// Verbal probability, on the scale `reasoning_probabilistic` uses
+fn verbal_probability(p: f64) -> &'static str {
+    match p {
+        p if p >= 0.95 => "almost_certain",
+        p if p >= 0.8 => "highly_likely",
+        p if p >= 0.6 => "likely",
+        p if p >= 0.4 => "possible",
+        p if p >= 0.2 => "unlikely",
+        p if p >= 0.05 => "highly_unlikely",
+        _ => "almost_impossible",
+    }
+}
+
+fn experiment_ids(experiments: &[Experiment]) -> Vec<&str> {
+    experiments
+        .iter()
+        .map(|e| e.experiment_id.as_str())
+        .collect()
+}
+
+/// Normalize an experiment's outcomes: drop unnamed, duplicate and invalid
+/// outcomes, scale each likelihood column to sum to 1, and floor it at
+/// [`MIN_OUTCOME_LIKELIHOOD`]. `None` if fewer than two outcomes remain or a
+/// column is all zero.
+fn normalize_outcomes(drafts: Vec<OutcomeDraft>) -> Option<Vec<ExpectedOutcome>> {
+    let mut seen = HashSet::new();
+    let outcomes: Vec<OutcomeDraft> = drafts
+        .into_iter()
+        .filter(|o| {
+            let valid = |l: f64| l.is_finite() && l >= 0.0;
+            !o.outcome.trim().is_empty()
+                && valid(o.likelihood_if_true)
+                && valid(o.likelihood_if_false)
+                && seen.insert(o.outcome.trim().to_lowercase())
+        })
+        .collect();
+    let sum_true: f64 = outcomes.iter().map(|o| o.likelihood_if_true).sum();
+    let sum_false: f64 = outcomes.iter().map(|o| o.likelihood_if_false).sum();
+    if outcomes.len() < 2 || sum_true <= 0.0 || sum_false <= 0.0 {
+        return None;
+    }
+    Some(
+        outcomes
+            .into_iter()
+            .map(|o| ExpectedOutcome {
+                outcome: o.outcome.trim().to_string(),
+                likelihood_if_true: (o.likelihood_if_true / sum_true).max(MIN_OUTCOME_LIKELIHOOD),
+                likelihood_if_false: (o.likelihood_if_false / sum_false)
+                    .max(MIN_OUTCOME_LIKELIHOOD),
+            })
+            .collect(),
+    )
+}
+
+/// Parse and check a design, ranking experiments by expected information
+fn parse_design(
+    completion: &str,
+    prior: f64,
+    max_experiments: usize,
+) -> AppResult<(Vec<Prediction>, Vec<Experiment>)> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let response: DesignResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse hypothesis test design: {}", e),
+        })?;
+
+    let predictions: Vec<Prediction> = response
+        .predictions
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| Prediction {
+            prediction_id: if p.key.is_empty() {
+                format!("p{}", i + 1)
+            } else {
+                p.key
+            },
+            statement: p.statement,
+            if_false: p.if_false,
+        })
+        .collect();
+    let prediction_ids: HashSet<&str> = predictions
+        .iter()
+        .map(|p| p.prediction_id.as_str())
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut experiments: Vec<Experiment> = Vec::new();
+    for (i, draft) in response.experiments.into_iter().enumerate() {
+        let experiment_id = if draft.key.is_empty() {
+            format!("e{}", i + 1)
+        } else {
+            draft.key
+        };
+        if !seen.insert(experiment_id.clone()) {
+            debug!(experiment_id = %experiment_id, "Dropping duplicate experiment");
+            continue;
+        }
+        let Some(outcomes) = normalize_outcomes(draft.outcomes) else {
+            debug!(experiment_id = %experiment_id, "Dropping experiment without two usable outcomes");
+            continue;
+        };
+        experiments.push(Experiment {
+            expected_information_bits: expected_information(prior, &outcomes),
+            experiment_id,
+            description: draft.description,
+            predictions: draft
+                .predictions
+                .into_iter()
+                .filter(|p| prediction_ids.contains(p.as_str()))
+                .collect(),
+            outcomes,
+        });
+    }
+    if experiments.is_empty() {
+        return Err(ToolError::Reasoning {
+            message: "Design has no experiment with at least two usable outcomes".to_string(),
+        }
+        .into());
+    }
+    experiments.sort_by(|a, b| {
+        b.expected_information_bits
+            .total_cmp(&a.expected_information_bits)
+    });
+    experiments.truncate(max_experiments);
+
+    Ok((predictions, experiments))
+}
+
+/// The design stored on a probability update, if it holds one
+fn stored_design(update: &ProbabilityUpdate) -> Option<StoredDesign> {
+    let design = update.metadata.as_ref()?.get(DESIGN_METADATA_KEY)?;
+    serde_json::from_value(design.clone()).ok()
+}
+
+/// A recorded observation of a design's experiment
+#[derive(Debug)]
+struct Observation {
+    experiment_id: String,
+    posterior: f64,
+    sequence: u64,
+}
+
+/// The observation of test `test_id` stored on a probability update, if it
+/// is one
+fn observation(update: &ProbabilityUpdate, test_id: &str) -> Option<Observation> {
+    let metadata = update.metadata.as_ref()?;
+    if metadata.get(OBSERVATION_METADATA_KEY)?.as_str()? != test_id {
+        return None;
+    }
+    Some(Observation {
+        experiment_id: metadata.get("experiment_id")?.as_str()?.to_string(),
+        posterior: update.posterior,
+        sequence: metadata.get("sequence")?.as_u64()?,
+    })
+}
+
+/// Design as kept in probability update metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDesign {
+    predictions: Vec<Prediction>,
+    experiments: Vec<Experiment>,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DesignResponse {
+    #[serde(default)]
+    predictions: Vec<PredictionDraft>,
+    experiments: Vec<ExperimentDraft>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PredictionDraft {
+    #[serde(default)]
+    key: String,
+    statement: String,
+    #[serde(default)]
+    if_false: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExperimentDraft {
+    #[serde(default)]
+    key: String,
+    description: String,
+    #[serde(default)]
+    predictions: Vec<String>,
+    #[serde(default)]
+    outcomes: Vec<OutcomeDraft>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OutcomeDraft {
+    outcome: String,
+    likelihood_if_true: f64,
+    likelihood_if_false: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn outcome(name: &str, if_true: f64, if_false: f64) -> OutcomeDraft {
+        OutcomeDraft {
+            outcome: name.to_string(),
+            likelihood_if_true: if_true,
+            likelihood_if_false: if_false,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_hypothesis_params_defaults() {
+        let params: HypothesisParams =
+            serde_json::from_value(json!({"hypothesis": "Caching cut latency"})).unwrap();
+        assert_eq!(params.prior, 0.5);
+        assert_eq!(params.max_experiments, DEFAULT_EXPERIMENTS);
+        assert!(params.session_id.is_none());
+    }
+
+    #[test]
+    fn test_bayes() {
+        let positive = ExpectedOutcome {
+            outcome: "faster".to_string(),
+            likelihood_if_true: 0.8,
+            likelihood_if_false: 0.2,
+        };
+        assert!(close(bayes(0.5, &positive), 0.8));
+        // 0.2 * 0.8 / (0.2 * 0.8 + 0.8 * 0.2)
+        assert!(close(bayes(0.2, &positive), 0.5));
+    }
+
+    #[test]
+    fn test_expected_information() {
+        let informative =
+            normalize_outcomes(vec![outcome("yes", 0.9, 0.1), outcome("no", 0.1, 0.9)]).unwrap();
+        let useless =
+            normalize_outcomes(vec![outcome("yes", 0.5, 0.5), outcome("no", 0.5, 0.5)]).unwrap();
+        let gain = expected_information(0.5, &informative);
+        // 1 bit minus the entropy of 0.9
+        assert!(close(gain, 1.0 - entropy(0.9)));
+        assert!(close(expected_information(0.5, &useless), 0.0));
+        // Near-certain priors leave little to learn
+        assert!(expected_information(0.99, &informative) < gain);
+    }
+
+    #[test]
+    fn test_normalize_outcomes() {
+        let outcomes = normalize_outcomes(vec![
+            outcome("up", 3.0, 1.0),
+            outcome("down", 1.0, 0.0),
+            outcome(" UP ", 1.0, 1.0),
+            outcome("", 1.0, 1.0),
+            outcome("bad", f64::NAN, 1.0),
+        ])
+        .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(close(outcomes[0].likelihood_if_true, 0.75));
+        assert!(close(outcomes[0].likelihood_if_false, 1.0));
+        // A zero likelihood is floored rather than ruling the outcome out
+        assert_eq!(outcomes[1].likelihood_if_false, MIN_OUTCOME_LIKELIHOOD);
+
+        assert!(normalize_outcomes(vec![outcome("only", 1.0, 1.0)]).is_none());
+        assert!(normalize_outcomes(vec![outcome("a", 0.0, 1.0), outcome("b", 0.0, 1.0)]).is_none());
+    }
+
+    #[test]
+    fn test_parse_design_ranks_and_filters() {
+        let completion = json!({
+            "predictions": [{"key": "p1", "statement": "Latency drops", "if_false": "No change"}],
+            "experiments": [
+                {"key": "weak", "description": "Ask users", "predictions": ["p1", "p9"],
+                 "outcomes": [{"outcome": "faster", "likelihood_if_true": 0.6, "likelihood_if_false": 0.4},
+                              {"outcome": "same", "likelihood_if_true": 0.4, "likelihood_if_false": 0.6}]},
+                {"key": "strong", "description": "A/B test",
+                 "outcomes": [{"outcome": "faster", "likelihood_if_true": 0.9, "likelihood_if_false": 0.1},
+                              {"outcome": "same", "likelihood_if_true": 0.1, "likelihood_if_false": 0.9}]},
+                {"key": "broken", "description": "One outcome",
+                 "outcomes": [{"outcome": "done", "likelihood_if_true": 1.0, "likelihood_if_false": 1.0}]}
+            ]
+        })
+        .to_string();
+        let (predictions, experiments) = parse_design(&completion, 0.5, 6).unwrap();
+        assert_eq!(predictions[0].prediction_id, "p1");
+        assert_eq!(experiment_ids(&experiments), ["strong", "weak"]);
+        assert_eq!(experiments[1].predictions, ["p1"]);
+
+        let (_, top) = parse_design(&completion, 0.5, 1).unwrap();
+        assert_eq!(experiment_ids(&top), ["strong"]);
+
+        let none = json!({"experiments": []}).to_string();
+        assert!(parse_design(&none, 0.5, 3).is_err());
+    }
+
+    #[test]
+    fn test_stored_design_and_observation_round_trip() {
+        let design = StoredDesign {
+            predictions: Vec::new(),
+            experiments: Vec::new(),
+        };
+        let record = ProbabilityUpdate::new("s1", "H", 0.5, 0.5, json!([]), json!({}))
+            .with_metadata(json!({ DESIGN_METADATA_KEY: design }));
+        assert!(stored_design(&record).is_some());
+        assert!(observation(&record, &record.id).is_none());
+
+        let observed = ProbabilityUpdate::new("s1", "H", 0.5, 0.8, json!([]), json!({}))
+            .with_metadata(json!({
+                OBSERVATION_METADATA_KEY: record.id,
+                "experiment_id": "e1",
+                "outcome": "faster",
+                "sequence": 2
+            }));
+        let found = observation(&observed, &record.id).unwrap();
+        assert_eq!(found.experiment_id, "e1");
+        assert_eq!(found.sequence, 2);
+        assert_eq!(found.posterior, 0.8);
+        assert!(observation(&observed, "other-test").is_none());
+        assert!(stored_design(&observed).is_none());
+    }
+
+    #[test]
+    fn test_verbal_probability() {
+        assert_eq!(verbal_probability(0.97), "almost_certain");
+        assert_eq!(verbal_probability(0.5), "possible");
+        assert_eq!(verbal_probability(0.01), "almost_impossible");
+    }
+}
@@ -4,17 +4,28 @@
 //! - Single-pass sequential reasoning
 //! - Session continuity with thought history
 //! - Confidence tracking
+//! - Revising an earlier step, marking the superseded thought
+//! - Step limits, and summarizing the history once it outgrows a token budget
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use tracing::{debug, info};
 
-use super::{serialize_for_log, ModeCore};
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest, ReasoningResponse};
-use crate::prompts::LINEAR_REASONING_PROMPT;
-use crate::storage::{Invocation, SqliteStorage, Storage, Thought};
+use crate::langbase::{GenerationParams, Message, PipeRequest, ReasoningResponse};
+use crate::prompts::{LINEAR_REASONING_PROMPT, LINEAR_SUMMARY_PROMPT};
+use crate::providers::{estimate_tokens, SharedBackend};
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{Invocation, Session, SharedStorage, Thought};
+
+/// Token budget for the history sent with each step when a session sets none
+pub const DEFAULT_CONTEXT_BUDGET: usize = 4000;
+
+/// Smallest token budget a session may set
+pub const MIN_CONTEXT_BUDGET: usize = 200;
 
 /// Input parameters for linear reasoning
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +38,23 @@ pub struct LinearParams {
     /// Confidence threshold (0.0-1.0)
     #[serde(default = "default_confidence")]
     pub confidence: f64,
+    /// Earlier thought in the session that this step corrects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revises_thought_id: Option<String>,
+    /// Most steps the session may hold; kept for later steps of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_steps: Option<usize>,
+    /// Token budget for the history sent with each step; kept for later
+    /// steps of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_budget: Option<usize>,
+    /// Bind (`true`) or unbind (`false`) the session to a Langbase thread;
+    /// unset keeps the session's current binding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_thread: Option<bool>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_confidence() -> f64 {
@@ -34,7 +62,7 @@ fn default_confidence() -> f64 {
 }
 
 /// Result of linear reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LinearResult {
     /// The ID of the created thought.
     pub thought_id: String,
@@ -46,23 +74,100 @@ pub struct LinearResult {
     pub confidence: f64,
     /// The ID of the previous thought in the chain, if any.
     pub previous_thought: Option<String>,
+    /// The ID of the thought this step revises, now marked superseded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revised_thought: Option<String>,
+    /// Position of this step in the session, summaries excluded.
+    #[serde(default)]
+    pub step: usize,
+    /// Most steps the session may hold, if limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_steps: Option<usize>,
+    /// The summary thought written before this step because the history
+    /// outgrew the context budget, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_thought: Option<String>,
+}
+
+/// Step limits of a linear session, kept in its metadata under `linear`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct LinearLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_steps: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context_budget: Option<usize>,
+}
+
+impl LinearLimits {
+    const METADATA_KEY: &'static str = "linear";
+
+    /// Limits stored on `session`
+    fn from_session(session: &Session) -> Self {
+        session
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(Self::METADATA_KEY))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// These limits with any set in `params` taking precedence
+    fn overridden_by(self, params: &LinearParams) -> Self {
+        Self {
+            max_steps: params.max_steps.or(self.max_steps),
+            context_budget: params.context_budget.or(self.context_budget),
+        }
+    }
+
+    /// Store these limits in `session`'s metadata
+    fn store(&self, session: &mut Session) {
+        let mut metadata = match session.metadata.take() {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        metadata.insert(
+            Self::METADATA_KEY.to_string(),
+            serde_json::to_value(self).unwrap_or_default(),
+        );
+        session.metadata = Some(serde_json::Value::Object(metadata));
+    }
+
+    fn context_budget(&self) -> usize {
+        self.context_budget.unwrap_or(DEFAULT_CONTEXT_BUDGET)
+    }
+}
+
+/// Response from the reflection pipe when summarizing a linear chain.
+#[derive(Debug, Deserialize)]
+struct SummaryResponse {
+    summary: String,
+    #[serde(default)]
+    confidence: f64,
 }
 
 /// Linear reasoning mode handler for sequential reasoning.
 #[derive(Clone)]
 pub struct LinearMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for linear reasoning.
     pipe_name: String,
+    /// The reflection pipe, which summarizes long histories.
+    reflection_pipe: String,
 }
 
 impl LinearMode {
     /// Create a new linear mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config.pipes.linear.clone(),
+            reflection_pipe: config.pipes.reflection.clone(),
         }
     }
 
@@ -78,26 +183,107 @@ impl LinearMode {
             }
             .into());
         }
+        params.generation.validate()?;
+        if params.revises_thought_id.is_some() && params.session_id.is_none() {
+            return Err(ToolError::Validation {
+                field: "revises_thought_id".to_string(),
+                reason: "Revising a thought requires the session_id it belongs to".to_string(),
+            }
+            .into());
+        }
+        if params.max_steps == Some(0) {
+            return Err(ToolError::Validation {
+                field: "max_steps".to_string(),
+                reason: "Must be at least 1".to_string(),
+            }
+            .into());
+        }
+        if params
+            .context_budget
+            .is_some_and(|b| b < MIN_CONTEXT_BUDGET)
+        {
+            return Err(ToolError::Validation {
+                field: "context_budget".to_string(),
+                reason: format!("Must be at least {} tokens", MIN_CONTEXT_BUDGET),
+            }
+            .into());
+        }
 
         // Get or create session
-        let session = self
+        let mut session = self
             .core
             .storage()
             .get_or_create_session(&params.session_id, "linear")
             .await?;
 
-        debug!(session_id = %session.id, "Processing linear reasoning");
+        let stored_limits = LinearLimits::from_session(&session);
+        let limits = stored_limits.overridden_by(&params);
+        let mut session_changed = limits != stored_limits;
+        if session_changed {
+            limits.store(&mut session);
+        }
+        if params.use_thread == Some(false) && session.thread_id.take().is_some() {
+            session_changed = true;
+        }
+        if session_changed {
+            self.core.storage().update_session(&session).await?;
+        }
+
+        debug!(
+            session_id = %session.id,
+            thread_id = ?session.thread_id,
+            "Processing linear reasoning"
+        );
 
         // Get previous thoughts for context
-        let previous_thoughts = self
+        let mut previous_thoughts = self
             .core
             .storage()
             .get_session_thoughts(&session.id)
             .await?;
+        let steps_taken = previous_thoughts.iter().filter(|t| !is_summary(t)).count();
+        if let Some(max_steps) = limits.max_steps {
+            if steps_taken >= max_steps {
+                return Err(ToolError::Validation {
+                    field: "max_steps".to_string(),
+                    reason: format!(
+                        "Session already holds {} of its {} steps",
+                        steps_taken, max_steps
+                    ),
+                }
+                .into());
+            }
+        }
         let previous_thought = previous_thoughts.last().cloned();
+        let revised = match &params.revises_thought_id {
+            Some(id) => Some(revised_thought(&previous_thoughts, id)?.clone()),
+            None => None,
+        };
 
-        // Build context for Langbase
-        let messages = self.build_messages(&params.content, &previous_thoughts);
+        // Fold the history into a summary once it outgrows the budget. A
+        // bound thread keeps its own history, so it is never summarized.
+        let mut summary_thought = None;
+        if session.thread_id.is_none() && needs_summary(&previous_thoughts, limits.context_budget())
+        {
+            let summary = self
+                .summarize(&session.id, &previous_thoughts, params.generation)
+                .await?;
+            summary_thought = Some(summary.id.clone());
+            previous_thoughts.push(summary);
+        }
+
+        // Build context for Langbase. A bound thread already holds the system
+        // prompt and earlier steps, so only the new thought is sent.
+        let content = match &revised {
+            Some(revised) => revision_request(&params.content, &previous_thoughts, revised),
+            None => params.content.clone(),
+        };
+        let messages = match &session.thread_id {
+            Some(_) => vec![Message::user(content)],
+            None => self.build_messages(&content, &previous_thoughts),
+        };
+
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
 
         // Create invocation log
         let mut invocation = Invocation::new(
@@ -105,11 +291,16 @@ impl LinearMode {
             serialize_for_log(&params, "reasoning.linear input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.pipe_name);
+        .with_pipe(&pipe_name);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.pipe_name, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let mut request = PipeRequest::new(&pipe_name, messages)
+            .with_output_schema(self.core.response_schema(SchemaKind::Linear, &pipe_name))
+            .with_generation(params.generation);
+        if let Some(thread_id) = &session.thread_id {
+            request = request.with_thread_id(thread_id);
+        }
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -118,15 +309,47 @@ impl LinearMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
+
+        // Bind the thread the pipe run started. Backends without threads
+        // return none, leaving the session on full-history prompts.
+        if params.use_thread == Some(true) && session.thread_id.is_none() {
+            if let Some(thread_id) = response.thread_id.clone() {
+                info!(session_id = %session.id, thread_id = %thread_id, "Bound session to Langbase thread");
+                session.thread_id = Some(thread_id);
+                self.core.storage().update_session(&session).await?;
+            }
+        }
 
         // Parse response
         let reasoning = ReasoningResponse::from_completion(&response.completion);
 
-        // Create and store thought
-        let thought = Thought::new(&session.id, &reasoning.thought, "linear")
+        // Create and store thought. A revision hangs off the thought it
+        // corrects, which is then marked superseded.
+        let mut thought = Thought::new(&session.id, &reasoning.thought, "linear")
             .with_confidence(reasoning.confidence.max(params.confidence));
+        if let Some(revised) = &revised {
+            thought = thought
+                .with_parent(&revised.id)
+                .with_metadata(serde_json::json!({ REVISION_KEY: true }));
+        }
 
         self.core.storage().create_thought(&thought).await?;
+        if let Some(revised) = &revised {
+            let mut superseded = revised.clone();
+            let mut metadata = match superseded.metadata.take() {
+                Some(serde_json::Value::Object(map)) => map,
+                _ => serde_json::Map::new(),
+            };
+            metadata.insert(SUPERSEDED_KEY.to_string(), serde_json::Value::Bool(true));
+            superseded.metadata = Some(serde_json::Value::Object(metadata));
+            self.core
+                .storage()
+                .update_thought_metadata(&superseded)
+                .await?;
+        }
 
         // Log successful invocation
         let latency = start.elapsed().as_millis() as i64;
@@ -139,6 +362,8 @@ impl LinearMode {
         info!(
             session_id = %session.id,
             thought_id = %thought.id,
+            revised_thought = ?revised.as_ref().map(|t| &t.id),
+            summarized = summary_thought.is_some(),
             latency_ms = latency,
             "Linear reasoning completed"
         );
@@ -149,10 +374,92 @@ impl LinearMode {
             content: reasoning.thought,
             confidence: reasoning.confidence,
             previous_thought: previous_thought.map(|t| t.id),
+            revised_thought: revised.map(|t| t.id),
+            step: steps_taken + 1,
+            max_steps: limits.max_steps,
+            summary_thought,
         })
     }
 
+    /// Summarize the history with the reflection pipe and store the summary
+    /// as a thought; later steps see it in place of the steps it covers
+    async fn summarize(
+        &self,
+        session_id: &str,
+        history: &[Thought],
+        generation: GenerationParams,
+    ) -> AppResult<Thought> {
+        let start = Instant::now();
+        let chain = render_chain(history, context_start(history));
+        let steps = history.iter().filter(|t| !is_summary(t)).count();
+        let pipe_name = self.core.select_pipe(&self.reflection_pipe);
+
+        let mut invocation = Invocation::new(
+            "reasoning.linear.summarize",
+            serde_json::json!({ "session_id": session_id, "steps": steps }),
+        )
+        .with_session(session_id)
+        .with_pipe(&pipe_name);
+
+        let messages = vec![
+            Message::system(LINEAR_SUMMARY_PROMPT),
+            Message::user(format!("Reasoning steps so far:\n{}", chain.join("\n"))),
+        ];
+        let request = PipeRequest::new(&pipe_name, messages).with_generation(generation);
+        let response = match self.core.backend().call_pipe(request).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e.into());
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
+
+        let json_str = extract_json_from_completion(&response.completion)
+            .map_err(|e| ToolError::Reasoning { message: e })?;
+        let summary: SummaryResponse =
+            serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                message: format!("Failed to parse linear summary: {}", e),
+            })?;
+        if summary.summary.trim().is_empty() {
+            return Err(ToolError::Reasoning {
+                message: "Linear summary is empty".to_string(),
+            }
+            .into());
+        }
+
+        let thought = Thought::new(session_id, &summary.summary, "linear")
+            .with_confidence(summary.confidence.clamp(0.0, 1.0))
+            .with_metadata(serde_json::json!({
+                SUMMARY_KEY: true,
+                "summarized_steps": steps
+            }));
+        self.core.storage().create_thought(&thought).await?;
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation.success(
+            serde_json::json!({ "thought_id": thought.id, "summary": summary.summary }),
+            latency,
+        );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session_id,
+            thought_id = %thought.id,
+            steps,
+            latency_ms = latency,
+            "Summarized linear history"
+        );
+        Ok(thought)
+    }
+
     /// Build messages for the Langbase pipe
+    ///
+    /// Only the history from the latest summary on is sent.
     fn build_messages(&self, content: &str, history: &[Thought]) -> Vec<Message> {
         let mut messages = Vec::new();
 
@@ -161,12 +468,9 @@ impl LinearMode {
 
         // Add history context if available
         if !history.is_empty() {
-            let history_text: Vec<String> =
-                history.iter().map(|t| format!("- {}", t.content)).collect();
-
             messages.push(Message::user(format!(
                 "Previous reasoning steps:\n{}\n\nNow process this thought:",
-                history_text.join("\n")
+                render_chain(history, context_start(history)).join("\n")
             )));
         }
 
@@ -184,6 +488,11 @@ impl LinearParams {
             content: content.into(),
             session_id: None,
             confidence: default_confidence(),
+            revises_thought_id: None,
+            max_steps: None,
+            context_budget: None,
+            use_thread: None,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -198,13 +507,165 @@ impl LinearParams {
         self.confidence = confidence.clamp(0.0, 1.0);
         self
     }
+
+    /// Revise an earlier thought in the session
+    pub fn with_revision_of(mut self, thought_id: impl Into<String>) -> Self {
+        self.revises_thought_id = Some(thought_id.into());
+        self
+    }
+
+    /// Limit the session to `max_steps` steps
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Set the token budget for the history sent with each step
+    pub fn with_context_budget(mut self, tokens: usize) -> Self {
+        self.context_budget = Some(tokens);
+        self
+    }
+
+    /// Bind or unbind the session's Langbase thread
+    pub fn with_thread(mut self, use_thread: bool) -> Self {
+        self.use_thread = Some(use_thread);
+        self
+    }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+/// Thought metadata flag set on a step that revises an earlier one
+const REVISION_KEY: &str = "revision";
+
+/// Thought metadata flag set on a step that a later step revised
+const SUPERSEDED_KEY: &str = "superseded";
+
+/// Thought metadata flag set on a summary of the steps before it
+const SUMMARY_KEY: &str = "summary";
+
+fn metadata_flag(thought: &Thought, key: &str) -> bool {
+    thought
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get(key))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn is_summary(thought: &Thought) -> bool {
+    metadata_flag(thought, SUMMARY_KEY)
+}
+
+/// The thought `id` in `history`, which must be a step not already
+/// superseded
+fn revised_thought<'a>(history: &'a [Thought], id: &str) -> AppResult<&'a Thought> {
+    let thought = history
+        .iter()
+        .find(|t| t.id == id && !is_summary(t))
+        .ok_or_else(|| ToolError::Validation {
+            field: "revises_thought_id".to_string(),
+            reason: format!("Thought not found in this session: {}", id),
+        })?;
+    if metadata_flag(thought, SUPERSEDED_KEY) {
+        return Err(ToolError::Validation {
+            field: "revises_thought_id".to_string(),
+            reason: format!(
+                "Thought {} was already revised; revise its latest revision instead",
+                id
+            ),
+        }
+        .into());
+    }
+    Ok(thought)
+}
+
+/// 1-based step number of the thought `id` in `history`, summaries excluded
+fn step_number(history: &[Thought], id: &str) -> Option<usize> {
+    let index = history.iter().position(|t| t.id == id)?;
+    Some(history[..=index].iter().filter(|t| !is_summary(t)).count())
+}
+
+/// Index of the latest summary in `history`, where the context sent to the
+/// pipe starts; `0` when there is none
+fn context_start(history: &[Thought]) -> usize {
+    history.iter().rposition(is_summary).unwrap_or(0)
+}
+
+/// Whether the context sent with the next step exceeds `budget` tokens and
+/// holds steps a summary could fold
+fn needs_summary(history: &[Thought], budget: usize) -> bool {
+    let start = context_start(history);
+    if !history[start..].iter().any(|t| !is_summary(t)) {
+        return false;
+    }
+    let chain = render_chain(history, start).join("\n");
+    estimate_tokens(&chain) > budget as u64
+}
+
+/// The user content of a revising step: which step it corrects, then the
+/// correction itself
+fn revision_request(content: &str, history: &[Thought], revised: &Thought) -> String {
+    let step = step_number(history, &revised.id).unwrap_or(history.len());
+    format!(
+        "Revise step {}, which said: \"{}\"\n\nCorrection: {}",
+        step, revised.content, content
+    )
+}
+
+/// One line per thought of `history` from index `from` on, noting summaries,
+/// which steps were superseded and by which revision. Step numbers count
+/// from the start of `history`.
+fn render_chain(history: &[Thought], from: usize) -> Vec<String> {
+    history
+        .iter()
+        .enumerate()
+        .skip(from)
+        .map(|(index, thought)| {
+            if is_summary(thought) {
+                let covered = history[..index].iter().filter(|t| !is_summary(t)).count();
+                return format!("- [summary of steps 1-{}] {}", covered, thought.content);
+            }
+            let revises = thought
+                .parent_id
+                .as_deref()
+                .filter(|_| metadata_flag(thought, REVISION_KEY))
+                .and_then(|id| step_number(history, id));
+            let superseded_by = history
+                .iter()
+                .find(|t| {
+                    t.parent_id.as_deref() == Some(thought.id.as_str())
+                        && metadata_flag(t, REVISION_KEY)
+                })
+                .and_then(|t| step_number(history, &t.id));
+
+            let mut line = "- ".to_string();
+            if metadata_flag(thought, SUPERSEDED_KEY) {
+                match superseded_by {
+                    Some(step) => line.push_str(&format!("[superseded by step {}] ", step)),
+                    None => line.push_str("[superseded] "),
+                }
+            }
+            if let Some(step) = revises {
+                line.push_str(&format!("[revises step {}] ", step));
+            }
+            line.push_str(&thought.content);
+            line
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::RequestConfig;
+    use crate::langbase::LangbaseClient;
     use crate::langbase::MessageRole;
+    use crate::storage::SqliteStorage;
 
     // ============================================================================
     // LinearParams Tests
@@ -297,6 +758,10 @@ mod tests {
             content: "Reasoning output".to_string(),
             confidence: 0.88,
             previous_thought: Some("thought-122".to_string()),
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -332,6 +797,10 @@ mod tests {
             content: "First thought".to_string(),
             confidence: 0.8,
             previous_thought: None,
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -543,6 +1012,10 @@ mod tests {
             content: "Test content".to_string(),
             confidence: 0.92,
             previous_thought: Some("t-122".to_string()),
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -579,6 +1052,10 @@ mod tests {
             content: "Content".to_string(),
             confidence: 0.8,
             previous_thought: None,
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -642,8 +1119,11 @@ mod tests {
 
     fn create_test_config() -> Config {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig, RequestConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig,
+            TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -651,10 +1131,15 @@ mod tests {
             langbase: LangbaseConfig {
                 api_key: "test-key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -663,6 +1148,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         }
     }
 
@@ -968,6 +1465,10 @@ mod tests {
             content: "Content".to_string(),
             confidence: 0.88,
             previous_thought: Some("t-0".to_string()),
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let cloned = original.clone();
@@ -987,6 +1488,10 @@ mod tests {
             content: "Debug result".to_string(),
             confidence: 0.92,
             previous_thought: None,
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         let debug_str = format!("{:?}", result);
@@ -1119,6 +1624,10 @@ mod tests {
             content: "Test content".to_string(),
             confidence: 0.777,
             previous_thought: Some("prev-id".to_string()),
+            revised_thought: None,
+            step: 1,
+            max_steps: None,
+            summary_thought: None,
         };
 
         assert_eq!(result.thought_id, "id-1");
@@ -1139,4 +1648,145 @@ mod tests {
         let params = LinearParams::new("Test");
         assert_eq!(params.confidence, 0.8);
     }
+
+    // ============================================================================
+    // Revision Tests
+    // ============================================================================
+
+    fn revised_history() -> Vec<Thought> {
+        let first = Thought::new("sess-1", "Load is 100 rps", "linear")
+            .with_metadata(serde_json::json!({ "superseded": true }));
+        let second = Thought::new("sess-1", "Two nodes suffice", "linear");
+        let third = Thought::new("sess-1", "Peak load is 400 rps", "linear")
+            .with_parent(&first.id)
+            .with_metadata(serde_json::json!({ "revision": true }));
+        vec![first, second, third]
+    }
+
+    #[test]
+    fn test_linear_params_with_revision_of() {
+        let params = LinearParams::new("Fix").with_revision_of("t-1");
+        assert_eq!(params.revises_thought_id, Some("t-1".to_string()));
+
+        let json = serde_json::to_string(&LinearParams::new("Plain")).unwrap();
+        assert!(!json.contains("revises_thought_id"));
+    }
+
+    #[test]
+    fn test_render_chain_marks_corrections() {
+        let lines = render_chain(&revised_history(), 0);
+        assert_eq!(
+            lines,
+            vec![
+                "- [superseded by step 3] Load is 100 rps",
+                "- Two nodes suffice",
+                "- [revises step 1] Peak load is 400 rps",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_chain_ignores_plain_parent_links() {
+        let first = Thought::new("sess-1", "Root", "linear");
+        let second = Thought::new("sess-1", "Child", "linear").with_parent(&first.id);
+        assert_eq!(render_chain(&[first, second], 0), vec!["- Root", "- Child"]);
+    }
+
+    #[test]
+    fn test_revised_thought_validation() {
+        let history = revised_history();
+        assert_eq!(
+            revised_thought(&history, &history[1].id).unwrap().id,
+            history[1].id
+        );
+        assert!(revised_thought(&history, &history[0].id).is_err());
+        assert!(revised_thought(&history, "missing").is_err());
+    }
+
+    #[test]
+    fn test_revision_request_names_step() {
+        let history = revised_history();
+        let request = revision_request("Use three nodes", &history, &history[1]);
+        assert_eq!(
+            request,
+            "Revise step 2, which said: \"Two nodes suffice\"\n\nCorrection: Use three nodes"
+        );
+    }
+
+    fn summarized_history() -> Vec<Thought> {
+        let mut history = revised_history();
+        history.push(
+            Thought::new("sess-1", "Three nodes handle the peak", "linear")
+                .with_metadata(serde_json::json!({ "summary": true, "summarized_steps": 3 })),
+        );
+        history.push(Thought::new("sess-1", "Add a replica", "linear"));
+        history
+    }
+
+    #[test]
+    fn test_linear_params_with_limits() {
+        let params = LinearParams::new("Plan")
+            .with_max_steps(5)
+            .with_context_budget(1000);
+        assert_eq!(params.max_steps, Some(5));
+        assert_eq!(params.context_budget, Some(1000));
+
+        let json = serde_json::to_string(&LinearParams::new("Plain")).unwrap();
+        assert!(!json.contains("max_steps"));
+        assert!(!json.contains("context_budget"));
+    }
+
+    #[test]
+    fn test_linear_limits_stored_in_session() {
+        let mut session = Session::new("linear");
+        session.metadata = Some(serde_json::json!({ "owner": "tests" }));
+        assert_eq!(
+            LinearLimits::from_session(&session),
+            LinearLimits::default()
+        );
+
+        let limits =
+            LinearLimits::default().overridden_by(&LinearParams::new("Plan").with_max_steps(4));
+        limits.store(&mut session);
+        assert_eq!(session.metadata.as_ref().unwrap()["owner"], "tests");
+
+        let stored = LinearLimits::from_session(&session);
+        assert_eq!(stored.max_steps, Some(4));
+        assert_eq!(stored.context_budget(), DEFAULT_CONTEXT_BUDGET);
+        let overridden = stored.overridden_by(&LinearParams::new("Next").with_context_budget(500));
+        assert_eq!(overridden.max_steps, Some(4));
+        assert_eq!(overridden.context_budget(), 500);
+    }
+
+    #[test]
+    fn test_render_chain_from_summary() {
+        let history = summarized_history();
+        assert_eq!(context_start(&history), 3);
+        assert_eq!(
+            render_chain(&history, context_start(&history)),
+            vec![
+                "- [summary of steps 1-3] Three nodes handle the peak",
+                "- Add a replica",
+            ]
+        );
+        assert_eq!(step_number(&history, &history[4].id), Some(4));
+    }
+
+    #[test]
+    fn test_needs_summary() {
+        let history = revised_history();
+        assert!(!needs_summary(&history, DEFAULT_CONTEXT_BUDGET));
+        assert!(needs_summary(&history, 5));
+
+        // A summary with no steps after it is not summarized again
+        let mut history = summarized_history();
+        history.pop();
+        assert!(!needs_summary(&history, 1));
+    }
+
+    #[test]
+    fn test_summary_cannot_be_revised() {
+        let history = summarized_history();
+        assert!(revised_thought(&history, &history[3].id).is_err());
+    }
 }
@@ -1,27 +1,36 @@
 //! MCTS reasoning mode - Monte Carlo Tree Search for reasoning exploration.
 //!
 //! This module provides MCTS-based reasoning for:
-//! - Systematic exploration of reasoning paths using UCB1 selection
-//! - Simulation-based evaluation of reasoning quality
+//! - Systematic exploration of reasoning paths using UCB1 selection, or
+//!   progressive widening for wide searches
+//! - Simulation-based evaluation of reasoning quality, optionally after a
+//!   multi-step rollout
 //! - Backpropagation of rewards through the search tree
+//! - Per-iteration statistics, stored for the metrics CLI
 //! - Automatic backtracking based on reward signals
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
-use crate::config::Config;
+use crate::config::{Config, MctsConfig};
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
 use crate::prompts::TREE_REASONING_PROMPT;
-use crate::storage::{Invocation, MCTSNode, SqliteStorage, Storage};
+use crate::providers::SharedBackend;
+use crate::storage::{Invocation, MCTSNode, MctsIteration, MctsPolicy, SharedStorage};
 
-/// Default exploration constant for UCB1 (sqrt(2))
-const DEFAULT_EXPLORATION_CONSTANT: f64 = std::f64::consts::SQRT_2;
+/// Largest exploration constant a call may set.
+const MAX_EXPLORATION_CONSTANT: f64 = 10.0;
 
-/// Default simulation depth
-const DEFAULT_SIMULATION_DEPTH: i32 = 3;
+/// Most continuation steps a simulation may roll out.
+const MAX_ROLLOUT_DEPTH: usize = 5;
+
+/// Most children one expansion may generate.
+const MAX_EXPANSION_WIDTH: usize = 5;
 
 /// Input parameters for MCTS exploration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,26 +46,27 @@ pub struct MCTSExploreParams {
     /// Number of MCTS iterations to perform
     #[serde(default = "default_iterations")]
     pub iterations: usize,
-    /// Exploration constant for UCB1
-    #[serde(default = "default_exploration")]
-    pub exploration_constant: f64,
-    /// Maximum simulation depth
-    #[serde(default = "default_sim_depth")]
-    pub simulation_depth: i32,
+    /// Node selection policy (default: MCTS_POLICY)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<MctsPolicy>,
+    /// Exploration constant for UCB1 (default: MCTS_EXPLORATION_CONSTANT)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exploration_constant: Option<f64>,
+    /// Continuation steps each simulation rolls out (default: MCTS_ROLLOUT_DEPTH)
+    #[serde(alias = "simulation_depth", skip_serializing_if = "Option::is_none")]
+    pub rollout_depth: Option<usize>,
+    /// Children generated per expansion (default: MCTS_EXPANSION_WIDTH)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expansion_width: Option<usize>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_iterations() -> usize {
     5
 }
 
-fn default_exploration() -> f64 {
-    DEFAULT_EXPLORATION_CONSTANT
-}
-
-fn default_sim_depth() -> i32 {
-    DEFAULT_SIMULATION_DEPTH
-}
-
 /// Input parameters for auto-backtracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoBacktrackParams {
@@ -81,8 +91,76 @@ fn default_reward_threshold() -> f64 {
     0.2
 }
 
+/// Settings one search ran with: the call's parameters over the configured
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchSettings {
+    /// Node selection policy
+    pub policy: MctsPolicy,
+    /// UCB1 exploration constant
+    pub exploration_constant: f64,
+    /// Continuation steps each simulation rolled out
+    pub rollout_depth: usize,
+    /// Children generated per expansion
+    pub expansion_width: usize,
+    /// Progressive widening coefficient k
+    pub widening_coefficient: f64,
+    /// Progressive widening exponent alpha
+    pub widening_exponent: f64,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self::resolve(
+            &MCTSExploreParams {
+                content: String::new(),
+                session_id: None,
+                timeline_id: None,
+                iterations: default_iterations(),
+                policy: None,
+                exploration_constant: None,
+                rollout_depth: None,
+                expansion_width: None,
+                generation: GenerationParams::default(),
+            },
+            &MctsConfig::default(),
+        )
+    }
+}
+
+impl SearchSettings {
+    /// Resolve the settings of a search, clamping each to its allowed range.
+    pub fn resolve(params: &MCTSExploreParams, config: &MctsConfig) -> Self {
+        let exploration_constant = params
+            .exploration_constant
+            .filter(|c| c.is_finite())
+            .unwrap_or(config.exploration_constant);
+        Self {
+            policy: params.policy.unwrap_or(config.policy),
+            exploration_constant: exploration_constant.clamp(0.0, MAX_EXPLORATION_CONSTANT),
+            rollout_depth: params
+                .rollout_depth
+                .unwrap_or(config.rollout_depth)
+                .min(MAX_ROLLOUT_DEPTH),
+            expansion_width: params
+                .expansion_width
+                .unwrap_or(config.expansion_width)
+                .clamp(1, MAX_EXPANSION_WIDTH),
+            widening_coefficient: config.widening_coefficient,
+            widening_exponent: config.widening_exponent,
+        }
+    }
+
+    /// Children a node visited `visits` times may have under progressive
+    /// widening: ceil(k * N^alpha), at least one.
+    pub fn widening_limit(&self, visits: i32) -> usize {
+        let limit = self.widening_coefficient * (visits.max(1) as f64).powf(self.widening_exponent);
+        (limit.ceil() as usize).max(1)
+    }
+}
+
 /// Result of MCTS exploration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MCTSExploreResult {
     /// Session ID
     pub session_id: String,
@@ -96,25 +174,46 @@ pub struct MCTSExploreResult {
     pub best_path_value: f64,
     /// Number of nodes explored
     pub nodes_explored: usize,
+    /// Settings the search ran with
+    #[serde(default)]
+    pub settings: SearchSettings,
+    /// Pipe calls the search made
+    #[serde(default)]
+    pub pipe_calls: usize,
     /// Statistics per iteration
     pub iteration_stats: Vec<IterationStats>,
 }
 
 /// Statistics for one MCTS iteration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IterationStats {
     /// Iteration number
     pub iteration: usize,
     /// Node selected for expansion
     pub selected_node: String,
+    /// Depth of the selected node (root is 0)
+    #[serde(default)]
+    pub selected_depth: usize,
+    /// Children the expansion created
+    #[serde(default)]
+    pub children_created: usize,
     /// Value from simulation
     pub simulation_value: f64,
     /// Nodes visited in backpropagation
     pub backprop_nodes: usize,
+    /// Highest mean value of any visited node after the iteration
+    #[serde(default)]
+    pub best_value: f64,
+    /// Nodes in the tree after the iteration
+    #[serde(default)]
+    pub nodes_total: usize,
+    /// Pipe calls the iteration made
+    #[serde(default)]
+    pub pipe_calls: usize,
 }
 
 /// Result of auto-backtracking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AutoBacktrackResult {
     /// Whether backtracking was triggered
     pub backtracked: bool,
@@ -131,7 +230,7 @@ pub struct AutoBacktrackResult {
 }
 
 /// Alternative path suggestion
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AlternativePath {
     /// Starting node ID
     pub from_node: String,
@@ -141,6 +240,224 @@ pub struct AlternativePath {
     pub expected_improvement: f64,
 }
 
+/// Summary of one stored search, for the metrics CLI.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MctsSearchSummary {
+    /// Root node of the search
+    pub root_node_id: String,
+    /// Session of the search
+    pub session_id: String,
+    /// Selection policy
+    pub policy: MctsPolicy,
+    /// UCB1 exploration constant
+    pub exploration_constant: f64,
+    /// Rollout depth
+    pub rollout_depth: i32,
+    /// Expansion width
+    pub expansion_width: i32,
+    /// Iterations run
+    pub iterations: usize,
+    /// Nodes in the final tree
+    pub nodes: i32,
+    /// Pipe calls across all iterations
+    pub pipe_calls: i32,
+    /// Best mean value after the last iteration
+    pub best_value: f64,
+    /// Time across all iterations in milliseconds
+    pub latency_ms: i64,
+}
+
+/// Mean result quality reached after a number of iterations, across the
+/// searches of one policy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MctsBudgetPoint {
+    /// Selection policy
+    pub policy: MctsPolicy,
+    /// Iterations spent
+    pub iteration: i32,
+    /// Searches that ran this many iterations
+    pub searches: usize,
+    /// Mean best value after the iteration
+    pub mean_best_value: f64,
+    /// Mean pipe calls spent up to and including the iteration
+    pub mean_pipe_calls: f64,
+    /// Mean nodes in the tree after the iteration
+    pub mean_nodes: f64,
+}
+
+/// Summarize stored iterations per search, in the order the searches appear.
+pub fn summarize_mcts_searches(iterations: &[MctsIteration]) -> Vec<MctsSearchSummary> {
+    let mut summaries: Vec<MctsSearchSummary> = Vec::new();
+    for iteration in iterations {
+        match summaries
+            .iter_mut()
+            .find(|s| s.root_node_id == iteration.root_node_id)
+        {
+            Some(summary) => {
+                summary.iterations += 1;
+                summary.nodes = summary.nodes.max(iteration.nodes_total);
+                summary.pipe_calls += iteration.pipe_calls;
+                summary.best_value = iteration.best_value;
+                summary.latency_ms += iteration.latency_ms;
+            }
+            None => summaries.push(MctsSearchSummary {
+                root_node_id: iteration.root_node_id.clone(),
+                session_id: iteration.session_id.clone(),
+                policy: iteration.policy,
+                exploration_constant: iteration.exploration_constant,
+                rollout_depth: iteration.rollout_depth,
+                expansion_width: iteration.expansion_width,
+                iterations: 1,
+                nodes: iteration.nodes_total,
+                pipe_calls: iteration.pipe_calls,
+                best_value: iteration.best_value,
+                latency_ms: iteration.latency_ms,
+            }),
+        }
+    }
+    summaries
+}
+
+/// Relate budget to quality: for each policy and iteration count, the mean
+/// best value searches had reached and the pipe calls they had spent.
+pub fn mcts_budget_curve(iterations: &[MctsIteration]) -> Vec<MctsBudgetPoint> {
+    // Points hold sums until every iteration has been counted.
+    let mut totals: BTreeMap<(String, i32), MctsBudgetPoint> = BTreeMap::new();
+    let mut spent: BTreeMap<&str, i32> = BTreeMap::new();
+    for iteration in iterations {
+        let calls = spent.entry(iteration.root_node_id.as_str()).or_insert(0);
+        *calls += iteration.pipe_calls;
+        let point = totals
+            .entry((iteration.policy.to_string(), iteration.iteration))
+            .or_insert(MctsBudgetPoint {
+                policy: iteration.policy,
+                iteration: iteration.iteration,
+                searches: 0,
+                mean_best_value: 0.0,
+                mean_pipe_calls: 0.0,
+                mean_nodes: 0.0,
+            });
+        point.searches += 1;
+        point.mean_best_value += iteration.best_value;
+        point.mean_pipe_calls += *calls as f64;
+        point.mean_nodes += iteration.nodes_total as f64;
+    }
+    totals
+        .into_values()
+        .map(|mut point| {
+            let searches = point.searches as f64;
+            point.mean_best_value /= searches;
+            point.mean_pipe_calls /= searches;
+            point.mean_nodes /= searches;
+            point
+        })
+        .collect()
+}
+
+/// Pick the node to expand next, descending from the root by UCB1.
+///
+/// Descent stops at a node that has not been expanded, that is terminal, or
+/// that has no children. Under progressive widening it also stops at a node
+/// with fewer children than its visit count allows, so the node is widened.
+fn select_node(nodes: &[MCTSNode], settings: &SearchSettings) -> usize {
+    let mut current = 0;
+    loop {
+        let node = &nodes[current];
+        if !node.is_expanded || node.is_terminal {
+            return current;
+        }
+        let children: Vec<usize> = child_indices(nodes, &node.id);
+        if children.is_empty() {
+            return current;
+        }
+        if settings.policy == MctsPolicy::ProgressiveWidening
+            && children.len() < settings.widening_limit(node.visit_count)
+        {
+            return current;
+        }
+        let parent_visits = node.visit_count.max(1);
+        current = children
+            .into_iter()
+            .max_by(|&a, &b| {
+                let ucb_a = nodes[a].calculate_ucb(parent_visits, settings.exploration_constant);
+                let ucb_b = nodes[b].calculate_ucb(parent_visits, settings.exploration_constant);
+                ucb_a
+                    .partial_cmp(&ucb_b)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(
+                        nodes[a]
+                            .prior
+                            .partial_cmp(&nodes[b].prior)
+                            .unwrap_or(std::cmp::Ordering::Equal),
+                    )
+            })
+            .unwrap_or(current);
+    }
+}
+
+/// Number of children an expansion of `nodes[index]` should generate.
+fn expansion_count(nodes: &[MCTSNode], index: usize, settings: &SearchSettings) -> usize {
+    match settings.policy {
+        MctsPolicy::Ucb1 => settings.expansion_width,
+        MctsPolicy::ProgressiveWidening => {
+            let existing = child_indices(nodes, &nodes[index].id).len();
+            settings
+                .widening_limit(nodes[index].visit_count)
+                .saturating_sub(existing)
+                .clamp(1, settings.expansion_width)
+        }
+    }
+}
+
+/// Indices of a node's children.
+fn child_indices(nodes: &[MCTSNode], id: &str) -> Vec<usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| n.parent_node_id.as_deref() == Some(id))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Highest mean value of any visited node.
+fn best_mean_value(nodes: &[MCTSNode]) -> f64 {
+    nodes
+        .iter()
+        .filter(|n| n.visit_count > 0)
+        .map(MCTSNode::q_value)
+        .fold(0.0, f64::max)
+}
+
+/// Find the best path through the tree, following the child with the
+/// highest average value from the root.
+fn find_best_path(nodes: &[MCTSNode]) -> (Vec<String>, Vec<String>, f64) {
+    let Some(root) = nodes.first() else {
+        return (Vec::new(), Vec::new(), 0.0);
+    };
+    let mut path_ids = vec![root.id.clone()];
+    let mut path_content = vec![root.content.clone()];
+    let mut current = root;
+    let mut total_value = root.total_value;
+
+    // Follow best children
+    while let Some(best_child) = nodes
+        .iter()
+        .filter(|n| n.parent_node_id.as_ref() == Some(&current.id))
+        .max_by(|a, b| {
+            a.q_value()
+                .partial_cmp(&b.q_value())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    {
+        path_ids.push(best_child.id.clone());
+        path_content.push(best_child.content.clone());
+        total_value += best_child.total_value;
+        current = best_child;
+    }
+
+    (path_ids, path_content, total_value)
+}
+
 /// MCTS mode handler for Monte Carlo Tree Search reasoning.
 #[derive(Clone)]
 pub struct MCTSMode {
@@ -158,11 +475,17 @@ pub struct MCTSMode {
     /// Reserved for future auto-backtrack quality assessment.
     #[allow(dead_code)]
     reflection_pipe: String,
+    /// Search defaults
+    mcts: MctsConfig,
 }
 
 impl MCTSMode {
     /// Create a new MCTS mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         // Extract pipe names from config, with defaults
         let decision_pipe = config
             .pipes
@@ -172,17 +495,19 @@ impl MCTSMode {
             .unwrap_or_else(|| "decision-framework-v1".to_string());
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend),
             tree_pipe: config.pipes.tree.clone(),
             decision_pipe,
             divergent_pipe: config.pipes.divergent.clone(),
             reflection_pipe: config.pipes.reflection.clone(),
+            mcts: config.mcts.clone(),
         }
     }
 
     /// Perform MCTS exploration
     pub async fn explore(&self, params: MCTSExploreParams) -> AppResult<MCTSExploreResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate input
         if params.content.trim().is_empty() {
@@ -194,6 +519,7 @@ impl MCTSMode {
         }
 
         let iterations = params.iterations.clamp(1, 20);
+        let settings = SearchSettings::resolve(&params, &self.mcts);
 
         // Get or create session
         let session = self
@@ -201,7 +527,12 @@ impl MCTSMode {
             .storage()
             .get_or_create_session(&params.session_id, "mcts")
             .await?;
-        debug!(session_id = %session.id, iterations = iterations, "Starting MCTS exploration");
+        debug!(
+            session_id = %session.id,
+            iterations = iterations,
+            policy = %settings.policy,
+            "Starting MCTS exploration"
+        );
 
         // Create or get a branch for MCTS
         let branches = self.core.storage().get_session_branches(&session.id).await?;
@@ -220,47 +551,92 @@ impl MCTSMode {
             root_node = root_node.with_timeline(timeline_id);
         }
         self.core.storage().create_mcts_node(&root_node).await?;
+        let root_node_id = root_node.id.clone();
 
+        // The tree of this search, root first
+        let mut nodes = vec![root_node];
         let mut iteration_stats = Vec::new();
-        let mut nodes_explored = 1;
+        let mut total_pipe_calls = 0;
 
         // Run MCTS iterations
         for i in 0..iterations {
-            // SELECTION: Find best node to expand using UCB
-            let selected = self.select_node(&session.id, params.exploration_constant).await?;
+            let iteration_start = Instant::now();
+
+            // SELECTION: Descend to the node to expand
+            let selected = select_node(&nodes, &settings);
+            let count = expansion_count(&nodes, selected, &settings);
 
             // EXPANSION: Generate child nodes
             let children = self
-                .expand_node(&selected, &session.id, &branch_id, params.simulation_depth)
+                .expand_node(&nodes, selected, count, &branch_id, params.generation)
                 .await?;
-            nodes_explored += children.len();
-
-            // SIMULATION: Evaluate the expansion
-            let simulation_value = self.simulate(&selected, &children).await?;
+            nodes[selected].is_expanded = true;
+
+            // SIMULATION: Evaluate the expansion, after a rollout if configured
+            let (simulation_value, rollout_calls) = self
+                .simulate(
+                    &nodes[selected],
+                    &children,
+                    settings.rollout_depth,
+                    params.generation,
+                )
+                .await?;
+            let children_created = children.len();
+            nodes.extend(children);
 
             // BACKPROPAGATION: Update values along the path
             let backprop_nodes = self
-                .backpropagate(&selected.id, simulation_value)
+                .backpropagate(&mut nodes, selected, simulation_value, &settings)
                 .await?;
 
-            iteration_stats.push(IterationStats {
+            let pipe_calls = 2 + rollout_calls;
+            total_pipe_calls += pipe_calls;
+            let stats = IterationStats {
                 iteration: i + 1,
-                selected_node: selected.id.clone(),
+                selected_node: nodes[selected].id.clone(),
+                selected_depth: nodes[selected].simulation_depth.max(0) as usize,
+                children_created,
                 simulation_value,
                 backprop_nodes,
-            });
+                best_value: best_mean_value(&nodes),
+                nodes_total: nodes.len(),
+                pipe_calls,
+            };
+
+            let record = MctsIteration {
+                policy: settings.policy,
+                exploration_constant: settings.exploration_constant,
+                rollout_depth: settings.rollout_depth as i32,
+                expansion_width: settings.expansion_width as i32,
+                selected_depth: stats.selected_depth as i32,
+                children_created: children_created as i32,
+                simulation_value,
+                best_value: stats.best_value,
+                nodes_total: nodes.len() as i32,
+                pipe_calls: pipe_calls as i32,
+                latency_ms: iteration_start.elapsed().as_millis() as i64,
+                ..MctsIteration::new(
+                    &session.id,
+                    &root_node_id,
+                    (i + 1) as i32,
+                    &stats.selected_node,
+                )
+            };
+            self.core.storage().create_mcts_iteration(&record).await?;
 
             debug!(
                 iteration = i + 1,
-                selected_node = %selected.id,
+                selected_node = %stats.selected_node,
                 simulation_value = simulation_value,
-                children = children.len(),
+                children = children_created,
                 "MCTS iteration complete"
             );
+            iteration_stats.push(stats);
         }
 
         // Find best path
-        let (best_path, best_path_content, best_value) = self.find_best_path(&session.id).await?;
+        let (best_path, best_path_content, best_value) = find_best_path(&nodes);
+        let nodes_explored = nodes.len();
 
         // Log invocation
         let latency = start.elapsed().as_millis() as i64;
@@ -271,7 +647,9 @@ impl MCTSMode {
         .with_session(&session.id)
         .success(serde_json::json!({
             "nodes_explored": nodes_explored,
-            "best_value": best_value
+            "best_value": best_value,
+            "policy": settings.policy,
+            "pipe_calls": total_pipe_calls
         }), latency);
         self.core.storage().log_invocation(&invocation).await?;
 
@@ -285,73 +663,81 @@ impl MCTSMode {
 
         Ok(MCTSExploreResult {
             session_id: session.id,
-            root_node_id: root_node.id,
+            root_node_id,
             best_path,
             best_path_content,
             best_path_value: best_value,
             nodes_explored,
+            settings,
+            pipe_calls: total_pipe_calls,
             iteration_stats,
         })
     }
 
-    /// Select the best node to expand using UCB1
-    async fn select_node(
+    /// Expand a node by generating up to `count` children
+    async fn expand_node(
         &self,
-        session_id: &str,
-        _exploration_constant: f64,
-    ) -> AppResult<MCTSNode> {
-        // Get unexpanded nodes first
-        let unexpanded = self
-            .core
-            .storage()
-            .get_unexpanded_mcts_nodes(session_id)
-            .await?;
+        nodes: &[MCTSNode],
+        index: usize,
+        count: usize,
+        branch_id: &str,
+        generation: GenerationParams,
+    ) -> AppResult<Vec<MCTSNode>> {
+        let node = &nodes[index];
 
-        if !unexpanded.is_empty() {
-            // Return first unexpanded node (highest UCB from unexpanded)
-            return Ok(unexpanded.into_iter().next().unwrap());
+        // Use tree pipe to generate alternatives, distinct from the children
+        // a widened node already has
+        let mut expand_prompt = format!(
+            "Generate {} alternative continuations for this reasoning:\n\n{}",
+            count, node.content
+        );
+        let existing = child_indices(nodes, &node.id);
+        if !existing.is_empty() {
+            expand_prompt.push_str("\n\nDo not repeat these existing continuations:");
+            for child in existing {
+                expand_prompt.push_str(&format!("\n- {}", nodes[child].content));
+            }
         }
 
-        // All nodes expanded, select by UCB score
-        let nodes = self
-            .core
-            .storage()
-            .get_mcts_nodes_by_ucb(session_id)
-            .await?;
+        let tree_response = self.call_tree_pipe(expand_prompt, generation).await?;
 
-        nodes.into_iter().next().ok_or_else(|| {
-            ToolError::Reasoning {
-                message: "No nodes available for selection".to_string(),
-            }
-            .into()
-        })
+        // Create child nodes
+        let mut children = Vec::new();
+        for branch in tree_response.branches.into_iter().take(count) {
+            let child = MCTSNode::new(&node.session_id, branch_id, &branch.thought)
+                .with_parent(&node.id)
+                .with_prior(branch.confidence)
+                .with_simulation_depth(node.simulation_depth + 1);
+            self.core.storage().create_mcts_node(&child).await?;
+            children.push(child);
+        }
+
+        // Mark parent as expanded
+        let mut expanded_node = node.clone();
+        expanded_node.mark_expanded();
+        self.core.storage().update_mcts_node(&expanded_node).await?;
+
+        Ok(children)
     }
 
-    /// Expand a node by generating children
-    async fn expand_node(
+    /// Ask the tree pipe for continuations of some reasoning
+    async fn call_tree_pipe(
         &self,
-        node: &MCTSNode,
-        session_id: &str,
-        branch_id: &str,
-        _simulation_depth: i32,
-    ) -> AppResult<Vec<MCTSNode>> {
-        // Use tree pipe to generate alternatives
-        let expand_prompt = format!(
-            "Generate 2-3 alternative continuations for this reasoning:\n\n{}",
-            node.content
-        );
-
+        prompt: String,
+        generation: GenerationParams,
+    ) -> AppResult<ExpandResponse> {
         let messages = vec![
             Message::system(TREE_REASONING_PROMPT),
-            Message::user(expand_prompt),
+            Message::user(prompt),
         ];
-        let request = PipeRequest::new(&self.tree_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let tree_pipe = self.core.select_pipe(&self.tree_pipe);
+        let request = PipeRequest::new(&tree_pipe, messages).with_generation(generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let json_str = extract_json_from_completion(&response.completion)
             .map_err(|e| ToolError::Reasoning { message: e })?;
-        let tree_response: ExpandResponse = serde_json::from_str(json_str).unwrap_or_else(|e| {
+        Ok(serde_json::from_str(json_str).unwrap_or_else(|e| {
             warn!(error = %e, "Failed to parse expansion, using fallback");
             ExpandResponse {
                 branches: vec![ExpandBranch {
@@ -359,38 +745,59 @@ impl MCTSMode {
                     confidence: 0.5,
                 }],
             }
-        });
-
-        // Create child nodes
-        let mut children = Vec::new();
-        for branch in tree_response.branches {
-            let child = MCTSNode::new(session_id, branch_id, &branch.thought)
-                .with_parent(&node.id)
-                .with_prior(branch.confidence)
-                .with_simulation_depth(node.simulation_depth + 1);
-            self.core.storage().create_mcts_node(&child).await?;
-            children.push(child);
-        }
-
-        // Mark parent as expanded
-        let mut expanded_node = node.clone();
-        expanded_node.is_expanded = true;
-        self.core.storage().update_mcts_node(&expanded_node).await?;
-
-        Ok(children)
+        }))
     }
 
-    /// Simulate to evaluate a node
-    async fn simulate(&self, node: &MCTSNode, children: &[MCTSNode]) -> AppResult<f64> {
-        // Use decision pipe to evaluate quality
-        let content_to_evaluate = if children.is_empty() {
-            node.content.clone()
+    /// Simulate to evaluate a node, returning the value and the pipe calls
+    /// the rollout made.
+    ///
+    /// Without a rollout the new children are scored directly. With one, the
+    /// most promising child is continued `rollout_depth` steps and the whole
+    /// trajectory is scored.
+    async fn simulate(
+        &self,
+        node: &MCTSNode,
+        children: &[MCTSNode],
+        rollout_depth: usize,
+        generation: GenerationParams,
+    ) -> AppResult<(f64, usize)> {
+        let mut rollout_calls = 0;
+        let content_to_evaluate = if rollout_depth == 0 {
+            if children.is_empty() {
+                node.content.clone()
+            } else {
+                children
+                    .iter()
+                    .map(|c| c.content.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n---\n")
+            }
         } else {
-            children
-                .iter()
-                .map(|c| c.content.as_str())
-                .collect::<Vec<_>>()
-                .join("\n---\n")
+            let mut trajectory = vec![node.content.clone()];
+            if let Some(child) = children.iter().max_by(|a, b| {
+                a.prior
+                    .partial_cmp(&b.prior)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }) {
+                trajectory.push(child.content.clone());
+            }
+            for _ in 0..rollout_depth {
+                let prompt = format!(
+                    "Continue this reasoning with its single most promising next step:\n\n{}",
+                    trajectory.join("\n\n")
+                );
+                let response = self.call_tree_pipe(prompt, generation).await?;
+                rollout_calls += 1;
+                match response.branches.into_iter().max_by(|a, b| {
+                    a.confidence
+                        .partial_cmp(&b.confidence)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                }) {
+                    Some(step) => trajectory.push(step.thought),
+                    None => break,
+                }
+            }
+            trajectory.join("\n\n")
         };
 
         let eval_prompt = format!(
@@ -402,8 +809,9 @@ impl MCTSMode {
             Message::system("Respond with JSON: {\"score\": 0.0-1.0, \"rationale\": \"...\"}"),
             Message::user(eval_prompt),
         ];
-        let request = PipeRequest::new(&self.decision_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let decision_pipe = self.core.select_pipe(&self.decision_pipe);
+        let request = PipeRequest::new(&decision_pipe, messages).with_generation(generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse score
         let json_str = extract_json_from_completion(&response.completion)
@@ -413,106 +821,47 @@ impl MCTSMode {
             rationale: "Evaluation parsing failed, using neutral score".to_string(),
         });
 
-        Ok(eval.score.clamp(0.0, 1.0))
+        Ok((eval.score.clamp(0.0, 1.0), rollout_calls))
     }
 
-    /// Backpropagate value through the tree
-    async fn backpropagate(&self, node_id: &str, value: f64) -> AppResult<usize> {
-        let mut current_id = Some(node_id.to_string());
-        let mut nodes_updated = 0;
-
-        while let Some(id) = current_id {
-            if let Some(mut node) = self.core.storage().get_mcts_node(&id).await? {
-                // Update visit count and value
-                node.visit_count += 1;
-                node.total_value += value;
-
-                // Recalculate UCB score
-                let parent_visits = if let Some(ref parent_id) = node.parent_node_id {
-                    self.core
-                        .storage()
-                        .get_mcts_node(parent_id)
-                        .await?
-                        .map(|p| p.visit_count)
-                        .unwrap_or(1)
-                } else {
-                    1
-                };
-
-                node.ucb_score = node.calculate_ucb(parent_visits, DEFAULT_EXPLORATION_CONSTANT);
-                node.update_last_visited();
-
-                self.core.storage().update_mcts_node(&node).await?;
-                nodes_updated += 1;
-
-                current_id = node.parent_node_id;
-            } else {
-                break;
-            }
-        }
-
-        Ok(nodes_updated)
-    }
-
-    /// Find the best path through the tree
-    async fn find_best_path(
+    /// Backpropagate value from a node to the root
+    async fn backpropagate(
         &self,
-        session_id: &str,
-    ) -> AppResult<(Vec<String>, Vec<String>, f64)> {
-        let nodes = self
-            .core
-            .storage()
-            .get_session_mcts_nodes(session_id)
-            .await?;
-
-        // Find root
-        let root = nodes.iter().find(|n| n.parent_node_id.is_none());
-
-        if let Some(root) = root {
-            let mut path_ids = vec![root.id.clone()];
-            let mut path_content = vec![root.content.clone()];
-            let mut current = root;
-            let mut total_value = root.total_value;
-
-            // Follow best children
-            loop {
-                let children: Vec<_> = nodes
-                    .iter()
-                    .filter(|n| n.parent_node_id.as_ref() == Some(&current.id))
-                    .collect();
-
-                if children.is_empty() {
-                    break;
-                }
+        nodes: &mut [MCTSNode],
+        index: usize,
+        value: f64,
+        settings: &SearchSettings,
+    ) -> AppResult<usize> {
+        // Path from the node up to the root
+        let mut path = vec![index];
+        let mut current = index;
+        while let Some(parent) = nodes[current]
+            .parent_node_id
+            .as_ref()
+            .and_then(|parent_id| nodes.iter().position(|n| &n.id == parent_id))
+        {
+            path.push(parent);
+            current = parent;
+        }
 
-                // Select child with highest average value
-                let best_child = children
-                    .into_iter()
-                    .max_by(|a, b| {
-                        let avg_a = if a.visit_count > 0 {
-                            a.total_value / a.visit_count as f64
-                        } else {
-                            0.0
-                        };
-                        let avg_b = if b.visit_count > 0 {
-                            b.total_value / b.visit_count as f64
-                        } else {
-                            0.0
-                        };
-                        avg_a.partial_cmp(&avg_b).unwrap_or(std::cmp::Ordering::Equal)
-                    })
-                    .unwrap();
-
-                path_ids.push(best_child.id.clone());
-                path_content.push(best_child.content.clone());
-                total_value += best_child.total_value;
-                current = best_child;
-            }
+        for &i in &path {
+            nodes[i].record_visit(value);
+        }
 
-            Ok((path_ids, path_content, total_value))
-        } else {
-            Ok((Vec::new(), Vec::new(), 0.0))
+        // Recalculate UCB scores once every visit count on the path is final
+        for &i in &path {
+            let parent_visits = nodes[i]
+                .parent_node_id
+                .as_ref()
+                .and_then(|parent_id| nodes.iter().find(|n| &n.id == parent_id))
+                .map(|p| p.visit_count)
+                .unwrap_or(1);
+            let ucb = nodes[i].calculate_ucb(parent_visits, settings.exploration_constant);
+            nodes[i].ucb_score = ucb;
+            self.core.storage().update_mcts_node(&nodes[i]).await?;
         }
+
+        Ok(path.len())
     }
 
     /// Auto-backtracking based on reward signals
@@ -729,8 +1078,11 @@ mod tests {
         let params: MCTSExploreParams = serde_json::from_value(json).unwrap();
         assert_eq!(params.content, "Content");
         assert_eq!(params.iterations, 5); // default
-        assert!((params.exploration_constant - std::f64::consts::SQRT_2).abs() < 0.001);
-        assert_eq!(params.simulation_depth, 3); // default
+        // Left unset, so the configured defaults apply
+        assert!(params.policy.is_none());
+        assert!(params.exploration_constant.is_none());
+        assert!(params.rollout_depth.is_none());
+        assert!(params.expansion_width.is_none());
         assert!(params.session_id.is_none());
         assert!(params.timeline_id.is_none());
     }
@@ -742,12 +1094,17 @@ mod tests {
             "iterations": 15,
             "exploration_constant": 2.5,
             "simulation_depth": 5,
+            "expansion_width": 2,
+            "policy": "progressive_widening",
             "timeline_id": "tl-123"
         });
         let params: MCTSExploreParams = serde_json::from_value(json).unwrap();
         assert_eq!(params.iterations, 15);
-        assert_eq!(params.exploration_constant, 2.5);
-        assert_eq!(params.simulation_depth, 5);
+        assert_eq!(params.exploration_constant, Some(2.5));
+        // simulation_depth is the older name of rollout_depth
+        assert_eq!(params.rollout_depth, Some(5));
+        assert_eq!(params.expansion_width, Some(2));
+        assert_eq!(params.policy, Some(MctsPolicy::ProgressiveWidening));
         assert_eq!(params.timeline_id, Some("tl-123".to_string()));
     }
 
@@ -758,13 +1115,18 @@ mod tests {
             session_id: None,
             timeline_id: Some("tl-1".to_string()),
             iterations: 10,
-            exploration_constant: 1.5,
-            simulation_depth: 4,
+            policy: None,
+            exploration_constant: Some(1.5),
+            rollout_depth: Some(4),
+            expansion_width: None,
+            generation: GenerationParams::default(),
         };
         let json = serde_json::to_value(&params).unwrap();
         assert_eq!(json["content"], "Test");
         assert_eq!(json["iterations"], 10);
+        assert_eq!(json["rollout_depth"], 4);
         assert!(json.get("session_id").is_none()); // skip_serializing_if
+        assert!(json.get("policy").is_none());
     }
 
     #[test]
@@ -772,16 +1134,6 @@ mod tests {
         assert_eq!(default_iterations(), 5);
     }
 
-    #[test]
-    fn test_default_exploration() {
-        let expected = DEFAULT_EXPLORATION_CONSTANT;
-        assert!((default_exploration() - expected).abs() < 0.0001);
-    }
-
-    #[test]
-    fn test_default_sim_depth() {
-        assert_eq!(default_sim_depth(), DEFAULT_SIMULATION_DEPTH);
-    }
 
     // ============================================================================
     // AutoBacktrackParams Tests
@@ -836,12 +1188,19 @@ mod tests {
             best_path_content: vec!["Content 1".to_string(), "Content 2".to_string()],
             best_path_value: 0.85,
             nodes_explored: 15,
+            settings: SearchSettings::default(),
+            pipe_calls: 2,
             iteration_stats: vec![
                 IterationStats {
                     iteration: 1,
                     selected_node: "node-1".to_string(),
+                    selected_depth: 0,
+                    children_created: 0,
                     simulation_value: 0.7,
                     backprop_nodes: 2,
+                    best_value: 0.0,
+                    nodes_total: 0,
+                    pipe_calls: 0,
                 },
             ],
         };
@@ -868,6 +1227,9 @@ mod tests {
         assert_eq!(result.session_id, "sess");
         assert_eq!(result.best_path_value, 0.9);
         assert!(result.iteration_stats.is_empty());
+        // Results stored before settings were reported deserialize with defaults
+        assert_eq!(result.settings, SearchSettings::default());
+        assert_eq!(result.pipe_calls, 0);
     }
 
     // ============================================================================
@@ -879,14 +1241,24 @@ mod tests {
         let stats = IterationStats {
             iteration: 5,
             selected_node: "node-123".to_string(),
+            selected_depth: 2,
+            children_created: 3,
             simulation_value: 0.75,
             backprop_nodes: 3,
+            best_value: 0.8,
+            nodes_total: 12,
+            pipe_calls: 2,
         };
         let json = serde_json::to_value(&stats).unwrap();
         assert_eq!(json["iteration"], 5);
         assert_eq!(json["selected_node"], "node-123");
+        assert_eq!(json["selected_depth"], 2);
+        assert_eq!(json["children_created"], 3);
         assert_eq!(json["simulation_value"], 0.75);
         assert_eq!(json["backprop_nodes"], 3);
+        assert_eq!(json["best_value"], 0.8);
+        assert_eq!(json["nodes_total"], 12);
+        assert_eq!(json["pipe_calls"], 2);
     }
 
     #[test]
@@ -894,8 +1266,13 @@ mod tests {
         let stats = IterationStats {
             iteration: 1,
             selected_node: "n".to_string(),
+            selected_depth: 0,
+            children_created: 0,
             simulation_value: 0.5,
             backprop_nodes: 1,
+            best_value: 0.0,
+            nodes_total: 0,
+            pipe_calls: 0,
         };
         let cloned = stats.clone();
         assert_eq!(stats.iteration, cloned.iteration);
@@ -1023,14 +1400,19 @@ mod tests {
             session_id: Some("sess-123".to_string()),
             timeline_id: Some("tl-456".to_string()),
             iterations: 8,
-            exploration_constant: 1.8,
-            simulation_depth: 4,
+            policy: Some(MctsPolicy::Ucb1),
+            exploration_constant: Some(1.8),
+            rollout_depth: Some(4),
+            expansion_width: Some(2),
+            generation: GenerationParams::default(),
         };
         let json = serde_json::to_string(&original).unwrap();
         let deserialized: MCTSExploreParams = serde_json::from_str(&json).unwrap();
         assert_eq!(original.content, deserialized.content);
         assert_eq!(original.iterations, deserialized.iterations);
         assert_eq!(original.session_id, deserialized.session_id);
+        assert_eq!(original.policy, deserialized.policy);
+        assert_eq!(original.expansion_width, deserialized.expansion_width);
     }
 
     #[test]
@@ -1056,20 +1438,6 @@ mod tests {
         assert_eq!(original.backtrack_to, deserialized.backtrack_to);
     }
 
-    // ============================================================================
-    // Constants Tests
-    // ============================================================================
-
-    #[test]
-    fn test_default_exploration_constant_value() {
-        assert!((DEFAULT_EXPLORATION_CONSTANT - std::f64::consts::SQRT_2).abs() < 0.0001);
-    }
-
-    #[test]
-    fn test_default_simulation_depth_value() {
-        assert_eq!(DEFAULT_SIMULATION_DEPTH, 3);
-    }
-
     // ============================================================================
     // Unicode and Edge Cases Tests
     // ============================================================================
@@ -1101,8 +1469,13 @@ mod tests {
         let stats = IterationStats {
             iteration: 0,
             selected_node: "".to_string(),
+            selected_depth: 0,
+            children_created: 0,
             simulation_value: 0.0,
             backprop_nodes: 0,
+            best_value: 0.0,
+            nodes_total: 0,
+            pipe_calls: 0,
         };
         let json = serde_json::to_value(&stats).unwrap();
         assert_eq!(json["iteration"], 0);
@@ -1118,9 +1491,260 @@ mod tests {
             best_path_content: vec![],
             best_path_value: 0.0,
             nodes_explored: 0,
+            settings: SearchSettings::default(),
+            pipe_calls: 0,
             iteration_stats: vec![],
         };
         let json = serde_json::to_value(&result).unwrap();
         assert!(json["best_path"].as_array().unwrap().is_empty());
     }
+
+    // ============================================================================
+    // Search Settings Tests
+    // ============================================================================
+
+    fn explore_params(json: serde_json::Value) -> MCTSExploreParams {
+        let mut json = json;
+        json["content"] = json!("Problem");
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_search_settings_use_config_defaults() {
+        let config = MctsConfig {
+            policy: MctsPolicy::ProgressiveWidening,
+            exploration_constant: 0.7,
+            rollout_depth: 2,
+            expansion_width: 4,
+            widening_coefficient: 2.0,
+            widening_exponent: 0.25,
+        };
+        let settings = SearchSettings::resolve(&explore_params(json!({})), &config);
+        assert_eq!(settings.policy, MctsPolicy::ProgressiveWidening);
+        assert_eq!(settings.exploration_constant, 0.7);
+        assert_eq!(settings.rollout_depth, 2);
+        assert_eq!(settings.expansion_width, 4);
+        assert_eq!(settings.widening_coefficient, 2.0);
+        assert_eq!(settings.widening_exponent, 0.25);
+
+        let defaults = SearchSettings::default();
+        assert_eq!(defaults.policy, MctsPolicy::Ucb1);
+        assert!((defaults.exploration_constant - std::f64::consts::SQRT_2).abs() < 1e-9);
+        assert_eq!(defaults.rollout_depth, 0);
+        assert_eq!(defaults.expansion_width, 3);
+    }
+
+    #[test]
+    fn test_search_settings_params_override_and_clamp() {
+        let params = explore_params(json!({
+            "policy": "ucb1",
+            "exploration_constant": 25.0,
+            "rollout_depth": 9,
+            "expansion_width": 0
+        }));
+        let config = MctsConfig {
+            policy: MctsPolicy::ProgressiveWidening,
+            ..MctsConfig::default()
+        };
+        let settings = SearchSettings::resolve(&params, &config);
+        assert_eq!(settings.policy, MctsPolicy::Ucb1);
+        assert_eq!(settings.exploration_constant, MAX_EXPLORATION_CONSTANT);
+        assert_eq!(settings.rollout_depth, MAX_ROLLOUT_DEPTH);
+        assert_eq!(settings.expansion_width, 1);
+
+        let params = explore_params(json!({"exploration_constant": -1.0, "expansion_width": 8}));
+        let settings = SearchSettings::resolve(&params, &MctsConfig::default());
+        assert_eq!(settings.exploration_constant, 0.0);
+        assert_eq!(settings.expansion_width, MAX_EXPANSION_WIDTH);
+    }
+
+    #[test]
+    fn test_widening_limit_grows_with_visits() {
+        let settings = SearchSettings::default();
+        // k = 1, alpha = 0.5: ceil(sqrt(N))
+        assert_eq!(settings.widening_limit(0), 1);
+        assert_eq!(settings.widening_limit(1), 1);
+        assert_eq!(settings.widening_limit(2), 2);
+        assert_eq!(settings.widening_limit(4), 2);
+        assert_eq!(settings.widening_limit(9), 3);
+        assert_eq!(settings.widening_limit(10), 4);
+    }
+
+    // ============================================================================
+    // Selection Tests
+    // ============================================================================
+
+    fn tree_node(id: &str, parent: Option<&str>, visits: i32, value: f64, prior: f64) -> MCTSNode {
+        let mut node = MCTSNode::new("s", "b", id).with_prior(prior);
+        node.id = id.to_string();
+        node.parent_node_id = parent.map(str::to_string);
+        node.visit_count = visits;
+        node.total_value = value;
+        node
+    }
+
+    #[test]
+    fn test_select_node_starts_at_unexpanded_root() {
+        let nodes = vec![tree_node("root", None, 0, 0.0, 0.5)];
+        assert_eq!(select_node(&nodes, &SearchSettings::default()), 0);
+    }
+
+    #[test]
+    fn test_select_node_ucb1_prefers_unvisited_then_prior() {
+        let mut nodes = vec![
+            tree_node("root", None, 2, 1.2, 0.5),
+            tree_node("a", Some("root"), 2, 1.9, 0.9),
+            tree_node("b", Some("root"), 0, 0.0, 0.4),
+            tree_node("c", Some("root"), 0, 0.0, 0.7),
+        ];
+        nodes[0].is_expanded = true;
+        let settings = SearchSettings::default();
+        // Unvisited children have infinite UCB; the higher prior breaks the tie
+        assert_eq!(select_node(&nodes, &settings), 3);
+
+        // Once every child is visited, the exploitation term decides when
+        // exploration is switched off
+        nodes[2].visit_count = 1;
+        nodes[2].total_value = 0.9;
+        nodes[3].visit_count = 1;
+        nodes[3].total_value = 0.2;
+        let greedy = SearchSettings {
+            exploration_constant: 0.0,
+            ..settings
+        };
+        assert_eq!(select_node(&nodes, &greedy), 1);
+        // A large constant favours a less visited child instead
+        let exploring = SearchSettings {
+            exploration_constant: 10.0,
+            ..settings
+        };
+        assert_eq!(select_node(&nodes, &exploring), 2);
+    }
+
+    #[test]
+    fn test_select_node_descends_to_unexpanded_leaf() {
+        let mut nodes = vec![
+            tree_node("root", None, 3, 2.0, 0.5),
+            tree_node("a", Some("root"), 2, 1.6, 0.5),
+            tree_node("a1", Some("a"), 0, 0.0, 0.5),
+        ];
+        nodes[0].is_expanded = true;
+        nodes[1].is_expanded = true;
+        assert_eq!(select_node(&nodes, &SearchSettings::default()), 2);
+    }
+
+    #[test]
+    fn test_select_node_progressive_widening_widens_visited_nodes() {
+        let mut nodes = vec![
+            tree_node("root", None, 1, 0.6, 0.5),
+            tree_node("a", Some("root"), 0, 0.0, 0.5),
+        ];
+        nodes[0].is_expanded = true;
+        let settings = SearchSettings {
+            policy: MctsPolicy::ProgressiveWidening,
+            ..SearchSettings::default()
+        };
+        // One visit allows one child, so descend into it
+        assert_eq!(select_node(&nodes, &settings), 1);
+        assert_eq!(expansion_count(&nodes, 1, &settings), 1);
+
+        // Four visits allow two children: widen the root by one
+        nodes[0].visit_count = 4;
+        assert_eq!(select_node(&nodes, &settings), 0);
+        assert_eq!(expansion_count(&nodes, 0, &settings), 1);
+
+        // UCB1 ignores the visit count and expands to the full width
+        let ucb1 = SearchSettings {
+            policy: MctsPolicy::Ucb1,
+            ..settings
+        };
+        assert_eq!(select_node(&nodes, &ucb1), 1);
+        assert_eq!(expansion_count(&nodes, 1, &ucb1), 3);
+    }
+
+    #[test]
+    fn test_find_best_path_follows_mean_value() {
+        let nodes = vec![
+            tree_node("root", None, 3, 2.0, 0.5),
+            tree_node("a", Some("root"), 2, 0.8, 0.5),
+            tree_node("b", Some("root"), 1, 0.9, 0.5),
+            tree_node("b1", Some("b"), 0, 0.0, 0.5),
+        ];
+        let (ids, content, value) = find_best_path(&nodes);
+        assert_eq!(ids, vec!["root", "b", "b1"]);
+        assert_eq!(content, vec!["root", "b", "b1"]);
+        assert!((value - 2.9).abs() < 1e-9);
+        assert!((best_mean_value(&nodes) - 0.9).abs() < 1e-9);
+        assert_eq!(find_best_path(&[]).0.len(), 0);
+    }
+
+    // ============================================================================
+    // Budget Summary Tests
+    // ============================================================================
+
+    fn stored_iteration(
+        root: &str,
+        policy: MctsPolicy,
+        iteration: i32,
+        best_value: f64,
+        nodes_total: i32,
+    ) -> MctsIteration {
+        MctsIteration {
+            policy,
+            best_value,
+            nodes_total,
+            pipe_calls: 2,
+            latency_ms: 10,
+            ..MctsIteration::new("s", root, iteration, root)
+        }
+    }
+
+    #[test]
+    fn test_summarize_mcts_searches() {
+        let iterations = vec![
+            stored_iteration("r1", MctsPolicy::Ucb1, 1, 0.5, 4),
+            stored_iteration("r1", MctsPolicy::Ucb1, 2, 0.7, 7),
+            stored_iteration("r2", MctsPolicy::ProgressiveWidening, 1, 0.6, 2),
+        ];
+        let summaries = summarize_mcts_searches(&iterations);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].root_node_id, "r1");
+        assert_eq!(summaries[0].iterations, 2);
+        assert_eq!(summaries[0].nodes, 7);
+        assert_eq!(summaries[0].pipe_calls, 4);
+        assert_eq!(summaries[0].best_value, 0.7);
+        assert_eq!(summaries[0].latency_ms, 20);
+        assert_eq!(summaries[1].policy, MctsPolicy::ProgressiveWidening);
+        assert_eq!(summaries[1].iterations, 1);
+    }
+
+    #[test]
+    fn test_mcts_budget_curve_averages_per_policy_and_iteration() {
+        let iterations = vec![
+            stored_iteration("r1", MctsPolicy::Ucb1, 1, 0.5, 4),
+            stored_iteration("r1", MctsPolicy::Ucb1, 2, 0.7, 7),
+            stored_iteration("r2", MctsPolicy::Ucb1, 1, 0.3, 4),
+            stored_iteration("r3", MctsPolicy::ProgressiveWidening, 1, 0.6, 2),
+        ];
+        let curve = mcts_budget_curve(&iterations);
+        assert_eq!(curve.len(), 3);
+
+        let first = &curve[0];
+        assert_eq!(first.policy, MctsPolicy::ProgressiveWidening);
+        assert_eq!(first.searches, 1);
+
+        let ucb1: Vec<&MctsBudgetPoint> = curve
+            .iter()
+            .filter(|p| p.policy == MctsPolicy::Ucb1)
+            .collect();
+        assert_eq!(ucb1[0].iteration, 1);
+        assert_eq!(ucb1[0].searches, 2);
+        assert!((ucb1[0].mean_best_value - 0.4).abs() < 1e-9);
+        assert_eq!(ucb1[0].mean_pipe_calls, 2.0);
+        // Pipe calls accumulate over a search's iterations
+        assert_eq!(ucb1[1].iteration, 2);
+        assert_eq!(ucb1[1].searches, 1);
+        assert_eq!(ucb1[1].mean_pipe_calls, 4.0);
+        assert_eq!(ucb1[1].mean_nodes, 7.0);
+    }
 }
@@ -14,42 +14,98 @@
 //! - `TimelineMode`: Timeline-based temporal reasoning exploration
 //! - `MCTSMode`: Monte Carlo Tree Search for reasoning exploration
 //! - `CounterfactualMode`: "What if?" analysis and causal reasoning
+//! - `DebateMode`: Adversarial pro/con debate judged for a verdict
+//! - `RcaMode`: Root cause analysis with 5 Whys or fishbone diagrams
+//! - `PlanningMode`: Goal decomposition into a task dependency graph
+//! - `EstimateMode`: Fermi estimation with locally computed bounds
+//! - `SwotMode`: SWOT analysis linked to stakeholder perspectives
+//! - `HypothesisMode`: Pre-registered experiments updating a hypothesis' probability
+//! - `SixHatsMode`: Six Thinking Hats passes from fixed cognitive stances
+//! - `DialecticMode`: Thesis-antithesis-synthesis rounds until the position settles
+//! - `PrioritizationMode`: RICE, MoSCoW and weighted rankings with local score math
+//! - `NegotiationMode`: Parties, BATNAs, ZOPA and package deals with concession paths
+//! - `RiskMode`: Per-session risk register with a probability-impact matrix
+//! - `SystemsMode`: Stocks, flows and causal links with feedback loop detection
+//! - `CalcMode`: Numeric claims recomputed by a local expression evaluator
+//! - `CodeReasoningMode`: Diff explanation, data flow tracing and edge cases with stored findings
+//! - `SynthesisMode`: Briefings consolidating the artifacts of several sessions
+//!
+//! The `health` module probes the pipes these modes call, and the `artifacts`
+//! module lets modes report partial results while a call is still running.
 //!
 //! All modes share common infrastructure via `ModeCore` composition.
 
+mod artifacts;
 mod auto;
 mod backtracking;
+mod calc;
+mod code;
 mod core;
 mod counterfactual;
+mod debate;
 mod decision;
+mod dialectic;
 mod detection;
 mod divergent;
+mod estimate;
 mod evidence;
 mod got;
+mod health;
+mod hypothesis;
 mod linear;
 mod mcts;
+mod negotiation;
+mod planning;
+mod prioritization;
+mod rca;
 mod reflection;
+mod risk;
+mod six_hats;
+mod swot;
+mod synthesis;
+mod systems;
 mod timeline;
 mod tree;
 
+pub use artifacts::*;
 pub use auto::*;
 pub use backtracking::*;
+pub use calc::*;
+pub use code::*;
 pub use core::*;
 pub use counterfactual::*;
+pub use debate::*;
 pub use decision::*;
+pub use dialectic::*;
 pub use detection::*;
 pub use divergent::*;
+pub use estimate::*;
 pub use evidence::*;
 pub use got::*;
+pub use health::*;
+pub use hypothesis::*;
 pub use linear::*;
 pub use mcts::*;
+pub use negotiation::*;
+pub use planning::*;
+pub use prioritization::*;
+pub use rca::*;
 pub use reflection::*;
+pub use risk::*;
+pub use six_hats::*;
+pub use swot::*;
+pub use synthesis::*;
+pub use systems::*;
 pub use timeline::*;
 pub use tree::*;
 
+pub(crate) use crate::langbase::extract_json_from_completion;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+use crate::storage::cosine_similarity;
+
 // ============================================================================
 // Shared Utilities
 // ============================================================================
@@ -76,51 +132,72 @@ pub(crate) fn serialize_for_log<T: serde::Serialize>(
     })
 }
 
-/// Extract JSON from a completion string, handling markdown code blocks.
-///
-/// Attempts extraction in this order:
-/// 1. Try parsing as raw JSON first (fast path)
-/// 2. Extract from ```json ... ``` code blocks
-/// 3. Extract from ``` ... ``` code blocks
-/// 4. Return error if none work
-///
-/// This helper is used by modes that parse structured responses from Langbase.
-pub(crate) fn extract_json_from_completion(completion: &str) -> Result<&str, String> {
-    // Fast path: raw JSON
-    let trimmed = completion.trim();
-    if trimmed.starts_with('{') || trimmed.starts_with('[') {
-        return Ok(trimmed);
-    }
-
-    // Try ```json ... ``` blocks
-    if completion.contains("```json") {
-        return completion
-            .split("```json")
-            .nth(1)
-            .and_then(|s| s.split("```").next())
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .ok_or_else(|| "Found ```json block but content was empty or malformed".to_string());
-    }
+/// What to do with a generated item that may paraphrase another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DuplicateFate {
+    /// Not a near-duplicate of anything; keep it.
+    Distinct,
+    /// Paraphrases the existing item at this index, which scores at least
+    /// as well; drop it.
+    DuplicateOfExisting(usize),
+    /// Paraphrases the better-scoring candidate at this index; drop it.
+    DuplicateOfCandidate(usize),
+    /// Paraphrases the lower-scoring existing item at this index; keep it
+    /// in that item's place.
+    Replaces(usize),
+}
 
-    // Try ``` ... ``` blocks
-    if completion.contains("```") {
-        return completion
-            .split("```")
-            .nth(1)
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-            .ok_or_else(|| "Found ``` block but content was empty or malformed".to_string());
+/// Decide which of `candidates` to keep so that of any two near-duplicates
+/// (cosine similarity at or above `threshold`) only the higher-scoring one
+/// survives
+///
+/// Candidates are considered best score first and compared with the
+/// candidates kept so far, then with the most similar existing item.
+/// Existing items without a score are never replaced. Fates come back in
+/// candidate order.
+pub(crate) fn merge_duplicates(
+    existing: &[Vec<f32>],
+    existing_scores: &[Option<f64>],
+    candidates: &[Vec<f32>],
+    candidate_scores: &[f64],
+    threshold: f64,
+) -> Vec<DuplicateFate> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|&a, &b| candidate_scores[b].total_cmp(&candidate_scores[a]));
+
+    let mut fates = vec![DuplicateFate::Distinct; candidates.len()];
+    let mut kept: Vec<usize> = Vec::new();
+    for i in order {
+        let similarity = |other: &Vec<f32>| cosine_similarity(&candidates[i], other);
+        if let Some(&k) = kept
+            .iter()
+            .find(|&&k| similarity(&candidates[k]) >= threshold)
+        {
+            fates[i] = DuplicateFate::DuplicateOfCandidate(k);
+            continue;
+        }
+        let closest = existing
+            .iter()
+            .enumerate()
+            .map(|(j, vector)| (j, similarity(vector)))
+            .filter(|&(_, s)| s >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        fates[i] = match closest {
+            None => DuplicateFate::Distinct,
+            Some((j, _)) => match existing_scores[j] {
+                Some(score) if score < candidate_scores[i] => DuplicateFate::Replaces(j),
+                _ => DuplicateFate::DuplicateOfExisting(j),
+            },
+        };
+        if !matches!(fates[i], DuplicateFate::DuplicateOfExisting(_)) {
+            kept.push(i);
+        }
     }
-
-    Err(format!(
-        "No JSON found in response. First 100 chars: '{}'",
-        completion.chars().take(100).collect::<String>()
-    ))
+    fates
 }
 
 /// Reasoning mode types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReasoningMode {
     /// Sequential step-by-step reasoning.
@@ -147,9 +224,67 @@ pub enum ReasoningMode {
     Mcts,
     /// Counterfactual "what if?" analysis.
     Counterfactual,
+    /// Adversarial pro/con debate judged for a verdict.
+    Debate,
+    /// Root cause analysis with 5 Whys or fishbone diagrams.
+    RootCause,
+    /// Goal decomposition into a task dependency graph.
+    Planning,
+    /// Fermi estimation with locally computed bounds.
+    Estimate,
+    /// SWOT analysis linked to stakeholder perspectives.
+    Swot,
+    /// Hypothesis testing with pre-registered experiments.
+    Hypothesis,
+    /// Six Thinking Hats passes from fixed cognitive stances.
+    SixHats,
+    /// Thesis-antithesis-synthesis rounds until the position settles.
+    Dialectic,
+    /// RICE, MoSCoW or weighted ranking of items.
+    Prioritization,
+    /// Negotiation analysis with BATNAs, ZOPA and package deals.
+    Negotiation,
+    /// Risk register scored by likelihood, impact and detectability.
+    Risk,
+    /// Systems model with feedback loops and leverage points.
+    Systems,
+    /// Code reasoning over diffs and snippets.
+    Code,
+    /// Briefing consolidating several sessions.
+    Synthesis,
 }
 
 impl ReasoningMode {
+    /// Every reasoning mode.
+    pub const ALL: [ReasoningMode; 26] = [
+        ReasoningMode::Linear,
+        ReasoningMode::Tree,
+        ReasoningMode::Divergent,
+        ReasoningMode::Reflection,
+        ReasoningMode::Backtracking,
+        ReasoningMode::Auto,
+        ReasoningMode::Got,
+        ReasoningMode::Decision,
+        ReasoningMode::Evidence,
+        ReasoningMode::Timeline,
+        ReasoningMode::Mcts,
+        ReasoningMode::Counterfactual,
+        ReasoningMode::Debate,
+        ReasoningMode::RootCause,
+        ReasoningMode::Planning,
+        ReasoningMode::Estimate,
+        ReasoningMode::Swot,
+        ReasoningMode::Hypothesis,
+        ReasoningMode::SixHats,
+        ReasoningMode::Dialectic,
+        ReasoningMode::Prioritization,
+        ReasoningMode::Negotiation,
+        ReasoningMode::Risk,
+        ReasoningMode::Systems,
+        ReasoningMode::Code,
+        ReasoningMode::Synthesis,
+    ];
+
     /// Get the mode name as a string
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -165,6 +300,20 @@ impl ReasoningMode {
             ReasoningMode::Timeline => "timeline",
             ReasoningMode::Mcts => "mcts",
             ReasoningMode::Counterfactual => "counterfactual",
+            ReasoningMode::Debate => "debate",
+            ReasoningMode::RootCause => "root_cause",
+            ReasoningMode::Planning => "planning",
+            ReasoningMode::Estimate => "estimate",
+            ReasoningMode::Swot => "swot",
+            ReasoningMode::Hypothesis => "hypothesis",
+            ReasoningMode::SixHats => "six_hats",
+            ReasoningMode::Dialectic => "dialectic",
+            ReasoningMode::Prioritization => "prioritization",
+            ReasoningMode::Negotiation => "negotiation",
+            ReasoningMode::Risk => "risk",
+            ReasoningMode::Systems => "systems",
+            ReasoningMode::Code => "code",
+            ReasoningMode::Synthesis => "synthesis",
         }
     }
 }
@@ -192,6 +341,20 @@ impl std::str::FromStr for ReasoningMode {
             "timeline" => Ok(ReasoningMode::Timeline),
             "mcts" => Ok(ReasoningMode::Mcts),
             "counterfactual" => Ok(ReasoningMode::Counterfactual),
+            "debate" => Ok(ReasoningMode::Debate),
+            "root_cause" => Ok(ReasoningMode::RootCause),
+            "planning" => Ok(ReasoningMode::Planning),
+            "estimate" => Ok(ReasoningMode::Estimate),
+            "swot" => Ok(ReasoningMode::Swot),
+            "hypothesis" => Ok(ReasoningMode::Hypothesis),
+            "six_hats" => Ok(ReasoningMode::SixHats),
+            "dialectic" => Ok(ReasoningMode::Dialectic),
+            "prioritization" => Ok(ReasoningMode::Prioritization),
+            "negotiation" => Ok(ReasoningMode::Negotiation),
+            "risk" => Ok(ReasoningMode::Risk),
+            "systems" => Ok(ReasoningMode::Systems),
+            "code" => Ok(ReasoningMode::Code),
+            "synthesis" => Ok(ReasoningMode::Synthesis),
             _ => Err(format!("Unknown reasoning mode: {}", s)),
         }
     }
@@ -280,6 +443,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reasoning_mode_all_round_trips() {
+        for mode in ReasoningMode::ALL {
+            assert_eq!(mode.as_str().parse::<ReasoningMode>(), Ok(mode));
+        }
+    }
+
     #[test]
     fn test_reasoning_mode_from_str_invalid() {
         let result = "invalid".parse::<ReasoningMode>();
@@ -403,4 +573,40 @@ mod tests {
         let result = serialize_for_log(&vec, "vec");
         assert_eq!(result, serde_json::json!([1, 2, 3]));
     }
+
+    #[test]
+    fn test_merge_duplicates_keeps_higher_scoring_candidate() {
+        let candidates = vec![vec![1.0, 0.0], vec![0.99, 0.1], vec![0.0, 1.0]];
+        let fates = merge_duplicates(&[], &[], &candidates, &[0.5, 0.8, 0.6], 0.9);
+        assert_eq!(
+            fates,
+            vec![
+                DuplicateFate::DuplicateOfCandidate(1),
+                DuplicateFate::Distinct,
+                DuplicateFate::Distinct,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_duplicates_against_existing() {
+        let existing = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![0.7, 0.7]];
+        let existing_scores = [Some(0.6), Some(0.9), None];
+        let candidates = vec![vec![0.98, 0.05], vec![0.05, 0.98], vec![0.71, 0.7]];
+        let fates = merge_duplicates(
+            &existing,
+            &existing_scores,
+            &candidates,
+            &[0.8, 0.8, 0.8],
+            0.95,
+        );
+        assert_eq!(
+            fates,
+            vec![
+                DuplicateFate::Replaces(0),
+                DuplicateFate::DuplicateOfExisting(1),
+                DuplicateFate::DuplicateOfExisting(2),
+            ]
+        );
+    }
 }
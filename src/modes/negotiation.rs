@@ -0,0 +1,738 @@
+//! Negotiation mode - parties, BATNAs, ZOPA, package deals and concessions.
+//!
+//! The decision framework pipe describes the parties and proposes package
+//! deals and concession steps; the deal math is computed locally:
+//! - the ZOPA (zone of possible agreement) on the main issue, from the
+//!   parties' reservation values
+//! - whether each package beats every party's BATNA, its Nash product
+//!   (the product of each party's surplus over its BATNA), and whether
+//!   another package is at least as good for everyone
+//! - each party's concession path, cheapest first, with its total cost
+//!
+//! An analysis can build on a perspective analysis from
+//! `reasoning_analyze_perspectives`, and is itself stored as one: the
+//! parties are its stakeholders and the strategy its synthesis.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::NEGOTIATION_ANALYSIS_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Invocation, PerspectiveAnalysis, SharedStorage};
+
+/// Input parameters for negotiation analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegotiationParams {
+    /// The negotiation scenario
+    pub scenario: String,
+    /// Parties to the negotiation (identified from the scenario if empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parties: Vec<String>,
+    /// The main distributive issue, such as price
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Perspective analysis to build on (defaults to the latest in the
+    /// session that is not itself a negotiation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspective_id: Option<String>,
+    /// Optional background on the negotiation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Which way a party wants the main issue to go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueDirection {
+    /// Higher is better, like a seller's price.
+    Maximize,
+    /// Lower is better, like a buyer's price.
+    Minimize,
+}
+
+/// A party to the negotiation
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NegotiationParty {
+    /// Party name
+    pub name: String,
+    /// What the party actually needs
+    #[serde(default)]
+    pub interests: Vec<String>,
+    /// What the party demands
+    #[serde(default)]
+    pub positions: Vec<String>,
+    /// Best alternative to a negotiated agreement
+    #[serde(default)]
+    pub batna: String,
+    /// How good the BATNA is for the party (0.0-1.0)
+    #[serde(default)]
+    pub batna_value: f64,
+    /// Walk-away point on the main issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reservation_value: Option<f64>,
+    /// Aspiration on the main issue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_value: Option<f64>,
+    /// Which way the party wants the main issue to go
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<IssueDirection>,
+}
+
+/// Zone of possible agreement on the main issue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Zopa {
+    /// The issue
+    pub issue: String,
+    /// Highest reservation value among parties maximizing the issue
+    pub lower_bound: f64,
+    /// Lowest reservation value among parties minimizing the issue
+    pub upper_bound: f64,
+    /// Whether any agreement satisfies every reservation value
+    pub exists: bool,
+}
+
+/// A candidate package deal
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PackageDeal {
+    /// Short package name
+    pub name: String,
+    /// Agreed terms by issue
+    #[serde(default)]
+    pub terms: BTreeMap<String, serde_json::Value>,
+    /// How good the package is for each party (0.0-1.0)
+    #[serde(default)]
+    pub values: BTreeMap<String, f64>,
+    /// Why the package could work
+    #[serde(default)]
+    pub rationale: String,
+    /// Whether every party does at least as well as its BATNA
+    #[serde(default)]
+    pub viable: bool,
+    /// Product of every party's surplus over its BATNA (0 if not viable)
+    #[serde(default)]
+    pub nash_product: f64,
+    /// A package at least as good for every party and better for one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dominated_by: Option<String>,
+}
+
+/// One concession step
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Concession {
+    /// Position in the party's path (1 = first)
+    #[serde(default)]
+    pub step: u32,
+    /// What the party concedes
+    pub give: String,
+    /// What the party asks in return
+    #[serde(default)]
+    pub in_exchange_for: String,
+    /// What the concession gives up (0.0-1.0)
+    #[serde(default)]
+    pub cost: f64,
+}
+
+/// The order in which a party concedes
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConcessionPath {
+    /// The conceding party
+    pub party: String,
+    /// Concessions, cheapest first
+    pub steps: Vec<Concession>,
+    /// Sum of the concession costs
+    pub total_cost: f64,
+}
+
+/// Result of negotiation analysis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NegotiationResult {
+    /// Stored analysis ID
+    pub analysis_id: String,
+    /// The session ID
+    pub session_id: String,
+    /// The scenario analyzed
+    pub scenario: String,
+    /// Parties with their interests and BATNAs
+    pub parties: Vec<NegotiationParty>,
+    /// Zone of possible agreement, when parties on both sides of the main
+    /// issue gave reservation values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zopa: Option<Zopa>,
+    /// Package deals, viable ones first by Nash product
+    pub packages: Vec<PackageDeal>,
+    /// The viable, undominated package with the highest Nash product
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recommended_package: Option<String>,
+    /// Each party's concession path
+    pub concession_paths: Vec<ConcessionPath>,
+    /// Overall negotiation strategy
+    pub strategy: String,
+    /// Perspective analysis the analysis built on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspective_id: Option<String>,
+    /// Confidence in the analysis (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Negotiation mode handler.
+#[derive(Clone)]
+pub struct NegotiationMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Decision framework pipe producing the analysis
+    decision_pipe: String,
+}
+
+impl NegotiationMode {
+    /// Create a new negotiation mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            decision_pipe,
+        }
+    }
+
+    /// Analyze a negotiation scenario
+    pub async fn analyze(&self, params: NegotiationParams) -> AppResult<NegotiationResult> {
+        let start = Instant::now();
+
+        if params.scenario.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "scenario".to_string(),
+                reason: "Scenario cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.parties.len() == 1 {
+            return Err(ToolError::Validation {
+                field: "parties".to_string(),
+                reason: "A negotiation needs at least two parties".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let session = storage
+            .get_or_create_session(&params.session_id, "negotiation")
+            .await?;
+        debug!(session_id = %session.id, "Processing negotiation request");
+
+        let perspective =
+            match params.perspective_id {
+                Some(ref id) => Some(storage.get_perspective(id).await?.ok_or_else(|| {
+                    ToolError::Validation {
+                        field: "perspective_id".to_string(),
+                        reason: format!("Perspective analysis not found: {}", id),
+                    }
+                })?),
+                // Newest first
+                None => storage
+                    .get_session_perspectives(&session.id)
+                    .await?
+                    .into_iter()
+                    .find(|analysis| !is_negotiation(analysis)),
+            };
+
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.negotiate",
+            serialize_for_log(&params, "reasoning.negotiate input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(NEGOTIATION_ANALYSIS_PROMPT),
+            Message::user(negotiation_prompt(&params, perspective.as_ref())),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let (parsed, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+
+        let issue = params
+            .issue
+            .clone()
+            .or(parsed.issue)
+            .unwrap_or_else(|| "main issue".to_string());
+        let mut parties = parsed.parties;
+        for party in &mut parties {
+            party.batna_value = party.batna_value.clamp(0.0, 1.0);
+        }
+        let zopa = compute_zopa(&issue, &parties);
+        let packages = evaluate_packages(&parties, parsed.packages);
+        let recommended_package = packages
+            .iter()
+            .find(|p| p.viable && p.dominated_by.is_none())
+            .map(|p| p.name.clone());
+        let concession_paths = concession_paths(&parties, parsed.concessions);
+        let confidence = parsed.confidence.clamp(0.0, 1.0);
+
+        let mut metadata = serde_json::json!({"negotiation": true, "issue": issue});
+        if let Some(ref perspective) = perspective {
+            metadata["perspective_id"] = serde_json::json!(perspective.id);
+        }
+        let stored = PerspectiveAnalysis::new(
+            &session.id,
+            params.scenario.trim(),
+            serde_json::to_value(&parties).unwrap_or_default(),
+            serde_json::json!({
+                "strategy": parsed.strategy,
+                "zopa": zopa,
+                "packages": packages,
+                "recommended_package": recommended_package,
+                "concession_paths": concession_paths,
+            }),
+            confidence,
+        )
+        .with_metadata(metadata);
+        storage.create_perspective(&stored).await?;
+
+        let result = NegotiationResult {
+            analysis_id: stored.id,
+            session_id: session.id.clone(),
+            scenario: stored.topic,
+            parties,
+            zopa,
+            packages,
+            recommended_package,
+            concession_paths,
+            strategy: parsed.strategy,
+            perspective_id: perspective.map(|p| p.id),
+            confidence,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.negotiate output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            analysis_id = %result.analysis_id,
+            parties = result.parties.len(),
+            zopa_exists = result.zopa.as_ref().map(|z| z.exists),
+            latency_ms = latency,
+            "Negotiation analysis completed"
+        );
+
+        Ok(result)
+    }
+}
+
+/// Whether a stored perspective analysis is a negotiation analysis
+fn is_negotiation(analysis: &PerspectiveAnalysis) -> bool {
+    analysis
+        .metadata
+        .as_ref()
+        .and_then(|m| m["negotiation"].as_bool())
+        .unwrap_or(false)
+}
+
+fn negotiation_prompt(
+    params: &NegotiationParams,
+    perspective: Option<&PerspectiveAnalysis>,
+) -> String {
+    let mut prompt = format!("SCENARIO: {}", params.scenario.trim());
+    if let Some(ref issue) = params.issue {
+        prompt.push_str(&format!("\nMAIN ISSUE: {}", issue));
+    }
+    if !params.parties.is_empty() {
+        prompt.push_str(&format!("\nPARTIES: {}", params.parties.join(", ")));
+    }
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+    }
+    if let Some(perspective) = perspective {
+        prompt.push_str(&format!(
+            "\n\nSTAKEHOLDER ANALYSIS ({}):\n{}",
+            perspective.topic, perspective.stakeholders
+        ));
+    }
+    prompt
+}
+
+fn parse_response(completion: &str) -> AppResult<NegotiationResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: NegotiationResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse negotiation analysis: {}", e),
+        })?;
+    if parsed.parties.len() < 2 {
+        return Err(ToolError::Reasoning {
+            message: "Negotiation analysis needs at least two parties".to_string(),
+        }
+        .into());
+    }
+    Ok(parsed)
+}
+
+/// The range of the main issue every reservation value allows; `None`
+/// unless parties on both sides gave one
+fn compute_zopa(issue: &str, parties: &[NegotiationParty]) -> Option<Zopa> {
+    let reservations = |direction| {
+        parties
+            .iter()
+            .filter(move |p| p.direction == Some(direction))
+            .filter_map(|p| p.reservation_value)
+    };
+    let lower_bound = reservations(IssueDirection::Maximize).reduce(f64::max)?;
+    let upper_bound = reservations(IssueDirection::Minimize).reduce(f64::min)?;
+    Some(Zopa {
+        issue: issue.to_string(),
+        lower_bound,
+        upper_bound,
+        exists: lower_bound <= upper_bound,
+    })
+}
+
+/// Judge packages against the parties' BATNAs and each other, viable ones
+/// first by Nash product; values for unknown parties are dropped and a
+/// missing value counts as 0
+fn evaluate_packages(
+    parties: &[NegotiationParty],
+    mut packages: Vec<PackageDeal>,
+) -> Vec<PackageDeal> {
+    for package in &mut packages {
+        let values: BTreeMap<String, f64> = parties
+            .iter()
+            .map(|party| {
+                let value = package
+                    .values
+                    .iter()
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case(&party.name))
+                    .map(|(_, v)| v.clamp(0.0, 1.0))
+                    .unwrap_or(0.0);
+                (party.name.clone(), value)
+            })
+            .collect();
+        package.viable = parties.iter().all(|p| values[&p.name] >= p.batna_value);
+        package.nash_product = if package.viable {
+            parties
+                .iter()
+                .map(|p| values[&p.name] - p.batna_value)
+                .product()
+        } else {
+            0.0
+        };
+        package.values = values;
+        package.dominated_by = None;
+    }
+
+    let dominators: Vec<Option<String>> = packages
+        .iter()
+        .map(|package| {
+            packages
+                .iter()
+                .find(|other| dominates(parties, other, package))
+                .map(|other| other.name.clone())
+        })
+        .collect();
+    for (package, dominator) in packages.iter_mut().zip(dominators) {
+        package.dominated_by = dominator;
+    }
+
+    packages.sort_by(|a, b| {
+        b.viable.cmp(&a.viable).then(
+            b.nash_product
+                .partial_cmp(&a.nash_product)
+                .unwrap_or(std::cmp::Ordering::Equal),
+        )
+    });
+    packages
+}
+
+/// Whether `a` is at least as good as `b` for every party and better for one
+fn dominates(parties: &[NegotiationParty], a: &PackageDeal, b: &PackageDeal) -> bool {
+    let mut better = false;
+    for party in parties {
+        let (va, vb) = (a.values[&party.name], b.values[&party.name]);
+        if va < vb {
+            return false;
+        }
+        better |= va > vb;
+    }
+    better
+}
+
+/// Group concessions by party in party order, cheapest first (ties keep the
+/// pipe's step order) and renumbered; concessions by unknown parties are
+/// dropped
+fn concession_paths(
+    parties: &[NegotiationParty],
+    concessions: Vec<ConcessionEntry>,
+) -> Vec<ConcessionPath> {
+    parties
+        .iter()
+        .filter_map(|party| {
+            let mut steps: Vec<Concession> = concessions
+                .iter()
+                .filter(|c| c.party.trim().eq_ignore_ascii_case(&party.name))
+                .map(|c| Concession {
+                    cost: c.concession.cost.clamp(0.0, 1.0),
+                    ..c.concession.clone()
+                })
+                .collect();
+            if steps.is_empty() {
+                return None;
+            }
+            steps.sort_by_key(|s| s.step);
+            steps.sort_by(|a, b| {
+                a.cost
+                    .partial_cmp(&b.cost)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            for (index, step) in steps.iter_mut().enumerate() {
+                step.step = index as u32 + 1;
+            }
+            Some(ConcessionPath {
+                party: party.name.clone(),
+                total_cost: steps.iter().map(|s| s.cost).sum(),
+                steps,
+            })
+        })
+        .collect()
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Deserialize)]
+struct NegotiationResponse {
+    #[serde(default)]
+    issue: Option<String>,
+    #[serde(default)]
+    parties: Vec<NegotiationParty>,
+    #[serde(default)]
+    packages: Vec<PackageDeal>,
+    #[serde(default)]
+    concessions: Vec<ConcessionEntry>,
+    #[serde(default)]
+    strategy: String,
+    confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConcessionEntry {
+    party: String,
+    #[serde(flatten)]
+    concession: Concession,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn party(name: &str, batna_value: f64) -> NegotiationParty {
+        NegotiationParty {
+            name: name.to_string(),
+            batna_value,
+            ..Default::default()
+        }
+    }
+
+    fn bound(name: &str, direction: IssueDirection, reservation: f64) -> NegotiationParty {
+        NegotiationParty {
+            reservation_value: Some(reservation),
+            direction: Some(direction),
+            ..party(name, 0.0)
+        }
+    }
+
+    fn package(name: &str, values: &[(&str, f64)]) -> PackageDeal {
+        PackageDeal {
+            name: name.to_string(),
+            values: values.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn entry(party: &str, step: u32, give: &str, cost: f64) -> ConcessionEntry {
+        ConcessionEntry {
+            party: party.to_string(),
+            concession: Concession {
+                step,
+                give: give.to_string(),
+                in_exchange_for: String::new(),
+                cost,
+            },
+        }
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: NegotiationParams =
+            serde_json::from_value(json!({"scenario": "Buying a used car"})).unwrap();
+        assert!(params.parties.is_empty());
+        assert!(params.issue.is_none());
+        assert!(params.perspective_id.is_none());
+    }
+
+    #[test]
+    fn test_zopa() {
+        let parties = [
+            bound("Seller", IssueDirection::Maximize, 100.0),
+            bound("Second seller", IssueDirection::Maximize, 90.0),
+            bound("Buyer", IssueDirection::Minimize, 120.0),
+        ];
+        let zopa = compute_zopa("price", &parties).unwrap();
+        assert_eq!(zopa.lower_bound, 100.0);
+        assert_eq!(zopa.upper_bound, 120.0);
+        assert!(zopa.exists);
+
+        let apart = [
+            bound("Seller", IssueDirection::Maximize, 130.0),
+            bound("Buyer", IssueDirection::Minimize, 120.0),
+        ];
+        assert!(!compute_zopa("price", &apart).unwrap().exists);
+
+        let one_sided = [
+            bound("Seller", IssueDirection::Maximize, 100.0),
+            party("Buyer", 0.0),
+        ];
+        assert!(compute_zopa("price", &one_sided).is_none());
+    }
+
+    #[test]
+    fn test_evaluate_packages() {
+        let parties = [party("Seller", 0.4), party("Buyer", 0.3)];
+        let packages = evaluate_packages(
+            &parties,
+            vec![
+                package("Price only", &[("Seller", 0.6), ("Buyer", 0.5)]),
+                package("Lowball", &[("seller", 0.3), ("Buyer", 0.9)]),
+                package(
+                    "Bundle",
+                    &[("Seller", 0.7), ("Buyer", 0.7), ("Broker", 1.0)],
+                ),
+            ],
+        );
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Bundle", "Price only", "Lowball"]);
+
+        assert!(packages[0].viable);
+        assert!((packages[0].nash_product - 0.12).abs() < 1e-9);
+        assert!(!packages[0].values.contains_key("Broker"));
+        assert!(packages[0].dominated_by.is_none());
+        assert_eq!(packages[1].dominated_by.as_deref(), Some("Bundle"));
+        assert!(!packages[2].viable);
+        assert_eq!(packages[2].nash_product, 0.0);
+        assert_eq!(packages[2].values["Seller"], 0.3);
+    }
+
+    #[test]
+    fn test_missing_value_counts_as_zero() {
+        let parties = [party("Seller", 0.0), party("Buyer", 0.1)];
+        let packages = evaluate_packages(&parties, vec![package("Vague", &[("Seller", 0.8)])]);
+        assert_eq!(packages[0].values["Buyer"], 0.0);
+        assert!(!packages[0].viable);
+    }
+
+    #[test]
+    fn test_concession_paths() {
+        let parties = [party("Seller", 0.4), party("Buyer", 0.3)];
+        let paths = concession_paths(
+            &parties,
+            vec![
+                entry("Seller", 1, "Free delivery", 0.2),
+                entry("Stranger", 1, "Anything", 0.1),
+                entry("seller", 2, "Support", 0.05),
+                entry("Seller", 3, "Warranty", 0.2),
+            ],
+        );
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].party, "Seller");
+        let gives: Vec<&str> = paths[0].steps.iter().map(|s| s.give.as_str()).collect();
+        assert_eq!(gives, ["Support", "Free delivery", "Warranty"]);
+        assert_eq!(paths[0].steps[2].step, 3);
+        assert!((paths[0].total_cost - 0.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let parsed = parse_response(
+            r#"{"parties": [{"name": "A", "batna_value": 0.2}, {"name": "B"}],
+                "concessions": [{"party": "A", "give": "Time", "cost": 0.1}],
+                "confidence": 0.6}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.parties.len(), 2);
+        assert_eq!(parsed.concessions[0].concession.give, "Time");
+        assert!(parsed.packages.is_empty());
+
+        let err = parse_response(r#"{"parties": [{"name": "A"}], "confidence": 0.5}"#).unwrap_err();
+        assert!(err.to_string().contains("two parties"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_negotiation_and_prompt() {
+        let perspective = PerspectiveAnalysis::new(
+            "s1",
+            "Office move",
+            json!([{"name": "Landlord"}]),
+            json!({}),
+            0.7,
+        );
+        assert!(!is_negotiation(&perspective));
+        let negotiation = perspective
+            .clone()
+            .with_metadata(json!({"negotiation": true}));
+        assert!(is_negotiation(&negotiation));
+
+        let params = NegotiationParams {
+            scenario: " Lease renewal ".to_string(),
+            parties: vec!["Landlord".to_string(), "Tenant".to_string()],
+            issue: Some("rent".to_string()),
+            perspective_id: None,
+            context: None,
+            session_id: None,
+            generation: GenerationParams::default(),
+        };
+        let prompt = negotiation_prompt(&params, Some(&perspective));
+        assert!(prompt.starts_with("SCENARIO: Lease renewal\nMAIN ISSUE: rent"));
+        assert!(prompt.contains("PARTIES: Landlord, Tenant"));
+        assert!(prompt.contains("STAKEHOLDER ANALYSIS (Office move)"));
+        assert!(!negotiation_prompt(&params, None).contains("STAKEHOLDER"));
+    }
+}
@@ -0,0 +1,969 @@
+//! Planning mode - goal decomposition into a task dependency graph.
+//!
+//! This module turns a goal into a plan:
+//! - The pipe decomposes the goal into tasks with prerequisites, effort
+//!   estimates, and risks
+//! - The dependency order, levels, and critical path are computed locally,
+//!   and a plan whose prerequisites form a cycle is rejected
+//! - When a task fails, `revise` drops it and every task depending on it,
+//!   then asks the pipe for replacement tasks
+//!
+//! Plans are stored in the graph tables: the goal is the root node, each
+//! task a node, and each prerequisite a `depends_on` edge from the task.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{PLAN_DECOMPOSE_PROMPT, PLAN_REVISE_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{EdgeType, GraphEdge, GraphNode, Invocation, NodeType, SharedStorage};
+
+/// Tasks asked for when the caller does not say.
+pub const DEFAULT_PLAN_TASKS: usize = 8;
+
+/// Most tasks one plan or revision may add.
+pub const MAX_PLAN_TASKS: usize = 20;
+
+/// Metadata key under which task details are stored on graph nodes.
+const TASK_METADATA_KEY: &str = "plan_task";
+
+fn default_max_tasks() -> usize {
+    DEFAULT_PLAN_TASKS
+}
+
+/// Input parameters for planning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanParams {
+    /// The goal to decompose
+    pub goal: String,
+    /// Optional constraints or background
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Most tasks to plan
+    #[serde(default = "default_max_tasks")]
+    pub max_tasks: usize,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for replanning after a task failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanReviseParams {
+    /// Session holding the plan
+    pub session_id: String,
+    /// The task that failed
+    pub failed_task_id: String,
+    /// Why the task failed
+    pub reason: String,
+    /// Most replacement tasks to plan
+    #[serde(default = "default_max_tasks")]
+    pub max_tasks: usize,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// State of a planned task
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Still part of the plan
+    #[default]
+    Pending,
+    /// Marked failed by a revision
+    Failed,
+    /// Dropped because a task it depends on failed
+    Superseded,
+}
+
+impl TaskStatus {
+    /// Get the status name as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Pending => "pending",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Superseded => "superseded",
+        }
+    }
+}
+
+/// One task of a plan
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlanTask {
+    /// Graph node holding the task
+    pub task_id: String,
+    /// Short task name
+    pub title: String,
+    /// What done looks like
+    pub description: String,
+    /// Tasks that must finish first
+    pub prerequisites: Vec<String>,
+    /// Estimated focused effort in hours
+    pub effort_hours: f64,
+    /// What could make the task fail or slip
+    pub risks: Vec<String>,
+    /// State of the task
+    pub status: TaskStatus,
+    /// Dependency level, from 1 for tasks without prerequisites; tasks on
+    /// the same level can run in parallel
+    pub level: u32,
+}
+
+/// Result of planning
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlanResult {
+    /// The session ID
+    pub session_id: String,
+    /// The goal planned for
+    pub goal: String,
+    /// Root graph node holding the goal
+    pub goal_node_id: String,
+    /// Pending tasks in an order that respects every prerequisite
+    pub tasks: Vec<PlanTask>,
+    /// Effort of every pending task
+    pub total_effort_hours: f64,
+    /// Longest chain of dependent tasks by effort
+    pub critical_path: Vec<String>,
+    /// Effort along the critical path
+    pub critical_path_hours: f64,
+    /// How the plan reaches the goal
+    pub summary: String,
+    /// Confidence the plan reaches the goal (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Result of replanning
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlanReviseResult {
+    /// The task marked failed
+    pub failed_task_id: String,
+    /// Tasks dropped because they depended on the failed task
+    pub superseded_task_ids: Vec<String>,
+    /// Replacement tasks added
+    pub added_task_ids: Vec<String>,
+    /// The revised plan
+    pub plan: PlanResult,
+}
+
+/// Planning mode handler for goal decomposition.
+#[derive(Clone)]
+pub struct PlanningMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Tree pipe decomposing goals
+    tree_pipe: String,
+}
+
+impl PlanningMode {
+    /// Create a new planning mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            tree_pipe: config.pipes.tree.clone(),
+        }
+    }
+
+    /// Decompose a goal into a task dependency graph
+    pub async fn plan(&self, params: PlanParams) -> AppResult<PlanResult> {
+        let start = Instant::now();
+
+        if params.goal.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "goal".to_string(),
+                reason: "Goal cannot be empty".to_string(),
+            }
+            .into());
+        }
+        validate_max_tasks(params.max_tasks)?;
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "planning")
+            .await?;
+        debug!(session_id = %session.id, "Processing planning request");
+
+        let pipe = self.core.select_pipe(&self.tree_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.plan",
+            serialize_for_log(&params, "reasoning.plan input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let mut prompt = format!(
+            "GOAL: {}\n\nDecompose the goal into at most {} tasks.",
+            params.goal, params.max_tasks
+        );
+        if let Some(ref context) = params.context {
+            prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+        }
+        let result = self
+            .call(&pipe, PLAN_DECOMPOSE_PROMPT, prompt, params.generation)
+            .await
+            .and_then(|(response, cache_hit, fallback)| {
+                let drafted = draft_tasks(
+                    &session.id,
+                    response.tasks,
+                    params.max_tasks,
+                    &HashSet::new(),
+                );
+                let scheduled = schedule(drafted.iter().map(|(_, task)| task).collect())?;
+                Ok((
+                    drafted,
+                    scheduled,
+                    response.summary,
+                    response.confidence,
+                    cache_hit,
+                    fallback,
+                ))
+            });
+        let (drafted, scheduled, summary, confidence, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let confidence = confidence.clamp(0.0, 1.0);
+
+        let goal_node = GraphNode::new(&session.id, &params.goal)
+            .with_type(NodeType::Root)
+            .as_root()
+            .with_score(confidence)
+            .with_metadata(serde_json::json!({"plan_summary": summary}));
+        let mut nodes = vec![goal_node.clone()];
+        let mut edges = Vec::new();
+        store_tasks(
+            &session.id,
+            &goal_node.id,
+            drafted,
+            &scheduled,
+            &mut nodes,
+            &mut edges,
+        );
+        self.core.storage().create_graph_nodes_batch(&nodes).await?;
+        self.core.storage().create_graph_edges_batch(&edges).await?;
+
+        let result = PlanResult {
+            session_id: session.id.clone(),
+            goal: params.goal.clone(),
+            goal_node_id: goal_node.id,
+            summary,
+            confidence,
+            ..scheduled.into_plan()
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(serialize_for_log(&result, "reasoning.plan output"), latency);
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            tasks = result.tasks.len(),
+            critical_path_hours = result.critical_path_hours,
+            latency_ms = latency,
+            "Plan created"
+        );
+
+        Ok(result)
+    }
+
+    /// Mark a task failed, drop the tasks depending on it, and replan
+    pub async fn revise(&self, params: PlanReviseParams) -> AppResult<PlanReviseResult> {
+        let start = Instant::now();
+
+        if params.reason.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "reason".to_string(),
+                reason: "Failure reason cannot be empty".to_string(),
+            }
+            .into());
+        }
+        validate_max_tasks(params.max_tasks)?;
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        storage
+            .get_session(&params.session_id)
+            .await?
+            .ok_or_else(|| {
+                ToolError::Session(format!("Session not found: {}", params.session_id))
+            })?;
+
+        let nodes = storage.get_session_graph_nodes(&params.session_id).await?;
+        let edges = storage.get_session_edges(&params.session_id).await?;
+        let goal =
+            nodes
+                .iter()
+                .find(|n| n.is_root)
+                .cloned()
+                .ok_or_else(|| ToolError::Validation {
+                    field: "session_id".to_string(),
+                    reason: "Session holds no plan".to_string(),
+                })?;
+        let mut tasks = load_tasks(&nodes, &edges);
+
+        let failed = tasks
+            .iter()
+            .find(|t| t.task_id == params.failed_task_id)
+            .ok_or_else(|| ToolError::Validation {
+                field: "failed_task_id".to_string(),
+                reason: format!("Task not found in plan: {}", params.failed_task_id),
+            })?;
+        if failed.status != TaskStatus::Pending {
+            return Err(ToolError::Validation {
+                field: "failed_task_id".to_string(),
+                reason: format!("Task is already {}", failed.status.as_str()),
+            }
+            .into());
+        }
+        let failed = failed.clone();
+
+        // Every pending task reachable through prerequisites of the failed one
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for task in &tasks {
+            for prerequisite in &task.prerequisites {
+                dependents
+                    .entry(prerequisite.as_str())
+                    .or_default()
+                    .push(task.task_id.as_str());
+            }
+        }
+        let mut superseded: BTreeSet<String> = BTreeSet::new();
+        let mut frontier = vec![failed.task_id.as_str()];
+        while let Some(id) = frontier.pop() {
+            for dependent in dependents.get(id).into_iter().flatten() {
+                if superseded.insert(dependent.to_string()) {
+                    frontier.push(dependent);
+                }
+            }
+        }
+        let superseded: Vec<String> = tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Pending && superseded.contains(&t.task_id))
+            .map(|t| t.task_id.clone())
+            .collect();
+
+        for task in &mut tasks {
+            if task.task_id == failed.task_id {
+                task.status = TaskStatus::Failed;
+            } else if superseded.contains(&task.task_id) {
+                task.status = TaskStatus::Superseded;
+            }
+        }
+        let remaining: Vec<PlanTask> = tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Pending)
+            .cloned()
+            .collect();
+
+        let pipe = self.core.select_pipe(&self.tree_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.plan_revise",
+            serialize_for_log(&params, "reasoning.plan_revise input"),
+        )
+        .with_session(&params.session_id)
+        .with_pipe(&pipe);
+
+        let prompt = revise_prompt(
+            &goal.content,
+            &remaining,
+            &failed,
+            &params.reason,
+            &tasks,
+            params.max_tasks,
+        );
+        let remaining_ids: HashSet<String> = remaining.iter().map(|t| t.task_id.clone()).collect();
+        let result = self
+            .call(&pipe, PLAN_REVISE_PROMPT, prompt, params.generation)
+            .await
+            .and_then(|(response, cache_hit, fallback)| {
+                let drafted = draft_tasks(
+                    &params.session_id,
+                    response.tasks,
+                    params.max_tasks,
+                    &remaining_ids,
+                );
+                let scheduled = schedule(
+                    remaining
+                        .iter()
+                        .chain(drafted.iter().map(|(_, task)| task))
+                        .collect(),
+                )?;
+                Ok((
+                    drafted,
+                    scheduled,
+                    response.summary,
+                    response.confidence,
+                    cache_hit,
+                    fallback,
+                ))
+            });
+        let (drafted, scheduled, summary, confidence, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let confidence = confidence.clamp(0.0, 1.0);
+
+        // Retire the failed and superseded tasks, then add the replacements
+        for node in &nodes {
+            let status = match tasks.iter().find(|t| t.task_id == node.id) {
+                Some(task) if task.status != TaskStatus::Pending => task.status,
+                _ => continue,
+            };
+            let mut retired = node.clone().as_inactive();
+            if let Some(details) = retired
+                .metadata
+                .as_mut()
+                .and_then(|m| m.get_mut(TASK_METADATA_KEY))
+            {
+                details["status"] = serde_json::json!(status);
+                if status == TaskStatus::Failed {
+                    details["failure_reason"] = serde_json::json!(params.reason);
+                }
+            }
+            storage.update_graph_node(&retired).await?;
+        }
+        let added_task_ids: Vec<String> = drafted.iter().map(|(node, _)| node.id.clone()).collect();
+        let mut new_nodes = Vec::new();
+        let mut new_edges = Vec::new();
+        store_tasks(
+            &params.session_id,
+            &goal.id,
+            drafted,
+            &scheduled,
+            &mut new_nodes,
+            &mut new_edges,
+        );
+        storage.create_graph_nodes_batch(&new_nodes).await?;
+        storage.create_graph_edges_batch(&new_edges).await?;
+
+        let mut goal = goal;
+        goal.metadata = Some(serde_json::json!({"plan_summary": summary}));
+        goal.score = Some(confidence);
+        storage.update_graph_node(&goal).await?;
+
+        let result = PlanReviseResult {
+            failed_task_id: failed.task_id,
+            superseded_task_ids: superseded,
+            added_task_ids,
+            plan: PlanResult {
+                session_id: params.session_id.clone(),
+                goal: goal.content,
+                goal_node_id: goal.id,
+                summary,
+                confidence,
+                ..scheduled.into_plan()
+            },
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.plan_revise output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %params.session_id,
+            superseded = result.superseded_task_ids.len(),
+            added = result.added_task_ids.len(),
+            latency_ms = latency,
+            "Plan revised"
+        );
+
+        Ok(result)
+    }
+
+    /// Call the pipe and parse its plan
+    async fn call(
+        &self,
+        pipe: &str,
+        system_prompt: &str,
+        prompt: String,
+        generation: GenerationParams,
+    ) -> AppResult<(PlanResponse, bool, Option<String>)> {
+        let messages = vec![Message::system(system_prompt), Message::user(prompt)];
+        let request = PipeRequest::new(pipe, messages).with_generation(generation);
+        let response = self.core.backend().call_pipe(request).await?;
+
+        let json_str = extract_json_from_completion(&response.completion)
+            .map_err(|e| ToolError::Reasoning { message: e })?;
+        let plan: PlanResponse =
+            serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                message: format!("Failed to parse plan: {}", e),
+            })?;
+        if plan.tasks.is_empty() {
+            return Err(ToolError::Reasoning {
+                message: "Plan has no tasks".to_string(),
+            }
+            .into());
+        }
+        Ok((plan, response.cache_hit, response.fallback))
+    }
+}
+
+fn validate_max_tasks(max_tasks: usize) -> AppResult<()> {
+    if max_tasks == 0 || max_tasks > MAX_PLAN_TASKS {
+        return Err(ToolError::Validation {
+            field: "max_tasks".to_string(),
+            reason: format!("Must be between 1 and {}", MAX_PLAN_TASKS),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Prompt asking for replacement tasks after `failed` failed
+fn revise_prompt(
+    goal: &str,
+    remaining: &[PlanTask],
+    failed: &PlanTask,
+    reason: &str,
+    tasks: &[PlanTask],
+    max_tasks: usize,
+) -> String {
+    let mut prompt = format!(
+        "GOAL: {}\n\nFAILED TASK: {}\n{}\nREASON: {}\n",
+        goal, failed.title, failed.description, reason
+    );
+    let dropped: Vec<&str> = tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Superseded)
+        .map(|t| t.title.as_str())
+        .collect();
+    if !dropped.is_empty() {
+        prompt.push_str(&format!("\nDROPPED WITH IT: {}\n", dropped.join("; ")));
+    }
+    prompt.push_str("\nREMAINING TASKS:\n");
+    if remaining.is_empty() {
+        prompt.push_str("(none)\n");
+    }
+    for task in remaining {
+        prompt.push_str(&format!("- {}: {}\n", task.task_id, task.title));
+    }
+    prompt.push_str(&format!(
+        "\nPlan at most {} replacement tasks that still reach the goal.",
+        max_tasks
+    ));
+    prompt
+}
+
+/// Give each drafted task a graph node and resolve its prerequisites, which
+/// may name other drafted tasks by key or `existing` tasks by ID; unknown
+/// prerequisites are dropped
+fn draft_tasks(
+    session_id: &str,
+    drafts: Vec<TaskDraft>,
+    max_tasks: usize,
+    existing: &HashSet<String>,
+) -> Vec<(GraphNode, PlanTask)> {
+    let drafts: Vec<TaskDraft> = drafts.into_iter().take(max_tasks).collect();
+    let nodes: Vec<GraphNode> = drafts
+        .iter()
+        .map(|draft| GraphNode::new(session_id, &draft.title).with_type(NodeType::Thought))
+        .collect();
+    let ids_by_key: HashMap<&str, &str> = drafts
+        .iter()
+        .zip(&nodes)
+        .filter(|(draft, _)| !draft.key.is_empty())
+        .map(|(draft, node)| (draft.key.as_str(), node.id.as_str()))
+        .collect();
+
+    let tasks: Vec<PlanTask> = drafts
+        .iter()
+        .zip(&nodes)
+        .map(|(draft, node)| {
+            let mut prerequisites = Vec::new();
+            for prerequisite in &draft.prerequisites {
+                let id = match ids_by_key.get(prerequisite.as_str()) {
+                    Some(id) => id.to_string(),
+                    None if existing.contains(prerequisite) => prerequisite.clone(),
+                    None => {
+                        debug!(task = %draft.title, prerequisite = %prerequisite, "Dropping unknown prerequisite");
+                        continue;
+                    }
+                };
+                if id != node.id && !prerequisites.contains(&id) {
+                    prerequisites.push(id);
+                }
+            }
+            PlanTask {
+                task_id: node.id.clone(),
+                title: draft.title.clone(),
+                description: draft.description.clone(),
+                prerequisites,
+                effort_hours: draft.effort_hours.max(0.0),
+                risks: draft.risks.clone(),
+                status: TaskStatus::Pending,
+                level: 0,
+            }
+        })
+        .collect();
+    nodes.into_iter().zip(tasks).collect()
+}
+
+/// Tasks stored in the graph, with prerequisites from `depends_on` edges
+fn load_tasks(nodes: &[GraphNode], edges: &[GraphEdge]) -> Vec<PlanTask> {
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let details: StoredTask =
+                serde_json::from_value(node.metadata.as_ref()?.get(TASK_METADATA_KEY)?.clone())
+                    .ok()?;
+            Some(PlanTask {
+                task_id: node.id.clone(),
+                title: node.content.clone(),
+                description: details.description,
+                prerequisites: edges
+                    .iter()
+                    .filter(|e| e.edge_type == EdgeType::DependsOn && e.from_node == node.id)
+                    .map(|e| e.to_node.clone())
+                    .collect(),
+                effort_hours: details.effort_hours,
+                risks: details.risks,
+                status: details.status,
+                level: node.depth.max(0) as u32,
+            })
+        })
+        .collect()
+}
+
+/// Add nodes and edges for `drafted` tasks of the goal `goal_id`
+fn store_tasks(
+    session_id: &str,
+    goal_id: &str,
+    drafted: Vec<(GraphNode, PlanTask)>,
+    scheduled: &Schedule,
+    nodes: &mut Vec<GraphNode>,
+    edges: &mut Vec<GraphEdge>,
+) {
+    for (node, task) in drafted {
+        let level = scheduled
+            .tasks
+            .iter()
+            .find(|t| t.task_id == task.task_id)
+            .map_or(1, |t| t.level);
+        edges.push(GraphEdge::new(session_id, goal_id, &node.id).with_type(EdgeType::Generates));
+        for prerequisite in &task.prerequisites {
+            edges.push(
+                GraphEdge::new(session_id, &node.id, prerequisite).with_type(EdgeType::DependsOn),
+            );
+        }
+        nodes.push(
+            node.with_depth(level as i32)
+                .with_metadata(serde_json::json!({
+                    TASK_METADATA_KEY: StoredTask {
+                        description: task.description,
+                        effort_hours: task.effort_hours,
+                        risks: task.risks,
+                        status: task.status,
+                    }
+                })),
+        );
+    }
+}
+
+/// Pending tasks in dependency order with their levels and critical path
+#[derive(Debug)]
+struct Schedule {
+    tasks: Vec<PlanTask>,
+    total_effort_hours: f64,
+    critical_path: Vec<String>,
+    critical_path_hours: f64,
+}
+
+impl Schedule {
+    fn into_plan(self) -> PlanResult {
+        PlanResult {
+            session_id: String::new(),
+            goal: String::new(),
+            goal_node_id: String::new(),
+            tasks: self.tasks,
+            total_effort_hours: self.total_effort_hours,
+            critical_path: self.critical_path,
+            critical_path_hours: self.critical_path_hours,
+            summary: String::new(),
+            confidence: 0.0,
+        }
+    }
+}
+
+/// Order `tasks` so every prerequisite comes first, keeping the given order
+/// among independent tasks, and find the longest chain by effort. Fails if
+/// the prerequisites form a cycle.
+fn schedule(tasks: Vec<&PlanTask>) -> AppResult<Schedule> {
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.task_id.as_str(), i))
+        .collect();
+    // Prerequisites outside the scheduled tasks are already done or dropped
+    let prerequisites: Vec<Vec<usize>> = tasks
+        .iter()
+        .map(|task| {
+            task.prerequisites
+                .iter()
+                .filter_map(|id| index.get(id.as_str()).copied())
+                .collect()
+        })
+        .collect();
+    let mut dependents = vec![Vec::new(); tasks.len()];
+    let mut waiting: Vec<usize> = prerequisites.iter().map(Vec::len).collect();
+    for (task, prereqs) in prerequisites.iter().enumerate() {
+        for &prereq in prereqs {
+            dependents[prereq].push(task);
+        }
+    }
+
+    let mut ready: BTreeSet<usize> = (0..tasks.len()).filter(|&i| waiting[i] == 0).collect();
+    let mut order = Vec::with_capacity(tasks.len());
+    let mut level = vec![1u32; tasks.len()];
+    let mut finish = vec![0.0f64; tasks.len()];
+    let mut via: Vec<Option<usize>> = vec![None; tasks.len()];
+    while let Some(task) = ready.pop_first() {
+        order.push(task);
+        for &prereq in &prerequisites[task] {
+            level[task] = level[task].max(level[prereq] + 1);
+            if via[task].map_or(true, |best| finish[prereq] > finish[best]) {
+                via[task] = Some(prereq);
+            }
+        }
+        finish[task] = tasks[task].effort_hours + via[task].map_or(0.0, |p| finish[p]);
+        for &dependent in &dependents[task] {
+            waiting[dependent] -= 1;
+            if waiting[dependent] == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+    if order.len() < tasks.len() {
+        let cycle: Vec<&str> = (0..tasks.len())
+            .filter(|&i| waiting[i] > 0)
+            .map(|i| tasks[i].title.as_str())
+            .collect();
+        return Err(ToolError::Reasoning {
+            message: format!(
+                "Plan prerequisites form a cycle among: {}",
+                cycle.join(", ")
+            ),
+        }
+        .into());
+    }
+
+    let mut critical_path = Vec::new();
+    let mut critical_path_hours = 0.0;
+    if let Some(end) = (0..tasks.len()).max_by(|&a, &b| finish[a].total_cmp(&finish[b])) {
+        critical_path_hours = finish[end];
+        let mut at = Some(end);
+        while let Some(task) = at {
+            critical_path.push(tasks[task].task_id.clone());
+            at = via[task];
+        }
+        critical_path.reverse();
+    }
+
+    Ok(Schedule {
+        total_effort_hours: tasks.iter().map(|t| t.effort_hours).sum(),
+        tasks: order
+            .into_iter()
+            .map(|i| PlanTask {
+                level: level[i],
+                ..tasks[i].clone()
+            })
+            .collect(),
+        critical_path,
+        critical_path_hours,
+    })
+}
+
+/// Task details stored in graph node metadata
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTask {
+    description: String,
+    effort_hours: f64,
+    #[serde(default)]
+    risks: Vec<String>,
+    #[serde(default)]
+    status: TaskStatus,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlanResponse {
+    tasks: Vec<TaskDraft>,
+    #[serde(default)]
+    summary: String,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskDraft {
+    #[serde(default)]
+    key: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    prerequisites: Vec<String>,
+    #[serde(default)]
+    effort_hours: f64,
+    #[serde(default)]
+    risks: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn task(id: &str, prerequisites: &[&str], effort_hours: f64) -> PlanTask {
+        PlanTask {
+            task_id: id.to_string(),
+            title: id.to_uppercase(),
+            description: String::new(),
+            prerequisites: prerequisites.iter().map(|p| p.to_string()).collect(),
+            effort_hours,
+            risks: vec![],
+            status: TaskStatus::Pending,
+            level: 0,
+        }
+    }
+
+    fn draft(key: &str, prerequisites: &[&str]) -> TaskDraft {
+        serde_json::from_value(json!({
+            "key": key,
+            "title": format!("Task {}", key),
+            "prerequisites": prerequisites,
+            "effort_hours": 2.0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_params_defaults() {
+        let params: PlanParams = serde_json::from_value(json!({"goal": "Ship v2"})).unwrap();
+        assert_eq!(params.goal, "Ship v2");
+        assert_eq!(params.max_tasks, DEFAULT_PLAN_TASKS);
+        assert!(params.session_id.is_none());
+
+        let params: PlanReviseParams = serde_json::from_value(json!({
+            "session_id": "s-1",
+            "failed_task_id": "t-1",
+            "reason": "Vendor API was retired"
+        }))
+        .unwrap();
+        assert_eq!(params.max_tasks, DEFAULT_PLAN_TASKS);
+    }
+
+    #[test]
+    fn test_schedule_orders_levels_and_critical_path() {
+        // a -> c, b -> c, c -> d; b is the longer way into c
+        let tasks = [
+            task("d", &["c"], 1.0),
+            task("a", &[], 2.0),
+            task("c", &["a", "b"], 3.0),
+            task("b", &[], 5.0),
+        ];
+        let scheduled = schedule(tasks.iter().collect()).unwrap();
+        let order: Vec<&str> = scheduled.tasks.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(order, ["a", "b", "c", "d"]);
+        let levels: Vec<u32> = scheduled.tasks.iter().map(|t| t.level).collect();
+        assert_eq!(levels, [1, 1, 2, 3]);
+        assert_eq!(scheduled.total_effort_hours, 11.0);
+        assert_eq!(scheduled.critical_path, ["b", "c", "d"]);
+        assert_eq!(scheduled.critical_path_hours, 9.0);
+    }
+
+    #[test]
+    fn test_schedule_ignores_outside_prerequisites() {
+        let tasks = [task("a", &["done-elsewhere"], 1.0)];
+        let scheduled = schedule(tasks.iter().collect()).unwrap();
+        assert_eq!(scheduled.tasks[0].level, 1);
+        assert_eq!(scheduled.critical_path, ["a"]);
+    }
+
+    #[test]
+    fn test_schedule_rejects_cycles() {
+        let tasks = [
+            task("a", &["b"], 1.0),
+            task("b", &["a"], 1.0),
+            task("c", &[], 1.0),
+        ];
+        let err = schedule(tasks.iter().collect()).unwrap_err();
+        assert!(err.to_string().contains("cycle among: A, B"), "{}", err);
+    }
+
+    #[test]
+    fn test_draft_tasks_resolves_prerequisites() {
+        let existing: HashSet<String> = ["kept-task".to_string()].into();
+        let drafted = draft_tasks(
+            "s-1",
+            vec![
+                draft("t1", &["kept-task", "nonsense"]),
+                draft("t2", &["t1", "t2", "t1"]),
+                draft("t3", &[]),
+            ],
+            2,
+            &existing,
+        );
+        assert_eq!(drafted.len(), 2);
+        let (first, second) = (&drafted[0].1, &drafted[1].1);
+        assert_eq!(first.task_id, drafted[0].0.id);
+        assert_eq!(first.prerequisites, ["kept-task"]);
+        // Self references and repeats are dropped
+        assert_eq!(second.prerequisites, vec![first.task_id.clone()]);
+    }
+
+    #[test]
+    fn test_stored_tasks_round_trip() {
+        let drafted = draft_tasks(
+            "s-1",
+            vec![draft("t1", &[]), draft("t2", &["t1"])],
+            5,
+            &HashSet::new(),
+        );
+        let scheduled = schedule(drafted.iter().map(|(_, t)| t).collect()).unwrap();
+        let (mut nodes, mut edges) = (Vec::new(), Vec::new());
+        store_tasks("s-1", "goal", drafted, &scheduled, &mut nodes, &mut edges);
+        assert_eq!(edges.len(), 3);
+        assert_eq!(nodes[1].depth, 2);
+
+        let loaded = load_tasks(&nodes, &edges);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].prerequisites, [loaded[0].task_id.clone()]);
+        assert_eq!(loaded[1].level, 2);
+        assert_eq!(loaded[1].effort_hours, 2.0);
+    }
+}
@@ -0,0 +1,1228 @@
+//! Prioritization mode - RICE, MoSCoW and weighted rankings of items.
+//!
+//! The decision framework pipe only estimates the inputs of the method for
+//! each item; scores and ranks are computed locally:
+//! - `rice`: reach × impact × confidence ÷ effort
+//! - `moscow`: must before should before could before won't, ordered by
+//!   value within a category
+//! - `weighted`: weighted mean of per-criterion scores (0-10)
+//!
+//! Rankings are stored as decisions (method `rice`, `moscow` or
+//! `weighted_sum`) with the estimates alongside each ranked item, so a
+//! revised estimate can be re-ranked without another pipe call. A revision
+//! is stored as a new ranking pointing at the one it revises.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::PRIORITIZATION_ESTIMATE_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Decision, Invocation, SharedStorage, StoredCriterion};
+
+/// Most items one ranking may hold.
+pub const MAX_PRIORITIZATION_ITEMS: usize = 30;
+
+/// Largest RICE impact (3 = massive).
+pub const MAX_RICE_IMPACT: f64 = 3.0;
+
+/// Top of the value and criterion score scale.
+pub const MAX_ITEM_SCORE: f64 = 10.0;
+
+/// Prioritization method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrioritizationMethod {
+    /// Reach × impact × confidence ÷ effort.
+    Rice,
+    /// Must / should / could / won't categories.
+    Moscow,
+    /// Weighted mean of criterion scores.
+    Weighted,
+}
+
+impl PrioritizationMethod {
+    /// Method name as stored on the decision.
+    pub fn stored_name(&self) -> &'static str {
+        match self {
+            PrioritizationMethod::Rice => "rice",
+            PrioritizationMethod::Moscow => "moscow",
+            PrioritizationMethod::Weighted => "weighted_sum",
+        }
+    }
+
+    /// Method of a stored decision, if it is one of these.
+    pub fn from_stored_name(name: &str) -> Option<Self> {
+        match name {
+            "rice" => Some(PrioritizationMethod::Rice),
+            "moscow" => Some(PrioritizationMethod::Moscow),
+            "weighted_sum" => Some(PrioritizationMethod::Weighted),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrioritizationMethod::Rice => "rice",
+            PrioritizationMethod::Moscow => "moscow",
+            PrioritizationMethod::Weighted => "weighted",
+        }
+    }
+}
+
+/// MoSCoW category, in priority order.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MoscowCategory {
+    /// Required for the outcome to be acceptable.
+    Must,
+    /// Important but not vital.
+    Should,
+    /// Desirable if there is room.
+    Could,
+    /// Agreed to be out of scope this time.
+    #[serde(alias = "won't", alias = "wont_have")]
+    Wont,
+}
+
+/// Criterion of a weighted ranking
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PriorityCriterion {
+    /// Criterion name
+    pub name: String,
+    /// Relative weight (greater than zero)
+    pub weight: f64,
+    /// What the criterion measures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Input parameters for prioritization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrioritizeParams {
+    /// The items to rank
+    pub items: Vec<String>,
+    /// Method to rank them by
+    pub method: PrioritizationMethod,
+    /// What the ranking is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal: Option<String>,
+    /// Criteria of a weighted ranking (required for `weighted`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criteria: Vec<PriorityCriterion>,
+    /// Optional background the estimates should take into account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for revising one estimate of a stored ranking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrioritizeReviseParams {
+    /// The ranking to revise
+    pub ranking_id: String,
+    /// The item whose estimate changes
+    pub item: String,
+    /// The estimate fields that change
+    pub estimate: EstimateRevision,
+    /// Why the estimate changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Estimate fields to replace; unset fields keep their value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EstimateRevision {
+    /// New RICE reach
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reach: Option<f64>,
+    /// New RICE impact (0-3)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<f64>,
+    /// New RICE confidence (0.0-1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// New RICE effort (greater than zero)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<f64>,
+    /// New MoSCoW category
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<MoscowCategory>,
+    /// New MoSCoW value (0-10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// New criterion scores (0-10) by criterion name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub criteria_scores: BTreeMap<String, f64>,
+}
+
+/// Estimated inputs of one item
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ItemEstimate {
+    /// RICE reach (people or events per period)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reach: Option<f64>,
+    /// RICE impact (0.25-3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impact: Option<f64>,
+    /// RICE confidence (0.0-1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// RICE effort (person-months)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<f64>,
+    /// MoSCoW category
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<MoscowCategory>,
+    /// Value within the MoSCoW category (0-10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// Weighted-ranking scores (0-10) by criterion name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub criteria_scores: BTreeMap<String, f64>,
+    /// Why the estimates were chosen
+    #[serde(default)]
+    pub rationale: String,
+}
+
+/// One item of a ranking
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RankedItem {
+    /// Position in the ranking (1 = highest priority)
+    pub rank: usize,
+    /// The item
+    pub item: String,
+    /// Score computed from the estimate (the value for MoSCoW)
+    pub score: f64,
+    /// The estimate the score was computed from
+    pub estimate: ItemEstimate,
+}
+
+/// An item whose rank changed in a revision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RankChange {
+    /// The item
+    pub item: String,
+    /// Rank in the ranking that was revised
+    pub from: usize,
+    /// Rank in the new ranking
+    pub to: usize,
+}
+
+/// Result of prioritization
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrioritizeResult {
+    /// Stored ranking ID
+    pub ranking_id: String,
+    /// The session ID
+    pub session_id: String,
+    /// Method the items were ranked by
+    pub method: PrioritizationMethod,
+    /// What the ranking is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal: Option<String>,
+    /// Criteria of a weighted ranking
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criteria: Vec<PriorityCriterion>,
+    /// Items, highest priority first
+    pub ranking: Vec<RankedItem>,
+    /// Ranking this one revises
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_ranking_id: Option<String>,
+    /// Items whose rank changed from the revised ranking
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moved: Vec<RankChange>,
+}
+
+/// Prioritization mode handler.
+#[derive(Clone)]
+pub struct PrioritizationMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Decision framework pipe estimating the inputs
+    decision_pipe: String,
+}
+
+impl PrioritizationMode {
+    /// Create a new prioritization mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            decision_pipe,
+        }
+    }
+
+    /// Estimate and rank items
+    pub async fn prioritize(&self, params: PrioritizeParams) -> AppResult<PrioritizeResult> {
+        let start = Instant::now();
+
+        let items = validate_items(&params.items)?;
+        validate_criteria(params.method, &params.criteria)?;
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let session = storage
+            .get_or_create_session(&params.session_id, "prioritization")
+            .await?;
+        debug!(session_id = %session.id, method = params.method.as_str(), "Processing prioritization request");
+
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.prioritize",
+            serialize_for_log(&params, "reasoning.prioritize input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(PRIORITIZATION_ESTIMATE_PROMPT),
+            Message::user(estimate_prompt(&params, &items)),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .and_then(|parsed| {
+                    match_estimates(params.method, &params.criteria, &items, parsed.estimates)
+                })
+                .map(|estimates| (estimates, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let (estimates, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+
+        let ranking = rank(params.method, &params.criteria, estimates);
+        let mut metadata = serde_json::json!({"prioritization": true});
+        if let Some(ref context) = params.context {
+            metadata["context"] = serde_json::json!(context);
+        }
+        let stored = to_decision(
+            &session.id,
+            params.goal.as_deref(),
+            params.method,
+            &params.criteria,
+            &items,
+            &ranking,
+            metadata,
+        );
+        storage.create_decision(&stored).await?;
+
+        let result = PrioritizeResult {
+            ranking_id: stored.id,
+            session_id: session.id.clone(),
+            method: params.method,
+            goal: params.goal,
+            criteria: params.criteria,
+            ranking,
+            previous_ranking_id: None,
+            moved: Vec::new(),
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.prioritize output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            ranking_id = %result.ranking_id,
+            method = params.method.as_str(),
+            items = result.ranking.len(),
+            latency_ms = latency,
+            "Prioritization completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Revise one estimate of a stored ranking and rank again, locally
+    pub async fn revise(&self, params: PrioritizeReviseParams) -> AppResult<PrioritizeResult> {
+        let storage = self.core.storage();
+        let previous = storage
+            .get_decision(&params.ranking_id)
+            .await?
+            .ok_or_else(|| ToolError::Validation {
+                field: "ranking_id".to_string(),
+                reason: format!("Ranking not found: {}", params.ranking_id),
+            })?;
+        let (method, criteria, ranking) =
+            from_decision(&previous).ok_or_else(|| ToolError::Validation {
+                field: "ranking_id".to_string(),
+                reason: format!("Not a prioritization ranking: {}", params.ranking_id),
+            })?;
+
+        let target = ranking
+            .iter()
+            .position(|r| r.item.eq_ignore_ascii_case(params.item.trim()))
+            .ok_or_else(|| ToolError::Validation {
+                field: "item".to_string(),
+                reason: format!("Item not in ranking: {}", params.item),
+            })?;
+        let mut estimates: Vec<(String, ItemEstimate)> = ranking
+            .iter()
+            .map(|r| (r.item.clone(), r.estimate.clone()))
+            .collect();
+        apply_revision(
+            method,
+            &criteria,
+            &mut estimates[target].1,
+            &params.estimate,
+        )?;
+        let item = estimates[target].0.clone();
+
+        let revised = rank(method, &criteria, estimates);
+        let moved = rank_changes(&ranking, &revised);
+
+        let mut metadata = serde_json::json!({
+            "prioritization": true,
+            "previous_ranking_id": previous.id,
+            "revised_item": item,
+            "revision": params.estimate,
+        });
+        if let Some(ref reason) = params.reason {
+            metadata["reason"] = serde_json::json!(reason);
+        }
+        let stored = to_decision(
+            &previous.session_id,
+            Some(&previous.question),
+            method,
+            &criteria,
+            &previous.options,
+            &revised,
+            metadata,
+        );
+        storage.create_decision(&stored).await?;
+
+        info!(
+            session_id = %stored.session_id,
+            ranking_id = %stored.id,
+            previous_ranking_id = %previous.id,
+            moved = moved.len(),
+            "Prioritization revised"
+        );
+
+        Ok(PrioritizeResult {
+            ranking_id: stored.id,
+            session_id: stored.session_id,
+            method,
+            goal: Some(previous.question),
+            criteria,
+            ranking: revised,
+            previous_ranking_id: Some(previous.id),
+            moved,
+        })
+    }
+}
+
+/// Trimmed items in order, rejecting empty and duplicate entries
+fn validate_items(items: &[String]) -> AppResult<Vec<String>> {
+    if items.len() < 2 || items.len() > MAX_PRIORITIZATION_ITEMS {
+        return Err(ToolError::Validation {
+            field: "items".to_string(),
+            reason: format!(
+                "Between 2 and {} items are required",
+                MAX_PRIORITIZATION_ITEMS
+            ),
+        }
+        .into());
+    }
+    let mut seen = HashSet::new();
+    let mut trimmed = Vec::with_capacity(items.len());
+    for item in items {
+        let item = item.trim();
+        if item.is_empty() {
+            return Err(ToolError::Validation {
+                field: "items".to_string(),
+                reason: "Items cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if !seen.insert(item.to_lowercase()) {
+            return Err(ToolError::Validation {
+                field: "items".to_string(),
+                reason: format!("Duplicate item: {}", item),
+            }
+            .into());
+        }
+        trimmed.push(item.to_string());
+    }
+    Ok(trimmed)
+}
+
+/// Weighted rankings need named criteria with positive weights; the other
+/// methods take none
+fn validate_criteria(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+) -> AppResult<()> {
+    let reason = match method {
+        PrioritizationMethod::Weighted if criteria.is_empty() => {
+            Some("Weighted ranking requires at least one criterion".to_string())
+        }
+        PrioritizationMethod::Weighted => criteria
+            .iter()
+            .find(|c| c.name.trim().is_empty() || c.weight <= 0.0)
+            .map(|c| format!("Criterion needs a name and a positive weight: '{}'", c.name)),
+        _ if !criteria.is_empty() => Some(format!(
+            "Criteria are only used by weighted ranking, not {}",
+            method.as_str()
+        )),
+        _ => None,
+    };
+    match reason {
+        Some(reason) => Err(ToolError::Validation {
+            field: "criteria".to_string(),
+            reason,
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+fn estimate_prompt(params: &PrioritizeParams, items: &[String]) -> String {
+    let mut prompt = format!("METHOD: {}", params.method.as_str());
+    if let Some(ref goal) = params.goal {
+        prompt.push_str(&format!("\nGOAL: {}", goal));
+    }
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+    }
+    if !params.criteria.is_empty() {
+        prompt.push_str("\n\nCRITERIA:");
+        for criterion in &params.criteria {
+            prompt.push_str(&format!("\n- {}", criterion.name.trim()));
+            if let Some(ref description) = criterion.description {
+                prompt.push_str(&format!(": {}", description));
+            }
+        }
+    }
+    prompt.push_str("\n\nITEMS:");
+    for item in items {
+        prompt.push_str(&format!("\n- {}", item));
+    }
+    prompt
+}
+
+fn parse_response(completion: &str) -> AppResult<EstimatesResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    serde_json::from_str(json_str).map_err(|e| {
+        ToolError::Reasoning {
+            message: format!("Failed to parse prioritization estimates: {}", e),
+        }
+        .into()
+    })
+}
+
+/// Pair every item with its estimate, normalized for the method; estimates
+/// of unlisted items are dropped and a missing estimate or input is an error
+fn match_estimates(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    items: &[String],
+    estimates: Vec<EstimateEntry>,
+) -> AppResult<Vec<(String, ItemEstimate)>> {
+    let mut matched = Vec::with_capacity(items.len());
+    for item in items {
+        let estimate = estimates
+            .iter()
+            .find(|e| e.item.trim().eq_ignore_ascii_case(item))
+            .map(|e| e.estimate.clone())
+            .ok_or_else(|| ToolError::Reasoning {
+                message: format!("No estimate for item: {}", item),
+            })?;
+        let estimate =
+            normalize(method, criteria, estimate).map_err(|missing| ToolError::Reasoning {
+                message: format!("Estimate for '{}' has no {}", item, missing),
+            })?;
+        matched.push((item.clone(), estimate));
+    }
+    Ok(matched)
+}
+
+/// Clamp the inputs of the method into range; `Err` names a missing input
+fn normalize(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    mut estimate: ItemEstimate,
+) -> Result<ItemEstimate, String> {
+    match method {
+        PrioritizationMethod::Rice => {
+            let reach = estimate.reach.ok_or("reach")?;
+            let impact = estimate.impact.ok_or("impact")?;
+            let confidence = estimate.confidence.ok_or("confidence")?;
+            let effort = estimate.effort.ok_or("effort")?;
+            if effort <= 0.0 {
+                return Err("positive effort".to_string());
+            }
+            estimate.reach = Some(reach.max(0.0));
+            estimate.impact = Some(impact.clamp(0.0, MAX_RICE_IMPACT));
+            estimate.confidence = Some(confidence.clamp(0.0, 1.0));
+        }
+        PrioritizationMethod::Moscow => {
+            estimate.category.ok_or("category")?;
+            estimate.value = Some(estimate.value.unwrap_or(0.0).clamp(0.0, MAX_ITEM_SCORE));
+        }
+        PrioritizationMethod::Weighted => {
+            let mut scores = BTreeMap::new();
+            for criterion in criteria {
+                let name = criterion.name.trim();
+                let score = estimate
+                    .criteria_scores
+                    .iter()
+                    .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+                    .map(|(_, v)| *v)
+                    .ok_or_else(|| format!("score for criterion '{}'", name))?;
+                scores.insert(name.to_string(), score.clamp(0.0, MAX_ITEM_SCORE));
+            }
+            estimate.criteria_scores = scores;
+        }
+    }
+    Ok(estimate)
+}
+
+/// Score of a normalized estimate
+fn score(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    estimate: &ItemEstimate,
+) -> f64 {
+    match method {
+        PrioritizationMethod::Rice => {
+            let reach = estimate.reach.unwrap_or(0.0);
+            let impact = estimate.impact.unwrap_or(0.0);
+            let confidence = estimate.confidence.unwrap_or(0.0);
+            match estimate.effort {
+                Some(effort) if effort > 0.0 => reach * impact * confidence / effort,
+                _ => 0.0,
+            }
+        }
+        PrioritizationMethod::Moscow => estimate.value.unwrap_or(0.0),
+        PrioritizationMethod::Weighted => {
+            let total: f64 = criteria.iter().map(|c| c.weight).sum();
+            if total <= 0.0 {
+                return 0.0;
+            }
+            criteria
+                .iter()
+                .map(|c| {
+                    c.weight
+                        * estimate
+                            .criteria_scores
+                            .get(c.name.trim())
+                            .copied()
+                            .unwrap_or(0.0)
+                })
+                .sum::<f64>()
+                / total
+        }
+    }
+}
+
+/// Rank items, highest priority first; ties keep the given order
+fn rank(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    estimates: Vec<(String, ItemEstimate)>,
+) -> Vec<RankedItem> {
+    let mut ranked: Vec<RankedItem> = estimates
+        .into_iter()
+        .map(|(item, estimate)| RankedItem {
+            rank: 0,
+            score: score(method, criteria, &estimate),
+            item,
+            estimate,
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        let by_category = match method {
+            PrioritizationMethod::Moscow => a.estimate.category.cmp(&b.estimate.category),
+            _ => std::cmp::Ordering::Equal,
+        };
+        by_category.then(
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal),
+        )
+    });
+    for (index, item) in ranked.iter_mut().enumerate() {
+        item.rank = index + 1;
+    }
+    ranked
+}
+
+/// Items whose rank differs between two rankings of the same items, in new
+/// rank order
+fn rank_changes(before: &[RankedItem], after: &[RankedItem]) -> Vec<RankChange> {
+    after
+        .iter()
+        .filter_map(|now| {
+            before
+                .iter()
+                .find(|was| was.item == now.item && was.rank != now.rank)
+                .map(|was| RankChange {
+                    item: now.item.clone(),
+                    from: was.rank,
+                    to: now.rank,
+                })
+        })
+        .collect()
+}
+
+/// Replace the revised fields of an estimate, rejecting fields the method
+/// does not use and values out of range
+fn apply_revision(
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    estimate: &mut ItemEstimate,
+    revision: &EstimateRevision,
+) -> AppResult<()> {
+    let invalid = |field: &str, reason: String| -> crate::error::AppError {
+        ToolError::Validation {
+            field: format!("estimate.{}", field),
+            reason,
+        }
+        .into()
+    };
+    let unused = |field: &str| invalid(field, format!("Not used by {} ranking", method.as_str()));
+
+    let rice = method == PrioritizationMethod::Rice;
+    let moscow = method == PrioritizationMethod::Moscow;
+    let mut changed = false;
+    for (field, value, used) in [
+        ("reach", revision.reach, rice),
+        ("impact", revision.impact, rice),
+        ("confidence", revision.confidence, rice),
+        ("effort", revision.effort, rice),
+        ("value", revision.value, moscow),
+    ] {
+        let Some(value) = value else { continue };
+        if !used {
+            return Err(unused(field));
+        }
+        let in_range = match field {
+            "reach" => value >= 0.0,
+            "impact" => (0.0..=MAX_RICE_IMPACT).contains(&value),
+            "confidence" => (0.0..=1.0).contains(&value),
+            "effort" => value > 0.0,
+            _ => (0.0..=MAX_ITEM_SCORE).contains(&value),
+        };
+        if !in_range {
+            return Err(invalid(field, format!("Out of range: {}", value)));
+        }
+        match field {
+            "reach" => estimate.reach = Some(value),
+            "impact" => estimate.impact = Some(value),
+            "confidence" => estimate.confidence = Some(value),
+            "effort" => estimate.effort = Some(value),
+            _ => estimate.value = Some(value),
+        }
+        changed = true;
+    }
+    if let Some(category) = revision.category {
+        if !moscow {
+            return Err(unused("category"));
+        }
+        estimate.category = Some(category);
+        changed = true;
+    }
+    for (name, value) in &revision.criteria_scores {
+        if method != PrioritizationMethod::Weighted {
+            return Err(unused("criteria_scores"));
+        }
+        let criterion = criteria
+            .iter()
+            .find(|c| c.name.trim().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| invalid("criteria_scores", format!("Unknown criterion: {}", name)))?;
+        if !(0.0..=MAX_ITEM_SCORE).contains(value) {
+            return Err(invalid(
+                "criteria_scores",
+                format!("Out of range: {}", value),
+            ));
+        }
+        estimate
+            .criteria_scores
+            .insert(criterion.name.trim().to_string(), *value);
+        changed = true;
+    }
+    if !changed {
+        return Err(ToolError::Validation {
+            field: "estimate".to_string(),
+            reason: "No estimate field to revise".to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+fn to_decision(
+    session_id: &str,
+    goal: Option<&str>,
+    method: PrioritizationMethod,
+    criteria: &[PriorityCriterion],
+    items: &[String],
+    ranking: &[RankedItem],
+    metadata: serde_json::Value,
+) -> Decision {
+    let question = goal
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Prioritize {} items", items.len()));
+    let recommendation = ranking
+        .first()
+        .map(|top| {
+            serde_json::json!({
+                "option": top.item,
+                "score": top.score,
+                "rationale": top.estimate.rationale,
+            })
+        })
+        .unwrap_or_default();
+    let mut decision = Decision::new(
+        session_id,
+        question,
+        items.to_vec(),
+        method.stored_name(),
+        recommendation,
+        serde_json::to_value(ranking).unwrap_or_default(),
+    )
+    .with_metadata(metadata);
+    if !criteria.is_empty() {
+        decision = decision.with_criteria(
+            criteria
+                .iter()
+                .map(|c| StoredCriterion {
+                    name: c.name.trim().to_string(),
+                    weight: c.weight,
+                    description: c.description.clone(),
+                })
+                .collect(),
+        );
+    }
+    decision
+}
+
+/// Method, criteria and ranking of a stored prioritization; `None` for
+/// decisions made by other tools
+fn from_decision(
+    decision: &Decision,
+) -> Option<(
+    PrioritizationMethod,
+    Vec<PriorityCriterion>,
+    Vec<RankedItem>,
+)> {
+    let marked = decision
+        .metadata
+        .as_ref()
+        .and_then(|m| m["prioritization"].as_bool())
+        .unwrap_or(false);
+    if !marked {
+        return None;
+    }
+    let method = PrioritizationMethod::from_stored_name(&decision.method)?;
+    let ranking: Vec<RankedItem> = serde_json::from_value(decision.scores.clone()).ok()?;
+    let criteria = decision
+        .criteria
+        .iter()
+        .flatten()
+        .map(|c| PriorityCriterion {
+            name: c.name.clone(),
+            weight: c.weight,
+            description: c.description.clone(),
+        })
+        .collect();
+    Some((method, criteria, ranking))
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Deserialize)]
+struct EstimatesResponse {
+    #[serde(default)]
+    estimates: Vec<EstimateEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EstimateEntry {
+    item: String,
+    #[serde(flatten)]
+    estimate: ItemEstimate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rice(reach: f64, impact: f64, confidence: f64, effort: f64) -> ItemEstimate {
+        ItemEstimate {
+            reach: Some(reach),
+            impact: Some(impact),
+            confidence: Some(confidence),
+            effort: Some(effort),
+            ..Default::default()
+        }
+    }
+
+    fn criterion(name: &str, weight: f64) -> PriorityCriterion {
+        PriorityCriterion {
+            name: name.to_string(),
+            weight,
+            description: None,
+        }
+    }
+
+    fn order(ranking: &[RankedItem]) -> Vec<&str> {
+        ranking.iter().map(|r| r.item.as_str()).collect()
+    }
+
+    #[test]
+    fn test_params_deserialize() {
+        let params: PrioritizeParams = serde_json::from_value(json!({
+            "items": ["A", "B"],
+            "method": "moscow"
+        }))
+        .unwrap();
+        assert_eq!(params.method, PrioritizationMethod::Moscow);
+        assert!(params.criteria.is_empty());
+
+        let revise: PrioritizeReviseParams = serde_json::from_value(json!({
+            "ranking_id": "r1",
+            "item": "A",
+            "estimate": {"category": "won't"}
+        }))
+        .unwrap();
+        assert_eq!(revise.estimate.category, Some(MoscowCategory::Wont));
+    }
+
+    #[test]
+    fn test_stored_method_names() {
+        for method in [
+            PrioritizationMethod::Rice,
+            PrioritizationMethod::Moscow,
+            PrioritizationMethod::Weighted,
+        ] {
+            assert_eq!(
+                PrioritizationMethod::from_stored_name(method.stored_name()),
+                Some(method)
+            );
+        }
+        assert_eq!(PrioritizationMethod::from_stored_name("topsis"), None);
+    }
+
+    #[test]
+    fn test_validate_items() {
+        let items = vec![" A ".to_string(), "B".to_string()];
+        assert_eq!(validate_items(&items).unwrap(), ["A", "B"]);
+
+        let err = validate_items(&["A".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Between 2"), "{}", err);
+        let err = validate_items(&["A".to_string(), "a ".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Duplicate"), "{}", err);
+        let err = validate_items(&["A".to_string(), " ".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("empty"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_criteria() {
+        assert!(validate_criteria(PrioritizationMethod::Rice, &[]).is_ok());
+        assert!(validate_criteria(PrioritizationMethod::Weighted, &[]).is_err());
+        assert!(
+            validate_criteria(PrioritizationMethod::Weighted, &[criterion("cost", 0.0)]).is_err()
+        );
+        assert!(
+            validate_criteria(PrioritizationMethod::Moscow, &[criterion("cost", 1.0)]).is_err()
+        );
+        assert!(
+            validate_criteria(PrioritizationMethod::Weighted, &[criterion("cost", 2.0)]).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_rice_ranking() {
+        let ranking = rank(
+            PrioritizationMethod::Rice,
+            &[],
+            vec![
+                ("SSO".to_string(), rice(500.0, 3.0, 0.9, 4.0)),
+                ("Dark mode".to_string(), rice(2000.0, 1.0, 0.8, 2.0)),
+                ("Export".to_string(), rice(600.0, 0.5, 1.0, 0.5)),
+            ],
+        );
+        assert_eq!(order(&ranking), ["Dark mode", "Export", "SSO"]);
+        assert!((ranking[0].score - 800.0).abs() < 1e-9);
+        assert!((ranking[2].score - 337.5).abs() < 1e-9);
+        assert_eq!(ranking[0].rank, 1);
+        assert_eq!(ranking[2].rank, 3);
+    }
+
+    #[test]
+    fn test_moscow_ranking_orders_category_before_value() {
+        let estimate = |category, value| ItemEstimate {
+            category: Some(category),
+            value: Some(value),
+            ..Default::default()
+        };
+        let ranking = rank(
+            PrioritizationMethod::Moscow,
+            &[],
+            vec![
+                ("Could".to_string(), estimate(MoscowCategory::Could, 10.0)),
+                ("Must low".to_string(), estimate(MoscowCategory::Must, 2.0)),
+                ("Must high".to_string(), estimate(MoscowCategory::Must, 8.0)),
+                ("Wont".to_string(), estimate(MoscowCategory::Wont, 9.0)),
+            ],
+        );
+        assert_eq!(order(&ranking), ["Must high", "Must low", "Could", "Wont"]);
+    }
+
+    #[test]
+    fn test_weighted_ranking() {
+        let criteria = [criterion("value", 0.7), criterion("cost", 0.3)];
+        let estimate = |value, cost| ItemEstimate {
+            criteria_scores: [("value".to_string(), value), ("cost".to_string(), cost)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let ranking = rank(
+            PrioritizationMethod::Weighted,
+            &criteria,
+            vec![
+                ("A".to_string(), estimate(6.0, 8.0)),
+                ("B".to_string(), estimate(9.0, 4.0)),
+            ],
+        );
+        assert_eq!(order(&ranking), ["B", "A"]);
+        assert!((ranking[0].score - 7.5).abs() < 1e-9);
+        assert!((ranking[1].score - 6.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_normalize_clamps_and_requires_inputs() {
+        let estimate =
+            normalize(PrioritizationMethod::Rice, &[], rice(-5.0, 7.0, 1.5, 1.0)).unwrap();
+        assert_eq!(estimate.reach, Some(0.0));
+        assert_eq!(estimate.impact, Some(MAX_RICE_IMPACT));
+        assert_eq!(estimate.confidence, Some(1.0));
+
+        let missing = ItemEstimate {
+            effort: None,
+            ..rice(1.0, 1.0, 1.0, 1.0)
+        };
+        assert_eq!(
+            normalize(PrioritizationMethod::Rice, &[], missing).unwrap_err(),
+            "effort"
+        );
+        assert!(normalize(PrioritizationMethod::Rice, &[], rice(1.0, 1.0, 1.0, 0.0)).is_err());
+
+        let scored = ItemEstimate {
+            criteria_scores: [("Cost".to_string(), 12.0)].into_iter().collect(),
+            ..Default::default()
+        };
+        let estimate = normalize(
+            PrioritizationMethod::Weighted,
+            &[criterion("cost", 1.0)],
+            scored,
+        )
+        .unwrap();
+        assert_eq!(estimate.criteria_scores.get("cost"), Some(&MAX_ITEM_SCORE));
+        assert!(normalize(
+            PrioritizationMethod::Weighted,
+            &[criterion("risk", 1.0)],
+            ItemEstimate::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_match_estimates() {
+        let parsed = parse_response(
+            r#"{"estimates": [
+                {"item": "b", "category": "must", "rationale": "Needed"},
+                {"item": "Unlisted", "category": "could"},
+                {"item": "A", "category": "should", "value": 3}
+            ]}"#,
+        )
+        .unwrap();
+        let items = vec!["A".to_string(), "B".to_string()];
+        let matched =
+            match_estimates(PrioritizationMethod::Moscow, &[], &items, parsed.estimates).unwrap();
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched[1].0, "B");
+        assert_eq!(matched[1].1.category, Some(MoscowCategory::Must));
+        assert_eq!(matched[1].1.value, Some(0.0));
+        assert_eq!(matched[1].1.rationale, "Needed");
+
+        let parsed =
+            parse_response(r#"{"estimates": [{"item": "A", "category": "must"}]}"#).unwrap();
+        let err = match_estimates(PrioritizationMethod::Moscow, &[], &items, parsed.estimates)
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("No estimate for item: B"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_apply_revision() {
+        let mut estimate = rice(100.0, 1.0, 0.5, 1.0);
+        let revision = EstimateRevision {
+            reach: Some(400.0),
+            ..Default::default()
+        };
+        apply_revision(PrioritizationMethod::Rice, &[], &mut estimate, &revision).unwrap();
+        assert_eq!(estimate.reach, Some(400.0));
+
+        let wrong_method = EstimateRevision {
+            category: Some(MoscowCategory::Must),
+            ..Default::default()
+        };
+        let err = apply_revision(
+            PrioritizationMethod::Rice,
+            &[],
+            &mut estimate,
+            &wrong_method,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Not used by rice"), "{}", err);
+
+        let out_of_range = EstimateRevision {
+            confidence: Some(2.0),
+            ..Default::default()
+        };
+        assert!(apply_revision(
+            PrioritizationMethod::Rice,
+            &[],
+            &mut estimate,
+            &out_of_range
+        )
+        .is_err());
+        assert!(apply_revision(
+            PrioritizationMethod::Rice,
+            &[],
+            &mut estimate,
+            &EstimateRevision::default()
+        )
+        .is_err());
+
+        let criteria = [criterion("Cost", 1.0)];
+        let mut weighted = ItemEstimate::default();
+        let rescore = EstimateRevision {
+            criteria_scores: [("cost".to_string(), 3.0)].into_iter().collect(),
+            ..Default::default()
+        };
+        apply_revision(
+            PrioritizationMethod::Weighted,
+            &criteria,
+            &mut weighted,
+            &rescore,
+        )
+        .unwrap();
+        assert_eq!(weighted.criteria_scores.get("Cost"), Some(&3.0));
+    }
+
+    #[test]
+    fn test_rank_changes() {
+        let before = rank(
+            PrioritizationMethod::Rice,
+            &[],
+            vec![
+                ("A".to_string(), rice(300.0, 1.0, 1.0, 1.0)),
+                ("B".to_string(), rice(200.0, 1.0, 1.0, 1.0)),
+                ("C".to_string(), rice(100.0, 1.0, 1.0, 1.0)),
+            ],
+        );
+        let after = rank(
+            PrioritizationMethod::Rice,
+            &[],
+            vec![
+                ("A".to_string(), rice(300.0, 1.0, 1.0, 1.0)),
+                ("B".to_string(), rice(200.0, 1.0, 1.0, 1.0)),
+                ("C".to_string(), rice(900.0, 1.0, 1.0, 1.0)),
+            ],
+        );
+        let moved = rank_changes(&before, &after);
+        assert_eq!(moved.len(), 3);
+        assert_eq!(
+            moved[0],
+            RankChange {
+                item: "C".to_string(),
+                from: 3,
+                to: 1
+            }
+        );
+        assert!(rank_changes(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_decision_round_trip() {
+        let criteria = [criterion("cost", 1.0)];
+        let ranking = rank(
+            PrioritizationMethod::Weighted,
+            &criteria,
+            vec![(
+                "A".to_string(),
+                ItemEstimate {
+                    criteria_scores: [("cost".to_string(), 5.0)].into_iter().collect(),
+                    ..Default::default()
+                },
+            )],
+        );
+        let items = vec!["A".to_string()];
+        let decision = to_decision(
+            "s1",
+            None,
+            PrioritizationMethod::Weighted,
+            &criteria,
+            &items,
+            &ranking,
+            json!({"prioritization": true}),
+        );
+        assert_eq!(decision.method, "weighted_sum");
+        assert_eq!(decision.question, "Prioritize 1 items");
+        assert_eq!(decision.recommendation["option"], "A");
+
+        let (method, restored_criteria, restored) = from_decision(&decision).unwrap();
+        assert_eq!(method, PrioritizationMethod::Weighted);
+        assert_eq!(restored_criteria[0].name, "cost");
+        assert_eq!(restored[0].estimate.criteria_scores.get("cost"), Some(&5.0));
+
+        let unmarked = decision.with_metadata(json!({}));
+        assert!(from_decision(&unmarked).is_none());
+    }
+}
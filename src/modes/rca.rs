@@ -0,0 +1,607 @@
+//! Root cause analysis mode - 5 Whys and fishbone diagrams.
+//!
+//! This module traces a problem back to its root causes:
+//! - 5 Whys: asks "why" of the latest cause, one pipe call per level, until
+//!   the pipe calls a cause the root or the depth limit is reached
+//! - Fishbone: gathers causes per Ishikawa category in one call
+//! - Labels each cause proximate, contributing or root
+//! - Suggests corrective actions targeting the root causes
+//!
+//! Results are stored as a branch tree: one branch for the problem and one
+//! child branch per cause category holding that category's causes.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{emit_artifact, extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{RCA_ACTIONS_PROMPT, RCA_FISHBONE_PROMPT, RCA_WHY_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{Branch, Invocation, SharedStorage, Thought};
+
+/// Whys asked when the caller does not say.
+pub const DEFAULT_WHY_DEPTH: u32 = 5;
+
+/// Most whys one analysis may ask; each costs a pipe call.
+pub const MAX_WHY_DEPTH: u32 = 7;
+
+fn default_max_depth() -> u32 {
+    DEFAULT_WHY_DEPTH
+}
+
+/// Input parameters for root cause analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootCauseParams {
+    /// The problem to trace back
+    pub problem: String,
+    /// Technique to apply
+    #[serde(default)]
+    pub method: RcaMethod,
+    /// Most whys to ask with the 5 Whys method
+    #[serde(default = "default_max_depth")]
+    pub max_depth: u32,
+    /// Optional background on the problem
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Root cause analysis technique
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RcaMethod {
+    /// Ask "why" repeatedly, building one causal chain
+    #[default]
+    FiveWhys,
+    /// Gather causes per Ishikawa category
+    Fishbone,
+}
+
+/// How directly a cause produced the problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CauseKind {
+    /// Directly triggered the problem
+    Proximate,
+    /// Made the problem more likely
+    Contributing,
+    /// Underlying reason; fixing it prevents recurrence
+    Root,
+}
+
+/// One cause found by the analysis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IdentifiedCause {
+    /// Thought holding the cause
+    pub thought_id: String,
+    /// Branch of the cause's category
+    pub branch_id: String,
+    /// Ishikawa category, e.g. `process`
+    pub category: String,
+    /// The cause
+    pub cause: String,
+    /// How directly it produced the problem
+    pub kind: CauseKind,
+    /// Why level the cause was found at, from 1; always 1 for fishbone
+    pub depth: u32,
+    /// Confidence the cause holds (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// An action removing a cause
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CorrectiveAction {
+    /// What to do
+    pub action: String,
+    /// The cause it removes
+    pub addresses: String,
+    /// `high`, `medium` or `low`
+    pub priority: String,
+}
+
+/// Result of root cause analysis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RootCauseResult {
+    /// The session ID
+    pub session_id: String,
+    /// The problem analyzed
+    pub problem: String,
+    /// Technique applied
+    pub method: RcaMethod,
+    /// Branch holding the problem, parent of the category branches
+    pub problem_branch_id: String,
+    /// Every cause found; a 5 Whys chain is in the order asked
+    pub causes: Vec<IdentifiedCause>,
+    /// Summary of the root causes
+    pub summary: String,
+    /// Actions removing the causes, root causes first
+    pub corrective_actions: Vec<CorrectiveAction>,
+    /// Confidence in the analysis (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Root cause analysis mode handler.
+#[derive(Clone)]
+pub struct RcaMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Reflection pipe tracing causes
+    reflection_pipe: String,
+}
+
+impl RcaMode {
+    /// Create a new root cause analysis mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            reflection_pipe: config.pipes.reflection.clone(),
+        }
+    }
+
+    /// Trace a problem to its root causes and suggest corrective actions
+    pub async fn analyze(&self, params: RootCauseParams) -> AppResult<RootCauseResult> {
+        let start = Instant::now();
+
+        if params.problem.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "problem".to_string(),
+                reason: "Problem cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.max_depth == 0 || params.max_depth > MAX_WHY_DEPTH {
+            return Err(ToolError::Validation {
+                field: "max_depth".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_WHY_DEPTH),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "root_cause")
+            .await?;
+        debug!(session_id = %session.id, method = ?params.method, "Processing root cause analysis");
+
+        let pipe = self.core.select_pipe(&self.reflection_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.root_cause",
+            serialize_for_log(&params, "reasoning.root_cause input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let traced = match self.trace(&params, &pipe).await {
+            Ok(traced) => traced,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let confidence = traced.actions.confidence.clamp(0.0, 1.0);
+
+        // One branch for the problem, one child branch per cause category
+        let problem_branch = Branch::new(&session.id)
+            .with_name(format!(
+                "Root cause: {}",
+                params.problem.chars().take(30).collect::<String>()
+            ))
+            .with_confidence(confidence);
+        let problem_thought = Thought::new(&session.id, &params.problem, "root_cause")
+            .with_branch(&problem_branch.id)
+            .with_metadata(serde_json::json!({"is_problem": true}));
+
+        let mut category_branches: BTreeMap<String, Branch> = BTreeMap::new();
+        let mut causes = Vec::with_capacity(traced.causes.len());
+        let mut thoughts = vec![problem_thought.clone()];
+        let mut previous_thought_id = problem_thought.id.clone();
+        for (index, found) in traced.causes.into_iter().enumerate() {
+            let branch = category_branches
+                .entry(found.category.clone())
+                .or_insert_with(|| {
+                    Branch::new(&session.id)
+                        .with_parent(&problem_branch.id)
+                        .with_name(found.category.clone())
+                });
+            // A 5 Whys chain links each cause to the one it explains
+            let parent_id = match params.method {
+                RcaMethod::FiveWhys => previous_thought_id.clone(),
+                RcaMethod::Fishbone => problem_thought.id.clone(),
+            };
+            let thought = Thought::new(&session.id, &found.cause, "root_cause")
+                .with_branch(&branch.id)
+                .with_parent(&parent_id)
+                .with_confidence(found.confidence)
+                .with_metadata(serde_json::json!({
+                    "category": found.category,
+                    "kind": found.kind,
+                    "depth": found.depth
+                }));
+            previous_thought_id = thought.id.clone();
+
+            let cause = IdentifiedCause {
+                thought_id: thought.id.clone(),
+                branch_id: branch.id.clone(),
+                category: found.category,
+                cause: found.cause,
+                kind: found.kind,
+                depth: found.depth,
+                confidence: found.confidence,
+            };
+            emit_artifact("cause", index, &cause);
+            causes.push(cause);
+            thoughts.push(thought);
+        }
+
+        let ActionsResponse {
+            summary,
+            corrective_actions,
+            ..
+        } = traced.actions;
+        thoughts.push(
+            Thought::new(&session.id, &summary, "root_cause")
+                .with_branch(&problem_branch.id)
+                .with_parent(&problem_thought.id)
+                .with_confidence(confidence)
+                .with_metadata(serde_json::json!({
+                    "is_summary": true,
+                    "corrective_actions": corrective_actions
+                })),
+        );
+
+        self.core.storage().create_branch(&problem_branch).await?;
+        for branch in category_branches.values() {
+            self.core.storage().create_branch(branch).await?;
+        }
+        self.core.storage().create_thoughts_batch(&thoughts).await?;
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(traced.cache_hit)
+            .with_pipe_fallback(traced.fallback.as_deref())
+            .success(
+                serialize_for_log(&causes, "reasoning.root_cause output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            causes = causes.len(),
+            categories = category_branches.len(),
+            latency_ms = latency,
+            "Root cause analysis complete"
+        );
+
+        Ok(RootCauseResult {
+            session_id: session.id,
+            problem: params.problem,
+            method: params.method,
+            problem_branch_id: problem_branch.id,
+            causes,
+            summary,
+            corrective_actions,
+            confidence,
+        })
+    }
+
+    /// Find the causes with the requested method, then ask for actions
+    async fn trace(&self, params: &RootCauseParams, pipe: &str) -> AppResult<Traced> {
+        let mut traced = Traced {
+            causes: Vec::new(),
+            actions: ActionsResponse::default(),
+            cache_hit: true,
+            fallback: None,
+        };
+
+        match params.method {
+            RcaMethod::FiveWhys => {
+                for depth in 1..=params.max_depth {
+                    let prompt = why_prompt(params, &traced.causes);
+                    let why: WhyResponse = self
+                        .call(params, pipe, RCA_WHY_PROMPT, prompt, &mut traced)
+                        .await?;
+                    traced.causes.push(FoundCause {
+                        category: normalize_category(&why.category),
+                        cause: why.cause,
+                        kind: CauseKind::Contributing,
+                        depth,
+                        confidence: why.confidence.clamp(0.0, 1.0),
+                    });
+                    if why.is_root_cause {
+                        break;
+                    }
+                }
+                // The first answer is the proximate cause and the deepest the
+                // root, whether the pipe called it that or the depth ran out
+                if let Some(first) = traced.causes.first_mut() {
+                    first.kind = CauseKind::Proximate;
+                }
+                if let Some(last) = traced.causes.last_mut() {
+                    last.kind = CauseKind::Root;
+                }
+            }
+            RcaMethod::Fishbone => {
+                let fishbone: FishboneResponse = self
+                    .call(
+                        params,
+                        pipe,
+                        RCA_FISHBONE_PROMPT,
+                        problem_prompt(params),
+                        &mut traced,
+                    )
+                    .await?;
+                for category in fishbone.categories {
+                    let name = normalize_category(&category.category);
+                    traced
+                        .causes
+                        .extend(category.causes.into_iter().map(|c| FoundCause {
+                            category: name.clone(),
+                            cause: c.cause,
+                            kind: c.kind,
+                            depth: 1,
+                            confidence: c.confidence.clamp(0.0, 1.0),
+                        }));
+                }
+            }
+        }
+
+        if traced.causes.is_empty() {
+            return Err(ToolError::Reasoning {
+                message: "Root cause analysis found no causes".to_string(),
+            }
+            .into());
+        }
+
+        let prompt = actions_prompt(params, &traced.causes);
+        traced.actions = self
+            .call(params, pipe, RCA_ACTIONS_PROMPT, prompt, &mut traced)
+            .await?;
+        Ok(traced)
+    }
+
+    /// Call the pipe and parse its JSON completion
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        params: &RootCauseParams,
+        pipe: &str,
+        system_prompt: &str,
+        prompt: String,
+        traced: &mut Traced,
+    ) -> AppResult<T> {
+        let messages = vec![Message::system(system_prompt), Message::user(prompt)];
+        let request = PipeRequest::new(pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
+        traced.cache_hit &= response.cache_hit;
+        if traced.fallback.is_none() {
+            traced.fallback = response.fallback;
+        }
+
+        let json_str = extract_json_from_completion(&response.completion)
+            .map_err(|e| ToolError::Reasoning { message: e })?;
+        serde_json::from_str(json_str).map_err(|e| {
+            ToolError::Reasoning {
+                message: format!("Failed to parse root cause analysis: {}", e),
+            }
+            .into()
+        })
+    }
+}
+
+/// The problem and its context
+fn problem_prompt(params: &RootCauseParams) -> String {
+    let mut prompt = format!("PROBLEM: {}\n", params.problem);
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+    prompt
+}
+
+/// Prompt asking why the latest cause in `chain` happened
+fn why_prompt(params: &RootCauseParams, chain: &[FoundCause]) -> String {
+    let mut prompt = problem_prompt(params);
+    match chain.last() {
+        None => prompt.push_str("\nWhy did this problem happen?"),
+        Some(latest) => {
+            prompt.push_str("\nCAUSES FOUND SO FAR:\n");
+            for cause in chain {
+                prompt.push_str(&format!("{}. {}\n", cause.depth, cause.cause));
+            }
+            prompt.push_str(&format!("\nWhy did this happen: {}?", latest.cause));
+        }
+    }
+    prompt
+}
+
+/// Prompt asking for corrective actions for `causes`
+fn actions_prompt(params: &RootCauseParams, causes: &[FoundCause]) -> String {
+    let mut prompt = problem_prompt(params);
+    prompt.push_str("\nCAUSES:\n");
+    for cause in causes {
+        let kind = match cause.kind {
+            CauseKind::Proximate => "proximate",
+            CauseKind::Contributing => "contributing",
+            CauseKind::Root => "root",
+        };
+        prompt.push_str(&format!(
+            "- [{}, {}] {}\n",
+            cause.category, kind, cause.cause
+        ));
+    }
+    prompt.push_str("\nSuggest corrective actions.");
+    prompt
+}
+
+/// Lowercase category name, `other` when blank
+fn normalize_category(category: &str) -> String {
+    let category = category.trim().to_lowercase();
+    if category.is_empty() {
+        "other".to_string()
+    } else {
+        category
+    }
+}
+
+/// A cause before it is stored
+struct FoundCause {
+    category: String,
+    cause: String,
+    kind: CauseKind,
+    depth: u32,
+    confidence: f64,
+}
+
+/// Everything the pipe found before it is stored
+struct Traced {
+    causes: Vec<FoundCause>,
+    actions: ActionsResponse,
+    cache_hit: bool,
+    fallback: Option<String>,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WhyResponse {
+    cause: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    is_root_cause: bool,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FishboneResponse {
+    categories: Vec<FishboneCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FishboneCategory {
+    category: String,
+    causes: Vec<FishboneCause>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FishboneCause {
+    cause: String,
+    kind: CauseKind,
+    confidence: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ActionsResponse {
+    summary: String,
+    #[serde(default)]
+    corrective_actions: Vec<CorrectiveAction>,
+    confidence: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn found(depth: u32, cause: &str, kind: CauseKind) -> FoundCause {
+        FoundCause {
+            category: "process".to_string(),
+            cause: cause.to_string(),
+            kind,
+            depth,
+            confidence: 0.7,
+        }
+    }
+
+    #[test]
+    fn test_root_cause_params_defaults() {
+        let params: RootCauseParams =
+            serde_json::from_value(json!({"problem": "The deploy failed"})).unwrap();
+        assert_eq!(params.problem, "The deploy failed");
+        assert_eq!(params.method, RcaMethod::FiveWhys);
+        assert_eq!(params.max_depth, DEFAULT_WHY_DEPTH);
+        assert!(params.context.is_none());
+        assert!(params.session_id.is_none());
+    }
+
+    #[test]
+    fn test_root_cause_params_fishbone() {
+        let params: RootCauseParams = serde_json::from_value(json!({
+            "problem": "Late shipments",
+            "method": "fishbone",
+            "context": "Since March"
+        }))
+        .unwrap();
+        assert_eq!(params.method, RcaMethod::Fishbone);
+        assert_eq!(params.context.as_deref(), Some("Since March"));
+        assert!(
+            serde_json::from_value::<RootCauseParams>(json!({"problem": "P", "method": "6m"}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_why_prompt_asks_about_latest_cause() {
+        let params: RootCauseParams =
+            serde_json::from_value(json!({"problem": "The deploy failed"})).unwrap();
+        let opening = why_prompt(&params, &[]);
+        assert!(opening.starts_with("PROBLEM: The deploy failed"));
+        assert!(opening.ends_with("Why did this problem happen?"));
+
+        let chain = [
+            found(1, "A migration timed out", CauseKind::Proximate),
+            found(2, "The table was locked", CauseKind::Contributing),
+        ];
+        let prompt = why_prompt(&params, &chain);
+        assert!(prompt.contains("1. A migration timed out\n2. The table was locked"));
+        assert!(prompt.ends_with("Why did this happen: The table was locked?"));
+    }
+
+    #[test]
+    fn test_actions_prompt_labels_causes() {
+        let params: RootCauseParams =
+            serde_json::from_value(json!({"problem": "The deploy failed"})).unwrap();
+        let prompt = actions_prompt(&params, &[found(1, "No lock timeout", CauseKind::Root)]);
+        assert!(prompt.contains("- [process, root] No lock timeout"));
+    }
+
+    #[test]
+    fn test_normalize_category() {
+        assert_eq!(normalize_category(" Process "), "process");
+        assert_eq!(normalize_category(""), "other");
+    }
+
+    #[test]
+    fn test_parse_fishbone_response() {
+        let fishbone: FishboneResponse = serde_json::from_value(json!({
+            "categories": [{
+                "category": "equipment",
+                "causes": [{"cause": "Worn belt", "kind": "root", "confidence": 0.8}]
+            }]
+        }))
+        .unwrap();
+        assert_eq!(fishbone.categories[0].causes[0].kind, CauseKind::Root);
+
+        let why: WhyResponse =
+            serde_json::from_value(json!({"cause": "C", "confidence": 0.5})).unwrap();
+        assert!(!why.is_root_cause);
+        assert!(why.category.is_empty());
+    }
+}
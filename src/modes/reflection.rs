@@ -1,21 +1,27 @@
 //! Reflection reasoning mode - meta-cognitive analysis and quality improvement.
 //!
 //! This module provides reflection capabilities for analyzing and improving reasoning:
-//! - Iterative refinement with quality thresholds
+//! - Iterative critique and revision until quality meets a threshold or stops
+//!   improving
+//! - Scoring against caller-supplied rubrics of weighted dimensions
 //! - Strength and weakness identification
 //! - Improved thought generation
 //! - Session evaluation for overall reasoning quality
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
+use crate::langbase::{GenerationParams, Message, OutputSchema, PipeRequest};
 use crate::prompts::REFLECTION_PROMPT;
-use crate::storage::{Invocation, SqliteStorage, Storage, Thought};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
+use crate::storage::{Invocation, SharedStorage, Thought};
 
 /// Input parameters for reflection reasoning
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,9 +44,20 @@ pub struct ReflectionParams {
     /// Quality threshold to stop iterating (0.0-1.0)
     #[serde(default = "default_quality_threshold")]
     pub quality_threshold: f64,
+    /// Smallest quality gain over the best earlier round that keeps the
+    /// cycle going (0.0-1.0)
+    #[serde(default = "default_min_improvement")]
+    pub min_improvement: f64,
+    /// Weighted dimensions to score each version against; when set, the
+    /// quality score is their weighted mean
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rubric: Vec<RubricDimension>,
     /// Whether to include full reasoning chain in context
     #[serde(default)]
     pub include_chain: bool,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_max_iterations() -> usize {
@@ -51,6 +68,58 @@ fn default_quality_threshold() -> f64 {
     0.8
 }
 
+fn default_min_improvement() -> f64 {
+    0.02
+}
+
+fn default_dimension_weight() -> f64 {
+    1.0
+}
+
+/// Most dimensions a rubric may have.
+pub const MAX_RUBRIC_DIMENSIONS: usize = 8;
+
+/// A named quality dimension that reflection scores against.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RubricDimension {
+    /// Dimension name, e.g. "correctness".
+    pub name: String,
+    /// Relative weight of the dimension in the quality score.
+    #[serde(default = "default_dimension_weight")]
+    pub weight: f64,
+    /// What the dimension measures, shown to the model.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+impl RubricDimension {
+    /// Create a dimension with a name and weight
+    pub fn new(name: impl Into<String>, weight: f64) -> Self {
+        Self {
+            name: name.into(),
+            weight,
+            description: String::new(),
+        }
+    }
+
+    /// Describe what the dimension measures
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+/// Score of one rubric dimension.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DimensionScore {
+    /// Dimension name, as given in the rubric.
+    pub name: String,
+    /// Weight of the dimension.
+    pub weight: f64,
+    /// Score of the dimension (0.0-1.0).
+    pub score: f64,
+}
+
 /// Response from reflection reasoning Langbase pipe.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReflectionResponse {
@@ -70,13 +139,23 @@ pub struct ReflectionResponse {
     /// Optional improved version of the thought.
     #[serde(default)]
     pub improved_thought: Option<String>,
+    /// Scores of the rubric dimensions, by name (0.0-1.0).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rubric_scores: BTreeMap<String, f64>,
     /// Additional metadata from the response.
     #[serde(default)]
     pub metadata: serde_json::Value,
 }
 
+impl ReflectionResponse {
+    /// Latest output schema for the reflection pipe.
+    pub fn output_schema() -> OutputSchema {
+        SchemaKind::Reflection.latest()
+    }
+}
+
 /// Result of reflection reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ReflectionResult {
     /// The session ID.
     pub session_id: String,
@@ -103,10 +182,129 @@ pub struct ReflectionResult {
     /// Optional branch ID for tree mode integration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch_id: Option<String>,
+    /// Each critique and revision round, in order.
+    #[serde(default)]
+    pub rounds: Vec<ReflectionRound>,
+    /// Why the rounds stopped.
+    #[serde(default)]
+    pub stop_reason: ReflectionStop,
+    /// Rubric dimension scores of the best-scoring version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rubric_scores: Vec<DimensionScore>,
+}
+
+/// One critique and revision round of reflection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ReflectionRound {
+    /// Round number, from 1.
+    pub round: usize,
+    /// The version critiqued this round: the original, then each revision.
+    pub content: String,
+    /// Quality score of that version (0.0-1.0).
+    pub quality_score: f64,
+    /// Change in quality from the previous round, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub improvement: Option<f64>,
+    /// Weaknesses the critique found.
+    pub weaknesses: Vec<String>,
+    /// Rubric dimension scores of that version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rubric_scores: Vec<DimensionScore>,
+    /// The revision proposed this round, critiqued in the next one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+}
+
+/// Why multi-round reflection stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReflectionStop {
+    /// A version met the quality threshold.
+    ThresholdMet,
+    /// A revision scored less than `min_improvement` above the best
+    /// earlier version.
+    Converged,
+    /// The critique proposed no new revision.
+    NoRevision,
+    /// The round limit was reached.
+    #[default]
+    MaxRounds,
+}
+
+impl ReflectionStop {
+    /// Get the stop reason as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReflectionStop::ThresholdMet => "threshold_met",
+            ReflectionStop::Converged => "converged",
+            ReflectionStop::NoRevision => "no_revision",
+            ReflectionStop::MaxRounds => "max_rounds",
+        }
+    }
+}
+
+/// How one rubric dimension has scored across reflection calls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RubricTrend {
+    /// Dimension name.
+    pub dimension: String,
+    /// Number of critique rounds that scored the dimension.
+    pub samples: usize,
+    /// Mean score over those rounds.
+    pub average: f64,
+    /// Oldest score.
+    pub first: f64,
+    /// Most recent score.
+    pub latest: f64,
+    /// Change from the oldest to the most recent score.
+    pub change: f64,
+}
+
+/// Aggregate the rubric scores logged by reflection invocations into one
+/// trend per dimension, sorted by name. Invocations may be in any order.
+pub fn rubric_trends(invocations: &[Invocation]) -> Vec<RubricTrend> {
+    let mut ordered: Vec<&Invocation> = invocations
+        .iter()
+        .filter(|i| i.tool_name == "reasoning.reflection" && i.success)
+        .collect();
+    ordered.sort_by_key(|i| i.created_at);
+
+    let mut scores: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for invocation in ordered {
+        let Some(rubric) = invocation
+            .output
+            .as_ref()
+            .and_then(|o| o.get("rubric_scores"))
+            .and_then(|r| r.as_object())
+        else {
+            continue;
+        };
+        for (name, score) in rubric {
+            if let Some(score) = score.as_f64() {
+                scores.entry(name.clone()).or_default().push(score);
+            }
+        }
+    }
+
+    scores
+        .into_iter()
+        .map(|(dimension, scores)| {
+            let first = scores[0];
+            let latest = scores[scores.len() - 1];
+            RubricTrend {
+                dimension,
+                samples: scores.len(),
+                average: scores.iter().sum::<f64>() / scores.len() as f64,
+                first,
+                latest,
+                change: latest - first,
+            }
+        })
+        .collect()
 }
 
 /// Improved thought generated from reflection.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ImprovedThought {
     /// The ID of the improved thought.
     pub thought_id: String,
@@ -119,7 +317,7 @@ pub struct ImprovedThought {
 /// Reflection reasoning mode handler for meta-cognitive analysis.
 #[derive(Clone)]
 pub struct ReflectionMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for reflection.
     pipe_name: String,
@@ -127,9 +325,14 @@ pub struct ReflectionMode {
 
 impl ReflectionMode {
     /// Create a new reflection mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config.pipes.reflection.clone(),
         }
     }
@@ -146,6 +349,15 @@ impl ReflectionMode {
             }
             .into());
         }
+        params.generation.validate()?;
+        if !(0.0..=1.0).contains(&params.min_improvement) {
+            return Err(ToolError::Validation {
+                field: "min_improvement".to_string(),
+                reason: "Must be between 0.0 and 1.0".to_string(),
+            }
+            .into());
+        }
+        validate_rubric(&params.rubric)?;
 
         // Get or create session
         let session = self
@@ -179,18 +391,25 @@ impl ReflectionMode {
             Vec::new()
         };
 
-        // Perform iterative reflection
-        let max_iterations = params.max_iterations.min(5);
+        // Critique and revise until the quality meets the threshold or stops
+        // improving
+        let max_iterations = params.max_iterations.clamp(1, 5);
         let mut current_content = original_content.clone();
         let mut iterations_performed = 0;
-        let mut best_quality = 0.0;
+        let mut best_quality: Option<f64> = None;
+        let mut best_rubric_scores: Vec<DimensionScore> = Vec::new();
+        let mut rounds: Vec<ReflectionRound> = Vec::new();
+        let mut stop_reason = ReflectionStop::MaxRounds;
         let mut final_response: Option<ReflectionResponse> = None;
 
         for iteration in 0..max_iterations {
             iterations_performed = iteration + 1;
 
             // Build messages for Langbase
-            let messages = self.build_messages(&current_content, &context_chain, iteration);
+            let messages =
+                self.build_messages(&current_content, &context_chain, iteration, &params.rubric);
+
+            let pipe_name = self.core.select_pipe(&self.pipe_name);
 
             // Create invocation log
             let mut invocation = Invocation::new(
@@ -201,11 +420,16 @@ impl ReflectionMode {
                 }),
             )
             .with_session(&session.id)
-            .with_pipe(&self.pipe_name);
+            .with_pipe(&pipe_name);
 
             // Call Langbase pipe
-            let request = PipeRequest::new(&self.pipe_name, messages);
-            let response = match self.core.langbase().call_pipe(request).await {
+            let request = PipeRequest::new(&pipe_name, messages)
+                .with_output_schema(
+                    self.core
+                        .response_schema(SchemaKind::Reflection, &pipe_name),
+                )
+                .with_generation(params.generation);
+            let response = match self.core.backend().call_pipe(request).await {
                 Ok(resp) => resp,
                 Err(e) => {
                     let latency = start.elapsed().as_millis() as i64;
@@ -214,10 +438,26 @@ impl ReflectionMode {
                     return Err(e.into());
                 }
             };
+            invocation = invocation
+                .with_cache_hit(response.cache_hit)
+                .with_pipe_fallback(response.fallback.as_deref());
 
             // Parse response
-            let reflection = self.parse_response(&response.completion)?;
-            let quality = reflection.quality_score.unwrap_or(reflection.confidence);
+            let mut reflection = self.parse_response(&response.completion)?;
+            let rubric_scores = score_rubric(&params.rubric, &reflection.rubric_scores);
+            if rubric_scores.len() < params.rubric.len() {
+                warn!(
+                    scored = rubric_scores.len(),
+                    dimensions = params.rubric.len(),
+                    "Reflection left rubric dimensions unscored"
+                );
+            }
+            reflection.rubric_scores = rubric_scores
+                .iter()
+                .map(|s| (s.name.clone(), s.score))
+                .collect();
+            let quality = rubric_quality(&rubric_scores)
+                .unwrap_or_else(|| reflection.quality_score.unwrap_or(reflection.confidence));
 
             // Log invocation
             let latency = start.elapsed().as_millis() as i64;
@@ -227,29 +467,42 @@ impl ReflectionMode {
             );
             self.core.storage().log_invocation(&invocation).await?;
 
-            // Check if quality threshold met
-            if quality >= params.quality_threshold {
+            let revision = reflection
+                .improved_thought
+                .clone()
+                .filter(|r| r.trim() != current_content.trim());
+            rounds.push(ReflectionRound {
+                round: iterations_performed,
+                content: current_content.clone(),
+                quality_score: quality,
+                improvement: rounds.last().map(|r| quality - r.quality_score),
+                weaknesses: reflection.weaknesses.clone(),
+                rubric_scores: rubric_scores.clone(),
+                revision: revision.clone(),
+            });
+            let stop = round_stop(quality, best_quality, revision.is_some(), &params);
+            if best_quality.map_or(true, |best| quality > best) {
+                best_quality = Some(quality);
+                best_rubric_scores = rubric_scores;
+            }
+            final_response = Some(reflection);
+
+            if let Some(stop) = stop {
                 debug!(
                     iteration = iteration,
                     quality = quality,
                     threshold = params.quality_threshold,
-                    "Quality threshold met, stopping iterations"
+                    reason = stop.as_str(),
+                    "Stopping reflection rounds"
                 );
-                best_quality = quality;
-                final_response = Some(reflection);
+                stop_reason = stop;
                 break;
             }
-
-            // Update for next iteration if improved thought available
-            if let Some(ref improved) = reflection.improved_thought {
-                if quality > best_quality {
-                    best_quality = quality;
-                    current_content = improved.clone();
-                }
+            if let Some(revision) = revision {
+                current_content = revision;
             }
-
-            final_response = Some(reflection);
         }
+        let best_quality = best_quality.unwrap_or(0.0);
 
         let reflection = final_response.ok_or_else(|| ToolError::Reasoning {
             message: "No reflection response generated".to_string(),
@@ -263,7 +516,10 @@ impl ReflectionMode {
                 "weaknesses": reflection.weaknesses,
                 "recommendations": reflection.recommendations,
                 "quality_score": best_quality,
-                "iterations": iterations_performed
+                "iterations": iterations_performed,
+                "round_scores": rounds.iter().map(|r| r.quality_score).collect::<Vec<_>>(),
+                "stop_reason": stop_reason.as_str(),
+                "rubric_scores": best_rubric_scores
             }));
 
         let reflection_thought = if let Some(ref thought) = original_thought {
@@ -324,6 +580,7 @@ impl ReflectionMode {
             session_id = %session.id,
             reflection_id = %reflection_thought.id,
             iterations = iterations_performed,
+            stop_reason = stop_reason.as_str(),
             quality_score = best_quality,
             quality_improved = quality_improved,
             latency_ms = start.elapsed().as_millis(),
@@ -343,6 +600,9 @@ impl ReflectionMode {
             iterations_performed,
             quality_improved,
             branch_id: params.branch_id,
+            rounds,
+            stop_reason,
+            rubric_scores: best_rubric_scores,
         })
     }
 
@@ -441,7 +701,13 @@ impl ReflectionMode {
         (link_ratio + confidence_stability) / 2.0
     }
 
-    fn build_messages(&self, content: &str, chain: &[Thought], iteration: usize) -> Vec<Message> {
+    fn build_messages(
+        &self,
+        content: &str,
+        chain: &[Thought],
+        iteration: usize,
+        rubric: &[RubricDimension],
+    ) -> Vec<Message> {
         let mut messages = Vec::new();
 
         // Enhanced system prompt for iteration
@@ -452,6 +718,9 @@ impl ReflectionMode {
                 iteration + 1
             ));
         }
+        if !rubric.is_empty() {
+            system_prompt.push_str(&rubric_prompt(rubric));
+        }
 
         messages.push(Message::system(system_prompt));
 
@@ -503,6 +772,106 @@ impl ReflectionMode {
     }
 }
 
+/// Check that rubric dimensions are named uniquely and weighted positively
+fn validate_rubric(rubric: &[RubricDimension]) -> AppResult<()> {
+    if rubric.len() > MAX_RUBRIC_DIMENSIONS {
+        return Err(ToolError::Validation {
+            field: "rubric".to_string(),
+            reason: format!("At most {} dimensions allowed", MAX_RUBRIC_DIMENSIONS),
+        }
+        .into());
+    }
+    for (i, dimension) in rubric.iter().enumerate() {
+        let name = dimension.name.trim();
+        if name.is_empty() {
+            return Err(ToolError::Validation {
+                field: format!("rubric[{}].name", i),
+                reason: "Dimension name must not be empty".to_string(),
+            }
+            .into());
+        }
+        if rubric[..i]
+            .iter()
+            .any(|d| d.name.trim().eq_ignore_ascii_case(name))
+        {
+            return Err(ToolError::Validation {
+                field: format!("rubric[{}].name", i),
+                reason: format!("Duplicate dimension: {}", name),
+            }
+            .into());
+        }
+        if dimension.weight <= 0.0 || !dimension.weight.is_finite() {
+            return Err(ToolError::Validation {
+                field: format!("rubric[{}].weight", i),
+                reason: "Weight must be a positive number".to_string(),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// System prompt addition asking for a score on every rubric dimension
+fn rubric_prompt(rubric: &[RubricDimension]) -> String {
+    let dimensions: Vec<String> = rubric
+        .iter()
+        .map(|d| match d.description.trim() {
+            "" => format!("- {} (weight {})", d.name.trim(), d.weight),
+            description => format!("- {} (weight {}): {}", d.name.trim(), d.weight, description),
+        })
+        .collect();
+    format!(
+        "\n\nScore the thought on each of these rubric dimensions:\n{}\n\nAdd a \"rubric_scores\" object to your JSON mapping every dimension name to a score from 0.0 to 1.0.",
+        dimensions.join("\n")
+    )
+}
+
+/// Match the scores a critique returned to the rubric's dimensions, in rubric
+/// order; dimensions it left unscored are omitted
+fn score_rubric(rubric: &[RubricDimension], scores: &BTreeMap<String, f64>) -> Vec<DimensionScore> {
+    rubric
+        .iter()
+        .filter_map(|dimension| {
+            let name = dimension.name.trim();
+            scores
+                .iter()
+                .find(|(key, score)| key.trim().eq_ignore_ascii_case(name) && score.is_finite())
+                .map(|(_, score)| DimensionScore {
+                    name: name.to_string(),
+                    weight: dimension.weight,
+                    score: score.clamp(0.0, 1.0),
+                })
+        })
+        .collect()
+}
+
+/// Weighted mean of the scored dimensions, if any were scored
+fn rubric_quality(scores: &[DimensionScore]) -> Option<f64> {
+    let total_weight: f64 = scores.iter().map(|s| s.weight).sum();
+    (total_weight > 0.0)
+        .then(|| scores.iter().map(|s| s.score * s.weight).sum::<f64>() / total_weight)
+}
+
+/// Whether to stop after a round whose version scored `quality`, given the
+/// best score of the earlier rounds and whether the round proposed a revision
+fn round_stop(
+    quality: f64,
+    best_earlier: Option<f64>,
+    has_revision: bool,
+    params: &ReflectionParams,
+) -> Option<ReflectionStop> {
+    if quality >= params.quality_threshold {
+        return Some(ReflectionStop::ThresholdMet);
+    }
+    if best_earlier.is_some_and(|best| quality < best + params.min_improvement) {
+        return Some(ReflectionStop::Converged);
+    }
+    if !has_revision {
+        return Some(ReflectionStop::NoRevision);
+    }
+    None
+}
+
 /// Session evaluation result showing overall reasoning quality.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionEvaluation {
@@ -530,7 +899,10 @@ impl ReflectionParams {
             branch_id: None,
             max_iterations: default_max_iterations(),
             quality_threshold: default_quality_threshold(),
+            min_improvement: default_min_improvement(),
+            rubric: Vec::new(),
             include_chain: false,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -543,7 +915,10 @@ impl ReflectionParams {
             branch_id: None,
             max_iterations: default_max_iterations(),
             quality_threshold: default_quality_threshold(),
+            min_improvement: default_min_improvement(),
+            rubric: Vec::new(),
             include_chain: false,
+            generation: GenerationParams::default(),
         }
     }
 
@@ -571,16 +946,36 @@ impl ReflectionParams {
         self
     }
 
+    /// Set the smallest quality gain that keeps the rounds going
+    pub fn with_min_improvement(mut self, min_improvement: f64) -> Self {
+        self.min_improvement = min_improvement.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Score against a rubric of weighted dimensions
+    pub fn with_rubric(mut self, rubric: Vec<RubricDimension>) -> Self {
+        self.rubric = rubric;
+        self
+    }
+
     /// Include reasoning chain context
     pub fn with_chain(mut self) -> Self {
         self.include_chain = true;
         self
     }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langbase::LangbaseClient;
+    use crate::storage::SqliteStorage;
 
     // ============================================================================
     // Default Function Tests
@@ -756,6 +1151,7 @@ mod tests {
             confidence: 0.85,
             quality_score: Some(0.9),
             improved_thought: Some("Improved version".to_string()),
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::json!({"key": "value"}),
         };
 
@@ -793,6 +1189,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reflection_output_schema() {
+        let schema = ReflectionResponse::output_schema();
+        let valid = r#"{
+            "analysis": "Analysis text",
+            "strengths": ["S1"],
+            "weaknesses": [],
+            "recommendations": ["R1"],
+            "confidence": 0.75,
+            "quality_score": null
+        }"#;
+        assert!(schema.validate_completion(valid).is_ok());
+
+        let invalid = r#"{
+            "analysis": "Analysis text",
+            "strengths": "S1",
+            "weaknesses": [],
+            "recommendations": [],
+            "confidence": 0.75
+        }"#;
+        let err = schema.validate_completion(invalid).unwrap_err();
+        assert_eq!(err.path, "$.strengths");
+    }
+
     #[test]
     fn test_reflection_response_deserialize_minimal() {
         let json = r#"{
@@ -865,6 +1285,9 @@ mod tests {
             iterations_performed: 2,
             quality_improved: true,
             branch_id: Some("branch-1".to_string()),
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -917,6 +1340,9 @@ mod tests {
             iterations_performed: 1,
             quality_improved: false,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -1005,6 +1431,7 @@ mod tests {
             confidence: 0.87,
             quality_score: Some(0.92),
             improved_thought: Some("Improved version".to_string()),
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::json!({"extra": "data"}),
         };
 
@@ -1039,6 +1466,9 @@ mod tests {
             iterations_performed: 3,
             quality_improved: true,
             branch_id: Some("br-1".to_string()),
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         let json = serde_json::to_string(&original).unwrap();
@@ -1144,6 +1574,7 @@ mod tests {
             confidence: 0.5,
             quality_score: None,
             improved_thought: None,
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::Value::Null,
         };
 
@@ -1233,6 +1664,9 @@ mod tests {
             iterations_performed: 1,
             quality_improved: false,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         assert!(result.original_thought_id.is_none());
@@ -1315,6 +1749,9 @@ mod tests {
             iterations_performed: 1,
             quality_improved: true,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         assert!(result_improved.quality_improved);
@@ -1405,6 +1842,9 @@ mod tests {
             iterations_performed: 1,
             quality_improved: false,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -1430,6 +1870,9 @@ mod tests {
             iterations_performed: 4,
             quality_improved: true,
             branch_id: Some("b-1".to_string()),
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         assert_eq!(result.strengths.len(), 2);
@@ -1489,6 +1932,7 @@ mod tests {
             confidence: 0.5,
             quality_score: None,
             improved_thought: None,
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::json!({
                 "nested": {
                     "field": "value",
@@ -1512,8 +1956,11 @@ mod tests {
 
     fn create_test_config() -> Config {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RetentionConfig, ToolsConfig, TransportConfig,
+            DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -1521,10 +1968,15 @@ mod tests {
             langbase: LangbaseConfig {
                 api_key: "test-key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1533,6 +1985,18 @@ mod tests {
             request: crate::config::RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         }
     }
 
@@ -1577,7 +2041,7 @@ mod tests {
             LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
 
         let mode = ReflectionMode::new(storage, langbase, &config);
-        let messages = mode.build_messages("Test content", &[], 0);
+        let messages = mode.build_messages("Test content", &[], 0, &[]);
 
         assert_eq!(messages.len(), 2);
         assert!(matches!(messages[0].role, MessageRole::System));
@@ -1595,7 +2059,7 @@ mod tests {
             LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
 
         let mode = ReflectionMode::new(storage, langbase, &config);
-        let messages = mode.build_messages("Test content", &[], 2);
+        let messages = mode.build_messages("Test content", &[], 2, &[]);
 
         assert_eq!(messages.len(), 2);
         assert!(messages[0].content.contains("iteration 3"));
@@ -1614,7 +2078,7 @@ mod tests {
 
         let mode = ReflectionMode::new(storage, langbase, &config);
         let chain: Vec<Thought> = vec![];
-        let messages = mode.build_messages("Content", &chain, 0);
+        let messages = mode.build_messages("Content", &chain, 0, &[]);
 
         assert_eq!(messages.len(), 2);
         assert!(!messages
@@ -1638,7 +2102,7 @@ mod tests {
         let thought2 = Thought::new("sess-1", "Second thought", "tree").with_confidence(0.8);
         let chain = vec![thought1, thought2];
 
-        let messages = mode.build_messages("Final thought", &chain, 0);
+        let messages = mode.build_messages("Final thought", &chain, 0, &[]);
 
         assert_eq!(messages.len(), 3);
         assert!(matches!(messages[0].role, MessageRole::System));
@@ -1665,7 +2129,7 @@ mod tests {
         let thought = Thought::new("sess-1", "Content", "divergent").with_confidence(0.65);
         let chain = vec![thought];
 
-        let messages = mode.build_messages("Test", &chain, 0);
+        let messages = mode.build_messages("Test", &chain, 0, &[]);
 
         let chain_message = &messages[1];
         assert!(chain_message.content.contains("[divergent]"));
@@ -1682,7 +2146,7 @@ mod tests {
             LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
 
         let mode = ReflectionMode::new(storage, langbase, &config);
-        let messages = mode.build_messages("", &[], 0);
+        let messages = mode.build_messages("", &[], 0, &[]);
 
         assert_eq!(messages.len(), 2);
         assert!(messages[1].content.contains("Thought to reflect upon"));
@@ -1698,7 +2162,7 @@ mod tests {
 
         let mode = ReflectionMode::new(storage, langbase, &config);
         let unicode_content = "Test with unicode: 你好 🌍 مرحبا";
-        let messages = mode.build_messages(unicode_content, &[], 0);
+        let messages = mode.build_messages(unicode_content, &[], 0, &[]);
 
         assert!(messages[1].content.contains(unicode_content));
     }
@@ -1713,7 +2177,7 @@ mod tests {
 
         let mode = ReflectionMode::new(storage, langbase, &config);
         let special = "Content with\nnewlines\tand\ttabs and \"quotes\"";
-        let messages = mode.build_messages(special, &[], 0);
+        let messages = mode.build_messages(special, &[], 0, &[]);
 
         assert!(messages[1].content.contains(special));
     }
@@ -1733,7 +2197,7 @@ mod tests {
             chain.push(Thought::new("sess-1", format!("Thought {}", i), "linear"));
         }
 
-        let messages = mode.build_messages("Final", &chain, 0);
+        let messages = mode.build_messages("Final", &chain, 0, &[]);
 
         assert_eq!(messages.len(), 3);
         let chain_msg = &messages[1];
@@ -2159,6 +2623,7 @@ mod tests {
             confidence: 0.0,
             quality_score: Some(0.0),
             improved_thought: None,
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::Value::Null,
         };
 
@@ -2176,6 +2641,7 @@ mod tests {
             confidence: 1.0,
             quality_score: Some(1.0),
             improved_thought: None,
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::Value::Null,
         };
 
@@ -2198,6 +2664,9 @@ mod tests {
             iterations_performed: 0,
             quality_improved: true,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         assert_eq!(result.iterations_performed, 0);
@@ -2251,7 +2720,10 @@ mod tests {
             branch_id: None,
             max_iterations: 1,
             quality_threshold: 0.5,
+            min_improvement: 0.0,
+            rubric: Vec::new(),
             include_chain: false,
+            generation: GenerationParams::default(),
         };
 
         assert!(params.thought_id.is_none());
@@ -2285,6 +2757,7 @@ mod tests {
             confidence: 0.5,
             quality_score: None,
             improved_thought: None,
+            rubric_scores: BTreeMap::new(),
             metadata: serde_json::Value::Null,
         };
 
@@ -2350,6 +2823,9 @@ mod tests {
             iterations_performed: 1,
             quality_improved: false,
             branch_id: None,
+            rounds: Vec::new(),
+            stop_reason: ReflectionStop::MaxRounds,
+            rubric_scores: Vec::new(),
         };
 
         assert_eq!(result.analysis.len(), 10000);
@@ -2402,7 +2878,7 @@ mod tests {
             LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
 
         let mode = ReflectionMode::new(storage, langbase, &config);
-        let messages = mode.build_messages("Content", &[], 0);
+        let messages = mode.build_messages("Content", &[], 0, &[]);
 
         assert!(!messages[0].content.contains("iteration"));
     }
@@ -2416,8 +2892,206 @@ mod tests {
             LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
 
         let mode = ReflectionMode::new(storage, langbase, &config);
-        let messages = mode.build_messages("Content", &[], 1);
+        let messages = mode.build_messages("Content", &[], 1, &[]);
 
         assert!(messages[0].content.contains("iteration 2"));
     }
+
+    // ============================================================================
+    // Multi-round Convergence Tests
+    // ============================================================================
+
+    #[test]
+    fn test_reflection_params_with_min_improvement() {
+        let params = ReflectionParams::for_content("x").with_min_improvement(0.1);
+        assert_eq!(params.min_improvement, 0.1);
+        assert_eq!(
+            ReflectionParams::for_content("x").min_improvement,
+            default_min_improvement()
+        );
+        let params = ReflectionParams::for_content("x").with_min_improvement(-1.0);
+        assert_eq!(params.min_improvement, 0.0);
+    }
+
+    #[test]
+    fn test_round_stop() {
+        let params = ReflectionParams::for_content("x")
+            .with_quality_threshold(0.9)
+            .with_min_improvement(0.05);
+
+        assert_eq!(
+            round_stop(0.95, Some(0.5), true, &params),
+            Some(ReflectionStop::ThresholdMet)
+        );
+        // The first round has nothing to improve on
+        assert_eq!(round_stop(0.5, None, true, &params), None);
+        assert_eq!(round_stop(0.6, Some(0.5), true, &params), None);
+        assert_eq!(
+            round_stop(0.53, Some(0.5), true, &params),
+            Some(ReflectionStop::Converged)
+        );
+        assert_eq!(
+            round_stop(0.4, Some(0.5), true, &params),
+            Some(ReflectionStop::Converged)
+        );
+        assert_eq!(
+            round_stop(0.5, None, false, &params),
+            Some(ReflectionStop::NoRevision)
+        );
+    }
+
+    #[test]
+    fn test_reflection_stop_serde() {
+        assert_eq!(
+            serde_json::to_value(ReflectionStop::NoRevision).unwrap(),
+            "no_revision"
+        );
+        for stop in [
+            ReflectionStop::ThresholdMet,
+            ReflectionStop::Converged,
+            ReflectionStop::NoRevision,
+            ReflectionStop::MaxRounds,
+        ] {
+            assert_eq!(serde_json::to_value(stop).unwrap(), stop.as_str());
+        }
+        assert_eq!(ReflectionStop::default(), ReflectionStop::MaxRounds);
+    }
+
+    // ============================================================================
+    // Rubric Tests
+    // ============================================================================
+
+    fn rubric() -> Vec<RubricDimension> {
+        vec![
+            RubricDimension::new("correctness", 2.0)
+                .with_description("Claims follow from the evidence"),
+            RubricDimension::new("clarity", 1.0),
+        ]
+    }
+
+    #[test]
+    fn test_rubric_dimension_default_weight() {
+        let dimension: RubricDimension = serde_json::from_str(r#"{"name": "clarity"}"#).unwrap();
+        assert_eq!(dimension.weight, 1.0);
+        assert!(dimension.description.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rubric() {
+        assert!(validate_rubric(&rubric()).is_ok());
+        assert!(validate_rubric(&[]).is_ok());
+
+        let cases = [
+            (vec![RubricDimension::new(" ", 1.0)], "rubric[0].name"),
+            (
+                vec![
+                    RubricDimension::new("clarity", 1.0),
+                    RubricDimension::new("Clarity", 1.0),
+                ],
+                "rubric[1].name",
+            ),
+            (
+                vec![RubricDimension::new("clarity", 0.0)],
+                "rubric[0].weight",
+            ),
+            (
+                vec![RubricDimension::new("clarity", f64::NAN)],
+                "rubric[0].weight",
+            ),
+            (
+                (0..=MAX_RUBRIC_DIMENSIONS)
+                    .map(|i| RubricDimension::new(format!("d{}", i), 1.0))
+                    .collect(),
+                "rubric",
+            ),
+        ];
+        for (rubric, field) in cases {
+            let err = validate_rubric(&rubric).unwrap_err().to_string();
+            assert!(err.contains(field), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_score_rubric_matches_dimensions() {
+        let scores = BTreeMap::from([
+            ("Clarity ".to_string(), 1.4),
+            ("correctness".to_string(), 0.6),
+            ("style".to_string(), 0.9),
+        ]);
+        let scored = score_rubric(&rubric(), &scores);
+        assert_eq!(
+            scored,
+            vec![
+                DimensionScore {
+                    name: "correctness".to_string(),
+                    weight: 2.0,
+                    score: 0.6,
+                },
+                DimensionScore {
+                    name: "clarity".to_string(),
+                    weight: 1.0,
+                    score: 1.0,
+                },
+            ]
+        );
+        let quality = rubric_quality(&scored).unwrap();
+        assert!((quality - 2.2 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rubric_quality_without_scores() {
+        assert!(rubric_quality(&[]).is_none());
+        let scored = score_rubric(&rubric(), &BTreeMap::new());
+        assert!(scored.is_empty());
+    }
+
+    #[test]
+    fn test_build_messages_with_rubric() {
+        let config = create_test_config();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let storage = rt.block_on(SqliteStorage::new_in_memory()).unwrap();
+        let langbase =
+            LangbaseClient::new(&config.langbase, crate::config::RequestConfig::default()).unwrap();
+        let mode = ReflectionMode::new(storage, langbase, &config);
+
+        let messages = mode.build_messages("Content", &[], 0, &rubric());
+        let system = &messages[0].content;
+        assert!(system.contains("rubric_scores"));
+        assert!(system.contains("- correctness (weight 2): Claims follow from the evidence"));
+        assert!(system.contains("- clarity (weight 1)\n"));
+        assert!(!mode.build_messages("Content", &[], 0, &[])[0]
+            .content
+            .contains("rubric_scores"));
+    }
+
+    #[test]
+    fn test_rubric_trends() {
+        let logged = |minutes: i64, scores: serde_json::Value| {
+            let mut invocation = Invocation::new("reasoning.reflection", serde_json::json!({}))
+                .success(serde_json::json!({ "rubric_scores": scores }), 10);
+            invocation.created_at = chrono::Utc::now() + chrono::Duration::minutes(minutes);
+            invocation
+        };
+        // Newest first, as storage returns them
+        let invocations = vec![
+            logged(2, serde_json::json!({"clarity": 0.9, "correctness": 0.8})),
+            logged(1, serde_json::json!({"clarity": 0.6})),
+            Invocation::new("reasoning.linear", serde_json::json!({}))
+                .success(serde_json::json!({"rubric_scores": {"clarity": 0.0}}), 10),
+            logged(0, serde_json::json!({"clarity": 0.3})),
+        ];
+
+        let trends = rubric_trends(&invocations);
+        assert_eq!(trends.len(), 2);
+        let clarity = &trends[0];
+        assert_eq!(clarity.dimension, "clarity");
+        assert_eq!(clarity.samples, 3);
+        assert_eq!(clarity.first, 0.3);
+        assert_eq!(clarity.latest, 0.9);
+        assert!((clarity.average - 0.6).abs() < 1e-9);
+        assert!((clarity.change - 0.6).abs() < 1e-9);
+        assert_eq!(trends[1].dimension, "correctness");
+        assert_eq!(trends[1].samples, 1);
+        assert_eq!(trends[1].change, 0.0);
+    }
 }
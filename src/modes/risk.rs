@@ -0,0 +1,899 @@
+//! Risk mode - a per-session risk register with a probability-impact matrix.
+//!
+//! `assess` has the decision framework pipe identify the risks of a subject,
+//! scored 1-5 for likelihood, impact and detectability with an owner and a
+//! mitigation, and adds them to the session's register. The matrix, score
+//! (likelihood × impact), priority number (× detectability) and level are
+//! computed locally.
+//!
+//! `update` re-scores a risk as evidence arrives from evidence mode:
+//! - a probability update (`reasoning_probabilistic`) sets the likelihood
+//!   from its posterior, locally
+//! - an evidence assessment (`reasoning_assess_evidence`) is weighed by the
+//!   pipe
+//!
+//! Explicit scores override both, and every re-scoring is kept in the
+//! risk's history.
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{RISK_IDENTIFICATION_PROMPT, RISK_RESCORE_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{EvidenceAssessment, Invocation, Risk, SharedStorage};
+
+/// Risks identified per call when the caller does not say.
+pub const DEFAULT_MAX_RISKS: u32 = 6;
+
+/// Most risks one call may identify.
+pub const MAX_RISKS: u32 = 15;
+
+/// Highest likelihood, impact or detectability score.
+pub const MAX_RISK_SCORE: i32 = 5;
+
+fn default_max_risks() -> u32 {
+    DEFAULT_MAX_RISKS
+}
+
+/// Input parameters for risk assessment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskParams {
+    /// The project, plan or decision whose risks to identify
+    pub subject: String,
+    /// Optional background on the subject
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Most new risks to add to the register
+    #[serde(default = "default_max_risks")]
+    pub max_risks: u32,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for re-scoring a risk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskUpdateParams {
+    /// The risk to update
+    pub risk_id: String,
+    /// Probability update or evidence assessment bearing on the risk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evidence_id: Option<String>,
+    /// New likelihood (1-5), overriding the evidence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub likelihood: Option<i32>,
+    /// New impact (1-5), overriding the evidence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impact: Option<i32>,
+    /// New detectability (1-5), overriding the evidence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detectability: Option<i32>,
+    /// New owner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// New mitigation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<String>,
+    /// Why the risk is re-scored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Level of a risk by its likelihood × impact score.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    /// Score below 5.
+    Low,
+    /// Score 5-9.
+    Medium,
+    /// Score 10-14.
+    High,
+    /// Score 15 or more.
+    Critical,
+}
+
+impl RiskLevel {
+    /// Level of a likelihood × impact score.
+    pub fn from_score(score: i32) -> Self {
+        match score {
+            s if s >= 15 => RiskLevel::Critical,
+            s if s >= 10 => RiskLevel::High,
+            s if s >= 5 => RiskLevel::Medium,
+            _ => RiskLevel::Low,
+        }
+    }
+}
+
+/// What re-scored a risk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RescoreSource {
+    /// Scores given by the caller.
+    Manual,
+    /// Likelihood from a probability update's posterior.
+    ProbabilityUpdate,
+    /// Scores weighed by the pipe against an evidence assessment.
+    EvidenceAssessment,
+}
+
+/// Likelihood, impact and detectability of a risk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RiskScores {
+    /// Likelihood (1-5)
+    pub likelihood: i32,
+    /// Impact (1-5)
+    pub impact: i32,
+    /// Detectability (1-5, 5 = hardest to detect)
+    pub detectability: i32,
+}
+
+/// One re-scoring of a risk
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskRescore {
+    /// When the risk was re-scored (RFC 3339)
+    pub at: String,
+    /// Scores before
+    pub from: RiskScores,
+    /// Scores after
+    pub to: RiskScores,
+    /// What re-scored the risk
+    pub source: RescoreSource,
+    /// Probability update or evidence assessment applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence_id: Option<String>,
+    /// Why the scores changed
+    #[serde(default)]
+    pub rationale: String,
+}
+
+/// A risk of the register
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskEntry {
+    /// Stored risk ID
+    pub risk_id: String,
+    /// Short name of the risk
+    pub title: String,
+    /// What could happen and why
+    pub description: String,
+    /// Category of the risk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Likelihood (1-5)
+    pub likelihood: i32,
+    /// Impact (1-5)
+    pub impact: i32,
+    /// Detectability (1-5, 5 = hardest to detect)
+    pub detectability: i32,
+    /// Likelihood × impact (1-25)
+    pub score: i32,
+    /// Likelihood × impact × detectability (1-125)
+    pub priority_number: i32,
+    /// Level by score
+    pub level: RiskLevel,
+    /// Who owns the risk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// How the risk is being reduced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<String>,
+    /// Earlier re-scorings, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<RiskRescore>,
+}
+
+/// Number of risks at each level
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LevelCounts {
+    /// Low risks
+    pub low: u32,
+    /// Medium risks
+    pub medium: u32,
+    /// High risks
+    pub high: u32,
+    /// Critical risks
+    pub critical: u32,
+}
+
+/// Probability-impact matrix of a register
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RiskMatrix {
+    /// Risk counts; rows are likelihood 5 down to 1, columns impact 1 to 5
+    pub cells: Vec<Vec<u32>>,
+    /// Risk counts by level
+    pub levels: LevelCounts,
+}
+
+/// Result of risk assessment: the session's whole register
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskRegisterResult {
+    /// The session ID
+    pub session_id: String,
+    /// IDs of the risks this call added
+    pub added: Vec<String>,
+    /// The register, highest priority number first
+    pub risks: Vec<RiskEntry>,
+    /// Probability-impact matrix of the register
+    pub matrix: RiskMatrix,
+}
+
+/// Result of re-scoring a risk
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskUpdateResult {
+    /// The updated risk
+    pub risk: RiskEntry,
+    /// The re-scoring, when scores were given or evidence applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rescore: Option<RiskRescore>,
+    /// Probability-impact matrix of the session's register
+    pub matrix: RiskMatrix,
+}
+
+/// Risk mode handler.
+#[derive(Clone)]
+pub struct RiskMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Decision framework pipe identifying and re-scoring risks
+    decision_pipe: String,
+}
+
+impl RiskMode {
+    /// Create a new risk mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            decision_pipe,
+        }
+    }
+
+    /// Identify a subject's risks and add them to the session's register
+    pub async fn assess(&self, params: RiskParams) -> AppResult<RiskRegisterResult> {
+        let start = Instant::now();
+
+        if params.subject.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "subject".to_string(),
+                reason: "Subject cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.max_risks == 0 || params.max_risks > MAX_RISKS {
+            return Err(ToolError::Validation {
+                field: "max_risks".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_RISKS),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let session = storage
+            .get_or_create_session(&params.session_id, "risk")
+            .await?;
+        debug!(session_id = %session.id, "Processing risk assessment request");
+        let register = storage.get_session_risks(&session.id).await?;
+
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.risk",
+            serialize_for_log(&params, "reasoning.risk input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(RISK_IDENTIFICATION_PROMPT),
+            Message::user(risk_prompt(&params, &register)),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_identified(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let (parsed, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+
+        let mut added = Vec::new();
+        for risk in new_risks(&session.id, params.subject.trim(), parsed, &register)
+            .into_iter()
+            .take(params.max_risks as usize)
+        {
+            storage.create_risk(&risk).await?;
+            added.push(risk.id);
+        }
+        let register = storage.get_session_risks(&session.id).await?;
+        let result = RiskRegisterResult {
+            session_id: session.id.clone(),
+            added,
+            matrix: risk_matrix(&register),
+            risks: register.into_iter().map(to_entry).collect(),
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(serialize_for_log(&result, "reasoning.risk output"), latency);
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            added = result.added.len(),
+            register = result.risks.len(),
+            latency_ms = latency,
+            "Risk assessment completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Re-score a risk from evidence or explicit scores
+    pub async fn update(&self, params: RiskUpdateParams) -> AppResult<RiskUpdateResult> {
+        let start = Instant::now();
+
+        for (field, score) in [
+            ("likelihood", params.likelihood),
+            ("impact", params.impact),
+            ("detectability", params.detectability),
+        ] {
+            if score.is_some_and(|s| !(1..=MAX_RISK_SCORE).contains(&s)) {
+                return Err(ToolError::Validation {
+                    field: field.to_string(),
+                    reason: format!("Must be between 1 and {}", MAX_RISK_SCORE),
+                }
+                .into());
+            }
+        }
+        let rescored = params.evidence_id.is_some()
+            || params.likelihood.is_some()
+            || params.impact.is_some()
+            || params.detectability.is_some();
+        if !rescored && params.owner.is_none() && params.mitigation.is_none() {
+            return Err(ToolError::Validation {
+                field: "risk_id".to_string(),
+                reason: "Give evidence_id, new scores, an owner or a mitigation".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let mut risk =
+            storage
+                .get_risk(&params.risk_id)
+                .await?
+                .ok_or_else(|| ToolError::Validation {
+                    field: "risk_id".to_string(),
+                    reason: format!("Risk not found: {}", params.risk_id),
+                })?;
+        debug!(risk_id = %risk.id, "Processing risk update request");
+
+        let from = scores_of(&risk);
+        let mut to = from;
+        let mut source = RescoreSource::Manual;
+        let mut rationale = None;
+
+        if let Some(ref evidence_id) = params.evidence_id {
+            if let Some(update) = storage.get_probability_update(evidence_id).await? {
+                to.likelihood = likelihood_from_probability(update.posterior);
+                source = RescoreSource::ProbabilityUpdate;
+                rationale = Some(format!(
+                    "Posterior {:.2} for '{}'",
+                    update.posterior, update.hypothesis
+                ));
+            } else if let Some(assessment) = storage.get_evidence_assessment(evidence_id).await? {
+                let rescore = self
+                    .rescore_from_assessment(&risk, &assessment, &params, start)
+                    .await?;
+                to = rescore.scores;
+                source = RescoreSource::EvidenceAssessment;
+                rationale = Some(rescore.rationale);
+            } else {
+                return Err(ToolError::Validation {
+                    field: "evidence_id".to_string(),
+                    reason: format!(
+                        "No probability update or evidence assessment: {}",
+                        evidence_id
+                    ),
+                }
+                .into());
+            }
+        }
+        let explicit = params.likelihood.is_some()
+            || params.impact.is_some()
+            || params.detectability.is_some();
+        to.likelihood = params.likelihood.unwrap_or(to.likelihood);
+        to.impact = params.impact.unwrap_or(to.impact);
+        to.detectability = params.detectability.unwrap_or(to.detectability);
+        if explicit && params.evidence_id.is_none() {
+            source = RescoreSource::Manual;
+        }
+
+        let rescore = rescored.then(|| RiskRescore {
+            at: Utc::now().to_rfc3339(),
+            from,
+            to,
+            source,
+            evidence_id: params.evidence_id.clone(),
+            rationale: params.rationale.clone().or(rationale).unwrap_or_default(),
+        });
+        risk.likelihood = to.likelihood;
+        risk.impact = to.impact;
+        risk.detectability = to.detectability;
+        if let Some(owner) = params.owner {
+            risk.owner = Some(owner);
+        }
+        if let Some(mitigation) = params.mitigation {
+            risk.mitigation = Some(mitigation);
+        }
+        if let Some(ref rescore) = rescore {
+            let mut history = history_of(&risk);
+            history.push(rescore.clone());
+            risk.history = serde_json::to_value(&history).unwrap_or_default();
+        }
+        risk.updated_at = Utc::now();
+        storage.update_risk(&risk).await?;
+
+        let register = storage.get_session_risks(&risk.session_id).await?;
+        info!(
+            risk_id = %risk.id,
+            from_score = from.likelihood * from.impact,
+            to_score = to.likelihood * to.impact,
+            "Risk updated"
+        );
+
+        Ok(RiskUpdateResult {
+            risk: to_entry(risk),
+            rescore,
+            matrix: risk_matrix(&register),
+        })
+    }
+
+    /// Have the pipe weigh an evidence assessment against a risk
+    async fn rescore_from_assessment(
+        &self,
+        risk: &Risk,
+        assessment: &EvidenceAssessment,
+        params: &RiskUpdateParams,
+        start: Instant,
+    ) -> AppResult<AssessmentRescore> {
+        let storage = self.core.storage();
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.risk_update",
+            serialize_for_log(params, "reasoning.risk_update input"),
+        )
+        .with_session(&risk.session_id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(RISK_RESCORE_PROMPT),
+            Message::user(rescore_prompt(risk, assessment)),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_rescore(&response.completion, scores_of(risk))
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let latency = start.elapsed().as_millis() as i64;
+        let (rescore, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&rescore.scores, "reasoning.risk_update output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+        Ok(rescore)
+    }
+}
+
+/// Likelihood band of a probability: 1 up to 10%, 2 up to 30%, 3 up to 60%,
+/// 4 up to 90%, 5 above
+pub fn likelihood_from_probability(probability: f64) -> i32 {
+    match probability {
+        p if p <= 0.1 => 1,
+        p if p <= 0.3 => 2,
+        p if p <= 0.6 => 3,
+        p if p <= 0.9 => 4,
+        _ => 5,
+    }
+}
+
+fn scores_of(risk: &Risk) -> RiskScores {
+    RiskScores {
+        likelihood: risk.likelihood,
+        impact: risk.impact,
+        detectability: risk.detectability,
+    }
+}
+
+/// Re-scorings of a stored risk; a malformed history reads as empty
+fn history_of(risk: &Risk) -> Vec<RiskRescore> {
+    serde_json::from_value(risk.history.clone()).unwrap_or_default()
+}
+
+fn to_entry(risk: Risk) -> RiskEntry {
+    RiskEntry {
+        score: risk.score(),
+        priority_number: risk.priority_number(),
+        level: RiskLevel::from_score(risk.score()),
+        history: history_of(&risk),
+        risk_id: risk.id,
+        title: risk.title,
+        description: risk.description,
+        category: risk.category,
+        likelihood: risk.likelihood,
+        impact: risk.impact,
+        detectability: risk.detectability,
+        owner: risk.owner,
+        mitigation: risk.mitigation,
+    }
+}
+
+fn risk_matrix(register: &[Risk]) -> RiskMatrix {
+    let size = MAX_RISK_SCORE as usize;
+    let mut cells = vec![vec![0u32; size]; size];
+    let mut levels = LevelCounts::default();
+    for risk in register {
+        let row = (MAX_RISK_SCORE - risk.likelihood.clamp(1, MAX_RISK_SCORE)) as usize;
+        let column = (risk.impact.clamp(1, MAX_RISK_SCORE) - 1) as usize;
+        cells[row][column] += 1;
+        match RiskLevel::from_score(risk.score()) {
+            RiskLevel::Low => levels.low += 1,
+            RiskLevel::Medium => levels.medium += 1,
+            RiskLevel::High => levels.high += 1,
+            RiskLevel::Critical => levels.critical += 1,
+        }
+    }
+    RiskMatrix { cells, levels }
+}
+
+/// Risks to add: untitled ones and ones already in the register (by title)
+/// are skipped, and scores are rounded into 1-5
+fn new_risks(
+    session_id: &str,
+    subject: &str,
+    identified: Vec<IdentifiedRisk>,
+    register: &[Risk],
+) -> Vec<Risk> {
+    let mut titles: Vec<String> = register.iter().map(|r| r.title.to_lowercase()).collect();
+    let mut risks = Vec::new();
+    for found in identified {
+        let title = found.title.trim();
+        if title.is_empty() || titles.contains(&title.to_lowercase()) {
+            continue;
+        }
+        titles.push(title.to_lowercase());
+        let mut risk = Risk::new(
+            session_id,
+            title,
+            to_score(found.likelihood),
+            to_score(found.impact),
+            to_score(found.detectability),
+        )
+        .with_description(found.description)
+        .with_metadata(serde_json::json!({"subject": subject}));
+        if let Some(category) = found.category.filter(|c| !c.trim().is_empty()) {
+            risk = risk.with_category(category);
+        }
+        if let Some(owner) = found.owner.filter(|o| !o.trim().is_empty()) {
+            risk = risk.with_owner(owner);
+        }
+        if let Some(mitigation) = found.mitigation.filter(|m| !m.trim().is_empty()) {
+            risk = risk.with_mitigation(mitigation);
+        }
+        risks.push(risk);
+    }
+    risks
+}
+
+fn to_score(value: f64) -> i32 {
+    (value.round() as i32).clamp(1, MAX_RISK_SCORE)
+}
+
+fn risk_prompt(params: &RiskParams, register: &[Risk]) -> String {
+    let mut prompt = format!(
+        "SUBJECT: {}\nIDENTIFY AT MOST: {} risks",
+        params.subject.trim(),
+        params.max_risks
+    );
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+    }
+    if !register.is_empty() {
+        prompt.push_str("\n\nALREADY IN THE REGISTER:");
+        for risk in register {
+            prompt.push_str(&format!(
+                "\n- {} (likelihood {}, impact {})",
+                risk.title, risk.likelihood, risk.impact
+            ));
+        }
+    }
+    prompt
+}
+
+fn rescore_prompt(risk: &Risk, assessment: &EvidenceAssessment) -> String {
+    format!(
+        "RISK: {}\n{}\nCURRENT SCORES: likelihood {}, impact {}, detectability {}\n\nEVIDENCE ASSESSMENT\nClaim: {}\nOverall support: {}\nEvidence: {}",
+        risk.title,
+        risk.description,
+        risk.likelihood,
+        risk.impact,
+        risk.detectability,
+        assessment.claim,
+        assessment.overall_support,
+        assessment.evidence
+    )
+}
+
+fn parse_identified(completion: &str) -> AppResult<Vec<IdentifiedRisk>> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: RiskResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse risk assessment: {}", e),
+        })?;
+    if parsed.risks.is_empty() {
+        return Err(ToolError::Reasoning {
+            message: "Risk assessment identified no risks".to_string(),
+        }
+        .into());
+    }
+    Ok(parsed.risks)
+}
+
+/// Scores the pipe gave, keeping current ones it left out
+fn parse_rescore(completion: &str, current: RiskScores) -> AppResult<AssessmentRescore> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: RescoreResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse risk re-scoring: {}", e),
+        })?;
+    Ok(AssessmentRescore {
+        scores: RiskScores {
+            likelihood: parsed.likelihood.map_or(current.likelihood, to_score),
+            impact: parsed.impact.map_or(current.impact, to_score),
+            detectability: parsed.detectability.map_or(current.detectability, to_score),
+        },
+        rationale: parsed.rationale,
+    })
+}
+
+/// Scores weighed against an evidence assessment
+struct AssessmentRescore {
+    scores: RiskScores,
+    rationale: String,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Deserialize)]
+struct RiskResponse {
+    #[serde(default)]
+    risks: Vec<IdentifiedRisk>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdentifiedRisk {
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    category: Option<String>,
+    likelihood: f64,
+    impact: f64,
+    #[serde(default = "default_detectability")]
+    detectability: f64,
+    #[serde(default)]
+    owner: Option<String>,
+    #[serde(default)]
+    mitigation: Option<String>,
+}
+
+/// Detectability assumed when the pipe gives none: neither obvious nor
+/// hidden
+fn default_detectability() -> f64 {
+    3.0
+}
+
+#[derive(Debug, Deserialize)]
+struct RescoreResponse {
+    #[serde(default)]
+    likelihood: Option<f64>,
+    #[serde(default)]
+    impact: Option<f64>,
+    #[serde(default)]
+    detectability: Option<f64>,
+    #[serde(default)]
+    rationale: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn identified(title: &str, likelihood: f64, impact: f64) -> IdentifiedRisk {
+        IdentifiedRisk {
+            title: title.to_string(),
+            description: String::new(),
+            category: Some("technical".to_string()),
+            likelihood,
+            impact,
+            detectability: 3.0,
+            owner: Some(" ".to_string()),
+            mitigation: Some("Add tests".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: RiskParams =
+            serde_json::from_value(json!({"subject": "Data migration"})).unwrap();
+        assert_eq!(params.max_risks, DEFAULT_MAX_RISKS);
+
+        let update: RiskUpdateParams =
+            serde_json::from_value(json!({"risk_id": "r1", "impact": 2})).unwrap();
+        assert_eq!(update.impact, Some(2));
+        assert!(update.evidence_id.is_none());
+    }
+
+    #[test]
+    fn test_risk_level_from_score() {
+        assert_eq!(RiskLevel::from_score(1), RiskLevel::Low);
+        assert_eq!(RiskLevel::from_score(4), RiskLevel::Low);
+        assert_eq!(RiskLevel::from_score(5), RiskLevel::Medium);
+        assert_eq!(RiskLevel::from_score(12), RiskLevel::High);
+        assert_eq!(RiskLevel::from_score(15), RiskLevel::Critical);
+        assert_eq!(RiskLevel::from_score(25), RiskLevel::Critical);
+    }
+
+    #[test]
+    fn test_likelihood_from_probability() {
+        assert_eq!(likelihood_from_probability(0.05), 1);
+        assert_eq!(likelihood_from_probability(0.3), 2);
+        assert_eq!(likelihood_from_probability(0.45), 3);
+        assert_eq!(likelihood_from_probability(0.75), 4);
+        assert_eq!(likelihood_from_probability(0.95), 5);
+    }
+
+    #[test]
+    fn test_risk_matrix() {
+        let register = [
+            Risk::new("s1", "Outage", 5, 5, 1),
+            Risk::new("s1", "Delay", 3, 2, 2),
+            Risk::new("s1", "Churn", 3, 2, 4),
+            Risk::new("s1", "Typo", 1, 1, 1),
+        ];
+        let matrix = risk_matrix(&register);
+        assert_eq!(matrix.cells.len(), 5);
+        assert_eq!(matrix.cells[0][4], 1);
+        assert_eq!(matrix.cells[2][1], 2);
+        assert_eq!(matrix.cells[4][0], 1);
+        assert_eq!(
+            matrix.levels,
+            LevelCounts {
+                low: 1,
+                medium: 2,
+                high: 0,
+                critical: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_risks_skip_duplicates_and_clamp() {
+        let register = [Risk::new("s1", "Data loss", 2, 5, 3)];
+        let risks = new_risks(
+            "s1",
+            "Migration",
+            vec![
+                identified("data loss", 4.0, 5.0),
+                identified("Schema drift", 7.2, 0.4),
+                identified("  ", 3.0, 3.0),
+                identified("Schema Drift", 2.0, 2.0),
+            ],
+            &register,
+        );
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].title, "Schema drift");
+        assert_eq!(risks[0].likelihood, 5);
+        assert_eq!(risks[0].impact, 1);
+        assert!(risks[0].owner.is_none());
+        assert_eq!(risks[0].mitigation.as_deref(), Some("Add tests"));
+        assert_eq!(risks[0].metadata, Some(json!({"subject": "Migration"})));
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let risks =
+            parse_identified(r#"{"risks": [{"title": "Outage", "likelihood": 2, "impact": 5}]}"#)
+                .unwrap();
+        assert_eq!(risks[0].detectability, 3.0);
+        assert!(parse_identified(r#"{"risks": []}"#).is_err());
+
+        let current = RiskScores {
+            likelihood: 2,
+            impact: 5,
+            detectability: 3,
+        };
+        let rescore = parse_rescore(
+            r#"{"likelihood": 4, "rationale": "Two incidents"}"#,
+            current,
+        )
+        .unwrap();
+        assert_eq!(rescore.scores.likelihood, 4);
+        assert_eq!(rescore.scores.impact, 5);
+        assert_eq!(rescore.rationale, "Two incidents");
+    }
+
+    #[test]
+    fn test_entry_reads_history() {
+        let mut risk = Risk::new("s1", "Outage", 2, 5, 3).with_owner("SRE");
+        let rescore = RiskRescore {
+            at: "2024-01-01T00:00:00Z".to_string(),
+            from: scores_of(&risk),
+            to: scores_of(&risk),
+            source: RescoreSource::Manual,
+            evidence_id: None,
+            rationale: "Checked".to_string(),
+        };
+        risk.history = serde_json::to_value(vec![rescore]).unwrap();
+        let entry = to_entry(risk);
+        assert_eq!(entry.score, 10);
+        assert_eq!(entry.priority_number, 30);
+        assert_eq!(entry.level, RiskLevel::High);
+        assert_eq!(entry.history[0].rationale, "Checked");
+
+        let malformed = Risk {
+            history: json!("not a list"),
+            ..Risk::new("s1", "Outage", 2, 5, 3)
+        };
+        assert!(to_entry(malformed).history.is_empty());
+    }
+}
@@ -0,0 +1,549 @@
+//! Six Thinking Hats mode - sequential passes from fixed cognitive stances.
+//!
+//! This module walks a topic through Edward de Bono's six hats in order:
+//! - White (facts), red (emotions), black (risks), yellow (benefits),
+//!   green (creativity) and blue (process)
+//! - Each pass sees the passes before it but keeps to its own stance
+//! - A final synthesis integrates every pass into one conclusion
+//!
+//! The session is stored as a branch holding the topic and the synthesis,
+//! with one child branch per hat holding that hat's pass.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{emit_artifact, extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{SIX_HATS_PASS_PROMPT, SIX_HATS_SYNTHESIS_PROMPT};
+use crate::providers::SharedBackend;
+use crate::storage::{Branch, Invocation, SharedStorage, Thought};
+
+/// Input parameters for a Six Thinking Hats session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SixHatsParams {
+    /// The topic to think about
+    pub topic: String,
+    /// Optional background every hat is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe calls
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// A thinking hat, each a fixed cognitive stance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThinkingHat {
+    /// Facts and information
+    White,
+    /// Emotions and intuition
+    Red,
+    /// Risks and caution
+    Black,
+    /// Benefits and optimism
+    Yellow,
+    /// Creativity and alternatives
+    Green,
+    /// Process and control
+    Blue,
+}
+
+impl ThinkingHat {
+    /// Every hat, in the order they are worn
+    pub const ALL: [ThinkingHat; 6] = [
+        ThinkingHat::White,
+        ThinkingHat::Red,
+        ThinkingHat::Black,
+        ThinkingHat::Yellow,
+        ThinkingHat::Green,
+        ThinkingHat::Blue,
+    ];
+
+    /// The stance the hat thinks from
+    pub fn stance(self) -> &'static str {
+        match self {
+            ThinkingHat::White => "facts",
+            ThinkingHat::Red => "emotions",
+            ThinkingHat::Black => "risks",
+            ThinkingHat::Yellow => "benefits",
+            ThinkingHat::Green => "creativity",
+            ThinkingHat::Blue => "process",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ThinkingHat::White => "White",
+            ThinkingHat::Red => "Red",
+            ThinkingHat::Black => "Black",
+            ThinkingHat::Yellow => "Yellow",
+            ThinkingHat::Green => "Green",
+            ThinkingHat::Blue => "Blue",
+        }
+    }
+
+    /// What the hat asks the thinker to do
+    fn instruction(self) -> &'static str {
+        match self {
+            ThinkingHat::White => {
+                "State the facts and information available, and what is missing or would \
+                 need checking. No opinions or interpretations."
+            }
+            ThinkingHat::Red => {
+                "Give the feelings, hunches and intuitions the topic provokes, in the people \
+                 involved and in you. No justification is needed."
+            }
+            ThinkingHat::Black => {
+                "Identify the risks, weaknesses and reasons it might fail or be a mistake. \
+                 Be critical but logical."
+            }
+            ThinkingHat::Yellow => {
+                "Identify the benefits, value and reasons it could work, and the conditions \
+                 under which it works best. Be optimistic but logical."
+            }
+            ThinkingHat::Green => {
+                "Generate new ideas, alternatives and modifications, including ones that \
+                 address the risks raised so far."
+            }
+            ThinkingHat::Blue => {
+                "Step back and think about the thinking: what the passes so far have and have \
+                 not covered, which decisions are open, and what process should come next."
+            }
+        }
+    }
+}
+
+/// One hat's pass over the topic
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HatPass {
+    /// The hat worn
+    pub hat: ThinkingHat,
+    /// The stance it thinks from
+    pub stance: String,
+    /// Branch holding the pass
+    pub branch_id: String,
+    /// Thought holding the pass
+    pub thought_id: String,
+    /// The thinking under this hat
+    pub content: String,
+    /// Main points of the pass
+    pub key_points: Vec<String>,
+    /// How well the pass covers the stance (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Result of a Six Thinking Hats session
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SixHatsResult {
+    /// The session ID
+    pub session_id: String,
+    /// The topic thought about
+    pub topic: String,
+    /// Branch holding the topic and the synthesis, parent of the hat branches
+    pub root_branch_id: String,
+    /// One pass per hat, in the order worn
+    pub passes: Vec<HatPass>,
+    /// Conclusion integrating every pass
+    pub synthesis: String,
+    /// Next steps following from the passes
+    pub recommendations: Vec<String>,
+    /// Thought holding the synthesis
+    pub synthesis_thought_id: String,
+    /// Confidence in the conclusion (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Six Thinking Hats mode handler.
+#[derive(Clone)]
+pub struct SixHatsMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Linear pipe making each hat's pass
+    linear_pipe: String,
+    /// Reflection pipe synthesizing the passes
+    reflection_pipe: String,
+}
+
+impl SixHatsMode {
+    /// Create a new Six Thinking Hats mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            linear_pipe: config.pipes.linear.clone(),
+            reflection_pipe: config.pipes.reflection.clone(),
+        }
+    }
+
+    /// Wear every hat in turn, then synthesize the passes
+    pub async fn think(&self, params: SixHatsParams) -> AppResult<SixHatsResult> {
+        let start = Instant::now();
+
+        if params.topic.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "topic".to_string(),
+                reason: "Topic cannot be empty".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let session = self
+            .core
+            .storage()
+            .get_or_create_session(&params.session_id, "six_hats")
+            .await?;
+        debug!(session_id = %session.id, "Processing six hats request");
+
+        let pass_pipe = self.core.select_pipe(&self.linear_pipe);
+        let synthesis_pipe = self.core.select_pipe(&self.reflection_pipe);
+
+        let mut invocation = Invocation::new(
+            "reasoning.six_hats",
+            serialize_for_log(&params, "reasoning.six_hats input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&synthesis_pipe);
+
+        let root_branch = Branch::new(&session.id).with_name(format!(
+            "Six Hats: {}",
+            params.topic.chars().take(30).collect::<String>()
+        ));
+
+        let outcome = match self
+            .wear_hats(
+                &params,
+                &session.id,
+                &root_branch,
+                &pass_pipe,
+                &synthesis_pipe,
+            )
+            .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let SixHatsOutcome {
+            passes,
+            mut branches,
+            mut thoughts,
+            synthesis,
+            cache_hit,
+            fallback,
+        } = outcome;
+
+        let confidence = synthesis.confidence.clamp(0.0, 1.0);
+        let synthesis_thought = Thought::new(&session.id, &synthesis.summary, "six_hats")
+            .with_branch(&root_branch.id)
+            .with_confidence(confidence)
+            .with_metadata(serde_json::json!({
+                "is_synthesis": true,
+                "recommendations": synthesis.recommendations,
+                "source_branches": passes.iter().map(|p| &p.branch_id).collect::<Vec<_>>()
+            }));
+        let synthesis_thought_id = synthesis_thought.id.clone();
+        thoughts.push(synthesis_thought);
+
+        // Store the session only once every hat and the synthesis are in,
+        // so a failed pipe call never leaves some hats without the rest
+        branches.insert(0, root_branch.clone());
+        for branch in &branches {
+            self.core.storage().create_branch(branch).await?;
+        }
+        self.core.storage().create_thoughts_batch(&thoughts).await?;
+
+        let result = SixHatsResult {
+            session_id: session.id.clone(),
+            topic: params.topic.clone(),
+            root_branch_id: root_branch.id,
+            passes,
+            synthesis: synthesis.summary,
+            recommendations: synthesis.recommendations,
+            synthesis_thought_id,
+            confidence,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.six_hats output"),
+                latency,
+            );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            root_branch_id = %result.root_branch_id,
+            confidence = confidence,
+            latency_ms = latency,
+            "Six hats session completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Make one pass per hat, each seeing the ones before, then synthesize
+    async fn wear_hats(
+        &self,
+        params: &SixHatsParams,
+        session_id: &str,
+        root_branch: &Branch,
+        pass_pipe: &str,
+        synthesis_pipe: &str,
+    ) -> AppResult<SixHatsOutcome> {
+        let mut passes: Vec<HatPass> = Vec::new();
+        let mut branches = Vec::new();
+        let mut thoughts = vec![Thought::new(session_id, &params.topic, "six_hats")
+            .with_branch(&root_branch.id)
+            .with_metadata(serde_json::json!({"is_topic": true}))];
+        let mut cache_hit = true;
+        let mut fallback = None;
+
+        for hat in ThinkingHat::ALL {
+            let messages = vec![
+                Message::system(SIX_HATS_PASS_PROMPT),
+                Message::user(pass_prompt(params, hat, &passes)),
+            ];
+            let request = PipeRequest::new(pass_pipe, messages).with_generation(params.generation);
+            let response = self.core.backend().call_pipe(request).await?;
+            cache_hit &= response.cache_hit;
+            fallback = fallback.or(response.fallback);
+
+            let parsed: PassResponse = parse_response(&response.completion, "hat pass")?;
+            let confidence = parsed.confidence.clamp(0.0, 1.0);
+            let branch = Branch::new(session_id)
+                .with_parent(&root_branch.id)
+                .with_name(format!("{} Hat ({})", hat.label(), hat.stance()))
+                .with_confidence(confidence);
+            let thought = Thought::new(session_id, &parsed.thought, "six_hats")
+                .with_branch(&branch.id)
+                .with_confidence(confidence)
+                .with_metadata(serde_json::json!({
+                    "hat": hat,
+                    "stance": hat.stance(),
+                    "key_points": parsed.key_points
+                }));
+
+            let pass = HatPass {
+                hat,
+                stance: hat.stance().to_string(),
+                branch_id: branch.id.clone(),
+                thought_id: thought.id.clone(),
+                content: parsed.thought,
+                key_points: parsed.key_points,
+                confidence,
+            };
+            emit_artifact("hat", passes.len(), &pass);
+            passes.push(pass);
+            branches.push(branch);
+            thoughts.push(thought);
+        }
+
+        let messages = vec![
+            Message::system(SIX_HATS_SYNTHESIS_PROMPT),
+            Message::user(synthesis_prompt(params, &passes)),
+        ];
+        let request = PipeRequest::new(synthesis_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
+        cache_hit &= response.cache_hit;
+        fallback = fallback.or(response.fallback);
+        let synthesis: SynthesisResponse = parse_response(&response.completion, "synthesis")?;
+
+        Ok(SixHatsOutcome {
+            passes,
+            branches,
+            thoughts,
+            synthesis,
+            cache_hit,
+            fallback,
+        })
+    }
+}
+
+/// Prompt asking for the pass under `hat`, given the passes before it
+fn pass_prompt(params: &SixHatsParams, hat: ThinkingHat, passes: &[HatPass]) -> String {
+    let mut prompt = format!("TOPIC: {}\n", params.topic);
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+    if !passes.is_empty() {
+        prompt.push_str(&format!("\nEARLIER HATS:\n{}\n", format_passes(passes)));
+    }
+    prompt.push_str(&format!(
+        "\nYou now wear the {} HAT ({}). {}",
+        hat.label().to_uppercase(),
+        hat.stance(),
+        hat.instruction()
+    ));
+    prompt
+}
+
+/// Prompt asking for the synthesis of every pass
+fn synthesis_prompt(params: &SixHatsParams, passes: &[HatPass]) -> String {
+    let mut prompt = format!("TOPIC: {}\n", params.topic);
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+    prompt.push_str(&format!(
+        "\nPASSES:\n{}\n\nIntegrate the passes into a conclusion.",
+        format_passes(passes)
+    ));
+    prompt
+}
+
+/// The passes as `[Hat] (stance): thinking` lines
+fn format_passes(passes: &[HatPass]) -> String {
+    passes
+        .iter()
+        .map(|p| format!("[{}] ({}): {}", p.hat.label(), p.stance, p.content))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse a JSON completion of the given `kind`
+fn parse_response<T: serde::de::DeserializeOwned>(completion: &str, kind: &str) -> AppResult<T> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    serde_json::from_str(json_str).map_err(|e| {
+        ToolError::Reasoning {
+            message: format!("Failed to parse six hats {}: {}", kind, e),
+        }
+        .into()
+    })
+}
+
+/// Everything a session produced before it is stored
+struct SixHatsOutcome {
+    passes: Vec<HatPass>,
+    branches: Vec<Branch>,
+    thoughts: Vec<Thought>,
+    synthesis: SynthesisResponse,
+    cache_hit: bool,
+    fallback: Option<String>,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PassResponse {
+    thought: String,
+    #[serde(default)]
+    key_points: Vec<String>,
+    confidence: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SynthesisResponse {
+    summary: String,
+    #[serde(default)]
+    recommendations: Vec<String>,
+    confidence: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pass(hat: ThinkingHat, content: &str) -> HatPass {
+        HatPass {
+            hat,
+            stance: hat.stance().to_string(),
+            branch_id: format!("b-{}", hat.stance()),
+            thought_id: format!("t-{}", hat.stance()),
+            content: content.to_string(),
+            key_points: Vec::new(),
+            confidence: 0.7,
+        }
+    }
+
+    fn params() -> SixHatsParams {
+        serde_json::from_value(json!({"topic": "Move to a four-day week"})).unwrap()
+    }
+
+    #[test]
+    fn test_six_hats_params() {
+        let params = params();
+        assert!(params.context.is_none());
+        assert!(params.session_id.is_none());
+        assert!(params.generation.is_empty());
+    }
+
+    #[test]
+    fn test_hats_cover_the_six_stances_in_order() {
+        let stances: Vec<&str> = ThinkingHat::ALL.iter().map(|h| h.stance()).collect();
+        assert_eq!(
+            stances,
+            [
+                "facts",
+                "emotions",
+                "risks",
+                "benefits",
+                "creativity",
+                "process"
+            ]
+        );
+        assert_eq!(json!(ThinkingHat::Yellow), json!("yellow"));
+    }
+
+    #[test]
+    fn test_pass_prompt_includes_earlier_hats() {
+        let mut params = params();
+        let first = pass_prompt(&params, ThinkingHat::White, &[]);
+        assert!(first.contains("WHITE HAT (facts)"));
+        assert!(!first.contains("EARLIER HATS"));
+
+        params.context = Some("Team of 12".to_string());
+        let passes = vec![pass(ThinkingHat::White, "Output held steady in trials")];
+        let second = pass_prompt(&params, ThinkingHat::Red, &passes);
+        assert!(second.contains("Team of 12"));
+        assert!(second.contains("[White] (facts): Output held steady in trials"));
+        assert!(second.ends_with(ThinkingHat::Red.instruction()));
+    }
+
+    #[test]
+    fn test_synthesis_prompt_lists_every_pass() {
+        let passes: Vec<HatPass> = ThinkingHat::ALL
+            .iter()
+            .map(|&hat| pass(hat, hat.label()))
+            .collect();
+        let prompt = synthesis_prompt(&params(), &passes);
+        for hat in ThinkingHat::ALL {
+            assert!(prompt.contains(&format!("[{}] ({})", hat.label(), hat.stance())));
+        }
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        let pass: PassResponse =
+            parse_response(r#"{"thought": "Facts", "confidence": 0.8}"#, "hat pass").unwrap();
+        assert!(pass.key_points.is_empty());
+        let synthesis: SynthesisResponse = parse_response(
+            r#"```json
+{"summary": "Try it for a quarter", "recommendations": ["Pilot"], "confidence": 0.6}
+```"#,
+            "synthesis",
+        )
+        .unwrap();
+        assert_eq!(synthesis.recommendations, ["Pilot"]);
+        assert!(parse_response::<SynthesisResponse>("{}", "synthesis").is_err());
+    }
+}
@@ -0,0 +1,551 @@
+//! SWOT mode - strengths, weaknesses, opportunities and threats.
+//!
+//! This module produces a structured SWOT analysis:
+//! - Every item carries its evidence and a confidence
+//! - Items name the stakeholders they affect, linked to a perspective
+//!   analysis from `reasoning_analyze_perspectives`: the one given, or the
+//!   latest in the session
+//! - The latest earlier analysis of the same subject, from any session, is
+//!   passed along so a new analysis can say what changed
+//!
+//! Analyses are kept in the `swot_analyses` table and can be listed by
+//! subject from later sessions.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::SWOT_ANALYSIS_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Invocation, PerspectiveAnalysis, SharedStorage, SwotAnalysis, SwotFilter};
+
+/// Analyses listed when the caller does not say.
+pub const DEFAULT_SWOT_LIST_LIMIT: u32 = 10;
+
+/// Most analyses one listing may return.
+pub const MAX_SWOT_LIST_LIMIT: u32 = 50;
+
+fn default_list_limit() -> u32 {
+    DEFAULT_SWOT_LIST_LIMIT
+}
+
+/// Input parameters for SWOT analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwotParams {
+    /// The subject to analyze (a product, plan, organization, ...)
+    pub subject: String,
+    /// Optional background on the subject and its environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Perspective analysis whose stakeholders items should reference
+    /// (defaults to the latest in the session)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspective_id: Option<String>,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Input parameters for listing stored SWOT analyses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwotListParams {
+    /// Only list analyses whose subject contains this text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Only list analyses made in this session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Most analyses to return, newest first
+    #[serde(default = "default_list_limit")]
+    pub limit: u32,
+}
+
+/// One item of a SWOT quadrant
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SwotItem {
+    /// The strength, weakness, opportunity or threat
+    pub factor: String,
+    /// Facts or observations supporting it
+    #[serde(default)]
+    pub evidence: Vec<String>,
+    /// How well the evidence supports it (0.0-1.0)
+    #[serde(default)]
+    pub confidence: f64,
+    /// Stakeholders of the linked perspective analysis it affects
+    #[serde(default)]
+    pub stakeholders: Vec<String>,
+}
+
+/// Result of SWOT analysis
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SwotResult {
+    /// Stored analysis ID
+    pub swot_id: String,
+    /// The session ID
+    pub session_id: String,
+    /// The subject analyzed
+    pub subject: String,
+    /// Internal advantages
+    pub strengths: Vec<SwotItem>,
+    /// Internal disadvantages
+    pub weaknesses: Vec<SwotItem>,
+    /// External factors to exploit
+    pub opportunities: Vec<SwotItem>,
+    /// External factors that could cause trouble
+    pub threats: Vec<SwotItem>,
+    /// Perspective analysis the stakeholder names refer to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspective_id: Option<String>,
+    /// Earlier analysis of the same subject the pipe was shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_swot_id: Option<String>,
+    /// Overall strategic assessment
+    pub summary: String,
+    /// Confidence in the analysis (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Result of listing SWOT analyses
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SwotListResult {
+    /// Matching analyses, newest first
+    pub analyses: Vec<SwotResult>,
+}
+
+/// SWOT mode handler.
+#[derive(Clone)]
+pub struct SwotMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Decision framework pipe producing the analysis
+    decision_pipe: String,
+}
+
+impl SwotMode {
+    /// Create a new SWOT mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let decision_pipe = config
+            .pipes
+            .decision
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "decision-framework-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            decision_pipe,
+        }
+    }
+
+    /// Analyze a subject's strengths, weaknesses, opportunities and threats
+    pub async fn analyze(&self, params: SwotParams) -> AppResult<SwotResult> {
+        let start = Instant::now();
+
+        if params.subject.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "subject".to_string(),
+                reason: "Subject cannot be empty".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let session = storage
+            .get_or_create_session(&params.session_id, "swot")
+            .await?;
+        debug!(session_id = %session.id, "Processing SWOT request");
+
+        let perspective =
+            match params.perspective_id {
+                Some(ref id) => Some(storage.get_perspective(id).await?.ok_or_else(|| {
+                    ToolError::Validation {
+                        field: "perspective_id".to_string(),
+                        reason: format!("Perspective analysis not found: {}", id),
+                    }
+                })?),
+                // Newest first
+                None => storage
+                    .get_session_perspectives(&session.id)
+                    .await?
+                    .into_iter()
+                    .next(),
+            };
+        let stakeholders = perspective
+            .as_ref()
+            .map(stakeholder_briefs)
+            .unwrap_or_default();
+        let previous = storage
+            .find_swot_analyses(
+                SwotFilter::new()
+                    .with_subject(params.subject.trim())
+                    .with_limit(1),
+            )
+            .await?
+            .into_iter()
+            .next();
+
+        let pipe = self.core.select_pipe(&self.decision_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.swot",
+            serialize_for_log(&params, "reasoning.swot input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let prompt = swot_prompt(&params, &stakeholders, previous.as_ref());
+        let messages = vec![Message::system(SWOT_ANALYSIS_PROMPT), Message::user(prompt)];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let (mut parsed, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        for item in parsed
+            .strengths
+            .iter_mut()
+            .chain(parsed.weaknesses.iter_mut())
+            .chain(parsed.opportunities.iter_mut())
+            .chain(parsed.threats.iter_mut())
+        {
+            item.confidence = item.confidence.clamp(0.0, 1.0);
+            item.stakeholders = link_stakeholders(&item.stakeholders, &stakeholders);
+        }
+
+        let mut stored = SwotAnalysis::new(
+            &session.id,
+            params.subject.trim(),
+            &parsed.summary,
+            parsed.confidence,
+        )
+        .with_quadrants(
+            serde_json::to_value(&parsed.strengths).unwrap_or_default(),
+            serde_json::to_value(&parsed.weaknesses).unwrap_or_default(),
+            serde_json::to_value(&parsed.opportunities).unwrap_or_default(),
+            serde_json::to_value(&parsed.threats).unwrap_or_default(),
+        );
+        if let Some(ref perspective) = perspective {
+            stored = stored.with_perspective(&perspective.id);
+        }
+        if let Some(ref previous) = previous {
+            stored = stored.with_metadata(serde_json::json!({"previous_swot_id": previous.id}));
+        }
+        storage.create_swot_analysis(&stored).await?;
+
+        let result = to_result(stored);
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(serialize_for_log(&result, "reasoning.swot output"), latency);
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            swot_id = %result.swot_id,
+            linked_perspective = result.perspective_id.is_some(),
+            latency_ms = latency,
+            "SWOT analysis completed"
+        );
+
+        Ok(result)
+    }
+
+    /// List stored analyses across sessions, newest first
+    pub async fn list(&self, params: SwotListParams) -> AppResult<SwotListResult> {
+        if params.limit == 0 || params.limit > MAX_SWOT_LIST_LIMIT {
+            return Err(ToolError::Validation {
+                field: "limit".to_string(),
+                reason: format!("Must be between 1 and {}", MAX_SWOT_LIST_LIMIT),
+            }
+            .into());
+        }
+
+        let mut filter = SwotFilter::new().with_limit(params.limit);
+        if let Some(subject) = params.subject.filter(|s| !s.trim().is_empty()) {
+            filter = filter.with_subject(subject.trim());
+        }
+        if let Some(session_id) = params.session_id {
+            filter = filter.in_session(session_id);
+        }
+        let analyses = self.core.storage().find_swot_analyses(filter).await?;
+
+        Ok(SwotListResult {
+            analyses: analyses.into_iter().map(to_result).collect(),
+        })
+    }
+}
+
+/// Stakeholder of a perspective analysis as shown to the pipe
+#[derive(Debug, Clone, Deserialize)]
+struct StakeholderBrief {
+    name: String,
+    #[serde(default)]
+    perspective: String,
+}
+
+/// Stakeholders of a stored perspective analysis; malformed entries are
+/// skipped
+fn stakeholder_briefs(analysis: &PerspectiveAnalysis) -> Vec<StakeholderBrief> {
+    analysis
+        .stakeholders
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Names of `known` stakeholders in `named`, matched case-insensitively;
+/// names the perspective analysis does not know are dropped
+fn link_stakeholders(named: &[String], known: &[StakeholderBrief]) -> Vec<String> {
+    let mut linked: Vec<String> = Vec::new();
+    for name in named {
+        if let Some(stakeholder) = known
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name.trim()))
+        {
+            if !linked.contains(&stakeholder.name) {
+                linked.push(stakeholder.name.clone());
+            }
+        }
+    }
+    linked
+}
+
+fn swot_prompt(
+    params: &SwotParams,
+    stakeholders: &[StakeholderBrief],
+    previous: Option<&SwotAnalysis>,
+) -> String {
+    let mut prompt = format!("SUBJECT: {}", params.subject.trim());
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+    }
+    if !stakeholders.is_empty() {
+        prompt.push_str("\n\nSTAKEHOLDERS:");
+        for stakeholder in stakeholders {
+            prompt.push_str(&format!("\n- {}", stakeholder.name));
+            if !stakeholder.perspective.is_empty() {
+                prompt.push_str(&format!(": {}", stakeholder.perspective));
+            }
+        }
+    }
+    if let Some(previous) = previous {
+        prompt.push_str(&format!(
+            "\n\nEARLIER ANALYSIS ({}):\n{}",
+            previous.created_at.format("%Y-%m-%d"),
+            serde_json::json!({
+                "strengths": previous.strengths,
+                "weaknesses": previous.weaknesses,
+                "opportunities": previous.opportunities,
+                "threats": previous.threats,
+                "summary": previous.summary,
+            })
+        ));
+    }
+    prompt
+}
+
+fn parse_response(completion: &str) -> AppResult<SwotResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: SwotResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse SWOT analysis: {}", e),
+        })?;
+    if parsed.strengths.is_empty()
+        && parsed.weaknesses.is_empty()
+        && parsed.opportunities.is_empty()
+        && parsed.threats.is_empty()
+    {
+        return Err(ToolError::Reasoning {
+            message: "SWOT analysis has no items".to_string(),
+        }
+        .into());
+    }
+    Ok(parsed)
+}
+
+/// Quadrant items of a stored analysis; a malformed quadrant reads as empty
+fn items(quadrant: serde_json::Value) -> Vec<SwotItem> {
+    serde_json::from_value(quadrant).unwrap_or_default()
+}
+
+fn to_result(stored: SwotAnalysis) -> SwotResult {
+    let previous_swot_id = stored
+        .metadata
+        .as_ref()
+        .and_then(|m| m["previous_swot_id"].as_str())
+        .map(str::to_string);
+    SwotResult {
+        swot_id: stored.id,
+        session_id: stored.session_id,
+        subject: stored.subject,
+        strengths: items(stored.strengths),
+        weaknesses: items(stored.weaknesses),
+        opportunities: items(stored.opportunities),
+        threats: items(stored.threats),
+        perspective_id: stored.perspective_id,
+        previous_swot_id,
+        summary: stored.summary,
+        confidence: stored.confidence,
+    }
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SwotResponse {
+    #[serde(default)]
+    strengths: Vec<SwotItem>,
+    #[serde(default)]
+    weaknesses: Vec<SwotItem>,
+    #[serde(default)]
+    opportunities: Vec<SwotItem>,
+    #[serde(default)]
+    threats: Vec<SwotItem>,
+    #[serde(default)]
+    summary: String,
+    confidence: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn brief(name: &str) -> StakeholderBrief {
+        StakeholderBrief {
+            name: name.to_string(),
+            perspective: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_swot_params_defaults() {
+        let params: SwotParams = serde_json::from_value(json!({"subject": "Pricing"})).unwrap();
+        assert_eq!(params.subject, "Pricing");
+        assert!(params.perspective_id.is_none());
+        assert!(params.session_id.is_none());
+
+        let list: SwotListParams = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(list.limit, DEFAULT_SWOT_LIST_LIMIT);
+        assert!(list.subject.is_none());
+    }
+
+    #[test]
+    fn test_link_stakeholders() {
+        let known = [brief("Customers"), brief("Sales team")];
+        let named = [
+            "customers".to_string(),
+            "Investors".to_string(),
+            " Sales team ".to_string(),
+            "CUSTOMERS".to_string(),
+        ];
+        assert_eq!(
+            link_stakeholders(&named, &known),
+            ["Customers", "Sales team"]
+        );
+        assert!(link_stakeholders(&named, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_stakeholder_briefs_skip_malformed() {
+        let analysis = PerspectiveAnalysis::new(
+            "s1",
+            "Pricing",
+            json!([
+                {"name": "Customers", "perspective": "Want stable prices"},
+                {"role": "missing name"},
+                "not an object"
+            ]),
+            json!({}),
+            0.7,
+        );
+        let briefs = stakeholder_briefs(&analysis);
+        assert_eq!(briefs.len(), 1);
+        assert_eq!(briefs[0].name, "Customers");
+        assert_eq!(briefs[0].perspective, "Want stable prices");
+    }
+
+    #[test]
+    fn test_swot_prompt_sections() {
+        let params = SwotParams {
+            subject: " Pricing change ".to_string(),
+            context: Some("Costs rose 10%".to_string()),
+            perspective_id: None,
+            session_id: None,
+            generation: GenerationParams::default(),
+        };
+        let bare = swot_prompt(&params, &[], None);
+        assert!(bare.starts_with("SUBJECT: Pricing change\n"));
+        assert!(bare.contains("Costs rose 10%"));
+        assert!(!bare.contains("STAKEHOLDERS"));
+
+        let previous = SwotAnalysis::new("s0", "Pricing change", "Hold prices", 0.6);
+        let full = swot_prompt(&params, &[brief("Customers")], Some(&previous));
+        assert!(full.contains("STAKEHOLDERS:\n- Customers"));
+        assert!(full.contains("EARLIER ANALYSIS"));
+        assert!(full.contains("Hold prices"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let parsed = parse_response(
+            r#"{"strengths": [{"factor": "Brand", "evidence": ["Survey"], "confidence": 0.8}],
+                "confidence": 0.7}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.strengths[0].factor, "Brand");
+        assert!(parsed.strengths[0].stakeholders.is_empty());
+        assert!(parsed.threats.is_empty());
+
+        let empty = parse_response(r#"{"summary": "Nothing", "confidence": 0.5}"#).unwrap_err();
+        assert!(empty.to_string().contains("no items"), "{}", empty);
+    }
+
+    #[test]
+    fn test_stored_round_trip() {
+        let stored = SwotAnalysis::new("s1", "Pricing", "Worth it", 0.7)
+            .with_quadrants(
+                json!([{"factor": "Brand", "evidence": [], "confidence": 0.8, "stakeholders": []}]),
+                json!("malformed"),
+                json!([]),
+                json!([]),
+            )
+            .with_perspective("p1")
+            .with_metadata(json!({"previous_swot_id": "w0"}));
+        let id = stored.id.clone();
+        let result = to_result(stored);
+        assert_eq!(result.swot_id, id);
+        assert_eq!(result.strengths[0].factor, "Brand");
+        assert!(result.weaknesses.is_empty());
+        assert_eq!(result.perspective_id.as_deref(), Some("p1"));
+        assert_eq!(result.previous_swot_id.as_deref(), Some("w0"));
+    }
+}
@@ -0,0 +1,929 @@
+//! Synthesis mode - consolidating prior sessions into one briefing.
+//!
+//! The stored artifacts of each session (thoughts, decisions, perspective
+//! analyses, evidence assessments, probability updates, detections, risks and
+//! counterfactuals) are collected as numbered sources `[S1]`, `[S2]`, ... and
+//! the reflection pipe writes a briefing citing them. Citations are checked
+//! locally, and each key finding's confidence is combined with the confidence
+//! of the sources it cites. The briefing is rendered as Markdown and stored as
+//! a thought.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::SYNTHESIS_BRIEFING_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{Invocation, SharedStorage, Storage, Thought};
+
+/// Most sessions one briefing may draw on.
+pub const MAX_SYNTHESIS_SESSIONS: usize = 10;
+
+/// Most sources shown to the pipe, shared evenly between the sessions.
+pub const MAX_SYNTHESIS_SOURCES: usize = 60;
+
+/// Recent thoughts taken from each session when the caller does not say.
+pub const DEFAULT_THOUGHTS_PER_SESSION: usize = 5;
+
+/// Longest source text shown to the pipe, in characters.
+const MAX_SOURCE_CHARS: usize = 600;
+
+fn default_thoughts_per_session() -> usize {
+    DEFAULT_THOUGHTS_PER_SESSION
+}
+
+/// Input parameters for synthesizing sessions into a briefing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynthesizeParams {
+    /// Sessions to draw on, e.g. a decision, an evidence assessment and a premortem
+    pub session_ids: Vec<String>,
+    /// Optional question or audience the briefing should address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    /// Most recent thoughts taken from each session (0-20)
+    #[serde(default = "default_thoughts_per_session")]
+    pub thoughts_per_session: usize,
+    /// Session to store the briefing in (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Kind of stored artifact a briefing source is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BriefingSourceKind {
+    /// A reasoning thought.
+    Thought,
+    /// A decision analysis.
+    Decision,
+    /// A stakeholder perspective analysis.
+    Perspective,
+    /// An evidence assessment.
+    Evidence,
+    /// A Bayesian probability update.
+    Probability,
+    /// A bias, fallacy or calculation detection.
+    Detection,
+    /// A risk register entry.
+    Risk,
+    /// A counterfactual analysis.
+    Counterfactual,
+}
+
+impl BriefingSourceKind {
+    /// Get the kind name as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BriefingSourceKind::Thought => "thought",
+            BriefingSourceKind::Decision => "decision",
+            BriefingSourceKind::Perspective => "perspective",
+            BriefingSourceKind::Evidence => "evidence",
+            BriefingSourceKind::Probability => "probability",
+            BriefingSourceKind::Detection => "detection",
+            BriefingSourceKind::Risk => "risk",
+            BriefingSourceKind::Counterfactual => "counterfactual",
+        }
+    }
+}
+
+/// A stored artifact the briefing draws on
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BriefingSource {
+    /// Reference used in the briefing, e.g. `S3`
+    pub reference: String,
+    /// Session the artifact belongs to
+    pub session_id: String,
+    /// Kind of artifact
+    pub kind: BriefingSourceKind,
+    /// ID of the artifact
+    pub artifact_id: String,
+    /// One-line summary of the artifact
+    pub summary: String,
+    /// The artifact's own confidence, where it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Number of times the briefing cites it
+    pub citations: usize,
+}
+
+/// A section of the briefing
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BriefingSection {
+    /// Section heading
+    pub heading: String,
+    /// Section text
+    pub content: String,
+    /// References of the sources it draws on
+    pub sources: Vec<String>,
+}
+
+/// A key finding of the briefing
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BriefingFinding {
+    /// The finding
+    pub statement: String,
+    /// References of the sources supporting it
+    pub sources: Vec<String>,
+    /// Confidence combined from the pipe's and the cited sources' confidence
+    pub confidence: f64,
+    /// Whether it cites no known source
+    pub unsupported: bool,
+}
+
+/// A disagreement between sources
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BriefingConflict {
+    /// What the sources disagree on
+    pub description: String,
+    /// References of the sources involved
+    pub sources: Vec<String>,
+}
+
+/// Result of synthesizing sessions into a briefing
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SynthesizeResult {
+    /// Session the briefing is stored in
+    pub session_id: String,
+    /// Thought holding the briefing document
+    pub thought_id: String,
+    /// Briefing title
+    pub title: String,
+    /// Executive summary
+    pub summary: String,
+    /// Sections, in order
+    pub sections: Vec<BriefingSection>,
+    /// Key findings, most confident first
+    pub key_findings: Vec<BriefingFinding>,
+    /// Disagreements between sources
+    pub conflicts: Vec<BriefingConflict>,
+    /// Questions the sources leave open
+    pub open_questions: Vec<String>,
+    /// Recommended next steps
+    pub recommendations: Vec<String>,
+    /// The sources, in reference order
+    pub sources: Vec<BriefingSource>,
+    /// Sources left out to stay within the source limit
+    pub omitted_sources: usize,
+    /// Confidence of the briefing as a whole
+    pub combined_confidence: f64,
+    /// The briefing rendered as Markdown
+    pub document: String,
+}
+
+/// Synthesis mode handler.
+#[derive(Clone)]
+pub struct SynthesisMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// Pipe that writes the briefing
+    reflection_pipe: String,
+}
+
+impl SynthesisMode {
+    /// Create a new synthesis mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        Self {
+            core: ModeCore::new(storage, backend),
+            reflection_pipe: config.pipes.reflection.clone(),
+        }
+    }
+
+    /// Consolidate the artifacts of several sessions into one briefing
+    pub async fn synthesize(&self, params: SynthesizeParams) -> AppResult<SynthesizeResult> {
+        let start = Instant::now();
+
+        let mut seen = HashSet::new();
+        let session_ids: Vec<String> = params
+            .session_ids
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+            .collect();
+        if session_ids.is_empty() || session_ids.len() > MAX_SYNTHESIS_SESSIONS {
+            return Err(ToolError::Validation {
+                field: "session_ids".to_string(),
+                reason: format!(
+                    "Must name between 1 and {} sessions",
+                    MAX_SYNTHESIS_SESSIONS
+                ),
+            }
+            .into());
+        }
+        if params.thoughts_per_session > 20 {
+            return Err(ToolError::Validation {
+                field: "thoughts_per_session".to_string(),
+                reason: "Must be between 0 and 20".to_string(),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let per_session = MAX_SYNTHESIS_SOURCES / session_ids.len();
+        let mut sources = Vec::new();
+        let mut omitted_sources = 0;
+        for session_id in &session_ids {
+            if storage.get_session(session_id).await?.is_none() {
+                return Err(
+                    ToolError::Session(format!("Session not found: {}", session_id)).into(),
+                );
+            }
+            let mut collected =
+                collect_sources(storage, session_id, params.thoughts_per_session).await?;
+            // Keep the most recent artifacts of each session
+            if collected.len() > per_session {
+                omitted_sources += collected.len() - per_session;
+                collected.drain(..collected.len() - per_session);
+            }
+            sources.extend(collected);
+        }
+        if sources.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_ids".to_string(),
+                reason: "The sessions hold no artifacts to synthesize".to_string(),
+            }
+            .into());
+        }
+        for (i, source) in sources.iter_mut().enumerate() {
+            source.reference = format!("S{}", i + 1);
+        }
+
+        let session = storage
+            .get_or_create_session(&params.session_id, "synthesis")
+            .await?;
+        debug!(
+            session_id = %session.id,
+            sessions = session_ids.len(),
+            sources = sources.len(),
+            "Processing synthesis request"
+        );
+
+        let pipe = self.core.select_pipe(&self.reflection_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.synthesize",
+            serialize_for_log(&params, "reasoning.synthesize input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(SYNTHESIS_BRIEFING_PROMPT),
+            Message::user(source_prompt(&sources, &params.focus)),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let latency = start.elapsed().as_millis() as i64;
+        let (response, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&response, "reasoning.synthesize output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        let mut result = build_briefing(response, sources, omitted_sources);
+        result.document = render_document(&result);
+
+        let thought = Thought::new(&session.id, &result.document, "synthesis")
+            .with_confidence(result.combined_confidence)
+            .with_metadata(serde_json::json!({
+                "title": result.title,
+                "source_sessions": session_ids,
+                "sources": result
+                    .sources
+                    .iter()
+                    .map(|s| serde_json::json!({"reference": s.reference, "artifact_id": s.artifact_id}))
+                    .collect::<Vec<_>>(),
+            }));
+        storage.create_thought(&thought).await?;
+
+        info!(
+            session_id = %session.id,
+            sources = result.sources.len(),
+            findings = result.key_findings.len(),
+            combined_confidence = result.combined_confidence,
+            latency_ms = latency,
+            "Synthesis completed"
+        );
+
+        result.session_id = session.id;
+        result.thought_id = thought.id;
+        Ok(result)
+    }
+}
+
+/// A session's artifacts, oldest first, without references assigned
+async fn collect_sources(
+    storage: &dyn Storage,
+    session_id: &str,
+    thoughts_per_session: usize,
+) -> AppResult<Vec<BriefingSource>> {
+    let mut dated = Vec::new();
+    let source =
+        |kind, artifact_id: &str, summary: String, confidence: Option<f64>| BriefingSource {
+            reference: String::new(),
+            session_id: session_id.to_string(),
+            kind,
+            artifact_id: artifact_id.to_string(),
+            summary: clip(&summary),
+            confidence: confidence.map(|c| c.clamp(0.0, 1.0)),
+            citations: 0,
+        };
+
+    let thoughts = storage.get_session_thoughts(session_id).await?;
+    let skip = thoughts.len().saturating_sub(thoughts_per_session);
+    for t in thoughts.iter().skip(skip) {
+        let summary = format!("({}) {}", t.mode, t.content);
+        dated.push((
+            t.created_at,
+            source(
+                BriefingSourceKind::Thought,
+                &t.id,
+                summary,
+                Some(t.confidence),
+            ),
+        ));
+    }
+    for d in storage.get_session_decisions(session_id).await? {
+        let option = d.recommendation.get("option").and_then(|v| v.as_str());
+        let rationale = d.recommendation.get("rationale").and_then(|v| v.as_str());
+        let summary = match option {
+            Some(option) => format!(
+                "{} Recommended: {}. {}",
+                d.question,
+                option,
+                rationale.unwrap_or_default()
+            ),
+            None => d.question.clone(),
+        };
+        let confidence = d.recommendation.get("confidence").and_then(|v| v.as_f64());
+        dated.push((
+            d.created_at,
+            source(BriefingSourceKind::Decision, &d.id, summary, confidence),
+        ));
+    }
+    for p in storage.get_session_perspectives(session_id).await? {
+        let recommendation = p
+            .synthesis
+            .get("recommendation")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let summary = format!("{} {}", p.topic, recommendation);
+        dated.push((
+            p.created_at,
+            source(
+                BriefingSourceKind::Perspective,
+                &p.id,
+                summary,
+                Some(p.confidence),
+            ),
+        ));
+    }
+    for e in storage.get_session_evidence_assessments(session_id).await? {
+        let level = e
+            .overall_support
+            .get("level")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let explanation = e
+            .overall_support
+            .get("explanation")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let summary = format!("Claim: {} Support: {}. {}", e.claim, level, explanation);
+        let confidence = e.overall_support.get("confidence").and_then(|v| v.as_f64());
+        dated.push((
+            e.created_at,
+            source(BriefingSourceKind::Evidence, &e.id, summary, confidence),
+        ));
+    }
+    for u in storage.get_session_probability_updates(session_id).await? {
+        let verbal = u
+            .interpretation
+            .get("verbal")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let summary = format!(
+            "{} Probability {:.2} -> {:.2} ({})",
+            u.hypothesis, u.prior, u.posterior, verbal
+        );
+        dated.push((
+            u.created_at,
+            source(BriefingSourceKind::Probability, &u.id, summary, None),
+        ));
+    }
+    for d in storage.get_session_detections(session_id).await? {
+        let summary = format!(
+            "{} {} (severity {}): {}",
+            d.detection_type, d.detected_issue, d.severity, d.explanation
+        );
+        dated.push((
+            d.created_at,
+            source(
+                BriefingSourceKind::Detection,
+                &d.id,
+                summary,
+                Some(d.confidence),
+            ),
+        ));
+    }
+    for r in storage.get_session_risks(session_id).await? {
+        let summary = format!(
+            "{} (likelihood {}, impact {}): {}",
+            r.title, r.likelihood, r.impact, r.description
+        );
+        dated.push((
+            r.created_at,
+            source(BriefingSourceKind::Risk, &r.id, summary, None),
+        ));
+    }
+    for c in storage.get_session_counterfactuals(session_id).await? {
+        let summary = format!(
+            "{} If {}: outcome changes by {:.2}",
+            c.question, c.intervention, c.outcome_delta
+        );
+        dated.push((
+            c.created_at,
+            source(
+                BriefingSourceKind::Counterfactual,
+                &c.id,
+                summary,
+                Some(c.confidence),
+            ),
+        ));
+    }
+
+    dated.sort_by_key(|(created_at, _)| *created_at);
+    Ok(dated.into_iter().map(|(_, source)| source).collect())
+}
+
+/// Whitespace-collapsed text cut to `MAX_SOURCE_CHARS`
+fn clip(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(MAX_SOURCE_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+fn source_prompt(sources: &[BriefingSource], focus: &Option<String>) -> String {
+    let mut prompt = String::new();
+    if let Some(focus) = focus {
+        let _ = writeln!(prompt, "Focus: {}\n", focus);
+    }
+    let _ = writeln!(prompt, "Sources:");
+    for source in sources {
+        let confidence = source
+            .confidence
+            .map(|c| format!(", confidence {:.2}", c))
+            .unwrap_or_default();
+        let _ = writeln!(
+            prompt,
+            "[{}] ({}, session {}{}) {}",
+            source.reference,
+            source.kind.as_str(),
+            source.session_id,
+            confidence,
+            source.summary
+        );
+    }
+    prompt
+}
+
+/// The reference a cited source label names, e.g. `[s3]` -> `S3`
+fn normalize_reference(label: &str) -> Option<String> {
+    let label = label.trim().trim_start_matches('[').trim_end_matches(']');
+    let number = label
+        .strip_prefix('S')
+        .or_else(|| label.strip_prefix('s'))?;
+    let number: usize = number.trim().parse().ok()?;
+    Some(format!("S{}", number))
+}
+
+/// Known references among `labels`, deduplicated, counting each citation
+fn resolve_references(labels: &[String], sources: &mut [BriefingSource]) -> Vec<String> {
+    let mut resolved: Vec<String> = Vec::new();
+    for reference in labels.iter().filter_map(|l| normalize_reference(l)) {
+        if resolved.contains(&reference) {
+            continue;
+        }
+        if let Some(source) = sources.iter_mut().find(|s| s.reference == reference) {
+            source.citations += 1;
+            resolved.push(reference);
+        }
+    }
+    resolved
+}
+
+/// Mean confidence of the cited sources that have one
+fn source_support(references: &[String], sources: &[BriefingSource]) -> Option<f64> {
+    let confidences: Vec<f64> = sources
+        .iter()
+        .filter(|s| references.contains(&s.reference))
+        .filter_map(|s| s.confidence)
+        .collect();
+    (!confidences.is_empty()).then(|| confidences.iter().sum::<f64>() / confidences.len() as f64)
+}
+
+/// Check the pipe's citations against the sources and combine confidences.
+///
+/// A finding's confidence is the mean of the pipe's confidence in it and the
+/// mean confidence of the sources it cites; a finding citing no known source
+/// keeps half the pipe's confidence. The briefing's combined confidence is the
+/// mean of its findings' confidences weighted by how many sources each cites
+/// (at least one), or the pipe's overall confidence when there are none.
+fn build_briefing(
+    response: BriefingResponse,
+    mut sources: Vec<BriefingSource>,
+    omitted_sources: usize,
+) -> SynthesizeResult {
+    let sections = response
+        .sections
+        .iter()
+        .filter(|s| !s.content.trim().is_empty())
+        .map(|s| BriefingSection {
+            heading: s.heading.trim().to_string(),
+            content: s.content.trim().to_string(),
+            sources: resolve_references(&s.sources, &mut sources),
+        })
+        .collect();
+
+    let mut key_findings: Vec<BriefingFinding> = response
+        .key_findings
+        .iter()
+        .filter(|f| !f.statement.trim().is_empty())
+        .map(|f| {
+            let references = resolve_references(&f.sources, &mut sources);
+            let stated = f.confidence.unwrap_or(response.confidence).clamp(0.0, 1.0);
+            let confidence = match source_support(&references, &sources) {
+                Some(support) => (stated + support) / 2.0,
+                None if references.is_empty() => stated / 2.0,
+                None => stated,
+            };
+            BriefingFinding {
+                statement: f.statement.trim().to_string(),
+                unsupported: references.is_empty(),
+                sources: references,
+                confidence,
+            }
+        })
+        .collect();
+    key_findings.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let conflicts = response
+        .conflicts
+        .iter()
+        .filter(|c| !c.description.trim().is_empty())
+        .map(|c| BriefingConflict {
+            description: c.description.trim().to_string(),
+            sources: resolve_references(&c.sources, &mut sources),
+        })
+        .collect();
+
+    let weights: f64 = key_findings
+        .iter()
+        .map(|f| f.sources.len().max(1) as f64)
+        .sum();
+    let combined_confidence = if key_findings.is_empty() {
+        response.confidence
+    } else {
+        key_findings
+            .iter()
+            .map(|f| f.confidence * f.sources.len().max(1) as f64)
+            .sum::<f64>()
+            / weights
+    };
+
+    SynthesizeResult {
+        session_id: String::new(),
+        thought_id: String::new(),
+        title: response.title,
+        summary: response.summary,
+        sections,
+        key_findings,
+        conflicts,
+        open_questions: non_empty(&response.open_questions),
+        recommendations: non_empty(&response.recommendations),
+        sources,
+        omitted_sources,
+        combined_confidence: combined_confidence.clamp(0.0, 1.0),
+        document: String::new(),
+    }
+}
+
+fn non_empty(items: &[String]) -> Vec<String> {
+    items
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn citation(references: &[String]) -> String {
+    references
+        .iter()
+        .map(|r| format!("[{}]", r))
+        .collect::<String>()
+}
+
+/// Render the briefing as a Markdown document with a source list
+fn render_document(result: &SynthesizeResult) -> String {
+    let mut doc = String::new();
+    let sessions: HashSet<&str> = result
+        .sources
+        .iter()
+        .map(|s| s.session_id.as_str())
+        .collect();
+    let _ = writeln!(doc, "# {}\n", result.title);
+    let _ = writeln!(doc, "{}\n", result.summary);
+    let _ = writeln!(
+        doc,
+        "_Combined confidence {:.0}% from {} sources in {} sessions_",
+        result.combined_confidence * 100.0,
+        result.sources.len(),
+        sessions.len()
+    );
+    for section in &result.sections {
+        let _ = writeln!(doc, "\n## {}\n", section.heading);
+        let _ = writeln!(doc, "{} {}", section.content, citation(&section.sources));
+    }
+    if !result.key_findings.is_empty() {
+        let _ = writeln!(doc, "\n## Key Findings\n");
+        for finding in &result.key_findings {
+            let support = if finding.unsupported {
+                "unsupported".to_string()
+            } else {
+                citation(&finding.sources)
+            };
+            let _ = writeln!(
+                doc,
+                "- {} {} ({:.0}%)",
+                finding.statement,
+                support,
+                finding.confidence * 100.0
+            );
+        }
+    }
+    if !result.conflicts.is_empty() {
+        let _ = writeln!(doc, "\n## Conflicts\n");
+        for conflict in &result.conflicts {
+            let _ = writeln!(
+                doc,
+                "- {} {}",
+                conflict.description,
+                citation(&conflict.sources)
+            );
+        }
+    }
+    for (heading, items) in [
+        ("Open Questions", &result.open_questions),
+        ("Recommendations", &result.recommendations),
+    ] {
+        if !items.is_empty() {
+            let _ = writeln!(doc, "\n## {}\n", heading);
+            for item in items {
+                let _ = writeln!(doc, "- {}", item);
+            }
+        }
+    }
+    let _ = writeln!(doc, "\n## Sources\n");
+    for source in &result.sources {
+        let _ = writeln!(
+            doc,
+            "- [{}] {} `{}` in session `{}`: {}",
+            source.reference,
+            source.kind.as_str(),
+            source.artifact_id,
+            source.session_id,
+            source.summary
+        );
+    }
+    doc.trim_end().to_string()
+}
+
+fn parse_response(completion: &str) -> AppResult<BriefingResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let mut parsed: BriefingResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse briefing: {}", e),
+        })?;
+    if parsed.summary.trim().is_empty() {
+        return Err(ToolError::Reasoning {
+            message: "Briefing has no summary".to_string(),
+        }
+        .into());
+    }
+    parsed.summary = parsed.summary.trim().to_string();
+    parsed.title = match parsed.title.trim() {
+        "" => "Briefing".to_string(),
+        title => title.to_string(),
+    };
+    parsed.confidence = parsed.confidence.clamp(0.0, 1.0);
+    Ok(parsed)
+}
+
+// Internal response types for parsing
+
+fn default_confidence() -> f64 {
+    0.7
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BriefingResponse {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    sections: Vec<SectionDraft>,
+    #[serde(default)]
+    key_findings: Vec<FindingDraft>,
+    #[serde(default)]
+    conflicts: Vec<ConflictDraft>,
+    #[serde(default)]
+    open_questions: Vec<String>,
+    #[serde(default)]
+    recommendations: Vec<String>,
+    #[serde(default = "default_confidence")]
+    confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SectionDraft {
+    #[serde(default)]
+    heading: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FindingDraft {
+    #[serde(default)]
+    statement: String,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConflictDraft {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    sources: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sources() -> Vec<BriefingSource> {
+        [
+            (BriefingSourceKind::Decision, Some(0.8)),
+            (BriefingSourceKind::Evidence, Some(0.6)),
+            (BriefingSourceKind::Risk, None),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (kind, confidence))| BriefingSource {
+            reference: format!("S{}", i + 1),
+            session_id: if i == 0 { "a" } else { "b" }.to_string(),
+            kind,
+            artifact_id: format!("artifact-{}", i + 1),
+            summary: format!("Source {}", i + 1),
+            confidence,
+            citations: 0,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: SynthesizeParams =
+            serde_json::from_value(json!({"session_ids": ["a", "b"]})).unwrap();
+        assert_eq!(params.thoughts_per_session, DEFAULT_THOUGHTS_PER_SESSION);
+        assert!(params.focus.is_none());
+    }
+
+    #[test]
+    fn test_normalize_reference() {
+        assert_eq!(normalize_reference("S3").as_deref(), Some("S3"));
+        assert_eq!(normalize_reference(" [s12] ").as_deref(), Some("S12"));
+        assert_eq!(normalize_reference("S03").as_deref(), Some("S3"));
+        assert_eq!(normalize_reference("source 3"), None);
+        assert_eq!(normalize_reference("S"), None);
+    }
+
+    #[test]
+    fn test_clip() {
+        assert_eq!(clip("a \n b"), "a b");
+        let long = "é".repeat(MAX_SOURCE_CHARS + 5);
+        let clipped = clip(&long);
+        assert_eq!(clipped.chars().count(), MAX_SOURCE_CHARS + 3);
+        assert!(clipped.ends_with("..."));
+    }
+
+    #[test]
+    fn test_build_briefing() {
+        let response: BriefingResponse = serde_json::from_value(json!({
+            "title": "Launch",
+            "summary": "Go ahead with safeguards.",
+            "sections": [
+                {"heading": "Decision", "content": "Option A won.", "sources": ["S1", "[S1]", "S9"]},
+                {"heading": "Empty", "content": " "}
+            ],
+            "key_findings": [
+                {"statement": "Option A is best", "sources": ["S1", "S2"], "confidence": 0.9},
+                {"statement": "Rollout is risky", "sources": ["S3"]},
+                {"statement": "Users want it", "sources": ["S7"], "confidence": 0.8}
+            ],
+            "conflicts": [{"description": "Evidence is weaker than the decision assumes", "sources": ["S1", "S2"]}],
+            "open_questions": ["Budget?", ""],
+            "confidence": 0.7
+        }))
+        .unwrap();
+        let result = build_briefing(response, sources(), 2);
+
+        assert_eq!(result.sections.len(), 1);
+        assert_eq!(result.sections[0].sources, ["S1"]);
+        // (0.9 + mean(0.8, 0.6)) / 2, the pipe's confidence where no source
+        // has one, and half of it where none is cited
+        let findings = &result.key_findings;
+        assert!((findings[0].confidence - 0.8).abs() < 1e-9);
+        assert_eq!(findings[1].statement, "Rollout is risky");
+        assert!((findings[1].confidence - 0.7).abs() < 1e-9);
+        assert!(findings[2].unsupported);
+        assert!((findings[2].confidence - 0.4).abs() < 1e-9);
+        // Weighted by cited sources: (0.8 * 2 + 0.7 + 0.4) / 4
+        assert!((result.combined_confidence - 0.675).abs() < 1e-9);
+
+        let citations: Vec<usize> = result.sources.iter().map(|s| s.citations).collect();
+        assert_eq!(citations, [3, 2, 1]);
+        assert_eq!(result.open_questions, ["Budget?"]);
+        assert_eq!(result.omitted_sources, 2);
+    }
+
+    #[test]
+    fn test_render_document() {
+        let response: BriefingResponse = serde_json::from_value(json!({
+            "title": "Launch",
+            "summary": "Go ahead.",
+            "sections": [{"heading": "Decision", "content": "Option A won.", "sources": ["S1"]}],
+            "key_findings": [{"statement": "Unsourced", "confidence": 0.5}],
+            "recommendations": ["Ship it"]
+        }))
+        .unwrap();
+        let result = build_briefing(response, sources(), 0);
+        let doc = render_document(&result);
+        assert!(doc.starts_with("# Launch\n\nGo ahead.\n"));
+        assert!(doc.contains("from 3 sources in 2 sessions"));
+        assert!(doc.contains("## Decision\n\nOption A won. [S1]"));
+        assert!(doc.contains("- Unsourced unsupported (25%)"));
+        assert!(doc.contains("## Recommendations\n\n- Ship it"));
+        assert!(doc.ends_with("- [S3] risk `artifact-3` in session `b`: Source 3"));
+        assert!(!doc.contains("## Conflicts"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let parsed = parse_response(r#"{"summary": " Go. ", "confidence": 2}"#).unwrap();
+        assert_eq!(parsed.title, "Briefing");
+        assert_eq!(parsed.summary, "Go.");
+        assert_eq!(parsed.confidence, 1.0);
+        assert!(parse_response(r#"{"title": "x"}"#).is_err());
+    }
+}
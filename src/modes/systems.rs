@@ -0,0 +1,892 @@
+//! Systems thinking mode - stocks, flows, causal links and feedback loops.
+//!
+//! The GoT pipe models a situation as elements (stocks, flows and other
+//! variables) joined by causal links, each raising (`positive`) or lowering
+//! (`negative`) its effect. The analysis is local:
+//! - feedback loops are the elementary cycles of the links; a loop with an
+//!   even number of negative links is reinforcing, otherwise balancing
+//! - leverage points are the elements on the most loops, ties broken by how
+//!   many elements they drive
+//! - the model is rendered as a Mermaid flowchart
+//!
+//! Models are stored in the graph tables: the situation is the root node,
+//! each element a node, and each causal link a `supports` (positive) or
+//! `contradicts` (negative) edge between elements.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::{debug, info};
+
+use super::{extract_json_from_completion, serialize_for_log, ModeCore};
+use crate::config::Config;
+use crate::error::{AppResult, ToolError};
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::SYSTEMS_MODEL_PROMPT;
+use crate::providers::SharedBackend;
+use crate::storage::{EdgeType, GraphEdge, GraphNode, Invocation, NodeType, SharedStorage};
+
+/// Elements modeled when the caller does not say.
+pub const DEFAULT_SYSTEM_ELEMENTS: usize = 10;
+
+/// Most elements one model may have.
+pub const MAX_SYSTEM_ELEMENTS: usize = 20;
+
+/// Most feedback loops reported for one model.
+pub const MAX_FEEDBACK_LOOPS: usize = 50;
+
+/// Most leverage points reported for one model.
+pub const MAX_LEVERAGE_POINTS: usize = 5;
+
+/// Metadata key under which element details are stored on graph nodes.
+const ELEMENT_METADATA_KEY: &str = "system_element";
+
+/// Metadata key under which link details are stored on graph edges.
+const LINK_METADATA_KEY: &str = "causal_link";
+
+fn default_max_elements() -> usize {
+    DEFAULT_SYSTEM_ELEMENTS
+}
+
+/// Input parameters for systems modeling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemsParams {
+    /// The situation to model
+    pub situation: String,
+    /// Optional background on the situation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Most elements to model
+    #[serde(default = "default_max_elements")]
+    pub max_elements: usize,
+    /// Optional session ID (creates new if not provided)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// What an element of the system is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementKind {
+    /// An accumulation, like a population or a backlog.
+    Stock,
+    /// A rate filling or draining a stock, like births.
+    Flow,
+    /// Any other quantity, like a policy or a perception.
+    #[default]
+    Variable,
+}
+
+/// Which way a cause moves its effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Polarity {
+    /// More of the cause means more of the effect.
+    #[serde(alias = "+")]
+    Positive,
+    /// More of the cause means less of the effect.
+    #[serde(alias = "-")]
+    Negative,
+}
+
+/// Whether a feedback loop amplifies or counteracts change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopKind {
+    /// Even number of negative links: change feeds on itself.
+    Reinforcing,
+    /// Odd number of negative links: change is pushed back.
+    Balancing,
+}
+
+/// An element of the system
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SystemElement {
+    /// Graph node holding the element
+    pub element_id: String,
+    /// Element name
+    pub name: String,
+    /// What the element is
+    pub kind: ElementKind,
+    /// What the element measures
+    pub description: String,
+}
+
+/// A causal link between two elements
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CausalLink {
+    /// Graph edge holding the link
+    pub link_id: String,
+    /// Cause element ID
+    pub from: String,
+    /// Effect element ID
+    pub to: String,
+    /// Which way the cause moves the effect
+    pub polarity: Polarity,
+    /// Whether the effect lags noticeably behind the cause
+    pub delayed: bool,
+    /// Why the cause moves the effect
+    pub rationale: String,
+}
+
+/// A feedback loop found in the links
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeedbackLoop {
+    /// Loop label: R1, R2, ... for reinforcing loops and B1, B2, ... for
+    /// balancing ones
+    pub label: String,
+    /// Whether the loop amplifies or counteracts change
+    pub kind: LoopKind,
+    /// Element IDs around the loop, starting from its first element
+    pub elements: Vec<String>,
+    /// The loop as element names, e.g. "Births → Population → Births"
+    pub path: String,
+    /// Whether any link of the loop is delayed
+    pub delayed: bool,
+}
+
+/// An element where an intervention moves the system most
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LeveragePoint {
+    /// The element
+    pub element_id: String,
+    /// Element name
+    pub name: String,
+    /// Feedback loops the element is on
+    pub loops: u32,
+    /// Elements the element directly drives
+    pub out_links: u32,
+    /// Intervention the model proposed at the element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intervention: Option<String>,
+}
+
+/// Result of systems modeling
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SystemsResult {
+    /// The session ID
+    pub session_id: String,
+    /// Root graph node holding the situation
+    pub model_id: String,
+    /// The situation modeled
+    pub situation: String,
+    /// Stocks, flows and variables
+    pub elements: Vec<SystemElement>,
+    /// Causal links between elements
+    pub links: Vec<CausalLink>,
+    /// Feedback loops, shortest first
+    pub loops: Vec<FeedbackLoop>,
+    /// Elements on the most loops, highest leverage first
+    pub leverage_points: Vec<LeveragePoint>,
+    /// The model as a Mermaid flowchart
+    pub mermaid: String,
+    /// How the system behaves
+    pub summary: String,
+    /// Confidence in the model (0.0-1.0)
+    pub confidence: f64,
+}
+
+/// Systems thinking mode handler.
+#[derive(Clone)]
+pub struct SystemsMode {
+    /// Core infrastructure
+    core: ModeCore,
+    /// GoT pipe modeling the system
+    got_pipe: String,
+}
+
+impl SystemsMode {
+    /// Create a new systems thinking mode handler
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
+        let got_pipe = config
+            .pipes
+            .got
+            .as_ref()
+            .and_then(|c| c.pipe.clone())
+            .unwrap_or_else(|| "got-reasoning-v1".to_string());
+        Self {
+            core: ModeCore::new(storage, backend),
+            got_pipe,
+        }
+    }
+
+    /// Model a situation and find its feedback loops and leverage points
+    pub async fn model(&self, params: SystemsParams) -> AppResult<SystemsResult> {
+        let start = Instant::now();
+
+        if params.situation.trim().is_empty() {
+            return Err(ToolError::Validation {
+                field: "situation".to_string(),
+                reason: "Situation cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if params.max_elements < 2 || params.max_elements > MAX_SYSTEM_ELEMENTS {
+            return Err(ToolError::Validation {
+                field: "max_elements".to_string(),
+                reason: format!("Must be between 2 and {}", MAX_SYSTEM_ELEMENTS),
+            }
+            .into());
+        }
+        params.generation.validate()?;
+
+        let storage = self.core.storage();
+        let session = storage
+            .get_or_create_session(&params.session_id, "systems")
+            .await?;
+        debug!(session_id = %session.id, "Processing systems modeling request");
+
+        let pipe = self.core.select_pipe(&self.got_pipe);
+        let mut invocation = Invocation::new(
+            "reasoning.systems",
+            serialize_for_log(&params, "reasoning.systems input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe);
+
+        let messages = vec![
+            Message::system(SYSTEMS_MODEL_PROMPT),
+            Message::user(systems_prompt(&params)),
+        ];
+        let request = PipeRequest::new(&pipe, messages).with_generation(params.generation);
+        let result = match self.core.backend().call_pipe(request).await {
+            Ok(response) => parse_response(&response.completion)
+                .map(|parsed| (parsed, response.cache_hit, response.fallback)),
+            Err(e) => Err(e.into()),
+        };
+        let (parsed, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                storage.log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        let confidence = parsed.confidence.clamp(0.0, 1.0);
+        let summary = parsed.summary.clone();
+
+        let root = GraphNode::new(&session.id, params.situation.trim())
+            .with_type(NodeType::Root)
+            .as_root()
+            .with_score(confidence)
+            .with_metadata(serde_json::json!({"systems_summary": summary}));
+        let (elements, links) = build_model(&session.id, &root.id, parsed, params.max_elements);
+        let mut nodes = vec![root.clone()];
+        nodes.extend(elements.iter().cloned());
+        storage.create_graph_nodes_batch(&nodes).await?;
+        storage.create_graph_edges_batch(&links).await?;
+
+        let analysis = analyze(&elements, &links);
+        let result = SystemsResult {
+            session_id: session.id.clone(),
+            model_id: root.id,
+            situation: root.content,
+            mermaid: render_mermaid(&analysis.elements, &analysis.links, &analysis.loops),
+            elements: analysis.elements,
+            links: analysis.links,
+            loops: analysis.loops,
+            leverage_points: analysis.leverage_points,
+            summary,
+            confidence,
+        };
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref())
+            .success(
+                serialize_for_log(&result, "reasoning.systems output"),
+                latency,
+            );
+        storage.log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            elements = result.elements.len(),
+            links = result.links.len(),
+            loops = result.loops.len(),
+            latency_ms = latency,
+            "Systems model completed"
+        );
+
+        Ok(result)
+    }
+}
+
+fn systems_prompt(params: &SystemsParams) -> String {
+    let mut prompt = format!(
+        "SITUATION: {}\nMODEL AT MOST: {} elements",
+        params.situation.trim(),
+        params.max_elements
+    );
+    if let Some(ref context) = params.context {
+        prompt.push_str(&format!("\n\nCONTEXT:\n{}", context));
+    }
+    prompt
+}
+
+fn parse_response(completion: &str) -> AppResult<SystemsResponse> {
+    let json_str = extract_json_from_completion(completion)
+        .map_err(|e| ToolError::Reasoning { message: e })?;
+    let parsed: SystemsResponse =
+        serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+            message: format!("Failed to parse systems model: {}", e),
+        })?;
+    if parsed.elements.len() < 2 {
+        return Err(ToolError::Reasoning {
+            message: "Systems model needs at least two elements".to_string(),
+        }
+        .into());
+    }
+    Ok(parsed)
+}
+
+/// Graph nodes for the first `max_elements` elements and graph edges for
+/// the links between them; links naming an unknown element (by key or
+/// name) and repeated links between the same two elements are dropped
+fn build_model(
+    session_id: &str,
+    root_id: &str,
+    response: SystemsResponse,
+    max_elements: usize,
+) -> (Vec<GraphNode>, Vec<GraphEdge>) {
+    let interventions: HashMap<String, String> = response
+        .leverage_points
+        .into_iter()
+        .map(|p| (p.element.trim().to_lowercase(), p.intervention))
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut ids_by_key: HashMap<String, String> = HashMap::new();
+    let mut edges = Vec::new();
+    for draft in response
+        .elements
+        .into_iter()
+        .filter(|e| !e.name.trim().is_empty())
+        .take(max_elements)
+    {
+        let name = draft.name.trim();
+        let key = draft.key.trim().to_lowercase();
+        let intervention = interventions
+            .get(&key)
+            .or_else(|| interventions.get(&name.to_lowercase()))
+            .cloned();
+        let node = GraphNode::new(session_id, name)
+            .with_type(NodeType::Thought)
+            .with_depth(1)
+            .with_metadata(serde_json::json!({
+                ELEMENT_METADATA_KEY: StoredElement {
+                    kind: draft.kind,
+                    description: draft.description,
+                    intervention,
+                }
+            }));
+        if !key.is_empty() {
+            ids_by_key.insert(key, node.id.clone());
+        }
+        ids_by_key.insert(name.to_lowercase(), node.id.clone());
+        edges.push(GraphEdge::new(session_id, root_id, &node.id).with_type(EdgeType::Generates));
+        nodes.push(node);
+    }
+
+    let mut linked = Vec::new();
+    for link in response.links {
+        let from = ids_by_key.get(&link.from.trim().to_lowercase());
+        let to = ids_by_key.get(&link.to.trim().to_lowercase());
+        let (Some(from), Some(to)) = (from, to) else {
+            debug!(from = %link.from, to = %link.to, "Dropping link to an unknown element");
+            continue;
+        };
+        if linked.contains(&(from, to)) {
+            continue;
+        }
+        linked.push((from, to));
+        let edge_type = match link.polarity {
+            Polarity::Positive => EdgeType::Supports,
+            Polarity::Negative => EdgeType::Contradicts,
+        };
+        edges.push(
+            GraphEdge::new(session_id, from, to)
+                .with_type(edge_type)
+                .with_metadata(serde_json::json!({
+                    LINK_METADATA_KEY: StoredLink {
+                        delayed: link.delay,
+                        rationale: link.rationale,
+                    }
+                })),
+        );
+    }
+    (nodes, edges)
+}
+
+/// Elements, links, loops and leverage points of a stored model
+struct Analysis {
+    elements: Vec<SystemElement>,
+    links: Vec<CausalLink>,
+    loops: Vec<FeedbackLoop>,
+    leverage_points: Vec<LeveragePoint>,
+}
+
+/// Read the model back from its graph nodes and edges and analyze it
+fn analyze(nodes: &[GraphNode], edges: &[GraphEdge]) -> Analysis {
+    let mut elements = Vec::new();
+    let mut interventions = HashMap::new();
+    for node in nodes {
+        let Some(stored) = node
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get(ELEMENT_METADATA_KEY))
+            .and_then(|v| serde_json::from_value::<StoredElement>(v.clone()).ok())
+        else {
+            continue;
+        };
+        if let Some(intervention) = stored.intervention {
+            interventions.insert(node.id.clone(), intervention);
+        }
+        elements.push(SystemElement {
+            element_id: node.id.clone(),
+            name: node.content.clone(),
+            kind: stored.kind,
+            description: stored.description,
+        });
+    }
+    let links = causal_links(edges);
+    let loops = feedback_loops(&elements, &links);
+    let leverage_points = leverage_points(&elements, &links, &loops, &interventions);
+    Analysis {
+        elements,
+        links,
+        loops,
+        leverage_points,
+    }
+}
+
+/// Causal links among graph edges: `supports` edges are positive links and
+/// `contradicts` edges negative ones
+fn causal_links(edges: &[GraphEdge]) -> Vec<CausalLink> {
+    edges
+        .iter()
+        .filter_map(|edge| {
+            let polarity = match edge.edge_type {
+                EdgeType::Supports => Polarity::Positive,
+                EdgeType::Contradicts => Polarity::Negative,
+                _ => return None,
+            };
+            let stored: StoredLink = edge
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get(LINK_METADATA_KEY))
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            Some(CausalLink {
+                link_id: edge.id.clone(),
+                from: edge.from_node.clone(),
+                to: edge.to_node.clone(),
+                polarity,
+                delayed: stored.delayed,
+                rationale: stored.rationale,
+            })
+        })
+        .collect()
+}
+
+/// Elementary cycles of the links, shortest first, at most
+/// `MAX_FEEDBACK_LOOPS`. Each cycle is found once, from its element listed
+/// first, by a depth-first search through later elements only.
+fn feedback_loops(elements: &[SystemElement], links: &[CausalLink]) -> Vec<FeedbackLoop> {
+    let index: HashMap<&str, usize> = elements
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.element_id.as_str(), i))
+        .collect();
+    let mut outgoing: Vec<Vec<(usize, &CausalLink)>> = vec![Vec::new(); elements.len()];
+    for link in links {
+        if let (Some(&from), Some(&to)) =
+            (index.get(link.from.as_str()), index.get(link.to.as_str()))
+        {
+            outgoing[from].push((to, link));
+        }
+    }
+
+    let mut cycles: Vec<Vec<&CausalLink>> = Vec::new();
+    for start in 0..elements.len() {
+        let mut path: Vec<&CausalLink> = Vec::new();
+        let mut on_path = vec![false; elements.len()];
+        on_path[start] = true;
+        // Stack of (element, next outgoing link to try)
+        let mut stack = vec![(start, 0usize)];
+        while let Some(&mut (current, ref mut next)) = stack.last_mut() {
+            if cycles.len() >= MAX_FEEDBACK_LOOPS {
+                break;
+            }
+            let Some(&(to, link)) = outgoing[current].get(*next) else {
+                stack.pop();
+                on_path[current] = false;
+                path.pop();
+                continue;
+            };
+            *next += 1;
+            if to == start {
+                let mut cycle = path.clone();
+                cycle.push(link);
+                cycles.push(cycle);
+            } else if to > start && !on_path[to] {
+                on_path[to] = true;
+                path.push(link);
+                stack.push((to, 0));
+            }
+        }
+    }
+    cycles.sort_by_key(|cycle| cycle.len());
+
+    let names: HashMap<&str, &str> = elements
+        .iter()
+        .map(|e| (e.element_id.as_str(), e.name.as_str()))
+        .collect();
+    let (mut reinforcing, mut balancing) = (0, 0);
+    cycles
+        .into_iter()
+        .map(|cycle| {
+            let negative = cycle
+                .iter()
+                .filter(|l| l.polarity == Polarity::Negative)
+                .count();
+            let kind = if negative % 2 == 0 {
+                LoopKind::Reinforcing
+            } else {
+                LoopKind::Balancing
+            };
+            let label = match kind {
+                LoopKind::Reinforcing => {
+                    reinforcing += 1;
+                    format!("R{}", reinforcing)
+                }
+                LoopKind::Balancing => {
+                    balancing += 1;
+                    format!("B{}", balancing)
+                }
+            };
+            let elements: Vec<String> = cycle.iter().map(|l| l.from.clone()).collect();
+            let mut path: Vec<&str> = elements
+                .iter()
+                .map(|id| names.get(id.as_str()).copied().unwrap_or_default())
+                .collect();
+            path.push(path[0]);
+            FeedbackLoop {
+                label,
+                kind,
+                delayed: cycle.iter().any(|l| l.delayed),
+                path: path.join(" → "),
+                elements,
+            }
+        })
+        .collect()
+}
+
+/// Elements on at least one loop or with a proposed intervention, by loops
+/// then links out, at most `MAX_LEVERAGE_POINTS`
+fn leverage_points(
+    elements: &[SystemElement],
+    links: &[CausalLink],
+    loops: &[FeedbackLoop],
+    interventions: &HashMap<String, String>,
+) -> Vec<LeveragePoint> {
+    let mut points: Vec<LeveragePoint> = elements
+        .iter()
+        .map(|element| LeveragePoint {
+            element_id: element.element_id.clone(),
+            name: element.name.clone(),
+            loops: loops
+                .iter()
+                .filter(|l| l.elements.contains(&element.element_id))
+                .count() as u32,
+            out_links: links
+                .iter()
+                .filter(|l| l.from == element.element_id)
+                .count() as u32,
+            intervention: interventions.get(&element.element_id).cloned(),
+        })
+        .filter(|p| p.loops > 0 || p.intervention.is_some())
+        .collect();
+    points.sort_by(|a, b| {
+        b.loops
+            .cmp(&a.loops)
+            .then(b.out_links.cmp(&a.out_links))
+            .then(b.intervention.is_some().cmp(&a.intervention.is_some()))
+    });
+    points.truncate(MAX_LEVERAGE_POINTS);
+    points
+}
+
+/// Mermaid flowchart of the model: stocks as boxes, flows as stadiums and
+/// other variables as rounded boxes; delayed links are dotted, and each
+/// loop is listed as a comment
+fn render_mermaid(
+    elements: &[SystemElement],
+    links: &[CausalLink],
+    loops: &[FeedbackLoop],
+) -> String {
+    let ids: HashMap<&str, String> = elements
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.element_id.as_str(), format!("n{}", i)))
+        .collect();
+    let mut lines = vec!["flowchart LR".to_string()];
+    for element in elements {
+        let name = element.name.replace('"', "#quot;");
+        let shape = match element.kind {
+            ElementKind::Stock => format!("[\"{}\"]", name),
+            ElementKind::Flow => format!("([\"{}\"])", name),
+            ElementKind::Variable => format!("(\"{}\")", name),
+        };
+        lines.push(format!("    {}{}", ids[element.element_id.as_str()], shape));
+    }
+    for link in links {
+        let (Some(from), Some(to)) = (ids.get(link.from.as_str()), ids.get(link.to.as_str()))
+        else {
+            continue;
+        };
+        let sign = match link.polarity {
+            Polarity::Positive => "+",
+            Polarity::Negative => "-",
+        };
+        let arrow = if link.delayed { "-.->" } else { "-->" };
+        lines.push(format!("    {} {}|{}| {}", from, arrow, sign, to));
+    }
+    for feedback in loops {
+        lines.push(format!("    %% {}: {}", feedback.label, feedback.path));
+    }
+    lines.join("\n")
+}
+
+/// Element details kept in graph node metadata
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredElement {
+    kind: ElementKind,
+    #[serde(default)]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    intervention: Option<String>,
+}
+
+/// Link details kept in graph edge metadata
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredLink {
+    #[serde(default)]
+    delayed: bool,
+    #[serde(default)]
+    rationale: String,
+}
+
+// Internal response types for parsing
+
+#[derive(Debug, Deserialize)]
+struct SystemsResponse {
+    #[serde(default)]
+    elements: Vec<ElementDraft>,
+    #[serde(default)]
+    links: Vec<LinkDraft>,
+    #[serde(default)]
+    leverage_points: Vec<InterventionDraft>,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ElementDraft {
+    #[serde(default)]
+    key: String,
+    name: String,
+    #[serde(default)]
+    kind: ElementKind,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LinkDraft {
+    from: String,
+    to: String,
+    polarity: Polarity,
+    #[serde(default)]
+    delay: bool,
+    #[serde(default)]
+    rationale: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct InterventionDraft {
+    element: String,
+    #[serde(default)]
+    intervention: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn element(key: &str, kind: ElementKind) -> ElementDraft {
+        ElementDraft {
+            key: key.to_lowercase(),
+            name: key.to_string(),
+            kind,
+            description: String::new(),
+        }
+    }
+
+    fn link(from: &str, to: &str, polarity: Polarity) -> LinkDraft {
+        LinkDraft {
+            from: from.to_lowercase(),
+            to: to.to_lowercase(),
+            polarity,
+            delay: false,
+            rationale: String::new(),
+        }
+    }
+
+    /// Births and deaths around a population: one reinforcing and one
+    /// balancing loop
+    fn population() -> SystemsResponse {
+        SystemsResponse {
+            elements: vec![
+                element("Population", ElementKind::Stock),
+                element("Births", ElementKind::Flow),
+                element("Deaths", ElementKind::Flow),
+                element("Food", ElementKind::Variable),
+            ],
+            links: vec![
+                link("Population", "Births", Polarity::Positive),
+                link("Births", "Population", Polarity::Positive),
+                link("Population", "Deaths", Polarity::Positive),
+                link("Deaths", "Population", Polarity::Negative),
+                link("Food", "Deaths", Polarity::Negative),
+                link("Food", "Missing", Polarity::Positive),
+                link("Births", "Population", Polarity::Negative),
+            ],
+            leverage_points: vec![InterventionDraft {
+                element: "food".to_string(),
+                intervention: "Stockpile grain".to_string(),
+            }],
+            summary: String::new(),
+            confidence: 0.8,
+        }
+    }
+
+    #[test]
+    fn test_params_defaults() {
+        let params: SystemsParams =
+            serde_json::from_value(json!({"situation": "Traffic congestion"})).unwrap();
+        assert_eq!(params.max_elements, DEFAULT_SYSTEM_ELEMENTS);
+    }
+
+    #[test]
+    fn test_build_model_drops_unknown_and_repeated_links() {
+        let (nodes, edges) = build_model("s1", "root", population(), 10);
+        assert_eq!(nodes.len(), 4);
+        let causal = causal_links(&edges);
+        assert_eq!(causal.len(), 5);
+        assert_eq!(
+            edges
+                .iter()
+                .filter(|e| e.edge_type == EdgeType::Generates)
+                .count(),
+            4
+        );
+
+        let (nodes, _) = build_model("s1", "root", population(), 2);
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_loops_and_polarity() {
+        let (nodes, edges) = build_model("s1", "root", population(), 10);
+        let analysis = analyze(&nodes, &edges);
+        assert_eq!(analysis.loops.len(), 2);
+        let reinforcing = &analysis.loops[0];
+        assert_eq!(reinforcing.label, "R1");
+        assert_eq!(reinforcing.path, "Population → Births → Population");
+        let balancing = &analysis.loops[1];
+        assert_eq!(balancing.label, "B1");
+        assert_eq!(balancing.kind, LoopKind::Balancing);
+        assert_eq!(balancing.elements.len(), 2);
+    }
+
+    #[test]
+    fn test_longer_loops_found_once() {
+        let response = SystemsResponse {
+            elements: vec![
+                element("A", ElementKind::Variable),
+                element("B", ElementKind::Variable),
+                element("C", ElementKind::Variable),
+            ],
+            links: vec![
+                link("A", "B", Polarity::Positive),
+                link("B", "C", Polarity::Negative),
+                link("C", "A", Polarity::Negative),
+                link("C", "C", Polarity::Positive),
+            ],
+            leverage_points: vec![],
+            summary: String::new(),
+            confidence: 0.5,
+        };
+        let (nodes, edges) = build_model("s1", "root", response, 10);
+        let loops = analyze(&nodes, &edges).loops;
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].path, "C → C");
+        assert_eq!(loops[1].path, "A → B → C → A");
+        assert_eq!(loops[1].kind, LoopKind::Reinforcing);
+        assert_eq!(loops[1].label, "R2");
+    }
+
+    #[test]
+    fn test_leverage_points() {
+        let (nodes, edges) = build_model("s1", "root", population(), 10);
+        let points = analyze(&nodes, &edges).leverage_points;
+        let names: Vec<&str> = points.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Population", "Births", "Deaths", "Food"]);
+        assert_eq!(points[0].loops, 2);
+        assert_eq!(points[3].loops, 0);
+        assert_eq!(points[3].intervention.as_deref(), Some("Stockpile grain"));
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let mut response = population();
+        response.links[3].delay = true;
+        response.elements[0].name = "Population \"N\"".to_string();
+        let (nodes, edges) = build_model("s1", "root", response, 10);
+        let analysis = analyze(&nodes, &edges);
+        let mermaid = render_mermaid(&analysis.elements, &analysis.links, &analysis.loops);
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!(lines[0], "flowchart LR");
+        assert_eq!(lines[1], "    n0[\"Population #quot;N#quot;\"]");
+        assert_eq!(lines[2], "    n1([\"Births\"])");
+        assert_eq!(lines[4], "    n3(\"Food\")");
+        assert!(lines.contains(&"    n0 -->|+| n1"));
+        assert!(lines.contains(&"    n2 -.->|-| n0"));
+        assert!(mermaid.contains("%% B1:"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let parsed = parse_response(
+            r#"{"elements": [{"name": "A"}, {"name": "B", "kind": "stock"}], "links": [{"from": "A", "to": "B", "polarity": "-"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.elements[0].kind, ElementKind::Variable);
+        assert_eq!(parsed.links[0].polarity, Polarity::Negative);
+        assert!(parse_response(r#"{"elements": [{"name": "A"}]}"#).is_err());
+    }
+}
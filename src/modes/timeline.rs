@@ -6,6 +6,7 @@
 //! - Comparing and merging timeline branches
 //! - Tracking branch performance via UCB scores
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use tracing::{debug, info};
@@ -13,10 +14,11 @@ use tracing::{debug, info};
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
-use crate::prompts::TREE_REASONING_PROMPT;
+use crate::langbase::{GenerationParams, Message, PipeRequest};
+use crate::prompts::{TIMELINE_COMPARE_PROMPT, TREE_REASONING_PROMPT};
+use crate::providers::SharedBackend;
 use crate::storage::{
-    Branch, Invocation, SqliteStorage, Storage, Thought, Timeline, TimelineBranch, TimelineState,
+    Branch, Invocation, SharedStorage, Thought, Timeline, TimelineBranch, TimelineState,
 };
 
 /// Input parameters for creating a timeline
@@ -47,6 +49,9 @@ pub struct TimelineBranchParams {
     /// Exploration constant for UCB calculation (default: sqrt(2))
     #[serde(default = "default_exploration_constant")]
     pub exploration_constant: f64,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_num_alternatives() -> usize {
@@ -67,6 +72,12 @@ pub struct TimelineCompareParams {
     /// Optional session ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Observed facts to check both branches against
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub observed_facts: Vec<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Input parameters for merging timelines
@@ -79,6 +90,9 @@ pub struct TimelineMergeParams {
     /// Optional merge strategy
     #[serde(default)]
     pub strategy: MergeStrategy,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 /// Merge strategies for timeline consolidation
@@ -95,7 +109,7 @@ pub enum MergeStrategy {
 }
 
 /// Response from timeline creation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimelineCreateResult {
     /// The created timeline ID
     pub timeline_id: String,
@@ -110,7 +124,7 @@ pub struct TimelineCreateResult {
 }
 
 /// Response from timeline branching
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimelineBranchResult {
     /// The timeline ID
     pub timeline_id: String,
@@ -123,7 +137,7 @@ pub struct TimelineBranchResult {
 }
 
 /// Branch information with MCTS score
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BranchWithScore {
     /// Branch ID
     pub branch_id: String,
@@ -138,22 +152,130 @@ pub struct BranchWithScore {
 }
 
 /// Response from timeline comparison
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimelineCompareResult {
+    /// Branch compared as A (a timeline's active branch if a timeline was given)
+    #[serde(default)]
+    pub branch_a: String,
+    /// Branch compared as B
+    #[serde(default)]
+    pub branch_b: String,
     /// Comparison summary
     pub summary: String,
+    /// Events both branches contain, in order
+    #[serde(default)]
+    pub shared_events: Vec<SharedEvent>,
+    /// Where the branches split; `None` if their paths are the same
+    #[serde(default)]
+    pub divergence: Option<DivergencePoint>,
+    /// Events only branch A contains
+    #[serde(default)]
+    pub unique_events_a: Vec<String>,
+    /// Events only branch B contains
+    #[serde(default)]
+    pub unique_events_b: Vec<String>,
     /// Key differences
     pub differences: Vec<String>,
     /// Shared insights
     pub shared_insights: Vec<String>,
+    /// Downstream consequences of branch A's choice
+    #[serde(default)]
+    pub consequences_a: Vec<String>,
+    /// Downstream consequences of branch B's choice
+    #[serde(default)]
+    pub consequences_b: Vec<String>,
+    /// Which branch each observed fact supports
+    #[serde(default)]
+    pub fact_assessments: Vec<FactAssessment>,
+    /// Share of the observed facts supporting branch A (0.0-1.0)
+    #[serde(default)]
+    pub fact_match_a: Option<f64>,
+    /// Share of the observed facts supporting branch B (0.0-1.0)
+    #[serde(default)]
+    pub fact_match_b: Option<f64>,
+    /// Branch matching the observed facts better; `None` without facts
+    #[serde(default)]
+    pub better_match: Option<ComparedBranch>,
     /// Recommendation on which path to pursue
     pub recommendation: String,
     /// Confidence in recommendation
     pub confidence: f64,
 }
 
+/// An event both compared branches contain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SharedEvent {
+    /// Event content
+    pub content: String,
+    /// Position of the event in branch A's path (0-based)
+    pub index_a: usize,
+    /// Position of the event in branch B's path (0-based)
+    pub index_b: usize,
+}
+
+/// Where two compared branches split
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DivergencePoint {
+    /// Number of events both paths start with
+    pub shared_prefix: usize,
+    /// Last event before the split, if the paths start the same
+    pub after_event: Option<String>,
+    /// Branch A's first event after the split, if any
+    pub event_a: Option<String>,
+    /// Branch B's first event after the split, if any
+    pub event_b: Option<String>,
+    /// Deepest branch both branches descend from, if any
+    pub fork_branch_id: Option<String>,
+    /// Why the paths split
+    pub reason: String,
+}
+
+/// Side of a timeline comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparedBranch {
+    /// Branch A
+    A,
+    /// Branch B
+    B,
+    /// Both branches equally
+    Both,
+    /// Neither branch
+    Neither,
+}
+
+impl ComparedBranch {
+    fn parse(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "a" => Self::A,
+            "b" => Self::B,
+            "both" => Self::Both,
+            _ => Self::Neither,
+        }
+    }
+
+    fn includes_a(self) -> bool {
+        matches!(self, Self::A | Self::Both)
+    }
+
+    fn includes_b(self) -> bool {
+        matches!(self, Self::B | Self::Both)
+    }
+}
+
+/// Which branch an observed fact supports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FactAssessment {
+    /// The observed fact
+    pub fact: String,
+    /// Branch the fact supports
+    pub supports: ComparedBranch,
+    /// Why the fact supports that branch
+    pub explanation: String,
+}
+
 /// Response from timeline merge
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TimelineMergeResult {
     /// The merged timeline/branch ID
     pub merged_id: String,
@@ -186,7 +308,11 @@ pub struct TimelineMode {
 
 impl TimelineMode {
     /// Create a new timeline mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         // Extract pipe names from config, with defaults
         let got_pipe = config
             .pipes
@@ -196,7 +322,7 @@ impl TimelineMode {
             .unwrap_or_else(|| "got-reasoning-v1".to_string());
 
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend),
             tree_pipe: config.pipes.tree.clone(),
             divergent_pipe: config.pipes.divergent.clone(),
             got_pipe,
@@ -288,6 +414,7 @@ impl TimelineMode {
     /// Branch a timeline with MCTS-based exploration
     pub async fn branch(&self, params: TimelineBranchParams) -> AppResult<TimelineBranchResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Validate
         if params.content.trim().is_empty() {
@@ -336,8 +463,9 @@ impl TimelineMode {
             Message::system(TREE_REASONING_PROMPT),
             Message::user(mcts_context),
         ];
-        let request = PipeRequest::new(&self.tree_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let tree_pipe = self.core.select_pipe(&self.tree_pipe);
+        let request = PipeRequest::new(&tree_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let json_str = extract_json_from_completion(&response.completion)
@@ -415,7 +543,9 @@ impl TimelineMode {
             serialize_for_log(&params, "timeline_branch_params"),
         )
         .with_session(&timeline.session_id)
-        .with_pipe(&self.tree_pipe)
+        .with_pipe(&tree_pipe)
+        .with_cache_hit(response.cache_hit)
+        .with_pipe_fallback(response.fallback.as_deref())
         .success(serde_json::json!({"branches": created_branches.len()}), latency);
         self.core.storage().log_invocation(&invocation).await?;
 
@@ -436,30 +566,52 @@ impl TimelineMode {
     }
 
     /// Compare two timeline branches
+    ///
+    /// Aligns the events of both branches' paths from their timeline's root,
+    /// finds where they split, and asks the GoT pipe why they split, what
+    /// follows from each branch, and which observed facts support which.
     pub async fn compare(
         &self,
         params: TimelineCompareParams,
     ) -> AppResult<TimelineCompareResult> {
         let start = Instant::now();
+        params.generation.validate()?;
+        if params.observed_facts.len() > MAX_OBSERVED_FACTS {
+            return Err(ToolError::Validation {
+                field: "observed_facts".to_string(),
+                reason: format!("At most {} observed facts", MAX_OBSERVED_FACTS),
+            }
+            .into());
+        }
+        if params.observed_facts.iter().any(|f| f.trim().is_empty()) {
+            return Err(ToolError::Validation {
+                field: "observed_facts".to_string(),
+                reason: "Observed facts cannot be empty".to_string(),
+            }
+            .into());
+        }
+
+        let branch_a = self
+            .resolve_branch(&params.timeline_a, "timeline_a")
+            .await?;
+        let branch_b = self
+            .resolve_branch(&params.timeline_b, "timeline_b")
+            .await?;
 
         // Get session
+        let session_id = params
+            .session_id
+            .clone()
+            .or_else(|| Some(branch_a.session_id.clone()));
         let session = self
             .core
             .storage()
-            .get_or_create_session(&params.session_id, "timeline")
+            .get_or_create_session(&session_id, "timeline")
             .await?;
 
-        // Get thoughts from both branches
-        let thoughts_a = self
-            .core
-            .storage()
-            .get_branch_thoughts(&params.timeline_a)
-            .await?;
-        let thoughts_b = self
-            .core
-            .storage()
-            .get_branch_thoughts(&params.timeline_b)
-            .await?;
+        // Get the events on both branches' paths
+        let (chain_a, thoughts_a) = self.branch_path(&branch_a).await?;
+        let (chain_b, thoughts_b) = self.branch_path(&branch_b).await?;
 
         if thoughts_a.is_empty() && thoughts_b.is_empty() {
             return Err(ToolError::Validation {
@@ -469,30 +621,27 @@ impl TimelineMode {
             .into());
         }
 
-        // Build comparison prompt
         let content_a: Vec<String> = thoughts_a.iter().map(|t| t.content.clone()).collect();
         let content_b: Vec<String> = thoughts_b.iter().map(|t| t.content.clone()).collect();
+        let alignment = align_events(&content_a, &content_b);
+        let fork_branch_id = chain_a
+            .iter()
+            .zip(&chain_b)
+            .take_while(|(a, b)| a == b)
+            .last()
+            .map(|(id, _)| id.clone());
 
-        let compare_prompt = format!(
-            "Compare these two reasoning paths and identify key differences, shared insights, \
-             and provide a recommendation on which path to pursue:\n\n\
-             PATH A:\n{}\n\n\
-             PATH B:\n{}",
-            content_a.join("\n---\n"),
-            content_b.join("\n---\n")
-        );
+        let compare_prompt =
+            compare_request(&content_a, &content_b, &alignment, &params.observed_facts);
 
         // Use GoT pipe for comparison (aggregation capability)
         let messages = vec![
-            Message::system(
-                "You are analyzing and comparing reasoning paths. \
-                 Respond with JSON: {\"summary\": \"...\", \"differences\": [...], \
-                 \"shared_insights\": [...], \"recommendation\": \"...\", \"confidence\": 0.0-1.0}"
-            ),
+            Message::system(TIMELINE_COMPARE_PROMPT),
             Message::user(compare_prompt),
         ];
-        let request = PipeRequest::new(&self.got_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let got_pipe = self.core.select_pipe(&self.got_pipe);
+        let request = PipeRequest::new(&got_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let json_str = extract_json_from_completion(&response.completion)
@@ -510,29 +659,149 @@ impl TimelineMode {
             serialize_for_log(&params, "timeline_compare_params"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.got_pipe)
+        .with_pipe(&got_pipe)
+        .with_cache_hit(response.cache_hit)
+        .with_pipe_fallback(response.fallback.as_deref())
         .success(serialize_for_log(&compare_response, "compare_result"), latency);
         self.core.storage().log_invocation(&invocation).await?;
 
+        let fact_assessments =
+            assess_facts(&params.observed_facts, &compare_response.fact_assessments);
+        let fact_match = |includes: fn(ComparedBranch) -> bool| {
+            (!params.observed_facts.is_empty()).then(|| {
+                let supporting = fact_assessments
+                    .iter()
+                    .filter(|a| includes(a.supports))
+                    .count();
+                supporting as f64 / params.observed_facts.len() as f64
+            })
+        };
+        let fact_match_a = fact_match(ComparedBranch::includes_a);
+        let fact_match_b = fact_match(ComparedBranch::includes_b);
+        let better_match = match (fact_match_a, fact_match_b) {
+            (Some(a), Some(b)) if a > b => Some(ComparedBranch::A),
+            (Some(a), Some(b)) if b > a => Some(ComparedBranch::B),
+            (Some(a), Some(_)) if a > 0.0 => Some(ComparedBranch::Both),
+            (Some(_), Some(_)) => Some(ComparedBranch::Neither),
+            _ => None,
+        };
+
+        let shared = &alignment.pairs;
+        let unique = |events: &[String], index: fn(&(usize, usize)) -> usize| -> Vec<String> {
+            events
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !shared.iter().any(|pair| index(pair) == *i))
+                .map(|(_, e)| e.clone())
+                .collect()
+        };
+        let unique_events_a = unique(&content_a, |pair| pair.0);
+        let unique_events_b = unique(&content_b, |pair| pair.1);
+
+        let prefix = alignment.shared_prefix;
+        let divergence =
+            (prefix < content_a.len() || prefix < content_b.len()).then(|| DivergencePoint {
+                shared_prefix: prefix,
+                after_event: prefix.checked_sub(1).map(|i| content_a[i].clone()),
+                event_a: content_a.get(prefix).cloned(),
+                event_b: content_b.get(prefix).cloned(),
+                fork_branch_id,
+                reason: compare_response.divergence_reason.clone(),
+            });
+
         info!(
             timeline_a = %params.timeline_a,
             timeline_b = %params.timeline_b,
+            shared_events = shared.len(),
             latency_ms = latency,
             "Timelines compared"
         );
 
         Ok(TimelineCompareResult {
+            branch_a: branch_a.id,
+            branch_b: branch_b.id,
             summary: compare_response.summary,
+            shared_events: shared
+                .iter()
+                .map(|&(index_a, index_b)| SharedEvent {
+                    content: content_a[index_a].clone(),
+                    index_a,
+                    index_b,
+                })
+                .collect(),
+            divergence,
+            unique_events_a,
+            unique_events_b,
             differences: compare_response.differences,
             shared_insights: compare_response.shared_insights,
+            consequences_a: compare_response.consequences_a,
+            consequences_b: compare_response.consequences_b,
+            fact_assessments,
+            fact_match_a,
+            fact_match_b,
+            better_match,
             recommendation: compare_response.recommendation,
             confidence: compare_response.confidence,
         })
     }
 
+    /// The branch `id` names: a timeline's active branch, or the branch itself
+    async fn resolve_branch(&self, id: &str, field: &str) -> AppResult<Branch> {
+        let branch_id = match self.core.storage().get_timeline(id).await? {
+            Some(timeline) => timeline.active_branch_id,
+            None => id.to_string(),
+        };
+        self.core
+            .storage()
+            .get_branch(&branch_id)
+            .await?
+            .ok_or_else(|| {
+                ToolError::Validation {
+                    field: field.to_string(),
+                    reason: format!("Timeline or branch not found: {}", id),
+                }
+                .into()
+            })
+    }
+
+    /// The IDs of `branch` and its ancestors, root first, and the thoughts
+    /// on that path in order.
+    ///
+    /// An ancestor's thoughts count only up to the point its child branched
+    /// off.
+    async fn branch_path(&self, branch: &Branch) -> AppResult<(Vec<String>, Vec<Thought>)> {
+        let mut chain = vec![branch.clone()];
+        while chain.len() < MAX_PATH_DEPTH {
+            let Some(parent_id) = chain.last().and_then(|b| b.parent_branch_id.clone()) else {
+                break;
+            };
+            if chain.iter().any(|b| b.id == parent_id) {
+                break;
+            }
+            match self.core.storage().get_branch(&parent_id).await? {
+                Some(parent) => chain.push(parent),
+                None => break,
+            }
+        }
+        chain.reverse();
+
+        let mut thoughts = Vec::new();
+        for (i, link) in chain.iter().enumerate() {
+            let branched_at = chain.get(i + 1).map(|child| child.created_at);
+            let branch_thoughts = self.core.storage().get_branch_thoughts(&link.id).await?;
+            thoughts.extend(
+                branch_thoughts
+                    .into_iter()
+                    .filter(|t| branched_at.map_or(true, |at| t.created_at <= at)),
+            );
+        }
+        Ok((chain.into_iter().map(|b| b.id).collect(), thoughts))
+    }
+
     /// Merge two timeline branches
     pub async fn merge(&self, params: TimelineMergeParams) -> AppResult<TimelineMergeResult> {
         let start = Instant::now();
+        params.generation.validate()?;
 
         // Get thoughts from both branches
         let source_thoughts = self
@@ -593,8 +862,10 @@ impl TimelineMode {
             ),
             Message::user(merge_prompt),
         ];
-        let request = PipeRequest::new(&self.reflection_pipe, messages);
-        let response = self.core.langbase().call_pipe(request).await?;
+        let reflection_pipe = self.core.select_pipe(&self.reflection_pipe);
+        let request =
+            PipeRequest::new(&reflection_pipe, messages).with_generation(params.generation);
+        let response = self.core.backend().call_pipe(request).await?;
 
         // Parse response
         let json_str = extract_json_from_completion(&response.completion)
@@ -639,7 +910,9 @@ impl TimelineMode {
             serialize_for_log(&params, "timeline_merge_params"),
         )
         .with_session(&source_branch.session_id)
-        .with_pipe(&self.reflection_pipe)
+        .with_pipe(&reflection_pipe)
+        .with_cache_hit(response.cache_hit)
+        .with_pipe_fallback(response.fallback.as_deref())
         .success(serialize_for_log(&merge_response, "merge_result"), latency);
         self.core.storage().log_invocation(&invocation).await?;
 
@@ -682,12 +955,148 @@ struct TreeBranchData {
 #[derive(Debug, Serialize, Deserialize)]
 struct CompareResponse {
     summary: String,
+    #[serde(default)]
+    divergence_reason: String,
     differences: Vec<String>,
     shared_insights: Vec<String>,
+    #[serde(default)]
+    consequences_a: Vec<String>,
+    #[serde(default)]
+    consequences_b: Vec<String>,
+    #[serde(default)]
+    fact_assessments: Vec<FactAssessmentResponse>,
     recommendation: String,
     confidence: f64,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct FactAssessmentResponse {
+    /// Label of the fact, e.g. "F1"
+    fact: String,
+    #[serde(default)]
+    supports: String,
+    #[serde(default)]
+    explanation: String,
+}
+
+/// Most observed facts a comparison checks
+const MAX_OBSERVED_FACTS: usize = 20;
+
+/// Most ancestors followed when building a branch's path
+const MAX_PATH_DEPTH: usize = 100;
+
+/// How the events of two paths line up
+#[derive(Debug, PartialEq)]
+struct Alignment {
+    /// Positions in A and B of the events both contain, in order
+    pairs: Vec<(usize, usize)>,
+    /// Number of events both paths start with
+    shared_prefix: usize,
+}
+
+/// Events compare equal when their content does, ignoring case and spacing
+fn event_key(event: &str) -> String {
+    event
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Align two paths by their longest common subsequence of events
+fn align_events(a: &[String], b: &[String]) -> Alignment {
+    let keys_a: Vec<String> = a.iter().map(|e| event_key(e)).collect();
+    let keys_b: Vec<String> = b.iter().map(|e| event_key(e)).collect();
+    let shared_prefix = keys_a
+        .iter()
+        .zip(&keys_b)
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    // lengths[i][j]: longest common subsequence of a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; keys_b.len() + 1]; keys_a.len() + 1];
+    for i in (0..keys_a.len()).rev() {
+        for j in (0..keys_b.len()).rev() {
+            lengths[i][j] = if keys_a[i] == keys_b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < keys_a.len() && j < keys_b.len() {
+        if keys_a[i] == keys_b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    Alignment {
+        pairs,
+        shared_prefix,
+    }
+}
+
+/// The user message of a comparison: both paths with their shared events
+/// marked, the split, and the labeled observed facts
+fn compare_request(a: &[String], b: &[String], alignment: &Alignment, facts: &[String]) -> String {
+    let path = |label: char, events: &[String], shared: &dyn Fn(usize) -> bool| {
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let mark = if shared(i) { " (shared)" } else { "" };
+                format!("[{}{}]{} {}", label, i + 1, mark, event)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let path_a = path('A', a, &|i| alignment.pairs.iter().any(|p| p.0 == i));
+    let path_b = path('B', b, &|i| alignment.pairs.iter().any(|p| p.1 == i));
+    let split = match alignment.shared_prefix {
+        0 => "The paths share no starting events.".to_string(),
+        n => format!("The paths split after A{} / B{}.", n, n),
+    };
+
+    let mut request = format!(
+        "Compare these two reasoning paths.\n\nBRANCH A:\n{}\n\nBRANCH B:\n{}\n\nSPLIT: {}",
+        path_a, path_b, split
+    );
+    if !facts.is_empty() {
+        request.push_str("\n\nOBSERVED FACTS:");
+        for (i, fact) in facts.iter().enumerate() {
+            request.push_str(&format!("\n[F{}] {}", i + 1, fact));
+        }
+    }
+    request
+}
+
+/// Map the pipe's fact assessments onto the observed facts, dropping
+/// unknown labels and repeated facts
+fn assess_facts(facts: &[String], assessed: &[FactAssessmentResponse]) -> Vec<FactAssessment> {
+    let mut seen = std::collections::HashSet::new();
+    assessed
+        .iter()
+        .filter_map(|a| {
+            let label = a.fact.trim().trim_start_matches(['[', 'F', 'f']);
+            let index = label.trim_end_matches(']').parse::<usize>().ok()?;
+            let fact = facts.get(index.checked_sub(1)?)?;
+            seen.insert(index).then(|| FactAssessment {
+                fact: fact.clone(),
+                supports: ComparedBranch::parse(&a.supports),
+                explanation: a.explanation.clone(),
+            })
+        })
+        .collect()
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct MergeResponse {
     content: String,
@@ -954,9 +1363,25 @@ mod tests {
     #[test]
     fn test_timeline_compare_result_serialize() {
         let result = TimelineCompareResult {
+            branch_a: "b-1".to_string(),
+            branch_b: "b-2".to_string(),
             summary: "Summary".to_string(),
+            shared_events: vec![SharedEvent {
+                content: "Start".to_string(),
+                index_a: 0,
+                index_b: 0,
+            }],
+            divergence: None,
+            unique_events_a: vec![],
+            unique_events_b: vec![],
             differences: vec!["Diff 1".to_string(), "Diff 2".to_string()],
             shared_insights: vec!["Shared".to_string()],
+            consequences_a: vec![],
+            consequences_b: vec![],
+            fact_assessments: vec![],
+            fact_match_a: Some(0.5),
+            fact_match_b: Some(0.0),
+            better_match: Some(ComparedBranch::A),
             recommendation: "Choose A".to_string(),
             confidence: 0.85,
         };
@@ -964,6 +1389,8 @@ mod tests {
         assert_eq!(json["summary"], "Summary");
         assert_eq!(json["differences"].as_array().unwrap().len(), 2);
         assert_eq!(json["confidence"], 0.85);
+        assert_eq!(json["better_match"], "a");
+        assert_eq!(json["shared_events"][0]["index_b"], 0);
     }
 
     #[test]
@@ -1098,9 +1525,28 @@ mod tests {
     #[test]
     fn test_timeline_compare_result_round_trip() {
         let original = TimelineCompareResult {
+            branch_a: "b-1".to_string(),
+            branch_b: "b-2".to_string(),
             summary: "Test summary".to_string(),
+            shared_events: vec![],
+            divergence: Some(DivergencePoint {
+                shared_prefix: 1,
+                after_event: Some("Start".to_string()),
+                event_a: Some("Pilot".to_string()),
+                event_b: None,
+                fork_branch_id: Some("root".to_string()),
+                reason: "Risk appetite".to_string(),
+            }),
+            unique_events_a: vec!["Pilot".to_string()],
+            unique_events_b: vec![],
             differences: vec!["diff1".to_string(), "diff2".to_string()],
             shared_insights: vec!["insight1".to_string()],
+            consequences_a: vec!["Slower".to_string()],
+            consequences_b: vec![],
+            fact_assessments: vec![],
+            fact_match_a: None,
+            fact_match_b: None,
+            better_match: None,
             recommendation: "Choose path A".to_string(),
             confidence: 0.85,
         };
@@ -1109,5 +1555,96 @@ mod tests {
         assert_eq!(original.summary, deserialized.summary);
         assert_eq!(original.differences, deserialized.differences);
         assert_eq!(original.confidence, deserialized.confidence);
+        assert_eq!(original.divergence, deserialized.divergence);
+    }
+
+    // ============================================================================
+    // Divergence Report Tests
+    // ============================================================================
+
+    fn events(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_align_events_shared_start() {
+        let a = events(&["Plan drafted", "Run a pilot", "Scale up"]);
+        let b = events(&["plan  DRAFTED", "Launch everywhere", "Scale up"]);
+        let alignment = align_events(&a, &b);
+        assert_eq!(alignment.shared_prefix, 1);
+        assert_eq!(alignment.pairs, vec![(0, 0), (2, 2)]);
+    }
+
+    #[test]
+    fn test_align_events_no_overlap() {
+        let alignment = align_events(&events(&["x"]), &events(&["y", "z"]));
+        assert_eq!(alignment.shared_prefix, 0);
+        assert!(alignment.pairs.is_empty());
+        assert_eq!(align_events(&[], &events(&["y"])).pairs, vec![]);
+    }
+
+    #[test]
+    fn test_align_events_reordered() {
+        // Only one of two swapped events can be aligned in order
+        let alignment = align_events(&events(&["a", "b", "c"]), &events(&["b", "a", "c"]));
+        assert_eq!(alignment.pairs.len(), 2);
+        assert_eq!(alignment.pairs.last(), Some(&(2, 2)));
+    }
+
+    #[test]
+    fn test_compare_request_marks_shared_and_facts() {
+        let a = events(&["Plan", "Pilot"]);
+        let b = events(&["Plan", "Launch"]);
+        let alignment = align_events(&a, &b);
+        let request = compare_request(&a, &b, &alignment, &events(&["Pilot churned"]));
+        assert!(request.contains("[A1] (shared) Plan"));
+        assert!(request.contains("[B2] Launch"));
+        assert!(request.contains("split after A1 / B1"));
+        assert!(request.contains("[F1] Pilot churned"));
+
+        let request = compare_request(&a, &b, &alignment, &[]);
+        assert!(!request.contains("OBSERVED FACTS"));
+    }
+
+    #[test]
+    fn test_assess_facts_maps_labels() {
+        let facts = events(&["Pilot churned", "Competitor launched"]);
+        let assessed = vec![
+            FactAssessmentResponse {
+                fact: "F2".to_string(),
+                supports: "B".to_string(),
+                explanation: "Speed mattered".to_string(),
+            },
+            FactAssessmentResponse {
+                fact: "[F1]".to_string(),
+                supports: "both".to_string(),
+                explanation: String::new(),
+            },
+            // Unknown and repeated labels are dropped
+            FactAssessmentResponse {
+                fact: "F9".to_string(),
+                supports: "a".to_string(),
+                explanation: String::new(),
+            },
+            FactAssessmentResponse {
+                fact: "F2".to_string(),
+                supports: "a".to_string(),
+                explanation: String::new(),
+            },
+        ];
+        let mapped = assess_facts(&facts, &assessed);
+        assert_eq!(mapped.len(), 2);
+        assert_eq!(mapped[0].fact, "Competitor launched");
+        assert_eq!(mapped[0].supports, ComparedBranch::B);
+        assert_eq!(mapped[1].supports, ComparedBranch::Both);
+    }
+
+    #[test]
+    fn test_compared_branch_parse() {
+        assert_eq!(ComparedBranch::parse(" A "), ComparedBranch::A);
+        assert_eq!(ComparedBranch::parse("both"), ComparedBranch::Both);
+        assert_eq!(ComparedBranch::parse("unclear"), ComparedBranch::Neither);
+        assert!(ComparedBranch::Both.includes_a() && ComparedBranch::Both.includes_b());
+        assert!(!ComparedBranch::A.includes_b());
     }
 }
@@ -5,18 +5,25 @@
 //! - Branch focusing and navigation
 //! - Cross-references between branches
 //! - Recommended path identification
+//! - Branch comparison against user-supplied criteria
+//! - Merging two branches into one reconciled branch
+//! - Exporting the branch hierarchy as a Mermaid flowchart or ASCII tree
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use tracing::{debug, info, warn};
 
 use super::{extract_json_from_completion, serialize_for_log, ModeCore};
 use crate::config::Config;
 use crate::error::{AppResult, ToolError};
-use crate::langbase::{LangbaseClient, Message, PipeRequest};
-use crate::prompts::TREE_REASONING_PROMPT;
+use crate::langbase::{GenerationParams, Message, OutputSchema, PipeRequest};
+use crate::prompts::{TREE_COMPARE_PROMPT, TREE_MERGE_PROMPT, TREE_REASONING_PROMPT};
+use crate::providers::SharedBackend;
+use crate::schemas::{SchemaKind, SchemaRegistry};
 use crate::storage::{
-    Branch, BranchState, CrossRef, CrossRefType, Invocation, SqliteStorage, Storage, Thought,
+    Branch, BranchState, CrossRef, CrossRefType, Invocation, SharedStorage, Thought,
 };
 
 /// Input parameters for tree reasoning
@@ -39,6 +46,9 @@ pub struct TreeParams {
     /// Cross-references to other branches
     #[serde(default)]
     pub cross_refs: Vec<CrossRefInput>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
 }
 
 fn default_confidence() -> f64 {
@@ -86,8 +96,15 @@ pub struct TreeBranch {
     pub rationale: String,
 }
 
+impl TreeResponse {
+    /// Latest output schema for the tree pipe.
+    pub fn output_schema() -> OutputSchema {
+        SchemaKind::Tree.latest()
+    }
+}
+
 /// Result of tree reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TreeResult {
     /// The session ID.
     pub session_id: String,
@@ -110,7 +127,7 @@ pub struct TreeResult {
 }
 
 /// Branch information in result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BranchInfo {
     /// The branch ID.
     pub id: String,
@@ -122,10 +139,246 @@ pub struct BranchInfo {
     pub rationale: String,
 }
 
+/// Most branches one comparison can weigh against each other
+pub const MAX_COMPARED_BRANCHES: usize = 6;
+
+/// Most criteria one comparison can score
+pub const MAX_COMPARE_CRITERIA: usize = 8;
+
+/// Criteria scored when a comparison names none
+pub const DEFAULT_COMPARE_CRITERIA: [&str; 3] = ["soundness", "feasibility", "impact"];
+
+/// Longest branch ancestry followed when collecting a thought chain
+const MAX_CHAIN_DEPTH: usize = 50;
+
+/// Input parameters for comparing tree branches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeCompareParams {
+    /// Session containing the branches
+    pub session_id: String,
+    /// Branches to compare (2-6)
+    pub branch_ids: Vec<String>,
+    /// Criteria to score each branch against (defaults to soundness,
+    /// feasibility and impact)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub criteria: Vec<String>,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Response from the tree pipe when comparing branches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CompareResponse {
+    /// One assessment per branch, keyed by label.
+    #[serde(default)]
+    assessments: Vec<BranchAssessment>,
+    /// Label of the branch to pursue.
+    #[serde(default)]
+    recommended: String,
+    /// Why the recommended branch beats the others.
+    #[serde(default)]
+    rationale: String,
+    /// Confidence in the recommendation (0.0-1.0).
+    #[serde(default)]
+    confidence: f64,
+}
+
+/// The pipe's assessment of one branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BranchAssessment {
+    /// Branch label (B1, B2, ...).
+    branch: String,
+    /// What the branch's chain concludes.
+    #[serde(default)]
+    summary: String,
+    /// Score per criterion name.
+    #[serde(default)]
+    scores: HashMap<String, f64>,
+    #[serde(default)]
+    strengths: Vec<String>,
+    #[serde(default)]
+    weaknesses: Vec<String>,
+}
+
+/// Result of comparing tree branches.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeCompareResult {
+    /// The session ID.
+    pub session_id: String,
+    /// Criteria each branch was scored against.
+    pub criteria: Vec<String>,
+    /// Assessment of each branch, in the order requested.
+    pub branches: Vec<BranchComparison>,
+    /// The branch to pursue.
+    pub recommended_branch_id: String,
+    /// Why the recommended branch beats the others.
+    pub rationale: String,
+    /// Confidence in the recommendation (0.0-1.0).
+    pub confidence: f64,
+    /// Thought recording the recommendation on the recommended branch.
+    pub recommendation_thought_id: String,
+    /// Number of cross-references created.
+    pub cross_refs_created: usize,
+}
+
+/// One branch's assessment in a comparison.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BranchComparison {
+    /// The branch ID.
+    pub branch_id: String,
+    /// Human-readable branch name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Thoughts in the branch's chain from the root.
+    pub thought_count: usize,
+    /// What the branch's chain concludes.
+    pub summary: String,
+    /// Score for each criterion the pipe scored (0.0-1.0).
+    pub scores: Vec<BranchCriterionScore>,
+    /// Mean of the criterion scores (0.0-1.0).
+    pub overall_score: f64,
+    /// What the branch does well.
+    pub strengths: Vec<String>,
+    /// Where the branch falls short.
+    pub weaknesses: Vec<String>,
+}
+
+/// A branch's score on one criterion.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BranchCriterionScore {
+    /// The criterion.
+    pub criterion: String,
+    /// Score (0.0-1.0).
+    pub score: f64,
+}
+
+/// Input parameters for merging two tree branches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeMergeParams {
+    /// Session containing the branches
+    pub session_id: String,
+    /// First branch to merge
+    pub branch_a: String,
+    /// Second branch to merge
+    pub branch_b: String,
+    /// Model parameter overrides for the pipe call
+    #[serde(default, skip_serializing_if = "GenerationParams::is_empty")]
+    pub generation: GenerationParams,
+}
+
+/// Response from the tree pipe when merging branches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MergeResponse {
+    /// Reasoning that reconciles both branches' conclusions.
+    merged_reasoning: String,
+    /// Conclusions both branches share.
+    #[serde(default)]
+    agreements: Vec<String>,
+    /// Conflicts between the branches and how they were resolved.
+    #[serde(default)]
+    resolved_conflicts: Vec<ResolvedConflict>,
+    /// Confidence in the merged reasoning (0.0-1.0).
+    #[serde(default)]
+    confidence: f64,
+}
+
+/// A conflict between merged branches and its resolution.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvedConflict {
+    /// Where the branches disagree.
+    pub conflict: String,
+    /// How the merged reasoning settles it.
+    pub resolution: String,
+}
+
+/// Result of merging two tree branches.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeMergeResult {
+    /// The session ID.
+    pub session_id: String,
+    /// The new branch holding the merged reasoning.
+    pub branch_id: String,
+    /// The thought holding the merged reasoning.
+    pub thought_id: String,
+    /// The merged reasoning.
+    pub content: String,
+    /// Confidence in the merged reasoning (0.0-1.0).
+    pub confidence: f64,
+    /// The branches both sources descend from, if any; parent of the new
+    /// branch.
+    pub parent_branch: Option<String>,
+    /// The source branches, now marked completed.
+    pub merged_branch_ids: Vec<String>,
+    /// Conclusions both branches share.
+    pub agreements: Vec<String>,
+    /// Conflicts between the branches and how they were resolved.
+    pub resolved_conflicts: Vec<ResolvedConflict>,
+    /// Number of cross-references created.
+    pub cross_refs_created: usize,
+}
+
+/// Output format for exporting a session's branch hierarchy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeExportFormat {
+    /// Mermaid flowchart, rendered by GitHub and most Markdown viewers.
+    #[default]
+    Mermaid,
+    /// Plain-text tree drawn with box characters.
+    Ascii,
+}
+
+impl std::fmt::Display for TreeExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeExportFormat::Mermaid => write!(f, "mermaid"),
+            TreeExportFormat::Ascii => write!(f, "ascii"),
+        }
+    }
+}
+
+impl std::str::FromStr for TreeExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mermaid" | "mmd" => Ok(TreeExportFormat::Mermaid),
+            "ascii" | "text" => Ok(TreeExportFormat::Ascii),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// Parameters for exporting a session's branches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeExportParams {
+    /// Session ID
+    pub session_id: String,
+    /// Output format
+    #[serde(default)]
+    pub format: TreeExportFormat,
+}
+
+/// Rendered branch hierarchy ready to paste into docs.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreeExportResult {
+    /// The session ID.
+    pub session_id: String,
+    /// Format of `content`.
+    pub format: TreeExportFormat,
+    /// Number of branches rendered.
+    pub branch_count: usize,
+    /// Number of thoughts across all branches.
+    pub thought_count: usize,
+    /// The rendered branch hierarchy.
+    pub content: String,
+}
+
 /// Tree reasoning mode handler for branching exploration.
 #[derive(Clone)]
 pub struct TreeMode {
-    /// Core infrastructure (storage and langbase client).
+    /// Core infrastructure (storage and LLM backend).
     core: ModeCore,
     /// The Langbase pipe name for tree reasoning.
     pipe_name: String,
@@ -133,9 +386,14 @@ pub struct TreeMode {
 
 impl TreeMode {
     /// Create a new tree mode handler
-    pub fn new(storage: SqliteStorage, langbase: LangbaseClient, config: &Config) -> Self {
+    pub fn new(
+        storage: impl Into<SharedStorage>,
+        backend: impl Into<SharedBackend>,
+        config: &Config,
+    ) -> Self {
         Self {
-            core: ModeCore::new(storage, langbase),
+            core: ModeCore::new(storage, backend)
+                .with_schemas(SchemaRegistry::from_config(&config.pipes)),
             pipe_name: config.pipes.tree.clone(),
         }
     }
@@ -152,6 +410,7 @@ impl TreeMode {
             }
             .into());
         }
+        params.generation.validate()?;
 
         let num_branches = params.num_branches.clamp(2, 4);
 
@@ -207,17 +466,21 @@ impl TreeMode {
         // Build messages for Langbase
         let messages = self.build_messages(&params.content, &branch_thoughts, num_branches);
 
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+
         // Create invocation log
         let mut invocation = Invocation::new(
             "reasoning.tree",
             serialize_for_log(&params, "reasoning.tree input"),
         )
         .with_session(&session.id)
-        .with_pipe(&self.pipe_name);
+        .with_pipe(&pipe_name);
 
         // Call Langbase pipe
-        let request = PipeRequest::new(&self.pipe_name, messages);
-        let response = match self.core.langbase().call_pipe(request).await {
+        let request = PipeRequest::new(&pipe_name, messages)
+            .with_output_schema(self.core.response_schema(SchemaKind::Tree, &pipe_name))
+            .with_generation(params.generation);
+        let response = match self.core.backend().call_pipe(request).await {
             Ok(resp) => resp,
             Err(e) => {
                 let latency = start.elapsed().as_millis() as i64;
@@ -226,6 +489,9 @@ impl TreeMode {
                 return Err(e.into());
             }
         };
+        invocation = invocation
+            .with_cache_hit(response.cache_hit)
+            .with_pipe_fallback(response.fallback.as_deref());
 
         // Parse response
         let tree_response = self.parse_response(&response.completion)?;
@@ -371,6 +637,447 @@ impl TreeMode {
         Ok(branch)
     }
 
+    /// Compare branches against criteria and recommend one to pursue
+    ///
+    /// Sends each branch's chain of thoughts from the root to the tree pipe,
+    /// which summarizes and scores every branch. The recommendation is stored
+    /// as a thought on the recommended branch, with an `alternative`
+    /// cross-reference from it to each of the others.
+    pub async fn compare_branches(
+        &self,
+        params: TreeCompareParams,
+    ) -> AppResult<TreeCompareResult> {
+        let start = Instant::now();
+        params.generation.validate()?;
+        let criteria = compare_criteria(&params.criteria)?;
+        if params.branch_ids.len() < 2 || params.branch_ids.len() > MAX_COMPARED_BRANCHES {
+            return Err(ToolError::Validation {
+                field: "branch_ids".to_string(),
+                reason: format!("Must name between 2 and {} branches", MAX_COMPARED_BRANCHES),
+            }
+            .into());
+        }
+        for (i, id) in params.branch_ids.iter().enumerate() {
+            if params.branch_ids[..i].contains(id) {
+                return Err(ToolError::Validation {
+                    field: "branch_ids".to_string(),
+                    reason: format!("Branch listed twice: {}", id),
+                }
+                .into());
+            }
+        }
+
+        let session = self
+            .core
+            .storage()
+            .get_session(&params.session_id)
+            .await?
+            .ok_or_else(|| {
+                ToolError::Session(format!("Session not found: {}", params.session_id))
+            })?;
+
+        let mut branches = Vec::new();
+        let mut chains = Vec::new();
+        for id in &params.branch_ids {
+            let branch = self.session_branch(&session.id, id).await?;
+            chains.push(self.branch_chain(&branch).await?);
+            branches.push(branch);
+        }
+        if chains.iter().all(|chain| chain.is_empty()) {
+            return Err(ToolError::Validation {
+                field: "branch_ids".to_string(),
+                reason: "None of the branches has thoughts to compare".to_string(),
+            }
+            .into());
+        }
+
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+        let mut invocation = Invocation::new(
+            "reasoning.tree.compare",
+            serialize_for_log(&params, "reasoning.tree.compare input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe_name);
+
+        let messages = vec![
+            Message::system(TREE_COMPARE_PROMPT),
+            Message::user(compare_request(&chains, &criteria)),
+        ];
+        let request = PipeRequest::new(&pipe_name, messages).with_generation(params.generation);
+        let result: AppResult<_> = self
+            .core
+            .backend()
+            .call_pipe(request)
+            .await
+            .map_err(Into::into)
+            .and_then(|response| {
+                let json_str = extract_json_from_completion(&response.completion)
+                    .map_err(|e| ToolError::Reasoning { message: e })?;
+                let parsed: CompareResponse =
+                    serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                        message: format!("Failed to parse branch comparison: {}", e),
+                    })?;
+                Ok((parsed, response.cache_hit, response.fallback))
+            });
+        let (compare_response, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref());
+
+        let comparisons: Vec<BranchComparison> = branches
+            .iter()
+            .zip(&chains)
+            .enumerate()
+            .map(|(i, (branch, chain))| {
+                let assessment = compare_response
+                    .assessments
+                    .iter()
+                    .find(|a| parse_branch_label(&a.branch) == Some(i));
+                assess_branch(branch, chain, assessment, &criteria)
+            })
+            .collect();
+
+        // Follow the pipe's recommendation, or the best overall score when it
+        // names no compared branch
+        let recommended = parse_branch_label(&compare_response.recommended)
+            .filter(|&i| i < comparisons.len())
+            .unwrap_or_else(|| {
+                comparisons.iter().enumerate().fold(0, |best, (i, c)| {
+                    if c.overall_score > comparisons[best].overall_score {
+                        i
+                    } else {
+                        best
+                    }
+                })
+            });
+        let recommended_branch = &branches[recommended];
+        let confidence = compare_response.confidence.clamp(0.0, 1.0);
+        let rationale = compare_response.rationale.trim().to_string();
+
+        // Record the recommendation on the recommended branch
+        let label = recommended_branch
+            .name
+            .clone()
+            .unwrap_or_else(|| recommended_branch.id.clone());
+        let content = if rationale.is_empty() {
+            format!("Recommended branch: {}", label)
+        } else {
+            format!("Recommended branch: {}. {}", label, rationale)
+        };
+        let mut thought = Thought::new(&session.id, content, "tree")
+            .with_confidence(confidence)
+            .with_branch(&recommended_branch.id)
+            .with_metadata(serde_json::json!({
+                "branch_comparison": {
+                    "criteria": criteria,
+                    "branch_ids": params.branch_ids,
+                    "overall_scores": comparisons.iter().map(|c| c.overall_score).collect::<Vec<_>>(),
+                }
+            }));
+        if let Some(last) = chains[recommended].last() {
+            thought = thought.with_parent(&last.id);
+        }
+        self.core.storage().create_thought(&thought).await?;
+
+        let mut cross_refs_created = 0;
+        for (i, branch) in branches.iter().enumerate() {
+            if i == recommended {
+                continue;
+            }
+            let mut cr = CrossRef::new(
+                &recommended_branch.id,
+                &branch.id,
+                CrossRefType::Alternative,
+            )
+            .with_strength(confidence);
+            if !rationale.is_empty() {
+                cr = cr.with_reason(format!("Preferred in comparison: {}", rationale));
+            }
+            self.core.storage().create_cross_ref(&cr).await?;
+            cross_refs_created += 1;
+        }
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation.success(
+            serialize_for_log(&compare_response, "reasoning.tree.compare output"),
+            latency,
+        );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            branches = branches.len(),
+            recommended_branch_id = %recommended_branch.id,
+            latency_ms = latency,
+            "Tree branches compared"
+        );
+
+        Ok(TreeCompareResult {
+            session_id: session.id,
+            criteria,
+            recommended_branch_id: recommended_branch.id.clone(),
+            branches: comparisons,
+            rationale,
+            confidence,
+            recommendation_thought_id: thought.id,
+            cross_refs_created,
+        })
+    }
+
+    /// Merge two branches into a new branch that reconciles them
+    ///
+    /// Sends both branches' chains of thoughts to the tree pipe and stores the
+    /// reconciled reasoning as a thought on a new branch under the branch
+    /// both sources descend from. The new branch `extends` both sources,
+    /// which are marked completed.
+    pub async fn merge_branches(&self, params: TreeMergeParams) -> AppResult<TreeMergeResult> {
+        let start = Instant::now();
+        params.generation.validate()?;
+        if params.branch_a == params.branch_b {
+            return Err(ToolError::Validation {
+                field: "branch_b".to_string(),
+                reason: "Cannot merge a branch with itself".to_string(),
+            }
+            .into());
+        }
+
+        let session = self
+            .core
+            .storage()
+            .get_session(&params.session_id)
+            .await?
+            .ok_or_else(|| {
+                ToolError::Session(format!("Session not found: {}", params.session_id))
+            })?;
+        let branch_a = self.session_branch(&session.id, &params.branch_a).await?;
+        let branch_b = self.session_branch(&session.id, &params.branch_b).await?;
+        let chain_a = self.branch_chain(&branch_a).await?;
+        let chain_b = self.branch_chain(&branch_b).await?;
+        if chain_a.is_empty() && chain_b.is_empty() {
+            return Err(ToolError::Validation {
+                field: "branch_a, branch_b".to_string(),
+                reason: "Both branches have no thoughts to merge".to_string(),
+            }
+            .into());
+        }
+
+        // The new branch hangs off the last branch both sources descend from
+        let lineage_a = self.branch_lineage(&branch_a).await?;
+        let lineage_b = self.branch_lineage(&branch_b).await?;
+        let fork = lineage_a
+            .iter()
+            .zip(&lineage_b)
+            .take_while(|(a, b)| a.id == b.id)
+            .last()
+            .map(|(a, _)| a.id.clone());
+
+        let pipe_name = self.core.select_pipe(&self.pipe_name);
+        let mut invocation = Invocation::new(
+            "reasoning.tree.merge",
+            serialize_for_log(&params, "reasoning.tree.merge input"),
+        )
+        .with_session(&session.id)
+        .with_pipe(&pipe_name);
+
+        let messages = vec![
+            Message::system(TREE_MERGE_PROMPT),
+            Message::user(merge_request(&chain_a, &chain_b)),
+        ];
+        let request = PipeRequest::new(&pipe_name, messages).with_generation(params.generation);
+        let result: AppResult<_> = self
+            .core
+            .backend()
+            .call_pipe(request)
+            .await
+            .map_err(Into::into)
+            .and_then(|response| {
+                let json_str = extract_json_from_completion(&response.completion)
+                    .map_err(|e| ToolError::Reasoning { message: e })?;
+                let parsed: MergeResponse =
+                    serde_json::from_str(json_str).map_err(|e| ToolError::Reasoning {
+                        message: format!("Failed to parse branch merge: {}", e),
+                    })?;
+                if parsed.merged_reasoning.trim().is_empty() {
+                    return Err(ToolError::Reasoning {
+                        message: "Branch merge has no merged reasoning".to_string(),
+                    }
+                    .into());
+                }
+                Ok((parsed, response.cache_hit, response.fallback))
+            });
+        let (merge_response, cache_hit, fallback) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                let latency = start.elapsed().as_millis() as i64;
+                invocation = invocation.failure(e.to_string(), latency);
+                self.core.storage().log_invocation(&invocation).await?;
+                return Err(e);
+            }
+        };
+        invocation = invocation
+            .with_cache_hit(cache_hit)
+            .with_pipe_fallback(fallback.as_deref());
+
+        let confidence = merge_response.confidence.clamp(0.0, 1.0);
+        let name = |b: &Branch| b.name.clone().unwrap_or_else(|| b.id[..8].to_string());
+        let mut merged = Branch::new(&session.id)
+            .with_name(format!("Merge: {} + {}", name(&branch_a), name(&branch_b)))
+            .with_confidence(confidence);
+        if let Some(ref fork) = fork {
+            merged = merged.with_parent(fork);
+        }
+        self.core.storage().create_branch(&merged).await?;
+
+        let thought = Thought::new(&session.id, &merge_response.merged_reasoning, "tree")
+            .with_confidence(confidence)
+            .with_branch(&merged.id)
+            .with_metadata(serde_json::json!({
+                "merged_from": [branch_a.id, branch_b.id],
+                "agreements": merge_response.agreements,
+                "resolved_conflicts": merge_response.resolved_conflicts,
+            }));
+        self.core.storage().create_thought(&thought).await?;
+
+        let mut cross_refs_created = 0;
+        for source in [&branch_a, &branch_b] {
+            let cr = CrossRef::new(&merged.id, &source.id, CrossRefType::Extends)
+                .with_reason("Merged from this branch")
+                .with_strength(confidence);
+            self.core.storage().create_cross_ref(&cr).await?;
+            cross_refs_created += 1;
+        }
+
+        for mut source in [branch_a, branch_b] {
+            source.state = BranchState::Completed;
+            source.updated_at = chrono::Utc::now();
+            self.core.storage().update_branch(&source).await?;
+        }
+
+        let latency = start.elapsed().as_millis() as i64;
+        invocation = invocation.success(
+            serialize_for_log(&merge_response, "reasoning.tree.merge output"),
+            latency,
+        );
+        self.core.storage().log_invocation(&invocation).await?;
+
+        info!(
+            session_id = %session.id,
+            branch_id = %merged.id,
+            branch_a = %params.branch_a,
+            branch_b = %params.branch_b,
+            latency_ms = latency,
+            "Tree branches merged"
+        );
+
+        Ok(TreeMergeResult {
+            session_id: session.id,
+            branch_id: merged.id,
+            thought_id: thought.id,
+            content: merge_response.merged_reasoning,
+            confidence,
+            parent_branch: fork,
+            merged_branch_ids: vec![params.branch_a, params.branch_b],
+            agreements: merge_response.agreements,
+            resolved_conflicts: merge_response.resolved_conflicts,
+            cross_refs_created,
+        })
+    }
+
+    /// Render the session's branch hierarchy as Mermaid or ASCII
+    pub async fn export(&self, params: TreeExportParams) -> AppResult<TreeExportResult> {
+        let branches = self
+            .core
+            .storage()
+            .get_session_branches(&params.session_id)
+            .await?;
+        if branches.is_empty() {
+            return Err(ToolError::Validation {
+                field: "session_id".to_string(),
+                reason: format!("Session has no branches: {}", params.session_id),
+            }
+            .into());
+        }
+        let thoughts = self
+            .core
+            .storage()
+            .get_session_thoughts(&params.session_id)
+            .await?;
+        let thought_counts = branch_thought_counts(&thoughts);
+
+        Ok(TreeExportResult {
+            content: render_branch_tree(&branches, &thought_counts, params.format),
+            session_id: params.session_id,
+            format: params.format,
+            branch_count: branches.len(),
+            thought_count: thought_counts.values().sum(),
+        })
+    }
+
+    /// The branch `id`, which must belong to `session_id`
+    async fn session_branch(&self, session_id: &str, id: &str) -> AppResult<Branch> {
+        let branch = self
+            .core
+            .storage()
+            .get_branch(id)
+            .await?
+            .ok_or_else(|| ToolError::Session(format!("Branch not found: {}", id)))?;
+        if branch.session_id != session_id {
+            return Err(ToolError::Session(format!(
+                "Branch does not belong to this session: {}",
+                id
+            ))
+            .into());
+        }
+        Ok(branch)
+    }
+
+    /// `branch` and its ancestors, root branch first
+    async fn branch_lineage(&self, branch: &Branch) -> AppResult<Vec<Branch>> {
+        let mut chain = vec![branch.clone()];
+        while chain.len() < MAX_CHAIN_DEPTH {
+            let Some(parent_id) = chain.last().and_then(|b| b.parent_branch_id.clone()) else {
+                break;
+            };
+            if chain.iter().any(|b| b.id == parent_id) {
+                break;
+            }
+            match self.core.storage().get_branch(&parent_id).await? {
+                Some(parent) => chain.push(parent),
+                None => break,
+            }
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Thoughts on `branch`'s path from the root branch, in order
+    ///
+    /// Each ancestor contributes only the thoughts it held when the path
+    /// branched off it.
+    async fn branch_chain(&self, branch: &Branch) -> AppResult<Vec<Thought>> {
+        let chain = self.branch_lineage(branch).await?;
+
+        let mut thoughts = Vec::new();
+        for (i, link) in chain.iter().enumerate() {
+            let branched_at = chain.get(i + 1).map(|child| child.created_at);
+            let branch_thoughts = self.core.storage().get_branch_thoughts(&link.id).await?;
+            thoughts.extend(
+                branch_thoughts
+                    .into_iter()
+                    .filter(|t| branched_at.map_or(true, |at| t.created_at <= at)),
+            );
+        }
+        Ok(thoughts)
+    }
+
     fn build_messages(
         &self,
         content: &str,
@@ -434,6 +1141,7 @@ impl TreeParams {
             confidence: default_confidence(),
             num_branches: default_num_branches(),
             cross_refs: Vec::new(),
+            generation: GenerationParams::default(),
         }
     }
 
@@ -475,6 +1183,366 @@ impl TreeParams {
         });
         self
     }
+
+    /// Override the pipe's model parameters
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+impl TreeCompareParams {
+    /// Create new params comparing `branch_ids` in a session
+    pub fn new(session_id: impl Into<String>, branch_ids: Vec<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            branch_ids,
+            criteria: Vec::new(),
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Set the criteria to score against
+    pub fn with_criteria(mut self, criteria: Vec<String>) -> Self {
+        self.criteria = criteria;
+        self
+    }
+
+    /// Override model parameters for the pipe call
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+/// The requested criteria, trimmed, or the defaults when none are given
+fn compare_criteria(criteria: &[String]) -> AppResult<Vec<String>> {
+    if criteria.is_empty() {
+        return Ok(DEFAULT_COMPARE_CRITERIA
+            .iter()
+            .map(|c| c.to_string())
+            .collect());
+    }
+    if criteria.len() > MAX_COMPARE_CRITERIA {
+        return Err(ToolError::Validation {
+            field: "criteria".to_string(),
+            reason: format!("At most {} criteria", MAX_COMPARE_CRITERIA),
+        }
+        .into());
+    }
+    let mut trimmed: Vec<String> = Vec::new();
+    for criterion in criteria {
+        let criterion = criterion.trim();
+        if criterion.is_empty() {
+            return Err(ToolError::Validation {
+                field: "criteria".to_string(),
+                reason: "Criteria cannot be empty".to_string(),
+            }
+            .into());
+        }
+        if !trimmed.iter().any(|c| c.eq_ignore_ascii_case(criterion)) {
+            trimmed.push(criterion.to_string());
+        }
+    }
+    Ok(trimmed)
+}
+
+/// The user message of a comparison: each branch's labeled chain, then the
+/// criteria
+fn compare_request(chains: &[Vec<Thought>], criteria: &[String]) -> String {
+    let mut request = String::from("Compare these branches of a reasoning tree.");
+    for (i, chain) in chains.iter().enumerate() {
+        request.push_str(&format!("\n\n[B{}]", i + 1));
+        if chain.is_empty() {
+            request.push_str("\n(no thoughts yet)");
+        }
+        for (step, thought) in chain.iter().enumerate() {
+            request.push_str(&format!("\n{}. {}", step + 1, thought.content));
+        }
+    }
+    request.push_str("\n\nCRITERIA:");
+    for criterion in criteria {
+        request.push_str(&format!("\n- {}", criterion));
+    }
+    request
+}
+
+impl TreeMergeParams {
+    /// Create new params merging `branch_a` and `branch_b` in a session
+    pub fn new(
+        session_id: impl Into<String>,
+        branch_a: impl Into<String>,
+        branch_b: impl Into<String>,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            branch_a: branch_a.into(),
+            branch_b: branch_b.into(),
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Override model parameters for the pipe call
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+}
+
+/// The user message of a merge: both branches' chains, labeled A and B
+fn merge_request(chain_a: &[Thought], chain_b: &[Thought]) -> String {
+    let path = |label: char, chain: &[Thought]| {
+        if chain.is_empty() {
+            return "(no thoughts yet)".to_string();
+        }
+        chain
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("[{}{}] {}", label, i + 1, t.content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    format!(
+        "Merge these two branches of a reasoning tree.\n\nBRANCH A:\n{}\n\nBRANCH B:\n{}",
+        path('A', chain_a),
+        path('B', chain_b)
+    )
+}
+
+/// The 0-based index a branch label such as `B2` names
+fn parse_branch_label(label: &str) -> Option<usize> {
+    let label = label.trim().trim_start_matches('[').trim_end_matches(']');
+    let number = label
+        .strip_prefix('B')
+        .or_else(|| label.strip_prefix('b'))
+        .unwrap_or(label);
+    number.parse::<usize>().ok()?.checked_sub(1)
+}
+
+/// Combine a branch with the pipe's assessment of it, keeping only scores
+/// for the requested criteria
+fn assess_branch(
+    branch: &Branch,
+    chain: &[Thought],
+    assessment: Option<&BranchAssessment>,
+    criteria: &[String],
+) -> BranchComparison {
+    let scores: Vec<BranchCriterionScore> = criteria
+        .iter()
+        .filter_map(|criterion| {
+            let (_, score) = assessment?
+                .scores
+                .iter()
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case(criterion))?;
+            Some(BranchCriterionScore {
+                criterion: criterion.clone(),
+                score: score.clamp(0.0, 1.0),
+            })
+        })
+        .collect();
+    let overall_score = if scores.is_empty() {
+        0.0
+    } else {
+        scores.iter().map(|s| s.score).sum::<f64>() / scores.len() as f64
+    };
+    let summary = match assessment {
+        Some(a) if !a.summary.trim().is_empty() => a.summary.clone(),
+        _ => chain.last().map(|t| t.content.clone()).unwrap_or_default(),
+    };
+
+    BranchComparison {
+        branch_id: branch.id.clone(),
+        name: branch.name.clone(),
+        thought_count: chain.len(),
+        summary,
+        scores,
+        overall_score,
+        strengths: assessment.map(|a| a.strengths.clone()).unwrap_or_default(),
+        weaknesses: assessment.map(|a| a.weaknesses.clone()).unwrap_or_default(),
+    }
+}
+
+impl TreeExportParams {
+    /// Create export parameters for a session (Mermaid by default).
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            format: TreeExportFormat::default(),
+        }
+    }
+
+    /// Set the output format.
+    pub fn with_format(mut self, format: TreeExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// Number of thoughts on each branch, keyed by branch ID
+pub fn branch_thought_counts(thoughts: &[Thought]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for branch_id in thoughts.iter().filter_map(|t| t.branch_id.as_ref()) {
+        *counts.entry(branch_id.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Render a session's branches as a hierarchy in the given format.
+///
+/// Each branch shows its name, thought count, state and confidence. Branches
+/// whose parent is not among `branches` are drawn as roots, as is the first
+/// branch of any parent cycle; siblings keep the order of `branches`.
+pub fn render_branch_tree(
+    branches: &[Branch],
+    thought_counts: &HashMap<String, usize>,
+    format: TreeExportFormat,
+) -> String {
+    let ids: HashSet<&str> = branches.iter().map(|b| b.id.as_str()).collect();
+    let mut children: HashMap<&str, Vec<&Branch>> = HashMap::new();
+    let mut roots = Vec::new();
+    for branch in branches {
+        match branch.parent_branch_id.as_deref() {
+            Some(parent) if parent != branch.id && ids.contains(parent) => {
+                children.entry(parent).or_default().push(branch)
+            }
+            _ => roots.push(branch),
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut lines = Vec::new();
+    match format {
+        TreeExportFormat::Mermaid => {
+            lines.push("flowchart TD".to_string());
+            let mut edges = Vec::new();
+            for root in roots.into_iter().chain(branches) {
+                mermaid_branch(
+                    root,
+                    &children,
+                    thought_counts,
+                    &mut visited,
+                    &mut lines,
+                    &mut edges,
+                );
+            }
+            lines.extend(edges);
+            lines.push("    classDef completed fill:#d4edda,stroke:#28a745".to_string());
+            lines.push(
+                "    classDef abandoned fill:#eeeeee,stroke:#999999,color:#777777,stroke-dasharray:5 5"
+                    .to_string(),
+            );
+        }
+        TreeExportFormat::Ascii => {
+            for root in roots.into_iter().chain(branches) {
+                if visited.contains(root.id.as_str()) {
+                    continue;
+                }
+                lines.push(branch_label(root, thought_counts));
+                ascii_children(
+                    root,
+                    &children,
+                    thought_counts,
+                    "",
+                    &mut visited,
+                    &mut lines,
+                );
+            }
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// `name (N thoughts, state, confidence C)`, naming unnamed branches by a
+/// short ID
+fn branch_label(branch: &Branch, thought_counts: &HashMap<String, usize>) -> String {
+    let name = branch
+        .name
+        .clone()
+        .unwrap_or_else(|| branch.id.chars().take(8).collect());
+    let count = thought_counts.get(&branch.id).copied().unwrap_or(0);
+    format!(
+        "{} ({} thought{}, {}, confidence {:.2})",
+        name,
+        count,
+        if count == 1 { "" } else { "s" },
+        branch.state,
+        branch.confidence
+    )
+}
+
+/// Declare `branch` and its descendants as Mermaid nodes, collecting the
+/// parent-child edges separately so they follow all node declarations.
+/// Returns the node ID given to `branch`, or `None` if it was already drawn.
+fn mermaid_branch<'a>(
+    branch: &'a Branch,
+    children: &HashMap<&str, Vec<&'a Branch>>,
+    thought_counts: &HashMap<String, usize>,
+    visited: &mut HashSet<&'a str>,
+    lines: &mut Vec<String>,
+    edges: &mut Vec<String>,
+) -> Option<String> {
+    if !visited.insert(branch.id.as_str()) {
+        return None;
+    }
+    let node_id = format!("b{}", visited.len() - 1);
+    let label = branch_label(branch, thought_counts).replace('"', "#quot;");
+    let class = match branch.state {
+        BranchState::Active => "",
+        BranchState::Completed => ":::completed",
+        BranchState::Abandoned => ":::abandoned",
+    };
+    lines.push(format!("    {}[\"{}\"]{}", node_id, label, class));
+
+    for child in children.get(branch.id.as_str()).into_iter().flatten() {
+        if let Some(child_id) =
+            mermaid_branch(child, children, thought_counts, visited, lines, edges)
+        {
+            edges.push(format!("    {} --> {}", node_id, child_id));
+        }
+    }
+    Some(node_id)
+}
+
+/// Append `branch`'s descendants as ASCII tree lines under `prefix`
+fn ascii_children<'a>(
+    branch: &'a Branch,
+    children: &HashMap<&str, Vec<&'a Branch>>,
+    thought_counts: &HashMap<String, usize>,
+    prefix: &str,
+    visited: &mut HashSet<&'a str>,
+    lines: &mut Vec<String>,
+) {
+    if !visited.insert(branch.id.as_str()) {
+        return;
+    }
+    let kids: Vec<&Branch> = children
+        .get(branch.id.as_str())
+        .into_iter()
+        .flatten()
+        .filter(|c| !visited.contains(c.id.as_str()))
+        .copied()
+        .collect();
+    for (i, child) in kids.iter().enumerate() {
+        let last = i + 1 == kids.len();
+        lines.push(format!(
+            "{}{}{}",
+            prefix,
+            if last { "└── " } else { "├── " },
+            branch_label(child, thought_counts)
+        ));
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        ascii_children(
+            child,
+            children,
+            thought_counts,
+            &child_prefix,
+            visited,
+            lines,
+        );
+    }
 }
 
 fn truncate(s: &str, max_len: usize) -> String {
@@ -488,6 +1556,8 @@ fn truncate(s: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langbase::LangbaseClient;
+    use crate::storage::SqliteStorage;
 
     // ============================================================================
     // Default Function Tests
@@ -793,6 +1863,22 @@ mod tests {
         assert_eq!(response.branches[0].thought, "Path A");
     }
 
+    #[test]
+    fn test_tree_output_schema() {
+        let schema = TreeResponse::output_schema();
+        let valid = r#"{
+            "branches": [{"thought": "Path A", "confidence": 0.9, "rationale": "Strong"}],
+            "recommended_branch": 0,
+            "metadata": null
+        }"#;
+        assert!(schema.validate_completion(valid).is_ok());
+
+        let invalid =
+            r#"{"branches": [{"thought": "Path A", "confidence": 0.9}], "recommended_branch": 0}"#;
+        let err = schema.validate_completion(invalid).unwrap_err();
+        assert_eq!(err.path, "$.branches[0].rationale");
+    }
+
     // ============================================================================
     // BranchInfo Tests
     // ============================================================================
@@ -1561,8 +2647,11 @@ mod tests {
 
     fn create_test_config() -> Config {
         use crate::config::{
-            DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-            PipeConfig,
+            BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+            LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+            PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+            ProviderConfig, RateLimitConfig, RetentionConfig, ToolsConfig, TransportConfig,
+            DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
         };
         use std::path::PathBuf;
 
@@ -1570,10 +2659,15 @@ mod tests {
             langbase: LangbaseConfig {
                 api_key: "test-key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -1582,6 +2676,18 @@ mod tests {
             request: crate::config::RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         }
     }
 
@@ -2432,4 +3538,194 @@ mod tests {
         let parsed: BranchInfo = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.rationale.len(), 50000);
     }
+
+    // ============================================================================
+    // Branch Comparison Tests
+    // ============================================================================
+
+    #[test]
+    fn test_compare_criteria_defaults_and_dedup() {
+        assert_eq!(
+            compare_criteria(&[]).unwrap(),
+            vec!["soundness", "feasibility", "impact"]
+        );
+        let criteria = vec![" Cost ".to_string(), "cost".to_string(), "risk".to_string()];
+        assert_eq!(compare_criteria(&criteria).unwrap(), vec!["Cost", "risk"]);
+    }
+
+    #[test]
+    fn test_compare_criteria_rejects_empty_and_too_many() {
+        assert!(compare_criteria(&[" ".to_string()]).is_err());
+        let many: Vec<String> = (0..=MAX_COMPARE_CRITERIA)
+            .map(|i| format!("c{}", i))
+            .collect();
+        assert!(compare_criteria(&many).is_err());
+    }
+
+    #[test]
+    fn test_parse_branch_label() {
+        assert_eq!(parse_branch_label("B1"), Some(0));
+        assert_eq!(parse_branch_label("[b3]"), Some(2));
+        assert_eq!(parse_branch_label("2"), Some(1));
+        assert_eq!(parse_branch_label("B0"), None);
+        assert_eq!(parse_branch_label("first"), None);
+    }
+
+    #[test]
+    fn test_compare_request_labels_chains() {
+        let chains = vec![
+            vec![
+                Thought::new("s-1", "Root idea", "tree"),
+                Thought::new("s-1", "Option A", "tree"),
+            ],
+            vec![],
+        ];
+        let request = compare_request(&chains, &["cost".to_string()]);
+        assert!(request.contains("[B1]\n1. Root idea\n2. Option A"));
+        assert!(request.contains("[B2]\n(no thoughts yet)"));
+        assert!(request.ends_with("CRITERIA:\n- cost"));
+    }
+
+    #[test]
+    fn test_merge_request_labels_both_branches() {
+        let chain_a = vec![
+            Thought::new("s-1", "Root idea", "tree"),
+            Thought::new("s-1", "Option A", "tree"),
+        ];
+        let request = merge_request(&chain_a, &[]);
+        assert!(request.contains("BRANCH A:\n[A1] Root idea\n[A2] Option A"));
+        assert!(request.ends_with("BRANCH B:\n(no thoughts yet)"));
+    }
+
+    #[test]
+    fn test_assess_branch_keeps_requested_criteria() {
+        let branch = Branch::new("s-1").with_name("Option 1");
+        let chain = vec![Thought::new("s-1", "Last thought", "tree")];
+        let assessment = BranchAssessment {
+            branch: "B1".to_string(),
+            summary: "Sound plan".to_string(),
+            scores: HashMap::from([
+                ("Cost".to_string(), 0.4),
+                ("risk".to_string(), 1.5),
+                ("unrequested".to_string(), 0.9),
+            ]),
+            strengths: vec!["Cheap".to_string()],
+            weaknesses: vec![],
+        };
+        let criteria = vec!["cost".to_string(), "risk".to_string(), "speed".to_string()];
+
+        let comparison = assess_branch(&branch, &chain, Some(&assessment), &criteria);
+        assert_eq!(comparison.summary, "Sound plan");
+        assert_eq!(comparison.scores.len(), 2);
+        assert_eq!(comparison.scores[0].criterion, "cost");
+        assert_eq!(comparison.scores[1].score, 1.0);
+        assert!((comparison.overall_score - 0.7).abs() < 1e-9);
+        assert_eq!(comparison.thought_count, 1);
+    }
+
+    #[test]
+    fn test_assess_branch_without_assessment_uses_last_thought() {
+        let branch = Branch::new("s-1");
+        let chain = vec![Thought::new("s-1", "Last thought", "tree")];
+        let comparison = assess_branch(&branch, &chain, None, &["cost".to_string()]);
+        assert_eq!(comparison.summary, "Last thought");
+        assert!(comparison.scores.is_empty());
+        assert_eq!(comparison.overall_score, 0.0);
+    }
+
+    // ============================================================================
+    // Branch Tree Export Tests
+    // ============================================================================
+
+    fn export_fixture() -> (Vec<Branch>, HashMap<String, usize>) {
+        let root = Branch::new("s-1").with_name("Root");
+        let a = Branch::new("s-1")
+            .with_name("Option \"A\"")
+            .with_parent(&root.id)
+            .with_state(BranchState::Completed)
+            .with_confidence(0.7);
+        let b = Branch::new("s-1")
+            .with_parent(&root.id)
+            .with_state(BranchState::Abandoned)
+            .with_confidence(0.3);
+        let a1 = Branch::new("s-1").with_name("Deeper").with_parent(&a.id);
+        let thoughts = vec![
+            Thought::new("s-1", "r1", "tree").with_branch(&root.id),
+            Thought::new("s-1", "r2", "tree").with_branch(&root.id),
+            Thought::new("s-1", "a1", "tree").with_branch(&a.id),
+            Thought::new("s-1", "loose", "linear"),
+        ];
+        (vec![root, a, b, a1], branch_thought_counts(&thoughts))
+    }
+
+    #[test]
+    fn test_tree_export_format_parse() {
+        assert_eq!("Mermaid".parse(), Ok(TreeExportFormat::Mermaid));
+        assert_eq!("text".parse(), Ok(TreeExportFormat::Ascii));
+        assert!("dot".parse::<TreeExportFormat>().is_err());
+        assert_eq!(TreeExportFormat::Ascii.to_string(), "ascii");
+    }
+
+    #[test]
+    fn test_branch_thought_counts_skips_unbranched() {
+        let (branches, counts) = export_fixture();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&branches[0].id], 2);
+        assert_eq!(counts[&branches[1].id], 1);
+    }
+
+    #[test]
+    fn test_render_branch_tree_ascii() {
+        let (branches, counts) = export_fixture();
+        let ascii = render_branch_tree(&branches, &counts, TreeExportFormat::Ascii);
+        let short_b: String = branches[2].id.chars().take(8).collect();
+        assert_eq!(
+            ascii,
+            format!(
+                "Root (2 thoughts, active, confidence 0.80)\n\
+                 ├── Option \"A\" (1 thought, completed, confidence 0.70)\n\
+                 │   └── Deeper (0 thoughts, active, confidence 0.80)\n\
+                 └── {} (0 thoughts, abandoned, confidence 0.30)\n",
+                short_b
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_branch_tree_mermaid() {
+        let (branches, counts) = export_fixture();
+        let mermaid = render_branch_tree(&branches, &counts, TreeExportFormat::Mermaid);
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!(lines[0], "flowchart TD");
+        assert_eq!(
+            lines[1],
+            "    b0[\"Root (2 thoughts, active, confidence 0.80)\"]"
+        );
+        assert_eq!(
+            lines[2],
+            "    b1[\"Option #quot;A#quot; (1 thought, completed, confidence 0.70)\"]:::completed"
+        );
+        assert!(lines[4].ends_with(":::abandoned"));
+        assert!(lines.contains(&"    b0 --> b1"));
+        assert!(lines.contains(&"    b1 --> b2"));
+        assert!(lines.contains(&"    b0 --> b3"));
+        assert!(mermaid.contains("classDef abandoned"));
+    }
+
+    #[test]
+    fn test_render_branch_tree_orphans_and_cycles_are_roots() {
+        let mut a = Branch::new("s-1").with_name("A");
+        let b = Branch::new("s-1").with_name("B").with_parent(&a.id);
+        a.parent_branch_id = Some(b.id.clone());
+        let orphan = Branch::new("s-1")
+            .with_name("Orphan")
+            .with_parent("missing");
+        let ascii = render_branch_tree(&[a, b, orphan], &HashMap::new(), TreeExportFormat::Ascii);
+        assert_eq!(
+            ascii,
+            "Orphan (0 thoughts, active, confidence 0.80)\n\
+             A (0 thoughts, active, confidence 0.80)\n\
+             └── B (0 thoughts, active, confidence 0.80)\n"
+        );
+    }
 }
@@ -1,6 +1,6 @@
 //! Built-in workflow presets for common reasoning tasks.
 
-use super::types::{ParamSpec, PresetStep, WorkflowPreset};
+use super::types::{ParamSpec, PresetExecution, PresetStep, WorkflowPreset};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -10,7 +10,7 @@ use std::collections::HashMap;
 /// 1. Divergent analysis - Generate multiple perspectives
 /// 2. Bias detection - Check for cognitive biases (optional)
 /// 3. Fallacy detection - Check for logical fallacies (optional)
-/// 4. Reflection - Synthesize findings
+/// 4. Reflection - Synthesize findings, scored against a code review rubric
 pub fn code_review_preset() -> WorkflowPreset {
     WorkflowPreset {
         id: "code-review".to_string(),
@@ -71,6 +71,15 @@ pub fn code_review_preset() -> WorkflowPreset {
                 .with_description("Synthesize findings into final assessment")
                 .with_input("content", "code")
                 .with_static("quality_threshold", json!(0.7))
+                .with_static(
+                    "rubric",
+                    json!([
+                        {"name": "correctness", "weight": 3, "description": "The code does what it claims, including edge cases"},
+                        {"name": "maintainability", "weight": 2, "description": "Readable, well-structured and easy to change"},
+                        {"name": "security", "weight": 2, "description": "No unsafe input handling, secrets or injection risks"},
+                        {"name": "performance", "weight": 1, "description": "No needless work on hot paths"}
+                    ]),
+                )
                 .store_as("reflection")
                 .depends_on(vec![
                     "divergent_analysis".to_string(),
@@ -78,6 +87,7 @@ pub fn code_review_preset() -> WorkflowPreset {
                     "fallacy_check".to_string(),
                 ]),
         ],
+        execution: PresetExecution::Local,
     }
 }
 
@@ -146,6 +156,7 @@ pub fn debug_analysis_preset() -> WorkflowPreset {
                 .store_as("conclusion")
                 .depends_on(vec!["hypothesis_tree".to_string()]),
         ],
+        execution: PresetExecution::Local,
     }
 }
 
@@ -223,6 +234,7 @@ pub fn architecture_decision_preset() -> WorkflowPreset {
                 .store_as("decision")
                 .depends_on(vec!["got_score".to_string()]),
         ],
+        execution: PresetExecution::Local,
     }
 }
 
@@ -310,6 +322,7 @@ pub fn strategic_decision_preset() -> WorkflowPreset {
                     "bias_check".to_string(),
                 ]),
         ],
+        execution: PresetExecution::Local,
     }
 }
 
@@ -401,6 +414,7 @@ pub fn evidence_based_conclusion_preset() -> WorkflowPreset {
                     "fallacy_check".to_string(),
                 ]),
         ],
+        execution: PresetExecution::Local,
     }
 }
 
@@ -418,6 +432,21 @@ mod tests {
         assert!(preset.input_schema.get("code").unwrap().required);
     }
 
+    #[test]
+    fn test_code_review_rubric_is_valid() {
+        let preset = code_review_preset();
+        let reflect = preset
+            .steps
+            .iter()
+            .find(|s| s.step_id == "reflect")
+            .unwrap();
+        let rubric: Vec<crate::modes::RubricDimension> =
+            serde_json::from_value(reflect.static_inputs["rubric"].clone()).unwrap();
+        assert_eq!(rubric.len(), 4);
+        assert_eq!(rubric[0].name, "correctness");
+        assert_eq!(rubric[0].weight, 3.0);
+    }
+
     #[test]
     fn test_debug_analysis_preset() {
         let preset = debug_analysis_preset();
@@ -6,9 +6,15 @@ use std::time::Instant;
 use thiserror::Error;
 use tracing::{info, warn};
 
-use super::types::{PresetResult, PresetStep, StepCondition, StepResult, WorkflowPreset};
-use crate::error::McpResult;
+use super::types::{
+    PresetExecution, PresetResult, PresetStep, StepCondition, StepResult, WorkflowPreset,
+};
+use super::workflow::{compile_workflow, workflow_step_mode};
+use crate::config::ProviderKind;
+use crate::error::{McpError, McpResult};
+use crate::langbase::{extract_json_from_completion, WorkflowRunResponse};
 use crate::server::handle_tool_call;
+use crate::storage::{Invocation, Thought};
 
 /// Shared state type alias for the executor.
 pub type SharedState = Arc<crate::server::AppState>;
@@ -72,6 +78,12 @@ pub async fn execute_preset(
     let mut context: HashMap<String, serde_json::Value> = inputs.clone();
     let mut completed_steps: HashSet<String> = HashSet::new();
 
+    if preset.execution == PresetExecution::LangbaseWorkflow {
+        if let Some(result) = execute_as_workflow(state, preset, &inputs, start).await? {
+            return Ok(result);
+        }
+    }
+
     info!(preset_id = %preset.id, steps = preset.steps.len(), "Starting preset execution");
 
     for (idx, step) in preset.steps.iter().enumerate() {
@@ -231,6 +243,7 @@ pub async fn execute_preset(
                         status: "failed".to_string(),
                         duration_ms: start.elapsed().as_millis() as i64,
                         error: Some(e.to_string()),
+                        workflow_run_id: None,
                     });
                 }
             }
@@ -273,9 +286,196 @@ pub async fn execute_preset(
         status: status.to_string(),
         duration_ms: start.elapsed().as_millis() as i64,
         error: None,
+        workflow_run_id: None,
     })
 }
 
+/// Run a preset as a single Langbase workflow, persisting each step's
+/// output as a thought in the preset's session.
+///
+/// Returns `None`, after logging why, when the preset should run locally
+/// instead: it does not compile, one of its pipes is not served by Langbase,
+/// or the workflow run itself fails.
+async fn execute_as_workflow(
+    state: &SharedState,
+    preset: &WorkflowPreset,
+    inputs: &HashMap<String, serde_json::Value>,
+    start: Instant,
+) -> McpResult<Option<PresetResult>> {
+    let request = match compile_workflow(preset, inputs, &state.config.pipes) {
+        Ok(request) => request,
+        Err(e) => {
+            warn!(preset_id = %preset.id, error = %e, "Preset cannot run as a Langbase workflow, executing locally");
+            return Ok(None);
+        }
+    };
+    if let Some(step) = request
+        .steps
+        .iter()
+        .find(|s| state.config.providers.provider_for(&s.pipe) != ProviderKind::Langbase)
+    {
+        warn!(preset_id = %preset.id, pipe = %step.pipe, "Pipe is not served by Langbase, executing preset locally");
+        return Ok(None);
+    }
+
+    info!(preset_id = %preset.id, steps = request.steps.len(), "Running preset as Langbase workflow");
+    let response = match state.langbase.run_workflow(&request).await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(preset_id = %preset.id, error = %e, "Langbase workflow run failed, executing locally");
+            return Ok(None);
+        }
+    };
+
+    let session_id = inputs
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+    let session = state
+        .storage
+        .get_or_create_session(&session_id, "preset")
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to get preset session: {}", e),
+        })?;
+
+    let step_results = workflow_step_results(preset, &response, &session.id);
+    let thoughts: Vec<Thought> = step_results
+        .iter()
+        .filter_map(|(_, thought)| thought.clone())
+        .collect();
+    state
+        .storage
+        .create_thoughts_batch(&thoughts)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to persist workflow results: {}", e),
+        })?;
+    let step_results: Vec<StepResult> = step_results.into_iter().map(|(r, _)| r).collect();
+
+    let successful = step_results
+        .iter()
+        .filter(|s| s.status == "success")
+        .count();
+    let failed_required = step_results.iter().any(|r| {
+        r.status == "failed"
+            && preset
+                .steps
+                .iter()
+                .any(|s| s.step_id == r.step_id && !s.optional)
+    });
+    let status = if successful == preset.steps.len() {
+        "success"
+    } else if failed_required || successful == 0 {
+        "failed"
+    } else {
+        "partial"
+    };
+    let duration_ms = start.elapsed().as_millis() as i64;
+
+    let invocation = Invocation::new(
+        "reasoning_preset_run",
+        serde_json::json!({"preset_id": preset.id, "execution": "langbase_workflow"}),
+    )
+    .with_session(&session.id)
+    .with_pipe(format!("workflow:{}", request.name))
+    .success(
+        serde_json::json!({"workflow_run_id": response.id, "status": status}),
+        duration_ms,
+    );
+    if let Err(e) = state.storage.log_invocation(&invocation).await {
+        warn!(error = %e, tool = %invocation.tool_name, "Failed to log invocation - audit trail incomplete");
+    }
+
+    info!(
+        preset_id = %preset.id,
+        workflow_run_id = %response.id,
+        status = status,
+        steps_completed = successful,
+        duration_ms = duration_ms,
+        "Preset workflow run completed"
+    );
+
+    Ok(Some(PresetResult {
+        preset_id: preset.id.clone(),
+        steps_completed: successful,
+        steps_total: preset.steps.len(),
+        final_output: step_results
+            .iter()
+            .rev()
+            .find(|s| s.status == "success")
+            .map(|s| s.result.clone()),
+        step_results,
+        status: status.to_string(),
+        duration_ms,
+        error: None,
+        workflow_run_id: Some(response.id),
+    }))
+}
+
+/// Step results of a workflow run, each with the thought to persist for a
+/// successful step
+fn workflow_step_results(
+    preset: &WorkflowPreset,
+    response: &WorkflowRunResponse,
+    session_id: &str,
+) -> Vec<(StepResult, Option<Thought>)> {
+    preset
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| {
+            let outcome = response.steps.iter().find(|r| r.id == step.step_id);
+            let mut result = StepResult {
+                step: idx + 1,
+                step_id: step.step_id.clone(),
+                tool: step.tool.clone(),
+                result: serde_json::json!(null),
+                duration_ms: 0,
+                status: "failed".to_string(),
+                error: None,
+            };
+            let Some(completion) = outcome.and_then(|o| o.completion.as_deref()) else {
+                result.error = Some(
+                    outcome
+                        .and_then(|o| o.error.clone())
+                        .unwrap_or_else(|| "Step did not run".to_string()),
+                );
+                return (result, None);
+            };
+
+            // Pipes answer in JSON; keep the reasoning text as the thought
+            let parsed: Option<serde_json::Value> = extract_json_from_completion(completion)
+                .ok()
+                .and_then(|json| serde_json::from_str(json).ok());
+            let content = parsed
+                .as_ref()
+                .and_then(|v| v.get("thought").or_else(|| v.get("analysis")))
+                .and_then(|v| v.as_str())
+                .unwrap_or(completion);
+            let mode = workflow_step_mode(&step.tool).unwrap_or("preset");
+            let mut thought = Thought::new(session_id, content, mode).with_metadata(
+                serde_json::json!({"preset_id": preset.id, "step_id": step.step_id, "workflow_run_id": response.id}),
+            );
+            if let Some(confidence) = parsed
+                .as_ref()
+                .and_then(|v| v.get("confidence"))
+                .and_then(|v| v.as_f64())
+            {
+                thought = thought.with_confidence(confidence);
+            }
+
+            result.status = "success".to_string();
+            result.result = serde_json::json!({
+                "session_id": session_id,
+                "thought_id": thought.id,
+                "output": parsed.unwrap_or_else(|| serde_json::json!(completion)),
+            });
+            (result, Some(thought))
+        })
+        .collect()
+}
+
 /// Build tool arguments from step configuration and context.
 fn build_step_arguments(
     step: &PresetStep,
@@ -310,6 +510,15 @@ fn build_step_arguments(
         }
     }
 
+    // Add the step's generation overrides unless the inputs already set them
+    if let Some(generation) = &step.generation {
+        if !args.contains_key("generation") {
+            if let Ok(value) = serde_json::to_value(generation) {
+                args.insert("generation".to_string(), value);
+            }
+        }
+    }
+
     serde_json::Value::Object(args)
 }
 
@@ -439,6 +648,7 @@ fn evaluate_condition(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langbase::GenerationParams;
 
     #[test]
     fn test_build_step_arguments_simple() {
@@ -457,6 +667,23 @@ mod tests {
         assert_eq!(args.get("confidence"), Some(&serde_json::json!(0.8)));
     }
 
+    #[test]
+    fn test_build_step_arguments_generation() {
+        let generation = GenerationParams {
+            temperature: Some(0.0),
+            ..Default::default()
+        };
+        let step = PresetStep::new("test", "reasoning_assess_evidence").with_generation(generation);
+
+        let args = build_step_arguments(&step, &HashMap::new());
+        assert_eq!(args["generation"], serde_json::json!({"temperature": 0.0}));
+
+        // Explicit inputs win over the step default
+        let step = step.with_static("generation", serde_json::json!({"temperature": 1.5}));
+        let args = build_step_arguments(&step, &HashMap::new());
+        assert_eq!(args["generation"]["temperature"], 1.5);
+    }
+
     #[test]
     fn test_build_step_arguments_nested() {
         let step = PresetStep::new("test", "reasoning_linear")
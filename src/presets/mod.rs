@@ -4,14 +4,17 @@
 //! - `WorkflowPreset`: Definition of multi-step reasoning workflows
 //! - `PresetRegistry`: Registration and lookup of presets
 //! - `execute_preset`: Workflow execution engine
+//! - `compile_workflow`: Compilation of presets into server-side Langbase workflows
 //! - Built-in presets for common tasks
 
 mod builtins;
 mod executor;
 mod registry;
 mod types;
+mod workflow;
 
 pub use builtins::*;
 pub use executor::execute_preset;
 pub use registry::PresetRegistry;
 pub use types::*;
+pub use workflow::{compile_workflow, workflow_step_mode, WorkflowCompileError};
@@ -163,6 +163,7 @@ mod tests {
             output_format: "json".to_string(),
             estimated_time: "1 minute".to_string(),
             tags: vec![],
+            execution: Default::default(),
         }
     }
 
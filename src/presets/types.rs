@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::langbase::GenerationParams;
+
 /// A reusable workflow preset that composes reasoning modes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowPreset {
@@ -25,6 +27,21 @@ pub struct WorkflowPreset {
     /// Tags for searchability.
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Where the steps run.
+    #[serde(default)]
+    pub execution: PresetExecution,
+}
+
+/// Where a preset's steps run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetExecution {
+    /// Each step is a local tool call.
+    #[default]
+    Local,
+    /// Compile the steps into one Langbase workflow and run it server-side,
+    /// falling back to local execution when the preset cannot run that way.
+    LangbaseWorkflow,
 }
 
 /// A single step in a preset workflow.
@@ -54,6 +71,9 @@ pub struct PresetStep {
     /// If true, failures don't stop the workflow.
     #[serde(default)]
     pub optional: bool,
+    /// Model parameter overrides passed to the step's tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<GenerationParams>,
 }
 
 /// Conditional execution logic.
@@ -111,6 +131,9 @@ pub struct PresetResult {
     /// Error message if failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Langbase workflow run ID, when the preset ran as a workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_run_id: Option<String>,
 }
 
 /// Result of a single step execution.
@@ -177,6 +200,7 @@ impl PresetStep {
             store_as: None,
             depends_on: Vec::new(),
             optional: false,
+            generation: None,
         }
     }
 
@@ -215,6 +239,12 @@ impl PresetStep {
         self.optional = true;
         self
     }
+
+    /// Set model parameter overrides for the step.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = Some(generation);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -236,6 +266,7 @@ mod tests {
             output_format: "json".to_string(),
             estimated_time: "1 minute".to_string(),
             tags: vec!["test".to_string()],
+            execution: PresetExecution::default(),
         };
 
         let summary = preset.to_summary();
@@ -278,6 +309,7 @@ mod tests {
             status: "partial".to_string(),
             duration_ms: 1500,
             error: None,
+            workflow_run_id: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
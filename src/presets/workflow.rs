@@ -0,0 +1,264 @@
+//! Compiling presets into Langbase workflows.
+//!
+//! A preset whose steps each map onto a single pipe call can run as one
+//! server-side Langbase workflow instead of one round trip per step. Step
+//! inputs taken from preset inputs are filled in here; inputs taken from an
+//! earlier step's stored result become `{{steps.<id>.output}}` references
+//! that Langbase resolves as the workflow runs.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use super::types::{PresetStep, WorkflowPreset};
+use crate::config::PipeConfig;
+use crate::langbase::{Message, WorkflowRunRequest, WorkflowStep};
+use crate::prompts::{
+    BIAS_DETECTION_PROMPT, DIVERGENT_REASONING_PROMPT, FALLACY_DETECTION_PROMPT,
+    LINEAR_REASONING_PROMPT, REFLECTION_PROMPT,
+};
+
+/// Reasons a preset cannot run as a Langbase workflow.
+#[derive(Debug, Error)]
+pub enum WorkflowCompileError {
+    /// The step's tool does more than a single pipe call.
+    #[error("Step '{step_id}' uses tool '{tool}', which has no single-pipe equivalent")]
+    UnsupportedTool {
+        /// The step that cannot be compiled.
+        step_id: String,
+        /// The step's tool.
+        tool: String,
+    },
+
+    /// Conditions are evaluated locally and cannot be expressed in a workflow.
+    #[error("Step '{step_id}' has a condition, which workflows cannot evaluate")]
+    ConditionalStep {
+        /// The conditional step.
+        step_id: String,
+    },
+
+    /// Per-step model parameter overrides are not forwarded to workflows.
+    #[error("Step '{step_id}' overrides generation parameters, which workflows do not support")]
+    GenerationOverride {
+        /// The step with overrides.
+        step_id: String,
+    },
+
+    /// The step's `content` input resolved to nothing.
+    #[error("Step '{step_id}' has no content input")]
+    MissingContent {
+        /// The step without content.
+        step_id: String,
+    },
+}
+
+/// Reasoning mode recorded for thoughts produced by `tool` when it runs as a
+/// workflow step, or `None` if the tool cannot be compiled.
+pub fn workflow_step_mode(tool: &str) -> Option<&'static str> {
+    tool_target(tool).map(|(mode, _)| mode)
+}
+
+/// Mode and system prompt of the tools that map onto a single pipe call
+fn tool_target(tool: &str) -> Option<(&'static str, &'static str)> {
+    match tool {
+        "reasoning_linear" => Some(("linear", LINEAR_REASONING_PROMPT)),
+        "reasoning_divergent" => Some(("divergent", DIVERGENT_REASONING_PROMPT)),
+        "reasoning_reflection" => Some(("reflection", REFLECTION_PROMPT)),
+        "reasoning_detect_biases" => Some(("detection", BIAS_DETECTION_PROMPT)),
+        "reasoning_detect_fallacies" => Some(("detection", FALLACY_DETECTION_PROMPT)),
+        _ => None,
+    }
+}
+
+/// Pipe a compilable tool's mode runs on
+fn mode_pipe(mode: &str, pipes: &PipeConfig) -> String {
+    match mode {
+        "linear" => pipes.linear.clone(),
+        "divergent" => pipes.divergent.clone(),
+        "reflection" => pipes.reflection.clone(),
+        _ => pipes
+            .detection
+            .as_ref()
+            .and_then(|d| d.pipe.clone())
+            .unwrap_or_else(|| "detection-v1".to_string()),
+    }
+}
+
+/// Compile `preset` with `inputs` into a Langbase workflow run.
+pub fn compile_workflow(
+    preset: &WorkflowPreset,
+    inputs: &HashMap<String, serde_json::Value>,
+    pipes: &PipeConfig,
+) -> Result<WorkflowRunRequest, WorkflowCompileError> {
+    // store_as key -> step that produced it, for steps compiled so far
+    let mut stored: HashMap<&str, &str> = HashMap::new();
+    let mut steps = Vec::with_capacity(preset.steps.len());
+
+    for step in &preset.steps {
+        let (mode, prompt) =
+            tool_target(&step.tool).ok_or_else(|| WorkflowCompileError::UnsupportedTool {
+                step_id: step.step_id.clone(),
+                tool: step.tool.clone(),
+            })?;
+        if step.condition.is_some() {
+            return Err(WorkflowCompileError::ConditionalStep {
+                step_id: step.step_id.clone(),
+            });
+        }
+        if step.generation.is_some() {
+            return Err(WorkflowCompileError::GenerationOverride {
+                step_id: step.step_id.clone(),
+            });
+        }
+
+        let mut arguments = resolve_arguments(step, inputs, &stored);
+        let content = arguments
+            .remove("content")
+            .filter(|c| !c.trim().is_empty())
+            .ok_or_else(|| WorkflowCompileError::MissingContent {
+                step_id: step.step_id.clone(),
+            })?;
+        arguments.remove("session_id");
+
+        let mut messages = vec![Message::system(prompt)];
+        if !arguments.is_empty() {
+            let mut names: Vec<&String> = arguments.keys().collect();
+            names.sort();
+            let lines: Vec<String> = names
+                .into_iter()
+                .map(|name| format!("- {}: {}", name, arguments[name]))
+                .collect();
+            messages.push(Message::user(format!("Parameters:\n{}", lines.join("\n"))));
+        }
+        messages.push(Message::user(content));
+
+        steps.push(WorkflowStep {
+            id: step.step_id.clone(),
+            pipe: mode_pipe(mode, pipes),
+            messages,
+            depends_on: step.depends_on.clone(),
+            optional: step.optional,
+        });
+        if let Some(key) = &step.store_as {
+            stored.insert(key, &step.step_id);
+        }
+    }
+
+    Ok(WorkflowRunRequest {
+        name: preset.id.clone(),
+        steps,
+    })
+}
+
+/// Step arguments as prompt text, mirroring how the local executor builds
+/// tool arguments
+fn resolve_arguments(
+    step: &PresetStep,
+    inputs: &HashMap<String, serde_json::Value>,
+    stored: &HashMap<&str, &str>,
+) -> HashMap<String, String> {
+    let mut arguments = HashMap::new();
+
+    for (param, source) in &step.input_map {
+        let key = source.split('.').next().unwrap_or(source);
+        // Earlier step results are only known server-side, so reference them
+        let value = match stored.get(key) {
+            Some(step_id) => Some(WorkflowStep::output_ref(step_id)),
+            None => {
+                let value = match source.split_once('.') {
+                    Some((key, field)) => inputs.get(key).and_then(|v| v.get(field)),
+                    None => inputs.get(source),
+                };
+                value.map(value_text)
+            }
+        };
+        if let Some(value) = value {
+            arguments.insert(param.clone(), value);
+        }
+    }
+
+    for (key, value) in &step.static_inputs {
+        arguments.insert(key.clone(), value_text(value));
+    }
+
+    arguments
+}
+
+/// Strings as-is, anything else as JSON
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presets::{code_review_preset, debug_analysis_preset};
+    use serde_json::json;
+
+    #[test]
+    fn test_compile_code_review_preset() {
+        let inputs = HashMap::from([("code".to_string(), json!("fn main() {}"))]);
+        let request =
+            compile_workflow(&code_review_preset(), &inputs, &PipeConfig::default()).unwrap();
+
+        assert_eq!(request.name, "code-review");
+        let ids: Vec<&str> = request.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "divergent_analysis",
+                "bias_check",
+                "fallacy_check",
+                "reflect"
+            ]
+        );
+
+        let divergent = &request.steps[0];
+        assert_eq!(divergent.pipe, "divergent-reasoning-v1");
+        assert_eq!(divergent.messages[0].content, DIVERGENT_REASONING_PROMPT);
+        assert!(divergent.messages[1]
+            .content
+            .contains("- num_perspectives: 3"));
+        assert_eq!(divergent.messages[2].content, "fn main() {}");
+
+        let bias = &request.steps[1];
+        assert_eq!(bias.pipe, "detection-v1");
+        assert!(bias.optional);
+        assert_eq!(bias.depends_on, vec!["divergent_analysis".to_string()]);
+    }
+
+    #[test]
+    fn test_compile_references_stored_step_results() {
+        let mut preset = code_review_preset();
+        preset.steps[3] = PresetStep::new("reflect", "reasoning_reflection")
+            .with_input("content", "perspectives.synthesis");
+        let inputs = HashMap::from([("code".to_string(), json!("x = 1"))]);
+
+        let request = compile_workflow(&preset, &inputs, &PipeConfig::default()).unwrap();
+        let reflect = request.steps.last().unwrap();
+        assert_eq!(
+            reflect.messages.last().unwrap().content,
+            "{{steps.divergent_analysis.output}}"
+        );
+    }
+
+    #[test]
+    fn test_compile_rejects_unsupported_presets() {
+        let inputs = HashMap::from([("problem".to_string(), json!("crash"))]);
+        let err = compile_workflow(&debug_analysis_preset(), &inputs, &PipeConfig::default())
+            .unwrap_err();
+        assert!(matches!(err, WorkflowCompileError::UnsupportedTool { .. }));
+
+        // Content is required
+        let err = compile_workflow(
+            &code_review_preset(),
+            &HashMap::new(),
+            &PipeConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, WorkflowCompileError::MissingContent { .. }));
+    }
+}
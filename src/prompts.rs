@@ -25,6 +25,24 @@ Guidelines:
 
 Always respond with valid JSON only, no other text."#;
 
+/// System prompt for folding a long linear chain into a summary that stands
+/// in for its steps.
+pub const LINEAR_SUMMARY_PROMPT: &str = r#"You are a structured reasoning assistant. Summarize the given chain of reasoning steps so that the reasoning can continue from your summary alone. Steps marked [superseded] were corrected by a later step; keep the correction and drop what it replaced.
+
+Your response MUST be valid JSON in this exact format:
+{
+  "summary": "the established facts, conclusions and open questions of the chain",
+  "confidence": 0.8
+}
+
+Guidelines:
+- Keep every conclusion later steps may build on
+- Keep open questions and unresolved assumptions
+- Drop restatements and dead ends
+- confidence reflects how faithfully the summary preserves the chain, between 0.0 and 1.0
+
+Always respond with valid JSON only, no other text."#;
+
 /// System prompt for tree-based reasoning mode (future use).
 pub const TREE_REASONING_PROMPT: &str = r#"You are a structured reasoning assistant that explores multiple reasoning paths.
 
@@ -47,6 +65,55 @@ Guidelines:
 - Recommend the most promising branch
 - Maintain logical consistency within each branch"#;
 
+/// System prompt for merging two tree branches into one.
+pub const TREE_MERGE_PROMPT: &str = r#"You are a reasoning editor merging two branches of a reasoning tree, A and B, into one line of reasoning. Each branch lists its chain of thoughts from the root in order, [A1], [A2], ... and [B1], [B2], ... The branches may share their first thoughts.
+
+Your response MUST be valid JSON in this format:
+{
+  "merged_reasoning": "one coherent line of reasoning that keeps what is sound in both branches",
+  "agreements": ["a conclusion both branches reach"],
+  "resolved_conflicts": [
+    {"conflict": "where A and B disagree", "resolution": "how the merged reasoning settles it"}
+  ],
+  "confidence": 0.75
+}
+
+Guidelines:
+- merged_reasoning: reconcile the branches' conclusions; do not just list both
+- resolved_conflicts: one entry per real disagreement; leave it empty if the branches agree
+- Prefer the better-supported side of a conflict, and say why in the resolution
+- confidence: 0.0 to 1.0 (how sound the merged reasoning is)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for comparing tree branches against criteria.
+pub const TREE_COMPARE_PROMPT: &str = r#"You are a reasoning reviewer comparing branches of a reasoning tree to decide which one to pursue. Each branch is labeled [B1], [B2], ... and lists its chain of thoughts from the root, in order. The criteria to score against are listed after the branches.
+
+Your response MUST be valid JSON in this format:
+{
+  "assessments": [
+    {
+      "branch": "B1",
+      "summary": "what the branch's chain of thoughts concludes, in one or two sentences",
+      "scores": {"criterion name": 0.7},
+      "strengths": ["what the branch does well"],
+      "weaknesses": ["where the branch falls short"]
+    }
+  ],
+  "recommended": "B1",
+  "rationale": "why the recommended branch is the one to pursue over the others",
+  "confidence": 0.75
+}
+
+Guidelines:
+- Give one assessment per branch, citing only the given labels
+- scores: one entry per listed criterion, using the criterion names exactly as given, each 0.0 to 1.0
+- Judge each branch by its whole chain, not only its last thought
+- recommended: the label of the single branch to pursue next
+- confidence: 0.0 to 1.0 (how sure you are of the recommendation)
+
+Always respond with valid JSON only, no other text."#;
+
 /// System prompt for divergent/creative reasoning mode (future use).
 pub const DIVERGENT_REASONING_PROMPT: &str = r#"You are a creative reasoning assistant that generates novel perspectives and unconventional solutions.
 
@@ -69,6 +136,48 @@ Guidelines:
 - Rate novelty and practical viability
 - Synthesize insights across perspectives"#;
 
+/// Divergent prompt addition for SCAMPER ideation.
+pub const SCAMPER_TECHNIQUE_PROMPT: &str = r#"TECHNIQUE: SCAMPER. Generate each perspective by applying one SCAMPER operation to the subject, a different operation for each perspective where possible:
+- Substitute: replace a component, material, person or process
+- Combine: merge it with something else, or merge two of its parts
+- Adapt: borrow an idea that works in another context
+- Modify: magnify, shrink or change an attribute
+- Put to another use: use it for something else, or for someone else
+- Eliminate: remove a part and see what still works
+- Reverse: rearrange, invert or do the steps in another order
+Start each perspective's thought with the operation applied, e.g. "Combine: ..."."#;
+
+/// Divergent prompt addition for reverse brainstorming.
+pub const REVERSE_BRAINSTORM_TECHNIQUE_PROMPT: &str = r#"TECHNIQUE: Reverse brainstorming. First ask how the problem could be caused or made worse, or how the goal could be guaranteed to fail. For each perspective, state one such way to fail, then invert it into an idea that prevents or counters it. The thought should contain both the failure and its inversion."#;
+
+/// Divergent prompt addition for random stimulus ideation.
+pub const RANDOM_STIMULUS_TECHNIQUE_PROMPT: &str = r#"TECHNIQUE: Random stimulus. You are given unrelated stimulus words. For each perspective, take one word, list its attributes, functions or associations, and force a connection from one of them to the subject. Name the word at the start of the thought, e.g. "Lighthouse: ...". Do not discard a word because the connection seems far-fetched."#;
+
+/// Divergent prompt addition for TRIZ-style ideation.
+pub const TRIZ_TECHNIQUE_PROMPT: &str = r#"TECHNIQUE: TRIZ (simplified). Identify the core contradiction: a parameter that must improve while another gets worse, or one that must be both high and low. Resolve it rather than trade it off, each perspective applying a different inventive principle, for example segmentation, taking out, local quality, asymmetry, merging, universality, nesting, prior action, the other way round, dynamics, partial or excessive action, self-service, or separating the conflicting requirements in time or space. Start each perspective's thought with the principle applied."#;
+
+/// System prompt for scoring generated ideas against weighted criteria.
+pub const DIVERGENT_EVALUATE_PROMPT: &str = r#"You are an idea evaluator. Score each of the given ideas against each of the given criteria. Ideas are numbered 1, 2, ...; criteria are listed with their descriptions.
+
+Your response MUST be valid JSON in this exact format:
+{
+  "evaluations": [
+    {
+      "idea": 1,
+      "scores": {"criterion name": 0.8},
+      "rationale": "why the idea scores as it does"
+    }
+  ]
+}
+
+Guidelines:
+- Evaluate every idea, and score every idea on every criterion, using the criterion names exactly as given
+- Scores are between 0.0 (fails the criterion) and 1.0 (fully meets it); higher is always better, so score a cost criterion high when the cost is low
+- Judge each idea on its own merits, not by how it compares to the others
+- Keep each rationale to one or two sentences
+
+Always respond with valid JSON only, no other text."#;
+
 /// System prompt for reflection/meta-reasoning mode (future use).
 pub const REFLECTION_PROMPT: &str = r#"You are a meta-cognitive reasoning assistant that analyzes and improves reasoning quality.
 
@@ -213,6 +322,23 @@ Guidelines:
 - Add missing considerations
 - Preserve core insights while enhancing quality"#;
 
+/// System prompt for head-to-head comparison of two GoT nodes.
+pub const GOT_COMPARE_PROMPT: &str = r#"You are a Graph-of-Thoughts judge. Compare two thought nodes, A and B, head to head and decide which better advances the problem.
+
+Your response MUST be valid JSON in this format:
+{
+  "winner": "a",
+  "confidence": 0.7,
+  "rationale": "why the winner is the better thought",
+  "metadata": {}
+}
+
+Guidelines:
+- winner: "a", "b", or "tie" when neither is clearly better
+- Judge relevance, logical validity, and depth; do not favor a thought for being longer
+- The order in which the thoughts are shown says nothing about their quality
+- confidence: 0.0 to 1.0 (how clear the difference is)"#;
+
 // ============================================================================
 // Phase 5: Decision Framework Prompts
 // ============================================================================
@@ -596,6 +722,526 @@ Guidelines:
 - Suggest alternative directions after backtracking
 - Preserve good reasoning segments while discarding problematic ones"#;
 
+// ============================================================================
+// Debate Prompts
+// ============================================================================
+
+/// System prompt for one side's argument in a debate.
+pub const DEBATE_ARGUMENT_PROMPT: &str = r#"You are a debater arguing one side of a proposition. You are told which side you argue and what has been said so far.
+
+Your response MUST be valid JSON in this format:
+{
+  "argument": "your argument for this round",
+  "key_points": ["point the argument rests on"],
+  "confidence": 0.75
+}
+
+Guidelines:
+- Argue only the side you are given, even if you disagree with it
+- From the second argument on, rebut the opponent's latest argument directly
+- Do not repeat points you already made; add new evidence or reasoning
+- Prefer concrete evidence and mechanisms over rhetoric
+- confidence: 0.0 to 1.0, how strong you judge your own argument to be
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for judging a debate.
+pub const DEBATE_JUDGE_PROMPT: &str = r#"You are an impartial judge of a debate between a PRO side arguing for a proposition and a CON side arguing against it.
+
+Your response MUST be valid JSON in this format:
+{
+  "winner": "pro|con|draw",
+  "verdict": "which side argued better and why",
+  "pro_score": 0.7,
+  "con_score": 0.6,
+  "argument_scores": [
+    {"side": "pro", "round": 1, "score": 0.7, "reasoning": "why this argument scored so"}
+  ],
+  "confidence": 0.75
+}
+
+Guidelines:
+- Score every argument from 0.0 to 1.0 on evidence, logic and how well it answered the opponent
+- pro_score and con_score: 0.0 to 1.0, the overall strength of each side
+- Judge the arguments made, not your own view of the proposition
+- Declare a draw only when neither side is clearly stronger
+- confidence: 0.0 to 1.0, how certain you are of the verdict
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Root Cause Analysis Prompts
+// ============================================================================
+
+/// System prompt for one step of a 5 Whys root cause analysis.
+pub const RCA_WHY_PROMPT: &str = r#"You are a root cause analyst applying the 5 Whys technique. You are given a problem and the causes found so far; answer why the latest one happened.
+
+Your response MUST be valid JSON in this format:
+{
+  "cause": "the direct cause of the latest finding",
+  "category": "people|process|equipment|materials|environment|measurement",
+  "is_root_cause": false,
+  "confidence": 0.75
+}
+
+Guidelines:
+- Give exactly one cause, one level deeper than the latest finding
+- State causes as verifiable facts, not blame
+- Set is_root_cause to true only when fixing this cause would stop the problem recurring and asking why again leads outside the organization's control
+- confidence: 0.0 to 1.0, how sure you are this cause holds
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for a fishbone (Ishikawa) root cause analysis.
+pub const RCA_FISHBONE_PROMPT: &str = r#"You are a root cause analyst building a fishbone (Ishikawa) diagram for a problem.
+
+Your response MUST be valid JSON in this format:
+{
+  "categories": [
+    {
+      "category": "people|process|equipment|materials|environment|measurement",
+      "causes": [
+        {"cause": "a cause in this category", "kind": "proximate|contributing|root", "confidence": 0.7}
+      ]
+    }
+  ]
+}
+
+Guidelines:
+- Only include categories that have at least one plausible cause
+- proximate: directly triggered the problem; contributing: made it more likely; root: the underlying reason that, if fixed, prevents recurrence
+- Each category should trace at least one cause down to its root
+- confidence: 0.0 to 1.0 for each cause
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for corrective actions after a root cause analysis.
+pub const RCA_ACTIONS_PROMPT: &str = r#"You are a root cause analyst recommending corrective actions for the causes found for a problem.
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "the problem's root causes in one or two sentences",
+  "corrective_actions": [
+    {"action": "what to do", "addresses": "the cause it removes", "priority": "high|medium|low"}
+  ],
+  "confidence": 0.75
+}
+
+Guidelines:
+- Target root causes first; containment of proximate causes comes second
+- Make each action specific enough to assign and verify
+- confidence: 0.0 to 1.0, how sure you are the root causes are correct
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Planning Prompts
+// ============================================================================
+
+/// System prompt for decomposing a goal into dependent tasks.
+pub const PLAN_DECOMPOSE_PROMPT: &str = r#"You are a planning assistant that decomposes a goal into concrete tasks and the order they depend on each other.
+
+Your response MUST be valid JSON in this format:
+{
+  "tasks": [
+    {
+      "key": "t1",
+      "title": "short imperative task name",
+      "description": "what done looks like",
+      "prerequisites": ["keys of tasks that must finish first"],
+      "effort_hours": 4.0,
+      "risks": ["what could make this task fail or slip"]
+    }
+  ],
+  "summary": "how the plan reaches the goal",
+  "confidence": 0.75
+}
+
+Guidelines:
+- Give each task a unique key and list only real prerequisites, so independent tasks can run in parallel
+- Prerequisites must not form a cycle
+- effort_hours: your best estimate of focused work, not elapsed time
+- Name the risks specific to each task
+- confidence: 0.0 to 1.0, how likely the plan is to reach the goal
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for replanning after a task failed.
+pub const PLAN_REVISE_PROMPT: &str = r#"You are a planning assistant revising a plan after one of its tasks failed. The failed task and the tasks that depended on it have been dropped; plan replacement tasks that still reach the goal.
+
+Your response MUST be valid JSON in this format:
+{
+  "tasks": [
+    {
+      "key": "r1",
+      "title": "short imperative task name",
+      "description": "what done looks like",
+      "prerequisites": ["keys of new tasks or IDs of remaining tasks"],
+      "effort_hours": 4.0,
+      "risks": ["what could make this task fail or slip"]
+    }
+  ],
+  "summary": "how the revised plan works around the failure",
+  "confidence": 0.7
+}
+
+Guidelines:
+- Do not repeat the approach that failed; address the reason it failed
+- Reuse remaining tasks as prerequisites by their IDs instead of re-planning them
+- Prerequisites must not form a cycle
+- confidence: 0.0 to 1.0, how likely the revised plan is to reach the goal
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Estimation Prompts
+// ============================================================================
+
+/// System prompt for decomposing a quantitative question into Fermi factors.
+pub const FERMI_ESTIMATE_PROMPT: &str = r#"You are an estimation assistant applying Fermi estimation. Decompose the quantitative question into factors that are easier to estimate than the question itself, and estimate each with a range.
+
+Your response MUST be valid JSON in this format:
+{
+  "factors": [
+    {
+      "key": "f1",
+      "name": "what the factor measures",
+      "parent": null,
+      "operation": "multiply",
+      "low": 1000.0,
+      "estimate": 2000.0,
+      "high": 5000.0,
+      "unit": "unit of the factor",
+      "rationale": "why these numbers are plausible"
+    }
+  ],
+  "unit": "unit of the answer",
+  "answer": 1234.0,
+  "confidence": 0.6
+}
+
+Guidelines:
+- The answer is the product of the top-level factors (parent null); a factor with operation "divide" divides instead of multiplying
+- A factor that is itself hard to estimate can be broken down: give its sub-factors its key as their parent, listed after it, and they combine the same way into it
+- low and high bound a 90% confidence interval; 0 < low <= estimate <= high
+- Keep every number a plain positive number, no units or thousands separators
+- answer: your own arithmetic result; it is checked, so do not round it to look cleaner
+- confidence: 0.0 to 1.0, how confident you are in the decomposition
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// SWOT Prompts
+// ============================================================================
+
+/// System prompt for SWOT analysis.
+pub const SWOT_ANALYSIS_PROMPT: &str = r#"You are a strategic analyst performing a SWOT analysis. Strengths and weaknesses are internal to the subject; opportunities and threats come from its environment.
+
+Your response MUST be valid JSON in this format:
+{
+  "strengths": [
+    {
+      "factor": "short statement of the factor",
+      "evidence": ["facts or observations supporting it"],
+      "confidence": 0.8,
+      "stakeholders": ["names of affected stakeholders"]
+    }
+  ],
+  "weaknesses": [],
+  "opportunities": [],
+  "threats": [],
+  "summary": "overall strategic assessment",
+  "confidence": 0.7
+}
+
+Guidelines:
+- Give 2-5 items per quadrant, each backed by concrete evidence
+- confidence per item: 0.0 to 1.0, how well the evidence supports it
+- stakeholders: only use names from the STAKEHOLDERS list when one is given; leave empty otherwise
+- When an EARLIER ANALYSIS is given, note what changed rather than repeating it
+- confidence: 0.0 to 1.0 for the analysis as a whole
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Hypothesis Testing Prompts
+// ============================================================================
+
+/// System prompt for designing experiments that test a hypothesis.
+pub const HYPOTHESIS_DESIGN_PROMPT: &str = r#"You are a research methodologist. Turn the hypothesis into testable predictions and design experiments or observations that discriminate between the hypothesis being true and false. The expected outcomes are pre-registered: they are fixed now, before any result is seen, and later results are scored against them.
+
+Your response MUST be valid JSON in this format:
+{
+  "predictions": [
+    {
+      "key": "p1",
+      "statement": "what should be observed if the hypothesis is true",
+      "if_false": "what should be observed instead if it is false"
+    }
+  ],
+  "experiments": [
+    {
+      "key": "e1",
+      "description": "what to do or observe, and how",
+      "predictions": ["keys of the predictions it tests"],
+      "outcomes": [
+        {"outcome": "short name of a possible result", "likelihood_if_true": 0.8, "likelihood_if_false": 0.2}
+      ]
+    }
+  ]
+}
+
+Guidelines:
+- Predictions must be falsifiable and specific
+- Each experiment lists every outcome it can have (at least two), mutually exclusive
+- likelihood_if_true / likelihood_if_false: probability of the outcome if the hypothesis is true / false; each column should sum to 1 over the outcomes
+- Prefer experiments whose outcomes are much more likely under one answer than the other; an experiment with the same likelihoods either way tells nothing
+- Prefer cheap experiments when they discriminate equally well
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Six Thinking Hats Prompts
+// ============================================================================
+
+/// System prompt for one Six Thinking Hats pass.
+pub const SIX_HATS_PASS_PROMPT: &str = r#"You are a thinker applying Edward de Bono's Six Thinking Hats. You wear exactly one hat at a time and think about the topic only from that hat's stance, even where an earlier hat disagrees.
+
+Your response MUST be valid JSON in this format:
+{
+  "thought": "your thinking about the topic under this hat",
+  "key_points": ["the main points of this pass"],
+  "confidence": 0.7
+}
+
+Guidelines:
+- Stay strictly within the hat's stance; leave other stances to their own hats
+- Build on earlier hats where it helps, but do not repeat them
+- Be concrete and specific to the topic
+- confidence: how well this pass covers the stance (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for the synthesis of a Six Thinking Hats session.
+pub const SIX_HATS_SYNTHESIS_PROMPT: &str = r#"You are a facilitator closing a Six Thinking Hats session. Integrate the passes made under every hat into one balanced conclusion.
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "the integrated conclusion about the topic",
+  "recommendations": ["concrete next steps"],
+  "confidence": 0.7
+}
+
+Guidelines:
+- Weigh the facts, feelings, risks, benefits and ideas against each other
+- Say where the hats conflict and how the conclusion resolves it
+- Recommendations follow from the passes, not from new material
+- confidence: confidence in the conclusion (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Dialectic Prompts
+// ============================================================================
+
+/// System prompt for the antithesis to a position.
+pub const DIALECTIC_ANTITHESIS_PROMPT: &str = r#"You are a dialectical thinker. Given a position, construct the strongest antithesis to it: the opposing position a well-informed, charitable critic would hold, not a strawman.
+
+Your response MUST be valid JSON in this format:
+{
+  "argument": "the antithesis, stated as a position in its own right",
+  "key_points": ["where and why the position fails"],
+  "confidence": 0.7
+}
+
+Guidelines:
+- Attack the position's strongest form, not its weakest
+- Target its central claims rather than details
+- Earlier rounds may already have absorbed some objections; find what is still contestable
+- confidence: how strong the antithesis is against the position (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for the synthesis of a position and its antithesis.
+pub const DIALECTIC_SYNTHESIS_PROMPT: &str = r#"You are a dialectical thinker. Given a position and its antithesis, form a synthesis: a new position that preserves the valid core of both and resolves their contradiction at a higher level, rather than splitting the difference.
+
+Your response MUST be valid JSON in this format:
+{
+  "synthesis": "the new position",
+  "preserved_from_thesis": ["what remains valid from the position"],
+  "preserved_from_antithesis": ["what remains valid from the antithesis"],
+  "confidence": 0.7
+}
+
+Guidelines:
+- State the synthesis as a position that can itself be challenged
+- Drop what either side got wrong, and say so only through what you keep
+- Do not average: explain how both valid cores can hold together
+- confidence: confidence in the synthesis (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Prioritization Prompts
+// ============================================================================
+
+/// System prompt for estimating the inputs of a prioritization.
+pub const PRIORITIZATION_ESTIMATE_PROMPT: &str = r#"You are a product prioritization analyst. Estimate the inputs of the requested prioritization method for every listed item. Do not rank the items yourself: the scores are computed from your estimates.
+
+Your response MUST be valid JSON in this format:
+{
+  "estimates": [
+    {
+      "item": "the item, exactly as listed",
+      "reach": 1000,
+      "impact": 1.0,
+      "confidence": 0.8,
+      "effort": 2.0,
+      "category": "must|should|could|wont",
+      "value": 7.0,
+      "criteria_scores": {"criterion name": 7.0},
+      "rationale": "why these estimates"
+    }
+  ]
+}
+
+Guidelines:
+- Give exactly one estimate per listed item, and fill only the fields of the requested method
+- rice: reach is people or events affected per period; impact is 0.25 (minimal), 0.5 (low), 1 (medium), 2 (high) or 3 (massive); confidence is 0.0-1.0; effort is person-months and greater than zero
+- moscow: category is must, should, could or wont; value (0-10) orders items within a category
+- weighted: criteria_scores scores every listed criterion from 0 (worst) to 10 (best)
+- Keep estimates comparable across items: use the same units and scale for all of them
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Negotiation Prompts
+// ============================================================================
+
+/// System prompt for negotiation analysis.
+pub const NEGOTIATION_ANALYSIS_PROMPT: &str = r#"You are a negotiation analyst. Analyze the scenario: the parties, their interests behind their positions, their best alternatives to a negotiated agreement (BATNA), candidate package deals, and the order in which each party can concede.
+
+Your response MUST be valid JSON in this format:
+{
+  "issue": "the main distributive issue, e.g. price",
+  "parties": [
+    {
+      "name": "party name",
+      "interests": ["what they actually need"],
+      "positions": ["what they demand"],
+      "batna": "what they do if no deal is reached",
+      "batna_value": 0.4,
+      "reservation_value": 100000,
+      "target_value": 130000,
+      "direction": "maximize|minimize"
+    }
+  ],
+  "packages": [
+    {
+      "name": "short package name",
+      "terms": {"issue": "agreed term"},
+      "values": {"party name": 0.7},
+      "rationale": "why the package could work"
+    }
+  ],
+  "concessions": [
+    {"party": "party name", "step": 1, "give": "what they concede", "in_exchange_for": "what they ask in return", "cost": 0.1}
+  ],
+  "strategy": "overall negotiation strategy",
+  "confidence": 0.7
+}
+
+Guidelines:
+- batna_value and every package value are on the same 0-1 scale of how good the outcome is for that party
+- reservation_value is the walk-away point on the main issue and target_value the aspiration; direction says whether the party wants the issue high (maximize) or low (minimize)
+- Value every package for every party; combine issues so packages trade what one party values cheaply for what the other values highly
+- Order each party's concessions from cheapest to most costly; cost is what the concession gives up on the 0-1 scale
+- confidence: confidence in the analysis (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Risk Assessment Prompts
+// ============================================================================
+
+/// System prompt for identifying the risks of a subject.
+pub const RISK_IDENTIFICATION_PROMPT: &str = r#"You are a risk analyst. Identify the most important risks of the given subject and score each for likelihood, impact and detectability.
+
+Your response MUST be valid JSON in this format:
+{
+  "risks": [
+    {
+      "title": "short name of the risk",
+      "description": "what could happen, and why",
+      "category": "technical|schedule|financial|operational|legal|people|external",
+      "likelihood": 3,
+      "impact": 4,
+      "detectability": 2,
+      "owner": "role best placed to own the risk",
+      "mitigation": "how to reduce likelihood or impact"
+    }
+  ]
+}
+
+Guidelines:
+- likelihood: 1 rare, 2 unlikely, 3 possible, 4 likely, 5 almost certain
+- impact: 1 negligible, 2 minor, 3 moderate, 4 major, 5 severe
+- detectability: 1 certain to be noticed in time, 5 likely to strike unnoticed
+- Do not repeat risks already in the register; name distinct causes rather than one cause several ways
+- Prefer mitigations that are concrete and assignable
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for re-scoring a risk against an evidence assessment.
+pub const RISK_RESCORE_PROMPT: &str = r#"You are a risk analyst. Re-score an existing risk in light of an evidence assessment. Change a score only when the evidence bears on it.
+
+Your response MUST be valid JSON in this format:
+{
+  "likelihood": 3,
+  "impact": 4,
+  "detectability": 2,
+  "rationale": "how the evidence moved the scores"
+}
+
+Guidelines:
+- Use the same 1-5 scales as the register: likelihood 1 rare to 5 almost certain, impact 1 negligible to 5 severe, detectability 1 certain to be noticed to 5 likely unnoticed
+- Weigh the evidence by the support level and confidence of the assessment
+- Keep a score unchanged when the evidence says nothing about it
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Systems Thinking Prompts
+// ============================================================================
+
+/// System prompt for modeling a situation as a system.
+pub const SYSTEMS_MODEL_PROMPT: &str = r#"You are a systems thinker. Model the situation as a system: the stocks that accumulate, the flows that fill or drain them, the other variables that drive them, and the causal links between all of these. Do not name the feedback loops yourself: they are found from your links.
+
+Your response MUST be valid JSON in this format:
+{
+  "elements": [
+    {"key": "short_id", "name": "element name", "kind": "stock|flow|variable", "description": "what the element measures"}
+  ],
+  "links": [
+    {"from": "cause key", "to": "effect key", "polarity": "positive|negative", "delay": false, "rationale": "why the cause moves the effect"}
+  ],
+  "leverage_points": [
+    {"element": "element key", "intervention": "what to change there and how"}
+  ],
+  "summary": "how the system behaves over time",
+  "confidence": 0.7
+}
+
+Guidelines:
+- Name elements as quantities that can rise or fall ("Customer trust", not "Trust issues")
+- polarity: positive when more of the cause means more of the effect, negative when it means less
+- Set delay when the effect lags noticeably behind the cause
+- Include every link that closes a loop; most behavior comes from feedback
+- Propose interventions at the elements where a small change shifts the whole system
+- confidence: confidence in the model (0.0-1.0)
+
+Always respond with valid JSON only, no other text."#;
+
 /// System prompt for cognitive bias detection.
 pub const BIAS_DETECTION_PROMPT: &str = r#"You are a cognitive bias detection assistant. Analyze the given content for cognitive biases that may affect reasoning quality.
 
@@ -689,6 +1335,350 @@ Guidelines:
 
 Always respond with valid JSON only, no other text."#;
 
+/// System prompt for extracting numeric claims to recompute.
+pub const CALC_EXTRACTION_PROMPT: &str = r#"You are a calculation auditor. Find every numeric claim in the given content: each statement that a quantity follows from other quantities by arithmetic. Write each as a formula and the result the text states. Do not compute the results yourself: the formulas are evaluated separately.
+
+Your response MUST be valid JSON in this format:
+{
+  "claims": [
+    {
+      "thought_id": "id from the [THOUGHT ...] header the claim is under, if any",
+      "text": "the claim as written",
+      "expression": "120 * (1 - 15%)",
+      "stated": 102
+    }
+  ]
+}
+
+Guidelines:
+- expression: plain arithmetic only, using numbers from the text, + - * / ^, parentheses, % and the functions sqrt, ln, log, exp, abs, round, floor, ceil, min, max
+- Convert units and rates into the formula (e.g. "3 hours at $40/hour" is 3 * 40)
+- stated: the number the text gives as the result, without units; omit it when the text gives none
+- Skip bare figures that are not derived from others (dates, IDs, quoted facts)
+- Include claims even when they look right; the point is to check them
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for explaining a unified diff.
+pub const CODE_EXPLAIN_DIFF_PROMPT: &str = r#"You are a code reviewer. Explain what the given unified diff changes and how the behavior of the code differs afterwards. Read the removed and added lines closely; do not guess at code the diff does not show.
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "what the diff does, in a few sentences",
+  "confidence": 0.8,
+  "changes": [
+    {"file": "path as it appears in the diff", "description": "one change"}
+  ],
+  "behavior_changes": ["an observable difference in behavior"],
+  "invariants": [
+    {"statement": "a property the code relies on or preserves", "location": "function or line"}
+  ],
+  "risks": [
+    {"description": "a way the code could fail or regress", "severity": 3, "location": "function or line"}
+  ],
+  "suggested_tests": [
+    {"name": "test_name", "description": "what the test does and asserts", "location": "code under test"}
+  ]
+}
+
+Guidelines:
+- changes: one entry per logical change, naming the file it is in
+- behavior_changes: what callers or users would notice; leave empty for pure refactors
+- invariants: only properties the code actually depends on; say where they hold
+- risks: severity 1 (cosmetic) to 5 (data loss, security or crash)
+- suggested_tests: concrete tests, each pinning one behavior
+- confidence: 0.0 to 1.0 (how sure you are of your reading of the code)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for tracing data flow through code.
+pub const CODE_TRACE_FLOW_PROMPT: &str = r#"You are a code analyst. Trace how the given target value flows through the code: where it comes from, every place it is assigned, transformed, checked or passed on, and where it ends up. The code is given with line numbers.
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "how the value flows, in a few sentences",
+  "confidence": 0.8,
+  "steps": [
+    {"line": 12, "operation": "source|assign|transform|check|call|return|sink", "description": "what happens to the value"}
+  ],
+  "sources": ["where the value comes from"],
+  "sinks": ["where the value ends up"],
+  "invariants": [
+    {"statement": "a property the code relies on or preserves", "location": "function or line"}
+  ],
+  "risks": [
+    {"description": "a way the code could fail or regress", "severity": 3, "location": "function or line"}
+  ],
+  "suggested_tests": [
+    {"name": "test_name", "description": "what the test does and asserts", "location": "code under test"}
+  ]
+}
+
+Guidelines:
+- steps: in the order the value flows; line is the number shown before the code
+- Follow aliases and copies of the value, not just its original name
+- Note checks the value passes (or skips) before reaching a sink
+- invariants: only properties the code actually depends on; say where they hold
+- risks: severity 1 (cosmetic) to 5 (data loss, security or crash)
+- suggested_tests: concrete tests, each pinning one behavior
+- confidence: 0.0 to 1.0 (how sure you are of your reading of the code)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for enumerating edge cases in code.
+pub const CODE_EDGE_CASES_PROMPT: &str = r#"You are a tester. Enumerate the edge cases of the given code: inputs and states it may mishandle. The code is given with line numbers.
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "how robust the code is, in a few sentences",
+  "confidence": 0.8,
+  "edge_cases": [
+    {"input": "the input or state", "category": "empty|boundary|overflow|null|encoding|concurrency|error|other", "expected": "what the code should do", "handled": false, "severity": 3, "line": 7}
+  ],
+  "invariants": [
+    {"statement": "a property the code relies on or preserves", "location": "function or line"}
+  ],
+  "risks": [
+    {"description": "a way the code could fail or regress", "severity": 3, "location": "function or line"}
+  ],
+  "suggested_tests": [
+    {"name": "test_name", "description": "what the test does and asserts", "location": "code under test"}
+  ]
+}
+
+Guidelines:
+- handled: true only when the code visibly deals with the case
+- severity: 1 (cosmetic) to 5 (data loss, security or crash) if mishandled
+- line: the number shown before the code where the case matters
+- invariants: only properties the code actually depends on; say where they hold
+- risks: severity 1 (cosmetic) to 5 (data loss, security or crash)
+- suggested_tests: concrete tests, each pinning one behavior
+- confidence: 0.0 to 1.0 (how sure you are of your reading of the code)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for the divergence report of two timeline branches.
+pub const TIMELINE_COMPARE_PROMPT: &str = r#"You are analyzing and comparing two reasoning paths, branch A and branch B. Each path lists its events in order, [A1], [A2], ... and [B1], [B2], ... Events both paths share are marked (shared), and the point where the paths split is given. Observed facts, if any, are labeled [F1], [F2], ...
+
+Your response MUST be valid JSON in this format:
+{
+  "summary": "how the two paths relate",
+  "divergence_reason": "why the paths split where they do: the assumption or choice that differs",
+  "differences": ["a key difference between the paths"],
+  "shared_insights": ["an insight both paths reach"],
+  "consequences_a": ["a downstream consequence of branch A's choice"],
+  "consequences_b": ["a downstream consequence of branch B's choice"],
+  "fact_assessments": [
+    {"fact": "F1", "supports": "a", "explanation": "why this fact fits branch A better"}
+  ],
+  "recommendation": "which path to pursue and why",
+  "confidence": 0.75
+}
+
+Guidelines:
+- divergence_reason: explain the split itself, not the paths in general
+- consequences: what follows from each branch's choice after the split, including risks
+- fact_assessments: one entry per observed fact, citing only the given labels; supports is "a", "b", "both" or "neither"
+- Leave fact_assessments empty when no facts are given
+- confidence: 0.0 to 1.0 (how sure you are of the recommendation)
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for consolidating prior sessions into a briefing.
+pub const SYNTHESIS_BRIEFING_PROMPT: &str = r#"You are a briefing writer. Consolidate the given sources, artifacts of earlier reasoning sessions, into one briefing for a decision maker. Each source is labeled [S1], [S2], ... with its kind, session and, where it has one, its confidence.
+
+Your response MUST be valid JSON in this format:
+{
+  "title": "short title of the briefing",
+  "summary": "executive summary in a few sentences",
+  "sections": [
+    {"heading": "section heading", "content": "what the sources establish on this point", "sources": ["S1", "S3"]}
+  ],
+  "key_findings": [
+    {"statement": "a finding the sources support", "sources": ["S2"], "confidence": 0.8}
+  ],
+  "conflicts": [
+    {"description": "where sources disagree and how", "sources": ["S1", "S4"]}
+  ],
+  "open_questions": ["a question the sources leave open"],
+  "recommendations": ["a recommended next step"],
+  "confidence": 0.75
+}
+
+Guidelines:
+- Cite every section, finding and conflict with the labels of the sources it rests on; cite only labels that were given
+- Combine sources across sessions: a decision, the evidence behind it and the risks against it belong in one argument
+- Surface conflicts between sources instead of smoothing them over
+- key_findings confidence: 0.0 to 1.0, how well the cited sources support the finding
+- Do not add facts the sources do not contain
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for proposing single-variable counterfactual interventions.
+pub const COUNTERFACTUAL_PROPOSE_PROMPT: &str = r#"You are a causal reasoning assistant searching for the smallest change to the antecedents of a reasoning chain that would flip its outcome.
+
+Propose single-variable interventions: each changes exactly one antecedent (a premise, decision, condition or event the outcome depends on) and nothing else. Interventions already tested are listed with their results; do not repeat them, and use their results to aim better.
+
+Your response MUST be valid JSON in this format:
+{
+  "outcome": "the actual outcome of the chain in one sentence",
+  "interventions": [
+    {"antecedent": "the one antecedent changed", "change": "what it is changed to", "rationale": "why this could flip the outcome"}
+  ]
+}
+
+Guidelines:
+- One variable per intervention; a change to two things is two interventions
+- Prefer antecedents the outcome depends on directly
+- Keep each change as small and plausible as possible
+- Propose at most the number of interventions asked for
+
+Always respond with valid JSON only, no other text."#;
+
+/// System prompt for testing whether a set of interventions flips an outcome.
+pub const COUNTERFACTUAL_TEST_PROMPT: &str = r#"You are a causal reasoning assistant testing a counterfactual. Apply all of the given changes to the reasoning chain together, hold everything else fixed, and trace the consequences to the outcome.
+
+Your response MUST be valid JSON in this format:
+{
+  "flips": true,
+  "flip_probability": 0.7,
+  "resulting_outcome": "the outcome with the changes applied",
+  "reasoning": "the causal path from the changes to the resulting outcome"
+}
+
+Guidelines:
+- flips: true only if the outcome becomes materially different (or becomes the target outcome, when one is given)
+- flip_probability: 0.0 to 1.0, how likely the changes are to flip the outcome
+- Do not assume changes beyond the ones given
+
+Always respond with valid JSON only, no other text."#;
+
+// ============================================================================
+// Pipe Definitions
+// ============================================================================
+
+/// Langbase pipe settings the server creates and keeps in sync.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeDefinition {
+    /// Pipe name.
+    pub name: &'static str,
+    /// Pipe description.
+    pub description: &'static str,
+    /// Model (e.g., "openai:gpt-4o-mini").
+    pub model: &'static str,
+    /// Model temperature.
+    pub temperature: f64,
+    /// Maximum tokens to generate.
+    pub max_tokens: u32,
+    /// System prompt stored on the pipe, or `None` for pipes whose prompts
+    /// are passed with each request.
+    pub system_prompt: Option<&'static str>,
+}
+
+/// Linear reasoning pipe.
+pub const LINEAR_PIPE: PipeDefinition = PipeDefinition {
+    name: "linear-reasoning-v1",
+    description: "Linear reasoning mode for MCP server",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.7,
+    max_tokens: 2000,
+    system_prompt: Some(LINEAR_REASONING_PROMPT),
+};
+
+/// Tree reasoning pipe.
+pub const TREE_PIPE: PipeDefinition = PipeDefinition {
+    name: "tree-reasoning-v1",
+    description: "Tree-based reasoning mode for exploring multiple paths",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.8, // Slightly higher for exploration
+    max_tokens: 3000, // More tokens for multiple branches
+    system_prompt: Some(TREE_REASONING_PROMPT),
+};
+
+/// Divergent reasoning pipe.
+pub const DIVERGENT_PIPE: PipeDefinition = PipeDefinition {
+    name: "divergent-reasoning-v1",
+    description: "Divergent reasoning mode for creative perspectives",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.9, // Higher for maximum creativity
+    max_tokens: 3000, // More tokens for multiple perspectives
+    system_prompt: Some(DIVERGENT_REASONING_PROMPT),
+};
+
+/// Reflection pipe.
+pub const REFLECTION_PIPE: PipeDefinition = PipeDefinition {
+    name: "reflection-v1",
+    description: "Reflection mode for meta-cognitive analysis",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.6, // Lower for precise analysis
+    max_tokens: 2500,
+    system_prompt: Some(REFLECTION_PROMPT),
+};
+
+/// Auto mode router pipe.
+pub const AUTO_ROUTER_PIPE: PipeDefinition = PipeDefinition {
+    name: "mode-router-v1",
+    description: "Auto mode router for intelligent mode selection",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.5,
+    max_tokens: 1000,
+    system_prompt: None,
+};
+
+/// Consolidated GoT pipe (operation prompt passed dynamically).
+pub const GOT_PIPE: PipeDefinition = PipeDefinition {
+    name: "got-reasoning-v1",
+    description: "Consolidated GoT mode for graph-based reasoning operations",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.7,
+    max_tokens: 2500,
+    system_prompt: None,
+};
+
+/// Consolidated detection pipe (bias/fallacy prompt passed dynamically).
+pub const DETECTION_PIPE: PipeDefinition = PipeDefinition {
+    name: "detection-v1",
+    description: "Consolidated detection mode for bias and fallacy analysis",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.5, // Lower for precise analysis
+    max_tokens: 3000,
+    system_prompt: None,
+};
+
+/// Consolidated decision framework pipe (operation prompt passed dynamically).
+pub const DECISION_FRAMEWORK_PIPE: PipeDefinition = PipeDefinition {
+    name: "decision-framework-v1",
+    description:
+        "Consolidated decision framework for decision, perspective, evidence, and Bayesian analysis",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.6,
+    max_tokens: 4000,
+    system_prompt: None,
+};
+
+/// Code reasoning pipe (operation prompt passed dynamically).
+pub const CODE_PIPE: PipeDefinition = PipeDefinition {
+    name: "code-reasoning-v1",
+    description: "Code reasoning for diff explanation, data flow tracing and edge cases",
+    model: "openai:gpt-4o-mini",
+    temperature: 0.3, // Low for faithful reading of code
+    max_tokens: 4000,
+    system_prompt: None,
+};
+
+/// Every pipe the server creates, in creation order.
+pub const PIPE_DEFINITIONS: &[PipeDefinition] = &[
+    LINEAR_PIPE,
+    TREE_PIPE,
+    DIVERGENT_PIPE,
+    REFLECTION_PIPE,
+    GOT_PIPE,
+    DETECTION_PIPE,
+    DECISION_FRAMEWORK_PIPE,
+    CODE_PIPE,
+    AUTO_ROUTER_PIPE,
+];
+
 /// Get the appropriate system prompt for a given mode.
 ///
 /// # Arguments
@@ -700,6 +1690,8 @@ pub fn get_prompt_for_mode(mode: &str) -> &'static str {
     match mode.to_lowercase().as_str() {
         "linear" => LINEAR_REASONING_PROMPT,
         "tree" => TREE_REASONING_PROMPT,
+        "tree_compare" | "compare_branches" => TREE_COMPARE_PROMPT,
+        "tree_merge" | "merge_branches" => TREE_MERGE_PROMPT,
         "divergent" => DIVERGENT_REASONING_PROMPT,
         "reflection" => REFLECTION_PROMPT,
         "auto" | "router" => AUTO_ROUTER_PROMPT,
@@ -708,11 +1700,13 @@ pub fn get_prompt_for_mode(mode: &str) -> &'static str {
         "got_score" | "got-score" => GOT_SCORE_PROMPT,
         "got_aggregate" | "got-aggregate" => GOT_AGGREGATE_PROMPT,
         "got_refine" | "got-refine" => GOT_REFINE_PROMPT,
+        "got_compare" | "got-compare" => GOT_COMPARE_PROMPT,
         // Phase 4: Bias & Fallacy Detection
         "detect_biases" | "detect-biases" | "bias" | "biases" => BIAS_DETECTION_PROMPT,
         "detect_fallacies" | "detect-fallacies" | "fallacy" | "fallacies" => {
             FALLACY_DETECTION_PROMPT
         }
+        "calculations" | "verify_calculations" => CALC_EXTRACTION_PROMPT,
         // Phase 5: Decision Framework & Evidence Assessment
         "decision" | "make_decision" | "decision-maker" => DECISION_MAKER_PROMPT,
         "perspective" | "analyze_perspectives" | "perspective-analyzer" => {
@@ -722,9 +1716,40 @@ pub fn get_prompt_for_mode(mode: &str) -> &'static str {
         "probabilistic" | "bayesian" | "bayesian-updater" => BAYESIAN_UPDATER_PROMPT,
         // Phase 6: Time Machine (Timeline, MCTS, Counterfactual)
         "timeline" | "timeline_reasoning" | "temporal" => TIMELINE_REASONING_PROMPT,
+        "timeline_compare" | "divergence" => TIMELINE_COMPARE_PROMPT,
         "mcts" | "mcts_exploration" | "monte_carlo" => MCTS_EXPLORATION_PROMPT,
         "counterfactual" | "what_if" | "causal" => COUNTERFACTUAL_ANALYSIS_PROMPT,
+        "minimal_change" => COUNTERFACTUAL_PROPOSE_PROMPT,
+        "counterfactual_test" => COUNTERFACTUAL_TEST_PROMPT,
         "autobacktrack" | "auto_backtrack" | "backtrack_decision" => AUTO_BACKTRACK_PROMPT,
+        // Debate
+        "debate" | "debate_argument" => DEBATE_ARGUMENT_PROMPT,
+        "debate_judge" => DEBATE_JUDGE_PROMPT,
+        // Root cause analysis
+        "root_cause" | "rca" | "five_whys" => RCA_WHY_PROMPT,
+        "fishbone" | "ishikawa" => RCA_FISHBONE_PROMPT,
+        "corrective_actions" => RCA_ACTIONS_PROMPT,
+        // Planning
+        "planning" | "plan" => PLAN_DECOMPOSE_PROMPT,
+        "plan_revise" | "replan" => PLAN_REVISE_PROMPT,
+        // Estimation
+        "estimate" | "fermi" => FERMI_ESTIMATE_PROMPT,
+        // SWOT
+        "swot" => SWOT_ANALYSIS_PROMPT,
+        // Hypothesis testing
+        "hypothesis" | "experiment_design" => HYPOTHESIS_DESIGN_PROMPT,
+        "six_hats" => SIX_HATS_PASS_PROMPT,
+        "dialectic" => DIALECTIC_SYNTHESIS_PROMPT,
+        "prioritization" => PRIORITIZATION_ESTIMATE_PROMPT,
+        "negotiation" => NEGOTIATION_ANALYSIS_PROMPT,
+        "risk" => RISK_IDENTIFICATION_PROMPT,
+        "systems" => SYSTEMS_MODEL_PROMPT,
+        // Code reasoning
+        "explain_diff" => CODE_EXPLAIN_DIFF_PROMPT,
+        "trace_flow" | "data_flow" => CODE_TRACE_FLOW_PROMPT,
+        "edge_cases" => CODE_EDGE_CASES_PROMPT,
+        // Synthesis
+        "synthesis" | "synthesize" | "briefing" => SYNTHESIS_BRIEFING_PROMPT,
         _ => LINEAR_REASONING_PROMPT,
     }
 }
@@ -979,6 +2004,22 @@ mod tests {
         assert!(TREE_REASONING_PROMPT.contains("rationale"));
     }
 
+    #[test]
+    fn test_tree_merge_prompt() {
+        assert_eq!(get_prompt_for_mode("tree_merge"), TREE_MERGE_PROMPT);
+        for field in ["merged_reasoning", "agreements", "resolved_conflicts"] {
+            assert!(TREE_MERGE_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_tree_compare_prompt() {
+        assert_eq!(get_prompt_for_mode("tree_compare"), TREE_COMPARE_PROMPT);
+        for field in ["assessments", "scores", "recommended", "confidence"] {
+            assert!(TREE_COMPARE_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
     // Test 7: Verify divergent prompt contains required keywords
     #[test]
     fn test_divergent_prompt_keywords() {
@@ -988,6 +2029,14 @@ mod tests {
         assert!(DIVERGENT_REASONING_PROMPT.contains("synthesis"));
     }
 
+    #[test]
+    fn test_ideation_technique_prompts() {
+        assert!(SCAMPER_TECHNIQUE_PROMPT.contains("Put to another use"));
+        assert!(REVERSE_BRAINSTORM_TECHNIQUE_PROMPT.contains("invert"));
+        assert!(RANDOM_STIMULUS_TECHNIQUE_PROMPT.contains("stimulus words"));
+        assert!(TRIZ_TECHNIQUE_PROMPT.contains("contradiction"));
+    }
+
     // Test 8: Verify reflection prompt contains required keywords
     #[test]
     fn test_reflection_prompt_keywords() {
@@ -1553,6 +2602,192 @@ mod tests {
         assert!(AUTO_BACKTRACK_PROMPT.len() > 500);
     }
 
+    #[test]
+    fn test_debate_prompts() {
+        assert_eq!(get_prompt_for_mode("debate"), DEBATE_ARGUMENT_PROMPT);
+        assert_eq!(get_prompt_for_mode("debate_argument"), DEBATE_ARGUMENT_PROMPT);
+        assert_eq!(get_prompt_for_mode("DEBATE_JUDGE"), DEBATE_JUDGE_PROMPT);
+        assert!(DEBATE_ARGUMENT_PROMPT.contains("\"argument\""));
+        assert!(DEBATE_JUDGE_PROMPT.contains("\"winner\""));
+        assert!(DEBATE_JUDGE_PROMPT.contains("\"argument_scores\""));
+    }
+
+    #[test]
+    fn test_root_cause_prompts() {
+        assert_eq!(get_prompt_for_mode("rca"), RCA_WHY_PROMPT);
+        assert_eq!(get_prompt_for_mode("five_whys"), RCA_WHY_PROMPT);
+        assert_eq!(get_prompt_for_mode("Fishbone"), RCA_FISHBONE_PROMPT);
+        assert_eq!(get_prompt_for_mode("corrective_actions"), RCA_ACTIONS_PROMPT);
+        assert!(RCA_WHY_PROMPT.contains("\"is_root_cause\""));
+        assert!(RCA_FISHBONE_PROMPT.contains("\"categories\""));
+        assert!(RCA_ACTIONS_PROMPT.contains("\"corrective_actions\""));
+    }
+
+    #[test]
+    fn test_planning_prompts() {
+        assert_eq!(get_prompt_for_mode("planning"), PLAN_DECOMPOSE_PROMPT);
+        assert_eq!(get_prompt_for_mode("plan"), PLAN_DECOMPOSE_PROMPT);
+        assert_eq!(get_prompt_for_mode("replan"), PLAN_REVISE_PROMPT);
+        assert!(PLAN_DECOMPOSE_PROMPT.contains("\"prerequisites\""));
+        assert!(PLAN_REVISE_PROMPT.contains("\"prerequisites\""));
+    }
+
+    #[test]
+    fn test_estimate_prompts() {
+        assert_eq!(get_prompt_for_mode("estimate"), FERMI_ESTIMATE_PROMPT);
+        assert_eq!(get_prompt_for_mode("fermi"), FERMI_ESTIMATE_PROMPT);
+        assert!(FERMI_ESTIMATE_PROMPT.contains("\"operation\""));
+        assert!(FERMI_ESTIMATE_PROMPT.contains("\"parent\""));
+    }
+
+    #[test]
+    fn test_hypothesis_prompt() {
+        assert_eq!(get_prompt_for_mode("hypothesis"), HYPOTHESIS_DESIGN_PROMPT);
+        assert_eq!(
+            get_prompt_for_mode("experiment_design"),
+            HYPOTHESIS_DESIGN_PROMPT
+        );
+        assert!(HYPOTHESIS_DESIGN_PROMPT.contains("\"likelihood_if_false\""));
+        assert!(HYPOTHESIS_DESIGN_PROMPT.contains("pre-registered"));
+    }
+
+    #[test]
+    fn test_six_hats_prompts() {
+        assert_eq!(get_prompt_for_mode("six_hats"), SIX_HATS_PASS_PROMPT);
+        assert!(SIX_HATS_PASS_PROMPT.contains("\"key_points\""));
+        assert!(SIX_HATS_SYNTHESIS_PROMPT.contains("\"recommendations\""));
+    }
+
+    #[test]
+    fn test_dialectic_prompts() {
+        assert_eq!(
+            get_prompt_for_mode("dialectic"),
+            DIALECTIC_SYNTHESIS_PROMPT
+        );
+        assert!(DIALECTIC_ANTITHESIS_PROMPT.contains("strongest antithesis"));
+        assert!(DIALECTIC_SYNTHESIS_PROMPT.contains("\"preserved_from_antithesis\""));
+    }
+
+    #[test]
+    fn test_negotiation_prompt() {
+        assert_eq!(
+            get_prompt_for_mode("negotiation"),
+            NEGOTIATION_ANALYSIS_PROMPT
+        );
+        for field in ["batna_value", "reservation_value", "packages", "concessions"] {
+            assert!(NEGOTIATION_ANALYSIS_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_risk_prompts() {
+        assert_eq!(get_prompt_for_mode("risk"), RISK_IDENTIFICATION_PROMPT);
+        assert!(RISK_IDENTIFICATION_PROMPT.contains("\"detectability\""));
+        assert!(RISK_RESCORE_PROMPT.contains("\"rationale\""));
+    }
+
+    #[test]
+    fn test_systems_prompt() {
+        assert_eq!(get_prompt_for_mode("systems"), SYSTEMS_MODEL_PROMPT);
+        for field in ["elements", "links", "polarity", "leverage_points"] {
+            assert!(SYSTEMS_MODEL_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_got_compare_prompt() {
+        assert_eq!(get_prompt_for_mode("got_compare"), GOT_COMPARE_PROMPT);
+        for field in ["winner", "confidence", "rationale"] {
+            assert!(GOT_COMPARE_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+        assert!(GOT_COMPARE_PROMPT.contains("\"tie\""));
+    }
+
+    #[test]
+    fn test_timeline_compare_prompt() {
+        assert_eq!(
+            get_prompt_for_mode("timeline_compare"),
+            TIMELINE_COMPARE_PROMPT
+        );
+        for field in ["divergence_reason", "consequences_a", "fact_assessments"] {
+            assert!(TIMELINE_COMPARE_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_counterfactual_search_prompts() {
+        assert_eq!(
+            get_prompt_for_mode("minimal_change"),
+            COUNTERFACTUAL_PROPOSE_PROMPT
+        );
+        assert_eq!(
+            get_prompt_for_mode("counterfactual_test"),
+            COUNTERFACTUAL_TEST_PROMPT
+        );
+        assert!(COUNTERFACTUAL_PROPOSE_PROMPT.contains("\"interventions\""));
+        assert!(COUNTERFACTUAL_TEST_PROMPT.contains("\"flip_probability\""));
+    }
+
+    #[test]
+    fn test_synthesis_prompt() {
+        assert_eq!(get_prompt_for_mode("briefing"), SYNTHESIS_BRIEFING_PROMPT);
+        for field in ["sections", "key_findings", "conflicts", "open_questions"] {
+            assert!(SYNTHESIS_BRIEFING_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_code_prompts() {
+        assert_eq!(
+            get_prompt_for_mode("explain_diff"),
+            CODE_EXPLAIN_DIFF_PROMPT
+        );
+        assert_eq!(get_prompt_for_mode("data_flow"), CODE_TRACE_FLOW_PROMPT);
+        assert_eq!(get_prompt_for_mode("edge_cases"), CODE_EDGE_CASES_PROMPT);
+        for prompt in [
+            CODE_EXPLAIN_DIFF_PROMPT,
+            CODE_TRACE_FLOW_PROMPT,
+            CODE_EDGE_CASES_PROMPT,
+        ] {
+            for field in ["summary", "invariants", "risks", "suggested_tests"] {
+                assert!(prompt.contains(&format!("\"{}\"", field)));
+            }
+        }
+        assert!(CODE_EXPLAIN_DIFF_PROMPT.contains("\"behavior_changes\""));
+        assert!(CODE_TRACE_FLOW_PROMPT.contains("\"sinks\""));
+        assert!(CODE_EDGE_CASES_PROMPT.contains("\"handled\""));
+    }
+
+    #[test]
+    fn test_calc_extraction_prompt() {
+        assert_eq!(
+            get_prompt_for_mode("verify_calculations"),
+            CALC_EXTRACTION_PROMPT
+        );
+        for field in ["claims", "expression", "stated", "thought_id"] {
+            assert!(CALC_EXTRACTION_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_prioritization_prompt() {
+        assert_eq!(
+            get_prompt_for_mode("prioritization"),
+            PRIORITIZATION_ESTIMATE_PROMPT
+        );
+        for field in ["reach", "effort", "category", "criteria_scores"] {
+            assert!(PRIORITIZATION_ESTIMATE_PROMPT.contains(&format!("\"{}\"", field)));
+        }
+    }
+
+    #[test]
+    fn test_swot_prompt() {
+        assert_eq!(get_prompt_for_mode("swot"), SWOT_ANALYSIS_PROMPT);
+        for quadrant in ["strengths", "weaknesses", "opportunities", "threats"] {
+            assert!(SWOT_ANALYSIS_PROMPT.contains(&format!("\"{}\"", quadrant)));
+        }
+    }
+
     #[test]
     fn test_mcts_prompt_ucb_formula() {
         assert!(MCTS_EXPLORATION_PROMPT.contains("Q(s,a)"));
@@ -1576,4 +2811,17 @@ mod tests {
         assert!(AUTO_BACKTRACK_PROMPT.contains("Dead-end"));
         assert!(AUTO_BACKTRACK_PROMPT.contains("alternative path"));
     }
+
+    #[test]
+    fn test_pipe_definitions_unique() {
+        for (i, def) in PIPE_DEFINITIONS.iter().enumerate() {
+            assert!(
+                PIPE_DEFINITIONS[i + 1..].iter().all(|d| d.name != def.name),
+                "duplicate pipe definition {}",
+                def.name
+            );
+            assert!(def.max_tokens > 0);
+        }
+        assert_eq!(LINEAR_PIPE.system_prompt, Some(LINEAR_REASONING_PROMPT));
+    }
 }
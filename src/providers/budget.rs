@@ -0,0 +1,414 @@
+//! Daily and per-session spend budgets.
+//!
+//! [`SpendBudget`] is a [`PipeMiddleware`], installed around the backend the
+//! modes call with a [`MiddlewareBackend`](super::MiddlewareBackend). Before
+//! each pipe call it checks the day's spend and, when the call runs inside
+//! [`with_budget_session`], the session's spend against the limits in
+//! [`BudgetConfig`], refusing the call with
+//! [`LangbaseError::BudgetExceeded`] once one is used up. Spend is tracked in
+//! memory, so it starts from zero when the server restarts.
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::config::BudgetConfig;
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{PipeAttempt, PipeMiddleware, PipeRequest, PipeResponse};
+
+tokio::task_local! {
+    static BUDGET_SESSION: String;
+}
+
+/// Run `future` with its pipe calls counted against `session_id`'s budget.
+pub async fn with_budget_session<F: Future>(session_id: String, future: F) -> F::Output {
+    BUDGET_SESSION.scope(session_id, future).await
+}
+
+/// The session the current task's pipe calls are charged to, if any.
+fn current_budget_session() -> Option<String> {
+    BUDGET_SESSION.try_with(Clone::clone).ok()
+}
+
+/// Tokens and cost spent so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Spend {
+    /// Prompt and completion tokens used.
+    pub tokens: u64,
+    /// Cost of those tokens in USD.
+    pub cost_usd: f64,
+}
+
+/// Spend against one budget.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetUsage {
+    /// Tokens used.
+    pub tokens_used: u64,
+    /// Cost incurred in USD.
+    pub cost_used_usd: f64,
+    /// Token limit, if set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_limit: Option<u64>,
+    /// Tokens left before the limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_remaining: Option<u64>,
+    /// Cost limit in USD, if set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_limit_usd: Option<f64>,
+    /// Cost left before the limit, in USD.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_remaining_usd: Option<f64>,
+    /// Whether a limit is reached and pipe calls are refused.
+    pub exceeded: bool,
+}
+
+impl BudgetUsage {
+    fn new(spend: Spend, token_limit: Option<u64>, cost_limit_usd: Option<f64>) -> Self {
+        let tokens_remaining = token_limit.map(|limit| limit.saturating_sub(spend.tokens));
+        let cost_remaining_usd = cost_limit_usd.map(|limit| (limit - spend.cost_usd).max(0.0));
+        Self {
+            tokens_used: spend.tokens,
+            cost_used_usd: spend.cost_usd,
+            token_limit,
+            tokens_remaining,
+            cost_limit_usd,
+            cost_remaining_usd,
+            exceeded: tokens_remaining == Some(0) || cost_remaining_usd == Some(0.0),
+        }
+    }
+}
+
+/// Remaining budget reported by `reasoning_budget_status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    /// Whether any limit is configured.
+    pub enabled: bool,
+    /// Spend across all pipe calls today (UTC).
+    pub daily: BudgetUsage,
+    /// Spend of the requested session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<BudgetUsage>,
+    /// When the daily budget resets.
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    day: NaiveDate,
+    daily: Spend,
+    sessions: HashMap<String, Spend>,
+}
+
+/// Spend tracker enforcing the configured budgets.
+#[derive(Debug)]
+pub struct SpendBudget {
+    config: BudgetConfig,
+    state: Mutex<BudgetState>,
+}
+
+impl SpendBudget {
+    /// Create a tracker with nothing spent.
+    pub fn new(config: &BudgetConfig) -> Self {
+        Self {
+            config: config.clone(),
+            state: Mutex::new(BudgetState {
+                day: Utc::now().date_naive(),
+                daily: Spend::default(),
+                sessions: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Fail if today's or `session_id`'s budget is used up.
+    pub fn check(&self, session_id: Option<&str>) -> LangbaseResult<()> {
+        self.check_on(Utc::now().date_naive(), session_id)
+    }
+
+    /// Charge a call's tokens to today and to `session_id`.
+    pub fn record(&self, session_id: Option<&str>, prompt_tokens: u64, completion_tokens: u64) {
+        self.record_on(
+            Utc::now().date_naive(),
+            session_id,
+            prompt_tokens,
+            completion_tokens,
+        );
+    }
+
+    /// Spend and remaining budget for today and, if given, `session_id`.
+    pub fn status(&self, session_id: Option<&str>) -> BudgetStatus {
+        self.status_on(Utc::now().date_naive(), session_id)
+    }
+
+    fn check_on(&self, today: NaiveDate, session_id: Option<&str>) -> LangbaseResult<()> {
+        let state = self.state_on(today);
+        let exceeded = |scope: String, spend: Spend, tokens: Option<u64>, cost: Option<f64>| {
+            if let Some(limit) = tokens.filter(|&limit| spend.tokens >= limit) {
+                return Err(LangbaseError::BudgetExceeded {
+                    scope,
+                    limit: format!("{} tokens", limit),
+                });
+            }
+            if let Some(limit) = cost.filter(|&limit| spend.cost_usd >= limit) {
+                return Err(LangbaseError::BudgetExceeded {
+                    scope,
+                    limit: format!("${:.2}", limit),
+                });
+            }
+            Ok(())
+        };
+
+        exceeded(
+            "daily".to_string(),
+            state.daily,
+            self.config.daily_tokens,
+            self.config.daily_cost_usd,
+        )?;
+        if let Some(session_id) = session_id {
+            let spend = state.sessions.get(session_id).copied().unwrap_or_default();
+            exceeded(
+                format!("session {}", session_id),
+                spend,
+                self.config.session_tokens,
+                self.config.session_cost_usd,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn record_on(
+        &self,
+        today: NaiveDate,
+        session_id: Option<&str>,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+    ) {
+        let cost_usd = (prompt_tokens as f64 * self.config.prompt_cost_per_1k
+            + completion_tokens as f64 * self.config.completion_cost_per_1k)
+            / 1000.0;
+        let tokens = prompt_tokens + completion_tokens;
+
+        let mut state = self.state_on(today);
+        state.daily.tokens += tokens;
+        state.daily.cost_usd += cost_usd;
+        if let Some(session_id) = session_id {
+            let spend = state.sessions.entry(session_id.to_string()).or_default();
+            spend.tokens += tokens;
+            spend.cost_usd += cost_usd;
+        }
+    }
+
+    fn status_on(&self, today: NaiveDate, session_id: Option<&str>) -> BudgetStatus {
+        let state = self.state_on(today);
+        let session = session_id.map(|id| {
+            BudgetUsage::new(
+                state.sessions.get(id).copied().unwrap_or_default(),
+                self.config.session_tokens,
+                self.config.session_cost_usd,
+            )
+        });
+        let resets_at = today
+            .succ_opt()
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .map(|midnight| midnight.and_utc())
+            .unwrap_or_else(Utc::now);
+
+        BudgetStatus {
+            enabled: self.config.is_enabled(),
+            daily: BudgetUsage::new(
+                state.daily,
+                self.config.daily_tokens,
+                self.config.daily_cost_usd,
+            ),
+            session,
+            resets_at,
+        }
+    }
+
+    /// Lock the state, starting a new day's spend if the day changed
+    fn state_on(&self, today: NaiveDate) -> std::sync::MutexGuard<'_, BudgetState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.day != today {
+            state.day = today;
+            state.daily = Spend::default();
+        }
+        state
+    }
+}
+
+#[async_trait]
+impl PipeMiddleware for SpendBudget {
+    async fn on_request(
+        &self,
+        request: &mut PipeRequest,
+        _headers: &mut HeaderMap,
+        _attempt: u32,
+    ) -> LangbaseResult<()> {
+        let session_id = current_budget_session();
+        if let Err(e) = self.check(session_id.as_deref()) {
+            warn!(pipe = %request.name, error = %e, "Pipe call refused");
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn on_response(
+        &self,
+        request: &PipeRequest,
+        response: &mut PipeResponse,
+        _attempt: &PipeAttempt,
+    ) -> LangbaseResult<()> {
+        // Cached answers cost nothing
+        if response.cache_hit {
+            return Ok(());
+        }
+        let usage = response.raw.as_ref().and_then(|raw| raw.usage.as_ref());
+        let prompt_tokens = usage.and_then(|u| u.prompt_tokens).map_or_else(
+            || {
+                request
+                    .messages
+                    .iter()
+                    .map(|m| estimate_tokens(&m.content))
+                    .sum()
+            },
+            u64::from,
+        );
+        let completion_tokens = usage
+            .and_then(|u| u.completion_tokens)
+            .map_or_else(|| estimate_tokens(&response.completion), u64::from);
+        self.record(
+            current_budget_session().as_deref(),
+            prompt_tokens,
+            completion_tokens,
+        );
+        Ok(())
+    }
+}
+
+/// Rough token count of `text`, at four characters per token
+pub(crate) fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64 + 3) / 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProviderKind;
+    use crate::langbase::{Message, RawResponse, Usage};
+    use crate::providers::{MiddlewareBackend, ReasoningBackend};
+    use std::sync::Arc;
+
+    fn config() -> BudgetConfig {
+        BudgetConfig {
+            daily_tokens: Some(100),
+            session_cost_usd: Some(0.01),
+            prompt_cost_per_1k: 0.1,
+            completion_cost_per_1k: 0.2,
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_budget_refuses_once_used_up() {
+        let budget = SpendBudget::new(&config());
+        budget.record_on(day(1), Some("s1"), 40, 20);
+        assert!(budget.check_on(day(1), Some("s1")).is_ok());
+
+        budget.record_on(day(1), None, 30, 10);
+        let err = budget.check_on(day(1), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Budget exceeded: daily budget of 100 tokens is used up"
+        );
+
+        // The daily budget resets the next day
+        assert!(budget.check_on(day(2), None).is_ok());
+        let status = budget.status_on(day(2), Some("s1"));
+        assert_eq!(status.daily.tokens_used, 0);
+        assert_eq!(status.daily.tokens_remaining, Some(100));
+        assert_eq!(status.session.unwrap().tokens_used, 60);
+    }
+
+    #[test]
+    fn test_session_cost_budget() {
+        let budget = SpendBudget::new(&config());
+        // 20 * 0.1 / 1000 + 40 * 0.2 / 1000 = $0.01
+        budget.record_on(day(1), Some("s1"), 20, 40);
+
+        let err = budget.check_on(day(1), Some("s1")).unwrap_err();
+        assert!(matches!(
+            err,
+            LangbaseError::BudgetExceeded { ref scope, ref limit }
+                if scope == "session s1" && limit == "$0.01"
+        ));
+        assert!(budget.check_on(day(1), Some("s2")).is_ok());
+
+        let status = budget.status_on(day(1), Some("s1"));
+        let session = status.session.unwrap();
+        assert!(session.exceeded);
+        assert_eq!(session.cost_remaining_usd, Some(0.0));
+        assert_eq!(
+            status.resets_at,
+            day(2).and_hms_opt(0, 0, 0).unwrap().and_utc()
+        );
+    }
+
+    struct UsageBackend;
+
+    #[async_trait]
+    impl ReasoningBackend for UsageBackend {
+        async fn call_pipe(&self, _request: PipeRequest) -> LangbaseResult<PipeResponse> {
+            Ok(PipeResponse {
+                success: true,
+                completion: "ok".to_string(),
+                thread_id: None,
+                raw: Some(RawResponse {
+                    model: None,
+                    usage: Some(Usage {
+                        prompt_tokens: Some(60),
+                        completion_tokens: Some(50),
+                        total_tokens: Some(110),
+                    }),
+                }),
+                cache_hit: false,
+                fallback: None,
+            })
+        }
+
+        fn provider(&self) -> ProviderKind {
+            ProviderKind::Langbase
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_middleware_charges_reported_usage() {
+        let budget = Arc::new(SpendBudget::new(&config()));
+        let backend =
+            MiddlewareBackend::new(Arc::new(UsageBackend)).with_middleware(budget.clone());
+        let request = || PipeRequest::new("linear-reasoning-v1", vec![Message::user("hi")]);
+
+        with_budget_session("s1".to_string(), backend.call_pipe(request()))
+            .await
+            .unwrap();
+        let status = budget.status(Some("s1"));
+        assert_eq!(status.daily.tokens_used, 110);
+        assert_eq!(status.session.unwrap().tokens_used, 110);
+
+        let err = backend.call_pipe(request()).await.unwrap_err();
+        assert!(matches!(err, LangbaseError::BudgetExceeded { .. }));
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
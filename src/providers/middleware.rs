@@ -0,0 +1,82 @@
+//! Pipe middleware for any backend.
+//!
+//! [`MiddlewareBackend`] runs [`PipeMiddleware`] hooks around a backend's
+//! calls, so middleware that must see every provider's traffic, such as the
+//! spend budget, does not depend on the Langbase client.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+
+use super::{ReasoningBackend, SharedBackend};
+use crate::config::ProviderKind;
+use crate::error::LangbaseResult;
+use crate::langbase::{PipeAttempt, PipeMiddleware, PipeRequest, PipeResponse};
+
+/// Backend running middleware hooks around each call of the backend it wraps.
+///
+/// Unlike on the Langbase client, hooks run once per call rather than once
+/// per attempt, cache hits included (see [`PipeResponse::cache_hit`]).
+/// Headers added in [`PipeMiddleware::on_request`] are dropped, since the
+/// wrapped backend builds its own HTTP requests.
+pub struct MiddlewareBackend {
+    inner: SharedBackend,
+    middlewares: Vec<Arc<dyn PipeMiddleware>>,
+}
+
+impl MiddlewareBackend {
+    /// Wrap `inner` with no middleware.
+    pub fn new(inner: SharedBackend) -> Self {
+        Self {
+            inner,
+            middlewares: Vec::new(),
+        }
+    }
+
+    /// Run `middleware` around every call, after those added before it.
+    pub fn with_middleware(mut self, middleware: Arc<dyn PipeMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+}
+
+#[async_trait]
+impl ReasoningBackend for MiddlewareBackend {
+    async fn call_pipe(&self, mut request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let mut headers = HeaderMap::new();
+        for middleware in &self.middlewares {
+            middleware.on_request(&mut request, &mut headers, 1).await?;
+        }
+
+        let start = Instant::now();
+        let mut result = self.inner.call_pipe(request.clone()).await;
+        let attempt = PipeAttempt {
+            number: 1,
+            latency: start.elapsed(),
+        };
+        if let Ok(response) = &mut result {
+            for middleware in &self.middlewares {
+                if let Err(e) = middleware.on_response(&request, response, &attempt).await {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if let Err(e) = &result {
+            for middleware in &self.middlewares {
+                middleware.on_error(&request, e, &attempt).await;
+            }
+        }
+        result
+    }
+
+    fn provider(&self) -> ProviderKind {
+        self.inner.provider()
+    }
+
+    fn select_pipe(&self, pipe: &str) -> String {
+        self.inner.select_pipe(pipe)
+    }
+}
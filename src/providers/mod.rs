@@ -0,0 +1,229 @@
+//! LLM provider abstraction.
+//!
+//! Modes send their [`PipeRequest`]s to a [`ReasoningBackend`] instead of the
+//! Langbase client directly, so each pipe can be served by Langbase Pipes, an
+//! OpenAI-compatible chat completions endpoint, a local Ollama server, the
+//! connected MCP client's model (through MCP sampling), or canned offline
+//! fixtures. [`ProviderRouter`] picks the backend per pipe name
+//! from [`ProviderConfig`].
+//!
+//! Non-Langbase backends receive the request's messages as-is (modes always
+//! include their system prompt), with `{{variable}}` placeholders filled in
+//! from the request's variables the way a Langbase pipe would.
+//!
+//! [`ProviderConfig`]: crate::config::ProviderConfig
+
+mod budget;
+mod middleware;
+mod offline;
+mod ollama;
+mod openai;
+mod router;
+mod sampling;
+
+pub(crate) use budget::estimate_tokens;
+pub use budget::{with_budget_session, BudgetStatus, BudgetUsage, Spend, SpendBudget};
+pub use middleware::MiddlewareBackend;
+pub use offline::{OfflineBackend, OFFLINE_MODEL};
+pub use ollama::OllamaBackend;
+pub use openai::OpenAiBackend;
+pub use router::ProviderRouter;
+pub use sampling::{with_sampler, Sampler, SamplingBackend};
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::config::{ProviderKind, RequestConfig};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{
+    client_log, fan_out, next_retry_delay, parse_retry_after, status_error, FanOut, LangbaseClient,
+    LogLevel, Message, PipeRequest, PipeResponse,
+};
+
+/// A provider that can run pipe requests.
+#[async_trait]
+pub trait ReasoningBackend: Send + Sync {
+    /// Run a pipe request and return its completion.
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse>;
+
+    /// Which provider this backend talks to.
+    fn provider(&self) -> ProviderKind;
+
+    /// Pipe name to call for `pipe`.
+    ///
+    /// Backends that split traffic across pipe versions return the version
+    /// picked for this call; the default calls `pipe` itself.
+    fn select_pipe(&self, pipe: &str) -> String {
+        pipe.to_string()
+    }
+
+    /// Run independent requests concurrently, at most `max_concurrency` at
+    /// a time (0 for no limit), collecting every response and error.
+    async fn call_pipe_parallel(
+        &self,
+        requests: Vec<PipeRequest>,
+        max_concurrency: usize,
+    ) -> FanOut {
+        fan_out(requests, max_concurrency, |request| self.call_pipe(request)).await
+    }
+}
+
+/// Backend shared by all modes.
+pub type SharedBackend = Arc<dyn ReasoningBackend>;
+
+#[async_trait]
+impl ReasoningBackend for LangbaseClient {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        LangbaseClient::call_pipe(self, request).await
+    }
+
+    fn provider(&self) -> ProviderKind {
+        ProviderKind::Langbase
+    }
+}
+
+impl From<LangbaseClient> for SharedBackend {
+    fn from(client: LangbaseClient) -> Self {
+        Arc::new(client)
+    }
+}
+
+/// Messages of a request with `{{name}}` placeholders replaced by its variables
+fn render_messages(request: &PipeRequest) -> Vec<Message> {
+    let Some(variables) = &request.variables else {
+        return request.messages.clone();
+    };
+    request
+        .messages
+        .iter()
+        .map(|message| {
+            let mut message = message.clone();
+            for (name, value) in variables {
+                message.content = message.content.replace(&format!("{{{{{}}}}}", name), value);
+            }
+            message
+        })
+        .collect()
+}
+
+/// Map a reqwest send/read failure, distinguishing timeouts
+fn http_error(e: reqwest::Error, timeout_ms: u64) -> LangbaseError {
+    if e.is_timeout() {
+        LangbaseError::Timeout { timeout_ms }
+    } else {
+        LangbaseError::Http(e)
+    }
+}
+
+/// Send a request, turning non-success statuses into [`LangbaseError::Api`]
+/// or [`LangbaseError::RateLimited`]
+async fn send(
+    request: reqwest::RequestBuilder,
+    timeout_ms: u64,
+) -> LangbaseResult<reqwest::Response> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| http_error(e, timeout_ms))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = parse_retry_after(response.headers());
+        let error_body = response.text().await.unwrap_or_else(|e| {
+            warn!(error = %e, status = %status, "Failed to read provider error response body");
+            "Unable to read error response".to_string()
+        });
+        return Err(status_error(status, retry_after, error_body));
+    }
+    Ok(response)
+}
+
+/// Run `attempt` with the same retry and backoff policy as the Langbase client
+async fn with_retries<T, F, Fut>(
+    config: &RequestConfig,
+    pipe: &str,
+    mut attempt: F,
+) -> LangbaseResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = LangbaseResult<T>>,
+{
+    let mut last_error = None;
+    let mut retries = 0;
+    let mut delay = Duration::ZERO;
+
+    while retries <= config.max_retries {
+        if retries > 0 {
+            warn!(
+                pipe = %pipe,
+                retry = retries,
+                delay_ms = delay.as_millis(),
+                "Retrying provider request"
+            );
+            client_log(
+                LogLevel::Warning,
+                "providers",
+                serde_json::json!({
+                    "message": "Retrying provider request",
+                    "pipe": pipe,
+                    "retry": retries,
+                    "delay_ms": delay.as_millis() as u64,
+                }),
+            );
+            tokio::time::sleep(delay).await;
+        }
+
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                warn!(pipe = %pipe, error = %e, retry = retries, "Provider call failed");
+                retries += 1;
+                let next_delay = next_retry_delay(config, retries, &e);
+                last_error = Some(e);
+                match next_delay {
+                    Some(next_delay) => delay = next_delay,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    Err(LangbaseError::Unavailable {
+        message: last_error
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "Unknown error".to_string()),
+        retries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_messages_fills_variables() {
+        let request = PipeRequest::new(
+            "pipe",
+            vec![
+                Message::system("Answer in {{language}}."),
+                Message::user("{{question}} ({{language}})"),
+            ],
+        )
+        .with_variable("language", "French")
+        .with_variable("question", "Why?");
+
+        let messages = render_messages(&request);
+        assert_eq!(messages[0].content, "Answer in French.");
+        assert_eq!(messages[1].content, "Why? (French)");
+    }
+
+    #[test]
+    fn test_render_messages_without_variables() {
+        let request = PipeRequest::new("pipe", vec![Message::user("Keep {{this}}")]);
+        assert_eq!(render_messages(&request)[0].content, "Keep {{this}}");
+    }
+}
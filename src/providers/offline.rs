@@ -0,0 +1,178 @@
+//! Deterministic backend serving canned fixtures, for development and CI.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tracing::debug;
+
+use super::ReasoningBackend;
+use crate::config::{PipeConfig, ProviderKind};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{
+    current_stream_sink, fixture_pipes, pipe_fixture, PipeRequest, PipeResponse, RawResponse,
+};
+
+/// Model name reported in offline responses.
+pub const OFFLINE_MODEL: &str = "offline-fixture";
+
+/// Backend answering every pipe call from its fixture, without network access.
+///
+/// Pipes renamed through configuration (e.g. `PIPE_LINEAR`) are mapped back to
+/// the fixture of the role they fill. The same request always gets the same
+/// completion.
+#[derive(Debug, Clone)]
+pub struct OfflineBackend {
+    fixtures: HashMap<String, &'static str>,
+}
+
+impl OfflineBackend {
+    /// Create an offline backend for the configured pipe names
+    pub fn new(pipes: &PipeConfig) -> Self {
+        let mut fixtures: HashMap<String, &'static str> = fixture_pipes()
+            .filter_map(|name| Some((name.to_string(), pipe_fixture(name)?)))
+            .collect();
+
+        let configured = [
+            (Some(pipes.linear.clone()), "linear-reasoning-v1"),
+            (Some(pipes.tree.clone()), "tree-reasoning-v1"),
+            (Some(pipes.divergent.clone()), "divergent-reasoning-v1"),
+            (Some(pipes.reflection.clone()), "reflection-v1"),
+            (pipes.auto.clone(), "mode-router-v1"),
+            (pipes.backtracking.clone(), "backtracking-reasoning-v1"),
+            (
+                pipes.got.as_ref().and_then(|c| c.pipe.clone()),
+                "got-reasoning-v1",
+            ),
+            (
+                pipes.detection.as_ref().and_then(|c| c.pipe.clone()),
+                "detection-v1",
+            ),
+            (
+                pipes.decision.as_ref().and_then(|c| c.pipe.clone()),
+                "decision-framework-v1",
+            ),
+            (
+                pipes.evidence.as_ref().and_then(|c| c.pipe.clone()),
+                "decision-framework-v1",
+            ),
+            (pipes.code.clone(), "code-reasoning-v1"),
+        ];
+        for (name, default) in configured {
+            if let (Some(name), Some(fixture)) = (name, pipe_fixture(default)) {
+                fixtures.entry(name).or_insert(fixture);
+            }
+        }
+        // Every version of a pipe answers like the pipe itself
+        for (pipe, versions) in &pipes.versions {
+            if let Some(fixture) = fixtures.get(pipe).copied() {
+                for version in versions {
+                    fixtures.entry(version.name.clone()).or_insert(fixture);
+                }
+            }
+        }
+
+        Self { fixtures }
+    }
+}
+
+#[async_trait]
+impl ReasoningBackend for OfflineBackend {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let completion = self
+            .fixtures
+            .get(&request.name)
+            .ok_or_else(|| LangbaseError::Api {
+                status: 404,
+                message: format!("No offline fixture for pipe '{}'", request.name),
+            })?
+            .to_string();
+
+        debug!(pipe = %request.name, "Serving offline fixture");
+        if let Some(sink) = current_stream_sink() {
+            sink(&completion);
+        }
+
+        Ok(PipeResponse {
+            success: true,
+            completion,
+            thread_id: None,
+            raw: Some(RawResponse {
+                model: Some(OFFLINE_MODEL.to_string()),
+                usage: None,
+            }),
+            cache_hit: false,
+            fallback: None,
+        })
+    }
+
+    fn provider(&self) -> ProviderKind {
+        ProviderKind::Offline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langbase::Message;
+
+    fn request(pipe: &str) -> PipeRequest {
+        PipeRequest::new(pipe, vec![Message::user("hi")])
+    }
+
+    #[tokio::test]
+    async fn test_offline_backend_serves_fixture() {
+        let backend = OfflineBackend::new(&PipeConfig::default());
+        let response = backend
+            .call_pipe(request("linear-reasoning-v1"))
+            .await
+            .unwrap();
+
+        assert!(response.success);
+        let value: serde_json::Value = serde_json::from_str(&response.completion).unwrap();
+        assert!(value["thought"].is_string());
+        assert_eq!(
+            response.raw.and_then(|r| r.model).as_deref(),
+            Some(OFFLINE_MODEL)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_offline_backend_maps_renamed_pipes() {
+        let pipes = PipeConfig {
+            linear: "my-linear".to_string(),
+            ..Default::default()
+        };
+        let backend = OfflineBackend::new(&pipes);
+
+        let renamed = backend.call_pipe(request("my-linear")).await.unwrap();
+        let default = backend
+            .call_pipe(request("linear-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(renamed.completion, default.completion);
+    }
+
+    #[tokio::test]
+    async fn test_offline_backend_maps_pipe_versions() {
+        let mut pipes = PipeConfig::default();
+        pipes.versions.insert(
+            "linear-reasoning-v1".to_string(),
+            vec!["linear-reasoning-v2".parse().unwrap()],
+        );
+        let backend = OfflineBackend::new(&pipes);
+
+        assert!(backend
+            .call_pipe(request("linear-reasoning-v2"))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_offline_backend_unknown_pipe() {
+        let backend = OfflineBackend::new(&PipeConfig::default());
+        let err = backend
+            .call_pipe(request("unknown-pipe"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No offline fixture"));
+    }
+}
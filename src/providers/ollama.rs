@@ -0,0 +1,153 @@
+//! Backend for a local Ollama server.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+
+use super::{http_error, render_messages, send, with_retries, ReasoningBackend};
+use crate::config::{OllamaConfig, ProviderKind, RequestConfig};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{GenerationParams, Message, PipeRequest, PipeResponse, RawResponse, Usage};
+
+/// Backend calling Ollama's `POST {base_url}/api/chat`.
+///
+/// Responses are not streamed; the full completion is returned at once.
+#[derive(Clone)]
+pub struct OllamaBackend {
+    client: Client,
+    config: OllamaConfig,
+    request_config: RequestConfig,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [Message],
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ChatOptions>,
+}
+
+/// Ollama's names for the generation parameters.
+#[derive(Serialize)]
+struct ChatOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    model: Option<String>,
+    message: ChatMessage,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: String,
+}
+
+impl OllamaBackend {
+    /// Create a new Ollama backend
+    pub fn new(config: &OllamaConfig, request_config: RequestConfig) -> LangbaseResult<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(request_config.timeout_ms))
+            .build()
+            .map_err(LangbaseError::Http)?;
+
+        Ok(Self {
+            client,
+            config: OllamaConfig {
+                base_url: config.base_url.trim_end_matches('/').to_string(),
+                model: config.model.clone(),
+            },
+            request_config,
+        })
+    }
+
+    /// Execute a single request (internal)
+    async fn execute_request(
+        &self,
+        messages: &[Message],
+        generation: GenerationParams,
+    ) -> LangbaseResult<PipeResponse> {
+        let url = format!("{}/api/chat", self.config.base_url);
+        let options = (!generation.is_empty()).then_some(ChatOptions {
+            temperature: generation.temperature,
+            top_p: generation.top_p,
+            num_predict: generation.max_tokens,
+        });
+        let request = self.client.post(&url).json(&ChatRequest {
+            model: &self.config.model,
+            messages,
+            stream: false,
+            options,
+        });
+
+        let timeout_ms = self.request_config.timeout_ms;
+        let body = send(request, timeout_ms)
+            .await?
+            .text()
+            .await
+            .map_err(|e| http_error(e, timeout_ms))?;
+        let chat: ChatResponse =
+            serde_json::from_str(&body).map_err(|e| LangbaseError::InvalidResponse {
+                message: format!("Failed to parse Ollama response: {}", e),
+            })?;
+
+        let usage = Usage {
+            prompt_tokens: chat.prompt_eval_count,
+            completion_tokens: chat.eval_count,
+            total_tokens: chat
+                .prompt_eval_count
+                .zip(chat.eval_count)
+                .map(|(prompt, completion)| prompt + completion),
+        };
+
+        Ok(PipeResponse {
+            success: true,
+            completion: chat.message.content,
+            thread_id: None,
+            raw: Some(RawResponse {
+                model: chat.model,
+                usage: Some(usage),
+            }),
+            cache_hit: false,
+            fallback: None,
+        })
+    }
+}
+
+#[async_trait]
+impl ReasoningBackend for OllamaBackend {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let messages = render_messages(&request);
+
+        info!(
+            pipe = %request.name,
+            model = %self.config.model,
+            messages_count = messages.len(),
+            "Calling Ollama provider"
+        );
+
+        with_retries(&self.request_config, &request.name, || {
+            self.execute_request(&messages, request.generation)
+        })
+        .await
+    }
+
+    fn provider(&self) -> ProviderKind {
+        ProviderKind::Ollama
+    }
+}
@@ -0,0 +1,157 @@
+//! Backend for OpenAI-compatible chat completions endpoints.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+
+use super::{http_error, render_messages, send, with_retries, ReasoningBackend};
+use crate::config::{OpenAiConfig, ProviderKind, RequestConfig};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{
+    current_stream_sink, read_sse_completion, GenerationParams, Message, PipeRequest, PipeResponse,
+    RawResponse, StreamSink, Usage,
+};
+
+/// Backend calling `POST {base_url}/chat/completions`.
+///
+/// Works with OpenAI and the many servers that mimic its API (vLLM, LM
+/// Studio, OpenRouter, ...). Streams like the Langbase client when a stream
+/// sink is installed.
+#[derive(Clone)]
+pub struct OpenAiBackend {
+    client: Client,
+    config: OpenAiConfig,
+    request_config: RequestConfig,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [Message],
+    stream: bool,
+    #[serde(flatten)]
+    generation: GenerationParams,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl OpenAiBackend {
+    /// Create a new OpenAI-compatible backend
+    pub fn new(config: &OpenAiConfig, request_config: RequestConfig) -> LangbaseResult<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(request_config.timeout_ms))
+            .build()
+            .map_err(LangbaseError::Http)?;
+
+        Ok(Self {
+            client,
+            config: OpenAiConfig {
+                base_url: config.base_url.trim_end_matches('/').to_string(),
+                ..config.clone()
+            },
+            request_config,
+        })
+    }
+
+    /// Execute a single request (internal)
+    async fn execute_request(
+        &self,
+        messages: &[Message],
+        generation: GenerationParams,
+        sink: Option<&StreamSink>,
+    ) -> LangbaseResult<PipeResponse> {
+        let url = format!("{}/chat/completions", self.config.base_url);
+        let mut request = self.client.post(&url).json(&ChatRequest {
+            model: &self.config.model,
+            messages,
+            stream: sink.is_some(),
+            generation,
+        });
+        if let Some(api_key) = &self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let timeout_ms = self.request_config.timeout_ms;
+        let response = send(request, timeout_ms).await?;
+        if let Some(sink) = sink {
+            return read_sse_completion(response, sink, timeout_ms).await;
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| http_error(e, timeout_ms))?;
+        let chat: ChatResponse =
+            serde_json::from_str(&body).map_err(|e| LangbaseError::InvalidResponse {
+                message: format!("Failed to parse chat completion: {}", e),
+            })?;
+        let completion = chat
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| LangbaseError::InvalidResponse {
+                message: "Chat completion has no choices".to_string(),
+            })?
+            .message
+            .content
+            .unwrap_or_default();
+
+        Ok(PipeResponse {
+            success: true,
+            completion,
+            thread_id: None,
+            raw: Some(RawResponse {
+                model: chat.model,
+                usage: chat.usage,
+            }),
+            cache_hit: false,
+            fallback: None,
+        })
+    }
+}
+
+#[async_trait]
+impl ReasoningBackend for OpenAiBackend {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let sink = current_stream_sink();
+        let messages = render_messages(&request);
+
+        info!(
+            pipe = %request.name,
+            model = %self.config.model,
+            messages_count = messages.len(),
+            stream = sink.is_some(),
+            "Calling OpenAI-compatible provider"
+        );
+
+        with_retries(&self.request_config, &request.name, || {
+            self.execute_request(&messages, request.generation, sink.as_ref())
+        })
+        .await
+    }
+
+    fn provider(&self) -> ProviderKind {
+        ProviderKind::OpenAi
+    }
+}
@@ -0,0 +1,586 @@
+//! Per-pipe provider, version and fallback selection.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use super::{
+    OfflineBackend, OllamaBackend, OpenAiBackend, ReasoningBackend, SamplingBackend, SharedBackend,
+};
+use crate::config::{random_fraction, Config, FallbackTarget, PipeVersion, ProviderKind};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{
+    cancellable, client_log, LangbaseClient, LogLevel, PipeRequest, PipeResponse,
+};
+
+/// Backend that forwards each request to the provider configured for its pipe.
+///
+/// It also picks which version of a pipe each call goes to when
+/// [`PipeConfig::versions`](crate::config::PipeConfig::versions) splits the
+/// pipe's traffic, and walks the pipe's fallback chain from
+/// [`ProviderConfig::fallbacks`](crate::config::ProviderConfig::fallbacks)
+/// when the pipe is unavailable.
+#[derive(Clone)]
+pub struct ProviderRouter {
+    default: SharedBackend,
+    pipes: HashMap<String, SharedBackend>,
+    versions: HashMap<String, Vec<PipeVersion>>,
+    providers: HashMap<ProviderKind, SharedBackend>,
+    fallbacks: HashMap<String, Vec<FallbackTarget>>,
+}
+
+impl ProviderRouter {
+    /// Create a router sending every pipe to `default`
+    pub fn new(default: impl Into<SharedBackend>) -> Self {
+        Self {
+            default: default.into(),
+            pipes: HashMap::new(),
+            versions: HashMap::new(),
+            providers: HashMap::new(),
+            fallbacks: HashMap::new(),
+        }
+    }
+
+    /// Send requests for `pipe` to `backend` instead of the default
+    pub fn with_pipe(mut self, pipe: impl Into<String>, backend: impl Into<SharedBackend>) -> Self {
+        self.pipes.insert(pipe.into(), backend.into());
+        self
+    }
+
+    /// Split calls to `pipe` across `versions` by weight
+    pub fn with_versions(mut self, pipe: impl Into<String>, versions: Vec<PipeVersion>) -> Self {
+        self.versions.insert(pipe.into(), versions);
+        self
+    }
+
+    /// Use `backend` for provider fallbacks to `kind` (and for heuristic
+    /// fallbacks when `kind` is offline)
+    pub fn with_provider(mut self, kind: ProviderKind, backend: impl Into<SharedBackend>) -> Self {
+        self.providers.insert(kind, backend.into());
+        self
+    }
+
+    /// Try `chain` in order when `pipe` is unavailable
+    pub fn with_fallbacks(mut self, pipe: impl Into<String>, chain: Vec<FallbackTarget>) -> Self {
+        self.fallbacks.insert(pipe.into(), chain);
+        self
+    }
+
+    /// Build the router described by `config.providers`
+    ///
+    /// Backends are created only for providers that some pipe uses; the
+    /// Langbase client is used as-is for Langbase pipes.
+    pub fn from_config(config: &Config, langbase: LangbaseClient) -> LangbaseResult<Self> {
+        let providers = &config.providers;
+        let mut backends: HashMap<ProviderKind, SharedBackend> = HashMap::new();
+        let mut backend = |kind: ProviderKind| -> LangbaseResult<SharedBackend> {
+            if let Some(existing) = backends.get(&kind) {
+                return Ok(Arc::clone(existing));
+            }
+            let created: SharedBackend = match kind {
+                ProviderKind::Langbase => Arc::new(langbase.clone()),
+                ProviderKind::OpenAi => Arc::new(OpenAiBackend::new(
+                    &providers.openai,
+                    config.request.clone(),
+                )?),
+                ProviderKind::Ollama => Arc::new(OllamaBackend::new(
+                    &providers.ollama,
+                    config.request.clone(),
+                )?),
+                ProviderKind::Offline => Arc::new(OfflineBackend::new(&config.pipes)),
+                ProviderKind::Sampling => Arc::new(SamplingBackend::new(&providers.sampling)),
+            };
+            backends.insert(kind, Arc::clone(&created));
+            Ok(created)
+        };
+
+        let mut router = Self::new(backend(providers.default)?);
+        for (pipe, &kind) in &providers.pipes {
+            router = router.with_pipe(pipe.clone(), backend(kind)?);
+        }
+        for (pipe, versions) in &config.pipes.versions {
+            router = router.with_versions(pipe.clone(), versions.clone());
+        }
+        for (pipe, chain) in &providers.fallbacks {
+            for target in chain {
+                let kind = match target {
+                    FallbackTarget::Pipe(_) => continue,
+                    FallbackTarget::Provider(kind) => *kind,
+                    FallbackTarget::Heuristic => ProviderKind::Offline,
+                };
+                router = router.with_provider(kind, backend(kind)?);
+            }
+            router = router.with_fallbacks(pipe.clone(), chain.clone());
+        }
+        Ok(router)
+    }
+
+    /// Backend that serves the given pipe
+    ///
+    /// A pipe version without its own provider override uses the provider of
+    /// the pipe it is a version of.
+    pub fn backend_for(&self, pipe: &str) -> &SharedBackend {
+        self.pipes
+            .get(pipe)
+            .or_else(|| self.pipes.get(self.base_pipe(pipe)))
+            .unwrap_or(&self.default)
+    }
+
+    /// Configured pipe that `pipe` is a version of, or `pipe` itself
+    fn base_pipe<'a>(&'a self, pipe: &'a str) -> &'a str {
+        self.versions
+            .iter()
+            .find(|(_, versions)| versions.iter().any(|v| v.name == pipe))
+            .map(|(base, _)| base.as_str())
+            .unwrap_or(pipe)
+    }
+
+    /// Call the backend for `request`'s pipe
+    async fn route(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let backend = self.backend_for(&request.name);
+        debug!(pipe = %request.name, provider = %backend.provider(), "Routing pipe call");
+        call_checked(backend, request).await
+    }
+
+    /// Walk the fallback chain after the pipe failed with `error`
+    ///
+    /// Returns the first fallback answer, marked with its fallback type, or
+    /// the original error when every fallback fails too.
+    async fn call_fallbacks(
+        &self,
+        request: PipeRequest,
+        chain: &[FallbackTarget],
+        error: LangbaseError,
+    ) -> LangbaseResult<PipeResponse> {
+        for target in chain {
+            let (backend, pipe) = match target {
+                FallbackTarget::Pipe(pipe) => (self.backend_for(pipe), pipe.as_str()),
+                FallbackTarget::Provider(kind) => match self.providers.get(kind) {
+                    Some(backend) => (backend, request.name.as_str()),
+                    None => continue,
+                },
+                FallbackTarget::Heuristic => match self.providers.get(&ProviderKind::Offline) {
+                    Some(backend) => (backend, request.name.as_str()),
+                    None => continue,
+                },
+            };
+            warn!(
+                pipe = %request.name,
+                fallback = ?target,
+                error = %error,
+                "Pipe unavailable, trying fallback"
+            );
+            client_log(
+                LogLevel::Warning,
+                "providers",
+                serde_json::json!({
+                    "message": "Pipe unavailable, trying fallback",
+                    "pipe": request.name,
+                    "fallback": target.fallback_type(),
+                    "fallback_pipe": pipe,
+                    "error": error.to_string(),
+                }),
+            );
+
+            let mut fallback_request = request.clone();
+            fallback_request.name = pipe.to_string();
+            match call_checked(backend, fallback_request).await {
+                Ok(mut response) => {
+                    response.fallback = Some(target.fallback_type().to_string());
+                    return Ok(response);
+                }
+                Err(e) => {
+                    warn!(pipe = %request.name, fallback = ?target, error = %e, "Fallback failed")
+                }
+            }
+        }
+        Err(error)
+    }
+}
+
+/// Call `backend`, checking the completion against the request's output
+/// schema when the backend does not enforce schemas itself
+///
+/// The call is abandoned if the current task's cancellation token fires.
+async fn call_checked(
+    backend: &SharedBackend,
+    request: PipeRequest,
+) -> LangbaseResult<PipeResponse> {
+    let schema = match backend.provider() {
+        ProviderKind::Langbase => None,
+        _ => request.output_schema().cloned(),
+    };
+    let pipe = request.name.clone();
+    let response = cancellable(backend.call_pipe(request)).await?;
+
+    if let Some(schema) = schema {
+        if let Err(violation) = schema.validate_completion(&response.completion) {
+            warn!(
+                pipe = %pipe,
+                schema = %schema.name,
+                path = %violation.path,
+                error = %violation.message,
+                "Pipe response violates its output schema"
+            );
+            return Err(LangbaseError::SchemaViolation {
+                pipe,
+                schema: schema.name,
+                path: violation.path,
+                message: violation.message,
+            });
+        }
+    }
+    Ok(response)
+}
+
+/// Whether `error` means the pipe could not answer at all, as opposed to
+/// answering with something unusable
+fn is_unavailable(error: &LangbaseError) -> bool {
+    match error {
+        LangbaseError::Api { status, .. } => *status == 404 || *status >= 500,
+        LangbaseError::Unavailable { .. }
+        | LangbaseError::RateLimited { .. }
+        | LangbaseError::Timeout { .. }
+        | LangbaseError::Http(_)
+        | LangbaseError::PipeNotFound { .. } => true,
+        LangbaseError::InvalidResponse { .. }
+        | LangbaseError::ResponseParseFailed { .. }
+        | LangbaseError::SchemaViolation { .. }
+        | LangbaseError::ClientConfig { .. }
+        | LangbaseError::BudgetExceeded { .. }
+        | LangbaseError::Cancelled => false,
+    }
+}
+
+/// Version whose cumulative weight range contains `roll` (in [0, 1))
+fn pick_version(versions: &[PipeVersion], roll: f64) -> Option<&str> {
+    let total: u64 = versions.iter().map(|v| u64::from(v.weight)).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut target = (roll * total as f64) as u64;
+    for version in versions {
+        let weight = u64::from(version.weight);
+        if target < weight {
+            return Some(&version.name);
+        }
+        target -= weight;
+    }
+    versions
+        .iter()
+        .rev()
+        .find(|v| v.weight > 0)
+        .map(|v| v.name.as_str())
+}
+
+#[async_trait]
+impl ReasoningBackend for ProviderRouter {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let chain = self
+            .fallbacks
+            .get(self.base_pipe(&request.name))
+            .filter(|chain| !chain.is_empty());
+        let Some(chain) = chain else {
+            return self.route(request).await;
+        };
+
+        match self.route(request.clone()).await {
+            Err(e) if is_unavailable(&e) => self.call_fallbacks(request, chain, e).await,
+            result => result,
+        }
+    }
+
+    fn provider(&self) -> ProviderKind {
+        self.default.provider()
+    }
+
+    fn select_pipe(&self, pipe: &str) -> String {
+        let selected = self
+            .versions
+            .get(pipe)
+            .and_then(|versions| pick_version(versions, random_fraction()))
+            .unwrap_or(pipe);
+        if selected != pipe {
+            debug!(pipe = %pipe, version = %selected, "Selected pipe version");
+        }
+        selected.to_string()
+    }
+}
+
+impl From<ProviderRouter> for SharedBackend {
+    fn from(router: ProviderRouter) -> Self {
+        Arc::new(router)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LangbaseConfig, NetworkConfig, RequestConfig};
+    use crate::langbase::Message;
+
+    /// Backend answering every request with a fixed completion
+    struct FixedBackend(&'static str);
+
+    #[async_trait]
+    impl ReasoningBackend for FixedBackend {
+        async fn call_pipe(&self, _request: PipeRequest) -> LangbaseResult<PipeResponse> {
+            Ok(PipeResponse {
+                success: true,
+                completion: self.0.to_string(),
+                thread_id: None,
+                raw: None,
+                cache_hit: false,
+                fallback: None,
+            })
+        }
+
+        fn provider(&self) -> ProviderKind {
+            ProviderKind::Ollama
+        }
+    }
+
+    /// Backend failing every request with the error from its function
+    struct FailingBackend(fn() -> LangbaseError);
+
+    #[async_trait]
+    impl ReasoningBackend for FailingBackend {
+        async fn call_pipe(&self, _request: PipeRequest) -> LangbaseResult<PipeResponse> {
+            Err((self.0)())
+        }
+
+        fn provider(&self) -> ProviderKind {
+            ProviderKind::Langbase
+        }
+    }
+
+    fn unavailable() -> LangbaseError {
+        LangbaseError::Unavailable {
+            message: "down".to_string(),
+            retries: 3,
+        }
+    }
+
+    fn request(pipe: &str) -> PipeRequest {
+        PipeRequest::new(pipe, vec![Message::user("hi")])
+    }
+
+    #[tokio::test]
+    async fn test_router_uses_pipe_override() {
+        let router = ProviderRouter::new(Arc::new(FixedBackend("default")) as SharedBackend)
+            .with_pipe(
+                "got-reasoning-v1",
+                Arc::new(FixedBackend("override")) as SharedBackend,
+            );
+
+        let routed = router.call_pipe(request("got-reasoning-v1")).await.unwrap();
+        assert_eq!(routed.completion, "override");
+        let fallback = router
+            .call_pipe(request("linear-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(fallback.completion, "default");
+    }
+
+    #[tokio::test]
+    async fn test_router_walks_fallback_chain() {
+        let router = ProviderRouter::new(Arc::new(FailingBackend(unavailable)) as SharedBackend)
+            .with_provider(
+                ProviderKind::Offline,
+                Arc::new(FixedBackend("heuristic")) as SharedBackend,
+            )
+            .with_pipe(
+                "linear-backup",
+                Arc::new(FixedBackend("backup")) as SharedBackend,
+            )
+            .with_fallbacks(
+                "linear-reasoning-v1",
+                vec![
+                    // Not registered, so skipped
+                    FallbackTarget::Provider(ProviderKind::OpenAi),
+                    FallbackTarget::Pipe("linear-backup".to_string()),
+                    FallbackTarget::Heuristic,
+                ],
+            )
+            .with_fallbacks(
+                "tree-reasoning-v1",
+                vec![
+                    FallbackTarget::Pipe("tree-backup".to_string()),
+                    FallbackTarget::Heuristic,
+                ],
+            );
+
+        let response = router
+            .call_pipe(request("linear-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(response.completion, "backup");
+        assert_eq!(response.fallback.as_deref(), Some("fallback_pipe"));
+
+        // tree-backup goes to the failing default, so the heuristic answers
+        let response = router
+            .call_pipe(request("tree-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(response.completion, "heuristic");
+        assert_eq!(response.fallback.as_deref(), Some("local_heuristic"));
+
+        // Pipes without a chain fail as before
+        let err = router
+            .call_pipe(request("reflection-v1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LangbaseError::Unavailable { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_router_checks_schemas_for_other_providers() {
+        let router = ProviderRouter::new(Arc::new(FixedBackend("not json")) as SharedBackend);
+        let checked = request("tree-reasoning-v1")
+            .with_output_schema(crate::schemas::SchemaKind::Tree.latest());
+        let err = router.call_pipe(checked).await.unwrap_err();
+        assert!(
+            matches!(err, LangbaseError::SchemaViolation { ref schema, .. } if schema == "tree_response")
+        );
+
+        // Requests without a schema are passed through unchecked
+        let response = router
+            .call_pipe(request("tree-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(response.completion, "not json");
+    }
+
+    #[tokio::test]
+    async fn test_router_only_falls_back_when_unavailable() {
+        fn schema_violation() -> LangbaseError {
+            LangbaseError::SchemaViolation {
+                pipe: "tree-reasoning-v1".to_string(),
+                schema: "tree_response".to_string(),
+                path: "$.branches".to_string(),
+                message: "missing".to_string(),
+            }
+        }
+        let chain = vec![FallbackTarget::Heuristic];
+        let router =
+            ProviderRouter::new(Arc::new(FailingBackend(schema_violation)) as SharedBackend)
+                .with_provider(
+                    ProviderKind::Offline,
+                    Arc::new(FixedBackend("heuristic")) as SharedBackend,
+                )
+                .with_fallbacks("tree-reasoning-v1", chain.clone());
+        let err = router
+            .call_pipe(request("tree-reasoning-v1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LangbaseError::SchemaViolation { .. }));
+
+        // A healthy pipe answers itself, without a fallback mark
+        let router = ProviderRouter::new(Arc::new(FixedBackend("primary")) as SharedBackend)
+            .with_fallbacks("tree-reasoning-v1", chain);
+        let response = router
+            .call_pipe(request("tree-reasoning-v1"))
+            .await
+            .unwrap();
+        assert_eq!(response.completion, "primary");
+        assert!(response.fallback.is_none());
+    }
+
+    fn version(name: &str, weight: u32) -> PipeVersion {
+        PipeVersion {
+            name: name.to_string(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_pick_version_by_weight() {
+        let versions = vec![version("v1", 90), version("v2", 10), version("v3", 0)];
+        assert_eq!(pick_version(&versions, 0.0), Some("v1"));
+        assert_eq!(pick_version(&versions, 0.899), Some("v1"));
+        assert_eq!(pick_version(&versions, 0.9), Some("v2"));
+        assert_eq!(pick_version(&versions, 0.999), Some("v2"));
+        assert_eq!(pick_version(&[version("v1", 0)], 0.5), None);
+    }
+
+    #[test]
+    fn test_select_pipe_uses_versions() {
+        let router = ProviderRouter::new(Arc::new(FixedBackend("default")) as SharedBackend)
+            .with_versions(
+                "linear-reasoning-v1",
+                vec![version("linear-reasoning-v2", 1)],
+            )
+            .with_pipe(
+                "linear-reasoning-v1",
+                Arc::new(FixedBackend("override")) as SharedBackend,
+            );
+
+        assert_eq!(
+            router.select_pipe("linear-reasoning-v1"),
+            "linear-reasoning-v2"
+        );
+        assert_eq!(router.select_pipe("tree-reasoning-v1"), "tree-reasoning-v1");
+        // The pinned version keeps the provider of its pipe
+        assert!(Arc::ptr_eq(
+            router.backend_for("linear-reasoning-v2"),
+            router.backend_for("linear-reasoning-v1")
+        ));
+    }
+
+    #[test]
+    fn test_router_from_config() {
+        let mut config = Config {
+            langbase: LangbaseConfig {
+                api_key: String::new(),
+                base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
+            },
+            database: crate::config::DatabaseConfig::in_memory(),
+            logging: crate::config::LoggingConfig {
+                level: "info".to_string(),
+                format: crate::config::LogFormat::Pretty,
+            },
+            request: RequestConfig::default(),
+            pipes: Default::default(),
+            error_handling: Default::default(),
+            retention: Default::default(),
+            embeddings: Default::default(),
+            providers: Default::default(),
+            pipe_cache: Default::default(),
+            rate_limits: Default::default(),
+            circuit_breaker: Default::default(),
+            concurrency: Default::default(),
+            payload_log: Default::default(),
+            budget: Default::default(),
+            transport: Default::default(),
+            tools: Default::default(),
+            mcts: Default::default(),
+        };
+        config.providers.default = ProviderKind::Ollama;
+        config
+            .providers
+            .pipes
+            .insert("tree-reasoning-v1".to_string(), ProviderKind::OpenAi);
+        config.providers.fallbacks.insert(
+            "tree-reasoning-v1".to_string(),
+            vec![FallbackTarget::Heuristic],
+        );
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+
+        let router = ProviderRouter::from_config(&config, langbase).unwrap();
+        assert_eq!(router.provider(), ProviderKind::Ollama);
+        assert_eq!(
+            router.backend_for("tree-reasoning-v1").provider(),
+            ProviderKind::OpenAi
+        );
+        assert_eq!(
+            router.backend_for("linear-reasoning-v1").provider(),
+            ProviderKind::Ollama
+        );
+        assert_eq!(
+            router.providers[&ProviderKind::Offline].provider(),
+            ProviderKind::Offline
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Backend delegating pipe calls to the connected MCP client.
+//!
+//! With MCP sampling, the server asks the client to run a completion with
+//! `sampling/createMessage`, so reasoning modes can use the client's own
+//! model access instead of a Langbase API key. The MCP server installs a
+//! [`Sampler`] for each tool call from a client that supports sampling (see
+//! [`with_sampler`]); pipe calls made outside one fail as unavailable.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use super::{render_messages, ReasoningBackend};
+use crate::config::{ProviderKind, SamplingConfig};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::langbase::{MessageRole, PipeRequest, PipeResponse, RawResponse};
+
+/// Sends `sampling/createMessage` requests to an MCP client.
+#[async_trait]
+pub trait Sampler: Send + Sync {
+    /// Send a request with `params` and return the client's result.
+    ///
+    /// Fails with [`LangbaseError::Unavailable`] if the client answers with
+    /// an error or goes away before answering.
+    async fn create_message(&self, params: Value) -> LangbaseResult<Value>;
+}
+
+tokio::task_local! {
+    static SAMPLER: Arc<dyn Sampler>;
+}
+
+/// Run `future` with `sampler` serving the sampling backend's calls.
+pub async fn with_sampler<F: Future>(sampler: Arc<dyn Sampler>, future: F) -> F::Output {
+    SAMPLER.scope(sampler, future).await
+}
+
+/// Backend running pipes as `sampling/createMessage` requests to the MCP
+/// client of the current tool call.
+///
+/// System messages become the request's `systemPrompt`. Requests are not
+/// retried, since a refusal usually comes from the user.
+#[derive(Debug, Clone)]
+pub struct SamplingBackend {
+    config: SamplingConfig,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateMessageParams {
+    messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_prompt: Option<String>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    include_context: &'static str,
+    metadata: Value,
+}
+
+#[derive(Serialize)]
+struct SamplingMessage {
+    role: &'static str,
+    content: SamplingContent,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SamplingContent {
+    #[serde(rename = "type")]
+    content_type: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CreateMessageResult {
+    content: SamplingContent,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+impl SamplingBackend {
+    /// Create a new sampling backend
+    pub fn new(config: &SamplingConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Build the `sampling/createMessage` parameters for `request`
+    fn params(&self, request: &PipeRequest) -> CreateMessageParams {
+        let mut system = Vec::new();
+        let mut messages = Vec::new();
+        for message in render_messages(request) {
+            let role = match message.role {
+                MessageRole::System => {
+                    system.push(message.content);
+                    continue;
+                }
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+            };
+            messages.push(SamplingMessage {
+                role,
+                content: SamplingContent {
+                    content_type: "text".to_string(),
+                    text: Some(message.content),
+                },
+            });
+        }
+
+        CreateMessageParams {
+            messages,
+            system_prompt: (!system.is_empty()).then(|| system.join("\n\n")),
+            max_tokens: request
+                .generation
+                .max_tokens
+                .unwrap_or(self.config.max_tokens),
+            temperature: request.generation.temperature,
+            include_context: "none",
+            metadata: serde_json::json!({ "pipe": request.name }),
+        }
+    }
+}
+
+#[async_trait]
+impl ReasoningBackend for SamplingBackend {
+    async fn call_pipe(&self, request: PipeRequest) -> LangbaseResult<PipeResponse> {
+        let sampler = SAMPLER
+            .try_with(Arc::clone)
+            .map_err(|_| LangbaseError::Unavailable {
+                message: "no connected MCP client supports sampling".to_string(),
+                retries: 0,
+            })?;
+        let params = serde_json::to_value(self.params(&request)).map_err(|e| {
+            LangbaseError::InvalidResponse {
+                message: format!("Failed to build sampling request: {}", e),
+            }
+        })?;
+
+        info!(pipe = %request.name, "Requesting sampling from MCP client");
+
+        let timeout_ms = self.config.timeout_ms;
+        let result = tokio::time::timeout(
+            Duration::from_millis(timeout_ms),
+            sampler.create_message(params),
+        )
+        .await
+        .map_err(|_| LangbaseError::Timeout { timeout_ms })??;
+
+        let result: CreateMessageResult =
+            serde_json::from_value(result).map_err(|e| LangbaseError::InvalidResponse {
+                message: format!("Failed to parse sampling result: {}", e),
+            })?;
+        let completion = match (result.content.content_type.as_str(), result.content.text) {
+            ("text", Some(text)) => text,
+            (content_type, _) => {
+                return Err(LangbaseError::InvalidResponse {
+                    message: format!("Sampling returned {} content instead of text", content_type),
+                });
+            }
+        };
+
+        Ok(PipeResponse {
+            success: true,
+            completion,
+            thread_id: None,
+            raw: Some(RawResponse {
+                model: result.model,
+                usage: None,
+            }),
+            cache_hit: false,
+            fallback: None,
+        })
+    }
+
+    fn provider(&self) -> ProviderKind {
+        ProviderKind::Sampling
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langbase::{GenerationParams, Message};
+    use std::sync::Mutex;
+
+    /// Sampler answering every request with `reply`, recording the params
+    struct FakeSampler {
+        reply: Value,
+        seen: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl Sampler for FakeSampler {
+        async fn create_message(&self, params: Value) -> LangbaseResult<Value> {
+            self.seen.lock().unwrap().push(params);
+            Ok(self.reply.clone())
+        }
+    }
+
+    fn request() -> PipeRequest {
+        PipeRequest::new(
+            "linear-reasoning-v1",
+            vec![
+                Message::system("Reason in {{style}} style."),
+                Message::user("Plan a release"),
+            ],
+        )
+        .with_variable("style", "terse")
+        .with_generation(GenerationParams {
+            temperature: Some(0.2),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_sampling_backend_sends_create_message() {
+        let sampler = Arc::new(FakeSampler {
+            reply: serde_json::json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": "{\"thought\": \"Ship it\"}"},
+                "model": "client-model",
+                "stopReason": "endTurn"
+            }),
+            seen: Mutex::new(Vec::new()),
+        });
+        let backend = SamplingBackend::new(&SamplingConfig::default());
+
+        let response = with_sampler(sampler.clone(), backend.call_pipe(request()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.completion, "{\"thought\": \"Ship it\"}");
+        assert_eq!(response.raw.unwrap().model.as_deref(), Some("client-model"));
+        let seen = sampler.seen.lock().unwrap();
+        assert_eq!(seen[0]["systemPrompt"], "Reason in terse style.");
+        assert_eq!(seen[0]["messages"][0]["role"], "user");
+        assert_eq!(seen[0]["messages"][0]["content"]["text"], "Plan a release");
+        assert_eq!(seen[0]["maxTokens"], 2048);
+        assert_eq!(seen[0]["temperature"], 0.2);
+        assert_eq!(seen[0]["metadata"]["pipe"], "linear-reasoning-v1");
+    }
+
+    #[tokio::test]
+    async fn test_sampling_backend_without_sampler_is_unavailable() {
+        let backend = SamplingBackend::new(&SamplingConfig::default());
+        let err = backend.call_pipe(request()).await.unwrap_err();
+        assert!(matches!(err, LangbaseError::Unavailable { retries: 0, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_sampling_backend_rejects_non_text_content() {
+        let sampler = Arc::new(FakeSampler {
+            reply: serde_json::json!({
+                "role": "assistant",
+                "content": {"type": "image", "data": "...", "mimeType": "image/png"}
+            }),
+            seen: Mutex::new(Vec::new()),
+        });
+        let backend = SamplingBackend::new(&SamplingConfig::default());
+
+        let err = with_sampler(sampler, backend.call_pipe(request()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LangbaseError::InvalidResponse { .. }));
+    }
+}
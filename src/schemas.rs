@@ -0,0 +1,529 @@
+//! Versioned response schemas for pipe completions.
+//!
+//! Every structured response a mode parses from a pipe (linear steps, tree
+//! branches, GoT continuations, detection results, ...) is registered here as
+//! a [`SchemaKind`]. Modes attach the schema to their requests, so the
+//! completion is checked before it is parsed and a malformed answer fails with
+//! the path of the offending field.
+//!
+//! Schemas are versioned. When a prompt update changes the shape a pipe
+//! returns, the new shape is added as the next version of its kind and the
+//! updated prompt is rolled out as a new pipe (see `PIPE_VERSIONS`). Pipes
+//! use the latest version of each kind unless `PIPE_SCHEMA_VERSIONS` pins
+//! them to an older one, so pipes still running the previous prompt keep
+//! being validated against the shape they actually return.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::config::PipeConfig;
+use crate::langbase::OutputSchema;
+
+/// A structured pipe response with a registered schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemaKind {
+    /// Linear reasoning step.
+    Linear,
+    /// Tree reasoning branches.
+    Tree,
+    /// Divergent reasoning perspectives.
+    Divergent,
+    /// Reflection analysis.
+    Reflection,
+    /// Auto mode recommendation.
+    Auto,
+    /// Backtracking continuation.
+    Backtracking,
+    /// GoT continuations.
+    GotGenerate,
+    /// GoT node score.
+    GotScore,
+    /// GoT aggregation.
+    GotAggregate,
+    /// GoT refinement.
+    GotRefine,
+    /// Cognitive bias detections.
+    BiasDetection,
+    /// Logical fallacy detections.
+    FallacyDetection,
+}
+
+impl SchemaKind {
+    /// Every registered kind.
+    pub const ALL: [SchemaKind; 12] = [
+        SchemaKind::Linear,
+        SchemaKind::Tree,
+        SchemaKind::Divergent,
+        SchemaKind::Reflection,
+        SchemaKind::Auto,
+        SchemaKind::Backtracking,
+        SchemaKind::GotGenerate,
+        SchemaKind::GotScore,
+        SchemaKind::GotAggregate,
+        SchemaKind::GotRefine,
+        SchemaKind::BiasDetection,
+        SchemaKind::FallacyDetection,
+    ];
+
+    /// Schema name reported to the model for version 1.
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaKind::Linear => "linear_response",
+            SchemaKind::Tree => "tree_response",
+            SchemaKind::Divergent => "divergent_response",
+            SchemaKind::Reflection => "reflection_response",
+            SchemaKind::Auto => "auto_response",
+            SchemaKind::Backtracking => "backtracking_response",
+            SchemaKind::GotGenerate => "got_generate_response",
+            SchemaKind::GotScore => "got_score_response",
+            SchemaKind::GotAggregate => "got_aggregate_response",
+            SchemaKind::GotRefine => "got_refine_response",
+            SchemaKind::BiasDetection => "bias_detection_response",
+            SchemaKind::FallacyDetection => "fallacy_detection_response",
+        }
+    }
+
+    /// Registered versions of this kind, oldest first.
+    pub fn versions(self) -> impl Iterator<Item = u32> {
+        SCHEMAS
+            .iter()
+            .filter(move |(kind, _, _)| *kind == self)
+            .map(|(_, version, _)| *version)
+    }
+
+    /// Newest registered version.
+    pub fn latest_version(self) -> u32 {
+        self.versions().max().unwrap_or(1)
+    }
+
+    /// Schema of `version`, if registered.
+    pub fn schema(self, version: u32) -> Option<OutputSchema> {
+        SCHEMAS
+            .iter()
+            .find(|(kind, v, _)| *kind == self && *v == version)
+            .map(|(_, _, build)| {
+                let name = match version {
+                    1 => self.name().to_string(),
+                    v => format!("{}_v{}", self.name(), v),
+                };
+                OutputSchema::new(name, build())
+            })
+    }
+
+    /// Schema of the newest version.
+    pub fn latest(self) -> OutputSchema {
+        self.schema(self.latest_version())
+            .expect("every schema kind has a registered version")
+    }
+}
+
+impl fmt::Display for SchemaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Schema versions each pipe's responses are validated against.
+///
+/// A pipe pinned to version N uses, for each kind, the newest version no
+/// newer than N, so one pin covers every kind a shared pipe serves (GoT
+/// generation, scoring, aggregation and refinement all share a pipe).
+/// Unpinned pipes use the latest version of every kind.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    pins: HashMap<String, u32>,
+}
+
+impl SchemaRegistry {
+    /// Create a registry with the given pipe pins.
+    pub fn new(pins: HashMap<String, u32>) -> Self {
+        Self { pins }
+    }
+
+    /// Create a registry from the configured pipe pins.
+    pub fn from_config(pipes: &PipeConfig) -> Self {
+        Self::new(pipes.schema_versions.clone())
+    }
+
+    /// Version of `kind` that responses from `pipe` are validated against.
+    pub fn version_for(&self, kind: SchemaKind, pipe: &str) -> u32 {
+        match self.pins.get(pipe) {
+            Some(&pin) => kind.versions().filter(|&v| v <= pin).max().unwrap_or(1),
+            None => kind.latest_version(),
+        }
+    }
+
+    /// Schema for `kind` responses from `pipe`.
+    pub fn schema(&self, kind: SchemaKind, pipe: &str) -> OutputSchema {
+        kind.schema(self.version_for(kind, pipe))
+            .unwrap_or_else(|| kind.latest())
+    }
+}
+
+/// Builds the JSON Schema document of one schema version
+type SchemaBuilder = fn() -> Value;
+
+/// Registered schema versions: kind, version, and the JSON Schema document
+const SCHEMAS: &[(SchemaKind, u32, SchemaBuilder)] = &[
+    (SchemaKind::Linear, 1, linear_v1),
+    (SchemaKind::Tree, 1, tree_v1),
+    (SchemaKind::Divergent, 1, divergent_v1),
+    (SchemaKind::Reflection, 1, reflection_v1),
+    (SchemaKind::Reflection, 2, reflection_v2),
+    (SchemaKind::Auto, 1, auto_v1),
+    (SchemaKind::Backtracking, 1, backtracking_v1),
+    (SchemaKind::GotGenerate, 1, got_generate_v1),
+    (SchemaKind::GotScore, 1, got_score_v1),
+    (SchemaKind::GotAggregate, 1, got_aggregate_v1),
+    (SchemaKind::GotRefine, 1, got_refine_v1),
+    (SchemaKind::BiasDetection, 1, bias_detection_v1),
+    (SchemaKind::FallacyDetection, 1, fallacy_detection_v1),
+];
+
+fn confidence() -> Value {
+    json!({"type": "number", "minimum": 0, "maximum": 1})
+}
+
+fn strings() -> Value {
+    json!({"type": "array", "items": {"type": "string"}})
+}
+
+fn metadata() -> Value {
+    json!({"type": ["object", "null"]})
+}
+
+fn linear_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["thought", "confidence"],
+        "properties": {
+            "thought": {"type": "string"},
+            "confidence": confidence(),
+            "metadata": metadata()
+        }
+    })
+}
+
+fn tree_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["branches", "recommended_branch"],
+        "properties": {
+            "branches": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["thought", "confidence", "rationale"],
+                    "properties": {
+                        "thought": {"type": "string"},
+                        "confidence": confidence(),
+                        "rationale": {"type": "string"}
+                    }
+                }
+            },
+            "recommended_branch": {"type": "integer", "minimum": 0},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn divergent_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["perspectives", "synthesis"],
+        "properties": {
+            "perspectives": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["thought", "novelty", "viability"],
+                    "properties": {
+                        "thought": {"type": "string"},
+                        "novelty": confidence(),
+                        "viability": confidence(),
+                        "assumptions_challenged": {
+                            "type": ["array", "null"],
+                            "items": {"type": "string"}
+                        }
+                    }
+                }
+            },
+            "synthesis": {"type": "string"},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn reflection_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["analysis", "strengths", "weaknesses", "recommendations", "confidence"],
+        "properties": {
+            "analysis": {"type": "string"},
+            "strengths": strings(),
+            "weaknesses": strings(),
+            "recommendations": strings(),
+            "confidence": confidence(),
+            "quality_score": {"type": ["number", "null"], "minimum": 0, "maximum": 1},
+            "improved_thought": {"type": ["string", "null"]},
+            "metadata": metadata()
+        }
+    })
+}
+
+/// Adds the per-dimension `rubric_scores` asked for when a rubric is given
+fn reflection_v2() -> Value {
+    let mut schema = reflection_v1();
+    schema["properties"]["rubric_scores"] = json!({
+        "type": "object",
+        "additionalProperties": {"type": "number", "minimum": 0, "maximum": 1}
+    });
+    schema
+}
+
+fn auto_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["recommended_mode", "confidence", "rationale"],
+        "properties": {
+            "recommended_mode": {"type": "string"},
+            "confidence": {"type": "number"},
+            "rationale": {"type": "string"},
+            "complexity": {"type": "number"},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn backtracking_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["thought", "confidence"],
+        "properties": {
+            "thought": {"type": "string"},
+            "confidence": {"type": "number"},
+            "context_restored": {"type": "boolean"},
+            "branch_from": {"type": ["string", "null"]},
+            "new_direction": {"type": ["string", "null"]},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn got_generate_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["continuations"],
+        "properties": {
+            "continuations": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["thought"],
+                    "properties": {
+                        "thought": {"type": "string"},
+                        "confidence": {"type": "number"},
+                        "novelty": {"type": "number"},
+                        "rationale": {"type": "string"}
+                    }
+                }
+            },
+            "metadata": metadata()
+        }
+    })
+}
+
+fn got_score_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["overall_score", "breakdown"],
+        "properties": {
+            "overall_score": {"type": "number"},
+            "breakdown": {
+                "type": "object",
+                "properties": {
+                    "relevance": {"type": "number"},
+                    "validity": {"type": "number"},
+                    "depth": {"type": "number"},
+                    "novelty": {"type": "number"}
+                }
+            },
+            "is_terminal_candidate": {"type": "boolean"},
+            "rationale": {"type": "string"},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn got_aggregate_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["aggregated_thought"],
+        "properties": {
+            "aggregated_thought": {"type": "string"},
+            "confidence": {"type": "number"},
+            "sources_used": strings(),
+            "synthesis_approach": {"type": "string"},
+            "conflicts_resolved": strings(),
+            "metadata": metadata()
+        }
+    })
+}
+
+fn got_refine_v1() -> Value {
+    json!({
+        "type": "object",
+        "required": ["refined_thought"],
+        "properties": {
+            "refined_thought": {"type": "string"},
+            "confidence": {"type": "number"},
+            "improvements_made": strings(),
+            "aspects_unchanged": strings(),
+            "quality_delta": {"type": "number"},
+            "metadata": metadata()
+        }
+    })
+}
+
+/// Detection response with `type_field` naming each detection and `score`
+/// rating the whole text
+fn detection(type_field: &str, extra_required: &[&str], score: &str) -> Value {
+    let mut required = vec![type_field, "severity", "confidence", "explanation"];
+    required.extend_from_slice(extra_required);
+    let mut properties = json!({
+        "severity": {"type": "integer"},
+        "confidence": {"type": "number"},
+        "explanation": {"type": "string"},
+        "remediation": {"type": ["string", "null"]},
+        "excerpt": {"type": ["string", "null"]}
+    });
+    for field in std::iter::once(&type_field).chain(extra_required) {
+        properties[*field] = json!({"type": "string"});
+    }
+
+    json!({
+        "type": "object",
+        "required": ["detections", score, "overall_assessment"],
+        "properties": {
+            "detections": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": required,
+                    "properties": properties
+                }
+            },
+            score: {"type": "number"},
+            "overall_assessment": {"type": "string"},
+            "metadata": metadata()
+        }
+    })
+}
+
+fn bias_detection_v1() -> Value {
+    detection("bias_type", &[], "reasoning_quality")
+}
+
+fn fallacy_detection_v1() -> Value {
+    detection("fallacy_type", &["category"], "argument_validity")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::langbase::pipe_fixture;
+
+    #[test]
+    fn test_every_kind_has_a_version() {
+        for kind in SchemaKind::ALL {
+            if kind == SchemaKind::Reflection {
+                continue;
+            }
+            assert_eq!(kind.latest_version(), 1, "{}", kind);
+            assert_eq!(kind.latest().name, kind.name());
+            assert!(kind.schema(2).is_none());
+        }
+    }
+
+    #[test]
+    fn test_reflection_v2_adds_rubric_scores() {
+        let kind = SchemaKind::Reflection;
+        assert_eq!(kind.versions().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(kind.latest().name, "reflection_response_v2");
+
+        let registry = SchemaRegistry::new(HashMap::from([("reflection-v1".to_string(), 1)]));
+        assert_eq!(
+            registry.schema(kind, "reflection-v1").name,
+            "reflection_response"
+        );
+
+        let out_of_range = r#"{
+            "analysis": "a", "strengths": [], "weaknesses": [],
+            "recommendations": [], "confidence": 0.7,
+            "rubric_scores": {"clarity": 1.5}
+        }"#;
+        let violation = kind.latest().validate_completion(out_of_range).unwrap_err();
+        assert!(
+            violation.path.contains("rubric_scores"),
+            "{}",
+            violation.path
+        );
+        assert!(kind
+            .schema(1)
+            .unwrap()
+            .validate_completion(out_of_range)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_offline_fixtures_match_their_schemas() {
+        let cases = [
+            (SchemaKind::Linear, "linear-reasoning-v1"),
+            (SchemaKind::Tree, "tree-reasoning-v1"),
+            (SchemaKind::Divergent, "divergent-reasoning-v1"),
+            (SchemaKind::Reflection, "reflection-v1"),
+            (SchemaKind::Auto, "mode-router-v1"),
+            (SchemaKind::Backtracking, "backtracking-reasoning-v1"),
+            (SchemaKind::GotGenerate, "got-reasoning-v1"),
+            (SchemaKind::GotScore, "got-reasoning-v1"),
+            (SchemaKind::GotAggregate, "got-reasoning-v1"),
+            (SchemaKind::GotRefine, "got-reasoning-v1"),
+            (SchemaKind::BiasDetection, "detection-v1"),
+            (SchemaKind::FallacyDetection, "detection-v1"),
+        ];
+        for (kind, pipe) in cases {
+            let fixture = pipe_fixture(pipe).unwrap();
+            assert!(
+                kind.latest().validate_completion(fixture).is_ok(),
+                "{} fixture does not match {}",
+                pipe,
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_detection_schema_requires_type_fields() {
+        let schema = SchemaKind::FallacyDetection.latest();
+        let missing_category = r#"{
+            "detections": [{"fallacy_type": "straw_man", "severity": 3,
+                            "confidence": 0.8, "explanation": "x"}],
+            "argument_validity": 0.4,
+            "overall_assessment": "weak"
+        }"#;
+        let violation = schema.validate_completion(missing_category).unwrap_err();
+        assert_eq!(violation.path, "$.detections[0].category");
+    }
+
+    #[test]
+    fn test_registry_pins_pipes() {
+        let registry = SchemaRegistry::new(HashMap::from([("got-v1".to_string(), 1)]));
+        assert_eq!(registry.version_for(SchemaKind::GotScore, "got-v1"), 1);
+        assert_eq!(registry.version_for(SchemaKind::GotScore, "got-v2"), 1);
+        assert_eq!(
+            registry.schema(SchemaKind::GotScore, "got-v1").name,
+            "got_score_response"
+        );
+    }
+}
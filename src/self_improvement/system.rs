@@ -42,7 +42,7 @@ use super::{
     SelfImprovementConfig, SelfImprovementPipes,
 };
 use crate::langbase::LangbaseClient;
-use crate::storage::SqliteStorage;
+use crate::storage::SharedStorage;
 
 // ============================================================================
 // Error Types
@@ -277,7 +277,7 @@ impl SelfImprovementSystem {
     /// # Arguments
     ///
     /// * `config` - System configuration
-    /// * `storage` - SQLite storage backend
+    /// * `storage` - Storage backend
     /// * `langbase` - Langbase API client for pipe calls
     ///
     /// # Returns
@@ -285,7 +285,7 @@ impl SelfImprovementSystem {
     /// A new `SelfImprovementSystem` instance.
     pub fn new(
         config: SelfImprovementConfig,
-        _storage: SqliteStorage,
+        _storage: SharedStorage,
         langbase: LangbaseClient,
     ) -> Self {
         info!(
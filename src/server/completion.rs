@@ -0,0 +1,266 @@
+//! Argument completion for `completion/complete`.
+//!
+//! Clients ask for the values an argument may take as the user types it.
+//! IDs are completed from live storage, so only sessions, branches and
+//! checkpoints that exist are offered; branch and checkpoint IDs need the
+//! `session_id` already chosen in the request context. Preset IDs come from
+//! the preset registry and modes from [`ReasoningMode`]; any other argument
+//! declared with an `enum` in a tool's input schema, such as an export format
+//! or merge strategy, is completed from it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use super::mcp::all_tools;
+use super::SharedState;
+use crate::error::{McpError, McpResult};
+use crate::modes::ReasoningMode;
+use crate::storage::SessionFilter;
+
+/// Most values returned in one completion, per the MCP specification.
+pub const MAX_COMPLETION_VALUES: usize = 100;
+
+/// What the completed argument belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    /// An argument of a prompt.
+    #[serde(rename = "ref/prompt")]
+    Prompt {
+        /// Prompt name.
+        name: String,
+    },
+    /// A variable of a resource template.
+    #[serde(rename = "ref/resource")]
+    Resource {
+        /// Resource template URI.
+        uri: String,
+    },
+}
+
+/// The argument being completed and what has been typed so far.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionArgument {
+    /// Argument name.
+    pub name: String,
+    /// Partial value typed so far.
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Arguments the client has already filled in.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CompletionContext {
+    /// Values of the other arguments, by name.
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+/// Parameters for a completion/complete request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompleteParams {
+    /// What the argument belongs to.
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    /// The argument to complete.
+    pub argument: CompletionArgument,
+    /// Arguments already filled in.
+    #[serde(default)]
+    pub context: CompletionContext,
+}
+
+/// Values offered for an argument.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Completion {
+    /// Matching values, at most [`MAX_COMPLETION_VALUES`].
+    pub values: Vec<String>,
+    /// Number of matching values, including those left out.
+    pub total: usize,
+    /// Whether matching values were left out.
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
+impl Completion {
+    /// Keep the `candidates` starting with `prefix`, in order.
+    pub fn matching(candidates: impl IntoIterator<Item = String>, prefix: &str) -> Self {
+        let mut values: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect();
+        let total = values.len();
+        values.truncate(MAX_COMPLETION_VALUES);
+        Self {
+            has_more: total > values.len(),
+            values,
+            total,
+        }
+    }
+}
+
+/// Complete `params.argument` from storage, presets and tool schemas.
+pub async fn complete(state: &SharedState, params: &CompleteParams) -> McpResult<Completion> {
+    let prefix = params.argument.value.as_str();
+    let context = &params.context.arguments;
+    let storage_error = |e: crate::error::StorageError| McpError::ExecutionFailed {
+        message: format!("Failed to complete {}: {}", params.argument.name, e),
+    };
+
+    let candidates: Vec<String> = match params.argument.name.as_str() {
+        // The session resource template names its variable `id`
+        "session_id" | "id" => state
+            .storage
+            .list_sessions(SessionFilter::default())
+            .await
+            .map_err(storage_error)?
+            .into_iter()
+            .map(|session| session.id)
+            .collect(),
+        "branch_id" | "parent_branch_id" => match context.get("session_id") {
+            Some(session_id) => state
+                .storage
+                .get_session_branches(session_id)
+                .await
+                .map_err(storage_error)?
+                .into_iter()
+                .map(|branch| branch.id)
+                .collect(),
+            None => Vec::new(),
+        },
+        "checkpoint_id" => match context.get("session_id") {
+            Some(session_id) => state
+                .storage
+                .get_session_checkpoints(session_id)
+                .await
+                .map_err(storage_error)?
+                .into_iter()
+                .map(|checkpoint| checkpoint.id)
+                .collect(),
+            None => Vec::new(),
+        },
+        "mode" => ReasoningMode::ALL
+            .iter()
+            .map(|mode| mode.as_str().to_string())
+            .collect(),
+        "preset_id" => state
+            .preset_registry
+            .list(None)
+            .into_iter()
+            .map(|preset| preset.id)
+            .collect(),
+        name => schema_enum_values(name),
+    };
+
+    Ok(Completion::matching(candidates, prefix))
+}
+
+/// Values any tool's input schema allows for the property `name`, sorted
+fn schema_enum_values(name: &str) -> Vec<String> {
+    let mut values = BTreeSet::new();
+    for tool in all_tools() {
+        collect_enum_values(&tool.input_schema, name, &mut values);
+    }
+    values.into_iter().collect()
+}
+
+/// Add the `enum` values of every property `name` in `schema`, at any depth
+fn collect_enum_values(schema: &serde_json::Value, name: &str, values: &mut BTreeSet<String>) {
+    let Some(object) = schema.as_object() else {
+        if let Some(items) = schema.as_array() {
+            for item in items {
+                collect_enum_values(item, name, values);
+            }
+        }
+        return;
+    };
+    if let Some(property) = object.get("properties").and_then(|p| p.get(name)) {
+        let allowed = property
+            .get("enum")
+            .or_else(|| property.get("items").and_then(|items| items.get("enum")));
+        if let Some(allowed) = allowed.and_then(|a| a.as_array()) {
+            values.extend(
+                allowed
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_string),
+            );
+        }
+    }
+    for value in object.values() {
+        collect_enum_values(value, name, values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_matching_filters_and_caps() {
+        let completion = Completion::matching(
+            vec!["alpha".to_string(), "beta".to_string(), "alps".to_string()],
+            "al",
+        );
+        assert_eq!(completion.values, vec!["alpha", "alps"]);
+        assert_eq!(completion.total, 2);
+        assert!(!completion.has_more);
+
+        let completion = Completion::matching((0..150).map(|i| format!("s{}", i)), "");
+        assert_eq!(completion.values.len(), MAX_COMPLETION_VALUES);
+        assert_eq!(completion.total, 150);
+        assert!(completion.has_more);
+    }
+
+    #[test]
+    fn test_collect_enum_values_at_any_depth() {
+        let schema = json!({
+            "properties": {
+                "mode": {"type": "string", "enum": ["linear", "tree"]},
+                "steps": {
+                    "type": "array",
+                    "items": {"properties": {"mode": {"enum": ["got"]}}}
+                }
+            }
+        });
+        let mut values = BTreeSet::new();
+        collect_enum_values(&schema, "mode", &mut values);
+        assert_eq!(
+            values.into_iter().collect::<Vec<_>>(),
+            ["got", "linear", "tree"]
+        );
+    }
+
+    #[test]
+    fn test_schema_enum_values_from_tools() {
+        assert_eq!(
+            schema_enum_values("format"),
+            ["ascii", "dot", "graphml", "mermaid"]
+        );
+        assert!(schema_enum_values("merge_strategy").contains(&"interleave".to_string()));
+        assert!(schema_enum_values("no_such_argument").is_empty());
+    }
+
+    #[test]
+    fn test_complete_params_deserialize() {
+        let params: CompleteParams = serde_json::from_value(json!({
+            "ref": {"type": "ref/resource", "uri": "reasoning://session/{id}"},
+            "argument": {"name": "id", "value": "ab"}
+        }))
+        .unwrap();
+        assert_eq!(
+            params.reference,
+            CompletionReference::Resource {
+                uri: "reasoning://session/{id}".to_string()
+            }
+        );
+        assert_eq!(params.argument.value, "ab");
+        assert!(params.context.arguments.is_empty());
+
+        assert!(serde_json::from_value::<CompleteParams>(json!({
+            "ref": {"type": "ref/tool", "name": "x"},
+            "argument": {"name": "id", "value": ""}
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,407 @@
+//! Critique of reasoning tool results.
+//!
+//! Every tool that calls a pipe accepts a `critique` argument. When it is
+//! set, the tool's result is reviewed by reflection and by bias and fallacy
+//! detection once the tool finishes, and the findings are attached to the
+//! result as `critique`. With `critique: {"revise": true}`, a result the
+//! review finds problems with also gets one revision round.
+//!
+//! A failed review step is reported in the critique's `errors`; it never
+//! fails the tool call it reviews.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::mcp::{all_tools, Tool};
+use super::SharedState;
+use crate::error::{McpError, McpResult};
+use crate::modes::{DetectBiasesParams, DetectFallaciesParams, ImprovedThought, ReflectionParams};
+use crate::storage::Detection;
+
+/// Tools that are themselves a critique step
+const CRITIQUE_TOOLS: &[&str] = &[
+    "reasoning_reflection",
+    "reasoning_reflection_evaluate",
+    "reasoning_detect_biases",
+    "reasoning_detect_fallacies",
+];
+
+/// Reflection quality below which a result needs revision
+const REVISION_QUALITY: f64 = 0.8;
+
+/// Most characters of a result's text sent for review
+const MAX_CRITIQUE_CHARS: usize = 6000;
+
+/// Options of the `critique` argument.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct CritiqueOptions {
+    /// Run one revision round when the review finds problems
+    #[serde(default)]
+    pub revise: bool,
+}
+
+impl CritiqueOptions {
+    /// Remove the `critique` argument from `arguments` and parse it.
+    ///
+    /// Returns `None` if the argument is missing or `false`.
+    pub fn take(tool_name: &str, arguments: &mut Option<Value>) -> McpResult<Option<Self>> {
+        let Some(value) = arguments
+            .as_mut()
+            .and_then(Value::as_object_mut)
+            .and_then(|arguments| arguments.remove("critique"))
+        else {
+            return Ok(None);
+        };
+
+        let options = match value {
+            Value::Null | Value::Bool(false) => return Ok(None),
+            Value::Bool(true) => Self::default(),
+            Value::Object(_) => serde_json::from_value(value)
+                .map_err(|e| invalid_critique(tool_name, format!("critique: {}", e)))?,
+            _ => {
+                return Err(invalid_critique(
+                    tool_name,
+                    "critique must be a boolean or an object".to_string(),
+                ))
+            }
+        };
+        if !supports_critique(tool_name) {
+            return Err(invalid_critique(
+                tool_name,
+                format!("{} does not support critique", tool_name),
+            ));
+        }
+        Ok(Some(options))
+    }
+}
+
+fn invalid_critique(tool_name: &str, message: String) -> McpError {
+    McpError::InvalidParameters {
+        tool_name: tool_name.to_string(),
+        message,
+    }
+}
+
+/// Findings of the review of a tool result.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CritiqueReport {
+    /// Session the review's thoughts and detections were stored in
+    pub session_id: Option<String>,
+    /// ID of the reflection thought holding the review
+    pub reflection_thought_id: Option<String>,
+    /// Quality of the result according to reflection (0.0-1.0)
+    pub quality_score: Option<f64>,
+    /// Strengths reflection found
+    pub strengths: Vec<String>,
+    /// Weaknesses reflection found
+    pub weaknesses: Vec<String>,
+    /// Recommendations from reflection
+    pub recommendations: Vec<String>,
+    /// Biases and fallacies found in the result
+    pub detections: Vec<Detection>,
+    /// Whether quality is low or the result has biases or fallacies
+    pub needs_revision: bool,
+    /// Revised version of the result, when revision was asked for and needed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<ImprovedThought>,
+    /// Review steps that failed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Whether calls to `tool` may ask for a critique: tools calling a pipe,
+/// other than the critique tools themselves.
+pub(super) fn accepts_critique(tool: &Tool) -> bool {
+    !CRITIQUE_TOOLS.contains(&tool.name.as_str())
+        && tool
+            .input_schema
+            .pointer("/properties/generation")
+            .is_some()
+}
+
+/// Schema of the `critique` argument
+pub(super) fn critique_schema() -> Value {
+    serde_json::json!({
+        "description": "Review the result with reflection and bias/fallacy detection. true, or an object with options",
+        "oneOf": [
+            { "type": "boolean" },
+            {
+                "type": "object",
+                "properties": {
+                    "revise": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Run one revision round when the review finds problems"
+                    }
+                }
+            }
+        ]
+    })
+}
+
+/// Whether `tool_name` accepts the `critique` argument
+pub fn supports_critique(tool_name: &str) -> bool {
+    static TOOLS: OnceLock<HashSet<String>> = OnceLock::new();
+    TOOLS
+        .get_or_init(|| {
+            all_tools()
+                .into_iter()
+                .filter(|tool| tool.input_schema.pointer("/properties/critique").is_some())
+                .map(|tool| tool.name)
+                .collect()
+        })
+        .contains(tool_name)
+}
+
+/// Review `result` and attach the findings to it as `critique`.
+///
+/// The review is stored in the result's session, or else in `session_id`.
+pub async fn critique_result(
+    state: &SharedState,
+    options: CritiqueOptions,
+    result: &mut Value,
+    session_id: Option<String>,
+) {
+    let session_id = result
+        .get("session_id")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or(session_id);
+    let content = critique_text(result);
+    let report = if content.is_empty() {
+        CritiqueReport {
+            session_id,
+            errors: vec!["result has no text to critique".to_string()],
+            ..Default::default()
+        }
+    } else {
+        review(state, options, &content, session_id).await
+    };
+
+    if let Value::Object(fields) = result {
+        match serde_json::to_value(&report) {
+            Ok(report) => {
+                fields.insert("critique".to_string(), report);
+            }
+            Err(e) => warn!(error = %e, "Failed to serialize critique"),
+        }
+    }
+}
+
+async fn review(
+    state: &SharedState,
+    options: CritiqueOptions,
+    content: &str,
+    session_id: Option<String>,
+) -> CritiqueReport {
+    let mut report = CritiqueReport {
+        session_id,
+        ..Default::default()
+    };
+    debug!(
+        chars = content.len(),
+        revise = options.revise,
+        "Critiquing tool result"
+    );
+
+    let mut params = ReflectionParams::for_content(content).with_max_iterations(1);
+    if let Some(ref session_id) = report.session_id {
+        params = params.with_session(session_id);
+    }
+    match state.reflection_mode.process(params).await {
+        Ok(reflection) => {
+            report.session_id = Some(reflection.session_id);
+            report.reflection_thought_id = Some(reflection.reflection_thought_id);
+            report.quality_score = Some(reflection.quality_score);
+            report.strengths = reflection.strengths;
+            report.weaknesses = reflection.weaknesses;
+            report.recommendations = reflection.recommendations;
+        }
+        Err(e) => report.errors.push(format!("reflection: {}", e)),
+    }
+
+    let biases = DetectBiasesParams {
+        content: Some(content.to_string()),
+        thought_id: None,
+        session_id: report.session_id.clone(),
+        check_types: None,
+        generation: Default::default(),
+    };
+    match state.detection_mode.detect_biases(biases).await {
+        Ok(biases) => report.detections.extend(biases.detections),
+        Err(e) => report.errors.push(format!("bias detection: {}", e)),
+    }
+
+    let fallacies = DetectFallaciesParams {
+        content: Some(content.to_string()),
+        thought_id: None,
+        session_id: report.session_id.clone(),
+        check_formal: true,
+        check_informal: true,
+        generation: Default::default(),
+    };
+    match state.detection_mode.detect_fallacies(fallacies).await {
+        Ok(fallacies) => report.detections.extend(fallacies.detections),
+        Err(e) => report.errors.push(format!("fallacy detection: {}", e)),
+    }
+
+    report.needs_revision = report
+        .quality_score
+        .is_some_and(|quality| quality < REVISION_QUALITY)
+        || !report.detections.is_empty();
+    if !(options.revise && report.needs_revision) {
+        return report;
+    }
+
+    let mut params =
+        ReflectionParams::for_content(revision_request(content, &report)).with_max_iterations(1);
+    if let Some(ref session_id) = report.session_id {
+        params = params.with_session(session_id);
+    }
+    match state.reflection_mode.process(params).await {
+        Ok(revision) => report.revision = revision.improved_thought,
+        Err(e) => report.errors.push(format!("revision: {}", e)),
+    }
+    report
+}
+
+/// Content for the revision round: the result followed by the problems the
+/// review found in it
+fn revision_request(content: &str, report: &CritiqueReport) -> String {
+    let mut request = format!("{}\n\nProblems found in review:", content);
+    for weakness in &report.weaknesses {
+        request.push_str(&format!("\n- {}", weakness));
+    }
+    for detection in &report.detections {
+        request.push_str(&format!(
+            "\n- {}: {}",
+            detection.detected_issue, detection.explanation
+        ));
+    }
+    request.push_str("\n\nRevise the reasoning above so it no longer has these problems.");
+    request
+}
+
+/// The reasoning text of a result: its string values, without IDs and
+/// timestamps, cut to `MAX_CRITIQUE_CHARS`
+fn critique_text(result: &Value) -> String {
+    let mut parts = Vec::new();
+    collect_text(result, &mut parts);
+    let text = parts.join("\n");
+    match text.char_indices().nth(MAX_CRITIQUE_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text,
+    }
+}
+
+fn collect_text<'a>(value: &'a Value, parts: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) if !text.trim().is_empty() => parts.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_text(item, parts)),
+        Value::Object(fields) => fields
+            .iter()
+            .filter(|(key, _)| !is_metadata_key(key))
+            .for_each(|(_, value)| collect_text(value, parts)),
+        _ => {}
+    }
+}
+
+fn is_metadata_key(key: &str) -> bool {
+    key == "id"
+        || key == "timestamp"
+        || key.ends_with("_id")
+        || key.ends_with("_ids")
+        || key.ends_with("_at")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_critique_options_take() {
+        let mut arguments = Some(json!({"content": "x", "critique": true}));
+        let options = CritiqueOptions::take("reasoning_linear", &mut arguments).unwrap();
+        assert_eq!(options, Some(CritiqueOptions { revise: false }));
+        // The argument is removed before the tool sees it
+        assert_eq!(arguments, Some(json!({"content": "x"})));
+
+        let mut arguments = Some(json!({"critique": {"revise": true}}));
+        let options = CritiqueOptions::take("reasoning_linear", &mut arguments).unwrap();
+        assert_eq!(options, Some(CritiqueOptions { revise: true }));
+
+        let mut arguments = Some(json!({"critique": false}));
+        assert_eq!(
+            CritiqueOptions::take("reasoning_linear", &mut arguments).unwrap(),
+            None
+        );
+        let mut arguments = None;
+        assert_eq!(
+            CritiqueOptions::take("reasoning_linear", &mut arguments).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_critique_options_rejected() {
+        for (tool, critique) in [
+            ("reasoning_linear", json!("yes")),
+            ("reasoning_linear", json!({"revise": "yes"})),
+            ("reasoning_reflection", json!(true)),
+            ("reasoning_session_list", json!(true)),
+        ] {
+            let mut arguments = Some(json!({ "critique": critique }));
+            let err = CritiqueOptions::take(tool, &mut arguments).unwrap_err();
+            assert!(
+                matches!(err, McpError::InvalidParameters { .. }),
+                "{}",
+                tool
+            );
+        }
+    }
+
+    #[test]
+    fn test_supports_critique() {
+        assert!(supports_critique("reasoning_linear"));
+        assert!(supports_critique("reasoning_tree"));
+        assert!(!supports_critique("reasoning_reflection"));
+        assert!(!supports_critique("reasoning_detect_biases"));
+        assert!(!supports_critique("reasoning_session_list"));
+        assert!(!supports_critique("no_such_tool"));
+    }
+
+    #[test]
+    fn test_critique_text_skips_metadata() {
+        let result = json!({
+            "thought_id": "abc",
+            "session_id": "def",
+            "created_at": "2024-01-01T00:00:00Z",
+            "content": "Prices rise",
+            "confidence": 0.8,
+            "steps": [{"id": "1", "text": "so demand falls"}, {"text": "  "}],
+        });
+        assert_eq!(critique_text(&result), "Prices rise\nso demand falls");
+    }
+
+    #[test]
+    fn test_critique_text_truncated() {
+        let result = json!({ "content": "é".repeat(MAX_CRITIQUE_CHARS + 10) });
+        assert_eq!(critique_text(&result).chars().count(), MAX_CRITIQUE_CHARS);
+    }
+
+    #[test]
+    fn test_revision_request_lists_problems() {
+        let report = CritiqueReport {
+            weaknesses: vec!["No evidence".to_string()],
+            ..Default::default()
+        };
+        let request = revision_request("Prices rise", &report);
+        assert!(request.starts_with("Prices rise"));
+        assert!(request.contains("- No evidence"));
+    }
+}
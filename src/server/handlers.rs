@@ -1,21 +1,49 @@
+use schemars::schema_for;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
 
-use super::SharedState;
-use crate::error::{McpError, McpResult};
+use super::{
+    critique_result, current_quota_client, holds_session, server_status, validate_arguments,
+    with_held_session, CritiqueOptions, QuotaStatus, ServerStatus, SessionGuard, SharedState,
+    ToolAnnotations,
+};
+use crate::error::{AppError, McpError, McpResult};
+use crate::modes::{check_pipes, pipe_probes, rubric_trends};
 use crate::modes::{
-    AutoBacktrackParams, AutoParams, BacktrackingParams, CounterfactualParams, DecisionParams,
-    DetectBiasesParams, DetectFallaciesParams, DivergentParams, EvidenceParams, GotAggregateParams,
-    GotFinalizeParams, GotGenerateParams, GotGetStateParams, GotInitParams, GotPruneParams,
-    GotRefineParams, GotScoreParams, LinearParams, MCTSExploreParams, PerspectiveParams,
-    ProbabilisticParams, ReflectionParams, TimelineBranchParams, TimelineCompareParams,
-    TimelineCreateParams, TimelineMergeParams, TreeParams,
+    AutoBacktrackParams, AutoBacktrackResult, AutoParams, AutoResult, BacktrackingParams,
+    BacktrackingResult, CalcParams, CalcResult, CheckpointDiff, CheckpointDiffParams,
+    CodeFindingsParams, CodeFindingsResult, CounterfactualParams, CounterfactualResult,
+    DebateParams, DebateResult, DecisionParams, DecisionResult, DetectBiasesParams,
+    DetectBiasesResult, DetectFallaciesParams, DetectFallaciesResult, DialecticParams,
+    DialecticResult, DivergentEvaluateParams, DivergentEvaluateResult, DivergentParams,
+    DivergentResult, EdgeCasesParams, EdgeCasesResult, EstimateParams, EstimateResult,
+    EvidenceParams, EvidenceResult, ExplainDiffParams, ExplainDiffResult, GotAggregateBestParams,
+    GotAggregateBestResult, GotAggregateParams, GotAggregateResult, GotBestPathParams,
+    GotBestPathResult, GotExportParams, GotExportResult, GotFinalizeParams, GotFinalizeResult,
+    GotGenerateParams, GotGenerateResult, GotGetStateParams, GotInitParams, GotInitResult,
+    GotPruneParams, GotPruneResult, GotRankParams, GotRankResult, GotRefineParams, GotRefineResult,
+    GotResumeParams, GotResumeResult, GotScoreParams, GotScoreResult, GotStateResult,
+    GotValidateParams, GotValidateResult, HypothesisObserveParams, HypothesisObserveResult,
+    HypothesisParams, HypothesisResult, LinearParams, LinearResult, MCTSExploreParams,
+    MCTSExploreResult, MinimalChangeParams, MinimalChangeResult, NegotiationParams,
+    NegotiationResult, PerspectiveParams, PerspectiveResult, PlanParams, PlanResult,
+    PlanReviseParams, PlanReviseResult, PrioritizeParams, PrioritizeResult, PrioritizeReviseParams,
+    ProbabilisticParams, ProbabilisticResult, ReflectionParams, ReflectionResult, RiskParams,
+    RiskRegisterResult, RiskUpdateParams, RiskUpdateResult, RootCauseParams, RootCauseResult,
+    RoutingFeedbackParams, RoutingFeedbackResult, SixHatsParams, SixHatsResult, SwotListParams,
+    SwotListResult, SwotParams, SwotResult, SynthesizeParams, SynthesizeResult, SystemsParams,
+    SystemsResult, TimelineBranchParams, TimelineBranchResult, TimelineCompareParams,
+    TimelineCompareResult, TimelineCreateParams, TimelineCreateResult, TimelineMergeParams,
+    TimelineMergeResult, TraceFlowParams, TraceFlowResult, TreeCompareParams, TreeCompareResult,
+    TreeExportParams, TreeExportResult, TreeMergeParams, TreeMergeResult, TreeParams, TreeResult,
 };
 use crate::presets::execute_preset;
-use crate::self_improvement::InvocationEvent;
-use crate::storage::BranchState;
+use crate::providers::with_budget_session;
+use crate::self_improvement::{CircuitState, InvocationEvent};
+use crate::storage::{BranchState, Page, Pagination};
 
 // ============================================================================
 // Auxiliary Handler Param Structs
@@ -35,6 +63,12 @@ pub struct TreeFocusParams {
 pub struct TreeListParams {
     /// Session ID to list branches for
     pub session_id: String,
+    /// Maximum number of branches to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Cursor from the previous page's `next_cursor`
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// Parameters for tree complete operation
@@ -74,47 +108,170 @@ pub struct CheckpointCreateParams {
 pub struct CheckpointListParams {
     /// Session ID to list checkpoints for
     pub session_id: String,
+    /// Maximum number of checkpoints to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Cursor from the previous page's `next_cursor`
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// Route tool calls to appropriate handlers
 pub async fn handle_tool_call(
     state: &SharedState,
     tool_name: &str,
-    arguments: Option<Value>,
+    mut arguments: Option<Value>,
 ) -> McpResult<Value> {
     info!(tool = %tool_name, "Routing tool call");
 
+    // The critique option is handled here, not by the tool
+    let critique = CritiqueOptions::take(tool_name, &mut arguments)?;
+
+    // Reject malformed arguments before they reach storage or a pipe
+    validate_arguments(&state.config.tools, tool_name, arguments.as_ref())?;
+
+    // Mutating calls on one session run one at a time, so clients sharing
+    // the server cannot interleave their changes to it
+    let audited = is_mutating_tool(tool_name);
+    let changed_session = if audited {
+        mutated_session_id(state, arguments.as_ref()).await
+    } else {
+        None
+    };
+    // Calls made on behalf of one holding the session, such as preset steps,
+    // already have its lock
+    let locked_session = changed_session
+        .clone()
+        .filter(|session_id| !holds_session(session_id));
+    let _session_guard = match &locked_session {
+        Some(session_id) => Some(lock_session(state, session_id).await?),
+        None => None,
+    };
+
+    // Capture the session state before mutating calls for the audit log
+    let audit_session_id = if audited {
+        argument_session_id(arguments.as_ref())
+    } else {
+        None
+    };
+    let before_hash = match audit_session_id {
+        Some(ref session_id) => session_hash(state, session_id).await,
+        None => None,
+    };
+
     // Start timing for self-improvement tracking
     let start = std::time::Instant::now();
 
-    let result = match tool_name {
+    // Pipe calls for a session are charged to its spend budget, including
+    // those of the critique
+    let budget_session = argument_session_id(arguments.as_ref());
+    let critique_session = budget_session.clone();
+    let call = async move {
+        let mut result = route_tool_call(state, tool_name, arguments).await?;
+        if let Some(options) = critique {
+            critique_result(state, options, &mut result, critique_session).await;
+        }
+        Ok(result)
+    };
+    let call = async move {
+        match locked_session {
+            Some(session_id) => with_held_session(session_id, call).await,
+            None => call.await,
+        }
+    };
+    let result = match budget_session {
+        Some(session_id) => with_budget_session(session_id, call).await,
+        None => call.await,
+    };
+
+    // Record invocation for self-improvement system (if enabled)
+    let latency_ms = start.elapsed().as_millis() as i64;
+    let success = result.is_ok();
+
+    // Extract quality score from response if available
+    let quality_score = result
+        .as_ref()
+        .ok()
+        .and_then(|v| v.get("confidence"))
+        .and_then(|c| c.as_f64());
+
+    state
+        .record_invocation(InvocationEvent {
+            tool_name: tool_name.to_string(),
+            latency_ms,
+            success,
+            quality_score,
+            timestamp: chrono::Utc::now(),
+        })
+        .await;
+
+    if audited && !matches!(result, Err(McpError::UnknownTool { .. })) {
+        record_audit(state, tool_name, audit_session_id, before_hash, &result).await;
+    }
+
+    // Tell subscribers of the session; calls starting one name it in their result
+    if let (true, Ok(value)) = (audited, &result) {
+        let session_id = changed_session.or_else(|| {
+            value
+                .get("session_id")
+                .and_then(Value::as_str)
+                .map(String::from)
+        });
+        if let Some(session_id) = session_id {
+            state.session_events.publish(&session_id, tool_name);
+        }
+    }
+
+    result
+}
+
+/// Dispatch a tool call to its handler
+async fn route_tool_call(
+    state: &SharedState,
+    tool_name: &str,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    match tool_name {
         // Phase 1-2 tools
         "reasoning_linear" => handle_linear(state, arguments).await,
         "reasoning_tree" => handle_tree(state, arguments).await,
         "reasoning_tree_focus" => handle_tree_focus(state, arguments).await,
         "reasoning_tree_list" => handle_tree_list(state, arguments).await,
         "reasoning_tree_complete" => handle_tree_complete(state, arguments).await,
+        "reasoning_tree_compare" => handle_tree_compare(state, arguments).await,
+        "reasoning_tree_merge" => handle_tree_merge(state, arguments).await,
+        "reasoning_tree_export" => handle_tree_export(state, arguments).await,
         "reasoning_divergent" => handle_divergent(state, arguments).await,
+        "reasoning_divergent_evaluate" => handle_divergent_evaluate(state, arguments).await,
         "reasoning_reflection" => handle_reflection(state, arguments).await,
         "reasoning_reflection_evaluate" => handle_reflection_evaluate(state, arguments).await,
         // Phase 3 tools - Backtracking
         "reasoning_backtrack" => handle_backtrack(state, arguments).await,
         "reasoning_checkpoint_create" => handle_checkpoint_create(state, arguments).await,
         "reasoning_checkpoint_list" => handle_checkpoint_list(state, arguments).await,
+        "reasoning_backtrack_diff" => handle_backtrack_diff(state, arguments).await,
         // Phase 3 tools - Auto Router
         "reasoning_auto" => handle_auto(state, arguments).await,
+        "reasoning_routing_feedback" => handle_routing_feedback(state, arguments).await,
         // Phase 3 tools - Graph-of-Thoughts
         "reasoning_got_init" => handle_got_init(state, arguments).await,
         "reasoning_got_generate" => handle_got_generate(state, arguments).await,
         "reasoning_got_score" => handle_got_score(state, arguments).await,
+        "reasoning_got_rank" => handle_got_rank(state, arguments).await,
         "reasoning_got_aggregate" => handle_got_aggregate(state, arguments).await,
+        "reasoning_got_aggregate_best" => handle_got_aggregate_best(state, arguments).await,
         "reasoning_got_refine" => handle_got_refine(state, arguments).await,
         "reasoning_got_prune" => handle_got_prune(state, arguments).await,
         "reasoning_got_finalize" => handle_got_finalize(state, arguments).await,
         "reasoning_got_state" => handle_got_state(state, arguments).await,
+        "reasoning_got_export" => handle_got_export(state, arguments).await,
+        "reasoning_got_validate" => handle_got_validate(state, arguments).await,
+        "reasoning_got_best_path" => handle_got_best_path(state, arguments).await,
+        "reasoning_got_resume" => handle_got_resume(state, arguments).await,
         // Phase 4 tools - Bias & Fallacy Detection
         "reasoning_detect_biases" => handle_detect_biases(state, arguments).await,
         "reasoning_detect_fallacies" => handle_detect_fallacies(state, arguments).await,
+        "reasoning_verify_calculations" => handle_verify_calculations(state, arguments).await,
         // Phase 5 tools - Workflow Presets
         "reasoning_preset_list" => handle_preset_list(state, arguments).await,
         "reasoning_preset_run" => handle_preset_run(state, arguments).await,
@@ -129,6 +286,27 @@ pub async fn handle_tool_call(
         "reasoning_metrics_invocations" => handle_metrics_invocations(state, arguments).await,
         "reasoning_fallback_metrics" => handle_fallback_metrics(state).await,
         "reasoning_debug_config" => handle_debug_config(state).await,
+        "reasoning_pipe_status" => handle_pipe_status(state).await,
+        "reasoning_pipe_health" => handle_pipe_health(state, arguments).await,
+        "reasoning_budget_status" => handle_budget_status(state, arguments).await,
+        "reasoning_quota_status" => handle_quota_status(state).await,
+        "reasoning_server_status" => handle_server_status(state).await,
+        // Search tools
+        "reasoning_search" => handle_search(state, arguments).await,
+        "reasoning_similar" => handle_similar(state, arguments).await,
+        "reasoning_session_thoughts" => handle_session_thoughts(state, arguments).await,
+        // Thought revision tools
+        "reasoning_revise" => handle_revise(state, arguments).await,
+        "reasoning_thought_history" => handle_thought_history(state, arguments).await,
+        // Session organization tools
+        "reasoning_session_tag" => handle_session_tag(state, arguments).await,
+        "reasoning_session_list" => handle_session_list(state, arguments).await,
+        "reasoning_session_delete" => handle_session_delete(state, arguments).await,
+        "reasoning_session_restore" => handle_session_restore(state, arguments).await,
+        "reasoning_session_fork" => handle_session_fork(state, arguments).await,
+        // Knowledge base tools
+        "reasoning_knowledge_query" => handle_knowledge_query(state, arguments).await,
+        "reasoning_knowledge_promote" => handle_knowledge_promote(state, arguments).await,
         // Phase 6 tools - Time Machine (Timeline, MCTS, Counterfactual)
         "reasoning_timeline_create" => handle_timeline_create(state, arguments).await,
         "reasoning_timeline_branch" => handle_timeline_branch(state, arguments).await,
@@ -137,33 +315,394 @@ pub async fn handle_tool_call(
         "reasoning_mcts_explore" => handle_mcts_explore(state, arguments).await,
         "reasoning_auto_backtrack" => handle_auto_backtrack(state, arguments).await,
         "reasoning_counterfactual" => handle_counterfactual(state, arguments).await,
+        "reasoning_counterfactual_minimal_change" => {
+            handle_counterfactual_minimal_change(state, arguments).await
+        }
+        // Structured reasoning tools
+        "reasoning_debate" => handle_debate(state, arguments).await,
+        "reasoning_root_cause" => handle_root_cause(state, arguments).await,
+        "reasoning_plan" => handle_plan(state, arguments).await,
+        "reasoning_plan_revise" => handle_plan_revise(state, arguments).await,
+        "reasoning_estimate" => handle_estimate(state, arguments).await,
+        "reasoning_swot" => handle_swot(state, arguments).await,
+        "reasoning_swot_list" => handle_swot_list(state, arguments).await,
+        "reasoning_hypothesis" => handle_hypothesis(state, arguments).await,
+        "reasoning_hypothesis_observe" => handle_hypothesis_observe(state, arguments).await,
+        "reasoning_six_hats" => handle_six_hats(state, arguments).await,
+        "reasoning_dialectic" => handle_dialectic(state, arguments).await,
+        "reasoning_prioritize" => handle_prioritize(state, arguments).await,
+        "reasoning_prioritize_revise" => handle_prioritize_revise(state, arguments).await,
+        "reasoning_negotiate" => handle_negotiate(state, arguments).await,
+        "reasoning_risk" => handle_risk(state, arguments).await,
+        "reasoning_risk_update" => handle_risk_update(state, arguments).await,
+        "reasoning_systems" => handle_systems(state, arguments).await,
+        "reasoning_code_explain_diff" => handle_code_explain_diff(state, arguments).await,
+        "reasoning_code_trace_flow" => handle_code_trace_flow(state, arguments).await,
+        "reasoning_code_edge_cases" => handle_code_edge_cases(state, arguments).await,
+        "reasoning_code_findings" => handle_code_findings(state, arguments).await,
+        "reasoning_synthesize" => handle_synthesize(state, arguments).await,
         _ => Err(McpError::UnknownTool {
             tool_name: tool_name.to_string(),
         }),
+    }
+}
+
+/// Tools that never modify stored state and are therefore not audited.
+///
+/// None of them call pipes either, so read-only API keys cannot spend on
+/// the server's behalf: `reasoning_pipe_health` (live probes) and
+/// `reasoning_similar` (which indexes pending embeddings) are left out.
+const READ_ONLY_TOOLS: &[&str] = &[
+    "reasoning_tree_list",
+    "reasoning_tree_export",
+    "reasoning_checkpoint_list",
+    "reasoning_backtrack_diff",
+    "reasoning_got_state",
+    "reasoning_got_export",
+    "reasoning_got_validate",
+    "reasoning_got_best_path",
+    "reasoning_preset_list",
+    "reasoning_metrics_summary",
+    "reasoning_metrics_by_pipe",
+    "reasoning_metrics_invocations",
+    "reasoning_fallback_metrics",
+    "reasoning_debug_config",
+    "reasoning_pipe_status",
+    "reasoning_budget_status",
+    "reasoning_quota_status",
+    "reasoning_server_status",
+    "reasoning_search",
+    "reasoning_session_thoughts",
+    "reasoning_thought_history",
+    "reasoning_session_list",
+    "reasoning_knowledge_query",
+    "reasoning_swot_list",
+    "reasoning_code_findings",
+];
+
+/// Whether a tool call is recorded in the audit log.
+fn is_mutating_tool(tool_name: &str) -> bool {
+    !READ_ONLY_TOOLS.contains(&tool_name)
+}
+
+/// Tools that may discard, hide or overwrite stored reasoning state.
+const DESTRUCTIVE_TOOLS: &[&str] = &[
+    "reasoning_backtrack",
+    "reasoning_auto_backtrack",
+    "reasoning_got_prune",
+    "reasoning_session_delete",
+];
+
+/// Mutating tools for which repeating a call with the same arguments has no
+/// further effect.
+const IDEMPOTENT_TOOLS: &[&str] = &[
+    "reasoning_tree_focus",
+    "reasoning_tree_complete",
+    "reasoning_got_prune",
+    "reasoning_got_finalize",
+    "reasoning_session_tag",
+    "reasoning_session_delete",
+    "reasoning_session_restore",
+    "reasoning_knowledge_promote",
+    "reasoning_similar",
+];
+
+/// Display titles of the tools.
+const TOOL_TITLES: &[(&str, &str)] = &[
+    ("reasoning_linear", "Linear Reasoning"),
+    ("reasoning_tree", "Tree Reasoning"),
+    ("reasoning_tree_focus", "Focus Tree Branch"),
+    ("reasoning_tree_list", "List Tree Branches"),
+    ("reasoning_tree_complete", "Complete Tree Branch"),
+    ("reasoning_tree_compare", "Compare Tree Branches"),
+    ("reasoning_tree_merge", "Merge Tree Branches"),
+    ("reasoning_tree_export", "Export Branch Tree"),
+    ("reasoning_divergent", "Divergent Perspectives"),
+    ("reasoning_divergent_evaluate", "Evaluate Ideas"),
+    ("reasoning_reflection", "Reflective Critique"),
+    ("reasoning_reflection_evaluate", "Evaluate Session Quality"),
+    ("reasoning_backtrack", "Restore Checkpoint"),
+    ("reasoning_checkpoint_create", "Create Checkpoint"),
+    ("reasoning_checkpoint_list", "List Checkpoints"),
+    ("reasoning_backtrack_diff", "Diff Checkpoints"),
+    ("reasoning_auto", "Choose Reasoning Mode"),
+    ("reasoning_routing_feedback", "Rate Mode Choice"),
+    ("reasoning_got_init", "Start Reasoning Graph"),
+    ("reasoning_got_generate", "Generate Graph Continuations"),
+    ("reasoning_got_score", "Score Graph Node"),
+    ("reasoning_got_rank", "Rank Sibling Nodes"),
+    ("reasoning_got_aggregate", "Aggregate Graph Nodes"),
+    ("reasoning_got_aggregate_best", "Aggregate Best Graph Nodes"),
+    ("reasoning_got_refine", "Refine Graph Node"),
+    ("reasoning_got_prune", "Prune Reasoning Graph"),
+    ("reasoning_got_finalize", "Finalize Reasoning Graph"),
+    ("reasoning_got_state", "Reasoning Graph State"),
+    ("reasoning_got_export", "Export Reasoning Graph"),
+    ("reasoning_got_validate", "Validate Reasoning Graph"),
+    ("reasoning_got_best_path", "Best Reasoning Path"),
+    ("reasoning_got_resume", "Resume Reasoning Graph"),
+    ("reasoning_detect_biases", "Detect Cognitive Biases"),
+    ("reasoning_detect_fallacies", "Detect Logical Fallacies"),
+    ("reasoning_verify_calculations", "Verify Calculations"),
+    ("reasoning_preset_list", "List Workflow Presets"),
+    ("reasoning_preset_run", "Run Workflow Preset"),
+    ("reasoning_make_decision", "Make Decision"),
+    (
+        "reasoning_analyze_perspectives",
+        "Analyze Stakeholder Perspectives",
+    ),
+    ("reasoning_assess_evidence", "Assess Evidence"),
+    ("reasoning_probabilistic", "Bayesian Update"),
+    ("reasoning_metrics_summary", "Usage Metrics Summary"),
+    ("reasoning_metrics_by_pipe", "Pipe Metrics"),
+    ("reasoning_metrics_invocations", "Invocation Log"),
+    ("reasoning_fallback_metrics", "Fallback Metrics"),
+    ("reasoning_debug_config", "Show Configuration"),
+    ("reasoning_pipe_status", "Pipe Circuit Status"),
+    ("reasoning_pipe_health", "Check Pipe Health"),
+    ("reasoning_budget_status", "Spend Budget Status"),
+    ("reasoning_quota_status", "Client Quota Status"),
+    ("reasoning_server_status", "Server Status"),
+    ("reasoning_search", "Search Thoughts"),
+    ("reasoning_similar", "Find Similar Thoughts"),
+    ("reasoning_session_thoughts", "Session Thoughts"),
+    ("reasoning_revise", "Revise Thought"),
+    ("reasoning_thought_history", "Thought Revision History"),
+    ("reasoning_session_tag", "Tag Session"),
+    ("reasoning_session_list", "List Sessions"),
+    ("reasoning_session_delete", "Move Session to Trash"),
+    ("reasoning_session_restore", "Restore Session from Trash"),
+    ("reasoning_session_fork", "Fork Session"),
+    ("reasoning_knowledge_query", "Query Knowledge Base"),
+    ("reasoning_knowledge_promote", "Promote to Knowledge Base"),
+    ("reasoning_timeline_create", "Create Timeline"),
+    ("reasoning_timeline_branch", "Branch Timeline"),
+    ("reasoning_timeline_compare", "Compare Timeline Branches"),
+    ("reasoning_timeline_merge", "Merge Timeline Branches"),
+    ("reasoning_mcts_explore", "Monte Carlo Tree Search"),
+    ("reasoning_auto_backtrack", "Auto Backtrack"),
+    ("reasoning_counterfactual", "Counterfactual Analysis"),
+    (
+        "reasoning_counterfactual_minimal_change",
+        "Counterfactual Minimal Change",
+    ),
+    ("reasoning_debate", "Pro/Con Debate"),
+    ("reasoning_root_cause", "Root Cause Analysis"),
+    ("reasoning_plan", "Plan Goal"),
+    ("reasoning_plan_revise", "Revise Plan"),
+    ("reasoning_estimate", "Fermi Estimate"),
+    ("reasoning_swot", "SWOT Analysis"),
+    ("reasoning_swot_list", "List SWOT Analyses"),
+    ("reasoning_hypothesis", "Design Hypothesis Test"),
+    ("reasoning_hypothesis_observe", "Record Observation"),
+    ("reasoning_six_hats", "Six Thinking Hats"),
+    ("reasoning_dialectic", "Dialectical Synthesis"),
+    ("reasoning_prioritize", "Prioritize Items"),
+    ("reasoning_prioritize_revise", "Revise Priority Estimate"),
+    ("reasoning_negotiate", "Negotiation Analysis"),
+    ("reasoning_risk", "Risk Assessment"),
+    ("reasoning_risk_update", "Update Risk"),
+    ("reasoning_systems", "Systems Thinking"),
+    ("reasoning_code_explain_diff", "Explain Diff"),
+    ("reasoning_code_trace_flow", "Trace Data Flow"),
+    ("reasoning_code_edge_cases", "Find Edge Cases"),
+    ("reasoning_code_findings", "List Code Findings"),
+    ("reasoning_synthesize", "Synthesize Briefing"),
+];
+
+/// Display title of a tool, if it is a known tool.
+pub fn tool_title(tool_name: &str) -> Option<&'static str> {
+    TOOL_TITLES
+        .iter()
+        .find(|(name, _)| *name == tool_name)
+        .map(|(_, title)| *title)
+}
+
+/// MCP annotations for a tool: its title and behavior hints.
+///
+/// Read-only tools are the ones that skip the audit log; the destructive and
+/// idempotent hints are only set for the others.
+pub fn tool_annotations(tool_name: &str) -> ToolAnnotations {
+    let read_only = !is_mutating_tool(tool_name);
+    ToolAnnotations {
+        title: tool_title(tool_name).map(String::from),
+        read_only_hint: read_only,
+        destructive_hint: (!read_only).then_some(DESTRUCTIVE_TOOLS.contains(&tool_name)),
+        idempotent_hint: (!read_only).then_some(IDEMPOTENT_TOOLS.contains(&tool_name)),
+    }
+}
+
+/// JSON Schema of a tool's structured result.
+///
+/// Generated from the mode result types; tools that assemble their result
+/// ad hoc have none.
+pub fn tool_output_schema(tool_name: &str) -> Option<Value> {
+    let schema = match tool_name {
+        "reasoning_linear" => schema_for!(LinearResult),
+        "reasoning_tree" => schema_for!(TreeResult),
+        "reasoning_tree_compare" => schema_for!(TreeCompareResult),
+        "reasoning_tree_merge" => schema_for!(TreeMergeResult),
+        "reasoning_tree_export" => schema_for!(TreeExportResult),
+        "reasoning_divergent" => schema_for!(DivergentResult),
+        "reasoning_divergent_evaluate" => schema_for!(DivergentEvaluateResult),
+        "reasoning_reflection" => schema_for!(ReflectionResult),
+        "reasoning_backtrack" => schema_for!(BacktrackingResult),
+        "reasoning_backtrack_diff" => schema_for!(CheckpointDiff),
+        "reasoning_auto" => schema_for!(AutoResult),
+        "reasoning_routing_feedback" => schema_for!(RoutingFeedbackResult),
+        "reasoning_got_init" => schema_for!(GotInitResult),
+        "reasoning_got_generate" => schema_for!(GotGenerateResult),
+        "reasoning_got_score" => schema_for!(GotScoreResult),
+        "reasoning_got_rank" => schema_for!(GotRankResult),
+        "reasoning_got_aggregate" => schema_for!(GotAggregateResult),
+        "reasoning_got_aggregate_best" => schema_for!(GotAggregateBestResult),
+        "reasoning_got_refine" => schema_for!(GotRefineResult),
+        "reasoning_got_prune" => schema_for!(GotPruneResult),
+        "reasoning_got_finalize" => schema_for!(GotFinalizeResult),
+        "reasoning_got_state" => schema_for!(GotStateResult),
+        "reasoning_got_export" => schema_for!(GotExportResult),
+        "reasoning_got_validate" => schema_for!(GotValidateResult),
+        "reasoning_got_best_path" => schema_for!(GotBestPathResult),
+        "reasoning_got_resume" => schema_for!(GotResumeResult),
+        "reasoning_detect_biases" => schema_for!(DetectBiasesResult),
+        "reasoning_detect_fallacies" => schema_for!(DetectFallaciesResult),
+        "reasoning_verify_calculations" => schema_for!(CalcResult),
+        "reasoning_make_decision" => schema_for!(DecisionResult),
+        "reasoning_analyze_perspectives" => schema_for!(PerspectiveResult),
+        "reasoning_assess_evidence" => schema_for!(EvidenceResult),
+        "reasoning_probabilistic" => schema_for!(ProbabilisticResult),
+        "reasoning_timeline_create" => schema_for!(TimelineCreateResult),
+        "reasoning_timeline_branch" => schema_for!(TimelineBranchResult),
+        "reasoning_timeline_compare" => schema_for!(TimelineCompareResult),
+        "reasoning_timeline_merge" => schema_for!(TimelineMergeResult),
+        "reasoning_mcts_explore" => schema_for!(MCTSExploreResult),
+        "reasoning_auto_backtrack" => schema_for!(AutoBacktrackResult),
+        "reasoning_counterfactual" => schema_for!(CounterfactualResult),
+        "reasoning_counterfactual_minimal_change" => schema_for!(MinimalChangeResult),
+        "reasoning_debate" => schema_for!(DebateResult),
+        "reasoning_root_cause" => schema_for!(RootCauseResult),
+        "reasoning_plan" => schema_for!(PlanResult),
+        "reasoning_plan_revise" => schema_for!(PlanReviseResult),
+        "reasoning_estimate" => schema_for!(EstimateResult),
+        "reasoning_swot" => schema_for!(SwotResult),
+        "reasoning_swot_list" => schema_for!(SwotListResult),
+        "reasoning_hypothesis" => schema_for!(HypothesisResult),
+        "reasoning_hypothesis_observe" => schema_for!(HypothesisObserveResult),
+        "reasoning_six_hats" => schema_for!(SixHatsResult),
+        "reasoning_dialectic" => schema_for!(DialecticResult),
+        "reasoning_prioritize" | "reasoning_prioritize_revise" => schema_for!(PrioritizeResult),
+        "reasoning_negotiate" => schema_for!(NegotiationResult),
+        "reasoning_risk" => schema_for!(RiskRegisterResult),
+        "reasoning_risk_update" => schema_for!(RiskUpdateResult),
+        "reasoning_systems" => schema_for!(SystemsResult),
+        "reasoning_code_explain_diff" => schema_for!(ExplainDiffResult),
+        "reasoning_code_trace_flow" => schema_for!(TraceFlowResult),
+        "reasoning_code_edge_cases" => schema_for!(EdgeCasesResult),
+        "reasoning_code_findings" => schema_for!(CodeFindingsResult),
+        "reasoning_synthesize" => schema_for!(SynthesizeResult),
+        "reasoning_quota_status" => schema_for!(QuotaStatus),
+        "reasoning_server_status" => schema_for!(ServerStatus),
+        _ => return None,
     };
+    serde_json::to_value(schema).ok()
+}
 
-    // Record invocation for self-improvement system (if enabled)
-    let latency_ms = start.elapsed().as_millis() as i64;
-    let success = result.is_ok();
+/// Extract the `session_id` argument of a tool call, if any.
+fn argument_session_id(arguments: Option<&Value>) -> Option<String> {
+    arguments?
+        .get("session_id")?
+        .as_str()
+        .map(|id| id.to_string())
+}
 
-    // Extract quality score from response if available
-    let quality_score = result
-        .as_ref()
-        .ok()
-        .and_then(|v| v.get("confidence"))
-        .and_then(|c| c.as_f64());
+/// The session a mutating call changes: its `session_id` argument, or the
+/// session of the branch, checkpoint, thought or timeline it names.
+async fn mutated_session_id(state: &SharedState, arguments: Option<&Value>) -> Option<String> {
+    if let Some(session_id) = argument_session_id(arguments) {
+        return Some(session_id);
+    }
+    let argument = |key: &str| arguments?.get(key)?.as_str();
+    let storage = &state.storage;
+    if let Some(id) = argument("branch_id") {
+        return storage.get_branch(id).await.ok()?.map(|b| b.session_id);
+    }
+    if let Some(id) = argument("checkpoint_id") {
+        return storage.get_checkpoint(id).await.ok()?.map(|c| c.session_id);
+    }
+    if let Some(id) = argument("thought_id") {
+        return storage.get_thought(id).await.ok()?.map(|t| t.session_id);
+    }
+    if let Some(id) = argument("timeline_id") {
+        return storage.get_timeline(id).await.ok()?.map(|t| t.session_id);
+    }
+    None
+}
 
+/// Lock a session for a mutating call, failing with a conflict if other
+/// calls hold it past `SESSION_LOCK_TIMEOUT_MS`.
+async fn lock_session(state: &SharedState, session_id: &str) -> McpResult<SessionGuard> {
+    let timeout_ms = state.config.concurrency.session_lock_timeout_ms;
     state
-        .record_invocation(InvocationEvent {
-            tool_name: tool_name.to_string(),
-            latency_ms,
-            success,
-            quality_score,
-            timestamp: chrono::Utc::now(),
+        .session_locks
+        .lock(session_id, Duration::from_millis(timeout_ms))
+        .await
+        .ok_or_else(|| {
+            warn!(session_id = %session_id, timeout_ms, "Session lock not acquired");
+            McpError::Conflict {
+                message: format!(
+                    "Session {} is busy with another mutating call; retry once it completes",
+                    session_id
+                ),
+            }
         })
-        .await;
+}
 
-    result
+/// Hash a session's state for the audit log, logging failures.
+async fn session_hash(state: &SharedState, session_id: &str) -> Option<String> {
+    match state.audit.session_hash(session_id).await {
+        Ok(hash) => hash,
+        Err(e) => {
+            warn!(error = %e, session_id = %session_id, "Failed to hash session state");
+            None
+        }
+    }
+}
+
+/// Append an audit entry for a mutating tool call.
+///
+/// The affected session is taken from the call's arguments, or from the
+/// result for calls that created a new session. Failures to write the entry
+/// are logged rather than failing the call.
+async fn record_audit(
+    state: &SharedState,
+    tool_name: &str,
+    session_id: Option<String>,
+    before_hash: Option<String>,
+    result: &McpResult<Value>,
+) {
+    let session_id = session_id.or_else(|| {
+        result
+            .as_ref()
+            .ok()
+            .and_then(|value| value.get("session_id"))
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+    });
+
+    let mut entry = state.audit.entry(tool_name);
+    if let Some(session_id) = session_id {
+        let after_hash = session_hash(state, &session_id).await;
+        entry = entry
+            .with_session(session_id)
+            .with_hashes(before_hash, after_hash);
+    }
+    if let Err(e) = result {
+        entry = entry.with_error(e.to_string());
+    }
+
+    if let Err(e) = state.audit.record(&entry).await {
+        warn!(error = %e, tool = %tool_name, "Failed to write audit log entry");
+    }
 }
 
 /// Handle reasoning.linear tool call
@@ -197,16 +736,37 @@ async fn handle_tree_focus(state: &SharedState, arguments: Option<Value>) -> Mcp
 }
 
 /// Handle reasoning.tree.list - list all branches in a session
+///
+/// Paged only when `cursor` or `limit` is given; otherwise the branches are
+/// returned as a bare array, as before pagination existed.
 async fn handle_tree_list(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
-    execute_handler(
+    let params: TreeListParams = parse_arguments("reasoning.tree.list", arguments)?;
+    let pagination = requested_page(
         "reasoning.tree.list",
-        arguments,
-        |params: TreeListParams| {
-            let session_id = params.session_id;
-            async move { state.tree_mode.list_branches(&session_id).await }
-        },
-    )
-    .await
+        params.cursor.as_deref(),
+        None,
+        params.limit.unwrap_or(100).min(500),
+    )?;
+
+    let branches = state
+        .tree_mode
+        .list_branches(&params.session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: e.to_string(),
+        })?;
+    // Without paging arguments the result keeps its original shape
+    if params.cursor.is_none() && params.limit.is_none() {
+        return Ok(serde_json::json!(branches));
+    }
+    let page = Page::from_all(branches, pagination);
+
+    Ok(serde_json::json!({
+        "count": page.items.len(),
+        "has_more": page.has_more,
+        "next_cursor": page.next_cursor(),
+        "branches": page.items,
+    }))
 }
 
 /// Handle reasoning.tree.complete - mark a branch as completed or abandoned
@@ -232,6 +792,36 @@ async fn handle_tree_complete(state: &SharedState, arguments: Option<Value>) ->
     .await
 }
 
+/// Handle reasoning.tree.compare - compare branches and recommend one
+async fn handle_tree_compare(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.tree.compare",
+        arguments,
+        |params: TreeCompareParams| state.tree_mode.compare_branches(params),
+    )
+    .await
+}
+
+/// Handle reasoning.tree.merge - merge two branches into a new one
+async fn handle_tree_merge(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.tree.merge",
+        arguments,
+        |params: TreeMergeParams| state.tree_mode.merge_branches(params),
+    )
+    .await
+}
+
+/// Handle reasoning.tree.export - render the branch hierarchy
+async fn handle_tree_export(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.tree.export",
+        arguments,
+        |params: TreeExportParams| state.tree_mode.export(params),
+    )
+    .await
+}
+
 /// Handle reasoning.divergent tool call
 async fn handle_divergent(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
     execute_handler(
@@ -242,6 +832,26 @@ async fn handle_divergent(state: &SharedState, arguments: Option<Value>) -> McpR
     .await
 }
 
+/// Handle reasoning.divergent.evaluate - score ideas, then decide among the
+/// winners
+async fn handle_divergent_evaluate(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.divergent.evaluate",
+        arguments,
+        |params: DivergentEvaluateParams| async move {
+            let mut result = state.divergent_mode.evaluate(params.clone()).await?;
+            if let Some(decision) = result.decision_params(&params) {
+                result.decision = Some(state.decision_mode.make_decision(decision).await?);
+            }
+            Ok::<_, AppError>(result)
+        },
+    )
+    .await
+}
+
 /// Handle reasoning.reflection tool call
 async fn handle_reflection(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
     execute_handler(
@@ -306,14 +916,45 @@ async fn handle_checkpoint_create(
 }
 
 /// Handle reasoning.checkpoint.list tool call
+///
+/// Paged only when `cursor` or `limit` is given; otherwise the checkpoints
+/// are returned as a bare array, as before pagination existed.
 async fn handle_checkpoint_list(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
-    execute_handler(
+    let params: CheckpointListParams = parse_arguments("reasoning.checkpoint.list", arguments)?;
+    let pagination = requested_page(
         "reasoning.checkpoint.list",
+        params.cursor.as_deref(),
+        None,
+        params.limit.unwrap_or(100).min(500),
+    )?;
+
+    let checkpoints = state
+        .backtracking_mode
+        .list_checkpoints(&params.session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: e.to_string(),
+        })?;
+    // Without paging arguments the result keeps its original shape
+    if params.cursor.is_none() && params.limit.is_none() {
+        return Ok(serde_json::json!(checkpoints));
+    }
+    let page = Page::from_all(checkpoints, pagination);
+
+    Ok(serde_json::json!({
+        "count": page.items.len(),
+        "has_more": page.has_more,
+        "next_cursor": page.next_cursor(),
+        "checkpoints": page.items,
+    }))
+}
+
+/// Handle reasoning.backtrack.diff tool call
+async fn handle_backtrack_diff(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.backtrack.diff",
         arguments,
-        |params: CheckpointListParams| {
-            let session_id = params.session_id;
-            async move { state.backtracking_mode.list_checkpoints(&session_id).await }
-        },
+        |params: CheckpointDiffParams| state.backtracking_mode.diff(params),
     )
     .await
 }
@@ -330,6 +971,19 @@ async fn handle_auto(state: &SharedState, arguments: Option<Value>) -> McpResult
     .await
 }
 
+/// Handle reasoning_routing_feedback tool call
+async fn handle_routing_feedback(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.routing_feedback",
+        arguments,
+        |params: RoutingFeedbackParams| state.auto_mode.feedback(params),
+    )
+    .await
+}
+
 // ============================================================================
 // Phase 3 Handlers - Graph-of-Thoughts
 // ============================================================================
@@ -362,6 +1016,14 @@ async fn handle_got_score(state: &SharedState, arguments: Option<Value>) -> McpR
     .await
 }
 
+/// Handle reasoning_got_rank tool call
+async fn handle_got_rank(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.got.rank", arguments, |params: GotRankParams| {
+        state.got_mode.rank(params)
+    })
+    .await
+}
+
 /// Handle reasoning.got.aggregate tool call
 async fn handle_got_aggregate(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
     execute_handler(
@@ -372,6 +1034,19 @@ async fn handle_got_aggregate(state: &SharedState, arguments: Option<Value>) ->
     .await
 }
 
+/// Handle reasoning_got_aggregate_best tool call
+async fn handle_got_aggregate_best(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.got.aggregate_best",
+        arguments,
+        |params: GotAggregateBestParams| state.got_mode.aggregate_best(params),
+    )
+    .await
+}
+
 /// Handle reasoning.got.refine tool call
 async fn handle_got_refine(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
     execute_handler(
@@ -412,6 +1087,46 @@ async fn handle_got_state(state: &SharedState, arguments: Option<Value>) -> McpR
     .await
 }
 
+/// Handle reasoning_got_export tool call
+async fn handle_got_export(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.got.export",
+        arguments,
+        |params: GotExportParams| state.got_mode.export(params),
+    )
+    .await
+}
+
+/// Handle reasoning_got_validate tool call
+async fn handle_got_validate(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.got.validate",
+        arguments,
+        |params: GotValidateParams| state.got_mode.validate(params),
+    )
+    .await
+}
+
+/// Handle reasoning_got_best_path tool call
+async fn handle_got_best_path(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.got.best_path",
+        arguments,
+        |params: GotBestPathParams| state.got_mode.best_path(params),
+    )
+    .await
+}
+
+/// Handle reasoning_got_resume tool call
+async fn handle_got_resume(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.got.resume",
+        arguments,
+        |params: GotResumeParams| state.got_mode.resume(params),
+    )
+    .await
+}
+
 // ============================================================================
 // Phase 4 Handlers - Bias & Fallacy Detection
 // ============================================================================
@@ -439,6 +1154,19 @@ async fn handle_detect_fallacies(
     .await
 }
 
+/// Handle reasoning_verify_calculations tool call
+async fn handle_verify_calculations(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.verify_calculations",
+        arguments,
+        |params: CalcParams| state.calc_mode.verify(params),
+    )
+    .await
+}
+
 // ============================================================================
 // Phase 5 Handlers - Workflow Presets
 // ============================================================================
@@ -461,6 +1189,9 @@ pub struct PresetRunParams {
     pub inputs: HashMap<String, serde_json::Value>,
     /// Optional session ID for context persistence
     pub session_id: Option<String>,
+    /// Where to run the steps, overriding the preset's own setting
+    #[serde(default)]
+    pub execution: Option<crate::presets::PresetExecution>,
 }
 
 /// Response for preset list
@@ -501,13 +1232,16 @@ async fn handle_preset_run(state: &SharedState, arguments: Option<Value>) -> Mcp
     info!(preset_id = %params.preset_id, "Running preset");
 
     // Get the preset from registry
-    let preset = state
+    let mut preset = state
         .preset_registry
         .get(&params.preset_id)
         .ok_or_else(|| McpError::InvalidParameters {
             tool_name: "reasoning_preset_run".to_string(),
             message: format!("Preset not found: {}", params.preset_id),
         })?;
+    if let Some(execution) = params.execution {
+        preset.execution = execution;
+    }
 
     // Build inputs with session_id if provided
     let mut inputs = params.inputs;
@@ -562,6 +1296,27 @@ fn parse_arguments_or_default<T: serde::de::DeserializeOwned + Default>(
     }
 }
 
+/// Page requested by a listing tool: `cursor` if given, else `offset`.
+///
+/// Cursors are the `next_cursor` of an earlier response; anything else is
+/// rejected as an invalid parameter.
+fn requested_page(
+    tool_name: &str,
+    cursor: Option<&str>,
+    offset: Option<u32>,
+    limit: u32,
+) -> McpResult<Pagination> {
+    match cursor {
+        Some(cursor) => {
+            Pagination::from_cursor(limit, cursor).ok_or_else(|| McpError::InvalidParameters {
+                tool_name: tool_name.to_string(),
+                message: format!("Invalid cursor: {}", cursor),
+            })
+        }
+        None => Ok(Pagination::new(limit, offset.unwrap_or(0))),
+    }
+}
+
 /// Generic handler that executes a mode operation with consistent error handling.
 ///
 /// This helper reduces boilerplate by handling:
@@ -673,22 +1428,25 @@ pub struct MetricsInvocationsParams {
     /// Maximum number of results to return
     #[serde(default)]
     pub limit: Option<u32>,
+    /// Number of results to skip
+    #[serde(default)]
+    pub offset: Option<u32>,
+    /// Cursor from the previous page's `next_cursor`, instead of `offset`
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 /// Handle reasoning_metrics_summary tool call
 async fn handle_metrics_summary(state: &SharedState) -> McpResult<Value> {
-    use crate::storage::Storage;
-
     info!("Handling metrics summary request");
 
-    let summaries =
-        state
-            .storage
-            .get_pipe_usage_summary()
-            .await
-            .map_err(|e| McpError::ExecutionFailed {
-                message: format!("Failed to get metrics: {}", e),
-            })?;
+    let summaries = state
+        .storage
+        .get_pipe_usage_summary(None)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to get metrics: {}", e),
+        })?;
 
     // Format the summaries into a more readable response
     let result = serde_json::json!({
@@ -716,23 +1474,79 @@ async fn handle_metrics_summary(state: &SharedState) -> McpResult<Value> {
                 total_calls,
                 if total_calls > 0 { (total_success as f64 / total_calls as f64) * 100.0 } else { 0.0 }
             )
-        }
+        },
+        "maintenance": maintenance_summary(state),
+        "cache": cache_summary(state),
+        "reflection_rubrics": rubric_summary(state).await,
     });
 
     Ok(result)
 }
 
-/// Handle reasoning_metrics_by_pipe tool call
-async fn handle_metrics_by_pipe(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
-    use crate::storage::Storage;
+/// Summarize the latest scheduled database maintenance run.
+fn maintenance_summary(state: &SharedState) -> Value {
+    let status = &state.maintenance;
+    serde_json::json!({
+        "enabled": state.config.database.maintenance.is_enabled(),
+        "last_run": status.last_report().map(|report| serde_json::json!({
+            "started_at": report.started_at.to_rfc3339(),
+            "duration_ms": report.duration_ms,
+            "integrity_ok": report.is_healthy(),
+            "integrity_errors": report.integrity_errors,
+            "vacuumed": report.vacuumed,
+            "analyzed": report.analyzed,
+        })),
+        "last_error": status.last_error(),
+    })
+}
 
-    let params: MetricsByPipeParams = parse_arguments("reasoning_metrics_by_pipe", arguments)?;
-    info!(pipe = %params.pipe_name, "Handling metrics by pipe request");
+/// Most recent reflection calls scanned for rubric score trends.
+const RUBRIC_TREND_WINDOW: u32 = 500;
 
-    let summary = state
-        .storage
-        .get_pipe_summary(&params.pipe_name)
-        .await
+/// Summarize how rubric dimensions have scored across recent reflections.
+async fn rubric_summary(state: &SharedState) -> Value {
+    use crate::storage::MetricsFilter;
+
+    let filter = MetricsFilter::new()
+        .with_tool("reasoning.reflection")
+        .successful_only()
+        .with_limit(RUBRIC_TREND_WINDOW);
+    match state.storage.get_invocations(filter).await {
+        Ok(invocations) => serde_json::json!({
+            "dimensions": rubric_trends(&invocations),
+        }),
+        Err(e) => {
+            warn!(error = %e, "Failed to load reflection rubric scores");
+            serde_json::json!({ "error": e.to_string() })
+        }
+    }
+}
+
+/// Summarize hot-state cache effectiveness.
+fn cache_summary(state: &SharedState) -> Value {
+    match &state.cache {
+        Some(cache) => {
+            let stats = cache.stats();
+            serde_json::json!({
+                "enabled": true,
+                "hits": stats.hits,
+                "misses": stats.misses,
+                "hit_rate": format!("{:.1}%", stats.hit_rate() * 100.0),
+            })
+        }
+        None => serde_json::json!({ "enabled": false }),
+    }
+}
+
+/// Handle reasoning_metrics_by_pipe tool call
+async fn handle_metrics_by_pipe(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: MetricsByPipeParams = parse_arguments("reasoning_metrics_by_pipe", arguments)?;
+    info!(pipe = %params.pipe_name, "Handling metrics by pipe request");
+
+    let summary = state
+        .storage
+        .get_pipe_summary(&params.pipe_name, None)
+        .await
         .map_err(|e| McpError::ExecutionFailed {
             message: format!("Failed to get pipe metrics: {}", e),
         })?;
@@ -764,56 +1578,767 @@ async fn handle_metrics_invocations(
     state: &SharedState,
     arguments: Option<Value>,
 ) -> McpResult<Value> {
-    use crate::storage::{MetricsFilter, Storage};
+    use crate::storage::MetricsFilter;
 
     let params: MetricsInvocationsParams = parse_arguments_or_default(arguments)?;
     info!("Handling metrics invocations request");
 
-    // Build filter from params
-    let mut filter = MetricsFilter::new();
+    // Build filter from params
+    let mut filter = MetricsFilter::new();
+
+    if let Some(pipe_name) = params.pipe_name {
+        filter = filter.with_pipe(pipe_name);
+    }
+    if let Some(tool_name) = params.tool_name {
+        filter = filter.with_tool(tool_name);
+    }
+    if let Some(session_id) = params.session_id {
+        filter = filter.with_session(session_id);
+    }
+    if let Some(success_only) = params.success_only {
+        if success_only {
+            filter = filter.successful_only();
+        } else {
+            filter = filter.failed_only();
+        }
+    }
+    // Fetch one extra row to learn whether another page exists
+    let pagination = requested_page(
+        "reasoning_metrics_invocations",
+        params.cursor.as_deref(),
+        params.offset,
+        params.limit.unwrap_or(100).min(1000),
+    )?;
+    filter = filter
+        .with_limit(pagination.limit + 1)
+        .with_offset(pagination.offset);
+
+    let invocations =
+        state
+            .storage
+            .get_invocations(filter)
+            .await
+            .map_err(|e| McpError::ExecutionFailed {
+                message: format!("Failed to get invocations: {}", e),
+            })?;
+    let page = Page::from_overfetch(invocations, pagination);
+    let invocations = &page.items;
+
+    let result = serde_json::json!({
+        "count": invocations.len(),
+        "offset": page.offset,
+        "has_more": page.has_more,
+        "next_offset": page.next_offset(),
+        "next_cursor": page.next_cursor(),
+        "invocations": invocations.iter().map(|inv| serde_json::json!({
+            "id": inv.id,
+            "tool_name": inv.tool_name,
+            "pipe_name": inv.pipe_name,
+            "session_id": inv.session_id,
+            "success": inv.success,
+            "error": inv.error,
+            "latency_ms": inv.latency_ms,
+            "created_at": inv.created_at.to_rfc3339(),
+        })).collect::<Vec<_>>()
+    });
+
+    Ok(result)
+}
+
+// ============================================================================
+// Search Handlers
+// ============================================================================
+
+/// Parameters for full-text thought search
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchParams {
+    /// Keywords to search for
+    pub query: String,
+    /// Restrict results to a single session
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Restrict results to a reasoning mode
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Maximum number of results to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// Handle reasoning_search tool call
+async fn handle_search(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    use crate::storage::ThoughtSearchFilter;
+
+    let params: SearchParams = parse_arguments("reasoning_search", arguments)?;
+    if params.query.trim().is_empty() {
+        return Err(McpError::InvalidParameters {
+            tool_name: "reasoning_search".to_string(),
+            message: "query must not be empty".to_string(),
+        });
+    }
+    info!(query = %params.query, "Handling search request");
+
+    let mut filter = ThoughtSearchFilter::new();
+    if let Some(session_id) = params.session_id {
+        filter = filter.with_session(session_id);
+    }
+    if let Some(mode) = params.mode {
+        filter = filter.with_mode(mode);
+    }
+    filter = filter.with_limit(params.limit.unwrap_or(20).min(100));
+
+    let hits = state
+        .storage
+        .search_thoughts(&params.query, filter)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to search thoughts: {}", e),
+        })?;
+
+    Ok(serde_json::json!({
+        "query": params.query,
+        "count": hits.len(),
+        "results": hits.iter().map(|hit| serde_json::json!({
+            "thought_id": hit.thought.id,
+            "session_id": hit.thought.session_id,
+            "mode": hit.thought.mode,
+            "content": hit.thought.content,
+            "snippet": hit.snippet,
+            "confidence": hit.thought.confidence,
+            "score": hit.score,
+            "created_at": hit.thought.created_at.to_rfc3339(),
+        })).collect::<Vec<_>>()
+    }))
+}
+
+/// Parameters for semantic similarity search
+#[derive(Debug, Clone, Deserialize)]
+pub struct SimilarParams {
+    /// Text to find similar prior reasoning for
+    pub query: String,
+    /// Current session, excluded from results
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Only return this kind of record
+    #[serde(default)]
+    pub source_type: Option<crate::storage::EmbeddingSource>,
+    /// Minimum cosine similarity for a result
+    #[serde(default)]
+    pub min_score: Option<f64>,
+    /// Maximum number of results to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// Maximum number of unembedded records indexed per similarity request.
+const SIMILAR_INDEX_LIMIT: u32 = 500;
+
+/// Embed thoughts and graph nodes that have no vector for the current model.
+///
+/// Embeddings are computed lazily so switching providers never requires a
+/// separate migration step. Returns the number of records indexed.
+async fn index_pending_embeddings(state: &SharedState) -> McpResult<usize> {
+    use crate::storage::Embedding;
+
+    let provider = &state.embeddings;
+    let candidates = state
+        .storage
+        .get_embedding_candidates(provider.model(), None, SIMILAR_INDEX_LIMIT)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to load records to embed: {}", e),
+        })?;
+    if candidates.is_empty() {
+        return Ok(0);
+    }
+
+    let texts: Vec<String> = candidates.iter().map(|c| c.content.clone()).collect();
+    let vectors = provider
+        .embed(&texts)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to compute embeddings: {}", e),
+        })?;
+
+    let embeddings: Vec<Embedding> = candidates
+        .into_iter()
+        .zip(vectors)
+        .map(|(c, vector)| {
+            Embedding::new(
+                c.source_id,
+                c.source_type,
+                c.session_id,
+                provider.model(),
+                vector,
+            )
+        })
+        .collect();
+    state
+        .storage
+        .save_embeddings(&embeddings)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to save embeddings: {}", e),
+        })?;
+
+    info!(count = embeddings.len(), model = %provider.model(), "Indexed embeddings");
+    Ok(embeddings.len())
+}
+
+/// Handle reasoning_similar tool call
+async fn handle_similar(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    use crate::storage::SimilarityFilter;
+
+    let params: SimilarParams = parse_arguments("reasoning_similar", arguments)?;
+    if params.query.trim().is_empty() {
+        return Err(McpError::InvalidParameters {
+            tool_name: "reasoning_similar".to_string(),
+            message: "query must not be empty".to_string(),
+        });
+    }
+    info!(query = %params.query, "Handling similarity search request");
+
+    let indexed = index_pending_embeddings(state).await?;
+
+    let query_vector = state
+        .embeddings
+        .embed(std::slice::from_ref(&params.query))
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to embed query: {}", e),
+        })?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    let mut filter = SimilarityFilter::new().with_limit(params.limit.unwrap_or(10).min(50));
+    if let Some(session_id) = params.session_id {
+        filter = filter.excluding_session(session_id);
+    }
+    if let Some(source_type) = params.source_type {
+        filter = filter.with_source_type(source_type);
+    }
+    if let Some(min_score) = params.min_score {
+        filter = filter.with_min_score(min_score);
+    }
+
+    let matches = state
+        .storage
+        .find_similar(&query_vector, state.embeddings.model(), filter)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to find similar thoughts: {}", e),
+        })?;
+
+    Ok(serde_json::json!({
+        "query": params.query,
+        "model": state.embeddings.model(),
+        "indexed": indexed,
+        "count": matches.len(),
+        "results": matches.iter().map(|m| serde_json::json!({
+            "id": m.source_id,
+            "source_type": m.source_type,
+            "session_id": m.session_id,
+            "content": m.content,
+            "score": m.score,
+        })).collect::<Vec<_>>()
+    }))
+}
+
+/// Parameters for listing a session's thoughts
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionThoughtsParams {
+    /// Session to list thoughts for
+    pub session_id: String,
+    /// Maximum number of thoughts to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Number of thoughts to skip
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+/// Handle reasoning_session_thoughts tool call
+async fn handle_session_thoughts(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    let params: SessionThoughtsParams = parse_arguments("reasoning_session_thoughts", arguments)?;
+    info!(session_id = %params.session_id, "Handling session thoughts request");
+
+    let pagination = Pagination::new(
+        params.limit.unwrap_or(50).min(500),
+        params.offset.unwrap_or(0),
+    );
+    let page = state
+        .storage
+        .get_session_thoughts_page(&params.session_id, pagination)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to get thoughts: {}", e),
+        })?;
+
+    Ok(serde_json::json!({
+        "session_id": params.session_id,
+        "count": page.items.len(),
+        "offset": page.offset,
+        "has_more": page.has_more,
+        "next_offset": page.next_offset(),
+        "thoughts": page.items,
+    }))
+}
+
+// ============================================================================
+// Thought Revision Handlers
+// ============================================================================
+
+/// Parameters for revising a thought
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReviseParams {
+    /// Thought to revise
+    pub thought_id: String,
+    /// Corrected content
+    pub content: String,
+    /// New confidence (keeps the current confidence if omitted)
+    #[serde(default)]
+    pub confidence: Option<f64>,
+    /// Why the thought is being revised
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Handle reasoning_revise tool call
+async fn handle_revise(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: ReviseParams = parse_arguments("reasoning_revise", arguments)?;
+    if params.content.trim().is_empty() {
+        return Err(McpError::InvalidParameters {
+            tool_name: "reasoning_revise".to_string(),
+            message: "content must not be empty".to_string(),
+        });
+    }
+    info!(thought_id = %params.thought_id, "Handling thought revision request");
+
+    let to_error = |e: crate::error::StorageError| McpError::ExecutionFailed {
+        message: format!("Failed to revise thought: {}", e),
+    };
+
+    let thought = state
+        .storage
+        .revise_thought(
+            &params.thought_id,
+            &params.content,
+            params.confidence,
+            params.reason.as_deref(),
+        )
+        .await
+        .map_err(to_error)?;
+    let revisions = state
+        .storage
+        .get_thought_revisions(&thought.id)
+        .await
+        .map_err(to_error)?;
+
+    Ok(serde_json::json!({
+        "thought": thought,
+        "version": revisions.len() + 1,
+        "previous": revisions.last(),
+    }))
+}
+
+/// Parameters for retrieving a thought's revision history
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThoughtHistoryParams {
+    /// Thought to get the history of
+    pub thought_id: String,
+}
+
+/// Handle reasoning_thought_history tool call
+async fn handle_thought_history(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: ThoughtHistoryParams = parse_arguments("reasoning_thought_history", arguments)?;
+    info!(thought_id = %params.thought_id, "Handling thought history request");
+
+    let to_error = |e: crate::error::StorageError| McpError::ExecutionFailed {
+        message: format!("Failed to get thought history: {}", e),
+    };
+
+    let thought = state
+        .storage
+        .get_thought(&params.thought_id)
+        .await
+        .map_err(to_error)?
+        .ok_or_else(|| McpError::ExecutionFailed {
+            message: format!("Thought not found: {}", params.thought_id),
+        })?;
+    let revisions = state
+        .storage
+        .get_thought_revisions(&thought.id)
+        .await
+        .map_err(to_error)?;
+
+    Ok(serde_json::json!({
+        "thought": thought,
+        "version": revisions.len() + 1,
+        "revisions": revisions,
+    }))
+}
+
+// ============================================================================
+// Session Organization Handlers
+// ============================================================================
+
+/// Parameters for session tag operation
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionTagParams {
+    /// Session to tag
+    pub session_id: String,
+    /// Tags to add
+    #[serde(default)]
+    pub add: Vec<String>,
+    /// Tags to remove
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+/// Parameters for session list operation
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionListParams {
+    /// Only sessions in this reasoning mode
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Only sessions with this tag
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Only sessions created at or after this time
+    #[serde(default)]
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only sessions created before this time
+    #[serde(default)]
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only sessions with (true) or without (false) detections
+    #[serde(default)]
+    pub has_detections: Option<bool>,
+    /// List trashed sessions instead of live sessions
+    #[serde(default)]
+    pub in_trash: bool,
+    /// Maximum number of sessions to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Number of sessions to skip
+    #[serde(default)]
+    pub offset: Option<u32>,
+    /// Cursor from the previous page's `next_cursor`, instead of `offset`
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// Summarize a session for list responses
+fn session_summary(session: &crate::storage::Session) -> Value {
+    serde_json::json!({
+        "session_id": session.id,
+        "mode": session.mode,
+        "tags": session.tags,
+        "created_at": session.created_at.to_rfc3339(),
+        "updated_at": session.updated_at.to_rfc3339(),
+        "deleted_at": session.deleted_at.map(|t| t.to_rfc3339()),
+        "forked_from": session.forked_from,
+        "thread_id": session.thread_id,
+    })
+}
+
+/// Handle reasoning_session_tag tool call
+async fn handle_session_tag(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: SessionTagParams = parse_arguments("reasoning_session_tag", arguments)?;
+    info!(session_id = %params.session_id, "Handling session tag request");
+
+    let to_error = |e: crate::error::StorageError| McpError::ExecutionFailed {
+        message: format!("Failed to update session tags: {}", e),
+    };
+
+    for tag in &params.add {
+        state
+            .storage
+            .add_tag(&params.session_id, tag)
+            .await
+            .map_err(to_error)?;
+    }
+    for tag in &params.remove {
+        state
+            .storage
+            .remove_tag(&params.session_id, tag)
+            .await
+            .map_err(to_error)?;
+    }
+
+    let session = state
+        .storage
+        .get_session(&params.session_id)
+        .await
+        .map_err(to_error)?
+        .ok_or_else(|| McpError::ExecutionFailed {
+            message: format!("Session not found: {}", params.session_id),
+        })?;
+
+    Ok(serde_json::json!({
+        "session_id": session.id,
+        "tags": session.tags,
+    }))
+}
+
+/// Parameters for session delete and restore operations
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionIdParams {
+    /// The session to operate on
+    pub session_id: String,
+}
+
+/// Load a session, failing if it does not exist
+async fn require_session(
+    state: &SharedState,
+    session_id: &str,
+) -> McpResult<crate::storage::Session> {
+    state
+        .storage
+        .get_session(session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to load session: {}", e),
+        })?
+        .ok_or_else(|| McpError::ExecutionFailed {
+            message: format!("Session not found: {}", session_id),
+        })
+}
+
+/// Handle reasoning_session_delete tool call
+async fn handle_session_delete(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: SessionIdParams = parse_arguments("reasoning_session_delete", arguments)?;
+    info!(session_id = %params.session_id, "Handling session delete request");
+
+    let trashed = state
+        .storage
+        .trash_session(&params.session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to delete session: {}", e),
+        })?;
+    let session = require_session(state, &params.session_id).await?;
+
+    Ok(serde_json::json!({
+        "session_id": session.id,
+        "deleted": trashed,
+        "deleted_at": session.deleted_at.map(|t| t.to_rfc3339()),
+        "restorable": true,
+    }))
+}
+
+/// Handle reasoning_session_restore tool call
+async fn handle_session_restore(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: SessionIdParams = parse_arguments("reasoning_session_restore", arguments)?;
+    info!(session_id = %params.session_id, "Handling session restore request");
+
+    let restored = state
+        .storage
+        .restore_session(&params.session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to restore session: {}", e),
+        })?;
+    let session = require_session(state, &params.session_id).await?;
+
+    Ok(serde_json::json!({
+        "restored": restored,
+        "session": session_summary(&session),
+    }))
+}
+
+/// Handle reasoning_session_fork tool call
+async fn handle_session_fork(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: SessionIdParams = parse_arguments("reasoning_session_fork", arguments)?;
+    info!(session_id = %params.session_id, "Handling session fork request");
+
+    let fork = state
+        .storage
+        .fork_session(&params.session_id)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to fork session: {}", e),
+        })?;
+
+    Ok(serde_json::json!({
+        "session": session_summary(&fork.session),
+        "copied": {
+            "thoughts": fork.thoughts,
+            "thought_revisions": fork.thought_revisions,
+            "branches": fork.branches,
+            "cross_refs": fork.cross_refs,
+            "checkpoints": fork.checkpoints,
+            "graph_nodes": fork.graph_nodes,
+            "graph_edges": fork.graph_edges,
+        },
+    }))
+}
+
+/// Handle reasoning_session_list tool call
+async fn handle_session_list(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    use crate::storage::SessionFilter;
+
+    let params: SessionListParams = parse_arguments_or_default(arguments)?;
+    info!("Handling session list request");
+
+    let mut filter = SessionFilter::new();
+    if let Some(mode) = params.mode {
+        filter = filter.with_mode(mode);
+    }
+    if let Some(tag) = params.tag {
+        filter = filter.with_tag(tag);
+    }
+    if let Some(after) = params.created_after {
+        filter = filter.created_after(after);
+    }
+    if let Some(before) = params.created_before {
+        filter = filter.created_before(before);
+    }
+    if let Some(has_detections) = params.has_detections {
+        filter = filter.with_detections(has_detections);
+    }
+    if params.in_trash {
+        filter = filter.in_trash();
+    }
+
+    // Fetch one extra row to learn whether another page exists
+    let pagination = requested_page(
+        "reasoning_session_list",
+        params.cursor.as_deref(),
+        params.offset,
+        params.limit.unwrap_or(50).min(500),
+    )?;
+    filter = filter
+        .with_limit(pagination.limit + 1)
+        .with_offset(pagination.offset);
+
+    let sessions =
+        state
+            .storage
+            .list_sessions(filter)
+            .await
+            .map_err(|e| McpError::ExecutionFailed {
+                message: format!("Failed to list sessions: {}", e),
+            })?;
+    let page = Page::from_overfetch(sessions, pagination);
+
+    Ok(serde_json::json!({
+        "count": page.items.len(),
+        "offset": page.offset,
+        "has_more": page.has_more,
+        "next_offset": page.next_offset(),
+        "next_cursor": page.next_cursor(),
+        "sessions": page.items.iter().map(session_summary).collect::<Vec<_>>(),
+    }))
+}
+
+/// Parameters for querying the knowledge base
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct KnowledgeQueryParams {
+    /// Question or problem statement to find prior conclusions for
+    #[serde(default)]
+    pub query: String,
+    /// Only return conclusions promoted from this kind of record
+    #[serde(default)]
+    pub source_type: Option<crate::storage::ConclusionSource>,
+    /// Minimum conclusion confidence
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Maximum number of conclusions to return
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// Parameters for promoting a reasoning result to the knowledge base
+#[derive(Debug, Clone, Deserialize)]
+pub struct KnowledgePromoteParams {
+    /// Kind of record to promote
+    pub source_type: crate::storage::ConclusionSource,
+    /// ID of the graph node, decision, or evidence assessment
+    pub source_id: String,
+}
+
+/// Summarize a conclusion for tool responses
+fn conclusion_summary(conclusion: &crate::storage::Conclusion) -> Value {
+    serde_json::json!({
+        "id": conclusion.id,
+        "source_type": conclusion.source_type,
+        "source_id": conclusion.source_id,
+        "session_id": conclusion.session_id,
+        "topic": conclusion.topic,
+        "content": conclusion.content,
+        "confidence": conclusion.confidence,
+        "created_at": conclusion.created_at.to_rfc3339(),
+    })
+}
+
+/// Handle reasoning_knowledge_query tool call
+async fn handle_knowledge_query(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    use crate::storage::{KnowledgeFilter, KnowledgeStore};
+
+    let params: KnowledgeQueryParams = parse_arguments_or_default(arguments)?;
+    info!(query = %params.query, "Handling knowledge query request");
 
-    if let Some(pipe_name) = params.pipe_name {
-        filter = filter.with_pipe(pipe_name);
-    }
-    if let Some(tool_name) = params.tool_name {
-        filter = filter.with_tool(tool_name);
-    }
-    if let Some(session_id) = params.session_id {
-        filter = filter.with_session(session_id);
+    let mut filter = KnowledgeFilter::new().with_limit(params.limit.unwrap_or(10).min(50));
+    if let Some(source_type) = params.source_type {
+        filter = filter.with_source_type(source_type);
     }
-    if let Some(success_only) = params.success_only {
-        if success_only {
-            filter = filter.successful_only();
-        } else {
-            filter = filter.failed_only();
-        }
+    if let Some(min_confidence) = params.min_confidence {
+        filter = filter.with_min_confidence(min_confidence);
     }
-    filter = filter.with_limit(params.limit.unwrap_or(100).min(1000));
 
-    let invocations =
-        state
-            .storage
-            .get_invocations(filter)
-            .await
-            .map_err(|e| McpError::ExecutionFailed {
-                message: format!("Failed to get invocations: {}", e),
-            })?;
+    let hits = KnowledgeStore::new(state.storage.clone())
+        .query(&params.query, filter)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to query knowledge base: {}", e),
+        })?;
 
-    let result = serde_json::json!({
-        "count": invocations.len(),
-        "invocations": invocations.iter().map(|inv| serde_json::json!({
-            "id": inv.id,
-            "tool_name": inv.tool_name,
-            "pipe_name": inv.pipe_name,
-            "session_id": inv.session_id,
-            "success": inv.success,
-            "error": inv.error,
-            "latency_ms": inv.latency_ms,
-            "created_at": inv.created_at.to_rfc3339(),
-        })).collect::<Vec<_>>()
-    });
+    Ok(serde_json::json!({
+        "query": params.query,
+        "count": hits.len(),
+        "results": hits.iter().map(|hit| {
+            let mut summary = conclusion_summary(&hit.conclusion);
+            summary["score"] = serde_json::json!(hit.score);
+            summary
+        }).collect::<Vec<_>>()
+    }))
+}
 
-    Ok(result)
+/// Handle reasoning_knowledge_promote tool call
+async fn handle_knowledge_promote(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    use crate::error::StorageError;
+    use crate::storage::KnowledgeStore;
+
+    let params: KnowledgePromoteParams = parse_arguments("reasoning_knowledge_promote", arguments)?;
+    info!(
+        source_type = %params.source_type,
+        source_id = %params.source_id,
+        "Handling knowledge promote request"
+    );
+
+    let conclusion = KnowledgeStore::new(state.storage.clone())
+        .promote(params.source_type, &params.source_id)
+        .await
+        .map_err(|e| match e {
+            StorageError::Knowledge { message } => McpError::InvalidParameters {
+                tool_name: "reasoning_knowledge_promote".to_string(),
+                message,
+            },
+            e => McpError::ExecutionFailed {
+                message: format!("Failed to promote conclusion: {}", e),
+            },
+        })?;
+
+    Ok(serde_json::json!({
+        "conclusion": conclusion_summary(&conclusion),
+    }))
 }
 
 /// Handle reasoning_debug_config tool call - returns current pipe configuration
@@ -848,6 +2373,11 @@ async fn handle_debug_config(state: &SharedState) -> McpResult<Value> {
         .and_then(|e| e.pipe.clone())
         .unwrap_or_else(|| "<fallback: decision-framework-v1>".to_string());
 
+    let code_pipe = pipes
+        .code
+        .clone()
+        .unwrap_or_else(|| "<fallback: code-reasoning-v1>".to_string());
+
     Ok(serde_json::json!({
         "debug_info": "Current pipe configuration",
         "pipes": {
@@ -860,6 +2390,7 @@ async fn handle_debug_config(state: &SharedState) -> McpResult<Value> {
             "detection": detection_pipe,
             "decision": decision_pipe,
             "evidence": evidence_pipe,
+            "code": code_pipe,
         },
         "detection_config_present": pipes.detection.is_some(),
         "decision_config_present": pipes.decision.is_some(),
@@ -868,20 +2399,103 @@ async fn handle_debug_config(state: &SharedState) -> McpResult<Value> {
     }))
 }
 
+/// Handle reasoning_pipe_status tool call - returns per-pipe circuit breaker state
+async fn handle_pipe_status(state: &SharedState) -> McpResult<Value> {
+    info!("Handling pipe status request");
+
+    let config = &state.config.circuit_breaker;
+    let pipes = state
+        .pipe_circuits
+        .as_ref()
+        .map(|breakers| breakers.status())
+        .unwrap_or_default();
+
+    Ok(serde_json::json!({
+        "circuit_breaker": {
+            "enabled": state.pipe_circuits.is_some(),
+            "failure_threshold": config.failure_threshold,
+            "cooldown_secs": config.cooldown_secs
+        },
+        "open_circuits": pipes.iter().filter(|p| p.state == CircuitState::Open).count(),
+        "pipes": pipes
+    }))
+}
+
+/// Parameters for reasoning_pipe_health.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PipeHealthParams {
+    /// Only probe these pipes (all configured pipes when empty).
+    #[serde(default)]
+    pub pipes: Vec<String>,
+}
+
+/// Handle reasoning_pipe_health tool call - probes configured pipes and reports
+/// reachability, latency and schema compliance
+async fn handle_pipe_health(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: PipeHealthParams = parse_arguments_or_default(arguments)?;
+    info!(filter = ?params.pipes, "Handling pipe health request");
+
+    let mut probes = pipe_probes(&state.config.pipes);
+    if !params.pipes.is_empty() {
+        probes.retain(|p| params.pipes.contains(&p.pipe));
+    }
+
+    let report = check_pipes(&state.backend, probes).await;
+    serde_json::to_value(report).map_err(|e| McpError::ExecutionFailed {
+        message: format!("Failed to serialize pipe health report: {}", e),
+    })
+}
+
+/// Parameters for reasoning_budget_status.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BudgetStatusParams {
+    /// Also report this session's spend.
+    pub session_id: Option<String>,
+}
+
+/// Handle reasoning_budget_status tool call - returns spend and remaining budget
+async fn handle_budget_status(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    let params: BudgetStatusParams = parse_arguments_or_default(arguments)?;
+    info!(session_id = ?params.session_id, "Handling budget status request");
+
+    let status = state.budget.status(params.session_id.as_deref());
+    serde_json::to_value(status).map_err(|e| McpError::ExecutionFailed {
+        message: format!("Failed to serialize budget status: {}", e),
+    })
+}
+
+/// Handle reasoning_quota_status tool call - returns the calling client's usage
+async fn handle_quota_status(state: &SharedState) -> McpResult<Value> {
+    let client = current_quota_client();
+    info!(client = ?client, "Handling quota status request");
+
+    let status = state.client_quotas.status(client.as_deref());
+    serde_json::to_value(status).map_err(|e| McpError::ExecutionFailed {
+        message: format!("Failed to serialize quota status: {}", e),
+    })
+}
+
+/// Handle reasoning_server_status tool call - returns readiness checks and uptime
+async fn handle_server_status(state: &SharedState) -> McpResult<Value> {
+    info!("Handling server status request");
+
+    let status = server_status(state).await;
+    serde_json::to_value(status).map_err(|e| McpError::ExecutionFailed {
+        message: format!("Failed to serialize server status: {}", e),
+    })
+}
+
 /// Handle reasoning_fallback_metrics tool call - returns fallback usage statistics
 async fn handle_fallback_metrics(state: &SharedState) -> McpResult<Value> {
-    use crate::storage::Storage;
-
     info!("Handling fallback metrics request");
 
-    let metrics =
-        state
-            .storage
-            .get_fallback_metrics()
-            .await
-            .map_err(|e| McpError::ExecutionFailed {
-                message: format!("Failed to get fallback metrics: {}", e),
-            })?;
+    let metrics = state
+        .storage
+        .get_fallback_metrics(None)
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to get fallback metrics: {}", e),
+        })?;
 
     Ok(serde_json::json!({
         "total_fallbacks": metrics.total_fallbacks,
@@ -970,6 +2584,238 @@ async fn handle_counterfactual(state: &SharedState, arguments: Option<Value>) ->
     .await
 }
 
+/// Handle reasoning_counterfactual_minimal_change tool call
+async fn handle_counterfactual_minimal_change(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.counterfactual_minimal_change",
+        arguments,
+        |params: MinimalChangeParams| state.counterfactual_mode.minimal_change(params),
+    )
+    .await
+}
+
+// ============================================================================
+// Structured Reasoning Handlers
+// ============================================================================
+
+/// Handle reasoning_debate tool call
+async fn handle_debate(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.debate", arguments, |params: DebateParams| {
+        state.debate_mode.debate(params)
+    })
+    .await
+}
+
+/// Handle reasoning_root_cause tool call
+async fn handle_root_cause(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.root_cause",
+        arguments,
+        |params: RootCauseParams| state.rca_mode.analyze(params),
+    )
+    .await
+}
+
+/// Handle reasoning_plan tool call
+async fn handle_plan(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.plan", arguments, |params: PlanParams| {
+        state.planning_mode.plan(params)
+    })
+    .await
+}
+
+/// Handle reasoning_plan_revise tool call
+async fn handle_plan_revise(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.plan_revise",
+        arguments,
+        |params: PlanReviseParams| state.planning_mode.revise(params),
+    )
+    .await
+}
+
+/// Handle reasoning_estimate tool call
+async fn handle_estimate(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.estimate", arguments, |params: EstimateParams| {
+        state.estimate_mode.estimate(params)
+    })
+    .await
+}
+
+/// Handle reasoning_swot tool call
+async fn handle_swot(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.swot", arguments, |params: SwotParams| {
+        state.swot_mode.analyze(params)
+    })
+    .await
+}
+
+/// Handle reasoning_swot_list tool call
+async fn handle_swot_list(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.swot_list",
+        arguments,
+        |params: SwotListParams| state.swot_mode.list(params),
+    )
+    .await
+}
+
+/// Handle reasoning_hypothesis tool call
+async fn handle_hypothesis(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.hypothesis",
+        arguments,
+        |params: HypothesisParams| state.hypothesis_mode.design(params),
+    )
+    .await
+}
+
+/// Handle reasoning_hypothesis_observe tool call
+async fn handle_hypothesis_observe(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.hypothesis_observe",
+        arguments,
+        |params: HypothesisObserveParams| state.hypothesis_mode.observe(params),
+    )
+    .await
+}
+
+/// Handle reasoning_six_hats tool call
+async fn handle_six_hats(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.six_hats", arguments, |params: SixHatsParams| {
+        state.six_hats_mode.think(params)
+    })
+    .await
+}
+
+/// Handle reasoning_dialectic tool call
+async fn handle_dialectic(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.dialectic",
+        arguments,
+        |params: DialecticParams| state.dialectic_mode.develop(params),
+    )
+    .await
+}
+
+/// Handle reasoning_prioritize tool call
+async fn handle_prioritize(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.prioritize",
+        arguments,
+        |params: PrioritizeParams| state.prioritization_mode.prioritize(params),
+    )
+    .await
+}
+
+/// Handle reasoning_prioritize_revise tool call
+async fn handle_prioritize_revise(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.prioritize_revise",
+        arguments,
+        |params: PrioritizeReviseParams| state.prioritization_mode.revise(params),
+    )
+    .await
+}
+
+/// Handle reasoning_negotiate tool call
+async fn handle_negotiate(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.negotiate",
+        arguments,
+        |params: NegotiationParams| state.negotiation_mode.analyze(params),
+    )
+    .await
+}
+
+/// Handle reasoning_risk tool call
+async fn handle_risk(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.risk", arguments, |params: RiskParams| {
+        state.risk_mode.assess(params)
+    })
+    .await
+}
+
+/// Handle reasoning_risk_update tool call
+async fn handle_risk_update(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.risk_update",
+        arguments,
+        |params: RiskUpdateParams| state.risk_mode.update(params),
+    )
+    .await
+}
+
+/// Handle reasoning_systems tool call
+async fn handle_systems(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler("reasoning.systems", arguments, |params: SystemsParams| {
+        state.systems_mode.model(params)
+    })
+    .await
+}
+
+/// Handle reasoning_code_explain_diff tool call
+async fn handle_code_explain_diff(
+    state: &SharedState,
+    arguments: Option<Value>,
+) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.code_explain_diff",
+        arguments,
+        |params: ExplainDiffParams| state.code_mode.explain_diff(params),
+    )
+    .await
+}
+
+/// Handle reasoning_code_trace_flow tool call
+async fn handle_code_trace_flow(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.code_trace_flow",
+        arguments,
+        |params: TraceFlowParams| state.code_mode.trace_flow(params),
+    )
+    .await
+}
+
+/// Handle reasoning_code_edge_cases tool call
+async fn handle_code_edge_cases(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.code_edge_cases",
+        arguments,
+        |params: EdgeCasesParams| state.code_mode.edge_cases(params),
+    )
+    .await
+}
+
+/// Handle reasoning_code_findings tool call
+async fn handle_code_findings(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.code_findings",
+        arguments,
+        |params: CodeFindingsParams| state.code_mode.findings(params),
+    )
+    .await
+}
+
+/// Handle reasoning_synthesize tool call
+async fn handle_synthesize(state: &SharedState, arguments: Option<Value>) -> McpResult<Value> {
+    execute_handler(
+        "reasoning.synthesize",
+        arguments,
+        |params: SynthesizeParams| state.synthesis_mode.synthesize(params),
+    )
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1604,6 +3450,63 @@ mod tests {
         assert_eq!(params.count, 0);
     }
 
+    #[test]
+    fn test_is_mutating_tool() {
+        assert!(is_mutating_tool("reasoning_linear"));
+        assert!(is_mutating_tool("reasoning_session_delete"));
+        assert!(is_mutating_tool("reasoning_knowledge_promote"));
+        assert!(!is_mutating_tool("reasoning_session_list"));
+        assert!(is_mutating_tool("reasoning_pipe_health"));
+        assert!(is_mutating_tool("reasoning_similar"));
+        assert!(!is_mutating_tool("reasoning_knowledge_query"));
+    }
+
+    #[test]
+    fn test_tool_annotations() {
+        let metrics = tool_annotations("reasoning_metrics_summary");
+        assert!(metrics.read_only_hint);
+        assert_eq!(metrics.destructive_hint, None);
+        assert_eq!(metrics.title.as_deref(), Some("Usage Metrics Summary"));
+
+        let restore = tool_annotations("reasoning_backtrack");
+        assert!(!restore.read_only_hint);
+        assert_eq!(restore.destructive_hint, Some(true));
+        assert_eq!(restore.idempotent_hint, Some(false));
+
+        let tag = tool_annotations("reasoning_session_tag");
+        assert_eq!(tag.destructive_hint, Some(false));
+        assert_eq!(tag.idempotent_hint, Some(true));
+    }
+
+    #[test]
+    fn test_tool_output_schema() {
+        let schema = tool_output_schema("reasoning_got_generate").unwrap();
+        assert_eq!(schema["title"], "GotGenerateResult");
+        assert!(schema["properties"]["continuations"].is_object());
+
+        // Ad hoc results have no schema
+        assert!(tool_output_schema("reasoning_session_list").is_none());
+    }
+
+    #[test]
+    fn test_hint_tables_name_known_tools() {
+        for tool in DESTRUCTIVE_TOOLS.iter().chain(IDEMPOTENT_TOOLS) {
+            assert!(tool_title(tool).is_some(), "{} has no title", tool);
+            assert!(is_mutating_tool(tool), "{} is read-only", tool);
+        }
+        for tool in READ_ONLY_TOOLS {
+            assert!(tool_title(tool).is_some(), "{} has no title", tool);
+        }
+    }
+
+    #[test]
+    fn test_argument_session_id() {
+        let args = json!({"session_id": "s1", "content": "x"});
+        assert_eq!(argument_session_id(Some(&args)), Some("s1".to_string()));
+        assert_eq!(argument_session_id(Some(&json!({"content": "x"}))), None);
+        assert_eq!(argument_session_id(None), None);
+    }
+
     #[test]
     fn test_parse_arguments_or_default_preset_list_params() {
         // Test the actual use case from handle_preset_list
@@ -0,0 +1,135 @@
+//! Liveness and readiness of the server.
+//!
+//! Orchestrators such as Kubernetes probe `/healthz` on the WebSocket port to
+//! learn the process is up, and `/readyz` to learn it can serve requests: the
+//! database answers, every embedded migration is applied, and Langbase
+//! answers if any pipe is served by it. `reasoning_server_status` reports the
+//! same checks to MCP clients.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use super::SharedState;
+use crate::config::TransportKind;
+
+/// Longest a readiness check waits for Langbase to answer.
+pub const LANGBASE_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of one readiness check.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReadinessCheck {
+    /// What was checked: `database`, `migrations` or `langbase`.
+    pub name: String,
+    /// Whether the check passed.
+    pub ok: bool,
+    /// Why the check failed, or why it was skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Time the check took.
+    pub latency_ms: u64,
+}
+
+impl ReadinessCheck {
+    fn passed(name: &str, started: Instant) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: true,
+            detail: None,
+            latency_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+
+    fn failed(name: &str, started: Instant, detail: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            detail: Some(detail.into()),
+            ..Self::passed(name, started)
+        }
+    }
+}
+
+/// The server's readiness and uptime, served by `/readyz` and
+/// `reasoning_server_status`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ServerStatus {
+    /// Whether every readiness check passed.
+    pub ready: bool,
+    /// Server version.
+    pub version: String,
+    /// Transport clients connect over: `stdio` or `ws`.
+    pub transport: String,
+    /// When the server started.
+    pub started_at: DateTime<Utc>,
+    /// Seconds since the server started.
+    pub uptime_secs: u64,
+    /// Outcome of each readiness check.
+    pub checks: Vec<ReadinessCheck>,
+}
+
+/// Run the readiness checks and report them with the server's uptime.
+pub async fn server_status(state: &SharedState) -> ServerStatus {
+    let checks = readiness_checks(state).await;
+    ServerStatus {
+        ready: checks.iter().all(|check| check.ok),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        transport: match state.config.transport.kind {
+            TransportKind::Stdio => "stdio",
+            TransportKind::WebSocket => "ws",
+        }
+        .to_string(),
+        started_at: state.started_at,
+        uptime_secs: (Utc::now() - state.started_at).num_seconds().max(0) as u64,
+        checks,
+    }
+}
+
+/// Check the database, its migrations and Langbase
+async fn readiness_checks(state: &SharedState) -> Vec<ReadinessCheck> {
+    let mut checks = Vec::with_capacity(3);
+
+    let started = Instant::now();
+    match state.storage.schema_status().await {
+        Ok(status) => {
+            checks.push(ReadinessCheck::passed("database", started));
+            let pending = status.pending().len();
+            checks.push(if let Err(e) = status.check() {
+                ReadinessCheck::failed("migrations", started, e.to_string())
+            } else if pending > 0 {
+                let detail = format!("{} migration(s) pending", pending);
+                ReadinessCheck::failed("migrations", started, detail)
+            } else {
+                ReadinessCheck::passed("migrations", started)
+            });
+        }
+        Err(e) => {
+            checks.push(ReadinessCheck::failed("database", started, e.to_string()));
+            let detail = "Database unreachable";
+            checks.push(ReadinessCheck::failed("migrations", started, detail));
+        }
+    }
+
+    let started = Instant::now();
+    checks.push(if !state.config.providers.uses_langbase() {
+        ReadinessCheck {
+            detail: Some("No pipe is served by Langbase".to_string()),
+            ..ReadinessCheck::passed("langbase", started)
+        }
+    } else {
+        match tokio::time::timeout(LANGBASE_PING_TIMEOUT, state.langbase.list_pipes()).await {
+            Ok(Ok(_)) => ReadinessCheck::passed("langbase", started),
+            Ok(Err(e)) => ReadinessCheck::failed("langbase", started, e.to_string()),
+            Err(_) => ReadinessCheck::failed(
+                "langbase",
+                started,
+                format!(
+                    "No answer within {} seconds",
+                    LANGBASE_PING_TIMEOUT.as_secs()
+                ),
+            ),
+        }
+    });
+
+    checks
+}
@@ -3,14 +3,50 @@
 //! This module provides the core MCP server implementation including:
 //! - JSON-RPC 2.0 request/response handling
 //! - Tool definitions and schemas
-//! - Stdio-based server communication
+//! - Stdio and WebSocket server transports
+//! - Progress notifications carrying streamed pipe output and partial results
+//! - Sampling requests delegating pipe calls to the client
 
+use async_trait::async_trait;
+use futures::future::{self, BoxFuture, FutureExt};
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing::{debug, error, info};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, Stdin, Stdout};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse as WsErrorResponse, Request as WsRequest, Response as WsResponse,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
-use super::{handle_tool_call, SharedState};
+use super::critique::{accepts_critique, critique_schema};
+use super::sampling::{ClientResponse, ClientSampler};
+use super::{
+    complete, get_prompt, handle_tool_call, is_tool_enabled, list_prompts, list_resources,
+    read_resource, resource_templates, server_status, session_id_from_uri, session_uri,
+    tool_annotations, tool_output_schema, tool_title, unknown_tool_entries, with_quota_client,
+    CompleteParams, CompletionReference, PromptArguments, QuotaExceeded, SessionEvent, SharedState,
+};
+use crate::config::{AuthScope, TransportKind};
+use crate::error::McpError;
+use crate::langbase::{
+    with_cancellation, with_client_log_sink, with_stream_sink, ClientLogMessage, ClientLogSink,
+    LogLevel, StreamSink,
+};
+use crate::modes::{with_artifact_sink, Artifact, ArtifactSink};
+use crate::providers::{with_sampler, Sampler};
+use crate::storage::{spawn_maintenance_task, spawn_retention_task, Page, Pagination};
 
 #[cfg(test)]
 #[path = "mcp_tests.rs"]
@@ -45,6 +81,42 @@ pub struct JsonRpcResponse {
     pub error: Option<JsonRpcError>,
 }
 
+/// JSON-RPC 2.0 notification sent by the server.
+#[derive(Debug, Serialize)]
+pub struct JsonRpcNotification {
+    /// JSON-RPC version (always "2.0").
+    pub jsonrpc: String,
+    /// The notification method.
+    pub method: String,
+    /// Optional notification parameters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// JSON-RPC 2.0 request sent by the server to the client.
+#[derive(Debug, Serialize)]
+pub struct JsonRpcServerRequest {
+    /// JSON-RPC version (always "2.0").
+    pub jsonrpc: String,
+    /// Request identifier, unique per connection.
+    pub id: Value,
+    /// The method name to invoke on the client.
+    pub method: String,
+    /// Optional parameters for the method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// Message sent by the server on its own initiative while handling a request.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ServerMessage {
+    /// A notification, such as progress.
+    Notification(JsonRpcNotification),
+    /// A request the client must answer, such as sampling.
+    Request(JsonRpcServerRequest),
+}
+
 /// JSON-RPC 2.0 error object.
 #[derive(Debug, Serialize)]
 pub struct JsonRpcError {
@@ -57,6 +129,9 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+/// JSON-RPC error code of requests refused by a client quota.
+pub const QUOTA_EXCEEDED_CODE: i32 = -32005;
+
 /// MCP server information returned during initialization.
 #[derive(Debug, Serialize)]
 pub struct ServerInfo {
@@ -71,6 +146,14 @@ pub struct ServerInfo {
 pub struct Capabilities {
     /// Tool-related capabilities.
     pub tools: ToolCapabilities,
+    /// Resource-related capabilities.
+    pub resources: ResourceCapabilities,
+    /// Prompt-related capabilities.
+    pub prompts: PromptCapabilities,
+    /// Logging capabilities.
+    pub logging: LoggingCapabilities,
+    /// Argument completion capabilities.
+    pub completions: CompletionCapabilities,
 }
 
 /// Tool-specific capabilities.
@@ -81,6 +164,40 @@ pub struct ToolCapabilities {
     pub list_changed: bool,
 }
 
+/// Resource-specific capabilities.
+#[derive(Debug, Default, Serialize)]
+pub struct ResourceCapabilities {
+    /// Whether clients can subscribe to resource updates.
+    pub subscribe: bool,
+    /// Whether the resource list can change dynamically.
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
+/// Prompt-specific capabilities.
+#[derive(Debug, Default, Serialize)]
+pub struct PromptCapabilities {
+    /// Whether the prompt list can change dynamically.
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
+/// Logging capabilities: the server sends `notifications/message` and
+/// accepts `logging/setLevel`.
+#[derive(Debug, Default, Serialize)]
+pub struct LoggingCapabilities {}
+
+/// Completion capabilities: the server answers `completion/complete`.
+#[derive(Debug, Default, Serialize)]
+pub struct CompletionCapabilities {}
+
+/// Parameters for a logging/setLevel request.
+#[derive(Debug, Deserialize)]
+pub struct SetLevelParams {
+    /// Minimum level of log messages to send to the client.
+    pub level: LogLevel,
+}
+
 /// Result of the MCP initialize handshake.
 #[derive(Debug, Serialize)]
 pub struct InitializeResult {
@@ -95,15 +212,56 @@ pub struct InitializeResult {
 }
 
 /// MCP tool definition with JSON Schema.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Tool {
     /// Unique tool name (used in tool calls).
     pub name: String,
+    /// Human-readable title for display in clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     /// Human-readable description of the tool.
     pub description: String,
     /// JSON Schema for the tool's input parameters.
     #[serde(rename = "inputSchema")]
     pub input_schema: Value,
+    /// JSON Schema for the tool's structured result, if it has a fixed shape.
+    #[serde(rename = "outputSchema", skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Hints about the tool's behavior.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// MCP tool annotations.
+///
+/// These are hints for clients deciding whether to call a tool or ask the
+/// user first; they do not change how the tool runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    /// Human-readable title, for clients that predate the tool `title`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Whether the tool leaves stored state unchanged.
+    pub read_only_hint: bool,
+    /// Whether the tool may discard or overwrite stored state. Only
+    /// meaningful when `read_only_hint` is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    /// Whether repeating a call with the same arguments has no further
+    /// effect. Only meaningful when `read_only_hint` is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+}
+
+/// Parameters for a prompts/get request.
+#[derive(Debug, Deserialize)]
+pub struct PromptGetParams {
+    /// The name of the prompt to render.
+    pub name: String,
+    /// Prompt arguments by name.
+    #[serde(default)]
+    pub arguments: PromptArguments,
 }
 
 /// Parameters for a tools/call request.
@@ -114,6 +272,18 @@ pub struct ToolCallParams {
     /// Optional arguments for the tool.
     #[serde(default)]
     pub arguments: Option<Value>,
+    /// Request metadata.
+    #[serde(rename = "_meta", default)]
+    pub meta: Option<RequestMeta>,
+}
+
+/// Metadata attached to a request.
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestMeta {
+    /// Token to send `notifications/progress` for; when present, pipe
+    /// output is streamed to the client as it is generated.
+    #[serde(rename = "progressToken", default)]
+    pub progress_token: Option<Value>,
 }
 
 /// Content item within a tool result.
@@ -131,6 +301,9 @@ pub struct ToolResultContent {
 pub struct ToolCallResult {
     /// The result content items.
     pub content: Vec<ToolResultContent>,
+    /// The result as JSON, for clients that parse it rather than the text.
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     /// Whether the result represents an error.
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
@@ -160,95 +333,824 @@ impl JsonRpcResponse {
             }),
         }
     }
+
+    /// Create the error response to a request refused by a client quota
+    ///
+    /// The error data names the limit reached, what it allows and how many
+    /// seconds to wait before retrying.
+    pub fn quota_exceeded(id: Option<Value>, exceeded: &QuotaExceeded) -> Self {
+        let mut response = Self::error(id, QUOTA_EXCEEDED_CODE, exceeded.to_string());
+        if let Some(error) = response.error.as_mut() {
+            error.data = serde_json::to_value(exceeded).ok();
+        }
+        response
+    }
+}
+
+impl JsonRpcNotification {
+    /// Create a `notifications/progress` notification carrying streamed text.
+    ///
+    /// `progress` is the number of characters streamed so far for the request.
+    pub fn progress(token: &Value, progress: u64, message: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/progress".to_string(),
+            params: Some(serde_json::json!({
+                "progressToken": token,
+                "progress": progress,
+                "message": message,
+            })),
+        }
+    }
+
+    /// Create a `notifications/progress` notification carrying a partial
+    /// result of the tool call.
+    pub fn artifact(token: &Value, progress: u64, artifact: &Artifact) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/progress".to_string(),
+            params: Some(serde_json::json!({
+                "progressToken": token,
+                "progress": progress,
+                "message": format!("{} {} ready", artifact.kind, artifact.index + 1),
+                "artifact": artifact,
+            })),
+        }
+    }
+
+    /// Create a `notifications/message` notification carrying a log message.
+    pub fn log_message(message: &ClientLogMessage) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/message".to_string(),
+            params: serde_json::to_value(message).ok(),
+        }
+    }
+
+    /// Create a `notifications/resources/updated` notification for a
+    /// subscribed resource that changed.
+    pub fn resource_updated(uri: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/resources/updated".to_string(),
+            params: Some(serde_json::json!({ "uri": uri })),
+        }
+    }
+
+    /// Create a `notifications/cancelled` notification for a request the
+    /// server sent and no longer waits on.
+    pub fn cancelled(request_id: &Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: Some(serde_json::json!({
+                "requestId": request_id,
+                "reason": "Request abandoned by server",
+            })),
+        }
+    }
+}
+
+impl JsonRpcServerRequest {
+    /// Create a request to the client
+    pub fn new(id: &str, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Value::from(id),
+            method: method.to_string(),
+            params: Some(params),
+        }
+    }
+}
+
+/// Sink forwarding streamed pipe output as progress notifications for `token`
+///
+/// `progress` is shared with the call's [`artifact_sink`] so the progress
+/// reported for the token keeps increasing.
+fn progress_sink(
+    token: Value,
+    progress: Arc<AtomicU64>,
+    notifications: mpsc::UnboundedSender<ServerMessage>,
+) -> StreamSink {
+    Arc::new(move |text: &str| {
+        let chars = text.chars().count() as u64;
+        let progress = progress.fetch_add(chars, Ordering::Relaxed) + chars;
+        // The receiver only closes when the server is shutting down
+        let _ = notifications.send(ServerMessage::Notification(JsonRpcNotification::progress(
+            &token, progress, text,
+        )));
+    })
+}
+
+/// Sink forwarding partial results as progress notifications for `token`,
+/// each advancing `progress` by one
+fn artifact_sink(
+    token: Value,
+    progress: Arc<AtomicU64>,
+    notifications: mpsc::UnboundedSender<ServerMessage>,
+) -> ArtifactSink {
+    Arc::new(move |artifact: &Artifact| {
+        let progress = progress.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = notifications.send(ServerMessage::Notification(JsonRpcNotification::artifact(
+            &token, progress, artifact,
+        )));
+    })
+}
+
+/// Sink forwarding log messages at or above `min_level` as
+/// `notifications/message`
+fn client_log_sink(
+    min_level: LogLevel,
+    notifications: mpsc::UnboundedSender<ServerMessage>,
+) -> ClientLogSink {
+    Arc::new(move |message: &ClientLogMessage| {
+        if message.level >= min_level {
+            let _ = notifications.send(ServerMessage::Notification(
+                JsonRpcNotification::log_message(message),
+            ));
+        }
+    })
+}
+
+/// Where a transport writes outgoing JSON-RPC messages
+#[async_trait]
+trait MessageSink: Send {
+    /// Send one serialized message
+    async fn send_json(&mut self, json: String) -> std::io::Result<()>;
+}
+
+#[async_trait]
+impl MessageSink for Stdout {
+    async fn send_json(&mut self, json: String) -> std::io::Result<()> {
+        self.write_all(json.as_bytes()).await?;
+        self.write_all(b"\n").await?;
+        self.flush().await
+    }
+}
+
+#[async_trait]
+impl MessageSink for SplitSink<WebSocketStream<TcpStream>, WsMessage> {
+    async fn send_json(&mut self, json: String) -> std::io::Result<()> {
+        self.send(WsMessage::Text(json)).await.map_err(ws_error)
+    }
+}
+
+/// Where a transport reads incoming JSON-RPC messages
+#[async_trait]
+trait MessageSource: Send {
+    /// Read the next message, or `None` once the peer has closed the
+    /// transport
+    ///
+    /// Must be cancel safe: the server polls it alongside the request being
+    /// handled and drops the read when the request finishes first.
+    async fn next_message(&mut self) -> std::io::Result<Option<String>>;
+}
+
+#[async_trait]
+impl MessageSource for Lines<BufReader<Stdin>> {
+    async fn next_message(&mut self) -> std::io::Result<Option<String>> {
+        self.next_line().await
+    }
+}
+
+#[async_trait]
+impl MessageSource for SplitStream<WebSocketStream<TcpStream>> {
+    async fn next_message(&mut self) -> std::io::Result<Option<String>> {
+        while let Some(frame) = self.next().await {
+            match frame.map_err(ws_error)? {
+                WsMessage::Text(text) => return Ok(Some(text)),
+                WsMessage::Binary(bytes) => match String::from_utf8(bytes) {
+                    Ok(text) => return Ok(Some(text)),
+                    Err(e) => warn!(error = %e, "Ignoring non-UTF-8 binary frame"),
+                },
+                WsMessage::Close(_) => break,
+                // Pings are answered by the WebSocket layer
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Incoming messages of one connection, with those read ahead while a
+/// request was being handled
+struct Inbox<'a, S> {
+    source: &'a mut S,
+    queued: VecDeque<String>,
+    /// Whether `source` has reported the end of its messages
+    closed: bool,
+}
+
+/// The request ID a `notifications/cancelled` message cancels, if `message`
+/// is one
+fn cancelled_request(message: &str) -> Option<Value> {
+    let request: JsonRpcRequest = serde_json::from_str(message.trim()).ok()?;
+    if request.method != "notifications/cancelled" {
+        return None;
+    }
+    request.params?.get("requestId").cloned()
+}
+
+/// Split an incoming message into the requests it carries
+///
+/// A JSON array is a batch of requests. Responses to the server's own
+/// requests in a batch are handed to `sampler`; entries that are not valid
+/// requests are answered with an error in their place. Returns whether the
+/// message was a batch.
+fn parse_entries(
+    message: &str,
+    sampler: &ClientSampler,
+) -> (Vec<Result<JsonRpcRequest, JsonRpcResponse>>, bool) {
+    let parse_error = |e: serde_json::Error| {
+        error!(error = %e, "Failed to parse request");
+        JsonRpcResponse::error(None, -32700, format!("Parse error: {}", e))
+    };
+    if !message.starts_with('[') {
+        let entry = serde_json::from_str::<JsonRpcRequest>(message).map_err(parse_error);
+        return (vec![entry], false);
+    }
+
+    let values = match serde_json::from_str::<Vec<Value>>(message) {
+        Ok(values) if values.is_empty() => {
+            let error = JsonRpcResponse::error(None, -32600, "Invalid Request: empty batch");
+            return (vec![Err(error)], false);
+        }
+        Ok(values) => values,
+        Err(e) => return (vec![Err(parse_error(e))], false),
+    };
+    debug!(size = values.len(), "Received batch");
+    let entries = values
+        .into_iter()
+        .filter_map(|value| {
+            if let Some(response) = ClientResponse::parse(&value.to_string()) {
+                sampler.deliver(response);
+                return None;
+            }
+            let id = value.get("id").cloned();
+            Some(
+                serde_json::from_value::<JsonRpcRequest>(value).map_err(|e| {
+                    JsonRpcResponse::error(id, -32600, format!("Invalid Request: {}", e))
+                }),
+            )
+        })
+        .collect();
+    (entries, true)
+}
+
+/// Whether handling `request` leaves stored and connection state unchanged
+fn leaves_state_unchanged(request: &JsonRpcRequest) -> bool {
+    match request.method.as_str() {
+        "tools/call" => request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("name"))
+            .and_then(Value::as_str)
+            .is_some_and(|name| tool_annotations(name).read_only_hint),
+        "tools/list"
+        | "resources/list"
+        | "resources/templates/list"
+        | "resources/read"
+        | "prompts/list"
+        | "prompts/get"
+        | "completion/complete"
+        | "ping" => true,
+        _ => false,
+    }
+}
+
+/// Report a WebSocket failure as an I/O error
+fn ws_error(e: tokio_tungstenite::tungstenite::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
+
+/// The API key a WebSocket handshake carries, as a bearer token or in an
+/// `X-API-Key` header
+fn request_token(request: &WsRequest) -> Option<&str> {
+    let header = |name: &str| request.headers().get(name)?.to_str().ok();
+    header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| header("x-api-key"))
+        .map(str::trim)
+}
+
+/// Endpoint a plain HTTP probe of the WebSocket port asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Probe {
+    /// `/healthz`: the process is up.
+    Health,
+    /// `/readyz`: the server can serve requests.
+    Ready,
+}
+
+/// The probe endpoint a request line such as `GET /readyz HTTP/1.1` asks for
+fn probe_endpoint(request_line: &str) -> Option<Probe> {
+    let mut parts = request_line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    match parts.next()?.split('?').next()? {
+        "/healthz" => Some(Probe::Health),
+        "/readyz" => Some(Probe::Ready),
+        _ => None,
+    }
+}
+
+/// Answer a probe over plain HTTP and close the connection
+///
+/// `/readyz` answers `503 Service Unavailable` while a readiness check fails;
+/// both endpoints describe the outcome in a JSON body.
+async fn answer_probe(state: &SharedState, stream: TcpStream, probe: Probe) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    // Read the request head so closing the connection doesn't reset it
+    let mut line = String::new();
+    while stream.read_line(&mut line).await? > 2 {
+        line.clear();
+    }
+
+    let (code, body) = match probe {
+        Probe::Health => (StatusCode::OK, serde_json::json!({"status": "ok"})),
+        Probe::Ready => {
+            let status = server_status(state).await;
+            let code = if status.ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            (code, serde_json::to_value(status)?)
+        }
+    };
+    debug!(probe = ?probe, status = %code, "Answering probe");
+
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code.as_u16(),
+        code.canonical_reason().unwrap_or_default(),
+        body.len(),
+        body
+    );
+    let stream = stream.get_mut();
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Whether a client with `scope` may call `tool_name`
+///
+/// Only tool calls are gated: every other method, `initialize` included,
+/// changes nothing beyond the client's own connection.
+fn scope_allows(scope: AuthScope, tool_name: &str) -> bool {
+    scope == AuthScope::Full || tool_annotations(tool_name).read_only_hint
 }
 
-/// MCP Server running over stdio.
+/// Write one JSON-RPC message to `sink`
+async fn write_message(
+    sink: &mut impl MessageSink,
+    message: &impl Serialize,
+) -> std::io::Result<()> {
+    let json = serde_json::to_string(message)?;
+    debug!(message = %json, "Sending message");
+    sink.send_json(json).await
+}
+
+/// MCP Server running over stdio or WebSocket.
 ///
-/// Handles JSON-RPC 2.0 messages over stdin/stdout for MCP protocol
-/// communication with clients.
+/// Handles JSON-RPC 2.0 messages for MCP protocol communication with
+/// clients; `config.transport` picks the transport.
 pub struct McpServer {
-    /// Shared application state.
-    state: SharedState,
+    /// Application state of this client.
+    ///
+    /// Starts as the server's state; `initialize` replaces it with one
+    /// confined to the client's workspace and attributing audit entries to
+    /// it, leaving other clients' state untouched.
+    state: RwLock<SharedState>,
+    /// Minimum level of log messages sent to the client, set with
+    /// `logging/setLevel`.
+    log_level: Mutex<LogLevel>,
+    /// What the client authenticated for.
+    scope: AuthScope,
+    /// The network client served, whose requests count against its quotas.
+    client: Option<String>,
+    /// Sessions the client subscribed to with `resources/subscribe`.
+    subscriptions: Mutex<HashSet<String>>,
 }
 
 impl McpServer {
     /// Create a new MCP server
     pub fn new(state: SharedState) -> Self {
-        Self { state }
+        Self {
+            state: RwLock::new(state),
+            log_level: Mutex::new(LogLevel::default()),
+            scope: AuthScope::Full,
+            client: None,
+            subscriptions: Mutex::new(HashSet::new()),
+        }
     }
 
-    /// Run the server using async stdio
+    /// The state requests are currently served with
+    fn state(&self) -> SharedState {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(&state)
+    }
+
+    /// Count the requests served against `client`'s quotas
+    pub fn with_client(mut self, client: impl Into<String>) -> Self {
+        self.client = Some(client.into());
+        self
+    }
+
+    /// Limit the client to the tools `scope` allows
+    pub fn with_scope(mut self, scope: AuthScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Run the server on the configured transport
+    ///
+    /// Also starts the background retention and database maintenance tasks
+    /// when they are configured; they are stopped when the server shuts down.
     pub async fn run(&self) -> std::io::Result<()> {
         info!("MCP Langbase Reasoning Server starting...");
+        let state = self.state();
+
+        for entry in unknown_tool_entries(&state.config.tools) {
+            warn!(entry = %entry, "TOOLS_ENABLED/TOOLS_DISABLED entry names no tool group or tool");
+        }
 
-        let stdin = tokio::io::stdin();
+        let retention_task =
+            spawn_retention_task(state.storage.clone(), state.config.retention.clone());
+        let maintenance_task = spawn_maintenance_task(
+            state.storage.clone(),
+            state.config.database.maintenance.clone(),
+            state.maintenance.clone(),
+        );
+
+        let result = match state.config.transport.kind {
+            TransportKind::Stdio => self.serve_stdio().await,
+            TransportKind::WebSocket => {
+                let listener = TcpListener::bind(state.config.transport.ws_bind).await?;
+                tokio::select! {
+                    result = self.serve_ws(listener) => result,
+                    _ = tokio::signal::ctrl_c() => {
+                        info!("Interrupt received, shutting down");
+                        Ok(())
+                    }
+                }
+            }
+        };
+
+        for task in [retention_task, maintenance_task].into_iter().flatten() {
+            task.abort();
+        }
+
+        result
+    }
+
+    /// Read JSON-RPC requests from stdin and write responses to stdout until EOF
+    async fn serve_stdio(&self) -> std::io::Result<()> {
+        let mut stdin = BufReader::new(tokio::io::stdin()).lines();
         let mut stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
-        let mut line = String::new();
+        self.serve(&mut stdin, &mut stdout).await?;
+        info!("EOF received, shutting down");
+        Ok(())
+    }
+
+    /// Accept WebSocket clients on `listener` until the server is stopped
+    ///
+    /// Each connection is served on its own task, one text frame per
+    /// JSON-RPC message. Connections share the server's storage, caches, and
+    /// quotas, but each picks its own workspace and audit actor. With
+    /// `config.transport.api_keys` set, the handshake must carry one of the
+    /// keys, and the connection gets that key's scope. Plain HTTP `GET
+    /// /healthz` and `GET /readyz` requests are answered without a key.
+    pub async fn serve_ws(&self, listener: TcpListener) -> std::io::Result<()> {
+        info!(addr = %listener.local_addr()?, "Listening for MCP clients over WebSocket");
 
         loop {
-            line.clear();
-            let bytes_read = reader.read_line(&mut line).await?;
+            let (stream, peer) = listener.accept().await?;
+            let state = self.state();
+            tokio::spawn(async move {
+                if let Err(e) = Self::serve_ws_connection(state, stream, peer).await {
+                    warn!(peer = %peer, error = %e, "WebSocket connection failed");
+                }
+            });
+        }
+    }
 
-            // EOF reached
-            if bytes_read == 0 {
-                info!("EOF received, shutting down");
-                break;
-            }
+    /// Authenticate one WebSocket client and serve it until it disconnects,
+    /// or answer a health probe
+    async fn serve_ws_connection(
+        state: SharedState,
+        stream: TcpStream,
+        peer: SocketAddr,
+    ) -> std::io::Result<()> {
+        let mut head = [0u8; 64];
+        let read = stream.peek(&mut head).await?;
+        if let Some(probe) = probe_endpoint(&String::from_utf8_lossy(&head[..read])) {
+            return answer_probe(&state, stream, probe).await;
+        }
 
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
+        let mut key = None;
+        // The rejection type is fixed by tungstenite's handshake callback
+        #[allow(clippy::result_large_err)]
+        let authenticate = |request: &WsRequest, response: WsResponse| {
+            if !state.config.transport.requires_auth() {
+                return Ok(response);
+            }
+            match request_token(request).and_then(|t| state.config.transport.key_position(t)) {
+                Some(position) => {
+                    key = Some(position);
+                    Ok(response)
+                }
+                None => {
+                    warn!(peer = %peer, "Rejecting WebSocket client without a valid API key");
+                    let mut rejection = WsErrorResponse::new(Some("Unauthorized".to_string()));
+                    *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+                    Err(rejection)
+                }
             }
+        };
+        let socket = tokio_tungstenite::accept_hdr_async(stream, authenticate)
+            .await
+            .map_err(ws_error)?;
+        // Clients are told apart by key, or by address without keys
+        let (client, scope) = match key {
+            Some(position) => (
+                format!("api-key-{}", position + 1),
+                state.config.transport.api_keys[position].scope,
+            ),
+            None => (peer.ip().to_string(), AuthScope::Full),
+        };
+        info!(peer = %peer, client = %client, scope = ?scope, "WebSocket client connected");
 
-            debug!(request = %trimmed, "Received request");
+        let server = McpServer::new(state).with_scope(scope).with_client(client);
+        let (mut sink, mut incoming) = socket.split();
+        server.serve(&mut incoming, &mut sink).await?;
 
-            let response = match serde_json::from_str::<JsonRpcRequest>(trimmed) {
-                Ok(request) => self.handle_request(request).await,
-                Err(e) => {
-                    error!(error = %e, "Failed to parse request");
-                    Some(JsonRpcResponse::error(
-                        None,
-                        -32700,
-                        format!("Parse error: {}", e),
-                    ))
-                }
+        info!(peer = %peer, "WebSocket client disconnected");
+        Ok(())
+    }
+
+    /// Handle messages from `source` one at a time until it is exhausted
+    ///
+    /// Messages that arrive while a request is handled are read straight
+    /// away, so a `notifications/cancelled` for that request can stop it
+    /// and responses to the server's sampling requests reach it; the others
+    /// are queued and handled in order once it completes. Between requests,
+    /// changes to subscribed sessions are sent as they happen.
+    async fn serve(
+        &self,
+        source: &mut impl MessageSource,
+        sink: &mut impl MessageSink,
+    ) -> std::io::Result<()> {
+        let mut inbox = Inbox {
+            source,
+            queued: VecDeque::new(),
+            closed: false,
+        };
+        let mut notifications = mpsc::unbounded_channel();
+        let sampler = Arc::new(ClientSampler::new(notifications.0.clone()));
+        let mut session_events = self.state().session_events.subscribe();
+
+        loop {
+            let message = match inbox.queued.pop_front() {
+                Some(message) => message,
+                None if inbox.closed => break,
+                None => tokio::select! {
+                    incoming = inbox.source.next_message() => match incoming? {
+                        Some(message) => message,
+                        None => break,
+                    },
+                    event = session_events.recv() => {
+                        self.notify_session_changed(sink, event).await?;
+                        continue;
+                    }
+                },
             };
+            self.process_message(&message, &mut inbox, sink, &mut notifications, &sampler)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Send `notifications/resources/updated` if `event` changed a session
+    /// the client subscribed to
+    async fn notify_session_changed(
+        &self,
+        sink: &mut impl MessageSink,
+        event: Result<SessionEvent, RecvError>,
+    ) -> std::io::Result<()> {
+        let changed: Vec<String> = {
+            let subscriptions = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+            match event {
+                Ok(event) if subscriptions.contains(&event.session_id) => vec![event.session_id],
+                // Any subscribed session may have changed in the events missed
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(
+                        missed,
+                        "Missed session events, reporting every subscription"
+                    );
+                    subscriptions.iter().cloned().collect()
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        for session_id in changed {
+            debug!(session_id = %session_id, "Notifying subscriber of session change");
+            let notification = JsonRpcNotification::resource_updated(&session_uri(&session_id));
+            write_message(sink, &notification).await?;
+        }
+        Ok(())
+    }
+
+    /// Handle one incoming JSON-RPC message and write what it produces
+    ///
+    /// Notifications and requests raised while the request is handled are
+    /// written as they arrive, always before the request's response. A
+    /// request cancelled by the client gets no response. Responses from the
+    /// client are handed to `sampler`.
+    async fn process_message<S: MessageSource>(
+        &self,
+        message: &str,
+        inbox: &mut Inbox<'_, S>,
+        sink: &mut impl MessageSink,
+        (notify_tx, notify_rx): &mut (
+            mpsc::UnboundedSender<ServerMessage>,
+            mpsc::UnboundedReceiver<ServerMessage>,
+        ),
+        sampler: &Arc<ClientSampler>,
+    ) -> std::io::Result<()> {
+        let trimmed = message.trim();
+        if trimmed.is_empty() {
+            return Ok(());
+        }
 
-            // Only send response if not a notification (per JSON-RPC 2.0 spec)
-            if let Some(response) = response {
-                let response_json = serde_json::to_string(&response)?;
-                debug!(response = %response_json, "Sending response");
+        if let Some(response) = ClientResponse::parse(trimmed) {
+            debug!(id = %response.id, "Received response from client");
+            sampler.deliver(response);
+            return Ok(());
+        }
+
+        debug!(request = %trimmed, "Received request");
 
-                stdout.write_all(response_json.as_bytes()).await?;
-                stdout.write_all(b"\n").await?;
-                stdout.flush().await?;
+        let (entries, is_batch) = parse_entries(trimmed, sampler);
+        let request_ids: Vec<Option<Value>> = entries
+            .iter()
+            .map(|entry| entry.as_ref().ok().and_then(|request| request.id.clone()))
+            .collect();
+        let cancels: Vec<CancellationToken> =
+            entries.iter().map(|_| CancellationToken::new()).collect();
+        let handling = self.handle_entries(entries, notify_tx, sampler, &cancels);
+        tokio::pin!(handling);
+        let responses = loop {
+            tokio::select! {
+                responses = &mut handling => break responses,
+                Some(notification) = notify_rx.recv() => {
+                    write_message(sink, &notification).await?;
+                }
+                incoming = inbox.source.next_message(), if !inbox.closed => {
+                    match incoming? {
+                        Some(incoming) => {
+                            let cancelled = cancelled_request(&incoming).and_then(|id| {
+                                request_ids.iter().position(|request_id| request_id.as_ref() == Some(&id))
+                            });
+                            if let Some(response) = ClientResponse::parse(&incoming) {
+                                sampler.deliver(response);
+                            } else if let Some(position) = cancelled {
+                                info!(id = ?request_ids[position], "Cancelling request at client's request");
+                                cancels[position].cancel();
+                            } else {
+                                inbox.queued.push_back(incoming);
+                            }
+                        }
+                        None => {
+                            inbox.closed = true;
+                            sampler.close();
+                        }
+                    }
+                }
             }
+        };
+        let responses: Vec<JsonRpcResponse> = responses
+            .into_iter()
+            .zip(&cancels)
+            .filter_map(|(response, cancel)| {
+                if cancel.is_cancelled() {
+                    debug!(id = ?response.as_ref().map(|r| &r.id), "Dropping response to cancelled request");
+                    return None;
+                }
+                response
+            })
+            .collect();
+
+        while let Ok(notification) = notify_rx.try_recv() {
+            write_message(sink, &notification).await?;
         }
 
+        // Only send responses to requests, not notifications (per JSON-RPC
+        // 2.0 spec); a batch is answered with one array in request order
+        if is_batch {
+            if !responses.is_empty() {
+                write_message(sink, &responses).await?;
+            }
+        } else if let Some(response) = responses.into_iter().next() {
+            write_message(sink, &response).await?;
+        }
         Ok(())
     }
 
+    /// Handle the entries of one message, returning their responses in order
+    ///
+    /// Consecutive entries that change no state, such as listings and
+    /// read-only tool calls, are handled concurrently; any other entry waits
+    /// for the ones before it and runs alone, so a batch's changes apply in
+    /// the order the client sent them.
+    async fn handle_entries(
+        &self,
+        entries: Vec<Result<JsonRpcRequest, JsonRpcResponse>>,
+        notify_tx: &mpsc::UnboundedSender<ServerMessage>,
+        sampler: &Arc<ClientSampler>,
+        cancels: &[CancellationToken],
+    ) -> Vec<Option<JsonRpcResponse>> {
+        let mut responses = Vec::with_capacity(entries.len());
+        let mut concurrent: Vec<BoxFuture<'_, Option<JsonRpcResponse>>> = Vec::new();
+        for (entry, cancel) in entries.into_iter().zip(cancels) {
+            let (handling, alone) = match entry {
+                Ok(request) => {
+                    let alone = !leaves_state_unchanged(&request);
+                    let handling = self.handle_request(request, notify_tx, sampler, cancel);
+                    (handling.boxed(), alone)
+                }
+                Err(response) => (future::ready(Some(response)).boxed(), false),
+            };
+            if alone {
+                responses.extend(future::join_all(std::mem::take(&mut concurrent)).await);
+                responses.push(handling.await);
+            } else {
+                concurrent.push(handling);
+            }
+        }
+        responses.extend(future::join_all(concurrent).await);
+        responses
+    }
+
     /// Handle a single JSON-RPC request
     /// Returns None for notifications (requests without id) per JSON-RPC 2.0 spec
-    async fn handle_request(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+    ///
+    /// Cancelling `cancel` stops the pipe calls of a tools/call request.
+    async fn handle_request(
+        &self,
+        request: JsonRpcRequest,
+        notifications: &mpsc::UnboundedSender<ServerMessage>,
+        sampler: &Arc<ClientSampler>,
+        cancel: &CancellationToken,
+    ) -> Option<JsonRpcResponse> {
         // Check if this is a notification (no id = no response required)
         let is_notification = request.id.is_none();
 
+        if let (Some(client), false) = (&self.client, is_notification) {
+            let tool_call = request.method == "tools/call";
+            if let Err(exceeded) = self.state().client_quotas.check(client, tool_call) {
+                warn!(client = %client, limit = exceeded.limit, "Refusing request over client quota");
+                return Some(JsonRpcResponse::quota_exceeded(request.id, &exceeded));
+            }
+        }
+
         match request.method.as_str() {
-            "initialize" => Some(self.handle_initialize(request.id)),
+            "initialize" => Some(self.handle_initialize(request.id, request.params, sampler)),
             "initialized" => {
                 // Notification - no response per JSON-RPC 2.0
                 debug!("Received initialized notification");
                 None
             }
             "notifications/cancelled" => {
-                // Notification - no response
-                debug!("Received cancelled notification");
+                // Notification - no response. Cancellations of a request in
+                // flight are picked up while it is handled, so this one
+                // names a request that has already completed.
+                debug!("Received cancelled notification for no request in flight");
                 None
             }
-            "tools/list" => Some(self.handle_tools_list(request.id)),
-            "tools/call" => Some(self.handle_tool_call(request.id, request.params).await),
+            "tools/list" => Some(self.handle_tools_list(request.id, request.params)),
+            "tools/call" => Some(
+                self.handle_tool_call(request.id, request.params, notifications, sampler, cancel)
+                    .await,
+            ),
+            "resources/list" => Some(self.handle_resources_list(request.id).await),
+            "resources/templates/list" => Some(JsonRpcResponse::success(
+                request.id,
+                serde_json::json!({ "resourceTemplates": resource_templates() }),
+            )),
+            "resources/read" => Some(self.handle_resources_read(request.id, request.params).await),
+            "resources/subscribe" => Some(self.handle_subscribe(request.id, request.params).await),
+            "resources/unsubscribe" => Some(self.handle_unsubscribe(request.id, request.params)),
+            "prompts/list" => Some(JsonRpcResponse::success(
+                request.id,
+                serde_json::json!({ "prompts": list_prompts() }),
+            )),
+            "prompts/get" => Some(self.handle_prompts_get(request.id, request.params)),
+            "logging/setLevel" => Some(self.handle_set_level(request.id, request.params)),
+            "completion/complete" => Some(self.handle_complete(request.id, request.params).await),
             "ping" => Some(JsonRpcResponse::success(
                 request.id,
                 Value::Object(Default::default()),
@@ -271,15 +1173,66 @@ impl McpServer {
     }
 
     /// Handle initialize request
-    fn handle_initialize(&self, id: Option<Value>) -> JsonRpcResponse {
+    ///
+    /// The client's self-reported name is used as the actor in audit entries,
+    /// and its sampling capability decides whether `sampler` may send it
+    /// requests. The actor and any selected workspace only apply to this
+    /// client's requests.
+    fn handle_initialize(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+        sampler: &ClientSampler,
+    ) -> JsonRpcResponse {
         info!("Handling initialize request");
 
+        let supports_sampling = params
+            .as_ref()
+            .and_then(|p| p.get("capabilities"))
+            .and_then(|c| c.get("sampling"))
+            .is_some_and(|s| !s.is_null());
+        if supports_sampling {
+            info!("Client supports sampling");
+        }
+        sampler.set_supported(supports_sampling);
+
+        let mut state = self.state();
+        if let Some(client_name) = params
+            .as_ref()
+            .and_then(|p| p.get("clientInfo"))
+            .and_then(|c| c.get("name"))
+            .and_then(|n| n.as_str())
+        {
+            info!(client = %client_name, "Client identified");
+            state = Arc::new(state.with_actor(format!("mcp:{}", client_name)));
+        }
+
+        if let Some(workspace_id) = params
+            .as_ref()
+            .and_then(|p| p.get("initializationOptions"))
+            .and_then(|o| o.get("workspace_id"))
+            .and_then(|w| w.as_str())
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+        {
+            info!(workspace = %workspace_id, "Workspace selected");
+            state = Arc::new(state.with_workspace(workspace_id));
+        }
+        *self.state.write().unwrap_or_else(|e| e.into_inner()) = state;
+
         let result = InitializeResult {
             protocol_version: "2024-11-05".to_string(),
             capabilities: Capabilities {
                 tools: ToolCapabilities {
                     list_changed: false,
                 },
+                resources: ResourceCapabilities {
+                    subscribe: true,
+                    list_changed: false,
+                },
+                prompts: PromptCapabilities::default(),
+                logging: LoggingCapabilities::default(),
+                completions: CompletionCapabilities::default(),
             },
             server_info: ServerInfo {
                 name: "mcp-langbase-reasoning".to_string(),
@@ -297,110 +1250,332 @@ impl McpServer {
     }
 
     /// Handle tools/list request
-    fn handle_tools_list(&self, id: Option<Value>) -> JsonRpcResponse {
+    ///
+    /// Only the tools selected by `config.tools` are listed. With a page size
+    /// configured, each response holds one page and a `nextCursor` for the
+    /// next.
+    fn handle_tools_list(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
         info!("Handling tools/list request");
 
-        let tools = vec![
-            // Phase 1-2 tools
-            get_linear_tool(),
-            get_tree_tool(),
-            get_tree_focus_tool(),
-            get_tree_list_tool(),
-            get_tree_complete_tool(),
-            get_divergent_tool(),
-            get_reflection_tool(),
-            get_reflection_evaluate_tool(),
-            // Phase 3 tools
-            get_backtracking_tool(),
-            get_backtracking_checkpoint_tool(),
-            get_backtracking_list_tool(),
-            get_auto_tool(),
-            get_got_init_tool(),
-            get_got_generate_tool(),
-            get_got_score_tool(),
-            get_got_aggregate_tool(),
-            get_got_refine_tool(),
-            get_got_prune_tool(),
-            get_got_finalize_tool(),
-            get_got_state_tool(),
-            // Phase 4 tools - Bias & Fallacy Detection
-            get_detect_biases_tool(),
-            get_detect_fallacies_tool(),
-            // Phase 5 tools - Workflow Presets
-            get_preset_list_tool(),
-            get_preset_run_tool(),
-            // Phase 6 tools - Decision Framework & Evidence Assessment
-            get_make_decision_tool(),
-            get_analyze_perspectives_tool(),
-            get_assess_evidence_tool(),
-            get_probabilistic_tool(),
-            // Metrics tools
-            get_metrics_summary_tool(),
-            get_metrics_by_pipe_tool(),
-            get_metrics_invocations_tool(),
-            get_fallback_metrics_tool(),
-            // Debug tools
-            get_debug_config_tool(),
-            // Phase 6 tools - Time Machine (Timeline, MCTS, Counterfactual)
-            get_timeline_create_tool(),
-            get_timeline_branch_tool(),
-            get_timeline_compare_tool(),
-            get_timeline_merge_tool(),
-            get_mcts_explore_tool(),
-            get_auto_backtrack_tool(),
-            get_counterfactual_tool(),
-        ];
-
-        JsonRpcResponse::success(
-            id,
-            serde_json::json!({
-                "tools": tools
-            }),
-        )
-    }
+        let mut tools = all_tools();
+        tools.retain(|tool| {
+            is_tool_enabled(&self.state().config.tools, &tool.name)
+                && scope_allows(self.scope, &tool.name)
+        });
 
-    /// Handle tools/call request
-    async fn handle_tool_call(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
-        let params: ToolCallParams = match params {
-            Some(p) => match serde_json::from_value(p) {
-                Ok(p) => p,
-                Err(e) => {
-                    return JsonRpcResponse::error(id, -32602, format!("Invalid params: {}", e));
+        let limit = self.state().config.tools.page_size.unwrap_or(tools.len()) as u32;
+        let cursor = params
+            .as_ref()
+            .and_then(|p| p.get("cursor"))
+            .and_then(Value::as_str);
+        let pagination = match cursor {
+            Some(cursor) => match Pagination::from_cursor(limit, cursor) {
+                Some(pagination) => pagination,
+                None => {
+                    return JsonRpcResponse::error(
+                        id,
+                        -32602,
+                        format!("Invalid params: invalid cursor {}", cursor),
+                    )
                 }
             },
-            None => {
-                return JsonRpcResponse::error(id, -32602, "Missing params");
-            }
+            None => Pagination::first(limit),
         };
+        let page = Page::from_all(tools, pagination);
 
-        info!(tool = %params.name, "Handling tool call");
+        let mut result = serde_json::json!({
+            "tools": page.items
+        });
+        if let Some(next_cursor) = page.next_cursor() {
+            result["nextCursor"] = Value::String(next_cursor);
+        }
+        JsonRpcResponse::success(id, result)
+    }
 
-        let (content, is_error) =
-            match handle_tool_call(&self.state, &params.name, params.arguments).await {
-                Ok(result) => {
-                    let text = serde_json::to_string_pretty(&result).unwrap_or_else(|e| {
-                        error!(error = %e, "Failed to serialize tool result");
-                        format!("{{\"error\": \"Serialization failed: {}\"}}", e)
-                    });
-                    (
-                        ToolResultContent {
-                            content_type: "text".to_string(),
-                            text,
-                        },
-                        None,
-                    )
+    /// Handle resources/list request
+    async fn handle_resources_list(&self, id: Option<Value>) -> JsonRpcResponse {
+        match list_resources(&self.state()).await {
+            Ok(resources) => JsonRpcResponse::success(
+                id,
+                serde_json::json!({
+                    "resources": resources
+                }),
+            ),
+            Err(e) => {
+                error!(error = %e, "Failed to list resources");
+                JsonRpcResponse::error(id, -32603, format!("Internal error: {}", e))
+            }
+        }
+    }
+
+    /// Handle resources/read request
+    async fn handle_resources_read(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+    ) -> JsonRpcResponse {
+        let uri = match params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(|u| u.as_str())
+        {
+            Some(uri) => uri,
+            None => return JsonRpcResponse::error(id, -32602, "Missing params: uri"),
+        };
+
+        match read_resource(&self.state(), uri).await {
+            Ok(contents) => JsonRpcResponse::success(
+                id,
+                serde_json::json!({
+                    "contents": [contents]
+                }),
+            ),
+            // MCP reserves -32002 for unknown resources
+            Err(e @ McpError::ResourceNotFound { .. }) => {
+                JsonRpcResponse::error(id, -32002, e.to_string())
+            }
+            Err(e) => {
+                error!(error = %e, uri = %uri, "Failed to read resource");
+                JsonRpcResponse::error(id, -32603, format!("Internal error: {}", e))
+            }
+        }
+    }
+
+    /// Handle prompts/get request
+    fn handle_prompts_get(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let params: PromptGetParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(id, -32602, format!("Invalid params: {}", e));
+            }
+            None => return JsonRpcResponse::error(id, -32602, "Missing params"),
+        };
+
+        match get_prompt(&params.name, &params.arguments) {
+            Ok(result) => match serde_json::to_value(result) {
+                Ok(val) => JsonRpcResponse::success(id, val),
+                Err(e) => JsonRpcResponse::error(id, -32603, format!("Internal error: {}", e)),
+            },
+            Err(e) => JsonRpcResponse::error(id, -32602, e.to_string()),
+        }
+    }
+
+    /// Handle logging/setLevel request
+    /// Handle completion/complete request
+    ///
+    /// Besides prompts and resource templates, a `ref/prompt` may name a
+    /// tool, so clients can complete tool arguments such as `session_id`.
+    async fn handle_complete(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let params: CompleteParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(id, -32602, format!("Invalid params: {}", e));
+            }
+            None => return JsonRpcResponse::error(id, -32602, "Missing params"),
+        };
+
+        let known = match &params.reference {
+            CompletionReference::Prompt { name } => {
+                list_prompts().iter().any(|prompt| &prompt.name == name)
+                    || is_tool_enabled(&self.state().config.tools, name)
+                        && all_tools().iter().any(|tool| &tool.name == name)
+            }
+            CompletionReference::Resource { uri } => resource_templates()
+                .iter()
+                .any(|template| &template.uri_template == uri),
+        };
+        if !known {
+            return JsonRpcResponse::error(
+                id,
+                -32602,
+                format!("Invalid params: unknown reference {:?}", params.reference),
+            );
+        }
+
+        debug!(argument = %params.argument.name, "Handling completion request");
+        match complete(&self.state(), &params).await {
+            Ok(completion) => {
+                JsonRpcResponse::success(id, serde_json::json!({ "completion": completion }))
+            }
+            Err(e) => {
+                error!(error = %e, "Completion failed");
+                JsonRpcResponse::error(id, -32603, format!("Internal error: {}", e))
+            }
+        }
+    }
+
+    async fn handle_subscribe(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let Some(uri) = params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(|u| u.as_str())
+        else {
+            return JsonRpcResponse::error(id, -32602, "Missing params: uri");
+        };
+        // MCP reserves -32002 for unknown resources
+        let not_found =
+            || JsonRpcResponse::error(id.clone(), -32002, format!("Resource not found: {}", uri));
+        let Some(session_id) = session_id_from_uri(uri) else {
+            return not_found();
+        };
+
+        match self.state().storage.get_session(session_id).await {
+            Ok(Some(_)) => {
+                info!(uri = %uri, "Client subscribed to resource");
+                self.subscriptions
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(session_id.to_string());
+                JsonRpcResponse::success(id, Value::Object(Default::default()))
+            }
+            Ok(None) => not_found(),
+            Err(e) => {
+                error!(error = %e, uri = %uri, "Failed to look up subscribed resource");
+                JsonRpcResponse::error(id, -32603, format!("Internal error: {}", e))
+            }
+        }
+    }
+
+    fn handle_unsubscribe(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let Some(uri) = params
+            .as_ref()
+            .and_then(|p| p.get("uri"))
+            .and_then(|u| u.as_str())
+        else {
+            return JsonRpcResponse::error(id, -32602, "Missing params: uri");
+        };
+
+        info!(uri = %uri, "Client unsubscribed from resource");
+        if let Some(session_id) = session_id_from_uri(uri) {
+            self.subscriptions
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(session_id);
+        }
+        JsonRpcResponse::success(id, Value::Object(Default::default()))
+    }
+
+    fn handle_set_level(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let params: SetLevelParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(id, -32602, format!("Invalid params: {}", e));
+            }
+            None => return JsonRpcResponse::error(id, -32602, "Missing params"),
+        };
+
+        info!(level = ?params.level, "Setting client log level");
+        if let Ok(mut level) = self.log_level.lock() {
+            *level = params.level;
+        }
+        JsonRpcResponse::success(id, Value::Object(Default::default()))
+    }
+
+    /// Handle tools/call request
+    ///
+    /// If the client supplied a progress token, pipe output and partial
+    /// results are streamed back as progress notifications while the tool
+    /// runs. Key events of the call are sent as log messages. Cancelling
+    /// `cancel` makes the tool's pipe calls fail with a cancellation error.
+    /// Pipes routed to the sampling provider are sent to the client via
+    /// `sampler`.
+    async fn handle_tool_call(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+        notifications: &mpsc::UnboundedSender<ServerMessage>,
+        sampler: &Arc<ClientSampler>,
+        cancel: &CancellationToken,
+    ) -> JsonRpcResponse {
+        let params: ToolCallParams = match params {
+            Some(p) => match serde_json::from_value(p) {
+                Ok(p) => p,
+                Err(e) => {
+                    return JsonRpcResponse::error(id, -32602, format!("Invalid params: {}", e));
                 }
-                Err(e) => (
+            },
+            None => {
+                return JsonRpcResponse::error(id, -32602, "Missing params");
+            }
+        };
+
+        info!(tool = %params.name, "Handling tool call");
+
+        let state = self.state();
+        let sampler: Arc<dyn Sampler> = sampler.clone();
+        let min_level = self.log_level.lock().map(|l| *l).unwrap_or_default();
+        // The handler future is boxed: inlined, every tool's state machine
+        // lands on the connection task's stack
+        let call = with_quota_client(
+            self.client.clone(),
+            with_client_log_sink(
+                client_log_sink(min_level, notifications.clone()),
+                with_sampler(
+                    sampler,
+                    with_cancellation(
+                        cancel.clone(),
+                        Box::pin(handle_tool_call(&state, &params.name, params.arguments)),
+                    ),
+                ),
+            ),
+        );
+        let result = match params.meta.and_then(|m| m.progress_token) {
+            // Disabled tools are hidden from clients, but presets still run them
+            _ if !is_tool_enabled(&state.config.tools, &params.name) => {
+                warn!(tool = %params.name, "Rejecting call to disabled tool");
+                Err(McpError::UnknownTool {
+                    tool_name: params.name.clone(),
+                })
+            }
+            _ if !scope_allows(self.scope, &params.name) => {
+                warn!(tool = %params.name, "Rejecting mutating call from read-only client");
+                Err(McpError::Unauthorized {
+                    message: format!(
+                        "{} changes stored state; the API key is read-only",
+                        params.name
+                    ),
+                })
+            }
+            Some(token) => {
+                debug!(tool = %params.name, "Streaming tool output as progress");
+                let progress = Arc::new(AtomicU64::new(0));
+                let artifacts =
+                    artifact_sink(token.clone(), progress.clone(), notifications.clone());
+                let text = progress_sink(token, progress, notifications.clone());
+                with_stream_sink(text, with_artifact_sink(artifacts, call)).await
+            }
+            None => call.await,
+        };
+
+        let (content, structured_content, is_error) = match result {
+            Ok(result) => {
+                let text = serde_json::to_string_pretty(&result).unwrap_or_else(|e| {
+                    error!(error = %e, "Failed to serialize tool result");
+                    format!("{{\"error\": \"Serialization failed: {}\"}}", e)
+                });
+                (
                     ToolResultContent {
                         content_type: "text".to_string(),
-                        text: format!("Error: {}", e),
+                        text,
                     },
-                    Some(true),
-                ),
-            };
+                    // Structured content must be an object
+                    result.is_object().then_some(result),
+                    None,
+                )
+            }
+            Err(e) => (
+                ToolResultContent {
+                    content_type: "text".to_string(),
+                    text: format!("Error: {}", e),
+                },
+                None,
+                Some(true),
+            ),
+        };
 
         let tool_result = ToolCallResult {
             content: vec![content],
+            structured_content,
             is_error,
         };
 
@@ -414,11 +1589,167 @@ impl McpServer {
     }
 }
 
+/// Definitions of every tool, before `config.tools` is applied
+pub(super) fn all_tools() -> Vec<Tool> {
+    let tools = vec![
+        // Phase 1-2 tools
+        get_linear_tool(),
+        get_tree_tool(),
+        get_tree_focus_tool(),
+        get_tree_list_tool(),
+        get_tree_complete_tool(),
+        get_tree_compare_tool(),
+        get_tree_merge_tool(),
+        get_tree_export_tool(),
+        get_divergent_tool(),
+        get_divergent_evaluate_tool(),
+        get_reflection_tool(),
+        get_reflection_evaluate_tool(),
+        // Phase 3 tools
+        get_backtracking_tool(),
+        get_backtracking_checkpoint_tool(),
+        get_backtracking_list_tool(),
+        get_backtrack_diff_tool(),
+        get_auto_tool(),
+        get_routing_feedback_tool(),
+        get_got_init_tool(),
+        get_got_generate_tool(),
+        get_got_score_tool(),
+        get_got_rank_tool(),
+        get_got_aggregate_tool(),
+        get_got_aggregate_best_tool(),
+        get_got_refine_tool(),
+        get_got_prune_tool(),
+        get_got_finalize_tool(),
+        get_got_state_tool(),
+        get_got_export_tool(),
+        get_got_validate_tool(),
+        get_got_best_path_tool(),
+        get_got_resume_tool(),
+        // Phase 4 tools - Bias & Fallacy Detection
+        get_detect_biases_tool(),
+        get_detect_fallacies_tool(),
+        get_verify_calculations_tool(),
+        // Phase 5 tools - Workflow Presets
+        get_preset_list_tool(),
+        get_preset_run_tool(),
+        // Phase 6 tools - Decision Framework & Evidence Assessment
+        get_make_decision_tool(),
+        get_analyze_perspectives_tool(),
+        get_assess_evidence_tool(),
+        get_probabilistic_tool(),
+        // Metrics tools
+        get_metrics_summary_tool(),
+        get_metrics_by_pipe_tool(),
+        get_metrics_invocations_tool(),
+        get_fallback_metrics_tool(),
+        // Debug tools
+        get_debug_config_tool(),
+        get_pipe_status_tool(),
+        get_pipe_health_tool(),
+        get_budget_status_tool(),
+        get_quota_status_tool(),
+        get_server_status_tool(),
+        // Search tools
+        get_search_tool(),
+        get_similar_tool(),
+        get_session_thoughts_tool(),
+        // Thought revision tools
+        get_revise_tool(),
+        get_thought_history_tool(),
+        // Session organization tools
+        get_session_tag_tool(),
+        get_session_list_tool(),
+        get_session_delete_tool(),
+        get_session_restore_tool(),
+        get_session_fork_tool(),
+        // Knowledge base tools
+        get_knowledge_query_tool(),
+        get_knowledge_promote_tool(),
+        // Phase 6 tools - Time Machine (Timeline, MCTS, Counterfactual)
+        get_timeline_create_tool(),
+        get_timeline_branch_tool(),
+        get_timeline_compare_tool(),
+        get_timeline_merge_tool(),
+        get_mcts_explore_tool(),
+        get_auto_backtrack_tool(),
+        get_counterfactual_tool(),
+        get_counterfactual_minimal_change_tool(),
+        // Structured reasoning tools
+        get_debate_tool(),
+        get_root_cause_tool(),
+        get_plan_tool(),
+        get_plan_revise_tool(),
+        get_estimate_tool(),
+        get_swot_tool(),
+        get_swot_list_tool(),
+        get_hypothesis_tool(),
+        get_hypothesis_observe_tool(),
+        get_six_hats_tool(),
+        get_dialectic_tool(),
+        get_prioritize_tool(),
+        get_prioritize_revise_tool(),
+        get_negotiate_tool(),
+        get_risk_tool(),
+        get_risk_update_tool(),
+        get_systems_tool(),
+        get_code_explain_diff_tool(),
+        get_code_trace_flow_tool(),
+        get_code_edge_cases_tool(),
+        get_code_findings_tool(),
+        get_synthesize_tool(),
+    ];
+    tools
+        .into_iter()
+        .map(|mut tool| {
+            if accepts_critique(&tool) {
+                tool.input_schema["properties"]["critique"] = critique_schema();
+            }
+            tool
+        })
+        .map(|tool| Tool {
+            title: tool_title(&tool.name).map(String::from),
+            annotations: Some(tool_annotations(&tool.name)),
+            output_schema: tool_output_schema(&tool.name),
+            ..tool
+        })
+        .collect()
+}
+
+/// Schema for the optional model parameter overrides accepted by every tool
+/// that calls a pipe
+fn generation_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "description": "Optional model parameter overrides for this call",
+        "properties": {
+            "temperature": {
+                "type": "number",
+                "minimum": 0,
+                "maximum": 2,
+                "description": "Sampling temperature (0 = deterministic)"
+            },
+            "top_p": {
+                "type": "number",
+                "minimum": 0,
+                "maximum": 1,
+                "description": "Nucleus sampling probability mass"
+            },
+            "max_tokens": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of tokens to generate"
+            }
+        },
+        "additionalProperties": false
+    })
+}
+
 /// Get the linear reasoning tool definition
 fn get_linear_tool() -> Tool {
     Tool {
         name: "reasoning_linear".to_string(),
-        description: "Single-pass sequential reasoning. Process a thought and get a logical continuation or analysis.".to_string(),
+        description: "Single-pass sequential reasoning. Process a thought and get a logical continuation or analysis. A step can revise an earlier step, which is then marked superseded. Sessions can cap their steps, and a history that outgrows its token budget is summarized.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -435,11 +1766,31 @@ fn get_linear_tool() -> Tool {
                     "minimum": 0,
                     "maximum": 1,
                     "description": "Confidence threshold (0.0-1.0)"
-                }
+                },
+                "revises_thought_id": {
+                    "type": "string",
+                    "description": "An earlier thought in the session that this step corrects; it is marked superseded (requires session_id)"
+                },
+                "max_steps": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Most steps the session may hold; kept for later steps"
+                },
+                "context_budget": {
+                    "type": "integer",
+                    "minimum": 200,
+                    "description": "Token budget for the history sent with each step (default: 4000); once exceeded the history is summarized. Kept for later steps"
+                },
+                "use_thread": {
+                    "type": "boolean",
+                    "description": "Bind the session to a Langbase thread so later steps reuse server-side history (false unbinds)"
+                },
+                "generation": generation_schema()
             },
             "required": ["content"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -488,11 +1839,13 @@ fn get_tree_tool() -> Tool {
                         "required": ["to_branch", "type"]
                     },
                     "description": "Optional cross-references to other branches"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["content"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -517,6 +1870,7 @@ fn get_tree_focus_tool() -> Tool {
             "required": ["session_id", "branch_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -524,18 +1878,30 @@ fn get_tree_focus_tool() -> Tool {
 fn get_tree_list_tool() -> Tool {
     Tool {
         name: "reasoning_tree_list".to_string(),
-        description: "List all branches in a session.".to_string(),
+        description: "List all branches in a session. Returns the branches as an array, or a page with count, has_more and next_cursor when limit or cursor is given.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "session_id": {
                     "type": "string",
                     "description": "The session ID"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 500,
+                    "default": 100,
+                    "description": "Maximum number of branches to return"
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "next_cursor from the previous page"
                 }
             },
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -559,6 +1925,96 @@ fn get_tree_complete_tool() -> Tool {
             "required": ["branch_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
+    }
+}
+
+/// Get the tree compare tool definition
+fn get_tree_compare_tool() -> Tool {
+    Tool {
+        name: "reasoning_tree_compare".to_string(),
+        description: "Compare tree branches: summarize each branch's chain of thoughts, score them against criteria, and recommend which branch to pursue. The recommendation is stored as a thought on that branch, with alternative cross-references to the others.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session containing the branches"
+                },
+                "branch_ids": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 2,
+                    "maxItems": 6,
+                    "description": "The branches to compare"
+                },
+                "criteria": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "maxItems": 8,
+                    "description": "Criteria to score each branch against (default: soundness, feasibility, impact)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id", "branch_ids"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the tree merge tool definition
+fn get_tree_merge_tool() -> Tool {
+    Tool {
+        name: "reasoning_tree_merge".to_string(),
+        description: "Merge two tree branches: reconcile their conclusions into a new branch that extends both, and mark the two source branches completed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session containing the branches"
+                },
+                "branch_a": {
+                    "type": "string",
+                    "description": "The first branch to merge"
+                },
+                "branch_b": {
+                    "type": "string",
+                    "description": "The second branch to merge"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id", "branch_a", "branch_b"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the tree export tool definition
+fn get_tree_export_tool() -> Tool {
+    Tool {
+        name: "reasoning_tree_export".to_string(),
+        description: "Export a session's branch hierarchy as a Mermaid flowchart or ASCII tree for docs and pull requests. Each branch shows its thought count, state, and confidence.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["mermaid", "ascii"],
+                    "default": "mermaid",
+                    "description": "Output format"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
     }
 }
 
@@ -596,16 +2052,82 @@ fn get_divergent_tool() -> Tool {
                     "type": "boolean",
                     "description": "Enable maximum creativity mode with contrarian viewpoints"
                 },
+                "technique": {
+                    "type": "string",
+                    "enum": ["scamper", "reverse_brainstorming", "random_stimulus", "triz"],
+                    "description": "Structured ideation technique; each perspective is tagged with it"
+                },
+                "shortlist": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Cluster perspectives by theme and return this many of the best-ranked clusters"
+                },
                 "confidence": {
                     "type": "number",
                     "minimum": 0,
                     "maximum": 1,
                     "description": "Confidence threshold (0.0-1.0, default: 0.7)"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["content"],
             "additionalProperties": false
         }),
+        ..Default::default()
+    }
+}
+
+/// Get the idea evaluation tool definition
+fn get_divergent_evaluate_tool() -> Tool {
+    Tool {
+        name: "reasoning_divergent_evaluate".to_string(),
+        description: "Score perspectives from reasoning_divergent against weighted criteria. The pipe scores each idea per criterion, the weighted means rank them, and the winners are handed to reasoning_make_decision as options.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session holding the perspectives"
+                },
+                "thought_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Perspective thoughts to evaluate (default: every perspective in the session)"
+                },
+                "criteria": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string", "description": "Criterion name" },
+                            "weight": { "type": "number", "exclusiveMinimum": 0, "description": "Importance weight" },
+                            "description": { "type": "string", "description": "Optional criterion description" }
+                        },
+                        "required": ["name", "weight"]
+                    },
+                    "minItems": 1,
+                    "description": "Weighted criteria to score the ideas against"
+                },
+                "question": {
+                    "type": "string",
+                    "description": "Question the winners should answer (default: the prompt that generated the perspectives)"
+                },
+                "winners": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 6,
+                    "description": "How many of the best-scoring ideas win (default: 3)"
+                },
+                "decide": {
+                    "type": "boolean",
+                    "description": "Hand two or more winners to decision mode as options (default: true)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id", "criteria"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
     }
 }
 
@@ -613,7 +2135,7 @@ fn get_divergent_tool() -> Tool {
 fn get_reflection_tool() -> Tool {
     Tool {
         name: "reasoning_reflection".to_string(),
-        description: "Meta-cognitive reasoning that analyzes and improves reasoning quality. Evaluates strengths, weaknesses, and provides recommendations.".to_string(),
+        description: "Meta-cognitive reasoning that analyzes and improves reasoning quality. Evaluates strengths, weaknesses, and provides recommendations. Critique and revision repeat until quality meets the threshold or stops improving, and every round's score and revision is returned. An optional rubric of weighted dimensions is scored per dimension, and its weighted mean becomes the quality score.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -645,13 +2167,35 @@ fn get_reflection_tool() -> Tool {
                     "maximum": 1,
                     "description": "Quality threshold to stop iterating (default: 0.8)"
                 },
+                "min_improvement": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Smallest quality gain over the best earlier round that keeps the rounds going (default: 0.02)"
+                },
+                "rubric": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string", "description": "Dimension name" },
+                            "weight": { "type": "number", "exclusiveMinimum": 0, "description": "Relative weight (default: 1)" },
+                            "description": { "type": "string", "description": "What the dimension measures" }
+                        },
+                        "required": ["name"]
+                    },
+                    "maxItems": 8,
+                    "description": "Weighted dimensions to score against; their weighted mean replaces the overall quality score"
+                },
                 "include_chain": {
                     "type": "boolean",
                     "description": "Whether to include full reasoning chain in context"
-                }
+                },
+                "generation": generation_schema()
             },
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -671,6 +2215,7 @@ fn get_reflection_evaluate_tool() -> Tool {
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -703,11 +2248,13 @@ fn get_backtracking_tool() -> Tool {
                     "minimum": 0,
                     "maximum": 1,
                     "description": "Confidence threshold (0.0-1.0, default: 0.8)"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["checkpoint_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -736,6 +2283,7 @@ fn get_backtracking_checkpoint_tool() -> Tool {
             "required": ["session_id", "name"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -743,18 +2291,54 @@ fn get_backtracking_checkpoint_tool() -> Tool {
 fn get_backtracking_list_tool() -> Tool {
     Tool {
         name: "reasoning_checkpoint_list".to_string(),
-        description: "List all checkpoints available for a session.".to_string(),
+        description: "List all checkpoints available for a session. Returns the checkpoints as an array, or a page with count, has_more and next_cursor when limit or cursor is given.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "session_id": {
                     "type": "string",
                     "description": "The session ID to list checkpoints for"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 500,
+                    "default": 100,
+                    "description": "Maximum number of checkpoints to return"
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "next_cursor from the previous page"
                 }
             },
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
+    }
+}
+
+/// Get the checkpoint diff tool definition
+fn get_backtrack_diff_tool() -> Tool {
+    Tool {
+        name: "reasoning_backtrack_diff".to_string(),
+        description: "Compare two checkpoints of a session before restoring one. Returns the change in thought, branch and graph counts, the thoughts added and removed, branches that appeared or changed state, and the key conclusions each checkpoint reached.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "checkpoint_a": {
+                    "type": "string",
+                    "description": "The earlier checkpoint"
+                },
+                "checkpoint_b": {
+                    "type": "string",
+                    "description": "The later checkpoint, in the same session"
+                }
+            },
+            "required": ["checkpoint_a", "checkpoint_b"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
     }
 }
 
@@ -762,7 +2346,7 @@ fn get_backtracking_list_tool() -> Tool {
 fn get_auto_tool() -> Tool {
     Tool {
         name: "reasoning_auto".to_string(),
-        description: "Automatically select the most appropriate reasoning mode based on content analysis. Routes to linear, tree, divergent, reflection, or got mode.".to_string(),
+        description: "Automatically select the most appropriate reasoning mode based on content analysis. Routes to linear, tree, divergent, reflection, or got mode. Outcomes of past decisions for similar content (session confidence, detections, feedback) are weighed into the choice, and each decision is stored for rating with reasoning_routing_feedback.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -777,27 +2361,67 @@ fn get_auto_tool() -> Tool {
                 },
                 "session_id": {
                     "type": "string",
-                    "description": "Optional session ID for context"
-                }
+                    "description": "Optional session ID for context; the session's results become the decision's outcome"
+                },
+                "use_history": {
+                    "type": "boolean",
+                    "description": "Weigh outcomes of past decisions for similar content (default: true)"
+                },
+                "generation": generation_schema()
             },
             "required": ["content"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the GoT initialization tool definition
-fn get_got_init_tool() -> Tool {
+/// Get the routing feedback tool definition
+fn get_routing_feedback_tool() -> Tool {
     Tool {
-        name: "reasoning_got_init".to_string(),
-        description: "Initialize a new Graph-of-Thoughts reasoning graph with a root node."
-            .to_string(),
+        name: "reasoning_routing_feedback".to_string(),
+        description: "Rate how well the mode chosen by reasoning_auto worked. The rating is combined with the session's confidence and detections into the decision's outcome, which later routing of similar content weighs.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "content": {
+                "routing_id": {
                     "type": "string",
-                    "description": "Initial thought content for the root node"
+                    "description": "Routing decision to rate (routing_id from reasoning_auto)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Rate the latest routing decision of this session instead"
+                },
+                "score": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "How well the routed mode worked (0.0-1.0)"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Optional note explaining the rating"
+                }
+            },
+            "required": ["score"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT initialization tool definition
+fn get_got_init_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_init".to_string(),
+        description: "Initialize a new Graph-of-Thoughts reasoning graph with a root node."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "Initial thought content for the root node"
                 },
                 "problem": {
                     "type": "string",
@@ -813,7 +2437,8 @@ fn get_got_init_tool() -> Tool {
                         "max_nodes": { "type": "integer", "minimum": 10, "maximum": 1000 },
                         "max_depth": { "type": "integer", "minimum": 1, "maximum": 20 },
                         "default_k": { "type": "integer", "minimum": 1, "maximum": 10 },
-                        "prune_threshold": { "type": "number", "minimum": 0, "maximum": 1 }
+                        "prune_threshold": { "type": "number", "minimum": 0, "maximum": 1 },
+                        "strict_dag": { "type": "boolean" }
                     },
                     "description": "Optional configuration overrides"
                 }
@@ -821,6 +2446,7 @@ fn get_got_init_tool() -> Tool {
             "required": ["content"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -850,11 +2476,13 @@ fn get_got_generate_tool() -> Tool {
                 "problem": {
                     "type": "string",
                     "description": "Optional problem context"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -878,11 +2506,62 @@ fn get_got_score_tool() -> Tool {
                 "problem": {
                     "type": "string",
                     "description": "Optional problem context"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["session_id", "node_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT rank tool definition
+fn get_got_rank_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_rank".to_string(),
+        description: "Rank sibling nodes by head-to-head comparison instead of absolute scores. Every pair is judged once per round (order alternating between rounds), ratings are fitted locally with Bradley-Terry or Elo over all stored comparisons between the nodes, and each node's score becomes its expected share of wins.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "parent_id": {
+                    "type": "string",
+                    "description": "Node whose generated children are ranked"
+                },
+                "node_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "minItems": 2,
+                    "maxItems": 8,
+                    "description": "Nodes to rank instead of the children of parent_id"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["bradley_terry", "elo"],
+                    "default": "bradley_terry",
+                    "description": "Rating model fitted to the comparisons"
+                },
+                "rounds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 3,
+                    "default": 1,
+                    "description": "Comparison rounds; two rounds cancel out position bias"
+                },
+                "problem": {
+                    "type": "string",
+                    "description": "Optional problem context"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
     }
 }
 
@@ -907,11 +2586,55 @@ fn get_got_aggregate_tool() -> Tool {
                 "problem": {
                     "type": "string",
                     "description": "Optional problem context"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["session_id", "node_ids"],
             "additionalProperties": false
         }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT aggregate-best tool definition
+fn get_got_aggregate_best_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_aggregate_best".to_string(),
+        description: "Merge the k highest-scoring active nodes into one aggregation node in a single call: selects them, synthesizes them through the pipe, wires aggregates edges, and optionally marks the result terminal.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "k": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "maximum": 10,
+                    "description": "Number of top-scoring active nodes to merge (default: 3)"
+                },
+                "min_score": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Only merge nodes scoring at least this much"
+                },
+                "mark_terminal": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Mark the aggregated node terminal, as finalize would"
+                },
+                "problem": {
+                    "type": "string",
+                    "description": "Optional problem context"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
     }
 }
 
@@ -934,11 +2657,13 @@ fn get_got_refine_tool() -> Tool {
                 "problem": {
                     "type": "string",
                     "description": "Optional problem context"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["session_id", "node_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -964,6 +2689,7 @@ fn get_got_prune_tool() -> Tool {
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -989,10 +2715,117 @@ fn get_got_finalize_tool() -> Tool {
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
 /// Get the GoT state tool definition
+fn get_got_export_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_export".to_string(),
+        description: "Export the reasoning graph as Graphviz DOT or GraphML for viewing in external tools. Includes node types, scores, depth, and pruned state.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["dot", "graphml"],
+                    "default": "dot",
+                    "description": "Output format"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT validate tool definition
+fn get_got_validate_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_validate".to_string(),
+        description: "Check the reasoning graph for structural issues: cycles, self-loops, dangling or duplicate edges, missing or multiple roots, unreachable nodes, depth mismatches, and terminal nodes left active. Reports whether the graph is a valid DAG.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT best path tool definition
+fn get_got_best_path_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_best_path".to_string(),
+        description: "Extract the highest-scoring path from a root to a terminal node (or leaf, before finalize) as an ordered explanation with per-step scores. Computed locally from stored scores and edges; no LLM call.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "terminal_node_id": {
+                    "type": "string",
+                    "description": "Node the path must end at (default: the best-scoring terminal node)"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the GoT resume tool definition
+fn get_got_resume_tool() -> Tool {
+    Tool {
+        name: "reasoning_got_resume".to_string(),
+        description: "Resume a stored reasoning graph, e.g. after a server restart: reloads it with the limits it was initialized with, repairs nodes left active after being expanded, recomputes the frontier, and expands its best nodes.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "expand": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 5,
+                    "description": "Frontier nodes to expand, best first; 0 only reloads (default: 1)"
+                },
+                "k": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 10,
+                    "description": "Continuations per expanded node (default: the graph's default_k)"
+                },
+                "problem": {
+                    "type": "string",
+                    "description": "Optional problem context"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
 fn get_got_state_tool() -> Tool {
     Tool {
         name: "reasoning_got_state".to_string(),
@@ -1005,11 +2838,24 @@ fn get_got_state_tool() -> Tool {
                 "session_id": {
                     "type": "string",
                     "description": "The session ID"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 500,
+                    "description": "Include a page of at most this many full node records"
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": 0,
+                    "description": "Number of nodes to skip when paging"
                 }
             },
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1041,10 +2887,12 @@ fn get_detect_biases_tool() -> Tool {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "Specific bias types to check (optional, checks all if not specified)"
-                }
+                },
+                "generation": generation_schema()
             },
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1075,10 +2923,50 @@ fn get_detect_fallacies_tool() -> Tool {
                 "check_informal": {
                     "type": "boolean",
                     "description": "Check for informal logical fallacies (default: true)"
-                }
+                },
+                "generation": generation_schema()
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the verify calculations tool definition
+fn get_verify_calculations_tool() -> Tool {
+    Tool {
+        name: "reasoning_verify_calculations".to_string(),
+        description: "Recompute every numeric claim in content, a thought or a whole session. Explicit equations are found in the text and prose claims are turned into formulas; each formula is evaluated by a local expression evaluator, not the model. Stated results off by more than the tolerance are filed as calculation detections with the correct value as remediation.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "The content to check"
+                },
+                "thought_id": {
+                    "type": "string",
+                    "description": "ID of an existing thought to check (alternative to content)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Session whose thoughts to check when neither content nor thought_id is given; detections are filed in it"
+                },
+                "tolerance": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 0.5,
+                    "description": "Relative difference allowed between stated and computed results (default: 0.01). Results rounded to their written digits always pass"
+                },
+                "local_only": {
+                    "type": "boolean",
+                    "description": "Check only equations written out in the text, without a pipe call (default: false)"
+                },
+                "generation": generation_schema()
             },
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1101,6 +2989,7 @@ fn get_preset_list_tool() -> Tool {
             },
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1124,11 +3013,17 @@ fn get_preset_run_tool() -> Tool {
                 "session_id": {
                     "type": "string",
                     "description": "Optional session ID for context persistence"
+                },
+                "execution": {
+                    "type": "string",
+                    "enum": ["local", "langbase_workflow"],
+                    "description": "Run steps as local tool calls, or as one server-side Langbase workflow (falls back to local when the preset cannot compile). Defaults to the preset's setting"
                 }
             },
             "required": ["preset_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1179,11 +3074,13 @@ fn get_make_decision_tool() -> Tool {
                 "context": {
                     "type": "string",
                     "description": "Additional context for the decision"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["question", "options"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1235,11 +3132,13 @@ fn get_analyze_perspectives_tool() -> Tool {
                 "context": {
                     "type": "string",
                     "description": "Additional context for the analysis"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["topic"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1281,11 +3180,13 @@ fn get_assess_evidence_tool() -> Tool {
                 "context": {
                     "type": "string",
                     "description": "Additional context for the assessment"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["claim", "evidence"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1334,11 +3235,13 @@ fn get_probabilistic_tool() -> Tool {
                 "session_id": {
                     "type": "string",
                     "description": "Optional session ID for context persistence"
-                }
+                },
+                "generation": generation_schema()
             },
             "required": ["hypothesis", "prior", "evidence"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1349,12 +3252,13 @@ fn get_probabilistic_tool() -> Tool {
 fn get_metrics_summary_tool() -> Tool {
     Tool {
         name: "reasoning_metrics_summary".to_string(),
-        description: "Get aggregated usage statistics for all Langbase pipes. Returns call counts, success rates, and latency statistics for each pipe that has been invoked.".to_string(),
+        description: "Get aggregated usage statistics for all Langbase pipes. Returns call counts, success rates, and latency statistics for each pipe that has been invoked, plus the outcome of the latest scheduled database maintenance run, hot-state cache hit rates, and how each reflection rubric dimension has scored over recent reflections.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {},
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1373,6 +3277,7 @@ fn get_metrics_by_pipe_tool() -> Tool {
             "required": ["pipe_name"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
@@ -1405,292 +3310,1643 @@ fn get_metrics_invocations_tool() -> Tool {
                     "maximum": 1000,
                     "default": 100,
                     "description": "Maximum number of results to return"
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": 0,
+                    "description": "Number of results to skip (use next_offset from the previous page)"
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "next_cursor from the previous page; takes precedence over offset"
                 }
             },
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-fn get_debug_config_tool() -> Tool {
+fn get_search_tool() -> Tool {
     Tool {
-        name: "reasoning_debug_config".to_string(),
-        description: "Debug tool to inspect the current pipe configuration. Returns the actual pipe names being used by the server.".to_string(),
+        name: "reasoning_search".to_string(),
+        description: "Full-text keyword search over stored thoughts across all sessions. Returns matching thoughts ranked by relevance with highlighted snippets.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Keywords to search for; all words must match"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Restrict results to a single session"
+                },
+                "mode": {
+                    "type": "string",
+                    "description": "Restrict results to thoughts from a reasoning mode (e.g. linear, tree)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "default": 20,
+                    "description": "Maximum number of results to return"
+                }
+            },
+            "required": ["query"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-fn get_fallback_metrics_tool() -> Tool {
+fn get_similar_tool() -> Tool {
     Tool {
-        name: "reasoning_fallback_metrics".to_string(),
-        description: "Get metrics about fallback usage across invocations. Returns total fallbacks, breakdown by type (parse_error, api_unavailable, local_calculation) and by pipe, plus recommendations for enabling strict mode.".to_string(),
+        name: "reasoning_similar".to_string(),
+        description: "Find prior thoughts and graph nodes that are semantically similar to a piece of text, so new reasoning can build on earlier work. Ranked by embedding cosine similarity.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
-            "additionalProperties": false
-        }),
-    }
-}
-
-// ============================================================================
-// Phase 6 Tool Definitions - Time Machine (Timeline, MCTS, Counterfactual)
-// ============================================================================
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Text to find similar prior reasoning for"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Current session; its own thoughts are excluded from results"
+                },
+                "source_type": {
+                    "type": "string",
+                    "enum": ["thought", "graph_node"],
+                    "description": "Only return thoughts or only Graph-of-Thoughts nodes"
+                },
+                "min_score": {
+                    "type": "number",
+                    "minimum": -1,
+                    "maximum": 1,
+                    "description": "Minimum cosine similarity for a result"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 50,
+                    "default": 10,
+                    "description": "Maximum number of results to return"
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_thoughts_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_thoughts".to_string(),
+        description: "List the thoughts recorded in a session, oldest first, one page at a time."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session ID"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 500,
+                    "default": 50,
+                    "description": "Maximum number of thoughts to return"
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": 0,
+                    "description": "Number of thoughts to skip (use next_offset from the previous page)"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_revise_tool() -> Tool {
+    Tool {
+        name: "reasoning_revise".to_string(),
+        description: "Correct a recorded thought. The previous version is kept in the thought's revision history, so later steps can build on the correction without losing the original.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thought_id": {
+                    "type": "string",
+                    "description": "The thought to revise"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The corrected thought"
+                },
+                "confidence": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Confidence in the corrected thought (default: unchanged)"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Why the thought needed correcting"
+                }
+            },
+            "required": ["thought_id", "content"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_thought_history_tool() -> Tool {
+    Tool {
+        name: "reasoning_thought_history".to_string(),
+        description:
+            "Show a thought's current version and every earlier version it replaced, oldest first."
+                .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thought_id": {
+                    "type": "string",
+                    "description": "The thought ID"
+                }
+            },
+            "required": ["thought_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_tag_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_tag".to_string(),
+        description: "Add or remove tags on a session to organize related reasoning runs. Tags are case-insensitive. Returns the session's tags after the change.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session to tag"
+                },
+                "add": {
+                    "type": "array",
+                    "items": { "type": "string", "minLength": 1 },
+                    "description": "Tags to add"
+                },
+                "remove": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tags to remove"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_list_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_list".to_string(),
+        description: "List reasoning sessions, most recently updated first. Filter by mode, creation time, tag, or whether bias/fallacy detections were recorded.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "mode": {
+                    "type": "string",
+                    "description": "Only sessions in this reasoning mode (e.g. linear, tree, got)"
+                },
+                "tag": {
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Only sessions with this tag"
+                },
+                "created_after": {
+                    "type": "string",
+                    "format": "date-time",
+                    "description": "Only sessions created at or after this RFC 3339 timestamp"
+                },
+                "created_before": {
+                    "type": "string",
+                    "format": "date-time",
+                    "description": "Only sessions created before this RFC 3339 timestamp"
+                },
+                "has_detections": {
+                    "type": "boolean",
+                    "description": "If true, only sessions with bias/fallacy detections; if false, only sessions without"
+                },
+                "in_trash": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "List deleted sessions that can still be restored instead of live sessions"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 500,
+                    "default": 50,
+                    "description": "Maximum number of sessions to return"
+                },
+                "offset": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": 0,
+                    "description": "Number of sessions to skip (use next_offset from the previous page)"
+                },
+                "cursor": {
+                    "type": "string",
+                    "description": "next_cursor from the previous page; takes precedence over offset"
+                }
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_delete_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_delete".to_string(),
+        description: "Move a session to the trash. Trashed sessions are hidden from listings and searches but keep their data and can be brought back with reasoning_session_restore until purged.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session to delete"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_restore_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_restore".to_string(),
+        description: "Restore a session from the trash, undoing reasoning_session_delete."
+            .to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The deleted session to restore"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_session_fork_tool() -> Tool {
+    Tool {
+        name: "reasoning_session_fork".to_string(),
+        description: "Copy a session, with its thoughts, branches, checkpoints, and graph, into a new session. Continue in the fork to explore an alternative path without disturbing the original reasoning history.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session to fork"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_knowledge_query_tool() -> Tool {
+    Tool {
+        name: "reasoning_knowledge_query".to_string(),
+        description: "Search the cross-session knowledge base of accepted conclusions. Consult it before reasoning about a problem from scratch; an empty query lists the most recent conclusions.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Question or problem statement to find prior conclusions for"
+                },
+                "source_type": {
+                    "type": "string",
+                    "enum": ["graph_node", "decision", "evidence"],
+                    "description": "Only return conclusions promoted from this kind of result"
+                },
+                "min_confidence": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Minimum confidence of returned conclusions"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 50,
+                    "default": 10,
+                    "description": "Maximum number of conclusions to return"
+                }
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_knowledge_promote_tool() -> Tool {
+    Tool {
+        name: "reasoning_knowledge_promote".to_string(),
+        description: "Promote a reasoning result to the knowledge base so later sessions can reuse it: a terminal Graph-of-Thoughts node, a decision recommendation, or an evidence verdict. Promoting the same result twice returns the existing conclusion.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "source_type": {
+                    "type": "string",
+                    "enum": ["graph_node", "decision", "evidence"],
+                    "description": "Kind of result to promote"
+                },
+                "source_id": {
+                    "type": "string",
+                    "description": "ID of the graph node, decision, or evidence assessment"
+                }
+            },
+            "required": ["source_type", "source_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_debug_config_tool() -> Tool {
+    Tool {
+        name: "reasoning_debug_config".to_string(),
+        description: "Debug tool to inspect the current pipe configuration. Returns the actual pipe names being used by the server.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_pipe_status_tool() -> Tool {
+    Tool {
+        name: "reasoning_pipe_status".to_string(),
+        description: "Get the circuit breaker state of each Langbase pipe. A pipe whose circuit is open fails immediately until its cooldown ends; shows consecutive and total failures and seconds until the next trial call.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_budget_status_tool() -> Tool {
+    Tool {
+        name: "reasoning_budget_status".to_string(),
+        description: "Get today's token and cost spend on pipe calls and how much of the daily budget remains; with a session_id, also that session's spend and remaining budget. Pipe calls are refused with a BudgetExceeded error once a budget is used up.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Also report this session's spend"
+                }
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_quota_status_tool() -> Tool {
+    Tool {
+        name: "reasoning_quota_status".to_string(),
+        description: "Get the calling client's requests in the last minute and tool calls today, against the per-client limits of the WebSocket transport, and when the daily quota resets. Requests beyond a limit are refused with a quota exceeded error.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_server_status_tool() -> Tool {
+    Tool {
+        name: "reasoning_server_status".to_string(),
+        description: "Check whether the server is ready to serve requests: the database answers, every schema migration is applied, and Langbase answers when pipes use it. Also reports the version, transport and uptime. The same checks back the /readyz endpoint of the WebSocket transport.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_pipe_health_tool() -> Tool {
+    Tool {
+        name: "reasoning_pipe_health".to_string(),
+        description: "Send a short probe prompt through every configured pipe (including pipe versions) and report whether each one answered, its latency, and whether the answer matched the mode's output schema. Makes one live call per pipe.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pipes": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only probe these pipe names (default: all configured pipes)"
+                }
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_fallback_metrics_tool() -> Tool {
+    Tool {
+        name: "reasoning_fallback_metrics".to_string(),
+        description: "Get metrics about fallback usage across invocations. Returns total fallbacks, breakdown by type (parse_error, api_unavailable, local_calculation) and by pipe, plus recommendations for enabling strict mode.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Phase 6 Tool Definitions - Time Machine (Timeline, MCTS, Counterfactual)
+// ============================================================================
+
+/// Get the timeline create tool definition
+fn get_timeline_create_tool() -> Tool {
+    Tool {
+        name: "reasoning_timeline_create".to_string(),
+        description: "Create a new reasoning timeline for temporal exploration. Timelines allow tracking multiple parallel reasoning paths over time with branching support.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "Initial content or problem to explore"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "metadata": {
+                    "type": "object",
+                    "description": "Optional metadata for the timeline",
+                    "additionalProperties": true
+                }
+            },
+            "required": ["content"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the timeline branch tool definition
+fn get_timeline_branch_tool() -> Tool {
+    Tool {
+        name: "reasoning_timeline_branch".to_string(),
+        description: "Create a new branch in a reasoning timeline. Enables exploring alternative paths from any point while preserving the original.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "timeline_id": {
+                    "type": "string",
+                    "description": "ID of the timeline to branch from"
+                },
+                "parent_branch_id": {
+                    "type": "string",
+                    "description": "ID of the parent branch (uses current if not specified)"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Content for the new branch"
+                },
+                "branch_point": {
+                    "type": "string",
+                    "description": "Optional specific point to branch from"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "Optional label for the branch"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["timeline_id", "content"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the timeline compare tool definition
+fn get_timeline_compare_tool() -> Tool {
+    Tool {
+        name: "reasoning_timeline_compare".to_string(),
+        description: "Compare two timeline branches: align their shared events, report where and why they split, the downstream consequences of each, and which branch better matches observed facts.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "timeline_a": {
+                    "type": "string",
+                    "description": "First branch, or a timeline to use its active branch"
+                },
+                "timeline_b": {
+                    "type": "string",
+                    "description": "Second branch, or a timeline to use its active branch"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Session to log the comparison in (default: branch A's session)"
+                },
+                "observed_facts": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "maxItems": 20,
+                    "description": "Observed facts to check both branches against"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["timeline_a", "timeline_b"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the timeline merge tool definition
+fn get_timeline_merge_tool() -> Tool {
+    Tool {
+        name: "reasoning_timeline_merge".to_string(),
+        description: "Merge two timeline branches, synthesizing insights and resolving conflicts to create a unified path.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "timeline_id": {
+                    "type": "string",
+                    "description": "ID of the timeline containing the branches"
+                },
+                "source_branch_id": {
+                    "type": "string",
+                    "description": "ID of the source branch to merge"
+                },
+                "target_branch_id": {
+                    "type": "string",
+                    "description": "ID of the target branch to merge into"
+                },
+                "merge_strategy": {
+                    "type": "string",
+                    "enum": ["synthesize", "prefer_source", "prefer_target", "interleave"],
+                    "description": "Strategy for resolving conflicts (default: synthesize)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["timeline_id", "source_branch_id", "target_branch_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the MCTS explore tool definition
+fn get_mcts_explore_tool() -> Tool {
+    Tool {
+        name: "reasoning_mcts_explore".to_string(),
+        description: "Monte Carlo Tree Search guided exploration. Uses UCB1 formula (Q/N + c*sqrt(ln(N_parent)/N)) to balance exploitation of promising paths with exploration of novel ones; progressive widening instead lets a node gain children as its visits grow. Each iteration's statistics are stored for the metrics CLI.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "content": {
+                    "type": "string",
+                    "description": "The reasoning problem or content to explore"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "timeline_id": {
+                    "type": "string",
+                    "description": "Optional timeline to associate the search with"
+                },
+                "iterations": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 20,
+                    "description": "Number of MCTS iterations to perform (default: 5)"
+                },
+                "policy": {
+                    "type": "string",
+                    "enum": ["ucb1", "progressive_widening"],
+                    "description": "Node selection policy: ucb1 expands a node once to the full width; progressive_widening lets a node visited N times have k*N^alpha children (default: MCTS_POLICY, ucb1)"
+                },
+                "exploration_constant": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 10,
+                    "description": "UCB1 exploration constant c (default: MCTS_EXPLORATION_CONSTANT, 1.414)"
+                },
+                "rollout_depth": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 5,
+                    "description": "Continuation steps each simulation rolls out before scoring; 0 scores the new children directly (default: MCTS_ROLLOUT_DEPTH, 0)"
+                },
+                "expansion_width": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "Children generated per expansion (default: MCTS_EXPANSION_WIDTH, 3)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["content"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the auto backtrack tool definition
+fn get_auto_backtrack_tool() -> Tool {
+    Tool {
+        name: "reasoning_auto_backtrack".to_string(),
+        description: "Intelligent auto-backtracking that monitors reasoning quality and automatically suggests or executes backtracking when quality drops or dead ends are detected.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID to monitor for quality"
+                },
+                "quality_threshold": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Minimum quality score before triggering backtrack (default: 0.5)"
+                },
+                "auto_execute": {
+                    "type": "boolean",
+                    "description": "Whether to automatically execute backtrack or just suggest (default: false)"
+                },
+                "lookback_depth": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 10,
+                    "description": "How many steps back to analyze for backtrack point (default: 5)"
+                }
+            },
+            "required": ["session_id"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the counterfactual analysis tool definition
+fn get_counterfactual_tool() -> Tool {
+    Tool {
+        name: "reasoning_counterfactual".to_string(),
+        description: "Counterfactual 'what if' analysis based on Pearl's Ladder of Causation. Explores alternative scenarios by modifying assumptions and tracing causal consequences.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "scenario": {
+                    "type": "string",
+                    "description": "The base scenario or situation to analyze"
+                },
+                "intervention": {
+                    "type": "string",
+                    "description": "The counterfactual change to apply (e.g., 'What if X had happened instead?')"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "causal_model": {
+                    "type": "object",
+                    "properties": {
+                        "variables": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "description": "Key variables in the causal model"
+                        },
+                        "relationships": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "cause": { "type": "string" },
+                                    "effect": { "type": "string" },
+                                    "strength": { "type": "number", "minimum": 0, "maximum": 1 }
+                                }
+                            },
+                            "description": "Causal relationships between variables"
+                        }
+                    },
+                    "description": "Optional explicit causal model for the analysis"
+                },
+                "analysis_depth": {
+                    "type": "string",
+                    "enum": ["association", "intervention", "counterfactual"],
+                    "description": "Depth of causal analysis per Pearl's Ladder (default: counterfactual)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["scenario", "intervention"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Structured Reasoning Tool Definitions
+// ============================================================================
+
+/// Get the debate tool definition
+fn get_debate_tool() -> Tool {
+    Tool {
+        name: "reasoning_debate".to_string(),
+        description: "Adversarial debate: a PRO and a CON persona argue a proposition over several rounds, each rebutting the other, then a judge scores every argument and returns a verdict with confidence. The transcript is stored as branches linked by contradicts/supports cross-references.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "proposition": {
+                    "type": "string",
+                    "description": "The proposition argued for and against"
+                },
+                "rounds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "Rounds to debate, each one PRO and one CON argument (default: 2)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background given to both sides and the judge"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["proposition"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the root cause analysis tool definition
+fn get_root_cause_tool() -> Tool {
+    Tool {
+        name: "reasoning_root_cause".to_string(),
+        description: "Root cause analysis: traces a problem back with 5 Whys (asking why of each cause in turn) or a fishbone diagram (causes per category), labels causes proximate, contributing or root, and suggests corrective actions. Stored as a branch per cause category under the problem.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "problem": {
+                    "type": "string",
+                    "description": "The problem to trace to its root causes"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["five_whys", "fishbone"],
+                    "description": "Technique to apply (default: five_whys)"
+                },
+                "max_depth": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 7,
+                    "description": "Most whys to ask with five_whys (default: 5)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the problem"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["problem"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
 
-/// Get the timeline create tool definition
-fn get_timeline_create_tool() -> Tool {
+/// Get the planning tool definition
+fn get_plan_tool() -> Tool {
     Tool {
-        name: "reasoning_timeline_create".to_string(),
-        description: "Create a new reasoning timeline for temporal exploration. Timelines allow tracking multiple parallel reasoning paths over time with branching support.".to_string(),
+        name: "reasoning_plan".to_string(),
+        description: "Decompose a goal into tasks with prerequisites, effort estimates, and risks. Returns the tasks in dependency order with parallelizable levels and the critical path, computed locally; the plan is stored as graph nodes with depends_on edges.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "goal": {
+                    "type": "string",
+                    "description": "The goal to decompose into tasks"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional constraints or background"
+                },
+                "max_tasks": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 20,
+                    "description": "Most tasks to plan (default: 8)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["goal"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the plan revision tool definition
+fn get_plan_revise_tool() -> Tool {
+    Tool {
+        name: "reasoning_plan_revise".to_string(),
+        description: "Mark a planned task failed and replan: the task and every task depending on it are dropped, and replacement tasks that work around the failure are added to the plan.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session holding the plan"
+                },
+                "failed_task_id": {
+                    "type": "string",
+                    "description": "The task that failed"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Why the task failed"
+                },
+                "max_tasks": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 20,
+                    "description": "Most replacement tasks to plan (default: 8)"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["session_id", "failed_task_id", "reason"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the Fermi estimation tool definition
+fn get_estimate_tool() -> Tool {
+    Tool {
+        name: "reasoning_estimate".to_string(),
+        description: "Fermi estimation: decompose a quantitative question into factors estimated with ranges, then compute the answer, worst-case bounds and a 90% interval locally rather than trusting model arithmetic. Numbers the model stated that disagree with the computed ones are reported.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "question": {
+                    "type": "string",
+                    "description": "The quantitative question to estimate"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional known facts or constraints"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["question"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the SWOT analysis tool definition
+fn get_swot_tool() -> Tool {
+    Tool {
+        name: "reasoning_swot".to_string(),
+        description: "SWOT analysis: strengths, weaknesses, opportunities and threats of a subject, each item with evidence, confidence and the stakeholders it affects. Stakeholders come from a perspective analysis (reasoning_analyze_perspectives), by default the latest in the session. Analyses are stored and the latest earlier one of the same subject is taken into account.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "subject": {
+                    "type": "string",
+                    "description": "The subject to analyze (a product, plan, organization, ...)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the subject and its environment"
+                },
+                "perspective_id": {
+                    "type": "string",
+                    "description": "Perspective analysis whose stakeholders items should reference (default: latest in the session)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["subject"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the SWOT listing tool definition
+fn get_swot_list_tool() -> Tool {
+    Tool {
+        name: "reasoning_swot_list".to_string(),
+        description: "List stored SWOT analyses from any session, newest first, optionally by subject text or session.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "subject": {
+                    "type": "string",
+                    "description": "Only list analyses whose subject contains this text (case-insensitive)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Only list analyses made in this session"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 50,
+                    "description": "Most analyses to return (default: 10)"
+                }
+            },
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the hypothesis test design tool definition
+fn get_hypothesis_tool() -> Tool {
+    Tool {
+        name: "reasoning_hypothesis".to_string(),
+        description: "Turn a hypothesis into testable predictions and experiments that discriminate it. Every experiment pre-registers its possible outcomes with their likelihoods if the hypothesis is true and if it is false; experiments are ranked by expected information gain. The design is stored as a probability update; record results with reasoning_hypothesis_observe.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "hypothesis": {
+                    "type": "string",
+                    "description": "The hypothesis to test"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background, constraints or available resources"
+                },
+                "prior": {
+                    "type": "number",
+                    "exclusiveMinimum": 0,
+                    "exclusiveMaximum": 1,
+                    "description": "Probability of the hypothesis before any experiment (default: 0.5)"
+                },
+                "max_experiments": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 6,
+                    "description": "Most experiments to design (default: 3)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["hypothesis"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the hypothesis observation tool definition
+fn get_hypothesis_observe_tool() -> Tool {
+    Tool {
+        name: "reasoning_hypothesis_observe".to_string(),
+        description: "Record the observed outcome of an experiment from reasoning_hypothesis and update the hypothesis' probability with Bayes' rule, using the likelihoods pre-registered in the design. Each experiment can be observed once; observations chain, each starting from the previous posterior.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session holding the test"
+                },
+                "test_id": {
+                    "type": "string",
+                    "description": "The test design from reasoning_hypothesis"
+                },
+                "experiment_id": {
+                    "type": "string",
+                    "description": "The experiment that was run"
+                },
+                "outcome": {
+                    "type": "string",
+                    "description": "The observed outcome, one of the experiment's pre-registered outcomes"
+                },
+                "notes": {
+                    "type": "string",
+                    "description": "Optional notes on how the result was obtained"
+                }
+            },
+            "required": ["session_id", "test_id", "experiment_id", "outcome"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the Six Thinking Hats tool definition
+fn get_six_hats_tool() -> Tool {
+    Tool {
+        name: "reasoning_six_hats".to_string(),
+        description: "Six Thinking Hats: sequential passes over a topic from six fixed stances - facts (white), emotions (red), risks (black), benefits (yellow), creativity (green) and process (blue) - each seeing the passes before it, then a synthesis with recommendations. Stored as one branch per hat under a branch holding the topic and the synthesis thought.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "topic": {
+                    "type": "string",
+                    "description": "The topic to think about"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background every hat is given"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["topic"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+/// Get the dialectic reasoning tool definition
+fn get_dialectic_tool() -> Tool {
+    Tool {
+        name: "reasoning_dialectic".to_string(),
+        description: "Dialectical reasoning: build the strongest antithesis to a thesis, then a synthesis preserving the valid core of both, and repeat with the synthesis as the new position. Stops after the given rounds or once a synthesis barely differs from the position it came from (convergence). Stored as linked branches: antitheses contradict, syntheses extend.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thesis": {
+                    "type": "string",
+                    "description": "The starting position"
+                },
+                "rounds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "Most thesis-antithesis-synthesis rounds to run (default: 3)"
+                },
+                "convergence_threshold": {
+                    "type": "number",
+                    "minimum": 0,
+                    "maximum": 1,
+                    "description": "Similarity between a synthesis and its position at which iteration stops (default: 0.9)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background for every call"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["thesis"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_prioritize_tool() -> Tool {
+    Tool {
+        name: "reasoning_prioritize".to_string(),
+        description: "Rank items by RICE (reach × impact × confidence ÷ effort), MoSCoW (must/should/could/won't, by value within a category) or weighted criteria. The model only estimates each item's inputs; scores and ranks are computed locally. The ranking is stored with its estimates so one can be revised with reasoning_prioritize_revise.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 2,
+                    "maxItems": 30,
+                    "description": "The items to rank"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["rice", "moscow", "weighted"],
+                    "description": "How to rank the items"
+                },
+                "goal": {
+                    "type": "string",
+                    "description": "What the ranking is for"
+                },
+                "criteria": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": {"type": "string"},
+                            "weight": {"type": "number", "exclusiveMinimum": 0},
+                            "description": {"type": "string"}
+                        },
+                        "required": ["name", "weight"]
+                    },
+                    "description": "Criteria of a weighted ranking (required for weighted, not allowed otherwise)"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background the estimates should take into account"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["items", "method"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_prioritize_revise_tool() -> Tool {
+    Tool {
+        name: "reasoning_prioritize_revise".to_string(),
+        description: "Revise one item's estimate in a stored ranking and rank again locally, without a model call. Stored as a new ranking pointing at the one it revises; reports which items moved.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "content": {
+                "ranking_id": {
                     "type": "string",
-                    "description": "Initial content or problem to explore"
+                    "description": "ID of the ranking to revise"
                 },
-                "session_id": {
+                "item": {
                     "type": "string",
-                    "description": "Optional session ID for context continuity"
+                    "description": "The item whose estimate changes"
                 },
-                "metadata": {
+                "estimate": {
                     "type": "object",
-                    "description": "Optional metadata for the timeline",
-                    "additionalProperties": true
+                    "properties": {
+                        "reach": {"type": "number", "minimum": 0},
+                        "impact": {"type": "number", "minimum": 0, "maximum": 3},
+                        "confidence": {"type": "number", "minimum": 0, "maximum": 1},
+                        "effort": {"type": "number", "exclusiveMinimum": 0},
+                        "category": {"type": "string", "enum": ["must", "should", "could", "wont"]},
+                        "value": {"type": "number", "minimum": 0, "maximum": 10},
+                        "criteria_scores": {
+                            "type": "object",
+                            "additionalProperties": {"type": "number", "minimum": 0, "maximum": 10}
+                        }
+                    },
+                    "additionalProperties": false,
+                    "description": "Estimate fields to replace; only fields used by the ranking's method are accepted"
+                },
+                "reason": {
+                    "type": "string",
+                    "description": "Why the estimate changed"
                 }
             },
-            "required": ["content"],
+            "required": ["ranking_id", "item", "estimate"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the timeline branch tool definition
-fn get_timeline_branch_tool() -> Tool {
+fn get_negotiate_tool() -> Tool {
     Tool {
-        name: "reasoning_timeline_branch".to_string(),
-        description: "Create a new branch in a reasoning timeline. Enables exploring alternative paths from any point while preserving the original.".to_string(),
+        name: "reasoning_negotiate".to_string(),
+        description: "Analyze a negotiation: parties with their interests, positions and BATNAs, the ZOPA on the main issue, candidate package deals and each party's concession path. Package viability against every BATNA, Nash products, dominated packages and the ZOPA are computed locally. Builds on a stakeholder perspective analysis and is stored as one.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "timeline_id": {
+                "scenario": {
                     "type": "string",
-                    "description": "ID of the timeline to branch from"
+                    "description": "The negotiation scenario"
                 },
-                "parent_branch_id": {
+                "parties": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 2,
+                    "description": "Parties to the negotiation (identified from the scenario if omitted)"
+                },
+                "issue": {
                     "type": "string",
-                    "description": "ID of the parent branch (uses current if not specified)"
+                    "description": "The main distributive issue, such as price"
                 },
-                "content": {
+                "perspective_id": {
                     "type": "string",
-                    "description": "Content for the new branch"
+                    "description": "Perspective analysis from reasoning_analyze_perspectives to build on (default: the latest in the session)"
                 },
-                "branch_point": {
+                "context": {
                     "type": "string",
-                    "description": "Optional specific point to branch from"
+                    "description": "Optional background on the negotiation"
                 },
-                "label": {
+                "session_id": {
                     "type": "string",
-                    "description": "Optional label for the branch"
-                }
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
             },
-            "required": ["timeline_id", "content"],
+            "required": ["scenario"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the timeline compare tool definition
-fn get_timeline_compare_tool() -> Tool {
+fn get_risk_tool() -> Tool {
     Tool {
-        name: "reasoning_timeline_compare".to_string(),
-        description: "Compare multiple timeline branches to analyze divergence points, quality differences, and convergence opportunities.".to_string(),
+        name: "reasoning_risk".to_string(),
+        description: "Identify the risks of a project, plan or decision and add them to the session's persisted risk register, each scored 1-5 for likelihood, impact and detectability with an owner and a mitigation. Risks already in the register are not added twice. Returns the whole register, highest priority number first, with scores, levels and the probability-impact matrix computed locally.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "timeline_id": {
+                "subject": {
                     "type": "string",
-                    "description": "ID of the timeline containing the branches"
+                    "description": "The project, plan or decision whose risks to identify"
                 },
-                "branch_ids": {
-                    "type": "array",
-                    "items": { "type": "string" },
-                    "minItems": 2,
-                    "description": "IDs of branches to compare (minimum 2)"
-                }
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the subject"
+                },
+                "max_risks": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 15,
+                    "default": 6,
+                    "description": "Most new risks to add to the register"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
             },
-            "required": ["timeline_id", "branch_ids"],
+            "required": ["subject"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the timeline merge tool definition
-fn get_timeline_merge_tool() -> Tool {
+fn get_risk_update_tool() -> Tool {
     Tool {
-        name: "reasoning_timeline_merge".to_string(),
-        description: "Merge two timeline branches, synthesizing insights and resolving conflicts to create a unified path.".to_string(),
+        name: "reasoning_risk_update".to_string(),
+        description: "Re-score a risk from the register as evidence arrives. A probability update from reasoning_probabilistic sets the likelihood from its posterior locally; an evidence assessment from reasoning_assess_evidence is weighed by the model. Explicit scores override both. Every re-scoring is kept in the risk's history; owner and mitigation can be changed too.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "timeline_id": {
+                "risk_id": {
                     "type": "string",
-                    "description": "ID of the timeline containing the branches"
+                    "description": "ID of the risk to update"
                 },
-                "source_branch_id": {
+                "evidence_id": {
                     "type": "string",
-                    "description": "ID of the source branch to merge"
+                    "description": "Probability update or evidence assessment bearing on the risk"
                 },
-                "target_branch_id": {
+                "likelihood": {"type": "integer", "minimum": 1, "maximum": 5},
+                "impact": {"type": "integer", "minimum": 1, "maximum": 5},
+                "detectability": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5,
+                    "description": "5 = hardest to detect"
+                },
+                "owner": {
                     "type": "string",
-                    "description": "ID of the target branch to merge into"
+                    "description": "New owner of the risk"
                 },
-                "merge_strategy": {
+                "mitigation": {
                     "type": "string",
-                    "enum": ["synthesize", "prefer_source", "prefer_target", "interleave"],
-                    "description": "Strategy for resolving conflicts (default: synthesize)"
-                }
+                    "description": "New mitigation"
+                },
+                "rationale": {
+                    "type": "string",
+                    "description": "Why the risk is re-scored"
+                },
+                "generation": generation_schema()
             },
-            "required": ["timeline_id", "source_branch_id", "target_branch_id"],
+            "required": ["risk_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the MCTS explore tool definition
-fn get_mcts_explore_tool() -> Tool {
+fn get_systems_tool() -> Tool {
     Tool {
-        name: "reasoning_mcts_explore".to_string(),
-        description: "Monte Carlo Tree Search guided exploration. Uses UCB1 formula (Q/N + c*sqrt(ln(N_parent)/N)) to balance exploitation of promising paths with exploration of novel ones.".to_string(),
+        name: "reasoning_systems".to_string(),
+        description: "Model a situation as a system of stocks, flows and variables joined by positive or negative causal links. Feedback loops are found locally as the cycles of the links and labeled reinforcing (R) or balancing (B); leverage points are the elements on the most loops. The model is stored as a graph and returned with a Mermaid flowchart.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "content": {
+                "situation": {
                     "type": "string",
-                    "description": "The reasoning problem or content to explore"
+                    "description": "The situation to model"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the situation"
+                },
+                "max_elements": {
+                    "type": "integer",
+                    "minimum": 2,
+                    "maximum": 20,
+                    "default": 10,
+                    "description": "Most stocks, flows and variables to model"
                 },
                 "session_id": {
                     "type": "string",
                     "description": "Optional session ID for context continuity"
                 },
-                "node_id": {
+                "generation": generation_schema()
+            },
+            "required": ["situation"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_code_explain_diff_tool() -> Tool {
+    Tool {
+        name: "reasoning_code_explain_diff".to_string(),
+        description: "Explain what a unified diff changes and how behavior differs afterwards. Files and added/removed lines are counted locally. Invariants, risks and suggested tests are stored as findings of the session.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "diff": {
                     "type": "string",
-                    "description": "Optional specific node to expand from"
+                    "description": "The change, as a unified diff"
                 },
-                "iterations": {
-                    "type": "integer",
-                    "minimum": 1,
-                    "maximum": 100,
-                    "description": "Number of MCTS iterations to perform (default: 10)"
+                "context": {
+                    "type": "string",
+                    "description": "Optional background: what the change is for, surrounding code"
                 },
-                "exploration_constant": {
-                    "type": "number",
-                    "minimum": 0,
-                    "maximum": 10,
-                    "description": "UCB1 exploration constant c (default: 1.414, sqrt(2))"
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
                 },
-                "simulation_depth": {
-                    "type": "integer",
-                    "minimum": 1,
-                    "maximum": 20,
-                    "description": "Maximum depth for rollout simulations (default: 5)"
-                }
+                "generation": generation_schema()
             },
-            "required": ["content"],
+            "required": ["diff"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the auto backtrack tool definition
-fn get_auto_backtrack_tool() -> Tool {
+fn get_code_trace_flow_tool() -> Tool {
     Tool {
-        name: "reasoning_auto_backtrack".to_string(),
-        description: "Intelligent auto-backtracking that monitors reasoning quality and automatically suggests or executes backtracking when quality drops or dead ends are detected.".to_string(),
+        name: "reasoning_code_trace_flow".to_string(),
+        description: "Trace a variable or value through a code snippet from its sources to its sinks. Each step cites a line of the snippet, checked locally. Invariants, risks and suggested tests are stored as findings of the session.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "The code to trace through"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "The variable, parameter or value to follow"
+                },
+                "language": {
+                    "type": "string",
+                    "description": "Language of the code"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the code"
+                },
                 "session_id": {
                     "type": "string",
-                    "description": "Session ID to monitor for quality"
+                    "description": "Optional session ID for context continuity"
                 },
-                "quality_threshold": {
-                    "type": "number",
-                    "minimum": 0,
-                    "maximum": 1,
-                    "description": "Minimum quality score before triggering backtrack (default: 0.5)"
+                "generation": generation_schema()
+            },
+            "required": ["code", "target"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_code_edge_cases_tool() -> Tool {
+    Tool {
+        name: "reasoning_code_edge_cases".to_string(),
+        description: "Enumerate the inputs and states a code snippet may mishandle, unhandled and most severe first. Invariants, risks and suggested tests are stored as findings of the session.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "The code to examine"
                 },
-                "auto_execute": {
-                    "type": "boolean",
-                    "description": "Whether to automatically execute backtrack or just suggest (default: false)"
+                "language": {
+                    "type": "string",
+                    "description": "Language of the code"
                 },
-                "lookback_depth": {
+                "context": {
+                    "type": "string",
+                    "description": "Optional background on the code"
+                },
+                "max_cases": {
                     "type": "integer",
                     "minimum": 1,
-                    "maximum": 10,
-                    "description": "How many steps back to analyze for backtrack point (default: 5)"
+                    "maximum": 20,
+                    "default": 8,
+                    "description": "Most edge cases to enumerate"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Optional session ID for context continuity"
+                },
+                "generation": generation_schema()
+            },
+            "required": ["code"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_code_findings_tool() -> Tool {
+    Tool {
+        name: "reasoning_code_findings".to_string(),
+        description: "List the invariants, risks and suggested tests recorded by code reasoning in a session.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "The session whose findings to list"
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["invariant", "risk", "test"],
+                    "description": "Only list findings of this kind"
                 }
             },
             "required": ["session_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
 
-/// Get the counterfactual analysis tool definition
-fn get_counterfactual_tool() -> Tool {
+fn get_synthesize_tool() -> Tool {
     Tool {
-        name: "reasoning_counterfactual".to_string(),
-        description: "Counterfactual 'what if' analysis based on Pearl's Ladder of Causation. Explores alternative scenarios by modifying assumptions and tracing causal consequences.".to_string(),
+        name: "reasoning_synthesize".to_string(),
+        description: "Consolidate the stored artifacts of several sessions (thoughts, decisions, perspectives, evidence assessments, probability updates, detections, risks, counterfactuals) into one briefing. Every section and finding cites its sources, and each finding's confidence is combined with the confidence of the sources it cites. The briefing is returned as Markdown and stored as a thought.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "scenario": {
-                    "type": "string",
-                    "description": "The base scenario or situation to analyze"
+                "session_ids": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 1,
+                    "maxItems": 10,
+                    "description": "Sessions to draw on, e.g. a decision, an evidence assessment and a premortem"
                 },
-                "intervention": {
+                "focus": {
                     "type": "string",
-                    "description": "The counterfactual change to apply (e.g., 'What if X had happened instead?')"
+                    "description": "Optional question or audience the briefing should address"
+                },
+                "thoughts_per_session": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 20,
+                    "default": 5,
+                    "description": "Most recent thoughts taken from each session"
                 },
                 "session_id": {
                     "type": "string",
-                    "description": "Optional session ID for context continuity"
+                    "description": "Session to store the briefing in (creates new if not provided)"
                 },
-                "causal_model": {
-                    "type": "object",
-                    "properties": {
-                        "variables": {
-                            "type": "array",
-                            "items": { "type": "string" },
-                            "description": "Key variables in the causal model"
-                        },
-                        "relationships": {
-                            "type": "array",
-                            "items": {
-                                "type": "object",
-                                "properties": {
-                                    "cause": { "type": "string" },
-                                    "effect": { "type": "string" },
-                                    "strength": { "type": "number", "minimum": 0, "maximum": 1 }
-                                }
-                            },
-                            "description": "Causal relationships between variables"
-                        }
-                    },
-                    "description": "Optional explicit causal model for the analysis"
+                "generation": generation_schema()
+            },
+            "required": ["session_ids"],
+            "additionalProperties": false
+        }),
+        ..Default::default()
+    }
+}
+
+fn get_counterfactual_minimal_change_tool() -> Tool {
+    Tool {
+        name: "reasoning_counterfactual_minimal_change".to_string(),
+        description: "Search for the smallest set of changed antecedents that flips a branch's outcome. Proposes single-variable interventions, tests each, then combines the most promising, and reports the smallest sufficient change set with its reasoning.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "branch_id": {
+                    "type": "string",
+                    "description": "Branch whose outcome to flip"
                 },
-                "analysis_depth": {
+                "target_outcome": {
                     "type": "string",
-                    "enum": ["association", "intervention", "counterfactual"],
-                    "description": "Depth of causal analysis per Pearl's Ladder (default: counterfactual)"
-                }
+                    "description": "Outcome to reach instead (default: any materially different outcome)"
+                },
+                "max_rounds": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 4,
+                    "default": 2,
+                    "description": "Proposal rounds of single-variable interventions"
+                },
+                "candidates_per_round": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 6,
+                    "default": 4,
+                    "description": "Interventions proposed per round"
+                },
+                "max_set_size": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 4,
+                    "default": 3,
+                    "description": "Largest change set tried"
+                },
+                "max_tests": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 40,
+                    "default": 16,
+                    "description": "Most change sets tested (one pipe call each)"
+                },
+                "timeline_id": {
+                    "type": "string",
+                    "description": "Optional timeline to associate the analysis with"
+                },
+                "generation": generation_schema()
             },
-            "required": ["scenario", "intervention"],
+            "required": ["branch_id"],
             "additionalProperties": false
         }),
+        ..Default::default()
     }
 }
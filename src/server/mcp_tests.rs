@@ -4,6 +4,7 @@
 //! and MCP type serialization.
 
 use super::*;
+use crate::server::{tool_group, TOOL_GROUPS};
 use serde_json::json;
 
 // ============================================================================
@@ -146,10 +147,283 @@ fn test_tool_call_params_without_arguments() {
     assert!(params.arguments.is_none());
 }
 
+#[test]
+fn test_tool_call_params_with_progress_token() {
+    let json_str = r#"{"name":"reasoning_linear","arguments":{},"_meta":{"progressToken":7}}"#;
+    let params: ToolCallParams = serde_json::from_str(json_str).unwrap();
+
+    assert_eq!(params.meta.unwrap().progress_token, Some(json!(7)));
+}
+
+// ============================================================================
+// Progress notification tests
+// ============================================================================
+
+#[test]
+fn test_progress_notification_format() {
+    let notification = JsonRpcNotification::progress(&json!("tok"), 12, "partial text");
+    let value = serde_json::to_value(&notification).unwrap();
+
+    assert_eq!(
+        value,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": {"progressToken": "tok", "progress": 12, "message": "partial text"}
+        })
+    );
+}
+
+#[test]
+fn test_resource_updated_notification_format() {
+    let notification = JsonRpcNotification::resource_updated("reasoning://session/s1");
+    let value = serde_json::to_value(&notification).unwrap();
+
+    assert_eq!(
+        value,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": {"uri": "reasoning://session/s1"}
+        })
+    );
+}
+
+#[test]
+fn test_progress_sink_reports_cumulative_characters() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sink = progress_sink(json!(1), Arc::new(AtomicU64::new(0)), tx);
+
+    sink("Hel");
+    sink("lo, w\u{f6}rld");
+
+    let first = serde_json::to_value(rx.try_recv().unwrap()).unwrap()["params"].clone();
+    assert_eq!(first["progress"], 3);
+    assert_eq!(first["message"], "Hel");
+    let second = serde_json::to_value(rx.try_recv().unwrap()).unwrap()["params"].clone();
+    assert_eq!(second["progress"], 12);
+    assert_eq!(second["progressToken"], 1);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_artifact_sink_shares_progress_with_text() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let progress = Arc::new(AtomicU64::new(0));
+    let text = progress_sink(json!("tok"), progress.clone(), tx.clone());
+    let artifacts = artifact_sink(json!("tok"), progress, tx);
+
+    text("abcd");
+    artifacts(&Artifact {
+        kind: "perspective".to_string(),
+        index: 0,
+        data: json!({"thought": "First angle"}),
+    });
+
+    let _ = rx.try_recv().unwrap();
+    let params = serde_json::to_value(rx.try_recv().unwrap()).unwrap()["params"].clone();
+    assert_eq!(params["progress"], 5);
+    assert_eq!(params["message"], "perspective 1 ready");
+    assert_eq!(params["artifact"]["kind"], "perspective");
+    assert_eq!(params["artifact"]["index"], 0);
+    assert_eq!(params["artifact"]["data"]["thought"], "First angle");
+}
+
+// ============================================================================
+// Client log tests
+// ============================================================================
+
+#[test]
+fn test_client_log_sink_filters_by_level() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sink = client_log_sink(LogLevel::Warning, tx);
+
+    for (level, message) in [(LogLevel::Info, "routine"), (LogLevel::Error, "failed")] {
+        sink(&ClientLogMessage {
+            level,
+            logger: "langbase".to_string(),
+            data: json!({ "message": message }),
+        });
+    }
+
+    let notification = serde_json::to_value(rx.try_recv().unwrap()).unwrap();
+    assert_eq!(notification["method"], "notifications/message");
+    assert_eq!(notification["params"]["level"], "error");
+    assert_eq!(notification["params"]["logger"], "langbase");
+    assert_eq!(notification["params"]["data"]["message"], "failed");
+    assert!(rx.try_recv().is_err());
+}
+
+// ============================================================================
+// Cancellation notification tests
+// ============================================================================
+
+#[test]
+fn test_cancelled_request_extracts_request_id() {
+    let message = r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":"abc","reason":"aborted"}}"#;
+    assert_eq!(cancelled_request(message), Some(json!("abc")));
+
+    let message =
+        r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":3}}"#;
+    assert_eq!(cancelled_request(message), Some(json!(3)));
+}
+
+#[test]
+fn test_cancelled_request_ignores_other_messages() {
+    assert_eq!(
+        cancelled_request(r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#),
+        None
+    );
+    assert_eq!(
+        cancelled_request(r#"{"jsonrpc":"2.0","method":"notifications/cancelled"}"#),
+        None
+    );
+    assert_eq!(cancelled_request("not json"), None);
+}
+
+// ============================================================================
+// Batch request tests
+// ============================================================================
+
+fn test_sampler() -> ClientSampler {
+    ClientSampler::new(mpsc::unbounded_channel().0)
+}
+
+#[test]
+fn test_parse_entries_single_and_batch() {
+    let sampler = test_sampler();
+
+    let (entries, is_batch) =
+        parse_entries(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#, &sampler);
+    assert!(!is_batch);
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].is_ok());
+
+    let batch = r#"[
+        {"jsonrpc":"2.0","id":1,"method":"ping"},
+        {"jsonrpc":"2.0","method":"initialized"},
+        {"jsonrpc":"2.0","id":2},
+        {"jsonrpc":"2.0","id":"s-1","result":{}}
+    ]"#;
+    let (entries, is_batch) = parse_entries(batch, &sampler);
+    assert!(is_batch);
+    // The response to the server's own request is not an entry
+    assert_eq!(entries.len(), 3);
+    assert!(entries[0].is_ok());
+    assert!(entries[1].as_ref().unwrap().id.is_none());
+    let invalid = entries[2].as_ref().unwrap_err();
+    assert_eq!(invalid.id, json!(2));
+    assert_eq!(invalid.error.as_ref().unwrap().code, -32600);
+}
+
+#[test]
+fn test_parse_entries_rejects_empty_and_malformed_batches() {
+    let sampler = test_sampler();
+
+    let (entries, is_batch) = parse_entries("[]", &sampler);
+    assert!(!is_batch);
+    assert_eq!(
+        entries[0]
+            .as_ref()
+            .unwrap_err()
+            .error
+            .as_ref()
+            .unwrap()
+            .code,
+        -32600
+    );
+
+    let (entries, is_batch) = parse_entries("[{", &sampler);
+    assert!(!is_batch);
+    assert_eq!(
+        entries[0]
+            .as_ref()
+            .unwrap_err()
+            .error
+            .as_ref()
+            .unwrap()
+            .code,
+        -32700
+    );
+}
+
+#[test]
+fn test_leaves_state_unchanged() {
+    let request = |method: &str, params: Value| JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: method.to_string(),
+        params: Some(params),
+    };
+
+    assert!(leaves_state_unchanged(&request("ping", json!({}))));
+    assert!(leaves_state_unchanged(&request(
+        "resources/read",
+        json!({})
+    )));
+    assert!(leaves_state_unchanged(&request(
+        "tools/call",
+        json!({"name": "reasoning_session_list"})
+    )));
+    assert!(!leaves_state_unchanged(&request(
+        "tools/call",
+        json!({"name": "reasoning_linear"})
+    )));
+    assert!(!leaves_state_unchanged(&request("tools/call", json!({}))));
+    assert!(!leaves_state_unchanged(&request("initialize", json!({}))));
+    assert!(!leaves_state_unchanged(&request(
+        "logging/setLevel",
+        json!({})
+    )));
+    assert!(leaves_state_unchanged(&request(
+        "completion/complete",
+        json!({})
+    )));
+}
+
 // ============================================================================
 // Tool definition tests
 // ============================================================================
 
+#[test]
+fn test_every_tool_belongs_to_a_group() {
+    let tools = all_tools();
+    for tool in &tools {
+        assert!(
+            tool_group(&tool.name).is_some(),
+            "{} is missing from TOOL_GROUPS",
+            tool.name
+        );
+    }
+    let grouped: usize = TOOL_GROUPS.iter().map(|(_, tools)| tools.len()).sum();
+    assert_eq!(grouped, tools.len());
+}
+
+#[test]
+fn test_every_tool_is_annotated() {
+    for tool in all_tools() {
+        assert!(tool.title.is_some(), "{} has no title", tool.name);
+        let json = serde_json::to_value(&tool).unwrap();
+        let annotations = &json["annotations"];
+        assert!(annotations["readOnlyHint"].is_boolean(), "{}", tool.name);
+        assert_eq!(annotations["title"], json["title"]);
+    }
+}
+
+#[test]
+fn test_output_schemas_describe_objects() {
+    let tools = all_tools();
+    for tool in &tools {
+        if let Some(schema) = &tool.output_schema {
+            assert_eq!(schema["type"], "object", "{}", tool.name);
+        }
+    }
+    let linear = tools.iter().find(|t| t.name == "reasoning_linear").unwrap();
+    let json = serde_json::to_value(linear).unwrap();
+    assert!(json["outputSchema"]["properties"]["session_id"].is_object());
+    assert!(json["outputSchema"]["properties"]["confidence"].is_object());
+}
+
 #[test]
 fn test_linear_tool_definition() {
     let tool = get_linear_tool();
@@ -160,6 +434,9 @@ fn test_linear_tool_definition() {
     let schema = &tool.input_schema;
     assert_eq!(schema["type"], "object");
     assert!(schema["properties"]["content"].is_object());
+    assert_eq!(schema["properties"]["revises_thought_id"]["type"], "string");
+    assert_eq!(schema["properties"]["max_steps"]["minimum"], 1);
+    assert_eq!(schema["properties"]["context_budget"]["type"], "integer");
     assert!(schema["required"]
         .as_array()
         .unwrap()
@@ -210,6 +487,51 @@ fn test_tree_complete_tool_definition() {
     assert!(required.contains(&json!("branch_id")));
 }
 
+#[test]
+fn test_tree_compare_tool_definition() {
+    let tool = get_tree_compare_tool();
+
+    assert_eq!(tool.name, "reasoning_tree_compare");
+    assert_eq!(
+        tool.input_schema["required"],
+        json!(["session_id", "branch_ids"])
+    );
+    assert_eq!(tool.input_schema["properties"]["branch_ids"]["maxItems"], 6);
+    assert_eq!(
+        tool.input_schema["properties"]["generation"]["type"],
+        "object"
+    );
+    assert!(tool_output_schema("reasoning_tree_compare").is_some());
+    assert_eq!(tool_group("reasoning_tree_compare"), Some("core"));
+}
+
+#[test]
+fn test_tree_merge_tool_definition() {
+    let tool = get_tree_merge_tool();
+
+    assert_eq!(tool.name, "reasoning_tree_merge");
+    assert_eq!(
+        tool.input_schema["required"],
+        json!(["session_id", "branch_a", "branch_b"])
+    );
+    assert!(tool_output_schema("reasoning_tree_merge").is_some());
+    assert_eq!(tool_group("reasoning_tree_merge"), Some("core"));
+}
+
+#[test]
+fn test_tree_export_tool_definition() {
+    let tool = get_tree_export_tool();
+
+    assert_eq!(tool.name, "reasoning_tree_export");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    assert_eq!(
+        tool.input_schema["properties"]["format"]["enum"],
+        json!(["mermaid", "ascii"])
+    );
+    assert!(tool_output_schema("reasoning_tree_export").is_some());
+    assert_eq!(tool_group("reasoning_tree_export"), Some("core"));
+}
+
 #[test]
 fn test_divergent_tool_definition() {
     let tool = get_divergent_tool();
@@ -219,6 +541,41 @@ fn test_divergent_tool_definition() {
 
     let schema = &tool.input_schema;
     assert!(schema["properties"]["num_perspectives"].is_object());
+    assert_eq!(schema["properties"]["shortlist"]["minimum"], 1);
+}
+
+#[test]
+fn test_divergent_evaluate_tool_definition() {
+    let tool = get_divergent_evaluate_tool();
+
+    assert_eq!(tool.name, "reasoning_divergent_evaluate");
+    assert_eq!(
+        tool.input_schema["required"],
+        json!(["session_id", "criteria"])
+    );
+    assert_eq!(tool.input_schema["properties"]["winners"]["maximum"], 6);
+    assert!(tool_output_schema("reasoning_divergent_evaluate").is_some());
+    assert_eq!(tool_group("reasoning_divergent_evaluate"), Some("core"));
+}
+
+#[test]
+fn test_pipe_tools_accept_generation_params() {
+    for tool in [
+        get_linear_tool(),
+        get_divergent_tool(),
+        get_assess_evidence_tool(),
+        get_mcts_explore_tool(),
+        get_counterfactual_tool(),
+    ] {
+        let generation = &tool.input_schema["properties"]["generation"];
+        assert_eq!(generation["type"], "object", "{}", tool.name);
+        assert_eq!(generation["properties"]["temperature"]["maximum"], 2);
+        assert_eq!(generation["properties"]["top_p"]["maximum"], 1);
+        assert_eq!(generation["properties"]["max_tokens"]["type"], "integer");
+    }
+
+    // Tools that never call a pipe do not advertise it
+    assert!(get_tree_list_tool().input_schema["properties"]["generation"].is_null());
 }
 
 #[test]
@@ -227,6 +584,13 @@ fn test_reflection_tool_definition() {
 
     assert_eq!(tool.name, "reasoning_reflection");
     assert!(tool.description.contains("Meta-cognitive"));
+    assert_eq!(
+        tool.input_schema["properties"]["min_improvement"]["type"],
+        "number"
+    );
+    let rubric = &tool.input_schema["properties"]["rubric"];
+    assert_eq!(rubric["maxItems"], 8);
+    assert_eq!(rubric["items"]["required"], json!(["name"]));
 }
 
 #[test]
@@ -265,6 +629,16 @@ fn test_checkpoint_list_tool_definition() {
     assert_eq!(tool.name, "reasoning_checkpoint_list");
 }
 
+#[test]
+fn test_backtrack_diff_tool_definition() {
+    let tool = get_backtrack_diff_tool();
+
+    assert_eq!(tool.name, "reasoning_backtrack_diff");
+    let required = tool.input_schema["required"].as_array().unwrap();
+    assert!(required.contains(&json!("checkpoint_a")));
+    assert!(required.contains(&json!("checkpoint_b")));
+}
+
 #[test]
 fn test_auto_tool_definition() {
     let tool = get_auto_tool();
@@ -306,6 +680,38 @@ fn test_got_score_tool_definition() {
     assert!(required.contains(&json!("node_id")));
 }
 
+#[test]
+fn test_got_rank_tool_definition() {
+    let tool = get_got_rank_tool();
+    assert_eq!(tool.name, "reasoning_got_rank");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    let properties = &tool.input_schema["properties"];
+    assert_eq!(
+        properties["method"]["enum"],
+        json!(["bradley_terry", "elo"])
+    );
+    assert_eq!(properties["rounds"]["maximum"], 3);
+    assert_eq!(properties["node_ids"]["maxItems"], 8);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["rankings"].is_object());
+    assert!(output["properties"]["comparisons"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("got"));
+}
+
+#[test]
+fn test_got_aggregate_best_tool_definition() {
+    let tool = get_got_aggregate_best_tool();
+    assert_eq!(tool.name, "reasoning_got_aggregate_best");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    let properties = &tool.input_schema["properties"];
+    assert_eq!(properties["k"]["minimum"], 2);
+    assert_eq!(properties["mark_terminal"]["type"], "boolean");
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["selected"].is_object());
+    assert!(output["properties"]["aggregation"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("got"));
+}
+
 #[test]
 fn test_got_aggregate_tool_definition() {
     let tool = get_got_aggregate_tool();
@@ -355,6 +761,56 @@ fn test_got_state_tool_definition() {
     assert!(required.contains(&json!("session_id")));
 }
 
+#[test]
+fn test_got_export_tool_definition() {
+    let tool = get_got_export_tool();
+
+    assert_eq!(tool.name, "reasoning_got_export");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    assert_eq!(
+        tool.input_schema["properties"]["format"]["enum"],
+        json!(["dot", "graphml"])
+    );
+}
+
+#[test]
+fn test_got_validate_tool_definition() {
+    let tool = get_got_validate_tool();
+    assert_eq!(tool.name, "reasoning_got_validate");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["is_dag"].is_object());
+    assert!(output["properties"]["issues"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("got"));
+}
+
+#[test]
+fn test_got_best_path_tool_definition() {
+    let tool = get_got_best_path_tool();
+    assert_eq!(tool.name, "reasoning_got_best_path");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    assert_eq!(
+        tool.input_schema["properties"]["terminal_node_id"]["type"],
+        "string"
+    );
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["steps"].is_object());
+    assert!(output["properties"]["explanation"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("got"));
+}
+
+#[test]
+fn test_got_resume_tool_definition() {
+    let tool = get_got_resume_tool();
+    assert_eq!(tool.name, "reasoning_got_resume");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    assert_eq!(tool.input_schema["properties"]["expand"]["maximum"], 5);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["frontier"].is_object());
+    assert!(output["properties"]["expanded"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("got"));
+}
+
 // ============================================================================
 // Phase 4 Tool Definition Tests - Bias & Fallacy Detection
 // ============================================================================
@@ -395,6 +851,449 @@ fn test_detect_fallacies_tool_definition() {
     assert_eq!(schema["properties"]["check_informal"]["type"], "boolean");
 }
 
+#[test]
+fn test_search_tool_definition() {
+    let tool = get_search_tool();
+
+    assert_eq!(tool.name, "reasoning_search");
+    assert!(tool.description.contains("search"));
+
+    let schema = &tool.input_schema;
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["query"]));
+    assert!(schema["properties"]["session_id"].is_object());
+    assert!(schema["properties"]["mode"].is_object());
+    assert_eq!(schema["properties"]["limit"]["maximum"], 100);
+    assert_eq!(schema["additionalProperties"], false);
+}
+
+#[test]
+fn test_similar_tool_definition() {
+    let tool = get_similar_tool();
+
+    assert_eq!(tool.name, "reasoning_similar");
+    assert!(tool.description.contains("similar"));
+
+    let schema = &tool.input_schema;
+    assert_eq!(schema["required"], json!(["query"]));
+    assert!(schema["properties"]["session_id"].is_object());
+    assert_eq!(
+        schema["properties"]["source_type"]["enum"],
+        json!(["thought", "graph_node"])
+    );
+    assert_eq!(schema["properties"]["limit"]["maximum"], 50);
+    assert_eq!(schema["additionalProperties"], false);
+}
+
+#[test]
+fn test_session_thoughts_tool_definition() {
+    let tool = get_session_thoughts_tool();
+
+    assert_eq!(tool.name, "reasoning_session_thoughts");
+
+    let schema = &tool.input_schema;
+    assert_eq!(schema["required"], json!(["session_id"]));
+    assert_eq!(schema["properties"]["limit"]["maximum"], 500);
+    assert_eq!(schema["properties"]["offset"]["minimum"], 0);
+}
+
+#[test]
+fn test_session_tag_tool_definition() {
+    let tool = get_session_tag_tool();
+
+    assert_eq!(tool.name, "reasoning_session_tag");
+
+    let schema = &tool.input_schema;
+    assert_eq!(schema["required"], json!(["session_id"]));
+    assert_eq!(schema["properties"]["add"]["type"], "array");
+    assert_eq!(schema["properties"]["remove"]["type"], "array");
+}
+
+#[test]
+fn test_session_list_tool_definition() {
+    let tool = get_session_list_tool();
+
+    assert_eq!(tool.name, "reasoning_session_list");
+
+    let props = &tool.input_schema["properties"];
+    for field in [
+        "mode",
+        "tag",
+        "created_after",
+        "created_before",
+        "has_detections",
+        "in_trash",
+        "limit",
+        "offset",
+    ] {
+        assert!(props[field].is_object(), "missing {}", field);
+    }
+    assert!(tool.input_schema.get("required").is_none());
+}
+
+#[test]
+fn test_quota_status_tool_definition() {
+    let tool = get_quota_status_tool();
+
+    assert_eq!(tool.name, "reasoning_quota_status");
+    assert_eq!(tool.input_schema["properties"], json!({}));
+    assert!(tool_annotations(&tool.name).read_only_hint);
+}
+
+#[test]
+fn test_server_status_tool_definition() {
+    let tool = get_server_status_tool();
+
+    assert_eq!(tool.name, "reasoning_server_status");
+    assert_eq!(tool.input_schema["properties"], json!({}));
+    assert!(tool_annotations(&tool.name).read_only_hint);
+    assert!(tool_output_schema(&tool.name).is_some());
+}
+
+#[test]
+fn test_debate_tool_definition() {
+    let tool = get_debate_tool();
+
+    assert_eq!(tool.name, "reasoning_debate");
+    let schema = &tool.input_schema;
+    assert_eq!(schema["required"], json!(["proposition"]));
+    assert_eq!(schema["properties"]["rounds"]["maximum"], 5);
+    assert!(schema["properties"]["generation"].is_object());
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["transcript"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("debate"));
+}
+
+#[test]
+fn test_root_cause_tool_definition() {
+    let tool = get_root_cause_tool();
+
+    assert_eq!(tool.name, "reasoning_root_cause");
+    let schema = &tool.input_schema;
+    assert_eq!(schema["required"], json!(["problem"]));
+    assert_eq!(
+        schema["properties"]["method"]["enum"],
+        json!(["five_whys", "fishbone"])
+    );
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["corrective_actions"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("root_cause"));
+}
+
+#[test]
+fn test_plan_tool_definitions() {
+    let plan = get_plan_tool();
+    assert_eq!(plan.name, "reasoning_plan");
+    assert_eq!(plan.input_schema["required"], json!(["goal"]));
+    assert_eq!(plan.input_schema["properties"]["max_tasks"]["maximum"], 20);
+    let output = tool_output_schema(&plan.name).unwrap();
+    assert!(output["properties"]["critical_path"].is_object());
+
+    let revise = get_plan_revise_tool();
+    assert_eq!(revise.name, "reasoning_plan_revise");
+    assert_eq!(
+        revise.input_schema["required"],
+        json!(["session_id", "failed_task_id", "reason"])
+    );
+    assert!(!tool_annotations(&revise.name).read_only_hint);
+    assert_eq!(tool_group(&plan.name), Some("planning"));
+    assert_eq!(tool_group(&revise.name), Some("planning"));
+}
+
+#[test]
+fn test_estimate_tool_definition() {
+    let tool = get_estimate_tool();
+
+    assert_eq!(tool.name, "reasoning_estimate");
+    assert_eq!(tool.input_schema["required"], json!(["question"]));
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["likely_low"].is_object());
+    assert!(output["properties"]["arithmetic_checks"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("estimation"));
+}
+
+#[test]
+fn test_swot_tool_definitions() {
+    let swot = get_swot_tool();
+    assert_eq!(swot.name, "reasoning_swot");
+    assert_eq!(swot.input_schema["required"], json!(["subject"]));
+    assert!(swot.input_schema["properties"]["perspective_id"].is_object());
+    assert!(!tool_annotations(&swot.name).read_only_hint);
+    let output = tool_output_schema(&swot.name).unwrap();
+    assert!(output["properties"]["threats"].is_object());
+
+    let list = get_swot_list_tool();
+    assert_eq!(list.name, "reasoning_swot_list");
+    assert!(list.input_schema.get("required").is_none());
+    assert!(tool_annotations(&list.name).read_only_hint);
+    assert!(tool_output_schema(&list.name).is_some());
+    assert_eq!(tool_group(&swot.name), Some("swot"));
+    assert_eq!(tool_group(&list.name), Some("swot"));
+}
+
+#[test]
+fn test_hypothesis_tool_definitions() {
+    let design = get_hypothesis_tool();
+    assert_eq!(design.name, "reasoning_hypothesis");
+    assert_eq!(design.input_schema["required"], json!(["hypothesis"]));
+    assert!(design.input_schema["properties"]["prior"].is_object());
+    assert!(!tool_annotations(&design.name).read_only_hint);
+    let output = tool_output_schema(&design.name).unwrap();
+    assert!(output["properties"]["experiments"].is_object());
+
+    let observe = get_hypothesis_observe_tool();
+    assert_eq!(observe.name, "reasoning_hypothesis_observe");
+    assert_eq!(
+        observe.input_schema["required"],
+        json!(["session_id", "test_id", "experiment_id", "outcome"])
+    );
+    assert!(!tool_annotations(&observe.name).read_only_hint);
+    assert!(tool_output_schema(&observe.name).is_some());
+    assert_eq!(tool_group(&design.name), Some("hypothesis"));
+    assert_eq!(tool_group(&observe.name), Some("hypothesis"));
+}
+
+#[test]
+fn test_six_hats_tool_definition() {
+    let tool = get_six_hats_tool();
+    assert_eq!(tool.name, "reasoning_six_hats");
+    assert_eq!(tool.input_schema["required"], json!(["topic"]));
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["passes"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("six_hats"));
+}
+
+#[test]
+fn test_dialectic_tool_definition() {
+    let tool = get_dialectic_tool();
+    assert_eq!(tool.name, "reasoning_dialectic");
+    assert_eq!(tool.input_schema["required"], json!(["thesis"]));
+    assert_eq!(tool.input_schema["properties"]["rounds"]["maximum"], 5);
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["converged"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("dialectic"));
+}
+
+#[test]
+fn test_prioritize_tool_definitions() {
+    let tool = get_prioritize_tool();
+    assert_eq!(tool.name, "reasoning_prioritize");
+    assert_eq!(tool.input_schema["required"], json!(["items", "method"]));
+    assert_eq!(
+        tool.input_schema["properties"]["method"]["enum"],
+        json!(["rice", "moscow", "weighted"])
+    );
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["ranking"].is_object());
+
+    let revise = get_prioritize_revise_tool();
+    assert_eq!(
+        revise.input_schema["required"],
+        json!(["ranking_id", "item", "estimate"])
+    );
+    assert!(tool_output_schema(&revise.name).unwrap()["properties"]["moved"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("prioritization"));
+    assert_eq!(tool_group(&revise.name), Some("prioritization"));
+}
+
+#[test]
+fn test_negotiate_tool_definition() {
+    let tool = get_negotiate_tool();
+    assert_eq!(tool.name, "reasoning_negotiate");
+    assert_eq!(tool.input_schema["required"], json!(["scenario"]));
+    assert_eq!(tool.input_schema["properties"]["parties"]["minItems"], 2);
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["zopa"].is_object());
+    assert!(output["properties"]["concession_paths"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("negotiation"));
+}
+
+#[test]
+fn test_risk_tool_definitions() {
+    let tool = get_risk_tool();
+    assert_eq!(tool.name, "reasoning_risk");
+    assert_eq!(tool.input_schema["required"], json!(["subject"]));
+    assert_eq!(tool.input_schema["properties"]["max_risks"]["maximum"], 15);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["matrix"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("risk"));
+
+    let update = get_risk_update_tool();
+    assert_eq!(update.name, "reasoning_risk_update");
+    assert_eq!(update.input_schema["required"], json!(["risk_id"]));
+    assert!(!tool_annotations(&update.name).read_only_hint);
+    let output = tool_output_schema(&update.name).unwrap();
+    assert!(output["properties"]["risk"].is_object());
+    assert_eq!(tool_group(&update.name), Some("risk"));
+}
+
+#[test]
+fn test_systems_tool_definition() {
+    let tool = get_systems_tool();
+    assert_eq!(tool.name, "reasoning_systems");
+    assert_eq!(tool.input_schema["required"], json!(["situation"]));
+    assert_eq!(
+        tool.input_schema["properties"]["max_elements"]["minimum"],
+        2
+    );
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["loops"].is_object());
+    assert!(output["properties"]["mermaid"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("systems"));
+}
+
+#[test]
+fn test_verify_calculations_tool_definition() {
+    let tool = get_verify_calculations_tool();
+    assert_eq!(tool.name, "reasoning_verify_calculations");
+    assert!(tool.input_schema.get("required").is_none());
+    assert_eq!(tool.input_schema["properties"]["tolerance"]["maximum"], 0.5);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["checks"].is_object());
+    assert!(output["properties"]["detections"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("detection"));
+}
+
+#[test]
+fn test_probe_endpoint() {
+    assert_eq!(
+        probe_endpoint("GET /healthz HTTP/1.1\r\nHost: x"),
+        Some(Probe::Health)
+    );
+    assert_eq!(
+        probe_endpoint("GET /readyz?verbose=1 HTTP/1.1"),
+        Some(Probe::Ready)
+    );
+    assert_eq!(probe_endpoint("GET / HTTP/1.1"), None);
+    assert_eq!(probe_endpoint("POST /readyz HTTP/1.1"), None);
+    assert_eq!(probe_endpoint("GET /readyzz HTTP/1.1"), None);
+    assert_eq!(probe_endpoint(""), None);
+}
+
+#[test]
+fn test_quota_exceeded_response() {
+    let exceeded = QuotaExceeded {
+        limit: "requests_per_minute",
+        allowed: 60,
+        retry_after_secs: 12,
+    };
+    let response = JsonRpcResponse::quota_exceeded(Some(json!(7)), &exceeded);
+    let error = response.error.unwrap();
+
+    assert_eq!(error.code, QUOTA_EXCEEDED_CODE);
+    assert_eq!(
+        error.message,
+        "Rate limit of 60 requests per minute reached"
+    );
+    assert_eq!(
+        error.data,
+        Some(json!({"limit": "requests_per_minute", "allowed": 60, "retry_after_secs": 12}))
+    );
+}
+
+#[test]
+fn test_paginated_tools_accept_offset() {
+    for tool in [
+        get_got_state_tool(),
+        get_metrics_invocations_tool(),
+        get_session_list_tool(),
+    ] {
+        let props = &tool.input_schema["properties"];
+        assert!(props["limit"].is_object(), "{} missing limit", tool.name);
+        assert!(props["offset"].is_object(), "{} missing offset", tool.name);
+    }
+}
+
+#[test]
+fn test_session_delete_and_restore_tool_definitions() {
+    for (tool, name) in [
+        (get_session_delete_tool(), "reasoning_session_delete"),
+        (get_session_restore_tool(), "reasoning_session_restore"),
+    ] {
+        assert_eq!(tool.name, name);
+        assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+        assert_eq!(tool.input_schema["additionalProperties"], false);
+    }
+    assert!(get_session_delete_tool().description.contains("restore"));
+}
+
+#[test]
+fn test_revision_tool_definitions() {
+    let revise = get_revise_tool();
+    assert_eq!(revise.name, "reasoning_revise");
+    assert_eq!(
+        revise.input_schema["required"],
+        json!(["thought_id", "content"])
+    );
+
+    let history = get_thought_history_tool();
+    assert_eq!(history.name, "reasoning_thought_history");
+    assert_eq!(history.input_schema["required"], json!(["thought_id"]));
+}
+
+#[test]
+fn test_session_fork_tool_definition() {
+    let tool = get_session_fork_tool();
+    assert_eq!(tool.name, "reasoning_session_fork");
+    assert_eq!(tool.input_schema["required"], json!(["session_id"]));
+    assert_eq!(tool.input_schema["additionalProperties"], false);
+}
+
+#[test]
+fn test_knowledge_tool_definitions() {
+    let query = get_knowledge_query_tool();
+    assert_eq!(query.name, "reasoning_knowledge_query");
+    assert!(query.input_schema.get("required").is_none());
+    assert_eq!(query.input_schema["properties"]["limit"]["maximum"], 50);
+
+    let promote = get_knowledge_promote_tool();
+    assert_eq!(promote.name, "reasoning_knowledge_promote");
+    assert_eq!(
+        promote.input_schema["required"],
+        json!(["source_type", "source_id"])
+    );
+    assert_eq!(
+        promote.input_schema["properties"]["source_type"]["enum"],
+        json!(["graph_node", "decision", "evidence"])
+    );
+}
+
+#[test]
+fn test_pipe_status_tool_definition() {
+    let tool = get_pipe_status_tool();
+
+    assert_eq!(tool.name, "reasoning_pipe_status");
+    assert_eq!(tool.input_schema["properties"], json!({}));
+}
+
+#[test]
+fn test_pipe_health_tool_definition() {
+    let tool = get_pipe_health_tool();
+
+    assert_eq!(tool.name, "reasoning_pipe_health");
+    assert_eq!(tool.input_schema["properties"]["pipes"]["type"], "array");
+    assert!(tool.input_schema.get("required").is_none());
+}
+
+#[test]
+fn test_budget_status_tool_definition() {
+    let tool = get_budget_status_tool();
+
+    assert_eq!(tool.name, "reasoning_budget_status");
+    assert_eq!(
+        tool.input_schema["properties"]["session_id"]["type"],
+        "string"
+    );
+    assert!(tool.input_schema.get("required").is_none());
+}
+
 // ============================================================================
 // Tool count and completeness tests
 // ============================================================================
@@ -514,6 +1413,10 @@ fn test_initialize_result_serialization() {
             tools: ToolCapabilities {
                 list_changed: false,
             },
+            resources: ResourceCapabilities::default(),
+            prompts: PromptCapabilities::default(),
+            logging: LoggingCapabilities::default(),
+            completions: CompletionCapabilities::default(),
         },
         server_info: ServerInfo {
             name: "test-server".to_string(),
@@ -525,6 +1428,10 @@ fn test_initialize_result_serialization() {
 
     assert_eq!(json["protocolVersion"], "2024-11-05");
     assert_eq!(json["capabilities"]["tools"]["listChanged"], false);
+    assert_eq!(json["capabilities"]["resources"]["subscribe"], false);
+    assert_eq!(json["capabilities"]["prompts"]["listChanged"], false);
+    assert!(json["capabilities"]["logging"].is_object());
+    assert!(json["capabilities"]["completions"].is_object());
     assert_eq!(json["serverInfo"]["name"], "test-server");
 }
 
@@ -534,12 +1441,15 @@ fn test_tool_serialization() {
         name: "test_tool".to_string(),
         description: "A test tool".to_string(),
         input_schema: json!({"type": "object"}),
+        ..Default::default()
     };
 
     let json = serde_json::to_value(&tool).unwrap();
 
     assert_eq!(json["name"], "test_tool");
     assert_eq!(json["inputSchema"]["type"], "object");
+    assert!(json.get("title").is_none());
+    assert!(json.get("annotations").is_none());
 }
 
 #[test]
@@ -549,6 +1459,7 @@ fn test_tool_call_result_serialization() {
             content_type: "text".to_string(),
             text: "Hello, world!".to_string(),
         }],
+        structured_content: None,
         is_error: None,
     };
 
@@ -560,6 +1471,23 @@ fn test_tool_call_result_serialization() {
     assert!(json.get("isError").is_none());
 }
 
+#[test]
+fn test_tool_call_result_with_structured_content() {
+    let result = ToolCallResult {
+        content: vec![ToolResultContent {
+            content_type: "text".to_string(),
+            text: "{}".to_string(),
+        }],
+        structured_content: Some(json!({"session_id": "s-1", "confidence": 0.8})),
+        is_error: None,
+    };
+
+    let json = serde_json::to_value(&result).unwrap();
+
+    assert_eq!(json["structuredContent"]["session_id"], "s-1");
+    assert_eq!(json["structuredContent"]["confidence"], 0.8);
+}
+
 #[test]
 fn test_tool_call_result_with_error() {
     let result = ToolCallResult {
@@ -567,6 +1495,7 @@ fn test_tool_call_result_with_error() {
             content_type: "text".to_string(),
             text: "Error occurred".to_string(),
         }],
+        structured_content: None,
         is_error: Some(true),
     };
 
@@ -865,6 +1794,23 @@ fn test_divergent_tool_num_perspectives_bounds() {
     assert_eq!(schema["properties"]["num_perspectives"]["type"], "integer");
 }
 
+#[test]
+fn test_divergent_tool_techniques() {
+    let tool = get_divergent_tool();
+    let techniques = &tool.input_schema["properties"]["technique"]["enum"];
+    assert_eq!(
+        techniques,
+        &json!([
+            "scamper",
+            "reverse_brainstorming",
+            "random_stimulus",
+            "triz"
+        ])
+    );
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["stimuli"].is_object());
+}
+
 #[test]
 fn test_reflection_tool_iteration_bounds() {
     let tool = get_reflection_tool();
@@ -1096,6 +2042,10 @@ fn test_initialize_result_field_names() {
             tools: ToolCapabilities {
                 list_changed: false,
             },
+            resources: ResourceCapabilities::default(),
+            prompts: PromptCapabilities::default(),
+            logging: LoggingCapabilities::default(),
+            completions: CompletionCapabilities::default(),
         },
         server_info: ServerInfo {
             name: "test".to_string(),
@@ -1179,6 +2129,7 @@ fn test_tool_clone() {
 fn test_empty_tool_result_content_vec() {
     let result = ToolCallResult {
         content: vec![],
+        structured_content: None,
         is_error: None,
     };
 
@@ -1199,6 +2150,7 @@ fn test_multiple_tool_result_content_items() {
                 text: "Second".to_string(),
             },
         ],
+        structured_content: None,
         is_error: None,
     };
 
@@ -1554,6 +2506,7 @@ fn test_tool_call_result_with_error_false() {
             content_type: "text".to_string(),
             text: "Success".to_string(),
         }],
+        structured_content: None,
         is_error: Some(false),
     };
 
@@ -1568,6 +2521,7 @@ fn test_tool_call_result_with_multiline_text() {
             content_type: "text".to_string(),
             text: "Line 1\nLine 2\nLine 3".to_string(),
         }],
+        structured_content: None,
         is_error: None,
     };
 
@@ -1582,6 +2536,7 @@ fn test_tool_call_result_with_empty_text() {
             content_type: "text".to_string(),
             text: "".to_string(),
         }],
+        structured_content: None,
         is_error: None,
     };
 
@@ -1602,6 +2557,7 @@ fn test_tool_call_result_with_different_content_types() {
                 text: r#"{"key":"value"}"#.to_string(),
             },
         ],
+        structured_content: None,
         is_error: None,
     };
 
@@ -1719,6 +2675,10 @@ fn test_initialize_result_roundtrip() {
             tools: ToolCapabilities {
                 list_changed: false,
             },
+            resources: ResourceCapabilities::default(),
+            prompts: PromptCapabilities::default(),
+            logging: LoggingCapabilities::default(),
+            completions: CompletionCapabilities::default(),
         },
         server_info: ServerInfo {
             name: "test".to_string(),
@@ -1732,3 +2692,115 @@ fn test_initialize_result_roundtrip() {
     assert_eq!(deserialized["protocolVersion"], "2024-11-05");
     assert_eq!(deserialized["serverInfo"]["name"], "test");
 }
+
+#[test]
+fn test_code_tool_definitions() {
+    let diff = get_code_explain_diff_tool();
+    assert_eq!(diff.name, "reasoning_code_explain_diff");
+    assert_eq!(diff.input_schema["required"], json!(["diff"]));
+    let output = tool_output_schema(&diff.name).unwrap();
+    assert!(output["properties"]["files"].is_object());
+    assert!(output["properties"]["findings"].is_object());
+
+    let flow = get_code_trace_flow_tool();
+    assert_eq!(flow.input_schema["required"], json!(["code", "target"]));
+    let edge = get_code_edge_cases_tool();
+    assert_eq!(edge.input_schema["properties"]["max_cases"]["maximum"], 20);
+
+    let findings = get_code_findings_tool();
+    assert_eq!(findings.input_schema["required"], json!(["session_id"]));
+    assert!(tool_annotations(&findings.name).read_only_hint);
+    assert!(!tool_annotations(&diff.name).read_only_hint);
+    for tool in [diff, flow, edge, findings] {
+        assert_eq!(tool_group(&tool.name), Some("code"));
+    }
+}
+
+#[test]
+fn test_synthesize_tool_definition() {
+    let tool = get_synthesize_tool();
+    assert_eq!(tool.name, "reasoning_synthesize");
+    assert_eq!(tool.input_schema["required"], json!(["session_ids"]));
+    assert_eq!(
+        tool.input_schema["properties"]["session_ids"]["maxItems"],
+        10
+    );
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["key_findings"].is_object());
+    assert!(output["properties"]["combined_confidence"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("synthesis"));
+}
+
+#[test]
+fn test_critique_argument_schema() {
+    let tools = all_tools();
+    let schema = |name: &str| {
+        tools
+            .iter()
+            .find(|tool| tool.name == name)
+            .unwrap()
+            .input_schema
+            .clone()
+    };
+    assert!(schema("reasoning_linear")["properties"]["critique"].is_object());
+    assert!(schema("reasoning_debate")["properties"]["critique"].is_object());
+    // Critique tools and tools without a pipe call do not take it
+    assert!(schema("reasoning_reflection")["properties"]
+        .get("critique")
+        .is_none());
+    assert!(schema("reasoning_detect_fallacies")["properties"]
+        .get("critique")
+        .is_none());
+    assert!(schema("reasoning_session_list")["properties"]
+        .get("critique")
+        .is_none());
+}
+
+#[test]
+fn test_counterfactual_minimal_change_tool_definition() {
+    let tool = get_counterfactual_minimal_change_tool();
+    assert_eq!(tool.name, "reasoning_counterfactual_minimal_change");
+    assert_eq!(tool.input_schema["required"], json!(["branch_id"]));
+    assert_eq!(tool.input_schema["properties"]["max_tests"]["maximum"], 40);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["change_set"].is_object());
+    assert!(output["properties"]["minimal"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("counterfactual"));
+    assert!(!tool_annotations(&tool.name).read_only_hint);
+}
+
+#[test]
+fn test_mcts_explore_tool_search_parameters() {
+    let tool = get_mcts_explore_tool();
+    let properties = &tool.input_schema["properties"];
+    assert_eq!(
+        properties["policy"]["enum"],
+        json!(["ucb1", "progressive_widening"])
+    );
+    assert_eq!(properties["rollout_depth"]["maximum"], 5);
+    assert_eq!(properties["expansion_width"]["minimum"], 1);
+    assert!(properties["exploration_constant"].is_object());
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["settings"].is_object());
+    assert!(output["properties"]["pipe_calls"].is_object());
+}
+
+#[test]
+fn test_routing_feedback_tool_definition() {
+    let tool = get_routing_feedback_tool();
+    assert_eq!(tool.name, "reasoning_routing_feedback");
+    assert_eq!(tool.input_schema["required"], json!(["score"]));
+    assert_eq!(tool.input_schema["properties"]["score"]["maximum"], 1);
+    let output = tool_output_schema(&tool.name).unwrap();
+    assert!(output["properties"]["outcome_score"].is_object());
+    assert_eq!(tool_group(&tool.name), Some("core"));
+
+    let auto = get_auto_tool();
+    assert_eq!(
+        auto.input_schema["properties"]["use_history"]["type"],
+        "boolean"
+    );
+    let output = tool_output_schema(&auto.name).unwrap();
+    assert!(output["properties"]["routing_id"].is_object());
+    assert!(output["properties"]["history"].is_object());
+}
@@ -3,26 +3,59 @@
 //! This module provides:
 //! - MCP server implementation over stdio
 //! - Tool call handlers and routing
+//! - Sessions exposed as MCP resources
+//! - Prompt templates for common reasoning tasks
 //! - Shared application state management
 //! - Self-improvement system integration
 
+mod completion;
+mod critique;
 mod handlers;
+mod health;
 mod mcp;
-
+mod prompts;
+mod quota;
+mod resources;
+mod sampling;
+mod session_locks;
+mod subscriptions;
+mod tool_groups;
+mod validation;
+
+pub use completion::*;
+pub use critique::*;
 pub use handlers::*;
+pub use health::*;
 pub use mcp::*;
-
+pub use prompts::*;
+pub use quota::*;
+pub use resources::*;
+pub use session_locks::*;
+pub use subscriptions::*;
+pub use tool_groups::*;
+pub use validation::*;
+
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 
 use crate::config::Config;
-use crate::langbase::LangbaseClient;
+use crate::langbase::{
+    embedding_provider, ConcurrencyLimiter, EmbeddingProvider, LangbaseClient, PayloadLogger,
+    PipeCache, PipeCircuitBreakers, RateLimiter, Redactor,
+};
 use crate::modes::{
-    AutoMode, BacktrackingMode, CounterfactualMode, DecisionMode, DetectionMode, DivergentMode,
-    EvidenceMode, GotMode, LinearMode, MCTSMode, ReflectionMode, TimelineMode, TreeMode,
+    AutoMode, BacktrackingMode, CalcMode, CodeReasoningMode, CounterfactualMode, DebateMode,
+    DecisionMode, DetectionMode, DialecticMode, DivergentMode, EstimateMode, EvidenceMode, GotMode,
+    HypothesisMode, LinearMode, MCTSMode, NegotiationMode, PlanningMode, PrioritizationMode,
+    RcaMode, ReflectionMode, RiskMode, SixHatsMode, SwotMode, SynthesisMode, SystemsMode,
+    TimelineMode, TreeMode,
 };
 use crate::presets::PresetRegistry;
+use crate::providers::{MiddlewareBackend, ProviderRouter, SharedBackend, SpendBudget};
 use crate::self_improvement::{SelfImprovementConfig, SelfImprovementSystem};
-use crate::storage::SqliteStorage;
+use crate::storage::{
+    AuditLogger, CachedStorage, MaintenanceStatus, SharedStorage, WorkspaceStorage,
+};
 
 /// Application state shared across handlers.
 ///
@@ -31,10 +64,40 @@ use crate::storage::SqliteStorage;
 pub struct AppState {
     /// Application configuration.
     pub config: Config,
-    /// SQLite storage backend.
-    pub storage: SqliteStorage,
+    /// Storage backend shared by all modes.
+    ///
+    /// Confined to the current workspace, and wrapped in the hot-state cache
+    /// unless `config.database.cache_capacity` is zero.
+    pub storage: SharedStorage,
+    /// Hot-state cache in front of `storage`, if enabled.
+    pub cache: Option<CachedStorage>,
+    /// Workspace view `storage` goes through; see
+    /// [`with_workspace`](Self::with_workspace) to use another.
+    pub workspace: WorkspaceStorage,
     /// Langbase API client.
     pub langbase: LangbaseClient,
+    /// LLM backend the modes call pipes through, routed per pipe by
+    /// `config.providers`.
+    pub backend: SharedBackend,
+    /// Per-pipe circuit breakers in the Langbase client, unless disabled by
+    /// `config.circuit_breaker`.
+    pub pipe_circuits: Option<Arc<PipeCircuitBreakers>>,
+    /// Daily and per-session spend on pipe calls made through `backend`.
+    pub budget: Arc<SpendBudget>,
+    /// Embedding provider for semantic similarity search.
+    pub embeddings: Arc<dyn EmbeddingProvider>,
+    /// Audit logger for mutating tool calls.
+    pub audit: AuditLogger,
+    /// Outcome of the latest scheduled database maintenance run.
+    pub maintenance: MaintenanceStatus,
+    /// Per-session locks serializing mutating tool calls.
+    pub session_locks: Arc<SessionLocks>,
+    /// Changes of sessions, for clients subscribed to them.
+    pub session_events: SessionEvents,
+    /// Request rate and tool-call usage of each network client.
+    pub client_quotas: Arc<ClientQuotas>,
+    /// When the server started.
+    pub started_at: DateTime<Utc>,
     /// Linear reasoning mode handler.
     pub linear_mode: LinearMode,
     /// Tree reasoning mode handler.
@@ -61,6 +124,36 @@ pub struct AppState {
     pub mcts_mode: MCTSMode,
     /// Counterfactual analysis mode handler.
     pub counterfactual_mode: CounterfactualMode,
+    /// Debate mode handler.
+    pub debate_mode: DebateMode,
+    /// Root cause analysis mode handler.
+    pub rca_mode: RcaMode,
+    /// Planning mode handler.
+    pub planning_mode: PlanningMode,
+    /// Estimate mode handler.
+    pub estimate_mode: EstimateMode,
+    /// SWOT mode handler.
+    pub swot_mode: SwotMode,
+    /// Hypothesis mode handler.
+    pub hypothesis_mode: HypothesisMode,
+    /// Six Thinking Hats mode handler.
+    pub six_hats_mode: SixHatsMode,
+    /// Dialectic mode handler.
+    pub dialectic_mode: DialecticMode,
+    /// Prioritization mode handler.
+    pub prioritization_mode: PrioritizationMode,
+    /// Negotiation mode handler.
+    pub negotiation_mode: NegotiationMode,
+    /// Risk mode handler.
+    pub risk_mode: RiskMode,
+    /// Systems thinking mode handler.
+    pub systems_mode: SystemsMode,
+    /// Calculation verification mode handler.
+    pub calc_mode: CalcMode,
+    /// Code reasoning mode handler.
+    pub code_mode: CodeReasoningMode,
+    /// Synthesis mode handler.
+    pub synthesis_mode: SynthesisMode,
     /// Workflow preset registry.
     pub preset_registry: Arc<PresetRegistry>,
     /// Self-improvement system (optional, enabled via config).
@@ -72,7 +165,25 @@ pub struct AppState {
 
 impl AppState {
     /// Create new application state
-    pub fn new(config: Config, storage: SqliteStorage, langbase: LangbaseClient) -> Self {
+    ///
+    /// Accepts any storage convertible into [`SharedStorage`], such as a
+    /// `SqliteStorage` or an `Arc` of a custom [`Storage`](crate::storage::Storage)
+    /// implementation.
+    pub fn new(
+        config: Config,
+        storage: impl Into<SharedStorage>,
+        langbase: LangbaseClient,
+    ) -> Self {
+        let storage: SharedStorage = storage.into();
+        let cache = (config.database.cache_capacity > 0)
+            .then(|| CachedStorage::new(storage.clone(), config.database.cache_capacity));
+        let storage: SharedStorage = match &cache {
+            Some(cache) => Arc::new(cache.clone()),
+            None => storage,
+        };
+        let workspace = WorkspaceStorage::new(storage, config.database.workspace_id.clone());
+        let storage: SharedStorage = Arc::new(workspace.clone());
+
         // Debug: Log pipe configuration
         tracing::info!(
             detection_pipe = ?config.pipes.detection.as_ref().and_then(|d| d.pipe.as_ref()),
@@ -81,19 +192,114 @@ impl AppState {
             "AppState initializing with pipe configuration"
         );
 
-        let linear_mode = LinearMode::new(storage.clone(), langbase.clone(), &config);
-        let tree_mode = TreeMode::new(storage.clone(), langbase.clone(), &config);
-        let divergent_mode = DivergentMode::new(storage.clone(), langbase.clone(), &config);
-        let reflection_mode = ReflectionMode::new(storage.clone(), langbase.clone(), &config);
-        let backtracking_mode = BacktrackingMode::new(storage.clone(), langbase.clone(), &config);
-        let auto_mode = AutoMode::new(storage.clone(), langbase.clone(), &config);
-        let got_mode = GotMode::new(storage.clone(), langbase.clone(), &config);
-        let decision_mode = DecisionMode::new(storage.clone(), langbase.clone(), &config);
-        let evidence_mode = EvidenceMode::new(storage.clone(), langbase.clone(), &config);
-        let detection_mode = DetectionMode::new(storage.clone(), langbase.clone(), &config);
-        let timeline_mode = TimelineMode::new(storage.clone(), langbase.clone(), &config);
-        let mcts_mode = MCTSMode::new(storage.clone(), langbase.clone(), &config);
-        let counterfactual_mode = CounterfactualMode::new(storage.clone(), langbase.clone(), &config);
+        let langbase = if config.pipe_cache.enabled {
+            tracing::info!(
+                ttl_secs = config.pipe_cache.ttl_secs,
+                capacity = config.pipe_cache.capacity,
+                "Pipe response cache enabled"
+            );
+            let cache = PipeCache::new(config.pipe_cache.clone()).with_storage(storage.clone());
+            langbase.with_cache(Arc::new(cache))
+        } else {
+            langbase
+        };
+        let rate_limiter = RateLimiter::new(&config.rate_limits);
+        let langbase = if rate_limiter.is_empty() {
+            langbase
+        } else {
+            tracing::info!(
+                pipes = ?config.rate_limits.pipes.keys().collect::<Vec<_>>(),
+                "Pipe rate limits enabled"
+            );
+            langbase.with_rate_limiter(Arc::new(rate_limiter))
+        };
+        let pipe_circuits = config
+            .circuit_breaker
+            .is_enabled()
+            .then(|| Arc::new(PipeCircuitBreakers::new(&config.circuit_breaker)));
+        let langbase = match &pipe_circuits {
+            Some(breakers) => langbase.with_circuit_breakers(breakers.clone()),
+            None => langbase,
+        };
+        let langbase = if config.concurrency.is_enabled() {
+            tracing::info!(
+                max_concurrent_requests = config.concurrency.max_concurrent_requests,
+                pipes = ?config.concurrency.pipes,
+                "Pipe concurrency limits enabled"
+            );
+            let limiter = ConcurrencyLimiter::new(&config.concurrency);
+            langbase.with_concurrency_limiter(Arc::new(limiter))
+        } else {
+            langbase
+        };
+        let langbase = if config.payload_log.enabled {
+            match Redactor::new(&config.payload_log) {
+                Ok(redactor) => {
+                    tracing::warn!(
+                        "Pipe payload logging enabled; redacted bodies are stored in pipe_payloads"
+                    );
+                    let logger = PayloadLogger::new(redactor, storage.clone());
+                    langbase.with_payload_logger(Arc::new(logger))
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Invalid payload redaction rules, payload logging disabled");
+                    langbase
+                }
+            }
+        } else {
+            langbase
+        };
+
+        let backend: SharedBackend = ProviderRouter::from_config(&config, langbase.clone())
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Failed to build provider backends, using Langbase");
+                ProviderRouter::new(langbase.clone())
+            })
+            .into();
+        let budget = Arc::new(SpendBudget::new(&config.budget));
+        if config.budget.is_enabled() {
+            tracing::info!(
+                daily_tokens = ?config.budget.daily_tokens,
+                daily_cost_usd = ?config.budget.daily_cost_usd,
+                session_tokens = ?config.budget.session_tokens,
+                session_cost_usd = ?config.budget.session_cost_usd,
+                "Spend budget enabled"
+            );
+        }
+        let backend: SharedBackend =
+            Arc::new(MiddlewareBackend::new(backend).with_middleware(budget.clone()));
+
+        let embeddings = embedding_provider(&config.embeddings, &langbase);
+        let Modes {
+            linear_mode,
+            tree_mode,
+            reflection_mode,
+            backtracking_mode,
+            divergent_mode,
+            auto_mode,
+            got_mode,
+            decision_mode,
+            evidence_mode,
+            detection_mode,
+            timeline_mode,
+            mcts_mode,
+            counterfactual_mode,
+            debate_mode,
+            rca_mode,
+            planning_mode,
+            estimate_mode,
+            swot_mode,
+            hypothesis_mode,
+            six_hats_mode,
+            dialectic_mode,
+            prioritization_mode,
+            negotiation_mode,
+            risk_mode,
+            systems_mode,
+            calc_mode,
+            code_mode,
+            synthesis_mode,
+        } = Modes::new(&storage, &backend, &config, &embeddings);
         let preset_registry = Arc::new(PresetRegistry::new());
 
         // Initialize self-improvement system (always enabled)
@@ -105,10 +311,25 @@ impl AppState {
             langbase.clone(),
         )));
 
+        let audit = AuditLogger::new(storage.clone(), "mcp");
+        let client_quotas = Arc::new(ClientQuotas::new(&config.transport));
+
         Self {
             config,
             storage,
+            cache,
+            workspace,
             langbase,
+            backend,
+            pipe_circuits,
+            budget,
+            embeddings,
+            audit,
+            maintenance: MaintenanceStatus::default(),
+            session_locks: Arc::new(SessionLocks::new()),
+            session_events: SessionEvents::new(),
+            client_quotas,
+            started_at: Utc::now(),
             linear_mode,
             tree_mode,
             divergent_mode,
@@ -122,11 +343,110 @@ impl AppState {
             timeline_mode,
             mcts_mode,
             counterfactual_mode,
+            debate_mode,
+            rca_mode,
+            planning_mode,
+            estimate_mode,
+            swot_mode,
+            hypothesis_mode,
+            six_hats_mode,
+            dialectic_mode,
+            prioritization_mode,
+            negotiation_mode,
+            risk_mode,
+            systems_mode,
+            calc_mode,
+            code_mode,
+            synthesis_mode,
             preset_registry,
             self_improvement,
         }
     }
 
+    /// A copy of this state confined to `workspace_id`.
+    ///
+    /// Storage, the audit logger, and every mode handler go through a new
+    /// workspace view; caches, locks, quotas, the budget, and the backend are
+    /// still shared. `self` keeps its workspace, so one client choosing a
+    /// workspace does not move the others.
+    pub fn with_workspace(&self, workspace_id: impl Into<String>) -> Self {
+        let workspace = self.workspace.with_workspace(workspace_id);
+        let storage: SharedStorage = Arc::new(workspace.clone());
+        let Modes {
+            linear_mode,
+            tree_mode,
+            reflection_mode,
+            backtracking_mode,
+            divergent_mode,
+            auto_mode,
+            got_mode,
+            decision_mode,
+            evidence_mode,
+            detection_mode,
+            timeline_mode,
+            mcts_mode,
+            counterfactual_mode,
+            debate_mode,
+            rca_mode,
+            planning_mode,
+            estimate_mode,
+            swot_mode,
+            hypothesis_mode,
+            six_hats_mode,
+            dialectic_mode,
+            prioritization_mode,
+            negotiation_mode,
+            risk_mode,
+            systems_mode,
+            calc_mode,
+            code_mode,
+            synthesis_mode,
+        } = Modes::new(&storage, &self.backend, &self.config, &self.embeddings);
+
+        Self {
+            audit: AuditLogger::new(storage.clone(), self.audit.actor()),
+            storage,
+            workspace,
+            linear_mode,
+            tree_mode,
+            reflection_mode,
+            backtracking_mode,
+            divergent_mode,
+            auto_mode,
+            got_mode,
+            decision_mode,
+            evidence_mode,
+            detection_mode,
+            timeline_mode,
+            mcts_mode,
+            counterfactual_mode,
+            debate_mode,
+            rca_mode,
+            planning_mode,
+            estimate_mode,
+            swot_mode,
+            hypothesis_mode,
+            six_hats_mode,
+            dialectic_mode,
+            prioritization_mode,
+            negotiation_mode,
+            risk_mode,
+            systems_mode,
+            calc_mode,
+            code_mode,
+            synthesis_mode,
+            ..self.clone()
+        }
+    }
+
+    /// A copy of this state whose audit entries are attributed to `actor`.
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            audit: self.audit.with_actor(actor),
+            ..self.clone()
+        }
+    }
+
     /// Record an invocation event for self-improvement monitoring.
     ///
     /// This should be called after each tool invocation to feed metrics
@@ -149,7 +469,19 @@ impl Clone for AppState {
         Self {
             config: self.config.clone(),
             storage: self.storage.clone(),
+            cache: self.cache.clone(),
+            workspace: self.workspace.clone(),
             langbase: self.langbase.clone(),
+            backend: Arc::clone(&self.backend),
+            pipe_circuits: self.pipe_circuits.clone(),
+            budget: Arc::clone(&self.budget),
+            embeddings: Arc::clone(&self.embeddings),
+            audit: self.audit.clone(),
+            maintenance: self.maintenance.clone(),
+            session_locks: Arc::clone(&self.session_locks),
+            session_events: self.session_events.clone(),
+            client_quotas: Arc::clone(&self.client_quotas),
+            started_at: self.started_at,
             linear_mode: self.linear_mode.clone(),
             tree_mode: self.tree_mode.clone(),
             divergent_mode: self.divergent_mode.clone(),
@@ -163,12 +495,131 @@ impl Clone for AppState {
             timeline_mode: self.timeline_mode.clone(),
             mcts_mode: self.mcts_mode.clone(),
             counterfactual_mode: self.counterfactual_mode.clone(),
+            debate_mode: self.debate_mode.clone(),
+            rca_mode: self.rca_mode.clone(),
+            planning_mode: self.planning_mode.clone(),
+            estimate_mode: self.estimate_mode.clone(),
+            swot_mode: self.swot_mode.clone(),
+            hypothesis_mode: self.hypothesis_mode.clone(),
+            six_hats_mode: self.six_hats_mode.clone(),
+            dialectic_mode: self.dialectic_mode.clone(),
+            prioritization_mode: self.prioritization_mode.clone(),
+            negotiation_mode: self.negotiation_mode.clone(),
+            risk_mode: self.risk_mode.clone(),
+            systems_mode: self.systems_mode.clone(),
+            calc_mode: self.calc_mode.clone(),
+            code_mode: self.code_mode.clone(),
+            synthesis_mode: self.synthesis_mode.clone(),
             preset_registry: Arc::clone(&self.preset_registry),
             self_improvement: self.self_improvement.as_ref().map(Arc::clone),
         }
     }
 }
 
+/// Mode handlers bound to one storage view.
+struct Modes {
+    linear_mode: LinearMode,
+    tree_mode: TreeMode,
+    reflection_mode: ReflectionMode,
+    backtracking_mode: BacktrackingMode,
+    divergent_mode: DivergentMode,
+    auto_mode: AutoMode,
+    got_mode: GotMode,
+    decision_mode: DecisionMode,
+    evidence_mode: EvidenceMode,
+    detection_mode: DetectionMode,
+    timeline_mode: TimelineMode,
+    mcts_mode: MCTSMode,
+    counterfactual_mode: CounterfactualMode,
+    debate_mode: DebateMode,
+    rca_mode: RcaMode,
+    planning_mode: PlanningMode,
+    estimate_mode: EstimateMode,
+    swot_mode: SwotMode,
+    hypothesis_mode: HypothesisMode,
+    six_hats_mode: SixHatsMode,
+    dialectic_mode: DialecticMode,
+    prioritization_mode: PrioritizationMode,
+    negotiation_mode: NegotiationMode,
+    risk_mode: RiskMode,
+    systems_mode: SystemsMode,
+    calc_mode: CalcMode,
+    code_mode: CodeReasoningMode,
+    synthesis_mode: SynthesisMode,
+}
+
+impl Modes {
+    fn new(
+        storage: &SharedStorage,
+        backend: &SharedBackend,
+        config: &Config,
+        embeddings: &Arc<dyn EmbeddingProvider>,
+    ) -> Self {
+        let linear_mode = LinearMode::new(storage.clone(), backend.clone(), config);
+        let tree_mode = TreeMode::new(storage.clone(), backend.clone(), config);
+        let reflection_mode = ReflectionMode::new(storage.clone(), backend.clone(), config);
+        let backtracking_mode = BacktrackingMode::new(storage.clone(), backend.clone(), config);
+        let divergent_mode = DivergentMode::new(storage.clone(), backend.clone(), config)
+            .with_embeddings(Arc::clone(embeddings));
+        let auto_mode = AutoMode::new(storage.clone(), backend.clone(), config)
+            .with_embeddings(Arc::clone(embeddings));
+        let got_mode = GotMode::new(storage.clone(), backend.clone(), config)
+            .with_embeddings(Arc::clone(embeddings));
+        let decision_mode = DecisionMode::new(storage.clone(), backend.clone(), config);
+        let evidence_mode = EvidenceMode::new(storage.clone(), backend.clone(), config);
+        let detection_mode = DetectionMode::new(storage.clone(), backend.clone(), config);
+        let timeline_mode = TimelineMode::new(storage.clone(), backend.clone(), config);
+        let mcts_mode = MCTSMode::new(storage.clone(), backend.clone(), config);
+        let counterfactual_mode = CounterfactualMode::new(storage.clone(), backend.clone(), config);
+        let debate_mode = DebateMode::new(storage.clone(), backend.clone(), config);
+        let rca_mode = RcaMode::new(storage.clone(), backend.clone(), config);
+        let planning_mode = PlanningMode::new(storage.clone(), backend.clone(), config);
+        let estimate_mode = EstimateMode::new(storage.clone(), backend.clone(), config);
+        let swot_mode = SwotMode::new(storage.clone(), backend.clone(), config);
+        let hypothesis_mode = HypothesisMode::new(storage.clone(), backend.clone(), config);
+        let six_hats_mode = SixHatsMode::new(storage.clone(), backend.clone(), config);
+        let dialectic_mode = DialecticMode::new(storage.clone(), backend.clone(), config);
+        let prioritization_mode = PrioritizationMode::new(storage.clone(), backend.clone(), config);
+        let negotiation_mode = NegotiationMode::new(storage.clone(), backend.clone(), config);
+        let risk_mode = RiskMode::new(storage.clone(), backend.clone(), config);
+        let systems_mode = SystemsMode::new(storage.clone(), backend.clone(), config);
+        let calc_mode = CalcMode::new(storage.clone(), backend.clone(), config);
+        let code_mode = CodeReasoningMode::new(storage.clone(), backend.clone(), config);
+        let synthesis_mode = SynthesisMode::new(storage.clone(), backend.clone(), config);
+
+        Self {
+            linear_mode,
+            tree_mode,
+            reflection_mode,
+            backtracking_mode,
+            divergent_mode,
+            auto_mode,
+            got_mode,
+            decision_mode,
+            evidence_mode,
+            detection_mode,
+            timeline_mode,
+            mcts_mode,
+            counterfactual_mode,
+            debate_mode,
+            rca_mode,
+            planning_mode,
+            estimate_mode,
+            swot_mode,
+            hypothesis_mode,
+            six_hats_mode,
+            dialectic_mode,
+            prioritization_mode,
+            negotiation_mode,
+            risk_mode,
+            systems_mode,
+            calc_mode,
+            code_mode,
+            synthesis_mode,
+        }
+    }
+}
+
 /// Shared application state handle
 pub type SharedState = Arc<AppState>;
 
@@ -176,9 +627,13 @@ pub type SharedState = Arc<AppState>;
 mod tests {
     use super::*;
     use crate::config::{
-        DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig, PipeConfig,
-        RequestConfig,
+        BudgetConfig, ConcurrencyConfig, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+        LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+        PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig, ProviderConfig,
+        RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig, TransportConfig,
+        DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
     };
+    use crate::storage::SqliteStorage;
     use std::path::PathBuf;
 
     fn create_test_config() -> Config {
@@ -186,10 +641,15 @@ mod tests {
             langbase: LangbaseConfig {
                 api_key: "test-key".to_string(),
                 base_url: "https://api.langbase.com".to_string(),
+                network: NetworkConfig::default(),
             },
             database: DatabaseConfig {
                 path: PathBuf::from(":memory:"),
                 max_connections: 5,
+                encryption_key: None,
+                maintenance: MaintenanceConfig::default(),
+                cache_capacity: DEFAULT_CACHE_CAPACITY,
+                workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
@@ -198,6 +658,18 @@ mod tests {
             request: RequestConfig::default(),
             pipes: PipeConfig::default(),
             error_handling: ErrorHandlingConfig::default(),
+            retention: RetentionConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            providers: ProviderConfig::default(),
+            pipe_cache: PipeCacheConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            circuit_breaker: PipeCircuitBreakerConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            payload_log: PayloadLogConfig::default(),
+            budget: BudgetConfig::default(),
+            transport: TransportConfig::default(),
+            tools: ToolsConfig::default(),
+            mcts: MctsConfig::default(),
         }
     }
 
@@ -258,8 +730,6 @@ mod tests {
 
     #[tokio::test]
     async fn test_app_state_storage_access() {
-        use crate::storage::Storage;
-
         let config = create_test_config();
         let storage = SqliteStorage::new_in_memory().await.unwrap();
         let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
@@ -273,6 +743,157 @@ mod tests {
         assert!(retrieved.is_some());
     }
 
+    #[tokio::test]
+    async fn test_app_state_accepts_shared_storage() {
+        let config = create_test_config();
+        let storage: SharedStorage = Arc::new(SqliteStorage::new_in_memory().await.unwrap());
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+
+        let state = AppState::new(config, Arc::clone(&storage), langbase);
+
+        // Modes and handlers share the caller's storage handle
+        let session = state
+            .storage
+            .get_or_create_session(&None, "linear")
+            .await
+            .unwrap();
+        assert!(storage.get_session(&session.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mutating_tool_calls_are_audited() {
+        use crate::storage::{AuditFilter, Session};
+
+        let config = create_test_config();
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state: SharedState =
+            Arc::new(AppState::new(config, storage, langbase).with_actor("mcp:test-client"));
+
+        let session = Session::new("linear");
+        state.storage.create_session(&session).await.unwrap();
+
+        handle_tool_call(
+            &state,
+            "reasoning_session_tag",
+            Some(serde_json::json!({"session_id": session.id, "add": ["audited"]})),
+        )
+        .await
+        .unwrap();
+        handle_tool_call(&state, "reasoning_session_list", None)
+            .await
+            .unwrap();
+
+        let entries = state
+            .storage
+            .list_audit_entries(AuditFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1, "read-only tools are not audited");
+        let entry = &entries[0];
+        assert_eq!(entry.actor, "mcp:test-client");
+        assert_eq!(entry.tool_name, "reasoning_session_tag");
+        assert_eq!(entry.session_id.as_deref(), Some(session.id.as_str()));
+        assert!(entry.success);
+        assert!(entry.before_hash.is_some());
+        assert!(entry.changed_state());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_summary_reports_maintenance() {
+        use crate::storage::run_maintenance;
+
+        let config = create_test_config();
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state: SharedState = Arc::new(AppState::new(config, storage, langbase));
+
+        let summary = handle_tool_call(&state, "reasoning_metrics_summary", None)
+            .await
+            .unwrap();
+        assert_eq!(summary["maintenance"]["enabled"], false);
+        assert!(summary["maintenance"]["last_run"].is_null());
+
+        let report = run_maintenance(state.storage.as_ref()).await.unwrap();
+        state.maintenance.record_report(report);
+
+        let summary = handle_tool_call(&state, "reasoning_metrics_summary", None)
+            .await
+            .unwrap();
+        let last_run = &summary["maintenance"]["last_run"];
+        assert_eq!(last_run["integrity_ok"], true);
+        assert_eq!(last_run["vacuumed"], true);
+        assert_eq!(last_run["analyzed"], true);
+        assert!(summary["maintenance"]["last_error"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_app_state_caches_session_reads() {
+        use crate::storage::Session;
+
+        let config = create_test_config();
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state: SharedState = Arc::new(AppState::new(config, storage, langbase));
+        let cache = state.cache.as_ref().expect("cache enabled by default");
+
+        let session = Session::new("linear");
+        state.storage.create_session(&session).await.unwrap();
+        state.storage.get_session(&session.id).await.unwrap();
+        state.storage.get_session(&session.id).await.unwrap();
+        assert_eq!(cache.stats().hits, 1);
+
+        let summary = handle_tool_call(&state, "reasoning_metrics_summary", None)
+            .await
+            .unwrap();
+        assert_eq!(summary["cache"]["enabled"], true);
+        assert_eq!(summary["cache"]["hits"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_app_state_isolates_workspaces() {
+        use crate::storage::Session;
+
+        let config = create_test_config();
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state: SharedState = Arc::new(AppState::new(config, storage, langbase));
+        assert_eq!(state.workspace.workspace_id(), "default");
+
+        let session = Session::new("linear");
+        state.storage.create_session(&session).await.unwrap();
+
+        let project_b: SharedState = Arc::new(state.with_workspace("project-b"));
+        assert_eq!(project_b.workspace.workspace_id(), "project-b");
+        let listed = handle_tool_call(&project_b, "reasoning_session_list", None)
+            .await
+            .unwrap();
+        assert_eq!(listed["count"], 0);
+        assert!(project_b
+            .storage
+            .get_session(&session.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        // The original state keeps its workspace
+        assert_eq!(state.workspace.workspace_id(), "default");
+        let listed = handle_tool_call(&state, "reasoning_session_list", None)
+            .await
+            .unwrap();
+        assert_eq!(listed["count"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_app_state_without_cache() {
+        let mut config = create_test_config();
+        config.database.cache_capacity = 0;
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = AppState::new(config, storage, langbase);
+        assert!(state.cache.is_none());
+    }
+
     #[tokio::test]
     async fn test_app_state_config_access() {
         let config = create_test_config();
@@ -0,0 +1,428 @@
+//! MCP prompt templates for common reasoning tasks.
+//!
+//! Each template turns a few arguments into a user message that walks the
+//! model through the tools or preset suited to the task, so clients can
+//! offer "analyze this decision" and the like as one-click actions.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::error::{McpError, McpResult};
+
+/// An argument a prompt template accepts.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptArgument {
+    /// Argument name.
+    pub name: String,
+    /// What the argument holds.
+    pub description: String,
+    /// Whether `prompts/get` fails without it.
+    pub required: bool,
+}
+
+/// A prompt template advertised by `prompts/list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Prompt {
+    /// Unique prompt name (used in `prompts/get`).
+    pub name: String,
+    /// What the prompt is for.
+    pub description: String,
+    /// Arguments the prompt accepts.
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// A message of a rendered prompt.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptMessage {
+    /// Message role ("user" or "assistant").
+    pub role: String,
+    /// Message content.
+    pub content: PromptContent,
+}
+
+/// Text content of a prompt message.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptContent {
+    /// Content type (always "text").
+    #[serde(rename = "type")]
+    pub content_type: String,
+    /// The message text.
+    pub text: String,
+}
+
+/// Result of `prompts/get`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptResult {
+    /// What the rendered prompt does.
+    pub description: String,
+    /// Messages to add to the conversation.
+    pub messages: Vec<PromptMessage>,
+}
+
+/// Arguments of a `prompts/get` request, by name.
+pub type PromptArguments = HashMap<String, String>;
+
+/// A built-in template: its definition and how to render it.
+struct Template {
+    name: &'static str,
+    description: &'static str,
+    arguments: &'static [(&'static str, &'static str, bool)],
+    render: fn(&PromptArguments) -> String,
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        name: "analyze_decision",
+        description: "Weigh the options of a decision against explicit criteria",
+        arguments: &[
+            ("question", "The decision to make", true),
+            (
+                "options",
+                "Options to choose between, one per line or comma-separated",
+                false,
+            ),
+            (
+                "criteria",
+                "Criteria to judge the options by, one per line or comma-separated",
+                false,
+            ),
+        ],
+        render: render_analyze_decision,
+    },
+    Template {
+        name: "premortem",
+        description: "Imagine a plan has failed and work out why, before committing to it",
+        arguments: &[
+            ("plan", "The plan to stress-test", true),
+            (
+                "horizon",
+                "When the failure is imagined, e.g. \"six months from now\"",
+                false,
+            ),
+        ],
+        render: render_premortem,
+    },
+    Template {
+        name: "verify_claim",
+        description: "Check a claim against evidence and for flawed reasoning",
+        arguments: &[
+            ("claim", "The claim to verify", true),
+            ("evidence", "Known evidence, one item per line", false),
+        ],
+        render: render_verify_claim,
+    },
+    Template {
+        name: "review_code",
+        description: "Review code with the code-review preset",
+        arguments: &[
+            ("code", "The code to review", true),
+            (
+                "focus",
+                "Review focus: quality, performance, or security",
+                false,
+            ),
+        ],
+        render: render_review_code,
+    },
+    Template {
+        name: "debug_issue",
+        description: "Investigate a bug with the debug-analysis preset",
+        arguments: &[
+            ("problem", "Description of the bug or issue", true),
+            (
+                "context",
+                "Relevant context such as recent changes or environment",
+                false,
+            ),
+        ],
+        render: render_debug_issue,
+    },
+];
+
+/// List the built-in prompt templates.
+pub fn list_prompts() -> Vec<Prompt> {
+    TEMPLATES
+        .iter()
+        .map(|t| Prompt {
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+            arguments: t
+                .arguments
+                .iter()
+                .map(|(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    required: *required,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Render the prompt `name` with `arguments`.
+pub fn get_prompt(name: &str, arguments: &PromptArguments) -> McpResult<PromptResult> {
+    info!(prompt = %name, "Rendering prompt");
+
+    let template =
+        TEMPLATES
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| McpError::InvalidRequest {
+                message: format!("Unknown prompt: {}", name),
+            })?;
+
+    for (arg, _, required) in template.arguments {
+        if *required && arguments.get(*arg).map_or(true, |v| v.trim().is_empty()) {
+            return Err(McpError::InvalidRequest {
+                message: format!("Prompt {} requires argument '{}'", name, arg),
+            });
+        }
+    }
+
+    Ok(PromptResult {
+        description: template.description.to_string(),
+        messages: vec![PromptMessage {
+            role: "user".to_string(),
+            content: PromptContent {
+                content_type: "text".to_string(),
+                text: (template.render)(arguments),
+            },
+        }],
+    })
+}
+
+/// A required argument, or an optional one that may be empty.
+fn arg<'a>(arguments: &'a PromptArguments, name: &str) -> &'a str {
+    arguments.get(name).map(|v| v.trim()).unwrap_or_default()
+}
+
+/// Split a list argument on newlines, or on commas if it is a single line.
+fn list(arguments: &PromptArguments, name: &str) -> Vec<String> {
+    let value = arg(arguments, name);
+    let separator = if value.contains('\n') { '\n' } else { ',' };
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn render_analyze_decision(arguments: &PromptArguments) -> String {
+    let question = arg(arguments, "question");
+    let options = list(arguments, "options");
+    let mut call = json!({ "question": question, "options": options });
+    let criteria = list(arguments, "criteria");
+    if !criteria.is_empty() {
+        call["criteria"] = criteria.iter().map(|c| json!({ "name": c })).collect();
+    }
+
+    let options_step = if options.is_empty() {
+        "1. List the realistic options, including doing nothing, and fill them into `options`."
+    } else {
+        "1. Check whether any realistic option is missing, and add it to `options`."
+    };
+    format!(
+        "Help me decide: {question}\n\n\
+         {options_step}\n\
+         2. Call `reasoning_make_decision` with:\n```json\n{call}\n```\n\
+         3. Call `reasoning_detect_biases` on the recommendation to check it for \
+         motivated or anchored reasoning.\n\
+         4. Summarize the recommendation, the trade-offs behind it, and what would \
+         change it.",
+        call = pretty(&call),
+    )
+}
+
+fn render_premortem(arguments: &PromptArguments) -> String {
+    let plan = arg(arguments, "plan");
+    let horizon = match arg(arguments, "horizon") {
+        "" => "some time from now",
+        horizon => horizon,
+    };
+    let call = json!({
+        "content": format!(
+            "It is {}. This plan was carried out and failed badly: {}. What caused the failure?",
+            horizon, plan
+        ),
+        "num_perspectives": 4,
+        "challenge_assumptions": true,
+    });
+    format!(
+        "Run a premortem on this plan: {plan}\n\n\
+         1. Call `reasoning_divergent` with:\n```json\n{call}\n```\n\
+         2. Call `reasoning_reflection` on the most likely causes of failure, in the \
+         same session, to separate real risks from speculation.\n\
+         3. List the top risks by likelihood and impact, each with an early warning \
+         sign and a mitigation to build into the plan.",
+        call = pretty(&call),
+    )
+}
+
+fn render_verify_claim(arguments: &PromptArguments) -> String {
+    let claim = arg(arguments, "claim");
+    let evidence: Vec<_> = arguments
+        .get("evidence")
+        .map(|e| e.lines().map(str::trim).filter(|l| !l.is_empty()).collect())
+        .unwrap_or_default();
+
+    let evidence_step = if evidence.is_empty() {
+        "1. Gather the evidence for and against the claim, noting each item's source."
+    } else {
+        "1. Add any evidence against the claim that is missing, noting each item's source."
+    };
+    let call = json!({
+        "claim": claim,
+        "evidence": evidence.iter().map(|e| json!({ "content": e })).collect::<Vec<_>>(),
+    });
+    format!(
+        "Verify this claim: {claim}\n\n\
+         {evidence_step}\n\
+         2. Call `reasoning_assess_evidence` with:\n```json\n{call}\n```\n\
+         3. Call `reasoning_detect_fallacies` on the argument for the claim.\n\
+         4. Give a verdict (supported, refuted, or undetermined) with its confidence, \
+         and say what evidence would settle it.",
+        call = pretty(&call),
+    )
+}
+
+fn render_review_code(arguments: &PromptArguments) -> String {
+    let mut inputs = json!({ "code": arg(arguments, "code") });
+    let focus = arg(arguments, "focus");
+    if !focus.is_empty() {
+        inputs["focus"] = json!(focus);
+    }
+    let call = json!({ "preset_id": "code-review", "inputs": inputs });
+    format!(
+        "Review this code.\n\n\
+         1. Call `reasoning_preset_run` with:\n```json\n{call}\n```\n\
+         2. Report the issues found, most severe first, each with a concrete fix.",
+        call = pretty(&call),
+    )
+}
+
+fn render_debug_issue(arguments: &PromptArguments) -> String {
+    let mut inputs = json!({ "problem": arg(arguments, "problem") });
+    let context = arg(arguments, "context");
+    if !context.is_empty() {
+        inputs["context"] = json!(context);
+    }
+    let call = json!({ "preset_id": "debug-analysis", "inputs": inputs });
+    format!(
+        "Help me debug this issue.\n\n\
+         1. Call `reasoning_preset_run` with:\n```json\n{call}\n```\n\
+         2. Rank the hypotheses by likelihood, and for each give the quickest \
+         experiment that would confirm or rule it out.",
+        call = pretty(&call),
+    )
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(pairs: &[(&str, &str)]) -> PromptArguments {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn text(result: &PromptResult) -> &str {
+        &result.messages[0].content.text
+    }
+
+    #[test]
+    fn test_list_prompts_serialization() {
+        let json = serde_json::to_value(list_prompts()).unwrap();
+        let names: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "analyze_decision",
+                "premortem",
+                "verify_claim",
+                "review_code",
+                "debug_issue"
+            ]
+        );
+        assert_eq!(json[0]["arguments"][0]["name"], "question");
+        assert_eq!(json[0]["arguments"][0]["required"], true);
+    }
+
+    #[test]
+    fn test_get_prompt_analyze_decision() {
+        let result = get_prompt(
+            "analyze_decision",
+            &args(&[
+                ("question", "Which database?"),
+                ("options", "Postgres, SQLite"),
+                ("criteria", "cost\nops burden"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].role, "user");
+        let text = text(&result);
+        assert!(text.contains("reasoning_make_decision"));
+        assert!(text.contains("\"Postgres\""));
+        assert!(text.contains("\"name\": \"ops burden\""));
+    }
+
+    #[test]
+    fn test_get_prompt_maps_to_presets() {
+        let review = get_prompt(
+            "review_code",
+            &args(&[("code", "fn main() {}"), ("focus", "security")]),
+        )
+        .unwrap();
+        assert!(text(&review).contains("\"preset_id\": \"code-review\""));
+        assert!(text(&review).contains("\"focus\": \"security\""));
+
+        let debug = get_prompt("debug_issue", &args(&[("problem", "Crash on start")])).unwrap();
+        assert!(text(&debug).contains("\"preset_id\": \"debug-analysis\""));
+    }
+
+    #[test]
+    fn test_get_prompt_verify_claim_and_premortem() {
+        let verify = get_prompt(
+            "verify_claim",
+            &args(&[("claim", "X causes Y"), ("evidence", "Study A\n\nStudy B")]),
+        )
+        .unwrap();
+        assert!(text(&verify).contains("reasoning_assess_evidence"));
+        assert!(text(&verify).contains("\"content\": \"Study B\""));
+
+        let premortem = get_prompt(
+            "premortem",
+            &args(&[("plan", "Launch in March"), ("horizon", "next year")]),
+        )
+        .unwrap();
+        assert!(text(&premortem).contains("It is next year."));
+        assert!(text(&premortem).contains("reasoning_divergent"));
+    }
+
+    #[test]
+    fn test_get_prompt_errors() {
+        assert!(matches!(
+            get_prompt("unknown", &args(&[])),
+            Err(McpError::InvalidRequest { .. })
+        ));
+        match get_prompt("premortem", &args(&[("plan", "  ")])) {
+            Err(McpError::InvalidRequest { message }) => assert!(message.contains("'plan'")),
+            other => panic!("expected missing argument error, got {:?}", other),
+        }
+    }
+}
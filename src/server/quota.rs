@@ -0,0 +1,314 @@
+//! Per-client request rate limits and daily tool-call quotas.
+//!
+//! Network clients share one server, so a single busy or misbehaving client
+//! could starve the others of pipe capacity. [`ClientQuotas`] counts each
+//! client's requests over the last minute and its tool calls for the day
+//! (UTC), and refuses requests beyond `CLIENT_REQUESTS_PER_MINUTE` and
+//! `CLIENT_DAILY_TOOL_CALLS`. Clients are told apart by API key, or by
+//! address when no keys are configured. Usage is kept in memory, so it starts
+//! from zero when the server restarts.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::TransportConfig;
+
+/// Window the per-minute request limit counts over.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+tokio::task_local! {
+    static QUOTA_CLIENT: Option<String>;
+}
+
+/// Run `future` as a call made by `client`, or by a stdio client if `None`.
+pub async fn with_quota_client<F: Future>(client: Option<String>, future: F) -> F::Output {
+    QUOTA_CLIENT.scope(client, future).await
+}
+
+/// The client the current task's call was made by, if it is a network client.
+pub(crate) fn current_quota_client() -> Option<String> {
+    QUOTA_CLIENT.try_with(Clone::clone).ok().flatten()
+}
+
+/// A request refused because its client used up a limit.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaExceeded {
+    /// The limit reached: `requests_per_minute` or `daily_tool_calls`.
+    pub limit: &'static str,
+    /// Requests or tool calls the limit allows.
+    pub allowed: u64,
+    /// Seconds until the client may try again.
+    pub retry_after_secs: u64,
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.limit {
+            "daily_tool_calls" => write!(f, "Daily quota of {} tool calls used up", self.allowed),
+            _ => write!(
+                f,
+                "Rate limit of {} requests per minute reached",
+                self.allowed
+            ),
+        }
+    }
+}
+
+/// A client's usage reported by `reasoning_quota_status`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QuotaStatus {
+    /// The client, or `None` for stdio clients, which are never limited.
+    pub client: Option<String>,
+    /// Whether any limit applies to the client.
+    pub enforced: bool,
+    /// Requests made in the last minute.
+    pub requests_last_minute: u64,
+    /// Requests allowed per minute, if limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Tool calls made today (UTC).
+    pub tool_calls_today: u64,
+    /// Tool calls allowed per day, if limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_tool_calls: Option<u64>,
+    /// Tool calls left today, if limited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls_remaining: Option<u64>,
+    /// When the daily quota resets.
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+struct ClientUsage {
+    recent: VecDeque<Instant>,
+    day: NaiveDate,
+    tool_calls: u64,
+}
+
+impl ClientUsage {
+    /// Forget requests outside the window and tool calls of earlier days
+    fn roll(&mut self, now: Instant, today: NaiveDate) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.day != today {
+            self.day = today;
+            self.tool_calls = 0;
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.recent.is_empty() && self.tool_calls == 0
+    }
+}
+
+/// Usage of every network client, checked against the configured limits.
+#[derive(Debug)]
+pub struct ClientQuotas {
+    requests_per_minute: Option<u32>,
+    daily_tool_calls: Option<u64>,
+    clients: Mutex<HashMap<String, ClientUsage>>,
+}
+
+impl ClientQuotas {
+    /// Create a tracker for the limits in `config`, with nothing used.
+    pub fn new(config: &TransportConfig) -> Self {
+        Self {
+            requests_per_minute: config.requests_per_minute,
+            daily_tool_calls: config.daily_tool_calls,
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether any limit is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_minute.is_some() || self.daily_tool_calls.is_some()
+    }
+
+    /// Count a request by `client`, or refuse it if a limit is used up.
+    ///
+    /// Refused requests are not counted.
+    pub fn check(&self, client: &str, tool_call: bool) -> Result<(), QuotaExceeded> {
+        self.check_at(Instant::now(), Utc::now().date_naive(), client, tool_call)
+    }
+
+    /// Usage and limits of `client`.
+    pub fn status(&self, client: Option<&str>) -> QuotaStatus {
+        self.status_at(Instant::now(), Utc::now().date_naive(), client)
+    }
+
+    fn check_at(
+        &self,
+        now: Instant,
+        today: NaiveDate,
+        client: &str,
+        tool_call: bool,
+    ) -> Result<(), QuotaExceeded> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if !clients.contains_key(client) {
+            // Drop clients with nothing left to count before adding one
+            clients.retain(|_, usage| {
+                usage.roll(now, today);
+                !usage.is_idle()
+            });
+        }
+        let usage = clients
+            .entry(client.to_string())
+            .or_insert_with(|| ClientUsage {
+                recent: VecDeque::new(),
+                day: today,
+                tool_calls: 0,
+            });
+        usage.roll(now, today);
+
+        if let Some(limit) = self.requests_per_minute {
+            if usage.recent.len() >= limit as usize {
+                let oldest = usage.recent.front().copied().unwrap_or(now);
+                let retry_after = RATE_WINDOW.saturating_sub(now.duration_since(oldest));
+                return Err(QuotaExceeded {
+                    limit: "requests_per_minute",
+                    allowed: limit as u64,
+                    retry_after_secs: retry_after.as_secs().max(1),
+                });
+            }
+        }
+        if let Some(limit) = self.daily_tool_calls.filter(|_| tool_call) {
+            if usage.tool_calls >= limit {
+                return Err(QuotaExceeded {
+                    limit: "daily_tool_calls",
+                    allowed: limit,
+                    retry_after_secs: (next_midnight(today) - Utc::now()).num_seconds().max(1)
+                        as u64,
+                });
+            }
+        }
+
+        usage.recent.push_back(now);
+        if tool_call {
+            usage.tool_calls += 1;
+        }
+        Ok(())
+    }
+
+    fn status_at(&self, now: Instant, today: NaiveDate, client: Option<&str>) -> QuotaStatus {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let usage = client.and_then(|client| clients.get_mut(client));
+        let (requests, tool_calls) = match usage {
+            Some(usage) => {
+                usage.roll(now, today);
+                (usage.recent.len() as u64, usage.tool_calls)
+            }
+            None => (0, 0),
+        };
+
+        QuotaStatus {
+            client: client.map(str::to_string),
+            enforced: client.is_some() && self.is_enabled(),
+            requests_last_minute: requests,
+            requests_per_minute: self.requests_per_minute,
+            tool_calls_today: tool_calls,
+            daily_tool_calls: self.daily_tool_calls,
+            tool_calls_remaining: self
+                .daily_tool_calls
+                .map(|limit| limit.saturating_sub(tool_calls)),
+            resets_at: next_midnight(today),
+        }
+    }
+}
+
+/// Start of the day after `today`, in UTC
+fn next_midnight(today: NaiveDate) -> DateTime<Utc> {
+    today
+        .succ_opt()
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotas(requests_per_minute: Option<u32>, daily_tool_calls: Option<u64>) -> ClientQuotas {
+        ClientQuotas::new(&TransportConfig {
+            requests_per_minute,
+            daily_tool_calls,
+            ..Default::default()
+        })
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_rate_limit_per_client_and_window() {
+        let quotas = quotas(Some(2), None);
+        let start = Instant::now();
+
+        assert!(quotas.check_at(start, day(1), "a", false).is_ok());
+        assert!(quotas.check_at(start, day(1), "a", true).is_ok());
+        let err = quotas.check_at(start, day(1), "a", false).unwrap_err();
+        assert_eq!(err.limit, "requests_per_minute");
+        assert_eq!(err.allowed, 2);
+        assert_eq!(err.retry_after_secs, 60);
+        assert!(quotas.check_at(start, day(1), "b", false).is_ok());
+
+        let later = start + Duration::from_secs(61);
+        assert!(quotas.check_at(later, day(1), "a", false).is_ok());
+    }
+
+    #[test]
+    fn test_daily_tool_calls_reset_next_day() {
+        let quotas = quotas(None, Some(1));
+        let now = Instant::now();
+
+        assert!(quotas.check_at(now, day(1), "a", true).is_ok());
+        // Other requests do not use the tool-call quota
+        assert!(quotas.check_at(now, day(1), "a", false).is_ok());
+        let err = quotas.check_at(now, day(1), "a", true).unwrap_err();
+        assert_eq!(err.limit, "daily_tool_calls");
+        assert!(err.to_string().contains("1 tool calls"));
+
+        assert!(quotas.check_at(now, day(2), "a", true).is_ok());
+    }
+
+    #[test]
+    fn test_status_reports_usage() {
+        let quotas = quotas(Some(10), Some(5));
+        let now = Instant::now();
+        quotas.check_at(now, day(1), "a", true).unwrap();
+        quotas.check_at(now, day(1), "a", false).unwrap();
+
+        let status = quotas.status_at(now, day(1), Some("a"));
+        assert!(status.enforced);
+        assert_eq!(status.requests_last_minute, 2);
+        assert_eq!(status.tool_calls_today, 1);
+        assert_eq!(status.tool_calls_remaining, Some(4));
+        assert_eq!(status.resets_at, next_midnight(day(1)));
+
+        let status = quotas.status_at(now, day(1), None);
+        assert!(!status.enforced);
+        assert_eq!(status.requests_last_minute, 0);
+    }
+
+    #[test]
+    fn test_idle_clients_are_dropped() {
+        let quotas = quotas(Some(10), None);
+        let start = Instant::now();
+        quotas.check_at(start, day(1), "a", false).unwrap();
+
+        let later = start + Duration::from_secs(61);
+        quotas.check_at(later, day(1), "b", false).unwrap();
+        assert_eq!(quotas.clients.lock().unwrap().len(), 1);
+    }
+}
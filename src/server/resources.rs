@@ -0,0 +1,300 @@
+//! MCP resources exposing reasoning sessions.
+//!
+//! Every session in the current workspace is a resource at
+//! `reasoning://session/{id}`. Reading one renders its thoughts, branches and
+//! graph as Markdown, so clients can pull a session into context without
+//! calling the session tools one by one.
+
+use serde::Serialize;
+use std::fmt::Write;
+use tracing::info;
+
+use super::SharedState;
+use crate::error::{McpError, McpResult};
+use crate::storage::{Session, SessionFilter};
+
+/// URI prefix of session resources.
+pub const SESSION_URI_PREFIX: &str = "reasoning://session/";
+
+/// Number of most recently updated sessions listed by `resources/list`.
+pub const RESOURCE_LIST_LIMIT: u32 = 50;
+
+/// Content longer than this many characters is cut in rendered lists.
+const MAX_ITEM_CHARS: usize = 500;
+
+/// A resource advertised by `resources/list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Resource {
+    /// Resource URI.
+    pub uri: String,
+    /// Human-readable name.
+    pub name: String,
+    /// What the resource holds.
+    pub description: String,
+    /// MIME type of the resource's contents.
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+/// A URI template advertised by `resources/templates/list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceTemplate {
+    /// RFC 6570 URI template.
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    /// Human-readable name.
+    pub name: String,
+    /// What resources matching the template hold.
+    pub description: String,
+    /// MIME type of matching resources' contents.
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+/// Text contents returned by `resources/read`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceContents {
+    /// URI of the resource read.
+    pub uri: String,
+    /// MIME type of `text`.
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// The rendered resource.
+    pub text: String,
+}
+
+/// Build the resource URI of a session.
+pub fn session_uri(session_id: &str) -> String {
+    format!("{}{}", SESSION_URI_PREFIX, session_id)
+}
+
+/// The session ID a session resource URI names, if it names one.
+pub fn session_id_from_uri(uri: &str) -> Option<&str> {
+    uri.strip_prefix(SESSION_URI_PREFIX)
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+}
+
+/// Templates for the resources this server exposes.
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    vec![ResourceTemplate {
+        uri_template: format!("{}{{id}}", SESSION_URI_PREFIX),
+        name: "Reasoning session".to_string(),
+        description: "Thoughts, branches and graph of a reasoning session".to_string(),
+        mime_type: "text/markdown".to_string(),
+    }]
+}
+
+/// List the most recently updated sessions in the current workspace.
+pub async fn list_resources(state: &SharedState) -> McpResult<Vec<Resource>> {
+    info!("Listing session resources");
+
+    let sessions = state
+        .storage
+        .list_sessions(SessionFilter::new().with_limit(RESOURCE_LIST_LIMIT))
+        .await
+        .map_err(|e| McpError::ExecutionFailed {
+            message: format!("Failed to list sessions: {}", e),
+        })?;
+
+    Ok(sessions.iter().map(session_resource).collect())
+}
+
+/// Read the resource at `uri`.
+pub async fn read_resource(state: &SharedState, uri: &str) -> McpResult<ResourceContents> {
+    info!(uri = %uri, "Reading resource");
+
+    let session_id = session_id_from_uri(uri).ok_or_else(|| McpError::ResourceNotFound {
+        uri: uri.to_string(),
+    })?;
+
+    let to_error = |e: crate::error::StorageError| McpError::ExecutionFailed {
+        message: format!("Failed to read session: {}", e),
+    };
+    let session = state
+        .storage
+        .get_session(session_id)
+        .await
+        .map_err(to_error)?
+        .ok_or_else(|| McpError::ResourceNotFound {
+            uri: uri.to_string(),
+        })?;
+    let thoughts = state
+        .storage
+        .get_session_thoughts(session_id)
+        .await
+        .map_err(to_error)?;
+    let branches = state
+        .storage
+        .get_session_branches(session_id)
+        .await
+        .map_err(to_error)?;
+    let nodes = state
+        .storage
+        .get_session_graph_nodes(session_id)
+        .await
+        .map_err(to_error)?;
+    let edges = state
+        .storage
+        .get_session_edges(session_id)
+        .await
+        .map_err(to_error)?;
+
+    let mut text = String::new();
+    // Writing to a String cannot fail
+    let _ = writeln!(text, "# Session {}", session.id);
+    let _ = writeln!(text);
+    let _ = writeln!(text, "- Mode: {}", session.mode);
+    let _ = writeln!(text, "- Created: {}", session.created_at.to_rfc3339());
+    let _ = writeln!(text, "- Updated: {}", session.updated_at.to_rfc3339());
+    if !session.tags.is_empty() {
+        let _ = writeln!(text, "- Tags: {}", session.tags.join(", "));
+    }
+    if let Some(parent) = &session.forked_from {
+        let _ = writeln!(text, "- Forked from: {}", parent);
+    }
+    if let Some(deleted_at) = session.deleted_at {
+        let _ = writeln!(text, "- In trash since: {}", deleted_at.to_rfc3339());
+    }
+
+    if !thoughts.is_empty() {
+        let _ = writeln!(text, "\n## Thoughts ({})\n", thoughts.len());
+        for (i, thought) in thoughts.iter().enumerate() {
+            let branch = thought
+                .branch_id
+                .as_deref()
+                .map(|b| format!(", branch {}", b))
+                .unwrap_or_default();
+            let _ = writeln!(
+                text,
+                "{}. [{}, confidence {:.2}{}] {}",
+                i + 1,
+                thought.mode,
+                thought.confidence,
+                branch,
+                truncate(&thought.content)
+            );
+        }
+    }
+
+    if !branches.is_empty() {
+        let _ = writeln!(text, "\n## Branches ({})\n", branches.len());
+        for branch in &branches {
+            let active = if session.active_branch_id.as_deref() == Some(branch.id.as_str()) {
+                " (active)"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                text,
+                "- {}{}: {}, confidence {:.2}",
+                branch.name.as_deref().unwrap_or(&branch.id),
+                active,
+                branch.state,
+                branch.confidence
+            );
+        }
+    }
+
+    if !nodes.is_empty() {
+        let _ = writeln!(
+            text,
+            "\n## Graph ({} nodes, {} edges)\n",
+            nodes.len(),
+            edges.len()
+        );
+        for node in &nodes {
+            let mut flags = Vec::new();
+            if let Some(score) = node.score {
+                flags.push(format!("score {:.2}", score));
+            }
+            if node.is_terminal {
+                flags.push("terminal".to_string());
+            }
+            if !node.is_active {
+                flags.push("pruned".to_string());
+            }
+            let flags = if flags.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", flags.join(", "))
+            };
+            let _ = writeln!(
+                text,
+                "{}- [{}]{} {}",
+                "  ".repeat(node.depth.max(0) as usize),
+                node.node_type,
+                flags,
+                truncate(&node.content)
+            );
+        }
+    }
+
+    Ok(ResourceContents {
+        uri: uri.to_string(),
+        mime_type: "text/markdown".to_string(),
+        text,
+    })
+}
+
+/// Describe a session as a resource.
+fn session_resource(session: &Session) -> Resource {
+    let mut description = format!(
+        "{} session, updated {}",
+        session.mode,
+        session.updated_at.to_rfc3339()
+    );
+    if !session.tags.is_empty() {
+        description.push_str(&format!(" [{}]", session.tags.join(", ")));
+    }
+    Resource {
+        uri: session_uri(&session.id),
+        name: format!("{} session {}", session.mode, session.id),
+        description,
+        mime_type: "text/markdown".to_string(),
+    }
+}
+
+/// Put `content` on one line, cut to [`MAX_ITEM_CHARS`] characters.
+fn truncate(content: &str) -> String {
+    let line = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(MAX_ITEM_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_uri_roundtrip() {
+        let uri = session_uri("abc-123");
+        assert_eq!(uri, "reasoning://session/abc-123");
+        assert_eq!(uri.strip_prefix(SESSION_URI_PREFIX), Some("abc-123"));
+        assert_eq!(session_id_from_uri(&uri), Some("abc-123"));
+    }
+
+    #[test]
+    fn test_session_id_from_other_uris() {
+        assert_eq!(session_id_from_uri("reasoning://session/"), None);
+        assert_eq!(session_id_from_uri("reasoning://session/a/b"), None);
+        assert_eq!(session_id_from_uri("file:///etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_resource_templates_serialization() {
+        let json = serde_json::to_value(resource_templates()).unwrap();
+        assert_eq!(json[0]["uriTemplate"], "reasoning://session/{id}");
+        assert_eq!(json[0]["mimeType"], "text/markdown");
+    }
+
+    #[test]
+    fn test_truncate_flattens_and_cuts() {
+        assert_eq!(truncate("a\n  b\tc"), "a b c");
+        let long = "é".repeat(MAX_ITEM_CHARS + 10);
+        let cut = truncate(&long);
+        assert_eq!(cut.chars().count(), MAX_ITEM_CHARS + 1);
+        assert!(cut.ends_with('…'));
+    }
+}
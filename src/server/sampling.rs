@@ -0,0 +1,244 @@
+//! Requests from the server to its MCP client.
+//!
+//! [`ClientSampler`] sends `sampling/createMessage` requests over a
+//! connection's outgoing channel and resolves them when the client's
+//! responses are read back in by the server loop.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, warn};
+
+use super::mcp::{JsonRpcNotification, JsonRpcServerRequest, ServerMessage};
+use crate::error::{LangbaseError, LangbaseResult};
+use crate::providers::Sampler;
+
+/// Outcome of a request to the client: its result, or its error message.
+type ClientOutcome = Result<Value, String>;
+
+/// A JSON-RPC response sent by the client to one of the server's requests.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ClientResponse {
+    /// ID of the server request answered.
+    pub id: Value,
+    /// The response's result, or its error message.
+    pub outcome: ClientOutcome,
+}
+
+impl ClientResponse {
+    /// Parse `message` if it is a response rather than a request or
+    /// notification.
+    pub fn parse(message: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(message.trim()).ok()?;
+        let object = value.as_object()?;
+        if object.contains_key("method") {
+            return None;
+        }
+        let id = object.get("id")?.clone();
+        let outcome = match (object.get("result"), object.get("error")) {
+            (_, Some(error)) => Err(error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+                .to_string()),
+            (Some(result), None) => Ok(result.clone()),
+            (None, None) => return None,
+        };
+        Some(Self { id, outcome })
+    }
+}
+
+/// Sampler sending `sampling/createMessage` to the client of one connection.
+pub(crate) struct ClientSampler {
+    outgoing: mpsc::UnboundedSender<ServerMessage>,
+    /// Whether the client declared the sampling capability.
+    supported: AtomicBool,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<String, oneshot::Sender<ClientOutcome>>>,
+}
+
+impl ClientSampler {
+    /// Create a sampler writing its requests to `outgoing`.
+    pub fn new(outgoing: mpsc::UnboundedSender<ServerMessage>) -> Self {
+        Self {
+            outgoing,
+            supported: AtomicBool::new(false),
+            next_id: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record whether the client supports sampling, from its `initialize`.
+    pub fn set_supported(&self, supported: bool) {
+        self.supported.store(supported, Ordering::Relaxed);
+    }
+
+    /// Resolve the request `response` answers.
+    pub fn deliver(&self, response: ClientResponse) {
+        let waiter = response
+            .id
+            .as_str()
+            .and_then(|id| self.pending.lock().ok()?.remove(id));
+        match waiter {
+            Some(waiter) => {
+                // The caller may have given up already
+                let _ = waiter.send(response.outcome);
+            }
+            None => debug!(id = %response.id, "Ignoring response to unknown request"),
+        }
+    }
+
+    /// Fail every pending request, e.g. once the client has disconnected.
+    pub fn close(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+    }
+}
+
+/// Removes a request from the pending map when its caller stops waiting,
+/// telling the client the request is cancelled if it is still unanswered
+struct PendingGuard<'a> {
+    sampler: &'a ClientSampler,
+    id: String,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        let unanswered = self
+            .sampler
+            .pending
+            .lock()
+            .map(|mut pending| pending.remove(&self.id).is_some())
+            .unwrap_or(false);
+        if unanswered {
+            let _ = self.sampler.outgoing.send(ServerMessage::Notification(
+                JsonRpcNotification::cancelled(&Value::from(self.id.as_str())),
+            ));
+        }
+    }
+}
+
+#[async_trait]
+impl Sampler for ClientSampler {
+    async fn create_message(&self, params: Value) -> LangbaseResult<Value> {
+        let unavailable = |message: String| LangbaseError::Unavailable {
+            message,
+            retries: 0,
+        };
+        if !self.supported.load(Ordering::Relaxed) {
+            return Err(unavailable(
+                "MCP client did not declare sampling support".to_string(),
+            ));
+        }
+
+        let id = format!("sampling-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(id.clone(), tx);
+        }
+        let _guard = PendingGuard {
+            sampler: self,
+            id: id.clone(),
+        };
+
+        let request = JsonRpcServerRequest::new(&id, "sampling/createMessage", params);
+        if self.outgoing.send(ServerMessage::Request(request)).is_err() {
+            return Err(unavailable("MCP client disconnected".to_string()));
+        }
+
+        match rx.await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(message)) => {
+                warn!(id = %id, error = %message, "MCP client rejected sampling request");
+                Err(unavailable(format!(
+                    "MCP client rejected sampling request: {}",
+                    message
+                )))
+            }
+            Err(_) => Err(unavailable("MCP client disconnected".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_response_parse() {
+        let response =
+            ClientResponse::parse(r#"{"jsonrpc":"2.0","id":"sampling-1","result":{"a":1}}"#)
+                .unwrap();
+        assert_eq!(response.id, json!("sampling-1"));
+        assert_eq!(response.outcome, Ok(json!({"a": 1})));
+
+        let response = ClientResponse::parse(
+            r#"{"jsonrpc":"2.0","id":"sampling-2","error":{"code":-1,"message":"User rejected"}}"#,
+        )
+        .unwrap();
+        assert_eq!(response.outcome, Err("User rejected".to_string()));
+
+        assert!(ClientResponse::parse(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#).is_none());
+        assert!(ClientResponse::parse(r#"{"jsonrpc":"2.0","id":1}"#).is_none());
+        assert!(ClientResponse::parse("not json").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_sampler_round_trip() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sampler = ClientSampler::new(tx);
+        sampler.set_supported(true);
+
+        let call = sampler.create_message(json!({"maxTokens": 10}));
+        tokio::pin!(call);
+        let request = tokio::select! {
+            _ = &mut call => panic!("call finished before the client answered"),
+            Some(message) = rx.recv() => message,
+        };
+        let request = serde_json::to_value(&request).unwrap();
+        assert_eq!(request["method"], "sampling/createMessage");
+        assert_eq!(request["params"]["maxTokens"], 10);
+
+        sampler.deliver(ClientResponse {
+            id: request["id"].clone(),
+            outcome: Ok(json!({"content": {"type": "text", "text": "hi"}})),
+        });
+        let result = call.await.unwrap();
+        assert_eq!(result["content"]["text"], "hi");
+        assert!(sampler.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_client_sampler_requires_capability() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let sampler = ClientSampler::new(tx);
+        let err = sampler.create_message(json!({})).await.unwrap_err();
+        assert!(matches!(err, LangbaseError::Unavailable { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_client_sampler_abandoned_request_is_cancelled() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sampler = ClientSampler::new(tx);
+        sampler.set_supported(true);
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            sampler.create_message(json!({})),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(sampler.pending.lock().unwrap().is_empty());
+
+        let _request = rx.recv().await.unwrap();
+        let cancelled = serde_json::to_value(rx.recv().await.unwrap()).unwrap();
+        assert_eq!(cancelled["method"], "notifications/cancelled");
+        assert_eq!(cancelled["params"]["requestId"], "sampling-1");
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-session serialization of mutating tool calls.
+//!
+//! Every client of a server shares its state, so two WebSocket clients (or
+//! one client pipelining requests) can change the same session at once, e.g.
+//! pruning a graph while another call adds nodes to it. Mutating calls hold
+//! their session's lock while they run, so their reads and writes never
+//! interleave. A call that cannot get the lock in time fails with a conflict
+//! instead of overwriting the other call's work.
+//!
+//! The lock is re-entrant within a task: calls made on behalf of a call that
+//! holds it, such as the steps of a preset run, go ahead without waiting.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::OwnedMutexGuard;
+use tracing::debug;
+
+tokio::task_local! {
+    /// Sessions whose locks the current task's calls hold.
+    static HELD_SESSIONS: Vec<String>;
+}
+
+/// Run `future` as the holder of `session_id`'s lock, so the calls it makes
+/// on that session do not wait for it.
+pub async fn with_held_session<F: Future>(session_id: String, future: F) -> F::Output {
+    let mut held = HELD_SESSIONS.try_with(Clone::clone).unwrap_or_default();
+    held.push(session_id);
+    HELD_SESSIONS.scope(held, future).await
+}
+
+/// Whether the current task already holds `session_id`'s lock.
+pub fn holds_session(session_id: &str) -> bool {
+    HELD_SESSIONS
+        .try_with(|held| held.iter().any(|id| id == session_id))
+        .unwrap_or(false)
+}
+
+/// Lock held on a session until dropped.
+pub type SessionGuard = OwnedMutexGuard<()>;
+
+/// Locks of the sessions with calls running or waiting.
+#[derive(Debug, Default)]
+pub struct SessionLocks {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SessionLocks {
+    /// Create an empty lock table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock `session_id`, waiting at most `wait` for calls holding it.
+    ///
+    /// A zero `wait` only takes the lock if it is free. Returns `None` if
+    /// the session stayed locked.
+    pub async fn lock(&self, session_id: &str, wait: Duration) -> Option<SessionGuard> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            // Drop the locks no call holds or waits for
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(session_id.to_string()).or_default().clone()
+        };
+
+        if let Ok(guard) = lock.clone().try_lock_owned() {
+            return Some(guard);
+        }
+        debug!(session_id = %session_id, "Session busy, waiting for its lock");
+        tokio::time::timeout(wait, lock.lock_owned()).await.ok()
+    }
+
+    /// Number of sessions with calls holding or waiting for their lock.
+    pub fn active(&self) -> usize {
+        self.locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .filter(|lock| Arc::strong_count(lock) > 1)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lock_serializes_one_session() {
+        let locks = SessionLocks::new();
+        let guard = locks.lock("s1", Duration::ZERO).await.unwrap();
+
+        assert!(locks.lock("s1", Duration::from_millis(10)).await.is_none());
+        assert!(locks.lock("s2", Duration::ZERO).await.is_some());
+
+        drop(guard);
+        assert!(locks.lock("s1", Duration::ZERO).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_waiter_gets_lock_when_released() {
+        let locks = Arc::new(SessionLocks::new());
+        let guard = locks.lock("s1", Duration::ZERO).await.unwrap();
+
+        let waiter = {
+            let locks = locks.clone();
+            tokio::spawn(async move { locks.lock("s1", Duration::from_secs(5)).await.is_some() })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(guard);
+
+        assert!(waiter.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_held_sessions_nest() {
+        assert!(!holds_session("s1"));
+        with_held_session("s1".to_string(), async {
+            assert!(holds_session("s1"));
+            with_held_session("s2".to_string(), async {
+                assert!(holds_session("s1"));
+                assert!(holds_session("s2"));
+            })
+            .await;
+            assert!(!holds_session("s2"));
+        })
+        .await;
+        assert!(!holds_session("s1"));
+    }
+
+    #[tokio::test]
+    async fn test_released_locks_are_dropped() {
+        let locks = SessionLocks::new();
+        let guard = locks.lock("s1", Duration::ZERO).await.unwrap();
+        assert_eq!(locks.active(), 1);
+
+        drop(guard);
+        assert_eq!(locks.active(), 0);
+        let _guard = locks.lock("s2", Duration::ZERO).await.unwrap();
+        assert_eq!(locks.locks.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+//! Change events of sessions, behind `resources/subscribe`.
+//!
+//! Every successful mutating tool call publishes a [`SessionEvent`] naming
+//! the session it changed, whether a client, a preset step or another
+//! workflow made it. Each connection listens for these events and sends
+//! `notifications/resources/updated` for the session resources its client
+//! subscribed to, so a client watching a session learns of thoughts,
+//! detections and checkpoints added by others.
+
+use tokio::sync::broadcast;
+
+/// Events kept for connections that fall behind; those that miss events are
+/// told every subscribed session changed.
+pub const SESSION_EVENT_CAPACITY: usize = 256;
+
+/// A session changed by a tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEvent {
+    /// The session changed.
+    pub session_id: String,
+    /// The tool that changed it.
+    pub tool: String,
+}
+
+/// Broadcasts session changes to every connection.
+#[derive(Debug, Clone)]
+pub struct SessionEvents {
+    sender: broadcast::Sender<SessionEvent>,
+}
+
+impl Default for SessionEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionEvents {
+    /// Create a channel with no listeners.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SESSION_EVENT_CAPACITY);
+        Self { sender }
+    }
+
+    /// Tell every listener `tool` changed `session_id`.
+    pub fn publish(&self, session_id: &str, tool: &str) {
+        // Sending only fails when nobody listens
+        let _ = self.sender.send(SessionEvent {
+            session_id: session_id.to_string(),
+            tool: tool.to_string(),
+        });
+    }
+
+    /// Listen for the changes published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_listeners_receive_published_events() {
+        let events = SessionEvents::new();
+        // Publishing without listeners is fine
+        events.publish("s0", "reasoning_linear");
+
+        let mut first = events.subscribe();
+        let mut second = events.subscribe();
+        events.publish("s1", "reasoning_detect_biases");
+
+        let expected = SessionEvent {
+            session_id: "s1".to_string(),
+            tool: "reasoning_detect_biases".to_string(),
+        };
+        assert_eq!(first.recv().await.unwrap(), expected);
+        assert_eq!(second.recv().await.unwrap(), expected);
+        assert!(first.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_slow_listener_learns_it_lagged() {
+        let events = SessionEvents::new();
+        let mut listener = events.subscribe();
+        for i in 0..SESSION_EVENT_CAPACITY + 1 {
+            events.publish(&format!("s{}", i), "reasoning_linear");
+        }
+
+        assert!(matches!(
+            listener.recv().await,
+            Err(broadcast::error::RecvError::Lagged(1))
+        ));
+    }
+}
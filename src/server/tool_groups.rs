@@ -0,0 +1,257 @@
+//! Tool groups and the configured tool surface.
+//!
+//! Every tool belongs to one group. `TOOLS_ENABLED` and `TOOLS_DISABLED`
+//! select tools by group or by name, so clients that struggle with a long
+//! `tools/list` can be given only the reasoning modes they use.
+
+use crate::config::ToolsConfig;
+
+/// Tool groups and the tools in each.
+pub const TOOL_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "core",
+        &[
+            "reasoning_linear",
+            "reasoning_tree",
+            "reasoning_tree_focus",
+            "reasoning_tree_list",
+            "reasoning_tree_complete",
+            "reasoning_tree_compare",
+            "reasoning_tree_merge",
+            "reasoning_tree_export",
+            "reasoning_divergent",
+            "reasoning_divergent_evaluate",
+            "reasoning_reflection",
+            "reasoning_reflection_evaluate",
+            "reasoning_auto",
+            "reasoning_routing_feedback",
+        ],
+    ),
+    (
+        "backtracking",
+        &[
+            "reasoning_backtrack",
+            "reasoning_checkpoint_create",
+            "reasoning_checkpoint_list",
+            "reasoning_backtrack_diff",
+            "reasoning_auto_backtrack",
+        ],
+    ),
+    (
+        "got",
+        &[
+            "reasoning_got_init",
+            "reasoning_got_generate",
+            "reasoning_got_score",
+            "reasoning_got_rank",
+            "reasoning_got_aggregate",
+            "reasoning_got_aggregate_best",
+            "reasoning_got_refine",
+            "reasoning_got_prune",
+            "reasoning_got_finalize",
+            "reasoning_got_state",
+            "reasoning_got_export",
+            "reasoning_got_validate",
+            "reasoning_got_best_path",
+            "reasoning_got_resume",
+        ],
+    ),
+    (
+        "detection",
+        &[
+            "reasoning_detect_biases",
+            "reasoning_detect_fallacies",
+            "reasoning_verify_calculations",
+        ],
+    ),
+    (
+        "presets",
+        &["reasoning_preset_list", "reasoning_preset_run"],
+    ),
+    (
+        "decision",
+        &["reasoning_make_decision", "reasoning_analyze_perspectives"],
+    ),
+    (
+        "evidence",
+        &["reasoning_assess_evidence", "reasoning_probabilistic"],
+    ),
+    (
+        "metrics",
+        &[
+            "reasoning_metrics_summary",
+            "reasoning_metrics_by_pipe",
+            "reasoning_metrics_invocations",
+            "reasoning_fallback_metrics",
+        ],
+    ),
+    (
+        "debug",
+        &[
+            "reasoning_debug_config",
+            "reasoning_pipe_status",
+            "reasoning_pipe_health",
+            "reasoning_budget_status",
+            "reasoning_quota_status",
+            "reasoning_server_status",
+        ],
+    ),
+    (
+        "search",
+        &[
+            "reasoning_search",
+            "reasoning_similar",
+            "reasoning_session_thoughts",
+        ],
+    ),
+    (
+        "sessions",
+        &[
+            "reasoning_revise",
+            "reasoning_thought_history",
+            "reasoning_session_tag",
+            "reasoning_session_list",
+            "reasoning_session_delete",
+            "reasoning_session_restore",
+            "reasoning_session_fork",
+        ],
+    ),
+    (
+        "knowledge",
+        &["reasoning_knowledge_query", "reasoning_knowledge_promote"],
+    ),
+    (
+        "timeline",
+        &[
+            "reasoning_timeline_create",
+            "reasoning_timeline_branch",
+            "reasoning_timeline_compare",
+            "reasoning_timeline_merge",
+        ],
+    ),
+    ("mcts", &["reasoning_mcts_explore"]),
+    (
+        "counterfactual",
+        &[
+            "reasoning_counterfactual",
+            "reasoning_counterfactual_minimal_change",
+        ],
+    ),
+    ("debate", &["reasoning_debate"]),
+    ("root_cause", &["reasoning_root_cause"]),
+    ("planning", &["reasoning_plan", "reasoning_plan_revise"]),
+    ("estimation", &["reasoning_estimate"]),
+    ("swot", &["reasoning_swot", "reasoning_swot_list"]),
+    (
+        "hypothesis",
+        &["reasoning_hypothesis", "reasoning_hypothesis_observe"],
+    ),
+    ("six_hats", &["reasoning_six_hats"]),
+    ("dialectic", &["reasoning_dialectic"]),
+    (
+        "prioritization",
+        &["reasoning_prioritize", "reasoning_prioritize_revise"],
+    ),
+    ("negotiation", &["reasoning_negotiate"]),
+    ("risk", &["reasoning_risk", "reasoning_risk_update"]),
+    ("systems", &["reasoning_systems"]),
+    (
+        "code",
+        &[
+            "reasoning_code_explain_diff",
+            "reasoning_code_trace_flow",
+            "reasoning_code_edge_cases",
+            "reasoning_code_findings",
+        ],
+    ),
+    ("synthesis", &["reasoning_synthesize"]),
+];
+
+/// The group `tool_name` belongs to, if it is a known tool.
+pub fn tool_group(tool_name: &str) -> Option<&'static str> {
+    TOOL_GROUPS
+        .iter()
+        .find(|(_, tools)| tools.contains(&tool_name))
+        .map(|(group, _)| *group)
+}
+
+/// Whether `config` exposes `tool_name`.
+///
+/// A tool is exposed when `enabled` is empty or names it or its group, and
+/// `disabled` names neither.
+pub fn is_tool_enabled(config: &ToolsConfig, tool_name: &str) -> bool {
+    let group = tool_group(tool_name);
+    let selects = |entry: &String| entry == tool_name || Some(entry.as_str()) == group;
+    (config.enabled.is_empty() || config.enabled.iter().any(selects))
+        && !config.disabled.iter().any(selects)
+}
+
+/// Entries of `config` that name neither a group nor a tool.
+pub fn unknown_tool_entries(config: &ToolsConfig) -> Vec<&str> {
+    config
+        .enabled
+        .iter()
+        .chain(&config.disabled)
+        .map(String::as_str)
+        .filter(|entry| {
+            !TOOL_GROUPS
+                .iter()
+                .any(|(group, tools)| group == entry || tools.contains(entry))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools_config(enabled: &[&str], disabled: &[&str]) -> ToolsConfig {
+        ToolsConfig {
+            enabled: enabled.iter().map(|s| s.to_string()).collect(),
+            disabled: disabled.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_every_tool_has_one_group() {
+        let mut seen = std::collections::HashSet::new();
+        for (_, tools) in TOOL_GROUPS {
+            for tool in *tools {
+                assert!(seen.insert(*tool), "{} is in two groups", tool);
+            }
+        }
+        assert_eq!(tool_group("reasoning_got_score"), Some("got"));
+        assert_eq!(tool_group("reasoning_nonexistent"), None);
+    }
+
+    #[test]
+    fn test_is_tool_enabled_defaults_to_all() {
+        let config = ToolsConfig::default();
+        assert!(is_tool_enabled(&config, "reasoning_linear"));
+        assert!(is_tool_enabled(&config, "reasoning_counterfactual"));
+    }
+
+    #[test]
+    fn test_is_tool_enabled_by_group_and_name() {
+        let config = tools_config(&["core", "reasoning_got_init"], &["reasoning_auto"]);
+        assert!(is_tool_enabled(&config, "reasoning_linear"));
+        assert!(is_tool_enabled(&config, "reasoning_got_init"));
+        assert!(!is_tool_enabled(&config, "reasoning_got_score"));
+        assert!(!is_tool_enabled(&config, "reasoning_auto"));
+
+        let config = tools_config(&[], &["got", "timeline"]);
+        assert!(is_tool_enabled(&config, "reasoning_linear"));
+        assert!(!is_tool_enabled(&config, "reasoning_got_export"));
+        assert!(!is_tool_enabled(&config, "reasoning_timeline_merge"));
+    }
+
+    #[test]
+    fn test_unknown_tool_entries() {
+        let config = tools_config(&["core", "reasoning_linaer"], &["gott"]);
+        assert_eq!(
+            unknown_tool_entries(&config),
+            vec!["reasoning_linaer", "gott"]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Validation of tool arguments before a call is routed.
+//!
+//! Every string argument, at any depth, must be at most `MAX_CONTENT_LENGTH`
+//! characters long and free of control characters other than tabs and line
+//! breaks. Top-level `*_id` arguments, and the entries of `*_ids` arrays, must
+//! look like the UUIDs the server issues: letters, digits, hyphens and
+//! underscores, so client-chosen session IDs such as `release-plan` still
+//! pass. A call breaking any rule fails with
+//! [`McpError::InvalidParameters`] naming each offending field, before it
+//! touches storage or calls a pipe.
+
+use serde_json::Value;
+
+use crate::config::ToolsConfig;
+use crate::error::{McpError, McpResult};
+
+/// Longest ID accepted.
+pub const MAX_ID_LENGTH: usize = 64;
+
+/// A rule one argument breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Path of the argument, e.g. `session_id` or `steps[1].content`.
+    pub field: String,
+    /// What is wrong with it.
+    pub problem: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.problem)
+    }
+}
+
+/// Check `arguments` of a call to `tool_name` against the limits in `config`.
+pub fn validate_arguments(
+    config: &ToolsConfig,
+    tool_name: &str,
+    arguments: Option<&Value>,
+) -> McpResult<()> {
+    let errors = argument_errors(config, arguments);
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(McpError::InvalidParameters {
+        tool_name: tool_name.to_string(),
+        message: errors
+            .iter()
+            .map(FieldError::to_string)
+            .collect::<Vec<_>>()
+            .join("; "),
+    })
+}
+
+/// Every rule `arguments` break, ordered by argument name
+pub fn argument_errors(config: &ToolsConfig, arguments: Option<&Value>) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let Some(Value::Object(arguments)) = arguments else {
+        return errors;
+    };
+
+    for (name, value) in arguments {
+        check_strings(config, name, value, &mut errors);
+        match value {
+            Value::String(id) if name.ends_with("_id") => {
+                if let Some(problem) = id_problem(id) {
+                    errors.push(FieldError {
+                        field: name.clone(),
+                        problem,
+                    });
+                }
+            }
+            Value::Array(ids) if name.ends_with("_ids") => {
+                for (index, id) in ids.iter().enumerate() {
+                    if let Some(problem) = id.as_str().and_then(id_problem) {
+                        errors.push(FieldError {
+                            field: format!("{}[{}]", name, index),
+                            problem,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    errors
+}
+
+/// Check the length and characters of every string in `value`
+fn check_strings(config: &ToolsConfig, path: &str, value: &Value, errors: &mut Vec<FieldError>) {
+    match value {
+        Value::String(text) => {
+            let length = text.chars().count();
+            if length > config.max_content_length {
+                errors.push(FieldError {
+                    field: path.to_string(),
+                    problem: format!(
+                        "{} characters, more than the {} allowed",
+                        length, config.max_content_length
+                    ),
+                });
+            }
+            if let Some(c) = text
+                .chars()
+                .find(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+            {
+                errors.push(FieldError {
+                    field: path.to_string(),
+                    problem: format!("contains control character U+{:04X}", c as u32),
+                });
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                check_strings(config, &format!("{}[{}]", path, index), item, errors);
+            }
+        }
+        Value::Object(fields) => {
+            for (name, field) in fields {
+                check_strings(config, &format!("{}.{}", path, name), field, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Why `id` does not look like an ID, if it doesn't
+fn id_problem(id: &str) -> Option<String> {
+    if id.is_empty() {
+        Some("must not be empty".to_string())
+    } else if id.len() > MAX_ID_LENGTH {
+        Some(format!("longer than {} characters", MAX_ID_LENGTH))
+    } else if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Some("may only hold letters, digits, '-' and '_'".to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn errors(arguments: Value) -> Vec<String> {
+        argument_errors(&ToolsConfig::default(), Some(&arguments))
+            .iter()
+            .map(FieldError::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_arguments_pass() {
+        let arguments = json!({
+            "content": "Line one\nLine two\twith a tab",
+            "session_id": "3f2a9c1e-7b4d-4e8a-9f0c-1d2e3f4a5b6c",
+            "branch_ids": ["release-plan", "b_2"],
+            "preset_id": "code-review",
+            "inputs": {"code": "fn main() {}"},
+            "limit": 10
+        });
+        assert!(errors(arguments).is_empty());
+        assert!(argument_errors(&ToolsConfig::default(), None).is_empty());
+    }
+
+    #[test]
+    fn test_content_length_limit() {
+        let config = ToolsConfig {
+            max_content_length: 5,
+            ..Default::default()
+        };
+        let arguments = json!({"content": "ab", "steps": [{"content": "éééééé"}]});
+        let errors = argument_errors(&config, Some(&arguments));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "steps[0].content");
+        assert_eq!(errors[0].problem, "6 characters, more than the 5 allowed");
+    }
+
+    #[test]
+    fn test_control_characters_rejected() {
+        assert_eq!(
+            errors(json!({"content": "bell\u{7}"})),
+            ["content: contains control character U+0007"]
+        );
+        assert_eq!(
+            errors(json!({"inputs": {"code": "a\u{0}b"}})),
+            ["inputs.code: contains control character U+0000"]
+        );
+    }
+
+    #[test]
+    fn test_ids_must_look_like_ids() {
+        assert_eq!(
+            errors(json!({
+                "session_id": "",
+                "thought_id": "../etc/passwd",
+                "node_ids": ["n-1", "n 2"],
+                "branch_id": "b".repeat(65)
+            })),
+            [
+                "branch_id: longer than 64 characters",
+                "node_ids[1]: may only hold letters, digits, '-' and '_'",
+                "session_id: must not be empty",
+                "thought_id: may only hold letters, digits, '-' and '_'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_arguments_names_every_field() {
+        let arguments = json!({"content": "\u{1b}[31m", "session_id": "a b"});
+        let err = validate_arguments(
+            &ToolsConfig::default(),
+            "reasoning_linear",
+            Some(&arguments),
+        )
+        .unwrap_err();
+        match err {
+            McpError::InvalidParameters { tool_name, message } => {
+                assert_eq!(tool_name, "reasoning_linear");
+                assert!(message.contains("content: contains control character U+001B"));
+                assert!(message.contains("session_id: may only hold"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
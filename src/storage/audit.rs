@@ -0,0 +1,78 @@
+//! Audit trail of mutating operations.
+//!
+//! [`AuditLogger`] records who called which tool against which session,
+//! together with hashes of the session state before and after the call.
+//! Entries go to the append-only `audit_log` table, separate from the
+//! invocation log used for metrics.
+
+use sha2::{Digest, Sha256};
+
+use super::{AuditEntry, SharedStorage};
+use crate::error::{StorageError, StorageResult};
+
+/// Records audit entries for mutating tool calls.
+#[derive(Clone)]
+pub struct AuditLogger {
+    storage: SharedStorage,
+    actor: String,
+}
+
+impl AuditLogger {
+    /// Create a logger that attributes entries to `actor`.
+    pub fn new(storage: impl Into<SharedStorage>, actor: impl Into<String>) -> Self {
+        Self {
+            storage: storage.into(),
+            actor: actor.into(),
+        }
+    }
+
+    /// The actor new entries are attributed to.
+    pub fn actor(&self) -> &str {
+        &self.actor
+    }
+
+    /// A logger writing to the same storage that attributes entries to
+    /// `actor` (e.g. once an MCP client has identified itself). `self` is
+    /// left unchanged.
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            actor: actor.into(),
+        }
+    }
+
+    /// Hash the current state of a session.
+    ///
+    /// Covers the session record and its thoughts, branches, checkpoints, and
+    /// graph nodes and edges. Returns `None` if the session does not exist.
+    pub async fn session_hash(&self, session_id: &str) -> StorageResult<Option<String>> {
+        let Some(session) = self.storage.get_session(session_id).await? else {
+            return Ok(None);
+        };
+
+        let state = serde_json::json!({
+            "session": session,
+            "thoughts": self.storage.get_session_thoughts(session_id).await?,
+            "branches": self.storage.get_session_branches(session_id).await?,
+            "checkpoints": self.storage.get_session_checkpoints(session_id).await?,
+            "graph_nodes": self.storage.get_session_graph_nodes(session_id).await?,
+            "graph_edges": self.storage.get_session_edges(session_id).await?,
+        });
+        let bytes = serde_json::to_vec(&state).map_err(|e| StorageError::Serialization {
+            message: format!("Failed to serialize session state: {}", e),
+        })?;
+
+        let digest = Sha256::digest(&bytes);
+        Ok(Some(digest.iter().map(|b| format!("{:02x}", b)).collect()))
+    }
+
+    /// Start an entry for a call to `tool_name` by the current actor.
+    pub fn entry(&self, tool_name: impl Into<String>) -> AuditEntry {
+        AuditEntry::new(self.actor(), tool_name)
+    }
+
+    /// Append an entry to the audit log.
+    pub async fn record(&self, entry: &AuditEntry) -> StorageResult<()> {
+        self.storage.append_audit_entry(entry).await
+    }
+}
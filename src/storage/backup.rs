@@ -0,0 +1,173 @@
+//! Database backup and restore helpers.
+//!
+//! Backups are consistent snapshots of the live database taken with
+//! `VACUUM INTO`, optionally gzip-compressed. Restores replace the database
+//! file and must be run while the server is stopped.
+//!
+//! # Why not the online backup API
+//!
+//! SQLite's `sqlite3_backup_*` API copies pages a few at a time and can
+//! release its locks between steps. sqlx does not expose it, and using it
+//! would mean raw FFI on the connection handle, so `VACUUM INTO` is used
+//! instead. The differences:
+//!
+//! - The copy is one statement that holds a read transaction from start to
+//!   finish. In WAL mode (sqlx's default) writers carry on, but the WAL cannot
+//!   be checkpointed past the snapshot, so it grows for as long as the backup
+//!   runs.
+//! - The database is rewritten rather than copied page by page: free pages are
+//!   dropped and tables are defragmented. The backup is usually smaller than
+//!   the live file and never byte-identical to it, and taking it costs CPU
+//!   proportional to the amount of data.
+//!
+//! # Encrypted databases
+//!
+//! With SQLCipher (the `sqlcipher` feature and `DATABASE_ENCRYPTION_KEY`),
+//! `VACUUM INTO` writes the copy through the same codec, so the backup is
+//! encrypted with the live database's key and no plaintext reaches disk.
+//! Restores open it with the configured key to verify it, so a backup can only
+//! be restored, or read at all, with the key it was taken under; keep old keys
+//! for as long as their backups are kept. Encrypted pages do not compress,
+//! making `compress` of little use for these backups. The online backup API
+//! would not help here either: it copies raw pages, which SQLCipher only
+//! allows between databases keyed identically.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{StorageError, StorageResult};
+
+/// Magic bytes at the start of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Result of a completed backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupReport {
+    /// Path the backup was written to.
+    pub path: PathBuf,
+    /// Size of the backup file in bytes.
+    pub size_bytes: u64,
+    /// Whether the backup is gzip-compressed.
+    pub compressed: bool,
+}
+
+/// Map an I/O error into [`StorageError::Backup`], prefixed with context.
+pub(crate) fn backup_error<E: std::fmt::Display>(context: &str) -> impl Fn(E) -> StorageError + '_ {
+    move |e| StorageError::Backup {
+        message: format!("{}: {}", context, e),
+    }
+}
+
+/// Path of the uncompressed staging file written next to `target`.
+pub(crate) fn staging_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// Build a writable SQLite URI for `path`, for use as a `VACUUM INTO` target.
+///
+/// The output of `VACUUM INTO` inherits the open flags of the source
+/// connection, so an in-memory source would otherwise snapshot into memory.
+/// `mode=rwc` forces a regular, created-if-missing file.
+pub(crate) fn vacuum_target_uri(path: &Path) -> String {
+    let escaped = path
+        .to_string_lossy()
+        .replace('%', "%25")
+        .replace('?', "%3F")
+        .replace('#', "%23");
+    format!("file:{}?mode=rwc", escaped)
+}
+
+/// Check whether a file is gzip-compressed by inspecting its magic bytes.
+pub(crate) fn is_gzip_file(path: &Path) -> StorageResult<bool> {
+    let mut magic = [0u8; 2];
+    let mut file = File::open(path).map_err(backup_error("Failed to open backup"))?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == GZIP_MAGIC),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(backup_error("Failed to read backup")(e)),
+    }
+}
+
+/// Gzip-compress `source` into `target`.
+pub(crate) fn compress_file(source: &Path, target: &Path) -> StorageResult<()> {
+    let mut reader =
+        BufReader::new(File::open(source).map_err(backup_error("Failed to open snapshot"))?);
+    let writer =
+        BufWriter::new(File::create(target).map_err(backup_error("Failed to create backup"))?);
+
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+    std::io::copy(&mut reader, &mut encoder).map_err(backup_error("Failed to compress backup"))?;
+    encoder
+        .finish()
+        .map_err(backup_error("Failed to compress backup"))?;
+    Ok(())
+}
+
+/// Decompress a gzip file at `source` into `target`.
+pub(crate) fn decompress_file(source: &Path, target: &Path) -> StorageResult<()> {
+    let mut decoder = GzDecoder::new(BufReader::new(
+        File::open(source).map_err(backup_error("Failed to open backup"))?,
+    ));
+    let mut writer =
+        BufWriter::new(File::create(target).map_err(backup_error("Failed to create database"))?);
+
+    std::io::copy(&mut decoder, &mut writer)
+        .map_err(backup_error("Failed to decompress backup"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_compress_decompress_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.db");
+        let compressed = dir.path().join("backup.db.gz");
+        let restored = dir.path().join("restored.db");
+
+        let data = b"SQLite format 3\0".repeat(64);
+        File::create(&original).unwrap().write_all(&data).unwrap();
+
+        compress_file(&original, &compressed).unwrap();
+        assert!(is_gzip_file(&compressed).unwrap());
+        assert!(!is_gzip_file(&original).unwrap());
+
+        decompress_file(&compressed, &restored).unwrap();
+        assert_eq!(std::fs::read(&restored).unwrap(), data);
+    }
+
+    #[test]
+    fn test_is_gzip_file_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty");
+        File::create(&empty).unwrap();
+        assert!(!is_gzip_file(&empty).unwrap());
+    }
+
+    #[test]
+    fn test_vacuum_target_uri_escapes_reserved_characters() {
+        assert_eq!(
+            vacuum_target_uri(Path::new("/backups/db?#%.bak")),
+            "file:/backups/db%3F%23%25.bak?mode=rwc"
+        );
+    }
+
+    #[test]
+    fn test_staging_path_appends_suffix() {
+        assert_eq!(
+            staging_path(Path::new("/backups/reasoning.db.gz")),
+            PathBuf::from("/backups/reasoning.db.gz.partial")
+        );
+    }
+}
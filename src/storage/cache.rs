@@ -0,0 +1,1075 @@
+//! In-process cache of hot session state.
+//!
+//! Modes re-read a session, its latest thought, and its active graph nodes on
+//! every call. [`CachedStorage`] wraps another [`Storage`] and keeps those
+//! reads in LRU caches keyed by session ID. Every write that can change a
+//! cached value invalidates it, and all other operations pass straight
+//! through to the wrapped storage.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hashlink::LruCache;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AuditEntry, AuditFilter, BackupReport, Branch, Checkpoint, CodeFinding, Conclusion,
+    ConclusionSource, CounterfactualAnalysis, CrossRef, Decision, Detection, DetectionType,
+    Embedding, EmbeddingCandidate, EvidenceAssessment, FallbackMetricsSummary, GraphComparison,
+    GraphEdge, GraphNode, InterventionType, Invocation, KnowledgeFilter, KnowledgeHit, MCTSNode,
+    MctsIteration, MctsIterationFilter, MetricsFilter, MigrationStatus, Page, Pagination,
+    PerspectiveAnalysis, PipeCacheEntry, PipePayload, PipeUsageSummary, ProbabilityUpdate, Risk,
+    RoutingRecord, RoutingRecordFilter, Session, SessionFilter, SessionFork, SharedStorage,
+    SimilarMatch, SimilarityFilter, StateSnapshot, Storage, SwotAnalysis, SwotFilter, Thought,
+    ThoughtRevision, ThoughtSearchFilter, ThoughtSearchHit, Timeline, TimelineBranch,
+    TimelineState,
+};
+use crate::error::StorageResult;
+
+/// Hit and miss counts for a [`CachedStorage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Reads answered from the cache.
+    pub hits: u64,
+    /// Reads that went to the wrapped storage.
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Fraction of reads answered from the cache (0.0 when nothing was read).
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct CacheState {
+    sessions: LruCache<String, Session>,
+    latest_thoughts: LruCache<String, Option<Thought>>,
+    active_nodes: LruCache<String, Vec<GraphNode>>,
+    /// Bumped on every invalidation so reads that raced a write don't
+    /// re-populate the cache with stale values.
+    generation: u64,
+}
+
+enum Lookup<T> {
+    Hit(T),
+    Miss(u64),
+}
+
+/// LRU cache layer in front of another storage backend.
+///
+/// Caches sessions, each session's latest thought, and each session's active
+/// graph nodes, holding at most `capacity` entries of each kind. Writes made
+/// directly to the wrapped storage bypass invalidation, so all access should
+/// go through this wrapper.
+#[derive(Clone)]
+pub struct CachedStorage {
+    inner: SharedStorage,
+    state: Arc<Mutex<CacheState>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl CachedStorage {
+    /// Wrap `inner` with caches holding up to `capacity` entries each.
+    ///
+    /// A capacity of zero is treated as one.
+    pub fn new(inner: impl Into<SharedStorage>, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner: inner.into(),
+            state: Arc::new(Mutex::new(CacheState {
+                sessions: LruCache::new(capacity),
+                latest_thoughts: LruCache::new(capacity),
+                active_nodes: LruCache::new(capacity),
+                generation: 0,
+            })),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Current hit and miss counts.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drop every cached entry.
+    pub fn clear(&self) {
+        self.invalidate(|state| {
+            state.sessions.clear();
+            state.latest_thoughts.clear();
+            state.active_nodes.clear();
+        });
+    }
+
+    fn lookup<T>(&self, get: impl FnOnce(&mut CacheState) -> Option<T>) -> Lookup<T> {
+        let Ok(mut state) = self.state.lock() else {
+            return Lookup::Miss(u64::MAX);
+        };
+        match get(&mut state) {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Lookup::Hit(value)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Lookup::Miss(state.generation)
+            }
+        }
+    }
+
+    /// Store a freshly read value unless a write invalidated the cache since
+    /// the read began.
+    fn fill(&self, generation: u64, insert: impl FnOnce(&mut CacheState)) {
+        if let Ok(mut state) = self.state.lock() {
+            if state.generation == generation {
+                insert(&mut state);
+            }
+        }
+    }
+
+    fn invalidate(&self, remove: impl FnOnce(&mut CacheState)) {
+        if let Ok(mut state) = self.state.lock() {
+            state.generation += 1;
+            remove(&mut state);
+        }
+    }
+
+    fn invalidate_session(&self, session_id: &str) {
+        self.invalidate(|state| {
+            state.sessions.remove(session_id);
+            state.latest_thoughts.remove(session_id);
+            state.active_nodes.remove(session_id);
+        });
+    }
+}
+
+#[async_trait]
+impl Storage for CachedStorage {
+    // Session operations
+
+    async fn create_session(&self, session: &Session) -> StorageResult<()> {
+        let result = self.inner.create_session(session).await;
+        self.invalidate_session(&session.id);
+        result
+    }
+
+    async fn get_session(&self, id: &str) -> StorageResult<Option<Session>> {
+        let generation = match self.lookup(|state| state.sessions.get(id).cloned()) {
+            Lookup::Hit(session) => return Ok(Some(session)),
+            Lookup::Miss(generation) => generation,
+        };
+
+        let session = self.inner.get_session(id).await?;
+        if let Some(session) = &session {
+            self.fill(generation, |state| {
+                state.sessions.insert(id.to_string(), session.clone());
+            });
+        }
+        Ok(session)
+    }
+
+    async fn update_session(&self, session: &Session) -> StorageResult<()> {
+        let result = self.inner.update_session(session).await;
+        self.invalidate_session(&session.id);
+        result
+    }
+
+    async fn delete_session(&self, id: &str) -> StorageResult<()> {
+        let result = self.inner.delete_session(id).await;
+        self.invalidate_session(id);
+        result
+    }
+
+    async fn trash_session(&self, id: &str) -> StorageResult<bool> {
+        let result = self.inner.trash_session(id).await;
+        self.invalidate_session(id);
+        result
+    }
+
+    async fn restore_session(&self, id: &str) -> StorageResult<bool> {
+        let result = self.inner.restore_session(id).await;
+        self.invalidate_session(id);
+        result
+    }
+
+    async fn add_tag(&self, session_id: &str, tag: &str) -> StorageResult<()> {
+        let result = self.inner.add_tag(session_id, tag).await;
+        self.invalidate_session(session_id);
+        result
+    }
+
+    async fn remove_tag(&self, session_id: &str, tag: &str) -> StorageResult<bool> {
+        let result = self.inner.remove_tag(session_id, tag).await;
+        self.invalidate_session(session_id);
+        result
+    }
+
+    async fn find_sessions_by_tag(&self, tag: &str) -> StorageResult<Vec<Session>> {
+        self.inner.find_sessions_by_tag(tag).await
+    }
+
+    async fn list_sessions(&self, filter: SessionFilter) -> StorageResult<Vec<Session>> {
+        self.inner.list_sessions(filter).await
+    }
+
+    async fn fork_session(&self, session_id: &str) -> StorageResult<SessionFork> {
+        self.inner.fork_session(session_id).await
+    }
+
+    // Thought operations
+
+    async fn create_thought(&self, thought: &Thought) -> StorageResult<()> {
+        let result = self.inner.create_thought(thought).await;
+        self.invalidate_session(&thought.session_id);
+        result
+    }
+
+    async fn create_thoughts_batch(&self, thoughts: &[Thought]) -> StorageResult<()> {
+        let result = self.inner.create_thoughts_batch(thoughts).await;
+        for thought in thoughts {
+            self.invalidate_session(&thought.session_id);
+        }
+        result
+    }
+
+    async fn get_thought(&self, id: &str) -> StorageResult<Option<Thought>> {
+        self.inner.get_thought(id).await
+    }
+
+    async fn get_session_thoughts(&self, session_id: &str) -> StorageResult<Vec<Thought>> {
+        self.inner.get_session_thoughts(session_id).await
+    }
+
+    async fn get_session_thoughts_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<Thought>> {
+        self.inner
+            .get_session_thoughts_page(session_id, pagination)
+            .await
+    }
+
+    async fn get_branch_thoughts(&self, branch_id: &str) -> StorageResult<Vec<Thought>> {
+        self.inner.get_branch_thoughts(branch_id).await
+    }
+
+    async fn get_latest_thought(&self, session_id: &str) -> StorageResult<Option<Thought>> {
+        let generation = match self.lookup(|state| state.latest_thoughts.get(session_id).cloned()) {
+            Lookup::Hit(thought) => return Ok(thought),
+            Lookup::Miss(generation) => generation,
+        };
+
+        let thought = self.inner.get_latest_thought(session_id).await?;
+        self.fill(generation, |state| {
+            state
+                .latest_thoughts
+                .insert(session_id.to_string(), thought.clone());
+        });
+        Ok(thought)
+    }
+
+    async fn search_thoughts(
+        &self,
+        query: &str,
+        filter: ThoughtSearchFilter,
+    ) -> StorageResult<Vec<ThoughtSearchHit>> {
+        self.inner.search_thoughts(query, filter).await
+    }
+
+    async fn revise_thought(
+        &self,
+        thought_id: &str,
+        content: &str,
+        confidence: Option<f64>,
+        reason: Option<&str>,
+    ) -> StorageResult<Thought> {
+        let thought = self
+            .inner
+            .revise_thought(thought_id, content, confidence, reason)
+            .await?;
+        self.invalidate_session(&thought.session_id);
+        Ok(thought)
+    }
+
+    async fn get_thought_revisions(&self, thought_id: &str) -> StorageResult<Vec<ThoughtRevision>> {
+        self.inner.get_thought_revisions(thought_id).await
+    }
+
+    async fn update_thought_metadata(&self, thought: &Thought) -> StorageResult<()> {
+        let result = self.inner.update_thought_metadata(thought).await;
+        self.invalidate_session(&thought.session_id);
+        result
+    }
+
+    // Embedding operations (semantic similarity)
+
+    async fn save_embeddings(&self, embeddings: &[Embedding]) -> StorageResult<()> {
+        self.inner.save_embeddings(embeddings).await
+    }
+
+    async fn get_embedding_candidates(
+        &self,
+        model: &str,
+        workspace_id: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<EmbeddingCandidate>> {
+        self.inner
+            .get_embedding_candidates(model, workspace_id, limit)
+            .await
+    }
+
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        model: &str,
+        filter: SimilarityFilter,
+    ) -> StorageResult<Vec<SimilarMatch>> {
+        self.inner.find_similar(vector, model, filter).await
+    }
+
+    // Knowledge base operations
+
+    async fn save_conclusion(&self, conclusion: &Conclusion) -> StorageResult<()> {
+        self.inner.save_conclusion(conclusion).await
+    }
+
+    async fn get_conclusion(&self, id: &str) -> StorageResult<Option<Conclusion>> {
+        self.inner.get_conclusion(id).await
+    }
+
+    async fn get_conclusion_by_source(
+        &self,
+        source_type: ConclusionSource,
+        source_id: &str,
+    ) -> StorageResult<Option<Conclusion>> {
+        self.inner
+            .get_conclusion_by_source(source_type, source_id)
+            .await
+    }
+
+    async fn search_conclusions(
+        &self,
+        query: &str,
+        filter: KnowledgeFilter,
+    ) -> StorageResult<Vec<KnowledgeHit>> {
+        self.inner.search_conclusions(query, filter).await
+    }
+
+    async fn delete_conclusion(&self, id: &str) -> StorageResult<bool> {
+        self.inner.delete_conclusion(id).await
+    }
+
+    // Audit log operations
+
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> StorageResult<()> {
+        self.inner.append_audit_entry(entry).await
+    }
+
+    async fn list_audit_entries(&self, filter: AuditFilter) -> StorageResult<Vec<AuditEntry>> {
+        self.inner.list_audit_entries(filter).await
+    }
+
+    // Pipe response cache (global, shared across workspaces)
+
+    async fn get_pipe_cache(&self, key: &str) -> StorageResult<Option<PipeCacheEntry>> {
+        self.inner.get_pipe_cache(key).await
+    }
+
+    async fn put_pipe_cache(&self, entry: &PipeCacheEntry) -> StorageResult<()> {
+        self.inner.put_pipe_cache(entry).await
+    }
+
+    async fn log_pipe_payload(&self, payload: &PipePayload) -> StorageResult<()> {
+        self.inner.log_pipe_payload(payload).await
+    }
+
+    async fn get_pipe_payloads(
+        &self,
+        pipe_name: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<PipePayload>> {
+        self.inner.get_pipe_payloads(pipe_name, limit).await
+    }
+
+    // Routing history operations
+
+    async fn create_routing_record(&self, record: &RoutingRecord) -> StorageResult<()> {
+        self.inner.create_routing_record(record).await
+    }
+
+    async fn get_routing_record(&self, id: &str) -> StorageResult<Option<RoutingRecord>> {
+        self.inner.get_routing_record(id).await
+    }
+
+    async fn update_routing_outcome(&self, record: &RoutingRecord) -> StorageResult<()> {
+        self.inner.update_routing_outcome(record).await
+    }
+
+    async fn list_routing_records(
+        &self,
+        filter: RoutingRecordFilter,
+    ) -> StorageResult<Vec<RoutingRecord>> {
+        self.inner.list_routing_records(filter).await
+    }
+
+    // Branch operations (tree mode)
+
+    async fn create_branch(&self, branch: &Branch) -> StorageResult<()> {
+        self.inner.create_branch(branch).await
+    }
+
+    async fn get_branch(&self, id: &str) -> StorageResult<Option<Branch>> {
+        self.inner.get_branch(id).await
+    }
+
+    async fn get_session_branches(&self, session_id: &str) -> StorageResult<Vec<Branch>> {
+        self.inner.get_session_branches(session_id).await
+    }
+
+    async fn get_child_branches(&self, parent_id: &str) -> StorageResult<Vec<Branch>> {
+        self.inner.get_child_branches(parent_id).await
+    }
+
+    async fn update_branch(&self, branch: &Branch) -> StorageResult<()> {
+        self.inner.update_branch(branch).await
+    }
+
+    async fn delete_branch(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_branch(id).await
+    }
+
+    // Cross-reference operations (tree mode)
+
+    async fn create_cross_ref(&self, cross_ref: &CrossRef) -> StorageResult<()> {
+        self.inner.create_cross_ref(cross_ref).await
+    }
+
+    async fn get_cross_refs_from(&self, branch_id: &str) -> StorageResult<Vec<CrossRef>> {
+        self.inner.get_cross_refs_from(branch_id).await
+    }
+
+    async fn get_cross_refs_to(&self, branch_id: &str) -> StorageResult<Vec<CrossRef>> {
+        self.inner.get_cross_refs_to(branch_id).await
+    }
+
+    async fn delete_cross_ref(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_cross_ref(id).await
+    }
+
+    // Checkpoint operations (backtracking)
+
+    async fn create_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        self.inner.create_checkpoint(checkpoint).await
+    }
+
+    async fn get_checkpoint(&self, id: &str) -> StorageResult<Option<Checkpoint>> {
+        self.inner.get_checkpoint(id).await
+    }
+
+    async fn get_session_checkpoints(&self, session_id: &str) -> StorageResult<Vec<Checkpoint>> {
+        self.inner.get_session_checkpoints(session_id).await
+    }
+
+    async fn get_branch_checkpoints(&self, branch_id: &str) -> StorageResult<Vec<Checkpoint>> {
+        self.inner.get_branch_checkpoints(branch_id).await
+    }
+
+    async fn delete_checkpoint(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_checkpoint(id).await
+    }
+
+    // Invocation logging and metrics
+
+    async fn log_invocation(&self, invocation: &Invocation) -> StorageResult<()> {
+        self.inner.log_invocation(invocation).await
+    }
+
+    async fn get_pipe_usage_summary(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Vec<PipeUsageSummary>> {
+        self.inner.get_pipe_usage_summary(workspace_id).await
+    }
+
+    async fn get_pipe_summary(
+        &self,
+        pipe_name: &str,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Option<PipeUsageSummary>> {
+        self.inner.get_pipe_summary(pipe_name, workspace_id).await
+    }
+
+    async fn get_invocations(&self, filter: MetricsFilter) -> StorageResult<Vec<Invocation>> {
+        self.inner.get_invocations(filter).await
+    }
+
+    async fn get_invocation_count(
+        &self,
+        pipe_name: Option<&str>,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<u64> {
+        self.inner
+            .get_invocation_count(pipe_name, workspace_id)
+            .await
+    }
+
+    async fn get_fallback_metrics(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<FallbackMetricsSummary> {
+        self.inner.get_fallback_metrics(workspace_id).await
+    }
+
+    // Graph node operations (GoT mode)
+
+    async fn create_graph_node(&self, node: &GraphNode) -> StorageResult<()> {
+        let result = self.inner.create_graph_node(node).await;
+        self.invalidate_session(&node.session_id);
+        result
+    }
+
+    async fn create_graph_nodes_batch(&self, nodes: &[GraphNode]) -> StorageResult<()> {
+        let result = self.inner.create_graph_nodes_batch(nodes).await;
+        for node in nodes {
+            self.invalidate_session(&node.session_id);
+        }
+        result
+    }
+
+    async fn get_graph_node(&self, id: &str) -> StorageResult<Option<GraphNode>> {
+        self.inner.get_graph_node(id).await
+    }
+
+    async fn get_session_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.inner.get_session_graph_nodes(session_id).await
+    }
+
+    async fn get_session_graph_nodes_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<GraphNode>> {
+        self.inner
+            .get_session_graph_nodes_page(session_id, pagination)
+            .await
+    }
+
+    async fn get_active_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        let generation = match self.lookup(|state| state.active_nodes.get(session_id).cloned()) {
+            Lookup::Hit(nodes) => return Ok(nodes),
+            Lookup::Miss(generation) => generation,
+        };
+
+        let nodes = self.inner.get_active_graph_nodes(session_id).await?;
+        self.fill(generation, |state| {
+            state
+                .active_nodes
+                .insert(session_id.to_string(), nodes.clone());
+        });
+        Ok(nodes)
+    }
+
+    async fn get_root_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.inner.get_root_nodes(session_id).await
+    }
+
+    async fn get_terminal_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.inner.get_terminal_nodes(session_id).await
+    }
+
+    async fn update_graph_node(&self, node: &GraphNode) -> StorageResult<()> {
+        let result = self.inner.update_graph_node(node).await;
+        self.invalidate_session(&node.session_id);
+        result
+    }
+
+    async fn delete_graph_node(&self, id: &str) -> StorageResult<()> {
+        let result = self.inner.delete_graph_node(id).await;
+        // The owning session is unknown here, so drop every cached node set
+        self.invalidate(|state| state.active_nodes.clear());
+        result
+    }
+
+    // Graph edge operations (GoT mode)
+
+    async fn create_graph_edge(&self, edge: &GraphEdge) -> StorageResult<()> {
+        self.inner.create_graph_edge(edge).await
+    }
+
+    async fn create_graph_edges_batch(&self, edges: &[GraphEdge]) -> StorageResult<()> {
+        self.inner.create_graph_edges_batch(edges).await
+    }
+
+    async fn get_graph_edge(&self, id: &str) -> StorageResult<Option<GraphEdge>> {
+        self.inner.get_graph_edge(id).await
+    }
+
+    async fn get_edges_from(&self, node_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        self.inner.get_edges_from(node_id).await
+    }
+
+    async fn get_edges_to(&self, node_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        self.inner.get_edges_to(node_id).await
+    }
+
+    async fn get_session_edges(&self, session_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        self.inner.get_session_edges(session_id).await
+    }
+
+    async fn delete_graph_edge(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_graph_edge(id).await
+    }
+
+    // Graph comparison operations (GoT pairwise ranking)
+
+    async fn create_graph_comparisons_batch(
+        &self,
+        comparisons: &[GraphComparison],
+    ) -> StorageResult<()> {
+        self.inner.create_graph_comparisons_batch(comparisons).await
+    }
+
+    async fn get_session_graph_comparisons(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<GraphComparison>> {
+        self.inner.get_session_graph_comparisons(session_id).await
+    }
+
+    // State snapshot operations (backtracking)
+
+    async fn create_snapshot(&self, snapshot: &StateSnapshot) -> StorageResult<()> {
+        self.inner.create_snapshot(snapshot).await
+    }
+
+    async fn get_snapshot(&self, id: &str) -> StorageResult<Option<StateSnapshot>> {
+        self.inner.get_snapshot(id).await
+    }
+
+    async fn get_session_snapshots(&self, session_id: &str) -> StorageResult<Vec<StateSnapshot>> {
+        self.inner.get_session_snapshots(session_id).await
+    }
+
+    async fn get_latest_snapshot(&self, session_id: &str) -> StorageResult<Option<StateSnapshot>> {
+        self.inner.get_latest_snapshot(session_id).await
+    }
+
+    async fn delete_snapshot(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_snapshot(id).await
+    }
+
+    // Detection operations (bias/fallacy analysis)
+
+    async fn create_detection(&self, detection: &Detection) -> StorageResult<()> {
+        self.inner.create_detection(detection).await
+    }
+
+    async fn get_detection(&self, id: &str) -> StorageResult<Option<Detection>> {
+        self.inner.get_detection(id).await
+    }
+
+    async fn get_session_detections(&self, session_id: &str) -> StorageResult<Vec<Detection>> {
+        self.inner.get_session_detections(session_id).await
+    }
+
+    async fn get_thought_detections(&self, thought_id: &str) -> StorageResult<Vec<Detection>> {
+        self.inner.get_thought_detections(thought_id).await
+    }
+
+    async fn get_detections_by_type(
+        &self,
+        detection_type: DetectionType,
+    ) -> StorageResult<Vec<Detection>> {
+        self.inner.get_detections_by_type(detection_type).await
+    }
+
+    async fn get_session_detections_by_type(
+        &self,
+        session_id: &str,
+        detection_type: DetectionType,
+    ) -> StorageResult<Vec<Detection>> {
+        self.inner
+            .get_session_detections_by_type(session_id, detection_type)
+            .await
+    }
+
+    async fn delete_detection(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_detection(id).await
+    }
+
+    // ========================================================================
+    // Decision operations (decision framework)
+    // ========================================================================
+
+    async fn create_decision(&self, decision: &Decision) -> StorageResult<()> {
+        self.inner.create_decision(decision).await
+    }
+
+    async fn get_decision(&self, id: &str) -> StorageResult<Option<Decision>> {
+        self.inner.get_decision(id).await
+    }
+
+    async fn get_session_decisions(&self, session_id: &str) -> StorageResult<Vec<Decision>> {
+        self.inner.get_session_decisions(session_id).await
+    }
+
+    async fn get_decisions_by_method(&self, method: &str) -> StorageResult<Vec<Decision>> {
+        self.inner.get_decisions_by_method(method).await
+    }
+
+    async fn delete_decision(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_decision(id).await
+    }
+
+    // ========================================================================
+    // Perspective analysis operations (decision framework)
+    // ========================================================================
+
+    async fn create_perspective(&self, analysis: &PerspectiveAnalysis) -> StorageResult<()> {
+        self.inner.create_perspective(analysis).await
+    }
+
+    async fn get_perspective(&self, id: &str) -> StorageResult<Option<PerspectiveAnalysis>> {
+        self.inner.get_perspective(id).await
+    }
+
+    async fn get_session_perspectives(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<PerspectiveAnalysis>> {
+        self.inner.get_session_perspectives(session_id).await
+    }
+
+    async fn delete_perspective(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_perspective(id).await
+    }
+
+    // ========================================================================
+    // SWOT analysis operations (swot mode)
+    // ========================================================================
+
+    async fn create_swot_analysis(&self, analysis: &SwotAnalysis) -> StorageResult<()> {
+        self.inner.create_swot_analysis(analysis).await
+    }
+
+    async fn find_swot_analyses(&self, filter: SwotFilter) -> StorageResult<Vec<SwotAnalysis>> {
+        self.inner.find_swot_analyses(filter).await
+    }
+
+    // ========================================================================
+    // Risk register operations (risk mode)
+    // ========================================================================
+
+    async fn create_risk(&self, risk: &Risk) -> StorageResult<()> {
+        self.inner.create_risk(risk).await
+    }
+
+    async fn get_risk(&self, id: &str) -> StorageResult<Option<Risk>> {
+        self.inner.get_risk(id).await
+    }
+
+    async fn get_session_risks(&self, session_id: &str) -> StorageResult<Vec<Risk>> {
+        self.inner.get_session_risks(session_id).await
+    }
+
+    async fn update_risk(&self, risk: &Risk) -> StorageResult<()> {
+        self.inner.update_risk(risk).await
+    }
+
+    // ========================================================================
+    // Code finding operations (code reasoning mode)
+    // ========================================================================
+
+    async fn create_code_finding(&self, finding: &CodeFinding) -> StorageResult<()> {
+        self.inner.create_code_finding(finding).await
+    }
+
+    async fn get_session_code_findings(&self, session_id: &str) -> StorageResult<Vec<CodeFinding>> {
+        self.inner.get_session_code_findings(session_id).await
+    }
+
+    // ========================================================================
+    // Evidence assessment operations (evidence mode)
+    // ========================================================================
+
+    async fn create_evidence_assessment(
+        &self,
+        assessment: &EvidenceAssessment,
+    ) -> StorageResult<()> {
+        self.inner.create_evidence_assessment(assessment).await
+    }
+
+    async fn get_evidence_assessment(&self, id: &str) -> StorageResult<Option<EvidenceAssessment>> {
+        self.inner.get_evidence_assessment(id).await
+    }
+
+    async fn get_session_evidence_assessments(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<EvidenceAssessment>> {
+        self.inner
+            .get_session_evidence_assessments(session_id)
+            .await
+    }
+
+    async fn delete_evidence_assessment(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_evidence_assessment(id).await
+    }
+
+    // ========================================================================
+    // Probability update operations (evidence mode)
+    // ========================================================================
+
+    async fn create_probability_update(&self, update: &ProbabilityUpdate) -> StorageResult<()> {
+        self.inner.create_probability_update(update).await
+    }
+
+    async fn get_probability_update(&self, id: &str) -> StorageResult<Option<ProbabilityUpdate>> {
+        self.inner.get_probability_update(id).await
+    }
+
+    async fn get_session_probability_updates(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<ProbabilityUpdate>> {
+        self.inner.get_session_probability_updates(session_id).await
+    }
+
+    async fn get_hypothesis_updates(
+        &self,
+        session_id: &str,
+        hypothesis: &str,
+    ) -> StorageResult<Vec<ProbabilityUpdate>> {
+        self.inner
+            .get_hypothesis_updates(session_id, hypothesis)
+            .await
+    }
+
+    async fn delete_probability_update(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_probability_update(id).await
+    }
+
+    // ========================================================================
+    // Timeline operations (Time Machine)
+    // ========================================================================
+
+    async fn create_timeline(&self, timeline: &Timeline) -> StorageResult<()> {
+        self.inner.create_timeline(timeline).await
+    }
+
+    async fn get_timeline(&self, id: &str) -> StorageResult<Option<Timeline>> {
+        self.inner.get_timeline(id).await
+    }
+
+    async fn get_session_timelines(&self, session_id: &str) -> StorageResult<Vec<Timeline>> {
+        self.inner.get_session_timelines(session_id).await
+    }
+
+    async fn get_timelines_by_state(&self, state: TimelineState) -> StorageResult<Vec<Timeline>> {
+        self.inner.get_timelines_by_state(state).await
+    }
+
+    async fn update_timeline(&self, timeline: &Timeline) -> StorageResult<()> {
+        self.inner.update_timeline(timeline).await
+    }
+
+    async fn delete_timeline(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_timeline(id).await
+    }
+
+    // ========================================================================
+    // Timeline branch operations (Time Machine)
+    // ========================================================================
+
+    async fn create_timeline_branch(&self, branch: &TimelineBranch) -> StorageResult<()> {
+        self.inner.create_timeline_branch(branch).await
+    }
+
+    async fn get_timeline_branch(&self, branch_id: &str) -> StorageResult<Option<TimelineBranch>> {
+        self.inner.get_timeline_branch(branch_id).await
+    }
+
+    async fn get_timeline_branches(&self, timeline_id: &str) -> StorageResult<Vec<TimelineBranch>> {
+        self.inner.get_timeline_branches(timeline_id).await
+    }
+
+    async fn get_branches_by_ucb(&self, timeline_id: &str) -> StorageResult<Vec<TimelineBranch>> {
+        self.inner.get_branches_by_ucb(timeline_id).await
+    }
+
+    async fn update_timeline_branch(&self, branch: &TimelineBranch) -> StorageResult<()> {
+        self.inner.update_timeline_branch(branch).await
+    }
+
+    async fn delete_timeline_branch(&self, branch_id: &str) -> StorageResult<()> {
+        self.inner.delete_timeline_branch(branch_id).await
+    }
+
+    // ========================================================================
+    // MCTS node operations (Time Machine)
+    // ========================================================================
+
+    async fn create_mcts_node(&self, node: &MCTSNode) -> StorageResult<()> {
+        self.inner.create_mcts_node(node).await
+    }
+
+    async fn get_mcts_node(&self, id: &str) -> StorageResult<Option<MCTSNode>> {
+        self.inner.get_mcts_node(id).await
+    }
+
+    async fn get_session_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_session_mcts_nodes(session_id).await
+    }
+
+    async fn get_timeline_mcts_nodes(&self, timeline_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_timeline_mcts_nodes(timeline_id).await
+    }
+
+    async fn get_mcts_children(&self, parent_node_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_mcts_children(parent_node_id).await
+    }
+
+    async fn get_unexpanded_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_unexpanded_mcts_nodes(session_id).await
+    }
+
+    async fn get_terminal_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_terminal_mcts_nodes(session_id).await
+    }
+
+    async fn get_mcts_nodes_by_ucb(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.inner.get_mcts_nodes_by_ucb(session_id).await
+    }
+
+    async fn update_mcts_node(&self, node: &MCTSNode) -> StorageResult<()> {
+        self.inner.update_mcts_node(node).await
+    }
+
+    async fn delete_mcts_node(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_mcts_node(id).await
+    }
+
+    async fn create_mcts_iteration(&self, iteration: &MctsIteration) -> StorageResult<()> {
+        self.inner.create_mcts_iteration(iteration).await
+    }
+
+    async fn get_mcts_iterations(
+        &self,
+        filter: MctsIterationFilter,
+    ) -> StorageResult<Vec<MctsIteration>> {
+        self.inner.get_mcts_iterations(filter).await
+    }
+
+    // ========================================================================
+    // Counterfactual analysis operations (Time Machine)
+    // ========================================================================
+
+    async fn create_counterfactual(&self, analysis: &CounterfactualAnalysis) -> StorageResult<()> {
+        self.inner.create_counterfactual(analysis).await
+    }
+
+    async fn get_counterfactual(&self, id: &str) -> StorageResult<Option<CounterfactualAnalysis>> {
+        self.inner.get_counterfactual(id).await
+    }
+
+    async fn get_session_counterfactuals(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        self.inner.get_session_counterfactuals(session_id).await
+    }
+
+    async fn get_timeline_counterfactuals(
+        &self,
+        timeline_id: &str,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        self.inner.get_timeline_counterfactuals(timeline_id).await
+    }
+
+    async fn get_counterfactuals_by_type(
+        &self,
+        intervention_type: InterventionType,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        self.inner
+            .get_counterfactuals_by_type(intervention_type)
+            .await
+    }
+
+    async fn delete_counterfactual(&self, id: &str) -> StorageResult<()> {
+        self.inner.delete_counterfactual(id).await
+    }
+
+    // ========================================================================
+    // Retention operations
+    // ========================================================================
+
+    async fn delete_sessions_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = self.inner.delete_sessions_before(cutoff).await;
+        self.clear();
+        result
+    }
+
+    async fn purge_trashed_sessions(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = self.inner.purge_trashed_sessions(cutoff).await;
+        self.clear();
+        result
+    }
+
+    async fn delete_snapshots_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_snapshots_before(cutoff).await
+    }
+
+    async fn delete_invocations_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_invocations_before(cutoff).await
+    }
+
+    async fn delete_pipe_payloads_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_pipe_payloads_before(cutoff).await
+    }
+
+    async fn trim_invocations(&self, max_entries: u64) -> StorageResult<u64> {
+        self.inner.trim_invocations(max_entries).await
+    }
+
+    // ========================================================================
+    // Maintenance operations
+    // ========================================================================
+
+    async fn integrity_check(&self) -> StorageResult<Vec<String>> {
+        self.inner.integrity_check().await
+    }
+
+    async fn schema_status(&self) -> StorageResult<MigrationStatus> {
+        self.inner.schema_status().await
+    }
+
+    async fn vacuum(&self) -> StorageResult<()> {
+        self.inner.vacuum().await
+    }
+
+    async fn analyze(&self) -> StorageResult<()> {
+        self.inner.analyze().await
+    }
+
+    // ========================================================================
+    // Backup operations
+    // ========================================================================
+
+    async fn backup(&self, target: &Path, compress: bool) -> StorageResult<BackupReport> {
+        self.inner.backup(target, compress).await
+    }
+}
@@ -0,0 +1,182 @@
+//! Cross-session knowledge base.
+//!
+//! [`KnowledgeStore`] promotes finished reasoning results — terminal
+//! Graph-of-Thoughts nodes, decision recommendations, and evidence verdicts —
+//! into reusable [`Conclusion`]s, and answers queries against them so later
+//! sessions can build on earlier work instead of reasoning from scratch.
+
+use super::{Conclusion, ConclusionSource, KnowledgeFilter, KnowledgeHit, SharedStorage};
+use crate::error::{StorageError, StorageResult};
+
+/// Promotes reasoning results to conclusions and queries them.
+#[derive(Clone)]
+pub struct KnowledgeStore {
+    storage: SharedStorage,
+}
+
+impl KnowledgeStore {
+    /// Create a knowledge store backed by the given storage.
+    pub fn new(storage: impl Into<SharedStorage>) -> Self {
+        Self {
+            storage: storage.into(),
+        }
+    }
+
+    /// Promote a source record to the knowledge base.
+    ///
+    /// Promotion is idempotent: if the record was already promoted, the
+    /// existing conclusion is returned unchanged.
+    pub async fn promote(
+        &self,
+        source_type: ConclusionSource,
+        source_id: &str,
+    ) -> StorageResult<Conclusion> {
+        if let Some(existing) = self
+            .storage
+            .get_conclusion_by_source(source_type, source_id)
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        let conclusion = match source_type {
+            ConclusionSource::GraphNode => self.conclusion_from_graph_node(source_id).await?,
+            ConclusionSource::Decision => self.conclusion_from_decision(source_id).await?,
+            ConclusionSource::Evidence => self.conclusion_from_evidence(source_id).await?,
+        };
+        self.storage.save_conclusion(&conclusion).await?;
+        Ok(conclusion)
+    }
+
+    /// Promote a terminal Graph-of-Thoughts node.
+    pub async fn promote_graph_node(&self, node_id: &str) -> StorageResult<Conclusion> {
+        self.promote(ConclusionSource::GraphNode, node_id).await
+    }
+
+    /// Promote a decision's recommendation.
+    pub async fn promote_decision(&self, decision_id: &str) -> StorageResult<Conclusion> {
+        self.promote(ConclusionSource::Decision, decision_id).await
+    }
+
+    /// Promote an evidence assessment's verdict.
+    pub async fn promote_evidence(&self, assessment_id: &str) -> StorageResult<Conclusion> {
+        self.promote(ConclusionSource::Evidence, assessment_id)
+            .await
+    }
+
+    /// Find conclusions relevant to a question or problem statement.
+    pub async fn query(
+        &self,
+        query: &str,
+        filter: KnowledgeFilter,
+    ) -> StorageResult<Vec<KnowledgeHit>> {
+        self.storage.search_conclusions(query, filter).await
+    }
+
+    async fn conclusion_from_graph_node(&self, node_id: &str) -> StorageResult<Conclusion> {
+        let node = self
+            .storage
+            .get_graph_node(node_id)
+            .await?
+            .ok_or_else(|| not_found("graph node", node_id))?;
+        if !node.is_terminal {
+            return Err(StorageError::Knowledge {
+                message: format!("graph node {} is not terminal", node_id),
+            });
+        }
+
+        // The graph's root holds the problem the conclusion answers
+        let topic = self
+            .storage
+            .get_session_graph_nodes(&node.session_id)
+            .await?
+            .into_iter()
+            .find(|n| n.is_root)
+            .map(|n| n.content)
+            .unwrap_or_else(|| node.content.clone());
+
+        let mut conclusion =
+            Conclusion::new(ConclusionSource::GraphNode, &node.id, topic, node.content)
+                .with_session(node.session_id);
+        if let Some(score) = node.score {
+            conclusion = conclusion.with_confidence(score);
+        }
+        Ok(conclusion)
+    }
+
+    async fn conclusion_from_decision(&self, decision_id: &str) -> StorageResult<Conclusion> {
+        let decision = self
+            .storage
+            .get_decision(decision_id)
+            .await?
+            .ok_or_else(|| not_found("decision", decision_id))?;
+
+        let recommendation = &decision.recommendation;
+        let option = recommendation
+            .get("option")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| StorageError::Knowledge {
+                message: format!("decision {} has no recommended option", decision_id),
+            })?;
+        let content = match recommendation.get("rationale").and_then(|v| v.as_str()) {
+            Some(rationale) if !rationale.is_empty() => {
+                format!("Recommended: {}. {}", option, rationale)
+            }
+            _ => format!("Recommended: {}", option),
+        };
+
+        let mut conclusion = Conclusion::new(
+            ConclusionSource::Decision,
+            &decision.id,
+            decision.question,
+            content,
+        )
+        .with_session(decision.session_id);
+        if let Some(confidence) = recommendation.get("confidence").and_then(|v| v.as_f64()) {
+            conclusion = conclusion.with_confidence(confidence);
+        }
+        Ok(conclusion)
+    }
+
+    async fn conclusion_from_evidence(&self, assessment_id: &str) -> StorageResult<Conclusion> {
+        let assessment = self
+            .storage
+            .get_evidence_assessment(assessment_id)
+            .await?
+            .ok_or_else(|| not_found("evidence assessment", assessment_id))?;
+
+        let support = &assessment.overall_support;
+        let level = support
+            .get("level")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| StorageError::Knowledge {
+                message: format!("evidence assessment {} has no verdict", assessment_id),
+            })?;
+        let content = match support.get("explanation").and_then(|v| v.as_str()) {
+            Some(explanation) if !explanation.is_empty() => {
+                format!("Support for the claim is {}. {}", level, explanation)
+            }
+            _ => format!("Support for the claim is {}.", level),
+        };
+
+        let mut conclusion = Conclusion::new(
+            ConclusionSource::Evidence,
+            &assessment.id,
+            assessment.claim,
+            content,
+        )
+        .with_session(assessment.session_id);
+        if let Some(confidence) = support.get("confidence").and_then(|v| v.as_f64()) {
+            conclusion = conclusion.with_confidence(confidence);
+        }
+        Ok(conclusion)
+    }
+}
+
+fn not_found(kind: &str, id: &str) -> StorageError {
+    StorageError::Knowledge {
+        message: format!("{} not found: {}", kind, id),
+    }
+}
@@ -0,0 +1,258 @@
+//! Scheduled database maintenance.
+//!
+//! Runs `PRAGMA integrity_check`, `VACUUM`, and `ANALYZE` on the schedule set
+//! by [`MaintenanceConfig`], so long-lived databases stay compact and keep
+//! good query plans. The outcome of the latest run is kept in a
+//! [`MaintenanceStatus`] and reported by `reasoning_metrics_summary`.
+
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use super::{SharedStorage, Storage};
+use crate::config::{MaintenanceConfig, MaintenanceWindow};
+use crate::error::StorageResult;
+
+/// Outcome of a maintenance run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// When the run started.
+    pub started_at: DateTime<Utc>,
+    /// How long the run took in milliseconds.
+    pub duration_ms: u64,
+    /// Problems reported by the integrity check (empty if the database is intact).
+    pub integrity_errors: Vec<String>,
+    /// Whether `VACUUM` ran.
+    pub vacuumed: bool,
+    /// Whether `ANALYZE` ran.
+    pub analyzed: bool,
+}
+
+impl MaintenanceReport {
+    /// Check whether the integrity check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+    }
+}
+
+/// Run a maintenance pass.
+///
+/// The integrity check runs first. `VACUUM` and `ANALYZE` only run when it
+/// passes, since rewriting a corrupt database can lose more data.
+pub async fn run_maintenance<S: Storage + ?Sized>(storage: &S) -> StorageResult<MaintenanceReport> {
+    let started_at = Utc::now();
+    let start = Instant::now();
+
+    let integrity_errors = storage.integrity_check().await?;
+    let healthy = integrity_errors.is_empty();
+    if healthy {
+        storage.vacuum().await?;
+        storage.analyze().await?;
+    }
+
+    Ok(MaintenanceReport {
+        started_at,
+        duration_ms: start.elapsed().as_millis() as u64,
+        integrity_errors,
+        vacuumed: healthy,
+        analyzed: healthy,
+    })
+}
+
+/// Latest maintenance outcome, shared between the background task and the
+/// metrics tools.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceStatus {
+    inner: Arc<RwLock<MaintenanceState>>,
+}
+
+#[derive(Debug, Default)]
+struct MaintenanceState {
+    last_report: Option<MaintenanceReport>,
+    last_error: Option<String>,
+}
+
+impl MaintenanceStatus {
+    /// Report of the latest successful run, if any.
+    pub fn last_report(&self) -> Option<MaintenanceReport> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|state| state.last_report.clone())
+    }
+
+    /// Error from the latest run, if it failed.
+    pub fn last_error(&self) -> Option<String> {
+        self.inner
+            .read()
+            .ok()
+            .and_then(|state| state.last_error.clone())
+    }
+
+    /// Record a completed run, clearing any earlier error.
+    pub fn record_report(&self, report: MaintenanceReport) {
+        if let Ok(mut state) = self.inner.write() {
+            state.last_report = Some(report);
+            state.last_error = None;
+        }
+    }
+
+    /// Record a failed run. The last successful report is kept.
+    pub fn record_error(&self, error: impl Into<String>) {
+        if let Ok(mut state) = self.inner.write() {
+            state.last_error = Some(error.into());
+        }
+    }
+}
+
+/// Time to wait from `now` until the maintenance window opens.
+///
+/// Zero when no window is configured or `now` is already inside it.
+fn delay_until_window(now: DateTime<Utc>, window: Option<MaintenanceWindow>) -> Duration {
+    let Some(window) = window else {
+        return Duration::zero();
+    };
+    if window.contains(now.hour()) {
+        return Duration::zero();
+    }
+
+    let mut opens = now
+        .date_naive()
+        .and_hms_opt(window.start_hour, 0, 0)
+        .map(|t| t.and_utc())
+        .unwrap_or(now);
+    if opens <= now {
+        opens += Duration::days(1);
+    }
+    opens - now
+}
+
+/// Spawn the background maintenance task.
+///
+/// Returns `None` without spawning anything if no maintenance interval is
+/// configured. The first run happens one interval after startup; each run
+/// that falls outside the configured window waits for it to open. Results
+/// are logged and recorded in `status`.
+pub fn spawn_maintenance_task(
+    storage: SharedStorage,
+    config: MaintenanceConfig,
+    status: MaintenanceStatus,
+) -> Option<JoinHandle<()>> {
+    let Some(interval_secs) = config.interval_secs else {
+        debug!("Database maintenance disabled, not starting maintenance task");
+        return None;
+    };
+
+    info!(
+        interval_secs,
+        window = ?config.window,
+        "Starting database maintenance task"
+    );
+
+    Some(tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+            let delay = delay_until_window(Utc::now(), config.window);
+            if let Ok(delay) = delay.to_std() {
+                tokio::time::sleep(delay).await;
+            }
+
+            match run_maintenance(storage.as_ref()).await {
+                Ok(report) => {
+                    if report.is_healthy() {
+                        info!(
+                            duration_ms = report.duration_ms,
+                            "Database maintenance completed"
+                        );
+                    } else {
+                        error!(
+                            problems = ?report.integrity_errors,
+                            "Database integrity check failed, skipped VACUUM and ANALYZE"
+                        );
+                    }
+                    status.record_report(report);
+                }
+                Err(e) => {
+                    warn!(error = %e, "Database maintenance failed");
+                    status.record_error(e.to_string());
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_delay_without_window_is_zero() {
+        assert_eq!(delay_until_window(at(14, 30), None), Duration::zero());
+    }
+
+    #[test]
+    fn test_delay_inside_window_is_zero() {
+        let window = MaintenanceWindow {
+            start_hour: 2,
+            end_hour: 5,
+        };
+        assert_eq!(
+            delay_until_window(at(3, 15), Some(window)),
+            Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_delay_waits_for_window_later_today() {
+        let window = MaintenanceWindow {
+            start_hour: 22,
+            end_hour: 4,
+        };
+        assert_eq!(
+            delay_until_window(at(20, 30), Some(window)),
+            Duration::minutes(90)
+        );
+    }
+
+    #[test]
+    fn test_delay_waits_for_window_tomorrow() {
+        let window = MaintenanceWindow {
+            start_hour: 2,
+            end_hour: 5,
+        };
+        assert_eq!(
+            delay_until_window(at(5, 0), Some(window)),
+            Duration::hours(21)
+        );
+    }
+
+    #[test]
+    fn test_status_keeps_report_after_error() {
+        let status = MaintenanceStatus::default();
+        assert!(status.last_report().is_none());
+
+        let report = MaintenanceReport {
+            started_at: at(3, 0),
+            duration_ms: 12,
+            integrity_errors: vec![],
+            vacuumed: true,
+            analyzed: true,
+        };
+        status.record_report(report.clone());
+        status.record_error("database is locked");
+        assert_eq!(status.last_report(), Some(report.clone()));
+        assert_eq!(status.last_error().as_deref(), Some("database is locked"));
+
+        status.record_report(report);
+        assert!(status.last_error().is_none());
+    }
+}
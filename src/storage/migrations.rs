@@ -0,0 +1,284 @@
+//! Schema migration status.
+//!
+//! Compares the migrations embedded in the binary with those recorded in the
+//! database's `_sqlx_migrations` table. Used by the `migrate` CLI subcommand to
+//! show what is applied and pending, and at startup to refuse a database whose
+//! schema is ahead of the binary with a clear explanation.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{StorageError, StorageResult};
+
+/// Where a migration stands relative to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    /// Recorded in the database and embedded in the binary.
+    Applied,
+    /// Embedded in the binary but not yet applied.
+    Pending,
+    /// Recorded in the database but unknown to this binary.
+    Unknown,
+    /// Applied, but the embedded SQL no longer matches the recorded checksum.
+    Modified,
+    /// Recorded as started but not completed.
+    Failed,
+}
+
+impl MigrationState {
+    /// Check whether this state prevents migrating the database.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Unknown | Self::Modified | Self::Failed)
+    }
+}
+
+impl std::fmt::Display for MigrationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Applied => write!(f, "applied"),
+            Self::Pending => write!(f, "pending"),
+            Self::Unknown => write!(f, "unknown"),
+            Self::Modified => write!(f, "modified"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// A single migration and its state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationInfo {
+    /// Migration version (the timestamp prefix of its file name).
+    pub version: i64,
+    /// Human-readable description.
+    pub description: String,
+    /// State relative to the database.
+    pub state: MigrationState,
+    /// When the migration was applied, if it was.
+    pub installed_on: Option<DateTime<Utc>>,
+    /// Whether the migration ships a down script that can revert it.
+    pub reversible: bool,
+}
+
+/// Comparison of embedded and applied migrations, ordered by version.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    /// Every migration known to the binary or the database.
+    pub migrations: Vec<MigrationInfo>,
+}
+
+/// A migration embedded in the binary.
+pub(crate) struct EmbeddedMigration {
+    pub version: i64,
+    pub description: String,
+    pub checksum: Vec<u8>,
+    pub reversible: bool,
+}
+
+/// A migration recorded in the database.
+pub(crate) struct RecordedMigration {
+    pub version: i64,
+    pub description: String,
+    pub checksum: Vec<u8>,
+    pub success: bool,
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+impl MigrationStatus {
+    /// Compare embedded migrations against those recorded in the database.
+    pub(crate) fn compare(
+        embedded: Vec<EmbeddedMigration>,
+        recorded: Vec<RecordedMigration>,
+    ) -> Self {
+        let mut migrations: Vec<MigrationInfo> = embedded
+            .iter()
+            .map(|m| {
+                let applied = recorded.iter().find(|r| r.version == m.version);
+                let state = match applied {
+                    None => MigrationState::Pending,
+                    Some(r) if !r.success => MigrationState::Failed,
+                    Some(r) if r.checksum != m.checksum => MigrationState::Modified,
+                    Some(_) => MigrationState::Applied,
+                };
+                MigrationInfo {
+                    version: m.version,
+                    description: m.description.clone(),
+                    state,
+                    installed_on: applied.and_then(|r| r.installed_on),
+                    reversible: m.reversible,
+                }
+            })
+            .collect();
+
+        migrations.extend(
+            recorded
+                .into_iter()
+                .filter(|r| !embedded.iter().any(|m| m.version == r.version))
+                .map(|r| MigrationInfo {
+                    version: r.version,
+                    description: r.description,
+                    state: if r.success {
+                        MigrationState::Unknown
+                    } else {
+                        MigrationState::Failed
+                    },
+                    installed_on: r.installed_on,
+                    reversible: false,
+                }),
+        );
+        migrations.sort_by_key(|m| m.version);
+
+        Self { migrations }
+    }
+
+    /// Migrations in the given state.
+    pub fn with_state(&self, state: MigrationState) -> Vec<&MigrationInfo> {
+        self.migrations
+            .iter()
+            .filter(|m| m.state == state)
+            .collect()
+    }
+
+    /// Migrations that would be applied by the next migration run.
+    pub fn pending(&self) -> Vec<&MigrationInfo> {
+        self.with_state(MigrationState::Pending)
+    }
+
+    /// Migrations that prevent the database from being migrated.
+    pub fn conflicts(&self) -> Vec<&MigrationInfo> {
+        self.migrations
+            .iter()
+            .filter(|m| m.state.is_conflict())
+            .collect()
+    }
+
+    /// Check whether the database has migrations this binary does not know.
+    pub fn is_ahead(&self) -> bool {
+        !self.with_state(MigrationState::Unknown).is_empty()
+    }
+
+    /// Check whether every embedded migration is applied with no conflicts.
+    pub fn is_up_to_date(&self) -> bool {
+        self.migrations
+            .iter()
+            .all(|m| m.state == MigrationState::Applied)
+    }
+
+    /// Fail with a description of every conflicting migration, if any.
+    pub fn check(&self) -> StorageResult<()> {
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = if self.is_ahead() {
+            "Database schema is ahead of this binary:".to_string()
+        } else {
+            "Database schema does not match this binary:".to_string()
+        };
+        for m in &conflicts {
+            let marker = match m.state {
+                MigrationState::Unknown => "+",
+                MigrationState::Modified => "~",
+                _ => "!",
+            };
+            message.push_str(&format!(
+                "\n  {} {} {} ({})",
+                marker, m.version, m.description, m.state
+            ));
+        }
+        if self.is_ahead() {
+            message.push_str(
+                "\nUpgrade mcp-langbase-reasoning, or restore a backup taken before these migrations.",
+            );
+        }
+
+        Err(StorageError::Migration { message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded(version: i64) -> EmbeddedMigration {
+        EmbeddedMigration {
+            version,
+            description: format!("migration {}", version),
+            checksum: vec![version as u8],
+            reversible: false,
+        }
+    }
+
+    fn recorded(version: i64) -> RecordedMigration {
+        RecordedMigration {
+            version,
+            description: format!("migration {}", version),
+            checksum: vec![version as u8],
+            success: true,
+            installed_on: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn test_compare_applied_and_pending() {
+        let status = MigrationStatus::compare(vec![embedded(1), embedded(2)], vec![recorded(1)]);
+
+        assert_eq!(status.migrations.len(), 2);
+        assert_eq!(status.migrations[0].state, MigrationState::Applied);
+        assert!(status.migrations[0].installed_on.is_some());
+        assert_eq!(status.pending().len(), 1);
+        assert_eq!(status.pending()[0].version, 2);
+        assert!(!status.is_up_to_date());
+        assert!(status.check().is_ok());
+    }
+
+    #[test]
+    fn test_compare_up_to_date() {
+        let status = MigrationStatus::compare(vec![embedded(1)], vec![recorded(1)]);
+        assert!(status.is_up_to_date());
+        assert!(!status.is_ahead());
+    }
+
+    #[test]
+    fn test_compare_schema_ahead() {
+        let mut newer = recorded(3);
+        newer.description = "workspaces".to_string();
+        let status = MigrationStatus::compare(vec![embedded(1)], vec![recorded(1), newer]);
+
+        assert!(status.is_ahead());
+        assert_eq!(status.migrations[1].state, MigrationState::Unknown);
+
+        let err = status.check().unwrap_err().to_string();
+        assert!(err.contains("ahead of this binary"));
+        assert!(err.contains("+ 3 workspaces (unknown)"));
+    }
+
+    #[test]
+    fn test_compare_modified_and_failed() {
+        let mut modified = recorded(1);
+        modified.checksum = vec![0xff];
+        let mut failed = recorded(2);
+        failed.success = false;
+        let status =
+            MigrationStatus::compare(vec![embedded(1), embedded(2)], vec![modified, failed]);
+
+        assert_eq!(status.migrations[0].state, MigrationState::Modified);
+        assert_eq!(status.migrations[1].state, MigrationState::Failed);
+        assert_eq!(status.conflicts().len(), 2);
+        assert!(!status.is_ahead());
+
+        let err = status.check().unwrap_err().to_string();
+        assert!(err.contains("does not match this binary"));
+        assert!(err.contains("~ 1 migration 1 (modified)"));
+        assert!(err.contains("! 2 migration 2 (failed)"));
+    }
+
+    #[test]
+    fn test_migration_state_display() {
+        assert_eq!(MigrationState::Pending.to_string(), "pending");
+        assert_eq!(MigrationState::Unknown.to_string(), "unknown");
+        assert!(MigrationState::Failed.is_conflict());
+        assert!(!MigrationState::Applied.is_conflict());
+    }
+}
@@ -3,24 +3,53 @@
 //! This module provides SQLite-based storage for sessions, thoughts, branches,
 //! checkpoints, graph nodes, and other reasoning artifacts.
 
+mod audit;
+mod backup;
+mod cache;
+mod knowledge;
+mod maintenance;
+mod migrations;
+mod retention;
 mod sqlite;
+mod workspace;
 
 #[cfg(test)]
 #[path = "types_tests.rs"]
 mod types_tests;
 
+pub use audit::AuditLogger;
+pub use backup::BackupReport;
+pub use cache::{CacheStats, CachedStorage};
+pub use knowledge::KnowledgeStore;
+pub use maintenance::{
+    run_maintenance, spawn_maintenance_task, MaintenanceReport, MaintenanceStatus,
+};
+pub use migrations::{MigrationInfo, MigrationState, MigrationStatus};
+pub use retention::{prune, spawn_retention_task, PruneReport};
 pub use sqlite::{
     get_record_skip_count, get_timestamp_reconstruction_count, reset_record_skip_count,
     reset_timestamp_reconstruction_count, SqliteStorage,
 };
+pub use workspace::WorkspaceStorage;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::config::DEFAULT_WORKSPACE_ID;
 use crate::error::StorageResult;
 
+/// Shared, dynamically dispatched storage backend.
+///
+/// Modes and server state hold storage through this type so that embedders
+/// can supply their own [`Storage`] implementation. `SqliteStorage` converts
+/// into it with `.into()`.
+pub type SharedStorage = Arc<dyn Storage>;
+
 /// A reasoning session context that groups related thoughts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -37,6 +66,21 @@ pub struct Session {
     /// Active branch for tree mode.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_branch_id: Option<String>,
+    /// Tags for organizing related sessions (normalized, sorted).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// When the session was moved to the trash (`None` if not deleted).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Workspace the session belongs to.
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
+    /// Session this one was forked from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+    /// Langbase thread holding the session's conversation history, if bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
 }
 
 /// A single reasoning step or thought within a session.
@@ -63,6 +107,28 @@ pub struct Thought {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// A superseded version of a revised thought.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThoughtRevision {
+    /// Unique revision identifier.
+    pub id: String,
+    /// The revised thought.
+    pub thought_id: String,
+    /// Session the thought belongs to.
+    pub session_id: String,
+    /// Version number; 1 is the thought as first written.
+    pub version: u32,
+    /// Content of this version.
+    pub content: String,
+    /// Confidence of this version.
+    pub confidence: f64,
+    /// Why this version was replaced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When this version was replaced.
+    pub revised_at: DateTime<Utc>,
+}
+
 /// A reasoning branch in tree mode, representing an exploration path.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Branch {
@@ -207,7 +273,7 @@ pub struct Checkpoint {
 }
 
 /// Graph node for Graph-of-Thoughts reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GraphNode {
     /// Unique node identifier.
     pub id: String,
@@ -234,7 +300,7 @@ pub struct GraphNode {
 }
 
 /// Type of graph node in Graph-of-Thoughts reasoning.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum NodeType {
     /// A standard reasoning thought.
@@ -321,6 +387,8 @@ pub enum EdgeType {
     Supports,
     /// Source node contradicts target node's conclusion.
     Contradicts,
+    /// Source node cannot start until target node is done.
+    DependsOn,
 }
 
 impl std::fmt::Display for EdgeType {
@@ -331,6 +399,7 @@ impl std::fmt::Display for EdgeType {
             EdgeType::Aggregates => write!(f, "aggregates"),
             EdgeType::Supports => write!(f, "supports"),
             EdgeType::Contradicts => write!(f, "contradicts"),
+            EdgeType::DependsOn => write!(f, "depends_on"),
         }
     }
 }
@@ -345,6 +414,7 @@ impl std::str::FromStr for EdgeType {
             "aggregates" => Ok(EdgeType::Aggregates),
             "supports" => Ok(EdgeType::Supports),
             "contradicts" => Ok(EdgeType::Contradicts),
+            "depends_on" => Ok(EdgeType::DependsOn),
             _ => Err(format!("Unknown edge type: {}", s)),
         }
     }
@@ -405,8 +475,8 @@ impl std::str::FromStr for SnapshotType {
     }
 }
 
-/// Detection type for bias and fallacy analysis.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Detection type for bias, fallacy and calculation analysis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DetectionType {
     /// Cognitive bias detection.
@@ -414,6 +484,8 @@ pub enum DetectionType {
     Bias,
     /// Logical fallacy detection.
     Fallacy,
+    /// Stated result that does not match its recomputed formula.
+    Calculation,
 }
 
 impl std::fmt::Display for DetectionType {
@@ -421,6 +493,7 @@ impl std::fmt::Display for DetectionType {
         match self {
             DetectionType::Bias => write!(f, "bias"),
             DetectionType::Fallacy => write!(f, "fallacy"),
+            DetectionType::Calculation => write!(f, "calculation"),
         }
     }
 }
@@ -432,13 +505,14 @@ impl std::str::FromStr for DetectionType {
         match s.to_lowercase().as_str() {
             "bias" => Ok(DetectionType::Bias),
             "fallacy" => Ok(DetectionType::Fallacy),
+            "calculation" => Ok(DetectionType::Calculation),
             _ => Err(format!("Unknown detection type: {}", s)),
         }
     }
 }
 
 /// Detection result from bias or fallacy analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Detection {
     /// Unique detection identifier.
     pub id: String,
@@ -462,6 +536,9 @@ pub struct Detection {
     pub created_at: DateTime<Utc>,
     /// Optional metadata.
     pub metadata: Option<serde_json::Value>,
+    /// Workspace the detection was recorded in.
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
 }
 
 impl GraphNode {
@@ -523,6 +600,12 @@ impl GraphNode {
         self.is_active = false;
         self
     }
+
+    /// Set metadata
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
 }
 
 impl GraphEdge {
@@ -555,6 +638,89 @@ impl GraphEdge {
         self.weight = weight.clamp(0.0, 1.0);
         self
     }
+
+    /// Set metadata
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Head-to-head judgment between two graph nodes, from which pairwise
+/// rankings are derived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphComparison {
+    /// Unique comparison identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// Common parent of the compared nodes, when they are siblings.
+    pub parent_node_id: Option<String>,
+    /// Node shown first to the judge.
+    pub node_a_id: String,
+    /// Node shown second to the judge.
+    pub node_b_id: String,
+    /// The better node, or `None` for a tie.
+    pub winner_id: Option<String>,
+    /// The judge's confidence in the outcome (0.0-1.0).
+    pub confidence: f64,
+    /// Why the judge preferred the winner.
+    pub rationale: String,
+    /// Comparison round, starting at 1.
+    pub round: i32,
+    /// When the comparison was made.
+    pub created_at: DateTime<Utc>,
+}
+
+impl GraphComparison {
+    /// Create a new comparison; a `winner_id` that is neither node counts
+    /// as a tie
+    pub fn new(
+        session_id: impl Into<String>,
+        node_a_id: impl Into<String>,
+        node_b_id: impl Into<String>,
+        winner_id: Option<String>,
+        confidence: f64,
+    ) -> Self {
+        let node_a_id = node_a_id.into();
+        let node_b_id = node_b_id.into();
+        let winner_id = winner_id.filter(|w| *w == node_a_id || *w == node_b_id);
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            parent_node_id: None,
+            node_a_id,
+            node_b_id,
+            winner_id,
+            confidence: confidence.clamp(0.0, 1.0),
+            rationale: String::new(),
+            round: 1,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the common parent node
+    pub fn with_parent(mut self, parent_node_id: impl Into<String>) -> Self {
+        self.parent_node_id = Some(parent_node_id.into());
+        self
+    }
+
+    /// Set the rationale
+    pub fn with_rationale(mut self, rationale: impl Into<String>) -> Self {
+        self.rationale = rationale.into();
+        self
+    }
+
+    /// Set the round
+    pub fn with_round(mut self, round: i32) -> Self {
+        self.round = round;
+        self
+    }
+
+    /// Whether the comparison is between `a` and `b`, in either order
+    pub fn is_between(&self, a: &str, b: &str) -> bool {
+        (self.node_a_id == a && self.node_b_id == b) || (self.node_a_id == b && self.node_b_id == a)
+    }
 }
 
 impl StateSnapshot {
@@ -617,6 +783,12 @@ pub struct Invocation {
     pub fallback_used: bool,
     /// Type of fallback if used (parse_error, api_unavailable, local_calculation).
     pub fallback_type: Option<String>,
+    /// Workspace the invocation was made in.
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
+    /// Whether the pipe response came from the response cache.
+    #[serde(default)]
+    pub cache_hit: bool,
 }
 
 // ============================================================================
@@ -700,9 +872,15 @@ pub struct MetricsFilter {
     /// Only include successful (true) or failed (false) calls.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub success_only: Option<bool>,
+    /// Only include calls made in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
     /// Limit number of results.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
+    /// Skip this many results (for pagination).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
 }
 
 impl MetricsFilter {
@@ -753,498 +931,1848 @@ impl MetricsFilter {
         self
     }
 
+    /// Only include calls made in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
+        self
+    }
+
     /// Limit number of results.
     pub fn with_limit(mut self, limit: u32) -> Self {
         self.limit = Some(limit);
         self
     }
-}
-
-impl Session {
-    /// Create a new session with the given mode
-    pub fn new(mode: impl Into<String>) -> Self {
-        let now = Utc::now();
-        Self {
-            id: Uuid::new_v4().to_string(),
-            mode: mode.into(),
-            created_at: now,
-            updated_at: now,
-            metadata: None,
-            active_branch_id: None,
-        }
-    }
 
-    /// Set the active branch
-    pub fn with_active_branch(mut self, branch_id: impl Into<String>) -> Self {
-        self.active_branch_id = Some(branch_id.into());
+    /// Skip this many results.
+    pub fn with_offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
         self
     }
 }
 
-impl Thought {
-    /// Create a new thought in a session
-    pub fn new(
-        session_id: impl Into<String>,
-        content: impl Into<String>,
-        mode: impl Into<String>,
-    ) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            content: content.into(),
-            confidence: 0.8,
-            mode: mode.into(),
-            parent_id: None,
-            branch_id: None,
-            created_at: Utc::now(),
-            metadata: None,
-        }
-    }
+/// Result of [`Storage::fork_session`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionFork {
+    /// The new session.
+    pub session: Session,
+    /// Number of thoughts copied.
+    pub thoughts: u64,
+    /// Number of earlier thought versions copied.
+    pub thought_revisions: u64,
+    /// Number of branches copied.
+    pub branches: u64,
+    /// Number of cross-references between branches copied.
+    pub cross_refs: u64,
+    /// Number of checkpoints copied.
+    pub checkpoints: u64,
+    /// Number of graph nodes copied.
+    pub graph_nodes: u64,
+    /// Number of graph edges copied.
+    pub graph_edges: u64,
+}
 
-    /// Set the confidence level
-    pub fn with_confidence(mut self, confidence: f64) -> Self {
-        self.confidence = confidence.clamp(0.0, 1.0);
-        self
+/// Filter options for listing sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionFilter {
+    /// Filter by reasoning mode (exact match).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Only sessions created at or after this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only sessions created before this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only sessions carrying this tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Only sessions with (true) or without (false) bias/fallacy detections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_detections: Option<bool>,
+    /// List sessions in the trash instead of live sessions.
+    #[serde(default)]
+    pub in_trash: bool,
+    /// Only sessions in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Limit number of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Skip this many results (for pagination).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+}
+
+impl SessionFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Set the parent thought
-    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
-        self.parent_id = Some(parent_id.into());
+    /// Filter by reasoning mode.
+    pub fn with_mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = Some(mode.into());
         self
     }
 
-    /// Set the branch ID for tree mode
-    pub fn with_branch(mut self, branch_id: impl Into<String>) -> Self {
-        self.branch_id = Some(branch_id.into());
+    /// Only sessions created at or after this time.
+    pub fn created_after(mut self, time: DateTime<Utc>) -> Self {
+        self.created_after = Some(time);
         self
     }
 
-    /// Set metadata
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Only sessions created before this time.
+    pub fn created_before(mut self, time: DateTime<Utc>) -> Self {
+        self.created_before = Some(time);
         self
     }
-}
 
-impl Branch {
-    /// Create a new branch in a session
-    pub fn new(session_id: impl Into<String>) -> Self {
-        let now = Utc::now();
-        Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            name: None,
-            parent_branch_id: None,
-            priority: 1.0,
-            confidence: 0.8,
-            state: BranchState::Active,
-            created_at: now,
-            updated_at: now,
-            metadata: None,
-        }
+    /// Only sessions carrying this tag.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
     }
 
-    /// Set the branch name
-    pub fn with_name(mut self, name: impl Into<String>) -> Self {
-        self.name = Some(name.into());
+    /// Only sessions with or without detections.
+    pub fn with_detections(mut self, has_detections: bool) -> Self {
+        self.has_detections = Some(has_detections);
         self
     }
 
-    /// Set the parent branch
-    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
-        self.parent_branch_id = Some(parent_id.into());
+    /// List sessions in the trash instead of live sessions.
+    pub fn in_trash(mut self) -> Self {
+        self.in_trash = true;
         self
     }
 
-    /// Set the priority
-    pub fn with_priority(mut self, priority: f64) -> Self {
-        self.priority = priority;
+    /// Only sessions in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
         self
     }
 
-    /// Set the confidence
-    pub fn with_confidence(mut self, confidence: f64) -> Self {
-        self.confidence = confidence.clamp(0.0, 1.0);
+    /// Limit number of results.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
         self
     }
 
-    /// Set the state
-    pub fn with_state(mut self, state: BranchState) -> Self {
-        self.state = state;
+    /// Skip this many results.
+    pub fn with_offset(mut self, offset: u32) -> Self {
+        self.offset = Some(offset);
         self
     }
 }
 
-impl CrossRef {
-    /// Create a new cross-reference between branches
-    pub fn new(
-        from_branch_id: impl Into<String>,
-        to_branch_id: impl Into<String>,
-        ref_type: CrossRefType,
-    ) -> Self {
+/// Limit/offset pagination parameters for list queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pagination {
+    /// Maximum number of items to return.
+    pub limit: u32,
+    /// Number of items to skip.
+    pub offset: u32,
+}
+
+impl Pagination {
+    /// Create pagination parameters.
+    ///
+    /// A `limit` of 0 is raised to 1, so a client following cursors always
+    /// makes progress.
+    pub fn new(limit: u32, offset: u32) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
-            from_branch_id: from_branch_id.into(),
-            to_branch_id: to_branch_id.into(),
-            ref_type,
-            reason: None,
-            strength: 1.0,
-            created_at: Utc::now(),
+            limit: limit.max(1),
+            offset,
         }
     }
 
-    /// Set the reason for the cross-reference
-    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
-        self.reason = Some(reason.into());
-        self
+    /// The first `limit` items.
+    pub fn first(limit: u32) -> Self {
+        Self::new(limit, 0)
     }
 
-    /// Set the strength of the cross-reference
-    pub fn with_strength(mut self, strength: f64) -> Self {
-        self.strength = strength.clamp(0.0, 1.0);
-        self
+    /// Resume at `cursor`, a [`Page::next_cursor`] of an earlier page.
+    ///
+    /// Returns `None` if `cursor` was not produced by this server.
+    pub fn from_cursor(limit: u32, cursor: &str) -> Option<Self> {
+        let offset = cursor.strip_prefix(CURSOR_PREFIX)?.parse().ok()?;
+        Some(Self::new(limit, offset))
     }
 }
 
-impl Checkpoint {
-    /// Create a new checkpoint
-    pub fn new(
-        session_id: impl Into<String>,
-        name: impl Into<String>,
-        snapshot: serde_json::Value,
-    ) -> Self {
+/// Prefix of page cursors, so they are not mistaken for plain offsets.
+const CURSOR_PREFIX: &str = "offset:";
+
+/// A page of results from a paginated query.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Page<T> {
+    /// Items on this page.
+    pub items: Vec<T>,
+    /// Offset of the first item on this page.
+    pub offset: u32,
+    /// Whether more items exist after this page.
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Build a page from rows fetched with `limit + 1`, dropping the extra row.
+    pub fn from_overfetch(mut items: Vec<T>, pagination: Pagination) -> Self {
+        let has_more = items.len() > pagination.limit as usize;
+        items.truncate(pagination.limit as usize);
         Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            branch_id: None,
-            name: name.into(),
-            description: None,
-            snapshot,
-            created_at: Utc::now(),
+            items,
+            offset: pagination.offset,
+            has_more,
         }
     }
 
-    /// Set the branch ID
-    pub fn with_branch(mut self, branch_id: impl Into<String>) -> Self {
-        self.branch_id = Some(branch_id.into());
-        self
-    }
-
-    /// Set the description
-    pub fn with_description(mut self, description: impl Into<String>) -> Self {
-        self.description = Some(description.into());
-        self
-    }
-}
-
-impl Invocation {
-    /// Create a new invocation log entry
-    pub fn new(tool_name: impl Into<String>, input: serde_json::Value) -> Self {
+    /// Build a page from every item of a list held in memory.
+    pub fn from_all(items: Vec<T>, pagination: Pagination) -> Self {
+        let end = pagination.offset as usize + pagination.limit as usize;
+        let has_more = items.len() > end;
         Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: None,
-            tool_name: tool_name.into(),
-            input,
-            output: None,
-            pipe_name: None,
-            latency_ms: None,
-            success: true,
-            error: None,
-            created_at: Utc::now(),
-            fallback_used: false,
-            fallback_type: None,
+            items: items
+                .into_iter()
+                .skip(pagination.offset as usize)
+                .take(pagination.limit as usize)
+                .collect(),
+            offset: pagination.offset,
+            has_more,
         }
     }
 
-    /// Set the session ID
-    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
-        self.session_id = Some(session_id.into());
-        self
+    /// Offset to request the next page, if there is one.
+    pub fn next_offset(&self) -> Option<u32> {
+        self.has_more
+            .then(|| self.offset.saturating_add(self.items.len() as u32))
     }
 
-    /// Set the pipe name
-    pub fn with_pipe(mut self, pipe_name: impl Into<String>) -> Self {
-        self.pipe_name = Some(pipe_name.into());
-        self
+    /// Opaque cursor to request the next page, if there is one.
+    pub fn next_cursor(&self) -> Option<String> {
+        self.next_offset()
+            .map(|offset| format!("{}{}", CURSOR_PREFIX, offset))
     }
+}
 
-    /// Mark as successful with output
-    pub fn success(mut self, output: serde_json::Value, latency_ms: i64) -> Self {
-        self.success = true;
-        self.output = Some(output);
-        self.latency_ms = Some(latency_ms);
-        self
+/// Filter options for full-text thought search.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThoughtSearchFilter {
+    /// Restrict results to a single session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Restrict results to thoughts produced by this reasoning mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Restrict results to sessions in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Limit number of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl ThoughtSearchFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Mark as failed with error
-    pub fn failure(mut self, error: impl Into<String>, latency_ms: i64) -> Self {
-        self.success = false;
-        self.error = Some(error.into());
-        self.latency_ms = Some(latency_ms);
+    /// Filter by session ID.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Set latency separately
-    pub fn with_latency(mut self, latency_ms: i64) -> Self {
-        self.latency_ms = Some(latency_ms);
+    /// Filter by reasoning mode.
+    pub fn with_mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = Some(mode.into());
         self
     }
 
-    /// Mark as successful (simple version without output)
-    pub fn mark_success(mut self) -> Self {
-        self.success = true;
+    /// Restrict results to a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
         self
     }
 
-    /// Mark as failed (simple version)
-    pub fn mark_failed(mut self, error: impl Into<String>) -> Self {
-        self.success = false;
-        self.error = Some(error.into());
+    /// Limit number of results.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
         self
     }
+}
 
-    /// Mark that a fallback was used
-    pub fn with_fallback(mut self, fallback_type: impl Into<String>) -> Self {
-        self.fallback_used = true;
-        self.fallback_type = Some(fallback_type.into());
+/// A thought matched by full-text search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThoughtSearchHit {
+    /// The matching thought.
+    pub thought: Thought,
+    /// Excerpt of the content around the match, with matched terms in `[...]`.
+    pub snippet: String,
+    /// Relevance score (higher is more relevant).
+    pub score: f64,
+}
+
+/// Kind of record an embedding was computed for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingSource {
+    /// A thought from linear/tree/divergent/reflection reasoning.
+    #[default]
+    Thought,
+    /// A Graph-of-Thoughts node.
+    GraphNode,
+}
+
+impl std::fmt::Display for EmbeddingSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddingSource::Thought => write!(f, "thought"),
+            EmbeddingSource::GraphNode => write!(f, "graph_node"),
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "thought" => Ok(EmbeddingSource::Thought),
+            "graph_node" => Ok(EmbeddingSource::GraphNode),
+            _ => Err(format!("Unknown embedding source: {}", s)),
+        }
+    }
+}
+
+/// Vector embedding of a thought or graph node's content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    /// ID of the embedded thought or graph node.
+    pub source_id: String,
+    /// Kind of record the embedding belongs to.
+    pub source_type: EmbeddingSource,
+    /// Session containing the embedded record.
+    pub session_id: String,
+    /// Model that produced the vector.
+    pub model: String,
+    /// The embedding vector.
+    pub vector: Vec<f32>,
+    /// When the embedding was computed.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Content of a thought or graph node that has no embedding yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCandidate {
+    /// ID of the thought or graph node.
+    pub source_id: String,
+    /// Kind of record.
+    pub source_type: EmbeddingSource,
+    /// Session containing the record.
+    pub session_id: String,
+    /// Text to embed.
+    pub content: String,
+}
+
+/// Filter for semantic similarity search.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimilarityFilter {
+    /// Only match records in this session.
+    pub session_id: Option<String>,
+    /// Skip records in this session (e.g. the session asking the question).
+    pub exclude_session_id: Option<String>,
+    /// Only match this kind of record.
+    pub source_type: Option<EmbeddingSource>,
+    /// Only match records in sessions of this workspace.
+    pub workspace_id: Option<String>,
+    /// Minimum cosine similarity for a match.
+    pub min_score: Option<f64>,
+    /// Maximum number of matches.
+    pub limit: Option<u32>,
+}
+
+impl SimilarityFilter {
+    /// Create an empty filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match records in a session
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Mark fallback with specific type constants
-    pub fn with_parse_error_fallback(self) -> Self {
-        self.with_fallback("parse_error")
+    /// Skip records in a session
+    pub fn excluding_session(mut self, session_id: impl Into<String>) -> Self {
+        self.exclude_session_id = Some(session_id.into());
+        self
     }
 
-    /// Mark fallback due to API unavailability
-    pub fn with_api_unavailable_fallback(self) -> Self {
-        self.with_fallback("api_unavailable")
+    /// Only match a kind of record
+    pub fn with_source_type(mut self, source_type: EmbeddingSource) -> Self {
+        self.source_type = Some(source_type);
+        self
     }
 
-    /// Mark fallback due to local calculation
-    pub fn with_local_calculation_fallback(self) -> Self {
-        self.with_fallback("local_calculation")
+    /// Only match records in a workspace
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Require a minimum similarity score
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Limit the number of matches
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
     }
 }
 
-impl Detection {
-    /// Create a new detection result
+/// A thought or graph node matched by semantic similarity search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarMatch {
+    /// ID of the matched thought or graph node.
+    pub source_id: String,
+    /// Kind of record matched.
+    pub source_type: EmbeddingSource,
+    /// Session containing the match.
+    pub session_id: String,
+    /// Content of the match.
+    pub content: String,
+    /// Cosine similarity to the query (-1.0 to 1.0, higher is more similar).
+    pub score: f64,
+}
+
+/// Cosine similarity between two vectors.
+///
+/// Returns 0.0 if the vectors differ in length or either has zero magnitude.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(*x), f64::from(*y));
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+impl Embedding {
+    /// Create a new embedding computed now
     pub fn new(
-        detection_type: DetectionType,
-        detected_issue: impl Into<String>,
-        severity: i32,
-        confidence: f64,
-        explanation: impl Into<String>,
+        source_id: impl Into<String>,
+        source_type: EmbeddingSource,
+        session_id: impl Into<String>,
+        model: impl Into<String>,
+        vector: Vec<f32>,
     ) -> Self {
         Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: None,
-            thought_id: None,
-            detection_type,
-            detected_issue: detected_issue.into(),
-            severity: severity.clamp(1, 5),
-            confidence: confidence.clamp(0.0, 1.0),
-            explanation: explanation.into(),
-            remediation: None,
+            source_id: source_id.into(),
+            source_type,
+            session_id: session_id.into(),
+            model: model.into(),
+            vector,
             created_at: Utc::now(),
+        }
+    }
+}
+
+impl Session {
+    /// Create a new session with the given mode
+    pub fn new(mode: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            mode: mode.into(),
+            created_at: now,
+            updated_at: now,
             metadata: None,
+            active_branch_id: None,
+            tags: Vec::new(),
+            deleted_at: None,
+            workspace_id: default_workspace_id(),
+            forked_from: None,
+            thread_id: None,
         }
     }
 
-    /// Set the session ID
-    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
-        self.session_id = Some(session_id.into());
-        self
+    /// Check whether the session is in the trash
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
     }
 
-    /// Set the thought ID
-    pub fn with_thought(mut self, thought_id: impl Into<String>) -> Self {
-        self.thought_id = Some(thought_id.into());
+    /// Set the workspace
+    pub fn with_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = workspace_id.into();
         self
     }
 
-    /// Set the remediation suggestion
-    pub fn with_remediation(mut self, remediation: impl Into<String>) -> Self {
-        self.remediation = Some(remediation.into());
+    /// Set the active branch
+    pub fn with_active_branch(mut self, branch_id: impl Into<String>) -> Self {
+        self.active_branch_id = Some(branch_id.into());
         self
     }
 
-    /// Set metadata
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Add a tag (normalized with [`normalize_tag`]; empty tags are ignored)
+    pub fn with_tag(mut self, tag: impl AsRef<str>) -> Self {
+        if let Some(tag) = normalize_tag(tag.as_ref()) {
+            if let Err(pos) = self.tags.binary_search(&tag) {
+                self.tags.insert(pos, tag);
+            }
+        }
         self
     }
 }
 
-// ============================================================================
-// Decision Framework Storage Types
-// ============================================================================
-
-/// Stored decision analysis result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Decision {
-    /// Unique decision identifier.
-    pub id: String,
-    /// Parent session ID.
-    pub session_id: String,
-    /// The decision question.
-    pub question: String,
-    /// Available options (JSON array).
-    pub options: Vec<String>,
-    /// Evaluation criteria with weights (JSON array).
-    pub criteria: Option<Vec<StoredCriterion>>,
-    /// Decision method ('weighted_sum', 'pairwise', 'topsis').
-    pub method: String,
-    /// Recommendation (JSON object).
-    pub recommendation: serde_json::Value,
-    /// Option scores (JSON array).
-    pub scores: serde_json::Value,
-    /// Sensitivity analysis results.
-    pub sensitivity_analysis: Option<serde_json::Value>,
-    /// Trade-offs between options.
-    pub trade_offs: Option<serde_json::Value>,
-    /// Constraint satisfaction per option.
-    pub constraints_satisfied: Option<serde_json::Value>,
-    /// When the decision was created.
-    pub created_at: DateTime<Utc>,
-    /// Optional metadata.
-    pub metadata: Option<serde_json::Value>,
+fn default_workspace_id() -> String {
+    DEFAULT_WORKSPACE_ID.to_string()
 }
 
-/// Stored criterion for decision analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StoredCriterion {
-    /// Criterion name.
-    pub name: String,
-    /// Weight (0.0-1.0).
-    pub weight: f64,
-    /// Optional description.
-    pub description: Option<String>,
+/// Normalize a session tag: trimmed and lowercased.
+///
+/// Returns `None` for tags that are empty after trimming.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag.to_lowercase())
+    }
 }
 
-impl Decision {
-    /// Create a new decision.
+impl Thought {
+    /// Create a new thought in a session
     pub fn new(
         session_id: impl Into<String>,
-        question: impl Into<String>,
-        options: Vec<String>,
-        method: impl Into<String>,
-        recommendation: serde_json::Value,
-        scores: serde_json::Value,
+        content: impl Into<String>,
+        mode: impl Into<String>,
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             session_id: session_id.into(),
-            question: question.into(),
-            options,
-            criteria: None,
-            method: method.into(),
-            recommendation,
-            scores,
-            sensitivity_analysis: None,
-            trade_offs: None,
-            constraints_satisfied: None,
+            content: content.into(),
+            confidence: 0.8,
+            mode: mode.into(),
+            parent_id: None,
+            branch_id: None,
             created_at: Utc::now(),
             metadata: None,
         }
     }
 
-    /// Set criteria.
-    pub fn with_criteria(mut self, criteria: Vec<StoredCriterion>) -> Self {
-        self.criteria = Some(criteria);
-        self
-    }
-
-    /// Set sensitivity analysis.
-    pub fn with_sensitivity(mut self, analysis: serde_json::Value) -> Self {
-        self.sensitivity_analysis = Some(analysis);
+    /// Set the confidence level
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence.clamp(0.0, 1.0);
         self
     }
 
-    /// Set trade-offs.
-    pub fn with_trade_offs(mut self, trade_offs: serde_json::Value) -> Self {
-        self.trade_offs = Some(trade_offs);
+    /// Set the parent thought
+    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_id = Some(parent_id.into());
         self
     }
 
-    /// Set constraints satisfied.
-    pub fn with_constraints(mut self, satisfied: serde_json::Value) -> Self {
-        self.constraints_satisfied = Some(satisfied);
+    /// Set the branch ID for tree mode
+    pub fn with_branch(mut self, branch_id: impl Into<String>) -> Self {
+        self.branch_id = Some(branch_id.into());
         self
     }
 
-    /// Set metadata.
+    /// Set metadata
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = Some(metadata);
         self
     }
 }
 
-/// Stored perspective analysis result.
+impl Branch {
+    /// Create a new branch in a session
+    pub fn new(session_id: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            name: None,
+            parent_branch_id: None,
+            priority: 1.0,
+            confidence: 0.8,
+            state: BranchState::Active,
+            created_at: now,
+            updated_at: now,
+            metadata: None,
+        }
+    }
+
+    /// Set the branch name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the parent branch
+    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_branch_id = Some(parent_id.into());
+        self
+    }
+
+    /// Set the priority
+    pub fn with_priority(mut self, priority: f64) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the confidence
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the state
+    pub fn with_state(mut self, state: BranchState) -> Self {
+        self.state = state;
+        self
+    }
+}
+
+impl CrossRef {
+    /// Create a new cross-reference between branches
+    pub fn new(
+        from_branch_id: impl Into<String>,
+        to_branch_id: impl Into<String>,
+        ref_type: CrossRefType,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            from_branch_id: from_branch_id.into(),
+            to_branch_id: to_branch_id.into(),
+            ref_type,
+            reason: None,
+            strength: 1.0,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the reason for the cross-reference
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Set the strength of the cross-reference
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+}
+
+impl Checkpoint {
+    /// Create a new checkpoint
+    pub fn new(
+        session_id: impl Into<String>,
+        name: impl Into<String>,
+        snapshot: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            branch_id: None,
+            name: name.into(),
+            description: None,
+            snapshot,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Set the branch ID
+    pub fn with_branch(mut self, branch_id: impl Into<String>) -> Self {
+        self.branch_id = Some(branch_id.into());
+        self
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl Invocation {
+    /// Create a new invocation log entry
+    pub fn new(tool_name: impl Into<String>, input: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: None,
+            tool_name: tool_name.into(),
+            input,
+            output: None,
+            pipe_name: None,
+            latency_ms: None,
+            success: true,
+            error: None,
+            created_at: Utc::now(),
+            fallback_used: false,
+            fallback_type: None,
+            workspace_id: default_workspace_id(),
+            cache_hit: false,
+        }
+    }
+
+    /// Set the session ID
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set the pipe name
+    pub fn with_pipe(mut self, pipe_name: impl Into<String>) -> Self {
+        self.pipe_name = Some(pipe_name.into());
+        self
+    }
+
+    /// Mark as successful with output
+    pub fn success(mut self, output: serde_json::Value, latency_ms: i64) -> Self {
+        self.success = true;
+        self.output = Some(output);
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+
+    /// Mark as failed with error
+    pub fn failure(mut self, error: impl Into<String>, latency_ms: i64) -> Self {
+        self.success = false;
+        self.error = Some(error.into());
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+
+    /// Set latency separately
+    pub fn with_latency(mut self, latency_ms: i64) -> Self {
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+
+    /// Mark as successful (simple version without output)
+    pub fn mark_success(mut self) -> Self {
+        self.success = true;
+        self
+    }
+
+    /// Mark as failed (simple version)
+    pub fn mark_failed(mut self, error: impl Into<String>) -> Self {
+        self.success = false;
+        self.error = Some(error.into());
+        self
+    }
+
+    /// Mark that a fallback was used
+    pub fn with_fallback(mut self, fallback_type: impl Into<String>) -> Self {
+        self.fallback_used = true;
+        self.fallback_type = Some(fallback_type.into());
+        self
+    }
+
+    /// Mark fallback with specific type constants
+    pub fn with_parse_error_fallback(self) -> Self {
+        self.with_fallback("parse_error")
+    }
+
+    /// Mark fallback due to API unavailability
+    pub fn with_api_unavailable_fallback(self) -> Self {
+        self.with_fallback("api_unavailable")
+    }
+
+    /// Mark fallback due to local calculation
+    pub fn with_local_calculation_fallback(self) -> Self {
+        self.with_fallback("local_calculation")
+    }
+
+    /// Record whether the pipe response was served from the response cache
+    pub fn with_cache_hit(mut self, cache_hit: bool) -> Self {
+        self.cache_hit = cache_hit;
+        self
+    }
+
+    /// Record the fallback that answered the pipe call, if any
+    pub fn with_pipe_fallback(self, fallback: Option<&str>) -> Self {
+        match fallback {
+            Some(fallback_type) => self.with_fallback(fallback_type),
+            None => self,
+        }
+    }
+}
+
+impl Detection {
+    /// Create a new detection result
+    pub fn new(
+        detection_type: DetectionType,
+        detected_issue: impl Into<String>,
+        severity: i32,
+        confidence: f64,
+        explanation: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: None,
+            thought_id: None,
+            detection_type,
+            detected_issue: detected_issue.into(),
+            severity: severity.clamp(1, 5),
+            confidence: confidence.clamp(0.0, 1.0),
+            explanation: explanation.into(),
+            remediation: None,
+            created_at: Utc::now(),
+            metadata: None,
+            workspace_id: default_workspace_id(),
+        }
+    }
+
+    /// Set the session ID
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Set the thought ID
+    pub fn with_thought(mut self, thought_id: impl Into<String>) -> Self {
+        self.thought_id = Some(thought_id.into());
+        self
+    }
+
+    /// Set the remediation suggestion
+    pub fn with_remediation(mut self, remediation: impl Into<String>) -> Self {
+        self.remediation = Some(remediation.into());
+        self
+    }
+
+    /// Set metadata
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+// ============================================================================
+// Decision Framework Storage Types
+// ============================================================================
+
+/// Stored decision analysis result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    /// Unique decision identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// The decision question.
+    pub question: String,
+    /// Available options (JSON array).
+    pub options: Vec<String>,
+    /// Evaluation criteria with weights (JSON array).
+    pub criteria: Option<Vec<StoredCriterion>>,
+    /// Decision method ('weighted_sum', 'pairwise', 'topsis', 'rice', 'moscow').
+    pub method: String,
+    /// Recommendation (JSON object).
+    pub recommendation: serde_json::Value,
+    /// Option scores (JSON array).
+    pub scores: serde_json::Value,
+    /// Sensitivity analysis results.
+    pub sensitivity_analysis: Option<serde_json::Value>,
+    /// Trade-offs between options.
+    pub trade_offs: Option<serde_json::Value>,
+    /// Constraint satisfaction per option.
+    pub constraints_satisfied: Option<serde_json::Value>,
+    /// When the decision was created.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Stored criterion for decision analysis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCriterion {
+    /// Criterion name.
+    pub name: String,
+    /// Weight (0.0-1.0).
+    pub weight: f64,
+    /// Optional description.
+    pub description: Option<String>,
+}
+
+impl Decision {
+    /// Create a new decision.
+    pub fn new(
+        session_id: impl Into<String>,
+        question: impl Into<String>,
+        options: Vec<String>,
+        method: impl Into<String>,
+        recommendation: serde_json::Value,
+        scores: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            question: question.into(),
+            options,
+            criteria: None,
+            method: method.into(),
+            recommendation,
+            scores,
+            sensitivity_analysis: None,
+            trade_offs: None,
+            constraints_satisfied: None,
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set criteria.
+    pub fn with_criteria(mut self, criteria: Vec<StoredCriterion>) -> Self {
+        self.criteria = Some(criteria);
+        self
+    }
+
+    /// Set sensitivity analysis.
+    pub fn with_sensitivity(mut self, analysis: serde_json::Value) -> Self {
+        self.sensitivity_analysis = Some(analysis);
+        self
+    }
+
+    /// Set trade-offs.
+    pub fn with_trade_offs(mut self, trade_offs: serde_json::Value) -> Self {
+        self.trade_offs = Some(trade_offs);
+        self
+    }
+
+    /// Set constraints satisfied.
+    pub fn with_constraints(mut self, satisfied: serde_json::Value) -> Self {
+        self.constraints_satisfied = Some(satisfied);
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Stored perspective analysis result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerspectiveAnalysis {
+    /// Unique analysis identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// The topic analyzed.
+    pub topic: String,
+    /// Stakeholder analyses (JSON array).
+    pub stakeholders: serde_json::Value,
+    /// Power/interest matrix (JSON object).
+    pub power_matrix: Option<serde_json::Value>,
+    /// Identified conflicts (JSON array).
+    pub conflicts: Option<serde_json::Value>,
+    /// Identified alignments (JSON array).
+    pub alignments: Option<serde_json::Value>,
+    /// Synthesis of perspectives (JSON object).
+    pub synthesis: serde_json::Value,
+    /// Overall confidence (0.0-1.0).
+    pub confidence: f64,
+    /// When the analysis was created.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl PerspectiveAnalysis {
+    /// Create a new perspective analysis.
+    pub fn new(
+        session_id: impl Into<String>,
+        topic: impl Into<String>,
+        stakeholders: serde_json::Value,
+        synthesis: serde_json::Value,
+        confidence: f64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            topic: topic.into(),
+            stakeholders,
+            power_matrix: None,
+            conflicts: None,
+            alignments: None,
+            synthesis,
+            confidence: confidence.clamp(0.0, 1.0),
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set power matrix.
+    pub fn with_power_matrix(mut self, matrix: serde_json::Value) -> Self {
+        self.power_matrix = Some(matrix);
+        self
+    }
+
+    /// Set conflicts.
+    pub fn with_conflicts(mut self, conflicts: serde_json::Value) -> Self {
+        self.conflicts = Some(conflicts);
+        self
+    }
+
+    /// Set alignments.
+    pub fn with_alignments(mut self, alignments: serde_json::Value) -> Self {
+        self.alignments = Some(alignments);
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+// ============================================================================
+// SWOT Analysis Storage Types
+// ============================================================================
+
+/// Stored SWOT analysis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwotAnalysis {
+    /// Unique analysis identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// The subject analyzed.
+    pub subject: String,
+    /// Strengths (JSON array of items).
+    pub strengths: serde_json::Value,
+    /// Weaknesses (JSON array of items).
+    pub weaknesses: serde_json::Value,
+    /// Opportunities (JSON array of items).
+    pub opportunities: serde_json::Value,
+    /// Threats (JSON array of items).
+    pub threats: serde_json::Value,
+    /// Perspective analysis whose stakeholders the items reference.
+    pub perspective_id: Option<String>,
+    /// Overall assessment.
+    pub summary: String,
+    /// Overall confidence (0.0-1.0).
+    pub confidence: f64,
+    /// When the analysis was created.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl SwotAnalysis {
+    /// Create a new SWOT analysis with empty quadrants.
+    pub fn new(
+        session_id: impl Into<String>,
+        subject: impl Into<String>,
+        summary: impl Into<String>,
+        confidence: f64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            subject: subject.into(),
+            strengths: serde_json::json!([]),
+            weaknesses: serde_json::json!([]),
+            opportunities: serde_json::json!([]),
+            threats: serde_json::json!([]),
+            perspective_id: None,
+            summary: summary.into(),
+            confidence: confidence.clamp(0.0, 1.0),
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set the four quadrants.
+    pub fn with_quadrants(
+        mut self,
+        strengths: serde_json::Value,
+        weaknesses: serde_json::Value,
+        opportunities: serde_json::Value,
+        threats: serde_json::Value,
+    ) -> Self {
+        self.strengths = strengths;
+        self.weaknesses = weaknesses;
+        self.opportunities = opportunities;
+        self.threats = threats;
+        self
+    }
+
+    /// Link the perspective analysis the items reference.
+    pub fn with_perspective(mut self, perspective_id: impl Into<String>) -> Self {
+        self.perspective_id = Some(perspective_id.into());
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Filter options for finding SWOT analyses across sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwotFilter {
+    /// Only match analyses whose subject contains this text (case-insensitive).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Only match analyses in this session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Only match analyses in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Limit number of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl SwotFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by subject text.
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Filter by session.
+    pub fn in_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Only match analyses in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Limit number of results.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+// ============================================================================
+// Risk Register Storage Types
+// ============================================================================
+
+/// Stored risk of a session's risk register.
+///
+/// Likelihood, impact and detectability are scored 1-5; a detectability of
+/// 5 means the risk is hardest to notice before it strikes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Risk {
+    /// Unique risk identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// Short name of the risk.
+    pub title: String,
+    /// What could happen and why.
+    pub description: String,
+    /// Optional category (technical, schedule, ...).
+    pub category: Option<String>,
+    /// How likely the risk is to occur (1-5).
+    pub likelihood: i32,
+    /// How bad it would be (1-5).
+    pub impact: i32,
+    /// How hard it is to detect in time (1-5).
+    pub detectability: i32,
+    /// Who is responsible for the risk.
+    pub owner: Option<String>,
+    /// How the risk is being reduced.
+    pub mitigation: Option<String>,
+    /// Earlier scores and why they changed (JSON array, oldest first).
+    pub history: serde_json::Value,
+    /// When the risk was recorded.
+    pub created_at: DateTime<Utc>,
+    /// When the risk was last re-scored.
+    pub updated_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl Risk {
+    /// Create a new risk; scores are clamped to 1-5.
+    pub fn new(
+        session_id: impl Into<String>,
+        title: impl Into<String>,
+        likelihood: i32,
+        impact: i32,
+        detectability: i32,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            title: title.into(),
+            description: String::new(),
+            category: None,
+            likelihood: likelihood.clamp(1, 5),
+            impact: impact.clamp(1, 5),
+            detectability: detectability.clamp(1, 5),
+            owner: None,
+            mitigation: None,
+            history: serde_json::json!([]),
+            created_at: now,
+            updated_at: now,
+            metadata: None,
+        }
+    }
+
+    /// Set description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set category.
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Set owner.
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Set mitigation.
+    pub fn with_mitigation(mut self, mitigation: impl Into<String>) -> Self {
+        self.mitigation = Some(mitigation.into());
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Likelihood times impact (1-25).
+    pub fn score(&self) -> i32 {
+        self.likelihood * self.impact
+    }
+
+    /// Likelihood times impact times detectability (1-125), as in FMEA.
+    pub fn priority_number(&self) -> i32 {
+        self.score() * self.detectability
+    }
+}
+
+// ============================================================================
+// Code Reasoning Storage Types
+// ============================================================================
+
+/// Kind of a finding from code reasoning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeFindingKind {
+    /// A property the code relies on or preserves.
+    #[default]
+    Invariant,
+    /// A way the code could fail or regress.
+    Risk,
+    /// A test that would pin the behavior down.
+    Test,
+}
+
+impl std::fmt::Display for CodeFindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodeFindingKind::Invariant => write!(f, "invariant"),
+            CodeFindingKind::Risk => write!(f, "risk"),
+            CodeFindingKind::Test => write!(f, "test"),
+        }
+    }
+}
+
+impl std::str::FromStr for CodeFindingKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "invariant" => Ok(CodeFindingKind::Invariant),
+            "risk" => Ok(CodeFindingKind::Risk),
+            "test" => Ok(CodeFindingKind::Test),
+            _ => Err(format!("Unknown code finding kind: {}", s)),
+        }
+    }
+}
+
+/// Stored finding of code reasoning: an invariant, a risk or a suggested
+/// test, accumulated per session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeFinding {
+    /// Unique finding identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// Thought of the analysis that produced the finding.
+    pub thought_id: Option<String>,
+    /// Operation that produced the finding (explain_diff, trace_flow, edge_cases).
+    pub operation: String,
+    /// Kind of finding.
+    pub kind: CodeFindingKind,
+    /// The invariant, risk or test, in prose.
+    pub content: String,
+    /// Where in the code it applies (file, function or line).
+    pub location: Option<String>,
+    /// Severity of a risk (1-5).
+    pub severity: Option<i32>,
+    /// When the finding was recorded.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl CodeFinding {
+    /// Create a new finding.
+    pub fn new(
+        session_id: impl Into<String>,
+        operation: impl Into<String>,
+        kind: CodeFindingKind,
+        content: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            thought_id: None,
+            operation: operation.into(),
+            kind,
+            content: content.into(),
+            location: None,
+            severity: None,
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set the thought of the analysis.
+    pub fn with_thought(mut self, thought_id: impl Into<String>) -> Self {
+        self.thought_id = Some(thought_id.into());
+        self
+    }
+
+    /// Set location.
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Set severity; clamped to 1-5.
+    pub fn with_severity(mut self, severity: i32) -> Self {
+        self.severity = Some(severity.clamp(1, 5));
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+// ============================================================================
+// Evidence Assessment Storage Types
+// ============================================================================
+
+/// Stored evidence assessment result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceAssessment {
+    /// Unique assessment identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// The claim being assessed.
+    pub claim: String,
+    /// Evidence items (JSON array).
+    pub evidence: serde_json::Value,
+    /// Overall support level (JSON object).
+    pub overall_support: serde_json::Value,
+    /// Individual evidence analyses (JSON array).
+    pub evidence_analysis: serde_json::Value,
+    /// Chain of reasoning analysis (JSON object).
+    pub chain_analysis: Option<serde_json::Value>,
+    /// Detected contradictions (JSON array).
+    pub contradictions: Option<serde_json::Value>,
+    /// Identified gaps (JSON array).
+    pub gaps: Option<serde_json::Value>,
+    /// Recommendations (JSON array).
+    pub recommendations: Option<serde_json::Value>,
+    /// When the assessment was created.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl EvidenceAssessment {
+    /// Create a new evidence assessment.
+    pub fn new(
+        session_id: impl Into<String>,
+        claim: impl Into<String>,
+        evidence: serde_json::Value,
+        overall_support: serde_json::Value,
+        evidence_analysis: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            claim: claim.into(),
+            evidence,
+            overall_support,
+            evidence_analysis,
+            chain_analysis: None,
+            contradictions: None,
+            gaps: None,
+            recommendations: None,
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set chain analysis.
+    pub fn with_chain_analysis(mut self, analysis: serde_json::Value) -> Self {
+        self.chain_analysis = Some(analysis);
+        self
+    }
+
+    /// Set contradictions.
+    pub fn with_contradictions(mut self, contradictions: serde_json::Value) -> Self {
+        self.contradictions = Some(contradictions);
+        self
+    }
+
+    /// Set gaps.
+    pub fn with_gaps(mut self, gaps: serde_json::Value) -> Self {
+        self.gaps = Some(gaps);
+        self
+    }
+
+    /// Set recommendations.
+    pub fn with_recommendations(mut self, recommendations: serde_json::Value) -> Self {
+        self.recommendations = Some(recommendations);
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// Stored probability update result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbabilityUpdate {
+    /// Unique update identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// The hypothesis evaluated.
+    pub hypothesis: String,
+    /// Prior probability (0-1).
+    pub prior: f64,
+    /// Posterior probability (0-1).
+    pub posterior: f64,
+    /// Lower bound of confidence interval.
+    pub confidence_lower: Option<f64>,
+    /// Upper bound of confidence interval.
+    pub confidence_upper: Option<f64>,
+    /// Confidence interval level (e.g., 0.95).
+    pub confidence_level: Option<f64>,
+    /// Bayesian update steps (JSON array).
+    pub update_steps: serde_json::Value,
+    /// Uncertainty analysis (JSON object).
+    pub uncertainty_analysis: Option<serde_json::Value>,
+    /// Sensitivity analysis (JSON object).
+    pub sensitivity: Option<serde_json::Value>,
+    /// Human interpretation (JSON object).
+    pub interpretation: serde_json::Value,
+    /// When the update was created.
+    pub created_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl ProbabilityUpdate {
+    /// Create a new probability update.
+    pub fn new(
+        session_id: impl Into<String>,
+        hypothesis: impl Into<String>,
+        prior: f64,
+        posterior: f64,
+        update_steps: serde_json::Value,
+        interpretation: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            hypothesis: hypothesis.into(),
+            prior: prior.clamp(0.0, 1.0),
+            posterior: posterior.clamp(0.0, 1.0),
+            confidence_lower: None,
+            confidence_upper: None,
+            confidence_level: None,
+            update_steps,
+            uncertainty_analysis: None,
+            sensitivity: None,
+            interpretation,
+            created_at: Utc::now(),
+            metadata: None,
+        }
+    }
+
+    /// Set confidence interval.
+    pub fn with_confidence_interval(
+        mut self,
+        lower: Option<f64>,
+        upper: Option<f64>,
+        level: Option<f64>,
+    ) -> Self {
+        self.confidence_lower = lower.map(|v| v.clamp(0.0, 1.0));
+        self.confidence_upper = upper.map(|v| v.clamp(0.0, 1.0));
+        self.confidence_level = level.map(|v| v.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Set uncertainty analysis.
+    pub fn with_uncertainty(mut self, analysis: serde_json::Value) -> Self {
+        self.uncertainty_analysis = Some(analysis);
+        self
+    }
+
+    /// Set sensitivity analysis.
+    pub fn with_sensitivity(mut self, sensitivity: serde_json::Value) -> Self {
+        self.sensitivity = Some(sensitivity);
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+// ============================================================================
+// Time Machine Storage Types (Phase 10)
+// ============================================================================
+
+/// State of a reasoning timeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineState {
+    /// Timeline is actively being explored.
+    #[default]
+    Active,
+    /// Timeline has been archived.
+    Archived,
+    /// Timeline has been merged into another.
+    Merged,
+}
+
+impl std::fmt::Display for TimelineState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelineState::Active => write!(f, "active"),
+            TimelineState::Archived => write!(f, "archived"),
+            TimelineState::Merged => write!(f, "merged"),
+        }
+    }
+}
+
+impl std::str::FromStr for TimelineState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "active" => Ok(TimelineState::Active),
+            "archived" => Ok(TimelineState::Archived),
+            "merged" => Ok(TimelineState::Merged),
+            _ => Err(format!("Unknown timeline state: {}", s)),
+        }
+    }
+}
+
+/// A reasoning timeline representing a complete path through reasoning space.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PerspectiveAnalysis {
-    /// Unique analysis identifier.
+pub struct Timeline {
+    /// Unique timeline identifier.
     pub id: String,
     /// Parent session ID.
     pub session_id: String,
-    /// The topic analyzed.
-    pub topic: String,
-    /// Stakeholder analyses (JSON array).
-    pub stakeholders: serde_json::Value,
-    /// Power/interest matrix (JSON object).
-    pub power_matrix: Option<serde_json::Value>,
-    /// Identified conflicts (JSON array).
-    pub conflicts: Option<serde_json::Value>,
-    /// Identified alignments (JSON array).
-    pub alignments: Option<serde_json::Value>,
-    /// Synthesis of perspectives (JSON object).
-    pub synthesis: serde_json::Value,
-    /// Overall confidence (0.0-1.0).
-    pub confidence: f64,
-    /// When the analysis was created.
+    /// Human-readable timeline name.
+    pub name: String,
+    /// Optional description.
+    pub description: Option<String>,
+    /// The root branch of this timeline.
+    pub root_branch_id: String,
+    /// Currently active branch in this timeline.
+    pub active_branch_id: String,
+    /// Timeline state.
+    pub state: TimelineState,
+    /// Total number of branches in this timeline.
+    pub branch_count: i32,
+    /// Deepest branch depth.
+    pub max_depth: i32,
+    /// When the timeline was created.
+    pub created_at: DateTime<Utc>,
+    /// When the timeline was last updated.
+    pub updated_at: DateTime<Utc>,
+    /// Optional metadata.
+    pub metadata: Option<serde_json::Value>,
+}
+
+impl Timeline {
+    /// Create a new timeline.
+    pub fn new(
+        session_id: impl Into<String>,
+        name: impl Into<String>,
+        root_branch_id: impl Into<String>,
+    ) -> Self {
+        let now = Utc::now();
+        let root_id = root_branch_id.into();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            session_id: session_id.into(),
+            name: name.into(),
+            description: None,
+            root_branch_id: root_id.clone(),
+            active_branch_id: root_id,
+            state: TimelineState::Active,
+            branch_count: 1,
+            max_depth: 0,
+            created_at: now,
+            updated_at: now,
+            metadata: None,
+        }
+    }
+
+    /// Set description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Update the active branch.
+    pub fn set_active_branch(&mut self, branch_id: impl Into<String>) {
+        self.active_branch_id = branch_id.into();
+        self.updated_at = Utc::now();
+    }
+
+    /// Increment branch count.
+    pub fn increment_branches(&mut self) {
+        self.branch_count += 1;
+        self.updated_at = Utc::now();
+    }
+
+    /// Update max depth if needed.
+    pub fn update_max_depth(&mut self, depth: i32) {
+        if depth > self.max_depth {
+            self.max_depth = depth;
+            self.updated_at = Utc::now();
+        }
+    }
+}
+
+/// Extended branch data for timeline-based reasoning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineBranch {
+    /// The underlying branch ID.
+    pub branch_id: String,
+    /// Parent timeline ID.
+    pub timeline_id: String,
+    /// Depth in the timeline tree (0 = root).
+    pub depth: i32,
+    /// MCTS visit count.
+    pub visit_count: i32,
+    /// MCTS total value/score.
+    pub total_value: f64,
+    /// UCB exploration score.
+    pub ucb_score: Option<f64>,
+    /// Counterfactual impact score.
+    pub counterfactual_impact: Option<f64>,
+    /// Whether this branch was auto-generated by MCTS.
+    pub mcts_generated: bool,
+    /// Alternative approaches explored from this point.
+    pub alternatives_explored: i32,
+    /// When created.
+    pub created_at: DateTime<Utc>,
+    /// When last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TimelineBranch {
+    /// Create a new timeline branch.
+    pub fn new(branch_id: impl Into<String>, timeline_id: impl Into<String>, depth: i32) -> Self {
+        let now = Utc::now();
+        Self {
+            branch_id: branch_id.into(),
+            timeline_id: timeline_id.into(),
+            depth,
+            visit_count: 0,
+            total_value: 0.0,
+            ucb_score: None,
+            counterfactual_impact: None,
+            mcts_generated: false,
+            alternatives_explored: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Mark as MCTS-generated.
+    pub fn with_mcts_generated(mut self) -> Self {
+        self.mcts_generated = true;
+        self
+    }
+
+    /// Record a visit with value update.
+    pub fn record_visit(&mut self, value: f64) {
+        self.visit_count += 1;
+        self.total_value += value;
+        self.updated_at = Utc::now();
+    }
+
+    /// Calculate UCB1 score for node selection.
+    /// UCB1 = Q(s,a) + c * sqrt(ln(N_parent) / N(s,a))
+    pub fn calculate_ucb(&self, parent_visits: i32, exploration_constant: f64) -> f64 {
+        if self.visit_count == 0 {
+            return f64::INFINITY;
+        }
+
+        let exploitation = self.total_value / self.visit_count as f64;
+        let exploration = exploration_constant
+            * ((parent_visits as f64).ln() / self.visit_count as f64).sqrt();
+
+        exploitation + exploration
+    }
+
+    /// Update UCB score.
+    pub fn update_ucb(&mut self, parent_visits: i32, exploration_constant: f64) {
+        self.ucb_score = Some(self.calculate_ucb(parent_visits, exploration_constant));
+        self.updated_at = Utc::now();
+    }
+}
+
+/// MCTS node for guided reasoning exploration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCTSNode {
+    /// Unique node identifier.
+    pub id: String,
+    /// Parent session ID.
+    pub session_id: String,
+    /// Associated timeline ID.
+    pub timeline_id: Option<String>,
+    /// Associated timeline branch ID.
+    pub branch_id: String,
+    /// Parent MCTS node ID.
+    pub parent_node_id: Option<String>,
+    /// Node content/reasoning state.
+    pub content: String,
+    /// Visit count (N).
+    pub visit_count: i32,
+    /// Total value (W).
+    pub total_value: f64,
+    /// Prior probability from policy network.
+    pub prior: f64,
+    /// UCB score for selection.
+    pub ucb_score: f64,
+    /// Whether this node is fully expanded.
+    pub is_expanded: bool,
+    /// Whether this is a terminal node.
+    pub is_terminal: bool,
+    /// Simulation depth reached.
+    pub simulation_depth: i32,
+    /// When the node was created.
     pub created_at: DateTime<Utc>,
+    /// When the node was last visited.
+    pub last_visited: DateTime<Utc>,
     /// Optional metadata.
     pub metadata: Option<serde_json::Value>,
 }
 
-impl PerspectiveAnalysis {
-    /// Create a new perspective analysis.
+impl MCTSNode {
+    /// Create a new MCTS node.
     pub fn new(
         session_id: impl Into<String>,
-        topic: impl Into<String>,
-        stakeholders: serde_json::Value,
-        synthesis: serde_json::Value,
-        confidence: f64,
+        branch_id: impl Into<String>,
+        content: impl Into<String>,
     ) -> Self {
+        let now = Utc::now();
         Self {
             id: Uuid::new_v4().to_string(),
             session_id: session_id.into(),
-            topic: topic.into(),
-            stakeholders,
-            power_matrix: None,
-            conflicts: None,
-            alignments: None,
-            synthesis,
-            confidence: confidence.clamp(0.0, 1.0),
-            created_at: Utc::now(),
+            timeline_id: None,
+            branch_id: branch_id.into(),
+            parent_node_id: None,
+            content: content.into(),
+            visit_count: 0,
+            total_value: 0.0,
+            prior: 0.5,
+            ucb_score: 0.0,
+            is_expanded: false,
+            is_terminal: false,
+            simulation_depth: 0,
+            created_at: now,
+            last_visited: now,
             metadata: None,
         }
     }
 
-    /// Set power matrix.
-    pub fn with_power_matrix(mut self, matrix: serde_json::Value) -> Self {
-        self.power_matrix = Some(matrix);
+    /// Set parent node.
+    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_node_id = Some(parent_id.into());
         self
     }
 
-    /// Set conflicts.
-    pub fn with_conflicts(mut self, conflicts: serde_json::Value) -> Self {
-        self.conflicts = Some(conflicts);
+    /// Set timeline.
+    pub fn with_timeline(mut self, timeline_id: impl Into<String>) -> Self {
+        self.timeline_id = Some(timeline_id.into());
         self
     }
 
-    /// Set alignments.
-    pub fn with_alignments(mut self, alignments: serde_json::Value) -> Self {
-        self.alignments = Some(alignments);
+    /// Set prior probability.
+    pub fn with_prior(mut self, prior: f64) -> Self {
+        self.prior = prior.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Mark as terminal.
+    pub fn as_terminal(mut self) -> Self {
+        self.is_terminal = true;
         self
     }
 
@@ -1253,687 +2781,883 @@ impl PerspectiveAnalysis {
         self.metadata = Some(metadata);
         self
     }
-}
 
-// ============================================================================
-// Evidence Assessment Storage Types
-// ============================================================================
+    /// Calculate UCB1 score for node selection.
+    /// UCB1 = Q(s,a) + c * sqrt(ln(N_parent) / N(s,a))
+    pub fn calculate_ucb(&self, parent_visits: i32, exploration_constant: f64) -> f64 {
+        if self.visit_count == 0 {
+            return f64::INFINITY;
+        }
 
-/// Stored evidence assessment result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EvidenceAssessment {
-    /// Unique assessment identifier.
-    pub id: String,
-    /// Parent session ID.
-    pub session_id: String,
-    /// The claim being assessed.
-    pub claim: String,
-    /// Evidence items (JSON array).
-    pub evidence: serde_json::Value,
-    /// Overall support level (JSON object).
-    pub overall_support: serde_json::Value,
-    /// Individual evidence analyses (JSON array).
-    pub evidence_analysis: serde_json::Value,
-    /// Chain of reasoning analysis (JSON object).
-    pub chain_analysis: Option<serde_json::Value>,
-    /// Detected contradictions (JSON array).
-    pub contradictions: Option<serde_json::Value>,
-    /// Identified gaps (JSON array).
-    pub gaps: Option<serde_json::Value>,
-    /// Recommendations (JSON array).
-    pub recommendations: Option<serde_json::Value>,
-    /// When the assessment was created.
-    pub created_at: DateTime<Utc>,
-    /// Optional metadata.
-    pub metadata: Option<serde_json::Value>,
-}
+        let exploitation = self.total_value / self.visit_count as f64;
+        let exploration = exploration_constant
+            * ((parent_visits as f64).ln() / self.visit_count as f64).sqrt();
 
-impl EvidenceAssessment {
-    /// Create a new evidence assessment.
-    pub fn new(
-        session_id: impl Into<String>,
-        claim: impl Into<String>,
-        evidence: serde_json::Value,
-        overall_support: serde_json::Value,
-        evidence_analysis: serde_json::Value,
-    ) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            claim: claim.into(),
-            evidence,
-            overall_support,
-            evidence_analysis,
-            chain_analysis: None,
-            contradictions: None,
-            gaps: None,
-            recommendations: None,
-            created_at: Utc::now(),
-            metadata: None,
-        }
+        exploitation + exploration
     }
 
-    /// Set chain analysis.
-    pub fn with_chain_analysis(mut self, analysis: serde_json::Value) -> Self {
-        self.chain_analysis = Some(analysis);
-        self
+    /// Record a visit with value update.
+    pub fn record_visit(&mut self, value: f64) {
+        self.visit_count += 1;
+        self.total_value += value;
+        self.last_visited = Utc::now();
     }
 
-    /// Set contradictions.
-    pub fn with_contradictions(mut self, contradictions: serde_json::Value) -> Self {
-        self.contradictions = Some(contradictions);
+    /// Set simulation depth.
+    pub fn with_simulation_depth(mut self, depth: i32) -> Self {
+        self.simulation_depth = depth;
         self
     }
 
-    /// Set gaps.
-    pub fn with_gaps(mut self, gaps: serde_json::Value) -> Self {
-        self.gaps = Some(gaps);
-        self
+    /// Update last visited timestamp.
+    pub fn update_last_visited(&mut self) {
+        self.last_visited = Utc::now();
     }
 
-    /// Set recommendations.
-    pub fn with_recommendations(mut self, recommendations: serde_json::Value) -> Self {
-        self.recommendations = Some(recommendations);
-        self
+    /// Update UCB score.
+    pub fn update_ucb(&mut self, parent_visits: i32, exploration_constant: f64) {
+        self.ucb_score = self.calculate_ucb(parent_visits, exploration_constant);
     }
 
-    /// Set metadata.
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
-        self
+    /// Mark as expanded.
+    pub fn mark_expanded(&mut self) {
+        self.is_expanded = true;
+    }
+
+    /// Get average value (Q-value).
+    pub fn q_value(&self) -> f64 {
+        if self.visit_count == 0 {
+            0.0
+        } else {
+            self.total_value / self.visit_count as f64
+        }
     }
 }
 
-/// Stored probability update result.
+/// Node selection policy of an MCTS search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MctsPolicy {
+    /// Descend by UCB1 and expand a node once, with every child at a time.
+    #[default]
+    Ucb1,
+    /// Descend by UCB1, but let a node gain children as its visit count
+    /// grows (k * N^alpha), so wide searches spend visits before breadth.
+    ProgressiveWidening,
+}
+
+impl std::fmt::Display for MctsPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MctsPolicy::Ucb1 => write!(f, "ucb1"),
+            MctsPolicy::ProgressiveWidening => write!(f, "progressive_widening"),
+        }
+    }
+}
+
+impl std::str::FromStr for MctsPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "ucb1" => Ok(MctsPolicy::Ucb1),
+            "progressive_widening" => Ok(MctsPolicy::ProgressiveWidening),
+            _ => Err(format!("Unknown MCTS policy: {}", s)),
+        }
+    }
+}
+
+/// Statistics of one iteration of an MCTS search, kept so the metrics CLI
+/// can relate search budget to result quality.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProbabilityUpdate {
-    /// Unique update identifier.
+pub struct MctsIteration {
+    /// Unique record identifier.
     pub id: String,
     /// Parent session ID.
     pub session_id: String,
-    /// The hypothesis evaluated.
-    pub hypothesis: String,
-    /// Prior probability (0-1).
-    pub prior: f64,
-    /// Posterior probability (0-1).
-    pub posterior: f64,
-    /// Lower bound of confidence interval.
-    pub confidence_lower: Option<f64>,
-    /// Upper bound of confidence interval.
-    pub confidence_upper: Option<f64>,
-    /// Confidence interval level (e.g., 0.95).
-    pub confidence_level: Option<f64>,
-    /// Bayesian update steps (JSON array).
-    pub update_steps: serde_json::Value,
-    /// Uncertainty analysis (JSON object).
-    pub uncertainty_analysis: Option<serde_json::Value>,
-    /// Sensitivity analysis (JSON object).
-    pub sensitivity: Option<serde_json::Value>,
-    /// Human interpretation (JSON object).
-    pub interpretation: serde_json::Value,
-    /// When the update was created.
+    /// Root node of the search the iteration belongs to.
+    pub root_node_id: String,
+    /// Iteration number, starting at 1.
+    pub iteration: i32,
+    /// Selection policy of the search.
+    pub policy: MctsPolicy,
+    /// UCB1 exploration constant of the search.
+    pub exploration_constant: f64,
+    /// Continuation steps each simulation rolled out.
+    pub rollout_depth: i32,
+    /// Children generated per expansion.
+    pub expansion_width: i32,
+    /// Node selected for expansion.
+    pub selected_node_id: String,
+    /// Depth of the selected node (root is 0).
+    pub selected_depth: i32,
+    /// Children the expansion created.
+    pub children_created: i32,
+    /// Value of the simulation.
+    pub simulation_value: f64,
+    /// Highest mean value of any visited node after the iteration.
+    pub best_value: f64,
+    /// Nodes in the search tree after the iteration.
+    pub nodes_total: i32,
+    /// Pipe calls the iteration made.
+    pub pipe_calls: i32,
+    /// Time the iteration took in milliseconds.
+    pub latency_ms: i64,
+    /// When the iteration finished.
     pub created_at: DateTime<Utc>,
-    /// Optional metadata.
-    pub metadata: Option<serde_json::Value>,
 }
 
-impl ProbabilityUpdate {
-    /// Create a new probability update.
+impl MctsIteration {
+    /// Create a new iteration record with zeroed statistics.
     pub fn new(
         session_id: impl Into<String>,
-        hypothesis: impl Into<String>,
-        prior: f64,
-        posterior: f64,
-        update_steps: serde_json::Value,
-        interpretation: serde_json::Value,
+        root_node_id: impl Into<String>,
+        iteration: i32,
+        selected_node_id: impl Into<String>,
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             session_id: session_id.into(),
-            hypothesis: hypothesis.into(),
-            prior: prior.clamp(0.0, 1.0),
-            posterior: posterior.clamp(0.0, 1.0),
-            confidence_lower: None,
-            confidence_upper: None,
-            confidence_level: None,
-            update_steps,
-            uncertainty_analysis: None,
-            sensitivity: None,
-            interpretation,
+            root_node_id: root_node_id.into(),
+            iteration,
+            policy: MctsPolicy::default(),
+            exploration_constant: 0.0,
+            rollout_depth: 0,
+            expansion_width: 0,
+            selected_node_id: selected_node_id.into(),
+            selected_depth: 0,
+            children_created: 0,
+            simulation_value: 0.0,
+            best_value: 0.0,
+            nodes_total: 0,
+            pipe_calls: 0,
+            latency_ms: 0,
             created_at: Utc::now(),
-            metadata: None,
         }
     }
+}
+
+/// Filter for MCTS iteration statistics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MctsIterationFilter {
+    /// Filter by session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Only match searches in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Only the iterations of this many most recent searches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
 
-    /// Set confidence interval.
-    pub fn with_confidence_interval(
-        mut self,
-        lower: Option<f64>,
-        upper: Option<f64>,
-        level: Option<f64>,
-    ) -> Self {
-        self.confidence_lower = lower.map(|v| v.clamp(0.0, 1.0));
-        self.confidence_upper = upper.map(|v| v.clamp(0.0, 1.0));
-        self.confidence_level = level.map(|v| v.clamp(0.0, 1.0));
-        self
+impl MctsIterationFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Set uncertainty analysis.
-    pub fn with_uncertainty(mut self, analysis: serde_json::Value) -> Self {
-        self.uncertainty_analysis = Some(analysis);
+    /// Filter by session.
+    pub fn in_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Set sensitivity analysis.
-    pub fn with_sensitivity(mut self, sensitivity: serde_json::Value) -> Self {
-        self.sensitivity = Some(sensitivity);
+    /// Only match searches in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
         self
     }
 
-    /// Set metadata.
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Limit the number of searches.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
         self
     }
 }
 
-// ============================================================================
-// Time Machine Storage Types (Phase 10)
-// ============================================================================
-
-/// State of a reasoning timeline.
+/// Type of counterfactual intervention.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub enum TimelineState {
-    /// Timeline is actively being explored.
+pub enum InterventionType {
+    /// Change a decision/thought to something else.
     #[default]
-    Active,
-    /// Timeline has been archived.
-    Archived,
-    /// Timeline has been merged into another.
-    Merged,
+    Change,
+    /// Remove a decision/thought entirely.
+    Remove,
+    /// Replace with alternative reasoning.
+    Replace,
+    /// Add new information at a point.
+    Inject,
 }
 
-impl std::fmt::Display for TimelineState {
+impl std::fmt::Display for InterventionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TimelineState::Active => write!(f, "active"),
-            TimelineState::Archived => write!(f, "archived"),
-            TimelineState::Merged => write!(f, "merged"),
+            InterventionType::Change => write!(f, "change"),
+            InterventionType::Remove => write!(f, "remove"),
+            InterventionType::Replace => write!(f, "replace"),
+            InterventionType::Inject => write!(f, "inject"),
         }
     }
 }
 
-impl std::str::FromStr for TimelineState {
+impl std::str::FromStr for InterventionType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "active" => Ok(TimelineState::Active),
-            "archived" => Ok(TimelineState::Archived),
-            "merged" => Ok(TimelineState::Merged),
-            _ => Err(format!("Unknown timeline state: {}", s)),
+            "change" => Ok(InterventionType::Change),
+            "remove" => Ok(InterventionType::Remove),
+            "replace" => Ok(InterventionType::Replace),
+            "inject" => Ok(InterventionType::Inject),
+            _ => Err(format!("Unknown intervention type: {}", s)),
         }
     }
 }
 
-/// A reasoning timeline representing a complete path through reasoning space.
+/// Counterfactual analysis result.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Timeline {
-    /// Unique timeline identifier.
+pub struct CounterfactualAnalysis {
+    /// Unique analysis identifier.
     pub id: String,
     /// Parent session ID.
     pub session_id: String,
-    /// Human-readable timeline name.
-    pub name: String,
-    /// Optional description.
-    pub description: Option<String>,
-    /// The root branch of this timeline.
-    pub root_branch_id: String,
-    /// Currently active branch in this timeline.
-    pub active_branch_id: String,
-    /// Timeline state.
-    pub state: TimelineState,
-    /// Total number of branches in this timeline.
-    pub branch_count: i32,
-    /// Deepest branch depth.
-    pub max_depth: i32,
-    /// When the timeline was created.
+    /// Associated timeline ID.
+    pub timeline_id: Option<String>,
+    /// Original branch being analyzed.
+    pub original_branch_id: String,
+    /// The counterfactual question posed.
+    pub question: String,
+    /// Intervention type.
+    pub intervention_type: InterventionType,
+    /// The specific intervention made.
+    pub intervention: String,
+    /// Target thought/decision being modified.
+    pub target_thought_id: Option<String>,
+    /// Counterfactual branch ID (the "what if" path).
+    pub counterfactual_branch_id: String,
+    /// Predicted outcome difference (-1.0 to 1.0).
+    pub outcome_delta: f64,
+    /// Causal attribution score (0.0-1.0).
+    pub causal_attribution: f64,
+    /// Confidence in the analysis (0.0-1.0).
+    pub confidence: f64,
+    /// Detailed comparison results (JSON).
+    pub comparison: serde_json::Value,
+    /// When the analysis was created.
     pub created_at: DateTime<Utc>,
-    /// When the timeline was last updated.
-    pub updated_at: DateTime<Utc>,
     /// Optional metadata.
     pub metadata: Option<serde_json::Value>,
 }
 
-impl Timeline {
-    /// Create a new timeline.
+impl CounterfactualAnalysis {
+    /// Create a new counterfactual analysis.
     pub fn new(
         session_id: impl Into<String>,
-        name: impl Into<String>,
-        root_branch_id: impl Into<String>,
+        original_branch_id: impl Into<String>,
+        question: impl Into<String>,
+        intervention_type: InterventionType,
+        intervention: impl Into<String>,
+        counterfactual_branch_id: impl Into<String>,
     ) -> Self {
-        let now = Utc::now();
-        let root_id = root_branch_id.into();
         Self {
             id: Uuid::new_v4().to_string(),
             session_id: session_id.into(),
-            name: name.into(),
-            description: None,
-            root_branch_id: root_id.clone(),
-            active_branch_id: root_id,
-            state: TimelineState::Active,
-            branch_count: 1,
-            max_depth: 0,
-            created_at: now,
-            updated_at: now,
+            timeline_id: None,
+            original_branch_id: original_branch_id.into(),
+            question: question.into(),
+            intervention_type,
+            intervention: intervention.into(),
+            target_thought_id: None,
+            counterfactual_branch_id: counterfactual_branch_id.into(),
+            outcome_delta: 0.0,
+            causal_attribution: 0.0,
+            confidence: 0.0,
+            comparison: serde_json::json!({}),
+            created_at: Utc::now(),
             metadata: None,
         }
     }
 
-    /// Set description.
-    pub fn with_description(mut self, description: impl Into<String>) -> Self {
-        self.description = Some(description.into());
+    /// Set timeline.
+    pub fn with_timeline(mut self, timeline_id: impl Into<String>) -> Self {
+        self.timeline_id = Some(timeline_id.into());
         self
     }
 
-    /// Set metadata.
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Set target thought.
+    pub fn with_target_thought(mut self, thought_id: impl Into<String>) -> Self {
+        self.target_thought_id = Some(thought_id.into());
         self
     }
 
-    /// Update the active branch.
-    pub fn set_active_branch(&mut self, branch_id: impl Into<String>) {
-        self.active_branch_id = branch_id.into();
-        self.updated_at = Utc::now();
-    }
-
-    /// Increment branch count.
-    pub fn increment_branches(&mut self) {
-        self.branch_count += 1;
-        self.updated_at = Utc::now();
+    /// Set outcome delta.
+    pub fn with_outcome_delta(mut self, delta: f64) -> Self {
+        self.outcome_delta = delta.clamp(-1.0, 1.0);
+        self
     }
 
-    /// Update max depth if needed.
-    pub fn update_max_depth(&mut self, depth: i32) {
-        if depth > self.max_depth {
-            self.max_depth = depth;
-            self.updated_at = Utc::now();
-        }
+    /// Set causal attribution.
+    pub fn with_causal_attribution(mut self, attribution: f64) -> Self {
+        self.causal_attribution = attribution.clamp(0.0, 1.0);
+        self
     }
-}
-
-/// Extended branch data for timeline-based reasoning.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TimelineBranch {
-    /// The underlying branch ID.
-    pub branch_id: String,
-    /// Parent timeline ID.
-    pub timeline_id: String,
-    /// Depth in the timeline tree (0 = root).
-    pub depth: i32,
-    /// MCTS visit count.
-    pub visit_count: i32,
-    /// MCTS total value/score.
-    pub total_value: f64,
-    /// UCB exploration score.
-    pub ucb_score: Option<f64>,
-    /// Counterfactual impact score.
-    pub counterfactual_impact: Option<f64>,
-    /// Whether this branch was auto-generated by MCTS.
-    pub mcts_generated: bool,
-    /// Alternative approaches explored from this point.
-    pub alternatives_explored: i32,
-    /// When created.
-    pub created_at: DateTime<Utc>,
-    /// When last updated.
-    pub updated_at: DateTime<Utc>,
-}
 
-impl TimelineBranch {
-    /// Create a new timeline branch.
-    pub fn new(branch_id: impl Into<String>, timeline_id: impl Into<String>, depth: i32) -> Self {
-        let now = Utc::now();
-        Self {
-            branch_id: branch_id.into(),
-            timeline_id: timeline_id.into(),
-            depth,
-            visit_count: 0,
-            total_value: 0.0,
-            ucb_score: None,
-            counterfactual_impact: None,
-            mcts_generated: false,
-            alternatives_explored: 0,
-            created_at: now,
-            updated_at: now,
-        }
+    /// Set confidence.
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence.clamp(0.0, 1.0);
+        self
     }
 
-    /// Mark as MCTS-generated.
-    pub fn with_mcts_generated(mut self) -> Self {
-        self.mcts_generated = true;
+    /// Set comparison results.
+    pub fn with_comparison(mut self, comparison: serde_json::Value) -> Self {
+        self.comparison = comparison;
         self
     }
 
-    /// Record a visit with value update.
-    pub fn record_visit(&mut self, value: f64) {
-        self.visit_count += 1;
-        self.total_value += value;
-        self.updated_at = Utc::now();
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
     }
+}
 
-    /// Calculate UCB1 score for node selection.
-    /// UCB1 = Q(s,a) + c * sqrt(ln(N_parent) / N(s,a))
-    pub fn calculate_ucb(&self, parent_visits: i32, exploration_constant: f64) -> f64 {
-        if self.visit_count == 0 {
-            return f64::INFINITY;
-        }
+// ============================================================================
+// Knowledge Base Storage Types
+// ============================================================================
 
-        let exploitation = self.total_value / self.visit_count as f64;
-        let exploration = exploration_constant
-            * ((parent_visits as f64).ln() / self.visit_count as f64).sqrt();
+/// Kind of reasoning result a conclusion was promoted from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConclusionSource {
+    /// A terminal Graph-of-Thoughts node.
+    #[default]
+    GraphNode,
+    /// A decision analysis recommendation.
+    Decision,
+    /// An evidence assessment verdict.
+    Evidence,
+}
 
-        exploitation + exploration
+impl std::fmt::Display for ConclusionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConclusionSource::GraphNode => write!(f, "graph_node"),
+            ConclusionSource::Decision => write!(f, "decision"),
+            ConclusionSource::Evidence => write!(f, "evidence"),
+        }
     }
+}
 
-    /// Update UCB score.
-    pub fn update_ucb(&mut self, parent_visits: i32, exploration_constant: f64) {
-        self.ucb_score = Some(self.calculate_ucb(parent_visits, exploration_constant));
-        self.updated_at = Utc::now();
+impl std::str::FromStr for ConclusionSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graph_node" => Ok(ConclusionSource::GraphNode),
+            "decision" => Ok(ConclusionSource::Decision),
+            "evidence" => Ok(ConclusionSource::Evidence),
+            _ => Err(format!("Unknown conclusion source: {}", s)),
+        }
     }
 }
 
-/// MCTS node for guided reasoning exploration.
+/// An accepted conclusion stored in the cross-session knowledge base.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MCTSNode {
-    /// Unique node identifier.
+pub struct Conclusion {
+    /// Unique conclusion identifier.
     pub id: String,
-    /// Parent session ID.
-    pub session_id: String,
-    /// Associated timeline ID.
-    pub timeline_id: Option<String>,
-    /// Associated timeline branch ID.
-    pub branch_id: String,
-    /// Parent MCTS node ID.
-    pub parent_node_id: Option<String>,
-    /// Node content/reasoning state.
+    /// Kind of record the conclusion was promoted from.
+    pub source_type: ConclusionSource,
+    /// ID of the graph node, decision, or evidence assessment.
+    pub source_id: String,
+    /// Originating session (cleared if the session is deleted).
+    pub session_id: Option<String>,
+    /// Question, claim, or problem the conclusion answers.
+    pub topic: String,
+    /// The accepted conclusion.
     pub content: String,
-    /// Visit count (N).
-    pub visit_count: i32,
-    /// Total value (W).
-    pub total_value: f64,
-    /// Prior probability from policy network.
-    pub prior: f64,
-    /// UCB score for selection.
-    pub ucb_score: f64,
-    /// Whether this node is fully expanded.
-    pub is_expanded: bool,
-    /// Whether this is a terminal node.
-    pub is_terminal: bool,
-    /// Simulation depth reached.
-    pub simulation_depth: i32,
-    /// When the node was created.
+    /// Confidence in the conclusion (0.0-1.0).
+    pub confidence: Option<f64>,
+    /// When the conclusion was promoted.
     pub created_at: DateTime<Utc>,
-    /// When the node was last visited.
-    pub last_visited: DateTime<Utc>,
     /// Optional metadata.
     pub metadata: Option<serde_json::Value>,
+    /// Workspace the conclusion belongs to.
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
 }
 
-impl MCTSNode {
-    /// Create a new MCTS node.
+impl Conclusion {
+    /// Create a new conclusion.
     pub fn new(
-        session_id: impl Into<String>,
-        branch_id: impl Into<String>,
+        source_type: ConclusionSource,
+        source_id: impl Into<String>,
+        topic: impl Into<String>,
         content: impl Into<String>,
     ) -> Self {
-        let now = Utc::now();
         Self {
             id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            timeline_id: None,
-            branch_id: branch_id.into(),
-            parent_node_id: None,
+            source_type,
+            source_id: source_id.into(),
+            session_id: None,
+            topic: topic.into(),
             content: content.into(),
-            visit_count: 0,
-            total_value: 0.0,
-            prior: 0.5,
-            ucb_score: 0.0,
-            is_expanded: false,
-            is_terminal: false,
-            simulation_depth: 0,
-            created_at: now,
-            last_visited: now,
+            confidence: None,
+            created_at: Utc::now(),
             metadata: None,
+            workspace_id: default_workspace_id(),
         }
     }
 
-    /// Set parent node.
-    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
-        self.parent_node_id = Some(parent_id.into());
+    /// Set the originating session.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Set timeline.
-    pub fn with_timeline(mut self, timeline_id: impl Into<String>) -> Self {
-        self.timeline_id = Some(timeline_id.into());
+    /// Set confidence.
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = Some(confidence.clamp(0.0, 1.0));
         self
     }
 
-    /// Set prior probability.
-    pub fn with_prior(mut self, prior: f64) -> Self {
-        self.prior = prior.clamp(0.0, 1.0);
+    /// Set metadata.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
         self
     }
+}
 
-    /// Mark as terminal.
-    pub fn as_terminal(mut self) -> Self {
-        self.is_terminal = true;
+/// Filter options for knowledge base queries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KnowledgeFilter {
+    /// Only match conclusions promoted from this kind of record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_type: Option<ConclusionSource>,
+    /// Minimum conclusion confidence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f64>,
+    /// Only match conclusions in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Limit number of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl KnowledgeFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by source type.
+    pub fn with_source_type(mut self, source_type: ConclusionSource) -> Self {
+        self.source_type = Some(source_type);
         self
     }
 
-    /// Set metadata.
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Require a minimum confidence.
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = Some(min_confidence);
         self
     }
 
-    /// Calculate UCB1 score for node selection.
-    /// UCB1 = Q(s,a) + c * sqrt(ln(N_parent) / N(s,a))
-    pub fn calculate_ucb(&self, parent_visits: i32, exploration_constant: f64) -> f64 {
-        if self.visit_count == 0 {
-            return f64::INFINITY;
+    /// Only match conclusions in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
+        self
+    }
+
+    /// Limit number of results.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// A conclusion matched by a knowledge base query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeHit {
+    /// The matching conclusion.
+    pub conclusion: Conclusion,
+    /// Relevance score (higher is more relevant).
+    pub score: f64,
+}
+
+// ============================================================================
+// Audit Log Storage Types
+// ============================================================================
+
+/// Append-only record of a mutating tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unique entry identifier.
+    pub id: String,
+    /// When the call completed.
+    pub created_at: DateTime<Utc>,
+    /// Who made the call (e.g. `mcp:<client name>`).
+    pub actor: String,
+    /// Tool that was called.
+    pub tool_name: String,
+    /// Session the call affected, if any.
+    pub session_id: Option<String>,
+    /// Hash of the session state before the call (`None` if it did not exist).
+    pub before_hash: Option<String>,
+    /// Hash of the session state after the call (`None` if it no longer exists).
+    pub after_hash: Option<String>,
+    /// Whether the call succeeded.
+    pub success: bool,
+    /// Error message for failed calls.
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Create a new successful audit entry.
+    pub fn new(actor: impl Into<String>, tool_name: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: Utc::now(),
+            actor: actor.into(),
+            tool_name: tool_name.into(),
+            session_id: None,
+            before_hash: None,
+            after_hash: None,
+            success: true,
+            error: None,
         }
+    }
 
-        let exploitation = self.total_value / self.visit_count as f64;
-        let exploration = exploration_constant
-            * ((parent_visits as f64).ln() / self.visit_count as f64).sqrt();
+    /// Set the affected session.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
 
-        exploitation + exploration
+    /// Set the session state hashes before and after the call.
+    pub fn with_hashes(mut self, before: Option<String>, after: Option<String>) -> Self {
+        self.before_hash = before;
+        self.after_hash = after;
+        self
     }
 
-    /// Record a visit with value update.
-    pub fn record_visit(&mut self, value: f64) {
-        self.visit_count += 1;
-        self.total_value += value;
-        self.last_visited = Utc::now();
+    /// Mark the call as failed.
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.success = false;
+        self.error = Some(error.into());
+        self
     }
 
-    /// Set simulation depth.
-    pub fn with_simulation_depth(mut self, depth: i32) -> Self {
-        self.simulation_depth = depth;
+    /// Whether the call changed the session state.
+    pub fn changed_state(&self) -> bool {
+        self.before_hash != self.after_hash
+    }
+}
+
+/// Filter options for querying the audit log.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditFilter {
+    /// Filter by affected session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Filter by tool name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Filter by actor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Only include entries recorded after this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<DateTime<Utc>>,
+    /// Limit number of results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl AuditFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by affected session.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Update last visited timestamp.
-    pub fn update_last_visited(&mut self) {
-        self.last_visited = Utc::now();
+    /// Filter by tool name.
+    pub fn with_tool(mut self, tool_name: impl Into<String>) -> Self {
+        self.tool_name = Some(tool_name.into());
+        self
     }
 
-    /// Update UCB score.
-    pub fn update_ucb(&mut self, parent_visits: i32, exploration_constant: f64) {
-        self.ucb_score = self.calculate_ucb(parent_visits, exploration_constant);
+    /// Filter by actor.
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
     }
 
-    /// Mark as expanded.
-    pub fn mark_expanded(&mut self) {
-        self.is_expanded = true;
+    /// Only include entries recorded after this time.
+    pub fn after(mut self, time: DateTime<Utc>) -> Self {
+        self.after = Some(time);
+        self
     }
 
-    /// Get average value (Q-value).
-    pub fn q_value(&self) -> f64 {
-        if self.visit_count == 0 {
-            0.0
-        } else {
-            self.total_value / self.visit_count as f64
+    /// Limit number of results.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+// ============================================================================
+// Pipe Response Cache Types
+// ============================================================================
+
+/// A pipe response kept in the persistent response cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipeCacheEntry {
+    /// Hash of the pipe name and request content.
+    pub key: String,
+    /// Pipe that produced the response.
+    pub pipe_name: String,
+    /// The cached response.
+    pub response: serde_json::Value,
+    /// When the response was cached.
+    pub created_at: DateTime<Utc>,
+    /// When the entry stops being served.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PipeCacheEntry {
+    /// Create an entry that expires `ttl_secs` from now.
+    pub fn new(
+        key: impl Into<String>,
+        pipe_name: impl Into<String>,
+        response: serde_json::Value,
+        ttl_secs: u64,
+    ) -> Self {
+        let created_at = Utc::now();
+        Self {
+            key: key.into(),
+            pipe_name: pipe_name.into(),
+            response,
+            created_at,
+            expires_at: created_at + chrono::Duration::seconds(ttl_secs as i64),
         }
     }
+
+    /// Whether the entry has expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
 }
 
-/// Type of counterfactual intervention.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum InterventionType {
-    /// Change a decision/thought to something else.
-    #[default]
-    Change,
-    /// Remove a decision/thought entirely.
-    Remove,
-    /// Replace with alternative reasoning.
-    Replace,
-    /// Add new information at a point.
-    Inject,
+// ============================================================================
+// Pipe Payload Log Types
+// ============================================================================
+
+/// Redacted request and response bodies of one pipe call attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipePayload {
+    /// Unique identifier.
+    pub id: String,
+    /// Pipe that was called.
+    pub pipe_name: String,
+    /// Attempt number (1 for the first try, 2+ for retries).
+    pub attempt: u32,
+    /// Request body sent to the pipe.
+    pub request: serde_json::Value,
+    /// Response body, if the attempt succeeded.
+    pub response: Option<serde_json::Value>,
+    /// Error message, if the attempt failed.
+    pub error: Option<String>,
+    /// Duration of the attempt in milliseconds.
+    pub latency_ms: i64,
+    /// When the attempt finished.
+    pub created_at: DateTime<Utc>,
 }
 
-impl std::fmt::Display for InterventionType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            InterventionType::Change => write!(f, "change"),
-            InterventionType::Remove => write!(f, "remove"),
-            InterventionType::Replace => write!(f, "replace"),
-            InterventionType::Inject => write!(f, "inject"),
+impl PipePayload {
+    /// Create a payload record for a pipe call attempt.
+    pub fn new(pipe_name: impl Into<String>, attempt: u32, request: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            pipe_name: pipe_name.into(),
+            attempt,
+            request,
+            response: None,
+            error: None,
+            latency_ms: 0,
+            created_at: Utc::now(),
         }
     }
-}
 
-impl std::str::FromStr for InterventionType {
-    type Err = String;
+    /// Record the response body.
+    pub fn with_response(mut self, response: serde_json::Value) -> Self {
+        self.response = Some(response);
+        self
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "change" => Ok(InterventionType::Change),
-            "remove" => Ok(InterventionType::Remove),
-            "replace" => Ok(InterventionType::Replace),
-            "inject" => Ok(InterventionType::Inject),
-            _ => Err(format!("Unknown intervention type: {}", s)),
-        }
+    /// Record the failure.
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    /// Record how long the attempt took.
+    pub fn with_latency(mut self, latency_ms: i64) -> Self {
+        self.latency_ms = latency_ms;
+        self
     }
 }
 
-/// Counterfactual analysis result.
+// ============================================================================
+// Routing History Types
+// ============================================================================
+
+/// An auto-routing decision and the outcome later observed for it.
+///
+/// The routed content is kept as an embedding so later decisions can find
+/// similar content and weigh how each mode worked out for it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CounterfactualAnalysis {
-    /// Unique analysis identifier.
+pub struct RoutingRecord {
+    /// Unique record identifier.
     pub id: String,
-    /// Parent session ID.
-    pub session_id: String,
-    /// Associated timeline ID.
-    pub timeline_id: Option<String>,
-    /// Original branch being analyzed.
-    pub original_branch_id: String,
-    /// The counterfactual question posed.
-    pub question: String,
-    /// Intervention type.
-    pub intervention_type: InterventionType,
-    /// The specific intervention made.
-    pub intervention: String,
-    /// Target thought/decision being modified.
-    pub target_thought_id: Option<String>,
-    /// Counterfactual branch ID (the "what if" path).
-    pub counterfactual_branch_id: String,
-    /// Predicted outcome difference (-1.0 to 1.0).
-    pub outcome_delta: f64,
-    /// Causal attribution score (0.0-1.0).
-    pub causal_attribution: f64,
-    /// Confidence in the analysis (0.0-1.0).
+    /// Session the decision was made for, if any.
+    pub session_id: Option<String>,
+    /// Workspace the decision was made in.
+    #[serde(default = "default_workspace_id")]
+    pub workspace_id: String,
+    /// Start of the routed content.
+    pub content_preview: String,
+    /// Embedding model that produced the vector.
+    pub model: String,
+    /// Embedding of the routed content.
+    #[serde(skip)]
+    pub vector: Vec<f32>,
+    /// Mode the router recommended.
+    pub recommended_mode: String,
+    /// Confidence of the recommendation (0.0-1.0).
     pub confidence: f64,
-    /// Detailed comparison results (JSON).
-    pub comparison: serde_json::Value,
-    /// When the analysis was created.
+    /// Whether past outcomes changed the recommendation.
+    pub history_applied: bool,
+    /// Mode the session actually ran, once observed.
+    pub outcome_mode: Option<String>,
+    /// Mean confidence of the session's thoughts or graph node scores.
+    pub outcome_confidence: Option<f64>,
+    /// Bias and fallacy detections recorded in the session.
+    pub detection_count: i32,
+    /// Rating given with `reasoning_routing_feedback` (0.0-1.0).
+    pub feedback_score: Option<f64>,
+    /// Note given with the rating.
+    pub feedback_note: Option<String>,
+    /// Combined outcome score (0.0-1.0), `None` until an outcome is observed.
+    pub outcome_score: Option<f64>,
+    /// When the decision was made.
     pub created_at: DateTime<Utc>,
-    /// Optional metadata.
-    pub metadata: Option<serde_json::Value>,
+    /// When the outcome was last evaluated.
+    pub evaluated_at: Option<DateTime<Utc>>,
 }
 
-impl CounterfactualAnalysis {
-    /// Create a new counterfactual analysis.
+impl RoutingRecord {
+    /// Create a record of a routing decision with no outcome yet.
     pub fn new(
-        session_id: impl Into<String>,
-        original_branch_id: impl Into<String>,
-        question: impl Into<String>,
-        intervention_type: InterventionType,
-        intervention: impl Into<String>,
-        counterfactual_branch_id: impl Into<String>,
+        content_preview: impl Into<String>,
+        model: impl Into<String>,
+        vector: Vec<f32>,
+        recommended_mode: impl Into<String>,
+        confidence: f64,
     ) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
-            session_id: session_id.into(),
-            timeline_id: None,
-            original_branch_id: original_branch_id.into(),
-            question: question.into(),
-            intervention_type,
-            intervention: intervention.into(),
-            target_thought_id: None,
-            counterfactual_branch_id: counterfactual_branch_id.into(),
-            outcome_delta: 0.0,
-            causal_attribution: 0.0,
-            confidence: 0.0,
-            comparison: serde_json::json!({}),
+            session_id: None,
+            workspace_id: default_workspace_id(),
+            content_preview: content_preview.into(),
+            model: model.into(),
+            vector,
+            recommended_mode: recommended_mode.into(),
+            confidence,
+            history_applied: false,
+            outcome_mode: None,
+            outcome_confidence: None,
+            detection_count: 0,
+            feedback_score: None,
+            feedback_note: None,
+            outcome_score: None,
             created_at: Utc::now(),
-            metadata: None,
+            evaluated_at: None,
         }
     }
 
-    /// Set timeline.
-    pub fn with_timeline(mut self, timeline_id: impl Into<String>) -> Self {
-        self.timeline_id = Some(timeline_id.into());
+    /// Set the session the decision was made for.
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Set target thought.
-    pub fn with_target_thought(mut self, thought_id: impl Into<String>) -> Self {
-        self.target_thought_id = Some(thought_id.into());
+    /// Mark the recommendation as changed by past outcomes.
+    pub fn with_history_applied(mut self, applied: bool) -> Self {
+        self.history_applied = applied;
         self
     }
 
-    /// Set outcome delta.
-    pub fn with_outcome_delta(mut self, delta: f64) -> Self {
-        self.outcome_delta = delta.clamp(-1.0, 1.0);
-        self
+    /// Mode the outcome is credited to: the mode the session ran, or the
+    /// recommended mode when the session has not been observed.
+    pub fn credited_mode(&self) -> &str {
+        self.outcome_mode
+            .as_deref()
+            .unwrap_or(&self.recommended_mode)
     }
+}
 
-    /// Set causal attribution.
-    pub fn with_causal_attribution(mut self, attribution: f64) -> Self {
-        self.causal_attribution = attribution.clamp(0.0, 1.0);
+/// Filter for routing records.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingRecordFilter {
+    /// Filter by session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Only match records in this workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Only match records embedded with this model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Maximum number of records, most recent first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl RoutingRecordFilter {
+    /// Create a new empty filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by session.
+    pub fn in_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
         self
     }
 
-    /// Set confidence.
-    pub fn with_confidence(mut self, confidence: f64) -> Self {
-        self.confidence = confidence.clamp(0.0, 1.0);
+    /// Only match records in a workspace.
+    pub fn in_workspace(mut self, workspace_id: impl Into<String>) -> Self {
+        self.workspace_id = Some(workspace_id.into());
         self
     }
 
-    /// Set comparison results.
-    pub fn with_comparison(mut self, comparison: serde_json::Value) -> Self {
-        self.comparison = comparison;
+    /// Only match records embedded with a model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
         self
     }
 
-    /// Set metadata.
-    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
-        self.metadata = Some(metadata);
+    /// Limit the number of records.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
         self
     }
 }
@@ -1952,8 +3676,36 @@ pub trait Storage: Send + Sync {
     async fn get_session(&self, id: &str) -> StorageResult<Option<Session>>;
     /// Update an existing session.
     async fn update_session(&self, session: &Session) -> StorageResult<()>;
-    /// Delete a session by ID.
+    /// Permanently delete a session and, via cascading deletes, all its data.
+    ///
+    /// Prefer [`Storage::trash_session`] for deletions that may need undoing.
     async fn delete_session(&self, id: &str) -> StorageResult<()>;
+    /// Move a session to the trash, hiding it from listings and searches.
+    ///
+    /// Returns `false` if the session does not exist or is already trashed.
+    async fn trash_session(&self, id: &str) -> StorageResult<bool>;
+    /// Restore a session from the trash.
+    ///
+    /// Returns `false` if the session does not exist or is not in the trash.
+    async fn restore_session(&self, id: &str) -> StorageResult<bool>;
+
+    /// Tag a session. Tags are normalized with [`normalize_tag`]; adding an
+    /// existing tag is a no-op.
+    async fn add_tag(&self, session_id: &str, tag: &str) -> StorageResult<()>;
+    /// Remove a tag from a session. Returns whether the tag was present.
+    async fn remove_tag(&self, session_id: &str, tag: &str) -> StorageResult<bool>;
+    /// Find all sessions with the given tag, most recently updated first.
+    async fn find_sessions_by_tag(&self, tag: &str) -> StorageResult<Vec<Session>>;
+    /// List sessions matching the filter, most recently updated first.
+    async fn list_sessions(&self, filter: SessionFilter) -> StorageResult<Vec<Session>>;
+    /// Copy a session into a new one, leaving the original untouched.
+    ///
+    /// The fork keeps the source's mode, metadata, tags, and workspace, and
+    /// gets copies of its thoughts with their revision history, branches with
+    /// their cross-references, checkpoints, and graph nodes and edges. Every
+    /// copy has a new ID, with references between copies remapped. Embeddings are not copied; the
+    /// fork is embedded again on demand.
+    async fn fork_session(&self, session_id: &str) -> StorageResult<SessionFork>;
 
     /// Get an existing session or create a new one.
     ///
@@ -1969,10 +3721,7 @@ pub trait Storage: Send + Sync {
         &self,
         session_id: &Option<String>,
         mode: &str,
-    ) -> StorageResult<Session>
-    where
-        Self: Sized,
-    {
+    ) -> StorageResult<Session> {
         match session_id {
             Some(id) => match self.get_session(id).await? {
                 Some(session) => Ok(session),
@@ -1995,14 +3744,137 @@ pub trait Storage: Send + Sync {
 
     /// Create a new thought.
     async fn create_thought(&self, thought: &Thought) -> StorageResult<()>;
+    /// Create several thoughts in a single transaction (all or none are stored).
+    async fn create_thoughts_batch(&self, thoughts: &[Thought]) -> StorageResult<()>;
     /// Get a thought by ID.
     async fn get_thought(&self, id: &str) -> StorageResult<Option<Thought>>;
     /// Get all thoughts in a session.
     async fn get_session_thoughts(&self, session_id: &str) -> StorageResult<Vec<Thought>>;
+    /// Get one page of a session's thoughts, oldest first.
+    async fn get_session_thoughts_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<Thought>>;
     /// Get all thoughts in a branch.
     async fn get_branch_thoughts(&self, branch_id: &str) -> StorageResult<Vec<Thought>>;
     /// Get the most recent thought in a session.
     async fn get_latest_thought(&self, session_id: &str) -> StorageResult<Option<Thought>>;
+    /// Full-text search over thought content across sessions.
+    ///
+    /// Every word in `query` must match (stemmed, case-insensitive). Results
+    /// are ordered by relevance. An empty query returns no results.
+    async fn search_thoughts(
+        &self,
+        query: &str,
+        filter: ThoughtSearchFilter,
+    ) -> StorageResult<Vec<ThoughtSearchHit>>;
+    /// Replace a thought's content, keeping the current version in its
+    /// revision history.
+    ///
+    /// `confidence` keeps the current confidence when `None`. Returns the
+    /// revised thought.
+    async fn revise_thought(
+        &self,
+        thought_id: &str,
+        content: &str,
+        confidence: Option<f64>,
+        reason: Option<&str>,
+    ) -> StorageResult<Thought>;
+    /// Get the superseded versions of a thought, oldest first.
+    async fn get_thought_revisions(&self, thought_id: &str) -> StorageResult<Vec<ThoughtRevision>>;
+    /// Replace a thought's metadata with `thought.metadata`.
+    async fn update_thought_metadata(&self, thought: &Thought) -> StorageResult<()>;
+
+    // Embedding operations (semantic similarity)
+
+    /// Save embeddings, replacing any existing vector for the same record and model.
+    async fn save_embeddings(&self, embeddings: &[Embedding]) -> StorageResult<()>;
+    /// Get thoughts and graph nodes with no embedding for `model`, oldest first.
+    ///
+    /// `workspace_id` restricts candidates to sessions in one workspace.
+    async fn get_embedding_candidates(
+        &self,
+        model: &str,
+        workspace_id: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<EmbeddingCandidate>>;
+    /// Find records whose `model` embeddings are most similar to `vector`.
+    ///
+    /// Results are ordered by descending cosine similarity.
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        model: &str,
+        filter: SimilarityFilter,
+    ) -> StorageResult<Vec<SimilarMatch>>;
+
+    // Knowledge base operations
+
+    /// Store an accepted conclusion.
+    ///
+    /// Fails if a conclusion was already promoted from the same source.
+    async fn save_conclusion(&self, conclusion: &Conclusion) -> StorageResult<()>;
+    /// Get a conclusion by ID.
+    async fn get_conclusion(&self, id: &str) -> StorageResult<Option<Conclusion>>;
+    /// Get the conclusion promoted from a given source record, if any.
+    async fn get_conclusion_by_source(
+        &self,
+        source_type: ConclusionSource,
+        source_id: &str,
+    ) -> StorageResult<Option<Conclusion>>;
+    /// Search conclusions by topic and content.
+    ///
+    /// Matches conclusions containing any term of `query`, most relevant first.
+    /// An empty query lists the most recent conclusions instead.
+    async fn search_conclusions(
+        &self,
+        query: &str,
+        filter: KnowledgeFilter,
+    ) -> StorageResult<Vec<KnowledgeHit>>;
+    /// Delete a conclusion. Returns whether it existed.
+    async fn delete_conclusion(&self, id: &str) -> StorageResult<bool>;
+
+    // Audit log operations
+
+    /// Append an entry to the audit log. Entries cannot be modified or deleted.
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> StorageResult<()>;
+    /// List audit entries matching the filter, most recent first.
+    async fn list_audit_entries(&self, filter: AuditFilter) -> StorageResult<Vec<AuditEntry>>;
+
+    // Pipe response cache operations
+
+    /// Get an unexpired cached pipe response by key.
+    async fn get_pipe_cache(&self, key: &str) -> StorageResult<Option<PipeCacheEntry>>;
+    /// Store a pipe response, replacing any entry with the same key.
+    ///
+    /// Expired entries are dropped at the same time.
+    async fn put_pipe_cache(&self, entry: &PipeCacheEntry) -> StorageResult<()>;
+
+    // Pipe payload log operations
+
+    /// Store the redacted payloads of a pipe call attempt.
+    async fn log_pipe_payload(&self, payload: &PipePayload) -> StorageResult<()>;
+    /// List stored payloads, most recent first, optionally for one pipe.
+    async fn get_pipe_payloads(
+        &self,
+        pipe_name: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<PipePayload>>;
+
+    // Routing history operations
+
+    /// Store an auto-routing decision.
+    async fn create_routing_record(&self, record: &RoutingRecord) -> StorageResult<()>;
+    /// Get a routing record by ID.
+    async fn get_routing_record(&self, id: &str) -> StorageResult<Option<RoutingRecord>>;
+    /// Store the outcome fields of a routing record.
+    async fn update_routing_outcome(&self, record: &RoutingRecord) -> StorageResult<()>;
+    /// List routing records matching the filter, most recent first.
+    async fn list_routing_records(
+        &self,
+        filter: RoutingRecordFilter,
+    ) -> StorageResult<Vec<RoutingRecord>>;
 
     // Branch operations (tree mode)
 
@@ -2051,12 +3923,20 @@ pub trait Storage: Send + Sync {
     /// Get aggregated usage summary for all pipes.
     ///
     /// Returns metrics for each pipe that has been invoked, ordered by total calls descending.
-    async fn get_pipe_usage_summary(&self) -> StorageResult<Vec<PipeUsageSummary>>;
+    /// `workspace_id` restricts the summary to one workspace; `None` covers all of them.
+    async fn get_pipe_usage_summary(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Vec<PipeUsageSummary>>;
 
     /// Get usage summary for a specific pipe.
     ///
-    /// Returns None if the pipe has never been invoked.
-    async fn get_pipe_summary(&self, pipe_name: &str) -> StorageResult<Option<PipeUsageSummary>>;
+    /// Returns None if the pipe has never been invoked (in `workspace_id`, if given).
+    async fn get_pipe_summary(
+        &self,
+        pipe_name: &str,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Option<PipeUsageSummary>>;
 
     /// Get invocations with optional filtering.
     ///
@@ -2066,23 +3946,38 @@ pub trait Storage: Send + Sync {
 
     /// Get total invocation count.
     ///
-    /// Optionally filter by pipe name.
-    async fn get_invocation_count(&self, pipe_name: Option<&str>) -> StorageResult<u64>;
+    /// Optionally filter by pipe name and workspace.
+    async fn get_invocation_count(
+        &self,
+        pipe_name: Option<&str>,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<u64>;
 
     /// Get fallback usage metrics.
     ///
-    /// Returns aggregated statistics about fallback usage across all invocations,
-    /// including breakdown by type and pipe.
-    async fn get_fallback_metrics(&self) -> StorageResult<FallbackMetricsSummary>;
+    /// Returns aggregated statistics about fallback usage across all invocations
+    /// (or those in `workspace_id`, if given), including breakdown by type and pipe.
+    async fn get_fallback_metrics(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<FallbackMetricsSummary>;
 
     // Graph node operations (GoT mode)
 
     /// Create a new graph node.
     async fn create_graph_node(&self, node: &GraphNode) -> StorageResult<()>;
+    /// Create several graph nodes in a single transaction (all or none are stored).
+    async fn create_graph_nodes_batch(&self, nodes: &[GraphNode]) -> StorageResult<()>;
     /// Get a graph node by ID.
     async fn get_graph_node(&self, id: &str) -> StorageResult<Option<GraphNode>>;
     /// Get all graph nodes in a session.
     async fn get_session_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>>;
+    /// Get one page of a session's graph nodes, oldest first.
+    async fn get_session_graph_nodes_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<GraphNode>>;
     /// Get active (non-pruned) graph nodes in a session.
     async fn get_active_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>>;
     /// Get root nodes in a session.
@@ -2098,6 +3993,8 @@ pub trait Storage: Send + Sync {
 
     /// Create a new graph edge.
     async fn create_graph_edge(&self, edge: &GraphEdge) -> StorageResult<()>;
+    /// Create several graph edges in a single transaction (all or none are stored).
+    async fn create_graph_edges_batch(&self, edges: &[GraphEdge]) -> StorageResult<()>;
     /// Get a graph edge by ID.
     async fn get_graph_edge(&self, id: &str) -> StorageResult<Option<GraphEdge>>;
     /// Get edges originating from a node.
@@ -2109,6 +4006,19 @@ pub trait Storage: Send + Sync {
     /// Delete a graph edge by ID.
     async fn delete_graph_edge(&self, id: &str) -> StorageResult<()>;
 
+    // Graph comparison operations (GoT pairwise ranking)
+
+    /// Create several graph comparisons in a single transaction (all or none are stored).
+    async fn create_graph_comparisons_batch(
+        &self,
+        comparisons: &[GraphComparison],
+    ) -> StorageResult<()>;
+    /// Get all comparisons in a session, oldest first.
+    async fn get_session_graph_comparisons(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<GraphComparison>>;
+
     // State snapshot operations (backtracking)
 
     /// Create a new state snapshot.
@@ -2184,6 +4094,44 @@ pub trait Storage: Send + Sync {
     /// Delete a perspective analysis by ID.
     async fn delete_perspective(&self, id: &str) -> StorageResult<()>;
 
+    // ========================================================================
+    // SWOT analysis operations (swot mode)
+    // ========================================================================
+
+    /// Create a new SWOT analysis.
+    async fn create_swot_analysis(&self, analysis: &SwotAnalysis) -> StorageResult<()>;
+
+    /// Find SWOT analyses across sessions, newest first.
+    ///
+    /// Analyses in trashed sessions are skipped.
+    async fn find_swot_analyses(&self, filter: SwotFilter) -> StorageResult<Vec<SwotAnalysis>>;
+
+    // ========================================================================
+    // Risk register operations (risk mode)
+    // ========================================================================
+
+    /// Create a new risk.
+    async fn create_risk(&self, risk: &Risk) -> StorageResult<()>;
+
+    /// Get a risk by ID.
+    async fn get_risk(&self, id: &str) -> StorageResult<Option<Risk>>;
+
+    /// Get a session's risk register, highest priority number first.
+    async fn get_session_risks(&self, session_id: &str) -> StorageResult<Vec<Risk>>;
+
+    /// Update a risk's scores, owner, mitigation and history.
+    async fn update_risk(&self, risk: &Risk) -> StorageResult<()>;
+
+    // ========================================================================
+    // Code finding operations (code reasoning mode)
+    // ========================================================================
+
+    /// Create a new code finding.
+    async fn create_code_finding(&self, finding: &CodeFinding) -> StorageResult<()>;
+
+    /// Get a session's code findings, oldest first.
+    async fn get_session_code_findings(&self, session_id: &str) -> StorageResult<Vec<CodeFinding>>;
+
     // ========================================================================
     // Evidence assessment operations (evidence mode)
     // ========================================================================
@@ -2310,6 +4258,16 @@ pub trait Storage: Send + Sync {
     /// Delete an MCTS node by ID.
     async fn delete_mcts_node(&self, id: &str) -> StorageResult<()>;
 
+    /// Record the statistics of one MCTS iteration.
+    async fn create_mcts_iteration(&self, iteration: &MctsIteration) -> StorageResult<()>;
+
+    /// Get MCTS iteration statistics, grouped by search (oldest search
+    /// first) and ordered by iteration within each search.
+    async fn get_mcts_iterations(
+        &self,
+        filter: MctsIterationFilter,
+    ) -> StorageResult<Vec<MctsIteration>>;
+
     // ========================================================================
     // Counterfactual analysis operations (Time Machine)
     // ========================================================================
@@ -2340,6 +4298,72 @@ pub trait Storage: Send + Sync {
 
     /// Delete a counterfactual analysis by ID.
     async fn delete_counterfactual(&self, id: &str) -> StorageResult<()>;
+
+    // ========================================================================
+    // Retention operations
+    // ========================================================================
+
+    /// Delete sessions last updated before the cutoff.
+    ///
+    /// Dependent records (thoughts, branches, graphs, snapshots, etc.) are
+    /// removed via cascading deletes. Returns the number of sessions deleted.
+    async fn delete_sessions_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64>;
+
+    /// Permanently delete sessions moved to the trash before the cutoff.
+    ///
+    /// Returns the number of sessions purged.
+    async fn purge_trashed_sessions(&self, cutoff: DateTime<Utc>) -> StorageResult<u64>;
+
+    /// Delete state snapshots created before the cutoff.
+    ///
+    /// Returns the number of snapshots deleted.
+    async fn delete_snapshots_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64>;
+
+    /// Delete invocation log entries created before the cutoff.
+    ///
+    /// Returns the number of invocations deleted.
+    async fn delete_invocations_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64>;
+
+    /// Delete pipe payloads created before `cutoff`.
+    ///
+    /// Returns the number of payloads deleted.
+    async fn delete_pipe_payloads_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64>;
+
+    /// Trim the invocation log to the newest `max_entries` entries.
+    ///
+    /// Returns the number of invocations deleted.
+    async fn trim_invocations(&self, max_entries: u64) -> StorageResult<u64>;
+
+    // ========================================================================
+    // Maintenance operations
+    // ========================================================================
+
+    /// Check the database for corruption.
+    ///
+    /// Returns the problems found; an empty list means the database is intact.
+    async fn integrity_check(&self) -> StorageResult<Vec<String>>;
+
+    /// Compare the migrations applied to the database with those this binary
+    /// embeds.
+    ///
+    /// Fails if the database cannot be reached.
+    async fn schema_status(&self) -> StorageResult<MigrationStatus>;
+
+    /// Rebuild the database file, reclaiming space left by deleted records.
+    async fn vacuum(&self) -> StorageResult<()>;
+
+    /// Refresh the statistics the query planner uses to choose indexes.
+    async fn analyze(&self) -> StorageResult<()>;
+
+    // ========================================================================
+    // Backup operations
+    // ========================================================================
+
+    /// Write a consistent snapshot of the live database to `target`.
+    ///
+    /// Safe to call while the server is handling requests. When `compress` is
+    /// true the snapshot is gzip-compressed. Fails if `target` already exists.
+    async fn backup(&self, target: &Path, compress: bool) -> StorageResult<BackupReport>;
 }
 
 #[cfg(test)]
@@ -2595,6 +4619,7 @@ mod tests {
         assert_eq!(EdgeType::Aggregates.to_string(), "aggregates");
         assert_eq!(EdgeType::Supports.to_string(), "supports");
         assert_eq!(EdgeType::Contradicts.to_string(), "contradicts");
+        assert_eq!(EdgeType::DependsOn.to_string(), "depends_on");
     }
 
     #[test]
@@ -2651,6 +4676,7 @@ mod tests {
             EdgeType::Aggregates,
             EdgeType::Supports,
             EdgeType::Contradicts,
+            EdgeType::DependsOn,
         ] {
             let str_val = edge_type.to_string();
             let parsed = EdgeType::from_str(&str_val).unwrap();
@@ -2731,6 +4757,7 @@ mod tests {
     fn test_detection_type_display() {
         assert_eq!(DetectionType::Bias.to_string(), "bias");
         assert_eq!(DetectionType::Fallacy.to_string(), "fallacy");
+        assert_eq!(DetectionType::Calculation.to_string(), "calculation");
     }
 
     #[test]
@@ -2743,6 +4770,10 @@ mod tests {
             DetectionType::from_str("fallacy").unwrap(),
             DetectionType::Fallacy
         );
+        assert_eq!(
+            DetectionType::from_str("calculation").unwrap(),
+            DetectionType::Calculation
+        );
     }
 
     #[test]
@@ -2774,13 +4805,75 @@ mod tests {
 
     #[test]
     fn test_detection_type_round_trip() {
-        for detection_type in [DetectionType::Bias, DetectionType::Fallacy] {
+        for detection_type in [
+            DetectionType::Bias,
+            DetectionType::Fallacy,
+            DetectionType::Calculation,
+        ] {
             let str_val = detection_type.to_string();
             let parsed = DetectionType::from_str(&str_val).unwrap();
             assert_eq!(parsed, detection_type);
         }
     }
 
+    // ========================================================================
+    // CodeFindingKind tests
+    // ========================================================================
+
+    #[test]
+    fn test_code_finding_kind_round_trip() {
+        for kind in [
+            CodeFindingKind::Invariant,
+            CodeFindingKind::Risk,
+            CodeFindingKind::Test,
+        ] {
+            assert_eq!(CodeFindingKind::from_str(&kind.to_string()).unwrap(), kind);
+        }
+        assert_eq!(
+            CodeFindingKind::from_str("RISK").unwrap(),
+            CodeFindingKind::Risk
+        );
+        assert_eq!(
+            CodeFindingKind::from_str("bug").unwrap_err(),
+            "Unknown code finding kind: bug"
+        );
+    }
+
+    #[test]
+    fn test_code_finding_builder() {
+        let finding = CodeFinding::new("s1", "edge_cases", CodeFindingKind::Risk, "Overflow")
+            .with_thought("t1")
+            .with_location("line 4")
+            .with_severity(9);
+        assert_eq!(finding.thought_id.as_deref(), Some("t1"));
+        assert_eq!(finding.location.as_deref(), Some("line 4"));
+        assert_eq!(finding.severity, Some(5));
+        assert!(finding.metadata.is_none());
+    }
+
+    // ========================================================================
+    // MctsPolicy tests
+    // ========================================================================
+
+    #[test]
+    fn test_mcts_policy_round_trip() {
+        for policy in [MctsPolicy::Ucb1, MctsPolicy::ProgressiveWidening] {
+            assert_eq!(MctsPolicy::from_str(&policy.to_string()).unwrap(), policy);
+        }
+        assert_eq!(
+            MctsPolicy::from_str("Progressive-Widening").unwrap(),
+            MctsPolicy::ProgressiveWidening
+        );
+        assert_eq!(
+            serde_json::to_value(MctsPolicy::ProgressiveWidening).unwrap(),
+            "progressive_widening"
+        );
+        assert_eq!(
+            MctsPolicy::from_str("uct").unwrap_err(),
+            "Unknown MCTS policy: uct"
+        );
+    }
+
     // ========================================================================
     // Builder method tests
     // ========================================================================
@@ -0,0 +1,117 @@
+//! Data retention and pruning.
+//!
+//! Applies a [`RetentionConfig`] to storage by deleting expired sessions,
+//! state snapshots, invocation logs, and sessions left in the trash. Used by the background task started
+//! in `McpServer::run` and by the `prune` CLI subcommand.
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::{SharedStorage, Storage};
+use crate::config::RetentionConfig;
+use crate::error::StorageResult;
+
+/// Counts of records removed by a pruning run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruneReport {
+    /// Number of expired sessions deleted (with their dependent records).
+    pub sessions_deleted: u64,
+    /// Number of expired state snapshots deleted.
+    pub snapshots_deleted: u64,
+    /// Number of invocation log entries deleted (by age or count limit).
+    pub invocations_deleted: u64,
+    /// Number of trashed sessions permanently deleted.
+    pub trashed_sessions_purged: u64,
+    /// Number of logged pipe payloads deleted.
+    pub payloads_deleted: u64,
+}
+
+impl PruneReport {
+    /// Total number of records deleted.
+    pub fn total(&self) -> u64 {
+        self.sessions_deleted
+            + self.snapshots_deleted
+            + self.invocations_deleted
+            + self.payloads_deleted
+            + self.trashed_sessions_purged
+    }
+}
+
+/// Prune storage according to the retention configuration.
+///
+/// Age-based pruning runs first (sessions, snapshots, invocations, then pipe
+/// payloads), followed by trimming the invocation log to `max_invocations`
+/// entries and purging sessions trashed more than `trash_retention_days` ago. Limits that
+/// are not configured are skipped.
+pub async fn prune<S: Storage + ?Sized>(
+    storage: &S,
+    config: &RetentionConfig,
+) -> StorageResult<PruneReport> {
+    let mut report = PruneReport::default();
+
+    if let Some(days) = config.retention_days {
+        let cutoff = Utc::now() - Duration::days(i64::from(days));
+        report.sessions_deleted = storage.delete_sessions_before(cutoff).await?;
+        report.snapshots_deleted = storage.delete_snapshots_before(cutoff).await?;
+        report.invocations_deleted = storage.delete_invocations_before(cutoff).await?;
+        report.payloads_deleted = storage.delete_pipe_payloads_before(cutoff).await?;
+    }
+
+    if let Some(max_entries) = config.max_invocations {
+        report.invocations_deleted += storage.trim_invocations(max_entries).await?;
+    }
+
+    if let Some(days) = config.trash_retention_days {
+        let cutoff = Utc::now() - Duration::days(i64::from(days));
+        report.trashed_sessions_purged = storage.purge_trashed_sessions(cutoff).await?;
+    }
+
+    Ok(report)
+}
+
+/// Spawn the background retention task.
+///
+/// Returns `None` without spawning anything if no retention limit is configured.
+/// The task prunes once immediately and then every `interval_secs` seconds;
+/// failures are logged and retried on the next tick.
+pub fn spawn_retention_task(
+    storage: SharedStorage,
+    config: RetentionConfig,
+) -> Option<JoinHandle<()>> {
+    if !config.is_enabled() {
+        debug!("Retention disabled, not starting pruning task");
+        return None;
+    }
+
+    info!(
+        retention_days = ?config.retention_days,
+        max_invocations = ?config.max_invocations,
+        trash_retention_days = ?config.trash_retention_days,
+        interval_secs = config.interval_secs,
+        "Starting retention task"
+    );
+
+    Some(tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(config.interval_secs));
+        loop {
+            interval.tick().await;
+            match prune(storage.as_ref(), &config).await {
+                Ok(report) if report.total() > 0 => {
+                    info!(
+                        sessions = report.sessions_deleted,
+                        snapshots = report.snapshots_deleted,
+                        invocations = report.invocations_deleted,
+                        payloads = report.payloads_deleted,
+                        trashed_sessions = report.trashed_sessions_purged,
+                        "Retention pruning completed"
+                    );
+                }
+                Ok(_) => debug!("Retention pruning found nothing to delete"),
+                Err(e) => warn!(error = %e, "Retention pruning failed"),
+            }
+        }
+    }))
+}
@@ -1,11 +1,17 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::migrate::Migrator;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteExecutor, SqlitePool, SqlitePoolOptions,
+};
 use sqlx::Row;
+use std::collections::HashMap;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tracing::{info, warn};
+use uuid::Uuid;
 
 // ============================================================================
 // Timestamp Reconstruction Tracking
@@ -86,11 +92,22 @@ fn parse_timestamp_or_skip(
     }
 }
 
+use super::backup::{
+    backup_error, compress_file, decompress_file, is_gzip_file, staging_path, vacuum_target_uri,
+    BackupReport,
+};
+use super::migrations::{EmbeddedMigration, MigrationStatus, RecordedMigration};
 use super::{
-    Branch, Checkpoint, CounterfactualAnalysis, CrossRef, Decision, Detection, DetectionType,
-    EvidenceAssessment, FallbackMetricsSummary, GraphEdge, GraphNode, InterventionType, Invocation,
-    MCTSNode, MetricsFilter, PerspectiveAnalysis, PipeUsageSummary, ProbabilityUpdate, Session,
-    StateSnapshot, Storage, StoredCriterion, Thought, Timeline, TimelineBranch, TimelineState,
+    cosine_similarity, normalize_tag, AuditEntry, AuditFilter, Branch, Checkpoint, CodeFinding,
+    Conclusion, ConclusionSource, CounterfactualAnalysis, CrossRef, Decision, Detection,
+    DetectionType, Embedding, EmbeddingCandidate, EvidenceAssessment, FallbackMetricsSummary,
+    GraphComparison, GraphEdge, GraphNode, InterventionType, Invocation, KnowledgeFilter,
+    KnowledgeHit, MCTSNode, MctsIteration, MctsIterationFilter, MetricsFilter, Page, Pagination,
+    PerspectiveAnalysis, PipeCacheEntry, PipePayload, PipeUsageSummary, ProbabilityUpdate, Risk,
+    RoutingRecord, RoutingRecordFilter, Session, SessionFilter, SessionFork, SharedStorage,
+    SimilarMatch, SimilarityFilter, StateSnapshot, Storage, StoredCriterion, SwotAnalysis,
+    SwotFilter, Thought, ThoughtRevision, ThoughtSearchFilter, ThoughtSearchHit, Timeline,
+    TimelineBranch, TimelineState,
 };
 #[cfg(test)]
 use super::{BranchState, CrossRefType, EdgeType};
@@ -120,6 +137,311 @@ fn serialize_json_required<T: serde::Serialize>(
     })
 }
 
+/// Build an FTS5 MATCH expression from free-form user input.
+///
+/// Each word is quoted so punctuation and FTS5 operators in the input are
+/// treated as plain text; the words are implicitly AND-ed. Returns `None` if
+/// the input contains no searchable words.
+fn fts5_match_query(query: &str) -> Option<String> {
+    fts5_join_terms(query, " ")
+}
+
+/// Like [`fts5_match_query`], but matches documents containing any of the words.
+fn fts5_match_any_query(query: &str) -> Option<String> {
+    fts5_join_terms(query, " OR ")
+}
+
+fn fts5_join_terms(query: &str, separator: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(separator))
+    }
+}
+
+/// Quote a passphrase as an SQL string literal for `PRAGMA key`.
+#[cfg(feature = "sqlcipher")]
+fn sqlcipher_key_literal(key: &str) -> String {
+    format!("'{}'", key.replace('\'', "''"))
+}
+
+/// Check whether a file starts with the plaintext SQLite header.
+///
+/// SQLCipher databases are encrypted from the first byte, so a readable header
+/// means the file has not been encrypted yet.
+#[cfg(feature = "sqlcipher")]
+fn is_plaintext_sqlite(path: &std::path::Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .map(|_| &header == b"SQLite format 3\0")
+        .unwrap_or(false)
+}
+
+/// Select expression yielding a session's tags as a sorted JSON array.
+///
+/// Used in queries over `sessions` so tags load with the session row.
+const SESSION_TAGS_COLUMN: &str = "(SELECT json_group_array(tag) FROM (SELECT tag FROM session_tags WHERE session_id = sessions.id ORDER BY tag)) AS tags";
+
+/// Append `LIMIT`/`OFFSET` clauses to a dynamically built query.
+fn push_limit_offset(query: &mut String, limit: Option<u32>, offset: Option<u32>) {
+    // SQLite only accepts OFFSET after LIMIT; -1 means no limit
+    match (limit, offset) {
+        (Some(limit), Some(offset)) => {
+            query.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset))
+        }
+        (Some(limit), None) => query.push_str(&format!(" LIMIT {}", limit)),
+        (None, Some(offset)) => query.push_str(&format!(" LIMIT -1 OFFSET {}", offset)),
+        (None, None) => {}
+    }
+}
+
+/// Encode an embedding vector as little-endian `f32` bytes for BLOB storage.
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Decode an embedding vector stored by [`encode_vector`].
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Insert a session and its tags on a connection (typically a transaction).
+async fn insert_session(conn: &mut SqliteConnection, session: &Session) -> StorageResult<()> {
+    let metadata = serialize_json(&session.metadata, "session.metadata")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO sessions (id, mode, created_at, updated_at, metadata, active_branch_id, workspace_id, forked_from, thread_id)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&session.id)
+    .bind(&session.mode)
+    .bind(session.created_at.to_rfc3339())
+    .bind(session.updated_at.to_rfc3339())
+    .bind(&metadata)
+    .bind(&session.active_branch_id)
+    .bind(&session.workspace_id)
+    .bind(&session.forked_from)
+    .bind(&session.thread_id)
+    .execute(&mut *conn)
+    .await?;
+
+    for tag in session.tags.iter().filter_map(|t| normalize_tag(t)) {
+        sqlx::query(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag, created_at) VALUES (?, ?, ?)",
+        )
+        .bind(&session.id)
+        .bind(tag)
+        .bind(session.created_at.to_rfc3339())
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Insert a thought using any executor (pool or transaction).
+async fn insert_thought<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    thought: &Thought,
+) -> StorageResult<()> {
+    let metadata = serialize_json(&thought.metadata, "thought.metadata")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO thoughts (id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&thought.id)
+    .bind(&thought.session_id)
+    .bind(&thought.content)
+    .bind(thought.confidence)
+    .bind(&thought.mode)
+    .bind(&thought.parent_id)
+    .bind(&thought.branch_id)
+    .bind(thought.created_at.to_rfc3339())
+    .bind(&metadata)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a thought revision using any executor (pool or transaction).
+async fn insert_thought_revision<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    revision: &ThoughtRevision,
+) -> StorageResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO thought_revisions (id, thought_id, session_id, version, content, confidence, reason, revised_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&revision.id)
+    .bind(&revision.thought_id)
+    .bind(&revision.session_id)
+    .bind(revision.version as i64)
+    .bind(&revision.content)
+    .bind(revision.confidence)
+    .bind(&revision.reason)
+    .bind(revision.revised_at.to_rfc3339())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a graph node using any executor (pool or transaction).
+async fn insert_graph_node<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    node: &GraphNode,
+) -> StorageResult<()> {
+    let metadata = serialize_json(&node.metadata, "graph_node.metadata")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO graph_nodes (id, session_id, content, node_type, score, depth, is_terminal, is_root, is_active, created_at, metadata)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&node.id)
+    .bind(&node.session_id)
+    .bind(&node.content)
+    .bind(node.node_type.to_string())
+    .bind(node.score)
+    .bind(node.depth)
+    .bind(node.is_terminal)
+    .bind(node.is_root)
+    .bind(node.is_active)
+    .bind(node.created_at.to_rfc3339())
+    .bind(&metadata)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a graph edge using any executor (pool or transaction).
+async fn insert_graph_edge<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    edge: &GraphEdge,
+) -> StorageResult<()> {
+    let metadata = serialize_json(&edge.metadata, "graph_edge.metadata")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO graph_edges (id, session_id, from_node, to_node, edge_type, weight, created_at, metadata)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&edge.id)
+    .bind(&edge.session_id)
+    .bind(&edge.from_node)
+    .bind(&edge.to_node)
+    .bind(edge.edge_type.to_string())
+    .bind(edge.weight)
+    .bind(edge.created_at.to_rfc3339())
+    .bind(&metadata)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a branch using any executor (pool or transaction).
+async fn insert_branch<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    branch: &Branch,
+) -> StorageResult<()> {
+    let metadata = serialize_json(&branch.metadata, "branch.metadata")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO branches (id, session_id, name, parent_branch_id, priority, confidence, state, created_at, updated_at, metadata)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&branch.id)
+    .bind(&branch.session_id)
+    .bind(&branch.name)
+    .bind(&branch.parent_branch_id)
+    .bind(branch.priority)
+    .bind(branch.confidence)
+    .bind(branch.state.to_string())
+    .bind(branch.created_at.to_rfc3339())
+    .bind(branch.updated_at.to_rfc3339())
+    .bind(&metadata)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a cross-reference using any executor (pool or transaction).
+async fn insert_cross_ref<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    cross_ref: &CrossRef,
+) -> StorageResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO cross_refs (id, from_branch_id, to_branch_id, ref_type, reason, strength, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&cross_ref.id)
+    .bind(&cross_ref.from_branch_id)
+    .bind(&cross_ref.to_branch_id)
+    .bind(cross_ref.ref_type.to_string())
+    .bind(&cross_ref.reason)
+    .bind(cross_ref.strength)
+    .bind(cross_ref.created_at.to_rfc3339())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a checkpoint using any executor (pool or transaction).
+async fn insert_checkpoint<'e, E: SqliteExecutor<'e>>(
+    executor: E,
+    checkpoint: &Checkpoint,
+) -> StorageResult<()> {
+    let snapshot = serialize_json_required(&checkpoint.snapshot, "checkpoint.snapshot")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO checkpoints (id, session_id, branch_id, name, description, snapshot, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&checkpoint.id)
+    .bind(&checkpoint.session_id)
+    .bind(&checkpoint.branch_id)
+    .bind(&checkpoint.name)
+    .bind(&checkpoint.description)
+    .bind(&snapshot)
+    .bind(checkpoint.created_at.to_rfc3339())
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
 /// Static migrator that embeds migrations at compile time
 static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
@@ -131,7 +453,39 @@ pub struct SqliteStorage {
 
 impl SqliteStorage {
     /// Create a new SQLite storage instance
+    ///
+    /// If the configuration selects an in-memory database (see
+    /// [`DatabaseConfig::is_in_memory`]), an ephemeral storage is created instead
+    /// and nothing is written to disk.
     pub async fn new(config: &DatabaseConfig) -> StorageResult<Self> {
+        if config.is_in_memory() {
+            info!("Using ephemeral in-memory database (data will not persist)");
+            return Self::new_in_memory().await;
+        }
+
+        let storage = Self::connect(config).await?;
+        storage.run_migrations().await?;
+
+        Ok(storage)
+    }
+
+    /// Compare the migrations embedded in this binary with those applied to
+    /// the configured database, without applying anything.
+    pub async fn migration_status(config: &DatabaseConfig) -> StorageResult<MigrationStatus> {
+        if config.is_in_memory() || !config.path.exists() {
+            // A fresh database has nothing applied; don't create the file here
+            return Ok(MigrationStatus::compare(embedded_migrations(), Vec::new()));
+        }
+
+        let storage = Self::connect(config).await?;
+        let status = storage.read_migration_status().await;
+        storage.pool.close().await;
+        status
+    }
+
+    /// Open a connection pool to the configured database file without
+    /// running migrations.
+    async fn connect(config: &DatabaseConfig) -> StorageResult<Self> {
         // Ensure parent directory exists
         if let Some(parent) = config.path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| StorageError::Connection {
@@ -146,6 +500,7 @@ impl SqliteStorage {
                 message: format!("Invalid database URL: {}", e),
             })?
             .create_if_missing(true);
+        let options = Self::apply_encryption_key(options, config)?;
 
         let pool = SqlitePoolOptions::new()
             .max_connections(config.max_connections)
@@ -156,15 +511,218 @@ impl SqliteStorage {
             })?;
 
         let storage = Self { pool };
-        storage.run_migrations().await?;
+        if config.encryption_key.is_some() {
+            storage.verify_encryption_key().await?;
+        }
 
         Ok(storage)
     }
 
+    /// Restore the database file from a backup created by [`Storage::backup`].
+    ///
+    /// Gzip-compressed backups are detected automatically. The backup is
+    /// integrity-checked before it replaces the database at `config.path`, so a
+    /// bad backup leaves the existing database untouched. Nothing else may have
+    /// the database open while restoring.
+    pub async fn restore(backup: &Path, config: &DatabaseConfig) -> StorageResult<()> {
+        use sqlx::{ConnectOptions, Connection};
+
+        if config.is_in_memory() {
+            return Err(StorageError::Backup {
+                message: "Cannot restore into an in-memory database".to_string(),
+            });
+        }
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(backup_error("Failed to create database directory"))?;
+        }
+
+        let staging = staging_path(&config.path);
+        let (source, dest) = (backup.to_path_buf(), staging.clone());
+        tokio::task::spawn_blocking(move || {
+            if is_gzip_file(&source)? {
+                decompress_file(&source, &dest)
+            } else {
+                std::fs::copy(&source, &dest)
+                    .map(|_| ())
+                    .map_err(backup_error("Failed to copy backup"))
+            }
+        })
+        .await
+        .map_err(backup_error("Restore task failed"))??;
+
+        let staging_config = DatabaseConfig {
+            path: staging.clone(),
+            ..config.clone()
+        };
+        let verified = async {
+            let options = Self::apply_encryption_key(
+                SqliteConnectOptions::new()
+                    .filename(&staging)
+                    .read_only(true),
+                &staging_config,
+            )?;
+            let mut conn = options.connect().await?;
+            let status: String = sqlx::query_scalar("PRAGMA integrity_check")
+                .fetch_one(&mut conn)
+                .await?;
+            conn.close().await?;
+
+            if status == "ok" {
+                Ok(())
+            } else {
+                Err(StorageError::Backup {
+                    message: format!("Backup failed integrity check: {}", status),
+                })
+            }
+        }
+        .await;
+
+        if let Err(e) = verified {
+            let _ = std::fs::remove_file(&staging);
+            return Err(e);
+        }
+
+        // Stale WAL files from the old database would be replayed onto the restored one
+        for suffix in ["-wal", "-shm"] {
+            let mut sidecar = config.path.as_os_str().to_owned();
+            sidecar.push(suffix);
+            let _ = std::fs::remove_file(sidecar);
+        }
+
+        std::fs::rename(&staging, &config.path)
+            .map_err(backup_error("Failed to replace database"))?;
+
+        info!(
+            backup = %backup.display(),
+            path = %config.path.display(),
+            "Database restore completed"
+        );
+        Ok(())
+    }
+
+    /// Configure SQLCipher encryption on the connection options, if a key is set.
+    #[cfg(feature = "sqlcipher")]
+    fn apply_encryption_key(
+        options: SqliteConnectOptions,
+        config: &DatabaseConfig,
+    ) -> StorageResult<SqliteConnectOptions> {
+        let Some(key) = config.encryption_key.as_deref() else {
+            return Ok(options);
+        };
+
+        if is_plaintext_sqlite(&config.path) {
+            return Err(StorageError::Connection {
+                message: format!(
+                    "Database {} is not encrypted; run `encrypt-db` to migrate it before setting DATABASE_ENCRYPTION_KEY",
+                    config.path.display()
+                ),
+            });
+        }
+
+        info!("Database encryption enabled (SQLCipher)");
+        Ok(options.pragma("key", sqlcipher_key_literal(key)))
+    }
+
+    /// Reject an encryption key when SQLCipher support is not compiled in.
+    ///
+    /// Plain SQLite silently ignores `PRAGMA key`, which would leave data
+    /// unencrypted while the operator believes it is protected.
+    #[cfg(not(feature = "sqlcipher"))]
+    fn apply_encryption_key(
+        options: SqliteConnectOptions,
+        config: &DatabaseConfig,
+    ) -> StorageResult<SqliteConnectOptions> {
+        if config.encryption_key.is_some() {
+            return Err(StorageError::Connection {
+                message: "DATABASE_ENCRYPTION_KEY is set but this build does not include the `sqlcipher` feature".to_string(),
+            });
+        }
+        Ok(options)
+    }
+
+    /// Check that the configured key can decrypt the database.
+    ///
+    /// SQLCipher accepts any key at connect time and only fails on the first
+    /// read, so probe the schema up front to fail fast with a clear error.
+    async fn verify_encryption_key(&self) -> StorageResult<()> {
+        sqlx::query("SELECT count(*) FROM sqlite_master")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| StorageError::Connection {
+                message: format!("Invalid database encryption key: {}", e),
+            })?;
+        Ok(())
+    }
+
+    /// Encrypt an existing plaintext database into a new SQLCipher database.
+    ///
+    /// The source is left untouched; `target` must not already exist. Once the
+    /// export succeeds, replace the original file with `target` and start the
+    /// server with `DATABASE_ENCRYPTION_KEY` set to `key`.
+    #[cfg(feature = "sqlcipher")]
+    pub async fn encrypt_database(
+        source: &std::path::Path,
+        target: &std::path::Path,
+        key: &str,
+    ) -> StorageResult<()> {
+        use sqlx::ConnectOptions;
+
+        if key.is_empty() {
+            return Err(StorageError::Connection {
+                message: "Encryption key must not be empty".to_string(),
+            });
+        }
+        if !is_plaintext_sqlite(source) {
+            return Err(StorageError::Connection {
+                message: format!("{} is not a plaintext SQLite database", source.display()),
+            });
+        }
+        if target.exists() {
+            return Err(StorageError::Connection {
+                message: format!("Target {} already exists", target.display()),
+            });
+        }
+
+        // create_if_missing lets ATTACH create the target file
+        let mut conn = SqliteConnectOptions::new()
+            .filename(source)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .map_err(|e| StorageError::Connection {
+                message: format!("Failed to open source database: {}", e),
+            })?;
+
+        sqlx::query("ATTACH DATABASE ? AS encrypted KEY ?")
+            .bind(target.to_string_lossy().into_owned())
+            .bind(key)
+            .execute(&mut conn)
+            .await?;
+        sqlx::query("SELECT sqlcipher_export('encrypted')")
+            .execute(&mut conn)
+            .await?;
+        sqlx::query("DETACH DATABASE encrypted")
+            .execute(&mut conn)
+            .await?;
+
+        info!(
+            source = %source.display(),
+            target = %target.display(),
+            "Encrypted database export completed"
+        );
+        Ok(())
+    }
+
     /// Run database migrations using embedded sqlx migrations
+    ///
+    /// Fails with a description of the offending migrations if the database
+    /// schema is ahead of this binary or an applied migration was changed.
     async fn run_migrations(&self) -> StorageResult<()> {
         info!("Running database migrations...");
 
+        self.read_migration_status().await?.check()?;
+
         MIGRATOR
             .run(&self.pool)
             .await
@@ -176,12 +734,54 @@ impl SqliteStorage {
         Ok(())
     }
 
+    /// Read the migrations recorded in the database and compare them with
+    /// the embedded ones.
+    async fn read_migration_status(&self) -> StorageResult<MigrationStatus> {
+        let has_table: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let recorded = if has_table {
+            let rows: Vec<(i64, String, String, bool, Vec<u8>)> = sqlx::query_as(
+                "SELECT version, description, installed_on, success, checksum FROM _sqlx_migrations ORDER BY version",
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(
+                    |(version, description, installed_on, success, checksum)| RecordedMigration {
+                        version,
+                        description,
+                        checksum,
+                        success,
+                        installed_on: NaiveDateTime::parse_from_str(
+                            &installed_on,
+                            "%Y-%m-%d %H:%M:%S",
+                        )
+                        .ok()
+                        .map(|t| t.and_utc()),
+                    },
+                )
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(MigrationStatus::compare(embedded_migrations(), recorded))
+    }
+
     /// Get the underlying pool for advanced queries
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 
-    /// Create an in-memory SQLite storage instance for testing
+    /// Create an ephemeral in-memory SQLite storage instance.
+    ///
+    /// The database lives in a single pooled connection that is never recycled,
+    /// so data persists for the lifetime of this storage (and its clones) and is
+    /// discarded when the last clone is dropped.
     pub async fn new_in_memory() -> StorageResult<Self> {
         let options = SqliteConnectOptions::from_str("sqlite::memory:").map_err(|e| {
             StorageError::Connection {
@@ -189,8 +789,13 @@ impl SqliteStorage {
             }
         })?;
 
+        // Each SQLite connection to :memory: is a separate database, so the pool
+        // must hold exactly one connection and never close it.
         let pool = SqlitePoolOptions::new()
             .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
             .connect_with(options)
             .await
             .map_err(|e| StorageError::Connection {
@@ -200,38 +805,800 @@ impl SqliteStorage {
         let storage = Self { pool };
         storage.run_migrations().await?;
 
-        Ok(storage)
+        Ok(storage)
+    }
+}
+
+/// Migrations embedded in the binary, excluding down scripts.
+fn embedded_migrations() -> Vec<EmbeddedMigration> {
+    MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| EmbeddedMigration {
+            version: m.version,
+            description: m.description.to_string(),
+            checksum: m.checksum.to_vec(),
+            reversible: m.migration_type.is_reversible(),
+        })
+        .collect()
+}
+
+impl From<SqliteStorage> for SharedStorage {
+    fn from(storage: SqliteStorage) -> Self {
+        Arc::new(storage)
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn create_session(&self, session: &Session) -> StorageResult<()> {
+        let mut tx = self.pool.begin().await?;
+        insert_session(&mut tx, session).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_session(&self, id: &str) -> StorageResult<Option<Session>> {
+        let row: Option<SessionRow> = sqlx::query_as(&format!(
+            r#"
+            SELECT id, mode, created_at, updated_at, metadata, active_branch_id, deleted_at,
+                   workspace_id, forked_from, thread_id, {}
+            FROM sessions
+            WHERE id = ?
+            "#,
+            SESSION_TAGS_COLUMN
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    async fn update_session(&self, session: &Session) -> StorageResult<()> {
+        let metadata = serialize_json(&session.metadata, "session.metadata")?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE sessions
+            SET mode = ?, updated_at = ?, metadata = ?, active_branch_id = ?, thread_id = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&session.mode)
+        .bind(session.updated_at.to_rfc3339())
+        .bind(&metadata)
+        .bind(&session.active_branch_id)
+        .bind(&session.thread_id)
+        .bind(&session.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StorageError::SessionNotFound {
+                session_id: session.id.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: &str) -> StorageResult<()> {
+        sqlx::query("DELETE FROM sessions WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn trash_session(&self, id: &str) -> StorageResult<bool> {
+        let result =
+            sqlx::query("UPDATE sessions SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                .bind(Utc::now().to_rfc3339())
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn restore_session(&self, id: &str) -> StorageResult<bool> {
+        let result = sqlx::query(
+            "UPDATE sessions SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_tag(&self, session_id: &str, tag: &str) -> StorageResult<()> {
+        let tag = normalize_tag(tag).ok_or_else(|| StorageError::Query {
+            message: "Tag must not be empty".to_string(),
+        })?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO session_tags (session_id, tag, created_at)
+            SELECT id, ?, ? FROM sessions WHERE id = ?
+            "#,
+        )
+        .bind(&tag)
+        .bind(Utc::now().to_rfc3339())
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
+        // Zero rows means either the tag already exists or the session doesn't
+        if result.rows_affected() == 0 && self.get_session(session_id).await?.is_none() {
+            return Err(StorageError::SessionNotFound {
+                session_id: session_id.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn remove_tag(&self, session_id: &str, tag: &str) -> StorageResult<bool> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(false);
+        };
+
+        let result = sqlx::query("DELETE FROM session_tags WHERE session_id = ? AND tag = ?")
+            .bind(session_id)
+            .bind(tag)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_sessions_by_tag(&self, tag: &str) -> StorageResult<Vec<Session>> {
+        self.list_sessions(SessionFilter::new().with_tag(tag)).await
+    }
+
+    async fn list_sessions(&self, filter: SessionFilter) -> StorageResult<Vec<Session>> {
+        let mut query = format!(
+            r#"
+            SELECT id, mode, created_at, updated_at, metadata, active_branch_id, deleted_at,
+                   workspace_id, forked_from, thread_id, {}
+            FROM sessions
+            WHERE 1=1
+            "#,
+            SESSION_TAGS_COLUMN
+        );
+        let mut bindings: Vec<String> = Vec::new();
+
+        query.push_str(if filter.in_trash {
+            " AND deleted_at IS NOT NULL"
+        } else {
+            " AND deleted_at IS NULL"
+        });
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            query.push_str(" AND workspace_id = ?");
+            bindings.push(workspace_id.clone());
+        }
+
+        if let Some(ref mode) = filter.mode {
+            query.push_str(" AND mode = ?");
+            bindings.push(mode.clone());
+        }
+
+        if let Some(ref after) = filter.created_after {
+            query.push_str(" AND created_at >= ?");
+            bindings.push(after.to_rfc3339());
+        }
+
+        if let Some(ref before) = filter.created_before {
+            query.push_str(" AND created_at < ?");
+            bindings.push(before.to_rfc3339());
+        }
+
+        if let Some(ref tag) = filter.tag {
+            let Some(tag) = normalize_tag(tag) else {
+                return Ok(Vec::new());
+            };
+            query.push_str(" AND id IN (SELECT session_id FROM session_tags WHERE tag = ?)");
+            bindings.push(tag);
+        }
+
+        match filter.has_detections {
+            Some(true) => query.push_str(
+                " AND EXISTS (SELECT 1 FROM detections WHERE detections.session_id = sessions.id)",
+            ),
+            Some(false) => query.push_str(
+                " AND NOT EXISTS (SELECT 1 FROM detections WHERE detections.session_id = sessions.id)",
+            ),
+            None => {}
+        }
+
+        query.push_str(" ORDER BY updated_at DESC, id ASC");
+
+        push_limit_offset(&mut query, filter.limit, filter.offset);
+
+        let mut sql_query = sqlx::query_as::<_, SessionRow>(&query);
+        for binding in &bindings {
+            sql_query = sql_query.bind(binding);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn fork_session(&self, session_id: &str) -> StorageResult<SessionFork> {
+        let source =
+            self.get_session(session_id)
+                .await?
+                .ok_or_else(|| StorageError::SessionNotFound {
+                    session_id: session_id.to_string(),
+                })?;
+        let thoughts = self.get_session_thoughts(session_id).await?;
+        let branches = self.get_session_branches(session_id).await?;
+        let checkpoints = self.get_session_checkpoints(session_id).await?;
+        let nodes = self.get_session_graph_nodes(session_id).await?;
+        let edges = self.get_session_edges(session_id).await?;
+        let mut cross_refs = Vec::new();
+        for branch in &branches {
+            cross_refs.extend(self.get_cross_refs_from(&branch.id).await?);
+        }
+        let mut revisions = Vec::new();
+        for thought in &thoughts {
+            revisions.extend(self.get_thought_revisions(&thought.id).await?);
+        }
+
+        // Every copied record gets a fresh ID; references to records outside
+        // the session (or that vanished meanwhile) are dropped
+        let ids: HashMap<String, String> = thoughts
+            .iter()
+            .map(|t| &t.id)
+            .chain(branches.iter().map(|b| &b.id))
+            .chain(checkpoints.iter().map(|c| &c.id))
+            .chain(nodes.iter().map(|n| &n.id))
+            .map(|id| (id.clone(), Uuid::new_v4().to_string()))
+            .collect();
+        let remap = |id: &Option<String>| id.as_ref().and_then(|id| ids.get(id).cloned());
+
+        let now = Utc::now();
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            active_branch_id: remap(&source.active_branch_id),
+            deleted_at: None,
+            forked_from: Some(source.id.clone()),
+            // The thread's server-side history belongs to the source session
+            thread_id: None,
+            ..source
+        };
+        let mut fork = SessionFork {
+            session,
+            thoughts: 0,
+            thought_revisions: 0,
+            branches: 0,
+            cross_refs: 0,
+            checkpoints: 0,
+            graph_nodes: 0,
+            graph_edges: 0,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        // Copies may reference records inserted after them (e.g. the active
+        // branch), so check foreign keys at commit
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+
+        insert_session(&mut tx, &fork.session).await?;
+        for branch in branches {
+            let branch = Branch {
+                id: ids[&branch.id].clone(),
+                session_id: fork.session.id.clone(),
+                parent_branch_id: remap(&branch.parent_branch_id),
+                ..branch
+            };
+            insert_branch(&mut *tx, &branch).await?;
+            fork.branches += 1;
+        }
+        for cross_ref in cross_refs {
+            let (Some(from), Some(to)) = (
+                ids.get(&cross_ref.from_branch_id),
+                ids.get(&cross_ref.to_branch_id),
+            ) else {
+                continue;
+            };
+            let cross_ref = CrossRef {
+                id: Uuid::new_v4().to_string(),
+                from_branch_id: from.clone(),
+                to_branch_id: to.clone(),
+                ..cross_ref
+            };
+            insert_cross_ref(&mut *tx, &cross_ref).await?;
+            fork.cross_refs += 1;
+        }
+        for thought in thoughts {
+            let thought = Thought {
+                id: ids[&thought.id].clone(),
+                session_id: fork.session.id.clone(),
+                parent_id: remap(&thought.parent_id),
+                branch_id: remap(&thought.branch_id),
+                ..thought
+            };
+            insert_thought(&mut *tx, &thought).await?;
+            fork.thoughts += 1;
+        }
+        for revision in revisions {
+            let revision = ThoughtRevision {
+                id: Uuid::new_v4().to_string(),
+                thought_id: ids[&revision.thought_id].clone(),
+                session_id: fork.session.id.clone(),
+                ..revision
+            };
+            insert_thought_revision(&mut *tx, &revision).await?;
+            fork.thought_revisions += 1;
+        }
+        for checkpoint in checkpoints {
+            let checkpoint = Checkpoint {
+                id: ids[&checkpoint.id].clone(),
+                session_id: fork.session.id.clone(),
+                branch_id: remap(&checkpoint.branch_id),
+                ..checkpoint
+            };
+            insert_checkpoint(&mut *tx, &checkpoint).await?;
+            fork.checkpoints += 1;
+        }
+        for node in nodes {
+            let node = GraphNode {
+                id: ids[&node.id].clone(),
+                session_id: fork.session.id.clone(),
+                ..node
+            };
+            insert_graph_node(&mut *tx, &node).await?;
+            fork.graph_nodes += 1;
+        }
+        for edge in edges {
+            let (Some(from), Some(to)) = (ids.get(&edge.from_node), ids.get(&edge.to_node)) else {
+                continue;
+            };
+            let edge = GraphEdge {
+                id: Uuid::new_v4().to_string(),
+                session_id: fork.session.id.clone(),
+                from_node: from.clone(),
+                to_node: to.clone(),
+                ..edge
+            };
+            insert_graph_edge(&mut *tx, &edge).await?;
+            fork.graph_edges += 1;
+        }
+        tx.commit().await?;
+
+        Ok(fork)
+    }
+
+    async fn create_thought(&self, thought: &Thought) -> StorageResult<()> {
+        insert_thought(&self.pool, thought).await
+    }
+
+    async fn create_thoughts_batch(&self, thoughts: &[Thought]) -> StorageResult<()> {
+        if thoughts.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for thought in thoughts {
+            insert_thought(&mut *tx, thought).await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_thought(&self, id: &str) -> StorageResult<Option<Thought>> {
+        let row: Option<ThoughtRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
+            FROM thoughts
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    async fn get_session_thoughts(&self, session_id: &str) -> StorageResult<Vec<Thought>> {
+        let rows: Vec<ThoughtRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
+            FROM thoughts
+            WHERE session_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn get_session_thoughts_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<Thought>> {
+        let rows: Vec<ThoughtRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
+            FROM thoughts
+            WHERE session_id = ?
+            ORDER BY created_at ASC, id ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(session_id)
+        .bind(i64::from(pagination.limit) + 1)
+        .bind(i64::from(pagination.offset))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Page::from_overfetch(
+            rows.into_iter().map(|r| r.into()).collect(),
+            pagination,
+        ))
+    }
+
+    async fn get_branch_thoughts(&self, branch_id: &str) -> StorageResult<Vec<Thought>> {
+        let rows: Vec<ThoughtRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
+            FROM thoughts
+            WHERE branch_id = ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(branch_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn get_latest_thought(&self, session_id: &str) -> StorageResult<Option<Thought>> {
+        let row: Option<ThoughtRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
+            FROM thoughts
+            WHERE session_id = ?
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    async fn search_thoughts(
+        &self,
+        query: &str,
+        filter: ThoughtSearchFilter,
+    ) -> StorageResult<Vec<ThoughtSearchHit>> {
+        let Some(match_query) = fts5_match_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut sql = String::from(
+            r#"
+            SELECT t.id, t.session_id, t.content, t.confidence, t.mode, t.parent_id, t.branch_id,
+                   t.created_at, t.metadata,
+                   snippet(thoughts_fts, 1, '[', ']', '...', 16) AS snippet,
+                   bm25(thoughts_fts) AS rank
+            FROM thoughts_fts
+            JOIN thoughts t ON t.id = thoughts_fts.thought_id
+            WHERE thoughts_fts MATCH ?
+              AND t.session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)
+            "#,
+        );
+        let mut bindings: Vec<String> = vec![match_query];
+
+        if let Some(ref session_id) = filter.session_id {
+            sql.push_str(" AND t.session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref mode) = filter.mode {
+            sql.push_str(" AND t.mode = ?");
+            bindings.push(mode.clone());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            sql.push_str(" AND t.session_id IN (SELECT id FROM sessions WHERE workspace_id = ?)");
+            bindings.push(workspace_id.clone());
+        }
+
+        sql.push_str(" ORDER BY rank");
+
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut sql_query = sqlx::query_as::<_, ThoughtSearchRow>(&sql);
+        for binding in &bindings {
+            sql_query = sql_query.bind(binding);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ThoughtSearchHit {
+                thought: row.thought.into(),
+                snippet: row.snippet,
+                // bm25() is lower-is-better; flip it so higher means more relevant
+                score: -row.rank,
+            })
+            .collect())
+    }
+
+    async fn revise_thought(
+        &self,
+        thought_id: &str,
+        content: &str,
+        confidence: Option<f64>,
+        reason: Option<&str>,
+    ) -> StorageResult<Thought> {
+        let mut tx = self.pool.begin().await?;
+
+        // Archive the current version before overwriting it
+        let archived = sqlx::query(
+            r#"
+            INSERT INTO thought_revisions (id, thought_id, session_id, version, content, confidence, reason, revised_at)
+            SELECT ?, id, session_id,
+                   (SELECT COUNT(*) + 1 FROM thought_revisions WHERE thought_id = thoughts.id),
+                   content, confidence, ?, ?
+            FROM thoughts
+            WHERE id = ?
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(reason)
+        .bind(Utc::now().to_rfc3339())
+        .bind(thought_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if archived.rows_affected() == 0 {
+            return Err(StorageError::ThoughtNotFound {
+                thought_id: thought_id.to_string(),
+            });
+        }
+
+        sqlx::query(
+            "UPDATE thoughts SET content = ?, confidence = COALESCE(?, confidence) WHERE id = ?",
+        )
+        .bind(content)
+        .bind(confidence.map(|c| c.clamp(0.0, 1.0)))
+        .bind(thought_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_thought(thought_id)
+            .await?
+            .ok_or_else(|| StorageError::ThoughtNotFound {
+                thought_id: thought_id.to_string(),
+            })
+    }
+
+    async fn get_thought_revisions(&self, thought_id: &str) -> StorageResult<Vec<ThoughtRevision>> {
+        let rows: Vec<ThoughtRevisionRow> = sqlx::query_as(
+            r#"
+            SELECT id, thought_id, session_id, version, content, confidence, reason, revised_at
+            FROM thought_revisions
+            WHERE thought_id = ?
+            ORDER BY version ASC
+            "#,
+        )
+        .bind(thought_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn update_thought_metadata(&self, thought: &Thought) -> StorageResult<()> {
+        let metadata = serialize_json(&thought.metadata, "thought.metadata")?;
+
+        let result = sqlx::query("UPDATE thoughts SET metadata = ? WHERE id = ?")
+            .bind(&metadata)
+            .bind(&thought.id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StorageError::ThoughtNotFound {
+                thought_id: thought.id.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn save_embeddings(&self, embeddings: &[Embedding]) -> StorageResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for embedding in embeddings {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO embeddings (source_id, source_type, session_id, model, dimensions, vector, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&embedding.source_id)
+            .bind(embedding.source_type.to_string())
+            .bind(&embedding.session_id)
+            .bind(&embedding.model)
+            .bind(embedding.vector.len() as i64)
+            .bind(encode_vector(&embedding.vector))
+            .bind(embedding.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_embedding_candidates(
+        &self,
+        model: &str,
+        workspace_id: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<EmbeddingCandidate>> {
+        let rows = sqlx::query_as::<_, EmbeddingCandidateRow>(
+            r#"
+            SELECT id AS source_id, 'thought' AS source_type, session_id, content, created_at
+            FROM thoughts t
+            WHERE NOT EXISTS (SELECT 1 FROM embeddings e WHERE e.source_id = t.id AND e.model = ?)
+              AND (? IS NULL OR session_id IN (SELECT id FROM sessions WHERE workspace_id = ?))
+            UNION ALL
+            SELECT id AS source_id, 'graph_node' AS source_type, session_id, content, created_at
+            FROM graph_nodes n
+            WHERE NOT EXISTS (SELECT 1 FROM embeddings e WHERE e.source_id = n.id AND e.model = ?)
+              AND (? IS NULL OR session_id IN (SELECT id FROM sessions WHERE workspace_id = ?))
+            ORDER BY created_at ASC, source_id ASC
+            LIMIT ?
+            "#,
+        )
+        .bind(model)
+        .bind(workspace_id)
+        .bind(workspace_id)
+        .bind(model)
+        .bind(workspace_id)
+        .bind(workspace_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.into()).collect())
+    }
+
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        model: &str,
+        filter: SimilarityFilter,
+    ) -> StorageResult<Vec<SimilarMatch>> {
+        let mut sql = String::from(
+            r#"
+            SELECT e.source_id, e.source_type, e.session_id, e.vector,
+                   COALESCE(t.content, n.content) AS content
+            FROM embeddings e
+            LEFT JOIN thoughts t ON e.source_type = 'thought' AND t.id = e.source_id
+            LEFT JOIN graph_nodes n ON e.source_type = 'graph_node' AND n.id = e.source_id
+            WHERE e.model = ?
+              AND e.session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)
+            "#,
+        );
+        let mut bindings: Vec<String> = vec![model.to_string()];
+
+        if let Some(ref session_id) = filter.session_id {
+            sql.push_str(" AND e.session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref session_id) = filter.exclude_session_id {
+            sql.push_str(" AND e.session_id != ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(source_type) = filter.source_type {
+            sql.push_str(" AND e.source_type = ?");
+            bindings.push(source_type.to_string());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            sql.push_str(" AND e.session_id IN (SELECT id FROM sessions WHERE workspace_id = ?)");
+            bindings.push(workspace_id.clone());
+        }
+
+        let mut sql_query = sqlx::query_as::<_, SimilarityRow>(&sql);
+        for binding in &bindings {
+            sql_query = sql_query.bind(binding);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+
+        // Brute-force scan: vectors are compared in process, which is fine for
+        // the few thousand records a local reasoning database accumulates
+        let min_score = filter.min_score.unwrap_or(f64::NEG_INFINITY);
+        let mut matches: Vec<SimilarMatch> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let content = row.content?;
+                let score = cosine_similarity(vector, &decode_vector(&row.vector));
+                (score >= min_score).then(|| SimilarMatch {
+                    source_type: parse_enum_with_logging(
+                        &row.source_type,
+                        &format!("embedding {} source_type", row.source_id),
+                    ),
+                    source_id: row.source_id,
+                    session_id: row.session_id,
+                    content,
+                    score,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(limit) = filter.limit {
+            matches.truncate(limit as usize);
+        }
+
+        Ok(matches)
     }
-}
 
-#[async_trait]
-impl Storage for SqliteStorage {
-    async fn create_session(&self, session: &Session) -> StorageResult<()> {
-        let metadata = serialize_json(&session.metadata, "session.metadata")?;
+    async fn save_conclusion(&self, conclusion: &Conclusion) -> StorageResult<()> {
+        let metadata = serialize_json(&conclusion.metadata, "conclusion.metadata")?;
 
         sqlx::query(
             r#"
-            INSERT INTO sessions (id, mode, created_at, updated_at, metadata, active_branch_id)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO conclusions (id, source_type, source_id, session_id, topic, content, confidence, created_at, metadata, workspace_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&session.id)
-        .bind(&session.mode)
-        .bind(session.created_at.to_rfc3339())
-        .bind(session.updated_at.to_rfc3339())
-        .bind(&metadata)
-        .bind(&session.active_branch_id)
+        .bind(&conclusion.id)
+        .bind(conclusion.source_type.to_string())
+        .bind(&conclusion.source_id)
+        .bind(&conclusion.session_id)
+        .bind(&conclusion.topic)
+        .bind(&conclusion.content)
+        .bind(conclusion.confidence)
+        .bind(conclusion.created_at.to_rfc3339())
+        .bind(metadata)
+        .bind(&conclusion.workspace_id)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn get_session(&self, id: &str) -> StorageResult<Option<Session>> {
-        let row: Option<SessionRow> = sqlx::query_as(
+    async fn get_conclusion(&self, id: &str) -> StorageResult<Option<Conclusion>> {
+        let row = sqlx::query_as::<_, ConclusionRow>(
             r#"
-            SELECT id, mode, created_at, updated_at, metadata, active_branch_id
-            FROM sessions
+            SELECT id, source_type, source_id, session_id, topic, content, confidence, created_at, metadata,
+                   workspace_id
+            FROM conclusions
             WHERE id = ?
             "#,
         )
@@ -239,141 +1606,424 @@ impl Storage for SqliteStorage {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| r.into()))
+        Ok(row.map(Into::into))
     }
 
-    async fn update_session(&self, session: &Session) -> StorageResult<()> {
-        let metadata = serialize_json(&session.metadata, "session.metadata")?;
-
-        let result = sqlx::query(
+    async fn get_conclusion_by_source(
+        &self,
+        source_type: ConclusionSource,
+        source_id: &str,
+    ) -> StorageResult<Option<Conclusion>> {
+        let row = sqlx::query_as::<_, ConclusionRow>(
             r#"
-            UPDATE sessions
-            SET mode = ?, updated_at = ?, metadata = ?, active_branch_id = ?
-            WHERE id = ?
+            SELECT id, source_type, source_id, session_id, topic, content, confidence, created_at, metadata,
+                   workspace_id
+            FROM conclusions
+            WHERE source_type = ? AND source_id = ?
             "#,
         )
-        .bind(&session.mode)
-        .bind(session.updated_at.to_rfc3339())
-        .bind(&metadata)
-        .bind(&session.active_branch_id)
-        .bind(&session.id)
-        .execute(&self.pool)
+        .bind(source_type.to_string())
+        .bind(source_id)
+        .fetch_optional(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(StorageError::SessionNotFound {
-                session_id: session.id.clone(),
-            });
+        Ok(row.map(Into::into))
+    }
+
+    async fn search_conclusions(
+        &self,
+        query: &str,
+        filter: KnowledgeFilter,
+    ) -> StorageResult<Vec<KnowledgeHit>> {
+        let mut bindings: Vec<String> = Vec::new();
+        let mut sql = match fts5_match_any_query(query) {
+            Some(match_query) => {
+                bindings.push(match_query);
+                String::from(
+                    r#"
+                    SELECT c.id, c.source_type, c.source_id, c.session_id, c.topic, c.content,
+                           c.confidence, c.created_at, c.metadata, c.workspace_id,
+                           bm25(conclusions_fts) AS rank
+                    FROM conclusions_fts
+                    JOIN conclusions c ON c.id = conclusions_fts.conclusion_id
+                    WHERE conclusions_fts MATCH ?
+                    "#,
+                )
+            }
+            None => String::from(
+                r#"
+                SELECT c.id, c.source_type, c.source_id, c.session_id, c.topic, c.content,
+                       c.confidence, c.created_at, c.metadata, c.workspace_id,
+                       0.0 AS rank
+                FROM conclusions c
+                WHERE 1=1
+                "#,
+            ),
+        };
+
+        if let Some(source_type) = filter.source_type {
+            sql.push_str(" AND c.source_type = ?");
+            bindings.push(source_type.to_string());
         }
 
-        Ok(())
+        if let Some(min_confidence) = filter.min_confidence {
+            sql.push_str(" AND c.confidence >= ?");
+            bindings.push(min_confidence.to_string());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            sql.push_str(" AND c.workspace_id = ?");
+            bindings.push(workspace_id.clone());
+        }
+
+        sql.push_str(" ORDER BY rank, c.created_at DESC");
+        push_limit_offset(&mut sql, filter.limit, None);
+
+        let mut sql_query = sqlx::query_as::<_, KnowledgeSearchRow>(&sql);
+        for binding in &bindings {
+            sql_query = sql_query.bind(binding);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| KnowledgeHit {
+                conclusion: row.conclusion.into(),
+                // bm25() is lower-is-better; flip it so higher means more relevant
+                score: -row.rank,
+            })
+            .collect())
     }
 
-    async fn delete_session(&self, id: &str) -> StorageResult<()> {
-        sqlx::query("DELETE FROM sessions WHERE id = ?")
+    async fn delete_conclusion(&self, id: &str) -> StorageResult<bool> {
+        let result = sqlx::query("DELETE FROM conclusions WHERE id = ?")
             .bind(id)
             .execute(&self.pool)
             .await?;
 
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
-    async fn create_thought(&self, thought: &Thought) -> StorageResult<()> {
-        let metadata = serialize_json(&thought.metadata, "thought.metadata")?;
-
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> StorageResult<()> {
         sqlx::query(
             r#"
-            INSERT INTO thoughts (id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata)
+            INSERT INTO audit_log (id, created_at, actor, tool_name, session_id, before_hash, after_hash, success, error)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(&thought.id)
-        .bind(&thought.session_id)
-        .bind(&thought.content)
-        .bind(thought.confidence)
-        .bind(&thought.mode)
-        .bind(&thought.parent_id)
-        .bind(&thought.branch_id)
-        .bind(thought.created_at.to_rfc3339())
-        .bind(&metadata)
+        .bind(&entry.id)
+        .bind(entry.created_at.to_rfc3339())
+        .bind(&entry.actor)
+        .bind(&entry.tool_name)
+        .bind(&entry.session_id)
+        .bind(&entry.before_hash)
+        .bind(&entry.after_hash)
+        .bind(entry.success)
+        .bind(&entry.error)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn get_thought(&self, id: &str) -> StorageResult<Option<Thought>> {
-        let row: Option<ThoughtRow> = sqlx::query_as(
+    async fn list_audit_entries(&self, filter: AuditFilter) -> StorageResult<Vec<AuditEntry>> {
+        let mut query = String::from(
             r#"
-            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
-            FROM thoughts
-            WHERE id = ?
+            SELECT id, created_at, actor, tool_name, session_id, before_hash, after_hash, success, error
+            FROM audit_log
+            WHERE 1=1
+            "#,
+        );
+        let mut bindings: Vec<String> = Vec::new();
+
+        if let Some(ref session_id) = filter.session_id {
+            query.push_str(" AND session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref tool_name) = filter.tool_name {
+            query.push_str(" AND tool_name = ?");
+            bindings.push(tool_name.clone());
+        }
+
+        if let Some(ref actor) = filter.actor {
+            query.push_str(" AND actor = ?");
+            bindings.push(actor.clone());
+        }
+
+        if let Some(ref after) = filter.after {
+            query.push_str(" AND created_at > ?");
+            bindings.push(after.to_rfc3339());
+        }
+
+        // rowid breaks ties between entries recorded in the same instant
+        query.push_str(" ORDER BY created_at DESC, rowid DESC");
+        push_limit_offset(&mut query, filter.limit, None);
+
+        let mut sql_query = sqlx::query_as::<_, AuditRow>(&query);
+        for binding in &bindings {
+            sql_query = sql_query.bind(binding);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_pipe_cache(&self, key: &str) -> StorageResult<Option<PipeCacheEntry>> {
+        let row: Option<PipeCacheRow> = sqlx::query_as(
+            r#"
+            SELECT key, pipe_name, response, created_at, expires_at
+            FROM pipe_cache
+            WHERE key = ? AND expires_at > ?
             "#,
         )
-        .bind(id)
+        .bind(key)
+        .bind(Utc::now().to_rfc3339())
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| r.into()))
+        Ok(row.and_then(|r| {
+            let response = parse_json_or_skip(&r.response, &r.key, "response")?;
+            Some(PipeCacheEntry {
+                created_at: parse_timestamp_with_logging(
+                    &r.created_at,
+                    &format!("pipe cache {} created_at", r.key),
+                ),
+                expires_at: parse_timestamp_with_logging(
+                    &r.expires_at,
+                    &format!("pipe cache {} expires_at", r.key),
+                ),
+                key: r.key,
+                pipe_name: r.pipe_name,
+                response,
+            })
+        }))
     }
 
-    async fn get_session_thoughts(&self, session_id: &str) -> StorageResult<Vec<Thought>> {
-        let rows: Vec<ThoughtRow> = sqlx::query_as(
+    async fn put_pipe_cache(&self, entry: &PipeCacheEntry) -> StorageResult<()> {
+        let response = serialize_json_required(&entry.response, "pipe_cache.response")?;
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM pipe_cache WHERE expires_at <= ?")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
             r#"
-            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
-            FROM thoughts
-            WHERE session_id = ?
-            ORDER BY created_at ASC
+            INSERT OR REPLACE INTO pipe_cache (key, pipe_name, response, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
-        .bind(session_id)
-        .fetch_all(&self.pool)
+        .bind(&entry.key)
+        .bind(&entry.pipe_name)
+        .bind(&response)
+        .bind(entry.created_at.to_rfc3339())
+        .bind(entry.expires_at.to_rfc3339())
+        .execute(&mut *tx)
         .await?;
 
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        tx.commit().await?;
+        Ok(())
     }
 
-    async fn get_branch_thoughts(&self, branch_id: &str) -> StorageResult<Vec<Thought>> {
-        let rows: Vec<ThoughtRow> = sqlx::query_as(
+    async fn log_pipe_payload(&self, payload: &PipePayload) -> StorageResult<()> {
+        let request = serialize_json_required(&payload.request, "pipe_payload.request")?;
+        let response = serialize_json(&payload.response, "pipe_payload.response")?;
+
+        sqlx::query(
             r#"
-            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
-            FROM thoughts
-            WHERE branch_id = ?
-            ORDER BY created_at ASC
+            INSERT INTO pipe_payloads (id, pipe_name, attempt, request, response, error, latency_ms, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(branch_id)
-        .fetch_all(&self.pool)
+        .bind(&payload.id)
+        .bind(&payload.pipe_name)
+        .bind(payload.attempt as i64)
+        .bind(&request)
+        .bind(&response)
+        .bind(&payload.error)
+        .bind(payload.latency_ms)
+        .bind(payload.created_at.to_rfc3339())
+        .execute(&self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(|r| r.into()).collect())
+        Ok(())
     }
 
-    async fn get_latest_thought(&self, session_id: &str) -> StorageResult<Option<Thought>> {
-        let row: Option<ThoughtRow> = sqlx::query_as(
+    async fn get_pipe_payloads(
+        &self,
+        pipe_name: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<PipePayload>> {
+        let rows: Vec<PipePayloadRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, content, confidence, mode, parent_id, branch_id, created_at, metadata
-            FROM thoughts
-            WHERE session_id = ?
+            SELECT id, pipe_name, attempt, request, response, error, latency_ms, created_at
+            FROM pipe_payloads
+            WHERE ? IS NULL OR pipe_name = ?
             ORDER BY created_at DESC
-            LIMIT 1
+            LIMIT ?
             "#,
         )
-        .bind(session_id)
+        .bind(pipe_name)
+        .bind(pipe_name)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                let request = parse_json_or_skip(&r.request, &r.id, "request")?;
+                let response = match &r.response {
+                    Some(json) => Some(parse_json_or_skip(json, &r.id, "response")?),
+                    None => None,
+                };
+                Some(PipePayload {
+                    created_at: parse_timestamp_with_logging(
+                        &r.created_at,
+                        &format!("pipe payload {} created_at", r.id),
+                    ),
+                    id: r.id,
+                    pipe_name: r.pipe_name,
+                    attempt: r.attempt as u32,
+                    request,
+                    response,
+                    error: r.error,
+                    latency_ms: r.latency_ms,
+                })
+            })
+            .collect())
+    }
+
+    async fn create_routing_record(&self, record: &RoutingRecord) -> StorageResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO routing_records (id, session_id, workspace_id, content_preview, model, vector,
+                                         recommended_mode, confidence, history_applied, outcome_mode,
+                                         outcome_confidence, detection_count, feedback_score,
+                                         feedback_note, outcome_score, created_at, evaluated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.id)
+        .bind(&record.session_id)
+        .bind(&record.workspace_id)
+        .bind(&record.content_preview)
+        .bind(&record.model)
+        .bind(encode_vector(&record.vector))
+        .bind(&record.recommended_mode)
+        .bind(record.confidence)
+        .bind(record.history_applied)
+        .bind(&record.outcome_mode)
+        .bind(record.outcome_confidence)
+        .bind(record.detection_count)
+        .bind(record.feedback_score)
+        .bind(&record.feedback_note)
+        .bind(record.outcome_score)
+        .bind(record.created_at.to_rfc3339())
+        .bind(record.evaluated_at.map(|t| t.to_rfc3339()))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_routing_record(&self, id: &str) -> StorageResult<Option<RoutingRecord>> {
+        let row: Option<RoutingRecordRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, workspace_id, content_preview, model, vector, recommended_mode,
+                   confidence, history_applied, outcome_mode, outcome_confidence, detection_count,
+                   feedback_score, feedback_note, outcome_score, created_at, evaluated_at
+            FROM routing_records
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
         Ok(row.map(|r| r.into()))
     }
 
+    async fn update_routing_outcome(&self, record: &RoutingRecord) -> StorageResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE routing_records
+            SET outcome_mode = ?, outcome_confidence = ?, detection_count = ?, feedback_score = ?,
+                feedback_note = ?, outcome_score = ?, evaluated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&record.outcome_mode)
+        .bind(record.outcome_confidence)
+        .bind(record.detection_count)
+        .bind(record.feedback_score)
+        .bind(&record.feedback_note)
+        .bind(record.outcome_score)
+        .bind(record.evaluated_at.map(|t| t.to_rfc3339()))
+        .bind(&record.id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_routing_records(
+        &self,
+        filter: RoutingRecordFilter,
+    ) -> StorageResult<Vec<RoutingRecord>> {
+        let mut sql = String::from(
+            r#"
+            SELECT id, session_id, workspace_id, content_preview, model, vector, recommended_mode,
+                   confidence, history_applied, outcome_mode, outcome_confidence, detection_count,
+                   feedback_score, feedback_note, outcome_score, created_at, evaluated_at
+            FROM routing_records
+            WHERE (session_id IS NULL
+                   OR session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL))
+            "#,
+        );
+        let mut bindings: Vec<String> = Vec::new();
+
+        if let Some(ref session_id) = filter.session_id {
+            sql.push_str(" AND session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            sql.push_str(" AND workspace_id = ?");
+            bindings.push(workspace_id.clone());
+        }
+
+        if let Some(ref model) = filter.model {
+            sql.push_str(" AND model = ?");
+            bindings.push(model.clone());
+        }
+
+        sql.push_str(" ORDER BY created_at DESC");
+
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut query = sqlx::query_as::<_, RoutingRecordRow>(&sql);
+        for binding in &bindings {
+            query = query.bind(binding);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
     async fn log_invocation(&self, invocation: &Invocation) -> StorageResult<()> {
         let input = serialize_json_required(&invocation.input, "invocation.input")?;
         let output = serialize_json(&invocation.output, "invocation.output")?;
 
         sqlx::query(
             r#"
-            INSERT INTO invocations (id, session_id, tool_name, input, output, pipe_name, latency_ms, success, error, created_at, fallback_used, fallback_type)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO invocations (id, session_id, tool_name, input, output, pipe_name, latency_ms, success, error, created_at, fallback_used, fallback_type, workspace_id, cache_hit)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&invocation.id)
@@ -388,13 +2038,18 @@ impl Storage for SqliteStorage {
         .bind(invocation.created_at.to_rfc3339())
         .bind(invocation.fallback_used)
         .bind(&invocation.fallback_type)
+        .bind(&invocation.workspace_id)
+        .bind(invocation.cache_hit)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn get_pipe_usage_summary(&self) -> StorageResult<Vec<PipeUsageSummary>> {
+    async fn get_pipe_usage_summary(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Vec<PipeUsageSummary>> {
         let rows = sqlx::query(
             r#"
             SELECT
@@ -409,10 +2064,13 @@ impl Storage for SqliteStorage {
                 MAX(created_at) as last_call
             FROM invocations
             WHERE pipe_name IS NOT NULL
+              AND (? IS NULL OR workspace_id = ?)
             GROUP BY pipe_name
             ORDER BY total_calls DESC
             "#,
         )
+        .bind(workspace_id)
+        .bind(workspace_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -457,7 +2115,11 @@ impl Storage for SqliteStorage {
         Ok(summaries)
     }
 
-    async fn get_pipe_summary(&self, pipe_name: &str) -> StorageResult<Option<PipeUsageSummary>> {
+    async fn get_pipe_summary(
+        &self,
+        pipe_name: &str,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<Option<PipeUsageSummary>> {
         let row = sqlx::query(
             r#"
             SELECT
@@ -472,10 +2134,13 @@ impl Storage for SqliteStorage {
                 MAX(created_at) as last_call
             FROM invocations
             WHERE pipe_name = ?
+              AND (? IS NULL OR workspace_id = ?)
             GROUP BY pipe_name
             "#,
         )
         .bind(pipe_name)
+        .bind(workspace_id)
+        .bind(workspace_id)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -521,7 +2186,8 @@ impl Storage for SqliteStorage {
         // Build dynamic query with filters
         let mut query = String::from(
             r#"
-            SELECT id, session_id, tool_name, input, output, pipe_name, latency_ms, success, error, created_at
+            SELECT id, session_id, tool_name, input, output, pipe_name, latency_ms, success, error, created_at,
+                   workspace_id, cache_hit
             FROM invocations
             WHERE 1=1
             "#,
@@ -558,11 +2224,14 @@ impl Storage for SqliteStorage {
             bindings.push(if success_only { "1" } else { "0" }.to_string());
         }
 
+        if let Some(ref workspace_id) = filter.workspace_id {
+            query.push_str(" AND workspace_id = ?");
+            bindings.push(workspace_id.clone());
+        }
+
         query.push_str(" ORDER BY created_at DESC");
 
-        if let Some(limit) = filter.limit {
-            query.push_str(&format!(" LIMIT {}", limit));
-        }
+        push_limit_offset(&mut query, filter.limit, filter.offset);
 
         // Execute with dynamic bindings
         let mut sql_query = sqlx::query(&query);
@@ -585,6 +2254,7 @@ impl Storage for SqliteStorage {
                 let success: bool = row.get("success");
                 let error: Option<String> = row.get("error");
                 let created_at_str: String = row.get("created_at");
+                let workspace_id: String = row.get("workspace_id");
 
                 // Parse with logging on failure
                 let input: serde_json::Value = parse_json_or_skip(&input_str, &id, "input")?;
@@ -595,6 +2265,7 @@ impl Storage for SqliteStorage {
                 // Get fallback fields (with defaults for old data)
                 let fallback_used: bool = row.try_get("fallback_used").unwrap_or(false);
                 let fallback_type: Option<String> = row.try_get("fallback_type").ok().flatten();
+                let cache_hit: bool = row.try_get("cache_hit").unwrap_or(false);
 
                 Some(Invocation {
                     id,
@@ -609,6 +2280,8 @@ impl Storage for SqliteStorage {
                     created_at,
                     fallback_used,
                     fallback_type,
+                    workspace_id,
+                    cache_hit,
                 })
             })
             .collect();
@@ -616,27 +2289,32 @@ impl Storage for SqliteStorage {
         Ok(invocations)
     }
 
-    async fn get_invocation_count(&self, pipe_name: Option<&str>) -> StorageResult<u64> {
-        let count: i64 = match pipe_name {
-            Some(name) => {
-                sqlx::query_scalar(r#"SELECT COUNT(*) FROM invocations WHERE pipe_name = ?"#)
-                    .bind(name)
-                    .fetch_one(&self.pool)
-                    .await?
-            }
-            None => {
-                sqlx::query_scalar(r#"SELECT COUNT(*) FROM invocations"#)
-                    .fetch_one(&self.pool)
-                    .await?
-            }
-        };
+    async fn get_invocation_count(
+        &self,
+        pipe_name: Option<&str>,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM invocations
+            WHERE (? IS NULL OR pipe_name = ?)
+              AND (? IS NULL OR workspace_id = ?)
+            "#,
+        )
+        .bind(pipe_name)
+        .bind(pipe_name)
+        .bind(workspace_id)
+        .bind(workspace_id)
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok(count as u64)
     }
 
-    async fn get_fallback_metrics(&self) -> StorageResult<FallbackMetricsSummary> {
-        use std::collections::HashMap;
-
+    async fn get_fallback_metrics(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> StorageResult<FallbackMetricsSummary> {
         // Get total invocations and total fallbacks
         let totals: (i64, i64) = sqlx::query_as(
             r#"
@@ -644,8 +2322,11 @@ impl Storage for SqliteStorage {
                 COUNT(*) as total_invocations,
                 SUM(CASE WHEN fallback_used = 1 THEN 1 ELSE 0 END) as total_fallbacks
             FROM invocations
+            WHERE (? IS NULL OR workspace_id = ?)
             "#,
         )
+        .bind(workspace_id)
+        .bind(workspace_id)
         .fetch_one(&self.pool)
         .await?;
 
@@ -658,10 +2339,13 @@ impl Storage for SqliteStorage {
             SELECT fallback_type, COUNT(*) as count
             FROM invocations
             WHERE fallback_used = 1 AND fallback_type IS NOT NULL
+              AND (? IS NULL OR workspace_id = ?)
             GROUP BY fallback_type
             ORDER BY count DESC
             "#,
         )
+        .bind(workspace_id)
+        .bind(workspace_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -676,10 +2360,13 @@ impl Storage for SqliteStorage {
             SELECT pipe_name, COUNT(*) as count
             FROM invocations
             WHERE fallback_used = 1 AND pipe_name IS NOT NULL
+              AND (? IS NULL OR workspace_id = ?)
             GROUP BY pipe_name
             ORDER BY count DESC
             "#,
         )
+        .bind(workspace_id)
+        .bind(workspace_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -711,46 +2398,25 @@ impl Storage for SqliteStorage {
         } else {
             format!(
                 "Low fallback rate ({:.2}%). Consider enabling STRICT_MODE=true to eliminate fallbacks entirely.",
-                fallback_rate * 100.0
-            )
-        };
-
-        Ok(FallbackMetricsSummary {
-            total_fallbacks,
-            fallbacks_by_type,
-            fallbacks_by_pipe,
-            total_invocations,
-            fallback_rate,
-            recommendation,
-            timestamp_reconstructions: get_timestamp_reconstruction_count(),
-            records_skipped: get_record_skip_count(),
-        })
-    }
-
-    // Branch operations
-    async fn create_branch(&self, branch: &Branch) -> StorageResult<()> {
-        let metadata = serialize_json(&branch.metadata, "branch.metadata")?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO branches (id, session_id, name, parent_branch_id, priority, confidence, state, created_at, updated_at, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&branch.id)
-        .bind(&branch.session_id)
-        .bind(&branch.name)
-        .bind(&branch.parent_branch_id)
-        .bind(branch.priority)
-        .bind(branch.confidence)
-        .bind(branch.state.to_string())
-        .bind(branch.created_at.to_rfc3339())
-        .bind(branch.updated_at.to_rfc3339())
-        .bind(&metadata)
-        .execute(&self.pool)
-        .await?;
+                fallback_rate * 100.0
+            )
+        };
 
-        Ok(())
+        Ok(FallbackMetricsSummary {
+            total_fallbacks,
+            fallbacks_by_type,
+            fallbacks_by_pipe,
+            total_invocations,
+            fallback_rate,
+            recommendation,
+            timestamp_reconstructions: get_timestamp_reconstruction_count(),
+            records_skipped: get_record_skip_count(),
+        })
+    }
+
+    // Branch operations
+    async fn create_branch(&self, branch: &Branch) -> StorageResult<()> {
+        insert_branch(&self.pool, branch).await
     }
 
     async fn get_branch(&self, id: &str) -> StorageResult<Option<Branch>> {
@@ -840,23 +2506,7 @@ impl Storage for SqliteStorage {
 
     // Cross-reference operations
     async fn create_cross_ref(&self, cross_ref: &CrossRef) -> StorageResult<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO cross_refs (id, from_branch_id, to_branch_id, ref_type, reason, strength, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&cross_ref.id)
-        .bind(&cross_ref.from_branch_id)
-        .bind(&cross_ref.to_branch_id)
-        .bind(cross_ref.ref_type.to_string())
-        .bind(&cross_ref.reason)
-        .bind(cross_ref.strength)
-        .bind(cross_ref.created_at.to_rfc3339())
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        insert_cross_ref(&self.pool, cross_ref).await
     }
 
     async fn get_cross_refs_from(&self, branch_id: &str) -> StorageResult<Vec<CrossRef>> {
@@ -902,25 +2552,7 @@ impl Storage for SqliteStorage {
 
     // Checkpoint operations
     async fn create_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
-        let snapshot = serialize_json_required(&checkpoint.snapshot, "checkpoint.snapshot")?;
-
-        sqlx::query(
-            r#"
-            INSERT INTO checkpoints (id, session_id, branch_id, name, description, snapshot, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&checkpoint.id)
-        .bind(&checkpoint.session_id)
-        .bind(&checkpoint.branch_id)
-        .bind(&checkpoint.name)
-        .bind(&checkpoint.description)
-        .bind(&snapshot)
-        .bind(checkpoint.created_at.to_rfc3339())
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+        insert_checkpoint(&self.pool, checkpoint).await
     }
 
     async fn get_checkpoint(&self, id: &str) -> StorageResult<Option<Checkpoint>> {
@@ -981,27 +2613,19 @@ impl Storage for SqliteStorage {
 
     // Graph node operations (GoT mode)
     async fn create_graph_node(&self, node: &GraphNode) -> StorageResult<()> {
-        let metadata = serialize_json(&node.metadata, "graph_node.metadata")?;
+        insert_graph_node(&self.pool, node).await
+    }
 
-        sqlx::query(
-            r#"
-            INSERT INTO graph_nodes (id, session_id, content, node_type, score, depth, is_terminal, is_root, is_active, created_at, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&node.id)
-        .bind(&node.session_id)
-        .bind(&node.content)
-        .bind(node.node_type.to_string())
-        .bind(node.score)
-        .bind(node.depth)
-        .bind(node.is_terminal)
-        .bind(node.is_root)
-        .bind(node.is_active)
-        .bind(node.created_at.to_rfc3339())
-        .bind(&metadata)
-        .execute(&self.pool)
-        .await?;
+    async fn create_graph_nodes_batch(&self, nodes: &[GraphNode]) -> StorageResult<()> {
+        if nodes.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for node in nodes {
+            insert_graph_node(&mut *tx, node).await?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
@@ -1037,6 +2661,32 @@ impl Storage for SqliteStorage {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    async fn get_session_graph_nodes_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<GraphNode>> {
+        let rows: Vec<GraphNodeRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, content, node_type, score, depth, is_terminal, is_root, is_active, created_at, metadata
+            FROM graph_nodes
+            WHERE session_id = ?
+            ORDER BY created_at ASC, id ASC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(session_id)
+        .bind(i64::from(pagination.limit) + 1)
+        .bind(i64::from(pagination.offset))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Page::from_overfetch(
+            rows.into_iter().map(|r| r.into()).collect(),
+            pagination,
+        ))
+    }
+
     async fn get_active_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
         let rows: Vec<GraphNodeRow> = sqlx::query_as(
             r#"
@@ -1127,24 +2777,19 @@ impl Storage for SqliteStorage {
 
     // Graph edge operations (GoT mode)
     async fn create_graph_edge(&self, edge: &GraphEdge) -> StorageResult<()> {
-        let metadata = serialize_json(&edge.metadata, "graph_edge.metadata")?;
+        insert_graph_edge(&self.pool, edge).await
+    }
 
-        sqlx::query(
-            r#"
-            INSERT INTO graph_edges (id, session_id, from_node, to_node, edge_type, weight, created_at, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&edge.id)
-        .bind(&edge.session_id)
-        .bind(&edge.from_node)
-        .bind(&edge.to_node)
-        .bind(edge.edge_type.to_string())
-        .bind(edge.weight)
-        .bind(edge.created_at.to_rfc3339())
-        .bind(&metadata)
-        .execute(&self.pool)
-        .await?;
+    async fn create_graph_edges_batch(&self, edges: &[GraphEdge]) -> StorageResult<()> {
+        if edges.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for edge in edges {
+            insert_graph_edge(&mut *tx, edge).await?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
@@ -1221,6 +2866,65 @@ impl Storage for SqliteStorage {
         Ok(())
     }
 
+    // Graph comparison operations (GoT pairwise ranking)
+
+    async fn create_graph_comparisons_batch(
+        &self,
+        comparisons: &[GraphComparison],
+    ) -> StorageResult<()> {
+        if comparisons.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for comparison in comparisons {
+            sqlx::query(
+                r#"
+                INSERT INTO graph_comparisons (
+                    id, session_id, parent_node_id, node_a_id, node_b_id, winner_id,
+                    confidence, rationale, round, created_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&comparison.id)
+            .bind(&comparison.session_id)
+            .bind(&comparison.parent_node_id)
+            .bind(&comparison.node_a_id)
+            .bind(&comparison.node_b_id)
+            .bind(&comparison.winner_id)
+            .bind(comparison.confidence)
+            .bind(&comparison.rationale)
+            .bind(comparison.round)
+            .bind(comparison.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_session_graph_comparisons(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<GraphComparison>> {
+        let rows: Vec<GraphComparisonRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, parent_node_id, node_a_id, node_b_id, winner_id,
+                   confidence, rationale, round, created_at
+            FROM graph_comparisons
+            WHERE session_id = ?
+            ORDER BY created_at ASC, round ASC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
     // State snapshot operations (backtracking)
     async fn create_snapshot(&self, snapshot: &StateSnapshot) -> StorageResult<()> {
         let state_data = serialize_json_required(&snapshot.state_data, "snapshot.state_data")?;
@@ -1310,8 +3014,8 @@ impl Storage for SqliteStorage {
 
         sqlx::query(
             r#"
-            INSERT INTO detections (id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO detections (id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&detection.id)
@@ -1325,6 +3029,7 @@ impl Storage for SqliteStorage {
         .bind(&detection.remediation)
         .bind(detection.created_at.to_rfc3339())
         .bind(&metadata)
+        .bind(&detection.workspace_id)
         .execute(&self.pool)
         .await?;
 
@@ -1334,7 +3039,7 @@ impl Storage for SqliteStorage {
     async fn get_detection(&self, id: &str) -> StorageResult<Option<Detection>> {
         let row: Option<DetectionRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata
+            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id
             FROM detections
             WHERE id = ?
             "#,
@@ -1349,7 +3054,7 @@ impl Storage for SqliteStorage {
     async fn get_session_detections(&self, session_id: &str) -> StorageResult<Vec<Detection>> {
         let rows: Vec<DetectionRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata
+            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id
             FROM detections
             WHERE session_id = ?
             ORDER BY created_at DESC
@@ -1365,7 +3070,7 @@ impl Storage for SqliteStorage {
     async fn get_thought_detections(&self, thought_id: &str) -> StorageResult<Vec<Detection>> {
         let rows: Vec<DetectionRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata
+            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id
             FROM detections
             WHERE thought_id = ?
             ORDER BY created_at DESC
@@ -1384,7 +3089,7 @@ impl Storage for SqliteStorage {
     ) -> StorageResult<Vec<Detection>> {
         let rows: Vec<DetectionRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata
+            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id
             FROM detections
             WHERE detection_type = ?
             ORDER BY created_at DESC
@@ -1404,7 +3109,7 @@ impl Storage for SqliteStorage {
     ) -> StorageResult<Vec<Detection>> {
         let rows: Vec<DetectionRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata
+            SELECT id, session_id, thought_id, detection_type, detected_issue, severity, confidence, explanation, remediation, created_at, metadata, workspace_id
             FROM detections
             WHERE session_id = ? AND detection_type = ?
             ORDER BY created_at DESC
@@ -1572,33 +3277,267 @@ impl Storage for SqliteStorage {
         Ok(())
     }
 
-    async fn get_perspective(&self, id: &str) -> StorageResult<Option<PerspectiveAnalysis>> {
-        let row: Option<PerspectiveRow> = sqlx::query_as(
+    async fn get_perspective(&self, id: &str) -> StorageResult<Option<PerspectiveAnalysis>> {
+        let row: Option<PerspectiveRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, topic, stakeholders, power_matrix,
+                   conflicts, alignments, synthesis, confidence, created_at, metadata
+            FROM perspective_analyses
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    async fn get_session_perspectives(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<PerspectiveAnalysis>> {
+        let rows: Vec<PerspectiveRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, topic, stakeholders, power_matrix,
+                   conflicts, alignments, synthesis, confidence, created_at, metadata
+            FROM perspective_analyses
+            WHERE session_id = ?
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn delete_perspective(&self, id: &str) -> StorageResult<()> {
+        sqlx::query("DELETE FROM perspective_analyses WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // SWOT analysis operations (swot mode)
+    // ========================================================================
+
+    async fn create_swot_analysis(&self, analysis: &SwotAnalysis) -> StorageResult<()> {
+        let metadata_json = analysis.metadata.as_ref().map(|v| v.to_string());
+
+        sqlx::query(
+            r#"
+            INSERT INTO swot_analyses (
+                id, session_id, subject, strengths, weaknesses, opportunities, threats,
+                perspective_id, summary, confidence, created_at, metadata
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&analysis.id)
+        .bind(&analysis.session_id)
+        .bind(&analysis.subject)
+        .bind(analysis.strengths.to_string())
+        .bind(analysis.weaknesses.to_string())
+        .bind(analysis.opportunities.to_string())
+        .bind(analysis.threats.to_string())
+        .bind(&analysis.perspective_id)
+        .bind(&analysis.summary)
+        .bind(analysis.confidence)
+        .bind(analysis.created_at.to_rfc3339())
+        .bind(&metadata_json)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_swot_analyses(&self, filter: SwotFilter) -> StorageResult<Vec<SwotAnalysis>> {
+        let mut sql = String::from(
+            r#"
+            SELECT s.id, s.session_id, s.subject, s.strengths, s.weaknesses, s.opportunities,
+                   s.threats, s.perspective_id, s.summary, s.confidence, s.created_at, s.metadata
+            FROM swot_analyses s
+            WHERE s.session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)
+            "#,
+        );
+        let mut bindings: Vec<String> = Vec::new();
+
+        if let Some(ref subject) = filter.subject {
+            sql.push_str(" AND instr(lower(s.subject), lower(?)) > 0");
+            bindings.push(subject.clone());
+        }
+
+        if let Some(ref session_id) = filter.session_id {
+            sql.push_str(" AND s.session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            sql.push_str(" AND s.session_id IN (SELECT id FROM sessions WHERE workspace_id = ?)");
+            bindings.push(workspace_id.clone());
+        }
+
+        sql.push_str(" ORDER BY s.created_at DESC");
+
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let mut query = sqlx::query_as::<_, SwotRow>(&sql);
+        for binding in &bindings {
+            query = query.bind(binding);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    // ========================================================================
+    // Risk register operations (risk mode)
+    // ========================================================================
+
+    async fn create_risk(&self, risk: &Risk) -> StorageResult<()> {
+        let metadata_json = risk.metadata.as_ref().map(|v| v.to_string());
+
+        sqlx::query(
+            r#"
+            INSERT INTO risks (
+                id, session_id, title, description, category, likelihood, impact,
+                detectability, owner, mitigation, history, created_at, updated_at, metadata
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&risk.id)
+        .bind(&risk.session_id)
+        .bind(&risk.title)
+        .bind(&risk.description)
+        .bind(&risk.category)
+        .bind(risk.likelihood)
+        .bind(risk.impact)
+        .bind(risk.detectability)
+        .bind(&risk.owner)
+        .bind(&risk.mitigation)
+        .bind(risk.history.to_string())
+        .bind(risk.created_at.to_rfc3339())
+        .bind(risk.updated_at.to_rfc3339())
+        .bind(&metadata_json)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_risk(&self, id: &str) -> StorageResult<Option<Risk>> {
+        let row: Option<RiskRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, title, description, category, likelihood, impact,
+                   detectability, owner, mitigation, history, created_at, updated_at, metadata
+            FROM risks
+            WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
+    async fn get_session_risks(&self, session_id: &str) -> StorageResult<Vec<Risk>> {
+        let rows: Vec<RiskRow> = sqlx::query_as(
+            r#"
+            SELECT id, session_id, title, description, category, likelihood, impact,
+                   detectability, owner, mitigation, history, created_at, updated_at, metadata
+            FROM risks
+            WHERE session_id = ?
+            ORDER BY likelihood * impact * detectability DESC, created_at ASC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn update_risk(&self, risk: &Risk) -> StorageResult<()> {
+        let metadata_json = risk.metadata.as_ref().map(|v| v.to_string());
+
+        let result = sqlx::query(
+            r#"
+            UPDATE risks
+            SET likelihood = ?, impact = ?, detectability = ?, owner = ?, mitigation = ?,
+                history = ?, updated_at = ?, metadata = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(risk.likelihood)
+        .bind(risk.impact)
+        .bind(risk.detectability)
+        .bind(&risk.owner)
+        .bind(&risk.mitigation)
+        .bind(risk.history.to_string())
+        .bind(risk.updated_at.to_rfc3339())
+        .bind(&metadata_json)
+        .bind(&risk.id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StorageError::Query {
+                message: format!("Risk not found: {}", risk.id),
+            });
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Code finding operations (code reasoning mode)
+    // ========================================================================
+
+    async fn create_code_finding(&self, finding: &CodeFinding) -> StorageResult<()> {
+        let metadata_json = finding.metadata.as_ref().map(|v| v.to_string());
+
+        sqlx::query(
             r#"
-            SELECT id, session_id, topic, stakeholders, power_matrix,
-                   conflicts, alignments, synthesis, confidence, created_at, metadata
-            FROM perspective_analyses
-            WHERE id = ?
+            INSERT INTO code_findings (
+                id, session_id, thought_id, operation, kind, content, location, severity,
+                created_at, metadata
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
-        .bind(id)
-        .fetch_optional(&self.pool)
+        .bind(&finding.id)
+        .bind(&finding.session_id)
+        .bind(&finding.thought_id)
+        .bind(&finding.operation)
+        .bind(finding.kind.to_string())
+        .bind(&finding.content)
+        .bind(&finding.location)
+        .bind(finding.severity)
+        .bind(finding.created_at.to_rfc3339())
+        .bind(&metadata_json)
+        .execute(&self.pool)
         .await?;
 
-        Ok(row.map(|r| r.into()))
+        Ok(())
     }
 
-    async fn get_session_perspectives(
-        &self,
-        session_id: &str,
-    ) -> StorageResult<Vec<PerspectiveAnalysis>> {
-        let rows: Vec<PerspectiveRow> = sqlx::query_as(
+    async fn get_session_code_findings(&self, session_id: &str) -> StorageResult<Vec<CodeFinding>> {
+        let rows: Vec<CodeFindingRow> = sqlx::query_as(
             r#"
-            SELECT id, session_id, topic, stakeholders, power_matrix,
-                   conflicts, alignments, synthesis, confidence, created_at, metadata
-            FROM perspective_analyses
+            SELECT id, session_id, thought_id, operation, kind, content, location, severity,
+                   created_at, metadata
+            FROM code_findings
             WHERE session_id = ?
-            ORDER BY created_at DESC
+            ORDER BY created_at ASC, rowid ASC
             "#,
         )
         .bind(session_id)
@@ -1608,15 +3547,6 @@ impl Storage for SqliteStorage {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
-    async fn delete_perspective(&self, id: &str) -> StorageResult<()> {
-        sqlx::query("DELETE FROM perspective_analyses WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
-
-        Ok(())
-    }
-
     // ========================================================================
     // Evidence assessment operations (evidence mode)
     // ========================================================================
@@ -2268,6 +4198,100 @@ impl Storage for SqliteStorage {
         Ok(())
     }
 
+    async fn create_mcts_iteration(&self, iteration: &MctsIteration) -> StorageResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO mcts_iterations (
+                id, session_id, root_node_id, iteration, policy, exploration_constant,
+                rollout_depth, expansion_width, selected_node_id, selected_depth,
+                children_created, simulation_value, best_value, nodes_total, pipe_calls,
+                latency_ms, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&iteration.id)
+        .bind(&iteration.session_id)
+        .bind(&iteration.root_node_id)
+        .bind(iteration.iteration)
+        .bind(iteration.policy.to_string())
+        .bind(iteration.exploration_constant)
+        .bind(iteration.rollout_depth)
+        .bind(iteration.expansion_width)
+        .bind(&iteration.selected_node_id)
+        .bind(iteration.selected_depth)
+        .bind(iteration.children_created)
+        .bind(iteration.simulation_value)
+        .bind(iteration.best_value)
+        .bind(iteration.nodes_total)
+        .bind(iteration.pipe_calls)
+        .bind(iteration.latency_ms)
+        .bind(iteration.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_mcts_iterations(
+        &self,
+        filter: MctsIterationFilter,
+    ) -> StorageResult<Vec<MctsIteration>> {
+        let mut conditions = String::from(
+            "i.session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)",
+        );
+        let mut bindings: Vec<String> = Vec::new();
+
+        if let Some(ref session_id) = filter.session_id {
+            conditions.push_str(" AND i.session_id = ?");
+            bindings.push(session_id.clone());
+        }
+
+        if let Some(ref workspace_id) = filter.workspace_id {
+            conditions
+                .push_str(" AND i.session_id IN (SELECT id FROM sessions WHERE workspace_id = ?)");
+            bindings.push(workspace_id.clone());
+        }
+
+        let mut sql = format!(
+            r#"
+            SELECT i.id, i.session_id, i.root_node_id, i.iteration, i.policy,
+                   i.exploration_constant, i.rollout_depth, i.expansion_width,
+                   i.selected_node_id, i.selected_depth, i.children_created,
+                   i.simulation_value, i.best_value, i.nodes_total, i.pipe_calls,
+                   i.latency_ms, i.created_at
+            FROM mcts_iterations i
+            JOIN (
+                SELECT root_node_id, MIN(created_at) AS started_at
+                FROM mcts_iterations
+                GROUP BY root_node_id
+            ) r ON r.root_node_id = i.root_node_id
+            WHERE {}
+            "#,
+            conditions
+        );
+
+        // The limit counts searches, so a search is never cut off mid-way
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(
+                " AND i.root_node_id IN (SELECT i.root_node_id FROM mcts_iterations i WHERE {} \
+                 GROUP BY i.root_node_id ORDER BY MIN(i.created_at) DESC LIMIT {})",
+                conditions, limit
+            ));
+            bindings.extend(bindings.clone());
+        }
+
+        sql.push_str(" ORDER BY r.started_at ASC, i.root_node_id ASC, i.iteration ASC");
+
+        let mut query = sqlx::query_as::<_, MctsIterationRow>(&sql);
+        for binding in &bindings {
+            query = query.bind(binding);
+        }
+        let rows = query.fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
     // ========================================================================
     // Counterfactual analysis operations (Time Machine)
     // ========================================================================
@@ -2399,6 +4423,163 @@ impl Storage for SqliteStorage {
 
         Ok(())
     }
+
+    // ========================================================================
+    // Retention operations
+    // ========================================================================
+
+    async fn delete_sessions_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = sqlx::query("DELETE FROM sessions WHERE updated_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_trashed_sessions(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result =
+            sqlx::query("DELETE FROM sessions WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+                .bind(cutoff.to_rfc3339())
+                .execute(&self.pool)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_snapshots_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = sqlx::query("DELETE FROM state_snapshots WHERE created_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_invocations_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = sqlx::query("DELETE FROM invocations WHERE created_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_pipe_payloads_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        let result = sqlx::query("DELETE FROM pipe_payloads WHERE created_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn trim_invocations(&self, max_entries: u64) -> StorageResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM invocations
+            WHERE id NOT IN (
+                SELECT id FROM invocations
+                ORDER BY created_at DESC
+                LIMIT ?
+            )
+            "#,
+        )
+        .bind(max_entries as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ========================================================================
+    // Maintenance operations
+    // ========================================================================
+
+    async fn integrity_check(&self) -> StorageResult<Vec<String>> {
+        let messages: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    async fn schema_status(&self) -> StorageResult<MigrationStatus> {
+        self.read_migration_status().await
+    }
+
+    async fn vacuum(&self) -> StorageResult<()> {
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn analyze(&self) -> StorageResult<()> {
+        sqlx::query("ANALYZE").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // Backup operations
+    // ========================================================================
+
+    async fn backup(&self, target: &Path, compress: bool) -> StorageResult<BackupReport> {
+        if target.exists() {
+            return Err(StorageError::Backup {
+                message: format!("Target {} already exists", target.display()),
+            });
+        }
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .map_err(backup_error("Failed to create backup directory"))?;
+        }
+
+        // VACUUM INTO produces a consistent, compacted copy inside one read
+        // transaction; see the backup module for the trade-offs against the
+        // online backup API.
+        let snapshot = if compress {
+            let staging = staging_path(target);
+            let _ = std::fs::remove_file(&staging);
+            staging
+        } else {
+            target.to_path_buf()
+        };
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(vacuum_target_uri(&snapshot))
+            .execute(&self.pool)
+            .await
+            .map_err(backup_error("Failed to snapshot database"))?;
+
+        if compress {
+            let (source, dest) = (snapshot.clone(), target.to_path_buf());
+            let result = tokio::task::spawn_blocking(move || compress_file(&source, &dest))
+                .await
+                .map_err(backup_error("Compression task failed"))
+                .and_then(|r| r);
+            let _ = std::fs::remove_file(&snapshot);
+            if result.is_err() {
+                let _ = std::fs::remove_file(target);
+            }
+            result?;
+        }
+
+        let size_bytes = std::fs::metadata(target)
+            .map_err(backup_error("Failed to read backup"))?
+            .len();
+
+        info!(
+            path = %target.display(),
+            size_bytes,
+            compressed = compress,
+            "Database backup completed"
+        );
+
+        Ok(BackupReport {
+            path: target.to_path_buf(),
+            size_bytes,
+            compressed: compress,
+        })
+    }
 }
 
 // ============================================================================
@@ -2474,6 +4655,12 @@ struct SessionRow {
     updated_at: String,
     metadata: Option<String>,
     active_branch_id: Option<String>,
+    deleted_at: Option<String>,
+    workspace_id: String,
+    forked_from: Option<String>,
+    thread_id: Option<String>,
+    /// JSON array of tags (see `SESSION_TAGS_COLUMN`).
+    tags: Option<String>,
 }
 
 impl From<SessionRow> for Session {
@@ -2493,6 +4680,22 @@ impl From<SessionRow> for Session {
                 parse_metadata_with_logging(&s, &format!("session {} metadata", row.id))
             }),
             active_branch_id: row.active_branch_id,
+            deleted_at: row.deleted_at.map(|s| {
+                parse_timestamp_with_logging(&s, &format!("session {} deleted_at", row.id))
+            }),
+            tags: row
+                .tags
+                .and_then(|s| {
+                    serde_json::from_str(&s)
+                        .map_err(|e| {
+                            warn!(session_id = %row.id, error = %e, "Failed to parse session tags");
+                        })
+                        .ok()
+                })
+                .unwrap_or_default(),
+            workspace_id: row.workspace_id,
+            forked_from: row.forked_from,
+            thread_id: row.thread_id,
         }
     }
 }
@@ -2531,6 +4734,174 @@ impl From<ThoughtRow> for Thought {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct ThoughtRevisionRow {
+    id: String,
+    thought_id: String,
+    session_id: String,
+    version: i64,
+    content: String,
+    confidence: f64,
+    reason: Option<String>,
+    revised_at: String,
+}
+
+impl From<ThoughtRevisionRow> for ThoughtRevision {
+    fn from(row: ThoughtRevisionRow) -> Self {
+        Self {
+            revised_at: parse_timestamp_with_logging(
+                &row.revised_at,
+                &format!("thought revision {} revised_at", row.id),
+            ),
+            id: row.id,
+            thought_id: row.thought_id,
+            session_id: row.session_id,
+            version: row.version as u32,
+            content: row.content,
+            confidence: row.confidence,
+            reason: row.reason,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ThoughtSearchRow {
+    #[sqlx(flatten)]
+    thought: ThoughtRow,
+    snippet: String,
+    rank: f64,
+}
+
+#[derive(sqlx::FromRow)]
+struct EmbeddingCandidateRow {
+    source_id: String,
+    source_type: String,
+    session_id: String,
+    content: String,
+}
+
+impl From<EmbeddingCandidateRow> for EmbeddingCandidate {
+    fn from(row: EmbeddingCandidateRow) -> Self {
+        Self {
+            source_type: parse_enum_with_logging(
+                &row.source_type,
+                &format!("embedding candidate {} source_type", row.source_id),
+            ),
+            source_id: row.source_id,
+            session_id: row.session_id,
+            content: row.content,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct SimilarityRow {
+    source_id: String,
+    source_type: String,
+    session_id: String,
+    vector: Vec<u8>,
+    content: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AuditRow {
+    id: String,
+    created_at: String,
+    actor: String,
+    tool_name: String,
+    session_id: Option<String>,
+    before_hash: Option<String>,
+    after_hash: Option<String>,
+    success: bool,
+    error: Option<String>,
+}
+
+impl From<AuditRow> for AuditEntry {
+    fn from(row: AuditRow) -> Self {
+        Self {
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("audit entry {} created_at", row.id),
+            ),
+            id: row.id,
+            actor: row.actor,
+            tool_name: row.tool_name,
+            session_id: row.session_id,
+            before_hash: row.before_hash,
+            after_hash: row.after_hash,
+            success: row.success,
+            error: row.error,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PipeCacheRow {
+    key: String,
+    pipe_name: String,
+    response: String,
+    created_at: String,
+    expires_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct PipePayloadRow {
+    id: String,
+    pipe_name: String,
+    attempt: i64,
+    request: String,
+    response: Option<String>,
+    error: Option<String>,
+    latency_ms: i64,
+    created_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct ConclusionRow {
+    id: String,
+    source_type: String,
+    source_id: String,
+    session_id: Option<String>,
+    topic: String,
+    content: String,
+    confidence: Option<f64>,
+    created_at: String,
+    metadata: Option<String>,
+    workspace_id: String,
+}
+
+impl From<ConclusionRow> for Conclusion {
+    fn from(row: ConclusionRow) -> Self {
+        Self {
+            id: row.id.clone(),
+            source_type: parse_enum_with_logging(
+                &row.source_type,
+                &format!("conclusion {} source_type", row.id),
+            ),
+            source_id: row.source_id,
+            session_id: row.session_id,
+            topic: row.topic,
+            content: row.content,
+            confidence: row.confidence,
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("conclusion {} created_at", row.id),
+            ),
+            metadata: row.metadata.and_then(|s| {
+                parse_metadata_with_logging(&s, &format!("conclusion {} metadata", row.id))
+            }),
+            workspace_id: row.workspace_id,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct KnowledgeSearchRow {
+    #[sqlx(flatten)]
+    conclusion: ConclusionRow,
+    rank: f64,
+}
+
 #[derive(sqlx::FromRow)]
 struct BranchRow {
     id: String,
@@ -2719,6 +5090,41 @@ impl From<GraphEdgeRow> for GraphEdge {
     }
 }
 
+/// Row struct for GraphComparison queries
+#[derive(Debug, sqlx::FromRow)]
+struct GraphComparisonRow {
+    id: String,
+    session_id: String,
+    parent_node_id: Option<String>,
+    node_a_id: String,
+    node_b_id: String,
+    winner_id: Option<String>,
+    confidence: f64,
+    rationale: String,
+    round: i32,
+    created_at: String,
+}
+
+impl From<GraphComparisonRow> for GraphComparison {
+    fn from(row: GraphComparisonRow) -> Self {
+        Self {
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("graph_comparison {} created_at", row.id),
+            ),
+            id: row.id,
+            session_id: row.session_id,
+            parent_node_id: row.parent_node_id,
+            node_a_id: row.node_a_id,
+            node_b_id: row.node_b_id,
+            winner_id: row.winner_id,
+            confidence: row.confidence,
+            rationale: row.rationale,
+            round: row.round,
+        }
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct StateSnapshotRow {
     id: String,
@@ -2777,6 +5183,7 @@ struct DetectionRow {
     remediation: Option<String>,
     created_at: String,
     metadata: Option<String>,
+    workspace_id: String,
 }
 
 impl From<DetectionRow> for Detection {
@@ -2803,6 +5210,7 @@ impl From<DetectionRow> for Detection {
                 &format!("detection {} created_at", row.id),
             ),
             metadata,
+            workspace_id: row.workspace_id,
         }
     }
 }
@@ -2964,6 +5372,154 @@ impl From<PerspectiveRow> for PerspectiveAnalysis {
     }
 }
 
+// ============================================================================
+// SWOT Analysis Row Types
+// ============================================================================
+
+/// Row struct for SwotAnalysis queries
+#[derive(Debug, sqlx::FromRow)]
+struct SwotRow {
+    id: String,
+    session_id: String,
+    subject: String,
+    strengths: String,
+    weaknesses: String,
+    opportunities: String,
+    threats: String,
+    perspective_id: Option<String>,
+    summary: String,
+    confidence: f64,
+    created_at: String,
+    metadata: Option<String>,
+}
+
+impl From<SwotRow> for SwotAnalysis {
+    fn from(row: SwotRow) -> Self {
+        // A quadrant that fails to parse reads as empty rather than hiding
+        // the rest of the analysis
+        let quadrant = |json: &str, name: &str| {
+            parse_metadata_with_logging(json, &format!("swot {} {}", row.id, name))
+                .unwrap_or_else(|| serde_json::json!([]))
+        };
+
+        Self {
+            strengths: quadrant(&row.strengths, "strengths"),
+            weaknesses: quadrant(&row.weaknesses, "weaknesses"),
+            opportunities: quadrant(&row.opportunities, "opportunities"),
+            threats: quadrant(&row.threats, "threats"),
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("swot {} created_at", row.id),
+            ),
+            metadata: row.metadata.as_deref().and_then(|s| {
+                parse_metadata_with_logging(s, &format!("swot {} metadata", row.id))
+            }),
+            id: row.id,
+            session_id: row.session_id,
+            subject: row.subject,
+            perspective_id: row.perspective_id,
+            summary: row.summary,
+            confidence: row.confidence,
+        }
+    }
+}
+
+// ============================================================================
+// Risk Register Row Types
+// ============================================================================
+
+/// Row struct for Risk queries
+#[derive(Debug, sqlx::FromRow)]
+struct RiskRow {
+    id: String,
+    session_id: String,
+    title: String,
+    description: String,
+    category: Option<String>,
+    likelihood: i32,
+    impact: i32,
+    detectability: i32,
+    owner: Option<String>,
+    mitigation: Option<String>,
+    history: String,
+    created_at: String,
+    updated_at: String,
+    metadata: Option<String>,
+}
+
+impl From<RiskRow> for Risk {
+    fn from(row: RiskRow) -> Self {
+        Self {
+            // A history that fails to parse reads as empty rather than
+            // hiding the risk
+            history: parse_metadata_with_logging(&row.history, &format!("risk {} history", row.id))
+                .unwrap_or_else(|| serde_json::json!([])),
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("risk {} created_at", row.id),
+            ),
+            updated_at: parse_timestamp_with_logging(
+                &row.updated_at,
+                &format!("risk {} updated_at", row.id),
+            ),
+            metadata: row.metadata.as_deref().and_then(|s| {
+                parse_metadata_with_logging(s, &format!("risk {} metadata", row.id))
+            }),
+            id: row.id,
+            session_id: row.session_id,
+            title: row.title,
+            description: row.description,
+            category: row.category,
+            likelihood: row.likelihood,
+            impact: row.impact,
+            detectability: row.detectability,
+            owner: row.owner,
+            mitigation: row.mitigation,
+        }
+    }
+}
+
+// ============================================================================
+// Code Finding Row Types
+// ============================================================================
+
+/// Row struct for CodeFinding queries
+#[derive(Debug, sqlx::FromRow)]
+struct CodeFindingRow {
+    id: String,
+    session_id: String,
+    thought_id: Option<String>,
+    operation: String,
+    kind: String,
+    content: String,
+    location: Option<String>,
+    severity: Option<i32>,
+    created_at: String,
+    metadata: Option<String>,
+}
+
+impl From<CodeFindingRow> for CodeFinding {
+    fn from(row: CodeFindingRow) -> Self {
+        Self {
+            kind: parse_enum_with_logging(&row.kind, &format!("code finding {} kind", row.id)),
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("code finding {} created_at", row.id),
+            ),
+            metadata: row.metadata.as_deref().and_then(|s| {
+                parse_metadata_with_logging(s, &format!("code finding {} metadata", row.id))
+            }),
+            id: row.id,
+            session_id: row.session_id,
+            thought_id: row.thought_id,
+            operation: row.operation,
+            content: row.content,
+            location: row.location,
+            severity: row.severity,
+        }
+    }
+}
+
 // ============================================================================
 // Evidence Assessment Row Types
 // ============================================================================
@@ -3255,6 +5811,109 @@ impl From<MCTSNodeRow> for MCTSNode {
     }
 }
 
+/// Row struct for MctsIteration queries
+#[derive(Debug, sqlx::FromRow)]
+struct MctsIterationRow {
+    id: String,
+    session_id: String,
+    root_node_id: String,
+    iteration: i32,
+    policy: String,
+    exploration_constant: f64,
+    rollout_depth: i32,
+    expansion_width: i32,
+    selected_node_id: String,
+    selected_depth: i32,
+    children_created: i32,
+    simulation_value: f64,
+    best_value: f64,
+    nodes_total: i32,
+    pipe_calls: i32,
+    latency_ms: i64,
+    created_at: String,
+}
+
+impl From<MctsIterationRow> for MctsIteration {
+    fn from(row: MctsIterationRow) -> Self {
+        Self {
+            policy: parse_enum_with_logging(
+                &row.policy,
+                &format!("mcts_iteration {} policy", row.id),
+            ),
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("mcts_iteration {} created_at", row.id),
+            ),
+            id: row.id,
+            session_id: row.session_id,
+            root_node_id: row.root_node_id,
+            iteration: row.iteration,
+            exploration_constant: row.exploration_constant,
+            rollout_depth: row.rollout_depth,
+            expansion_width: row.expansion_width,
+            selected_node_id: row.selected_node_id,
+            selected_depth: row.selected_depth,
+            children_created: row.children_created,
+            simulation_value: row.simulation_value,
+            best_value: row.best_value,
+            nodes_total: row.nodes_total,
+            pipe_calls: row.pipe_calls,
+            latency_ms: row.latency_ms,
+        }
+    }
+}
+
+/// Row struct for RoutingRecord queries
+#[derive(Debug, sqlx::FromRow)]
+struct RoutingRecordRow {
+    id: String,
+    session_id: Option<String>,
+    workspace_id: String,
+    content_preview: String,
+    model: String,
+    vector: Vec<u8>,
+    recommended_mode: String,
+    confidence: f64,
+    history_applied: bool,
+    outcome_mode: Option<String>,
+    outcome_confidence: Option<f64>,
+    detection_count: i32,
+    feedback_score: Option<f64>,
+    feedback_note: Option<String>,
+    outcome_score: Option<f64>,
+    created_at: String,
+    evaluated_at: Option<String>,
+}
+
+impl From<RoutingRecordRow> for RoutingRecord {
+    fn from(row: RoutingRecordRow) -> Self {
+        Self {
+            created_at: parse_timestamp_with_logging(
+                &row.created_at,
+                &format!("routing_record {} created_at", row.id),
+            ),
+            evaluated_at: row.evaluated_at.as_deref().map(|t| {
+                parse_timestamp_with_logging(t, &format!("routing_record {} evaluated_at", row.id))
+            }),
+            vector: decode_vector(&row.vector),
+            id: row.id,
+            session_id: row.session_id,
+            workspace_id: row.workspace_id,
+            content_preview: row.content_preview,
+            model: row.model,
+            recommended_mode: row.recommended_mode,
+            confidence: row.confidence,
+            history_applied: row.history_applied,
+            outcome_mode: row.outcome_mode,
+            outcome_confidence: row.outcome_confidence,
+            detection_count: row.detection_count,
+            feedback_score: row.feedback_score,
+            feedback_note: row.feedback_note,
+            outcome_score: row.outcome_score,
+        }
+    }
+}
+
 /// Row struct for CounterfactualAnalysis queries
 #[derive(Debug, sqlx::FromRow)]
 struct CounterfactualRow {
@@ -3575,6 +6234,11 @@ mod tests {
             updated_at: "2024-01-15T11:00:00Z".to_string(),
             metadata: Some(r#"{"key": "value"}"#.to_string()),
             active_branch_id: Some("branch-1".to_string()),
+            deleted_at: Some("2024-01-16T09:00:00Z".to_string()),
+            tags: Some(r#"["alpha","beta"]"#.to_string()),
+            workspace_id: "project-a".to_string(),
+            forked_from: Some("sess-100".to_string()),
+            thread_id: Some("thread-1".to_string()),
         };
 
         let session: Session = row.into();
@@ -3582,6 +6246,11 @@ mod tests {
         assert_eq!(session.mode, "linear");
         assert_eq!(session.active_branch_id, Some("branch-1".to_string()));
         assert!(session.metadata.is_some());
+        assert_eq!(session.tags, vec!["alpha", "beta"]);
+        assert!(session.is_deleted());
+        assert_eq!(session.workspace_id, "project-a");
+        assert_eq!(session.forked_from.as_deref(), Some("sess-100"));
+        assert_eq!(session.thread_id.as_deref(), Some("thread-1"));
     }
 
     #[test]
@@ -3593,6 +6262,11 @@ mod tests {
             updated_at: "2024-01-15T11:00:00Z".to_string(),
             metadata: None,
             active_branch_id: None,
+            deleted_at: None,
+            tags: None,
+            workspace_id: "default".to_string(),
+            forked_from: None,
+            thread_id: None,
         };
 
         let session: Session = row.into();
@@ -3791,6 +6465,107 @@ mod tests {
         assert!(storage.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_sqlite_storage_new_with_in_memory_config() {
+        let config = DatabaseConfig::in_memory();
+        let storage = SqliteStorage::new(&config).await.unwrap();
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+
+        // A clone shares the same single in-memory connection
+        let cloned = storage.clone();
+        assert!(cloned.get_session(&session.id).await.unwrap().is_some());
+    }
+
+    fn encrypted_config(path: std::path::PathBuf, key: Option<&str>) -> DatabaseConfig {
+        DatabaseConfig {
+            path,
+            max_connections: 1,
+            encryption_key: key.map(String::from),
+            maintenance: Default::default(),
+            cache_capacity: crate::config::DEFAULT_CACHE_CAPACITY,
+            workspace_id: crate::config::DEFAULT_WORKSPACE_ID.to_string(),
+        }
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_encryption_key_requires_sqlcipher_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = encrypted_config(dir.path().join("reasoning.db"), Some("secret"));
+
+        let err = SqliteStorage::new(&config).await.err().unwrap();
+        assert!(err.to_string().contains("sqlcipher"));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_encrypted_database_requires_correct_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reasoning.db");
+
+        let storage = SqliteStorage::new(&encrypted_config(path.clone(), Some("secret")))
+            .await
+            .unwrap();
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        storage.pool().close().await;
+
+        assert!(!is_plaintext_sqlite(&path));
+        assert!(
+            SqliteStorage::new(&encrypted_config(path.clone(), Some("wrong")))
+                .await
+                .is_err()
+        );
+
+        let reopened = SqliteStorage::new(&encrypted_config(path, Some("secret")))
+            .await
+            .unwrap();
+        assert!(reopened.get_session(&session.id).await.unwrap().is_some());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_encrypt_existing_plaintext_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.db");
+        let encrypted = dir.path().join("encrypted.db");
+
+        let storage = SqliteStorage::new(&encrypted_config(plain.clone(), None))
+            .await
+            .unwrap();
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        storage
+            .create_thought(&Thought::new(&session.id, "Sensitive reasoning", "linear"))
+            .await
+            .unwrap();
+        storage.pool().close().await;
+
+        // A plaintext file must be migrated before a key is configured
+        assert!(
+            SqliteStorage::new(&encrypted_config(plain.clone(), Some("secret")))
+                .await
+                .is_err()
+        );
+
+        SqliteStorage::encrypt_database(&plain, &encrypted, "secret")
+            .await
+            .unwrap();
+        assert!(!is_plaintext_sqlite(&encrypted));
+
+        let storage = SqliteStorage::new(&encrypted_config(encrypted, Some("secret")))
+            .await
+            .unwrap();
+        assert!(storage.get_session(&session.id).await.unwrap().is_some());
+        let hits = storage
+            .search_thoughts("sensitive", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_session_crud_operations() {
         let storage = SqliteStorage::new_in_memory().await.unwrap();
@@ -4116,6 +6891,92 @@ mod tests {
         assert_eq!(retrieved.parent_id, Some(parent.id));
     }
 
+    #[tokio::test]
+    async fn test_search_thoughts_across_sessions() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+
+        let session1 = Session::new("linear");
+        let session2 = Session::new("tree");
+        storage.create_session(&session1).await.unwrap();
+        storage.create_session(&session2).await.unwrap();
+
+        let t1 = Thought::new(&session1.id, "Caching reduces database load", "linear");
+        let t2 = Thought::new(&session2.id, "A cache layer for the database", "tree");
+        let t3 = Thought::new(&session2.id, "Unrelated idea about UI colors", "tree");
+        storage.create_thought(&t1).await.unwrap();
+        storage.create_thought(&t2).await.unwrap();
+        storage.create_thought(&t3).await.unwrap();
+
+        let hits = storage
+            .search_thoughts("database", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.thought.id != t3.id));
+        assert!(hits[0].snippet.contains("[database]"));
+
+        let hits = storage
+            .search_thoughts(
+                "database",
+                ThoughtSearchFilter::new().with_session(&session2.id),
+            )
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].thought.id, t2.id);
+
+        let hits = storage
+            .search_thoughts("database", ThoughtSearchFilter::new().with_mode("linear"))
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].thought.id, t1.id);
+    }
+
+    #[tokio::test]
+    async fn test_search_thoughts_ignores_fts_syntax_and_deleted_thoughts() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Rate-limit the \"public\" API", "linear");
+        storage.create_thought(&thought).await.unwrap();
+
+        let hits = storage
+            .search_thoughts("rate-limit \"API\" OR (", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert!(hits.is_empty(), "OR is a literal term, not an operator");
+
+        let hits = storage
+            .search_thoughts("\"rate-limit\" api*", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let hits = storage
+            .search_thoughts("  ()  ", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+
+        storage.delete_session(&session.id).await.unwrap();
+        let hits = storage
+            .search_thoughts("api", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_fts5_match_query_quotes_terms() {
+        assert_eq!(
+            fts5_match_query("error-handling \"retry\" OR"),
+            Some("\"error\" \"handling\" \"retry\" \"OR\"".to_string())
+        );
+        assert_eq!(fts5_match_query(" *() "), None);
+    }
+
     #[tokio::test]
     async fn test_get_root_graph_nodes() {
         let storage = SqliteStorage::new_in_memory().await.unwrap();
@@ -4228,7 +7089,7 @@ mod tests {
     async fn test_get_pipe_usage_summary_empty() {
         let storage = SqliteStorage::new_in_memory().await.unwrap();
 
-        let summary = storage.get_pipe_usage_summary().await.unwrap();
+        let summary = storage.get_pipe_usage_summary(None).await.unwrap();
         assert!(summary.is_empty());
     }
 
@@ -4254,7 +7115,7 @@ mod tests {
         storage.log_invocation(&inv2).await.unwrap();
         storage.log_invocation(&inv3).await.unwrap();
 
-        let summary = storage.get_pipe_usage_summary().await.unwrap();
+        let summary = storage.get_pipe_usage_summary(None).await.unwrap();
         assert_eq!(summary.len(), 1);
 
         let pipe_summary = &summary[0];
@@ -4289,7 +7150,7 @@ mod tests {
             storage.log_invocation(&inv).await.unwrap();
         }
 
-        let summary = storage.get_pipe_usage_summary().await.unwrap();
+        let summary = storage.get_pipe_usage_summary(None).await.unwrap();
         assert_eq!(summary.len(), 2);
 
         // Should be ordered by total_calls descending
@@ -4309,7 +7170,7 @@ mod tests {
             .mark_success();
         storage.log_invocation(&inv).await.unwrap();
 
-        let summary = storage.get_pipe_summary("linear-v1").await.unwrap();
+        let summary = storage.get_pipe_summary("linear-v1", None).await.unwrap();
         assert!(summary.is_some());
         assert_eq!(summary.unwrap().total_calls, 1);
     }
@@ -4318,7 +7179,10 @@ mod tests {
     async fn test_get_pipe_summary_nonexistent() {
         let storage = SqliteStorage::new_in_memory().await.unwrap();
 
-        let summary = storage.get_pipe_summary("nonexistent-pipe").await.unwrap();
+        let summary = storage
+            .get_pipe_summary("nonexistent-pipe", None)
+            .await
+            .unwrap();
         assert!(summary.is_none());
     }
 
@@ -4418,6 +7282,89 @@ mod tests {
         assert_eq!(invocations.len(), 5);
     }
 
+    #[tokio::test]
+    async fn test_get_invocations_with_offset() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+
+        for i in 0..10 {
+            let inv = Invocation::new(format!("tool{}", i), serde_json::json!({}))
+                .with_pipe("pipe1")
+                .mark_success();
+            storage.log_invocation(&inv).await.unwrap();
+        }
+
+        let first = storage
+            .get_invocations(super::super::MetricsFilter::new().with_limit(4))
+            .await
+            .unwrap();
+        let second = storage
+            .get_invocations(
+                super::super::MetricsFilter::new()
+                    .with_limit(4)
+                    .with_offset(4),
+            )
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 4);
+        assert!(second
+            .iter()
+            .all(|inv| first.iter().all(|f| f.id != inv.id)));
+
+        // Offset without a limit returns the remainder
+        let rest = storage
+            .get_invocations(super::super::MetricsFilter::new().with_offset(8))
+            .await
+            .unwrap();
+        assert_eq!(rest.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_session_thoughts_page() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        for i in 0..5 {
+            let thought = Thought::new(&session.id, format!("Thought {}", i), "linear");
+            storage.create_thought(&thought).await.unwrap();
+        }
+
+        let page = storage
+            .get_session_thoughts_page(&session.id, Pagination::first(2))
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(page.next_offset(), Some(2));
+
+        let last = storage
+            .get_session_thoughts_page(&session.id, Pagination::new(2, 4))
+            .await
+            .unwrap();
+        assert_eq!(last.items.len(), 1);
+        assert!(!last.has_more);
+        assert_eq!(last.next_offset(), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_session_graph_nodes_page() {
+        let storage = SqliteStorage::new_in_memory().await.unwrap();
+
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        for i in 0..3 {
+            let node = GraphNode::new(&session.id, format!("Node {}", i));
+            storage.create_graph_node(&node).await.unwrap();
+        }
+
+        let page = storage
+            .get_session_graph_nodes_page(&session.id, Pagination::first(3))
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 3);
+        assert!(!page.has_more);
+    }
+
     #[tokio::test]
     async fn test_get_invocation_count_all() {
         let storage = SqliteStorage::new_in_memory().await.unwrap();
@@ -4429,7 +7376,7 @@ mod tests {
             storage.log_invocation(&inv).await.unwrap();
         }
 
-        let count = storage.get_invocation_count(None).await.unwrap();
+        let count = storage.get_invocation_count(None, None).await.unwrap();
         assert_eq!(count, 5);
     }
 
@@ -4451,14 +7398,20 @@ mod tests {
             storage.log_invocation(&inv).await.unwrap();
         }
 
-        let count = storage.get_invocation_count(Some("pipe1")).await.unwrap();
+        let count = storage
+            .get_invocation_count(Some("pipe1"), None)
+            .await
+            .unwrap();
         assert_eq!(count, 3);
 
-        let count = storage.get_invocation_count(Some("pipe2")).await.unwrap();
+        let count = storage
+            .get_invocation_count(Some("pipe2"), None)
+            .await
+            .unwrap();
         assert_eq!(count, 2);
 
         let count = storage
-            .get_invocation_count(Some("nonexistent"))
+            .get_invocation_count(Some("nonexistent"), None)
             .await
             .unwrap();
         assert_eq!(count, 0);
@@ -1647,3 +1647,131 @@ fn test_detection_type_copy() {
     let copied = detection_type;
     assert_eq!(detection_type, copied);
 }
+
+// ============================================================================
+// Pagination tests
+// ============================================================================
+
+#[test]
+fn test_page_from_overfetch_with_more() {
+    let page = Page::from_overfetch(vec![1, 2, 3], Pagination::new(2, 10));
+    assert_eq!(page.items, vec![1, 2]);
+    assert_eq!(page.offset, 10);
+    assert!(page.has_more);
+    assert_eq!(page.next_offset(), Some(12));
+}
+
+#[test]
+fn test_page_from_overfetch_last_page() {
+    let page = Page::from_overfetch(vec![1, 2], Pagination::first(2));
+    assert_eq!(page.items.len(), 2);
+    assert!(!page.has_more);
+    assert_eq!(page.next_offset(), None);
+}
+
+#[test]
+fn test_page_from_all() {
+    let page = Page::from_all(vec![1, 2, 3, 4, 5], Pagination::new(2, 2));
+    assert_eq!(page.items, vec![3, 4]);
+    assert!(page.has_more);
+
+    let page = Page::from_all(vec![1, 2, 3, 4, 5], Pagination::new(2, 4));
+    assert_eq!(page.items, vec![5]);
+    assert!(!page.has_more);
+    assert_eq!(page.next_cursor(), None);
+}
+
+#[test]
+fn test_page_cursor_roundtrip() {
+    let page = Page::from_overfetch(vec![1, 2, 3], Pagination::new(2, 10));
+    let cursor = page.next_cursor().unwrap();
+    assert_eq!(
+        Pagination::from_cursor(5, &cursor),
+        Some(Pagination::new(5, 12))
+    );
+    assert_eq!(Pagination::from_cursor(5, "12"), None);
+    assert_eq!(Pagination::from_cursor(5, "offset:x"), None);
+}
+
+#[test]
+fn test_pagination_limit_at_least_one() {
+    assert_eq!(Pagination::new(0, 3).limit, 1);
+    assert_eq!(Pagination::first(0).limit, 1);
+    assert_eq!(
+        Pagination::from_cursor(0, "offset:3"),
+        Some(Pagination::new(1, 3))
+    );
+
+    // Following cursors with a zero limit still moves forward
+    let page = Page::from_all(vec![1, 2], Pagination::first(0));
+    assert_eq!(page.items, vec![1]);
+    assert_eq!(page.next_cursor().as_deref(), Some("offset:1"));
+}
+
+// ============================================================================
+// Embedding tests
+// ============================================================================
+
+#[test]
+fn test_cosine_similarity() {
+    assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-9);
+    assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-9);
+    assert!((cosine_similarity(&[1.0, 1.0], &[-1.0, -1.0]) + 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_cosine_similarity_degenerate_inputs() {
+    assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+    assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    assert_eq!(cosine_similarity(&[], &[]), 0.0);
+}
+
+#[test]
+fn test_embedding_source_round_trip() {
+    for source in [EmbeddingSource::Thought, EmbeddingSource::GraphNode] {
+        assert_eq!(source.to_string().parse::<EmbeddingSource>(), Ok(source));
+        assert_eq!(
+            serde_json::to_value(source).unwrap(),
+            json!(source.to_string())
+        );
+    }
+    assert!("vector".parse::<EmbeddingSource>().is_err());
+}
+
+// ============================================================================
+// Knowledge base tests
+// ============================================================================
+
+#[test]
+fn test_conclusion_source_round_trip() {
+    for source in [
+        ConclusionSource::GraphNode,
+        ConclusionSource::Decision,
+        ConclusionSource::Evidence,
+    ] {
+        assert_eq!(source.to_string().parse::<ConclusionSource>(), Ok(source));
+        assert_eq!(
+            serde_json::to_value(source).unwrap(),
+            json!(source.to_string())
+        );
+    }
+    assert!("thought".parse::<ConclusionSource>().is_err());
+}
+
+#[test]
+fn test_conclusion_builder() {
+    let conclusion = Conclusion::new(
+        ConclusionSource::Decision,
+        "d1",
+        "Which database?",
+        "Recommended: Postgres",
+    )
+    .with_session("s1")
+    .with_confidence(1.4);
+
+    assert!(!conclusion.id.is_empty());
+    assert_eq!(conclusion.source_id, "d1");
+    assert_eq!(conclusion.session_id.as_deref(), Some("s1"));
+    assert_eq!(conclusion.confidence, Some(1.0));
+    assert!(conclusion.metadata.is_none());
+}
@@ -0,0 +1,1300 @@
+//! Workspace isolation.
+//!
+//! One server can serve several projects by giving each its own workspace.
+//! [`WorkspaceStorage`] wraps another [`Storage`] and confines every operation
+//! to its workspace: new sessions, invocations, detections, and
+//! conclusions are stamped with it, and records belonging to other
+//! workspaces are invisible — reads skip them, and writes treat them as
+//! missing.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{
+    AuditEntry, AuditFilter, BackupReport, Branch, Checkpoint, CodeFinding, Conclusion,
+    ConclusionSource, CounterfactualAnalysis, CrossRef, Decision, Detection, DetectionType,
+    Embedding, EmbeddingCandidate, EvidenceAssessment, FallbackMetricsSummary, GraphComparison,
+    GraphEdge, GraphNode, InterventionType, Invocation, KnowledgeFilter, KnowledgeHit, MCTSNode,
+    MctsIteration, MctsIterationFilter, MetricsFilter, MigrationStatus, Page, Pagination,
+    PerspectiveAnalysis, PipeCacheEntry, PipePayload, PipeUsageSummary, ProbabilityUpdate, Risk,
+    RoutingRecord, RoutingRecordFilter, Session, SessionFilter, SessionFork, SharedStorage,
+    SimilarMatch, SimilarityFilter, StateSnapshot, Storage, SwotAnalysis, SwotFilter, Thought,
+    ThoughtRevision, ThoughtSearchFilter, ThoughtSearchHit, Timeline, TimelineBranch,
+    TimelineState,
+};
+use crate::error::{StorageError, StorageResult};
+
+/// Storage view confined to a single workspace.
+///
+/// The workspace is fixed for the life of the view; use
+/// [`with_workspace`](Self::with_workspace) to get a view of another one
+/// (e.g. once an MCP client has chosen one) without affecting other holders.
+/// Filters and arguments that name a workspace are overridden with the
+/// view's.
+///
+/// Retention, maintenance, backup, and the audit log cover the whole
+/// database and pass straight through.
+#[derive(Clone)]
+pub struct WorkspaceStorage {
+    inner: SharedStorage,
+    workspace_id: String,
+}
+
+impl WorkspaceStorage {
+    /// Wrap `inner`, confined to `workspace_id`.
+    pub fn new(inner: impl Into<SharedStorage>, workspace_id: impl Into<String>) -> Self {
+        Self {
+            inner: inner.into(),
+            workspace_id: workspace_id.into(),
+        }
+    }
+
+    /// The workspace this view is confined to.
+    pub fn workspace_id(&self) -> &str {
+        &self.workspace_id
+    }
+
+    /// A view of the same storage confined to `workspace_id`.
+    pub fn with_workspace(&self, workspace_id: impl Into<String>) -> Self {
+        Self::new(self.inner.clone(), workspace_id)
+    }
+
+    async fn owns_session(&self, session_id: &str) -> StorageResult<bool> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_session(session_id)
+            .await?
+            .is_some_and(|s| s.workspace_id == workspace_id))
+    }
+
+    /// Fail as if the session did not exist unless it is in this workspace.
+    async fn require_session(&self, session_id: &str) -> StorageResult<()> {
+        if self.owns_session(session_id).await? {
+            Ok(())
+        } else {
+            Err(StorageError::SessionNotFound {
+                session_id: session_id.to_string(),
+            })
+        }
+    }
+
+    /// Run a session-scoped read, or return an empty result for sessions in
+    /// other workspaces.
+    async fn read_session<T: Default>(
+        &self,
+        session_id: &str,
+        read: impl Future<Output = StorageResult<T>> + Send,
+    ) -> StorageResult<T> {
+        if self.owns_session(session_id).await? {
+            read.await
+        } else {
+            Ok(T::default())
+        }
+    }
+
+    /// Hide a record whose session is in another workspace.
+    async fn visible<T: Send>(
+        &self,
+        record: Option<T>,
+        session_id: impl FnOnce(&T) -> &str + Send,
+    ) -> StorageResult<Option<T>> {
+        let Some(record) = record else {
+            return Ok(None);
+        };
+        let owned = self.owns_session(session_id(&record)).await?;
+        Ok(owned.then_some(record))
+    }
+
+    /// Keep only records whose session is in this workspace.
+    async fn retain_visible<T: Send>(
+        &self,
+        records: Vec<T>,
+        session_id: impl Fn(&T) -> &str + Send + Sync,
+    ) -> StorageResult<Vec<T>> {
+        let mut owned: HashMap<String, bool> = HashMap::new();
+        let mut visible = Vec::with_capacity(records.len());
+        for record in records {
+            let id = session_id(&record);
+            let is_owned = match owned.get(id) {
+                Some(&is_owned) => is_owned,
+                None => {
+                    let is_owned = self.owns_session(id).await?;
+                    owned.insert(id.to_string(), is_owned);
+                    is_owned
+                }
+            };
+            if is_owned {
+                visible.push(record);
+            }
+        }
+        Ok(visible)
+    }
+}
+
+#[async_trait]
+impl Storage for WorkspaceStorage {
+    // Session operations
+
+    async fn create_session(&self, session: &Session) -> StorageResult<()> {
+        let mut session = session.clone();
+        session.workspace_id = self.workspace_id.clone();
+        self.inner.create_session(&session).await
+    }
+
+    async fn get_session(&self, id: &str) -> StorageResult<Option<Session>> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_session(id)
+            .await?
+            .filter(|s| s.workspace_id == workspace_id))
+    }
+
+    async fn update_session(&self, session: &Session) -> StorageResult<()> {
+        self.require_session(&session.id).await?;
+        self.inner.update_session(session).await
+    }
+
+    async fn delete_session(&self, id: &str) -> StorageResult<()> {
+        if self.owns_session(id).await? {
+            self.inner.delete_session(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn trash_session(&self, id: &str) -> StorageResult<bool> {
+        if !self.owns_session(id).await? {
+            return Ok(false);
+        }
+        self.inner.trash_session(id).await
+    }
+
+    async fn restore_session(&self, id: &str) -> StorageResult<bool> {
+        if !self.owns_session(id).await? {
+            return Ok(false);
+        }
+        self.inner.restore_session(id).await
+    }
+
+    async fn add_tag(&self, session_id: &str, tag: &str) -> StorageResult<()> {
+        self.require_session(session_id).await?;
+        self.inner.add_tag(session_id, tag).await
+    }
+
+    async fn remove_tag(&self, session_id: &str, tag: &str) -> StorageResult<bool> {
+        if !self.owns_session(session_id).await? {
+            return Ok(false);
+        }
+        self.inner.remove_tag(session_id, tag).await
+    }
+
+    async fn find_sessions_by_tag(&self, tag: &str) -> StorageResult<Vec<Session>> {
+        self.list_sessions(SessionFilter::new().with_tag(tag)).await
+    }
+
+    async fn list_sessions(&self, filter: SessionFilter) -> StorageResult<Vec<Session>> {
+        self.inner
+            .list_sessions(filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    async fn fork_session(&self, session_id: &str) -> StorageResult<SessionFork> {
+        self.require_session(session_id).await?;
+        self.inner.fork_session(session_id).await
+    }
+
+    // Thought operations
+
+    async fn create_thought(&self, thought: &Thought) -> StorageResult<()> {
+        self.require_session(&thought.session_id).await?;
+        self.inner.create_thought(thought).await
+    }
+
+    async fn create_thoughts_batch(&self, thoughts: &[Thought]) -> StorageResult<()> {
+        for thought in thoughts {
+            self.require_session(&thought.session_id).await?;
+        }
+        self.inner.create_thoughts_batch(thoughts).await
+    }
+
+    async fn get_thought(&self, id: &str) -> StorageResult<Option<Thought>> {
+        let thought = self.inner.get_thought(id).await?;
+        self.visible(thought, |t| &t.session_id).await
+    }
+
+    async fn get_session_thoughts(&self, session_id: &str) -> StorageResult<Vec<Thought>> {
+        self.read_session(session_id, self.inner.get_session_thoughts(session_id))
+            .await
+    }
+
+    async fn get_session_thoughts_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<Thought>> {
+        if !self.owns_session(session_id).await? {
+            return Ok(Page::from_overfetch(Vec::new(), pagination));
+        }
+        self.inner
+            .get_session_thoughts_page(session_id, pagination)
+            .await
+    }
+
+    async fn get_branch_thoughts(&self, branch_id: &str) -> StorageResult<Vec<Thought>> {
+        if self.get_branch(branch_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_branch_thoughts(branch_id).await
+    }
+
+    async fn get_latest_thought(&self, session_id: &str) -> StorageResult<Option<Thought>> {
+        self.read_session(session_id, self.inner.get_latest_thought(session_id))
+            .await
+    }
+
+    async fn search_thoughts(
+        &self,
+        query: &str,
+        filter: ThoughtSearchFilter,
+    ) -> StorageResult<Vec<ThoughtSearchHit>> {
+        self.inner
+            .search_thoughts(query, filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    async fn revise_thought(
+        &self,
+        thought_id: &str,
+        content: &str,
+        confidence: Option<f64>,
+        reason: Option<&str>,
+    ) -> StorageResult<Thought> {
+        if self.get_thought(thought_id).await?.is_none() {
+            return Err(StorageError::ThoughtNotFound {
+                thought_id: thought_id.to_string(),
+            });
+        }
+        self.inner
+            .revise_thought(thought_id, content, confidence, reason)
+            .await
+    }
+
+    async fn get_thought_revisions(&self, thought_id: &str) -> StorageResult<Vec<ThoughtRevision>> {
+        if self.get_thought(thought_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_thought_revisions(thought_id).await
+    }
+
+    async fn update_thought_metadata(&self, thought: &Thought) -> StorageResult<()> {
+        if self.get_thought(&thought.id).await?.is_none() {
+            return Err(StorageError::ThoughtNotFound {
+                thought_id: thought.id.clone(),
+            });
+        }
+        self.inner.update_thought_metadata(thought).await
+    }
+
+    // Embedding operations (semantic similarity)
+
+    async fn save_embeddings(&self, embeddings: &[Embedding]) -> StorageResult<()> {
+        for embedding in embeddings {
+            self.require_session(&embedding.session_id).await?;
+        }
+        self.inner.save_embeddings(embeddings).await
+    }
+
+    async fn get_embedding_candidates(
+        &self,
+        model: &str,
+        _workspace_id: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<EmbeddingCandidate>> {
+        self.inner
+            .get_embedding_candidates(model, Some(self.workspace_id()), limit)
+            .await
+    }
+
+    async fn find_similar(
+        &self,
+        vector: &[f32],
+        model: &str,
+        filter: SimilarityFilter,
+    ) -> StorageResult<Vec<SimilarMatch>> {
+        self.inner
+            .find_similar(vector, model, filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    // Knowledge base operations
+
+    async fn save_conclusion(&self, conclusion: &Conclusion) -> StorageResult<()> {
+        if let Some(session_id) = &conclusion.session_id {
+            self.require_session(session_id).await?;
+        }
+        let mut conclusion = conclusion.clone();
+        conclusion.workspace_id = self.workspace_id.clone();
+        self.inner.save_conclusion(&conclusion).await
+    }
+
+    async fn get_conclusion(&self, id: &str) -> StorageResult<Option<Conclusion>> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_conclusion(id)
+            .await?
+            .filter(|c| c.workspace_id == workspace_id))
+    }
+
+    async fn get_conclusion_by_source(
+        &self,
+        source_type: ConclusionSource,
+        source_id: &str,
+    ) -> StorageResult<Option<Conclusion>> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_conclusion_by_source(source_type, source_id)
+            .await?
+            .filter(|c| c.workspace_id == workspace_id))
+    }
+
+    async fn search_conclusions(
+        &self,
+        query: &str,
+        filter: KnowledgeFilter,
+    ) -> StorageResult<Vec<KnowledgeHit>> {
+        self.inner
+            .search_conclusions(query, filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    async fn delete_conclusion(&self, id: &str) -> StorageResult<bool> {
+        if self.get_conclusion(id).await?.is_none() {
+            return Ok(false);
+        }
+        self.inner.delete_conclusion(id).await
+    }
+
+    // Audit log operations
+
+    async fn append_audit_entry(&self, entry: &AuditEntry) -> StorageResult<()> {
+        self.inner.append_audit_entry(entry).await
+    }
+
+    async fn list_audit_entries(&self, filter: AuditFilter) -> StorageResult<Vec<AuditEntry>> {
+        self.inner.list_audit_entries(filter).await
+    }
+
+    // Pipe response cache (global, shared across workspaces)
+
+    async fn get_pipe_cache(&self, key: &str) -> StorageResult<Option<PipeCacheEntry>> {
+        self.inner.get_pipe_cache(key).await
+    }
+
+    async fn put_pipe_cache(&self, entry: &PipeCacheEntry) -> StorageResult<()> {
+        self.inner.put_pipe_cache(entry).await
+    }
+
+    async fn log_pipe_payload(&self, payload: &PipePayload) -> StorageResult<()> {
+        self.inner.log_pipe_payload(payload).await
+    }
+
+    async fn get_pipe_payloads(
+        &self,
+        pipe_name: Option<&str>,
+        limit: u32,
+    ) -> StorageResult<Vec<PipePayload>> {
+        self.inner.get_pipe_payloads(pipe_name, limit).await
+    }
+
+    // Routing history operations
+
+    async fn create_routing_record(&self, record: &RoutingRecord) -> StorageResult<()> {
+        // The session may be created after routing, but an existing one
+        // must belong to this workspace
+        if let Some(session_id) = &record.session_id {
+            if self.inner.get_session(session_id).await?.is_some() {
+                self.require_session(session_id).await?;
+            }
+        }
+        let mut record = record.clone();
+        record.workspace_id = self.workspace_id.clone();
+        self.inner.create_routing_record(&record).await
+    }
+
+    async fn get_routing_record(&self, id: &str) -> StorageResult<Option<RoutingRecord>> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_routing_record(id)
+            .await?
+            .filter(|r| r.workspace_id == workspace_id))
+    }
+
+    async fn update_routing_outcome(&self, record: &RoutingRecord) -> StorageResult<()> {
+        if self.get_routing_record(&record.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Routing record not found: {}", record.id),
+            });
+        }
+        self.inner.update_routing_outcome(record).await
+    }
+
+    async fn list_routing_records(
+        &self,
+        filter: RoutingRecordFilter,
+    ) -> StorageResult<Vec<RoutingRecord>> {
+        self.inner
+            .list_routing_records(filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    // Branch operations (tree mode)
+
+    async fn create_branch(&self, branch: &Branch) -> StorageResult<()> {
+        self.require_session(&branch.session_id).await?;
+        self.inner.create_branch(branch).await
+    }
+
+    async fn get_branch(&self, id: &str) -> StorageResult<Option<Branch>> {
+        let branch = self.inner.get_branch(id).await?;
+        self.visible(branch, |b| &b.session_id).await
+    }
+
+    async fn get_session_branches(&self, session_id: &str) -> StorageResult<Vec<Branch>> {
+        self.read_session(session_id, self.inner.get_session_branches(session_id))
+            .await
+    }
+
+    async fn get_child_branches(&self, parent_id: &str) -> StorageResult<Vec<Branch>> {
+        let branches = self.inner.get_child_branches(parent_id).await?;
+        self.retain_visible(branches, |b| &b.session_id).await
+    }
+
+    async fn update_branch(&self, branch: &Branch) -> StorageResult<()> {
+        if self.get_branch(&branch.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Branch not found: {}", branch.id),
+            });
+        }
+        self.inner.update_branch(branch).await
+    }
+
+    async fn delete_branch(&self, id: &str) -> StorageResult<()> {
+        if self.get_branch(id).await?.is_some() {
+            self.inner.delete_branch(id).await?;
+        }
+        Ok(())
+    }
+
+    // Cross-reference operations (tree mode)
+
+    async fn create_cross_ref(&self, cross_ref: &CrossRef) -> StorageResult<()> {
+        for branch_id in [&cross_ref.from_branch_id, &cross_ref.to_branch_id] {
+            if self.get_branch(branch_id).await?.is_none() {
+                return Err(StorageError::Query {
+                    message: format!("Branch not found: {}", branch_id),
+                });
+            }
+        }
+        self.inner.create_cross_ref(cross_ref).await
+    }
+
+    async fn get_cross_refs_from(&self, branch_id: &str) -> StorageResult<Vec<CrossRef>> {
+        if self.get_branch(branch_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_cross_refs_from(branch_id).await
+    }
+
+    async fn get_cross_refs_to(&self, branch_id: &str) -> StorageResult<Vec<CrossRef>> {
+        if self.get_branch(branch_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_cross_refs_to(branch_id).await
+    }
+
+    async fn delete_cross_ref(&self, id: &str) -> StorageResult<()> {
+        // Cross-references can only be found through their branches, so an
+        // ID from another workspace can't be obtained without guessing it
+        self.inner.delete_cross_ref(id).await
+    }
+
+    // Checkpoint operations (backtracking)
+
+    async fn create_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        self.require_session(&checkpoint.session_id).await?;
+        self.inner.create_checkpoint(checkpoint).await
+    }
+
+    async fn get_checkpoint(&self, id: &str) -> StorageResult<Option<Checkpoint>> {
+        let checkpoint = self.inner.get_checkpoint(id).await?;
+        self.visible(checkpoint, |c| &c.session_id).await
+    }
+
+    async fn get_session_checkpoints(&self, session_id: &str) -> StorageResult<Vec<Checkpoint>> {
+        self.read_session(session_id, self.inner.get_session_checkpoints(session_id))
+            .await
+    }
+
+    async fn get_branch_checkpoints(&self, branch_id: &str) -> StorageResult<Vec<Checkpoint>> {
+        let checkpoints = self.inner.get_branch_checkpoints(branch_id).await?;
+        self.retain_visible(checkpoints, |c| &c.session_id).await
+    }
+
+    async fn delete_checkpoint(&self, id: &str) -> StorageResult<()> {
+        if self.get_checkpoint(id).await?.is_some() {
+            self.inner.delete_checkpoint(id).await?;
+        }
+        Ok(())
+    }
+
+    // Invocation logging and metrics
+
+    async fn log_invocation(&self, invocation: &Invocation) -> StorageResult<()> {
+        let mut invocation = invocation.clone();
+        invocation.workspace_id = self.workspace_id.clone();
+        self.inner.log_invocation(&invocation).await
+    }
+
+    async fn get_pipe_usage_summary(
+        &self,
+        _workspace_id: Option<&str>,
+    ) -> StorageResult<Vec<PipeUsageSummary>> {
+        self.inner
+            .get_pipe_usage_summary(Some(self.workspace_id()))
+            .await
+    }
+
+    async fn get_pipe_summary(
+        &self,
+        pipe_name: &str,
+        _workspace_id: Option<&str>,
+    ) -> StorageResult<Option<PipeUsageSummary>> {
+        self.inner
+            .get_pipe_summary(pipe_name, Some(self.workspace_id()))
+            .await
+    }
+
+    async fn get_invocations(&self, filter: MetricsFilter) -> StorageResult<Vec<Invocation>> {
+        self.inner
+            .get_invocations(filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    async fn get_invocation_count(
+        &self,
+        pipe_name: Option<&str>,
+        _workspace_id: Option<&str>,
+    ) -> StorageResult<u64> {
+        self.inner
+            .get_invocation_count(pipe_name, Some(self.workspace_id()))
+            .await
+    }
+
+    async fn get_fallback_metrics(
+        &self,
+        _workspace_id: Option<&str>,
+    ) -> StorageResult<FallbackMetricsSummary> {
+        self.inner
+            .get_fallback_metrics(Some(self.workspace_id()))
+            .await
+    }
+
+    // Graph node operations (GoT mode)
+
+    async fn create_graph_node(&self, node: &GraphNode) -> StorageResult<()> {
+        self.require_session(&node.session_id).await?;
+        self.inner.create_graph_node(node).await
+    }
+
+    async fn create_graph_nodes_batch(&self, nodes: &[GraphNode]) -> StorageResult<()> {
+        for node in nodes {
+            self.require_session(&node.session_id).await?;
+        }
+        self.inner.create_graph_nodes_batch(nodes).await
+    }
+
+    async fn get_graph_node(&self, id: &str) -> StorageResult<Option<GraphNode>> {
+        let node = self.inner.get_graph_node(id).await?;
+        self.visible(node, |n| &n.session_id).await
+    }
+
+    async fn get_session_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.read_session(session_id, self.inner.get_session_graph_nodes(session_id))
+            .await
+    }
+
+    async fn get_session_graph_nodes_page(
+        &self,
+        session_id: &str,
+        pagination: Pagination,
+    ) -> StorageResult<Page<GraphNode>> {
+        if !self.owns_session(session_id).await? {
+            return Ok(Page::from_overfetch(Vec::new(), pagination));
+        }
+        self.inner
+            .get_session_graph_nodes_page(session_id, pagination)
+            .await
+    }
+
+    async fn get_active_graph_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.read_session(session_id, self.inner.get_active_graph_nodes(session_id))
+            .await
+    }
+
+    async fn get_root_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.read_session(session_id, self.inner.get_root_nodes(session_id))
+            .await
+    }
+
+    async fn get_terminal_nodes(&self, session_id: &str) -> StorageResult<Vec<GraphNode>> {
+        self.read_session(session_id, self.inner.get_terminal_nodes(session_id))
+            .await
+    }
+
+    async fn update_graph_node(&self, node: &GraphNode) -> StorageResult<()> {
+        if self.get_graph_node(&node.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Graph node not found: {}", node.id),
+            });
+        }
+        self.inner.update_graph_node(node).await
+    }
+
+    async fn delete_graph_node(&self, id: &str) -> StorageResult<()> {
+        if self.get_graph_node(id).await?.is_some() {
+            self.inner.delete_graph_node(id).await?;
+        }
+        Ok(())
+    }
+
+    // Graph edge operations (GoT mode)
+
+    async fn create_graph_edge(&self, edge: &GraphEdge) -> StorageResult<()> {
+        self.require_session(&edge.session_id).await?;
+        self.inner.create_graph_edge(edge).await
+    }
+
+    async fn create_graph_edges_batch(&self, edges: &[GraphEdge]) -> StorageResult<()> {
+        for edge in edges {
+            self.require_session(&edge.session_id).await?;
+        }
+        self.inner.create_graph_edges_batch(edges).await
+    }
+
+    async fn get_graph_edge(&self, id: &str) -> StorageResult<Option<GraphEdge>> {
+        let edge = self.inner.get_graph_edge(id).await?;
+        self.visible(edge, |e| &e.session_id).await
+    }
+
+    async fn get_edges_from(&self, node_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        let edges = self.inner.get_edges_from(node_id).await?;
+        self.retain_visible(edges, |e| &e.session_id).await
+    }
+
+    async fn get_edges_to(&self, node_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        let edges = self.inner.get_edges_to(node_id).await?;
+        self.retain_visible(edges, |e| &e.session_id).await
+    }
+
+    async fn get_session_edges(&self, session_id: &str) -> StorageResult<Vec<GraphEdge>> {
+        self.read_session(session_id, self.inner.get_session_edges(session_id))
+            .await
+    }
+
+    async fn delete_graph_edge(&self, id: &str) -> StorageResult<()> {
+        if self.get_graph_edge(id).await?.is_some() {
+            self.inner.delete_graph_edge(id).await?;
+        }
+        Ok(())
+    }
+
+    // Graph comparison operations (GoT pairwise ranking)
+
+    async fn create_graph_comparisons_batch(
+        &self,
+        comparisons: &[GraphComparison],
+    ) -> StorageResult<()> {
+        for comparison in comparisons {
+            self.require_session(&comparison.session_id).await?;
+        }
+        self.inner.create_graph_comparisons_batch(comparisons).await
+    }
+
+    async fn get_session_graph_comparisons(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<GraphComparison>> {
+        self.read_session(
+            session_id,
+            self.inner.get_session_graph_comparisons(session_id),
+        )
+        .await
+    }
+
+    // State snapshot operations (backtracking)
+
+    async fn create_snapshot(&self, snapshot: &StateSnapshot) -> StorageResult<()> {
+        self.require_session(&snapshot.session_id).await?;
+        self.inner.create_snapshot(snapshot).await
+    }
+
+    async fn get_snapshot(&self, id: &str) -> StorageResult<Option<StateSnapshot>> {
+        let snapshot = self.inner.get_snapshot(id).await?;
+        self.visible(snapshot, |s| &s.session_id).await
+    }
+
+    async fn get_session_snapshots(&self, session_id: &str) -> StorageResult<Vec<StateSnapshot>> {
+        self.read_session(session_id, self.inner.get_session_snapshots(session_id))
+            .await
+    }
+
+    async fn get_latest_snapshot(&self, session_id: &str) -> StorageResult<Option<StateSnapshot>> {
+        self.read_session(session_id, self.inner.get_latest_snapshot(session_id))
+            .await
+    }
+
+    async fn delete_snapshot(&self, id: &str) -> StorageResult<()> {
+        if self.get_snapshot(id).await?.is_some() {
+            self.inner.delete_snapshot(id).await?;
+        }
+        Ok(())
+    }
+
+    // Detection operations (bias/fallacy analysis)
+
+    async fn create_detection(&self, detection: &Detection) -> StorageResult<()> {
+        if let Some(session_id) = &detection.session_id {
+            self.require_session(session_id).await?;
+        }
+        let mut detection = detection.clone();
+        detection.workspace_id = self.workspace_id.clone();
+        self.inner.create_detection(&detection).await
+    }
+
+    async fn get_detection(&self, id: &str) -> StorageResult<Option<Detection>> {
+        let workspace_id = self.workspace_id();
+        Ok(self
+            .inner
+            .get_detection(id)
+            .await?
+            .filter(|d| d.workspace_id == workspace_id))
+    }
+
+    async fn get_session_detections(&self, session_id: &str) -> StorageResult<Vec<Detection>> {
+        self.read_session(session_id, self.inner.get_session_detections(session_id))
+            .await
+    }
+
+    async fn get_thought_detections(&self, thought_id: &str) -> StorageResult<Vec<Detection>> {
+        if self.get_thought(thought_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_thought_detections(thought_id).await
+    }
+
+    async fn get_detections_by_type(
+        &self,
+        detection_type: DetectionType,
+    ) -> StorageResult<Vec<Detection>> {
+        let workspace_id = self.workspace_id();
+        let mut detections = self.inner.get_detections_by_type(detection_type).await?;
+        detections.retain(|d| d.workspace_id == workspace_id);
+        Ok(detections)
+    }
+
+    async fn get_session_detections_by_type(
+        &self,
+        session_id: &str,
+        detection_type: DetectionType,
+    ) -> StorageResult<Vec<Detection>> {
+        self.read_session(
+            session_id,
+            self.inner
+                .get_session_detections_by_type(session_id, detection_type),
+        )
+        .await
+    }
+
+    async fn delete_detection(&self, id: &str) -> StorageResult<()> {
+        if self.get_detection(id).await?.is_some() {
+            self.inner.delete_detection(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Decision operations (decision framework)
+    // ========================================================================
+
+    async fn create_decision(&self, decision: &Decision) -> StorageResult<()> {
+        self.require_session(&decision.session_id).await?;
+        self.inner.create_decision(decision).await
+    }
+
+    async fn get_decision(&self, id: &str) -> StorageResult<Option<Decision>> {
+        let decision = self.inner.get_decision(id).await?;
+        self.visible(decision, |d| &d.session_id).await
+    }
+
+    async fn get_session_decisions(&self, session_id: &str) -> StorageResult<Vec<Decision>> {
+        self.read_session(session_id, self.inner.get_session_decisions(session_id))
+            .await
+    }
+
+    async fn get_decisions_by_method(&self, method: &str) -> StorageResult<Vec<Decision>> {
+        let decisions = self.inner.get_decisions_by_method(method).await?;
+        self.retain_visible(decisions, |d| &d.session_id).await
+    }
+
+    async fn delete_decision(&self, id: &str) -> StorageResult<()> {
+        if self.get_decision(id).await?.is_some() {
+            self.inner.delete_decision(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Perspective analysis operations (decision framework)
+    // ========================================================================
+
+    async fn create_perspective(&self, analysis: &PerspectiveAnalysis) -> StorageResult<()> {
+        self.require_session(&analysis.session_id).await?;
+        self.inner.create_perspective(analysis).await
+    }
+
+    async fn get_perspective(&self, id: &str) -> StorageResult<Option<PerspectiveAnalysis>> {
+        let analysis = self.inner.get_perspective(id).await?;
+        self.visible(analysis, |a| &a.session_id).await
+    }
+
+    async fn get_session_perspectives(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<PerspectiveAnalysis>> {
+        self.read_session(session_id, self.inner.get_session_perspectives(session_id))
+            .await
+    }
+
+    async fn delete_perspective(&self, id: &str) -> StorageResult<()> {
+        if self.get_perspective(id).await?.is_some() {
+            self.inner.delete_perspective(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // SWOT analysis operations (swot mode)
+    // ========================================================================
+
+    async fn create_swot_analysis(&self, analysis: &SwotAnalysis) -> StorageResult<()> {
+        self.require_session(&analysis.session_id).await?;
+        self.inner.create_swot_analysis(analysis).await
+    }
+
+    async fn find_swot_analyses(&self, filter: SwotFilter) -> StorageResult<Vec<SwotAnalysis>> {
+        self.inner
+            .find_swot_analyses(filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    // ========================================================================
+    // Risk register operations (risk mode)
+    // ========================================================================
+
+    async fn create_risk(&self, risk: &Risk) -> StorageResult<()> {
+        self.require_session(&risk.session_id).await?;
+        self.inner.create_risk(risk).await
+    }
+
+    async fn get_risk(&self, id: &str) -> StorageResult<Option<Risk>> {
+        let risk = self.inner.get_risk(id).await?;
+        self.visible(risk, |r| &r.session_id).await
+    }
+
+    async fn get_session_risks(&self, session_id: &str) -> StorageResult<Vec<Risk>> {
+        self.read_session(session_id, self.inner.get_session_risks(session_id))
+            .await
+    }
+
+    async fn update_risk(&self, risk: &Risk) -> StorageResult<()> {
+        if self.get_risk(&risk.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Risk not found: {}", risk.id),
+            });
+        }
+        self.inner.update_risk(risk).await
+    }
+
+    // ========================================================================
+    // Code finding operations (code reasoning mode)
+    // ========================================================================
+
+    async fn create_code_finding(&self, finding: &CodeFinding) -> StorageResult<()> {
+        self.require_session(&finding.session_id).await?;
+        self.inner.create_code_finding(finding).await
+    }
+
+    async fn get_session_code_findings(&self, session_id: &str) -> StorageResult<Vec<CodeFinding>> {
+        self.read_session(session_id, self.inner.get_session_code_findings(session_id))
+            .await
+    }
+
+    // ========================================================================
+    // Evidence assessment operations (evidence mode)
+    // ========================================================================
+
+    async fn create_evidence_assessment(
+        &self,
+        assessment: &EvidenceAssessment,
+    ) -> StorageResult<()> {
+        self.require_session(&assessment.session_id).await?;
+        self.inner.create_evidence_assessment(assessment).await
+    }
+
+    async fn get_evidence_assessment(&self, id: &str) -> StorageResult<Option<EvidenceAssessment>> {
+        let assessment = self.inner.get_evidence_assessment(id).await?;
+        self.visible(assessment, |a| &a.session_id).await
+    }
+
+    async fn get_session_evidence_assessments(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<EvidenceAssessment>> {
+        self.read_session(
+            session_id,
+            self.inner.get_session_evidence_assessments(session_id),
+        )
+        .await
+    }
+
+    async fn delete_evidence_assessment(&self, id: &str) -> StorageResult<()> {
+        if self.get_evidence_assessment(id).await?.is_some() {
+            self.inner.delete_evidence_assessment(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Probability update operations (evidence mode)
+    // ========================================================================
+
+    async fn create_probability_update(&self, update: &ProbabilityUpdate) -> StorageResult<()> {
+        self.require_session(&update.session_id).await?;
+        self.inner.create_probability_update(update).await
+    }
+
+    async fn get_probability_update(&self, id: &str) -> StorageResult<Option<ProbabilityUpdate>> {
+        let update = self.inner.get_probability_update(id).await?;
+        self.visible(update, |u| &u.session_id).await
+    }
+
+    async fn get_session_probability_updates(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<ProbabilityUpdate>> {
+        self.read_session(
+            session_id,
+            self.inner.get_session_probability_updates(session_id),
+        )
+        .await
+    }
+
+    async fn get_hypothesis_updates(
+        &self,
+        session_id: &str,
+        hypothesis: &str,
+    ) -> StorageResult<Vec<ProbabilityUpdate>> {
+        self.read_session(
+            session_id,
+            self.inner.get_hypothesis_updates(session_id, hypothesis),
+        )
+        .await
+    }
+
+    async fn delete_probability_update(&self, id: &str) -> StorageResult<()> {
+        if self.get_probability_update(id).await?.is_some() {
+            self.inner.delete_probability_update(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Timeline operations (Time Machine)
+    // ========================================================================
+
+    async fn create_timeline(&self, timeline: &Timeline) -> StorageResult<()> {
+        self.require_session(&timeline.session_id).await?;
+        self.inner.create_timeline(timeline).await
+    }
+
+    async fn get_timeline(&self, id: &str) -> StorageResult<Option<Timeline>> {
+        let timeline = self.inner.get_timeline(id).await?;
+        self.visible(timeline, |t| &t.session_id).await
+    }
+
+    async fn get_session_timelines(&self, session_id: &str) -> StorageResult<Vec<Timeline>> {
+        self.read_session(session_id, self.inner.get_session_timelines(session_id))
+            .await
+    }
+
+    async fn get_timelines_by_state(&self, state: TimelineState) -> StorageResult<Vec<Timeline>> {
+        let timelines = self.inner.get_timelines_by_state(state).await?;
+        self.retain_visible(timelines, |t| &t.session_id).await
+    }
+
+    async fn update_timeline(&self, timeline: &Timeline) -> StorageResult<()> {
+        if self.get_timeline(&timeline.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Timeline not found: {}", timeline.id),
+            });
+        }
+        self.inner.update_timeline(timeline).await
+    }
+
+    async fn delete_timeline(&self, id: &str) -> StorageResult<()> {
+        if self.get_timeline(id).await?.is_some() {
+            self.inner.delete_timeline(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Timeline branch operations (Time Machine)
+    // ========================================================================
+
+    async fn create_timeline_branch(&self, branch: &TimelineBranch) -> StorageResult<()> {
+        if self.get_timeline(&branch.timeline_id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Timeline not found: {}", branch.timeline_id),
+            });
+        }
+        self.inner.create_timeline_branch(branch).await
+    }
+
+    async fn get_timeline_branch(&self, branch_id: &str) -> StorageResult<Option<TimelineBranch>> {
+        let Some(branch) = self.inner.get_timeline_branch(branch_id).await? else {
+            return Ok(None);
+        };
+        let visible = self.get_timeline(&branch.timeline_id).await?.is_some();
+        Ok(visible.then_some(branch))
+    }
+
+    async fn get_timeline_branches(&self, timeline_id: &str) -> StorageResult<Vec<TimelineBranch>> {
+        if self.get_timeline(timeline_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_timeline_branches(timeline_id).await
+    }
+
+    async fn get_branches_by_ucb(&self, timeline_id: &str) -> StorageResult<Vec<TimelineBranch>> {
+        if self.get_timeline(timeline_id).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        self.inner.get_branches_by_ucb(timeline_id).await
+    }
+
+    async fn update_timeline_branch(&self, branch: &TimelineBranch) -> StorageResult<()> {
+        if self.get_timeline_branch(&branch.branch_id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("Timeline branch not found: {}", branch.branch_id),
+            });
+        }
+        self.inner.update_timeline_branch(branch).await
+    }
+
+    async fn delete_timeline_branch(&self, branch_id: &str) -> StorageResult<()> {
+        if self.get_timeline_branch(branch_id).await?.is_some() {
+            self.inner.delete_timeline_branch(branch_id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // MCTS node operations (Time Machine)
+    // ========================================================================
+
+    async fn create_mcts_node(&self, node: &MCTSNode) -> StorageResult<()> {
+        self.require_session(&node.session_id).await?;
+        self.inner.create_mcts_node(node).await
+    }
+
+    async fn get_mcts_node(&self, id: &str) -> StorageResult<Option<MCTSNode>> {
+        let node = self.inner.get_mcts_node(id).await?;
+        self.visible(node, |n| &n.session_id).await
+    }
+
+    async fn get_session_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.read_session(session_id, self.inner.get_session_mcts_nodes(session_id))
+            .await
+    }
+
+    async fn get_timeline_mcts_nodes(&self, timeline_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        let nodes = self.inner.get_timeline_mcts_nodes(timeline_id).await?;
+        self.retain_visible(nodes, |n| &n.session_id).await
+    }
+
+    async fn get_mcts_children(&self, parent_node_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        let nodes = self.inner.get_mcts_children(parent_node_id).await?;
+        self.retain_visible(nodes, |n| &n.session_id).await
+    }
+
+    async fn get_unexpanded_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.read_session(session_id, self.inner.get_unexpanded_mcts_nodes(session_id))
+            .await
+    }
+
+    async fn get_terminal_mcts_nodes(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.read_session(session_id, self.inner.get_terminal_mcts_nodes(session_id))
+            .await
+    }
+
+    async fn get_mcts_nodes_by_ucb(&self, session_id: &str) -> StorageResult<Vec<MCTSNode>> {
+        self.read_session(session_id, self.inner.get_mcts_nodes_by_ucb(session_id))
+            .await
+    }
+
+    async fn update_mcts_node(&self, node: &MCTSNode) -> StorageResult<()> {
+        if self.get_mcts_node(&node.id).await?.is_none() {
+            return Err(StorageError::Query {
+                message: format!("MCTS node not found: {}", node.id),
+            });
+        }
+        self.inner.update_mcts_node(node).await
+    }
+
+    async fn delete_mcts_node(&self, id: &str) -> StorageResult<()> {
+        if self.get_mcts_node(id).await?.is_some() {
+            self.inner.delete_mcts_node(id).await?;
+        }
+        Ok(())
+    }
+
+    async fn create_mcts_iteration(&self, iteration: &MctsIteration) -> StorageResult<()> {
+        self.require_session(&iteration.session_id).await?;
+        self.inner.create_mcts_iteration(iteration).await
+    }
+
+    async fn get_mcts_iterations(
+        &self,
+        filter: MctsIterationFilter,
+    ) -> StorageResult<Vec<MctsIteration>> {
+        self.inner
+            .get_mcts_iterations(filter.in_workspace(self.workspace_id()))
+            .await
+    }
+
+    // ========================================================================
+    // Counterfactual analysis operations (Time Machine)
+    // ========================================================================
+
+    async fn create_counterfactual(&self, analysis: &CounterfactualAnalysis) -> StorageResult<()> {
+        self.require_session(&analysis.session_id).await?;
+        self.inner.create_counterfactual(analysis).await
+    }
+
+    async fn get_counterfactual(&self, id: &str) -> StorageResult<Option<CounterfactualAnalysis>> {
+        let analysis = self.inner.get_counterfactual(id).await?;
+        self.visible(analysis, |a| &a.session_id).await
+    }
+
+    async fn get_session_counterfactuals(
+        &self,
+        session_id: &str,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        self.read_session(
+            session_id,
+            self.inner.get_session_counterfactuals(session_id),
+        )
+        .await
+    }
+
+    async fn get_timeline_counterfactuals(
+        &self,
+        timeline_id: &str,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        let analyses = self.inner.get_timeline_counterfactuals(timeline_id).await?;
+        self.retain_visible(analyses, |a| &a.session_id).await
+    }
+
+    async fn get_counterfactuals_by_type(
+        &self,
+        intervention_type: InterventionType,
+    ) -> StorageResult<Vec<CounterfactualAnalysis>> {
+        let analyses = self
+            .inner
+            .get_counterfactuals_by_type(intervention_type)
+            .await?;
+        self.retain_visible(analyses, |a| &a.session_id).await
+    }
+
+    async fn delete_counterfactual(&self, id: &str) -> StorageResult<()> {
+        if self.get_counterfactual(id).await?.is_some() {
+            self.inner.delete_counterfactual(id).await?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Retention operations
+    // ========================================================================
+
+    async fn delete_sessions_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_sessions_before(cutoff).await
+    }
+
+    async fn purge_trashed_sessions(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.purge_trashed_sessions(cutoff).await
+    }
+
+    async fn delete_snapshots_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_snapshots_before(cutoff).await
+    }
+
+    async fn delete_invocations_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_invocations_before(cutoff).await
+    }
+
+    async fn delete_pipe_payloads_before(&self, cutoff: DateTime<Utc>) -> StorageResult<u64> {
+        self.inner.delete_pipe_payloads_before(cutoff).await
+    }
+
+    async fn trim_invocations(&self, max_entries: u64) -> StorageResult<u64> {
+        self.inner.trim_invocations(max_entries).await
+    }
+
+    // ========================================================================
+    // Maintenance operations
+    // ========================================================================
+
+    async fn integrity_check(&self) -> StorageResult<Vec<String>> {
+        self.inner.integrity_check().await
+    }
+
+    async fn schema_status(&self) -> StorageResult<MigrationStatus> {
+        self.inner.schema_status().await
+    }
+
+    async fn vacuum(&self) -> StorageResult<()> {
+        self.inner.vacuum().await
+    }
+
+    async fn analyze(&self) -> StorageResult<()> {
+        self.inner.analyze().await
+    }
+
+    // ========================================================================
+    // Backup operations
+    // ========================================================================
+
+    async fn backup(&self, target: &Path, compress: bool) -> StorageResult<BackupReport> {
+        self.inner.backup(target, compress).await
+    }
+}
@@ -3,6 +3,8 @@
 //! These tests verify the end-to-end behavior of the reasoning system,
 //! ensuring all components work together correctly.
 
+use std::collections::HashMap;
+
 use serde_json::json;
 use tempfile::tempdir;
 use wiremock::{
@@ -11,8 +13,11 @@ use wiremock::{
 };
 
 use mcp_langbase_reasoning::config::{
-    Config, DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-    PipeConfig, RequestConfig,
+    BudgetConfig, ConcurrencyConfig, Config, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+    LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig, NetworkConfig,
+    PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig, ProviderConfig,
+    RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig, TransportConfig,
+    DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
 };
 use mcp_langbase_reasoning::langbase::LangbaseClient;
 use mcp_langbase_reasoning::modes::{
@@ -27,10 +32,15 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
         langbase: LangbaseConfig {
             api_key: "test-api-key".to_string(),
             base_url: mock_url.to_string(),
+            network: NetworkConfig::default(),
         },
         database: DatabaseConfig {
             path: db_path,
             max_connections: 1,
+            encryption_key: None,
+            maintenance: MaintenanceConfig::default(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
         },
         logging: LoggingConfig {
             level: "debug".to_string(),
@@ -40,6 +50,8 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
             timeout_ms: 5000,
             max_retries: 0,
             retry_delay_ms: 100,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         },
         pipes: PipeConfig {
             linear: "linear-reasoning-v1".to_string(),
@@ -53,8 +65,24 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
             detection: None,
             decision: None,
             evidence: None,
+            code: None,
+            versions: HashMap::new(),
+            schema_versions: HashMap::new(),
+            health_check_on_startup: false,
         },
         error_handling: ErrorHandlingConfig::default(),
+        retention: RetentionConfig::default(),
+        embeddings: EmbeddingConfig::default(),
+        providers: ProviderConfig::default(),
+        pipe_cache: PipeCacheConfig::default(),
+        rate_limits: RateLimitConfig::default(),
+        circuit_breaker: PipeCircuitBreakerConfig::default(),
+        concurrency: ConcurrencyConfig::default(),
+        payload_log: PayloadLogConfig::default(),
+        budget: BudgetConfig::default(),
+        transport: TransportConfig::default(),
+        tools: ToolsConfig::default(),
+        mcts: MctsConfig::default(),
     }
 }
 
@@ -63,6 +91,10 @@ async fn create_test_storage(db_path: std::path::PathBuf) -> SqliteStorage {
     let config = DatabaseConfig {
         path: db_path,
         max_connections: 1,
+        encryption_key: None,
+        maintenance: MaintenanceConfig::default(),
+        cache_capacity: DEFAULT_CACHE_CAPACITY,
+        workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
     };
     SqliteStorage::new(&config)
         .await
@@ -241,6 +273,55 @@ mod linear_mode_integration {
         assert_eq!(stored_thought.unwrap().content, linear_result.content);
     }
 
+    #[tokio::test]
+    async fn test_linear_mode_uses_pinned_pipe_version() {
+        use mcp_langbase_reasoning::config::PipeVersion;
+        use mcp_langbase_reasoning::providers::ProviderRouter;
+        use wiremock::matchers::body_partial_json;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({"name": "linear-reasoning-v2"})))
+            .respond_with(mock_linear_response())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = create_test_config(&mock_server.uri(), db_path.clone());
+        config.pipes.versions.insert(
+            "linear-reasoning-v1".to_string(),
+            vec![PipeVersion {
+                name: "linear-reasoning-v2".to_string(),
+                weight: 100,
+            }],
+        );
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let router = ProviderRouter::from_config(&config, langbase).unwrap();
+
+        let mode = LinearMode::new(storage.clone(), router, &config);
+        mode.process(LinearParams::new("Pinned version"))
+            .await
+            .unwrap();
+
+        // The invocation is attributed to the version that served it
+        let summary = storage
+            .get_pipe_summary("linear-reasoning-v2", None)
+            .await
+            .unwrap()
+            .expect("invocation logged for pinned version");
+        assert_eq!(summary.total_calls, 1);
+        assert!(storage
+            .get_pipe_summary("linear-reasoning-v1", None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_linear_mode_with_session_continuation() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -281,6 +362,70 @@ mod linear_mode_integration {
         assert!(history.is_ok());
         assert_eq!(history.unwrap().len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_linear_mode_thread_binding() {
+        use wiremock::matchers::body_partial_json;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({"threadId": "thread-123"})))
+            .respond_with(mock_linear_response())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(mock_linear_response())
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let mode = LinearMode::new(storage.clone(), langbase, &config);
+
+        // Binding stores the thread the first run started
+        let first = mode
+            .process(LinearParams::new("First thought").with_thread(true))
+            .await
+            .unwrap();
+        let session = storage
+            .get_session(&first.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.thread_id.as_deref(), Some("thread-123"));
+
+        // Later steps continue the thread with only the new thought
+        mode.process(LinearParams::new("Second thought").with_session(&first.session_id))
+            .await
+            .unwrap();
+        let requests = mock_server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body["threadId"], "thread-123");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+
+        // Unbinding falls back to sending the full history
+        mode.process(
+            LinearParams::new("Third thought")
+                .with_session(&first.session_id)
+                .with_thread(false),
+        )
+        .await
+        .unwrap();
+        let session = storage
+            .get_session(&first.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.thread_id.is_none());
+    }
 }
 
 #[cfg(test)]
@@ -661,6 +806,158 @@ mod error_handling_integration {
         // Just verify it doesn't panic - behavior depends on implementation
         let _ = result;
     }
+
+    #[tokio::test]
+    async fn test_failing_pipe_circuit_opens() {
+        use mcp_langbase_reasoning::server::{handle_tool_call, AppState};
+        use std::sync::Arc;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        // Two failures open the circuit; the third call never reaches the server
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = create_test_config(&mock_server.uri(), db_path.clone());
+        config.circuit_breaker = PipeCircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown_secs: 300,
+        };
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage, langbase));
+
+        for _ in 0..3 {
+            let args = json!({"content": "This should fail"});
+            assert!(handle_tool_call(&state, "reasoning_linear", Some(args))
+                .await
+                .is_err());
+        }
+
+        let status = handle_tool_call(&state, "reasoning_pipe_status", None)
+            .await
+            .unwrap();
+        assert_eq!(status["circuit_breaker"]["enabled"], true);
+        assert_eq!(status["open_circuits"], 1);
+        assert_eq!(status["pipes"][0]["pipe"], "linear-reasoning-v1");
+        assert_eq!(status["pipes"][0]["state"], "open");
+        assert_eq!(status["pipes"][0]["total_failures"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_pipe_health_reports_schema_violations() {
+        use mcp_langbase_reasoning::server::{handle_tool_call, AppState};
+        use std::sync::Arc;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        // Both pipes answer, but the tree pipe's answer lacks its branches
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(mock_linear_response())
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage, langbase));
+
+        let args = json!({"pipes": ["linear-reasoning-v1", "tree-reasoning-v1"]});
+        let report = handle_tool_call(&state, "reasoning_pipe_health", Some(args))
+            .await
+            .unwrap();
+
+        assert_eq!(report["healthy"], 1);
+        assert_eq!(report["unhealthy"], 1);
+        assert_eq!(report["pipes"][0]["pipe"], "linear-reasoning-v1");
+        assert_eq!(report["pipes"][0]["reachable"], true);
+        assert_eq!(report["pipes"][1]["pipe"], "tree-reasoning-v1");
+        assert_eq!(report["pipes"][1]["reachable"], true);
+        assert_eq!(report["pipes"][1]["schema_compliant"], false);
+        assert!(report["pipes"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("$.branches"));
+    }
+
+    #[tokio::test]
+    async fn test_budget_refuses_calls_once_used_up() {
+        use mcp_langbase_reasoning::server::{handle_tool_call, AppState};
+        use std::sync::Arc;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": r#"{"thought": "Step", "confidence": 0.8}"#,
+                "threadId": null,
+                "raw": {
+                    "model": "test",
+                    "usage": {"prompt_tokens": 400, "completion_tokens": 200, "total_tokens": 600}
+                }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = create_test_config(&mock_server.uri(), db_path.clone());
+        config.budget.session_tokens = Some(500);
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage, langbase));
+
+        let first = handle_tool_call(
+            &state,
+            "reasoning_linear",
+            Some(json!({"content": "Start"})),
+        )
+        .await
+        .unwrap();
+        let session_id = first["session_id"].as_str().unwrap().to_string();
+
+        // The first call started the session, so only the daily total saw it
+        let status = handle_tool_call(&state, "reasoning_budget_status", None)
+            .await
+            .unwrap();
+        assert_eq!(status["enabled"], true);
+        assert_eq!(status["daily"]["tokens_used"], 600);
+
+        // Charge the session directly, then the next call is refused
+        state.budget.record(Some(&session_id), 300, 200);
+        let err = handle_tool_call(
+            &state,
+            "reasoning_linear",
+            Some(json!({"content": "Continue", "session_id": session_id})),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Budget exceeded: session"));
+
+        let status = handle_tool_call(
+            &state,
+            "reasoning_budget_status",
+            Some(json!({"session_id": session_id})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status["session"]["tokens_used"], 500);
+        assert_eq!(status["session"]["tokens_remaining"], 0);
+        assert_eq!(status["session"]["exceeded"], true);
+    }
 }
 
 #[cfg(test)]
@@ -1111,3 +1408,324 @@ mod detection_error_handling {
         assert!(response.is_err(), "Should return error on Langbase failure");
     }
 }
+
+#[cfg(test)]
+mod preset_workflow_integration {
+    use super::*;
+    use mcp_langbase_reasoning::server::{handle_tool_call, AppState};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_preset_runs_as_langbase_workflow() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        // One round trip for the whole preset, and no per-step pipe runs
+        Mock::given(method("POST"))
+            .and(path("/v1/workflows/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "run-1",
+                "steps": [
+                    {"id": "divergent_analysis", "completion": "{\"thought\": \"Three views\", \"confidence\": 0.7}"},
+                    {"id": "bias_check", "completion": "No biases found"},
+                    {"id": "fallacy_check", "error": "model overloaded"},
+                    {"id": "reflect", "completion": "{\"analysis\": \"Solid code\", \"confidence\": 0.9}"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage.clone(), langbase));
+
+        let args = json!({
+            "preset_id": "code-review",
+            "inputs": {"code": "fn main() {}"},
+            "session_id": "preset-session",
+            "execution": "langbase_workflow"
+        });
+        let result = handle_tool_call(&state, "reasoning_preset_run", Some(args))
+            .await
+            .unwrap();
+
+        assert_eq!(result["workflow_run_id"], "run-1");
+        // The failed fallacy check is optional, so the run is partial
+        assert_eq!(result["status"], "partial");
+        assert_eq!(result["steps_completed"], 3);
+        assert_eq!(result["step_results"][2]["error"], "model overloaded");
+        assert_eq!(result["final_output"]["output"]["analysis"], "Solid code");
+
+        let thoughts = storage
+            .get_session_thoughts("preset-session")
+            .await
+            .unwrap();
+        assert_eq!(thoughts.len(), 3);
+        let reflection = thoughts.iter().find(|t| t.mode == "reflection").unwrap();
+        assert_eq!(reflection.content, "Solid code");
+        assert!((reflection.confidence - 0.9).abs() < f64::EPSILON);
+    }
+}
+
+#[cfg(test)]
+mod cancellation_integration {
+    use super::*;
+    use mcp_langbase_reasoning::langbase::with_cancellation;
+    use mcp_langbase_reasoning::storage::MetricsFilter;
+    use std::time::{Duration, Instant};
+    use tokio_util::sync::CancellationToken;
+
+    fn slow_linear_response() -> ResponseTemplate {
+        mock_linear_response().set_delay(Duration::from_secs(3))
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_pipe_call_is_logged_as_cancelled() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(slow_linear_response())
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let mode = LinearMode::new(storage.clone(), langbase, &config);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let start = Instant::now();
+        let result =
+            with_cancellation(token, mode.process(LinearParams::new("Slow thought"))).await;
+        assert!(result.is_err(), "Cancelled call should fail");
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let invocations = storage.get_invocations(MetricsFilter::new()).await.unwrap();
+        assert_eq!(invocations.len(), 1);
+        assert!(!invocations[0].success);
+        assert_eq!(
+            invocations[0].error.as_deref(),
+            Some("Request cancelled by client")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_notification_stops_tool_call() {
+        use futures::{SinkExt, StreamExt};
+        use mcp_langbase_reasoning::server::{AppState, McpServer};
+        use std::sync::Arc;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(slow_linear_response())
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage, langbase));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = McpServer::new(state);
+        tokio::spawn(async move { server.serve_ws(listener).await });
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        let send = |message: serde_json::Value| Message::Text(message.to_string());
+
+        let start = Instant::now();
+        socket
+            .send(send(json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": {"name": "reasoning_linear", "arguments": {"content": "Slow thought"}}
+            })))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        socket
+            .send(send(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/cancelled",
+                "params": {"requestId": 7, "reason": "User aborted"}
+            })))
+            .await
+            .unwrap();
+        socket
+            .send(send(json!({"jsonrpc": "2.0", "id": 8, "method": "ping"})))
+            .await
+            .unwrap();
+
+        // The cancelled request gets no response; the ping is answered once
+        // the tool call has stopped
+        let response = loop {
+            match socket.next().await.unwrap().unwrap() {
+                Message::Text(text) => {
+                    break serde_json::from_str::<serde_json::Value>(&text).unwrap()
+                }
+                _ => continue,
+            }
+        };
+        assert_eq!(response["id"], 8);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
+
+#[cfg(test)]
+mod sampling_integration {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::config::ProviderKind;
+    use mcp_langbase_reasoning::server::{AppState, McpServer};
+    use std::sync::Arc;
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    async fn connect(config: Config, db_path: std::path::PathBuf) -> Socket {
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let state = Arc::new(AppState::new(config, storage, langbase));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = McpServer::new(state);
+        tokio::spawn(async move { server.serve_ws(listener).await });
+
+        let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        socket
+    }
+
+    async fn send(socket: &mut Socket, message: serde_json::Value) {
+        socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+    }
+
+    async fn receive(socket: &mut Socket) -> serde_json::Value {
+        loop {
+            match socket.next().await.unwrap().unwrap() {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    fn sampling_config(db_path: std::path::PathBuf) -> Config {
+        // No Langbase server is needed: every pipe goes to the client
+        let mut config = create_test_config("http://127.0.0.1:9", db_path);
+        config.providers.default = ProviderKind::Sampling;
+        config
+    }
+
+    fn linear_call(id: u64) -> serde_json::Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "reasoning_linear", "arguments": {"content": "Sampled thought"}}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_samples_from_client() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mut socket = connect(sampling_config(db_path.clone()), db_path).await;
+
+        send(
+            &mut socket,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {"capabilities": {"sampling": {}}, "clientInfo": {"name": "test"}}
+            }),
+        )
+        .await;
+        assert_eq!(receive(&mut socket).await["id"], 1);
+
+        send(&mut socket, linear_call(2)).await;
+        let request = receive(&mut socket).await;
+        assert_eq!(request["method"], "sampling/createMessage");
+        assert_eq!(request["params"]["messages"][0]["role"], "user");
+        assert!(request["params"]["systemPrompt"].is_string());
+
+        send(
+            &mut socket,
+            json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": {
+                    "role": "assistant",
+                    "content": {
+                        "type": "text",
+                        "text": r#"{"thought": "Sampled result", "confidence": 0.8, "metadata": {}}"#
+                    },
+                    "model": "client-model",
+                    "stopReason": "endTurn"
+                }
+            }),
+        )
+        .await;
+
+        let response = receive(&mut socket).await;
+        assert_eq!(response["id"], 2);
+        assert!(response["result"]["isError"].is_null(), "{}", response);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("Sampled result"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_without_client_sampling_fails() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mut socket = connect(sampling_config(db_path.clone()), db_path).await;
+
+        send(
+            &mut socket,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        )
+        .await;
+        assert_eq!(receive(&mut socket).await["id"], 1);
+
+        send(&mut socket, linear_call(2)).await;
+        let response = receive(&mut socket).await;
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("sampling"), "{}", text);
+    }
+}
@@ -4,11 +4,11 @@
 
 use serde_json::json;
 use wiremock::{
-    matchers::{header, method, path},
+    matchers::{body_string_contains, header, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
-use mcp_langbase_reasoning::config::{LangbaseConfig, RequestConfig};
+use mcp_langbase_reasoning::config::{LangbaseConfig, NetworkConfig, RequestConfig};
 use mcp_langbase_reasoning::langbase::{LangbaseClient, Message, PipeRequest};
 
 /// Create a test client pointing to mock server
@@ -16,12 +16,15 @@ fn create_test_client(base_url: &str) -> LangbaseClient {
     let config = LangbaseConfig {
         api_key: "test-api-key".to_string(),
         base_url: base_url.to_string(),
+        network: NetworkConfig::default(),
     };
 
     let request_config = RequestConfig {
         timeout_ms: 5000,
         max_retries: 0, // No retries for testing
         retry_delay_ms: 100,
+        max_retry_delay_ms: 30000,
+        retry_jitter: false,
     };
 
     LangbaseClient::new(&config, request_config).expect("Failed to create client")
@@ -249,6 +252,39 @@ mod request_format_tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_request_with_generation_params() {
+        use mcp_langbase_reasoning::langbase::GenerationParams;
+        use wiremock::matchers::body_partial_json;
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({
+                "name": "test-pipe",
+                "temperature": 0.0,
+                "max_tokens": 512
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "Deterministic"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let request = create_test_request("Test").with_generation(GenerationParams {
+            temperature: Some(0.0),
+            max_tokens: Some(512),
+            ..Default::default()
+        });
+        let result = client.call_pipe(request).await;
+
+        assert!(result.is_ok());
+    }
 }
 
 #[cfg(test)]
@@ -379,11 +415,14 @@ mod timeout_tests {
         let config = LangbaseConfig {
             api_key: "test-api-key".to_string(),
             base_url: mock_server.uri(),
+            network: NetworkConfig::default(),
         };
         let request_config = RequestConfig {
             timeout_ms: 100, // 100ms timeout
             max_retries: 0,
             retry_delay_ms: 100,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
         let client = LangbaseClient::new(&config, request_config).unwrap();
 
@@ -416,11 +455,14 @@ mod retry_tests {
         let config = LangbaseConfig {
             api_key: "test-api-key".to_string(),
             base_url: mock_server.uri(),
+            network: NetworkConfig::default(),
         };
         let request_config = RequestConfig {
             timeout_ms: 5000,
             max_retries: 0, // No retries
             retry_delay_ms: 10,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         };
         let client = LangbaseClient::new(&config, request_config).unwrap();
 
@@ -429,70 +471,1204 @@ mod retry_tests {
 
         assert!(result.is_err());
     }
+
+    /// Client that retries up to three times with short delays
+    fn retrying_client(base_url: &str) -> LangbaseClient {
+        let config = LangbaseConfig {
+            api_key: "test-api-key".to_string(),
+            base_url: base_url.to_string(),
+            network: NetworkConfig::default(),
+        };
+        let request_config = RequestConfig {
+            timeout_ms: 5000,
+            max_retries: 3,
+            retry_delay_ms: 10,
+            max_retry_delay_ms: 1000,
+            retry_jitter: true,
+        };
+        LangbaseClient::new(&config, request_config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_is_not_retried() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {"message": "Invalid request"}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = retrying_client(&mock_server.uri());
+        let result = client.call_pipe(create_test_request("Test")).await;
+
+        assert!(result.unwrap_err().to_string().contains("400"));
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_retried_until_success() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "recovered"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = retrying_client(&mock_server.uri());
+        let response = client.call_pipe(create_test_request("Test")).await.unwrap();
+
+        assert_eq!(response.completion, "recovered");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_honors_retry_after() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "after wait"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = retrying_client(&mock_server.uri());
+        let response = client.call_pipe(create_test_request("Test")).await.unwrap();
+
+        assert_eq!(response.completion, "after wait");
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_beyond_cap_gives_up() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "60"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = retrying_client(&mock_server.uri());
+        let result = client.call_pipe(create_test_request("Test")).await;
+
+        assert!(result.unwrap_err().to_string().contains("Rate limited"));
+    }
 }
 
 #[cfg(test)]
-mod message_tests {
+mod embed_tests {
     use super::*;
+    use wiremock::matchers::body_json;
 
-    #[test]
-    fn test_message_creation() {
-        let system = Message::system("You are a helper");
-        let user = Message::user("Hello");
-        let assistant = Message::assistant("Hi there");
+    #[tokio::test]
+    async fn test_embed_returns_vectors_in_order() {
+        let mock_server = MockServer::start().await;
 
-        assert_eq!(system.content, "You are a helper");
-        assert_eq!(user.content, "Hello");
-        assert_eq!(assistant.content, "Hi there");
+        Mock::given(method("POST"))
+            .and(path("/v1/embed"))
+            .and(header("Authorization", "Bearer test-api-key"))
+            .and(body_json(json!({
+                "chunks": ["first", "second"],
+                "embeddingModel": "openai:text-embedding-3-large"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([[0.1, 0.2], [0.3, 0.4]])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let chunks = vec!["first".to_string(), "second".to_string()];
+        let vectors = client
+            .embed(&chunks, "openai:text-embedding-3-large")
+            .await
+            .unwrap();
+
+        assert_eq!(vectors, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
     }
 
-    #[test]
-    fn test_pipe_request_builder() {
-        let request = PipeRequest::new("my-pipe", vec![Message::user("Test")])
-            .with_thread_id("thread-1")
-            .with_variable("key", "value");
+    #[tokio::test]
+    async fn test_embed_rejects_mismatched_vector_count() {
+        let mock_server = MockServer::start().await;
 
-        assert_eq!(request.name, "my-pipe");
-        assert_eq!(request.thread_id, Some("thread-1".to_string()));
-        assert!(request.variables.is_some());
-        assert_eq!(
-            request.variables.unwrap().get("key"),
-            Some(&"value".to_string())
-        );
+        Mock::given(method("POST"))
+            .and(path("/v1/embed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([[0.1, 0.2]])))
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let chunks = vec!["first".to_string(), "second".to_string()];
+        let result = client.embed(&chunks, "openai:text-embedding-3-large").await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embed_api_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/embed"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("Unauthorized"))
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let result = client.embed(&["text".to_string()], "model").await;
+
+        assert!(result.is_err());
     }
 }
 
 #[cfg(test)]
-mod reasoning_response_tests {
-    use mcp_langbase_reasoning::langbase::ReasoningResponse;
+mod proxy_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::NetworkConfig;
 
-    #[test]
-    fn test_parse_valid_json_response() {
-        let json =
-            r#"{"thought": "This is analysis", "confidence": 0.85, "metadata": {"key": "value"}}"#;
-        let response = ReasoningResponse::from_completion(json);
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        // The mock server stands in for the proxy; the base URL is unresolvable
+        let proxy = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "via proxy"
+            })))
+            .expect(1)
+            .mount(&proxy)
+            .await;
 
-        assert_eq!(response.thought, "This is analysis");
-        assert!((response.confidence - 0.85).abs() < 0.001);
-        assert!(response.metadata.is_some());
+        let config = LangbaseConfig {
+            api_key: "test-api-key".to_string(),
+            base_url: "http://langbase.invalid".to_string(),
+            network: NetworkConfig {
+                proxy: Some(proxy.uri()),
+                ..Default::default()
+            },
+        };
+        let request_config = RequestConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let client = LangbaseClient::new(&config, request_config).unwrap();
+
+        let response = client.call_pipe(create_test_request("hi")).await.unwrap();
+        assert_eq!(response.completion, "via proxy");
     }
+}
 
-    #[test]
-    fn test_parse_plain_text_response() {
-        let text = "This is plain text reasoning output.";
-        let response = ReasoningResponse::from_completion(text);
+#[cfg(test)]
+mod thread_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::LangbaseError;
+    use mcp_langbase_reasoning::langbase::{CreateThreadRequest, MessageRole};
+    use wiremock::matchers::body_json;
+
+    fn thread_message(id: &str, role: &str, content: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "thread_id": "thread-1",
+            "role": role,
+            "content": content,
+            "created_at": 1714000000
+        })
+    }
 
-        assert_eq!(response.thought, text);
-        assert!((response.confidence - 0.8).abs() < 0.001); // Default confidence
-        assert!(response.metadata.is_none());
+    #[tokio::test]
+    async fn test_create_thread() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/threads"))
+            .and(header("Authorization", "Bearer test-api-key"))
+            .and(body_json(json!({
+                "messages": [{"role": "system", "content": "Reason step by step"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "thread-1",
+                "object": "thread",
+                "created_at": 1714000000,
+                "metadata": {}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let thread = client
+            .create_thread(CreateThreadRequest::new(vec![Message::system(
+                "Reason step by step",
+            )]))
+            .await
+            .unwrap();
+
+        assert_eq!(thread.id, "thread-1");
+        assert_eq!(thread.created_at, 1714000000);
     }
 
-    #[test]
-    fn test_parse_minimal_json_response() {
-        let json = r#"{"thought": "Minimal", "confidence": 0.5}"#;
-        let response = ReasoningResponse::from_completion(json);
+    #[tokio::test]
+    async fn test_append_and_list_thread_messages() {
+        let mock_server = MockServer::start().await;
 
-        assert_eq!(response.thought, "Minimal");
-        assert!((response.confidence - 0.5).abs() < 0.001);
+        Mock::given(method("POST"))
+            .and(path("/v1/threads/thread-1/messages"))
+            .and(body_json(json!([{"role": "user", "content": "Next step"}])))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([thread_message(
+                    "msg-2",
+                    "user",
+                    "Next step"
+                )])),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/threads/thread-1/messages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                thread_message("msg-1", "system", "Reason step by step"),
+                thread_message("msg-2", "user", "Next step"),
+            ])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let appended = client
+            .append_thread_messages("thread-1", &[Message::user("Next step")])
+            .await
+            .unwrap();
+        assert_eq!(appended.len(), 1);
+        assert_eq!(appended[0].id, "msg-2");
+
+        let messages = client.list_thread_messages("thread-1").await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[0].role, MessageRole::System));
+        assert_eq!(messages[1].content.as_deref(), Some("Next step"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_thread_is_an_api_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/v1/threads/missing/messages"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Thread not found"))
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let err = client.list_thread_messages("missing").await.unwrap_err();
+
+        assert!(matches!(err, LangbaseError::Api { status: 404, .. }));
+    }
+}
+
+#[cfg(test)]
+mod sync_tests {
+    use super::*;
+    use mcp_langbase_reasoning::langbase::{sync_pipes, SyncAction};
+    use mcp_langbase_reasoning::prompts::{PipeDefinition, AUTO_ROUTER_PIPE, LINEAR_PIPE};
+    use wiremock::matchers::body_partial_json;
+
+    fn remote_pipe(def: &PipeDefinition, temperature: f64) -> serde_json::Value {
+        json!({
+            "name": def.name,
+            "description": def.description,
+            "status": "private",
+            "owner_login": "acme",
+            "model": def.model,
+            "temperature": temperature,
+            "max_tokens": def.max_tokens,
+            "json": true,
+            "messages": [{"role": "system", "content": def.system_prompt.unwrap_or("")}]
+        })
+    }
+
+    async fn mount_pipe_list(mock_server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/v1/pipes"))
+            .and(header("Authorization", "Bearer test-api-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                remote_pipe(&LINEAR_PIPE, 0.2),
+                remote_pipe(&AUTO_ROUTER_PIPE, AUTO_ROUTER_PIPE.temperature)
+            ])))
+            .mount(mock_server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_dry_run_writes_nothing() {
+        let mock_server = MockServer::start().await;
+        mount_pipe_list(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let plan = sync_pipes(&client, &[LINEAR_PIPE], true).await.unwrap();
+
+        assert_eq!(plan[0].action, SyncAction::Update);
+        assert_eq!(plan[0].changes, vec!["temperature: 0.2 -> 0.7".to_string()]);
+        assert!(plan[0].prompt_diff.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_upserts_changed_pipes_only() {
+        let mock_server = MockServer::start().await;
+        mount_pipe_list(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes"))
+            .and(body_partial_json(json!({
+                "name": "linear-reasoning-v1",
+                "upsert": true,
+                "temperature": 0.7
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "linear-reasoning-v1",
+                "description": LINEAR_PIPE.description,
+                "status": "private",
+                "owner_login": "acme",
+                "url": "https://langbase.com/acme/linear-reasoning-v1",
+                "type": "chat",
+                "api_key": "pipe-key"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let plan = sync_pipes(&client, &[LINEAR_PIPE, AUTO_ROUTER_PIPE], false)
+            .await
+            .unwrap();
+
+        assert_eq!(plan[0].action, SyncAction::Update);
+        assert_eq!(plan[1].action, SyncAction::Unchanged);
+    }
+}
+
+#[cfg(test)]
+mod schema_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::LangbaseError;
+    use mcp_langbase_reasoning::langbase::OutputSchema;
+    use wiremock::matchers::body_partial_json;
+
+    fn schema() -> OutputSchema {
+        OutputSchema::new(
+            "step",
+            json!({
+                "type": "object",
+                "required": ["thought", "confidence"],
+                "properties": {
+                    "thought": {"type": "string"},
+                    "confidence": {"type": "number", "minimum": 0, "maximum": 1}
+                }
+            }),
+        )
+    }
+
+    async fn mount_completion(server: &MockServer, completion: &str) {
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {"name": "step", "strict": true}
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": completion
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_conforming_completion_is_returned() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, r#"{"thought": "ok", "confidence": 0.9}"#).await;
+
+        let client = create_test_client(&mock_server.uri());
+        let response = client
+            .call_pipe(create_test_request("Test").with_output_schema(schema()))
+            .await
+            .unwrap();
+
+        assert!(response.completion.contains("\"ok\""));
+    }
+
+    #[tokio::test]
+    async fn test_violation_names_the_field() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, r#"{"thought": "ok", "confidence": "high"}"#).await;
+
+        let client = create_test_client(&mock_server.uri());
+        let err = client
+            .call_pipe(create_test_request("Test").with_output_schema(schema()))
+            .await
+            .unwrap_err();
+
+        match err {
+            LangbaseError::SchemaViolation {
+                pipe, schema, path, ..
+            } => {
+                assert_eq!(pipe, "test-pipe");
+                assert_eq!(schema, "step");
+                assert_eq!(path, "$.confidence");
+            }
+            other => panic!("expected schema violation, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+    use mcp_langbase_reasoning::langbase::{with_stream_sink, StreamSink};
+    use std::sync::{Arc, Mutex};
+    use wiremock::matchers::body_partial_json;
+
+    fn sse_body(fragments: &[&str]) -> String {
+        let mut body = String::new();
+        for fragment in fragments {
+            let chunk = json!({
+                "object": "chat.completion.chunk",
+                "model": "gpt-4o-mini",
+                "choices": [{"index": 0, "delta": {"content": fragment}, "finish_reason": null}]
+            });
+            body.push_str(&format!("data: {}\n\n", chunk));
+        }
+        body.push_str("data: [DONE]\n\n");
+        body
+    }
+
+    #[tokio::test]
+    async fn test_streamed_pipe_call_forwards_fragments() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({"stream": true})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("lb-thread-id", "thread-stream")
+                    .set_body_raw(sse_body(&["Step one. ", "Step two."]), "text/event-stream"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink: StreamSink = {
+            let received = received.clone();
+            Arc::new(move |text: &str| received.lock().unwrap().push(text.to_string()))
+        };
+
+        let response = with_stream_sink(sink, client.call_pipe(create_test_request("Go")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.completion, "Step one. Step two.");
+        assert_eq!(response.thread_id.as_deref(), Some("thread-stream"));
+        assert_eq!(
+            response.raw.and_then(|r| r.model).as_deref(),
+            Some("gpt-4o-mini")
+        );
+        assert_eq!(*received.lock().unwrap(), vec!["Step one. ", "Step two."]);
+    }
+
+    #[tokio::test]
+    async fn test_pipe_call_without_sink_is_not_streamed() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({"stream": false})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "Buffered"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let response = client.call_pipe(create_test_request("Go")).await.unwrap();
+
+        assert_eq!(response.completion, "Buffered");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_pipe_call_rejects_malformed_chunk() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("data: {not json\n\n", "text/event-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let sink: StreamSink = Arc::new(|_| {});
+        let result = with_stream_sink(sink, client.call_pipe(create_test_request("Go"))).await;
+
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::PipeCacheConfig;
+    use mcp_langbase_reasoning::langbase::PipeCache;
+    use mcp_langbase_reasoning::storage::{SharedStorage, SqliteStorage};
+    use std::sync::Arc;
+
+    fn cache_config() -> PipeCacheConfig {
+        PipeCacheConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    async fn mount_completion(server: &MockServer, expected_calls: u64) {
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "cached completion"
+            })))
+            .expect(expected_calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_repeated_request_served_from_cache() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, 1).await;
+
+        let client = create_test_client(&mock_server.uri())
+            .with_cache(Arc::new(PipeCache::new(cache_config())));
+
+        let first = client.call_pipe(create_test_request("same")).await.unwrap();
+        let second = client.call_pipe(create_test_request("same")).await.unwrap();
+
+        assert!(!first.cache_hit);
+        assert!(second.cache_hit);
+        assert_eq!(second.completion, "cached completion");
+    }
+
+    #[tokio::test]
+    async fn test_different_prompts_are_not_shared() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, 2).await;
+
+        let client = create_test_client(&mock_server.uri())
+            .with_cache(Arc::new(PipeCache::new(cache_config())));
+
+        client.call_pipe(create_test_request("one")).await.unwrap();
+        let second = client.call_pipe(create_test_request("two")).await.unwrap();
+        assert!(!second.cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_pipes_outside_allow_list_bypass_cache() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, 2).await;
+
+        let config = PipeCacheConfig {
+            pipes: vec!["detection-v1".to_string()],
+            ..cache_config()
+        };
+        let client =
+            create_test_client(&mock_server.uri()).with_cache(Arc::new(PipeCache::new(config)));
+
+        client.call_pipe(create_test_request("same")).await.unwrap();
+        let second = client.call_pipe(create_test_request("same")).await.unwrap();
+        assert!(!second.cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_persisted_cache_survives_new_client() {
+        let mock_server = MockServer::start().await;
+        mount_completion(&mock_server, 1).await;
+
+        let storage: SharedStorage = Arc::new(SqliteStorage::new_in_memory().await.unwrap());
+        let new_client = || {
+            let cache = PipeCache::new(cache_config()).with_storage(storage.clone());
+            create_test_client(&mock_server.uri()).with_cache(Arc::new(cache))
+        };
+
+        new_client()
+            .call_pipe(create_test_request("same"))
+            .await
+            .unwrap();
+        let response = new_client()
+            .call_pipe(create_test_request("same"))
+            .await
+            .unwrap();
+        assert!(response.cache_hit);
+    }
+}
+
+#[cfg(test)]
+mod circuit_breaker_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::PipeCircuitBreakerConfig;
+    use mcp_langbase_reasoning::error::LangbaseError;
+    use mcp_langbase_reasoning::langbase::PipeCircuitBreakers;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_open_circuit_short_circuits_calls() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let breakers = Arc::new(PipeCircuitBreakers::new(&PipeCircuitBreakerConfig {
+            failure_threshold: 3,
+            cooldown_secs: 300,
+        }));
+        let client = create_test_client(&mock_server.uri()).with_circuit_breakers(breakers);
+
+        for _ in 0..3 {
+            assert!(client.call_pipe(create_test_request("x")).await.is_err());
+        }
+
+        let err = client
+            .call_pipe(create_test_request("x"))
+            .await
+            .unwrap_err();
+        match err {
+            LangbaseError::Unavailable { message, retries } => {
+                assert!(message.contains("Circuit open for pipe 'test-pipe'"));
+                assert_eq!(retries, 0);
+            }
+            other => panic!("expected Unavailable, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trial_call_after_cooldown_closes_circuit() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "recovered"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let breakers = Arc::new(PipeCircuitBreakers::new(&PipeCircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown_secs: 0,
+        }));
+        let client = create_test_client(&mock_server.uri()).with_circuit_breakers(breakers.clone());
+
+        assert!(client.call_pipe(create_test_request("x")).await.is_err());
+        let response = client.call_pipe(create_test_request("x")).await.unwrap();
+        assert_eq!(response.completion, "recovered");
+        assert_eq!(breakers.status()[0].consecutive_failures, 0);
+    }
+}
+
+#[cfg(test)]
+mod rate_limit_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::{RateLimit, RateLimitConfig};
+    use mcp_langbase_reasoning::langbase::RateLimiter;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_throttled_requests_wait_instead_of_failing() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "ok"
+            })))
+            .expect(11)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = RateLimitConfig::default();
+        config.pipes.insert(
+            "test-pipe".to_string(),
+            RateLimit {
+                requests: 10,
+                period_secs: 1,
+            },
+        );
+        let client = Arc::new(
+            create_test_client(&mock_server.uri())
+                .with_rate_limiter(Arc::new(RateLimiter::new(&config))),
+        );
+
+        // Drain the burst so the next calls have to queue
+        for _ in 0..8 {
+            client.call_pipe(create_test_request("warm")).await.unwrap();
+        }
+
+        let start = Instant::now();
+        let calls: Vec<_> = (0..3)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .call_pipe(create_test_request(&format!("call {}", i)))
+                        .await
+                })
+            })
+            .collect();
+        for call in calls {
+            assert!(call.await.unwrap().is_ok());
+        }
+
+        // 2 tokens left, the third call waits ~100ms for a refill
+        assert!(start.elapsed() >= Duration::from_millis(80));
+    }
+}
+
+#[cfg(test)]
+mod concurrency_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::ConcurrencyConfig;
+    use mcp_langbase_reasoning::langbase::ConcurrencyLimiter;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_requests_over_limit_are_queued() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "success": true,
+                        "completion": "ok"
+                    }))
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(4)
+            .mount(&mock_server)
+            .await;
+
+        let config = ConcurrencyConfig {
+            max_concurrent_requests: 2,
+            ..Default::default()
+        };
+        let limiter = Arc::new(ConcurrencyLimiter::new(&config));
+        let client = Arc::new(
+            create_test_client(&mock_server.uri()).with_concurrency_limiter(limiter.clone()),
+        );
+
+        let start = Instant::now();
+        let calls: Vec<_> = (0..4)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .call_pipe(create_test_request(&format!("call {}", i)))
+                        .await
+                })
+            })
+            .collect();
+        for call in calls {
+            assert!(call.await.unwrap().is_ok());
+        }
+
+        // Two at a time: the second pair starts after the first finishes
+        assert!(start.elapsed() >= Duration::from_millis(190));
+        assert_eq!(limiter.available("test-pipe"), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_call_pipe_parallel_collects_responses_and_errors() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_string_contains("call 2"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "success": true,
+                        "completion": "ok"
+                    }))
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let requests = (0..4)
+            .map(|i| create_test_request(&format!("call {}", i)))
+            .collect();
+
+        let start = Instant::now();
+        let result = client.call_pipe_parallel(requests, 0).await;
+
+        // All calls ran at once rather than one after another
+        assert!(start.elapsed() < Duration::from_millis(300));
+        assert!(!result.is_complete());
+        let indices: Vec<usize> = result.responses.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![0, 1, 3]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, 2);
+    }
+}
+
+#[cfg(test)]
+mod message_tests {
+    use super::*;
+
+    #[test]
+    fn test_message_creation() {
+        let system = Message::system("You are a helper");
+        let user = Message::user("Hello");
+        let assistant = Message::assistant("Hi there");
+
+        assert_eq!(system.content, "You are a helper");
+        assert_eq!(user.content, "Hello");
+        assert_eq!(assistant.content, "Hi there");
+    }
+
+    #[test]
+    fn test_pipe_request_builder() {
+        let request = PipeRequest::new("my-pipe", vec![Message::user("Test")])
+            .with_thread_id("thread-1")
+            .with_variable("key", "value");
+
+        assert_eq!(request.name, "my-pipe");
+        assert_eq!(request.thread_id, Some("thread-1".to_string()));
+        assert!(request.variables.is_some());
+        assert_eq!(
+            request.variables.unwrap().get("key"),
+            Some(&"value".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod reasoning_response_tests {
+    use mcp_langbase_reasoning::langbase::ReasoningResponse;
+
+    #[test]
+    fn test_parse_valid_json_response() {
+        let json =
+            r#"{"thought": "This is analysis", "confidence": 0.85, "metadata": {"key": "value"}}"#;
+        let response = ReasoningResponse::from_completion(json);
+
+        assert_eq!(response.thought, "This is analysis");
+        assert!((response.confidence - 0.85).abs() < 0.001);
+        assert!(response.metadata.is_some());
+    }
+
+    #[test]
+    fn test_parse_plain_text_response() {
+        let text = "This is plain text reasoning output.";
+        let response = ReasoningResponse::from_completion(text);
+
+        assert_eq!(response.thought, text);
+        assert!((response.confidence - 0.8).abs() < 0.001); // Default confidence
+        assert!(response.metadata.is_none());
+    }
+
+    #[test]
+    fn test_parse_minimal_json_response() {
+        let json = r#"{"thought": "Minimal", "confidence": 0.5}"#;
+        let response = ReasoningResponse::from_completion(json);
+
+        assert_eq!(response.thought, "Minimal");
+        assert!((response.confidence - 0.5).abs() < 0.001);
+    }
+}
+
+#[cfg(test)]
+mod payload_log_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::PayloadLogConfig;
+    use mcp_langbase_reasoning::langbase::{PayloadLogger, Redactor, REDACTED};
+    use mcp_langbase_reasoning::storage::{SharedStorage, SqliteStorage};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_payloads_are_stored_redacted() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "Reply sent to carol@example.com"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let storage: SharedStorage = Arc::new(SqliteStorage::new_in_memory().await.unwrap());
+        let redactor = Redactor::new(&PayloadLogConfig::default()).unwrap();
+        let logger = PayloadLogger::new(redactor, storage.clone());
+        let client = create_test_client(&mock_server.uri()).with_payload_logger(Arc::new(logger));
+
+        let request = create_test_request("Email carol@example.com about the outage")
+            .with_variable("api_key", "sk-abcdefghijklmnopqrstuvwxyz");
+        client.call_pipe(request).await.unwrap();
+
+        let payloads = storage
+            .get_pipe_payloads(Some("test-pipe"), 10)
+            .await
+            .unwrap();
+        assert_eq!(payloads.len(), 1);
+        let payload = &payloads[0];
+        assert_eq!(payload.attempt, 1);
+
+        let stored = format!("{} {}", payload.request, payload.response.as_ref().unwrap());
+        assert!(!stored.contains("carol@example.com"));
+        assert!(!stored.contains("sk-abcdefghijklmnopqrstuvwxyz"));
+        assert_eq!(payload.request["variables"]["api_key"], REDACTED);
+        assert_eq!(
+            payload.request["messages"][0]["content"],
+            "Email [REDACTED] about the outage"
+        );
+    }
+}
+
+#[cfg(test)]
+mod middleware_tests {
+    use super::*;
+    use async_trait::async_trait;
+    use mcp_langbase_reasoning::error::{LangbaseError, LangbaseResult};
+    use mcp_langbase_reasoning::langbase::{PipeAttempt, PipeMiddleware, PipeResponse};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use wiremock::matchers::body_string_contains;
+
+    /// Signs requests, filters completions and counts failures
+    #[derive(Default)]
+    struct TestMiddleware {
+        refuse: bool,
+        errors: AtomicU32,
+    }
+
+    #[async_trait]
+    impl PipeMiddleware for TestMiddleware {
+        async fn on_request(
+            &self,
+            request: &mut PipeRequest,
+            headers: &mut HeaderMap,
+            attempt: u32,
+        ) -> LangbaseResult<()> {
+            if self.refuse {
+                return Err(LangbaseError::InvalidResponse {
+                    message: "blocked".to_string(),
+                });
+            }
+            headers.insert("x-signature", HeaderValue::from_static("signed"));
+            request.messages[0]
+                .content
+                .push_str(&format!(" (attempt {})", attempt));
+            Ok(())
+        }
+
+        async fn on_response(
+            &self,
+            _request: &PipeRequest,
+            response: &mut PipeResponse,
+            _attempt: &PipeAttempt,
+        ) -> LangbaseResult<()> {
+            response.completion = response.completion.replace("secret", "[filtered]");
+            Ok(())
+        }
+
+        async fn on_error(
+            &self,
+            _request: &PipeRequest,
+            _error: &LangbaseError,
+            _attempt: &PipeAttempt,
+        ) {
+            self.errors.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_edits_requests_and_responses() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(header("x-signature", "signed"))
+            .and(body_string_contains("Hello (attempt 1)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": "the secret answer"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let middleware = Arc::new(TestMiddleware::default());
+        let client = create_test_client(&mock_server.uri()).with_middleware(middleware.clone());
+
+        let response = client
+            .call_pipe(create_test_request("Hello"))
+            .await
+            .unwrap();
+        assert_eq!(response.completion, "the [filtered] answer");
+        assert_eq!(middleware.errors.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_observes_errors_and_refuses_calls() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let middleware = Arc::new(TestMiddleware::default());
+        let client = create_test_client(&mock_server.uri()).with_middleware(middleware.clone());
+        assert!(client
+            .call_pipe(create_test_request("Hello"))
+            .await
+            .is_err());
+        assert_eq!(middleware.errors.load(Ordering::SeqCst), 1);
+
+        // A refusing middleware stops the call before anything is sent
+        let refusing = Arc::new(TestMiddleware {
+            refuse: true,
+            ..Default::default()
+        });
+        let client = create_test_client(&mock_server.uri()).with_middleware(refusing);
+        let err = client
+            .call_pipe(create_test_request("Hello"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LangbaseError::InvalidResponse { .. }));
+    }
+}
+
+#[cfg(test)]
+mod workflow_tests {
+    use super::*;
+    use mcp_langbase_reasoning::langbase::{WorkflowRunRequest, WorkflowStep};
+    use wiremock::matchers::body_json;
+
+    #[tokio::test]
+    async fn test_run_workflow() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/workflows/run"))
+            .and(header("Authorization", "Bearer test-api-key"))
+            .and(body_json(json!({
+                "name": "review",
+                "steps": [
+                    {
+                        "id": "draft",
+                        "pipe": "linear-reasoning-v1",
+                        "messages": [{"role": "user", "content": "Draft it"}]
+                    },
+                    {
+                        "id": "critique",
+                        "pipe": "reflection-v1",
+                        "messages": [{"role": "user", "content": "{{steps.draft.output}}"}],
+                        "dependsOn": ["draft"],
+                        "optional": true
+                    }
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "run-1",
+                "steps": [
+                    {"id": "draft", "completion": "A draft"},
+                    {"id": "critique", "error": "timeout"}
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = create_test_client(&mock_server.uri());
+        let request = WorkflowRunRequest {
+            name: "review".to_string(),
+            steps: vec![
+                WorkflowStep {
+                    id: "draft".to_string(),
+                    pipe: "linear-reasoning-v1".to_string(),
+                    messages: vec![Message::user("Draft it")],
+                    depends_on: vec![],
+                    optional: false,
+                },
+                WorkflowStep {
+                    id: "critique".to_string(),
+                    pipe: "reflection-v1".to_string(),
+                    messages: vec![Message::user(WorkflowStep::output_ref("draft"))],
+                    depends_on: vec!["draft".to_string()],
+                    optional: true,
+                },
+            ],
+        };
+        let response = client.run_workflow(&request).await.unwrap();
+
+        assert_eq!(response.id, "run-1");
+        assert_eq!(response.steps[0].completion.as_deref(), Some("A draft"));
+        assert!(response.steps[1].completion.is_none());
+        assert_eq!(response.steps[1].error.as_deref(), Some("timeout"));
     }
 }
@@ -2,21 +2,28 @@
 //!
 //! Tests tree, divergent, and reflection modes using mocked Langbase responses.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use serde_json::json;
 use tempfile::tempdir;
 use wiremock::{
-    matchers::{method, path},
+    matchers::{body_partial_json, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
 use mcp_langbase_reasoning::config::{
-    Config, DatabaseConfig, ErrorHandlingConfig, LangbaseConfig, LogFormat, LoggingConfig,
-    PipeConfig, RequestConfig,
+    BudgetConfig, ConcurrencyConfig, Config, DatabaseConfig, EmbeddingConfig, ErrorHandlingConfig,
+    FallbackTarget, LangbaseConfig, LogFormat, LoggingConfig, MaintenanceConfig, MctsConfig,
+    NetworkConfig, PayloadLogConfig, PipeCacheConfig, PipeCircuitBreakerConfig, PipeConfig,
+    ProviderConfig, ProviderKind, RateLimitConfig, RequestConfig, RetentionConfig, ToolsConfig,
+    TransportConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
 };
-use mcp_langbase_reasoning::langbase::LangbaseClient;
+use mcp_langbase_reasoning::langbase::{GenerationParams, LangbaseClient};
 use mcp_langbase_reasoning::modes::{
     DivergentMode, DivergentParams, ReflectionMode, ReflectionParams, TreeMode, TreeParams,
 };
+use mcp_langbase_reasoning::providers::{OfflineBackend, ProviderRouter, SharedBackend};
 use mcp_langbase_reasoning::storage::{Session, SqliteStorage, Storage, Thought};
 
 /// Create test configuration with mock server URL
@@ -25,10 +32,15 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
         langbase: LangbaseConfig {
             api_key: "test-api-key".to_string(),
             base_url: mock_url.to_string(),
+            network: NetworkConfig::default(),
         },
         database: DatabaseConfig {
             path: db_path,
             max_connections: 1,
+            encryption_key: None,
+            maintenance: MaintenanceConfig::default(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
         },
         logging: LoggingConfig {
             level: "debug".to_string(),
@@ -38,6 +50,8 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
             timeout_ms: 5000,
             max_retries: 0,
             retry_delay_ms: 100,
+            max_retry_delay_ms: 30000,
+            retry_jitter: false,
         },
         pipes: PipeConfig {
             linear: "linear-reasoning-v1".to_string(),
@@ -51,8 +65,24 @@ fn create_test_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
             detection: None,
             decision: None,
             evidence: None,
+            code: None,
+            versions: HashMap::new(),
+            schema_versions: HashMap::new(),
+            health_check_on_startup: false,
         },
         error_handling: ErrorHandlingConfig::default(),
+        retention: RetentionConfig::default(),
+        embeddings: EmbeddingConfig::default(),
+        providers: ProviderConfig::default(),
+        pipe_cache: PipeCacheConfig::default(),
+        rate_limits: RateLimitConfig::default(),
+        circuit_breaker: PipeCircuitBreakerConfig::default(),
+        concurrency: ConcurrencyConfig::default(),
+        payload_log: PayloadLogConfig::default(),
+        budget: BudgetConfig::default(),
+        transport: TransportConfig::default(),
+        tools: ToolsConfig::default(),
+        mcts: MctsConfig::default(),
     }
 }
 
@@ -61,6 +91,10 @@ async fn create_test_storage(db_path: std::path::PathBuf) -> SqliteStorage {
     let config = DatabaseConfig {
         path: db_path,
         max_connections: 1,
+        encryption_key: None,
+        maintenance: MaintenanceConfig::default(),
+        cache_capacity: DEFAULT_CACHE_CAPACITY,
+        workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
     };
     SqliteStorage::new(&config)
         .await
@@ -204,6 +238,49 @@ mod tree_mode_tests {
         assert!((result.child_branches[0].confidence - 0.85).abs() < 0.001);
     }
 
+    #[tokio::test]
+    async fn test_tree_mode_sends_registered_schema() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(json!({
+                "name": "tree-reasoning-v1",
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {"name": "tree_response"}
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": json!({
+                    "branches": [{"thought": "Only", "confidence": 1.5, "rationale": "x"}],
+                    "recommended_branch": 0
+                }).to_string()
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = create_test_config(&mock_server.uri(), db_path.clone());
+        config
+            .pipes
+            .schema_versions
+            .insert("tree-reasoning-v1".to_string(), 1);
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let tree_mode = TreeMode::new(storage, langbase, &config);
+
+        // Confidence above 1 breaks the schema before the mode parses it
+        let err = tree_mode
+            .process(TreeParams::new("Explore options"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("$.branches[0].confidence"));
+    }
+
     #[tokio::test]
     async fn test_tree_mode_focus_branch() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -338,6 +415,98 @@ mod divergent_mode_tests {
         assert!(err.contains("Content cannot be empty"));
     }
 
+    #[tokio::test]
+    async fn test_divergent_mode_forwards_generation_params() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .and(body_partial_json(
+                json!({"temperature": 1.4, "top_p": 0.95}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": json!({
+                    "perspectives": [
+                        {"thought": "Wild idea", "novelty": 0.9, "viability": 0.4},
+                        {"thought": "Wilder idea", "novelty": 0.95, "viability": 0.2}
+                    ],
+                    "synthesis": "Both push the boundaries"
+                }).to_string()
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let divergent_mode = DivergentMode::new(storage, langbase, &config);
+
+        let hot = GenerationParams {
+            temperature: Some(1.4),
+            top_p: Some(0.95),
+            max_tokens: None,
+        };
+        let result = divergent_mode
+            .process(DivergentParams::new("Brainstorm").with_generation(hot))
+            .await;
+        assert!(
+            result.is_ok(),
+            "Divergent processing failed: {:?}",
+            result.err()
+        );
+
+        // Out-of-range values fail before any pipe call
+        let too_hot = GenerationParams {
+            temperature: Some(3.0),
+            ..hot
+        };
+        let err = divergent_mode
+            .process(DivergentParams::new("Brainstorm").with_generation(too_hot))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("generation.temperature"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_divergent_mode_records_fallback() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("down"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = create_test_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let router = ProviderRouter::new(langbase)
+            .with_provider(
+                ProviderKind::Offline,
+                Arc::new(OfflineBackend::new(&config.pipes)) as SharedBackend,
+            )
+            .with_fallbacks(&config.pipes.divergent, vec![FallbackTarget::Heuristic]);
+        let divergent_mode = DivergentMode::new(storage.clone(), router, &config);
+
+        let result = divergent_mode
+            .process(DivergentParams::new("Brainstorm"))
+            .await;
+        assert!(result.is_ok(), "Fallback should answer: {:?}", result.err());
+
+        let metrics = storage.get_fallback_metrics(None).await.unwrap();
+        assert_eq!(metrics.total_fallbacks, 1);
+        assert_eq!(metrics.fallbacks_by_type["local_heuristic"], 1);
+        assert_eq!(metrics.fallbacks_by_pipe["divergent-reasoning-v1"], 1);
+    }
+
     #[tokio::test]
     async fn test_divergent_mode_generates_perspectives() {
         let dir = tempdir().expect("Failed to create temp dir");
@@ -514,7 +683,10 @@ mod reflection_mode_tests {
             branch_id: None,
             max_iterations: 3,
             quality_threshold: 0.8,
+            min_improvement: 0.02,
+            rubric: Vec::new(),
             include_chain: false,
+            generation: Default::default(),
         };
 
         let result = reflection_mode.process(params).await;
@@ -749,3 +921,96 @@ mod cross_ref_tests {
         assert_eq!(cross_refs[0].to_branch_id, result1.branch_id);
     }
 }
+
+#[cfg(test)]
+mod fan_out_tests {
+    use super::*;
+    use mcp_langbase_reasoning::modes::{GotGenerateParams, GotInitParams, GotMode};
+
+    fn fan_out_config(mock_url: &str, db_path: std::path::PathBuf) -> Config {
+        let mut config = create_test_config(mock_url, db_path);
+        config.concurrency.fan_out = 4;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_divergent_fan_out_calls_once_per_perspective() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        // Three perspective calls plus the synthesis call
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": json!({
+                    "perspectives": [
+                        {"thought": "One view", "novelty": 0.5, "viability": 0.7},
+                        {"thought": "Ignored extra", "novelty": 0.1, "viability": 0.1}
+                    ],
+                    "synthesis": "Combined insight",
+                    "metadata": {}
+                }).to_string()
+            })))
+            .expect(4)
+            .mount(&mock_server)
+            .await;
+
+        let config = fan_out_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let divergent_mode = DivergentMode::new(storage, langbase, &config);
+
+        let result = divergent_mode
+            .process(DivergentParams::new("How might we cut latency?").with_num_perspectives(3))
+            .await
+            .unwrap();
+
+        assert_eq!(result.perspectives.len(), 3);
+        assert!(result.perspectives.iter().all(|p| p.content == "One view"));
+        assert_eq!(result.synthesis, "Combined insight");
+    }
+
+    #[tokio::test]
+    async fn test_got_generate_fan_out_calls_once_per_continuation() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let db_path = dir.path().join("test.db");
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/pipes/run"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "success": true,
+                "completion": json!({
+                    "continuations": [
+                        {"thought": "Next step", "confidence": 0.8},
+                        {"thought": "Ignored extra", "confidence": 0.2}
+                    ]
+                }).to_string()
+            })))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let config = fan_out_config(&mock_server.uri(), db_path.clone());
+        let storage = create_test_storage(db_path).await;
+        let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+        let got_mode = GotMode::new(storage, langbase, &config);
+
+        let init = got_mode
+            .initialize(GotInitParams::new("Root thought"))
+            .await
+            .unwrap();
+        let result = got_mode
+            .generate(GotGenerateParams::new(&init.session_id).with_k(3))
+            .await
+            .unwrap();
+
+        assert_eq!(result.continuations.len(), 3);
+        assert!(result
+            .continuations
+            .iter()
+            .all(|c| c.content == "Next step"));
+    }
+}
@@ -0,0 +1,3938 @@
+//! End-to-end tool tests against the offline fixture provider
+//!
+//! Every pipe-backed tool is driven through the MCP handlers with
+//! `ProviderKind::Offline`, so no network access or API key is needed.
+
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcp_langbase_reasoning::config::{
+    Config, DatabaseConfig, LangbaseConfig, LogFormat, LoggingConfig, NetworkConfig, PipeConfig,
+    ProviderConfig, ProviderKind, RequestConfig,
+};
+use mcp_langbase_reasoning::error::McpError;
+use mcp_langbase_reasoning::langbase::LangbaseClient;
+use mcp_langbase_reasoning::modes::likelihood_from_probability;
+use mcp_langbase_reasoning::server::{
+    complete, handle_tool_call, list_resources, read_resource, session_uri, tool_output_schema,
+    AppState, SharedState,
+};
+use mcp_langbase_reasoning::storage::{GraphEdge, SqliteStorage};
+
+/// Config whose pipes are all served from fixtures
+fn offline_config() -> Config {
+    Config {
+        langbase: LangbaseConfig {
+            api_key: String::new(),
+            // Unroutable, so any accidental Langbase call fails fast
+            base_url: "http://127.0.0.1:1".to_string(),
+            network: NetworkConfig::default(),
+        },
+        database: DatabaseConfig::in_memory(),
+        logging: LoggingConfig {
+            level: "warn".to_string(),
+            format: LogFormat::Pretty,
+        },
+        request: RequestConfig::default(),
+        pipes: PipeConfig::default(),
+        error_handling: Default::default(),
+        retention: Default::default(),
+        embeddings: Default::default(),
+        providers: ProviderConfig {
+            default: ProviderKind::Offline,
+            ..Default::default()
+        },
+        pipe_cache: Default::default(),
+        rate_limits: Default::default(),
+        circuit_breaker: Default::default(),
+        concurrency: Default::default(),
+        payload_log: Default::default(),
+        budget: Default::default(),
+        transport: Default::default(),
+        tools: Default::default(),
+        mcts: Default::default(),
+    }
+}
+
+/// Create app state whose pipes are all served from fixtures
+async fn create_offline_state() -> SharedState {
+    create_state(offline_config()).await
+}
+
+/// Create app state for `config`
+async fn create_state(config: Config) -> SharedState {
+    let storage = SqliteStorage::new(&config.database)
+        .await
+        .expect("Failed to create storage");
+    let langbase = LangbaseClient::new(&config.langbase, config.request.clone()).unwrap();
+    Arc::new(AppState::new(config, storage, langbase))
+}
+
+async fn call(state: &SharedState, tool: &str, arguments: Value) -> Value {
+    handle_tool_call(state, tool, Some(arguments))
+        .await
+        .unwrap_or_else(|e| panic!("{} failed offline: {:?}", tool, e))
+}
+
+fn id(value: &Value, key: &str) -> String {
+    value[key]
+        .as_str()
+        .unwrap_or_else(|| panic!("missing {} in {}", key, value))
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_offline_core_modes() {
+    let state = create_offline_state().await;
+
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+    assert!(!id(&linear, "content").is_empty());
+    let session_id = id(&linear, "session_id");
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database", "session_id": session_id}),
+    )
+    .await;
+    assert!(!tree["child_branches"].as_array().unwrap().is_empty());
+
+    call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn"}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_reflection",
+        json!({"content": "All swans are white"}),
+    )
+    .await;
+    call(&state, "reasoning_auto", json!({"content": "What next?"})).await;
+
+    let checkpoint = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": session_id, "name": "before"}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_backtrack",
+        json!({"checkpoint_id": id(&checkpoint, "id"), "session_id": session_id}),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_offline_linear_revision() {
+    let state = create_offline_state().await;
+
+    let first = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Estimate the load"}),
+    )
+    .await;
+    let session_id = id(&first, "session_id");
+    let wrong = id(&first, "thought_id");
+    call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Size the cluster", "session_id": session_id}),
+    )
+    .await;
+
+    let revision = call(
+        &state,
+        "reasoning_linear",
+        json!({
+            "content": "The load estimate ignored peak traffic",
+            "session_id": session_id,
+            "revises_thought_id": wrong
+        }),
+    )
+    .await;
+    assert_eq!(revision["revised_thought"], wrong.as_str());
+
+    let superseded = state.storage.get_thought(&wrong).await.unwrap().unwrap();
+    assert_eq!(superseded.metadata.unwrap()["superseded"], true);
+    let revised = state
+        .storage
+        .get_thought(&id(&revision, "thought_id"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(revised.parent_id.as_deref(), Some(wrong.as_str()));
+    assert_eq!(revised.metadata.unwrap()["revision"], true);
+
+    // The superseded step cannot be revised twice, and a revision needs its session
+    for arguments in [
+        json!({"content": "Again", "session_id": session_id, "revises_thought_id": wrong}),
+        json!({"content": "Again", "revises_thought_id": id(&revision, "thought_id")}),
+    ] {
+        assert!(
+            handle_tool_call(&state, "reasoning_linear", Some(arguments))
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_linear_step_limits() {
+    let state = create_offline_state().await;
+
+    let first = call(
+        &state,
+        "reasoning_linear",
+        json!({
+            "content": "Plan the migration of the billing database to the new cluster",
+            "max_steps": 6,
+            "context_budget": 200
+        }),
+    )
+    .await;
+    let session_id = id(&first, "session_id");
+    assert_eq!(first["step"], 1);
+    assert_eq!(first["max_steps"], 6);
+    assert!(first.get("summary_thought").is_none());
+
+    // The limits carry over, and the history soon outgrows the small budget
+    let mut summary = None;
+    for step in 2..=6 {
+        let result = call(
+            &state,
+            "reasoning_linear",
+            json!({"content": "Continue the plan", "session_id": session_id}),
+        )
+        .await;
+        assert_eq!(result["step"], step);
+        assert_eq!(result["max_steps"], 6);
+        if let Some(thought_id) = result["summary_thought"].as_str() {
+            summary = Some(thought_id.to_string());
+        }
+    }
+    let summary = summary.expect("history was summarized");
+    let thought = state.storage.get_thought(&summary).await.unwrap().unwrap();
+    assert_eq!(thought.metadata.unwrap()["summary"], true);
+
+    // A seventh step exceeds max_steps
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_linear",
+        Some(json!({"content": "One more", "session_id": session_id})),
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_reflection_rounds() {
+    let state = create_offline_state().await;
+
+    // The fixture scores every version 0.8: the first round meets the
+    // default threshold
+    let once = call(
+        &state,
+        "reasoning_reflection",
+        json!({"content": "All swans are white"}),
+    )
+    .await;
+    assert_eq!(once["iterations_performed"], 1);
+    assert_eq!(once["stop_reason"], "threshold_met");
+
+    // Above it, the revision scores no better and the rounds converge
+    let result = call(
+        &state,
+        "reasoning_reflection",
+        json!({"content": "All swans are white", "quality_threshold": 0.95, "max_iterations": 5}),
+    )
+    .await;
+    assert_eq!(result["stop_reason"], "converged");
+    assert_eq!(result["iterations_performed"], 2);
+    let rounds = result["rounds"].as_array().unwrap();
+    assert_eq!(rounds.len(), 2);
+    assert_eq!(rounds[0]["content"], "All swans are white");
+    assert!(rounds[0].get("improvement").is_none());
+    assert_eq!(rounds[1]["content"], rounds[0]["revision"]);
+    assert_eq!(rounds[1]["quality_score"], 0.8);
+    assert_eq!(rounds[1]["improvement"], 0.0);
+
+    let thought = state
+        .storage
+        .get_thought(&id(&result, "reflection_thought_id"))
+        .await
+        .unwrap()
+        .unwrap();
+    let metadata = thought.metadata.unwrap();
+    assert_eq!(metadata["round_scores"], json!([0.8, 0.8]));
+    assert_eq!(metadata["stop_reason"], "converged");
+
+    let invalid = handle_tool_call(
+        &state,
+        "reasoning_reflection",
+        Some(json!({"content": "x", "min_improvement": 2.0})),
+    )
+    .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_reflection_rubric() {
+    let state = create_offline_state().await;
+
+    // The fixture scores correctness 0.9 and clarity 0.7 and leaves
+    // completeness unscored
+    let rubric = json!([
+        {"name": "Correctness", "weight": 2, "description": "Claims follow from the evidence"},
+        {"name": "clarity"},
+        {"name": "completeness"}
+    ]);
+    let result = call(
+        &state,
+        "reasoning_reflection",
+        json!({"content": "All swans are white", "rubric": rubric}),
+    )
+    .await;
+    let scores = result["rubric_scores"].as_array().unwrap();
+    assert_eq!(scores.len(), 2);
+    assert_eq!(
+        scores[0],
+        json!({"name": "Correctness", "weight": 2.0, "score": 0.9})
+    );
+    assert_eq!(
+        scores[1],
+        json!({"name": "clarity", "weight": 1.0, "score": 0.7})
+    );
+    let quality = result["quality_score"].as_f64().unwrap();
+    assert!((quality - 2.5 / 3.0).abs() < 1e-9);
+    assert_eq!(
+        result["rounds"][0]["rubric_scores"],
+        result["rubric_scores"]
+    );
+
+    let thought = state
+        .storage
+        .get_thought(&id(&result, "reflection_thought_id"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        thought.metadata.unwrap()["rubric_scores"],
+        result["rubric_scores"]
+    );
+
+    // Per-dimension scores are tracked across calls in the metrics summary
+    call(
+        &state,
+        "reasoning_reflection",
+        json!({"content": "Some swans are black", "rubric": [{"name": "clarity"}]}),
+    )
+    .await;
+    let summary = call(&state, "reasoning_metrics_summary", json!({})).await;
+    let dimensions = summary["reflection_rubrics"]["dimensions"]
+        .as_array()
+        .unwrap();
+    let names: Vec<&str> = dimensions
+        .iter()
+        .map(|d| d["dimension"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Correctness", "clarity"]);
+    // Clarity alone scores 0.7, below the threshold, so the second call
+    // converges after two rounds
+    assert_eq!(dimensions[0]["samples"], 1);
+    assert_eq!(dimensions[1]["samples"], 3);
+    assert_eq!(dimensions[1]["latest"], 0.7);
+
+    let duplicate = handle_tool_call(
+        &state,
+        "reasoning_reflection",
+        Some(json!({"content": "x", "rubric": [{"name": "clarity"}, {"name": "Clarity"}]})),
+    )
+    .await;
+    assert!(duplicate.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_backtrack_diff() {
+    let state = create_offline_state().await;
+
+    let first = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&first, "session_id");
+    let before = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": session_id, "name": "before"}),
+    )
+    .await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Compare storage engines", "session_id": session_id}),
+    )
+    .await;
+    let branches = tree["child_branches"].as_array().unwrap().len();
+    let after = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": session_id, "name": "after"}),
+    )
+    .await;
+
+    let diff = call(
+        &state,
+        "reasoning_backtrack_diff",
+        json!({"checkpoint_a": id(&before, "id"), "checkpoint_b": id(&after, "id")}),
+    )
+    .await;
+    assert_eq!(diff["session_id"], session_id);
+    assert_eq!(diff["from"]["name"], "before");
+    assert_eq!(diff["thoughts"]["before"], 1);
+    let added = diff["added_thoughts"].as_array().unwrap();
+    assert!(!added.is_empty());
+    assert_eq!(diff["thoughts"]["change"], added.len());
+    assert!(diff["removed_thoughts"].as_array().unwrap().is_empty());
+    assert!(diff["branches"]["change"].as_i64().unwrap() >= branches as i64);
+    assert_eq!(diff["graph"]["nodes"]["change"], 0);
+    // The tree branches add conclusions next to the linear thought,
+    // which nothing has built on yet
+    assert_eq!(
+        diff["from"]["conclusions"][0]["thought_id"],
+        first["thought_id"]
+    );
+    assert!(diff["dropped_conclusions"].as_array().unwrap().is_empty());
+    let new_conclusions = diff["new_conclusions"].as_array().unwrap();
+    assert!(!new_conclusions.is_empty());
+    assert!(new_conclusions.iter().all(|c| c["mode"] == "tree"));
+    assert!(diff["summary"]
+        .as_str()
+        .unwrap()
+        .starts_with(&format!("{} thoughts added, 0 removed", added.len())));
+
+    // Reversing the order reports the same thoughts as removed
+    let reverse = call(
+        &state,
+        "reasoning_backtrack_diff",
+        json!({"checkpoint_a": id(&after, "id"), "checkpoint_b": id(&before, "id")}),
+    )
+    .await;
+    assert_eq!(reverse["removed_thoughts"], diff["added_thoughts"]);
+
+    let other = call(&state, "reasoning_linear", json!({"content": "Unrelated"})).await;
+    let foreign = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": id(&other, "session_id"), "name": "other"}),
+    )
+    .await;
+    let mismatch = handle_tool_call(
+        &state,
+        "reasoning_backtrack_diff",
+        Some(json!({"checkpoint_a": id(&before, "id"), "checkpoint_b": id(&foreign, "id")})),
+    )
+    .await;
+    assert!(mismatch.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_graph_of_thoughts() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Improve onboarding"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 2}),
+    )
+    .await;
+    let nodes: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+    assert_eq!(nodes.len(), 2);
+
+    call(
+        &state,
+        "reasoning_got_score",
+        json!({"session_id": session_id, "node_id": nodes[0]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_got_aggregate",
+        json!({"session_id": session_id, "node_ids": nodes}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_got_refine",
+        json!({"session_id": session_id, "node_id": nodes[1]}),
+    )
+    .await;
+
+    // The fixture repeats its continuations, so the ones already in the
+    // graph are skipped as duplicates
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": nodes[0], "k": 3}),
+    )
+    .await;
+    assert_eq!(generated["continuations"].as_array().unwrap().len(), 1);
+    assert_eq!(generated["duplicates_skipped"], 2);
+}
+
+#[tokio::test]
+async fn test_offline_session_resources() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Improve onboarding"}),
+    )
+    .await;
+    let got_session = id(&init, "session_id");
+    call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": got_session, "node_id": id(&init, "root_node_id"), "k": 2}),
+    )
+    .await;
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+    let linear_session = id(&linear, "session_id");
+
+    let resources = list_resources(&state).await.unwrap();
+    let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(
+        uris,
+        vec![session_uri(&linear_session), session_uri(&got_session)]
+    );
+    assert_eq!(resources[0].mime_type, "text/markdown");
+
+    let linear = read_resource(&state, &session_uri(&linear_session))
+        .await
+        .unwrap();
+    assert!(linear
+        .text
+        .starts_with(&format!("# Session {}", linear_session)));
+    assert!(linear.text.contains("## Thoughts (1)"));
+
+    let got = read_resource(&state, &session_uri(&got_session))
+        .await
+        .unwrap();
+    assert!(got.text.contains("## Graph (3 nodes, 2 edges)"));
+
+    for uri in ["reasoning://session/missing", "file:///etc/passwd"] {
+        assert!(matches!(
+            read_resource(&state, uri).await,
+            Err(McpError::ResourceNotFound { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_offline_argument_validation() {
+    let state = create_offline_state().await;
+
+    let err = handle_tool_call(
+        &state,
+        "reasoning_linear",
+        Some(json!({"content": "Plan\u{0}a release", "session_id": "../other"})),
+    )
+    .await
+    .unwrap_err();
+    match err {
+        McpError::InvalidParameters { tool_name, message } => {
+            assert_eq!(tool_name, "reasoning_linear");
+            assert!(message.contains("content: contains control character U+0000"));
+            assert!(message.contains("session_id:"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    // Nothing was stored for the rejected call
+    let sessions = call(&state, "reasoning_session_list", json!({})).await;
+    assert_eq!(
+        sessions["sessions"].as_array().unwrap().len(),
+        0,
+        "{}",
+        sessions
+    );
+
+    // Client-chosen session IDs are still accepted
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release", "session_id": "release-plan"}),
+    )
+    .await;
+    assert_eq!(linear["session_id"], "release-plan");
+}
+
+#[tokio::test]
+async fn test_offline_analysis_modes() {
+    let state = create_offline_state().await;
+    let content = json!({"content": "Everyone uses it, so it must be good."});
+
+    call(&state, "reasoning_detect_biases", content.clone()).await;
+    call(&state, "reasoning_detect_fallacies", content).await;
+    call(
+        &state,
+        "reasoning_make_decision",
+        json!({"question": "Which vendor?", "options": ["Option A", "Option B"]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_analyze_perspectives",
+        json!({"topic": "Office move", "stakeholders": [{"name": "Users"}]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_assess_evidence",
+        json!({"claim": "The fix works", "evidence": [{"content": "Tests pass"}]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_probabilistic",
+        json!({
+            "hypothesis": "The fix works",
+            "prior": 0.5,
+            "evidence": [{"description": "Tests pass", "likelihood_if_true": 0.9, "likelihood_if_false": 0.3}]
+        }),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_offline_timeline_and_exploration() {
+    let state = create_offline_state().await;
+
+    let timeline = call(
+        &state,
+        "reasoning_timeline_create",
+        json!({"name": "Launch", "content": "Launch the product"}),
+    )
+    .await;
+    let timeline_id = id(&timeline, "timeline_id");
+
+    let branched = call(
+        &state,
+        "reasoning_timeline_branch",
+        json!({"timeline_id": timeline_id, "content": "Alternatives", "num_alternatives": 2}),
+    )
+    .await;
+    let branches: Vec<String> = branched["branches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| id(b, "branch_id"))
+        .collect();
+    assert!(branches.len() >= 2);
+
+    call(
+        &state,
+        "reasoning_timeline_compare",
+        json!({"timeline_a": branches[0], "timeline_b": branches[1]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_timeline_merge",
+        json!({"source_id": branches[0], "target_id": branches[1]}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_counterfactual",
+        json!({
+            "question": "What if we launched later?",
+            "branch_id": branches[0],
+            "intervention": "Delay the launch by a month"
+        }),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_mcts_explore",
+        json!({"content": "Pricing strategy", "iterations": 2}),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_offline_session_locks() {
+    let mut config = offline_config();
+    config.concurrency.session_lock_timeout_ms = 0;
+    let state = create_state(config).await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    let branch_id = id(&tree, "branch_id");
+
+    // Another call holds the session: mutating calls on it conflict, even
+    // when they only name one of its branches
+    let guard = state
+        .session_locks
+        .lock(&session_id, Duration::ZERO)
+        .await
+        .unwrap();
+    for (tool, arguments) in [
+        ("reasoning_tree_complete", json!({"branch_id": branch_id})),
+        (
+            "reasoning_session_tag",
+            json!({"session_id": session_id, "add": ["busy"]}),
+        ),
+    ] {
+        let err = handle_tool_call(&state, tool, Some(arguments))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, McpError::Conflict { .. }),
+            "{}: {:?}",
+            tool,
+            err
+        );
+        assert!(err.to_string().contains(&session_id));
+    }
+    call(
+        &state,
+        "reasoning_tree_list",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+
+    drop(guard);
+    call(
+        &state,
+        "reasoning_tree_complete",
+        json!({"branch_id": branch_id}),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_offline_preset_run_in_session() {
+    let mut config = offline_config();
+    config.concurrency.session_lock_timeout_ms = 500;
+    let state = create_state(config).await;
+
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Review the parser"}),
+    )
+    .await;
+    let session_id = id(&linear, "session_id");
+
+    // The steps run on the session the preset call already holds
+    let result = call(
+        &state,
+        "reasoning_preset_run",
+        json!({
+            "preset_id": "code-review",
+            "inputs": {"code": "fn main() {}"},
+            "session_id": session_id
+        }),
+    )
+    .await;
+    assert_eq!(result["status"], "success", "{}", result);
+    assert_eq!(result["steps_completed"], result["steps_total"]);
+    for step in result["step_results"].as_array().unwrap() {
+        assert_eq!(step["status"], "success", "{}", step);
+    }
+    assert_eq!(state.session_locks.active(), 0);
+}
+
+#[tokio::test]
+async fn test_offline_concurrent_calls_on_one_session() {
+    let state = create_offline_state().await;
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+    let session_id = id(&linear, "session_id");
+
+    // Calls on one session wait for each other instead of conflicting
+    let calls = (0..4).map(|i| {
+        let state = state.clone();
+        let arguments = json!({"content": format!("Step {}", i), "session_id": session_id});
+        tokio::spawn(
+            async move { handle_tool_call(&state, "reasoning_linear", Some(arguments)).await },
+        )
+    });
+    for result in futures::future::join_all(calls).await {
+        result.unwrap().unwrap();
+    }
+
+    let thoughts = call(
+        &state,
+        "reasoning_session_thoughts",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(thoughts["thoughts"].as_array().unwrap().len(), 5);
+    assert_eq!(state.session_locks.active(), 0);
+}
+
+#[tokio::test]
+async fn test_offline_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = create_offline_state().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+
+    socket
+        .send(request(
+            1,
+            "initialize",
+            json!({"clientInfo": {"name": "ws-test"}}),
+        ))
+        .await
+        .unwrap();
+    socket
+        .send(request(
+            2,
+            "tools/call",
+            json!({"name": "reasoning_linear", "arguments": {"content": "Plan a release"}}),
+        ))
+        .await
+        .unwrap();
+
+    let mut responses = Vec::new();
+    while responses.len() < 2 {
+        match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => responses.push(serde_json::from_str::<Value>(&text).unwrap()),
+            _ => continue,
+        }
+    }
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(
+        responses[0]["result"]["serverInfo"]["name"],
+        "mcp-langbase-reasoning"
+    );
+    assert_eq!(responses[1]["id"], 2);
+    assert!(responses[1]["result"]["isError"].is_null());
+    let text = responses[1]["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("session_id"));
+
+    // The structured result carries every field its output schema requires
+    let structured = &responses[1]["result"]["structuredContent"];
+    let schema = tool_output_schema("reasoning_linear").unwrap();
+    for field in schema["required"].as_array().unwrap() {
+        let field = field.as_str().unwrap();
+        assert!(!structured[field].is_null(), "missing {}", field);
+    }
+}
+
+#[tokio::test]
+async fn test_offline_workspaces_per_connection_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use mcp_langbase_reasoning::storage::AuditFilter;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    async fn exchange(socket: &mut Socket, id: u64, method: &str, params: Value) -> Value {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .unwrap();
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    async fn tool_result(socket: &mut Socket, id: u64, name: &str, arguments: Value) -> Value {
+        let response = exchange(
+            socket,
+            id,
+            "tools/call",
+            json!({"name": name, "arguments": arguments}),
+        )
+        .await;
+        assert!(response["result"]["isError"].is_null(), "{}", response);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    }
+
+    let state = create_offline_state().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state.clone());
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let mut alpha = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap()
+        .0;
+    let mut beta = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap()
+        .0;
+
+    // Both clients pick a workspace before either does any work
+    for (socket, name) in [(&mut alpha, "alpha"), (&mut beta, "beta")] {
+        let params = json!({
+            "clientInfo": {"name": name},
+            "initializationOptions": {"workspace_id": name}
+        });
+        exchange(socket, 1, "initialize", params).await;
+    }
+
+    let mut sessions = Vec::new();
+    for socket in [&mut alpha, &mut beta] {
+        let linear = tool_result(
+            socket,
+            2,
+            "reasoning_linear",
+            json!({"content": "Plan a release"}),
+        )
+        .await;
+        sessions.push(id(&linear, "session_id"));
+    }
+
+    // Each client sees only its own session
+    for (socket, own) in [(&mut alpha, &sessions[0]), (&mut beta, &sessions[1])] {
+        let listed = tool_result(socket, 3, "reasoning_session_list", json!({})).await;
+        assert_eq!(listed["count"], 1);
+        assert_eq!(listed["sessions"][0]["session_id"], json!(own));
+    }
+    let thoughts = tool_result(
+        &mut alpha,
+        4,
+        "reasoning_session_thoughts",
+        json!({"session_id": sessions[1]}),
+    )
+    .await;
+    assert_eq!(thoughts["count"], 0);
+
+    // The server's own state stays in the configured workspace, and each
+    // client's calls are audited under its own name
+    assert_eq!(state.workspace.workspace_id(), "default");
+    for (session_id, actor) in [(&sessions[0], "mcp:alpha"), (&sessions[1], "mcp:beta")] {
+        let entries = state
+            .storage
+            .list_audit_entries(AuditFilter::new().with_session(session_id))
+            .await
+            .unwrap();
+        assert!(!entries.is_empty());
+        assert!(entries.iter().all(|entry| entry.actor == actor));
+    }
+}
+
+#[tokio::test]
+async fn test_offline_partial_results_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = create_offline_state().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let message = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/call",
+        "params": {
+            "name": "reasoning_divergent",
+            "arguments": {"content": "Grow the user base"},
+            "_meta": {"progressToken": "div"}
+        }
+    });
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .unwrap();
+
+    // Every perspective arrives as an artifact before the final result
+    let mut artifacts = Vec::new();
+    let response = loop {
+        let message: Value = match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        };
+        if message["id"] == 1 {
+            break message;
+        }
+        assert_eq!(message["method"], "notifications/progress");
+        if !message["params"]["artifact"].is_null() {
+            artifacts.push(message["params"]["artifact"].clone());
+        }
+    };
+
+    let text = response["result"]["content"][0]["text"].as_str().unwrap();
+    let result: Value = serde_json::from_str(text).unwrap();
+    let perspectives = result["perspectives"].as_array().unwrap();
+    assert!(!perspectives.is_empty());
+    assert_eq!(artifacts.len(), perspectives.len());
+    for (i, (artifact, perspective)) in artifacts.iter().zip(perspectives).enumerate() {
+        assert_eq!(artifact["kind"], "perspective");
+        assert_eq!(artifact["index"], i);
+        assert_eq!(artifact["data"]["thought"], perspective["content"]);
+    }
+}
+
+#[tokio::test]
+async fn test_offline_tool_groups_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::config::ToolsConfig;
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut config = offline_config();
+    config.tools = ToolsConfig {
+        enabled: vec!["core".to_string(), "reasoning_got_init".to_string()],
+        disabled: vec!["reasoning_auto".to_string()],
+        ..Default::default()
+    };
+    let state = create_state(config).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+    socket
+        .send(request(1, "tools/list", json!({})))
+        .await
+        .unwrap();
+    socket
+        .send(request(
+            2,
+            "tools/call",
+            json!({"name": "reasoning_got_score", "arguments": {"session_id": "s", "node_id": "n"}}),
+        ))
+        .await
+        .unwrap();
+
+    let mut responses = Vec::new();
+    while responses.len() < 2 {
+        match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => responses.push(serde_json::from_str::<Value>(&text).unwrap()),
+            _ => continue,
+        }
+    }
+
+    let names: Vec<&str> = responses[0]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"reasoning_linear"));
+    assert!(names.contains(&"reasoning_got_init"));
+    assert!(!names.contains(&"reasoning_got_score"));
+    assert!(!names.contains(&"reasoning_auto"));
+    assert_eq!(names.len(), 14);
+
+    assert_eq!(responses[1]["result"]["isError"], true);
+    let text = responses[1]["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("reasoning_got_score"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_offline_api_keys_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::config::{ApiKey, AuthScope};
+    use mcp_langbase_reasoning::server::McpServer;
+    use mcp_langbase_reasoning::storage::AuditFilter;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::{Error, Message};
+
+    let mut config = offline_config();
+    config.transport.api_keys = vec![
+        ApiKey {
+            key: "reader".to_string(),
+            scope: AuthScope::Read,
+        },
+        ApiKey {
+            key: "admin".to_string(),
+            scope: AuthScope::Full,
+        },
+    ];
+    let state = create_state(config).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state.clone());
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let connect = |header: Option<(&'static str, &'static str)>| {
+        let mut request = format!("ws://{}", addr).into_client_request().unwrap();
+        if let Some((name, value)) = header {
+            request.headers_mut().insert(name, value.parse().unwrap());
+        }
+        tokio_tungstenite::connect_async(request)
+    };
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+
+    for header in [None, Some(("authorization", "Bearer wrong"))] {
+        match connect(header).await {
+            Err(Error::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("expected 401, got {:?}", other.map(|(_, r)| r.status())),
+        }
+    }
+
+    // A read-only key sees and calls only read-only tools
+    let (mut reader, _) = connect(Some(("x-api-key", "reader"))).await.unwrap();
+    reader
+        .send(request(1, "tools/list", json!({})))
+        .await
+        .unwrap();
+    reader
+        .send(request(
+            2,
+            "tools/call",
+            json!({"name": "reasoning_linear", "arguments": {"content": "Plan a release"}}),
+        ))
+        .await
+        .unwrap();
+    reader
+        .send(request(
+            3,
+            "tools/call",
+            json!({"name": "reasoning_session_list", "arguments": {}}),
+        ))
+        .await
+        .unwrap();
+    let mut responses = Vec::new();
+    while responses.len() < 3 {
+        if let Message::Text(text) = reader.next().await.unwrap().unwrap() {
+            responses.push(serde_json::from_str::<Value>(&text).unwrap());
+        }
+    }
+    let names: Vec<&str> = responses[0]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"reasoning_session_list"));
+    assert!(!names.contains(&"reasoning_linear"));
+    assert_eq!(responses[1]["result"]["isError"], true);
+    let text = responses[1]["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("Unauthorized"), "{}", text);
+    assert!(responses[2]["result"]["isError"].is_null());
+
+    // A read-only key's initialize options only apply to its own connection
+    let options = json!({
+        "clientInfo": {"name": "reader"},
+        "initializationOptions": {"workspace_id": "elsewhere"}
+    });
+    reader
+        .send(request(4, "initialize", options))
+        .await
+        .unwrap();
+    loop {
+        if let Message::Text(text) = reader.next().await.unwrap().unwrap() {
+            let response: Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(response["id"], 4);
+            break;
+        }
+    }
+
+    // A full key calls anything
+    let (mut admin, _) = connect(Some(("authorization", "Bearer admin")))
+        .await
+        .unwrap();
+    admin
+        .send(request(
+            1,
+            "tools/call",
+            json!({"name": "reasoning_linear", "arguments": {"content": "Plan a release"}}),
+        ))
+        .await
+        .unwrap();
+    let linear: Value = loop {
+        if let Message::Text(text) = admin.next().await.unwrap().unwrap() {
+            let response: Value = serde_json::from_str(&text).unwrap();
+            assert!(response["result"]["isError"].is_null(), "{}", response);
+            let text = response["result"]["content"][0]["text"].as_str().unwrap();
+            break serde_json::from_str(text).unwrap();
+        }
+    };
+
+    // The full key still works in the configured workspace, audited as itself
+    assert_eq!(state.workspace.workspace_id(), "default");
+    let session_id = id(&linear, "session_id");
+    assert!(state
+        .storage
+        .get_session(&session_id)
+        .await
+        .unwrap()
+        .is_some());
+    let entries = state
+        .storage
+        .list_audit_entries(AuditFilter::new().with_session(&session_id))
+        .await
+        .unwrap();
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|entry| entry.actor == "mcp"));
+}
+
+#[tokio::test]
+async fn test_offline_client_quotas_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::{McpServer, QUOTA_EXCEEDED_CODE};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut config = offline_config();
+    config.transport.requests_per_minute = Some(2);
+    config.transport.daily_tool_calls = Some(1);
+    let state = create_state(config).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+    let quota_status = json!({"name": "reasoning_quota_status", "arguments": {}});
+    for message in [
+        request(1, "tools/call", quota_status.clone()),
+        request(2, "tools/call", quota_status),
+        request(3, "ping", json!({})),
+        request(4, "ping", json!({})),
+    ] {
+        socket.send(message).await.unwrap();
+    }
+
+    let mut responses = Vec::new();
+    while responses.len() < 4 {
+        if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+            responses.push(serde_json::from_str::<Value>(&text).unwrap());
+        }
+    }
+
+    let status = &responses[0]["result"]["structuredContent"];
+    assert_eq!(status["client"], "127.0.0.1");
+    assert_eq!(status["enforced"], true);
+    assert_eq!(status["tool_calls_today"], 1);
+    assert_eq!(status["tool_calls_remaining"], 0);
+
+    // The second tool call is over the daily quota and is not counted, so
+    // the third request fills the per-minute limit
+    assert_eq!(responses[1]["error"]["code"], QUOTA_EXCEEDED_CODE);
+    assert_eq!(responses[1]["error"]["data"]["limit"], "daily_tool_calls");
+    assert!(responses[2]["error"].is_null());
+    assert_eq!(responses[3]["error"]["code"], QUOTA_EXCEEDED_CODE);
+    assert_eq!(
+        responses[3]["error"]["data"]["limit"],
+        "requests_per_minute"
+    );
+    assert!(
+        responses[3]["error"]["data"]["retry_after_secs"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+}
+
+#[tokio::test]
+async fn test_offline_debate() {
+    use mcp_langbase_reasoning::storage::CrossRefType;
+
+    let state = create_offline_state().await;
+    let debate = call(
+        &state,
+        "reasoning_debate",
+        json!({"proposition": "Adopt a four-day week", "rounds": 2}),
+    )
+    .await;
+    assert_eq!(debate["winner"], "pro");
+    assert!(debate["confidence"].as_f64().unwrap() > 0.0);
+
+    let transcript = debate["transcript"].as_array().unwrap();
+    let sides: Vec<&str> = transcript
+        .iter()
+        .map(|a| a["side"].as_str().unwrap())
+        .collect();
+    assert_eq!(sides, ["pro", "con", "pro", "con"]);
+    // The fixture judge scores the first round only
+    assert_eq!(transcript[0]["score"], 0.74);
+    assert!(transcript[2]["score"].is_null());
+    assert!(transcript[0]["rebuts_branch_id"].is_null());
+    assert_eq!(
+        transcript[1]["rebuts_branch_id"],
+        transcript[0]["branch_id"]
+    );
+
+    // Each rebuttal contradicts the argument it answers
+    let rebuttal = id(&transcript[1], "branch_id");
+    let refs = state.storage.get_cross_refs_from(&rebuttal).await.unwrap();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].ref_type, CrossRefType::Contradicts);
+    assert_eq!(refs[0].to_branch_id, id(&transcript[0], "branch_id"));
+
+    // The verdict supports every argument of the winning side
+    let verdict = id(&debate, "verdict_branch_id");
+    let refs = state.storage.get_cross_refs_from(&verdict).await.unwrap();
+    assert_eq!(refs.len(), 2);
+    assert!(refs.iter().all(|r| r.ref_type == CrossRefType::Supports));
+
+    let session_id = id(&debate, "session_id");
+    let branches = state
+        .storage
+        .get_session_branches(&session_id)
+        .await
+        .unwrap();
+    assert_eq!(branches.len(), 6);
+
+    let err = handle_tool_call(
+        &state,
+        "reasoning_debate",
+        Some(json!({"proposition": "P", "rounds": 6})),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("rounds"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_offline_root_cause() {
+    let state = create_offline_state().await;
+
+    // The fixture never calls a cause the root, so the chain runs to the
+    // depth limit and the deepest cause is taken as the root
+    let whys = call(
+        &state,
+        "reasoning_root_cause",
+        json!({"problem": "The release broke checkout", "max_depth": 3}),
+    )
+    .await;
+    let causes = whys["causes"].as_array().unwrap();
+    let kinds: Vec<&str> = causes.iter().map(|c| c["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["proximate", "contributing", "root"]);
+    assert_eq!(causes[2]["depth"], 3);
+    assert!(!whys["corrective_actions"].as_array().unwrap().is_empty());
+
+    let fishbone = call(
+        &state,
+        "reasoning_root_cause",
+        json!({"problem": "Late shipments", "method": "fishbone"}),
+    )
+    .await;
+    let session_id = id(&fishbone, "session_id");
+    let problem_branch = id(&fishbone, "problem_branch_id");
+    let branches = state
+        .storage
+        .get_session_branches(&session_id)
+        .await
+        .unwrap();
+    // The problem branch and one per category
+    assert_eq!(branches.len(), 3);
+    let categories: Vec<&str> = branches
+        .iter()
+        .filter(|b| b.parent_branch_id.as_deref() == Some(problem_branch.as_str()))
+        .filter_map(|b| b.name.as_deref())
+        .collect();
+    assert_eq!(categories.len(), 2);
+    assert!(categories.contains(&"equipment"));
+    assert_eq!(fishbone["causes"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_offline_planning() {
+    let state = create_offline_state().await;
+
+    let plan = call(
+        &state,
+        "reasoning_plan",
+        json!({"goal": "Ship the new checkout flow"}),
+    )
+    .await;
+    let session_id = id(&plan, "session_id");
+    let tasks = plan["tasks"].as_array().unwrap();
+    let titles: Vec<&str> = tasks.iter().map(|t| t["title"].as_str().unwrap()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Agree on scope",
+            "Build the change",
+            "Prepare rollout notes",
+            "Roll out"
+        ]
+    );
+    let levels: Vec<u64> = tasks.iter().map(|t| t["level"].as_u64().unwrap()).collect();
+    assert_eq!(levels, [1, 2, 2, 3]);
+    assert_eq!(plan["critical_path"].as_array().unwrap().len(), 3);
+    assert_eq!(plan["critical_path_hours"], 22.0);
+    assert_eq!(plan["total_effort_hours"], 25.0);
+
+    // Tasks and prerequisites land in the graph tables
+    let nodes = state
+        .storage
+        .get_session_graph_nodes(&session_id)
+        .await
+        .unwrap();
+    assert_eq!(nodes.len(), 5);
+    let edges = state.storage.get_session_edges(&session_id).await.unwrap();
+    let depends_on = edges
+        .iter()
+        .filter(|e| e.edge_type.to_string() == "depends_on")
+        .count();
+    assert_eq!(depends_on, 4);
+
+    // Failing the rollout notes drops the rollout that waits on them
+    let notes = tasks[2]["task_id"].as_str().unwrap().to_string();
+    let rollout = tasks[3]["task_id"].as_str().unwrap().to_string();
+    let revised = call(
+        &state,
+        "reasoning_plan_revise",
+        json!({
+            "session_id": session_id,
+            "failed_task_id": notes,
+            "reason": "Docs team is unavailable"
+        }),
+    )
+    .await;
+    assert_eq!(revised["superseded_task_ids"], json!([rollout]));
+    assert_eq!(revised["added_task_ids"].as_array().unwrap().len(), 4);
+    let remaining = revised["plan"]["tasks"].as_array().unwrap();
+    assert_eq!(remaining.len(), 6);
+    assert!(remaining
+        .iter()
+        .all(|t| t["task_id"] != json!(notes) && t["task_id"] != json!(rollout)));
+
+    // A failed task cannot fail again
+    let again = handle_tool_call(
+        &state,
+        "reasoning_plan_revise",
+        Some(json!({
+            "session_id": session_id,
+            "failed_task_id": notes,
+            "reason": "Still unavailable"
+        })),
+    )
+    .await;
+    assert!(again.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_estimate() {
+    let state = create_offline_state().await;
+
+    let estimate = call(
+        &state,
+        "reasoning_estimate",
+        json!({"question": "How many piano tuners work in Chicago?"}),
+    )
+    .await;
+    let session_id = id(&estimate, "session_id");
+    // 2.7M / 2.5 * 0.05 * 1 / (4 * 240), whatever the fixture claims
+    let value = estimate["estimate"].as_f64().unwrap();
+    assert!((value - 56.25).abs() < 1e-9, "{}", value);
+    let low = estimate["low"].as_f64().unwrap();
+    let high = estimate["high"].as_f64().unwrap();
+    let likely_low = estimate["likely_low"].as_f64().unwrap();
+    let likely_high = estimate["likely_high"].as_f64().unwrap();
+    assert!(low < likely_low && likely_low < value);
+    assert!(value < likely_high && likely_high < high);
+    assert_eq!(estimate["unit"], "workers");
+
+    // The worker capacity is broken down, and both it and the final answer
+    // were stated wrong
+    let factors = estimate["factors"].as_array().unwrap();
+    assert_eq!(factors.len(), 7);
+    assert_eq!(factors[4]["derived"], true);
+    assert_eq!(factors[4]["estimate"], 960.0);
+    let checks: Vec<&str> = estimate["arithmetic_checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(checks, ["Services one worker does per year", "answer"]);
+
+    let nodes = state
+        .storage
+        .get_session_graph_nodes(&session_id)
+        .await
+        .unwrap();
+    assert_eq!(nodes.len(), 8);
+    let edges = state.storage.get_session_edges(&session_id).await.unwrap();
+    assert_eq!(edges.len(), 7);
+    let root = id(&estimate, "root_node_id");
+    assert_eq!(edges.iter().filter(|e| e.from_node == root).count(), 5);
+}
+
+#[tokio::test]
+async fn test_offline_swot() {
+    let state = create_offline_state().await;
+
+    let perspectives = call(
+        &state,
+        "reasoning_analyze_perspectives",
+        json!({"topic": "Expanding into a new market"}),
+    )
+    .await;
+    let session_id = id(&perspectives, "session_id");
+    let analysis_id = id(&perspectives, "analysis_id");
+
+    // The session's perspective analysis is linked, and only its
+    // stakeholders survive on the items
+    let first = call(
+        &state,
+        "reasoning_swot",
+        json!({"subject": "Market expansion", "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(first["perspective_id"], json!(analysis_id));
+    assert_eq!(first["strengths"][0]["stakeholders"], json!(["Users"]));
+    assert_eq!(first["opportunities"][0]["stakeholders"], json!(["Users"]));
+    assert_eq!(first["threats"][0]["confidence"], 1.0);
+    assert!(first.get("previous_swot_id").is_none());
+
+    // A later session sees the earlier analysis of the same subject
+    let second = call(
+        &state,
+        "reasoning_swot",
+        json!({"subject": "market expansion"}),
+    )
+    .await;
+    assert_eq!(second["previous_swot_id"], first["swot_id"]);
+    assert!(second.get("perspective_id").is_none());
+    assert_eq!(second["strengths"][0]["stakeholders"], json!([]));
+
+    let listed = call(
+        &state,
+        "reasoning_swot_list",
+        json!({"subject": "EXPANSION"}),
+    )
+    .await;
+    let ids: Vec<&Value> = listed["analyses"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| &a["swot_id"])
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&&first["swot_id"]) && ids.contains(&&second["swot_id"]));
+    let in_session = call(
+        &state,
+        "reasoning_swot_list",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(in_session["analyses"].as_array().unwrap().len(), 1);
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_swot",
+        Some(json!({"subject": "Market expansion", "perspective_id": "nope"})),
+    )
+    .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_hypothesis() {
+    let state = create_offline_state().await;
+
+    let design = call(
+        &state,
+        "reasoning_hypothesis",
+        json!({"hypothesis": "The new onboarding raises retention"}),
+    )
+    .await;
+    let session_id = id(&design, "session_id");
+    let test_id = id(&design, "test_id");
+    // The A/B test tells more than the feedback review and ranks first
+    let experiments: Vec<&Value> = design["experiments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| &e["experiment_id"])
+        .collect();
+    assert_eq!(experiments, [&json!("e1"), &json!("e2")]);
+    assert_eq!(design["predictions"].as_array().unwrap().len(), 2);
+
+    let first = call(
+        &state,
+        "reasoning_hypothesis_observe",
+        json!({"session_id": session_id, "test_id": test_id,
+               "experiment_id": "e1", "outcome": "Confirmed"}),
+    )
+    .await;
+    assert_eq!(first["prior"], 0.5);
+    assert!((first["posterior"].as_f64().unwrap() - 0.8).abs() < 1e-9);
+    assert_eq!(first["outcome"], "confirmed");
+    assert_eq!(first["remaining_experiments"], json!(["e2"]));
+
+    // Observations chain from the previous posterior
+    let second = call(
+        &state,
+        "reasoning_hypothesis_observe",
+        json!({"session_id": session_id, "test_id": test_id,
+               "experiment_id": "e2", "outcome": "not_mentioned"}),
+    )
+    .await;
+    assert_eq!(second["prior"], first["posterior"]);
+    assert!((second["posterior"].as_f64().unwrap() - 0.32 / 0.44).abs() < 1e-9);
+    assert_eq!(second["remaining_experiments"], json!([]));
+
+    let repeated = handle_tool_call(
+        &state,
+        "reasoning_hypothesis_observe",
+        Some(json!({"session_id": session_id, "test_id": test_id,
+                    "experiment_id": "e1", "outcome": "refuted"})),
+    )
+    .await;
+    assert!(repeated.is_err());
+    let unknown = handle_tool_call(
+        &state,
+        "reasoning_hypothesis_observe",
+        Some(json!({"session_id": session_id, "test_id": test_id,
+                    "experiment_id": "e2", "outcome": "maybe"})),
+    )
+    .await;
+    assert!(unknown.is_err());
+    let elsewhere = handle_tool_call(
+        &state,
+        "reasoning_hypothesis_observe",
+        Some(json!({"session_id": "other", "test_id": test_id,
+                    "experiment_id": "e2", "outcome": "mentioned"})),
+    )
+    .await;
+    assert!(elsewhere.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_divergent_technique() {
+    let state = create_offline_state().await;
+
+    let result = call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn", "technique": "random_stimulus", "num_perspectives": 2}),
+    )
+    .await;
+    assert_eq!(result["technique"], "random_stimulus");
+    assert_eq!(result["stimuli"].as_array().unwrap().len(), 2);
+    for perspective in result["perspectives"].as_array().unwrap() {
+        assert_eq!(perspective["technique"], "random_stimulus");
+    }
+
+    let plain = call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn"}),
+    )
+    .await;
+    assert!(plain.get("technique").is_none());
+    assert!(plain.get("stimuli").is_none());
+
+    let unknown = handle_tool_call(
+        &state,
+        "reasoning_divergent",
+        Some(json!({"content": "Reduce churn", "technique": "mind_map"})),
+    )
+    .await;
+    assert!(unknown.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_divergent_shortlist() {
+    let state = create_offline_state().await;
+
+    let result = call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn", "shortlist": 1}),
+    )
+    .await;
+    let shortlist = result["shortlist"].as_array().unwrap();
+    assert_eq!(shortlist.len(), 1);
+    let best = &shortlist[0];
+    assert_eq!(best["rank"], 1);
+    assert!(best["theme"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invert the problem"));
+    assert!((best["score"].as_f64().unwrap() - 0.75).abs() < 1e-9);
+
+    // Every perspective records its cluster, shortlisted or not
+    for perspective in result["perspectives"].as_array().unwrap() {
+        let thought = state
+            .storage
+            .get_thought(&id(perspective, "thought_id"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(thought.metadata.unwrap()["cluster"]["rank"]
+            .as_u64()
+            .is_some());
+    }
+    assert_eq!(
+        best["thought_ids"][0],
+        result["perspectives"][best["perspectives"][0].as_u64().unwrap() as usize]["thought_id"]
+    );
+
+    let plain = call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn"}),
+    )
+    .await;
+    assert!(plain.get("shortlist").is_none());
+}
+
+#[tokio::test]
+async fn test_offline_divergent_evaluate() {
+    let state = create_offline_state().await;
+
+    let ideas = call(
+        &state,
+        "reasoning_divergent",
+        json!({"content": "Reduce churn", "num_perspectives": 2}),
+    )
+    .await;
+    let session_id = id(&ideas, "session_id");
+    let criteria = json!([
+        {"name": "impact", "weight": 0.6},
+        {"name": "effort", "weight": 0.4, "description": "Higher is cheaper"}
+    ]);
+
+    let result = call(
+        &state,
+        "reasoning_divergent_evaluate",
+        json!({"session_id": session_id, "criteria": criteria}),
+    )
+    .await;
+    assert_eq!(result["question"], "Reduce churn");
+    let evaluations = result["evaluations"].as_array().unwrap();
+    assert_eq!(evaluations.len(), 2);
+    assert!(evaluations[0]["content"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invert the problem"));
+    assert!((evaluations[0]["score"].as_f64().unwrap() - 0.78).abs() < 1e-9);
+    assert!((evaluations[1]["score"].as_f64().unwrap() - 0.74).abs() < 1e-9);
+    assert_eq!(result["winners"].as_array().unwrap().len(), 2);
+
+    // The winners went to decision mode, and each idea records its score
+    assert!(result["decision"]["decision_id"].is_string());
+    let winner = state
+        .storage
+        .get_thought(&id(&evaluations[0], "thought_id"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(winner.metadata.unwrap()["evaluation"]["rank"], 1);
+
+    let single = call(
+        &state,
+        "reasoning_divergent_evaluate",
+        json!({"session_id": session_id, "criteria": criteria, "winners": 1}),
+    )
+    .await;
+    assert!(single.get("decision").is_none());
+
+    for arguments in [
+        json!({"session_id": session_id, "criteria": []}),
+        json!({"session_id": session_id, "criteria": criteria, "thought_ids": [session_id]}),
+        json!({"session_id": "no-such-session", "criteria": criteria}),
+    ] {
+        assert!(
+            handle_tool_call(&state, "reasoning_divergent_evaluate", Some(arguments))
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_six_hats() {
+    let state = create_offline_state().await;
+
+    let result = call(
+        &state,
+        "reasoning_six_hats",
+        json!({"topic": "Move to a four-day week"}),
+    )
+    .await;
+    let session_id = id(&result, "session_id");
+    let hats: Vec<&str> = result["passes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["hat"].as_str().unwrap())
+        .collect();
+    assert_eq!(hats, ["white", "red", "black", "yellow", "green", "blue"]);
+    assert!(!result["synthesis"].as_str().unwrap().is_empty());
+
+    // One branch per hat under the root branch
+    let root = id(&result, "root_branch_id");
+    let branches = call(
+        &state,
+        "reasoning_tree_list",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    let children = branches
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|b| b["parent_branch_id"] == json!(root))
+        .count();
+    assert_eq!(children, 6);
+
+    let empty = handle_tool_call(&state, "reasoning_six_hats", Some(json!({"topic": " "}))).await;
+    assert!(empty.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_dialectic() {
+    let state = create_offline_state().await;
+
+    // The fixture pipe answers every round alike, so the second synthesis
+    // repeats the first and the position converges
+    let result = call(
+        &state,
+        "reasoning_dialectic",
+        json!({"thesis": "Remote work raises productivity", "rounds": 4}),
+    )
+    .await;
+    let rounds = result["rounds"].as_array().unwrap();
+    assert_eq!(rounds.len(), 2);
+    assert_eq!(result["converged"], true);
+    assert!(rounds[1]["similarity"].as_f64().unwrap() > 0.99);
+    assert_eq!(rounds[0]["position_branch_id"], result["thesis_branch_id"]);
+    assert_eq!(
+        rounds[1]["position_branch_id"],
+        rounds[0]["synthesis_branch_id"]
+    );
+    assert_eq!(result["final_branch_id"], rounds[1]["synthesis_branch_id"]);
+
+    let one = call(
+        &state,
+        "reasoning_dialectic",
+        json!({"thesis": "Remote work raises productivity", "rounds": 1}),
+    )
+    .await;
+    assert_eq!(one["rounds"].as_array().unwrap().len(), 1);
+    assert_eq!(one["converged"], false);
+
+    let too_many = handle_tool_call(
+        &state,
+        "reasoning_dialectic",
+        Some(json!({"thesis": "Remote work raises productivity", "rounds": 6})),
+    )
+    .await;
+    assert!(too_many.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_prioritize() {
+    let state = create_offline_state().await;
+    let items = json!(["Single sign-on", "Dark mode", "CSV export"]);
+
+    // Scores come from the fixture estimates: 337.5, 800 and 600
+    let rice = call(
+        &state,
+        "reasoning_prioritize",
+        json!({"items": items, "method": "rice", "goal": "Next quarter"}),
+    )
+    .await;
+    let order: Vec<&str> = rice["ranking"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["item"].as_str().unwrap())
+        .collect();
+    assert_eq!(order, ["Dark mode", "CSV export", "Single sign-on"]);
+    assert_eq!(rice["ranking"][0]["score"], 800.0);
+    assert_eq!(rice["ranking"][2]["estimate"]["effort"], 4.0);
+
+    let moscow = call(
+        &state,
+        "reasoning_prioritize",
+        json!({"items": items, "method": "moscow"}),
+    )
+    .await;
+    assert_eq!(moscow["ranking"][0]["item"], "Single sign-on");
+    assert_eq!(moscow["ranking"][2]["estimate"]["category"], "could");
+
+    let weighted = call(
+        &state,
+        "reasoning_prioritize",
+        json!({
+            "items": items,
+            "method": "weighted",
+            "criteria": [{"name": "value", "weight": 0.7}, {"name": "cost", "weight": 0.3}]
+        }),
+    )
+    .await;
+    assert_eq!(weighted["ranking"][0]["item"], "Single sign-on");
+    assert_eq!(weighted["criteria"][1]["name"], "cost");
+
+    // Revising reach moves single sign-on to the top without a pipe call
+    let revised = call(
+        &state,
+        "reasoning_prioritize_revise",
+        json!({
+            "ranking_id": id(&rice, "ranking_id"),
+            "item": "single sign-on",
+            "estimate": {"reach": 2000},
+            "reason": "Two new enterprise leads"
+        }),
+    )
+    .await;
+    assert_eq!(revised["previous_ranking_id"], rice["ranking_id"]);
+    assert_eq!(revised["session_id"], rice["session_id"]);
+    assert_eq!(revised["goal"], "Next quarter");
+    assert_eq!(revised["ranking"][0]["item"], "Single sign-on");
+    assert_eq!(revised["ranking"][0]["score"], 1350.0);
+    assert_eq!(
+        revised["moved"][0],
+        json!({"item": "Single sign-on", "from": 3, "to": 1})
+    );
+    assert_eq!(revised["moved"].as_array().unwrap().len(), 3);
+
+    let wrong_field = handle_tool_call(
+        &state,
+        "reasoning_prioritize_revise",
+        Some(json!({
+            "ranking_id": id(&rice, "ranking_id"),
+            "item": "Dark mode",
+            "estimate": {"category": "must"}
+        })),
+    )
+    .await;
+    assert!(wrong_field.is_err());
+
+    // Decisions from reasoning_make_decision cannot be revised here
+    let decision = call(
+        &state,
+        "reasoning_make_decision",
+        json!({"question": "Which?", "options": ["Option A", "Option B"]}),
+    )
+    .await;
+    let not_ranking = handle_tool_call(
+        &state,
+        "reasoning_prioritize_revise",
+        Some(json!({
+            "ranking_id": id(&decision, "decision_id"),
+            "item": "Option A",
+            "estimate": {"reach": 1}
+        })),
+    )
+    .await;
+    assert!(not_ranking.is_err());
+
+    // Items the fixture has no estimate for fail the ranking
+    let unknown = handle_tool_call(
+        &state,
+        "reasoning_prioritize",
+        Some(json!({"items": ["Dark mode", "Offline mode"], "method": "rice"})),
+    )
+    .await;
+    assert!(unknown.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_negotiate() {
+    let state = create_offline_state().await;
+
+    let perspectives = call(
+        &state,
+        "reasoning_analyze_perspectives",
+        json!({"topic": "Selling the press"}),
+    )
+    .await;
+    let session_id = id(&perspectives, "session_id");
+
+    // The session's perspective analysis is built on, and the deal math
+    // comes from the fixture's estimates
+    let result = call(
+        &state,
+        "reasoning_negotiate",
+        json!({"scenario": "Selling a used printing press", "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(result["perspective_id"], perspectives["analysis_id"]);
+    assert_eq!(
+        result["zopa"],
+        json!({"issue": "price", "lower_bound": 100000.0, "upper_bound": 120000.0, "exists": true})
+    );
+    assert_eq!(result["recommended_package"], "Bundle with support");
+    assert_eq!(result["packages"][0]["name"], "Bundle with support");
+    assert_eq!(result["packages"][1]["dominated_by"], "Bundle with support");
+    assert_eq!(result["packages"][2]["viable"], false);
+    assert_eq!(result["concession_paths"][0]["party"], "Seller");
+    assert_eq!(
+        result["concession_paths"][0]["steps"][0]["give"],
+        "Flexible delivery date"
+    );
+
+    // The analysis is stored as a perspective analysis, and a second one
+    // still builds on the stakeholder analysis rather than the negotiation
+    let second = call(
+        &state,
+        "reasoning_negotiate",
+        json!({"scenario": "Selling a used printing press", "session_id": session_id, "issue": "total price"}),
+    )
+    .await;
+    assert_eq!(second["perspective_id"], perspectives["analysis_id"]);
+    assert_eq!(second["zopa"]["issue"], "total price");
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_negotiate",
+        Some(json!({"scenario": "Selling a press", "perspective_id": "missing"})),
+    )
+    .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_risk_register() {
+    let state = create_offline_state().await;
+
+    let register = call(
+        &state,
+        "reasoning_risk",
+        json!({"subject": "Database migration"}),
+    )
+    .await;
+    let session_id = id(&register, "session_id");
+    assert_eq!(register["added"].as_array().unwrap().len(), 3);
+    // Highest priority number (likelihood × impact × detectability) first
+    assert_eq!(register["risks"][0]["title"], "Data loss during cutover");
+    assert_eq!(register["risks"][0]["priority_number"], 40);
+    assert_eq!(register["risks"][0]["level"], "high");
+    assert_eq!(register["matrix"]["cells"][3][4], 1);
+    assert_eq!(register["matrix"]["levels"]["low"], 1);
+
+    // The same risks are not added twice to the session's register
+    let again = call(
+        &state,
+        "reasoning_risk",
+        json!({"subject": "Database migration", "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(again["added"], json!([]));
+    assert_eq!(again["risks"].as_array().unwrap().len(), 3);
+
+    let risk_id = id(&register["risks"][1], "risk_id");
+
+    // A probability update sets the likelihood from its posterior locally
+    let update = call(
+        &state,
+        "reasoning_probabilistic",
+        json!({
+            "hypothesis": "The scripts overrun",
+            "prior": 0.5,
+            "evidence": [{"description": "Staging run took twice as long", "likelihood_if_true": 0.9, "likelihood_if_false": 0.3}]
+        }),
+    )
+    .await;
+    let posterior = update["posterior"].as_f64().unwrap();
+    let rescored = call(
+        &state,
+        "reasoning_risk_update",
+        json!({"risk_id": risk_id, "evidence_id": update["update_id"]}),
+    )
+    .await;
+    assert_eq!(rescored["rescore"]["source"], "probability_update");
+    assert_eq!(rescored["rescore"]["from"]["likelihood"], 3);
+    assert_eq!(
+        rescored["risk"]["likelihood"],
+        likelihood_from_probability(posterior)
+    );
+
+    // An evidence assessment is weighed by the pipe; explicit scores win
+    let assessment = call(
+        &state,
+        "reasoning_assess_evidence",
+        json!({"claim": "The scripts overrun", "evidence": [{"content": "Staging run took twice as long"}]}),
+    )
+    .await;
+    let rescored = call(
+        &state,
+        "reasoning_risk_update",
+        json!({
+            "risk_id": risk_id,
+            "evidence_id": assessment["assessment_id"],
+            "detectability": 1,
+            "owner": "Database team"
+        }),
+    )
+    .await;
+    assert_eq!(rescored["rescore"]["source"], "evidence_assessment");
+    assert_eq!(rescored["risk"]["likelihood"], 4);
+    assert_eq!(rescored["risk"]["impact"], 5);
+    assert_eq!(rescored["risk"]["detectability"], 1);
+    assert_eq!(rescored["risk"]["owner"], "Database team");
+    assert_eq!(rescored["risk"]["history"].as_array().unwrap().len(), 2);
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_risk_update",
+        Some(json!({"risk_id": risk_id, "evidence_id": "missing"})),
+    )
+    .await;
+    assert!(missing.is_err());
+    let nothing = handle_tool_call(
+        &state,
+        "reasoning_risk_update",
+        Some(json!({"risk_id": risk_id})),
+    )
+    .await;
+    assert!(nothing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_systems() {
+    let state = create_offline_state().await;
+
+    let result = call(
+        &state,
+        "reasoning_systems",
+        json!({"situation": "Delivery keeps slowing down"}),
+    )
+    .await;
+    assert_eq!(result["elements"].as_array().unwrap().len(), 5);
+    assert_eq!(result["links"].as_array().unwrap().len(), 6);
+
+    // Loops come from the links, shortest first
+    let loops = result["loops"].as_array().unwrap();
+    assert_eq!(loops.len(), 2);
+    assert_eq!(loops[0]["label"], "B1");
+    assert_eq!(loops[0]["path"], "Tech debt → Refactoring → Tech debt");
+    assert_eq!(loops[0]["delayed"], true);
+    assert_eq!(loops[1]["label"], "R1");
+    assert_eq!(loops[1]["kind"], "reinforcing");
+    assert_eq!(loops[1]["elements"].as_array().unwrap().len(), 4);
+
+    assert_eq!(result["leverage_points"][0]["name"], "Tech debt");
+    assert_eq!(result["leverage_points"][0]["loops"], 2);
+    assert_eq!(result["leverage_points"][1]["name"], "Refactoring");
+    assert!(result["leverage_points"][1]["intervention"].is_string());
+    assert!(result["mermaid"]
+        .as_str()
+        .unwrap()
+        .starts_with("flowchart LR"));
+
+    // The model is stored as a graph under the situation
+    let session_id = id(&result, "session_id");
+    let state_result = call(
+        &state,
+        "reasoning_got_state",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(state_result["total_nodes"], 6);
+    assert_eq!(state_result["total_edges"], 11);
+
+    let invalid = handle_tool_call(
+        &state,
+        "reasoning_systems",
+        Some(json!({"situation": "Traffic", "max_elements": 1})),
+    )
+    .await;
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_verify_calculations() {
+    let state = create_offline_state().await;
+
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Staffing the migration"}),
+    )
+    .await;
+    let session_id = id(&linear, "session_id");
+    let content = "Three teams of 8 make 25 people. Each works 3 hours at $40/hour, \
+                   so 25 * 3 * 40 = 3,000 dollars, and 120 * 3 = 350 tickets.";
+
+    // Explicit equations only, checked without the pipe
+    let local = call(
+        &state,
+        "reasoning_verify_calculations",
+        json!({"content": content, "session_id": session_id, "local_only": true}),
+    )
+    .await;
+    assert_eq!(local["checks"].as_array().unwrap().len(), 2);
+    assert_eq!(local["checks"][0]["status"], "correct");
+    assert_eq!(local["checks"][1]["status"], "mismatch");
+    assert_eq!(local["checks"][1]["computed"], 360.0);
+    assert_eq!(local["mismatches"], 1);
+    assert_eq!(local["detections"][0]["detection_type"], "calculation");
+    assert_eq!(local["detections"][0]["remediation"], "Use 360");
+
+    // Prose claims extracted by the pipe are recomputed locally too
+    let full = call(
+        &state,
+        "reasoning_verify_calculations",
+        json!({"content": content, "session_id": session_id}),
+    )
+    .await;
+    let checks = full["checks"].as_array().unwrap();
+    assert_eq!(checks.len(), 4);
+    assert_eq!(checks[2]["source"], "extracted");
+    assert_eq!(checks[2]["status"], "correct");
+    assert_eq!(checks[3]["expression"], "3 * 8");
+    assert_eq!(checks[3]["status"], "mismatch");
+    assert_eq!(full["mismatches"], 2);
+    assert_eq!(full["session_id"], session_id.as_str());
+
+    let invalid = handle_tool_call(
+        &state,
+        "reasoning_verify_calculations",
+        Some(json!({"content": content, "tolerance": 0.9})),
+    )
+    .await;
+    assert!(invalid.is_err());
+    let missing = handle_tool_call(&state, "reasoning_verify_calculations", Some(json!({}))).await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_code_reasoning() {
+    let state = create_offline_state().await;
+    let diff = "diff --git a/src/cache.rs b/src/cache.rs\n\
+                --- a/src/cache.rs\n\
+                +++ b/src/cache.rs\n\
+                @@ -1,2 +1,4 @@\n\
+                -    self.entries.insert(key, value);\n\
+                +    if self.entries.len() < self.capacity {\n\
+                +        self.entries.insert(key, value);\n\
+                +    }\n";
+
+    let explained = call(&state, "reasoning_code_explain_diff", json!({"diff": diff})).await;
+    assert_eq!(explained["files"][0]["path"], "src/cache.rs");
+    assert_eq!(explained["files"][0]["added"], 3);
+    assert_eq!(explained["files"][0]["removed"], 1);
+    assert_eq!(explained["changes"][0]["file"], "src/cache.rs");
+    assert_eq!(explained["findings"]["risks"][0]["severity"], 3);
+    assert_eq!(explained["new_findings"], 3);
+    let session_id = id(&explained, "session_id");
+
+    // Findings already in the session are referenced, not stored again
+    let code = "fn first(items: &[u32]) -> u32 {\n    items[0]\n}";
+    let traced = call(
+        &state,
+        "reasoning_code_trace_flow",
+        json!({"code": code, "target": "items", "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(traced["target_found"], true);
+    assert_eq!(traced["steps"][0]["operation"], "source");
+    assert_eq!(traced["steps"][1]["code"], "items[0]");
+    assert_eq!(traced["new_findings"], 0);
+    assert_eq!(
+        traced["findings"]["invariants"][0]["finding_id"],
+        explained["findings"]["invariants"][0]["finding_id"]
+    );
+
+    let edges = call(
+        &state,
+        "reasoning_code_edge_cases",
+        json!({"code": code, "session_id": session_id, "max_cases": 1}),
+    )
+    .await;
+    let cases = edges["edge_cases"].as_array().unwrap();
+    assert_eq!(cases.len(), 1);
+    assert_eq!(cases[0]["handled"], false);
+    assert_eq!(edges["unhandled"], 1);
+
+    let findings = call(
+        &state,
+        "reasoning_code_findings",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(findings["total"], 3);
+    assert_eq!(
+        findings["findings"]["suggested_tests"][0]["name"],
+        "test_insert_past_capacity"
+    );
+    let risks = call(
+        &state,
+        "reasoning_code_findings",
+        json!({"session_id": session_id, "kind": "risk"}),
+    )
+    .await;
+    assert_eq!(risks["total"], 1);
+
+    let no_changes = handle_tool_call(
+        &state,
+        "reasoning_code_explain_diff",
+        Some(json!({"diff": "no changes here"})),
+    )
+    .await;
+    assert!(no_changes.is_err());
+    let too_many = handle_tool_call(
+        &state,
+        "reasoning_code_edge_cases",
+        Some(json!({"code": code, "max_cases": 21})),
+    )
+    .await;
+    assert!(too_many.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_synthesize() {
+    let state = create_offline_state().await;
+
+    let decision = call(
+        &state,
+        "reasoning_make_decision",
+        json!({"question": "Which vendor?", "options": ["Option A", "Option B"]}),
+    )
+    .await;
+    let evidence = call(
+        &state,
+        "reasoning_assess_evidence",
+        json!({"claim": "Option A is reliable", "evidence": [{"content": "No outages in a year"}]}),
+    )
+    .await;
+    let decision_session = id(&decision, "session_id");
+    let evidence_session = id(&evidence, "session_id");
+
+    let briefing = call(
+        &state,
+        "reasoning_synthesize",
+        json!({"session_ids": [decision_session, evidence_session, decision_session]}),
+    )
+    .await;
+    let sources = briefing["sources"].as_array().unwrap();
+    assert_eq!(sources[0]["reference"], "S1");
+    assert_eq!(sources[0]["session_id"], decision_session.as_str());
+    assert_eq!(sources[0]["kind"], "decision");
+    assert!(sources
+        .iter()
+        .any(|s| s["kind"] == "evidence" && s["session_id"] == evidence_session.as_str()));
+
+    // Unknown citations are dropped, and uncited findings are flagged
+    assert_eq!(briefing["sections"][1]["sources"], json!(["S2"]));
+    let findings = briefing["key_findings"].as_array().unwrap();
+    assert_eq!(findings[0]["sources"], json!(["S1", "S2"]));
+    assert_eq!(findings[1]["unsupported"], true);
+    let combined = briefing["combined_confidence"].as_f64().unwrap();
+    assert!(combined > 0.0 && combined < 1.0);
+    let document = briefing["document"].as_str().unwrap();
+    assert!(document.starts_with("# Launch readiness"));
+    assert!(document.contains("## Sources"));
+
+    // The briefing is stored as a thought of its own session
+    let briefing_session = id(&briefing, "session_id");
+    assert_ne!(briefing_session, decision_session);
+    let thoughts = call(
+        &state,
+        "reasoning_session_thoughts",
+        json!({"session_id": briefing_session}),
+    )
+    .await;
+    assert_eq!(thoughts["thoughts"][0]["mode"], "synthesis");
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_synthesize",
+        Some(json!({"session_ids": ["no-such-session"]})),
+    )
+    .await;
+    assert!(missing.is_err());
+    let none = handle_tool_call(
+        &state,
+        "reasoning_synthesize",
+        Some(json!({"session_ids": []})),
+    )
+    .await;
+    assert!(none.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_timeline_compare() {
+    let state = create_offline_state().await;
+
+    let timeline = call(
+        &state,
+        "reasoning_timeline_create",
+        json!({"name": "Launch", "content": "Launch the product"}),
+    )
+    .await;
+    let timeline_id = id(&timeline, "timeline_id");
+    let branched = call(
+        &state,
+        "reasoning_timeline_branch",
+        json!({"timeline_id": timeline_id, "content": "Alternatives", "num_alternatives": 2}),
+    )
+    .await;
+    let branch_a = id(&branched["branches"][0], "branch_id");
+    let branch_b = id(&branched["branches"][1], "branch_id");
+
+    let report = call(
+        &state,
+        "reasoning_timeline_compare",
+        json!({
+            "timeline_a": branch_a,
+            "timeline_b": branch_b,
+            "observed_facts": ["The pilot region churned", "A competitor launched first"]
+        }),
+    )
+    .await;
+
+    // Both branches start from the timeline's initial thought
+    assert_eq!(report["shared_events"][0]["content"], "Launch the product");
+    let divergence = &report["divergence"];
+    assert_eq!(divergence["shared_prefix"], 1);
+    assert_eq!(divergence["after_event"], "Launch the product");
+    assert_eq!(divergence["fork_branch_id"], timeline["root_branch_id"]);
+    assert!(divergence["reason"].as_str().unwrap().contains("test"));
+    assert_eq!(report["unique_events_a"].as_array().unwrap().len(), 1);
+    assert!(!report["consequences_a"].as_array().unwrap().is_empty());
+
+    // The fixture's assessment of an unknown fact label is dropped
+    assert_eq!(report["fact_assessments"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        report["fact_assessments"][0]["fact"],
+        "The pilot region churned"
+    );
+    assert_eq!(report["fact_match_a"], 0.5);
+    assert_eq!(report["fact_match_b"], 0.0);
+    assert_eq!(report["better_match"], "a");
+
+    // A timeline stands for its active branch, the root
+    let against_root = call(
+        &state,
+        "reasoning_timeline_compare",
+        json!({"timeline_a": timeline_id, "timeline_b": branch_b}),
+    )
+    .await;
+    assert_eq!(against_root["branch_a"], timeline["root_branch_id"]);
+    assert!(against_root["unique_events_a"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+    assert!(against_root["better_match"].is_null());
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_timeline_compare",
+        Some(json!({"timeline_a": "no-such-branch", "timeline_b": branch_b})),
+    )
+    .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_counterfactual_minimal_change() {
+    let state = create_offline_state().await;
+
+    let timeline = call(
+        &state,
+        "reasoning_timeline_create",
+        json!({"name": "Launch", "content": "The launch slipped because the load test was skipped"}),
+    )
+    .await;
+    let branch_id = id(&timeline, "root_branch_id");
+
+    let search = call(
+        &state,
+        "reasoning_counterfactual_minimal_change",
+        json!({"branch_id": branch_id, "target_outcome": "The launch ships on time"}),
+    )
+    .await;
+    assert_eq!(search["outcome"], "The launch slipped by a quarter.");
+    // The fixture's duplicate proposal is dropped; both others are tested
+    assert_eq!(search["candidates"].as_array().unwrap().len(), 2);
+    assert_eq!(search["tests"].as_array().unwrap().len(), 2);
+    assert_eq!(search["rounds"], 1);
+
+    // Every single change flips the outcome offline, so the first one wins
+    assert_eq!(search["found"], true);
+    assert_eq!(search["minimal"], true);
+    let change_set = search["change_set"].as_array().unwrap();
+    assert_eq!(change_set.len(), 1);
+    assert_eq!(change_set[0]["antecedent"], "Release checklist");
+    assert_eq!(search["confidence"], 0.7);
+    assert!(search["reasoning"].as_str().unwrap().contains("load test"));
+
+    // The counterfactual is stored on a branch of the analyzed one
+    let cf_branch = id(&search, "counterfactual_branch_id");
+    let thoughts = call(
+        &state,
+        "reasoning_session_thoughts",
+        json!({"session_id": id(&timeline, "session_id")}),
+    )
+    .await;
+    assert!(thoughts["thoughts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|t| t["branch_id"] == cf_branch.as_str() && t["mode"] == "counterfactual"));
+
+    let missing = handle_tool_call(
+        &state,
+        "reasoning_counterfactual_minimal_change",
+        Some(json!({"branch_id": "no-such-branch"})),
+    )
+    .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_mcts_progressive_widening() {
+    use mcp_langbase_reasoning::storage::MctsIterationFilter;
+
+    // The expansion width comes from the config, the rest from the call
+    let mut config = offline_config();
+    config.mcts.expansion_width = 2;
+    let state = create_state(config).await;
+
+    let result = call(
+        &state,
+        "reasoning_mcts_explore",
+        json!({
+            "content": "Pricing strategy",
+            "iterations": 3,
+            "policy": "progressive_widening",
+            "rollout_depth": 1
+        }),
+    )
+    .await;
+    assert_eq!(result["settings"]["policy"], "progressive_widening");
+    assert_eq!(result["settings"]["expansion_width"], 2);
+    assert_eq!(result["settings"]["rollout_depth"], 1);
+
+    // The root gains a second child only once it has two visits, so the
+    // second iteration descends into its first child instead
+    let stats = result["iteration_stats"].as_array().unwrap();
+    let depths: Vec<u64> = stats
+        .iter()
+        .map(|s| s["selected_depth"].as_u64().unwrap())
+        .collect();
+    assert_eq!(depths, [0, 1, 0]);
+    assert!(stats.iter().all(|s| s["children_created"] == 1));
+    // Expansion, one rollout step and the evaluation
+    assert!(stats.iter().all(|s| s["pipe_calls"] == 3));
+    assert_eq!(result["pipe_calls"], 9);
+    assert_eq!(result["nodes_explored"], 4);
+    assert_eq!(stats[2]["nodes_total"], 4);
+
+    // Every iteration is stored for the metrics CLI
+    let session_id = id(&result, "session_id");
+    let stored = state
+        .storage
+        .get_mcts_iterations(MctsIterationFilter::new().in_session(&session_id))
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 3);
+    assert!(stored
+        .iter()
+        .all(|i| i.root_node_id == result["root_node_id"] && i.expansion_width == 2));
+    assert_eq!(stored[1].selected_depth, 1);
+
+    // With UCB1 the root is expanded to the full width at once
+    let result = call(
+        &state,
+        "reasoning_mcts_explore",
+        json!({"content": "Pricing strategy", "iterations": 1, "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(result["settings"]["policy"], "ucb1");
+    assert_eq!(result["iteration_stats"][0]["children_created"], 2);
+    assert_eq!(result["pipe_calls"], 2);
+}
+
+#[tokio::test]
+async fn test_offline_auto_routing_learns_from_outcomes() {
+    let state = create_offline_state().await;
+    let content = "Work out how many piano tuners a mid-sized city needs each year";
+
+    let linear = call(&state, "reasoning_linear", json!({"content": content})).await;
+    let session_id = id(&linear, "session_id");
+
+    // The first decision has no similar history, but is recorded
+    let first = call(
+        &state,
+        "reasoning_auto",
+        json!({"content": content, "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(first["recommended_mode"], "linear");
+    assert!(first.get("history").is_none());
+    let routing_id = id(&first, "routing_id");
+
+    // Feedback is combined with the session's thought confidence (0.8)
+    let rated = call(
+        &state,
+        "reasoning_routing_feedback",
+        json!({"routing_id": routing_id, "score": 0.2, "note": "Too shallow"}),
+    )
+    .await;
+    assert_eq!(rated["outcome_mode"], "linear");
+    assert_eq!(rated["outcome_confidence"], 0.8);
+    assert_eq!(rated["detection_count"], 0);
+    let outcome = rated["outcome_score"].as_f64().unwrap();
+    assert!((outcome - 0.5).abs() < 1e-9);
+
+    // Routing the same content again weighs that outcome: one identical
+    // decision gives history a sixth of the confidence
+    let second = call(
+        &state,
+        "reasoning_auto",
+        json!({"content": content, "session_id": session_id}),
+    )
+    .await;
+    assert_eq!(second["recommended_mode"], "linear");
+    let confidence = second["confidence"].as_f64().unwrap();
+    assert!(
+        (confidence - 0.75).abs() < 1e-9,
+        "confidence {}",
+        confidence
+    );
+    let history = &second["history"];
+    assert_eq!(history["similar_decisions"], 1);
+    assert_eq!(history["applied"], false);
+    assert_eq!(history["modes"][0]["mode"], "linear");
+    assert_eq!(history["modes"][0]["decisions"], 1);
+    assert_ne!(id(&second, "routing_id"), routing_id);
+
+    // Without history the router's confidence is returned unchanged
+    let unweighed = call(
+        &state,
+        "reasoning_auto",
+        json!({"content": content, "use_history": false}),
+    )
+    .await;
+    assert_eq!(unweighed["confidence"], 0.8);
+    assert!(unweighed.get("history").is_none());
+
+    // A session's latest decision can be rated by session ID
+    let by_session = call(
+        &state,
+        "reasoning_routing_feedback",
+        json!({"session_id": session_id, "score": 1.0}),
+    )
+    .await;
+    assert_eq!(by_session["routing_id"], second["routing_id"]);
+    assert_eq!(by_session["outcome_score"], 0.9);
+
+    for arguments in [
+        json!({"routing_id": routing_id, "score": 1.5}),
+        json!({"score": 0.5}),
+        json!({"routing_id": "missing", "score": 0.5}),
+    ] {
+        assert!(
+            handle_tool_call(&state, "reasoning_routing_feedback", Some(arguments))
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_got_pairwise_rank() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Pick a caching strategy"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 3}),
+    )
+    .await;
+    let children: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+    assert_eq!(children.len(), 3);
+
+    // The fixture judge always prefers the first node shown, so one round
+    // ranks the children in generation order
+    let ranked = call(
+        &state,
+        "reasoning_got_rank",
+        json!({"session_id": session_id, "parent_id": root}),
+    )
+    .await;
+    assert_eq!(ranked["method"], "bradley_terry");
+    assert_eq!(ranked["comparisons"].as_array().unwrap().len(), 3);
+    assert_eq!(ranked["comparisons_used"], 3);
+    assert_eq!(ranked["failed_comparisons"], 0);
+    let rankings = ranked["rankings"].as_array().unwrap();
+    let order: Vec<String> = rankings.iter().map(|r| id(r, "node_id")).collect();
+    assert_eq!(order, children);
+    assert_eq!(rankings[0]["wins"], 2);
+    assert_eq!(rankings[2]["losses"], 2);
+    assert!((rankings[1]["score"].as_f64().unwrap() - 0.5).abs() < 1e-6);
+    assert!(rankings[0]["previous_score"].is_number());
+
+    // Scores are stored on the nodes
+    let graph = call(
+        &state,
+        "reasoning_got_state",
+        json!({"session_id": session_id, "limit": 10}),
+    )
+    .await;
+    let best = graph["nodes"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["id"] == children[0])
+        .unwrap();
+    assert_eq!(best["score"], rankings[0]["score"]);
+    assert_eq!(best["metadata"]["pairwise"]["rank"], 1);
+
+    // Two more rounds show each pair both ways and build on the first call
+    let ranked = call(
+        &state,
+        "reasoning_got_rank",
+        json!({"session_id": session_id, "node_ids": children, "method": "elo", "rounds": 2}),
+    )
+    .await;
+    assert_eq!(ranked["comparisons"].as_array().unwrap().len(), 6);
+    assert_eq!(ranked["comparisons_used"], 9);
+    assert_eq!(ranked["rankings"][0]["node_id"], children[0].as_str());
+
+    for arguments in [
+        json!({"session_id": session_id}),
+        json!({"session_id": session_id, "node_ids": [children[0]]}),
+        json!({"session_id": session_id, "parent_id": root, "rounds": 4}),
+        json!({"session_id": session_id, "parent_id": "missing"}),
+    ] {
+        assert!(
+            handle_tool_call(&state, "reasoning_got_rank", Some(arguments.clone()))
+                .await
+                .is_err(),
+            "{}",
+            arguments
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_got_aggregate_best() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Reduce support tickets"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": id(&init, "root_node_id"), "k": 3}),
+    )
+    .await;
+    let children: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+
+    // Continuations are scored 0.75, 0.65 and 0.7 by the fixture
+    let result = call(
+        &state,
+        "reasoning_got_aggregate_best",
+        json!({"session_id": session_id, "k": 2, "mark_terminal": true}),
+    )
+    .await;
+    let selected: Vec<String> = result["selected"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| id(s, "node_id"))
+        .collect();
+    assert_eq!(selected, vec![children[0].clone(), children[2].clone()]);
+    assert_eq!(result["skipped_count"], 1);
+    assert_eq!(result["is_terminal"], true);
+    let aggregated = id(&result["aggregation"], "aggregated_node_id");
+    assert_eq!(result["aggregation"]["source_nodes"], json!(selected));
+
+    let graph = call(
+        &state,
+        "reasoning_got_state",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(graph["terminal_node_ids"], json!([aggregated]));
+    assert_eq!(graph["active_node_ids"], json!([children[1]]));
+
+    // Only one active node is left to merge
+    for arguments in [
+        json!({"session_id": session_id}),
+        json!({"session_id": session_id, "k": 1}),
+        json!({"session_id": session_id, "min_score": 1.5}),
+    ] {
+        assert!(
+            handle_tool_call(
+                &state,
+                "reasoning_got_aggregate_best",
+                Some(arguments.clone())
+            )
+            .await
+            .is_err(),
+            "{}",
+            arguments
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_got_validate() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Reduce support tickets"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 3}),
+    )
+    .await;
+    let child = id(&generated["continuations"][0], "node_id");
+
+    let report = call(
+        &state,
+        "reasoning_got_validate",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(report["is_valid"], true);
+    assert_eq!(report["is_dag"], true);
+    assert_eq!(report["node_count"], 4);
+    assert_eq!(report["edge_count"], 3);
+    assert_eq!(report["issues"], json!([]));
+
+    // A back edge written straight to storage closes a cycle
+    state
+        .storage
+        .create_graph_edge(&GraphEdge::new(&session_id, &child, &root))
+        .await
+        .unwrap();
+    let report = call(
+        &state,
+        "reasoning_got_validate",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(report["is_valid"], false);
+    assert_eq!(report["is_dag"], false);
+    assert_eq!(report["issues"][0]["kind"], "cycle");
+    assert_eq!(report["issues"][0]["severity"], "error");
+    let mut cycle = vec![root, child];
+    cycle.sort();
+    assert_eq!(report["issues"][0]["node_ids"], json!(cycle));
+
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_got_validate",
+        Some(json!({"session_id": "no-such-session"}))
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_got_best_path() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Reduce support tickets"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 3}),
+    )
+    .await;
+    let children: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+
+    // Nothing is terminal yet, so the best leaf (scored 0.75) ends the path
+    let path = call(
+        &state,
+        "reasoning_got_best_path",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(path["root_node_id"], json!(root));
+    assert_eq!(path["end_node_id"], json!(children[0]));
+    assert_eq!(path["reached_terminal"], false);
+    assert_eq!(path["steps"].as_array().unwrap().len(), 2);
+    assert_eq!(path["steps"][1]["via"], "generates");
+    assert_eq!(path["total_score"], 0.75);
+    assert!(path["explanation"]
+        .as_str()
+        .unwrap()
+        .starts_with("1. [root] Reduce support tickets"));
+
+    call(
+        &state,
+        "reasoning_got_finalize",
+        json!({"session_id": session_id, "terminal_node_ids": [children[2]]}),
+    )
+    .await;
+    let path = call(
+        &state,
+        "reasoning_got_best_path",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(path["end_node_id"], json!(children[2]));
+    assert_eq!(path["reached_terminal"], true);
+
+    let path = call(
+        &state,
+        "reasoning_got_best_path",
+        json!({"session_id": session_id, "terminal_node_id": children[1]}),
+    )
+    .await;
+    assert_eq!(path["end_node_id"], json!(children[1]));
+
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_got_best_path",
+        Some(json!({"session_id": session_id, "terminal_node_id": "missing"}))
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_got_resume() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Reduce support tickets", "config": {"max_nodes": 10, "default_k": 2}}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 3}),
+    )
+    .await;
+    let children: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+
+    // Simulate a generate cut short before it marked its source expanded
+    let mut stale = state.storage.get_graph_node(&root).await.unwrap().unwrap();
+    stale.is_active = true;
+    state.storage.update_graph_node(&stale).await.unwrap();
+
+    // Continuations are scored 0.75, 0.65 and 0.7 by the fixture, so the
+    // best two are expanded. The fixture repeats its continuations, which
+    // are dropped as duplicates of the existing children.
+    let resumed = call(
+        &state,
+        "reasoning_got_resume",
+        json!({"session_id": session_id, "expand": 2}),
+    )
+    .await;
+    assert_eq!(resumed["repaired_node_ids"], json!([root]));
+    assert_eq!(resumed["config"]["max_nodes"], 10);
+    assert_eq!(resumed["config"]["default_k"], 2);
+    let expanded: Vec<String> = resumed["expanded"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| id(e, "source_node_id"))
+        .collect();
+    assert_eq!(expanded, vec![children[0].clone(), children[2].clone()]);
+    assert_eq!(resumed["expanded"][0]["duplicates_skipped"], 2);
+    assert_eq!(resumed["node_count"], 4);
+    assert_eq!(resumed["remaining_nodes"], 6);
+    assert_eq!(resumed["frontier"][0]["node_id"], json!(children[1]));
+    assert_eq!(resumed["frontier"].as_array().unwrap().len(), 1);
+
+    // Limits stored at init outlive the call that set them
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Pick a database", "config": {"max_depth": 1}}),
+    )
+    .await;
+    let shallow = id(&init, "session_id");
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": shallow, "node_id": id(&init, "root_node_id")}),
+    )
+    .await;
+    let resumed = call(
+        &state,
+        "reasoning_got_resume",
+        json!({"session_id": shallow}),
+    )
+    .await;
+    assert_eq!(resumed["frontier"], json!([]));
+    assert_eq!(resumed["expanded"], json!([]));
+    assert_eq!(resumed["stopped_reason"], "Only 0 frontier nodes");
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_got_generate",
+        Some(json!({
+            "session_id": shallow,
+            "node_id": id(&generated["continuations"][0], "node_id")
+        }))
+    )
+    .await
+    .is_err());
+
+    for arguments in [
+        json!({"session_id": "no-such-session"}),
+        json!({"session_id": session_id, "expand": 6}),
+        json!({"session_id": session_id, "k": 0}),
+    ] {
+        assert!(
+            handle_tool_call(&state, "reasoning_got_resume", Some(arguments.clone()))
+                .await
+                .is_err(),
+            "{}",
+            arguments
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_offline_got_generate_replaces_weaker_duplicate() {
+    let state = create_offline_state().await;
+
+    let init = call(
+        &state,
+        "reasoning_got_init",
+        json!({"content": "Improve onboarding"}),
+    )
+    .await;
+    let session_id = id(&init, "session_id");
+    let root = id(&init, "root_node_id");
+
+    let generated = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": root, "k": 3}),
+    )
+    .await;
+    let nodes: Vec<String> = generated["continuations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| id(c, "node_id"))
+        .collect();
+    assert_eq!(nodes.len(), 3);
+
+    // Lower the first child below the 0.75 the fixture gives its paraphrase
+    let mut weak = state
+        .storage
+        .get_graph_node(&nodes[0])
+        .await
+        .unwrap()
+        .unwrap();
+    weak.score = Some(0.2);
+    state.storage.update_graph_node(&weak).await.unwrap();
+
+    // Expanding the second child repeats all three continuations: the weak
+    // node is replaced, the source and the 0.7 sibling are kept
+    let expanded = call(
+        &state,
+        "reasoning_got_generate",
+        json!({"session_id": session_id, "node_id": nodes[1], "k": 3}),
+    )
+    .await;
+    let continuations = expanded["continuations"].as_array().unwrap();
+    assert_eq!(continuations.len(), 1);
+    assert_eq!(expanded["duplicates_skipped"], 2);
+    assert_eq!(continuations[0]["replaces_node_id"], nodes[0]);
+    let replacement = id(&continuations[0], "node_id");
+
+    let replaced = state
+        .storage
+        .get_graph_node(&nodes[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!replaced.is_active);
+    let edges = state.storage.get_session_edges(&session_id).await.unwrap();
+    assert!(edges.iter().any(|e| e.from_node == nodes[0]
+        && e.to_node == replacement
+        && e.edge_type.to_string() == "refines"));
+}
+
+#[tokio::test]
+async fn test_offline_tree_compare_branches() {
+    let state = create_offline_state().await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    let branches: Vec<String> = tree["child_branches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| id(b, "id"))
+        .collect();
+
+    // The fixture scores B1 and B2 on the default criteria and recommends B1
+    let compared = call(
+        &state,
+        "reasoning_tree_compare",
+        json!({"session_id": session_id, "branch_ids": [branches[0], branches[1]]}),
+    )
+    .await;
+    assert_eq!(compared["recommended_branch_id"], branches[0]);
+    assert_eq!(compared["criteria"].as_array().unwrap().len(), 3);
+    let assessed = compared["branches"].as_array().unwrap();
+    assert_eq!(assessed[0]["thought_count"], 2);
+    assert_eq!(assessed[0]["scores"].as_array().unwrap().len(), 3);
+    assert!(assessed[0]["overall_score"].as_f64().unwrap() > 0.7);
+    assert_eq!(compared["cross_refs_created"], 1);
+
+    let refs = state
+        .storage
+        .get_cross_refs_from(&branches[0])
+        .await
+        .unwrap();
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].to_branch_id, branches[1]);
+    let thoughts = state
+        .storage
+        .get_branch_thoughts(&branches[0])
+        .await
+        .unwrap();
+    assert!(thoughts
+        .iter()
+        .any(|t| t.id == id(&compared, "recommendation_thought_id")));
+
+    // A branch from another session is rejected
+    let other = call(&state, "reasoning_tree", json!({"content": "Other"})).await;
+    let foreign = id(&other["child_branches"][0], "id");
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_tree_compare",
+        Some(json!({"session_id": session_id, "branch_ids": [branches[0], foreign]})),
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_tree_merge_branches() {
+    let state = create_offline_state().await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    let root = id(&tree, "branch_id");
+    let branches: Vec<String> = tree["child_branches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| id(b, "id"))
+        .collect();
+
+    let merged = call(
+        &state,
+        "reasoning_tree_merge",
+        json!({"session_id": session_id, "branch_a": branches[0], "branch_b": branches[1]}),
+    )
+    .await;
+    let merged_id = id(&merged, "branch_id");
+    assert_eq!(merged["parent_branch"], root);
+    assert_eq!(merged["resolved_conflicts"].as_array().unwrap().len(), 1);
+    assert_eq!(merged["cross_refs_created"], 2);
+
+    let refs = state.storage.get_cross_refs_from(&merged_id).await.unwrap();
+    assert_eq!(refs.len(), 2);
+    assert!(refs.iter().all(|r| r.ref_type.to_string() == "extends"));
+    for source in &branches[..2] {
+        let branch = state.storage.get_branch(source).await.unwrap().unwrap();
+        assert_eq!(branch.state.to_string(), "completed");
+    }
+    let thoughts = state.storage.get_branch_thoughts(&merged_id).await.unwrap();
+    assert_eq!(thoughts.len(), 1);
+    assert_eq!(thoughts[0].id, id(&merged, "thought_id"));
+
+    // A branch cannot be merged with itself
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_tree_merge",
+        Some(json!({"session_id": session_id, "branch_a": branches[2], "branch_b": branches[2]})),
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_tree_export() {
+    let state = create_offline_state().await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    let abandoned = id(&tree["child_branches"][1], "id");
+    call(
+        &state,
+        "reasoning_tree_complete",
+        json!({"branch_id": abandoned, "completed": false}),
+    )
+    .await;
+
+    let ascii = call(
+        &state,
+        "reasoning_tree_export",
+        json!({"session_id": session_id, "format": "ascii"}),
+    )
+    .await;
+    assert_eq!(ascii["format"], "ascii");
+    assert_eq!(ascii["branch_count"], 4);
+    assert_eq!(ascii["thought_count"], 4);
+    let content = ascii["content"].as_str().unwrap();
+    assert!(content.starts_with("Root (1 thought, active, confidence"));
+    assert_eq!(content.matches("├── ").count(), 2);
+    assert_eq!(content.matches("└── ").count(), 1);
+    assert!(content.contains("abandoned"));
+
+    let mermaid = call(
+        &state,
+        "reasoning_tree_export",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(mermaid["format"], "mermaid");
+    let content = mermaid["content"].as_str().unwrap();
+    assert!(content.starts_with("flowchart TD\n"));
+    assert_eq!(content.matches(" --> ").count(), 3);
+    assert_eq!(content.matches(":::abandoned").count(), 1);
+
+    // A session without branches has nothing to export
+    let linear = call(&state, "reasoning_linear", json!({"content": "Step one"})).await;
+    assert!(handle_tool_call(
+        &state,
+        "reasoning_tree_export",
+        Some(json!({"session_id": id(&linear, "session_id")})),
+    )
+    .await
+    .is_err());
+}
+
+#[tokio::test]
+async fn test_offline_critique() {
+    let state = create_offline_state().await;
+
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({
+            "content": "Sales rose after the redesign, so the redesign caused it",
+            "critique": {"revise": true}
+        }),
+    )
+    .await;
+    let session_id = id(&linear, "session_id");
+    let critique = &linear["critique"];
+    assert_eq!(critique["session_id"], session_id.as_str());
+    assert!(critique["reflection_thought_id"].is_string());
+    assert!(!critique["weaknesses"].as_array().unwrap().is_empty());
+    // The offline fixtures rate the result good enough and find no biases
+    // or fallacies, so no revision round runs
+    assert_eq!(critique["quality_score"], 0.8);
+    assert!(critique["detections"].as_array().unwrap().is_empty());
+    assert_eq!(critique["needs_revision"], false);
+    assert!(critique.get("revision").is_none());
+    assert!(critique.get("errors").is_none());
+
+    // The review is stored in the reviewed session
+    let thoughts = call(
+        &state,
+        "reasoning_session_thoughts",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    let modes: Vec<&str> = thoughts["thoughts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|t| t["mode"].as_str())
+        .collect();
+    assert!(modes.contains(&"linear"));
+    assert!(modes.contains(&"reflection"));
+
+    // Without the option there is no review
+    let plain = call(&state, "reasoning_linear", json!({"content": "Plain"})).await;
+    assert!(plain.get("critique").is_none());
+
+    let unsupported = handle_tool_call(
+        &state,
+        "reasoning_reflection",
+        Some(json!({"content": "x", "critique": true})),
+    )
+    .await;
+    assert!(unsupported.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_server_status() {
+    use mcp_langbase_reasoning::server::server_status;
+
+    let state = create_offline_state().await;
+    let status = call(&state, "reasoning_server_status", json!({})).await;
+    assert_eq!(status["ready"], true, "{}", status);
+    assert_eq!(status["transport"], "stdio");
+    let checks: Vec<&str> = status["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| check["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(checks, ["database", "migrations", "langbase"]);
+
+    // Langbase is unroutable in the offline config, so serving pipes from it
+    // leaves the server unready
+    let mut config = offline_config();
+    config.providers.default = ProviderKind::Langbase;
+    let state = create_state(config).await;
+    let status = server_status(&state).await;
+    assert!(!status.ready);
+    let langbase = status.checks.iter().find(|c| c.name == "langbase").unwrap();
+    assert!(!langbase.ok);
+    assert!(langbase.detail.is_some());
+    assert!(status
+        .checks
+        .iter()
+        .filter(|c| c.name != "langbase")
+        .all(|c| c.ok));
+}
+
+#[tokio::test]
+async fn test_offline_health_probes_over_websocket() {
+    use mcp_langbase_reasoning::config::{ApiKey, AuthScope};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut config = offline_config();
+    // Probes need no key
+    config.transport.api_keys = vec![ApiKey {
+        key: "admin".to_string(),
+        scope: AuthScope::Full,
+    }];
+    let state = create_state(config).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let probe = |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    };
+
+    let health = probe("/healthz").await;
+    assert!(health.starts_with("HTTP/1.1 200 OK\r\n"), "{}", health);
+    assert!(health.ends_with(r#"{"status":"ok"}"#));
+
+    let ready = probe("/readyz").await;
+    assert!(ready.starts_with("HTTP/1.1 200 OK\r\n"), "{}", ready);
+    let body: Value = serde_json::from_str(ready.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["transport"], "stdio");
+
+    // Other paths are still WebSocket handshakes, and need a key
+    let refused = tokio_tungstenite::connect_async(format!("ws://{}", addr)).await;
+    assert!(refused.is_err());
+}
+
+#[tokio::test]
+async fn test_offline_session_subscriptions_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = create_offline_state().await;
+    let session_id = id(
+        &call(
+            &state,
+            "reasoning_linear",
+            json!({"content": "Plan a release"}),
+        )
+        .await,
+        "session_id",
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let url = format!("ws://{}", addr);
+    let (mut watcher, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let (mut writer, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+    async fn next_json(
+        socket: &mut tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+    ) -> Value {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    let uri = session_uri(&session_id);
+    watcher
+        .send(request(1, "resources/subscribe", json!({"uri": uri})))
+        .await
+        .unwrap();
+    assert!(next_json(&mut watcher).await["error"].is_null());
+    watcher
+        .send(request(
+            2,
+            "resources/subscribe",
+            json!({"uri": session_uri("missing")}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(next_json(&mut watcher).await["error"]["code"], -32002);
+
+    // Another client's checkpoint is reported to the watcher
+    let checkpoint = json!({
+        "name": "reasoning_checkpoint_create",
+        "arguments": {"session_id": session_id, "name": "before"}
+    });
+    writer
+        .send(request(1, "tools/call", checkpoint))
+        .await
+        .unwrap();
+    assert!(next_json(&mut writer).await["result"]["isError"].is_null());
+    let notification = next_json(&mut watcher).await;
+    assert_eq!(notification["method"], "notifications/resources/updated");
+    assert_eq!(notification["params"]["uri"], uri);
+
+    // Changes after unsubscribing are not
+    watcher
+        .send(request(3, "resources/unsubscribe", json!({"uri": uri})))
+        .await
+        .unwrap();
+    assert!(next_json(&mut watcher).await["error"].is_null());
+    let thought = json!({
+        "name": "reasoning_linear",
+        "arguments": {"session_id": session_id, "content": "Ship it"}
+    });
+    writer
+        .send(request(2, "tools/call", thought))
+        .await
+        .unwrap();
+    next_json(&mut writer).await;
+    watcher.send(request(4, "ping", json!({}))).await.unwrap();
+    assert_eq!(next_json(&mut watcher).await["id"], 4);
+}
+
+#[tokio::test]
+async fn test_offline_batch_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = create_offline_state().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |id: u64, method: &str, params: Value| json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+    let batch = json!([
+        request(
+            1,
+            "tools/call",
+            json!({"name": "reasoning_linear", "arguments": {"content": "Plan a release"}}),
+        ),
+        request(
+            2,
+            "tools/call",
+            json!({"name": "reasoning_session_list", "arguments": {}}),
+        ),
+        request(3, "ping", json!({})),
+        {"jsonrpc": "2.0", "method": "initialized"},
+        {"jsonrpc": "2.0", "id": 4},
+        request(5, "tools/list", json!({})),
+    ]);
+    socket.send(Message::Text(batch.to_string())).await.unwrap();
+    socket.send(Message::Text("[]".to_string())).await.unwrap();
+
+    let mut messages = Vec::new();
+    while messages.len() < 2 {
+        if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+            messages.push(serde_json::from_str::<Value>(&text).unwrap());
+        }
+    }
+
+    // One response per request, in request order; none for the notification
+    let responses = messages[0].as_array().unwrap();
+    let ids: Vec<&Value> = responses.iter().map(|r| &r["id"]).collect();
+    assert_eq!(ids, [&json!(1), &json!(2), &json!(3), &json!(4), &json!(5)]);
+    assert!(responses[0]["result"]["isError"].is_null());
+    // The session list ran after the linear call that created a session
+    assert_eq!(
+        responses[1]["result"]["structuredContent"]["sessions"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(responses[3]["error"]["code"], -32600);
+    assert!(responses[4]["result"]["tools"].is_array());
+
+    assert_eq!(messages[1]["error"]["code"], -32600);
+}
+
+#[tokio::test]
+async fn test_offline_argument_completion() {
+    use mcp_langbase_reasoning::server::CompleteParams;
+
+    let state = create_offline_state().await;
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    let branch_id = id(&tree, "branch_id");
+    let checkpoint = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": session_id, "name": "before"}),
+    )
+    .await;
+    call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+
+    let completion = |argument: &str, value: &str, context: Value| {
+        let params: CompleteParams = serde_json::from_value(json!({
+            "ref": {"type": "ref/prompt", "name": "reasoning_backtrack"},
+            "argument": {"name": argument, "value": value},
+            "context": {"arguments": context}
+        }))
+        .unwrap();
+        let state = state.clone();
+        async move { complete(&state, &params).await.unwrap() }
+    };
+
+    let sessions = completion("session_id", "", json!({})).await;
+    assert_eq!(sessions.total, 2);
+    let sessions = completion("session_id", &session_id[..8], json!({})).await;
+    assert_eq!(sessions.values, vec![session_id.clone()]);
+
+    let branches = completion("branch_id", "", json!({"session_id": session_id})).await;
+    assert!(branches.values.contains(&branch_id));
+    let checkpoints = completion("checkpoint_id", "", json!({"session_id": session_id})).await;
+    assert_eq!(checkpoints.values, vec![id(&checkpoint, "id")]);
+    // Without the session chosen there is nothing to offer
+    assert!(completion("checkpoint_id", "", json!({}))
+        .await
+        .values
+        .is_empty());
+
+    let presets = completion("preset_id", "", json!({})).await;
+    assert!(!presets.values.is_empty());
+    assert_eq!(
+        completion("mode", "co", json!({})).await.values,
+        ["counterfactual", "code"]
+    );
+    assert_eq!(
+        completion("format", "g", json!({})).await.values,
+        ["graphml"]
+    );
+}
+
+#[tokio::test]
+async fn test_offline_list_cursors() {
+    let state = create_offline_state().await;
+
+    let tree = call(
+        &state,
+        "reasoning_tree",
+        json!({"content": "Choose a database"}),
+    )
+    .await;
+    let session_id = id(&tree, "session_id");
+    call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+    for name in ["first", "second"] {
+        call(
+            &state,
+            "reasoning_checkpoint_create",
+            json!({"session_id": session_id, "name": name}),
+        )
+        .await;
+    }
+
+    let first = call(&state, "reasoning_session_list", json!({"limit": 1})).await;
+    assert_eq!(first["count"], 1);
+    let cursor = id(&first, "next_cursor");
+    let second = call(
+        &state,
+        "reasoning_session_list",
+        json!({"limit": 1, "cursor": cursor}),
+    )
+    .await;
+    assert_eq!(second["count"], 1);
+    assert!(second["next_cursor"].is_null());
+    assert_ne!(
+        first["sessions"][0]["session_id"],
+        second["sessions"][0]["session_id"]
+    );
+
+    let branches = call(
+        &state,
+        "reasoning_tree_list",
+        json!({"session_id": session_id, "limit": 1}),
+    )
+    .await;
+    assert_eq!(branches["branches"].as_array().unwrap().len(), 1);
+    assert_eq!(branches["has_more"], true);
+
+    let checkpoints = call(
+        &state,
+        "reasoning_checkpoint_list",
+        json!({"session_id": session_id, "limit": 1}),
+    )
+    .await;
+    let rest = call(
+        &state,
+        "reasoning_checkpoint_list",
+        json!({"session_id": session_id, "cursor": id(&checkpoints, "next_cursor")}),
+    )
+    .await;
+    assert_eq!(rest["count"], 1);
+    assert_ne!(
+        checkpoints["checkpoints"][0]["id"],
+        rest["checkpoints"][0]["id"]
+    );
+
+    let err = handle_tool_call(
+        &state,
+        "reasoning_session_list",
+        Some(json!({"cursor": "bogus"})),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("Invalid cursor"), "{}", err);
+
+    // A zero limit is treated as 1, so following cursors ends
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let page = call(
+            &state,
+            "reasoning_checkpoint_list",
+            json!({"session_id": session_id, "limit": 0, "cursor": cursor}),
+        )
+        .await;
+        assert_eq!(page["count"], 1);
+        pages += 1;
+        assert!(pages <= 2, "cursor did not advance");
+        match page["next_cursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 2);
+
+    // Without paging arguments the listings keep their array shape
+    let branches = call(
+        &state,
+        "reasoning_tree_list",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert!(!branches.as_array().unwrap().is_empty());
+    let checkpoints = call(
+        &state,
+        "reasoning_checkpoint_list",
+        json!({"session_id": session_id}),
+    )
+    .await;
+    assert_eq!(checkpoints.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_offline_tools_list_pages_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::config::ToolsConfig;
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut config = offline_config();
+    config.tools = ToolsConfig {
+        enabled: vec!["core".to_string()],
+        page_size: Some(5),
+        ..Default::default()
+    };
+    let state = create_state(config).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list", "params": params});
+        Message::Text(message.to_string())
+    };
+
+    let mut pages = Vec::new();
+    let mut params = json!({});
+    let invalid = loop {
+        socket.send(request(params)).await.unwrap();
+        let response = loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                break serde_json::from_str::<Value>(&text).unwrap();
+            }
+        };
+        if response["error"].is_object() {
+            break response;
+        }
+        pages.push(response["result"]["tools"].as_array().unwrap().len());
+        params = match response["result"]["nextCursor"].as_str() {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({"cursor": "bogus"}),
+        };
+    };
+
+    assert_eq!(pages, vec![5, 5, 4]);
+    assert_eq!(invalid["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn test_offline_client_logging_over_websocket() {
+    use futures::{SinkExt, StreamExt};
+    use mcp_langbase_reasoning::server::McpServer;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let state = create_offline_state().await;
+    let linear = call(
+        &state,
+        "reasoning_linear",
+        json!({"content": "Plan a release"}),
+    )
+    .await;
+    let session_id = id(&linear, "session_id");
+    let checkpoint = call(
+        &state,
+        "reasoning_checkpoint_create",
+        json!({"session_id": session_id, "name": "before"}),
+    )
+    .await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = McpServer::new(state);
+    tokio::spawn(async move { server.serve_ws(listener).await });
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+        .await
+        .unwrap();
+    let request = |id: u64, method: &str, params: Value| {
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        Message::Text(message.to_string())
+    };
+    socket
+        .send(request(1, "logging/setLevel", json!({"level": "verbose"})))
+        .await
+        .unwrap();
+    socket
+        .send(request(2, "logging/setLevel", json!({"level": "notice"})))
+        .await
+        .unwrap();
+    socket
+        .send(request(
+            3,
+            "tools/call",
+            json!({
+                "name": "reasoning_backtrack",
+                "arguments": {"checkpoint_id": id(&checkpoint, "id"), "session_id": session_id}
+            }),
+        ))
+        .await
+        .unwrap();
+
+    let mut responses = Vec::new();
+    let mut log_messages = Vec::new();
+    while responses.len() < 3 {
+        let message = match socket.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str::<Value>(&text).unwrap(),
+            _ => continue,
+        };
+        if message["method"] == "notifications/message" {
+            log_messages.push(message["params"].clone());
+        } else {
+            responses.push(message);
+        }
+    }
+
+    assert_eq!(responses[0]["error"]["code"], -32602);
+    assert!(responses[1]["result"].is_object());
+    assert!(responses[2]["result"]["isError"].is_null());
+    assert_eq!(log_messages.len(), 1);
+    assert_eq!(log_messages[0]["level"], "notice");
+    assert_eq!(log_messages[0]["logger"], "backtracking");
+    assert_eq!(log_messages[0]["data"]["message"], "Checkpoint restored");
+    assert_eq!(
+        log_messages[0]["data"]["checkpoint_id"],
+        id(&checkpoint, "id")
+    );
+}
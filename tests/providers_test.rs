@@ -0,0 +1,243 @@
+//! Integration tests for the non-Langbase provider backends
+//!
+//! Tests the OpenAI-compatible and Ollama backends against wiremock servers.
+
+use serde_json::json;
+use wiremock::{
+    matchers::{body_partial_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use mcp_langbase_reasoning::config::{OllamaConfig, OpenAiConfig, RequestConfig};
+use mcp_langbase_reasoning::error::LangbaseError;
+use mcp_langbase_reasoning::langbase::{GenerationParams, Message, PipeRequest};
+use mcp_langbase_reasoning::providers::{OllamaBackend, OpenAiBackend, ReasoningBackend};
+
+/// Request config without retries
+fn request_config() -> RequestConfig {
+    RequestConfig {
+        timeout_ms: 5000,
+        max_retries: 0,
+        retry_delay_ms: 100,
+        max_retry_delay_ms: 30000,
+        retry_jitter: false,
+    }
+}
+
+fn create_openai_backend(base_url: &str, api_key: Option<&str>) -> OpenAiBackend {
+    let config = OpenAiConfig {
+        base_url: format!("{}/v1", base_url),
+        api_key: api_key.map(str::to_string),
+        model: "test-model".to_string(),
+    };
+    OpenAiBackend::new(&config, request_config()).expect("Failed to create backend")
+}
+
+fn create_ollama_backend(base_url: &str) -> OllamaBackend {
+    let config = OllamaConfig {
+        base_url: base_url.to_string(),
+        model: "llama3.1".to_string(),
+    };
+    OllamaBackend::new(&config, request_config()).expect("Failed to create backend")
+}
+
+fn hot_generation() -> GenerationParams {
+    GenerationParams {
+        temperature: Some(1.2),
+        top_p: Some(0.95),
+        max_tokens: Some(300),
+    }
+}
+
+fn create_test_request() -> PipeRequest {
+    PipeRequest::new(
+        "linear-reasoning-v1",
+        vec![
+            Message::system("Reason in {{style}} style."),
+            Message::user("Why is the sky blue?"),
+        ],
+    )
+    .with_variable("style", "concise")
+}
+
+#[cfg(test)]
+mod openai_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_openai_chat_completion() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("Authorization", "Bearer sk-test"))
+            .and(body_partial_json(json!({
+                "model": "test-model",
+                "stream": false,
+                "messages": [
+                    {"role": "system", "content": "Reason in concise style."},
+                    {"role": "user", "content": "Why is the sky blue?"}
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "model": "test-model",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "Rayleigh scattering."},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 3, "total_tokens": 23}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_openai_backend(&mock_server.uri(), Some("sk-test"));
+        let response = backend.call_pipe(create_test_request()).await.unwrap();
+
+        assert!(response.success);
+        assert_eq!(response.completion, "Rayleigh scattering.");
+        assert!(response.thread_id.is_none());
+        let raw = response.raw.unwrap();
+        assert_eq!(raw.model.as_deref(), Some("test-model"));
+        assert_eq!(raw.usage.unwrap().total_tokens, Some(23));
+    }
+
+    #[tokio::test]
+    async fn test_openai_forwards_generation_params() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({
+                "temperature": 1.2,
+                "top_p": 0.95,
+                "max_tokens": 300
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "Hot take."}}]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_openai_backend(&mock_server.uri(), None);
+        let request = create_test_request().with_generation(hot_generation());
+        let response = backend.call_pipe(request).await.unwrap();
+        assert_eq!(response.completion, "Hot take.");
+    }
+
+    #[tokio::test]
+    async fn test_openai_api_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_openai_backend(&mock_server.uri(), None);
+        let err = backend.call_pipe(create_test_request()).await.unwrap_err();
+
+        match err {
+            LangbaseError::Unavailable { message, retries } => {
+                assert!(message.contains("401"));
+                assert_eq!(retries, 1);
+            }
+            other => panic!("Expected Unavailable error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openai_empty_choices() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"choices": []})))
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_openai_backend(&mock_server.uri(), None);
+        let err = backend.call_pipe(create_test_request()).await.unwrap_err();
+        assert!(err.to_string().contains("no choices"));
+    }
+}
+
+#[cfg(test)]
+mod ollama_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ollama_chat() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(json!({
+                "model": "llama3.1",
+                "stream": false,
+                "messages": [{"role": "system", "content": "Reason in concise style."}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "llama3.1",
+                "created_at": "2024-01-01T00:00:00Z",
+                "message": {"role": "assistant", "content": "Light scatters."},
+                "done": true,
+                "prompt_eval_count": 12,
+                "eval_count": 4
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_ollama_backend(&mock_server.uri());
+        let response = backend.call_pipe(create_test_request()).await.unwrap();
+
+        assert_eq!(response.completion, "Light scatters.");
+        let usage = response.raw.unwrap().usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(12));
+        assert_eq!(usage.completion_tokens, Some(4));
+        assert_eq!(usage.total_tokens, Some(16));
+    }
+
+    #[tokio::test]
+    async fn test_ollama_maps_generation_params_to_options() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(json!({
+                "options": {"temperature": 1.2, "top_p": 0.95, "num_predict": 300}
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "message": {"role": "assistant", "content": "Hot take."}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_ollama_backend(&mock_server.uri());
+        let request = create_test_request().with_generation(hot_generation());
+        let response = backend.call_pipe(request).await.unwrap();
+        assert_eq!(response.completion, "Hot take.");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_invalid_response() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+            .mount(&mock_server)
+            .await;
+
+        let backend = create_ollama_backend(&mock_server.uri());
+        let err = backend.call_pipe(create_test_request()).await.unwrap_err();
+        assert!(err.to_string().contains("Failed to parse Ollama response"));
+    }
+}
@@ -9,7 +9,7 @@ use chrono::Utc;
 use tokio::sync::RwLock;
 use wiremock::MockServer;
 
-use mcp_langbase_reasoning::config::{LangbaseConfig, RequestConfig};
+use mcp_langbase_reasoning::config::{LangbaseConfig, NetworkConfig, RequestConfig};
 use mcp_langbase_reasoning::langbase::LangbaseClient;
 use mcp_langbase_reasoning::self_improvement::{
     ActionAllowlist, ActionId, ActionOutcome, Baselines, CircuitBreaker,
@@ -29,11 +29,14 @@ fn create_test_langbase_client(mock_url: &str) -> LangbaseClient {
     let config = LangbaseConfig {
         api_key: "test-api-key".to_string(),
         base_url: mock_url.to_string(),
+        network: NetworkConfig::default(),
     };
     let request_config = RequestConfig {
         timeout_ms: 5000,
         max_retries: 0,
         retry_delay_ms: 100,
+        max_retry_delay_ms: 30000,
+        retry_jitter: false,
     };
     LangbaseClient::new(&config, request_config).unwrap()
 }
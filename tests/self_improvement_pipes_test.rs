@@ -11,7 +11,7 @@ use wiremock::{
     Mock, MockServer, ResponseTemplate,
 };
 
-use mcp_langbase_reasoning::config::{LangbaseConfig, RequestConfig};
+use mcp_langbase_reasoning::config::{LangbaseConfig, NetworkConfig, RequestConfig};
 use mcp_langbase_reasoning::langbase::LangbaseClient;
 use mcp_langbase_reasoning::self_improvement::{
     ActionAllowlist, Baselines, ConfigScope, DiagnosisId, DiagnosisStatus, HealthReport,
@@ -29,11 +29,14 @@ fn create_test_langbase_client(mock_url: &str) -> LangbaseClient {
     let config = LangbaseConfig {
         api_key: "test-api-key".to_string(),
         base_url: mock_url.to_string(),
+        network: NetworkConfig::default(),
     };
     let request_config = RequestConfig {
         timeout_ms: 5000,
         max_retries: 0,
         retry_delay_ms: 100,
+        max_retry_delay_ms: 30000,
+        retry_jitter: false,
     };
     LangbaseClient::new(&config, request_config).unwrap()
 }
@@ -259,11 +262,14 @@ async fn test_generate_diagnosis_timeout() {
     let config = LangbaseConfig {
         api_key: "test".to_string(),
         base_url: mock_server.uri(),
+        network: NetworkConfig::default(),
     };
     let request_config = RequestConfig {
         timeout_ms: 100, // Very short timeout
         max_retries: 0,
         retry_delay_ms: 100,
+        max_retry_delay_ms: 30000,
+        retry_jitter: false,
     };
     let langbase = Arc::new(LangbaseClient::new(&config, request_config).unwrap());
     let pipe_config = SelfImprovementPipeConfig {
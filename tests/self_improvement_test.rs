@@ -8,7 +8,9 @@ use chrono::{Duration, Utc};
 use serde_json::json;
 use tempfile::TempDir;
 
-use mcp_langbase_reasoning::config::DatabaseConfig;
+use mcp_langbase_reasoning::config::{
+    DatabaseConfig, MaintenanceConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
+};
 use mcp_langbase_reasoning::self_improvement::cli::{execute_command, SelfImproveCommands};
 use mcp_langbase_reasoning::self_improvement::storage::SelfImprovementStorage;
 use mcp_langbase_reasoning::self_improvement::{
@@ -28,6 +30,10 @@ async fn create_test_storage() -> (SqliteStorage, TempDir) {
     let config = DatabaseConfig {
         path: db_path,
         max_connections: 1,
+        encryption_key: None,
+        maintenance: MaintenanceConfig::default(),
+        cache_capacity: DEFAULT_CACHE_CAPACITY,
+        workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
     };
     let storage = SqliteStorage::new(&config)
         .await
@@ -74,6 +74,30 @@ mod session_tests {
         assert_eq!(retrieved.mode, "tree");
     }
 
+    #[tokio::test]
+    async fn test_session_thread_binding() {
+        let storage = create_test_storage().await;
+
+        let mut session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        assert!(storage
+            .get_session(&session.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .thread_id
+            .is_none());
+
+        session.thread_id = Some("thread-abc".to_string());
+        storage.update_session(&session).await.unwrap();
+        let retrieved = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.thread_id.as_deref(), Some("thread-abc"));
+
+        // A fork starts without a thread so it cannot append to the source's history
+        let fork = storage.fork_session(&session.id).await.unwrap();
+        assert!(fork.session.thread_id.is_none());
+    }
+
     #[tokio::test]
     async fn test_delete_session() {
         let storage = create_test_storage().await;
@@ -294,6 +318,66 @@ mod invocation_tests {
     }
 }
 
+#[cfg(test)]
+mod pipe_payload_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::storage::PipePayload;
+
+    #[tokio::test]
+    async fn test_pipe_payload_roundtrip() {
+        let storage = create_test_storage().await;
+
+        let ok = PipePayload::new("linear-reasoning-v1", 1, json!({"messages": []}))
+            .with_response(json!({"completion": "done"}))
+            .with_latency(42);
+        let failed = PipePayload::new("tree-reasoning-v1", 2, json!({"messages": []}))
+            .with_error("HTTP 500");
+        storage.log_pipe_payload(&ok).await.unwrap();
+        storage.log_pipe_payload(&failed).await.unwrap();
+
+        let all = storage.get_pipe_payloads(None, 10).await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let linear = storage
+            .get_pipe_payloads(Some("linear-reasoning-v1"), 10)
+            .await
+            .unwrap();
+        assert_eq!(linear.len(), 1);
+        assert_eq!(linear[0].attempt, 1);
+        assert_eq!(linear[0].latency_ms, 42);
+        assert_eq!(linear[0].response, Some(json!({"completion": "done"})));
+
+        let tree = storage
+            .get_pipe_payloads(Some("tree-reasoning-v1"), 10)
+            .await
+            .unwrap();
+        assert_eq!(tree[0].response, None);
+        assert_eq!(tree[0].error.as_deref(), Some("HTTP 500"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_pipe_payloads_before() {
+        let storage = create_test_storage().await;
+
+        let mut old = PipePayload::new("linear-reasoning-v1", 1, json!({}));
+        old.created_at = Utc::now() - Duration::days(10);
+        let recent = PipePayload::new("linear-reasoning-v1", 1, json!({}));
+        storage.log_pipe_payload(&old).await.unwrap();
+        storage.log_pipe_payload(&recent).await.unwrap();
+
+        let deleted = storage
+            .delete_pipe_payloads_before(Utc::now() - Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+
+        let remaining = storage.get_pipe_payloads(None, 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, recent.id);
+    }
+}
+
 #[cfg(test)]
 mod cascade_delete_tests {
     use super::*;
@@ -514,6 +598,29 @@ mod detection_tests {
         assert_eq!(fallacies[0].detected_issue, "ad_hominem");
     }
 
+    #[tokio::test]
+    async fn test_calculation_detection_round_trip() {
+        let storage = create_test_storage().await;
+
+        let detection = Detection::new(
+            DetectionType::Calculation,
+            "calculation_error",
+            3,
+            1.0,
+            "'120 * 3 = 350' states 350 but 120 * 3 computes to 360",
+        )
+        .with_remediation("Use 360");
+        storage.create_detection(&detection).await.unwrap();
+
+        let calculations = storage
+            .get_detections_by_type(DetectionType::Calculation)
+            .await
+            .unwrap();
+        assert_eq!(calculations.len(), 1);
+        assert_eq!(calculations[0].id, detection.id);
+        assert_eq!(calculations[0].remediation.as_deref(), Some("Use 360"));
+    }
+
     #[tokio::test]
     async fn test_delete_detection() {
         let storage = create_test_storage().await;
@@ -580,3 +687,2260 @@ mod detection_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod tag_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_and_find_tags() {
+        let storage = create_test_storage().await;
+
+        let tagged = Session::new("linear");
+        let other = Session::new("tree");
+        storage.create_session(&tagged).await.unwrap();
+        storage.create_session(&other).await.unwrap();
+
+        storage.add_tag(&tagged.id, "Architecture").await.unwrap();
+        storage
+            .add_tag(&tagged.id, "  architecture ")
+            .await
+            .unwrap();
+        storage.add_tag(&tagged.id, "q3-review").await.unwrap();
+
+        let found = storage.find_sessions_by_tag("ARCHITECTURE").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, tagged.id);
+        assert_eq!(found[0].tags, vec!["architecture", "q3-review"]);
+
+        let retrieved = storage.get_session(&other.id).await.unwrap().unwrap();
+        assert!(retrieved.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_tag() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        storage.add_tag(&session.id, "draft").await.unwrap();
+
+        assert!(storage.remove_tag(&session.id, "Draft").await.unwrap());
+        assert!(!storage.remove_tag(&session.id, "draft").await.unwrap());
+        assert!(storage
+            .find_sessions_by_tag("draft")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_session_with_tags() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear").with_tag("beta").with_tag("Alpha");
+        storage.create_session(&session).await.unwrap();
+
+        let retrieved = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(retrieved.tags, vec!["alpha", "beta"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_tag_rejects_missing_session_and_empty_tag() {
+        let storage = create_test_storage().await;
+
+        assert!(storage.add_tag("missing", "tag").await.is_err());
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        assert!(storage.add_tag(&session.id, "   ").await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{GraphEdge, GraphNode};
+
+    #[tokio::test]
+    async fn test_create_thoughts_batch() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("divergent");
+        storage.create_session(&session).await.unwrap();
+
+        let root = Thought::new(&session.id, "Root", "divergent");
+        let child = Thought::new(&session.id, "Child", "divergent").with_parent(&root.id);
+        storage
+            .create_thoughts_batch(&[root.clone(), child.clone()])
+            .await
+            .unwrap();
+
+        let thoughts = storage.get_session_thoughts(&session.id).await.unwrap();
+        assert_eq!(thoughts.len(), 2);
+        assert_eq!(
+            storage
+                .get_thought(&child.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .parent_id,
+            Some(root.id)
+        );
+
+        storage.create_thoughts_batch(&[]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_create_thoughts_batch_is_atomic() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("divergent");
+        storage.create_session(&session).await.unwrap();
+
+        let first = Thought::new(&session.id, "First", "divergent");
+        let mut duplicate = Thought::new(&session.id, "Duplicate", "divergent");
+        duplicate.id = first.id.clone();
+
+        assert!(storage
+            .create_thoughts_batch(&[first, duplicate])
+            .await
+            .is_err());
+        assert!(storage
+            .get_session_thoughts(&session.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_graph_nodes_and_edges_batch() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+
+        let root = GraphNode::new(&session.id, "Root").as_root();
+        let children: Vec<GraphNode> = (0..3)
+            .map(|i| GraphNode::new(&session.id, format!("Child {}", i)))
+            .collect();
+        let edges: Vec<GraphEdge> = children
+            .iter()
+            .map(|child| GraphEdge::new(&session.id, &root.id, &child.id))
+            .collect();
+
+        let mut nodes = vec![root.clone()];
+        nodes.extend(children);
+        storage.create_graph_nodes_batch(&nodes).await.unwrap();
+        storage.create_graph_edges_batch(&edges).await.unwrap();
+
+        assert_eq!(
+            storage
+                .get_session_graph_nodes(&session.id)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(storage.get_edges_from(&root.id).await.unwrap().len(), 3);
+    }
+}
+
+#[cfg(test)]
+mod list_sessions_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::storage::SessionFilter;
+
+    #[tokio::test]
+    async fn test_list_sessions_filters() {
+        let storage = create_test_storage().await;
+
+        let mut old = Session::new("linear");
+        old.created_at = Utc::now() - Duration::days(10);
+        old.updated_at = old.created_at;
+        storage.create_session(&old).await.unwrap();
+
+        let tagged = Session::new("tree").with_tag("review");
+        storage.create_session(&tagged).await.unwrap();
+
+        let detected = Session::new("linear");
+        storage.create_session(&detected).await.unwrap();
+        let detection = Detection::new(
+            DetectionType::Bias,
+            "anchoring",
+            3,
+            0.8,
+            "Relies on the first estimate",
+        )
+        .with_session(&detected.id);
+        storage.create_detection(&detection).await.unwrap();
+
+        let all = storage.list_sessions(SessionFilter::new()).await.unwrap();
+        assert_eq!(all.len(), 3);
+
+        let linear = storage
+            .list_sessions(SessionFilter::new().with_mode("linear"))
+            .await
+            .unwrap();
+        assert_eq!(linear.len(), 2);
+        assert!(linear.iter().all(|s| s.mode == "linear"));
+
+        let reviewed = storage
+            .list_sessions(SessionFilter::new().with_tag("Review"))
+            .await
+            .unwrap();
+        assert_eq!(reviewed.len(), 1);
+        assert_eq!(reviewed[0].id, tagged.id);
+
+        let recent = storage
+            .list_sessions(SessionFilter::new().created_after(Utc::now() - Duration::days(1)))
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent.iter().all(|s| s.id != old.id));
+
+        let older = storage
+            .list_sessions(SessionFilter::new().created_before(Utc::now() - Duration::days(1)))
+            .await
+            .unwrap();
+        assert_eq!(older.len(), 1);
+        assert_eq!(older[0].id, old.id);
+
+        let with = storage
+            .list_sessions(SessionFilter::new().with_detections(true))
+            .await
+            .unwrap();
+        assert_eq!(with.len(), 1);
+        assert_eq!(with[0].id, detected.id);
+
+        let without = storage
+            .list_sessions(SessionFilter::new().with_detections(false))
+            .await
+            .unwrap();
+        assert_eq!(without.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_pagination() {
+        let storage = create_test_storage().await;
+
+        for _ in 0..5 {
+            storage
+                .create_session(&Session::new("linear"))
+                .await
+                .unwrap();
+        }
+
+        let first = storage
+            .list_sessions(SessionFilter::new().with_limit(2))
+            .await
+            .unwrap();
+        let rest = storage
+            .list_sessions(SessionFilter::new().with_limit(10).with_offset(2))
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(rest.len(), 3);
+        assert!(first.iter().all(|s| rest.iter().all(|r| r.id != s.id)));
+    }
+}
+
+#[cfg(test)]
+mod trash_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::config::RetentionConfig;
+    use mcp_langbase_reasoning::storage::{prune, SessionFilter, ThoughtSearchFilter};
+
+    #[tokio::test]
+    async fn test_trash_and_restore_session() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Keep this reasoning", "linear");
+        storage.create_thought(&thought).await.unwrap();
+
+        assert!(storage.trash_session(&session.id).await.unwrap());
+        assert!(!storage.trash_session(&session.id).await.unwrap());
+
+        // Trashed sessions keep their data but drop out of listings and search
+        let trashed = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert!(trashed.is_deleted());
+        assert!(storage.get_thought(&thought.id).await.unwrap().is_some());
+        assert!(storage
+            .list_sessions(SessionFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .search_thoughts("reasoning", ThoughtSearchFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+
+        let in_trash = storage
+            .list_sessions(SessionFilter::new().in_trash())
+            .await
+            .unwrap();
+        assert_eq!(in_trash.len(), 1);
+        assert_eq!(in_trash[0].id, session.id);
+
+        assert!(storage.restore_session(&session.id).await.unwrap());
+        assert!(!storage.restore_session(&session.id).await.unwrap());
+
+        let restored = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert!(!restored.is_deleted());
+        assert_eq!(
+            storage
+                .search_thoughts("reasoning", ThoughtSearchFilter::new())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trash_missing_session() {
+        let storage = create_test_storage().await;
+
+        assert!(!storage.trash_session("missing").await.unwrap());
+        assert!(!storage.restore_session("missing").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_prune_purges_old_trash() {
+        let storage = create_test_storage().await;
+
+        let live = Session::new("linear");
+        let trashed = Session::new("linear");
+        storage.create_session(&live).await.unwrap();
+        storage.create_session(&trashed).await.unwrap();
+        storage.trash_session(&trashed.id).await.unwrap();
+
+        // Recently trashed sessions survive the purge
+        assert_eq!(
+            storage
+                .purge_trashed_sessions(Utc::now() - Duration::days(1))
+                .await
+                .unwrap(),
+            0
+        );
+
+        let config = RetentionConfig {
+            trash_retention_days: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(prune(&storage, &config).await.unwrap().total(), 0);
+
+        let report = storage
+            .purge_trashed_sessions(Utc::now() + Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(report, 1);
+        assert!(storage.get_session(&trashed.id).await.unwrap().is_none());
+        assert!(storage.get_session(&live.id).await.unwrap().is_some());
+    }
+}
+
+#[cfg(test)]
+mod embedding_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{
+        Embedding, EmbeddingSource, GraphNode, SimilarityFilter,
+    };
+
+    #[tokio::test]
+    async fn test_embedding_candidates_and_similarity() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let cache = Thought::new(&session.id, "Use a write-through cache", "linear");
+        let queue = Thought::new(&session.id, "Add a message queue", "linear");
+        storage.create_thought(&cache).await.unwrap();
+        storage.create_thought(&queue).await.unwrap();
+        let node = GraphNode::new(&session.id, "Cache warming on deploy");
+        storage.create_graph_node(&node).await.unwrap();
+
+        let candidates = storage
+            .get_embedding_candidates("test", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(candidates.len(), 3);
+
+        storage
+            .save_embeddings(&[
+                Embedding::new(
+                    &cache.id,
+                    EmbeddingSource::Thought,
+                    &session.id,
+                    "test",
+                    vec![1.0, 0.0],
+                ),
+                Embedding::new(
+                    &queue.id,
+                    EmbeddingSource::Thought,
+                    &session.id,
+                    "test",
+                    vec![0.0, 1.0],
+                ),
+                Embedding::new(
+                    &node.id,
+                    EmbeddingSource::GraphNode,
+                    &session.id,
+                    "test",
+                    vec![0.8, 0.6],
+                ),
+            ])
+            .await
+            .unwrap();
+
+        assert!(storage
+            .get_embedding_candidates("test", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        // Other models still need their own embeddings
+        assert_eq!(
+            storage
+                .get_embedding_candidates("other", None, 10)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let matches = storage
+            .find_similar(&[1.0, 0.0], "test", SimilarityFilter::new())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = matches.iter().map(|m| m.source_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![cache.id.as_str(), node.id.as_str(), queue.id.as_str()]
+        );
+        assert_eq!(matches[0].content, "Use a write-through cache");
+        assert_eq!(matches[1].source_type, EmbeddingSource::GraphNode);
+
+        let filtered = storage
+            .find_similar(
+                &[1.0, 0.0],
+                "test",
+                SimilarityFilter::new()
+                    .with_source_type(EmbeddingSource::Thought)
+                    .with_min_score(0.5),
+            )
+            .await
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].source_id, cache.id);
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_excludes_session() {
+        let storage = create_test_storage().await;
+
+        let current = Session::new("linear");
+        let prior = Session::new("linear");
+        storage.create_session(&current).await.unwrap();
+        storage.create_session(&prior).await.unwrap();
+        let mine = Thought::new(&current.id, "Current idea", "linear");
+        let earlier = Thought::new(&prior.id, "Earlier idea", "linear");
+        storage.create_thought(&mine).await.unwrap();
+        storage.create_thought(&earlier).await.unwrap();
+
+        storage
+            .save_embeddings(&[
+                Embedding::new(
+                    &mine.id,
+                    EmbeddingSource::Thought,
+                    &current.id,
+                    "test",
+                    vec![1.0],
+                ),
+                Embedding::new(
+                    &earlier.id,
+                    EmbeddingSource::Thought,
+                    &prior.id,
+                    "test",
+                    vec![1.0],
+                ),
+            ])
+            .await
+            .unwrap();
+
+        let matches = storage
+            .find_similar(
+                &[1.0],
+                "test",
+                SimilarityFilter::new().excluding_session(&current.id),
+            )
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].source_id, earlier.id);
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_removed_with_session() {
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Disposable", "linear");
+        storage.create_thought(&thought).await.unwrap();
+        storage
+            .save_embeddings(&[Embedding::new(
+                &thought.id,
+                EmbeddingSource::Thought,
+                &session.id,
+                "test",
+                vec![1.0],
+            )])
+            .await
+            .unwrap();
+
+        storage.delete_session(&session.id).await.unwrap();
+
+        let matches = storage
+            .find_similar(&[1.0], "test", SimilarityFilter::new())
+            .await
+            .unwrap();
+        assert!(matches.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod retention_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::config::RetentionConfig;
+    use mcp_langbase_reasoning::storage::{prune, MetricsFilter};
+
+    #[tokio::test]
+    async fn test_prune_expired_sessions_and_invocations() {
+        let storage = create_test_storage().await;
+
+        let mut old_session = Session::new("linear");
+        old_session.created_at = Utc::now() - Duration::days(40);
+        old_session.updated_at = old_session.created_at;
+        storage.create_session(&old_session).await.unwrap();
+        let thought = Thought::new(&old_session.id, "Old reasoning", "linear");
+        storage.create_thought(&thought).await.unwrap();
+
+        let recent_session = Session::new("linear");
+        storage.create_session(&recent_session).await.unwrap();
+
+        let mut old_invocation = Invocation::new("reasoning.linear", json!({}));
+        old_invocation.created_at = Utc::now() - Duration::days(40);
+        storage.log_invocation(&old_invocation).await.unwrap();
+        storage
+            .log_invocation(&Invocation::new("reasoning.linear", json!({})))
+            .await
+            .unwrap();
+
+        let config = RetentionConfig {
+            retention_days: Some(30),
+            ..Default::default()
+        };
+        let report = prune(&storage, &config).await.unwrap();
+
+        assert_eq!(report.sessions_deleted, 1);
+        assert_eq!(report.invocations_deleted, 1);
+        assert!(storage
+            .get_session(&old_session.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage.get_thought(&thought.id).await.unwrap().is_none());
+        assert!(storage
+            .get_session(&recent_session.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_prune_trims_invocations_to_max() {
+        let storage = create_test_storage().await;
+
+        for i in 0..5 {
+            let mut invocation = Invocation::new("reasoning.linear", json!({"i": i}));
+            invocation.created_at = Utc::now() - Duration::minutes(10 - i);
+            storage.log_invocation(&invocation).await.unwrap();
+        }
+
+        let config = RetentionConfig {
+            max_invocations: Some(2),
+            ..Default::default()
+        };
+        let report = prune(&storage, &config).await.unwrap();
+
+        assert_eq!(report.invocations_deleted, 3);
+        let remaining = storage.get_invocations(MetricsFilter::new()).await.unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].input["i"], 4);
+        assert_eq!(remaining[1].input["i"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_prune_disabled_deletes_nothing() {
+        let storage = create_test_storage().await;
+
+        let mut old_session = Session::new("linear");
+        old_session.updated_at = Utc::now() - Duration::days(365);
+        storage.create_session(&old_session).await.unwrap();
+
+        let report = prune(&storage, &RetentionConfig::default()).await.unwrap();
+
+        assert_eq!(report.total(), 0);
+        assert!(storage
+            .get_session(&old_session.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
+
+#[cfg(test)]
+mod backup_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::{
+        DatabaseConfig, MaintenanceConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
+    };
+    use std::path::PathBuf;
+
+    fn file_config(path: PathBuf) -> DatabaseConfig {
+        DatabaseConfig {
+            path,
+            max_connections: 1,
+            encryption_key: None,
+            maintenance: MaintenanceConfig::default(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
+        }
+    }
+
+    async fn backup_and_restore(compress: bool) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = create_test_storage().await;
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+
+        let backup_path = dir.path().join("backups").join("reasoning.bak");
+        let report = storage.backup(&backup_path, compress).await.unwrap();
+        assert_eq!(report.path, backup_path);
+        assert_eq!(report.compressed, compress);
+        assert!(report.size_bytes > 0);
+
+        let config = file_config(dir.path().join("restored.db"));
+        SqliteStorage::restore(&backup_path, &config).await.unwrap();
+
+        let restored = SqliteStorage::new(&config).await.unwrap();
+        assert!(restored.get_session(&session.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_plain() {
+        backup_and_restore(false).await;
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_compressed() {
+        backup_and_restore(true).await;
+    }
+
+    #[tokio::test]
+    async fn test_backup_refuses_existing_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = create_test_storage().await;
+
+        let target = dir.path().join("reasoning.bak");
+        std::fs::write(&target, b"existing").unwrap();
+
+        assert!(storage.backup(&target, false).await.is_err());
+        assert_eq!(std::fs::read(&target).unwrap(), b"existing");
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_corrupt_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = file_config(dir.path().join("reasoning.db"));
+
+        let storage = SqliteStorage::new(&config).await.unwrap();
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        storage.pool().close().await;
+
+        let corrupt = dir.path().join("corrupt.bak");
+        std::fs::write(&corrupt, b"not a database").unwrap();
+        assert!(SqliteStorage::restore(&corrupt, &config).await.is_err());
+
+        // The existing database is left untouched
+        let storage = SqliteStorage::new(&config).await.unwrap();
+        assert!(storage.get_session(&session.id).await.unwrap().is_some());
+    }
+}
+
+#[cfg(test)]
+mod knowledge_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::StorageError;
+    use mcp_langbase_reasoning::storage::{
+        ConclusionSource, Decision, EvidenceAssessment, GraphNode, KnowledgeFilter, KnowledgeStore,
+    };
+
+    #[tokio::test]
+    async fn test_promote_graph_node_and_query() {
+        let storage = create_test_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        let root = GraphNode::new(&session.id, "How should we shard the orders table?").as_root();
+        let terminal = GraphNode::new(&session.id, "Shard orders by customer region")
+            .with_score(0.85)
+            .as_terminal();
+        storage
+            .create_graph_nodes_batch(&[root.clone(), terminal.clone()])
+            .await
+            .unwrap();
+
+        let knowledge = KnowledgeStore::new(storage.clone());
+        let conclusion = knowledge.promote_graph_node(&terminal.id).await.unwrap();
+        assert_eq!(conclusion.topic, root.content);
+        assert_eq!(conclusion.content, terminal.content);
+        assert_eq!(conclusion.confidence, Some(0.85));
+        assert_eq!(conclusion.session_id.as_deref(), Some(session.id.as_str()));
+
+        // Promoting again returns the stored conclusion
+        let again = knowledge.promote_graph_node(&terminal.id).await.unwrap();
+        assert_eq!(again.id, conclusion.id);
+
+        let hits = knowledge
+            .query("sharding strategy for orders", KnowledgeFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].conclusion.id, conclusion.id);
+    }
+
+    #[tokio::test]
+    async fn test_promote_rejects_non_terminal_and_missing() {
+        let storage = create_test_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        let node = GraphNode::new(&session.id, "Still exploring");
+        storage.create_graph_node(&node).await.unwrap();
+
+        let knowledge = KnowledgeStore::new(storage);
+        assert!(matches!(
+            knowledge.promote_graph_node(&node.id).await,
+            Err(StorageError::Knowledge { .. })
+        ));
+        assert!(matches!(
+            knowledge.promote_decision("missing").await,
+            Err(StorageError::Knowledge { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_promote_decision_and_evidence() {
+        let storage = create_test_storage().await;
+        let session = Session::new("decision");
+        storage.create_session(&session).await.unwrap();
+        let decision = Decision::new(
+            &session.id,
+            "Which queue should we adopt?",
+            vec!["Kafka".to_string(), "RabbitMQ".to_string()],
+            "weighted_sum",
+            json!({"option": "Kafka", "score": 0.8, "confidence": 0.7, "rationale": "Best throughput"}),
+            json!([]),
+        );
+        storage.create_decision(&decision).await.unwrap();
+        let assessment = EvidenceAssessment::new(
+            &session.id,
+            "Kafka handles our peak load",
+            json!([]),
+            json!({"level": "strong", "confidence": 0.9, "explanation": "Benchmarks agree"}),
+            json!([]),
+        );
+        storage
+            .create_evidence_assessment(&assessment)
+            .await
+            .unwrap();
+
+        let knowledge = KnowledgeStore::new(storage);
+        let recommended = knowledge.promote_decision(&decision.id).await.unwrap();
+        assert_eq!(recommended.topic, "Which queue should we adopt?");
+        assert_eq!(recommended.content, "Recommended: Kafka. Best throughput");
+        assert_eq!(recommended.confidence, Some(0.7));
+
+        let verdict = knowledge.promote_evidence(&assessment.id).await.unwrap();
+        assert!(verdict.content.contains("strong"));
+
+        let evidence_only = knowledge
+            .query(
+                "kafka",
+                KnowledgeFilter::new().with_source_type(ConclusionSource::Evidence),
+            )
+            .await
+            .unwrap();
+        assert_eq!(evidence_only.len(), 1);
+        assert_eq!(evidence_only[0].conclusion.id, verdict.id);
+
+        let confident = knowledge
+            .query("kafka", KnowledgeFilter::new().with_min_confidence(0.8))
+            .await
+            .unwrap();
+        assert_eq!(confident.len(), 1);
+
+        // An empty query lists recent conclusions
+        let recent = knowledge.query("", KnowledgeFilter::new()).await.unwrap();
+        assert_eq!(recent.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conclusions_outlive_their_session() {
+        let storage = create_test_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        let node = GraphNode::new(&session.id, "Prefer idempotent consumers").as_terminal();
+        storage.create_graph_node(&node).await.unwrap();
+
+        let conclusion = KnowledgeStore::new(storage.clone())
+            .promote_graph_node(&node.id)
+            .await
+            .unwrap();
+        storage.delete_session(&session.id).await.unwrap();
+
+        let kept = storage
+            .get_conclusion(&conclusion.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(kept.session_id.is_none());
+
+        assert!(storage.delete_conclusion(&conclusion.id).await.unwrap());
+        assert!(storage
+            .get_conclusion_by_source(ConclusionSource::GraphNode, &node.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{AuditEntry, AuditFilter, AuditLogger};
+
+    #[tokio::test]
+    async fn test_append_and_filter_audit_entries() {
+        let storage = create_test_storage().await;
+
+        let first = AuditEntry::new("mcp:desktop", "reasoning_linear").with_session("s1");
+        let second = AuditEntry::new("mcp:desktop", "reasoning_session_delete")
+            .with_session("s2")
+            .with_error("Session not found: s2");
+        let third = AuditEntry::new("cli", "reasoning_linear").with_session("s1");
+        for entry in [&first, &second, &third] {
+            storage.append_audit_entry(entry).await.unwrap();
+        }
+
+        let all = storage
+            .list_audit_entries(AuditFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].id, third.id, "most recent first");
+
+        let for_session = storage
+            .list_audit_entries(AuditFilter::new().with_session("s1"))
+            .await
+            .unwrap();
+        assert_eq!(for_session.len(), 2);
+
+        let by_actor_and_tool = storage
+            .list_audit_entries(
+                AuditFilter::new()
+                    .with_actor("mcp:desktop")
+                    .with_tool("reasoning_session_delete"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(by_actor_and_tool.len(), 1);
+        assert!(!by_actor_and_tool[0].success);
+        assert_eq!(
+            by_actor_and_tool[0].error.as_deref(),
+            Some("Session not found: s2")
+        );
+
+        let limited = storage
+            .list_audit_entries(AuditFilter::new().with_limit(1))
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_is_append_only() {
+        let storage = create_test_storage().await;
+        let entry = AuditEntry::new("mcp", "reasoning_linear");
+        storage.append_audit_entry(&entry).await.unwrap();
+
+        let update = sqlx::query("UPDATE audit_log SET actor = 'someone-else'")
+            .execute(storage.pool())
+            .await;
+        assert!(update.is_err());
+        let delete = sqlx::query("DELETE FROM audit_log")
+            .execute(storage.pool())
+            .await;
+        assert!(delete.is_err());
+
+        let entries = storage
+            .list_audit_entries(AuditFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor, "mcp");
+    }
+
+    #[tokio::test]
+    async fn test_session_hash_tracks_changes() {
+        let storage = create_test_storage().await;
+        let audit = AuditLogger::new(storage.clone(), "test");
+
+        assert!(audit.session_hash("missing").await.unwrap().is_none());
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let before = audit.session_hash(&session.id).await.unwrap().unwrap();
+        assert_eq!(before.len(), 64);
+        assert_eq!(
+            audit.session_hash(&session.id).await.unwrap().unwrap(),
+            before,
+            "hash is stable while nothing changes"
+        );
+
+        let thought = Thought::new(&session.id, "A new step", "linear");
+        storage.create_thought(&thought).await.unwrap();
+        let after = audit.session_hash(&session.id).await.unwrap().unwrap();
+        assert_ne!(before, after);
+    }
+
+    #[tokio::test]
+    async fn test_audit_entries_survive_session_deletion() {
+        let storage = create_test_storage().await;
+        let audit = AuditLogger::new(storage.clone(), "mcp");
+
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let entry = audit
+            .entry("reasoning_linear")
+            .with_session(&session.id)
+            .with_hashes(None, audit.session_hash(&session.id).await.unwrap());
+        audit.record(&entry).await.unwrap();
+
+        storage.delete_session(&session.id).await.unwrap();
+
+        let entries = storage
+            .list_audit_entries(AuditFilter::new().with_session(&session.id))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor, "mcp");
+        assert!(entries[0].changed_state());
+    }
+}
+
+#[cfg(test)]
+mod maintenance_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::run_maintenance;
+
+    #[tokio::test]
+    async fn test_integrity_check_passes_on_fresh_database() {
+        let storage = create_test_storage().await;
+        assert!(storage.integrity_check().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_reclaims_space_and_analyzes() {
+        let storage = create_test_storage().await;
+
+        for _ in 0..50 {
+            let session = Session::new("linear");
+            storage.create_session(&session).await.unwrap();
+            let thought = Thought::new(&session.id, "x".repeat(4096), "linear");
+            storage.create_thought(&thought).await.unwrap();
+        }
+        storage
+            .delete_sessions_before(Utc::now() + chrono::Duration::days(1))
+            .await
+            .unwrap();
+
+        let freelist: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(storage.pool())
+            .await
+            .unwrap();
+        assert!(freelist > 0, "deletes leave free pages behind");
+
+        let report = run_maintenance(&storage).await.unwrap();
+        assert!(report.is_healthy());
+        assert!(report.vacuumed);
+        assert!(report.analyzed);
+
+        let freelist: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(storage.pool())
+            .await
+            .unwrap();
+        assert_eq!(freelist, 0);
+
+        let stats: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'sqlite_stat1'")
+                .fetch_one(storage.pool())
+                .await
+                .unwrap();
+        assert_eq!(stats, 1);
+    }
+}
+
+#[cfg(test)]
+mod migration_tests {
+    use super::*;
+    use mcp_langbase_reasoning::config::{
+        DatabaseConfig, MaintenanceConfig, DEFAULT_CACHE_CAPACITY, DEFAULT_WORKSPACE_ID,
+    };
+    use mcp_langbase_reasoning::storage::MigrationState;
+    use std::path::PathBuf;
+
+    fn file_config(path: PathBuf) -> DatabaseConfig {
+        DatabaseConfig {
+            path,
+            max_connections: 1,
+            encryption_key: None,
+            maintenance: MaintenanceConfig::default(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            workspace_id: DEFAULT_WORKSPACE_ID.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_of_new_database_is_all_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = file_config(dir.path().join("reasoning.db"));
+
+        let status = SqliteStorage::migration_status(&config).await.unwrap();
+        assert!(!status.migrations.is_empty());
+        assert_eq!(status.pending().len(), status.migrations.len());
+        assert!(status.conflicts().is_empty());
+        assert!(!config.path.exists(), "checking status creates no file");
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_after_migrating_is_up_to_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = file_config(dir.path().join("reasoning.db"));
+
+        let storage = SqliteStorage::new(&config).await.unwrap();
+        storage.pool().close().await;
+
+        let status = SqliteStorage::migration_status(&config).await.unwrap();
+        assert!(status.is_up_to_date());
+        assert!(status.migrations.iter().all(|m| m.installed_on.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_schema_ahead_of_binary_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = file_config(dir.path().join("reasoning.db"));
+
+        let storage = SqliteStorage::new(&config).await.unwrap();
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (29990101000001, 'from the future', TRUE, X'00', 0)",
+        )
+        .execute(storage.pool())
+        .await
+        .unwrap();
+        storage.pool().close().await;
+
+        let status = SqliteStorage::migration_status(&config).await.unwrap();
+        assert!(status.is_ahead());
+        let unknown = status.with_state(MigrationState::Unknown);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].description, "from the future");
+
+        let err = SqliteStorage::new(&config).await.err().unwrap().to_string();
+        assert!(err.contains("ahead of this binary"), "{}", err);
+        assert!(err.contains("29990101000001 from the future"), "{}", err);
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{CachedStorage, GraphNode};
+
+    async fn create_cached_storage() -> CachedStorage {
+        CachedStorage::new(create_test_storage().await, 16)
+    }
+
+    #[tokio::test]
+    async fn test_session_reads_hit_cache_until_updated() {
+        let storage = create_cached_storage().await;
+        let mut session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+
+        storage.get_session(&session.id).await.unwrap();
+        storage.get_session(&session.id).await.unwrap();
+        assert_eq!(storage.stats().hits, 1);
+        assert_eq!(storage.stats().misses, 1);
+
+        session.mode = "tree".to_string();
+        storage.update_session(&session).await.unwrap();
+        let fetched = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(fetched.mode, "tree");
+        assert_eq!(storage.stats().misses, 2);
+
+        storage.add_tag(&session.id, "cached").await.unwrap();
+        let fetched = storage.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(fetched.tags, vec!["cached".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_latest_thought_invalidated_by_new_thought() {
+        let storage = create_cached_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+
+        assert!(storage
+            .get_latest_thought(&session.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .get_latest_thought(&session.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(storage.stats().hits, 1);
+
+        let thought = Thought::new(&session.id, "First step", "linear");
+        storage.create_thought(&thought).await.unwrap();
+        let latest = storage.get_latest_thought(&session.id).await.unwrap();
+        assert_eq!(latest.map(|t| t.id), Some(thought.id));
+    }
+
+    #[tokio::test]
+    async fn test_active_nodes_invalidated_by_node_updates() {
+        let storage = create_cached_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+
+        let mut node = GraphNode::new(&session.id, "Root idea");
+        storage.create_graph_node(&node).await.unwrap();
+        assert_eq!(
+            storage
+                .get_active_graph_nodes(&session.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        node.is_active = false;
+        storage.update_graph_node(&node).await.unwrap();
+        assert!(storage
+            .get_active_graph_nodes(&session.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let other = GraphNode::new(&session.id, "Second idea");
+        storage.create_graph_node(&other).await.unwrap();
+        storage.get_active_graph_nodes(&session.id).await.unwrap();
+        storage.delete_graph_node(&other.id).await.unwrap();
+        assert!(storage
+            .get_active_graph_nodes(&session.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_session_is_not_served_from_cache() {
+        let storage = create_cached_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        storage.get_session(&session.id).await.unwrap();
+
+        storage.delete_session(&session.id).await.unwrap();
+        assert!(storage.get_session(&session.id).await.unwrap().is_none());
+
+        let expired = Session::new("linear");
+        storage.create_session(&expired).await.unwrap();
+        storage.get_session(&expired.id).await.unwrap();
+        storage
+            .delete_sessions_before(Utc::now() + chrono::Duration::days(1))
+            .await
+            .unwrap();
+        assert!(storage.get_session(&expired.id).await.unwrap().is_none());
+    }
+}
+
+#[cfg(test)]
+mod fork_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::StorageError;
+    use mcp_langbase_reasoning::storage::{
+        Branch, Checkpoint, CrossRef, CrossRefType, GraphEdge, GraphNode,
+    };
+
+    #[tokio::test]
+    async fn test_fork_copies_session_with_remapped_ids() {
+        let storage = create_test_storage().await;
+        let session = Session::new("tree").with_tag("original");
+        storage.create_session(&session).await.unwrap();
+
+        let root = Branch::new(&session.id);
+        let child = Branch::new(&session.id).with_parent(&root.id);
+        storage.create_branch(&root).await.unwrap();
+        storage.create_branch(&child).await.unwrap();
+        let session = session.with_active_branch(&root.id);
+        storage.update_session(&session).await.unwrap();
+        storage
+            .create_cross_ref(&CrossRef::new(&child.id, &root.id, CrossRefType::Extends))
+            .await
+            .unwrap();
+
+        let first = Thought::new(&session.id, "First step", "tree").with_branch(&root.id);
+        let second = Thought::new(&session.id, "Second step", "tree")
+            .with_parent(&first.id)
+            .with_branch(&child.id);
+        storage.create_thought(&first).await.unwrap();
+        storage.create_thought(&second).await.unwrap();
+        storage
+            .create_checkpoint(
+                &Checkpoint::new(&session.id, "cp", json!({"step": 2})).with_branch(&child.id),
+            )
+            .await
+            .unwrap();
+
+        let fork = storage.fork_session(&session.id).await.unwrap();
+        assert_ne!(fork.session.id, session.id);
+        assert_eq!(
+            fork.session.forked_from.as_deref(),
+            Some(session.id.as_str())
+        );
+        assert_eq!(fork.session.tags, vec!["original".to_string()]);
+        assert_eq!(
+            (
+                fork.thoughts,
+                fork.branches,
+                fork.cross_refs,
+                fork.checkpoints
+            ),
+            (2, 2, 1, 1)
+        );
+
+        let branches = storage
+            .get_session_branches(&fork.session.id)
+            .await
+            .unwrap();
+        let fork_root = branches
+            .iter()
+            .find(|b| b.parent_branch_id.is_none())
+            .unwrap();
+        let fork_child = branches
+            .iter()
+            .find(|b| b.parent_branch_id.is_some())
+            .unwrap();
+        assert_eq!(
+            fork_child.parent_branch_id.as_deref(),
+            Some(fork_root.id.as_str())
+        );
+        assert_eq!(
+            fork.session.active_branch_id.as_deref(),
+            Some(fork_root.id.as_str())
+        );
+        assert!(branches.iter().all(|b| b.id != root.id && b.id != child.id));
+
+        let thoughts = storage
+            .get_session_thoughts(&fork.session.id)
+            .await
+            .unwrap();
+        assert_eq!(thoughts.len(), 2);
+        assert_eq!(
+            thoughts[1].parent_id.as_deref(),
+            Some(thoughts[0].id.as_str())
+        );
+        assert_eq!(
+            thoughts[1].branch_id.as_deref(),
+            Some(fork_child.id.as_str())
+        );
+
+        let refs = storage.get_cross_refs_from(&fork_child.id).await.unwrap();
+        assert_eq!(refs[0].to_branch_id, fork_root.id);
+
+        let checkpoints = storage
+            .get_session_checkpoints(&fork.session.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            checkpoints[0].branch_id.as_deref(),
+            Some(fork_child.id.as_str())
+        );
+        assert_eq!(checkpoints[0].snapshot, json!({"step": 2}));
+    }
+
+    #[tokio::test]
+    async fn test_fork_leaves_original_untouched() {
+        let storage = create_test_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        let a = GraphNode::new(&session.id, "Idea A");
+        let b = GraphNode::new(&session.id, "Idea B");
+        storage.create_graph_node(&a).await.unwrap();
+        storage.create_graph_node(&b).await.unwrap();
+        storage
+            .create_graph_edge(&GraphEdge::new(&session.id, &a.id, &b.id))
+            .await
+            .unwrap();
+
+        let fork = storage.fork_session(&session.id).await.unwrap();
+        assert_eq!((fork.graph_nodes, fork.graph_edges), (2, 1));
+
+        let nodes = storage
+            .get_session_graph_nodes(&fork.session.id)
+            .await
+            .unwrap();
+        for node in &nodes {
+            storage.delete_graph_node(&node.id).await.unwrap();
+        }
+        storage
+            .create_thought(&Thought::new(&fork.session.id, "Alternative", "got"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage
+                .get_session_graph_nodes(&session.id)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            storage.get_session_edges(&session.id).await.unwrap().len(),
+            1
+        );
+        assert!(storage
+            .get_session_thoughts(&session.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fork_missing_session() {
+        let storage = create_test_storage().await;
+        let err = storage.fork_session("no-such-session").await.unwrap_err();
+        assert!(matches!(err, StorageError::SessionNotFound { .. }));
+    }
+}
+
+#[cfg(test)]
+mod workspace_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::StorageError;
+    use mcp_langbase_reasoning::storage::{
+        MetricsFilter, SessionFilter, SharedStorage, ThoughtSearchFilter, WorkspaceStorage,
+    };
+    use std::sync::Arc;
+
+    async fn create_workspaces() -> (WorkspaceStorage, WorkspaceStorage) {
+        let storage: SharedStorage = Arc::new(create_test_storage().await);
+        (
+            WorkspaceStorage::new(storage.clone(), "alpha"),
+            WorkspaceStorage::new(storage, "beta"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_invisible_across_workspaces() {
+        let (alpha, beta) = create_workspaces().await;
+        let session = Session::new("linear");
+        alpha.create_session(&session).await.unwrap();
+
+        let fetched = alpha.get_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(fetched.workspace_id, "alpha");
+        assert!(beta.get_session(&session.id).await.unwrap().is_none());
+        assert_eq!(
+            alpha
+                .list_sessions(SessionFilter::new())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(beta
+            .list_sessions(SessionFilter::new().in_workspace("alpha"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Deleting from another workspace leaves the session alone
+        beta.delete_session(&session.id).await.unwrap();
+        assert!(alpha.get_session(&session.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_session_records_are_confined_to_workspace() {
+        let (alpha, beta) = create_workspaces().await;
+        let session = Session::new("linear");
+        alpha.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Workspace reasoning", "linear");
+        alpha.create_thought(&thought).await.unwrap();
+
+        assert!(beta.get_thought(&thought.id).await.unwrap().is_none());
+        assert!(beta
+            .get_session_thoughts(&session.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(beta
+            .search_thoughts("reasoning", ThoughtSearchFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            alpha
+                .search_thoughts("reasoning", ThoughtSearchFilter::new())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let intruder = Thought::new(&session.id, "Injected", "linear");
+        let err = beta.create_thought(&intruder).await.unwrap_err();
+        assert!(matches!(err, StorageError::SessionNotFound { .. }));
+        let err = beta.fork_session(&session.id).await.unwrap_err();
+        assert!(matches!(err, StorageError::SessionNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_invocations_and_metrics_are_per_workspace() {
+        let (alpha, beta) = create_workspaces().await;
+        alpha
+            .log_invocation(&Invocation::new("reasoning_linear", json!({})).with_pipe("linear-v1"))
+            .await
+            .unwrap();
+
+        assert_eq!(alpha.get_invocation_count(None, None).await.unwrap(), 1);
+        assert_eq!(
+            beta.get_invocation_count(None, Some("alpha"))
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(beta
+            .get_invocations(MetricsFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(beta.get_pipe_usage_summary(None).await.unwrap().is_empty());
+        assert_eq!(alpha.get_pipe_usage_summary(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_with_workspace_leaves_original_view() {
+        let (alpha, _) = create_workspaces().await;
+        let gamma = alpha.with_workspace("gamma");
+        let session = Session::new("linear");
+        alpha.create_session(&session).await.unwrap();
+
+        assert_eq!(gamma.workspace_id(), "gamma");
+        assert_eq!(alpha.clone().workspace_id(), "alpha");
+        assert!(gamma.get_session(&session.id).await.unwrap().is_none());
+        assert!(alpha.get_session(&session.id).await.unwrap().is_some());
+    }
+}
+
+#[cfg(test)]
+mod revision_tests {
+    use super::*;
+    use mcp_langbase_reasoning::error::StorageError;
+    use mcp_langbase_reasoning::storage::ThoughtSearchFilter;
+
+    #[tokio::test]
+    async fn test_revise_thought_keeps_previous_versions() {
+        let storage = create_test_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought =
+            Thought::new(&session.id, "Two plus two is five", "linear").with_confidence(0.6);
+        storage.create_thought(&thought).await.unwrap();
+
+        let revised = storage
+            .revise_thought(
+                &thought.id,
+                "Two plus two is four",
+                Some(0.95),
+                Some("arithmetic"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(revised.id, thought.id);
+        assert_eq!(revised.content, "Two plus two is four");
+        assert!((revised.confidence - 0.95).abs() < f64::EPSILON);
+
+        let revised = storage
+            .revise_thought(&thought.id, "2 + 2 = 4", None, None)
+            .await
+            .unwrap();
+        assert!((revised.confidence - 0.95).abs() < f64::EPSILON);
+
+        let revisions = storage.get_thought_revisions(&thought.id).await.unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].version, 1);
+        assert_eq!(revisions[0].content, "Two plus two is five");
+        assert!((revisions[0].confidence - 0.6).abs() < f64::EPSILON);
+        assert_eq!(revisions[0].reason.as_deref(), Some("arithmetic"));
+        assert_eq!(revisions[1].version, 2);
+        assert_eq!(revisions[1].content, "Two plus two is four");
+        assert_eq!(revisions[1].session_id, session.id);
+    }
+
+    #[tokio::test]
+    async fn test_revise_missing_thought() {
+        let storage = create_test_storage().await;
+
+        let result = storage
+            .revise_thought("missing", "content", None, None)
+            .await;
+        assert!(matches!(result, Err(StorageError::ThoughtNotFound { .. })));
+        assert!(storage
+            .get_thought_revisions("missing")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_thought_metadata() {
+        let storage = create_test_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let mut thought = Thought::new(&session.id, "Draft", "linear");
+        storage.create_thought(&thought).await.unwrap();
+
+        thought.metadata = Some(serde_json::json!({"superseded": true}));
+        storage.update_thought_metadata(&thought).await.unwrap();
+        let stored = storage.get_thought(&thought.id).await.unwrap().unwrap();
+        assert_eq!(stored.metadata, thought.metadata);
+        assert_eq!(stored.content, "Draft");
+
+        let missing = Thought::new(&session.id, "Never stored", "linear");
+        let result = storage.update_thought_metadata(&missing).await;
+        assert!(matches!(result, Err(StorageError::ThoughtNotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_revised_content_is_searchable() {
+        let storage = create_test_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Initial guess", "linear");
+        storage.create_thought(&thought).await.unwrap();
+
+        storage
+            .revise_thought(&thought.id, "Corrected reasoning", None, None)
+            .await
+            .unwrap();
+
+        let found = storage
+            .search_thoughts("reasoning", ThoughtSearchFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].thought.id, thought.id);
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_revision_history() {
+        let storage = create_test_storage().await;
+        let session = Session::new("linear");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Draft", "linear");
+        storage.create_thought(&thought).await.unwrap();
+        storage
+            .revise_thought(&thought.id, "Final", None, None)
+            .await
+            .unwrap();
+
+        let fork = storage.fork_session(&session.id).await.unwrap();
+        assert_eq!(fork.thought_revisions, 1);
+
+        let forked = storage
+            .get_session_thoughts(&fork.session.id)
+            .await
+            .unwrap();
+        assert_eq!(forked[0].content, "Final");
+        let revisions = storage.get_thought_revisions(&forked[0].id).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].content, "Draft");
+        assert_eq!(revisions[0].session_id, fork.session.id);
+    }
+}
+
+#[cfg(test)]
+mod pipe_cache_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{MetricsFilter, PipeCacheEntry};
+
+    #[tokio::test]
+    async fn test_put_and_get_pipe_cache() {
+        let storage = create_test_storage().await;
+        let entry = PipeCacheEntry::new(
+            "abc123",
+            "detection-v1",
+            json!({"success": true, "completion": "{}"}),
+            60,
+        );
+        storage.put_pipe_cache(&entry).await.unwrap();
+
+        let loaded = storage.get_pipe_cache("abc123").await.unwrap().unwrap();
+        assert_eq!(loaded.pipe_name, "detection-v1");
+        assert_eq!(loaded.response["completion"], "{}");
+        assert!(storage.get_pipe_cache("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_pipe_cache_entries_are_not_served() {
+        let storage = create_test_storage().await;
+        let mut entry = PipeCacheEntry::new("old", "detection-v1", json!({}), 60);
+        entry.expires_at = Utc::now() - chrono::Duration::seconds(1);
+        assert!(entry.is_expired());
+        storage.put_pipe_cache(&entry).await.unwrap();
+
+        assert!(storage.get_pipe_cache("old").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_put_pipe_cache_replaces_entry() {
+        let storage = create_test_storage().await;
+        let first = PipeCacheEntry::new("k", "detection-v1", json!({"completion": "a"}), 60);
+        let second = PipeCacheEntry::new("k", "detection-v1", json!({"completion": "b"}), 60);
+        storage.put_pipe_cache(&first).await.unwrap();
+        storage.put_pipe_cache(&second).await.unwrap();
+
+        let loaded = storage.get_pipe_cache("k").await.unwrap().unwrap();
+        assert_eq!(loaded.response["completion"], "b");
+    }
+
+    #[tokio::test]
+    async fn test_invocation_cache_hit_round_trip() {
+        let storage = create_test_storage().await;
+        let invocation = Invocation::new("reasoning_detect_biases", json!({}))
+            .with_pipe("detection-v1")
+            .with_cache_hit(true)
+            .success(json!({}), 1);
+        storage.log_invocation(&invocation).await.unwrap();
+
+        let invocations = storage.get_invocations(MetricsFilter::new()).await.unwrap();
+        assert_eq!(invocations.len(), 1);
+        assert!(invocations[0].cache_hit);
+    }
+}
+
+#[cfg(test)]
+mod swot_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{
+        PerspectiveAnalysis, SharedStorage, SwotAnalysis, SwotFilter, WorkspaceStorage,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_find_swot_across_sessions() {
+        let storage = create_test_storage().await;
+        let first = Session::new("swot");
+        let second = Session::new("swot");
+        storage.create_session(&first).await.unwrap();
+        storage.create_session(&second).await.unwrap();
+
+        let perspective = PerspectiveAnalysis::new(
+            &first.id,
+            "Pricing change",
+            json!([{"name": "Customers"}]),
+            json!({}),
+            0.7,
+        );
+        storage.create_perspective(&perspective).await.unwrap();
+        let pricing = SwotAnalysis::new(&first.id, "Pricing change", "Worth trying", 0.7)
+            .with_quadrants(
+                json!([{"factor": "Loyal customers"}]),
+                json!([]),
+                json!([]),
+                json!([{"factor": "Competitor discounts"}]),
+            )
+            .with_perspective(&perspective.id);
+        storage.create_swot_analysis(&pricing).await.unwrap();
+        let hiring = SwotAnalysis::new(&second.id, "Hiring plan", "Risky", 0.4);
+        storage.create_swot_analysis(&hiring).await.unwrap();
+
+        // A later session finds the analysis by subject, case-insensitively
+        let found = storage
+            .find_swot_analyses(SwotFilter::new().with_subject("PRICING"))
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, pricing.id);
+        assert_eq!(found[0].threats[0]["factor"], "Competitor discounts");
+        assert_eq!(
+            found[0].perspective_id.as_deref(),
+            Some(perspective.id.as_str())
+        );
+
+        let all = storage
+            .find_swot_analyses(SwotFilter::new().with_limit(10))
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        let in_second = storage
+            .find_swot_analyses(SwotFilter::new().in_session(&second.id))
+            .await
+            .unwrap();
+        assert_eq!(in_second.len(), 1);
+        assert_eq!(in_second[0].subject, "Hiring plan");
+
+        // Trashed sessions drop out of the results
+        storage.trash_session(&second.id).await.unwrap();
+        let all = storage.find_swot_analyses(SwotFilter::new()).await.unwrap();
+        assert_eq!(all.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_swot_confined_to_workspace() {
+        let storage: SharedStorage = Arc::new(create_test_storage().await);
+        let alpha = WorkspaceStorage::new(storage.clone(), "alpha");
+        let beta = WorkspaceStorage::new(storage, "beta");
+        let session = Session::new("swot");
+        alpha.create_session(&session).await.unwrap();
+        let analysis = SwotAnalysis::new(&session.id, "Pricing change", "Worth trying", 0.7);
+        alpha.create_swot_analysis(&analysis).await.unwrap();
+
+        assert_eq!(
+            alpha
+                .find_swot_analyses(SwotFilter::new())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(beta
+            .find_swot_analyses(SwotFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+        // Another workspace cannot attach analyses to the session
+        let foreign = SwotAnalysis::new(&session.id, "Pricing change", "Intruding", 0.5);
+        assert!(beta.create_swot_analysis(&foreign).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod prioritization_storage_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::Decision;
+
+    #[tokio::test]
+    async fn test_decisions_accept_prioritization_methods() {
+        let storage = create_test_storage().await;
+        let session = Session::new("prioritization");
+        storage.create_session(&session).await.unwrap();
+
+        for method in ["rice", "moscow"] {
+            let ranking = Decision::new(
+                &session.id,
+                "Next quarter",
+                vec!["Dark mode".to_string(), "CSV export".to_string()],
+                method,
+                json!({"option": "Dark mode", "score": 800.0}),
+                json!([{"rank": 1, "item": "Dark mode", "score": 800.0}]),
+            );
+            storage.create_decision(&ranking).await.unwrap();
+            let stored = storage.get_decision(&ranking.id).await.unwrap().unwrap();
+            assert_eq!(stored.method, method);
+        }
+        let by_method = storage.get_decisions_by_method("rice").await.unwrap();
+        assert_eq!(by_method.len(), 1);
+
+        // Unknown methods are still rejected
+        let unknown = Decision::new(
+            &session.id,
+            "Next quarter",
+            vec![],
+            "coin_flip",
+            json!({}),
+            json!([]),
+        );
+        assert!(storage.create_decision(&unknown).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod risk_storage_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::Risk;
+
+    #[tokio::test]
+    async fn test_risk_register_round_trip() {
+        let storage = create_test_storage().await;
+        let session = Session::new("risk");
+        storage.create_session(&session).await.unwrap();
+
+        let outage = Risk::new(&session.id, "Outage", 2, 5, 4)
+            .with_category("technical")
+            .with_owner("SRE");
+        let slip = Risk::new(&session.id, "Schedule slip", 3, 3, 2);
+        let typo = Risk::new(&session.id, "Typo", 9, 0, 1);
+        for risk in [&slip, &outage, &typo] {
+            storage.create_risk(risk).await.unwrap();
+        }
+
+        // Scores are clamped into 1-5
+        let stored = storage.get_risk(&typo.id).await.unwrap().unwrap();
+        assert_eq!((stored.likelihood, stored.impact), (5, 1));
+        assert!(storage.get_risk("missing").await.unwrap().is_none());
+
+        // The register is ordered by priority number, highest first
+        let register = storage.get_session_risks(&session.id).await.unwrap();
+        let titles: Vec<&str> = register.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Outage", "Schedule slip", "Typo"]);
+        assert_eq!(register[0].owner.as_deref(), Some("SRE"));
+        assert_eq!(register[0].category.as_deref(), Some("technical"));
+
+        let mut updated = register[1].clone();
+        updated.likelihood = 5;
+        updated.detectability = 3;
+        updated.mitigation = Some("Rehearse on staging".to_string());
+        updated.history = json!([{"source": "manual"}]);
+        storage.update_risk(&updated).await.unwrap();
+        let stored = storage.get_risk(&slip.id).await.unwrap().unwrap();
+        assert_eq!(stored.likelihood, 5);
+        assert_eq!(stored.mitigation.as_deref(), Some("Rehearse on staging"));
+        assert_eq!(stored.history, json!([{"source": "manual"}]));
+        let register = storage.get_session_risks(&session.id).await.unwrap();
+        assert_eq!(register[0].title, "Schedule slip");
+
+        let missing = Risk::new(&session.id, "Missing", 1, 1, 1);
+        assert!(storage.update_risk(&missing).await.is_err());
+    }
+}
+
+#[cfg(test)]
+mod code_finding_storage_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{CodeFinding, CodeFindingKind};
+
+    #[tokio::test]
+    async fn test_code_findings_round_trip() {
+        let storage = create_test_storage().await;
+        let session = Session::new("code");
+        storage.create_session(&session).await.unwrap();
+        let thought = Thought::new(&session.id, "Adds a capacity check", "code");
+        storage.create_thought(&thought).await.unwrap();
+
+        let invariant = CodeFinding::new(
+            &session.id,
+            "explain_diff",
+            CodeFindingKind::Invariant,
+            "Length never exceeds capacity",
+        )
+        .with_thought(&thought.id)
+        .with_location("Cache::insert");
+        let risk = CodeFinding::new(
+            &session.id,
+            "edge_cases",
+            CodeFindingKind::Risk,
+            "New entries are dropped",
+        )
+        .with_severity(4);
+        let test = CodeFinding::new(
+            &session.id,
+            "edge_cases",
+            CodeFindingKind::Test,
+            "Insert past capacity",
+        )
+        .with_metadata(json!({"name": "test_full_cache"}));
+        for finding in [&invariant, &risk, &test] {
+            storage.create_code_finding(finding).await.unwrap();
+        }
+
+        // Findings come back oldest first with every field intact
+        let stored = storage
+            .get_session_code_findings(&session.id)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = stored.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, [&invariant.id, &risk.id, &test.id]);
+        assert_eq!(stored[0].thought_id.as_deref(), Some(thought.id.as_str()));
+        assert_eq!(stored[0].location.as_deref(), Some("Cache::insert"));
+        assert_eq!(stored[1].kind, CodeFindingKind::Risk);
+        assert_eq!(stored[1].severity, Some(4));
+        assert_eq!(stored[2].metadata, Some(json!({"name": "test_full_cache"})));
+
+        let other = storage.get_session_code_findings("missing").await.unwrap();
+        assert!(other.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod mcts_iteration_storage_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::storage::{
+        MctsIteration, MctsIterationFilter, MctsPolicy, SharedStorage, WorkspaceStorage,
+    };
+    use std::sync::Arc;
+
+    fn iteration(session_id: &str, root: &str, number: i32, minutes: i64) -> MctsIteration {
+        MctsIteration {
+            policy: MctsPolicy::ProgressiveWidening,
+            exploration_constant: 0.5,
+            rollout_depth: 2,
+            expansion_width: 3,
+            selected_depth: number - 1,
+            children_created: 1,
+            simulation_value: 0.6,
+            best_value: 0.1 * number as f64,
+            nodes_total: number + 1,
+            pipe_calls: 4,
+            latency_ms: 25,
+            created_at: Utc::now() - Duration::minutes(60 - minutes),
+            ..MctsIteration::new(
+                session_id,
+                root,
+                number,
+                format!("{}-node-{}", root, number),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mcts_iterations_round_trip_grouped_by_search() {
+        let storage = create_test_storage().await;
+        let session = Session::new("mcts");
+        storage.create_session(&session).await.unwrap();
+        let other = Session::new("mcts");
+        storage.create_session(&other).await.unwrap();
+
+        // Two searches in one session, one in another; stored out of order
+        for record in [
+            iteration(&session.id, "late", 1, 30),
+            iteration(&session.id, "early", 2, 11),
+            iteration(&session.id, "early", 1, 10),
+            iteration(&other.id, "other", 1, 20),
+            iteration(&session.id, "late", 2, 31),
+        ] {
+            storage.create_mcts_iteration(&record).await.unwrap();
+        }
+
+        // Searches come back oldest first, iterations in order
+        let all = storage
+            .get_mcts_iterations(MctsIterationFilter::new())
+            .await
+            .unwrap();
+        let order: Vec<(&str, i32)> = all
+            .iter()
+            .map(|i| (i.root_node_id.as_str(), i.iteration))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("early", 1),
+                ("early", 2),
+                ("other", 1),
+                ("late", 1),
+                ("late", 2)
+            ]
+        );
+        let first = &all[0];
+        assert_eq!(first.policy, MctsPolicy::ProgressiveWidening);
+        assert_eq!(first.exploration_constant, 0.5);
+        assert_eq!(first.rollout_depth, 2);
+        assert_eq!(first.expansion_width, 3);
+        assert_eq!(first.selected_node_id, "early-node-1");
+        assert_eq!(first.nodes_total, 2);
+        assert_eq!(first.pipe_calls, 4);
+        assert_eq!(first.latency_ms, 25);
+
+        let in_session = storage
+            .get_mcts_iterations(MctsIterationFilter::new().in_session(&session.id))
+            .await
+            .unwrap();
+        assert_eq!(in_session.len(), 4);
+        assert!(in_session.iter().all(|i| i.session_id == session.id));
+
+        // The limit counts whole searches, most recent first
+        let recent = storage
+            .get_mcts_iterations(MctsIterationFilter::new().with_limit(2))
+            .await
+            .unwrap();
+        let roots: Vec<&str> = recent.iter().map(|i| i.root_node_id.as_str()).collect();
+        assert_eq!(roots, ["other", "late", "late"]);
+        let recent_in_session = storage
+            .get_mcts_iterations(
+                MctsIterationFilter::new()
+                    .in_session(&session.id)
+                    .with_limit(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(recent_in_session.len(), 2);
+        assert!(recent_in_session.iter().all(|i| i.root_node_id == "late"));
+    }
+
+    #[tokio::test]
+    async fn test_mcts_iterations_are_confined_to_workspace() {
+        let storage: SharedStorage = Arc::new(create_test_storage().await);
+        let alpha = WorkspaceStorage::new(storage.clone(), "alpha");
+        let beta = WorkspaceStorage::new(storage.clone(), "beta");
+
+        let session = Session::new("mcts");
+        alpha.create_session(&session).await.unwrap();
+        alpha
+            .create_mcts_iteration(&iteration(&session.id, "root", 1, 0))
+            .await
+            .unwrap();
+
+        // Another workspace can neither add to nor read the search
+        assert!(beta
+            .create_mcts_iteration(&iteration(&session.id, "root", 2, 1))
+            .await
+            .is_err());
+        let filter = MctsIterationFilter::new();
+        assert_eq!(
+            alpha
+                .get_mcts_iterations(filter.clone())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(beta
+            .get_mcts_iterations(filter.clone())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(storage.get_mcts_iterations(filter).await.unwrap().len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod routing_record_storage_tests {
+    use super::*;
+    use chrono::Duration;
+    use mcp_langbase_reasoning::storage::{
+        RoutingRecord, RoutingRecordFilter, SharedStorage, WorkspaceStorage,
+    };
+    use std::sync::Arc;
+
+    fn record(mode: &str, model: &str, minutes: i64) -> RoutingRecord {
+        RoutingRecord {
+            created_at: Utc::now() - Duration::minutes(60 - minutes),
+            ..RoutingRecord::new(
+                format!("Route this {} content", mode),
+                model,
+                vec![0.6, 0.8, 0.0],
+                mode,
+                0.75,
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routing_records_round_trip_and_outcome_update() {
+        let storage = create_test_storage().await;
+        // The session may be created after the routing decision
+        let session = Session::new("tree");
+        let mut routed = record("linear", "local:hash-3", 10)
+            .with_session(&session.id)
+            .with_history_applied(true);
+        storage.create_routing_record(&routed).await.unwrap();
+        storage.create_session(&session).await.unwrap();
+        storage
+            .create_routing_record(&record("tree", "local:hash-3", 20))
+            .await
+            .unwrap();
+        storage
+            .create_routing_record(&record("got", "other-model", 30))
+            .await
+            .unwrap();
+
+        let loaded = storage
+            .get_routing_record(&routed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.session_id.as_deref(), Some(session.id.as_str()));
+        assert_eq!(loaded.vector, vec![0.6, 0.8, 0.0]);
+        assert_eq!(loaded.recommended_mode, "linear");
+        assert!(loaded.history_applied);
+        assert!(loaded.outcome_score.is_none());
+        assert!(loaded.evaluated_at.is_none());
+
+        routed.outcome_mode = Some("tree".to_string());
+        routed.outcome_confidence = Some(0.7);
+        routed.detection_count = 2;
+        routed.feedback_score = Some(0.9);
+        routed.feedback_note = Some("Branching helped".to_string());
+        routed.outcome_score = Some(0.7);
+        routed.evaluated_at = Some(Utc::now());
+        storage.update_routing_outcome(&routed).await.unwrap();
+        let updated = storage
+            .get_routing_record(&routed.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.credited_mode(), "tree");
+        assert_eq!(updated.detection_count, 2);
+        assert_eq!(updated.feedback_score, Some(0.9));
+        assert_eq!(updated.feedback_note.as_deref(), Some("Branching helped"));
+        assert_eq!(updated.outcome_score, Some(0.7));
+        assert!(updated.evaluated_at.is_some());
+
+        // Most recent first, narrowed by model, session and limit
+        let all = storage
+            .list_routing_records(RoutingRecordFilter::new())
+            .await
+            .unwrap();
+        let modes: Vec<&str> = all.iter().map(|r| r.recommended_mode.as_str()).collect();
+        assert_eq!(modes, ["got", "tree", "linear"]);
+        let same_model = storage
+            .list_routing_records(
+                RoutingRecordFilter::new()
+                    .with_model("local:hash-3")
+                    .with_limit(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(same_model.len(), 1);
+        assert_eq!(same_model[0].recommended_mode, "tree");
+        let in_session = storage
+            .list_routing_records(RoutingRecordFilter::new().in_session(&session.id))
+            .await
+            .unwrap();
+        assert_eq!(in_session.len(), 1);
+
+        // Decisions go with their session
+        storage.delete_session(&session.id).await.unwrap();
+        assert!(storage
+            .get_routing_record(&routed.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_routing_records_are_confined_to_workspace() {
+        let storage: SharedStorage = Arc::new(create_test_storage().await);
+        let alpha = WorkspaceStorage::new(storage.clone(), "alpha");
+        let beta = WorkspaceStorage::new(storage.clone(), "beta");
+
+        let session = Session::new("linear");
+        alpha.create_session(&session).await.unwrap();
+        let routed = record("linear", "local:hash-3", 0).with_session(&session.id);
+        alpha.create_routing_record(&routed).await.unwrap();
+
+        // Another workspace can neither route for the session nor see or
+        // rate the decision
+        assert!(beta
+            .create_routing_record(&record("tree", "local:hash-3", 1).with_session(&session.id))
+            .await
+            .is_err());
+        assert!(beta.get_routing_record(&routed.id).await.unwrap().is_none());
+        assert!(beta.update_routing_outcome(&routed).await.is_err());
+        assert!(beta
+            .list_routing_records(RoutingRecordFilter::new())
+            .await
+            .unwrap()
+            .is_empty());
+        let listed = alpha
+            .list_routing_records(RoutingRecordFilter::new())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].workspace_id, "alpha");
+    }
+}
+
+#[cfg(test)]
+mod graph_comparison_storage_tests {
+    use super::*;
+    use mcp_langbase_reasoning::storage::{GraphComparison, GraphNode};
+
+    #[tokio::test]
+    async fn test_graph_comparisons_round_trip_and_node_delete() {
+        let storage = create_test_storage().await;
+        let session = Session::new("got");
+        storage.create_session(&session).await.unwrap();
+        let nodes: Vec<GraphNode> = (0..3)
+            .map(|i| GraphNode::new(&session.id, format!("Option {}", i)))
+            .collect();
+        storage.create_graph_nodes_batch(&nodes).await.unwrap();
+
+        let comparisons = vec![
+            GraphComparison::new(
+                &session.id,
+                &nodes[0].id,
+                &nodes[1].id,
+                Some(nodes[0].id.clone()),
+                0.8,
+            )
+            .with_parent("root")
+            .with_rationale("More concrete"),
+            // A winner outside the pair is stored as a tie
+            GraphComparison::new(
+                &session.id,
+                &nodes[2].id,
+                &nodes[1].id,
+                Some("elsewhere".to_string()),
+                1.5,
+            )
+            .with_round(2),
+        ];
+        storage
+            .create_graph_comparisons_batch(&comparisons)
+            .await
+            .unwrap();
+
+        let loaded = storage
+            .get_session_graph_comparisons(&session.id)
+            .await
+            .unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].winner_id.as_deref(), Some(nodes[0].id.as_str()));
+        assert_eq!(loaded[0].parent_node_id.as_deref(), Some("root"));
+        assert_eq!(loaded[0].rationale, "More concrete");
+        assert!(loaded[0].is_between(&nodes[1].id, &nodes[0].id));
+        assert!(loaded[1].winner_id.is_none());
+        assert_eq!(loaded[1].confidence, 1.0);
+        assert_eq!(loaded[1].round, 2);
+
+        // Comparisons go with the nodes they judged
+        storage.delete_graph_node(&nodes[0].id).await.unwrap();
+        let remaining = storage
+            .get_session_graph_comparisons(&session.id)
+            .await
+            .unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].node_a_id, nodes[2].id);
+    }
+}